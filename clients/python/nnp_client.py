@@ -0,0 +1,524 @@
+"""Minimal client for the Neural Network Protocol (NNP) spoken by Benny nodes.
+
+Generated by `benny protocol client` for protocol version 2; do not edit,
+regenerate it instead. Needs nothing but the Python 3 standard library.
+
+Field values are represented as in the conformance fixtures
+(`benny protocol fixtures`): integers and floats as numbers, UUIDs as
+hyphenated strings, raw bytes as lowercase hex strings, lists as lists, and
+spikes and health components as dicts.
+
+    with NnpClient("127.0.0.1", 8080, name="rig", layers=[2]) as node:
+        node.handshake()
+        node.send_forward([0.5, 0.25])
+        print(node.heartbeat())
+        print(node.info())
+
+Check this module against the fixtures with `python3 nnp_client.py check DIR`,
+or probe a node with `python3 nnp_client.py ping HOST:PORT`.
+"""
+
+import json
+import math
+import os
+import socket
+import struct
+import sys
+import time
+import uuid
+import zlib
+
+MAGIC = b"NNP\x00"
+VERSION = 2
+HEADER_SIZE = 22
+VERSION_MASK = 0x0F
+ACK_REQUIRED = 0x80
+
+FORWARD_PROPAGATION = 0x001
+BACKPROPAGATION = 0x002
+HEBBIAN_LEARNING = 0x004
+WEIGHT_SYNC = 0x008
+CORRELATION_ANALYSIS = 0x010
+MULTI_LAYER = 0x020
+REAL_TIME = 0x040
+COMPRESSION = 0x080
+ACKNOWLEDGED_DELIVERY = 0x100
+SPIKE_EVENTS = 0x200
+REMOTE_CONFIG = 0x400
+MODEL_DEPLOYMENT = 0x800
+
+MESSAGE_TYPES = {
+    "Handshake": 0x01,
+    "HandshakeAck": 0x02,
+    "Ack": 0x03,
+    "ForwardData": 0x10,
+    "BackwardData": 0x11,
+    "HebbianData": 0x12,
+    "WeightSync": 0x13,
+    "SpikeEvents": 0x14,
+    "Heartbeat": 0x20,
+    "Disconnect": 0x21,
+    "Info": 0x22,
+    "InfoResponse": 0x23,
+    "ConfigUpdate": 0x24,
+    "ModelPush": 0x25,
+    "ModelPushAck": 0x26,
+    "Ping": 0x27,
+    "Pong": 0x28,
+    "Error": 0xFF,
+}
+MESSAGE_NAMES = {code: name for name, code in MESSAGE_TYPES.items()}
+
+# Payload fields of every message type in wire order: (name, kind, optional)
+LAYOUTS = {
+    "Handshake": [
+        ("network_id", "uuid", False),
+        ("name", "string8", False),
+        ("layers", "u16list8", False),
+        ("capabilities", "u32", False),
+        ("namespace", "string8", True),
+        ("channels", "stringlist8", True),
+        ("ranges", "bytes:16", True),
+    ],
+    "HandshakeAck": [
+        ("network_id", "uuid", False),
+        ("accepted", "bool", False),
+        ("reason", "string8", True),
+        ("ranges", "bytes:16", True),
+    ],
+    "Ack": [
+        ("sequence", "u64", False),
+        ("received_at", "u64", True),
+        ("replied_at", "u64", True),
+    ],
+    "ForwardData": [
+        ("layer_id", "u8", False),
+        ("data", "f32list32", False),
+        ("timestamp", "u64", True),
+        ("channel", "u8", True),
+    ],
+    "BackwardData": [
+        ("layer_id", "u8", False),
+        ("gradients", "f32list32", False),
+    ],
+    "HebbianData": [
+        ("layer_id", "u8", False),
+        ("learning_rate", "f32", False),
+        ("correlations", "f32list32", False),
+        ("timestamp", "u64", True),
+    ],
+    "WeightSync": [
+        ("layer_id", "u8", False),
+        ("weights", "f32list32", False),
+        ("biases", "f32list32", False),
+    ],
+    "SpikeEvents": [
+        ("layer_id", "u8", False),
+        ("events", "spikelist32", False),
+    ],
+    "Heartbeat": [
+        ("timestamp", "u64", False),
+    ],
+    "Disconnect": [
+        ("reason", "string8", False),
+    ],
+    "Info": [],
+    "InfoResponse": [
+        ("network_id", "uuid", False),
+        ("name", "string8", False),
+        ("layers", "u16list8", False),
+        ("parameters", "u64", False),
+        ("learning_mode", "string8", False),
+        ("uptime_secs", "u64", False),
+        ("peers", "u32", False),
+        ("messages_sent", "u64", False),
+        ("messages_received", "u64", False),
+        ("bytes_sent", "u64", False),
+        ("bytes_received", "u64", False),
+        ("weight_norm", "f32", True),
+        ("loss", "f32", True),
+        ("outputs", "f32list32", True),
+    ],
+    "ConfigUpdate": [
+        ("present", "u8", False),
+        ("hebbian_rate", "f32", False),
+        ("online_learning", "bool", False),
+        ("decay_rate", "f32", False),
+        ("token", "string8", True),
+    ],
+    "ModelPush": [
+        ("transfer_id", "u64", False),
+        ("version", "u64", False),
+        ("index", "u32", False),
+        ("count", "u32", False),
+        ("checksum", "u32", False),
+        ("data", "bytes32", False),
+    ],
+    "ModelPushAck": [
+        ("transfer_id", "u64", False),
+        ("accepted", "bool", False),
+        ("previous_version", "u64", False),
+        ("reason", "string8", True),
+    ],
+    "Ping": [],
+    "Pong": [
+        ("components", "componentlist8", False),
+    ],
+    "Error": [
+        ("code", "u16", False),
+        ("message", "string8", False),
+    ],
+}
+
+
+class ProtocolError(Exception):
+    """A frame that does not follow the NNP layout."""
+
+
+def _string8(value):
+    data = value.encode("utf-8")
+    if len(data) > 255:
+        raise ProtocolError("string of %d bytes does not fit a u8 length" % len(data))
+    return bytes([len(data)]) + data
+
+
+def _encode_field(kind, value):
+    if kind == "u8":
+        return struct.pack(">B", value)
+    if kind == "u16":
+        return struct.pack(">H", value)
+    if kind == "u32":
+        return struct.pack(">I", value)
+    if kind == "u64":
+        return struct.pack(">Q", value)
+    if kind == "f32":
+        return struct.pack(">f", float(value))
+    if kind == "bool":
+        return b"\x01" if value else b"\x00"
+    if kind == "uuid":
+        return uuid.UUID(value).bytes
+    if kind.startswith("bytes:"):
+        data = bytes.fromhex(value)
+        if len(data) != int(kind[6:]):
+            raise ProtocolError("expected %s, got %d bytes" % (kind, len(data)))
+        return data
+    if kind == "string8":
+        return _string8(value)
+    if kind == "u16list8":
+        return bytes([len(value)]) + b"".join(struct.pack(">H", v) for v in value)
+    if kind == "stringlist8":
+        return bytes([len(value)]) + b"".join(_string8(v) for v in value)
+    if kind == "componentlist8":
+        return bytes([len(value)]) + b"".join(
+            _string8(c["name"]) + (b"\x01" if c["ready"] else b"\x00") + _string8(c["detail"])
+            for c in value
+        )
+    if kind == "f32list32":
+        return struct.pack(">I", len(value)) + b"".join(struct.pack(">f", float(v)) for v in value)
+    if kind == "spikelist32":
+        return struct.pack(">I", len(value)) + b"".join(
+            struct.pack(">IQ", s["neuron"], s["timestamp"]) for s in value
+        )
+    if kind == "bytes32":
+        data = bytes.fromhex(value)
+        return struct.pack(">I", len(data)) + data
+    raise ProtocolError("unknown field kind %s" % kind)
+
+
+def _take(data, offset, size):
+    if offset + size > len(data):
+        raise ProtocolError("truncated at byte %d" % offset)
+    return data[offset:offset + size], offset + size
+
+
+def _decode_string8(data, offset):
+    (length,), offset = struct.unpack(">B", _take(data, offset, 1)[0]), offset + 1
+    raw, offset = _take(data, offset, length)
+    return raw.decode("utf-8"), offset
+
+
+def _decode_field(kind, data, offset):
+    fixed = {"u8": ">B", "u16": ">H", "u32": ">I", "u64": ">Q", "f32": ">f"}
+    if kind in fixed:
+        raw, offset = _take(data, offset, struct.calcsize(fixed[kind]))
+        return struct.unpack(fixed[kind], raw)[0], offset
+    if kind == "bool":
+        raw, offset = _take(data, offset, 1)
+        return raw[0] != 0, offset
+    if kind == "uuid":
+        raw, offset = _take(data, offset, 16)
+        return str(uuid.UUID(bytes=raw)), offset
+    if kind.startswith("bytes:"):
+        raw, offset = _take(data, offset, int(kind[6:]))
+        return raw.hex(), offset
+    if kind == "string8":
+        return _decode_string8(data, offset)
+    if kind in ("u16list8", "stringlist8", "componentlist8"):
+        raw, offset = _take(data, offset, 1)
+        values = []
+        for _ in range(raw[0]):
+            if kind == "u16list8":
+                value, offset = _decode_field("u16", data, offset)
+            elif kind == "stringlist8":
+                value, offset = _decode_string8(data, offset)
+            else:
+                name, offset = _decode_string8(data, offset)
+                ready, offset = _decode_field("bool", data, offset)
+                detail, offset = _decode_string8(data, offset)
+                value = {"name": name, "ready": ready, "detail": detail}
+            values.append(value)
+        return values, offset
+    if kind in ("f32list32", "spikelist32", "bytes32"):
+        count, offset = _decode_field("u32", data, offset)
+        if kind == "bytes32":
+            raw, offset = _take(data, offset, count)
+            return raw.hex(), offset
+        values = []
+        for _ in range(count):
+            if kind == "f32list32":
+                value, offset = _decode_field("f32", data, offset)
+            else:
+                raw, offset = _take(data, offset, 12)
+                neuron, timestamp = struct.unpack(">IQ", raw)
+                value = {"neuron": neuron, "timestamp": timestamp}
+            values.append(value)
+        return values, offset
+    raise ProtocolError("unknown field kind %s" % kind)
+
+
+def encode_payload(msg_type, fields):
+    """Payload bytes of `msg_type` (a name of MESSAGE_TYPES) with `fields`.
+
+    Optional trailing fields may be left out; every field after the first
+    one left out is then left out too.
+    """
+    out = b""
+    for name, kind, optional in LAYOUTS[msg_type]:
+        if name not in fields:
+            if optional:
+                break
+            raise ProtocolError("%s needs the field %s" % (msg_type, name))
+        out += _encode_field(kind, fields[name])
+    return out
+
+
+def decode_payload(msg_type, data):
+    """Fields of a `msg_type` payload, without the optional fields it omits."""
+    fields, offset = {}, 0
+    for name, kind, optional in LAYOUTS[msg_type]:
+        if optional and offset == len(data):
+            break
+        fields[name], offset = _decode_field(kind, data, offset)
+    if offset != len(data):
+        raise ProtocolError("%s has %d bytes past its last field" % (msg_type, len(data) - offset))
+    return fields
+
+
+def encode_frame(msg_type, fields, sequence, flags=0, version=VERSION):
+    """A complete frame: header, then payload."""
+    payload = encode_payload(msg_type, fields)
+    header = MAGIC + struct.pack(
+        ">BBIQI",
+        version | (flags & ~VERSION_MASK & 0xFF),
+        MESSAGE_TYPES[msg_type],
+        len(payload),
+        sequence,
+        zlib.crc32(payload) & 0xFFFFFFFF,
+    )
+    return header + payload
+
+
+def decode_header(header):
+    """Header fields of a frame, as named in the fixtures."""
+    if len(header) < HEADER_SIZE or header[:4] != MAGIC:
+        raise ProtocolError("not an NNP frame")
+    version, code, length, sequence, checksum = struct.unpack(">BBIQI", header[4:HEADER_SIZE])
+    return {
+        "magic": MAGIC.hex(),
+        "version": version,
+        "msg_type": code,
+        "length": length,
+        "sequence": sequence,
+        "checksum": checksum,
+    }
+
+
+def decode_frame(frame):
+    """(message type name, header fields, payload fields) of a complete frame."""
+    header = decode_header(frame)
+    payload = frame[HEADER_SIZE:]
+    if len(payload) != header["length"]:
+        raise ProtocolError("payload of %d bytes, header says %d" % (len(payload), header["length"]))
+    if zlib.crc32(payload) & 0xFFFFFFFF != header["checksum"]:
+        raise ProtocolError("checksum mismatch")
+    msg_type = MESSAGE_NAMES.get(header["msg_type"])
+    if msg_type is None:
+        raise ProtocolError("unknown message type 0x%02X" % header["msg_type"])
+    return msg_type, header, decode_payload(msg_type, payload)
+
+
+def _recv_exact(sock, size):
+    data = b""
+    while len(data) < size:
+        chunk = sock.recv(size - len(data))
+        if not chunk:
+            raise ConnectionError("connection closed by the node")
+        data += chunk
+    return data
+
+
+class NnpClient:
+    """One NNP connection to a Benny node."""
+
+    def __init__(self, host, port, name="python", layers=(), capabilities=FORWARD_PROPAGATION,
+                 namespace=None, network_id=None, timeout=5.0):
+        self.host, self.port, self.timeout = host, port, timeout
+        self.name, self.layers, self.capabilities = name, list(layers), capabilities
+        self.namespace = namespace
+        self.network_id = network_id or str(uuid.uuid4())
+        self.sequence = 0
+        self.sock = None
+
+    def __enter__(self):
+        self.connect()
+        return self
+
+    def __exit__(self, *_):
+        self.close()
+
+    def connect(self):
+        self.sock = socket.create_connection((self.host, self.port), timeout=self.timeout)
+
+    def close(self, reason=None):
+        """Close the connection, first saying why with a Disconnect when `reason` is given."""
+        if self.sock is not None:
+            if reason is not None:
+                self.send("Disconnect", {"reason": reason})
+            self.sock.close()
+            self.sock = None
+
+    def send(self, msg_type, fields, flags=0):
+        """Send one message, returning its sequence number."""
+        self.sequence += 1
+        self.sock.sendall(encode_frame(msg_type, fields, self.sequence, flags))
+        return self.sequence
+
+    def recv(self):
+        """Next message from the node: (type name, header fields, payload fields)."""
+        header = _recv_exact(self.sock, HEADER_SIZE)
+        length = decode_header(header)["length"]
+        return decode_frame(header + _recv_exact(self.sock, length))
+
+    def _recv_type(self, msg_type):
+        while True:
+            received, _, fields = self.recv()
+            if received == msg_type:
+                return fields
+            if received == "Error":
+                raise ProtocolError("node error %d: %s" % (fields["code"], fields["message"]))
+
+    def handshake(self):
+        """Introduce this client; returns the node's HandshakeAck fields."""
+        fields = {
+            "network_id": self.network_id,
+            "name": self.name,
+            "layers": self.layers,
+            "capabilities": self.capabilities,
+        }
+        if self.namespace is not None:
+            fields["namespace"] = self.namespace
+        self.send("Handshake", fields)
+        ack = self._recv_type("HandshakeAck")
+        if not ack["accepted"]:
+            raise ProtocolError("handshake refused: %s" % ack.get("reason", ""))
+        return ack
+
+    def send_forward(self, data, layer_id=0, channel=None):
+        """Send activations; `channel` tags them with an index into the handshake channels."""
+        fields = {"layer_id": layer_id, "data": list(data)}
+        if channel is not None:
+            fields["timestamp"] = 0
+            fields["channel"] = channel
+        return self.send("ForwardData", fields)
+
+    def heartbeat(self):
+        """Send an acknowledged heartbeat; returns the round trip time in seconds."""
+        started = time.monotonic()
+        sequence = self.send("Heartbeat", {"timestamp": int(time.time() * 1000)}, ACK_REQUIRED)
+        while self._recv_type("Ack")["sequence"] != sequence:
+            pass
+        return time.monotonic() - started
+
+    def info(self):
+        """The node's architecture and activity statistics."""
+        self.send("Info", {})
+        return self._recv_type("InfoResponse")
+
+    def ping(self):
+        """Health components of the node; works without a handshake."""
+        self.send("Ping", {})
+        return self._recv_type("Pong")["components"]
+
+
+def _fixture_value(value):
+    if value in ("NaN", "inf", "-inf"):
+        return float(value.replace("NaN", "nan"))
+    if isinstance(value, list):
+        return [_fixture_value(v) for v in value]
+    return value
+
+
+def _same(a, b):
+    if isinstance(a, float) and isinstance(b, float) and math.isnan(a) and math.isnan(b):
+        return True
+    if isinstance(a, list) and isinstance(b, list):
+        return len(a) == len(b) and all(_same(x, y) for x, y in zip(a, b))
+    return a == b
+
+
+def check_fixtures(directory):
+    """Decode and re-encode every fixture of `directory`, returning the failures."""
+    failures = []
+    names = sorted(f[:-5] for f in os.listdir(directory) if f.endswith(".json"))
+    for name in names:
+        with open(os.path.join(directory, name + ".json")) as f:
+            expected = json.load(f)
+        with open(os.path.join(directory, name + ".bin"), "rb") as f:
+            frame = f.read()
+        payload = {k: _fixture_value(v) for k, v in expected["payload"].items()}
+        try:
+            msg_type, header, fields = decode_frame(frame)
+            if msg_type != expected["type"] or header != expected["header"]:
+                failures.append("%s: header %s instead of %s" % (name, header, expected["header"]))
+            if fields.keys() != payload.keys() or not all(_same(fields[k], payload[k]) for k in payload):
+                failures.append("%s: payload %s instead of %s" % (name, fields, payload))
+            encoded = encode_frame(msg_type, payload, header["sequence"],
+                                   header["version"] & ~VERSION_MASK, header["version"] & VERSION_MASK)
+            if encoded != frame:
+                failures.append("%s: re-encoding gives different bytes" % name)
+        except (ProtocolError, KeyError, struct.error) as e:
+            failures.append("%s: %s" % (name, e))
+    return names, failures
+
+
+def main(argv):
+    if len(argv) == 3 and argv[1] == "check":
+        names, failures = check_fixtures(argv[2])
+        for failure in failures:
+            print("FAIL", failure)
+        print("%d of %d fixtures pass" % (len(names) - len(failures), len(names)))
+        return 1 if failures or not names else 0
+    if len(argv) == 3 and argv[1] == "ping":
+        host, _, port = argv[2].rpartition(":")
+        with NnpClient(host, int(port)) as node:
+            components = node.ping()
+        for component in components:
+            print("%s %s: %s" % ("ok  " if component["ready"] else "FAIL", component["name"],
+                                 component["detail"]))
+        return 0 if all(c["ready"] for c in components) else 1
+    print("usage: %s check FIXTURE_DIR | ping HOST:PORT" % argv[0])
+    return 2
+
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv))
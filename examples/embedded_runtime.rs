@@ -0,0 +1,61 @@
+use neural_network::{BennyRuntime, NeuralNetwork, OnlineTrainingConfig};
+use std::time::Duration;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    println!("🧠 Embedded Runtime");
+    println!("===================");
+
+    // One process serves NNP on 8110, metrics on 9110, and learns from the samples it is handed
+    let runtime = BennyRuntime::builder(NeuralNetwork::with_layers(&[2, 4, 1], 0.5))
+        .with_endpoint("127.0.0.1", 8110)
+        .with_metrics(9110)
+        .with_training(OnlineTrainingConfig {
+            batch_size: 4,
+            max_wait: Duration::from_millis(50),
+            queue_capacity: 64,
+        })
+        .build()?;
+    let handle = runtime.handle();
+    let running = tokio::spawn(runtime.run());
+
+    println!("✅ Serving NNP on port 8110, metrics on http://127.0.0.1:9110/metrics");
+
+    // The application streams labelled XOR samples as they arrive
+    let xor = [
+        ([0.0, 0.0], 0.0),
+        ([0.0, 1.0], 1.0),
+        ([1.0, 0.0], 1.0),
+        ([1.0, 1.0], 0.0),
+    ];
+    for _ in 0..500 {
+        for (inputs, target) in &xor {
+            while handle.train(inputs.to_vec(), vec![*target]).is_err() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        }
+    }
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let stats = handle.training_stats();
+    println!(
+        "\n🎯 Trained {} batches ({} samples), last loss {:.4}",
+        stats.batches,
+        stats.samples,
+        stats.last_loss.unwrap_or(f64::NAN)
+    );
+    for (inputs, target) in &xor {
+        println!(
+            "   {:?} -> {:.3} (target {})",
+            inputs,
+            handle.predict(inputs)?[0],
+            target
+        );
+    }
+
+    handle.shutdown();
+    running
+        .await?
+        .map_err(|e| format!("Runtime failed: {:?}", e))?;
+    Ok(())
+}
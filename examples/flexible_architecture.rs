@@ -13,7 +13,11 @@ fn main() {
 
     // Example 2: Deep network (4 inputs, multiple hidden layers, 2 outputs)
     println!("🏗️  Example 2: Deep Network");
-    let nn2 = NeuralNetwork::with_layers(&[4, 8, 6, 4, 2], 0.01);
+    let mut nn2 = NeuralNetwork::with_layers(&[4, 8, 6, 4, 2], 0.01);
+    // Normalize the hidden layers so deep sigmoid stacks don't saturate
+    for layer in 0..3 {
+        nn2.set_layer_norm(layer, true);
+    }
     println!("Architecture: {}", nn2.info());
     println!("Parameters: {}", nn2.num_parameters());
     println!("Hidden layers: {}\n", nn2.num_hidden_layers());
@@ -19,6 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         key_path: None,
         data_transformation: None,
         input_size: 4,
+        ..Default::default()
     };
     
     let (input_node, _) = InputNode::new(config);
@@ -46,6 +47,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         key_path: None,
         data_transformation: None,
         input_size: 4,
+        ..Default::default()
     };
     
     let (input_node, _) = InputNode::new(config);
@@ -70,6 +72,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         key_path: None,
         data_transformation: None,
         input_size: 16,
+        ..Default::default()
     };
     
     let (input_node, _) = InputNode::new(config);
@@ -37,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             println!("📥 Received message: {:?}", message.msg_type);
 
             match message.payload {
-                neural_network::MessagePayload::ForwardData { layer_id, data } => {
+                neural_network::MessagePayload::ForwardData { layer_id, data, .. } => {
                     println!(
                         "📊 Processing input data for layer {}: {:?}",
                         layer_id, data
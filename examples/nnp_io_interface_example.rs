@@ -21,6 +21,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cert_path: None,
         key_path: None,
         data_transformation: None,
+        ..Default::default()
     };
 
     let output_config = IoNodeConfig {
@@ -34,6 +35,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cert_path: None,
         key_path: None,
         data_transformation: None,
+        ..Default::default()
     };
 
     println!("✅ Created I/O node configurations");
@@ -117,6 +119,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cert_path: Some("certs/sensor.crt".to_string()),
         key_path: Some("certs/sensor.key".to_string()),
         data_transformation: None,
+        ..Default::default()
     };
 
     let (_secure_input, _secure_receiver) = SecureInputNode::new(secure_input_config);
@@ -18,6 +18,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cert_path: None,
         key_path: None,
         data_transformation: None,
+        ..Default::default()
     };
 
     // Create input node
@@ -50,6 +51,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         cert_path: None,
         key_path: None,
         data_transformation: None,
+        ..Default::default()
     };
 
     // Create output node
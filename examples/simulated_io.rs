@@ -0,0 +1,103 @@
+use neural_network::{
+    ExternalSinkConfig, ExternalSourceConfig, InputNode, IoNodeConfig, OutputNode, SimSourceConfig,
+    VirtualActuator, VirtualSensor, WatchdogConfig,
+};
+use uuid::Uuid;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🧠 Simulated Sensors and Actuators");
+    println!("==================================");
+
+    // The output node stands in for the network: inputs are delivered straight to its actuator,
+    // which is parked at the failsafe values once they stop for 250ms
+    let output_config = IoNodeConfig {
+        node_id: Uuid::new_v4(),
+        name: "VirtualActuatorNode".to_string(),
+        listen_address: "127.0.0.1".to_string(),
+        listen_port: 8102,
+        input_size: 4,
+        watchdog: Some(WatchdogConfig {
+            timeout_ms: 250,
+            failsafe: vec![0.0; 4],
+            repeat: false,
+            webhook: None,
+        }),
+        ..Default::default()
+    };
+    let (mut output_node, output_receiver) = OutputNode::new(output_config);
+    output_node.start().await?;
+
+    let actuator = VirtualActuator::new("gripper").verbose();
+    output_node
+        .process_messages(
+            output_receiver,
+            ExternalSinkConfig::Simulated(actuator.clone()),
+        )
+        .await?;
+
+    println!("✅ Started output node with a virtual actuator on port 8102");
+
+    let input_config = IoNodeConfig {
+        node_id: Uuid::new_v4(),
+        name: "VirtualSensorNode".to_string(),
+        listen_address: "127.0.0.1".to_string(),
+        listen_port: 8101,
+        target_address: Some("127.0.0.1".to_string()),
+        target_port: Some(8102),
+        input_size: 4,
+        ..Default::default()
+    };
+    let (mut input_node, _input_receiver) = InputNode::new(input_config);
+    input_node.start().await?;
+
+    // A noisy sine, a bounded random walk and a correlated pair: four channels at 20 Hz
+    let sensors = SimSourceConfig {
+        sensors: vec![
+            VirtualSensor::NoisySine {
+                amplitude: 0.4,
+                frequency_hz: 0.5,
+                phase: 0.0,
+                offset: 0.5,
+                noise: 0.02,
+            },
+            VirtualSensor::RandomWalk {
+                start: 0.5,
+                step: 0.05,
+                min: Some(0.0),
+                max: Some(1.0),
+            },
+            VirtualSensor::Correlated {
+                channels: 2,
+                correlation: 0.8,
+                mean: 0.5,
+                std_dev: 0.1,
+            },
+        ],
+        rate_hz: 20.0,
+        seed: Some(42),
+        samples: Some(40),
+    };
+    input_node
+        .connect_external_source(ExternalSourceConfig::Simulated(sensors))
+        .await?;
+
+    println!("✅ Streaming 40 simulated samples from port 8101");
+
+    tokio::time::sleep(tokio::time::Duration::from_secs(3)).await;
+
+    println!(
+        "\n🎯 The virtual actuator received {} commands",
+        actuator.len()
+    );
+    if let Some(last) = actuator.last() {
+        println!("   Last: {:?} after {:?}", last.values, last.elapsed);
+    }
+    let watchdog = output_node.watchdog_stats();
+    println!(
+        "   Watchdog tripped {} times, failsafe engaged: {}",
+        watchdog.trips, watchdog.tripped
+    );
+
+    Ok(())
+}
@@ -46,7 +46,10 @@ async fn main() -> Result<(), ProtocolError> {
             payload: MessagePayload::ForwardData {
                 layer_id: 0,
                 data: test_data,
+                timestamp: None,
+                channel: None,
             },
+            flags: 0,
         };
         
         distributed_network.handle_message(message).await?;
@@ -0,0 +1,167 @@
+#[cfg(feature = "std")]
+use crate::activation_registry::CustomActivation;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Activation function of a layer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Activation {
+    /// Logistic sigmoid, outputs in (0, 1)
+    #[default]
+    Sigmoid,
+    /// Hyperbolic tangent, outputs in (-1, 1)
+    Tanh,
+    /// Rectified linear unit
+    Relu,
+    /// Identity
+    Linear,
+    /// Binary threshold unit: 1 for non-negative inputs, 0 otherwise
+    Step,
+    /// Bipolar threshold unit: 1 for non-negative inputs, -1 otherwise
+    Sign,
+    /// Function registered with `activation_registry::register`
+    #[cfg(feature = "std")]
+    Custom(CustomActivation),
+}
+
+/// Largest pre-activation magnitude; beyond it every activation is saturated
+pub const SATURATION_LIMIT: f64 = 1e12;
+
+impl Activation {
+    /// Clamp a pre-activation into the finite working range
+    ///
+    /// Overflowing weighted sums (`inf`, or `NaN` from `inf - inf`) would
+    /// otherwise poison every later update; `NaN` carries no sign and maps to 0.
+    pub fn saturate(x: f64) -> f64 {
+        if x.is_nan() {
+            0.0
+        } else {
+            x.clamp(-SATURATION_LIMIT, SATURATION_LIMIT)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn apply(self, x: f64) -> f64 {
+        let x = Self::saturate(x);
+        match self {
+            // Only ever exponentiate a non-positive value so exp cannot overflow
+            Activation::Sigmoid if x >= 0.0 => 1.0 / (1.0 + (-x).exp()),
+            Activation::Sigmoid => {
+                let e = x.exp();
+                e / (1.0 + e)
+            }
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.0),
+            Activation::Linear => x,
+            Activation::Step => {
+                if x >= 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::Sign => {
+                if x >= 0.0 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Activation::Custom(custom) => custom.apply(x),
+        }
+    }
+
+    /// Rough floating point operations of one `apply`, counting `exp` and `tanh` as four
+    pub fn estimated_flops(self) -> u64 {
+        match self {
+            Activation::Sigmoid => 6,
+            Activation::Tanh => 4,
+            Activation::Relu | Activation::Step | Activation::Sign => 1,
+            Activation::Linear => 0,
+            // Unknown cost, assumed to be like the sigmoid's
+            #[cfg(feature = "std")]
+            Activation::Custom(_) => 6,
+        }
+    }
+
+    /// Derivative expressed in terms of the activation's output
+    ///
+    /// Threshold units are flat almost everywhere, so their derivative is 0.
+    pub fn derivative(self, output: f64) -> f64 {
+        match self {
+            Activation::Sigmoid => output * (1.0 - output),
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => {
+                if output > 0.0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Activation::Linear => 1.0,
+            Activation::Step | Activation::Sign => 0.0,
+            #[cfg(feature = "std")]
+            Activation::Custom(custom) => custom.derivative(output),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivative_matches_numerical() {
+        for activation in [
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Relu,
+            Activation::Linear,
+        ] {
+            for x in [-2.0, -0.3, 0.4, 1.7] {
+                let h = 1e-6;
+                let numeric = (activation.apply(x + h) - activation.apply(x - h)) / (2.0 * h);
+                let analytic = activation.derivative(activation.apply(x));
+                assert!(
+                    (numeric - analytic).abs() < 1e-6,
+                    "{:?} at {}",
+                    activation,
+                    x
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_extreme_inputs_stay_finite() {
+        for activation in [
+            Activation::Sigmoid,
+            Activation::Tanh,
+            Activation::Relu,
+            Activation::Linear,
+            Activation::Step,
+            Activation::Sign,
+        ] {
+            for x in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY, 1e300, -1e300] {
+                let y = activation.apply(x);
+                assert!(y.is_finite(), "{:?}({}) = {}", activation, x, y);
+                assert!(activation.derivative(y).is_finite());
+            }
+        }
+
+        let sigmoid = Activation::Sigmoid;
+        assert_eq!(sigmoid.apply(1e300), 1.0);
+        assert_eq!(sigmoid.apply(-1e300), 0.0);
+        assert_eq!(sigmoid.apply(f64::NAN), 0.5);
+        assert!(sigmoid.apply(-700.0) > 0.0);
+        for x in [0.5, 20.0, 40.0] {
+            assert!((sigmoid.apply(x) + sigmoid.apply(-x) - 1.0).abs() < 1e-15);
+        }
+
+        assert_eq!(Activation::Sign.apply(-1e300), -1.0);
+        assert_eq!(Activation::Sign.apply(0.0), 1.0);
+        assert_eq!(Activation::Step.apply(-0.1), 0.0);
+        assert_eq!(Activation::Sign.apply(f64::NAN), 1.0);
+    }
+}
@@ -0,0 +1,118 @@
+use parquet::data_type::{DoubleType, Int32Type};
+use parquet::file::properties::WriterProperties;
+use parquet::file::writer::SerializedFileWriter;
+use parquet::schema::parser::parse_message_type;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Parquet schema of an exported activation history
+const PARQUET_SCHEMA: &str = "
+    message activation_history {
+        REQUIRED INT32 layer;
+        REQUIRED INT32 neuron;
+        REQUIRED INT32 time;
+        REQUIRED DOUBLE activation;
+    }
+";
+
+/// File format of `NeuralNetwork::export_activation_history`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+    Csv,
+    Parquet,
+}
+
+impl HistoryFormat {
+    /// Format named by the extension of `path`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .and_then(|extension| extension.parse().ok())
+    }
+}
+
+impl FromStr for HistoryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(HistoryFormat::Csv),
+            "parquet" => Ok(HistoryFormat::Parquet),
+            _ => Err(format!(
+                "Unknown history format '{}' (expected csv or parquet)",
+                s
+            )),
+        }
+    }
+}
+
+/// Write `history[layer][neuron][time]` as one `layer, neuron, time, activation` row per value
+///
+/// Rows are ordered by layer, neuron and time; time 0 is the oldest
+/// remembered step.
+pub(crate) fn write<P: AsRef<Path>>(
+    history: &[Vec<Vec<f64>>],
+    path: P,
+    format: HistoryFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let rows = history.iter().enumerate().flat_map(|(layer, neurons)| {
+        neurons.iter().enumerate().flat_map(move |(neuron, steps)| {
+            steps
+                .iter()
+                .enumerate()
+                .map(move |(time, &activation)| (layer, neuron, time, activation))
+        })
+    });
+    match format {
+        HistoryFormat::Csv => {
+            let mut writer = csv::Writer::from_path(path)?;
+            writer.write_record(["layer", "neuron", "time", "activation"])?;
+            for (layer, neuron, time, activation) in rows {
+                writer.write_record([
+                    layer.to_string(),
+                    neuron.to_string(),
+                    time.to_string(),
+                    activation.to_string(),
+                ])?;
+            }
+            writer.flush()?;
+        }
+        HistoryFormat::Parquet => {
+            let mut layers = Vec::new();
+            let mut neurons = Vec::new();
+            let mut times = Vec::new();
+            let mut activations = Vec::new();
+            for (layer, neuron, time, activation) in rows {
+                layers.push(layer as i32);
+                neurons.push(neuron as i32);
+                times.push(time as i32);
+                activations.push(activation);
+            }
+            let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+            let properties = Arc::new(WriterProperties::builder().build());
+            let mut writer =
+                SerializedFileWriter::new(fs::File::create(path)?, schema, properties)?;
+            let mut row_group = writer.next_row_group()?;
+            for integers in [&layers, &neurons, &times] {
+                if let Some(mut column) = row_group.next_column()? {
+                    column
+                        .typed::<Int32Type>()
+                        .write_batch(integers, None, None)?;
+                    column.close()?;
+                }
+            }
+            if let Some(mut column) = row_group.next_column()? {
+                column
+                    .typed::<DoubleType>()
+                    .write_batch(&activations, None, None)?;
+                column.close()?;
+            }
+            row_group.close()?;
+            writer.close()?;
+        }
+    }
+    Ok(())
+}
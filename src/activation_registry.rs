@@ -0,0 +1,208 @@
+use crate::activation::Activation;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::sync::{OnceLock, RwLock};
+
+/// Names taken by the built-in activations
+const BUILT_IN: [&str; 6] = ["sigmoid", "tanh", "relu", "linear", "step", "sign"];
+
+/// A user-defined activation function registered under a name and version
+///
+/// Models store only `name@version`; loading one fails unless exactly that
+/// version is registered, so a changed function never silently replaces the
+/// one a model was trained with.
+#[derive(Clone, Copy)]
+pub struct CustomActivation {
+    name: &'static str,
+    version: u32,
+    forward: fn(f64) -> f64,
+    derivative: fn(f64) -> f64,
+}
+
+impl CustomActivation {
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn apply(&self, x: f64) -> f64 {
+        (self.forward)(x)
+    }
+
+    /// Derivative expressed in terms of the activation's output
+    pub fn derivative(&self, output: f64) -> f64 {
+        (self.derivative)(output)
+    }
+}
+
+impl PartialEq for CustomActivation {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.version == other.version
+    }
+}
+
+impl Eq for CustomActivation {}
+
+impl std::fmt::Debug for CustomActivation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+impl Serialize for CustomActivation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("{}@{}", self.name, self.version))
+    }
+}
+
+/// Accepts `name@version`, or a bare `name` for its latest registered version
+impl<'de> Deserialize<'de> for CustomActivation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let key = String::deserialize(deserializer)?;
+        let found = match key.split_once('@') {
+            Some((name, version)) => {
+                let version = version.parse().map_err(|_| {
+                    serde::de::Error::custom(format!("Invalid activation version in {}", key))
+                })?;
+                lookup(name, version)
+            }
+            None => latest(&key),
+        };
+        match found {
+            Ok(Activation::Custom(custom)) => Ok(custom),
+            Ok(_) => unreachable!("the registry only holds custom activations"),
+            Err(e) => Err(serde::de::Error::custom(e)),
+        }
+    }
+}
+
+fn registry() -> &'static RwLock<BTreeMap<(String, u32), CustomActivation>> {
+    static REGISTRY: OnceLock<RwLock<BTreeMap<(String, u32), CustomActivation>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(BTreeMap::new()))
+}
+
+/// Register `forward` and its `derivative` (in terms of the output) as `name@version`
+///
+/// Several versions of one name may be registered side by side. Registering
+/// the same version again is allowed only with the same functions.
+pub fn register(
+    name: &str,
+    version: u32,
+    forward: fn(f64) -> f64,
+    derivative: fn(f64) -> f64,
+) -> Result<Activation, String> {
+    if name.is_empty() || name.contains('@') {
+        return Err(format!("Invalid activation name {:?}", name));
+    }
+    if BUILT_IN.contains(&name.to_lowercase().as_str()) {
+        return Err(format!("{} is a built-in activation", name));
+    }
+    let mut registry = registry().write().unwrap();
+    if let Some(existing) = registry.get(&(name.to_string(), version)) {
+        if std::ptr::fn_addr_eq(existing.forward, forward)
+            && std::ptr::fn_addr_eq(existing.derivative, derivative)
+        {
+            return Ok(Activation::Custom(*existing));
+        }
+        return Err(format!(
+            "Activation {}@{} is already registered with other functions",
+            name, version
+        ));
+    }
+    let custom = CustomActivation {
+        // Registrations live for the whole process, so the name is leaked once
+        name: Box::leak(name.to_string().into_boxed_str()),
+        version,
+        forward,
+        derivative,
+    };
+    registry.insert((name.to_string(), version), custom);
+    Ok(Activation::Custom(custom))
+}
+
+/// The activation registered as `name@version`
+pub fn lookup(name: &str, version: u32) -> Result<Activation, String> {
+    let registry = registry().read().unwrap();
+    match registry.get(&(name.to_string(), version)) {
+        Some(custom) => Ok(Activation::Custom(*custom)),
+        None => {
+            let versions: Vec<String> = registry
+                .keys()
+                .filter(|(registered, _)| registered == name)
+                .map(|(_, version)| version.to_string())
+                .collect();
+            if versions.is_empty() {
+                Err(format!("Activation {} is not registered", name))
+            } else {
+                Err(format!(
+                    "Activation {}@{} is not registered (available versions: {})",
+                    name,
+                    version,
+                    versions.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+/// The highest registered version of `name`
+pub fn latest(name: &str) -> Result<Activation, String> {
+    registry()
+        .read()
+        .unwrap()
+        .range((name.to_string(), 0)..=(name.to_string(), u32::MAX))
+        .next_back()
+        .map(|(_, custom)| Activation::Custom(*custom))
+        .ok_or_else(|| format!("Activation {} is not registered", name))
+}
+
+/// Every registered `(name, version)`, sorted
+pub fn registered() -> Vec<(String, u32)> {
+    registry().read().unwrap().keys().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn softsign(x: f64) -> f64 {
+        x / (1.0 + x.abs())
+    }
+
+    fn softsign_derivative(output: f64) -> f64 {
+        (1.0 - output.abs()).powi(2)
+    }
+
+    #[test]
+    fn test_versions_must_match_at_load_time() {
+        let v1 = register("registry_test_softsign", 1, softsign, softsign_derivative).unwrap();
+        assert_eq!(v1.apply(1.0), 0.5);
+        assert_eq!(v1.derivative(0.5), 0.25);
+        assert!(register("registry_test_softsign", 1, softsign, softsign_derivative).is_ok());
+        assert!(register("registry_test_softsign", 1, f64::tanh, softsign_derivative).is_err());
+        assert!(register("Sigmoid", 1, softsign, softsign_derivative).is_err());
+        assert!(register("a@b", 1, softsign, softsign_derivative).is_err());
+
+        let json = serde_json::to_string(&v1).unwrap();
+        assert_eq!(json, r#"{"Custom":"registry_test_softsign@1"}"#);
+        assert_eq!(serde_json::from_str::<Activation>(&json).unwrap(), v1);
+
+        // A bare name means the latest version
+        let v3 = register("registry_test_softsign", 3, softsign, softsign_derivative).unwrap();
+        let latest: Activation =
+            serde_json::from_str(r#"{"Custom":"registry_test_softsign"}"#).unwrap();
+        assert_eq!(latest, v3);
+
+        let error = serde_json::from_str::<Activation>(r#"{"Custom":"registry_test_softsign@2"}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("available versions: 1, 3"), "{}", error);
+        assert!(
+            serde_json::from_str::<Activation>(r#"{"Custom":"registry_test_missing"}"#).is_err()
+        );
+        assert!(registered().contains(&("registry_test_softsign".to_string(), 3)));
+    }
+}
@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+/// Per-connection adaptive Hebbian learning rates
+///
+/// Every weight keeps a running mean of its squared Hebbian updates, in the
+/// spirit of Adagrad/RMSprop. Each update is scaled by a gain of
+/// `target / rms`, limited to `max_gain`: connections that rarely change learn
+/// faster when they finally do, while busy ones are slowed down and stay stable.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveRates {
+    /// Update magnitude at which the gain is 1
+    pub target: f64,
+    /// Largest gain given to quiet connections
+    pub max_gain: f64,
+    /// Weight of the newest squared update in the running mean, in (0, 1]
+    pub smoothing: f64,
+    variances: Vec<Vec<Vec<f64>>>, // variances[layer][from][to], running mean of Δw²
+}
+
+impl AdaptiveRates {
+    pub fn new(target: f64, max_gain: f64, smoothing: f64) -> Result<Self, String> {
+        if target.is_nan() || target <= 0.0 {
+            return Err(format!("Target {} must be positive", target));
+        }
+        if max_gain.is_nan() || max_gain < 1.0 {
+            return Err(format!("Max gain {} must be at least 1", max_gain));
+        }
+        if !(smoothing > 0.0 && smoothing <= 1.0) {
+            return Err(format!("Smoothing {} must be in (0, 1]", smoothing));
+        }
+        Ok(Self {
+            target,
+            max_gain,
+            smoothing,
+            variances: Vec::new(),
+        })
+    }
+
+    /// Rescale the updates that turned `before` into `after` for weight layer `layer`
+    ///
+    /// State for a layer is reset whenever its shape changes.
+    pub fn apply(&mut self, layer: usize, before: &[Vec<f64>], after: &mut [Vec<f64>]) {
+        if self.variances.len() <= layer {
+            self.variances.resize(layer + 1, Vec::new());
+        }
+        let same_shape = self.variances[layer].len() == after.len()
+            && self.variances[layer]
+                .iter()
+                .zip(after.iter())
+                .all(|(variances, row)| variances.len() == row.len());
+        if !same_shape {
+            self.variances[layer] = after.iter().map(|row| vec![0.0; row.len()]).collect();
+        }
+
+        let variances = self.variances[layer].iter_mut().flatten();
+        let weights = before.iter().flatten().zip(after.iter_mut().flatten());
+        for (variance, (old, new)) in variances.zip(weights) {
+            let update = *new - old;
+            if update == 0.0 {
+                continue;
+            }
+            *variance += self.smoothing * (update * update - *variance);
+            *new = old + update * Self::gain_for(self.target, self.max_gain, *variance);
+        }
+    }
+
+    /// Gain the next update of the weight from `from` to `to` in weight layer `layer` gets
+    ///
+    /// Untracked weights get `max_gain`.
+    pub fn gain(&self, layer: usize, from: usize, to: usize) -> f64 {
+        let variance = self
+            .variances
+            .get(layer)
+            .and_then(|variances| variances.get(from))
+            .and_then(|variances| variances.get(to))
+            .copied()
+            .unwrap_or(0.0);
+        Self::gain_for(self.target, self.max_gain, variance)
+    }
+
+    fn gain_for(target: f64, max_gain: f64, variance: f64) -> f64 {
+        let rms = variance.sqrt();
+        if rms * max_gain <= target {
+            max_gain
+        } else {
+            target / rms
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_busy_connections_slow_down() {
+        let mut rates = AdaptiveRates::new(0.01, 4.0, 0.5).unwrap();
+        let mut weights = vec![vec![0.0, 0.0]];
+        for _ in 0..20 {
+            let before = weights.clone();
+            weights[0][0] += 0.1;
+            rates.apply(0, &before, &mut weights);
+        }
+        let busy = rates.gain(0, 0, 0);
+        assert!((busy - 0.1).abs() < 1e-6);
+        assert_eq!(rates.gain(0, 0, 1), 4.0);
+
+        // The quiet connection fires once and learns at the maximum gain
+        let before = weights.clone();
+        weights[0][1] += 0.001;
+        rates.apply(0, &before, &mut weights);
+        assert!((weights[0][1] - 0.004).abs() < 1e-12);
+
+        assert!(AdaptiveRates::new(0.0, 4.0, 0.5).is_err());
+        assert!(AdaptiveRates::new(0.01, 0.5, 0.5).is_err());
+        assert!(AdaptiveRates::new(0.01, 4.0, 1.5).is_err());
+    }
+}
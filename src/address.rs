@@ -0,0 +1,306 @@
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Preferred address family when a hostname resolves to both IPv4 and IPv6 addresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum AddressPreference {
+    /// Keep the order returned by the system resolver
+    #[default]
+    System,
+    /// Try IPv4 addresses first, then IPv6
+    PreferIpv4,
+    /// Try IPv6 addresses first, then IPv4
+    PreferIpv6,
+    /// Only use IPv4 addresses
+    Ipv4Only,
+    /// Only use IPv6 addresses
+    Ipv6Only,
+}
+
+impl std::str::FromStr for AddressPreference {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "system" => Ok(AddressPreference::System),
+            "prefer-ipv4" | "ipv4-first" => Ok(AddressPreference::PreferIpv4),
+            "prefer-ipv6" | "ipv6-first" => Ok(AddressPreference::PreferIpv6),
+            "ipv4-only" | "ipv4" => Ok(AddressPreference::Ipv4Only),
+            "ipv6-only" | "ipv6" => Ok(AddressPreference::Ipv6Only),
+            _ => Err(format!(
+                "Unknown address preference '{}' (expected system, prefer-ipv4, prefer-ipv6, ipv4-only or ipv6-only)",
+                s
+            )),
+        }
+    }
+}
+
+/// Options controlling how listeners are bound and how hostnames are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BindOptions {
+    /// Address family preference used for DNS resolution
+    pub preference: AddressPreference,
+    /// Accept IPv4 clients on IPv6 listeners (e.g. when binding `::`)
+    pub dual_stack: bool,
+    /// File permissions applied to Unix domain socket listeners (e.g. `0o660`)
+    pub socket_mode: Option<u32>,
+}
+
+impl Default for BindOptions {
+    fn default() -> Self {
+        Self {
+            preference: AddressPreference::System,
+            dual_stack: true,
+            socket_mode: None,
+        }
+    }
+}
+
+/// Remove the surrounding brackets from an IPv6 literal such as `[::1]`
+pub fn strip_brackets(host: &str) -> &str {
+    host.strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+/// Format a host and port as an address string, bracketing IPv6 literals
+///
+/// # Examples
+/// ```
+/// use neural_network::address::format_host_port;
+///
+/// assert_eq!(format_host_port("127.0.0.1", 8080), "127.0.0.1:8080");
+/// assert_eq!(format_host_port("::1", 8080), "[::1]:8080");
+/// ```
+pub fn format_host_port(host: &str, port: u16) -> String {
+    let host = strip_brackets(host);
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Parse an endpoint string (`host`, `host:port`, `[v6]:port` or a bare IPv6 literal)
+///
+/// Returns the host without brackets and the port, falling back to `default_port`
+/// when the endpoint does not specify one.
+pub fn parse_host_port(endpoint: &str, default_port: u16) -> io::Result<(String, u16)> {
+    let invalid = || {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Invalid endpoint '{}'", endpoint),
+        )
+    };
+    let parse_port = |port: &str| port.parse::<u16>().map_err(|_| invalid());
+
+    let endpoint = endpoint.trim();
+    if endpoint.is_empty() {
+        return Err(invalid());
+    }
+
+    if let Some(rest) = endpoint.strip_prefix('[') {
+        let end = rest.find(']').ok_or_else(invalid)?;
+        let host = &rest[..end];
+        let port = match &rest[end + 1..] {
+            "" => default_port,
+            tail => parse_port(tail.strip_prefix(':').ok_or_else(invalid)?)?,
+        };
+        return Ok((host.to_string(), port));
+    }
+
+    match endpoint.matches(':').count() {
+        0 => Ok((endpoint.to_string(), default_port)),
+        1 => {
+            let (host, port) = endpoint.split_once(':').ok_or_else(invalid)?;
+            Ok((host.to_string(), parse_port(port)?))
+        }
+        // More than one colon without brackets can only be a bare IPv6 literal
+        _ => {
+            endpoint.parse::<IpAddr>().map_err(|_| invalid())?;
+            Ok((endpoint.to_string(), default_port))
+        }
+    }
+}
+
+/// Filter and order resolved addresses according to the preference
+pub fn apply_preference(
+    mut addrs: Vec<SocketAddr>,
+    preference: AddressPreference,
+) -> Vec<SocketAddr> {
+    match preference {
+        AddressPreference::System => {}
+        AddressPreference::PreferIpv4 => addrs.sort_by_key(|addr| addr.is_ipv6()),
+        AddressPreference::PreferIpv6 => addrs.sort_by_key(|addr| addr.is_ipv4()),
+        AddressPreference::Ipv4Only => addrs.retain(|addr| addr.is_ipv4()),
+        AddressPreference::Ipv6Only => addrs.retain(|addr| addr.is_ipv6()),
+    }
+    addrs
+}
+
+/// Resolve a host (IP literal or DNS name) into socket addresses ordered by preference
+pub async fn resolve(
+    host: &str,
+    port: u16,
+    preference: AddressPreference,
+) -> io::Result<Vec<SocketAddr>> {
+    let host = strip_brackets(host);
+
+    let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => tokio::net::lookup_host((host, port)).await?.collect(),
+    };
+
+    let addrs = apply_preference(addrs, preference);
+    if addrs.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrNotAvailable,
+            format!("No {:?} address found for '{}'", preference, host),
+        ));
+    }
+
+    Ok(addrs)
+}
+
+/// Bind a standard library TCP listener on a single socket address
+///
+/// IPv6 sockets honour `dual_stack` so that binding `::` can also accept IPv4 clients.
+pub fn bind_std_listener(addr: SocketAddr, dual_stack: bool) -> io::Result<std::net::TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(!dual_stack)?;
+    }
+    #[cfg(not(windows))]
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+    Ok(socket.into())
+}
+
+/// Ports a server's listeners were bound to
+///
+/// Configuring port 0 lets the OS pick free ports, e.g. in tests; these are
+/// the ports it picked. `None` until the listener is bound.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BoundPorts {
+    pub web: Option<u16>,
+    pub websocket: Option<u16>,
+    /// NNP listener of an `OutputServer` (`Some(0)` for a Unix socket)
+    pub nnp: Option<u16>,
+}
+
+/// Resolve `host` and bind a standard library TCP listener on the first usable address
+pub async fn bind_std_tcp_listener(
+    host: &str,
+    port: u16,
+    options: BindOptions,
+) -> io::Result<std::net::TcpListener> {
+    let mut last_error = None;
+    for addr in resolve(host, port, options.preference).await? {
+        match bind_std_listener(addr, options.dual_stack) {
+            Ok(listener) => return Ok(listener),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable)))
+}
+
+/// Resolve `host` and bind a tokio TCP listener on the first usable address
+pub async fn bind_tcp_listener(
+    host: &str,
+    port: u16,
+    options: BindOptions,
+) -> io::Result<TcpListener> {
+    let listener = bind_std_tcp_listener(host, port, options).await?;
+    TcpListener::from_std(listener)
+}
+
+/// Resolve `host` and connect to the first address that accepts the connection
+pub async fn connect_tcp(
+    host: &str,
+    port: u16,
+    preference: AddressPreference,
+) -> io::Result<TcpStream> {
+    let mut last_error = None;
+    for addr in resolve(host, port, preference).await? {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_host_port() {
+        assert_eq!(format_host_port("localhost", 80), "localhost:80");
+        assert_eq!(format_host_port("::", 8000), "[::]:8000");
+        assert_eq!(format_host_port("[fe80::1]", 8000), "[fe80::1]:8000");
+    }
+
+    #[test]
+    fn test_parse_host_port() {
+        assert_eq!(
+            parse_host_port("example.com:9000", 8080).unwrap(),
+            ("example.com".to_string(), 9000)
+        );
+        assert_eq!(
+            parse_host_port("example.com", 8080).unwrap(),
+            ("example.com".to_string(), 8080)
+        );
+        assert_eq!(
+            parse_host_port("[::1]:9000", 8080).unwrap(),
+            ("::1".to_string(), 9000)
+        );
+        assert_eq!(
+            parse_host_port("[::1]", 8080).unwrap(),
+            ("::1".to_string(), 8080)
+        );
+        assert_eq!(
+            parse_host_port("2001:db8::1", 8080).unwrap(),
+            ("2001:db8::1".to_string(), 8080)
+        );
+        assert!(parse_host_port("host:notaport", 8080).is_err());
+        assert!(parse_host_port("[::1", 8080).is_err());
+    }
+
+    #[test]
+    fn test_apply_preference() {
+        let v4: SocketAddr = "127.0.0.1:80".parse().unwrap();
+        let v6: SocketAddr = "[::1]:80".parse().unwrap();
+
+        let addrs = vec![v4, v6];
+        assert_eq!(
+            apply_preference(addrs.clone(), AddressPreference::PreferIpv6),
+            vec![v6, v4]
+        );
+        assert_eq!(
+            apply_preference(addrs.clone(), AddressPreference::Ipv4Only),
+            vec![v4]
+        );
+        assert_eq!(
+            apply_preference(addrs, AddressPreference::System),
+            vec![v4, v6]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_literal_respects_preference() {
+        assert!(resolve("::1", 80, AddressPreference::Ipv4Only)
+            .await
+            .is_err());
+        let addrs = resolve("[::1]", 80, AddressPreference::System)
+            .await
+            .unwrap();
+        assert_eq!(addrs, vec!["[::1]:80".parse().unwrap()]);
+    }
+}
@@ -0,0 +1,316 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// How long a webhook may take before the alert is dropped
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Training events that can raise an alert
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertEvent {
+    Completed,
+    EarlyStopped,
+    Diverged,
+    Improved,
+}
+
+/// Something noteworthy that happened during training
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TrainingAlert {
+    /// Training ran to the end (or stopped early) and the model is ready
+    Completed {
+        epochs: usize,
+        train_error: f64,
+        val_error: Option<f64>,
+        seconds: f64,
+    },
+    /// Validation error stopped improving
+    EarlyStopped { epoch: usize, best_val_error: f64 },
+    /// The training error became NaN or infinite
+    Diverged { epoch: usize },
+    /// Validation error improved by more than the configured threshold
+    Improved {
+        epoch: usize,
+        val_error: f64,
+        previous: f64,
+    },
+}
+
+impl TrainingAlert {
+    pub fn event(&self) -> AlertEvent {
+        match self {
+            TrainingAlert::Completed { .. } => AlertEvent::Completed,
+            TrainingAlert::EarlyStopped { .. } => AlertEvent::EarlyStopped,
+            TrainingAlert::Diverged { .. } => AlertEvent::Diverged,
+            TrainingAlert::Improved { .. } => AlertEvent::Improved,
+        }
+    }
+
+    /// One-line human-readable description
+    pub fn message(&self) -> String {
+        match self {
+            TrainingAlert::Completed {
+                epochs,
+                train_error,
+                val_error,
+                seconds,
+            } => {
+                let val = val_error.map_or_else(String::new, |e| format!(", val error {:.6}", e));
+                format!(
+                    "✅ Training completed after {} epochs in {:.1}s: train error {:.6}{}",
+                    epochs, seconds, train_error, val
+                )
+            }
+            TrainingAlert::EarlyStopped {
+                epoch,
+                best_val_error,
+            } => format!(
+                "🛑 Early stopping at epoch {} (best val error {:.6})",
+                epoch, best_val_error
+            ),
+            TrainingAlert::Diverged { epoch } => {
+                format!(
+                    "💥 Training diverged at epoch {} (error is not finite)",
+                    epoch
+                )
+            }
+            TrainingAlert::Improved {
+                epoch,
+                val_error,
+                previous,
+            } => format!(
+                "📉 Val error improved to {:.6} (from {:.6}) at epoch {}",
+                val_error, previous, epoch
+            ),
+        }
+    }
+}
+
+/// Payload format expected by a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    /// `{"text": ...}`
+    Slack,
+    /// `{"content": ...}`
+    Discord,
+    /// The alert itself as JSON, plus a `message` field
+    #[default]
+    Generic,
+}
+
+/// An HTTP endpoint alerts are POSTed to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    #[serde(default)]
+    pub kind: WebhookKind,
+    /// Events sent to this webhook (all of them when empty)
+    #[serde(default)]
+    pub events: Vec<AlertEvent>,
+}
+
+impl WebhookConfig {
+    pub fn wants(&self, event: AlertEvent) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+
+    /// JSON body for `alert`
+    pub fn payload(&self, alert: &TrainingAlert) -> serde_json::Value {
+        match self.kind {
+            WebhookKind::Slack => serde_json::json!({ "text": alert.message() }),
+            WebhookKind::Discord => serde_json::json!({ "content": alert.message() }),
+            WebhookKind::Generic => {
+                let mut value = serde_json::to_value(alert).unwrap_or_default();
+                value["message"] = serde_json::Value::String(alert.message());
+                value
+            }
+        }
+    }
+}
+
+/// Where and when training alerts are sent (`[training.alerts]`)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+    /// Also show a desktop notification (`notify-send` on Linux, `osascript` on macOS)
+    #[serde(default)]
+    pub desktop: bool,
+    /// Alert when the val error drops this much below the last alerted value
+    /// (improvement alerts are off when unset)
+    #[serde(default)]
+    pub improvement_threshold: Option<f64>,
+}
+
+/// Sends training alerts; delivery failures are reported but never stop training
+#[derive(Debug, Clone)]
+pub struct Alerter {
+    config: AlertConfig,
+    /// Val error of the last improvement alert, or the first one seen
+    last_improvement: Option<f64>,
+}
+
+impl Alerter {
+    pub fn new(config: AlertConfig) -> Self {
+        Self {
+            config,
+            last_improvement: None,
+        }
+    }
+
+    /// Whether any alert will be delivered anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.config.desktop || !self.config.webhooks.is_empty()
+    }
+
+    /// Improvement alert for this epoch's val error, when it beats the threshold
+    pub fn check_improvement(&mut self, epoch: usize, val_error: f64) -> Option<TrainingAlert> {
+        let threshold = self.config.improvement_threshold?;
+        let Some(previous) = self.last_improvement else {
+            self.last_improvement = Some(val_error);
+            return None;
+        };
+        if previous - val_error <= threshold {
+            return None;
+        }
+        self.last_improvement = Some(val_error);
+        Some(TrainingAlert::Improved {
+            epoch,
+            val_error,
+            previous,
+        })
+    }
+
+    /// Deliver `alert` to every webhook that wants it and the desktop
+    pub fn notify(&self, alert: &TrainingAlert) {
+        for webhook in self
+            .config
+            .webhooks
+            .iter()
+            .filter(|w| w.wants(alert.event()))
+        {
+            let result = ureq::post(&webhook.url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .set("Content-Type", "application/json")
+                .send_string(&webhook.payload(alert).to_string());
+            if let Err(e) = result {
+                println!("⚠️ Failed to send alert to {}: {}", webhook.url, e);
+            }
+        }
+        if self.config.desktop {
+            if let Err(e) = desktop_notification(&alert.message()) {
+                println!("⚠️ Failed to show desktop notification: {}", e);
+            }
+        }
+    }
+}
+
+fn desktop_notification(message: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let mut command = std::process::Command::new("osascript");
+        let script = format!(
+            "display notification {:?} with title \"benny\"",
+            message.replace('"', "'")
+        );
+        command.args(["-e", &script]);
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.args(["benny", message]);
+        command
+    };
+    command.status().map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read};
+
+    #[test]
+    fn test_improvement_alerts_and_payloads() {
+        let mut alerter = Alerter::new(AlertConfig {
+            improvement_threshold: Some(0.1),
+            ..Default::default()
+        });
+        assert!(!alerter.is_enabled());
+        assert_eq!(alerter.check_improvement(0, 1.0), None);
+        assert_eq!(alerter.check_improvement(1, 0.95), None);
+        assert_eq!(
+            alerter.check_improvement(2, 0.8),
+            Some(TrainingAlert::Improved {
+                epoch: 2,
+                val_error: 0.8,
+                previous: 1.0
+            })
+        );
+        assert_eq!(alerter.check_improvement(3, 0.75), None);
+
+        let alert = TrainingAlert::Diverged { epoch: 7 };
+        let slack = WebhookConfig {
+            url: "http://localhost".to_string(),
+            kind: WebhookKind::Slack,
+            events: vec![AlertEvent::Diverged],
+        };
+        assert!(slack.payload(&alert)["text"]
+            .as_str()
+            .unwrap()
+            .contains("epoch 7"));
+        assert!(!slack.wants(AlertEvent::Completed));
+        let generic = WebhookConfig {
+            kind: WebhookKind::Generic,
+            events: Vec::new(),
+            ..slack
+        };
+        let payload = generic.payload(&alert);
+        assert_eq!(payload["event"], "diverged");
+        assert_eq!(payload["epoch"], 7);
+        assert!(generic.wants(AlertEvent::Completed));
+    }
+
+    #[test]
+    fn test_webhook_delivery() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.to_ascii_lowercase().strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            use std::io::Write;
+            reader
+                .get_mut()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                .unwrap();
+            String::from_utf8(body).unwrap()
+        });
+
+        let alerter = Alerter::new(AlertConfig {
+            webhooks: vec![WebhookConfig {
+                url,
+                kind: WebhookKind::Discord,
+                events: Vec::new(),
+            }],
+            ..Default::default()
+        });
+        alerter.notify(&TrainingAlert::EarlyStopped {
+            epoch: 12,
+            best_val_error: 0.25,
+        });
+        let body: serde_json::Value = serde_json::from_str(&server.join().unwrap()).unwrap();
+        assert!(body["content"].as_str().unwrap().contains("epoch 12"));
+    }
+}
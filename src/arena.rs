@@ -0,0 +1,334 @@
+use crate::environment::Controller;
+use crate::input_adapter::InputAdapter;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+
+/// Which agents each agent observes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Topology {
+    /// Every agent observes every other agent
+    AllToAll,
+    /// Agent `i` observes agent `i - 1`, the first observes the last
+    Ring,
+    /// The hub observes everyone, everyone else observes the hub
+    Star { hub: usize },
+    /// Explicit `(observed, observer)` pairs
+    Edges { edges: Vec<(usize, usize)> },
+}
+
+impl Topology {
+    /// Agents `agent` observes, in the order their outputs are concatenated
+    pub fn sources(&self, agent: usize, agents: usize) -> Vec<usize> {
+        match self {
+            Topology::AllToAll => (0..agents).filter(|&other| other != agent).collect(),
+            Topology::Ring if agents < 2 => Vec::new(),
+            Topology::Ring => vec![(agent + agents - 1) % agents],
+            Topology::Star { hub } if agent == *hub => {
+                (0..agents).filter(|&other| other != agent).collect()
+            }
+            Topology::Star { hub } => vec![*hub],
+            Topology::Edges { edges } => edges
+                .iter()
+                .filter(|&&(from, to)| to == agent && from < agents)
+                .map(|&(from, _)| from)
+                .collect(),
+        }
+    }
+}
+
+/// How a local agent learns from each round
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AgentLearning {
+    /// No weight updates
+    #[default]
+    Frozen,
+    /// Unsupervised Hebbian update on every observation
+    Hebbian,
+    /// Supervised step towards the mean output of the observed agents
+    Imitate,
+}
+
+enum Policy {
+    Local(Box<NeuralNetwork>),
+    Remote(Box<dyn Controller + Send>),
+}
+
+struct ArenaAgent {
+    name: String,
+    policy: Policy,
+    learning: AgentLearning,
+    /// Fits the concatenated outputs of the observed agents to the input size
+    adapter: InputAdapter,
+    outputs: Vec<f64>,
+}
+
+/// Outputs of every agent after one round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ArenaRound {
+    pub round: usize,
+    pub outputs: Vec<Vec<f64>>,
+    /// Mean squared difference between observed and observing agents' outputs
+    pub disagreement: f64,
+}
+
+/// Agents that observe each other's outputs over repeated rounds
+///
+/// Every round all agents act at once on observations built from the
+/// previous round's outputs, so unlike a `NetworkComposer` the interaction
+/// graph may contain cycles. Agents are local networks with their own
+/// learning settings, or remote nodes behind a `Controller` such as
+/// `RemoteController`. Outputs start at zero.
+pub struct Arena {
+    agents: Vec<ArenaAgent>,
+    topology: Topology,
+    round: usize,
+}
+
+impl Arena {
+    pub fn new(topology: Topology) -> Self {
+        Self {
+            agents: Vec::new(),
+            topology,
+            round: 0,
+        }
+    }
+
+    /// Add a local network; returns its index
+    pub fn add_agent(
+        &mut self,
+        name: &str,
+        network: NeuralNetwork,
+        learning: AgentLearning,
+    ) -> Result<usize, String> {
+        let outputs = network.get_layers()[network.num_layers() - 1];
+        self.push(name, Policy::Local(Box::new(network)), learning, outputs)
+    }
+
+    /// Add an agent running elsewhere that produces `outputs` values per round; returns its index
+    pub fn add_remote_agent(
+        &mut self,
+        name: &str,
+        controller: Box<dyn Controller + Send>,
+        outputs: usize,
+    ) -> Result<usize, String> {
+        self.push(
+            name,
+            Policy::Remote(controller),
+            AgentLearning::Frozen,
+            outputs,
+        )
+    }
+
+    fn push(
+        &mut self,
+        name: &str,
+        policy: Policy,
+        learning: AgentLearning,
+        outputs: usize,
+    ) -> Result<usize, String> {
+        if self.agents.iter().any(|agent| agent.name == name) {
+            return Err(format!("Agent '{}' already exists", name));
+        }
+        self.agents.push(ArenaAgent {
+            name: name.to_string(),
+            policy,
+            learning,
+            adapter: InputAdapter::Truncate,
+            outputs: vec![0.0; outputs],
+        });
+        Ok(self.agents.len() - 1)
+    }
+
+    /// Map observations of agent `agent` with `adapter` (truncating or zero-padding by default)
+    pub fn set_adapter(&mut self, agent: usize, adapter: InputAdapter) -> Result<(), String> {
+        let slot = self
+            .agents
+            .get_mut(agent)
+            .ok_or_else(|| format!("Agent {} does not exist", agent))?;
+        slot.adapter = adapter;
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    /// Rounds played so far
+    pub fn rounds(&self) -> usize {
+        self.round
+    }
+
+    pub fn agent_index(&self, name: &str) -> Option<usize> {
+        self.agents.iter().position(|agent| agent.name == name)
+    }
+
+    /// Latest outputs of an agent
+    pub fn outputs(&self, agent: usize) -> &[f64] {
+        &self.agents[agent].outputs
+    }
+
+    /// The network of a local agent
+    pub fn network(&self, agent: usize) -> Option<&NeuralNetwork> {
+        match &self.agents.get(agent)?.policy {
+            Policy::Local(network) => Some(network.as_ref()),
+            Policy::Remote(_) => None,
+        }
+    }
+
+    /// What agent `agent` sees next round: the observed agents' outputs, concatenated
+    pub fn observation(&self, agent: usize) -> Result<Vec<f64>, String> {
+        let slot = &self.agents[agent];
+        let raw: Vec<f64> = self
+            .topology
+            .sources(agent, self.agents.len())
+            .into_iter()
+            .flat_map(|source| self.agents[source].outputs.iter().copied())
+            .collect();
+        let size = match &slot.policy {
+            Policy::Local(network) => network.input_size(),
+            Policy::Remote(_) => return Ok(raw),
+        };
+        slot.adapter
+            .adapt(&raw, size)
+            .map_err(|e| format!("Agent '{}': {}", slot.name, e))
+    }
+
+    /// Play one round: every agent acts on the previous outputs, then learns
+    pub fn step(&mut self) -> Result<ArenaRound, String> {
+        let observations = (0..self.agents.len())
+            .map(|agent| self.observation(agent))
+            .collect::<Result<Vec<_>, _>>()?;
+        let targets: Vec<Vec<f64>> = (0..self.agents.len())
+            .map(|agent| self.imitation_target(agent))
+            .collect();
+
+        for ((slot, observation), target) in self.agents.iter_mut().zip(&observations).zip(targets)
+        {
+            slot.outputs = match &mut slot.policy {
+                Policy::Local(network) => {
+                    let (outputs, _) = network.forward(observation);
+                    match slot.learning {
+                        AgentLearning::Frozen => {}
+                        AgentLearning::Hebbian => network.train_unsupervised(observation),
+                        AgentLearning::Imitate => {
+                            network.train(observation, &target);
+                        }
+                    }
+                    outputs
+                }
+                Policy::Remote(controller) => controller
+                    .act(observation)
+                    .map_err(|e| format!("Agent '{}': {}", slot.name, e))?,
+            };
+        }
+
+        self.round += 1;
+        Ok(ArenaRound {
+            round: self.round,
+            outputs: self.agents.iter().map(|a| a.outputs.clone()).collect(),
+            disagreement: self.disagreement(),
+        })
+    }
+
+    pub fn run(&mut self, rounds: usize) -> Result<Vec<ArenaRound>, String> {
+        (0..rounds).map(|_| self.step()).collect()
+    }
+
+    /// Mean squared difference between the outputs of every observed/observer pair
+    ///
+    /// Falls as agents reach consensus; outputs of different sizes are
+    /// compared over their common prefix.
+    pub fn disagreement(&self) -> f64 {
+        let mut total = 0.0;
+        let mut count = 0;
+        for agent in 0..self.agents.len() {
+            for source in self.topology.sources(agent, self.agents.len()) {
+                let (a, b) = (&self.agents[agent].outputs, &self.agents[source].outputs);
+                for (x, y) in a.iter().zip(b) {
+                    total += (x - y).powi(2);
+                    count += 1;
+                }
+            }
+        }
+        if count == 0 {
+            0.0
+        } else {
+            total / count as f64
+        }
+    }
+
+    /// Mean previous output of the agents `agent` observes, resampled to its output size
+    fn imitation_target(&self, agent: usize) -> Vec<f64> {
+        let size = self.agents[agent].outputs.len();
+        let sources = self.topology.sources(agent, self.agents.len());
+        let mut target = vec![0.0; size];
+        for &source in &sources {
+            let outputs = InputAdapter::Interpolate
+                .adapt(&self.agents[source].outputs, size)
+                .unwrap_or_else(|_| vec![0.0; size]);
+            for (sum, value) in target.iter_mut().zip(outputs) {
+                *sum += value / sources.len() as f64;
+            }
+        }
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_topologies() {
+        assert_eq!(Topology::AllToAll.sources(1, 3), vec![0, 2]);
+        assert_eq!(Topology::Ring.sources(0, 3), vec![2]);
+        assert_eq!(Topology::Star { hub: 1 }.sources(1, 3), vec![0, 2]);
+        assert_eq!(Topology::Star { hub: 1 }.sources(2, 3), vec![1]);
+        let edges = Topology::Edges {
+            edges: vec![(0, 2), (1, 2), (2, 0), (5, 2)],
+        };
+        assert_eq!(edges.sources(2, 3), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_rounds_feed_outputs_to_observers() {
+        let mut arena = Arena::new(Topology::Ring);
+        let mut learner = NeuralNetwork::with_layers(&[2, 3, 2], 0.0);
+        learner.set_backprop_enabled(true, 0.5);
+        let frozen = NeuralNetwork::with_layers(&[2, 2], 0.0);
+        let weights = frozen.get_layer_weights(0);
+        arena
+            .add_agent("learner", learner, AgentLearning::Imitate)
+            .unwrap();
+        arena
+            .add_agent("frozen", frozen, AgentLearning::Frozen)
+            .unwrap();
+        assert!(arena
+            .add_agent(
+                "frozen",
+                NeuralNetwork::with_layers(&[2, 2], 0.0),
+                AgentLearning::Frozen
+            )
+            .is_err());
+
+        let first = arena.step().unwrap();
+        assert_eq!(first.outputs.len(), 2);
+        assert_eq!(arena.observation(1).unwrap(), first.outputs[0]);
+
+        let rounds = arena.run(200).unwrap();
+        assert_eq!(arena.rounds(), 201);
+        assert_eq!(arena.network(1).unwrap().get_layer_weights(0), weights);
+        // The learner ends up copying what it observes: the frozen agent's previous outputs
+        let last = &rounds[rounds.len() - 1];
+        let previous = &rounds[rounds.len() - 2];
+        for (copied, observed) in last.outputs[0].iter().zip(&previous.outputs[1]) {
+            assert!((copied - observed).abs() < 0.1);
+        }
+    }
+}
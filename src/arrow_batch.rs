@@ -0,0 +1,224 @@
+use crate::neural_network::NeuralNetwork;
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type,
+    UInt64Type, UInt8Type,
+};
+use arrow_array::{Array, FixedSizeListArray, Float64Array, RecordBatch};
+use arrow_schema::{ArrowError, DataType, Field};
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// A numeric column borrowed from a record batch
+enum Column<'a> {
+    F64(&'a [f64]),
+    F32(&'a [f32]),
+    I64(&'a [i64]),
+    I32(&'a [i32]),
+    I16(&'a [i16]),
+    I8(&'a [i8]),
+    U64(&'a [u64]),
+    U32(&'a [u32]),
+    U16(&'a [u16]),
+    U8(&'a [u8]),
+    /// A `FixedSizeList<Float64>` column filling `width` consecutive inputs
+    List {
+        values: &'a [f64],
+        width: usize,
+    },
+}
+
+impl<'a> Column<'a> {
+    fn new(name: &str, array: &'a dyn Array) -> Result<Self, ArrowError> {
+        if array.null_count() > 0 {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Input column '{}' has {} nulls",
+                name,
+                array.null_count()
+            )));
+        }
+        Ok(match array.data_type() {
+            DataType::Float64 => Column::F64(array.as_primitive::<Float64Type>().values()),
+            DataType::Float32 => Column::F32(array.as_primitive::<Float32Type>().values()),
+            DataType::Int64 => Column::I64(array.as_primitive::<Int64Type>().values()),
+            DataType::Int32 => Column::I32(array.as_primitive::<Int32Type>().values()),
+            DataType::Int16 => Column::I16(array.as_primitive::<Int16Type>().values()),
+            DataType::Int8 => Column::I8(array.as_primitive::<Int8Type>().values()),
+            DataType::UInt64 => Column::U64(array.as_primitive::<UInt64Type>().values()),
+            DataType::UInt32 => Column::U32(array.as_primitive::<UInt32Type>().values()),
+            DataType::UInt16 => Column::U16(array.as_primitive::<UInt16Type>().values()),
+            DataType::UInt8 => Column::U8(array.as_primitive::<UInt8Type>().values()),
+            DataType::FixedSizeList(item, width) if item.data_type() == &DataType::Float64 => {
+                let list = array.as_fixed_size_list();
+                let values = list.values();
+                if values.null_count() > 0 {
+                    return Err(ArrowError::InvalidArgumentError(format!(
+                        "Input column '{}' has null list items",
+                        name
+                    )));
+                }
+                Column::List {
+                    values: values.as_primitive::<Float64Type>().values(),
+                    width: *width as usize,
+                }
+            }
+            other => {
+                return Err(ArrowError::InvalidArgumentError(format!(
+                    "Input column '{}' has non-numeric type {}",
+                    name, other
+                )))
+            }
+        })
+    }
+
+    /// Inputs this column fills per row
+    fn width(&self) -> usize {
+        match self {
+            Column::List { width, .. } => *width,
+            _ => 1,
+        }
+    }
+
+    /// Append the values of `row` to `inputs`
+    fn read(&self, row: usize, inputs: &mut Vec<f64>) {
+        match self {
+            Column::F64(values) => inputs.push(values[row]),
+            Column::F32(values) => inputs.push(values[row] as f64),
+            Column::I64(values) => inputs.push(values[row] as f64),
+            Column::I32(values) => inputs.push(values[row] as f64),
+            Column::I16(values) => inputs.push(values[row] as f64),
+            Column::I8(values) => inputs.push(values[row] as f64),
+            Column::U64(values) => inputs.push(values[row] as f64),
+            Column::U32(values) => inputs.push(values[row] as f64),
+            Column::U16(values) => inputs.push(values[row] as f64),
+            Column::U8(values) => inputs.push(values[row] as f64),
+            Column::List { values, width } => {
+                let start = row * width;
+                inputs.extend_from_slice(&values[start..start + width]);
+            }
+        }
+    }
+}
+
+impl NeuralNetwork {
+    /// Run `forward_batch` over an Arrow record batch, one sample per row
+    ///
+    /// `columns` names the input columns in input order; when empty every
+    /// column is used in schema order. Columns may hold any primitive numeric
+    /// type, or be fixed-size lists of `Float64` spanning several inputs, and
+    /// are read where they are instead of being converted to rows first. The
+    /// outputs come back as a `FixedSizeList<Float64>` array with one entry
+    /// per row, ready to be attached to the batch as a new column.
+    pub fn forward_record_batch(
+        &self,
+        batch: &RecordBatch,
+        columns: &[&str],
+    ) -> Result<FixedSizeListArray, ArrowError> {
+        let schema = batch.schema();
+        let indices = if columns.is_empty() {
+            (0..batch.num_columns()).collect()
+        } else {
+            columns
+                .iter()
+                .map(|name| schema.index_of(name))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        let inputs = indices
+            .into_iter()
+            .map(|i| Column::new(schema.field(i).name(), batch.column(i).as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let width: usize = inputs.iter().map(Column::width).sum();
+        if width != self.input_size() {
+            return Err(ArrowError::InvalidArgumentError(format!(
+                "Input columns provide {} values per row, the network takes {}",
+                width,
+                self.input_size()
+            )));
+        }
+
+        let output_size = *self.get_layers().last().unwrap_or(&0);
+        let outputs: Vec<f64> = (0..batch.num_rows())
+            .into_par_iter()
+            .flat_map_iter(|row| {
+                let mut sample = Vec::with_capacity(width);
+                for column in &inputs {
+                    column.read(row, &mut sample);
+                }
+                let mut activations = self.forward_all_layers(&sample);
+                activations.pop().unwrap_or_default()
+            })
+            .collect();
+        FixedSizeListArray::try_new(
+            Arc::new(Field::new("item", DataType::Float64, false)),
+            output_size as i32,
+            Arc::new(Float64Array::from(outputs)),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use arrow_array::{Float32Array, Int32Array, StringArray};
+    use arrow_schema::Schema;
+
+    #[test]
+    fn test_record_batches_score_like_forward_batch() {
+        let nn = NeuralNetwork::with_layers(&[3, 4, 2], 0.1);
+        let features = FixedSizeListArray::try_new(
+            Arc::new(Field::new("item", DataType::Float64, false)),
+            2,
+            Arc::new(Float64Array::from(vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6])),
+            None,
+        )
+        .unwrap();
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Utf8, false),
+            Field::new("bias", DataType::Float32, false),
+            Field::new("features", features.data_type().clone(), false),
+            Field::new("count", DataType::Int32, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringArray::from(vec!["a", "b", "c"])),
+                Arc::new(Float32Array::from(vec![1.0, 0.5, 0.0])),
+                Arc::new(features),
+                Arc::new(Int32Array::from(vec![7, 8, 9])),
+            ],
+        )
+        .unwrap();
+
+        let outputs = nn
+            .forward_record_batch(&batch, &["bias", "features"])
+            .unwrap();
+        let expected = nn.forward_batch(&[
+            vec![1.0, 0.1, 0.2],
+            vec![0.5, 0.3, 0.4],
+            vec![0.0, 0.5, 0.6],
+        ]);
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs.value_length(), 2);
+        let values = outputs.values().as_primitive::<Float64Type>().values();
+        assert_eq!(values.to_vec(), expected.concat());
+
+        // A sliced batch reads the same rows in place
+        let tail = nn
+            .forward_record_batch(&batch.slice(1, 2), &["bias", "features"])
+            .unwrap();
+        assert_eq!(
+            tail.values()
+                .as_primitive::<Float64Type>()
+                .values()
+                .to_vec(),
+            expected[1..].concat()
+        );
+
+        assert!(nn.forward_record_batch(&batch, &["bias", "count"]).is_err());
+        assert!(nn
+            .forward_record_batch(&batch, &["id", "features"])
+            .is_err());
+        assert!(nn.forward_record_batch(&batch, &["missing"]).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use crate::atomic_file::{self, temporary_path};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Read, Write};
+use std::path::Path;
+use tokio::io::AsyncWriteExt;
+
+/// First bytes of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Compression of files written by the `*_async` save functions
+///
+/// Reading never needs it: compressed files are recognized by their header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    #[default]
+    None,
+    Gzip,
+}
+
+impl Compression {
+    /// `bytes` as they are stored on disk
+    pub fn encode(self, bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(bytes),
+            Compression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(&bytes)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// `bytes` read from disk, decompressed if they are gzip
+pub fn decode(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(bytes);
+    }
+    let mut decoded = Vec::new();
+    GzDecoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+    Ok(decoded)
+}
+
+/// Whether a model file holds bincode rather than JSON: `.bin`, also under a `.gz` suffix
+pub fn is_binary(path: &Path) -> bool {
+    let path = match path.extension() {
+        Some(extension) if extension == "gz" => path.with_extension(""),
+        _ => path.to_path_buf(),
+    };
+    path.extension().is_some_and(|extension| extension == "bin")
+}
+
+/// Write `bytes` to `path` without blocking the runtime, atomically
+///
+/// The async twin of `atomic_file::write_atomic`: the data is compressed on
+/// the blocking thread pool, written and synced to disk next to `path` and
+/// renamed over it, so readers and crashes never see a partial file.
+pub async fn write_atomic(path: &Path, bytes: Vec<u8>, compression: Compression) -> io::Result<()> {
+    let bytes = tokio::task::spawn_blocking(move || compression.encode(bytes))
+        .await
+        .map_err(io::Error::other)??;
+    let temporary = temporary_path(path);
+    let result = async {
+        let mut file = tokio::fs::File::create(&temporary).await?;
+        file.write_all(&bytes).await?;
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&temporary, path).await
+    }
+    .await;
+    if result.is_err() {
+        let _ = tokio::fs::remove_file(&temporary).await;
+        return result;
+    }
+    let path = path.to_path_buf();
+    let _ = tokio::task::spawn_blocking(move || atomic_file::sync_directory(&path)).await;
+    Ok(())
+}
+
+/// Read `path` without blocking the runtime, decompressing it if needed
+pub async fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let bytes = tokio::fs::read(path).await?;
+    tokio::task::spawn_blocking(move || decode(bytes))
+        .await
+        .map_err(io::Error::other)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_atomic_compressed_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("benny-async-io-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("model.json.gz");
+        let data = b"{\"weights\": [0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5, 0.5]}".repeat(20);
+
+        write_atomic(&path, data.clone(), Compression::Gzip)
+            .await
+            .unwrap();
+        let stored = tokio::fs::read(&path).await.unwrap();
+        assert!(stored.starts_with(&GZIP_MAGIC));
+        assert!(stored.len() < data.len());
+        assert_eq!(read(&path).await.unwrap(), data);
+        // Nothing is left next to the file
+        assert!(!temporary_path(&path).exists());
+
+        // Plain files read back as they are, replacing the compressed one
+        write_atomic(&path, b"plain".to_vec(), Compression::None)
+            .await
+            .unwrap();
+        assert_eq!(read(&path).await.unwrap(), b"plain");
+
+        assert!(is_binary(Path::new("model.bin.gz")));
+        assert!(is_binary(Path::new("model.bin")));
+        assert!(!is_binary(Path::new("model.json.gz")));
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Sibling of `path` a save goes to before it is renamed over `path`
+pub fn temporary_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Replace `path` with `bytes` so that a crash leaves either the old or the new file
+pub fn write_atomic<P: AsRef<Path>>(path: P, bytes: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |out| out.write_all(bytes))
+}
+
+/// `write_atomic` for files produced piece by piece
+///
+/// `write` fills a buffered temporary file next to `path`, which is flushed,
+/// synced to disk and only then renamed over `path`. The directory is synced
+/// too, so the rename itself survives a power loss. On failure the temporary
+/// file is removed and `path` is left untouched.
+pub fn write_atomic_with<P, F>(path: P, write: F) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut BufWriter<fs::File>) -> io::Result<()>,
+{
+    let path = path.as_ref();
+    let temporary = temporary_path(path);
+    let result = (|| {
+        let mut out = BufWriter::new(fs::File::create(&temporary)?);
+        write(&mut out)?;
+        out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&temporary, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+        return result;
+    }
+    sync_directory(path);
+    Ok(())
+}
+
+/// Make a rename in `path`'s directory durable; directories cannot be synced on every platform
+pub(crate) fn sync_directory(path: &Path) {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    if let Ok(dir) = fs::File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// A saved file that ends before its contents do
+///
+/// Files written by `write_atomic` are never partial, so this points at a
+/// file written by an older version or copied incompletely, or at a disk
+/// that lost data. `interrupted` names the temporary file a crashed save
+/// left behind, if there is one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TruncatedFile {
+    pub path: PathBuf,
+    pub size: u64,
+    pub interrupted: Option<PathBuf>,
+}
+
+impl TruncatedFile {
+    pub fn new(path: &Path) -> Self {
+        let temporary = temporary_path(path);
+        Self {
+            path: path.to_path_buf(),
+            size: fs::metadata(path).map_or(0, |m| m.len()),
+            interrupted: temporary.exists().then_some(temporary),
+        }
+    }
+}
+
+impl fmt::Display for TruncatedFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is truncated: its {} bytes end in the middle of the data; restore it from a backup or checkpoint",
+            self.path.display(),
+            self.size
+        )?;
+        if let Some(interrupted) = &self.interrupted {
+            write!(
+                f,
+                " ({} is left from an interrupted save)",
+                interrupted.display()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TruncatedFile {}
+
+/// Why a saved file could not be read back
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error(transparent)]
+    Truncated(#[from] TruncatedFile),
+    #[error(transparent)]
+    Json(serde_json::Error),
+    #[error(transparent)]
+    Bincode(bincode::Error),
+}
+
+/// `error` from parsing the JSON in `path`, reported as `TruncatedFile` when the data ran out
+pub fn check_json(path: &Path, error: serde_json::Error) -> LoadError {
+    if error.is_eof() {
+        LoadError::Truncated(TruncatedFile::new(path))
+    } else {
+        LoadError::Json(error)
+    }
+}
+
+/// `error` from decoding the bincode in `path`, reported as `TruncatedFile` when the data ran out
+pub fn check_bincode(path: &Path, error: bincode::Error) -> LoadError {
+    match *error {
+        bincode::ErrorKind::Io(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+            LoadError::Truncated(TruncatedFile::new(path))
+        }
+        _ => LoadError::Bincode(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atomic_write_and_truncation() {
+        let dir = std::env::temp_dir().join(format!("benny-atomic-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("model.json");
+
+        write_atomic(&path, b"{\"a\": 1}").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"{\"a\": 1}");
+        assert!(!temporary_path(&path).exists());
+
+        // A failing save leaves the previous file and no temporary behind
+        let failed = write_atomic_with(&path, |out| {
+            out.write_all(b"{\"a\"")?;
+            Err(io::Error::other("disk full"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read(&path).unwrap(), b"{\"a\": 1}");
+        assert!(!temporary_path(&path).exists());
+
+        // A cut-off file is told apart from a malformed one
+        fs::write(&path, b"{\"a\": [1, 2").unwrap();
+        fs::write(temporary_path(&path), b"{").unwrap();
+        let error = serde_json::from_slice::<serde_json::Value>(&fs::read(&path).unwrap())
+            .map_err(|e| check_json(&path, e))
+            .unwrap_err();
+        assert!(error.to_string().contains("interrupted save"));
+        let LoadError::Truncated(truncated) = error else {
+            panic!("expected a truncated file, got {:?}", error);
+        };
+        assert_eq!(truncated.size, 11);
+        assert_eq!(truncated.interrupted, Some(temporary_path(&path)));
+        let error = check_json(
+            &path,
+            serde_json::from_str::<serde_json::Value>("{]").unwrap_err(),
+        );
+        assert!(matches!(error, LoadError::Json(_)));
+
+        let encoded = bincode::serialize(&vec![1.0f64, 2.0, 3.0]).unwrap();
+        let error = bincode::deserialize::<Vec<f64>>(&encoded[..encoded.len() - 3]).unwrap_err();
+        assert!(matches!(
+            check_bincode(&path, error),
+            LoadError::Truncated(_)
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,476 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Where raw PCM audio is read from
+///
+/// There is no built-in capture backend: live audio comes from a device file or
+/// from an external capture program such as `arecord` (ALSA) or `parec` (PulseAudio)
+/// that must be installed separately.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AudioSource {
+    /// A file, FIFO or character device
+    File { path: PathBuf },
+    /// Standard output of a capture program, e.g. `arecord -q -t raw -f S16_LE -c 1 -r 16000`
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl AudioSource {
+    fn describe(&self) -> String {
+        match self {
+            AudioSource::File { path } => path.display().to_string(),
+            AudioSource::Command { command, .. } => command.clone(),
+        }
+    }
+}
+
+/// Encoding of the PCM samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SampleFormat {
+    /// Signed 16-bit little endian
+    #[default]
+    S16Le,
+    /// 32-bit float little endian
+    F32Le,
+}
+
+impl SampleFormat {
+    fn width(self) -> usize {
+        match self {
+            SampleFormat::S16Le => 2,
+            SampleFormat::F32Le => 4,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> f64 {
+        match self {
+            SampleFormat::S16Le => i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32768.0,
+            SampleFormat::F32Le => {
+                f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64
+            }
+        }
+    }
+}
+
+/// What is computed from every window of audio
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum AudioFeatures {
+    /// Magnitudes of the lowest `bins` FFT bins, all `window / 2 + 1` when unset
+    Spectrum {
+        #[serde(default)]
+        bins: Option<usize>,
+    },
+    /// Magnitudes summed by `bands` triangular mel-spaced filters between `min_hz` and `max_hz`
+    MelBands {
+        bands: usize,
+        #[serde(default)]
+        min_hz: f64,
+        /// Half the sample rate when unset
+        #[serde(default)]
+        max_hz: Option<f64>,
+    },
+}
+
+/// Live audio turned into input vectors of an InputServer target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AudioInputConfig {
+    pub source: AudioSource,
+    /// InputServer target the features are sent to
+    pub target_id: String,
+    #[serde(default)]
+    pub format: SampleFormat,
+    /// Interleaved channels, averaged into one
+    #[serde(default = "default_channels")]
+    pub channels: usize,
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: u32,
+    /// Samples per FFT, a power of two
+    #[serde(default = "default_window")]
+    pub window: usize,
+    /// Samples between the starts of consecutive windows; one input vector is sent per hop
+    #[serde(default = "default_hop")]
+    pub hop: usize,
+    pub features: AudioFeatures,
+    /// Compress every feature with `ln(1 + x)`
+    #[serde(default)]
+    pub log: bool,
+}
+
+fn default_channels() -> usize {
+    1
+}
+
+fn default_sample_rate() -> u32 {
+    16000
+}
+
+fn default_window() -> usize {
+    512
+}
+
+fn default_hop() -> usize {
+    256
+}
+
+impl AudioInputConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.channels == 0 || self.sample_rate == 0 {
+            return Err("Audio input needs at least one channel and a sample rate".to_string());
+        }
+        if self.window < 2 || !self.window.is_power_of_two() {
+            return Err(format!(
+                "Audio window {} must be a power of two",
+                self.window
+            ));
+        }
+        if self.hop == 0 || self.hop > self.window {
+            return Err(format!(
+                "Audio hop {} must be between 1 and the window of {}",
+                self.hop, self.window
+            ));
+        }
+        let nyquist = self.sample_rate as f64 / 2.0;
+        match self.features {
+            AudioFeatures::Spectrum { bins: Some(bins) }
+                if bins == 0 || bins > self.window / 2 + 1 =>
+            {
+                Err(format!(
+                    "A window of {} samples has {} bins, not {}",
+                    self.window,
+                    self.window / 2 + 1,
+                    bins
+                ))
+            }
+            AudioFeatures::MelBands { bands: 0, .. } => {
+                Err("Mel features need at least one band".to_string())
+            }
+            AudioFeatures::MelBands { min_hz, max_hz, .. } => {
+                let max_hz = max_hz.unwrap_or(nyquist);
+                if !(min_hz >= 0.0 && min_hz < max_hz && max_hz <= nyquist) {
+                    Err(format!(
+                        "Mel bands [{}, {}] Hz must lie within [0, {}] Hz",
+                        min_hz, max_hz, nyquist
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Values in every input vector
+    pub fn feature_count(&self) -> usize {
+        match self.features {
+            AudioFeatures::Spectrum { bins } => bins.unwrap_or(self.window / 2 + 1),
+            AudioFeatures::MelBands { bands, .. } => bands,
+        }
+    }
+
+    /// Input vectors sent per second
+    pub fn frame_rate(&self) -> f64 {
+        self.sample_rate as f64 / self.hop as f64
+    }
+}
+
+/// In-place iterative radix-2 FFT; `re` and `im` have the same power-of-two length
+pub fn fft(re: &mut [f64], im: &mut [f64]) {
+    let n = re.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -2.0 * PI / len as f64;
+        for start in (0..n).step_by(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f64).sin_cos();
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cos - im[b] * sin;
+                let t_im = re[b] * sin + im[b] * cos;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+            }
+        }
+        len <<= 1;
+    }
+}
+
+fn hz_to_mel(hz: f64) -> f64 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+fn mel_to_hz(mel: f64) -> f64 {
+    700.0 * (10f64.powf(mel / 2595.0) - 1.0)
+}
+
+/// Turns a stream of PCM bytes into feature vectors, one per hop
+#[derive(Debug, Clone)]
+pub struct FeatureExtractor {
+    config: AudioInputConfig,
+    /// Hann window
+    weights: Vec<f64>,
+    /// Bins and weights of every mel filter
+    filters: Vec<Vec<(usize, f64)>>,
+    samples: VecDeque<f64>,
+    pending: Vec<u8>,
+}
+
+impl FeatureExtractor {
+    pub fn new(config: AudioInputConfig) -> Result<Self, String> {
+        config.validate()?;
+        let n = config.window;
+        let weights = (0..n)
+            .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / n as f64).cos())
+            .collect();
+        let filters = match config.features {
+            AudioFeatures::MelBands {
+                bands,
+                min_hz,
+                max_hz,
+            } => {
+                let max_hz = max_hz.unwrap_or(config.sample_rate as f64 / 2.0);
+                let (low, high) = (hz_to_mel(min_hz), hz_to_mel(max_hz));
+                let edges: Vec<f64> = (0..bands + 2)
+                    .map(|i| mel_to_hz(low + (high - low) * i as f64 / (bands + 1) as f64))
+                    .collect();
+                let bin_hz = config.sample_rate as f64 / n as f64;
+                edges
+                    .windows(3)
+                    .map(|edge| {
+                        (0..=n / 2)
+                            .filter_map(|bin| {
+                                let hz = bin as f64 * bin_hz;
+                                let weight = if hz <= edge[1] {
+                                    (hz - edge[0]) / (edge[1] - edge[0])
+                                } else {
+                                    (edge[2] - hz) / (edge[2] - edge[1])
+                                };
+                                (weight > 0.0).then_some((bin, weight))
+                            })
+                            .collect()
+                    })
+                    .collect()
+            }
+            AudioFeatures::Spectrum { .. } => Vec::new(),
+        };
+        Ok(Self {
+            config,
+            weights,
+            filters,
+            samples: VecDeque::new(),
+            pending: Vec::new(),
+        })
+    }
+
+    pub fn config(&self) -> &AudioInputConfig {
+        &self.config
+    }
+
+    /// Feed raw PCM bytes, returning the feature vectors of every window they complete
+    pub fn push_bytes(&mut self, bytes: &[u8]) -> Vec<Vec<f64>> {
+        self.pending.extend_from_slice(bytes);
+        let frame = self.config.format.width() * self.config.channels;
+        let complete = self.pending.len() / frame * frame;
+        let samples: Vec<f64> = self.pending[..complete]
+            .chunks(frame)
+            .map(|frame| {
+                frame
+                    .chunks(self.config.format.width())
+                    .map(|sample| self.config.format.decode(sample))
+                    .sum::<f64>()
+                    / self.config.channels as f64
+            })
+            .collect();
+        self.pending.drain(..complete);
+        self.push(&samples)
+    }
+
+    /// Feed mono samples in `[-1, 1]`, returning the feature vectors of every window they complete
+    pub fn push(&mut self, samples: &[f64]) -> Vec<Vec<f64>> {
+        self.samples.extend(samples);
+        let mut frames = Vec::new();
+        while self.samples.len() >= self.config.window {
+            frames.push(self.features());
+            self.samples.drain(..self.config.hop);
+        }
+        frames
+    }
+
+    /// Features of the window at the front of the buffer
+    fn features(&self) -> Vec<f64> {
+        let mut re: Vec<f64> = self
+            .samples
+            .iter()
+            .zip(&self.weights)
+            .map(|(sample, weight)| sample * weight)
+            .collect();
+        let mut im = vec![0.0; re.len()];
+        fft(&mut re, &mut im);
+        // Scaled so a full-scale sine on a bin has magnitude 1
+        let scale = 2.0 / self.weights.iter().sum::<f64>();
+        let magnitudes: Vec<f64> = (0..=self.config.window / 2)
+            .map(|bin| re[bin].hypot(im[bin]) * scale)
+            .collect();
+        let features: Vec<f64> = match self.config.features {
+            AudioFeatures::Spectrum { .. } => magnitudes[..self.config.feature_count()].to_vec(),
+            AudioFeatures::MelBands { .. } => self
+                .filters
+                .iter()
+                .map(|filter| filter.iter().map(|&(bin, w)| magnitudes[bin] * w).sum())
+                .collect(),
+        };
+        if self.config.log {
+            features.into_iter().map(f64::ln_1p).collect()
+        } else {
+            features
+        }
+    }
+}
+
+/// Open `source`, with the capture program to stop once done reading
+fn open(source: &AudioSource) -> Result<(Box<dyn Read>, Option<Child>), String> {
+    match source {
+        AudioSource::File { path } => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            Ok((Box::new(file), None))
+        }
+        AudioSource::Command { command, args } => {
+            let mut child = Command::new(command)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| e.to_string())?;
+            let stdout = child.stdout.take().ok_or("Capture program has no stdout")?;
+            Ok((Box::new(stdout), Some(child)))
+        }
+    }
+}
+
+/// Capture audio from the configured source on a dedicated thread until the receiver is dropped
+///
+/// A file that ends or a capture program that exits is reopened every second.
+pub fn spawn_reader(mut extractor: FeatureExtractor) -> mpsc::UnboundedReceiver<Vec<f64>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        let source = extractor.config().source.clone();
+        while !sender.is_closed() {
+            match open(&source) {
+                Ok((mut reader, mut child)) => {
+                    println!("🎤 Capturing audio from {}", source.describe());
+                    let mut buffer = [0u8; 4096];
+                    'capture: loop {
+                        match reader.read(&mut buffer) {
+                            Ok(0) => {
+                                println!("🔌 Audio from {} ended", source.describe());
+                                break;
+                            }
+                            Ok(read) => {
+                                for features in extractor.push_bytes(&buffer[..read]) {
+                                    if sender.send(features).is_err() {
+                                        break 'capture;
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                println!("❌ Audio source {}: {}", source.describe(), e);
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(child) = child.as_mut() {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                }
+                Err(e) => println!("❌ Cannot capture audio from {}: {}", source.describe(), e),
+            }
+            if !sender.is_closed() {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_features() {
+        let mut config: AudioInputConfig = serde_json::from_str(
+            r#"{
+                "source": {"type": "File", "path": "/tmp/audio.fifo"},
+                "target_id": "main",
+                "channels": 2,
+                "window": 64,
+                "hop": 32,
+                "features": {"type": "Spectrum", "bins": 16}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.sample_rate, 16000);
+        assert_eq!(config.frame_rate(), 500.0);
+
+        // A 2 kHz sine falls on bin 8; the second channel is silent
+        let sine: Vec<u8> = (0..96)
+            .flat_map(|i| {
+                let value = (2.0 * PI * 2000.0 * i as f64 / 16000.0).sin() * 0.5;
+                let sample = ((value * 2.0) * 32767.0) as i16;
+                [sample.to_le_bytes(), 0i16.to_le_bytes()].concat()
+            })
+            .collect();
+        let mut extractor = FeatureExtractor::new(config.clone()).unwrap();
+        assert!(extractor.push_bytes(&sine[..200]).is_empty());
+        let frames = extractor.push_bytes(&sine[200..]);
+        assert_eq!(frames.len(), 2);
+        let spectrum = &frames[0];
+        assert_eq!(spectrum.len(), 16);
+        assert!((spectrum[8] - 0.5).abs() < 0.01);
+        assert!(spectrum[..6]
+            .iter()
+            .chain(&spectrum[11..])
+            .all(|&m| m < 0.01));
+
+        // Four bands up to 8 kHz peak at 0.46, 1.2, 2.5 and 4.6 kHz; 2 kHz is closest to the third
+        config.features = AudioFeatures::MelBands {
+            bands: 4,
+            min_hz: 0.0,
+            max_hz: None,
+        };
+        let mut extractor = FeatureExtractor::new(config.clone()).unwrap();
+        let bands = extractor.push_bytes(&sine).remove(0);
+        assert_eq!(bands.len(), config.feature_count());
+        let loudest = (0..4).max_by(|&a, &b| bands[a].total_cmp(&bands[b]));
+        assert_eq!(loudest, Some(2));
+
+        config.window = 48;
+        assert!(config.validate().unwrap_err().contains("power of two"));
+    }
+}
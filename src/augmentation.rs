@@ -0,0 +1,174 @@
+use crate::datasets::gaussian;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// On-the-fly corruption of training samples (`[training.augmentation]`)
+///
+/// Every option defaults to off. Corruption is redrawn each epoch from
+/// `seed` and the epoch number, so runs are repeatable while the network
+/// never sees the same corrupted sample twice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AugmentationConfig {
+    /// Standard deviation of Gaussian noise added to every input
+    #[serde(default)]
+    pub gaussian_noise: f64,
+    /// Probability of replacing an input with `salt` or `pepper`
+    #[serde(default)]
+    pub salt_and_pepper: f64,
+    /// Value written by salt noise (pepper writes `pepper`)
+    #[serde(default = "default_salt")]
+    pub salt: f64,
+    #[serde(default)]
+    pub pepper: f64,
+    /// Probability of zeroing an input feature
+    #[serde(default)]
+    pub feature_dropout: f64,
+    /// Move targets this fraction of the way towards a uniform distribution
+    #[serde(default)]
+    pub label_smoothing: f64,
+    /// Seed of the corruption (0 when unset)
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_salt() -> f64 {
+    1.0
+}
+
+impl Default for AugmentationConfig {
+    fn default() -> Self {
+        Self {
+            gaussian_noise: 0.0,
+            salt_and_pepper: 0.0,
+            salt: default_salt(),
+            pepper: 0.0,
+            feature_dropout: 0.0,
+            label_smoothing: 0.0,
+            seed: 0,
+        }
+    }
+}
+
+impl AugmentationConfig {
+    /// Whether any augmentation is configured
+    pub fn is_enabled(&self) -> bool {
+        self.corrupts_inputs() || self.label_smoothing > 0.0
+    }
+
+    fn corrupts_inputs(&self) -> bool {
+        self.gaussian_noise > 0.0 || self.salt_and_pepper > 0.0 || self.feature_dropout > 0.0
+    }
+
+    /// Augmenter for one epoch
+    pub fn for_epoch(&self, epoch: usize) -> Augmenter<'_> {
+        let seed = self
+            .seed
+            .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            .wrapping_add(epoch as u64);
+        Augmenter {
+            config: self,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Applies an `AugmentationConfig` to the samples of one epoch
+pub struct Augmenter<'a> {
+    config: &'a AugmentationConfig,
+    rng: StdRng,
+}
+
+impl Augmenter<'_> {
+    /// A corrupted copy of `input`
+    pub fn input(&mut self, input: &[f64]) -> Vec<f64> {
+        let config = self.config;
+        if !config.corrupts_inputs() {
+            return input.to_vec();
+        }
+        input
+            .iter()
+            .map(|&value| {
+                if config.feature_dropout > 0.0 && self.rng.gen_bool(config.feature_dropout) {
+                    return 0.0;
+                }
+                if config.salt_and_pepper > 0.0 && self.rng.gen_bool(config.salt_and_pepper) {
+                    return if self.rng.gen() {
+                        config.salt
+                    } else {
+                        config.pepper
+                    };
+                }
+                if config.gaussian_noise > 0.0 {
+                    return value + config.gaussian_noise * gaussian(&mut self.rng);
+                }
+                value
+            })
+            .collect()
+    }
+
+    /// `target` with label smoothing applied
+    ///
+    /// A single output is treated as a binary label, so it is pulled towards 0.5.
+    pub fn target(&self, target: &[f64]) -> Vec<f64> {
+        let epsilon = self.config.label_smoothing;
+        if epsilon <= 0.0 {
+            return target.to_vec();
+        }
+        let classes = target.len().max(2) as f64;
+        target
+            .iter()
+            .map(|&t| t * (1.0 - epsilon) + epsilon / classes)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_augmentation_is_seeded_per_epoch() {
+        let off = AugmentationConfig::default();
+        assert!(!off.is_enabled());
+        assert_eq!(off.for_epoch(0).input(&[0.5, 0.25]), vec![0.5, 0.25]);
+
+        let noisy = AugmentationConfig {
+            gaussian_noise: 0.1,
+            seed: 42,
+            ..Default::default()
+        };
+        let input = vec![0.5; 16];
+        let first = noisy.for_epoch(3).input(&input);
+        assert_ne!(first, input);
+        assert_eq!(first, noisy.for_epoch(3).input(&input));
+        assert_ne!(first, noisy.for_epoch(4).input(&input));
+
+        let dropout = AugmentationConfig {
+            feature_dropout: 1.0,
+            ..Default::default()
+        };
+        assert_eq!(dropout.for_epoch(0).input(&[0.7, 0.3]), vec![0.0, 0.0]);
+
+        let masked = AugmentationConfig {
+            salt_and_pepper: 1.0,
+            pepper: -1.0,
+            ..Default::default()
+        };
+        assert!(masked
+            .for_epoch(0)
+            .input(&input)
+            .iter()
+            .all(|&v| v == 1.0 || v == -1.0));
+
+        let smoothing = AugmentationConfig {
+            label_smoothing: 0.2,
+            ..Default::default()
+        };
+        let augmenter = smoothing.for_epoch(0);
+        assert!((augmenter.target(&[1.0])[0] - 0.9).abs() < 1e-12);
+        let smoothed = augmenter.target(&[0.0, 1.0, 0.0, 0.0]);
+        assert!((smoothed[1] - 0.85).abs() < 1e-12);
+        assert!((smoothed[0] - 0.05).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,279 @@
+use crate::distributed_network::{MessageType, NetworkId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Direction of traffic relative to the local node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+impl Direction {
+    fn label(self) -> &'static str {
+        match self {
+            Direction::Sent => "sent",
+            Direction::Received => "received",
+        }
+    }
+}
+
+/// Byte and message counters for one (direction, message type) pair
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrafficCounter {
+    pub bytes: u64,
+    pub messages: u64,
+}
+
+/// Per-peer bandwidth cap enforced with a token bucket
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BandwidthLimit {
+    /// Sustained rate in bytes per second
+    pub bytes_per_second: u64,
+    /// Largest burst allowed above the sustained rate
+    pub burst_bytes: u64,
+}
+
+impl BandwidthLimit {
+    pub fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            burst_bytes: bytes_per_second,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Traffic recorded for a single peer
+#[derive(Debug, Default)]
+struct PeerTraffic {
+    counters: HashMap<(Direction, MessageType), TrafficCounter>,
+    bucket: Option<TokenBucket>,
+}
+
+/// Bandwidth usage of one peer, broken down by direction and message type
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerBandwidthStats {
+    pub peer_id: NetworkId,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub by_type: Vec<(Direction, String, TrafficCounter)>,
+}
+
+/// Thread-safe bandwidth accounting shared by all clones of a node
+#[derive(Debug, Clone, Default)]
+pub struct BandwidthTracker {
+    peers: Arc<Mutex<HashMap<NetworkId, PeerTraffic>>>,
+    default_limit: Arc<Mutex<Option<BandwidthLimit>>>,
+    peer_limits: Arc<Mutex<HashMap<NetworkId, BandwidthLimit>>>,
+}
+
+impl BandwidthTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account `bytes` of a message exchanged with `peer`
+    pub fn record(
+        &self,
+        peer: NetworkId,
+        direction: Direction,
+        msg_type: MessageType,
+        bytes: usize,
+    ) {
+        let mut peers = self.peers.lock().unwrap();
+        let counter = peers
+            .entry(peer)
+            .or_default()
+            .counters
+            .entry((direction, msg_type))
+            .or_default();
+        counter.bytes += bytes as u64;
+        counter.messages += 1;
+    }
+
+    /// Set the cap applied to peers without an explicit limit
+    pub fn set_default_limit(&self, limit: Option<BandwidthLimit>) {
+        *self.default_limit.lock().unwrap() = limit;
+    }
+
+    /// Set or clear the cap for a single peer
+    pub fn set_peer_limit(&self, peer: NetworkId, limit: Option<BandwidthLimit>) {
+        let mut limits = self.peer_limits.lock().unwrap();
+        match limit {
+            Some(limit) => limits.insert(peer, limit),
+            None => limits.remove(&peer),
+        };
+    }
+
+    fn limit_for(&self, peer: NetworkId) -> Option<BandwidthLimit> {
+        self.peer_limits
+            .lock()
+            .unwrap()
+            .get(&peer)
+            .copied()
+            .or(*self.default_limit.lock().unwrap())
+    }
+
+    /// Reserve `bytes` of send budget for `peer`, returning how long the caller must wait
+    pub fn reserve(&self, peer: NetworkId, bytes: usize) -> Duration {
+        let Some(limit) = self.limit_for(peer) else {
+            return Duration::ZERO;
+        };
+        if limit.bytes_per_second == 0 {
+            return Duration::ZERO;
+        }
+
+        let now = Instant::now();
+        let capacity = limit.burst_bytes.max(1) as f64;
+        let mut peers = self.peers.lock().unwrap();
+        let bucket = peers
+            .entry(peer)
+            .or_default()
+            .bucket
+            .get_or_insert(TokenBucket {
+                tokens: capacity,
+                last_refill: now,
+            });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * limit.bytes_per_second as f64).min(capacity);
+        bucket.last_refill = now;
+        bucket.tokens -= bytes as f64;
+
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / limit.bytes_per_second as f64)
+        }
+    }
+
+    /// Wait until `bytes` may be sent to `peer` under its bandwidth cap
+    pub async fn throttle(&self, peer: NetworkId, bytes: usize) {
+        let wait = self.reserve(peer, bytes);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Usage for every peer seen so far
+    pub fn snapshot(&self) -> Vec<PeerBandwidthStats> {
+        let peers = self.peers.lock().unwrap();
+        let mut stats: Vec<PeerBandwidthStats> = peers
+            .iter()
+            .map(|(peer_id, traffic)| {
+                let mut by_type: Vec<(Direction, String, TrafficCounter)> = traffic
+                    .counters
+                    .iter()
+                    .map(|((direction, msg_type), counter)| {
+                        (*direction, format!("{:?}", msg_type), *counter)
+                    })
+                    .collect();
+                by_type.sort_by(|a, b| (a.0.label(), &a.1).cmp(&(b.0.label(), &b.1)));
+
+                let total = |wanted: Direction| -> u64 {
+                    by_type
+                        .iter()
+                        .filter(|(direction, _, _)| *direction == wanted)
+                        .map(|(_, _, counter)| counter.bytes)
+                        .sum()
+                };
+
+                PeerBandwidthStats {
+                    peer_id: *peer_id,
+                    bytes_sent: total(Direction::Sent),
+                    bytes_received: total(Direction::Received),
+                    by_type,
+                }
+            })
+            .collect();
+        stats.sort_by_key(|s| s.peer_id);
+        stats
+    }
+
+    /// Render counters in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let stats = self.snapshot();
+        let mut out = String::new();
+
+        out.push_str("# HELP benny_peer_bytes_total Bytes exchanged with a peer by message type\n");
+        out.push_str("# TYPE benny_peer_bytes_total counter\n");
+        for peer in &stats {
+            for (direction, msg_type, counter) in &peer.by_type {
+                let _ = writeln!(
+                    out,
+                    "benny_peer_bytes_total{{peer=\"{}\",direction=\"{}\",message_type=\"{}\"}} {}",
+                    peer.peer_id,
+                    direction.label(),
+                    msg_type,
+                    counter.bytes
+                );
+            }
+        }
+
+        out.push_str(
+            "# HELP benny_peer_messages_total Messages exchanged with a peer by message type\n",
+        );
+        out.push_str("# TYPE benny_peer_messages_total counter\n");
+        for peer in &stats {
+            for (direction, msg_type, counter) in &peer.by_type {
+                let _ = writeln!(
+                    out,
+                    "benny_peer_messages_total{{peer=\"{}\",direction=\"{}\",message_type=\"{}\"}} {}",
+                    peer.peer_id,
+                    direction.label(),
+                    msg_type,
+                    counter.messages
+                );
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_accounting_by_peer_and_type() {
+        let tracker = BandwidthTracker::new();
+        let peer = Uuid::new_v4();
+        tracker.record(peer, Direction::Sent, MessageType::ForwardData, 100);
+        tracker.record(peer, Direction::Sent, MessageType::ForwardData, 50);
+        tracker.record(peer, Direction::Received, MessageType::Ack, 30);
+
+        let stats = tracker.snapshot();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].bytes_sent, 150);
+        assert_eq!(stats[0].bytes_received, 30);
+
+        let metrics = tracker.to_prometheus();
+        assert!(metrics.contains("direction=\"sent\",message_type=\"ForwardData\"} 150"));
+        assert!(metrics.contains("benny_peer_messages_total"));
+    }
+
+    #[test]
+    fn test_limit_requests_wait_when_exhausted() {
+        let tracker = BandwidthTracker::new();
+        let peer = Uuid::new_v4();
+        tracker.set_peer_limit(peer, Some(BandwidthLimit::new(1000)));
+
+        assert_eq!(tracker.reserve(peer, 1000), Duration::ZERO);
+        let wait = tracker.reserve(peer, 500);
+        assert!(wait > Duration::from_millis(400) && wait <= Duration::from_millis(500));
+
+        // Peers without a limit are never throttled
+        assert_eq!(tracker.reserve(Uuid::new_v4(), 1_000_000), Duration::ZERO);
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::Duration;
+
+/// Largest batch size ever tried
+const MAX_BATCH_SIZE: usize = 1024;
+
+/// Upper bound on batch size times parameter count, keeping batches of large networks small
+const BATCH_PARAMETER_BUDGET: usize = 1 << 24;
+
+/// Picks the training batch size with the best throughput during the first epochs
+///
+/// Every tuning epoch trains with the next candidate size, starting at one
+/// sample per core and doubling up to a limit set by the network size and the
+/// training set. Once each candidate has run an epoch, the fastest one is kept
+/// for the rest of training.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchTuner {
+    candidates: Vec<usize>,
+    samples_per_sec: Vec<f64>,
+    chosen: Option<usize>,
+}
+
+impl BatchTuner {
+    pub fn new(samples: usize, parameters: usize, cores: usize) -> Self {
+        let limit = (BATCH_PARAMETER_BUDGET / parameters.max(1))
+            .min(MAX_BATCH_SIZE)
+            .min(samples)
+            .max(1);
+        let mut candidates = Vec::new();
+        let mut size = cores.clamp(1, limit);
+        while size <= limit {
+            candidates.push(size);
+            size *= 2;
+        }
+        Self {
+            candidates,
+            samples_per_sec: Vec::new(),
+            chosen: None,
+        }
+    }
+
+    /// Sizes tried, in order
+    pub fn candidates(&self) -> &[usize] {
+        &self.candidates
+    }
+
+    /// Batch size to train the next epoch with
+    pub fn batch_size(&self) -> usize {
+        self.chosen
+            .unwrap_or_else(|| self.candidates[self.samples_per_sec.len()])
+    }
+
+    /// Whether the next epoch still measures a candidate
+    pub fn is_tuning(&self) -> bool {
+        self.chosen.is_none()
+    }
+
+    /// Record an epoch trained with `batch_size()`; returns the chosen size once tuning ends
+    pub fn record(&mut self, samples: usize, elapsed: Duration) -> Option<usize> {
+        if self.chosen.is_some() {
+            return None;
+        }
+        self.samples_per_sec
+            .push(samples as f64 / elapsed.as_secs_f64().max(f64::EPSILON));
+        if self.samples_per_sec.len() < self.candidates.len() {
+            return None;
+        }
+        self.chosen = self.best();
+        self.chosen
+    }
+
+    /// Fastest size measured so far, ties going to the smaller batch
+    pub fn best(&self) -> Option<usize> {
+        let mut best: Option<(usize, f64)> = None;
+        for (size, rate) in self.measurements() {
+            if best.is_none_or(|(_, best_rate)| rate > best_rate) {
+                best = Some((size, rate));
+            }
+        }
+        best.map(|(size, _)| size)
+    }
+
+    /// Throughput of each measured candidate as `(batch size, samples/sec)`
+    pub fn measurements(&self) -> impl Iterator<Item = (usize, f64)> + '_ {
+        self.candidates
+            .iter()
+            .copied()
+            .zip(self.samples_per_sec.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidates_follow_cores_and_network_size() {
+        assert_eq!(
+            BatchTuner::new(100, 50, 4).candidates(),
+            &[4, 8, 16, 32, 64]
+        );
+        assert_eq!(BatchTuner::new(3, 50, 8).candidates(), &[3]);
+        assert_eq!(
+            BatchTuner::new(10_000, BATCH_PARAMETER_BUDGET / 20, 4).candidates(),
+            &[4, 8, 16]
+        );
+        assert_eq!(
+            BatchTuner::new(10_000, 10, 0).candidates().last(),
+            Some(&1024)
+        );
+    }
+
+    #[test]
+    fn test_fastest_candidate_is_kept() {
+        let mut tuner = BatchTuner::new(16, 10, 4);
+        assert_eq!(tuner.candidates(), &[4, 8, 16]);
+
+        for (size, millis) in [(4, 40), (8, 20), (16, 30)] {
+            assert_eq!(tuner.batch_size(), size);
+            let chosen = tuner.record(16, Duration::from_millis(millis));
+            assert_eq!(chosen.is_some(), size == 16);
+        }
+        assert!(!tuner.is_tuning());
+        assert_eq!(tuner.batch_size(), 8);
+        assert_eq!(tuner.record(16, Duration::from_millis(1)), None);
+        assert_eq!(tuner.measurements().count(), 3);
+    }
+}
@@ -0,0 +1,146 @@
+use crate::neural_network::{HebbianLearningMode, NeuralNetwork};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::time::Instant;
+
+/// Every Hebbian learning mode, in the order comparisons are reported
+pub const LEARNING_MODES: [HebbianLearningMode; 6] = [
+    HebbianLearningMode::Classic,
+    HebbianLearningMode::Competitive,
+    HebbianLearningMode::Oja,
+    HebbianLearningMode::BCM,
+    HebbianLearningMode::AntiHebbian,
+    HebbianLearningMode::Hybrid,
+];
+
+/// Samples in the held-out set the final error is measured on
+const EVALUATION_SAMPLES: usize = 100;
+
+/// Seed for a named experiment: numbers are used as-is, other names are hashed
+///
+/// Uses FNV-1a so a name maps to the same seed on every platform and release.
+pub fn experiment_seed(name: &str) -> u64 {
+    name.parse().unwrap_or_else(|_| {
+        name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+    })
+}
+
+/// Result of training one learning mode on the toy task
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeBenchmark {
+    pub mode: HebbianLearningMode,
+    /// Training steps per second
+    pub ops_per_sec: f64,
+    /// Mean squared error on held-out samples after training
+    pub final_error: f64,
+    /// Weight L2 norm after training divided by the norm before
+    pub weight_norm_growth: f64,
+}
+
+/// Redraw the weights and biases of `network` from `rng`, with the spread of a fresh network
+pub fn seed_parameters(network: &mut NeuralNetwork, rng: &mut StdRng) {
+    for layer in 0..network.num_layers() - 1 {
+        let weights: Vec<f64> = (0..network.get_layer_weights(layer).len())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect();
+        let biases: Vec<f64> = (0..network.get_layer_biases(layer).len())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect();
+        let _ = network.set_layer_parameters(layer, &weights, &biases);
+    }
+}
+
+/// Train a copy of `network` in every learning mode on the same seeded toy task
+///
+/// The task is to reproduce a random single-layer sigmoid teacher. Initial
+/// weights, training data and evaluation data are all drawn from `seed`, so
+/// modes are compared on identical conditions and runs are repeatable.
+pub fn compare_modes(network: &NeuralNetwork, seed: u64, iterations: usize) -> Vec<ModeBenchmark> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut base = network.clone();
+    seed_parameters(&mut base, &mut rng);
+
+    let layers = base.get_layers();
+    let (inputs, outputs) = (layers[0], layers[layers.len() - 1]);
+    let teacher: Vec<Vec<f64>> = (0..outputs)
+        .map(|_| (0..=inputs).map(|_| rng.gen_range(-2.0..2.0)).collect())
+        .collect();
+    let sample = |rng: &mut StdRng| {
+        let x: Vec<f64> = (0..inputs).map(|_| rng.gen_range(0.0..1.0)).collect();
+        let y = teacher
+            .iter()
+            .map(|row| {
+                let sum = row[inputs] + row.iter().zip(&x).map(|(w, x)| w * x).sum::<f64>();
+                1.0 / (1.0 + (-sum).exp())
+            })
+            .collect::<Vec<f64>>();
+        (x, y)
+    };
+    let training: Vec<_> = (0..iterations).map(|_| sample(&mut rng)).collect();
+    let evaluation: Vec<_> = (0..EVALUATION_SAMPLES).map(|_| sample(&mut rng)).collect();
+
+    let initial_norm = base.weight_norm();
+    LEARNING_MODES
+        .iter()
+        .map(|&mode| {
+            let mut network = base.clone();
+            network.set_learning_mode(mode);
+
+            let start = Instant::now();
+            for (x, y) in &training {
+                network.train(x, y);
+            }
+            let elapsed = start.elapsed().as_secs_f64();
+
+            let final_error = evaluation
+                .iter()
+                .map(|(x, y)| {
+                    let output = network.forward_static(x).0;
+                    output
+                        .iter()
+                        .zip(y)
+                        .map(|(o, t)| (t - o).powi(2))
+                        .sum::<f64>()
+                        / outputs as f64
+                })
+                .sum::<f64>()
+                / EVALUATION_SAMPLES as f64;
+
+            ModeBenchmark {
+                mode,
+                ops_per_sec: iterations as f64 / elapsed.max(f64::EPSILON),
+                final_error,
+                weight_norm_growth: network.weight_norm() / initial_norm.max(f64::EPSILON),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_experiment_seeds() {
+        assert_eq!(experiment_seed("42"), 42);
+        assert_eq!(experiment_seed("baseline"), experiment_seed("baseline"));
+        assert_ne!(experiment_seed("baseline"), experiment_seed("baseline-2"));
+    }
+
+    #[test]
+    fn test_mode_comparison_is_repeatable() {
+        let network = NeuralNetwork::with_layers(&[3, 4, 2], 0.05);
+        let first = compare_modes(&network, 7, 50);
+        let second = compare_modes(&network, 7, 50);
+
+        assert_eq!(first.len(), LEARNING_MODES.len());
+        for (a, b) in first.iter().zip(&second) {
+            assert_eq!(a.mode, b.mode);
+            assert_eq!(a.final_error, b.final_error);
+            assert_eq!(a.weight_norm_growth, b.weight_norm_growth);
+            assert!(a.final_error.is_finite() && a.weight_norm_growth > 0.0);
+        }
+    }
+}
@@ -1,5 +1,12 @@
 use clap::{Arg, Command};
-use neural_network::{InputServer, InputServerConfig, NeuralNetworkTarget};
+use neural_network::address::format_host_port;
+use neural_network::reload;
+use neural_network::{
+    AddressPreference, BindOptions, InputAdapter, InputServer, InputServerConfig,
+    NeuralNetworkTarget, RecordingConfig, ScriptConfig,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -50,6 +57,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Number of neural network inputs")
                 .default_value("4"),
         )
+        .arg(
+            Arg::new("input-adapter")
+                .long("input-adapter")
+                .value_name("ADAPTER")
+                .help("Mapping for submissions of another size: reject, truncate, pad[:value], interpolate")
+                .default_value("reject"),
+        )
         .arg(
             Arg::new("use-tls")
                 .long("use-tls")
@@ -68,6 +82,56 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_name("PATH")
                 .help("Path to TLS private key file"),
         )
+        .arg(
+            Arg::new("address-preference")
+                .long("address-preference")
+                .value_name("PREFERENCE")
+                .help("Address family preference: system, prefer-ipv4, prefer-ipv6, ipv4-only, ipv6-only")
+                .default_value("system"),
+        )
+        .arg(
+            Arg::new("no-dual-stack")
+                .long("no-dual-stack")
+                .help("Do not accept IPv4 clients on IPv6 listeners")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("socket-mode")
+                .long("socket-mode")
+                .value_name("MODE")
+                .help("Octal permissions for a Unix socket listener (e.g. 660)"),
+        )
+        .arg(
+            Arg::new("preprocess")
+                .long("preprocess")
+                .value_name("FILE")
+                .help("Rhai script run on every submitted input sample"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .value_name("FILE")
+                .help("Record sent inputs into a training dataset (.json, or .csv with --record-outputs)"),
+        )
+        .arg(
+            Arg::new("record-outputs")
+                .long("record-outputs")
+                .value_name("URL")
+                .requires("record")
+                .help("OutputServer WebSocket (ws://HOST:PORT) whose outputs become the recorded targets"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help("TOML or JSON server configuration replacing the options above; re-read on SIGHUP"),
+        )
+        .after_help(
+            "The configuration file's input_bridge reads Linux joystick (/dev/input/jsN) and \
+             ALSA raw MIDI (/dev/snd/midiCxDy) devices and is not available on other platforms.\n\
+             Its audio_input has no capture backend of its own and reads raw PCM from a file or \
+             from the output of an installed capture program such as arecord.",
+        )
         .get_matches();
 
     let network_host = matches.get_one::<String>("network-host").unwrap().clone();
@@ -79,16 +143,37 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap()
         .parse()?;
     let input_size: usize = matches.get_one::<String>("input-size").unwrap().parse()?;
+    let input_adapter: InputAdapter = matches.get_one::<String>("input-adapter").unwrap().parse()?;
     let use_tls = matches.get_flag("use-tls");
     let cert_path = matches.get_one::<String>("cert-path").cloned();
     let key_path = matches.get_one::<String>("key-path").cloned();
+    let bind_options = BindOptions {
+        preference: matches
+            .get_one::<String>("address-preference")
+            .unwrap()
+            .parse::<AddressPreference>()?,
+        dual_stack: !matches.get_flag("no-dual-stack"),
+        socket_mode: matches
+            .get_one::<String>("socket-mode")
+            .map(|mode| u32::from_str_radix(mode, 8))
+            .transpose()?,
+    };
+    let preprocess = matches.get_one::<String>("preprocess").map(ScriptConfig::file);
+    let recording = matches.get_one::<String>("record").map(|path| RecordingConfig {
+        output_url: matches.get_one::<String>("record-outputs").cloned(),
+        ..RecordingConfig::new(path)
+    });
 
     println!("🚀 Starting InputServer");
     println!("   Neural Network: {}:{}", network_host, network_port);
-    println!("   Web Interface: http://{}:{}", web_host, web_port);
-    println!("   WebSocket: ws://{}:{}", web_host, websocket_port);
+    println!("   Web Interface: http://{}", format_host_port(&web_host, web_port));
+    println!("   WebSocket: ws://{}", format_host_port(&web_host, websocket_port));
+    println!("   Health Probes: http://{}/healthz, /readyz", format_host_port(&web_host, web_port));
     println!("   Input Size: {}", input_size);
     println!("   TLS: {}", if use_tls { "Enabled" } else { "Disabled" });
+    if let Some(recording) = &recording {
+        println!("   Recording: {}", recording.path.display());
+    }
 
     // Create neural network target configuration
     let neural_network = NeuralNetworkTarget {
@@ -98,6 +183,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         port: network_port,
         input_count: input_size,
         use_tls,
+        input_adapter,
+        preprocess,
+        display: Default::default(),
+        constraints: Vec::new(),
     };
 
     // Create InputServer configuration
@@ -108,10 +197,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         neural_networks: vec![neural_network],
         cert_path,
         key_path,
+        bind_options,
+        recording,
+        closed_loop: None,
+        input_bridge: None,
+        audio_input: None,
+        camera_input: None,
+    };
+
+    // A configuration file takes precedence and is applied again on every SIGHUP
+    let config_file = matches.get_one::<String>("config").map(PathBuf::from);
+    let config = match &config_file {
+        Some(path) => {
+            println!("   Config File: {}", path.display());
+            reload::load_config(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+        }
+        None => config,
     };
 
     // Create and start the InputServer
-    let server = InputServer::new(config);
+    let server = Arc::new(InputServer::new(config));
+    if let Some(path) = config_file {
+        let server = Arc::clone(&server);
+        reload::spawn_on_sighup(move || {
+            let server = Arc::clone(&server);
+            let path = path.clone();
+            async move {
+                match reload::load_config::<InputServerConfig>(&path) {
+                    Ok(config) => server.reload(config).await.print(),
+                    Err(e) => println!("❌ Failed to reload {}: {}", path.display(), e),
+                }
+            }
+        });
+    }
     server.start().await?;
 
     Ok(())
@@ -1,5 +1,12 @@
 use clap::{Arg, Command};
-use neural_network::{OutputServer, OutputServerConfig, NeuralNetworkSource};
+use neural_network::address::format_host_port;
+use neural_network::reload;
+use neural_network::{
+    AddressPreference, BindOptions, Decimation, HistoryConfig, NeuralNetworkSource, OutputServer,
+    OutputServerConfig, RetentionPolicy, ScriptConfig, SinkConfig, SinkTarget,
+};
+use std::path::PathBuf;
+use std::sync::Arc;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -12,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             Arg::new("listen-host")
                 .long("listen-host")
                 .value_name("HOST")
-                .help("Host address to listen for neural network connections")
+                .help("Host address (or unix:/path.sock) to listen for neural network connections")
                 .default_value("0.0.0.0"),
         )
         .arg(
@@ -57,6 +64,73 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .help("Display name for the neural network")
                 .default_value("Main Neural Network"),
         )
+        .arg(
+            Arg::new("info-endpoint")
+                .long("info-endpoint")
+                .value_name("HOST:PORT")
+                .help("NNP endpoint of the producing network, queried for the details panel"),
+        )
+        .arg(
+            Arg::new("decimation")
+                .long("decimation")
+                .value_name("MODE")
+                .help("Output decimation: off, every:N, envelope:N or smooth:ALPHA[:N]; adjustable from the UI")
+                .default_value("off"),
+        )
+        .arg(
+            Arg::new("postprocess")
+                .long("postprocess")
+                .value_name("FILE")
+                .help("Rhai script run on every received output sample"),
+        )
+        .arg(
+            Arg::new("dashboard-file")
+                .long("dashboard-file")
+                .value_name("FILE")
+                .help("JSON file to keep dashboards saved from the web interface in"),
+        )
+        .arg(
+            Arg::new("threshold-file")
+                .long("threshold-file")
+                .value_name("FILE")
+                .help("JSON file to keep alert thresholds set from the web interface in"),
+        )
+        .arg(
+            Arg::new("history-db")
+                .long("history-db")
+                .value_name("FILE")
+                .help("SQLite database to record every received output in, for browsing past windows"),
+        )
+        .arg(
+            Arg::new("history-max-age")
+                .long("history-max-age")
+                .value_name("SECONDS")
+                .help("Drop recorded outputs older than this"),
+        )
+        .arg(
+            Arg::new("history-max-samples")
+                .long("history-max-samples")
+                .value_name("COUNT")
+                .help("Keep at most this many recorded outputs per network"),
+        )
+        .arg(
+            Arg::new("line-protocol-url")
+                .long("line-protocol-url")
+                .value_name("URL")
+                .help("InfluxDB-compatible write URL to forward received outputs to as line protocol"),
+        )
+        .arg(
+            Arg::new("line-protocol-token")
+                .long("line-protocol-token")
+                .value_name("TOKEN")
+                .help("API token for --line-protocol-url"),
+        )
+        .arg(
+            Arg::new("postgres-sink")
+                .long("postgres-sink")
+                .value_name("CONNECTION")
+                .help("PostgreSQL/TimescaleDB connection string to forward received outputs to"),
+        )
         .arg(
             Arg::new("use-tls")
                 .long("use-tls")
@@ -75,6 +149,31 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_name("PATH")
                 .help("Path to TLS private key file"),
         )
+        .arg(
+            Arg::new("address-preference")
+                .long("address-preference")
+                .value_name("PREFERENCE")
+                .help("Address family preference: system, prefer-ipv4, prefer-ipv6, ipv4-only, ipv6-only")
+                .default_value("system"),
+        )
+        .arg(
+            Arg::new("no-dual-stack")
+                .long("no-dual-stack")
+                .help("Do not accept IPv4 clients on IPv6 listeners")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("socket-mode")
+                .long("socket-mode")
+                .value_name("MODE")
+                .help("Octal permissions for a Unix socket listener (e.g. 660)"),
+        )
+        .arg(
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .help("TOML or JSON server configuration replacing the options above; re-read on SIGHUP"),
+        )
         .get_matches();
 
     let listen_host = matches.get_one::<String>("listen-host").unwrap().clone();
@@ -88,16 +187,58 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let output_size: usize = matches.get_one::<String>("output-size").unwrap().parse()?;
     let network_name = matches.get_one::<String>("network-name").unwrap().clone();
     let use_tls = matches.get_flag("use-tls");
+    let info_endpoint = matches.get_one::<String>("info-endpoint").cloned();
+    let decimation: Decimation = matches.get_one::<String>("decimation").unwrap().parse()?;
+    let postprocess = matches.get_one::<String>("postprocess").map(ScriptConfig::file);
     let cert_path = matches.get_one::<String>("cert-path").cloned();
     let key_path = matches.get_one::<String>("key-path").cloned();
+    let dashboard_file = matches.get_one::<String>("dashboard-file").cloned();
+    let threshold_file = matches.get_one::<String>("threshold-file").cloned();
+    let history = match matches.get_one::<String>("history-db") {
+        Some(path) => {
+            let mut history = HistoryConfig::new(path);
+            history.retention = RetentionPolicy {
+                max_age_secs: matches.get_one::<String>("history-max-age").map(|s| s.parse()).transpose()?,
+                max_samples: matches.get_one::<String>("history-max-samples").map(|s| s.parse()).transpose()?,
+            };
+            Some(history)
+        }
+        None => None,
+    };
+    let mut sinks = Vec::new();
+    if let Some(url) = matches.get_one::<String>("line-protocol-url") {
+        sinks.push(SinkConfig::new(SinkTarget::LineProtocolHttp {
+            url: url.clone(),
+            token: matches.get_one::<String>("line-protocol-token").cloned(),
+        }));
+    }
+    if let Some(connection) = matches.get_one::<String>("postgres-sink") {
+        sinks.push(SinkConfig::new(SinkTarget::Postgres {
+            connection: connection.clone(),
+            table: "network_outputs".to_string(),
+        }));
+    }
+    let bind_options = BindOptions {
+        preference: matches
+            .get_one::<String>("address-preference")
+            .unwrap()
+            .parse::<AddressPreference>()?,
+        dual_stack: !matches.get_flag("no-dual-stack"),
+        socket_mode: matches
+            .get_one::<String>("socket-mode")
+            .map(|mode| u32::from_str_radix(mode, 8))
+            .transpose()?,
+    };
 
     println!("🚀 Starting OutputServer");
     println!("   Listening for Neural Networks: {}:{}", listen_host, listen_port);
-    println!("   Web Interface: http://{}:{}", web_host, web_port);
-    println!("   WebSocket: ws://{}:{}", web_host, websocket_port);
+    println!("   Web Interface: http://{}", format_host_port(&web_host, web_port));
+    println!("   WebSocket: ws://{}", format_host_port(&web_host, websocket_port));
+    println!("   Health Probes: http://{}/healthz, /readyz", format_host_port(&web_host, web_port));
     println!("   Expected Output Size: {}", output_size);
     println!("   Network Name: {}", network_name);
     println!("   TLS: {}", if use_tls { "Enabled" } else { "Disabled" });
+    println!("   Decimation: {:?}", decimation);
 
     // Create neural network source configuration
     let neural_network = NeuralNetworkSource {
@@ -107,6 +248,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         listen_port,
         output_count: output_size,
         use_tls,
+        info_endpoint,
+        decimation,
+        derived: Vec::new(),
+        postprocess,
+        display: Default::default(),
     };
 
     // Create OutputServer configuration
@@ -118,10 +264,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         neural_networks: vec![neural_network],
         cert_path,
         key_path,
+        bind_options,
+        dashboard_file,
+        threshold_file,
+        history,
+        sinks,
+        sessions: Default::default(),
+    };
+
+    // A configuration file takes precedence and is applied again on every SIGHUP
+    let config_file = matches.get_one::<String>("config").map(PathBuf::from);
+    let config = match &config_file {
+        Some(path) => {
+            println!("   Config File: {}", path.display());
+            reload::load_config(path).map_err(|e| format!("Failed to load {}: {}", path.display(), e))?
+        }
+        None => config,
     };
 
     // Create and start the OutputServer
-    let server = OutputServer::new(config);
+    let server = Arc::new(OutputServer::new(config));
+    if let Some(path) = config_file {
+        let server = Arc::clone(&server);
+        reload::spawn_on_sighup(move || {
+            let server = Arc::clone(&server);
+            let path = path.clone();
+            async move {
+                match reload::load_config::<OutputServerConfig>(&path) {
+                    Ok(config) => server.reload(config).await.print(),
+                    Err(e) => println!("❌ Failed to reload {}: {}", path.display(), e),
+                }
+            }
+        });
+    }
     server.start().await?;
 
     Ok(())
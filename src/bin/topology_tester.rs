@@ -1,3 +1,4 @@
+use neural_network::address::parse_host_port;
 use neural_network::{DistributedNetwork, NeuralNetwork};
 use std::time::Duration;
 use tokio::time::sleep;
@@ -89,14 +90,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Connect to entry points
     let mut connections = HashMap::new();
     for (name, addr) in &entry_points {
-        let parts: Vec<&str> = addr.split(':').collect();
-        if parts.len() != 2 {
-            eprintln!("❌ Invalid address format: {}", addr);
-            continue;
-        }
-        
-        let host = parts[0];
-        let port: u16 = parts[1].parse()?;
+        let (host, port) = match parse_host_port(addr, 0) {
+            Ok((host, port)) if port != 0 => (host, port),
+            _ => {
+                eprintln!("❌ Invalid address format: {}", addr);
+                continue;
+            }
+        };
+        let host = host.as_str();
         
         println!("🔗 Connecting to {}: {}:{}", name, host, port);
         
@@ -0,0 +1,353 @@
+use crate::address;
+use crate::distributed_network::{
+    capabilities, DistributedNetwork, MessagePayload, NetworkId, NetworkMessage,
+};
+use crate::secure_network::{SecureDistributedNetwork, SecureNetworkError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Which data messages may cross the bridge in each direction
+///
+/// Each mask is a set of `capabilities` flags; a data message crosses only if
+/// the capability it requires is in the mask. Control messages (handshakes,
+/// acks, heartbeats) are hop-local and never forwarded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BridgePolicy {
+    /// Allowed from the TLS cluster into the plaintext segment
+    pub to_plaintext: u32,
+    /// Allowed from the plaintext segment into the TLS cluster
+    pub to_secure: u32,
+}
+
+impl Default for BridgePolicy {
+    fn default() -> Self {
+        Self {
+            to_plaintext: capabilities::FORWARD_PROPAGATION,
+            to_secure: capabilities::FORWARD_PROPAGATION,
+        }
+    }
+}
+
+impl BridgePolicy {
+    /// Whether `payload` may be forwarded given the direction's capability mask
+    pub fn allows(mask: u32, payload: &MessagePayload) -> bool {
+        let required = payload.required_capability();
+        required != 0 && mask & required == required
+    }
+}
+
+/// Where the bridge listens and which plaintext peers it feeds
+#[derive(Debug, Clone)]
+pub struct BridgeConfig {
+    /// Address of the TLS listener facing the secure cluster
+    pub secure_address: String,
+    pub secure_port: u16,
+    /// Plaintext NNP endpoints (host:port) on the trusted segment
+    pub plaintext_endpoints: Vec<String>,
+    pub policy: BridgePolicy,
+}
+
+/// Messages forwarded and filtered by a bridge, per direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BridgeStats {
+    pub forwarded_to_plaintext: u64,
+    pub filtered_to_plaintext: u64,
+    pub forwarded_to_secure: u64,
+    pub filtered_to_secure: u64,
+}
+
+#[derive(Debug, Default)]
+struct BridgeCounters {
+    forwarded_to_plaintext: AtomicU64,
+    filtered_to_plaintext: AtomicU64,
+    forwarded_to_secure: AtomicU64,
+    filtered_to_secure: AtomicU64,
+}
+
+/// Relays NNP traffic between a TLS cluster and a plaintext segment
+///
+/// The secure side terminates TLS with `SecureDistributedNetwork`; the
+/// plaintext side is an ordinary `DistributedNetwork` on a trusted local
+/// segment. Messages are re-sequenced on each hop and filtered by
+/// `BridgePolicy`, which lets a cluster adopt TLS one segment at a time.
+pub struct BridgeNode {
+    secure: Arc<SecureDistributedNetwork>,
+    secure_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+    plaintext: DistributedNetwork,
+    plaintext_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+    config: BridgeConfig,
+    counters: Arc<BridgeCounters>,
+}
+
+impl BridgeNode {
+    pub fn new(
+        (secure, secure_receiver): (
+            SecureDistributedNetwork,
+            mpsc::UnboundedReceiver<NetworkMessage>,
+        ),
+        (plaintext, plaintext_receiver): (
+            DistributedNetwork,
+            mpsc::UnboundedReceiver<NetworkMessage>,
+        ),
+        config: BridgeConfig,
+    ) -> Self {
+        Self {
+            secure: Arc::new(secure),
+            secure_receiver,
+            plaintext,
+            plaintext_receiver,
+            config,
+            counters: Arc::new(BridgeCounters::default()),
+        }
+    }
+
+    /// Forwarding counters since the bridge was created
+    pub fn stats(&self) -> BridgeStats {
+        BridgeStats {
+            forwarded_to_plaintext: self.counters.forwarded_to_plaintext.load(Ordering::Relaxed),
+            filtered_to_plaintext: self.counters.filtered_to_plaintext.load(Ordering::Relaxed),
+            forwarded_to_secure: self.counters.forwarded_to_secure.load(Ordering::Relaxed),
+            filtered_to_secure: self.counters.filtered_to_secure.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Start both listeners, connect the plaintext endpoints and relay until a side shuts down
+    pub async fn run(mut self) -> Result<(), SecureNetworkError> {
+        self.plaintext.start_server().await?;
+
+        let secure = self.secure.clone();
+        let (secure_address, secure_port) =
+            (self.config.secure_address.clone(), self.config.secure_port);
+        tokio::spawn(async move {
+            if let Err(e) = secure
+                .start_secure_server(&secure_address, secure_port)
+                .await
+            {
+                println!("❌ Bridge TLS listener failed: {}", e);
+            }
+        });
+
+        let mut plaintext_peers = Vec::new();
+        for endpoint in &self.config.plaintext_endpoints {
+            let (host, port) = address::parse_host_port(endpoint, 8080)
+                .map_err(|e| SecureNetworkError::TlsError(format!("{}: {}", endpoint, e)))?;
+            match self.plaintext.connect_to(&host, port).await {
+                Ok(peer_id) => plaintext_peers.push(peer_id),
+                Err(e) => println!("⚠️ Bridge could not reach {}: {:?}", endpoint, e),
+            }
+        }
+
+        println!(
+            "🌉 Bridge relaying TLS {} <-> plaintext {:?}",
+            address::format_host_port(&self.config.secure_address, self.config.secure_port),
+            self.config.plaintext_endpoints
+        );
+
+        loop {
+            tokio::select! {
+                message = self.secure_receiver.recv() => {
+                    let Some(message) = message else { break };
+                    self.forward_to_plaintext(&plaintext_peers, message).await;
+                }
+                message = self.plaintext_receiver.recv() => {
+                    let Some(message) = message else { break };
+                    self.forward_to_secure(message).await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn forward_to_plaintext(&self, peers: &[NetworkId], message: NetworkMessage) {
+        if !BridgePolicy::allows(self.config.policy.to_plaintext, &message.payload) {
+            self.counters
+                .filtered_to_plaintext
+                .fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        for &peer_id in peers {
+            if let Err(e) = self
+                .plaintext
+                .send_payload(peer_id, message.payload.clone())
+                .await
+            {
+                println!(
+                    "⚠️ Bridge failed to forward to plaintext peer {}: {:?}",
+                    peer_id, e
+                );
+            }
+        }
+        self.counters
+            .forwarded_to_plaintext
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    async fn forward_to_secure(&self, message: NetworkMessage) {
+        if !BridgePolicy::allows(self.config.policy.to_secure, &message.payload) {
+            self.counters
+                .filtered_to_secure
+                .fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        let peers: Vec<NetworkId> = self
+            .secure
+            .connections
+            .lock()
+            .unwrap()
+            .keys()
+            .copied()
+            .collect();
+        for peer_id in peers {
+            if let Err(e) = self
+                .secure
+                .send_secure_payload(peer_id, message.payload.clone())
+                .await
+            {
+                println!(
+                    "⚠️ Bridge failed to forward to secure peer {}: {}",
+                    peer_id, e
+                );
+            }
+        }
+        self.counters
+            .forwarded_to_secure
+            .fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_network::NeuralNetwork;
+    use crate::secure_network::{NetworkCertificate, TlsConfig};
+    use std::path::Path;
+    use std::time::Duration;
+
+    fn free_port() -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    fn plaintext_node(
+        name: &str,
+        port: u16,
+    ) -> (DistributedNetwork, mpsc::UnboundedReceiver<NetworkMessage>) {
+        DistributedNetwork::new(
+            name.to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[1, 1], 0.0),
+        )
+    }
+
+    /// A TLS node with one of the test certificates, trusting the test CA
+    fn secure_node(
+        name: &str,
+    ) -> (SecureDistributedNetwork, mpsc::UnboundedReceiver<NetworkMessage>) {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/tls");
+        let cert = fixtures.join(format!("{}.pem", name));
+        let key = fixtures.join(format!("{}-key.pem", name));
+        let tls = TlsConfig::from_files(&cert, &key, Some(&fixtures.join("ca.pem"))).unwrap();
+        let certificate = NetworkCertificate::from_pem(&std::fs::read(&cert).unwrap()).unwrap();
+        SecureDistributedNetwork::new(
+            name.to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[1, 1], 0.0),
+            certificate,
+            tls,
+        )
+    }
+
+    fn forward(value: f32) -> MessagePayload {
+        MessagePayload::ForwardData {
+            layer_id: 0,
+            data: vec![value],
+            timestamp: None,
+            channel: None,
+        }
+    }
+
+    /// Wait for the next forward data message, skipping control messages
+    async fn next_forward(inbox: &mut mpsc::UnboundedReceiver<NetworkMessage>) -> Vec<f32> {
+        loop {
+            let message = tokio::time::timeout(Duration::from_secs(5), inbox.recv())
+                .await
+                .expect("timed out waiting for forward data")
+                .unwrap();
+            if let MessagePayload::ForwardData { data, .. } = message.payload {
+                return data;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_relays_between_tls_and_plaintext() {
+        let (sink_port, bridge_port, secure_port) = (free_port(), free_port(), free_port());
+        let (sink, mut sink_inbox) = plaintext_node("sink", sink_port);
+        sink.start_server().await.unwrap();
+
+        let bridge = BridgeNode::new(
+            secure_node("node-a"),
+            plaintext_node("bridge", bridge_port),
+            BridgeConfig {
+                secure_address: "127.0.0.1".to_string(),
+                secure_port,
+                plaintext_endpoints: vec![format!("127.0.0.1:{}", sink_port)],
+                policy: BridgePolicy::default(),
+            },
+        );
+        tokio::spawn(bridge.run());
+
+        // TLS cluster -> plaintext segment
+        let (client, mut client_inbox) = secure_node("node-b");
+        let mut bridge_id = None;
+        for _ in 0..50 {
+            match client.connect_to_secure("127.0.0.1", secure_port).await {
+                Ok(peer_id) => {
+                    bridge_id = Some(peer_id);
+                    break;
+                }
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        }
+        let bridge_id = bridge_id.expect("bridge TLS listener never came up");
+        client.send_secure_payload(bridge_id, forward(0.25)).await.unwrap();
+        assert_eq!(next_forward(&mut sink_inbox).await, vec![0.25]);
+
+        // Plaintext segment -> TLS cluster
+        let (source, _) = plaintext_node("source", 0);
+        let peer_id = source.connect_to("127.0.0.1", bridge_port).await.unwrap();
+        source.send_payload(peer_id, forward(0.75)).await.unwrap();
+        assert_eq!(next_forward(&mut client_inbox).await, vec![0.75]);
+    }
+
+    #[test]
+    fn test_policy_filters_by_capability() {
+        let forward = MessagePayload::ForwardData {
+            layer_id: 0,
+            data: vec![1.0],
+            timestamp: None,
+            channel: None,
+        };
+        let hebbian = MessagePayload::HebbianData {
+            layer_id: 0,
+            correlations: vec![0.5],
+            learning_rate: 0.1,
+            timestamp: None,
+        };
+        let heartbeat = MessagePayload::Heartbeat { timestamp: 1 };
+
+        let mask = capabilities::FORWARD_PROPAGATION;
+        assert!(BridgePolicy::allows(mask, &forward));
+        assert!(!BridgePolicy::allows(mask, &hebbian));
+        assert!(!BridgePolicy::allows(mask, &heartbeat));
+
+        let mask = capabilities::FORWARD_PROPAGATION | capabilities::HEBBIAN_LEARNING;
+        assert!(BridgePolicy::allows(mask, &hebbian));
+    }
+}
@@ -0,0 +1,336 @@
+use futures_util::{Sink, SinkExt};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+/// A message serialized once and shared by every client it is sent to
+///
+/// Every frame has a JSON form; data messages may carry a binary form too,
+/// which is written instead to clients that negotiated binary frames.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    text: Arc<str>,
+    binary: Option<Arc<[u8]>>,
+}
+
+impl Frame {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn binary(&self) -> Option<&[u8]> {
+        self.binary.as_deref()
+    }
+
+    fn message(&self, binary: bool) -> Message {
+        match &self.binary {
+            Some(bytes) if binary => Message::Binary(bytes.to_vec()),
+            _ => Message::Text(self.text.to_string()),
+        }
+    }
+}
+
+/// Frames queued for one client, in order
+pub struct ClientFrames {
+    rx: mpsc::UnboundedReceiver<Arc<[Frame]>>,
+    binary: Arc<AtomicBool>,
+}
+
+impl ClientFrames {
+    pub async fn recv(&mut self) -> Option<Arc<[Frame]>> {
+        self.rx.recv().await
+    }
+
+    /// Whether binary forms are written to this client
+    pub fn is_binary(&self) -> bool {
+        self.binary.load(Ordering::Relaxed)
+    }
+}
+
+/// Most frames the fan-out task gathers into one batch
+const MAX_BATCH: usize = 64;
+
+/// Fan-out of WebSocket messages to every connected dashboard client
+///
+/// `broadcast` serializes a message once and queues the shared frame. A
+/// dedicated fan-out task (see `spawn_fan_out`) hands everything queued since
+/// its last round to each client as one batch, and each client's writer (see
+/// `write_frames`) sends a batch with a single flush. Serialization cost no
+/// longer grows with the number of clients, and slow clients never hold up
+/// the connection that produced the message.
+#[derive(Clone)]
+pub struct Broadcaster {
+    clients: Arc<Mutex<Vec<WebSocketClient>>>,
+    queue: mpsc::UnboundedSender<Frame>,
+    pending: Arc<Mutex<Option<mpsc::UnboundedReceiver<Frame>>>>,
+    serialized: Arc<AtomicU64>,
+    delivered: Arc<AtomicU64>,
+}
+
+/// Sending half of one client's frame queue
+#[derive(Clone)]
+pub struct WebSocketClient {
+    tx: mpsc::UnboundedSender<Arc<[Frame]>>,
+    binary: Arc<AtomicBool>,
+}
+
+impl WebSocketClient {
+    /// Serialize `message` for this client alone; `false` once it disconnected
+    pub fn send<T: Serialize>(&self, message: &T) -> bool {
+        match serde_json::to_string(message) {
+            Ok(json) => {
+                let frame = Frame {
+                    text: json.into(),
+                    binary: None,
+                };
+                self.tx.send(Arc::new([frame])).is_ok()
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Choose between binary and JSON data frames; control messages stay JSON
+    pub fn set_binary(&self, binary: bool) {
+        self.binary.store(binary, Ordering::Relaxed);
+    }
+
+    pub fn is_binary(&self) -> bool {
+        self.binary.load(Ordering::Relaxed)
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+impl Default for Broadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Broadcaster {
+    pub fn new() -> Self {
+        let (queue, pending) = mpsc::unbounded_channel();
+        Self {
+            clients: Arc::new(Mutex::new(Vec::new())),
+            queue,
+            pending: Arc::new(Mutex::new(Some(pending))),
+            serialized: Arc::new(AtomicU64::new(0)),
+            delivered: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Start the fan-out task; later calls do nothing
+    ///
+    /// Frames broadcast before the task starts wait in its queue.
+    pub fn spawn_fan_out(&self) {
+        let Some(mut pending) = self.pending.lock().unwrap().take() else {
+            return;
+        };
+        let clients = Arc::clone(&self.clients);
+        let delivered = Arc::clone(&self.delivered);
+        tokio::spawn(async move {
+            while let Some(frame) = pending.recv().await {
+                let mut batch = vec![frame];
+                while batch.len() < MAX_BATCH {
+                    match pending.try_recv() {
+                        Ok(frame) => batch.push(frame),
+                        Err(_) => break,
+                    }
+                }
+                let batch: Arc<[Frame]> = batch.into();
+                let mut clients = clients.lock().unwrap();
+                clients.retain(|client| !client.is_closed());
+                delivered.fetch_add((batch.len() * clients.len()) as u64, Ordering::Relaxed);
+                for client in clients.iter() {
+                    let _ = client.tx.send(Arc::clone(&batch));
+                }
+            }
+        });
+    }
+
+    /// Register a new client, returning its handle and the frames to write to it
+    pub fn connect(&self) -> (WebSocketClient, ClientFrames) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let binary = Arc::new(AtomicBool::new(false));
+        let client = WebSocketClient {
+            tx,
+            binary: Arc::clone(&binary),
+        };
+        self.clients.lock().unwrap().push(client.clone());
+        (client, ClientFrames { rx, binary })
+    }
+
+    /// Forget clients whose connection has closed
+    pub fn remove_closed(&self) {
+        self.clients
+            .lock()
+            .unwrap()
+            .retain(|client| !client.is_closed());
+    }
+
+    /// Queue `message` for every connected client, serializing it once
+    pub fn broadcast<T: Serialize>(&self, message: &T) -> Result<(), serde_json::Error> {
+        self.queue(message, None)
+    }
+
+    /// Like `broadcast`, adding the binary form built by `encode` when any client negotiated it
+    pub fn broadcast_binary<T: Serialize>(
+        &self,
+        message: &T,
+        encode: impl FnOnce() -> Vec<u8>,
+    ) -> Result<(), serde_json::Error> {
+        let binary = self.binary_client_count() > 0;
+        self.queue(message, binary.then(|| encode().into()))
+    }
+
+    fn queue<T: Serialize>(
+        &self,
+        message: &T,
+        binary: Option<Arc<[u8]>>,
+    ) -> Result<(), serde_json::Error> {
+        let frame = Frame {
+            text: serde_json::to_string(message)?.into(),
+            binary,
+        };
+        self.serialized.fetch_add(1, Ordering::Relaxed);
+        // The receiver lives as long as the broadcaster, so this cannot fail
+        let _ = self.queue.send(frame);
+        Ok(())
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Connected clients that negotiated binary frames
+    pub fn binary_client_count(&self) -> usize {
+        self.clients
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|client| client.is_binary() && !client.is_closed())
+            .count()
+    }
+
+    /// Messages serialized by `broadcast`
+    pub fn serialized_count(&self) -> u64 {
+        self.serialized.load(Ordering::Relaxed)
+    }
+
+    /// Frames handed to clients by the fan-out task, counting each client separately
+    pub fn delivered_count(&self) -> u64 {
+        self.delivered.load(Ordering::Relaxed)
+    }
+}
+
+/// Write a client's frames to `sink` until either side goes away
+///
+/// Batches that are already waiting are fed together and flushed once.
+pub async fn write_frames<S>(sink: &mut S, frames: &mut ClientFrames)
+where
+    S: Sink<Message> + Unpin,
+{
+    while let Some(batch) = frames.rx.recv().await {
+        let mut batches = vec![batch];
+        while let Ok(batch) = frames.rx.try_recv() {
+            batches.push(batch);
+        }
+        let binary = frames.is_binary();
+        for frame in batches.iter().flat_map(|batch| batch.iter()) {
+            if sink.feed(frame.message(binary)).await.is_err() {
+                return;
+            }
+        }
+        if sink.flush().await.is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test]
+    async fn test_frames_are_serialized_once() {
+        let broadcaster = Broadcaster::new();
+        let mut written = broadcaster.connect().1;
+        let mut others: Vec<ClientFrames> = (0..2).map(|_| broadcaster.connect().1).collect();
+        let (closed, closed_frames) = broadcaster.connect();
+        drop(closed_frames);
+        assert!(closed.is_closed());
+        broadcaster.remove_closed();
+        assert_eq!(broadcaster.client_count(), 3);
+
+        for value in 0..5 {
+            broadcaster
+                .broadcast(&serde_json::json!({ "value": value }))
+                .unwrap();
+        }
+        broadcaster.spawn_fan_out();
+        broadcaster.spawn_fan_out();
+
+        let (mut sink, stream) = futures::channel::mpsc::unbounded::<Message>();
+        tokio::spawn(async move { write_frames(&mut sink, &mut written).await });
+        let texts: Vec<String> = stream
+            .take(5)
+            .map(|message| message.into_text().unwrap())
+            .collect()
+            .await;
+        assert_eq!(texts[0], r#"{"value":0}"#);
+        assert_eq!(texts[4], r#"{"value":4}"#);
+        assert_eq!(broadcaster.serialized_count(), 5);
+        assert_eq!(broadcaster.delivered_count(), 15);
+
+        // Every client shares the same frames
+        let first = others[0].recv().await.unwrap();
+        let second = others[1].recv().await.unwrap();
+        assert_eq!(first.len(), 5);
+        assert!(Arc::ptr_eq(&first[4].text, &second[4].text));
+
+        let (client, mut frames) = broadcaster.connect();
+        assert!(client.send(&"hello"));
+        assert_eq!(frames.recv().await.unwrap()[0].text(), r#""hello""#);
+    }
+
+    #[tokio::test]
+    async fn test_binary_frames_are_negotiated_per_client() {
+        let broadcaster = Broadcaster::new();
+        broadcaster.spawn_fan_out();
+        let (_, mut json_frames) = broadcaster.connect();
+        let (binary, mut binary_frames) = broadcaster.connect();
+
+        // Nobody asked for binary frames, so none is encoded
+        broadcaster
+            .broadcast_binary(&"first", || unreachable!())
+            .unwrap();
+        binary.set_binary(true);
+        assert_eq!(broadcaster.binary_client_count(), 1);
+        broadcaster
+            .broadcast_binary(&"second", || vec![1, 2, 3])
+            .unwrap();
+
+        let mut messages = Vec::new();
+        for frames in [&mut json_frames, &mut binary_frames] {
+            let mut received = Vec::new();
+            while received.len() < 2 {
+                received.extend(frames.recv().await.unwrap().iter().cloned());
+            }
+            messages.extend(received.iter().map(|frame| frame.message(frames.is_binary())));
+        }
+        assert_eq!(
+            messages,
+            vec![
+                Message::Text(r#""first""#.to_string()),
+                Message::Text(r#""second""#.to_string()),
+                Message::Text(r#""first""#.to_string()),
+                Message::Binary(vec![1, 2, 3]),
+            ]
+        );
+    }
+}
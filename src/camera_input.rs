@@ -0,0 +1,331 @@
+use image::GrayImage;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Where camera frames come from, as a stream of concatenated JPEG images
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum FrameSource {
+    /// MJPEG over HTTP, as served by IP cameras and `mjpg-streamer`
+    Mjpeg { url: String },
+    /// Standard output of a capture program, e.g.
+    /// `ffmpeg -loglevel error -f v4l2 -i /dev/video0 -f mjpeg -` for a webcam
+    Command {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+    /// A file or FIFO of JPEG frames
+    File { path: PathBuf },
+}
+
+impl FrameSource {
+    fn describe(&self) -> String {
+        match self {
+            FrameSource::Mjpeg { url } => url.clone(),
+            FrameSource::Command { command, .. } => command.clone(),
+            FrameSource::File { path } => path.display().to_string(),
+        }
+    }
+}
+
+/// Part of every frame that is kept, as fractions of its width and height
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RegionOfInterest {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Default for RegionOfInterest {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1.0,
+            height: 1.0,
+        }
+    }
+}
+
+/// Camera frames turned into input vectors of an InputServer target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CameraInputConfig {
+    pub source: FrameSource,
+    /// InputServer target the intensities are sent to
+    pub target_id: String,
+    /// Columns of the grid the region is averaged down to
+    pub width: usize,
+    /// Rows of the grid; inputs are `width * height` intensities in `[0, 1]`, row by row
+    pub height: usize,
+    /// Frames sent per second, the newest one captured each time
+    #[serde(default = "default_fps")]
+    pub fps: f64,
+    #[serde(default)]
+    pub roi: RegionOfInterest,
+    /// Send `1 - intensity`, so dark shapes on a light background become the active inputs
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn default_fps() -> f64 {
+    10.0
+}
+
+impl CameraInputConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.width == 0 || self.height == 0 {
+            return Err(format!(
+                "Camera grid {}x{} needs at least one cell",
+                self.width, self.height
+            ));
+        }
+        if !(self.fps > 0.0 && self.fps.is_finite()) {
+            return Err(format!("Camera rate {} fps must be positive", self.fps));
+        }
+        let roi = self.roi;
+        let inside = |start: f64, size: f64| start >= 0.0 && size > 0.0 && start + size <= 1.0;
+        if !(inside(roi.x, roi.width) && inside(roi.y, roi.height)) {
+            return Err(format!(
+                "Region of interest {}x{} at ({}, {}) must lie within the frame",
+                roi.width, roi.height, roi.x, roi.y
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn input_count(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Time between two frames
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.fps)
+    }
+
+    /// Crop a grayscale frame to the region of interest and average it into the grid
+    pub fn sample(&self, frame: &GrayImage) -> Vec<f64> {
+        let (frame_width, frame_height) = (frame.width() as f64, frame.height() as f64);
+        let left = (self.roi.x * frame_width) as u32;
+        let top = (self.roi.y * frame_height) as u32;
+        let region_width = ((self.roi.width * frame_width) as u32).max(1);
+        let region_height = ((self.roi.height * frame_height) as u32).max(1);
+        // Cell `i` of `cells` across `length` pixels, at least one pixel wide
+        let span = |i: usize, cells: usize, start: u32, length: u32| {
+            let from = start + (i as u64 * length as u64 / cells as u64) as u32;
+            let to = start + ((i + 1) as u64 * length as u64 / cells as u64) as u32;
+            (from, to.max(from + 1))
+        };
+        let mut grid = Vec::with_capacity(self.input_count());
+        for row in 0..self.height {
+            let (y0, y1) = span(row, self.height, top, region_height);
+            for column in 0..self.width {
+                let (x0, x1) = span(column, self.width, left, region_width);
+                let mut sum = 0u64;
+                let mut count = 0u64;
+                for y in y0..y1.min(frame.height()) {
+                    for x in x0..x1.min(frame.width()) {
+                        sum += frame.get_pixel(x, y).0[0] as u64;
+                        count += 1;
+                    }
+                }
+                let intensity = sum as f64 / count.max(1) as f64 / 255.0;
+                grid.push(if self.invert {
+                    1.0 - intensity
+                } else {
+                    intensity
+                });
+            }
+        }
+        grid
+    }
+
+    /// Decode a JPEG or PNG frame and sample it
+    pub fn sample_encoded(&self, bytes: &[u8]) -> Result<Vec<f64>, String> {
+        let frame = image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+        Ok(self.sample(&frame.to_luma8()))
+    }
+}
+
+/// Cuts a byte stream into the JPEG images it carries, skipping anything in between
+///
+/// Works on multipart MJPEG bodies as well as plain concatenated JPEGs, so
+/// the part headers never need to be parsed.
+#[derive(Debug, Clone, Default)]
+pub struct JpegSplitter {
+    buffer: Vec<u8>,
+}
+
+impl JpegSplitter {
+    /// Feed bytes, returning every image they complete
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<Vec<u8>> {
+        self.buffer.extend_from_slice(bytes);
+        let mut images = Vec::new();
+        loop {
+            let Some(start) = find(&self.buffer, &[0xff, 0xd8]) else {
+                // Keep a trailing 0xff that may begin the next start marker
+                let keep = usize::from(self.buffer.last() == Some(&0xff));
+                self.buffer.drain(..self.buffer.len() - keep);
+                break;
+            };
+            let Some(end) = find(&self.buffer[start + 2..], &[0xff, 0xd9]) else {
+                self.buffer.drain(..start);
+                break;
+            };
+            let end = start + 2 + end + 2;
+            images.push(self.buffer[start..end].to_vec());
+            self.buffer.drain(..end);
+        }
+        images
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Open `source`, with the capture program to stop once done reading
+fn open(source: &FrameSource) -> Result<(Box<dyn Read>, Option<Child>), String> {
+    match source {
+        FrameSource::Mjpeg { url } => {
+            let response = ureq::get(url).call().map_err(|e| e.to_string())?;
+            Ok((Box::new(response.into_reader()), None))
+        }
+        FrameSource::Command { command, args } => {
+            let mut child = Command::new(command)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .spawn()
+                .map_err(|e| e.to_string())?;
+            let stdout = child.stdout.take().ok_or("Capture program has no stdout")?;
+            Ok((Box::new(stdout), Some(child)))
+        }
+        FrameSource::File { path } => {
+            let file = File::open(path).map_err(|e| e.to_string())?;
+            Ok((Box::new(file), None))
+        }
+    }
+}
+
+/// Capture frames on a dedicated thread, keeping the sampled grid of the newest one
+///
+/// The receiver holds `None` until the first frame is decoded. A stream that
+/// ends or fails is reopened every second; frames that fail to decode are
+/// skipped.
+pub fn spawn_reader(config: CameraInputConfig) -> watch::Receiver<Option<Vec<f64>>> {
+    let (sender, receiver) = watch::channel(None);
+    std::thread::spawn(move || {
+        let source = config.source.describe();
+        while !sender.is_closed() {
+            match open(&config.source) {
+                Ok((mut reader, mut child)) => {
+                    println!("📷 Capturing frames from {}", source);
+                    let mut splitter = JpegSplitter::default();
+                    let mut buffer = vec![0u8; 64 * 1024];
+                    'capture: loop {
+                        match reader.read(&mut buffer) {
+                            Ok(0) => {
+                                println!("🔌 Frames from {} ended", source);
+                                break;
+                            }
+                            Ok(read) => {
+                                // Only the newest complete frame matters
+                                if let Some(frame) = splitter.push(&buffer[..read]).pop() {
+                                    match config.sample_encoded(&frame) {
+                                        Ok(grid) => {
+                                            if sender.send(Some(grid)).is_err() {
+                                                break 'capture;
+                                            }
+                                        }
+                                        Err(e) => {
+                                            println!("❌ Undecodable frame from {}: {}", source, e)
+                                        }
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                println!("❌ Camera source {}: {}", source, e);
+                                break;
+                            }
+                        }
+                    }
+                    if let Some(child) = child.as_mut() {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                }
+                Err(e) => println!("❌ Cannot capture frames from {}: {}", source, e),
+            }
+            if !sender.is_closed() {
+                std::thread::sleep(Duration::from_secs(1));
+            }
+        }
+    });
+    receiver
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{codecs::jpeg::JpegEncoder, Luma};
+
+    #[test]
+    fn test_camera_frames() {
+        let config: CameraInputConfig = serde_json::from_str(
+            r#"{
+                "source": {"type": "Mjpeg", "url": "http://camera.local/stream"},
+                "target_id": "main",
+                "width": 2,
+                "height": 2,
+                "roi": {"x": 0.5, "y": 0.0, "width": 0.5, "height": 1.0}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.fps, 10.0);
+        assert_eq!(config.input_count(), 4);
+
+        // Left half black; the right half, the region of interest, white above and grey below
+        let frame = GrayImage::from_fn(8, 4, |x, y| match (x < 4, y < 2) {
+            (true, _) => Luma([0]),
+            (false, true) => Luma([255]),
+            (false, false) => Luma([51]),
+        });
+        assert_eq!(config.sample(&frame), vec![1.0, 1.0, 0.2, 0.2]);
+
+        // Two JPEGs inside a multipart body, split across reads
+        let mut jpeg = Vec::new();
+        JpegEncoder::new_with_quality(&mut jpeg, 100)
+            .encode_image(&frame)
+            .unwrap();
+        let part = |jpeg: &[u8]| {
+            [
+                b"--frame\r\nContent-Type: image/jpeg\r\n\r\n".as_slice(),
+                jpeg,
+                b"\r\n",
+            ]
+            .concat()
+        };
+        let body = [part(&jpeg), part(&jpeg)].concat();
+        let mut splitter = JpegSplitter::default();
+        let mut images = splitter.push(&body[..jpeg.len()]);
+        images.extend(splitter.push(&body[jpeg.len()..]));
+        assert_eq!(images, vec![jpeg.clone(), jpeg.clone()]);
+        let grid = config.sample_encoded(&images[1]).unwrap();
+        assert!(
+            grid[..2].iter().all(|v| *v > 0.95) && grid[2..].iter().all(|v| (v - 0.2).abs() < 0.05)
+        );
+
+        let mut outside = config.clone();
+        outside.roi.x = 0.6;
+        assert!(outside.validate().unwrap_err().contains("within the frame"));
+    }
+}
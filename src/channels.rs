@@ -0,0 +1,108 @@
+use crate::distributed_network::NetworkId;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Most channels one node can name; the table size and tags are single bytes on the wire
+pub const MAX_CHANNELS: usize = u8::MAX as usize;
+
+/// Named forward data streams multiplexed over one connection
+///
+/// A node announces its channel names in every handshake, and tagged
+/// `ForwardData` messages then carry only the index into that table. The
+/// receiver keeps the last table each peer announced to turn tags back into
+/// names.
+#[derive(Debug, Clone, Default)]
+pub struct ChannelTable {
+    local: Arc<Mutex<Vec<String>>>,
+    peers: Arc<Mutex<HashMap<NetworkId, Vec<String>>>>,
+    diverted: Arc<Mutex<HashSet<String>>>,
+}
+
+impl ChannelTable {
+    /// Tag for the local channel `name`, adding it to the table if it is new
+    pub fn register(&self, name: &str) -> Result<u8, String> {
+        let mut local = self.local.lock().unwrap();
+        if let Some(index) = local.iter().position(|existing| existing == name) {
+            return Ok(index as u8);
+        }
+        if name.len() > u8::MAX as usize {
+            return Err(format!("Channel name '{}' is longer than 255 bytes", name));
+        }
+        if local.len() == MAX_CHANNELS {
+            return Err(format!(
+                "Cannot register '{}': all {} channels in use",
+                name, MAX_CHANNELS
+            ));
+        }
+        local.push(name.to_string());
+        Ok((local.len() - 1) as u8)
+    }
+
+    /// Channel names this node announces, in tag order
+    pub fn local(&self) -> Vec<String> {
+        self.local.lock().unwrap().clone()
+    }
+
+    /// Remember the table `peer` announced in its handshake
+    pub fn set_peer(&self, peer: NetworkId, names: Vec<String>) {
+        let mut peers = self.peers.lock().unwrap();
+        if names.is_empty() {
+            peers.remove(&peer);
+        } else {
+            peers.insert(peer, names);
+        }
+    }
+
+    /// Name of the channel `peer` tagged with `tag`, if it announced one
+    pub fn resolve(&self, peer: NetworkId, tag: u8) -> Option<String> {
+        self.peers
+            .lock()
+            .unwrap()
+            .get(&peer)?
+            .get(tag as usize)
+            .cloned()
+    }
+
+    pub fn forget_peer(&self, peer: NetworkId) {
+        self.peers.lock().unwrap().remove(&peer);
+    }
+
+    /// Deliver data received on `name` to subscribers only, keeping it out of the inbox
+    ///
+    /// For side streams such as ground-truth labels, which a node's message
+    /// loop would otherwise take for activations to process.
+    pub fn divert(&self, name: &str) {
+        self.diverted.lock().unwrap().insert(name.to_string());
+    }
+
+    pub fn is_diverted(&self, name: &str) -> bool {
+        self.diverted.lock().unwrap().contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_register_and_resolve() {
+        let table = ChannelTable::default();
+        assert_eq!(table.register("imu"), Ok(0));
+        assert_eq!(table.register("lidar"), Ok(1));
+        assert_eq!(table.register("imu"), Ok(0));
+        assert!(table.register(&"x".repeat(300)).is_err());
+
+        let peer = Uuid::new_v4();
+        assert_eq!(table.resolve(peer, 0), None);
+        table.set_peer(peer, table.local());
+        assert_eq!(table.resolve(peer, 1), Some("lidar".to_string()));
+        assert_eq!(table.resolve(peer, 2), None);
+        table.forget_peer(peer);
+        assert_eq!(table.resolve(peer, 1), None);
+
+        table.divert("labels");
+        assert!(table.is_diverted("labels"));
+        assert!(!table.is_diverted("imu"));
+    }
+}
@@ -0,0 +1,252 @@
+use crate::atomic_file;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BASE_FILE: &str = "base.bin";
+
+/// Parameters that changed since the previous checkpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightDelta {
+    /// Parameter count of the network the delta applies to
+    pub parameters: usize,
+    /// (parameter index, new value)
+    pub changes: Vec<(u32, f64)>,
+}
+
+/// Directory of incremental checkpoints: a base snapshot plus a chain of weight deltas
+///
+/// Each `save` writes only the parameters that moved by more than `tolerance`
+/// since the last checkpoint (`delta-000001.bin`, ...). When a delta would not
+/// be smaller than a full snapshot, or the architecture changed, a new base
+/// is written instead. Deltas carry learned values only; settings and
+/// activation history come from the base snapshot.
+pub struct CheckpointStore {
+    dir: PathBuf,
+    tolerance: f64,
+    state: Option<NeuralNetwork>, // base with every delta applied
+    deltas: usize,
+}
+
+impl CheckpointStore {
+    /// Open (or create) a checkpoint directory, replaying any existing chain
+    pub fn open<P: AsRef<Path>>(
+        dir: P,
+        tolerance: f64,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut store = Self {
+            dir,
+            tolerance,
+            state: None,
+            deltas: 0,
+        };
+
+        let base = store.dir.join(BASE_FILE);
+        if base.exists() {
+            let mut state = NeuralNetwork::load_from_binary(&base)?;
+            let deltas = store.delta_files()?;
+            for path in &deltas {
+                let delta: WeightDelta = bincode::deserialize(&fs::read(path)?)
+                    .map_err(|e| atomic_file::check_bincode(path, e))?;
+                apply_delta(&mut state, &delta)?;
+            }
+            store.deltas = deltas.len();
+            store.state = Some(state);
+        }
+        Ok(store)
+    }
+
+    /// Number of deltas on top of the base snapshot
+    pub fn num_deltas(&self) -> usize {
+        self.deltas
+    }
+
+    /// Checkpoint `network`, returning the number of bytes written
+    pub fn save(&mut self, network: &NeuralNetwork) -> Result<usize, Box<dyn std::error::Error>> {
+        let mut next = network.clone();
+        let delta = match &mut self.state {
+            Some(state) if state.get_layers() == next.get_layers() => {
+                diff(state, &mut next, self.tolerance)
+            }
+            _ => None,
+        };
+
+        match delta {
+            // 12 bytes per change against 8 per parameter in a snapshot
+            Some(delta) if delta.changes.len() * 3 < delta.parameters * 2 => {
+                let encoded = bincode::serialize(&delta)?;
+                self.deltas += 1;
+                atomic_file::write_atomic(self.delta_path(self.deltas), &encoded)?;
+                if let Some(state) = &mut self.state {
+                    apply_delta(state, &delta)?;
+                }
+                Ok(encoded.len())
+            }
+            _ => self.write_base(next),
+        }
+    }
+
+    /// File written by the latest `save`: the newest delta, or the base snapshot
+    pub fn latest_path(&self) -> PathBuf {
+        match self.deltas {
+            0 => self.dir.join(BASE_FILE),
+            index => self.delta_path(index),
+        }
+    }
+
+    /// Network as of the latest checkpoint
+    pub fn load(&self) -> Result<NeuralNetwork, Box<dyn std::error::Error>> {
+        self.state
+            .clone()
+            .ok_or_else(|| format!("No checkpoint in {}", self.dir.display()).into())
+    }
+
+    /// Fold every delta into a new base snapshot and remove the delta files
+    pub fn compact(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let state = self.load()?;
+        self.write_base(state)?;
+        Ok(())
+    }
+
+    fn write_base(&mut self, network: NeuralNetwork) -> Result<usize, Box<dyn std::error::Error>> {
+        let encoded = bincode::serialize(&network)?;
+        // Old deltas go first: a crash in between leaves the previous base on
+        // its own, never the new base with deltas meant for the old one
+        for path in self.delta_files()? {
+            fs::remove_file(path)?;
+        }
+        self.deltas = 0;
+        atomic_file::write_atomic(self.dir.join(BASE_FILE), &encoded)?;
+        self.state = Some(network);
+        Ok(encoded.len())
+    }
+
+    fn delta_path(&self, index: usize) -> PathBuf {
+        self.dir.join(format!("delta-{:06}.bin", index))
+    }
+
+    /// Delta files in application order
+    fn delta_files(&self) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("delta-") && name.ends_with(".bin"))
+            })
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+}
+
+/// Parameters of `next` that differ from `state` by more than `tolerance`
+fn diff(
+    state: &mut NeuralNetwork,
+    next: &mut NeuralNetwork,
+    tolerance: f64,
+) -> Option<WeightDelta> {
+    let old = state.state_parameters_mut();
+    let new = next.state_parameters_mut();
+    if old.len() != new.len() {
+        return None;
+    }
+    let changes = old
+        .iter()
+        .zip(&new)
+        .enumerate()
+        .filter(|(_, (old, new))| (***new - ***old).abs() > tolerance)
+        .map(|(index, (_, new))| (index as u32, **new))
+        .collect();
+    Some(WeightDelta {
+        parameters: new.len(),
+        changes,
+    })
+}
+
+fn apply_delta(network: &mut NeuralNetwork, delta: &WeightDelta) -> Result<(), String> {
+    let mut parameters = network.state_parameters_mut();
+    if parameters.len() != delta.parameters {
+        return Err(format!(
+            "Delta for {} parameters cannot apply to a network with {}",
+            delta.parameters,
+            parameters.len()
+        ));
+    }
+    for &(index, value) in &delta.changes {
+        let parameter = parameters.get_mut(index as usize).ok_or_else(|| {
+            format!(
+                "Delta changes parameter {} of a network with {}",
+                index, delta.parameters
+            )
+        })?;
+        **parameter = value;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deltas_replay_and_compact() {
+        let dir = std::env::temp_dir().join(format!("benny-checkpoint-{}", uuid::Uuid::new_v4()));
+        let mut nn = NeuralNetwork::with_layers(&[16, 32, 8], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+
+        let mut store = CheckpointStore::open(&dir, 0.0).unwrap();
+        let base_bytes = store.save(&nn).unwrap();
+        assert_eq!(store.num_deltas(), 0);
+
+        let mut weights = nn.get_layer_weights(1);
+        weights[3] += 0.25;
+        nn.set_layer_parameters(1, &weights, &[0.1; 8]).unwrap();
+        let delta_bytes = store.save(&nn).unwrap();
+        assert_eq!(store.num_deltas(), 1);
+        assert!(delta_bytes * 10 < base_bytes);
+
+        // Unchanged parameters produce an empty delta
+        store.save(&nn).unwrap();
+        assert_eq!(store.num_deltas(), 2);
+
+        let reopened = CheckpointStore::open(&dir, 0.0).unwrap();
+        assert_eq!(reopened.num_deltas(), 2);
+        let restored = reopened.load().unwrap();
+        assert_eq!(restored.get_layer_weights(1), nn.get_layer_weights(1));
+        assert_eq!(restored.get_layer_biases(1), nn.get_layer_biases(1));
+
+        let mut store = reopened;
+        store.compact().unwrap();
+        assert_eq!(store.num_deltas(), 0);
+        assert_eq!(store.delta_files().unwrap().len(), 0);
+        let compacted = CheckpointStore::open(&dir, 0.0).unwrap().load().unwrap();
+        assert_eq!(compacted.get_layer_weights(1), nn.get_layer_weights(1));
+
+        // A different architecture starts a new base
+        store
+            .save(&NeuralNetwork::with_layers(&[16, 3, 8], 0.0))
+            .unwrap();
+        assert_eq!(store.num_deltas(), 0);
+        assert_eq!(store.load().unwrap().get_layers(), &[16, 3, 8]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_corrupt_delta_is_an_error() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 2], 0.0);
+        let parameters = nn.state_parameters_mut().len();
+        let delta = WeightDelta {
+            parameters,
+            changes: vec![(parameters as u32, 1.0)],
+        };
+        assert!(apply_delta(&mut nn, &delta)
+            .unwrap_err()
+            .contains("parameter"));
+    }
+}
@@ -1,6 +1,25 @@
+use crate::adaptive_rates::AdaptiveRates;
+use crate::async_io::Compression;
+use crate::alerts::AlertConfig;
+use crate::augmentation::AugmentationConfig;
+use crate::split::SplitConfig;
+use crate::spsa::SpsaConfig;
+use crate::swa::SwaConfig;
+use crate::convolution::{ConvSpec, PoolKind, PoolSpec};
+use crate::layer_spec::LayerSpec;
 use crate::neural_network::{HebbianLearningMode, NeuralNetwork};
+use crate::nnp_tool::SendKind;
+use crate::population::{PopulationCoder, PopulationColumn};
+use crate::projection::ProjectionMethod;
+use crate::retraining::PromotionMetric;
+use crate::robust_hebbian::RobustHebbian;
+use crate::synaptic_tagging::SynapticTagging;
+use crate::tokens::Scope;
+use crate::differential::StoragePrecision;
+use crate::visualize::ImageFormat;
 use chrono::{DateTime, Utc};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -9,31 +28,27 @@ use std::path::PathBuf;
 #[command(name = "benny")]
 #[command(about = "A configurable neural network runner")]
 #[command(version = "1.0")]
+#[command(after_help = "Run `benny examples` for copy-pasteable command lines, or \
+`benny completions <SHELL>` to enable tab completion.")]
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Override a configuration setting, e.g. `--set training.batch_size=64`
+    /// (repeatable; wins over `BENNY_TRAINING__BATCH_SIZE=...` environment variables)
+    #[arg(
+        long = "set",
+        global = true,
+        value_name = "KEY=VALUE",
+        value_parser = crate::config_overrides::parse_assignment
+    )]
+    pub set: Vec<(String, String)>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// Train a neural network
-    Train {
-        /// Configuration file path
-        #[arg(short, long)]
-        config: PathBuf,
-        /// Training data file path
-        #[arg(short, long)]
-        data: PathBuf,
-        /// Output model file path
-        #[arg(short, long)]
-        output: Option<PathBuf>,
-        /// Number of epochs
-        #[arg(short, long, default_value = "1000")]
-        epochs: usize,
-        /// Verbose output
-        #[arg(short, long)]
-        verbose: bool,
-    },
+    Train(TrainArgs),
     /// Run inference on a trained network
     Predict {
         /// Configuration file path
@@ -42,12 +57,39 @@ pub enum Commands {
         /// Input data file path or single input values (comma-separated)
         #[arg(short, long)]
         input: String,
-        /// Model file path (if available)
+        /// Model file path or `s3://` / `https://` URL (if available); `.mmap` models are memory-mapped
         #[arg(short, long)]
         model: Option<PathBuf>,
         /// Output format
         #[arg(short, long, default_value = "json")]
         format: OutputFormat,
+        /// Average this many passes with dropout left on and report their variance (MC dropout)
+        #[arg(long)]
+        mc_samples: Option<usize>,
+        /// Average this many copies of the model with noise added to their weights and report their variance
+        #[arg(long, conflicts_with = "mc_samples")]
+        ensemble: Option<usize>,
+        /// Standard deviation of the weight noise of `--ensemble`
+        #[arg(long, default_value = "0.01", requires = "ensemble")]
+        ensemble_noise: f64,
+    },
+    /// Measure a trained model on a labelled data set without training it
+    Evaluate {
+        /// Model file path or `s3://` / `https://` URL
+        #[arg(short, long)]
+        model: PathBuf,
+        /// Labelled data (CSV or JSON) file path or URL
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Configuration declaring the data's categorical columns
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Report format
+        #[arg(short, long, default_value = "plain")]
+        format: OutputFormat,
+        /// Write the report here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
     /// Create a sample configuration file
     InitConfig {
@@ -58,11 +100,19 @@ pub enum Commands {
         #[arg(short, long, default_value = "feedforward")]
         network_type: NetworkType,
     },
+    /// Check network configuration files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
     /// Run interactive mode
     Interactive {
         /// Configuration file path
         #[arg(short, long)]
         config: Option<PathBuf>,
+        /// Run the commands in this file (one per line) instead of prompting
+        #[arg(short, long)]
+        script: Option<PathBuf>,
     },
     /// Benchmark network performance
     Benchmark {
@@ -72,42 +122,799 @@ pub enum Commands {
         /// Number of iterations
         #[arg(short, long, default_value = "1000")]
         iterations: usize,
+        /// Experiment seed for data and initial weights: a number or any name
+        #[arg(long)]
+        seed: Option<String>,
+        /// Compare every Hebbian learning mode on the same seeded toy task
+        #[arg(long)]
+        compare_modes: bool,
     },
     /// Run as a network server/daemon
-    Server {
-        /// Configuration file path
+    Server(Box<ServerArgs>),
+    /// Soak-test online learning stability on synthetic inputs
+    Soak(SoakArgs),
+    /// Retrain on a dataset directory on a schedule, promoting models that beat production
+    Retrain(RetrainArgs),
+    /// Find sparse subnetworks by iterative magnitude pruning with weight rewinding
+    Lottery(LotteryArgs),
+    /// Train every run of a sweep file, several at a time, and compare them
+    Sweep(SweepArgs),
+    /// Train two configurations on the same seeded data and report how far they drift apart
+    Difftest(DifftestArgs),
+    /// Show demo of different network capabilities
+    Demo {
+        /// Demo type to run
+        #[arg(short, long, default_value = "all")]
+        demo_type: DemoType,
+    },
+    /// Inspect the Neural Network Protocol (NNP)
+    #[command(visible_alias = "nnp")]
+    Protocol {
+        #[command(subcommand)]
+        command: ProtocolCommand,
+    },
+    /// Manage incremental training checkpoints
+    Checkpoint {
+        #[command(subcommand)]
+        command: CheckpointCommand,
+    },
+    /// Inspect saved models
+    Model {
+        #[command(subcommand)]
+        command: ModelCommand,
+    },
+    /// Look inside what a trained model learned
+    Analyze {
+        #[command(subcommand)]
+        command: AnalyzeCommand,
+    },
+    /// Generate synthetic datasets
+    Data {
+        #[command(subcommand)]
+        command: DataCommand,
+    },
+    /// Issue, list and revoke API tokens
+    Token {
+        #[command(subcommand)]
+        command: TokenCommand,
+    },
+    /// Browse the training history recorded in a telemetry database
+    Runs {
+        #[command(subcommand)]
+        command: RunsCommand,
+    },
+    /// Live terminal dashboard of one or more nodes
+    Top {
+        /// Nodes to monitor (host:port, repeatable)
+        #[arg(short, long = "node", required = true)]
+        nodes: Vec<String>,
+        /// Milliseconds between polls
+        #[arg(short, long, default_value = "1000")]
+        interval_ms: u64,
+    },
+    /// Start the nodes and servers described in a cluster file, wired together
+    Up {
+        /// Cluster file listing the nodes, input server and output server
+        #[arg(default_value = "cluster.toml")]
+        file: PathBuf,
+    },
+    /// Write the processes, ports, shapes and data flow of a cluster file as JSON
+    Plan {
+        /// Cluster file listing the nodes, input server and output server
+        #[arg(default_value = "cluster.toml")]
+        file: PathBuf,
+        /// Forward passes per second the bandwidth is estimated for
+        #[arg(long, default_value = "10")]
+        rate: f64,
+        /// Plan file (printed when unset)
         #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a shell completion script
+    #[command(after_help = "Examples:\n  \
+        benny completions bash > /etc/bash_completion.d/benny\n  \
+        benny completions zsh > ~/.zfunc/_benny\n  \
+        benny completions fish > ~/.config/fish/completions/benny.fish")]
+    Completions {
+        /// Shell to generate the script for
+        shell: Shell,
+    },
+    /// Print example command lines for common workflows
+    Examples {
+        /// Only show this workflow
+        workflow: Option<Workflow>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Report unknown settings, out-of-range values and architecture problems with their line and column
+    Validate {
+        /// Configuration file path or `s3://` / `https://` URL
         config: PathBuf,
-        /// Pre-trained model file path (optional)
+        /// Only check the file itself, without building the network
+        #[arg(long)]
+        no_build: bool,
+        /// Fail on warnings too
+        #[arg(long)]
+        strict: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum CheckpointCommand {
+    /// Fold all weight deltas into a new base snapshot
+    Compact {
+        /// Checkpoint directory
         #[arg(short, long)]
-        model: Option<PathBuf>,
-        /// Port to listen on
-        #[arg(short, long, default_value = "8080")]
-        port: u16,
-        /// SSL certificate file path
+        dir: PathBuf,
+    },
+    /// Write the latest checkpoint as a standalone model file
+    Export {
+        /// Checkpoint directory
+        #[arg(short, long)]
+        dir: PathBuf,
+        /// Model file to write (.bin for binary, .mmap for memory-mappable, JSON otherwise)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ModelCommand {
+    /// Print a model's architecture and the metadata saved with it
+    Info {
+        /// Model file path or `s3://` / `https://` URL
+        model: PathBuf,
+    },
+    /// Draw a model's layers and weights as an SVG or PNG image
+    Viz {
+        /// Model file path or `s3://` / `https://` URL
+        model: PathBuf,
+        /// Image to write; the extension picks SVG or PNG
+        #[arg(short, long, default_value = "network.svg")]
+        output: PathBuf,
+        /// Image width in pixels
+        #[arg(long, default_value = "1200")]
+        width: u32,
+        /// Image height in pixels
+        #[arg(long, default_value = "800")]
+        height: u32,
+        /// Neurons drawn per layer before the rest are only counted
+        #[arg(long, default_value = "24")]
+        max_neurons: usize,
+    },
+    /// Remove the least used hidden neurons and fine-tune what is left
+    Prune {
+        /// Model file path or `s3://` / `https://` URL
+        model: PathBuf,
+        /// Data (CSV or JSON) to measure neuron activity on and fine-tune with
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Configuration declaring the data's categorical columns and population codes
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Parameters of the original model per parameter of the pruned one
+        #[arg(long, default_value = "2.0")]
+        compression: f64,
+        /// Training epochs after the neurons are removed
+        #[arg(short, long, default_value = "20")]
+        epochs: usize,
+        /// Neurons every hidden layer keeps
+        #[arg(long, default_value = "1")]
+        min_neurons: usize,
+        /// Pruned model to write (.bin for binary, .mmap for memory-mappable, JSON otherwise)
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AnalyzeCommand {
+    /// Scatter a layer's activations over a dataset in 2D, coloured by label
+    Project {
+        /// Model file path or `s3://` / `https://` URL
+        #[arg(short, long)]
+        model: PathBuf,
+        /// Labelled data (CSV or JSON) file path or URL
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Configuration declaring the data's categorical columns and population codes
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Layer to project, 0 being the input layer (default: the last hidden layer)
+        #[arg(short, long)]
+        layer: Option<usize>,
+        /// Projection method
+        #[arg(long, default_value = "pca")]
+        method: ProjectionMethod,
+        /// Effective neighbours per point (t-SNE)
+        #[arg(long, default_value = "30")]
+        perplexity: f64,
+        /// Optimisation steps (t-SNE)
+        #[arg(long, default_value = "1000")]
+        iterations: usize,
+        /// Seed of the initial layout (t-SNE)
+        #[arg(long, default_value = "42")]
+        seed: u64,
+        /// Image to write; the extension picks SVG or PNG
+        #[arg(short, long, default_value = "projection.svg")]
+        output: PathBuf,
+    },
+    /// Draw the decision surface of a two-input model, with a dataset's samples on top
+    Boundary {
+        /// Model file path or `s3://` / `https://` URL
+        #[arg(short, long)]
+        model: PathBuf,
+        /// Labelled data (CSV or JSON) to draw and to fit the grid around
+        #[arg(short, long)]
+        data: Option<PathBuf>,
+        /// Cells along each side of the grid
+        #[arg(long, default_value = "100")]
+        resolution: usize,
+        /// Image to write; the extension picks SVG or PNG
+        #[arg(short, long, default_value = "boundary.svg")]
+        output: PathBuf,
+    },
+    /// Rank input features by how much shuffling each one raises the validation error
+    Importance {
+        /// Model file path or `s3://` / `https://` URL
+        #[arg(short, long)]
+        model: PathBuf,
+        /// Labelled data (CSV or JSON) file path or URL
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Training configuration; only its validation split of the data is used
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Shuffles per feature
+        #[arg(short, long, default_value = "5")]
+        repeats: usize,
+        /// Seed of the shuffles
+        #[arg(long, default_value = "42")]
+        seed: u64,
+        /// Report format
+        #[arg(short, long, default_value = "plain")]
+        format: OutputFormat,
+        /// Write the report here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Tune each output's decision threshold on the validation data and report precision/recall trade-offs
+    Thresholds {
+        /// Model file path or `s3://` / `https://` URL
+        #[arg(short, long)]
+        model: PathBuf,
+        /// Labelled data (CSV or JSON) file path or URL
+        #[arg(short, long)]
+        data: PathBuf,
+        /// Training configuration; only its validation split of the data is used
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Thresholds swept between 0 and 1
+        #[arg(long, default_value = "100")]
+        steps: usize,
+        /// Pick the threshold with the best recall at this precision instead of the best F1
+        #[arg(long)]
+        min_precision: Option<f64>,
+        /// Store the chosen thresholds in the model's metadata, used by `predict` and `/predict`
+        #[arg(long)]
+        save: bool,
+        /// Report format
+        #[arg(short, long, default_value = "plain")]
+        format: OutputFormat,
+        /// Write the report here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DataCommand {
+    /// Write a classic synthetic task as training data
+    Generate {
+        /// Task to generate
+        kind: DatasetKind,
+        /// Number of samples
+        #[arg(short, long, default_value = "1000")]
+        samples: usize,
+        /// Standard deviation of Gaussian noise (cluster spread for blobs)
+        #[arg(short, long, default_value = "0.05")]
+        noise: f64,
+        /// Seed: a number or any name
         #[arg(long)]
-        cert: Option<PathBuf>,
-        /// SSL private key file path
+        seed: Option<String>,
+        /// Input bits of the XOR task
+        #[arg(long, default_value = "2")]
+        bits: usize,
+        /// Spiral revolutions, sine periods, or inner circle radius
         #[arg(long)]
-        key: Option<PathBuf>,
-        /// Output network endpoints (host:port)
+        shape: Option<f64>,
+        /// Number of blobs (one-hot target size)
+        #[arg(long, default_value = "3")]
+        classes: usize,
+        /// Input dimensions of the blobs
+        #[arg(long, default_value = "2")]
+        dims: usize,
+        /// Cut the samples into episodes of this many steps and write the sequence format
         #[arg(long)]
-        outputs: Vec<String>,
-        /// Run as daemon (background process)
+        episode_length: Option<usize>,
+        /// File to write (.csv or JSON), or an `s3://` / `https://` URL
+        #[arg(short, long, default_value = "data.json")]
+        output: PathBuf,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DatasetKind {
+    /// Parity of `--bits` random bits
+    Xor,
+    /// Two interleaved spirals
+    Spiral,
+    /// Concentric circles
+    Circles,
+    /// Gaussian clusters with one-hot labels
+    Blobs,
+    /// Sine regression
+    Sine,
+    /// Running parity of a bit stream, one bit per sample
+    TemporalParity,
+}
+
+#[derive(Subcommand)]
+pub enum TokenCommand {
+    /// Create a token and print its secret, which is shown only once
+    Issue {
+        /// Name to recognise the token by
+        name: String,
+        /// What the token may do (repeatable): predict, train, admin or metrics
+        #[arg(short, long = "scope", required = true)]
+        scopes: Vec<Scope>,
+        /// Token file
+        #[arg(short, long, default_value = "tokens.json")]
+        file: PathBuf,
+    },
+    /// List issued tokens
+    List {
+        /// Token file
+        #[arg(short, long, default_value = "tokens.json")]
+        file: PathBuf,
+    },
+    /// Revoke a token by id or name; running servers stop accepting it right away
+    Revoke {
+        /// Token id or name
+        token: String,
+        /// Token file
+        #[arg(short, long, default_value = "tokens.json")]
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RunsCommand {
+    /// List the most recent runs
+    List {
+        /// Telemetry database
+        #[arg(short, long, default_value = "runs.db")]
+        db: PathBuf,
+        /// Number of runs to show
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    /// List runs matching a filter, e.g. "val_error < 0.01 and status = 'completed'"
+    Query {
+        /// Conditions on id, status, epochs, train_error, val_error, learning_rate,
+        /// architecture, started_at, finished_at, config_path or data_path
+        filter: String,
+        /// Telemetry database
+        #[arg(short, long, default_value = "runs.db")]
+        db: PathBuf,
+    },
+    /// Show the hyperparameters, per-epoch metrics and checkpoints of a run
+    Show {
+        /// Run id
+        id: i64,
+        /// Telemetry database
+        #[arg(short, long, default_value = "runs.db")]
+        db: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProtocolCommand {
+    /// Relay NNP traffic to a node and record every frame as pcap
+    Dump {
+        /// Address the capturing relay listens on (host:port)
+        #[arg(short, long, default_value = "127.0.0.1:9090")]
+        listen: String,
+        /// Node to relay to (host:port)
         #[arg(short, long)]
-        daemon: bool,
-        /// Enable Hebbian learning on activations
-        #[arg(long, default_value = "true")]
-        hebbian_learning: bool,
+        target: String,
+        /// Capture file to write
+        #[arg(short, long, default_value = "nnp.pcap")]
+        output: PathBuf,
+        /// Also write a Wireshark Lua dissector to this path
+        #[arg(long)]
+        lua: Option<PathBuf>,
     },
-    /// Show demo of different network capabilities
-    Demo {
-        /// Demo type to run
-        #[arg(short, long, default_value = "all")]
-        demo_type: DemoType,
+    /// Probe a node's readiness with an NNP Ping; fails unless it is ready
+    Ping {
+        /// Node to probe (host:port)
+        target: String,
+        /// Milliseconds to wait for the reply
+        #[arg(short, long, default_value = "2000")]
+        timeout_ms: u64,
+    },
+    /// Send one hand-built NNP message to a node and print the decoded replies
+    Send {
+        /// Node to send to (host:port)
+        #[arg(long)]
+        to: String,
+        /// Message type
+        #[arg(long = "type", value_enum, default_value = "forward")]
+        kind: SendKind,
+        /// Comma-separated forward activations or backward gradients
+        #[arg(long, value_delimiter = ',', allow_hyphen_values = true)]
+        data: Vec<f64>,
+        /// Layer the data belongs to
+        #[arg(long, default_value = "0")]
+        layer: u8,
+        /// Flag the message `ACK_REQUIRED`
+        #[arg(long)]
+        ack: bool,
+        /// Introduce the sender with a handshake first, as nodes in a namespace require
+        #[arg(long)]
+        handshake: bool,
+        /// Namespace announced in the handshake (implies `--handshake`)
+        #[arg(long)]
+        namespace: Option<String>,
+        /// Stop printing replies after this many milliseconds of silence
+        #[arg(long, default_value = "500")]
+        wait_ms: u64,
+    },
+    /// Accept NNP connections and print every decoded message, accepting handshakes and acking
+    Listen {
+        /// Port to listen on
+        #[arg(short, long)]
+        port: u16,
+        /// Address to bind, or a Unix socket path
+        #[arg(short, long, default_value = "127.0.0.1")]
+        address: String,
+    },
+    /// Print the NNP byte layout of every message type as Markdown
+    Spec {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write the canonical NNP frames with their decoded fields, for testing other implementations
+    Fixtures {
+        /// Directory to write `<name>.bin` frames and `<name>.json` fields into
+        #[arg(short, long, default_value = "tests/fixtures/nnp")]
+        output: PathBuf,
+    },
+    /// Check every frame of a fixture directory against its decoded fields and the reference codec
+    Conformance {
+        /// Directory of `<name>.bin` frames and `<name>.json` fields
+        #[arg(default_value = "tests/fixtures/nnp")]
+        dir: PathBuf,
+    },
+    /// Generate a dependency-free Python client module speaking NNP
+    Client {
+        /// Write to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
+/// Arguments for the `train` subcommand
+#[derive(Args, Clone)]
+pub struct TrainArgs {
+    /// Configuration file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub config: PathBuf,
+    /// Training data file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub data: PathBuf,
+    /// Output model file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Number of epochs
+    #[arg(short, long, default_value = "1000")]
+    pub epochs: usize,
+    /// Start from the weights of a trained model, widening or deepening them to the configured architecture
+    #[arg(long)]
+    pub init_from: Option<PathBuf>,
+    /// Label saved in the model's metadata as `key=value` (repeatable)
+    #[arg(long = "tag")]
+    pub tags: Vec<String>,
+    /// Verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
+    /// Write error, learning rate and weight norm charts of the run into this directory
+    #[arg(long)]
+    pub plots_dir: Option<PathBuf>,
+    /// Image format of the charts
+    #[arg(long, default_value = "svg")]
+    pub plot_format: ImageFormat,
+    /// Animate the decision surface of a two-input network into this GIF, one frame per printed epoch
+    #[arg(long)]
+    pub boundary_animation: Option<PathBuf>,
+    /// Animate heatmaps of the weight matrices into this GIF, one frame per printed epoch
+    #[arg(long)]
+    pub weight_animation: Option<PathBuf>,
+    /// Stop once training has run this long (e.g. `2h`, `45m`), after the current batch,
+    /// saving a checkpoint and the model as usual
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub max_duration: Option<std::time::Duration>,
+    /// Load the configuration and dataset, check the shapes, print the plan and exit without training
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for the `soak` subcommand
+#[derive(Args, Clone)]
+pub struct SoakArgs {
+    /// Configuration file path
+    #[arg(short, long)]
+    pub config: PathBuf,
+    /// Soak a trained model instead of a freshly created network
+    #[arg(short, long)]
+    pub model: Option<PathBuf>,
+    /// Length of the run in seconds
+    #[arg(short, long, default_value = "3600")]
+    pub duration: u64,
+    /// Seconds between health samples
+    #[arg(short, long, default_value = "60")]
+    pub interval: u64,
+    /// Seed of the synthetic inputs: a number or any name
+    #[arg(long)]
+    pub seed: Option<String>,
+    /// Fail when the weight norm exceeds this value
+    #[arg(long, default_value = "1e6")]
+    pub max_weight_norm: f64,
+    /// Fail when the output entropy of a sample window drops below this many bits
+    #[arg(long, default_value = "0")]
+    pub min_entropy: f64,
+    /// Write the JSON report to this file
+    #[arg(short, long)]
+    pub report: Option<PathBuf>,
+}
+
+/// Arguments for the `retrain` subcommand
+#[derive(Args, Clone)]
+pub struct RetrainArgs {
+    /// Configuration the candidates are trained with
+    #[arg(short, long)]
+    pub config: PathBuf,
+    /// Directory whose `.csv` and `.json` datasets are merged for every run
+    #[arg(short, long)]
+    pub data_dir: PathBuf,
+    /// Production model, replaced when a candidate beats it (created by the first run if missing)
+    #[arg(short, long)]
+    pub model: PathBuf,
+    /// When to retrain, as a cron spec in UTC (`minute hour day month weekday`, or `@daily` etc.)
+    #[arg(short, long, default_value = "0 3 * * *")]
+    pub schedule: String,
+    /// Number of epochs per run
+    #[arg(short, long, default_value = "1000")]
+    pub epochs: usize,
+    /// Validation metric a candidate has to improve
+    #[arg(long, default_value = "mse")]
+    pub metric: PromotionMetric,
+    /// Least improvement of the metric over production for a promotion
+    #[arg(long, default_value = "0")]
+    pub min_improvement: f64,
+    /// Directory for the merged dataset and candidate models
+    #[arg(long, default_value = "retrain")]
+    pub work_dir: PathBuf,
+    /// Run once right away, before waiting for the schedule
+    #[arg(long)]
+    pub now: bool,
+    /// Stop after the first run
+    #[arg(long)]
+    pub once: bool,
+}
+
+/// Arguments for the `sweep` subcommand
+#[derive(Args, Clone)]
+pub struct SweepArgs {
+    /// Sweep file with the base configuration, data, grid and named runs
+    #[arg(default_value = "sweep.toml")]
+    pub file: PathBuf,
+    /// Runs trained at the same time
+    #[arg(short, long, default_value = "1")]
+    pub parallel: usize,
+    /// Save the model of every run in this directory, as `<run>.json`
+    #[arg(long)]
+    pub models_dir: Option<PathBuf>,
+    /// Report file (printed when unset)
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+    /// Report format
+    #[arg(short, long, default_value = "plain")]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `difftest` subcommand
+#[derive(Args, Clone)]
+pub struct DifftestArgs {
+    /// Configuration of side A
+    #[arg(short = 'a', long)]
+    pub config_a: PathBuf,
+    /// Configuration of side B (side A's when unset, to compare precisions alone)
+    #[arg(short = 'b', long)]
+    pub config_b: Option<PathBuf>,
+    /// Precision side A keeps its parameters in
+    #[arg(long, default_value = "f64")]
+    pub precision_a: StoragePrecision,
+    /// Precision side B keeps its parameters in
+    #[arg(long, default_value = "f64")]
+    pub precision_b: StoragePrecision,
+    /// Training data file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub data: PathBuf,
+    /// Training epochs
+    #[arg(short, long, default_value = "50")]
+    pub epochs: usize,
+    /// Experiment seed of the initial weights and sample order: a number or any name
+    #[arg(long, default_value = "difftest")]
+    pub seed: String,
+    /// Largest output difference allowed; the command fails when it is exceeded
+    #[arg(long, default_value = "1e-6")]
+    pub tolerance: f64,
+    /// Write output divergence, parameter distance and error charts into this directory
+    #[arg(long)]
+    pub plots_dir: Option<PathBuf>,
+    /// Image format of the charts
+    #[arg(long, default_value = "svg")]
+    pub plot_format: ImageFormat,
+    /// Report file (printed when unset)
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+    /// Report format
+    #[arg(short, long, default_value = "plain")]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `lottery` subcommand
+#[derive(Args, Clone)]
+pub struct LotteryArgs {
+    /// Configuration file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub config: PathBuf,
+    /// Training data file path or `s3://` / `https://` URL
+    #[arg(short, long)]
+    pub data: PathBuf,
+    /// Sparse model of the last round (.bin for binary, .mmap for memory-mappable, JSON otherwise)
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+    /// Pruning rounds after the dense run
+    #[arg(short, long, default_value = "5")]
+    pub rounds: usize,
+    /// Share of the remaining weights pruned each round
+    #[arg(short, long, default_value = "0.2")]
+    pub prune: f64,
+    /// Training epochs of every round
+    #[arg(short, long, default_value = "100")]
+    pub epochs: usize,
+    /// Epoch of the dense run every round restarts from (0 resets to the initialization)
+    #[arg(long, default_value = "0")]
+    pub rewind_epoch: usize,
+    /// Accuracy against sparsity report file (printed when unset)
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+    /// Report format
+    #[arg(short, long, default_value = "plain")]
+    pub format: OutputFormat,
+}
+
+/// Arguments for the `server` subcommand
+#[derive(Args, Clone)]
+pub struct ServerArgs {
+    /// Configuration file path
+    #[arg(short, long)]
+    pub config: PathBuf,
+    /// Pre-trained model file path or `s3://` / `https://` URL (optional)
+    #[arg(short, long)]
+    pub model: Option<PathBuf>,
+    /// Port to listen on
+    #[arg(short, long, default_value = "8080")]
+    pub port: u16,
+    /// SSL certificate file path
+    #[arg(long)]
+    pub cert: Option<PathBuf>,
+    /// SSL private key file path
+    #[arg(long)]
+    pub key: Option<PathBuf>,
+    /// Output network endpoints (host:port)
+    #[arg(long)]
+    pub outputs: Vec<String>,
+    /// Run as daemon (background process)
+    #[arg(short, long)]
+    pub daemon: bool,
+    /// Enable Hebbian learning on activations
+    #[arg(long, default_value = "true")]
+    pub hebbian_learning: bool,
+    /// Port for the HTTP metrics endpoint (disabled when omitted)
+    #[arg(long)]
+    pub metrics_port: Option<u16>,
+    /// Per-peer outgoing bandwidth cap in bytes per second
+    #[arg(long)]
+    pub bandwidth_limit: Option<u64>,
+    /// Node state file restored on startup and saved periodically; `--model` takes
+    /// precedence over the restored network
+    #[arg(long)]
+    pub state_file: Option<PathBuf>,
+    /// Compression of the saved node state
+    #[arg(long, value_enum, default_value = "none", requires = "state_file")]
+    pub state_compression: Compression,
+    /// Fixed node identity (UUID)
+    #[arg(long, conflicts_with = "identity_file")]
+    pub node_id: Option<uuid::Uuid>,
+    /// File holding the node identity, created on first run
+    #[arg(long)]
+    pub identity_file: Option<PathBuf>,
+    /// Cluster namespace; peers from other namespaces are rejected
+    #[arg(long)]
+    pub namespace: Option<String>,
+    /// Accept peers from this foreign namespace (repeatable)
+    #[arg(long = "bridge-namespace")]
+    pub bridged_namespaces: Vec<String>,
+    /// Lock the model weights into RAM: the mapping of a `.mmap` model, the
+    /// whole process memory otherwise
+    #[arg(long)]
+    pub lock_pages: bool,
+    /// Accept peers with mismatched layer shapes, padding or truncating their activations
+    #[arg(long)]
+    pub adapt_shapes: bool,
+    /// Batch forward requests arriving within this many microseconds (disabled when omitted)
+    #[arg(long)]
+    pub batch_window_us: Option<u64>,
+    /// Largest batch of forward requests run together
+    #[arg(long, default_value = "64")]
+    pub max_batch_size: usize,
+    /// Speak an older protocol version while the cluster is being upgraded
+    #[arg(long)]
+    pub protocol_version: Option<u8>,
+    /// Lowest input value the network expects; announced so senders rescale their data
+    #[arg(long, requires = "input_max")]
+    pub input_min: Option<f64>,
+    /// Highest input value the network expects
+    #[arg(long, requires = "input_min")]
+    pub input_max: Option<f64>,
+    /// API token file (see `benny token`); enables the REST API on the metrics port
+    #[arg(long, requires = "metrics_port")]
+    pub token_file: Option<PathBuf>,
+    /// Evaluate the served outputs against ground-truth labels arriving on this NNP channel
+    #[arg(long)]
+    pub label_channel: Option<String>,
+    /// Labelled predictions the live accuracy and error cover
+    #[arg(long, default_value = "1000", requires = "label_channel")]
+    pub evaluation_window: usize,
+    /// Capture a sample of the served (input, prediction) pairs into this rotating dataset (.csv or .json)
+    #[arg(long)]
+    pub capture: Option<PathBuf>,
+    /// Share of the requests captured
+    #[arg(long, default_value = "0.01", requires = "capture")]
+    pub capture_rate: f64,
+    /// Samples per capture file
+    #[arg(long, default_value = "10000", requires = "capture")]
+    pub capture_max_samples: usize,
+    /// Capture files kept before the oldest is deleted
+    #[arg(long, default_value = "10", requires = "capture")]
+    pub capture_max_files: usize,
+    /// Input column left out of the capture, e.g. personal data (repeatable)
+    #[arg(long = "capture-drop-input", requires = "capture")]
+    pub capture_drop_inputs: Vec<usize>,
+    /// Serve each forward request within this many milliseconds, forwarding the last outputs flagged stale when a fresh pass would take longer
+    #[arg(long, conflicts_with = "batch_window_us")]
+    pub latency_budget_ms: Option<f64>,
+    /// Resolve the configuration and model, check ports and peers, print the plan and exit
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
 #[derive(ValueEnum, Clone)]
 pub enum OutputFormat {
     Json,
@@ -123,6 +930,135 @@ pub enum NetworkType {
     Distributed,
 }
 
+/// Workflows covered by `benny examples`
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum Workflow {
+    Train,
+    Predict,
+    Serve,
+    Node,
+}
+
+impl Workflow {
+    pub const ALL: [Workflow; 4] = [
+        Workflow::Train,
+        Workflow::Predict,
+        Workflow::Serve,
+        Workflow::Node,
+    ];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Workflow::Train => "Training",
+            Workflow::Predict => "Prediction",
+            Workflow::Serve => "Serving",
+            Workflow::Node => "Distributed nodes",
+        }
+    }
+
+    /// `(description, command line)` pairs for this workflow
+    pub fn examples(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Workflow::Train => &[
+                (
+                    "Write a sample configuration",
+                    "benny init-config -o network_config.toml -n feedforward",
+                ),
+                (
+                    "Generate a two-spirals dataset",
+                    "benny data generate spiral --samples 1000 -o data.csv",
+                ),
+                (
+                    "Train on a CSV file and save the model",
+                    "benny train -c network_config.toml -d data.csv -o model.json -e 1000",
+                ),
+                (
+                    "Check the dataset and print the epochs and batches without training",
+                    "benny train -c network_config.toml -d data.csv -o model.json --dry-run",
+                ),
+                (
+                    "Compare Hebbian learning modes on a seeded toy task",
+                    "benny benchmark -c network_config.toml --seed xor --compare-modes",
+                ),
+                (
+                    "Export the latest training checkpoint",
+                    "benny checkpoint export -d checkpoints -o model.bin",
+                ),
+            ],
+            Workflow::Predict => &[
+                (
+                    "Evaluate a model on a held-out set",
+                    "benny evaluate -m model.json -d test.csv -f json -o report.json",
+                ),
+                (
+                    "Predict a single input",
+                    "benny predict -m model.json -i 1.0,0.0 -f plain",
+                ),
+                (
+                    "Predict every row of a file as CSV",
+                    "benny predict -c network_config.toml -m model.json -i inputs.csv -f csv",
+                ),
+                (
+                    "Predict with a memory-mapped model",
+                    "benny predict -m model.mmap -i 0.5,0.5",
+                ),
+            ],
+            Workflow::Serve => &[
+                (
+                    "Serve a trained model on port 8080",
+                    "benny server -c network_config.toml -m model.json -p 8080",
+                ),
+                (
+                    "Serve over TLS with a metrics endpoint",
+                    "benny server -c network_config.toml --cert cert.pem --key key.pem --metrics-port 9100",
+                ),
+                (
+                    "Batch forward requests arriving within 500µs",
+                    "benny server -c network_config.toml --batch-window-us 500 --max-batch-size 32",
+                ),
+                (
+                    "Check online learning stays stable for an hour",
+                    "benny soak -c network_config.toml -d 3600 -r soak.json",
+                ),
+            ],
+            Workflow::Node => &[
+                (
+                    "Run a node that forwards its outputs to the next one",
+                    "benny server -c network_config.toml -p 8081 --outputs 127.0.0.1:8082",
+                ),
+                (
+                    "Check ports, peers and model shapes without starting the node",
+                    "benny server -c network_config.toml -p 8081 --outputs 127.0.0.1:8082 --dry-run",
+                ),
+                (
+                    "Keep the node identity and state across restarts",
+                    "benny server -c network_config.toml --identity-file node.id --state-file node.state",
+                ),
+                (
+                    "Join a namespace and accept peers from a bridged one",
+                    "benny server -c network_config.toml --namespace prod --bridge-namespace staging",
+                ),
+                (
+                    "Speak the previous protocol version during a rolling upgrade",
+                    "benny server -c network_config.toml --protocol-version 1",
+                ),
+                (
+                    "Start every node and server of a local demo in one terminal",
+                    "benny up cluster.toml",
+                ),
+                (
+                    "Watch two nodes in a terminal dashboard",
+                    "benny top -n 127.0.0.1:8081 -n 127.0.0.1:8082",
+                ),
+                (
+                    "Capture NNP traffic to a node for Wireshark",
+                    "benny protocol dump -t 127.0.0.1:8080 -o nnp.pcap --lua nnp.lua",
+                ),
+            ],
+        }
+    }
+}
+
 #[derive(ValueEnum, Clone)]
 pub enum DemoType {
     All,
@@ -131,12 +1067,19 @@ pub enum DemoType {
     Serialization,
     MultiCore,
     Composition,
+    Hopfield,
+    Recurrent,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NetworkConfig {
-    /// Network architecture (layer sizes)
+    /// Network architecture (layer sizes); shorthand for an Input layer
+    /// followed by sigmoid Dense layers when `layers` is empty
+    #[serde(default)]
     pub architecture: Vec<usize>,
+    /// Heterogeneous layer stack; takes precedence over `architecture`
+    #[serde(default)]
+    pub layers: Vec<LayerSpec>,
     /// Learning rate
     pub learning_rate: f64,
     /// Hebbian learning mode
@@ -159,10 +1102,244 @@ pub struct NetworkConfig {
     pub backprop_rate: f64,
     /// Enable online learning
     pub online_learning: bool,
+    /// Layers (0 = first hidden layer) whose pre-activations are layer-normalized
+    #[serde(default)]
+    pub layer_norm: Vec<usize>,
+    /// Layers (0 = first hidden layer) whose pre-activations are batch-normalized
+    #[serde(default)]
+    pub batch_norm: Vec<usize>,
+    /// Skip connections as `[from, to]` layer pairs (0 = input layer)
+    #[serde(default)]
+    pub skip_connections: Vec<(usize, usize)>,
+    /// Convolutional layers; their shapes must match `architecture`
+    #[serde(default)]
+    pub conv_layers: Vec<ConvLayerConfig>,
+    /// Pooling layers; their shapes must match `architecture`
+    #[serde(default)]
+    pub pool_layers: Vec<PoolLayerConfig>,
+    /// Tie decoder weights to the transposed encoder weights of a symmetric autoencoder
+    #[serde(default)]
+    pub tied_weights: bool,
+    /// Categorical input columns, embedded instead of one-hot encoded
+    #[serde(default)]
+    pub categorical: Vec<CategoricalColumn>,
+    /// Raw input columns spread over Gaussian tuning curves; `architecture`
+    /// counts the encoded inputs
+    #[serde(default)]
+    pub population_inputs: Vec<PopulationColumn>,
+    /// Raw target columns learned as population codes and decoded after inference;
+    /// `architecture` counts the encoded outputs
+    #[serde(default)]
+    pub population_outputs: Vec<PopulationColumn>,
+    /// Activity targets overriding `target_activity` for single layers or neurons
+    #[serde(default)]
+    pub target_activities: Vec<TargetActivityConfig>,
+    /// Sigmoid layers whose neurons adapt their activation gain
+    #[serde(default)]
+    pub intrinsic_plasticity: Vec<IntrinsicPlasticityConfig>,
+    /// Reduced decay for weights with recent large updates
+    #[serde(default)]
+    pub synaptic_tagging: Option<SynapticTaggingConfig>,
+    /// Outlier-robust Hebbian updates for noisy inputs
+    #[serde(default)]
+    pub robust_hebbian: Option<RobustHebbianConfig>,
+    /// Per-connection Hebbian gains that favour rarely active pathways
+    #[serde(default)]
+    pub adaptive_rates: Option<AdaptiveRatesConfig>,
     /// Training configuration
     pub training: TrainingConfig,
 }
 
+/// Homeostatic activity target of layer `layer` (0 = first hidden layer)
+///
+/// Give either one `target` for the whole layer or one entry per neuron in `neurons`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TargetActivityConfig {
+    pub layer: usize,
+    #[serde(default)]
+    pub target: Option<f64>,
+    #[serde(default)]
+    pub neurons: Vec<f64>,
+}
+
+/// Intrinsic plasticity of layer `layer` (0 = first hidden layer)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntrinsicPlasticityConfig {
+    pub layer: usize,
+    /// Mean of the exponential output distribution each neuron adapts towards
+    pub target_mean: f64,
+    #[serde(default = "default_intrinsic_rate")]
+    pub rate: f64,
+}
+
+fn default_intrinsic_rate() -> f64 {
+    0.001
+}
+
+/// Synaptic tagging settings; see `SynapticTagging`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SynapticTaggingConfig {
+    /// Average update magnitude at which a weight is tagged
+    pub threshold: f64,
+    /// Fraction of the normal decay a tagged weight still undergoes
+    #[serde(default = "default_tag_protection")]
+    pub protection: f64,
+    /// Decay steps a tag lasts
+    #[serde(default = "default_tag_duration")]
+    pub duration: u32,
+    /// Weight of the newest update in the running average
+    #[serde(default = "default_tag_smoothing")]
+    pub smoothing: f64,
+}
+
+fn default_tag_protection() -> f64 {
+    0.1
+}
+
+fn default_tag_duration() -> u32 {
+    100
+}
+
+fn default_tag_smoothing() -> f64 {
+    0.1
+}
+
+/// Robust Hebbian update settings; see `RobustHebbian`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RobustHebbianConfig {
+    /// Fraction of each tail of the activation history clipped away
+    #[serde(default)]
+    pub winsorize: Option<f64>,
+    /// Correlate activation histories by medians instead of means
+    #[serde(default)]
+    pub median_correlation: bool,
+    /// Update magnitude cap in running MADs above the running median
+    #[serde(default)]
+    pub mad_cap: Option<f64>,
+    /// Weight of the newest updates in the running median and MAD
+    #[serde(default = "default_robust_smoothing")]
+    pub smoothing: f64,
+}
+
+fn default_robust_smoothing() -> f64 {
+    0.05
+}
+
+/// Adaptive Hebbian rate settings; see `AdaptiveRates`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdaptiveRatesConfig {
+    /// Update magnitude at which a connection keeps the plain Hebbian rate
+    pub target: f64,
+    /// Largest gain given to rarely active connections
+    #[serde(default = "default_max_gain")]
+    pub max_gain: f64,
+    /// Weight of the newest update in each connection's running variance
+    #[serde(default = "default_tag_smoothing")]
+    pub smoothing: f64,
+}
+
+fn default_max_gain() -> f64 {
+    10.0
+}
+
+/// Convolution replacing the dense connection at weight layer `layer`
+///
+/// Leave `height` and `kernel_height` at 1 for a 1D convolution.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ConvLayerConfig {
+    pub layer: usize,
+    pub in_channels: usize,
+    #[serde(default = "default_one")]
+    pub height: usize,
+    pub width: usize,
+    pub out_channels: usize,
+    #[serde(default = "default_one")]
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+    #[serde(default = "default_one")]
+    pub stride: usize,
+    #[serde(default)]
+    pub padding: usize,
+}
+
+/// Pooling replacing the dense connection at weight layer `layer`
+///
+/// Leave `height` and `kernel_height` at 1 for 1D pooling.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PoolLayerConfig {
+    pub layer: usize,
+    pub kind: PoolKind,
+    pub channels: usize,
+    #[serde(default = "default_one")]
+    pub height: usize,
+    pub width: usize,
+    #[serde(default = "default_one")]
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+    pub stride: usize,
+}
+
+impl From<&PoolLayerConfig> for PoolSpec {
+    fn from(config: &PoolLayerConfig) -> Self {
+        PoolSpec::pool2d(
+            config.kind,
+            config.channels,
+            (config.height, config.width),
+            (config.kernel_height, config.kernel_width),
+            config.stride,
+        )
+    }
+}
+
+/// Categorical input column with its vocabulary
+///
+/// `column` indexes the raw inputs (the CSV columns before the target);
+/// `architecture[0]` counts it as one input.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CategoricalColumn {
+    pub column: usize,
+    pub vocabulary: Vec<String>,
+    #[serde(default = "default_embedding_dim")]
+    pub embedding_dim: usize,
+}
+
+impl CategoricalColumn {
+    /// Category index of a CSV value
+    pub fn encode(&self, value: &str) -> Result<f64, String> {
+        self.vocabulary
+            .iter()
+            .position(|v| v == value.trim())
+            .map(|index| index as f64)
+            .ok_or_else(|| {
+                format!(
+                    "Value '{}' is not in the vocabulary of column {}",
+                    value, self.column
+                )
+            })
+    }
+}
+
+fn default_embedding_dim() -> usize {
+    4
+}
+
+fn default_one() -> usize {
+    1
+}
+
+impl From<&ConvLayerConfig> for ConvSpec {
+    fn from(config: &ConvLayerConfig) -> Self {
+        ConvSpec::conv2d(
+            config.in_channels,
+            (config.height, config.width),
+            config.out_channels,
+            (config.kernel_height, config.kernel_width),
+            config.stride,
+            config.padding,
+        )
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum HebbianLearningModeConfig {
     Classic,
@@ -177,6 +1354,9 @@ pub enum HebbianLearningModeConfig {
 pub struct TrainingConfig {
     /// Batch size for training
     pub batch_size: usize,
+    /// Pick the batch size with the best throughput during the first epochs instead
+    #[serde(default)]
+    pub auto_batch_size: bool,
     /// Print progress every N epochs
     pub print_interval: usize,
     /// Early stopping threshold
@@ -185,6 +1365,40 @@ pub struct TrainingConfig {
     pub early_stop_patience: usize,
     /// Validation split ratio
     pub validation_split: f64,
+    /// Fraction of samples held out for the final evaluation only
+    #[serde(default)]
+    pub test_split: f64,
+    /// Shuffling, stratification and persisted indices of the splits
+    #[serde(default)]
+    pub split: SplitConfig,
+    /// Directory for incremental checkpoints (base snapshot plus weight deltas)
+    #[serde(default)]
+    pub checkpoint_dir: Option<PathBuf>,
+    /// Checkpoint every N epochs
+    #[serde(default = "default_checkpoint_interval")]
+    pub checkpoint_interval: usize,
+    /// Parameter changes at or below this size are left out of deltas
+    #[serde(default)]
+    pub checkpoint_tolerance: f64,
+    /// Webhooks and desktop notifications for training events
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    /// SQLite database recording metrics, hyperparameters and checkpoints of each run
+    #[serde(default)]
+    pub telemetry_db: Option<PathBuf>,
+    /// Noise, masking, dropout and label smoothing applied to training samples
+    #[serde(default)]
+    pub augmentation: AugmentationConfig,
+    /// Stochastic weight averaging of the last epochs, saved as a second model
+    #[serde(default)]
+    pub swa: SwaConfig,
+    /// Gradient-free updates by simultaneous perturbation instead of `train`
+    #[serde(default)]
+    pub spsa: SpsaConfig,
+}
+
+fn default_checkpoint_interval() -> usize {
+    10
 }
 
 #[derive(Serialize, Deserialize)]
@@ -199,6 +1413,13 @@ pub struct PredictionResult {
     pub input: Vec<f64>,
     pub output: Vec<f64>,
     pub confidence: f64,
+    /// Outputs clearing the decision thresholds saved with the model
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decisions: Option<Vec<bool>>,
+    /// Variance of every output across the MC dropout passes or ensemble members;
+    /// `output` is then their mean
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variance: Option<Vec<f64>>,
     pub processing_time_ms: f64,
 }
 
@@ -206,6 +1427,7 @@ impl Default for NetworkConfig {
     fn default() -> Self {
         Self {
             architecture: vec![2, 4, 1],
+            layers: Vec::new(),
             learning_rate: 0.1,
             hebbian_mode: HebbianLearningModeConfig::Classic,
             hebbian_rate: 0.05,
@@ -217,6 +1439,20 @@ impl Default for NetworkConfig {
             use_backprop: false,
             backprop_rate: 0.0,
             online_learning: false,
+            layer_norm: Vec::new(),
+            batch_norm: Vec::new(),
+            skip_connections: Vec::new(),
+            conv_layers: Vec::new(),
+            pool_layers: Vec::new(),
+            tied_weights: false,
+            categorical: Vec::new(),
+            population_inputs: Vec::new(),
+            population_outputs: Vec::new(),
+            target_activities: Vec::new(),
+            intrinsic_plasticity: Vec::new(),
+            synaptic_tagging: None,
+            robust_hebbian: None,
+            adaptive_rates: None,
             training: TrainingConfig::default(),
         }
     }
@@ -226,10 +1462,21 @@ impl Default for TrainingConfig {
     fn default() -> Self {
         Self {
             batch_size: 32,
+            auto_batch_size: false,
             print_interval: 100,
             early_stop_threshold: 0.001,
             early_stop_patience: 50,
             validation_split: 0.2,
+            test_split: 0.0,
+            split: SplitConfig::default(),
+            checkpoint_dir: None,
+            checkpoint_interval: default_checkpoint_interval(),
+            checkpoint_tolerance: 0.0,
+            alerts: AlertConfig::default(),
+            telemetry_db: None,
+            augmentation: AugmentationConfig::default(),
+            swa: SwaConfig::default(),
+            spsa: SpsaConfig::default(),
         }
     }
 }
@@ -249,19 +1496,12 @@ impl From<HebbianLearningModeConfig> for HebbianLearningMode {
 
 impl NetworkConfig {
     pub fn create_network(&self) -> Result<NeuralNetwork, Box<dyn std::error::Error>> {
-        let mut nn = if self.online_learning {
-            NeuralNetwork::with_online_learning(
-                &self.architecture,
-                self.learning_rate,
-                self.hebbian_mode.clone().into(),
-            )
-        } else {
-            NeuralNetwork::with_layers_and_mode(
-                &self.architecture,
-                self.learning_rate,
-                self.hebbian_mode.clone().into(),
-            )
-        };
+        let mut nn = NeuralNetwork::from_layer_specs(
+            &self.layer_specs(),
+            self.learning_rate,
+            self.hebbian_mode.clone().into(),
+        )?;
+        nn.set_online_learning(self.online_learning);
 
         // Configure additional parameters
         nn.set_hebbian_rate(self.hebbian_rate);
@@ -271,15 +1511,121 @@ impl NetworkConfig {
             nn.set_backprop_enabled(true, self.backprop_rate);
         }
 
+        for column in &self.categorical {
+            nn.add_embedding(column.column, column.vocabulary.len(), column.embedding_dim)?;
+        }
+        for conv in &self.conv_layers {
+            nn.set_conv_layer(conv.layer, conv.into())?;
+        }
+        for pool in &self.pool_layers {
+            if self.conv_layers.iter().any(|conv| conv.layer == pool.layer) {
+                return Err(format!("layer {} cannot be both conv and pool", pool.layer).into());
+            }
+            nn.set_pool_layer(pool.layer, pool.into())?;
+        }
+        if self.tied_weights {
+            nn.set_tied_weights(true)?;
+        }
+
+        let num_weight_layers = nn.num_layers() - 1;
+        for (name, layers) in [
+            ("layer_norm", &self.layer_norm),
+            ("batch_norm", &self.batch_norm),
+        ] {
+            for &layer in layers {
+                if layer >= num_weight_layers {
+                    return Err(format!("{} layer {} does not exist", name, layer).into());
+                }
+                if nn.get_pool_layer(layer).is_some() {
+                    return Err(format!("{} layer {} is a pooling layer", name, layer).into());
+                }
+            }
+        }
+        for &layer in &self.layer_norm {
+            nn.set_layer_norm(layer, true);
+        }
+        for &layer in &self.batch_norm {
+            if self.layer_norm.contains(&layer) {
+                return Err(
+                    format!("layer {} cannot use both layer_norm and batch_norm", layer).into(),
+                );
+            }
+            nn.set_batch_norm(layer, true);
+        }
+        for &(from, to) in &self.skip_connections {
+            nn.add_skip_connection(from, to)?;
+        }
+        for config in &self.target_activities {
+            match (config.target, config.neurons.is_empty()) {
+                (Some(target), true) => nn.set_layer_target_activity(config.layer, target)?,
+                (None, false) => nn.set_neuron_target_activities(config.layer, &config.neurons)?,
+                _ => {
+                    return Err(format!(
+                        "target_activities for layer {} needs either target or neurons",
+                        config.layer
+                    )
+                    .into())
+                }
+            }
+        }
+        for config in &self.intrinsic_plasticity {
+            nn.set_intrinsic_plasticity(config.layer, config.target_mean, config.rate)?;
+        }
+        if let Some(config) = &self.synaptic_tagging {
+            nn.set_synaptic_tagging(SynapticTagging::new(
+                config.threshold,
+                config.protection,
+                config.duration,
+                config.smoothing,
+            )?);
+        }
+        if let Some(config) = &self.robust_hebbian {
+            nn.set_robust_hebbian(RobustHebbian::new(
+                config.winsorize,
+                config.median_correlation,
+                config.mad_cap,
+                config.smoothing,
+            )?);
+        }
+        if let Some(config) = &self.adaptive_rates {
+            nn.set_adaptive_rates(AdaptiveRates::new(
+                config.target,
+                config.max_gain,
+                config.smoothing,
+            )?);
+        }
+
         Ok(nn)
     }
 
+    /// Input stage encoding `population_inputs`
+    pub fn input_coder(&self) -> Result<PopulationCoder, String> {
+        PopulationCoder::new(self.population_inputs.clone())
+    }
+
+    /// Output stage encoding targets and decoding outputs of `population_outputs`
+    pub fn output_coder(&self) -> Result<PopulationCoder, String> {
+        PopulationCoder::new(self.population_outputs.clone())
+    }
+
+    /// The layer stack, expanding `architecture` when no `layers` are given
+    pub fn layer_specs(&self) -> Vec<LayerSpec> {
+        if self.layers.is_empty() {
+            LayerSpec::from_sizes(&self.architecture)
+        } else {
+            self.layers.clone()
+        }
+    }
+
     pub fn load_from_file<P: AsRef<std::path::Path>>(
         path: P,
     ) -> Result<Self, Box<dyn std::error::Error>> {
-        let content = fs::read_to_string(path)?;
-        let config: NetworkConfig = toml::from_str(&content)?;
-        Ok(config)
+        let path = path.as_ref();
+        let overrides = crate::config_overrides::ConfigOverrides::active();
+        let content = overrides.apply(&fs::read_to_string(path)?)?;
+        let (config, mut report) = crate::config_schema::validate_config(&content, false);
+        overrides.annotate(&mut report);
+        config.ok_or_else(|| report.to_text(&path.display().to_string(), &content).into())
     }
 
     pub fn save_to_file<P: AsRef<std::path::Path>>(
@@ -295,6 +1641,17 @@ impl NetworkConfig {
 impl TrainingData {
     pub fn load_from_csv<P: AsRef<std::path::Path>>(
         path: P,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_from_csv_with_categories(path, &[])
+    }
+
+    /// Load a CSV whose `categorical` columns hold vocabulary values instead of numbers
+    ///
+    /// Categorical values are replaced by their vocabulary index, ready for the
+    /// network's embeddings.
+    pub fn load_from_csv_with_categories<P: AsRef<std::path::Path>>(
+        path: P,
+        categorical: &[CategoricalColumn],
     ) -> Result<Self, Box<dyn std::error::Error>> {
         let mut reader = csv::Reader::from_path(path)?;
         let mut inputs = Vec::new();
@@ -304,7 +1661,13 @@ impl TrainingData {
             let record = result?;
             let values: Vec<f64> = record
                 .iter()
-                .map(|s| s.parse::<f64>())
+                .enumerate()
+                .map(|(column, s)| -> Result<f64, Box<dyn std::error::Error>> {
+                    match categorical.iter().find(|c| c.column == column) {
+                        Some(category) => Ok(category.encode(s)?),
+                        None => Ok(s.parse::<f64>()?),
+                    }
+                })
                 .collect::<Result<Vec<_>, _>>()?;
 
             if values.len() < 2 {
@@ -338,6 +1701,26 @@ impl TrainingData {
         fs::write(path, content)?;
         Ok(())
     }
+
+    /// Write inputs then the target as CSV columns, in the layout `load_from_csv` reads
+    pub fn save_to_csv<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.targets.iter().any(|target| target.len() != 1) {
+            return Err("CSV holds a single target column; use JSON for multi-output data".into());
+        }
+        let mut writer = csv::Writer::from_path(path)?;
+        let inputs = self.inputs.first().map_or(0, Vec::len);
+        let mut header: Vec<String> = (0..inputs).map(|i| format!("x{}", i)).collect();
+        header.push("y".to_string());
+        writer.write_record(&header)?;
+        for (input, target) in self.inputs.iter().zip(&self.targets) {
+            writer.write_record(input.iter().chain(target).map(|v| v.to_string()))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
 }
 
 pub fn parse_input_string(input: &str) -> Result<Vec<f64>, Box<dyn std::error::Error>> {
@@ -363,3 +1746,73 @@ pub fn parse_input_string(input: &str) -> Result<Vec<f64>, Box<dyn std::error::E
         Ok(values?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_categorical_columns() {
+        let path =
+            std::env::temp_dir().join(format!("benny_categorical_{}.csv", std::process::id()));
+        fs::write(&path, "size,color,target\n0.5,red,1\n0.25,blue,0\n").unwrap();
+        let color = CategoricalColumn {
+            column: 1,
+            vocabulary: vec!["blue".to_string(), "red".to_string()],
+            embedding_dim: 3,
+        };
+
+        let data = TrainingData::load_from_csv_with_categories(&path, std::slice::from_ref(&color))
+            .unwrap();
+        assert_eq!(data.inputs, vec![vec![0.5, 1.0], vec![0.25, 0.0]]);
+        assert_eq!(data.targets, vec![vec![1.0], vec![0.0]]);
+        assert!(TrainingData::load_from_csv(&path).is_err());
+
+        let config = NetworkConfig {
+            architecture: vec![2, 4, 1],
+            categorical: vec![color],
+            ..NetworkConfig::default()
+        };
+        let mut network = config.create_network().unwrap();
+        assert_eq!(network.get_layers()[0], 4);
+        assert_eq!(network.forward(&data.inputs[0]).0.len(), 1);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_target_activities() {
+        let mut config = NetworkConfig {
+            target_activities: vec![
+                TargetActivityConfig {
+                    layer: 1,
+                    target: Some(0.05),
+                    neurons: Vec::new(),
+                },
+                TargetActivityConfig {
+                    layer: 0,
+                    target: None,
+                    neurons: vec![0.3, 0.2, 0.3, 0.2],
+                },
+            ],
+            ..NetworkConfig::default()
+        };
+        let network = config.create_network().unwrap();
+        assert_eq!(network.get_target_activity(1, 0), 0.05);
+        assert_eq!(network.get_target_activity(0, 1), 0.2);
+
+        config.target_activities[0].neurons = vec![0.1];
+        assert!(config.create_network().is_err());
+    }
+
+    #[test]
+    fn test_workflow_examples_parse() {
+        for workflow in Workflow::ALL {
+            assert!(!workflow.examples().is_empty());
+            for (_, command) in workflow.examples() {
+                if let Err(e) = Cli::try_parse_from(command.split_whitespace()) {
+                    panic!("example `{}` does not parse: {}", command, e);
+                }
+            }
+        }
+    }
+}
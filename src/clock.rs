@@ -0,0 +1,162 @@
+use crate::distributed_network::NetworkId;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Exchanges kept per peer; the one with the shortest round trip wins
+pub const DEFAULT_WINDOW: usize = 8;
+
+/// Microseconds since the Unix epoch on the local wall clock
+pub fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_micros() as u64
+}
+
+/// Clock offset estimated from one request/response exchange with a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSample {
+    /// Peer clock minus local clock, in microseconds
+    pub offset: i64,
+    /// Round trip spent on the network, excluding the peer's processing time
+    pub delay: u64,
+}
+
+impl ClockSample {
+    /// NTP estimate from the four timestamps of an exchange
+    ///
+    /// `sent` and `returned` are read from the local clock when the request
+    /// leaves and the reply arrives, `received` and `replied` from the peer's
+    /// clock when the request arrives and the reply leaves. The estimate is
+    /// exact when both directions take equally long.
+    pub fn from_exchange(sent: u64, received: u64, replied: u64, returned: u64) -> Self {
+        let (sent, received, replied, returned) = (
+            sent as i64,
+            received as i64,
+            replied as i64,
+            returned as i64,
+        );
+        Self {
+            offset: ((received - sent) + (replied - returned)) / 2,
+            delay: ((returned - sent) - (replied - received)).max(0) as u64,
+        }
+    }
+}
+
+/// Per-peer clock offsets, so timestamps can be compared across nodes
+///
+/// Queueing delays only ever add to a round trip and skew its estimate, so of
+/// the last `window` samples of a peer the one with the smallest delay is used.
+#[derive(Debug, Clone)]
+pub struct ClockSync {
+    samples: Arc<Mutex<HashMap<NetworkId, VecDeque<ClockSample>>>>,
+    window: usize,
+}
+
+impl Default for ClockSync {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl ClockSync {
+    pub fn new(window: usize) -> Self {
+        Self {
+            samples: Arc::new(Mutex::new(HashMap::new())),
+            window: window.max(1),
+        }
+    }
+
+    /// Add the result of an exchange with `peer_id`
+    pub fn record(&self, peer_id: NetworkId, sample: ClockSample) {
+        let mut samples = self.samples.lock().unwrap();
+        let peer_samples = samples.entry(peer_id).or_default();
+        if peer_samples.len() == self.window {
+            peer_samples.pop_front();
+        }
+        peer_samples.push_back(sample);
+    }
+
+    /// Best sample of `peer_id`, `None` until the first exchange
+    pub fn estimate(&self, peer_id: NetworkId) -> Option<ClockSample> {
+        self.samples
+            .lock()
+            .unwrap()
+            .get(&peer_id)?
+            .iter()
+            .min_by_key(|sample| sample.delay)
+            .copied()
+    }
+
+    /// Estimated peer clock minus local clock, in microseconds
+    pub fn offset(&self, peer_id: NetworkId) -> Option<i64> {
+        self.estimate(peer_id).map(|sample| sample.offset)
+    }
+
+    /// Convert a local timestamp to the clock of `peer_id`, unchanged while no estimate exists
+    pub fn to_peer_time(&self, peer_id: NetworkId, local_micros: u64) -> u64 {
+        local_micros.saturating_add_signed(self.offset(peer_id).unwrap_or(0))
+    }
+
+    /// Convert a timestamp read from the clock of `peer_id` to the local clock
+    pub fn to_local_time(&self, peer_id: NetworkId, peer_micros: u64) -> u64 {
+        peer_micros.saturating_add_signed(-self.offset(peer_id).unwrap_or(0))
+    }
+
+    /// Drop the samples of a peer that left
+    pub fn forget(&self, peer_id: NetworkId) {
+        self.samples.lock().unwrap().remove(&peer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offset_uses_fastest_exchange() {
+        // Peer runs 500µs ahead; 100µs each way, 20µs processing
+        let sample = ClockSample::from_exchange(1_000, 1_600, 1_620, 1_220);
+        assert_eq!(
+            sample,
+            ClockSample {
+                offset: 500,
+                delay: 200
+            }
+        );
+
+        let clock = ClockSync::new(2);
+        let peer = NetworkId::new_v4();
+        assert_eq!(clock.offset(peer), None);
+        assert_eq!(clock.to_peer_time(peer, 10_000), 10_000);
+
+        // A reply stuck in a queue makes the exchange look asymmetric
+        clock.record(peer, ClockSample::from_exchange(2_000, 2_600, 2_620, 3_220));
+        assert_eq!(clock.offset(peer), Some(0));
+        clock.record(peer, sample);
+        assert_eq!(clock.offset(peer), Some(500));
+        assert_eq!(clock.to_peer_time(peer, 10_000), 10_500);
+        assert_eq!(clock.to_local_time(peer, 10_500), 10_000);
+
+        // The window only keeps the latest exchanges
+        clock.record(
+            peer,
+            ClockSample {
+                offset: -40,
+                delay: 900,
+            },
+        );
+        clock.record(
+            peer,
+            ClockSample {
+                offset: -30,
+                delay: 800,
+            },
+        );
+        assert_eq!(clock.offset(peer), Some(-30));
+
+        clock.forget(peer);
+        assert_eq!(clock.estimate(peer), None);
+    }
+}
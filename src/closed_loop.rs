@@ -0,0 +1,343 @@
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+/// How observed outputs are turned into the next inputs
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LoopMapping {
+    /// Feed the outputs back unchanged
+    #[default]
+    Identity,
+    /// `inputs = weights · outputs + bias`, one weight row per input
+    Linear {
+        weights: Vec<Vec<f64>>,
+        #[serde(default)]
+        bias: Vec<f64>,
+    },
+    /// An external program started once for the whole loop
+    ///
+    /// It reads one JSON array of outputs per line on stdin and answers each
+    /// with one JSON array of inputs on stdout.
+    Script {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+    },
+}
+
+impl LoopMapping {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            LoopMapping::Identity => Ok(()),
+            LoopMapping::Linear { weights, bias } => {
+                if weights.is_empty() {
+                    return Err("A linear loop mapping needs at least one weight row".to_string());
+                }
+                let width = weights[0].len();
+                if let Some(row) = weights.iter().position(|row| row.len() != width) {
+                    return Err(format!(
+                        "Weight row {} has {} columns, expected {}",
+                        row,
+                        weights[row].len(),
+                        width
+                    ));
+                }
+                if !bias.is_empty() && bias.len() != weights.len() {
+                    return Err(format!(
+                        "Bias has {} values for {} weight rows",
+                        bias.len(),
+                        weights.len()
+                    ));
+                }
+                Ok(())
+            }
+            LoopMapping::Script { command, .. } => {
+                if command.trim().is_empty() {
+                    Err("A scripted loop mapping needs a command".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// Bounds that keep a closed loop from running away
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct SafetyLimits {
+    /// Inputs are clamped to at least this value
+    #[serde(default)]
+    pub min: Option<f64>,
+    /// Inputs are clamped to at most this value
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// Largest change of any input from one iteration to the next
+    #[serde(default)]
+    pub max_step: Option<f64>,
+    /// Stop the loop after feeding this many samples
+    #[serde(default)]
+    pub max_iterations: Option<u64>,
+}
+
+/// Outputs of one network fed back as inputs of an InputServer target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoopConfig {
+    /// WebSocket URL of the OutputServer observing the outputs
+    pub output_url: String,
+    /// Output network followed, as named by the OutputServer
+    pub network_id: String,
+    /// InputServer target the mapped inputs are sent to
+    pub target_id: String,
+    #[serde(default)]
+    pub mapping: LoopMapping,
+    /// Most iterations per second; outputs arriving in between are superseded
+    #[serde(default = "default_rate_hz")]
+    pub rate_hz: f64,
+    #[serde(default)]
+    pub limits: SafetyLimits,
+}
+
+fn default_rate_hz() -> f64 {
+    10.0
+}
+
+impl LoopConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.rate_hz > 0.0 && self.rate_hz.is_finite()) {
+            return Err(format!("Loop rate {} Hz must be positive", self.rate_hz));
+        }
+        if let (Some(min), Some(max)) = (self.limits.min, self.limits.max) {
+            if min > max {
+                return Err(format!("Loop limits min {} is above max {}", min, max));
+            }
+        }
+        if self.limits.max_step.is_some_and(|step| step <= 0.0) {
+            return Err("Loop max_step must be positive".to_string());
+        }
+        self.mapping.validate()
+    }
+
+    /// Time between two iterations
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate_hz)
+    }
+}
+
+/// A running `LoopMapping::Script`
+struct LoopScript {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl LoopScript {
+    fn spawn(command: &str, args: &[String]) -> Result<Self, String> {
+        let mut child = Command::new(command)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Cannot start loop script {}: {}", command, e))?;
+        let stdin = child.stdin.take().ok_or("Loop script has no stdin")?;
+        let stdout = child.stdout.take().ok_or("Loop script has no stdout")?;
+        Ok(Self {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+        })
+    }
+
+    async fn map(&mut self, outputs: &[f64]) -> Result<Vec<f64>, String> {
+        let mut line = serde_json::to_string(outputs).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("Loop script stopped reading: {}", e))?;
+        self.stdin.flush().await.map_err(|e| e.to_string())?;
+        let reply = self
+            .stdout
+            .next_line()
+            .await
+            .map_err(|e| format!("Cannot read loop script output: {}", e))?
+            .ok_or("Loop script exited")?;
+        serde_json::from_str(&reply).map_err(|e| format!("Loop script answered '{}': {}", reply, e))
+    }
+}
+
+/// Maps outputs to inputs under a `LoopConfig`, enforcing its safety limits
+pub struct LoopController {
+    config: LoopConfig,
+    script: Option<LoopScript>,
+    previous: Option<Vec<f64>>,
+    iterations: u64,
+}
+
+impl LoopController {
+    /// Validate `config`, starting its script if it has one
+    pub fn new(config: LoopConfig) -> Result<Self, String> {
+        config.validate()?;
+        let script = match &config.mapping {
+            LoopMapping::Script { command, args } => Some(LoopScript::spawn(command, args)?),
+            _ => None,
+        };
+        Ok(Self {
+            config,
+            script,
+            previous: None,
+            iterations: 0,
+        })
+    }
+
+    pub fn config(&self) -> &LoopConfig {
+        &self.config
+    }
+
+    /// Samples fed back so far
+    pub fn iterations(&self) -> u64 {
+        self.iterations
+    }
+
+    /// Whether `max_iterations` has been reached
+    pub fn is_finished(&self) -> bool {
+        self.config
+            .limits
+            .max_iterations
+            .is_some_and(|max| self.iterations >= max)
+    }
+
+    /// Inputs for the next iteration
+    ///
+    /// Errors when the loop should stop: the iteration budget is spent, the
+    /// mapping failed, or it produced values that are not finite.
+    pub async fn step(&mut self, outputs: &[f64]) -> Result<Vec<f64>, String> {
+        if self.is_finished() {
+            return Err(format!(
+                "Loop finished after {} iterations",
+                self.iterations
+            ));
+        }
+        let mut inputs = match (&self.config.mapping, &mut self.script) {
+            (LoopMapping::Linear { weights, bias }, _) => {
+                if weights[0].len() != outputs.len() {
+                    return Err(format!(
+                        "Linear loop mapping expects {} outputs, got {}",
+                        weights[0].len(),
+                        outputs.len()
+                    ));
+                }
+                weights
+                    .iter()
+                    .enumerate()
+                    .map(|(i, row)| {
+                        let sum: f64 = row.iter().zip(outputs).map(|(w, o)| w * o).sum();
+                        sum + bias.get(i).copied().unwrap_or(0.0)
+                    })
+                    .collect()
+            }
+            (LoopMapping::Script { .. }, Some(script)) => script.map(outputs).await?,
+            _ => outputs.to_vec(),
+        };
+        if let Some(value) = inputs.iter().find(|v| !v.is_finite()) {
+            return Err(format!("Loop mapping produced {}", value));
+        }
+
+        let limits = self.config.limits;
+        for (i, input) in inputs.iter_mut().enumerate() {
+            if let Some(min) = limits.min {
+                *input = input.max(min);
+            }
+            if let Some(max) = limits.max {
+                *input = input.min(max);
+            }
+            let previous = self.previous.as_ref().and_then(|p| p.get(i));
+            if let (Some(step), Some(previous)) = (limits.max_step, previous) {
+                *input = input.clamp(previous - step, previous + step);
+            }
+        }
+        self.previous = Some(inputs.clone());
+        self.iterations += 1;
+        Ok(inputs)
+    }
+
+    /// Stop the script, if any
+    pub async fn shutdown(&mut self) {
+        if let Some(mut script) = self.script.take() {
+            let _ = script.child.kill().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(mapping: LoopMapping, limits: SafetyLimits) -> LoopConfig {
+        LoopConfig {
+            output_url: "ws://127.0.0.1:12001".to_string(),
+            network_id: "main-network".to_string(),
+            target_id: "main-network".to_string(),
+            mapping,
+            rate_hz: default_rate_hz(),
+            limits,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_loop_maps_outputs_within_limits() {
+        let linear = LoopMapping::Linear {
+            weights: vec![vec![2.0, 0.0], vec![1.0, 1.0]],
+            bias: vec![0.0, -1.0],
+        };
+        let limits = SafetyLimits {
+            min: Some(-1.0),
+            max: Some(1.0),
+            max_step: Some(0.5),
+            max_iterations: Some(2),
+        };
+        let mut controller = LoopController::new(config(linear, limits)).unwrap();
+        assert_eq!(
+            controller.step(&[0.25, 0.5]).await.unwrap(),
+            vec![0.5, -0.25]
+        );
+        // Clamped to max, then limited to one step from the previous inputs
+        assert_eq!(controller.step(&[3.0, 3.0]).await.unwrap(), vec![1.0, 0.25]);
+        assert!(controller.is_finished());
+        assert!(controller.step(&[0.0, 0.0]).await.is_err());
+
+        let mut identity =
+            LoopController::new(config(LoopMapping::Identity, Default::default())).unwrap();
+        assert!(identity.step(&[f64::NAN]).await.is_err());
+
+        let bad = LoopMapping::Linear {
+            weights: vec![vec![1.0], vec![1.0, 2.0]],
+            bias: Vec::new(),
+        };
+        assert!(LoopController::new(config(bad, Default::default())).is_err());
+        let mut slow = config(LoopMapping::Identity, Default::default());
+        slow.rate_hz = 0.0;
+        assert!(slow.validate().is_err());
+
+        let json: LoopConfig = serde_json::from_str(
+            r#"{"output_url":"ws://localhost:8081","network_id":"a","target_id":"b"}"#,
+        )
+        .unwrap();
+        assert_eq!(json.mapping, LoopMapping::Identity);
+        assert_eq!(json.interval(), Duration::from_millis(100));
+
+        #[cfg(unix)]
+        {
+            let script = LoopMapping::Script {
+                command: "cat".to_string(),
+                args: Vec::new(),
+            };
+            let mut controller = LoopController::new(config(script, Default::default())).unwrap();
+            assert_eq!(controller.step(&[0.5, 1.5]).await.unwrap(), vec![0.5, 1.5]);
+            controller.shutdown().await;
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+/// Reaction to a peer whose declared layer sizes do not line up with the local network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ShapePolicy {
+    /// Refuse the handshake and tell the peer why
+    #[default]
+    Reject,
+    /// Accept the peer and zero-pad or truncate its activations to the local input size
+    Adapt,
+}
+
+/// Check a peer's declared layer sizes against the local network's
+///
+/// The shapes are compatible when the peer's output layer feeds the local
+/// input layer, the local output layer feeds the peer's input layer, or both
+/// run the same architecture (replicas). Peers that declare no layers pass.
+pub fn check_layers(local: &[u16], peer: &[u16]) -> Result<(), String> {
+    let (Some(&local_in), Some(&local_out), Some(&peer_in), Some(&peer_out)) =
+        (local.first(), local.last(), peer.first(), peer.last())
+    else {
+        return Ok(());
+    };
+    if peer_out == local_in || local_out == peer_in || peer == local {
+        return Ok(());
+    }
+    Err(format!(
+        "Layer shapes {:?} (peer) and {:?} (local) are incompatible: \
+         peer outputs {} values but the local network takes {} inputs",
+        peer, local, peer_out, local_in
+    ))
+}
+
+/// Check a producer's declared layer sizes against the output size a sink expects
+pub fn check_output_size(expected: usize, peer: &[u16]) -> Result<(), String> {
+    match peer.last() {
+        Some(&outputs) if outputs as usize != expected => Err(format!(
+            "Peer layers {:?} produce {} outputs, expected {}",
+            peer, outputs, expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Fit received activations to a network taking `size` inputs
+pub fn fit_inputs(
+    mut data: Vec<f64>,
+    size: usize,
+    policy: ShapePolicy,
+) -> Result<Vec<f64>, String> {
+    if data.len() == size {
+        return Ok(data);
+    }
+    match policy {
+        ShapePolicy::Reject => Err(format!(
+            "Received {} activations for a network with {} inputs",
+            data.len(),
+            size
+        )),
+        ShapePolicy::Adapt => {
+            data.resize(size, 0.0);
+            Ok(data)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_compatibility() {
+        assert!(check_layers(&[3, 4, 2], &[5, 3]).is_ok()); // peer feeds us
+        assert!(check_layers(&[3, 4, 2], &[2, 7]).is_ok()); // we feed the peer
+        assert!(check_layers(&[2, 1], &[2, 1]).is_ok()); // replica
+        assert!(check_layers(&[3, 4, 2], &[]).is_ok());
+
+        let error = check_layers(&[3, 4, 2], &[4, 5]).unwrap_err();
+        assert!(error.contains("peer outputs 5 values"), "{}", error);
+
+        assert!(check_output_size(2, &[4, 2]).is_ok());
+        assert!(check_output_size(3, &[4, 2]).is_err());
+    }
+
+    #[test]
+    fn test_fit_inputs() {
+        assert_eq!(
+            fit_inputs(vec![1.0, 2.0], 2, ShapePolicy::Reject),
+            Ok(vec![1.0, 2.0])
+        );
+        assert!(fit_inputs(vec![1.0], 2, ShapePolicy::Reject).is_err());
+        assert_eq!(
+            fit_inputs(vec![1.0], 3, ShapePolicy::Adapt),
+            Ok(vec![1.0, 0.0, 0.0])
+        );
+        assert_eq!(
+            fit_inputs(vec![1.0, 2.0, 3.0], 2, ShapePolicy::Adapt),
+            Ok(vec![1.0, 2.0])
+        );
+    }
+}
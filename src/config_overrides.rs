@@ -0,0 +1,224 @@
+use crate::config_schema::ConfigReport;
+use std::fmt;
+use std::sync::OnceLock;
+use toml_edit::{DocumentMut, Item, Value};
+
+/// Prefix of the environment variables overriding configuration settings
+pub const ENV_PREFIX: &str = "BENNY_";
+
+/// `BENNY_` variables that configure something else than the network configuration
+const RESERVED_VARIABLES: &[&str] = &["BENNY_HTTP_TOKEN"];
+
+/// `--set` assignments of this process, installed once by the command line
+static CLI_OVERRIDES: OnceLock<Vec<(String, String)>> = OnceLock::new();
+
+/// Where an override comes from, quoted in validation errors
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverrideSource {
+    /// The environment variable of that name
+    Environment(String),
+    /// A `--set` flag
+    CommandLine,
+    /// The settings of the named run of a sweep file
+    Sweep(String),
+}
+
+impl fmt::Display for OverrideSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OverrideSource::Environment(variable) => write!(f, "${}", variable),
+            OverrideSource::CommandLine => f.write_str("--set"),
+            OverrideSource::Sweep(run) => write!(f, "sweep run '{}'", run),
+        }
+    }
+}
+
+/// One setting replaced on top of the configuration file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOverride {
+    /// Dotted path of the setting, e.g. `training.batch_size`
+    pub key: String,
+    /// TOML value (`64`, `0.1`, `true`, `[2, 8, 1]`); anything else is taken as a string
+    pub value: String,
+    pub source: OverrideSource,
+}
+
+/// Settings layered over a configuration file: environment variables first, then `--set` flags
+///
+/// `BENNY_LEARNING_RATE=0.05` sets `learning_rate`; a double underscore
+/// descends into a table, so `BENNY_TRAINING__BATCH_SIZE=64` sets
+/// `training.batch_size`, like `--set training.batch_size=64`. Later
+/// overrides of the same key win. Whole arrays can be replaced
+/// (`--set architecture=[4,16,1]`), single entries of arrays of tables
+/// cannot.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigOverrides {
+    overrides: Vec<ConfigOverride>,
+}
+
+/// Split a `key=value` assignment, as given to `--set`
+pub fn parse_assignment(assignment: &str) -> Result<(String, String), String> {
+    let (key, value) = assignment
+        .split_once('=')
+        .ok_or_else(|| format!("'{}' is not a key=value assignment", assignment))?;
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(format!("'{}' is not a dotted setting name", key));
+    }
+    Ok((key.to_string(), value.trim().to_string()))
+}
+
+/// Install the `--set` assignments every configuration of this process is loaded with
+pub fn set_cli_overrides(assignments: Vec<(String, String)>) {
+    let _ = CLI_OVERRIDES.set(assignments);
+}
+
+impl ConfigOverrides {
+    /// Overrides from `BENNY_*` variables of `vars`, in name order
+    pub fn from_vars<I: IntoIterator<Item = (String, String)>>(vars: I) -> Self {
+        let mut vars: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(name, _)| {
+                name.starts_with(ENV_PREFIX) && !RESERVED_VARIABLES.contains(&name.as_str())
+            })
+            .collect();
+        vars.sort();
+        let overrides = vars
+            .into_iter()
+            .map(|(name, value)| ConfigOverride {
+                key: name[ENV_PREFIX.len()..]
+                    .to_lowercase()
+                    .split("__")
+                    .collect::<Vec<_>>()
+                    .join("."),
+                value,
+                source: OverrideSource::Environment(name),
+            })
+            .collect();
+        Self { overrides }
+    }
+
+    /// `BENNY_*` variables of the environment, then the installed `--set` flags
+    pub fn active() -> Self {
+        let mut overrides = Self::from_vars(std::env::vars());
+        for (key, value) in CLI_OVERRIDES.get().into_iter().flatten() {
+            overrides.push(key, value, OverrideSource::CommandLine);
+        }
+        overrides
+    }
+
+    pub fn push(&mut self, key: &str, value: &str, source: OverrideSource) {
+        self.overrides.push(ConfigOverride {
+            key: key.to_string(),
+            value: value.to_string(),
+            source,
+        });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    pub fn overrides(&self) -> &[ConfigOverride] {
+        &self.overrides
+    }
+
+    /// `content` (TOML) with every override applied, keeping the rest of the file as written
+    pub fn apply(&self, content: &str) -> Result<String, String> {
+        if self.overrides.is_empty() {
+            return Ok(content.to_string());
+        }
+        let mut document: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+        for entry in &self.overrides {
+            let value = entry
+                .value
+                .parse::<Value>()
+                .unwrap_or_else(|_| Value::from(entry.value.as_str()));
+            let segments: Vec<&str> = entry.key.split('.').collect();
+            let Some((last, parents)) = segments.split_last() else {
+                continue;
+            };
+            let mut table = document.as_table_mut() as &mut dyn toml_edit::TableLike;
+            for segment in parents {
+                table = table
+                    .entry(segment)
+                    .or_insert(toml_edit::table())
+                    .as_table_like_mut()
+                    .ok_or_else(|| {
+                        format!(
+                            "Cannot override `{}` ({}): `{}` is not a table",
+                            entry.key, entry.source, segment
+                        )
+                    })?;
+            }
+            table.insert(last, Item::Value(value));
+        }
+        Ok(document.to_string())
+    }
+
+    /// Name the override behind every issue about an overridden setting
+    pub fn annotate(&self, report: &mut ConfigReport) {
+        for issue in &mut report.issues {
+            if let Some(entry) = self
+                .overrides
+                .iter()
+                .rev()
+                .find(|entry| entry.key == issue.key)
+            {
+                issue.message = format!("{} (set by {})", issue.message, entry.source);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::NetworkConfig;
+    use crate::config_schema::validate_config;
+
+    #[test]
+    fn test_layered_overrides() {
+        let content = toml::to_string(&NetworkConfig::default()).unwrap();
+        let vars = [
+            ("BENNY_TRAINING__BATCH_SIZE", "8"),
+            ("BENNY_HEBBIAN_MODE", "Oja"),
+            ("BENNY_HTTP_TOKEN", "secret"),
+            ("HOME", "/root"),
+        ];
+        let mut overrides =
+            ConfigOverrides::from_vars(vars.map(|(k, v)| (k.to_string(), v.to_string())));
+        assert_eq!(overrides.overrides().len(), 2);
+        // The command line wins over the environment
+        let (key, value) = parse_assignment("training.batch_size = 64").unwrap();
+        overrides.push(&key, &value, OverrideSource::CommandLine);
+        overrides.push("architecture", "[3, 5, 2]", OverrideSource::CommandLine);
+        overrides.push("training.spsa.enabled", "true", OverrideSource::CommandLine);
+
+        let (config, report) = validate_config(&overrides.apply(&content).unwrap(), true);
+        assert!(report.is_valid(), "{:?}", report.issues);
+        let config = config.unwrap();
+        assert_eq!(config.training.batch_size, 64);
+        assert_eq!(config.architecture, vec![3, 5, 2]);
+        assert!(matches!(
+            config.hebbian_mode,
+            crate::cli::HebbianLearningModeConfig::Oja
+        ));
+        assert!(config.training.spsa.enabled);
+
+        // Mistakes are reported against the override that made them
+        let mut overrides =
+            ConfigOverrides::from_vars([("BENNY_LERNING_RATE".to_string(), "0.5".to_string())]);
+        overrides.push("decay_rate", "-1", OverrideSource::CommandLine);
+        let (_, mut report) = validate_config(&overrides.apply(&content).unwrap(), false);
+        overrides.annotate(&mut report);
+        let messages: Vec<String> = report.errors().map(|i| i.message.clone()).collect();
+        assert!(messages.contains(
+            &"unknown setting; did you mean `learning_rate`? (set by $BENNY_LERNING_RATE)"
+                .to_string()
+        ));
+        assert!(messages.contains(&"-1 must be a non-negative number (set by --set)".to_string()));
+        assert!(parse_assignment("batch_size").is_err());
+        assert!(parse_assignment("training..batch_size=1").is_err());
+    }
+}
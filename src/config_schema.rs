@@ -0,0 +1,542 @@
+use crate::cli::NetworkConfig;
+use std::fmt::{self, Write};
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The configuration cannot be used
+    Error,
+    /// Usable, but probably not what was meant
+    Warning,
+}
+
+/// One step of the path to a configuration value
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+fn key_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if out.is_empty() => out.push_str(key),
+            Segment::Key(key) => {
+                let _ = write!(out, ".{}", key);
+            }
+            Segment::Index(index) => {
+                let _ = write!(out, "[{}]", index);
+            }
+        }
+    }
+    out
+}
+
+/// A problem found in a configuration file
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub severity: Severity,
+    /// Dotted path of the offending value, e.g. `training.validation_split`
+    /// (empty for syntax errors)
+    pub key: String,
+    /// 1-based position in the file, when the value is written there
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, "line {}, column {}: ", line, column)?;
+        }
+        if !self.key.is_empty() {
+            write!(f, "`{}`: ", self.key)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+/// Everything wrong with a configuration file, in file order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigReport {
+    pub issues: Vec<ConfigIssue>,
+}
+
+impl ConfigReport {
+    pub fn errors(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &ConfigIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Compiler-style listing of the issues in `file`, quoting the lines of `content` they point at
+    pub fn to_text(&self, file: &str, content: &str) -> String {
+        let lines: Vec<&str> = content.lines().collect();
+        let mut out = String::new();
+        for issue in &self.issues {
+            let (icon, label) = match issue.severity {
+                Severity::Error => ("❌", "error"),
+                Severity::Warning => ("⚠️ ", "warning"),
+            };
+            let location = match (issue.line, issue.column) {
+                (Some(line), Some(column)) => format!("{}:{}:{}", file, line, column),
+                _ => file.to_string(),
+            };
+            let subject = match issue.key.as_str() {
+                "" => String::new(),
+                key => format!("`{}`: ", key),
+            };
+            let _ = writeln!(
+                out,
+                "{} {}: {}: {}{}",
+                icon, location, label, subject, issue.message
+            );
+            if let Some(text) = issue.line.and_then(|line| lines.get(line - 1)) {
+                let _ = writeln!(out, "   {:>4} | {}", issue.line.unwrap_or(0), text);
+            }
+        }
+        let errors = self.errors().count();
+        let warnings = self.warnings().count();
+        let _ = match (errors, warnings) {
+            (0, 0) => writeln!(out, "✅ {} is valid", file),
+            (0, _) => writeln!(out, "✅ {} is valid, with {} warning(s)", file, warnings),
+            _ => writeln!(
+                out,
+                "{} error(s), {} warning(s) in {}",
+                errors, warnings, file
+            ),
+        };
+        out
+    }
+
+    fn push(
+        &mut self,
+        document: Option<&ImDocument<&str>>,
+        severity: Severity,
+        path: &[Segment],
+        message: String,
+    ) {
+        let span = document.and_then(|document| locate(document, path));
+        let (line, column) = match (document, span) {
+            (Some(document), Some(span)) => {
+                let (line, column) = line_column(document.raw(), span.start);
+                (Some(line), Some(column))
+            }
+            _ => (None, None),
+        };
+        self.issues.push(ConfigIssue {
+            severity,
+            key: key_path(path),
+            line,
+            column,
+            message,
+        });
+    }
+}
+
+/// 1-based line and column of byte `offset`
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+/// Where the value at `path` is written: its key, or its entry for array elements
+fn locate(document: &ImDocument<&str>, path: &[Segment]) -> Option<Range<usize>> {
+    enum Cursor<'a> {
+        Table(&'a dyn TableLike),
+        Item(&'a Item),
+        Value(&'a Value),
+    }
+    let mut cursor = Cursor::Table(document.as_table());
+    let mut span = None;
+    for segment in path {
+        cursor = match (segment, cursor) {
+            (Segment::Key(key), cursor) => {
+                let table = match cursor {
+                    Cursor::Table(table) => table,
+                    Cursor::Item(item) => item.as_table_like()?,
+                    Cursor::Value(Value::InlineTable(table)) => table,
+                    Cursor::Value(_) => return span,
+                };
+                let (key, item) = table.get_key_value(key)?;
+                span = key.span().or(span);
+                Cursor::Item(item)
+            }
+            (Segment::Index(index), Cursor::Item(Item::ArrayOfTables(tables))) => {
+                let table = tables.get(*index)?;
+                span = table.span().or(span);
+                Cursor::Table(table)
+            }
+            (Segment::Index(index), Cursor::Item(Item::Value(Value::Array(array))))
+            | (Segment::Index(index), Cursor::Value(Value::Array(array))) => {
+                let value = array.get(*index)?;
+                span = value.span().or(span);
+                Cursor::Value(value)
+            }
+            _ => return span,
+        };
+    }
+    span
+}
+
+/// Keys of `written` that deserializing into a `NetworkConfig` ignored
+///
+/// `understood` is the parsed configuration serialized again: every key
+/// serde knows reappears in it, so whatever is missing was never read.
+fn unknown_keys(
+    written: &toml::Value,
+    understood: &toml::Value,
+    path: &mut Vec<Segment>,
+    found: &mut Vec<(Vec<Segment>, Option<String>)>,
+) {
+    match (written, understood) {
+        (toml::Value::Table(written), toml::Value::Table(understood)) => {
+            for (key, value) in written {
+                path.push(Segment::Key(key.clone()));
+                match understood.get(key) {
+                    Some(known) => unknown_keys(value, known, path, found),
+                    None => found.push((path.clone(), closest(key, understood.keys()))),
+                }
+                path.pop();
+            }
+        }
+        (toml::Value::Array(written), toml::Value::Array(understood)) => {
+            for (index, (value, known)) in written.iter().zip(understood).enumerate() {
+                path.push(Segment::Index(index));
+                unknown_keys(value, known, path, found);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The known key a misspelled `key` most likely meant
+fn closest<'a>(key: &str, known: impl Iterator<Item = &'a String>) -> Option<String> {
+    known
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= 2.max(candidate.len() / 4))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Range and consistency checks on the values of a parsed configuration
+fn check_values(config: &NetworkConfig, report: &mut ConfigReport, doc: &ImDocument<&str>) {
+    let key = |name: &str| vec![Segment::Key(name.to_string())];
+    let training = |name: &str| {
+        vec![
+            Segment::Key("training".to_string()),
+            Segment::Key(name.to_string()),
+        ]
+    };
+
+    for (name, rate) in [
+        ("learning_rate", config.learning_rate),
+        ("hebbian_rate", config.hebbian_rate),
+        ("anti_hebbian_rate", config.anti_hebbian_rate),
+        ("decay_rate", config.decay_rate),
+        ("homeostatic_rate", config.homeostatic_rate),
+        ("backprop_rate", config.backprop_rate),
+    ] {
+        if !(rate.is_finite() && rate >= 0.0) {
+            report.push(
+                Some(doc),
+                Severity::Error,
+                &key(name),
+                format!("{} must be a non-negative number", rate),
+            );
+        } else if rate > 1.0 {
+            report.push(
+                Some(doc),
+                Severity::Warning,
+                &key(name),
+                format!(
+                    "{} is unusually large for a rate; training will likely diverge",
+                    rate
+                ),
+            );
+        }
+    }
+    if !(0.0..=1.0).contains(&config.target_activity) {
+        report.push(
+            Some(doc),
+            Severity::Error,
+            &key("target_activity"),
+            format!(
+                "{} is not an activity level between 0 and 1",
+                config.target_activity
+            ),
+        );
+    }
+    if config.use_backprop && config.backprop_rate == 0.0 {
+        report.push(
+            Some(doc),
+            Severity::Warning,
+            &key("backprop_rate"),
+            "is 0 although use_backprop is on, so backpropagation changes nothing".to_string(),
+        );
+    }
+
+    let splits = &config.training;
+    for (name, split) in [
+        ("validation_split", splits.validation_split),
+        ("test_split", splits.test_split),
+    ] {
+        if !(0.0..1.0).contains(&split) {
+            report.push(
+                Some(doc),
+                Severity::Error,
+                &training(name),
+                format!("{} must be at least 0 and below 1", split),
+            );
+        }
+    }
+    if splits.validation_split + splits.test_split >= 1.0
+        && splits.validation_split < 1.0
+        && splits.test_split < 1.0
+    {
+        report.push(
+            Some(doc),
+            Severity::Error,
+            &training("test_split"),
+            format!(
+                "validation ({}) and test ({}) splits leave no training data",
+                splits.validation_split, splits.test_split
+            ),
+        );
+    }
+
+    if config.layers.is_empty() {
+        if config.architecture.len() < 2 {
+            report.push(
+                Some(doc),
+                Severity::Error,
+                &key("architecture"),
+                format!(
+                    "needs at least an input and an output layer, got {:?}",
+                    config.architecture
+                ),
+            );
+        }
+        for (index, &size) in config.architecture.iter().enumerate() {
+            if size == 0 {
+                report.push(
+                    Some(doc),
+                    Severity::Error,
+                    &[
+                        Segment::Key("architecture".to_string()),
+                        Segment::Index(index),
+                    ],
+                    "layers need at least one neuron".to_string(),
+                );
+            }
+        }
+    } else if !config.architecture.is_empty() {
+        report.push(
+            Some(doc),
+            Severity::Warning,
+            &key("architecture"),
+            "is ignored because `layers` is given".to_string(),
+        );
+    }
+}
+
+/// A syntax or type error reported by the TOML parser
+fn parse_error(content: &str, span: Option<Range<usize>>, message: &str) -> ConfigIssue {
+    let (line, column) = match span {
+        Some(span) => {
+            let (line, column) = line_column(content, span.start);
+            (Some(line), Some(column))
+        }
+        None => (None, None),
+    };
+    ConfigIssue {
+        severity: Severity::Error,
+        key: String::new(),
+        line,
+        column,
+        message: message.trim().to_string(),
+    }
+}
+
+/// Report the keys of `document` missing from `understood`; with `typos_only`, just those resembling a known key
+fn report_unknown_keys(
+    report: &mut ConfigReport,
+    document: &ImDocument<&str>,
+    understood: &toml::Value,
+    typos_only: bool,
+) {
+    let Ok(written) = toml::from_str::<toml::Value>(document.raw()) else {
+        return;
+    };
+    let mut found = Vec::new();
+    unknown_keys(&written, understood, &mut Vec::new(), &mut found);
+    for (path, suggestion) in found {
+        let message = match suggestion {
+            Some(known) => format!("unknown setting; did you mean `{}`?", known),
+            None if typos_only => continue,
+            None => "unknown setting".to_string(),
+        };
+        report.push(Some(document), Severity::Error, &path, message);
+    }
+}
+
+/// Parse and check a network configuration (TOML), collecting every problem at once
+///
+/// Syntax and type errors come from the TOML parser with their position.
+/// A configuration that parses is then checked for keys no setting reads
+/// (typos such as `lerning_rate`, with a suggestion), for rates and splits
+/// outside their ranges and for an unusable architecture. With
+/// `build_network` the network is also built, which catches the remaining
+/// architecture problems (convolution shapes, normalization and skip
+/// connection layers). The configuration is returned unless it has errors.
+pub fn validate_config(
+    content: &str,
+    build_network: bool,
+) -> (Option<NetworkConfig>, ConfigReport) {
+    let mut report = ConfigReport::default();
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            report
+                .issues
+                .push(parse_error(content, e.span(), e.message()));
+            return (None, report);
+        }
+    };
+
+    let config: NetworkConfig = match toml::from_str(content) {
+        Ok(config) => config,
+        Err(e) => {
+            report
+                .issues
+                .push(parse_error(content, e.span(), e.message()));
+            // A missing setting is often a misspelled one
+            if let Ok(defaults) = toml::Value::try_from(NetworkConfig::default()) {
+                report_unknown_keys(&mut report, &document, &defaults, true);
+            }
+            report
+                .issues
+                .sort_by_key(|issue| (issue.line, issue.column));
+            return (None, report);
+        }
+    };
+
+    if let Ok(understood) = toml::Value::try_from(&config) {
+        report_unknown_keys(&mut report, &document, &understood, false);
+    }
+    check_values(&config, &mut report, &document);
+
+    if build_network && report.is_valid() {
+        if let Err(e) = config.create_network() {
+            let key = if config.layers.is_empty() {
+                "architecture"
+            } else {
+                "layers"
+            };
+            report.push(
+                Some(&document),
+                Severity::Error,
+                &[Segment::Key(key.to_string())],
+                format!("the network cannot be built: {}", e),
+            );
+        }
+    }
+
+    report
+        .issues
+        .sort_by_key(|issue| (issue.line.unwrap_or(usize::MAX), issue.column));
+    let config = report.is_valid().then_some(config);
+    (config, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_config() {
+        let valid = toml::to_string(&NetworkConfig::default()).unwrap();
+        let (config, report) = validate_config(&valid, true);
+        assert!(config.is_some());
+        assert!(report.issues.is_empty(), "{:?}", report.issues);
+
+        let content = valid
+            .replacen(
+                "learning_rate = 0.1",
+                "lerning_rate = 0.1\nlearning_rate = -0.1",
+                1,
+            )
+            .replace(
+                "validation_split = 0.2",
+                "validation_split = 0.2\nbatchsize = 4",
+            );
+        let (config, report) = validate_config(&content, false);
+        assert!(config.is_none());
+        let messages: Vec<String> = report.errors().map(ToString::to_string).collect();
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(
+            messages[0].contains("`lerning_rate`: unknown setting; did you mean `learning_rate`?")
+        );
+        assert!(messages[1].contains("`learning_rate`: -0.1 must be a non-negative number"));
+        assert!(messages[2]
+            .contains("`training.batchsize`: unknown setting; did you mean `batch_size`?"));
+        let line = content
+            .lines()
+            .position(|l| l.starts_with("lerning_rate"))
+            .unwrap()
+            + 1;
+        assert_eq!(report.issues[0].line, Some(line));
+        assert_eq!(report.issues[0].column, Some(1));
+        let text = report.to_text("config.toml", &content);
+        assert!(text.contains(&format!("config.toml:{}:1: error:", line)));
+        assert!(text.contains("| lerning_rate = 0.1"));
+
+        // Type and syntax errors keep the parser's position
+        let (_, report) = validate_config(
+            &valid.replace("history_size = 20", "history_size = \"20\""),
+            false,
+        );
+        assert_eq!(report.errors().count(), 1);
+        assert!(report.issues[0].line.is_some());
+        let (_, report) = validate_config("architecture = [2, 4", false);
+        assert_eq!(report.issues[0].line, Some(1));
+
+        // Architectures are only fully checked by building them
+        let broken = valid.replace("batch_norm = []", "batch_norm = [5]");
+        assert!(validate_config(&broken, false).1.is_valid());
+        let (_, report) = validate_config(&broken, true);
+        assert!(report.issues[0]
+            .message
+            .contains("batch_norm layer 5 does not exist"));
+    }
+}
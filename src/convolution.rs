@@ -0,0 +1,478 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Shape of a 2D convolution over channel-major (`[channel][row][col]`) activations
+///
+/// A 1D convolution is the special case `in_height == kernel_height == 1`;
+/// see `ConvSpec::conv1d`. Zero padding and stride apply to both dimensions
+/// (only the width for 1D).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConvSpec {
+    pub in_channels: usize,
+    pub in_height: usize,
+    pub in_width: usize,
+    pub out_channels: usize,
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+    pub stride: usize,
+    pub padding: usize,
+}
+
+impl ConvSpec {
+    /// 1D convolution over `in_channels` signals of `length` samples
+    pub fn conv1d(
+        in_channels: usize,
+        length: usize,
+        out_channels: usize,
+        kernel: usize,
+        stride: usize,
+        padding: usize,
+    ) -> Self {
+        Self {
+            in_channels,
+            in_height: 1,
+            in_width: length,
+            out_channels,
+            kernel_height: 1,
+            kernel_width: kernel,
+            stride,
+            padding,
+        }
+    }
+
+    /// 2D convolution over `in_channels` images of `height` x `width`
+    pub fn conv2d(
+        in_channels: usize,
+        (height, width): (usize, usize),
+        out_channels: usize,
+        (kernel_height, kernel_width): (usize, usize),
+        stride: usize,
+        padding: usize,
+    ) -> Self {
+        Self {
+            in_channels,
+            in_height: height,
+            in_width: width,
+            out_channels,
+            kernel_height,
+            kernel_width,
+            stride,
+            padding,
+        }
+    }
+
+    fn is_1d(&self) -> bool {
+        self.in_height == 1 && self.kernel_height == 1
+    }
+
+    fn vertical_padding(&self) -> usize {
+        if self.is_1d() {
+            0
+        } else {
+            self.padding
+        }
+    }
+
+    fn output_dim(input: usize, kernel: usize, stride: usize, padding: usize) -> usize {
+        (input + 2 * padding)
+            .checked_sub(kernel)
+            .map_or(0, |span| span / stride.max(1) + 1)
+    }
+
+    pub fn out_height(&self) -> usize {
+        Self::output_dim(
+            self.in_height,
+            self.kernel_height,
+            self.stride,
+            self.vertical_padding(),
+        )
+    }
+
+    pub fn out_width(&self) -> usize {
+        Self::output_dim(self.in_width, self.kernel_width, self.stride, self.padding)
+    }
+
+    /// Number of input neurons
+    pub fn input_len(&self) -> usize {
+        self.in_channels * self.in_height * self.in_width
+    }
+
+    /// Number of output neurons
+    pub fn output_len(&self) -> usize {
+        self.out_channels * self.out_height() * self.out_width()
+    }
+
+    /// Number of shared kernel weights
+    pub fn kernel_len(&self) -> usize {
+        self.out_channels * self.in_channels * self.kernel_height * self.kernel_width
+    }
+
+    /// Check the spec against the sizes of the layers it connects
+    pub fn validate(&self, from_size: usize, to_size: usize) -> Result<(), String> {
+        if self.stride == 0 || self.kernel_height == 0 || self.kernel_width == 0 {
+            return Err("Convolution stride and kernel must be non-zero".to_string());
+        }
+        if self.output_len() == 0 {
+            return Err("Convolution kernel is larger than its padded input".to_string());
+        }
+        if self.input_len() != from_size || self.output_len() != to_size {
+            return Err(format!(
+                "Convolution maps {} inputs to {} outputs but the layers have {} and {}",
+                self.input_len(),
+                self.output_len(),
+                from_size,
+                to_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Every `(from_neuron, to_neuron, kernel_index)` connection; padded taps are skipped
+    pub fn connections(&self) -> Vec<(usize, usize, usize)> {
+        let (out_h, out_w) = (self.out_height(), self.out_width());
+        let pad_y = self.vertical_padding() as isize;
+        let pad_x = self.padding as isize;
+        let mut connections = Vec::new();
+
+        for oc in 0..self.out_channels {
+            for oy in 0..out_h {
+                for ox in 0..out_w {
+                    let to = (oc * out_h + oy) * out_w + ox;
+                    for ic in 0..self.in_channels {
+                        for ky in 0..self.kernel_height {
+                            let iy = (oy * self.stride + ky) as isize - pad_y;
+                            if iy < 0 || iy >= self.in_height as isize {
+                                continue;
+                            }
+                            for kx in 0..self.kernel_width {
+                                let ix = (ox * self.stride + kx) as isize - pad_x;
+                                if ix < 0 || ix >= self.in_width as isize {
+                                    continue;
+                                }
+                                let from = (ic * self.in_height + iy as usize) * self.in_width
+                                    + ix as usize;
+                                let kernel = ((oc * self.in_channels + ic) * self.kernel_height
+                                    + ky)
+                                    * self.kernel_width
+                                    + kx;
+                                connections.push((from, to, kernel));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        connections
+    }
+}
+
+/// Convolution layer: shared kernels and per-channel biases
+///
+/// The network keeps the layer unrolled into its dense weight matrix so every
+/// forward, backprop and Hebbian path works unchanged. After each learning
+/// step `absorb` folds the dense updates back into the shared parameters —
+/// each kernel weight moves by the average update of the positions it
+/// covers, so for Hebbian rules it follows the patch/feature-map coincidence
+/// — and `unroll` rebuilds the tied matrix.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConvLayer {
+    pub spec: ConvSpec,
+    pub kernels: Vec<f64>, // kernels[out_channel][in_channel][ky][kx]
+    pub biases: Vec<f64>,  // biases[out_channel]
+}
+
+impl ConvLayer {
+    /// Randomly initialized layer
+    pub fn new(spec: ConvSpec) -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            spec,
+            kernels: (0..spec.kernel_len())
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect(),
+            biases: (0..spec.out_channels)
+                .map(|_| rng.gen_range(-1.0..1.0))
+                .collect(),
+        }
+    }
+
+    /// Dense weights (`[from][to]`) and per-neuron biases equivalent to this layer
+    pub fn unroll(&self) -> (Vec<Vec<f64>>, Vec<f64>) {
+        let mut weights = vec![vec![0.0; self.spec.output_len()]; self.spec.input_len()];
+        for (from, to, kernel) in self.spec.connections() {
+            weights[from][to] = self.kernels[kernel];
+        }
+        let per_channel = self.spec.out_height() * self.spec.out_width();
+        let biases = (0..self.spec.output_len())
+            .map(|neuron| self.biases[neuron / per_channel])
+            .collect();
+        (weights, biases)
+    }
+
+    /// Fold updates made to the unrolled weights and biases into the shared parameters
+    pub fn absorb(&mut self, weights: &[Vec<f64>], biases: &[f64]) {
+        let mut kernel_delta = vec![0.0; self.kernels.len()];
+        let mut kernel_taps = vec![0usize; self.kernels.len()];
+        for (from, to, kernel) in self.spec.connections() {
+            kernel_delta[kernel] += weights[from][to] - self.kernels[kernel];
+            kernel_taps[kernel] += 1;
+        }
+        for ((kernel, delta), taps) in self.kernels.iter_mut().zip(kernel_delta).zip(kernel_taps) {
+            if taps > 0 {
+                *kernel += delta / taps as f64;
+            }
+        }
+
+        let per_channel = self.spec.out_height() * self.spec.out_width();
+        for (channel, bias) in self.biases.iter_mut().enumerate() {
+            let channel_biases = &biases[channel * per_channel..(channel + 1) * per_channel];
+            let delta: f64 = channel_biases.iter().map(|b| b - *bias).sum();
+            *bias += delta / per_channel as f64;
+        }
+    }
+
+    /// Number of learnable parameters
+    pub fn num_parameters(&self) -> usize {
+        self.kernels.len() + self.biases.len()
+    }
+}
+
+/// Pooling reduction applied to each window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PoolKind {
+    Max,
+    Avg,
+}
+
+/// Pooling over channel-major activations, channel by channel
+///
+/// Pooling layers have no weights and no activation function: each output
+/// neuron is the max or average of its window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolSpec {
+    pub kind: PoolKind,
+    pub channels: usize,
+    pub in_height: usize,
+    pub in_width: usize,
+    pub kernel_height: usize,
+    pub kernel_width: usize,
+    pub stride: usize,
+}
+
+impl PoolSpec {
+    /// 1D pooling over `channels` signals of `length` samples
+    pub fn pool1d(
+        kind: PoolKind,
+        channels: usize,
+        length: usize,
+        kernel: usize,
+        stride: usize,
+    ) -> Self {
+        Self::pool2d(kind, channels, (1, length), (1, kernel), stride)
+    }
+
+    /// 2D pooling over `channels` maps of `height` x `width`
+    pub fn pool2d(
+        kind: PoolKind,
+        channels: usize,
+        (height, width): (usize, usize),
+        (kernel_height, kernel_width): (usize, usize),
+        stride: usize,
+    ) -> Self {
+        Self {
+            kind,
+            channels,
+            in_height: height,
+            in_width: width,
+            kernel_height,
+            kernel_width,
+            stride,
+        }
+    }
+
+    pub fn out_height(&self) -> usize {
+        ConvSpec::output_dim(self.in_height, self.kernel_height, self.stride, 0)
+    }
+
+    pub fn out_width(&self) -> usize {
+        ConvSpec::output_dim(self.in_width, self.kernel_width, self.stride, 0)
+    }
+
+    /// Number of input neurons
+    pub fn input_len(&self) -> usize {
+        self.channels * self.in_height * self.in_width
+    }
+
+    /// Number of output neurons
+    pub fn output_len(&self) -> usize {
+        self.channels * self.out_height() * self.out_width()
+    }
+
+    /// Check the spec against the sizes of the layers it connects
+    pub fn validate(&self, from_size: usize, to_size: usize) -> Result<(), String> {
+        if self.stride == 0 || self.kernel_height == 0 || self.kernel_width == 0 {
+            return Err("Pooling stride and kernel must be non-zero".to_string());
+        }
+        if self.output_len() == 0 {
+            return Err("Pooling kernel is larger than its input".to_string());
+        }
+        if self.input_len() != from_size || self.output_len() != to_size {
+            return Err(format!(
+                "Pooling maps {} inputs to {} outputs but the layers have {} and {}",
+                self.input_len(),
+                self.output_len(),
+                from_size,
+                to_size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Input neurons covered by each output neuron
+    fn windows(&self) -> Vec<Vec<usize>> {
+        let (out_h, out_w) = (self.out_height(), self.out_width());
+        let mut windows = Vec::with_capacity(self.output_len());
+        for channel in 0..self.channels {
+            for oy in 0..out_h {
+                for ox in 0..out_w {
+                    let mut window = Vec::with_capacity(self.kernel_height * self.kernel_width);
+                    for ky in 0..self.kernel_height {
+                        for kx in 0..self.kernel_width {
+                            let (iy, ix) = (oy * self.stride + ky, ox * self.stride + kx);
+                            window.push((channel * self.in_height + iy) * self.in_width + ix);
+                        }
+                    }
+                    windows.push(window);
+                }
+            }
+        }
+        windows
+    }
+
+    fn argmax(window: &[usize], inputs: &[f64]) -> usize {
+        window
+            .iter()
+            .copied()
+            .max_by(|&a, &b| inputs[a].partial_cmp(&inputs[b]).unwrap())
+            .unwrap()
+    }
+
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        self.windows()
+            .iter()
+            .map(|window| match self.kind {
+                PoolKind::Max => inputs[Self::argmax(window, inputs)],
+                PoolKind::Avg => {
+                    window.iter().map(|&i| inputs[i]).sum::<f64>() / window.len() as f64
+                }
+            })
+            .collect()
+    }
+
+    /// Route `grad_output` back to the inputs: to the window maximum for max
+    /// pooling, spread evenly for average pooling
+    pub fn backward(&self, inputs: &[f64], grad_output: &[f64]) -> Vec<f64> {
+        let mut grad_inputs = vec![0.0; inputs.len()];
+        for (window, &grad) in self.windows().iter().zip(grad_output) {
+            match self.kind {
+                PoolKind::Max => grad_inputs[Self::argmax(window, inputs)] += grad,
+                PoolKind::Avg => {
+                    for &i in window {
+                        grad_inputs[i] += grad / window.len() as f64;
+                    }
+                }
+            }
+        }
+        grad_inputs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_shapes() {
+        let spec = ConvSpec::conv2d(1, (5, 5), 2, (3, 3), 1, 0);
+        assert_eq!((spec.out_height(), spec.out_width()), (3, 3));
+        assert_eq!(spec.output_len(), 18);
+
+        let padded = ConvSpec::conv2d(3, (4, 4), 1, (3, 3), 2, 1);
+        assert_eq!((padded.out_height(), padded.out_width()), (2, 2));
+
+        let signal = ConvSpec::conv1d(1, 8, 4, 3, 1, 1);
+        assert_eq!((signal.out_height(), signal.out_width()), (1, 8));
+        assert!(signal.validate(8, 32).is_ok());
+        assert!(signal.validate(8, 30).is_err());
+    }
+
+    #[test]
+    fn test_unroll_matches_direct_convolution() {
+        let spec = ConvSpec::conv1d(1, 4, 1, 2, 1, 0);
+        let layer = ConvLayer {
+            spec,
+            kernels: vec![1.0, -1.0],
+            biases: vec![0.5],
+        };
+        let (weights, biases) = layer.unroll();
+        let input = [1.0, 3.0, 2.0, 5.0];
+        let output: Vec<f64> = (0..spec.output_len())
+            .map(|to| {
+                biases[to]
+                    + (0..4)
+                        .map(|from| input[from] * weights[from][to])
+                        .sum::<f64>()
+            })
+            .collect();
+        assert_eq!(output, vec![-1.5, 1.5, -2.5]);
+    }
+
+    #[test]
+    fn test_absorb_averages_tied_updates() {
+        let spec = ConvSpec::conv1d(1, 3, 1, 2, 1, 0);
+        let mut layer = ConvLayer {
+            spec,
+            kernels: vec![1.0, 2.0],
+            biases: vec![0.0],
+        };
+        let (mut weights, mut biases) = layer.unroll();
+        // Kernel tap 0 connects (0 -> 0) and (1 -> 1)
+        weights[0][0] += 0.4;
+        weights[1][1] += 0.2;
+        // Unconnected entries are discarded
+        weights[2][0] += 9.0;
+        biases[0] += 0.2;
+
+        layer.absorb(&weights, &biases);
+        assert!((layer.kernels[0] - 1.3).abs() < 1e-12);
+        assert_eq!(layer.kernels[1], 2.0);
+        assert!((layer.biases[0] - 0.1).abs() < 1e-12);
+        assert_eq!(layer.unroll().0[2][0], 0.0);
+    }
+
+    #[test]
+    fn test_pooling_forward_and_backward() {
+        // 1 channel 2x4 map, 2x2 windows with stride 2
+        let inputs = [1.0, 5.0, 2.0, 0.0, 3.0, 4.0, 8.0, 6.0];
+        let max = PoolSpec::pool2d(PoolKind::Max, 1, (2, 4), (2, 2), 2);
+        assert!(max.validate(8, 2).is_ok());
+        assert_eq!(max.forward(&inputs), vec![5.0, 8.0]);
+        assert_eq!(
+            max.backward(&inputs, &[1.0, 2.0]),
+            vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 2.0, 0.0]
+        );
+
+        let avg = PoolSpec {
+            kind: PoolKind::Avg,
+            ..max
+        };
+        assert_eq!(avg.forward(&inputs), vec![3.25, 4.0]);
+        assert_eq!(
+            avg.backward(&inputs, &[4.0, 0.0])[..4],
+            [1.0, 1.0, 0.0, 0.0]
+        );
+
+        let signal = PoolSpec::pool1d(PoolKind::Max, 2, 4, 2, 1);
+        assert_eq!(signal.output_len(), 6);
+    }
+}
@@ -0,0 +1,157 @@
+use serde::{Deserialize, Serialize};
+use std::ops::{Add, AddAssign, Mul};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Arithmetic work of one or more network operations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpCost {
+    /// Multiply-accumulate operations (weighted sums and weight updates)
+    pub macs: u64,
+    /// Neuron outputs computed
+    pub activations: u64,
+    /// Estimated floating point operations: two per MAC plus bias, normalization
+    /// and activation function work
+    pub flops: u64,
+}
+
+impl Add for OpCost {
+    type Output = OpCost;
+
+    fn add(self, other: OpCost) -> OpCost {
+        OpCost {
+            macs: self.macs + other.macs,
+            activations: self.activations + other.activations,
+            flops: self.flops + other.flops,
+        }
+    }
+}
+
+impl AddAssign for OpCost {
+    fn add_assign(&mut self, other: OpCost) {
+        *self = *self + other;
+    }
+}
+
+impl Mul<u64> for OpCost {
+    type Output = OpCost;
+
+    fn mul(self, times: u64) -> OpCost {
+        OpCost {
+            macs: self.macs * times,
+            activations: self.activations * times,
+            flops: self.flops * times,
+        }
+    }
+}
+
+/// Accumulated cost of one kind of call
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallCost {
+    pub calls: u64,
+    pub total: OpCost,
+}
+
+impl CallCost {
+    /// Average cost of a single call (zero before the first call)
+    pub fn per_call(&self) -> OpCost {
+        match self.calls {
+            0 => OpCost::default(),
+            calls => OpCost {
+                macs: self.total.macs / calls,
+                activations: self.total.activations / calls,
+                flops: self.total.flops / calls,
+            },
+        }
+    }
+}
+
+/// Work done by a network since cost accounting was enabled or last reset
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CostReport {
+    /// Inference calls, including the learning done by online forward passes
+    pub forward: CallCost,
+    /// Training calls, including the forward passes they run
+    pub train: CallCost,
+}
+
+impl CostReport {
+    pub fn total(&self) -> OpCost {
+        self.forward.total + self.train.total
+    }
+}
+
+/// Thread-safe counters behind `CostReport`, so `&self` inference can be counted
+#[derive(Debug, Default)]
+pub(crate) struct CostMeter {
+    counters: [AtomicU64; 8], // forward then train: calls, macs, activations, flops
+}
+
+impl Clone for CostMeter {
+    fn clone(&self) -> Self {
+        Self {
+            counters: std::array::from_fn(|i| {
+                AtomicU64::new(self.counters[i].load(Ordering::Relaxed))
+            }),
+        }
+    }
+}
+
+impl CostMeter {
+    fn add(&self, base: usize, calls: u64, cost: OpCost) {
+        let values = [calls, cost.macs, cost.activations, cost.flops];
+        for (counter, value) in self.counters[base..base + 4].iter().zip(values) {
+            counter.fetch_add(value, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_forward(&self, calls: u64, cost: OpCost) {
+        self.add(0, calls, cost);
+    }
+
+    pub(crate) fn record_train(&self, calls: u64, cost: OpCost) {
+        self.add(4, calls, cost);
+    }
+
+    pub(crate) fn report(&self) -> CostReport {
+        let read = |base: usize| {
+            let value = |i: usize| self.counters[base + i].load(Ordering::Relaxed);
+            CallCost {
+                calls: value(0),
+                total: OpCost {
+                    macs: value(1),
+                    activations: value(2),
+                    flops: value(3),
+                },
+            }
+        };
+        CostReport {
+            forward: read(0),
+            train: read(4),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_meter_accumulates_per_call_kind() {
+        let meter = CostMeter::default();
+        let pass = OpCost {
+            macs: 10,
+            activations: 3,
+            flops: 25,
+        };
+        meter.record_forward(1, pass);
+        meter.record_forward(1, pass);
+        meter.record_train(1, pass * 3);
+
+        let report = meter.clone().report();
+        assert_eq!(report.forward.calls, 2);
+        assert_eq!(report.forward.per_call(), pass);
+        assert_eq!(report.train.total, pass * 3);
+        assert_eq!(report.total(), pass * 5);
+        assert_eq!(CallCost::default().per_call(), OpCost::default());
+    }
+}
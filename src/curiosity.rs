@@ -0,0 +1,167 @@
+use crate::input_adapter::InputAdapter;
+use crate::neural_network::NeuralNetwork;
+use std::sync::{Arc, Mutex};
+
+/// Curiosity module shared between the `InputNode` and `OutputNode` of a closed loop
+pub type SharedCuriosity = Arc<Mutex<Curiosity>>;
+
+/// Intrinsic motivation from the prediction errors of a learned forward model
+///
+/// The forward model maps the last observation followed by the action taken
+/// after it to the next observation. Each new observation is scored by how
+/// badly the model predicted it and then used to train the model, so
+/// familiar transitions grow boring while unexplored ones stay surprising.
+/// `novelty` divides the error by its running average, making the score
+/// independent of the scale of the observations: above 1 is more
+/// surprising than usual.
+#[derive(Debug, Clone)]
+pub struct Curiosity {
+    model: NeuralNetwork,
+    observation_size: usize,
+    /// Weight of the newest error in the running average
+    smoothing: f64,
+    model_input: Option<Vec<f64>>, // last observation followed by the last action
+    error: f64,
+    average_error: Option<f64>,
+}
+
+impl Curiosity {
+    /// Wrap a forward model with `observation_size` outputs
+    ///
+    /// Inputs beyond the first `observation_size` receive the action. The
+    /// model learns with `NeuralNetwork::train`, so enable backpropagation
+    /// on it for the predictions to improve.
+    pub fn new(model: NeuralNetwork, observation_size: usize) -> Result<Self, String> {
+        let layers = model.get_layers();
+        if layers[layers.len() - 1] != observation_size {
+            return Err(format!(
+                "Forward model predicts {} values, observations have {}",
+                layers[layers.len() - 1],
+                observation_size
+            ));
+        }
+        if layers[0] < observation_size {
+            return Err(format!(
+                "Forward model has {} inputs, fewer than the {} observation values",
+                layers[0], observation_size
+            ));
+        }
+        Ok(Self {
+            model,
+            observation_size,
+            smoothing: 0.05,
+            model_input: None,
+            error: 0.0,
+            average_error: None,
+        })
+    }
+
+    /// Weight of the newest error in the running average (clamped to `(0, 1]`)
+    pub fn set_smoothing(&mut self, smoothing: f64) {
+        self.smoothing = smoothing.clamp(f64::EPSILON, 1.0);
+    }
+
+    pub fn model(&self) -> &NeuralNetwork {
+        &self.model
+    }
+
+    pub fn action_size(&self) -> usize {
+        self.model.get_layers()[0] - self.observation_size
+    }
+
+    /// Record the action taken after the latest observation
+    ///
+    /// Actions of another size are truncated or padded with zeros.
+    pub fn record_action(&mut self, action: &[f64]) {
+        let size = self.action_size();
+        if let Some(input) = &mut self.model_input {
+            input.truncate(self.observation_size);
+            input.extend(InputAdapter::Truncate.adapt(action, size).unwrap_or_default());
+        }
+    }
+
+    /// Record `output` as the action and append the current novelty to it
+    pub fn annotate(&mut self, output: &mut Vec<f64>) {
+        self.record_action(output);
+        output.push(self.novelty());
+    }
+
+    /// Score and learn from a new observation; returns its novelty
+    ///
+    /// The first observation only starts the loop and scores 0.
+    pub fn observe(&mut self, observation: &[f64]) -> f64 {
+        assert_eq!(
+            observation.len(),
+            self.observation_size,
+            "Observation size mismatch"
+        );
+        if let Some(mut input) = self.model_input.take() {
+            input.resize(self.model.get_layers()[0], 0.0);
+            self.error = self.model.train(&input, observation);
+            let average = self.average_error.unwrap_or(self.error);
+            self.average_error = Some(average + self.smoothing * (self.error - average));
+        }
+        let mut input = observation.to_vec();
+        input.resize(self.model.get_layers()[0], 0.0);
+        self.model_input = Some(input);
+        self.novelty()
+    }
+
+    /// Forward model error on the latest observation
+    pub fn prediction_error(&self) -> f64 {
+        self.error
+    }
+
+    /// Latest prediction error relative to its running average
+    pub fn novelty(&self) -> f64 {
+        match self.average_error {
+            Some(average) if average > f64::EPSILON => self.error / average,
+            Some(_) if self.error > f64::EPSILON => 1.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Forget the previous observation, e.g. when the environment is reset
+    pub fn reset_episode(&mut self) {
+        self.model_input = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_familiar_transitions_become_boring() {
+        let mut model = NeuralNetwork::with_layers(&[2, 4, 1], 0.0);
+        model.set_backprop_enabled(true, 0.5);
+        let mut curiosity = Curiosity::new(model, 1).unwrap();
+        assert_eq!(curiosity.action_size(), 1);
+        assert_eq!(curiosity.observe(&[0.0]), 0.0);
+
+        // The action moves the observation: next = 0.2 + 0.6 * action
+        let mut errors = Vec::new();
+        for step in 0..2000 {
+            let action = (step % 2) as f64;
+            curiosity.record_action(&[action]);
+            curiosity.observe(&[0.2 + 0.6 * action]);
+            errors.push(curiosity.prediction_error());
+        }
+        let early: f64 = errors[..100].iter().sum();
+        let late: f64 = errors[errors.len() - 100..].iter().sum();
+        assert!(late < early / 5.0, "early {} late {}", early, late);
+
+        // An unseen transition stands out against the familiar ones
+        let mut output = vec![0.5];
+        curiosity.annotate(&mut output);
+        assert_eq!(output.len(), 2);
+        assert!(curiosity.observe(&[0.95]) > 2.0);
+
+        // A new episode starts without scoring its first observation
+        let error = curiosity.prediction_error();
+        curiosity.reset_episode();
+        curiosity.observe(&[0.2]);
+        assert_eq!(curiosity.prediction_error(), error);
+        assert!(Curiosity::new(NeuralNetwork::with_layers(&[2, 3], 0.0), 2).is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How a panel draws a network's outputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ChartType {
+    /// One bar per output with its value underneath
+    #[default]
+    Bars,
+    /// A line per output over the most recent samples
+    Line,
+    /// Numbers only
+    Values,
+}
+
+/// One network's visualization on a dashboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DashboardPanel {
+    pub network_id: String,
+    /// Output indices shown, in order; empty shows every output
+    #[serde(default)]
+    pub outputs: Vec<usize>,
+    #[serde(default)]
+    pub chart: ChartType,
+    /// Values marked on the chart; outputs above the highest one are highlighted
+    #[serde(default)]
+    pub thresholds: Vec<f64>,
+}
+
+/// A named visualization layout of the output monitor
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub name: String,
+    pub panels: Vec<DashboardPanel>,
+}
+
+impl Dashboard {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("A dashboard needs a name".to_string());
+        }
+        for panel in &self.panels {
+            if let Some(threshold) = panel.thresholds.iter().find(|t| !t.is_finite()) {
+                return Err(format!(
+                    "Threshold {} of panel {} is not a number",
+                    threshold, panel.network_id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Saved dashboards by name, kept in a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardStore {
+    dashboards: BTreeMap<String, Dashboard>,
+}
+
+impl DashboardStore {
+    /// Read a dashboard file; a missing file holds no dashboards
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid dashboard file {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    /// Names of the saved dashboards, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.dashboards.keys().cloned().collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Dashboard> {
+        self.dashboards.get(name)
+    }
+
+    /// Add a dashboard, replacing any saved under the same name
+    pub fn insert(&mut self, dashboard: Dashboard) -> Result<(), String> {
+        dashboard.validate()?;
+        self.dashboards.insert(dashboard.name.clone(), dashboard);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Dashboard> {
+        self.dashboards.remove(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboards_persist_by_name() {
+        let mut store = DashboardStore::default();
+        let dashboard = Dashboard {
+            name: "Motor outputs".to_string(),
+            panels: vec![DashboardPanel {
+                network_id: "main-network".to_string(),
+                outputs: vec![1, 0],
+                chart: ChartType::Line,
+                thresholds: vec![0.8],
+            }],
+        };
+        store.insert(dashboard.clone()).unwrap();
+        assert!(store
+            .insert(Dashboard {
+                name: " ".to_string(),
+                panels: Vec::new(),
+            })
+            .is_err());
+
+        let path =
+            std::env::temp_dir().join(format!("benny-dashboards-{}.json", std::process::id()));
+        assert_eq!(
+            DashboardStore::load(&path).unwrap(),
+            DashboardStore::default()
+        );
+        store.save(&path).unwrap();
+        let mut loaded = DashboardStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.names(), vec!["Motor outputs".to_string()]);
+        assert_eq!(loaded.get("Motor outputs"), Some(&dashboard));
+
+        // Panels written by hand only need a network
+        let minimal: DashboardPanel =
+            serde_json::from_str(r#"{"network_id":"main-network"}"#).unwrap();
+        assert_eq!(minimal.chart, ChartType::Bars);
+        assert!(minimal.outputs.is_empty());
+
+        assert_eq!(loaded.remove("Motor outputs"), Some(dashboard));
+        assert!(loaded.names().is_empty());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::cli::TrainingData;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f64::consts::PI;
+
+/// Every input pattern of `bits` bits with its parity (`bits = 2` is classic XOR)
+pub fn xor_table(bits: usize) -> TrainingData {
+    let mut data = empty();
+    for pattern in 0..1usize << bits {
+        let input: Vec<f64> = (0..bits).map(|bit| ((pattern >> bit) & 1) as f64).collect();
+        data.targets.push(vec![(pattern.count_ones() % 2) as f64]);
+        data.inputs.push(input);
+    }
+    data
+}
+
+/// Random `bits`-bit patterns labelled with their parity, with Gaussian noise on the inputs
+pub fn xor(bits: usize, samples: usize, noise: f64, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = empty();
+    for _ in 0..samples {
+        let pattern: Vec<bool> = (0..bits).map(|_| rng.gen()).collect();
+        let parity = pattern.iter().filter(|&&bit| bit).count() % 2;
+        data.inputs.push(
+            pattern
+                .iter()
+                .map(|&bit| bit as u8 as f64 + noise * gaussian(&mut rng))
+                .collect(),
+        );
+        data.targets.push(vec![parity as f64]);
+    }
+    data
+}
+
+/// Two interleaved spirals of `turns` revolutions, labelled 0 and 1
+///
+/// Points lie in [-1, 1]² before noise; classes alternate so any prefix is balanced.
+pub fn spirals(samples: usize, turns: f64, noise: f64, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = empty();
+    for i in 0..samples {
+        let class = i % 2;
+        let t = rng.gen_range(0.0..1.0f64).sqrt();
+        let angle = 2.0 * PI * turns * t + PI * class as f64;
+        data.inputs.push(vec![
+            t * angle.cos() + noise * gaussian(&mut rng),
+            t * angle.sin() + noise * gaussian(&mut rng),
+        ]);
+        data.targets.push(vec![class as f64]);
+    }
+    data
+}
+
+/// A unit circle (class 0) around a circle of radius `factor` (class 1)
+pub fn circles(samples: usize, factor: f64, noise: f64, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = empty();
+    for i in 0..samples {
+        let class = i % 2;
+        let radius = if class == 0 { 1.0 } else { factor };
+        let angle = rng.gen_range(0.0..2.0 * PI);
+        data.inputs.push(vec![
+            radius * angle.cos() + noise * gaussian(&mut rng),
+            radius * angle.sin() + noise * gaussian(&mut rng),
+        ]);
+        data.targets.push(vec![class as f64]);
+    }
+    data
+}
+
+/// Gaussian clusters around `centers` random points in [-1, 1]^`dims`, one-hot labelled
+pub fn blobs(samples: usize, centers: usize, dims: usize, spread: f64, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let means: Vec<Vec<f64>> = (0..centers)
+        .map(|_| (0..dims).map(|_| rng.gen_range(-1.0..1.0)).collect())
+        .collect();
+    let mut data = empty();
+    for i in 0..samples {
+        let class = i % centers.max(1);
+        data.inputs.push(
+            means[class]
+                .iter()
+                .map(|mean| mean + spread * gaussian(&mut rng))
+                .collect(),
+        );
+        let mut target = vec![0.0; centers];
+        target[class] = 1.0;
+        data.targets.push(target);
+    }
+    data
+}
+
+/// `x` in [0, 1] mapped to `periods` sine periods, scaled into [0, 1] to suit sigmoid outputs
+pub fn sine(samples: usize, periods: f64, noise: f64, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = empty();
+    for _ in 0..samples {
+        let x = rng.gen_range(0.0..1.0);
+        let y = 0.5 + 0.5 * (2.0 * PI * periods * x).sin();
+        data.inputs.push(vec![x]);
+        data.targets.push(vec![y + noise * gaussian(&mut rng)]);
+    }
+    data
+}
+
+/// A random bit stream, one bit per sample, labelled with the parity of the stream so far
+///
+/// Samples must be presented in order: the target depends on every earlier
+/// input, so the task needs memory (online learning, recurrence or a delay
+/// line) rather than a single feedforward pass.
+pub fn temporal_parity(samples: usize, seed: u64) -> TrainingData {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut data = empty();
+    let mut parity = false;
+    for _ in 0..samples {
+        let bit: bool = rng.gen();
+        parity ^= bit;
+        data.inputs.push(vec![bit as u8 as f64]);
+        data.targets.push(vec![parity as u8 as f64]);
+    }
+    data
+}
+
+fn empty() -> TrainingData {
+    TrainingData {
+        inputs: Vec::new(),
+        targets: Vec::new(),
+    }
+}
+
+/// Standard normal sample (Box-Muller)
+pub(crate) fn gaussian<R: Rng>(rng: &mut R) -> f64 {
+    let u1: f64 = rng.gen_range(f64::EPSILON..1.0);
+    let u2: f64 = rng.gen_range(0.0..1.0);
+    (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_tasks() {
+        let table = xor_table(3);
+        assert_eq!(table.inputs.len(), 8);
+        assert_eq!(table.inputs[7], vec![1.0, 1.0, 1.0]);
+        assert_eq!(table.targets[7], vec![1.0]);
+        assert_eq!(table.targets[3], vec![0.0]);
+
+        let noisy = xor(4, 50, 0.0, 1);
+        for (input, target) in noisy.inputs.iter().zip(&noisy.targets) {
+            assert_eq!(input.iter().sum::<f64>() as usize % 2, target[0] as usize);
+        }
+
+        let spiral = spirals(200, 1.5, 0.0, 7);
+        assert_eq!(spiral.inputs, spirals(200, 1.5, 0.0, 7).inputs);
+        assert_ne!(spiral.inputs, spirals(200, 1.5, 0.0, 8).inputs);
+        assert!(spiral.inputs.iter().flatten().all(|v| v.abs() <= 1.0));
+        assert_eq!(spiral.targets.iter().filter(|t| t[0] == 1.0).count(), 100);
+
+        let rings = circles(10, 0.5, 0.0, 3);
+        let radius = |p: &Vec<f64>| (p[0] * p[0] + p[1] * p[1]).sqrt();
+        assert!((radius(&rings.inputs[0]) - 1.0).abs() < 1e-9);
+        assert!((radius(&rings.inputs[1]) - 0.5).abs() < 1e-9);
+
+        let clusters = blobs(30, 3, 4, 0.1, 5);
+        assert_eq!(clusters.inputs[0].len(), 4);
+        assert_eq!(clusters.targets[2], vec![0.0, 0.0, 1.0]);
+
+        let wave = sine(100, 2.0, 0.0, 9);
+        assert!(wave.targets.iter().all(|t| (0.0..=1.0).contains(&t[0])));
+
+        let stream = temporal_parity(64, 11);
+        let mut parity = 0.0;
+        for (input, target) in stream.inputs.iter().zip(&stream.targets) {
+            parity = (parity + input[0]) % 2.0;
+            assert_eq!(target[0], parity);
+        }
+    }
+}
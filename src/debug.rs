@@ -0,0 +1,128 @@
+use crate::neural_network::NeuralNetwork;
+
+/// Outcome of a successful gradient check
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradCheck {
+    /// Number of parameters compared
+    pub parameters: usize,
+    /// Largest error between analytic and numerical gradients
+    pub max_error: f64,
+}
+
+/// Compare backpropagation gradients with central finite differences
+///
+/// The loss is the squared error `0.5 * sum((target - output)^2)` used by
+/// `train`. Every parameter trained by backpropagation is checked (weights,
+/// biases, normalization, skip and embedding parameters); the network itself
+/// is left untouched and dropout is disabled on the copy under test. Errors
+/// are relative to the larger gradient, or absolute when both are below 1.
+/// Returns the worst mismatch when it exceeds `tolerance`.
+pub fn grad_check(
+    network: &NeuralNetwork,
+    inputs: &[f64],
+    targets: &[f64],
+    tolerance: f64,
+) -> Result<GradCheck, String> {
+    const STEP: f64 = 1e-5;
+
+    let mut network = network.clone();
+    for layer in 0..network.num_layers() - 1 {
+        network.set_dropout(layer, 0.0);
+    }
+    network.set_backprop_enabled(true, 1.0);
+
+    let before: Vec<f64> = network
+        .backprop_parameters_mut()
+        .into_iter()
+        .map(|p| *p)
+        .collect();
+    let mut stepped = network.clone();
+    stepped.backprop_step(inputs, targets);
+    let analytic: Vec<f64> = stepped
+        .backprop_parameters_mut()
+        .into_iter()
+        .zip(&before)
+        .map(|(after, before)| before - *after)
+        .collect();
+
+    let loss = |network: &NeuralNetwork| -> f64 {
+        let activations = network.forward_all_layers(inputs);
+        let output = &activations[activations.len() - 1];
+        0.5 * output
+            .iter()
+            .zip(targets)
+            .map(|(o, t)| (t - o).powi(2))
+            .sum::<f64>()
+    };
+
+    let mut worst: Option<(usize, f64, f64, f64)> = None;
+    for (index, (&original, &analytic)) in before.iter().zip(&analytic).enumerate() {
+        *network.backprop_parameters_mut()[index] = original + STEP;
+        let plus = loss(&network);
+        *network.backprop_parameters_mut()[index] = original - STEP;
+        let minus = loss(&network);
+        *network.backprop_parameters_mut()[index] = original;
+
+        let numeric = (plus - minus) / (2.0 * STEP);
+        let error = (analytic - numeric).abs() / analytic.abs().max(numeric.abs()).max(1.0);
+        if worst.is_none_or(|(_, _, _, e)| error > e) {
+            worst = Some((index, analytic, numeric, error));
+        }
+    }
+
+    let max_error = worst.map_or(0.0, |(_, _, _, e)| e);
+    match worst {
+        Some((index, analytic, numeric, error)) if error > tolerance => Err(format!(
+            "Gradient mismatch at parameter {}: analytic {:.6e}, numerical {:.6e} (error {:.2e} > {:.2e})",
+            index, analytic, numeric, error, tolerance
+        )),
+        _ => Ok(GradCheck {
+            parameters: before.len(),
+            max_error,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::Activation;
+    use crate::convolution::{ConvSpec, PoolKind, PoolSpec};
+
+    #[test]
+    fn test_backprop_matches_finite_differences() {
+        let mut dense = NeuralNetwork::with_layers(&[3, 4, 2], 0.1);
+        dense.set_activation(0, Activation::Tanh);
+        dense.add_embedding(1, 4, 2).unwrap();
+        let check = grad_check(&dense, &[0.2, 3.0, 0.9], &[1.0, 0.0], 1e-6).unwrap();
+        assert_eq!(check.parameters, 4 * 4 + 4 + 4 * 2 + 2 + 4 * 2);
+
+        let mut mixed = NeuralNetwork::with_layers(&[8, 8, 4, 3, 1], 0.1);
+        mixed
+            .set_conv_layer(0, ConvSpec::conv1d(1, 8, 1, 3, 1, 1))
+            .unwrap();
+        mixed
+            .set_pool_layer(1, PoolSpec::pool1d(PoolKind::Avg, 1, 8, 2, 2))
+            .unwrap();
+        mixed.set_layer_norm(2, true);
+        mixed.add_skip_connection(0, 3).unwrap();
+        let inputs = [0.1, 0.5, 0.7, -0.3, 0.8, 0.2, -0.6, 0.4];
+        grad_check(&mixed, &inputs, &[0.3], 1e-6).unwrap();
+
+        // Adapted intrinsic gains scale the activation derivatives
+        let mut adaptive = NeuralNetwork::with_layers(&[3, 4, 2], 0.0);
+        adaptive.set_intrinsic_plasticity(0, 0.1, 0.05).unwrap();
+        adaptive.set_intrinsic_plasticity(1, 0.1, 0.05).unwrap();
+        for _ in 0..20 {
+            adaptive.train_unsupervised(&[0.4, 0.1, 0.8]);
+        }
+        grad_check(&adaptive, &[0.4, 0.1, 0.8], &[1.0, 0.0], 1e-6).unwrap();
+    }
+
+    #[test]
+    fn test_reports_mismatch_above_tolerance() {
+        let network = NeuralNetwork::with_layers(&[2, 2], 0.1);
+        let result = grad_check(&network, &[0.5, 0.5], &[1.0, 0.0], -1.0);
+        assert!(result.unwrap_err().contains("Gradient mismatch"));
+    }
+}
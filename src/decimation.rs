@@ -0,0 +1,245 @@
+use serde::{Deserialize, Serialize};
+
+/// How a fast output stream is thinned out before it reaches dashboards
+///
+/// Configured per `NeuralNetworkSource` and adjustable live from the output
+/// monitor; samples are dropped on the server so clients only see the rate
+/// they can draw.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Decimation {
+    /// Forward every sample
+    #[default]
+    Off,
+    /// Forward the first of every `n` samples
+    EveryNth { n: usize },
+    /// Forward the last of every `interval` samples with the per-output min and max over them
+    Envelope { interval: usize },
+    /// Exponentially smooth every sample, forwarding the smoothed values every `every` samples
+    Smoothing {
+        alpha: f64,
+        #[serde(default = "default_every")]
+        every: usize,
+    },
+}
+
+fn default_every() -> usize {
+    1
+}
+
+impl Decimation {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Decimation::Off => Ok(()),
+            Decimation::EveryNth { n: 0 } => {
+                Err("Decimation factor must be at least 1".to_string())
+            }
+            Decimation::Envelope { interval: 0 } => {
+                Err("Envelope interval must be at least 1".to_string())
+            }
+            Decimation::Smoothing { alpha, every } => {
+                if !(*alpha > 0.0 && *alpha <= 1.0) {
+                    Err(format!("Smoothing factor {} must be in (0, 1]", alpha))
+                } else if *every == 0 {
+                    Err("Smoothing interval must be at least 1".to_string())
+                } else {
+                    Ok(())
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for Decimation {
+    type Err = String;
+
+    /// Parse `off`, `every:N`, `envelope:N` or `smooth:ALPHA[:N]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        let mut parts = lower.split(':');
+        let count = |part: Option<&str>| -> Result<usize, String> {
+            let part = part.ok_or_else(|| format!("Missing sample count in '{}'", s))?;
+            part.parse()
+                .map_err(|_| format!("Invalid sample count '{}'", part))
+        };
+        let decimation = match parts.next() {
+            Some("off") => Decimation::Off,
+            Some("every") => Decimation::EveryNth {
+                n: count(parts.next())?,
+            },
+            Some("envelope") => Decimation::Envelope {
+                interval: count(parts.next())?,
+            },
+            Some("smooth") => {
+                let alpha = parts
+                    .next()
+                    .ok_or_else(|| format!("Missing smoothing factor in '{}'", s))?;
+                Decimation::Smoothing {
+                    alpha: alpha
+                        .parse()
+                        .map_err(|_| format!("Invalid smoothing factor '{}'", alpha))?,
+                    every: match parts.next() {
+                        Some(every) => count(Some(every))?,
+                        None => 1,
+                    },
+                }
+            }
+            _ => return Err(format!(
+                "Unknown decimation '{}' (expected off, every:N, envelope:N or smooth:ALPHA[:N])",
+                s
+            )),
+        };
+        if parts.next().is_some() {
+            return Err(format!("Unexpected trailing values in decimation '{}'", s));
+        }
+        decimation.validate()?;
+        Ok(decimation)
+    }
+}
+
+/// A sample let through by a `Decimator`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecimatedSample {
+    pub outputs: Vec<f64>,
+    /// Per-output (min, max) over the interval, for `Decimation::Envelope`
+    pub envelope: Option<(Vec<f64>, Vec<f64>)>,
+}
+
+/// Running state of one output stream under a `Decimation`
+#[derive(Debug, Clone)]
+pub struct Decimator {
+    decimation: Decimation,
+    count: usize,
+    min: Vec<f64>,
+    max: Vec<f64>,
+    smoothed: Vec<f64>,
+}
+
+impl Decimator {
+    pub fn new(decimation: Decimation) -> Self {
+        Self {
+            decimation,
+            count: 0,
+            min: Vec::new(),
+            max: Vec::new(),
+            smoothed: Vec::new(),
+        }
+    }
+
+    pub fn decimation(&self) -> &Decimation {
+        &self.decimation
+    }
+
+    /// Switch to `decimation`, starting a fresh interval when it differs from the current one
+    pub fn set_decimation(&mut self, decimation: &Decimation) {
+        if *decimation != self.decimation {
+            *self = Self::new(decimation.clone());
+        }
+    }
+
+    /// Feed one sample, returning what should be forwarded now
+    pub fn push(&mut self, outputs: &[f64]) -> Option<DecimatedSample> {
+        let sample = |outputs: Vec<f64>| DecimatedSample {
+            outputs,
+            envelope: None,
+        };
+        match self.decimation {
+            Decimation::Off => Some(sample(outputs.to_vec())),
+            Decimation::EveryNth { n } => {
+                let forward = self.count == 0;
+                self.count = (self.count + 1) % n.max(1);
+                forward.then(|| sample(outputs.to_vec()))
+            }
+            Decimation::Envelope { interval } => {
+                if self.count == 0 || self.min.len() != outputs.len() {
+                    self.count = 0;
+                    self.min = outputs.to_vec();
+                    self.max = outputs.to_vec();
+                }
+                for ((min, max), value) in self.min.iter_mut().zip(&mut self.max).zip(outputs) {
+                    *min = min.min(*value);
+                    *max = max.max(*value);
+                }
+                self.count += 1;
+                if self.count < interval.max(1) {
+                    return None;
+                }
+                self.count = 0;
+                Some(DecimatedSample {
+                    outputs: outputs.to_vec(),
+                    envelope: Some((self.min.clone(), self.max.clone())),
+                })
+            }
+            Decimation::Smoothing { alpha, every } => {
+                if self.smoothed.len() == outputs.len() {
+                    for (smoothed, value) in self.smoothed.iter_mut().zip(outputs) {
+                        *smoothed += alpha * (value - *smoothed);
+                    }
+                } else {
+                    self.smoothed = outputs.to_vec();
+                }
+                let forward = self.count == 0;
+                self.count = (self.count + 1) % every.max(1);
+                forward.then(|| sample(self.smoothed.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decimation_modes() {
+        let samples: Vec<Vec<f64>> = (0..6).map(|i| vec![i as f64, -(i as f64)]).collect();
+        let forwarded = |decimation: Decimation| {
+            let mut decimator = Decimator::new(decimation);
+            samples
+                .iter()
+                .filter_map(|sample| decimator.push(sample))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(forwarded(Decimation::Off).len(), 6);
+        let every = forwarded(Decimation::EveryNth { n: 4 });
+        assert_eq!(every.len(), 2);
+        assert_eq!(every[1].outputs, vec![4.0, -4.0]);
+
+        let envelope = forwarded(Decimation::Envelope { interval: 3 });
+        assert_eq!(envelope.len(), 2);
+        assert_eq!(envelope[1].outputs, vec![5.0, -5.0]);
+        assert_eq!(
+            envelope[1].envelope,
+            Some((vec![3.0, -5.0], vec![5.0, -3.0]))
+        );
+
+        let smoothed = forwarded(Decimation::Smoothing {
+            alpha: 0.5,
+            every: 2,
+        });
+        assert_eq!(smoothed.len(), 3);
+        assert_eq!(smoothed[1].outputs, vec![1.25, -1.25]);
+
+        assert_eq!("every:10".parse(), Ok(Decimation::EveryNth { n: 10 }));
+        assert_eq!(
+            "smooth:0.2".parse(),
+            Ok(Decimation::Smoothing {
+                alpha: 0.2,
+                every: 1
+            })
+        );
+        assert!("every:0".parse::<Decimation>().is_err());
+        assert!("smooth:2".parse::<Decimation>().is_err());
+        assert!("envelope:5:1".parse::<Decimation>().is_err());
+        let json: Decimation = serde_json::from_str(r#"{"type":"Smoothing","alpha":0.1}"#).unwrap();
+        assert_eq!(
+            json,
+            Decimation::Smoothing {
+                alpha: 0.1,
+                every: 1
+            }
+        );
+    }
+}
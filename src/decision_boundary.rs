@@ -0,0 +1,263 @@
+use crate::neural_network::NeuralNetwork;
+use crate::visualize::ImageFormat;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+const PLOT_SIZE: (u32, u32) = (600, 600);
+const LOW: (f64, f64, f64) = (33.0, 102.0, 172.0);
+const HIGH: (f64, f64, f64) = (178.0, 24.0, 43.0);
+
+/// The square of inputs a two-input network is evaluated on
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecisionGrid {
+    pub x_range: (f64, f64),
+    pub y_range: (f64, f64),
+    /// Cells along each side
+    pub resolution: usize,
+}
+
+impl DecisionGrid {
+    pub fn new(x_range: (f64, f64), y_range: (f64, f64), resolution: usize) -> Self {
+        Self {
+            x_range,
+            y_range,
+            resolution: resolution.max(2),
+        }
+    }
+
+    /// A grid covering `inputs` with a tenth of their extent to spare on every side
+    pub fn around(inputs: &[Vec<f64>], resolution: usize) -> Self {
+        let range = |axis: usize| {
+            let (low, high) = inputs
+                .iter()
+                .filter_map(|input| input.get(axis).copied())
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                    (lo.min(v), hi.max(v))
+                });
+            if !low.is_finite() || !high.is_finite() {
+                return (0.0, 1.0);
+            }
+            let margin = ((high - low) * 0.1).max(1e-3);
+            (low - margin, high + margin)
+        };
+        Self::new(range(0), range(1), resolution)
+    }
+
+    /// Input at the centre of cell `(column, row)`, row 0 at the bottom
+    pub fn input(&self, column: usize, row: usize) -> [f64; 2] {
+        let step = |(low, high): (f64, f64), index: usize| {
+            low + (high - low) * (index as f64 + 0.5) / self.resolution as f64
+        };
+        [step(self.x_range, column), step(self.y_range, row)]
+    }
+
+    /// Outputs of `network` for every cell, as `outputs[row][column]`
+    pub fn evaluate(&self, network: &NeuralNetwork) -> Result<Vec<Vec<Vec<f64>>>, String> {
+        if network.input_size() != 2 {
+            return Err(format!(
+                "Decision boundaries need a network with 2 inputs, this one takes {}",
+                network.input_size()
+            ));
+        }
+        Ok((0..self.resolution)
+            .map(|row| {
+                (0..self.resolution)
+                    .map(|column| network.predict_static(&self.input(column, row)))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+/// Colour of a cell: blue to red through white with a single output (0.5
+/// being the boundary), otherwise the palette colour of the winning output
+/// faded by its margin
+fn cell_color(outputs: &[f64]) -> RGBColor {
+    let blend = |(r, g, b): (f64, f64, f64), strength: f64| {
+        let strength = strength.clamp(0.0, 1.0);
+        let mix = |c: f64| (255.0 - (255.0 - c) * strength) as u8;
+        RGBColor(mix(r), mix(g), mix(b))
+    };
+    match outputs {
+        [] => WHITE,
+        [value] => {
+            let distance = (value - 0.5) * 2.0;
+            blend(if distance < 0.0 { LOW } else { HIGH }, distance.abs())
+        }
+        _ => {
+            let class = crate::projection::class_of(outputs);
+            let runner_up = outputs
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| i != class)
+                .map(|(_, &v)| v)
+                .fold(f64::NEG_INFINITY, f64::max);
+            let (r, g, b) = Palette99::pick(class).rgb();
+            blend(
+                (r as f64, g as f64, b as f64),
+                0.3 + (outputs[class] - runner_up).max(0.0),
+            )
+        }
+    }
+}
+
+/// Heatmap of `outputs` over `grid` with the labelled `samples` on top
+fn draw<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    grid: &DecisionGrid,
+    outputs: &[Vec<Vec<f64>>],
+    samples: &[(Vec<f64>, usize)],
+    labels: bool,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    area.fill(&WHITE)?;
+    let (x_low, x_high) = grid.x_range;
+    let (y_low, y_high) = grid.y_range;
+    let mut builder = ChartBuilder::on(area);
+    builder.margin(10);
+    if labels {
+        builder.x_label_area_size(30).y_label_area_size(40);
+    }
+    let mut chart = builder.build_cartesian_2d(x_low..x_high, y_low..y_high)?;
+    if labels {
+        chart.configure_mesh().disable_mesh().draw()?;
+    }
+
+    let width = (x_high - x_low) / grid.resolution as f64;
+    let height = (y_high - y_low) / grid.resolution as f64;
+    chart.draw_series(outputs.iter().enumerate().flat_map(|(row, cells)| {
+        cells.iter().enumerate().map(move |(column, cell)| {
+            let x = x_low + column as f64 * width;
+            let y = y_low + row as f64 * height;
+            Rectangle::new([(x, y), (x + width, y + height)], cell_color(cell).filled())
+        })
+    }))?;
+    chart.draw_series(samples.iter().filter(|(input, _)| input.len() >= 2).map(
+        |(input, class)| {
+            let color = Palette99::pick(*class).to_rgba();
+            EmptyElement::at((input[0], input[1]))
+                + Circle::new((0, 0), 4, color.filled())
+                + Circle::new((0, 0), 4, BLACK.stroke_width(1))
+        },
+    ))?;
+    area.present()
+}
+
+/// Render the decision surface of a two-input `network` to `path`, SVG or PNG following its extension
+///
+/// `samples` are drawn over it as `(input, class)` points, coloured like the
+/// classes of a multi-output network. Only SVG images carry axis labels.
+pub fn render<P: AsRef<Path>>(
+    network: &NeuralNetwork,
+    grid: &DecisionGrid,
+    samples: &[(Vec<f64>, usize)],
+    path: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let outputs = grid.evaluate(network)?;
+    match ImageFormat::from_path(path) {
+        Some(ImageFormat::Svg) => {
+            let area = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw(&area, grid, &outputs, samples, true)?;
+        }
+        Some(ImageFormat::Png) => {
+            let area = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw(&area, grid, &outputs, samples, false)?;
+        }
+        None => {
+            return Err(format!(
+                "Unknown image format for '{}' (expected .svg or .png)",
+                path.display()
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+/// Decision surfaces captured while a network trains, written as an animated GIF
+#[derive(Debug, Clone)]
+pub struct BoundaryAnimation {
+    grid: DecisionGrid,
+    /// Epoch and cell outputs of each frame
+    frames: Vec<(usize, Vec<Vec<Vec<f64>>>)>,
+}
+
+impl BoundaryAnimation {
+    pub fn new(grid: DecisionGrid) -> Self {
+        Self {
+            grid,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Capture the surface of `network` after `epoch`
+    pub fn record(&mut self, epoch: usize, network: &NeuralNetwork) -> Result<(), String> {
+        self.frames.push((epoch, self.grid.evaluate(network)?));
+        Ok(())
+    }
+
+    /// Epochs captured so far
+    pub fn epochs(&self) -> Vec<usize> {
+        self.frames.iter().map(|(epoch, _)| *epoch).collect()
+    }
+
+    /// Write every frame to `path`, showing each for `frame_delay_ms`
+    pub fn write_gif<P: AsRef<Path>>(
+        &self,
+        path: P,
+        samples: &[(Vec<f64>, usize)],
+        frame_delay_ms: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let area = BitMapBackend::gif(path, PLOT_SIZE, frame_delay_ms)?.into_drawing_area();
+        for (_, outputs) in &self.frames {
+            draw(&area, &self.grid, outputs, samples, false)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decision_surfaces() {
+        let inputs = vec![vec![0.0, 0.0], vec![1.0, 1.0]];
+        let grid = DecisionGrid::around(&inputs, 4);
+        assert!((grid.x_range.0 + 0.1).abs() < 1e-9);
+        assert!((grid.y_range.1 - 1.1).abs() < 1e-9);
+        let [x, y] = grid.input(0, 3);
+        assert!((x - 0.05).abs() < 1e-9 && (y - 0.95).abs() < 1e-9);
+
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let outputs = grid.evaluate(&network).unwrap();
+        assert_eq!(outputs.len(), 4);
+        assert_eq!(outputs[3][0], network.predict_static(&grid.input(0, 3)));
+        assert!(grid
+            .evaluate(&NeuralNetwork::with_layers(&[3, 1], 0.1))
+            .is_err());
+
+        assert_eq!(cell_color(&[0.5]), WHITE);
+        assert_eq!(cell_color(&[1.0]), RGBColor(178, 24, 43));
+
+        let samples = vec![(inputs[0].clone(), 0), (inputs[1].clone(), 1)];
+        let dir = std::env::temp_dir();
+        let svg = dir.join(format!("benny-boundary-{}.svg", std::process::id()));
+        render(&network, &grid, &samples, &svg).unwrap();
+        let image = std::fs::read_to_string(&svg).unwrap();
+        std::fs::remove_file(&svg).unwrap();
+        assert_eq!(image.matches("<rect").count(), 1 + 16);
+
+        let mut animation = BoundaryAnimation::new(grid);
+        animation.record(0, &network).unwrap();
+        network.train(&[1.0, 1.0], &[1.0]);
+        animation.record(1, &network).unwrap();
+        assert_eq!(animation.epochs(), vec![0, 1]);
+        let gif = dir.join(format!("benny-boundary-{}.gif", std::process::id()));
+        animation.write_gif(&gif, &samples, 200).unwrap();
+        let bytes = std::fs::read(&gif).unwrap();
+        std::fs::remove_file(&gif).unwrap();
+        assert_eq!(&bytes[..3], b"GIF");
+    }
+}
@@ -0,0 +1,311 @@
+use crate::distributed_network::{MessagePayload, MessageType, NetworkMessage};
+use crate::launcher::{ClusterConfig, NodeSpec};
+use crate::neural_network::NeuralNetwork;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Layer sizes and parameter count of a node's network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NodeShape {
+    pub layers: Vec<usize>,
+    pub input_size: usize,
+    pub output_size: usize,
+    pub parameters: usize,
+}
+
+impl NodeShape {
+    pub fn of(network: &NeuralNetwork) -> Self {
+        Self {
+            layers: network.get_layers().to_vec(),
+            input_size: network.input_size(),
+            output_size: network.get_layers().last().copied().unwrap_or(0),
+            parameters: network.num_parameters(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentKind {
+    Node,
+    InputServer,
+    OutputServer,
+}
+
+/// One process of the plan, with the values it takes and produces
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlannedComponent {
+    pub name: String,
+    pub kind: ComponentKind,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub ports: BTreeMap<String, u16>,
+    /// Layer sizes of a node's network (empty for the input and output servers)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<usize>,
+    /// Values it receives per forward pass (`None` for the input server, or an unloadable node)
+    pub input_size: Option<usize>,
+    /// Values it sends per forward pass (`None` for the output server, or an unloadable node)
+    pub output_size: Option<usize>,
+    pub parameters: usize,
+}
+
+/// Activations flowing from one component to another over NNP
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataFlowEdge {
+    pub from: String,
+    pub to: String,
+    /// NNP port of the receiver
+    pub port: u16,
+    pub values: Option<usize>,
+    /// Size of one forward message on the wire, header included
+    pub bytes_per_message: Option<usize>,
+    /// Bandwidth at the plan's rate of forward passes
+    pub bytes_per_second: Option<f64>,
+}
+
+/// Machine-readable plan of a cluster file, written by `benny plan`
+///
+/// Lists every process `benny up` would start with its ports, the shapes of
+/// the nodes' networks, the data flow between them with the bandwidth it
+/// takes at `rate` forward passes per second, and the shape mismatches that
+/// would break the pipeline.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeploymentPlan {
+    pub source: PathBuf,
+    /// Forward passes per second the bandwidth is estimated for
+    pub rate: f64,
+    /// Components in start order
+    pub components: Vec<PlannedComponent>,
+    pub edges: Vec<DataFlowEdge>,
+    pub total_parameters: usize,
+    pub total_bytes_per_second: f64,
+    pub problems: Vec<String>,
+}
+
+/// Bytes of an NNP forward message carrying `values` activations
+pub fn forward_message_size(values: usize) -> usize {
+    NetworkMessage {
+        msg_type: MessageType::ForwardData,
+        sequence: 0,
+        payload: MessagePayload::ForwardData {
+            layer_id: 0,
+            data: vec![0.0; values],
+            timestamp: Some(0),
+            channel: None,
+        },
+        flags: 0,
+    }
+    .to_bytes()
+    .len()
+}
+
+impl DeploymentPlan {
+    /// Plan `cluster` (read from `source`), with `shape_of` loading the network of each node
+    pub fn build<F>(
+        cluster: &ClusterConfig,
+        source: &Path,
+        bin_dir: &Path,
+        rate: f64,
+        mut shape_of: F,
+    ) -> Result<Self, String>
+    where
+        F: FnMut(&NodeSpec) -> Result<NodeShape, String>,
+    {
+        let mut problems = Vec::new();
+        let mut components = Vec::new();
+        for process in cluster.plan(bin_dir)? {
+            let mut component = PlannedComponent {
+                name: process.name.clone(),
+                kind: ComponentKind::Node,
+                program: process.program,
+                args: process.args,
+                ports: process
+                    .ports
+                    .iter()
+                    .map(|&(purpose, port)| (purpose.to_string(), port))
+                    .collect(),
+                layers: Vec::new(),
+                input_size: None,
+                output_size: None,
+                parameters: 0,
+            };
+            if let Some(node) = cluster.nodes.iter().find(|node| node.name == process.name) {
+                match shape_of(node) {
+                    Ok(shape) => {
+                        component.input_size = Some(shape.input_size);
+                        component.output_size = Some(shape.output_size);
+                        component.parameters = shape.parameters;
+                        component.layers = shape.layers;
+                    }
+                    Err(e) => problems.push(format!("{}: {}", node.name, e)),
+                }
+            } else if let Some(input) = &cluster.input_server {
+                if input.name == process.name {
+                    component.kind = ComponentKind::InputServer;
+                    component.output_size = Some(input.input_size);
+                }
+            }
+            if let Some(output) = &cluster.output_server {
+                if output.name == process.name {
+                    component.kind = ComponentKind::OutputServer;
+                    component.input_size = Some(output.output_size);
+                }
+            }
+            components.push(component);
+        }
+
+        let links = cluster
+            .nodes
+            .iter()
+            .flat_map(|node| node.outputs.iter().map(move |to| (&node.name, to)))
+            .chain(
+                cluster
+                    .input_server
+                    .iter()
+                    .map(|input| (&input.name, &input.target)),
+            );
+        let mut edges = Vec::new();
+        for (from, to) in links {
+            let find = |name: &str| components.iter().find(|component| component.name == name);
+            // `plan` has already rejected links to unknown components
+            let (Some(sender), Some(receiver)) = (find(from), find(to)) else {
+                continue;
+            };
+            if let (Some(values), Some(expected)) = (sender.output_size, receiver.input_size) {
+                if values != expected {
+                    problems.push(format!(
+                        "{} sends {} values to {}, which takes {}",
+                        from, values, to, expected
+                    ));
+                }
+            }
+            let bytes_per_message = sender.output_size.map(forward_message_size);
+            edges.push(DataFlowEdge {
+                from: from.clone(),
+                to: to.clone(),
+                port: receiver.ports.get("nnp").copied().unwrap_or_default(),
+                values: sender.output_size,
+                bytes_per_message,
+                bytes_per_second: bytes_per_message.map(|bytes| bytes as f64 * rate),
+            });
+        }
+
+        Ok(Self {
+            source: source.to_path_buf(),
+            rate,
+            total_parameters: components.iter().map(|c| c.parameters).sum(),
+            total_bytes_per_second: edges.iter().filter_map(|e| e.bytes_per_second).sum(),
+            components,
+            edges,
+            problems,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_shapes_and_bandwidth() {
+        let cluster: ClusterConfig = toml::from_str(
+            r#"
+            base_port = 9000
+
+            [[node]]
+            name = "hidden"
+            config = "hidden.toml"
+            outputs = ["head"]
+
+            [[node]]
+            name = "head"
+            config = "head.toml"
+            outputs = ["output"]
+
+            [input_server]
+            target = "hidden"
+            input_size = 4
+
+            [output_server]
+            output_size = 2
+            "#,
+        )
+        .unwrap();
+        let shape = |layers: Vec<usize>| NodeShape {
+            input_size: layers[0],
+            output_size: *layers.last().unwrap(),
+            parameters: layers.windows(2).map(|w| w[0] * w[1] + w[1]).sum(),
+            layers,
+        };
+        let plan = DeploymentPlan::build(
+            &cluster,
+            Path::new("cluster.toml"),
+            Path::new("bin"),
+            10.0,
+            |node| match node.name.as_str() {
+                "hidden" => Ok(shape(vec![4, 3])),
+                _ => Ok(shape(vec![2, 2])),
+            },
+        )
+        .unwrap();
+
+        let kinds: Vec<(&str, ComponentKind)> = plan
+            .components
+            .iter()
+            .map(|c| (c.name.as_str(), c.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("output", ComponentKind::OutputServer),
+                ("head", ComponentKind::Node),
+                ("hidden", ComponentKind::Node),
+                ("input", ComponentKind::InputServer),
+            ]
+        );
+        assert_eq!(plan.components[2].ports["nnp"], 9000);
+        assert_eq!(plan.total_parameters, 15 + 6);
+
+        // hidden -> head, head -> output, input -> hidden
+        assert_eq!(plan.edges.len(), 3);
+        let edge = &plan.edges[0];
+        assert_eq!((edge.from.as_str(), edge.to.as_str()), ("hidden", "head"));
+        assert_eq!(edge.values, Some(3));
+        assert_eq!(edge.port, plan.components[1].ports["nnp"]);
+        assert!(forward_message_size(3) > forward_message_size(2));
+        assert_eq!(edge.bytes_per_message, Some(forward_message_size(3)));
+        assert_eq!(
+            edge.bytes_per_second,
+            Some(forward_message_size(3) as f64 * 10.0)
+        );
+        assert_eq!(
+            plan.problems,
+            vec!["hidden sends 3 values to head, which takes 2"]
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&plan.to_json().unwrap()).unwrap();
+        assert_eq!(json["components"][3]["kind"], "input_server");
+        assert_eq!(json["edges"][2]["to"], "hidden");
+
+        // A node that cannot be loaded is reported and its links left unchecked
+        let plan = DeploymentPlan::build(
+            &cluster,
+            Path::new("cluster.toml"),
+            Path::new("bin"),
+            10.0,
+            |node| match node.name.as_str() {
+                "hidden" => Err("hidden.toml: not found".to_string()),
+                _ => Ok(shape(vec![2, 2])),
+            },
+        )
+        .unwrap();
+        assert_eq!(plan.problems, vec!["hidden: hidden.toml: not found"]);
+        assert_eq!(plan.edges[0].values, None);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::scaling::ValueRange;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// What a derived channel computes from a network's raw outputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum DerivedSignal {
+    /// Mean of the last `window` samples of `output`
+    MovingAverage { output: usize, window: usize },
+    /// Change of `output` per second between consecutive samples
+    Derivative { output: usize },
+    /// Output `a` minus output `b`
+    Difference { a: usize, b: usize },
+}
+
+/// Channel the output server computes from every raw sample of a network
+///
+/// Derived values are streamed to the monitor next to the raw outputs, so
+/// every dashboard shows the same smoothing without computing it itself.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DerivedChannel {
+    pub name: String,
+    #[serde(flatten)]
+    pub signal: DerivedSignal,
+    /// Expected range shown by the monitor; moving averages default to their output's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<ValueRange>,
+}
+
+/// Check the derived channels of a network with `outputs` raw outputs
+pub fn validate(channels: &[DerivedChannel], outputs: usize) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for channel in channels {
+        if channel.name.is_empty() {
+            return Err("Derived channel without a name".to_string());
+        }
+        if !names.insert(channel.name.as_str()) {
+            return Err(format!("Derived channel {} is defined twice", channel.name));
+        }
+        let sources = match channel.signal {
+            DerivedSignal::MovingAverage { output, window } => {
+                if window == 0 {
+                    return Err(format!(
+                        "Moving average {} needs a window of at least 1 sample",
+                        channel.name
+                    ));
+                }
+                vec![output]
+            }
+            DerivedSignal::Derivative { output } => vec![output],
+            DerivedSignal::Difference { a, b } => vec![a, b],
+        };
+        if let Some(output) = sources.into_iter().find(|&output| output >= outputs) {
+            return Err(format!(
+                "Derived channel {} reads output {} of a network with {} outputs",
+                channel.name, output, outputs
+            ));
+        }
+        if let Some(range) = channel.range {
+            if !(range.min.is_finite() && range.max.is_finite() && range.min < range.max) {
+                return Err(format!(
+                    "Invalid range of derived channel {}: [{}, {}]",
+                    channel.name, range.min, range.max
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Running state of one derived channel
+#[derive(Debug, Clone)]
+enum ChannelState {
+    MovingAverage(VecDeque<f64>),
+    /// Timestamp and value of the previous sample, and the last rate computed
+    Derivative(Option<(u64, f64)>, f64),
+    Difference,
+}
+
+/// Derived channels of one output stream, fed every raw sample
+#[derive(Debug, Clone, Default)]
+pub struct DerivedSignals {
+    channels: Vec<DerivedChannel>,
+    states: Vec<ChannelState>,
+}
+
+impl DerivedSignals {
+    pub fn new(channels: &[DerivedChannel]) -> Self {
+        let states = channels
+            .iter()
+            .map(|channel| match channel.signal {
+                DerivedSignal::MovingAverage { .. } => ChannelState::MovingAverage(VecDeque::new()),
+                DerivedSignal::Derivative { .. } => ChannelState::Derivative(None, 0.0),
+                DerivedSignal::Difference { .. } => ChannelState::Difference,
+            })
+            .collect();
+        Self {
+            channels: channels.to_vec(),
+            states,
+        }
+    }
+
+    /// Switch to `channels`, starting afresh when they differ from the current ones
+    pub fn set_channels(&mut self, channels: &[DerivedChannel]) {
+        if channels != self.channels.as_slice() {
+            *self = Self::new(channels);
+        }
+    }
+
+    pub fn channels(&self) -> &[DerivedChannel] {
+        &self.channels
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Feed one raw sample taken at `timestamp` (ms), returning every channel's value
+    ///
+    /// Outputs a sample lacks read as 0. A derivative is 0 until a second
+    /// sample arrives and keeps its last value while the timestamp stands still.
+    pub fn push(&mut self, outputs: &[f64], timestamp: u64) -> Vec<f64> {
+        let value = |output: usize| outputs.get(output).copied().unwrap_or(0.0);
+        self.channels
+            .iter()
+            .zip(&mut self.states)
+            .map(|(channel, state)| match (&channel.signal, state) {
+                (
+                    DerivedSignal::MovingAverage { output, window },
+                    ChannelState::MovingAverage(samples),
+                ) => {
+                    if samples.len() == *window {
+                        samples.pop_front();
+                    }
+                    samples.push_back(value(*output));
+                    samples.iter().sum::<f64>() / samples.len() as f64
+                }
+                (
+                    DerivedSignal::Derivative { output },
+                    ChannelState::Derivative(previous, rate),
+                ) => {
+                    let current = value(*output);
+                    if let Some((since, last)) = *previous {
+                        if timestamp > since {
+                            *rate = (current - last) / ((timestamp - since) as f64 / 1000.0);
+                        }
+                    }
+                    *previous = Some((timestamp, current));
+                    *rate
+                }
+                (DerivedSignal::Difference { a, b }, _) => value(*a) - value(*b),
+                // States are built from the channels, so the kinds always match
+                _ => 0.0,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derived_channels() {
+        let channels: Vec<DerivedChannel> = serde_json::from_str(
+            r#"[
+                {"name": "avg", "type": "MovingAverage", "output": 0, "window": 2},
+                {"name": "rate", "type": "Derivative", "output": 1},
+                {"name": "gap", "type": "Difference", "a": 0, "b": 1, "range": {"min": -1, "max": 1}}
+            ]"#,
+        )
+        .unwrap();
+        validate(&channels, 2).unwrap();
+        assert!(validate(&channels, 1)
+            .unwrap_err()
+            .contains("reads output 1"));
+        let twice = [channels[0].clone(), channels[0].clone()];
+        assert!(validate(&twice, 2).unwrap_err().contains("defined twice"));
+
+        let mut signals = DerivedSignals::new(&channels);
+        assert_eq!(signals.push(&[1.0, 0.0], 1000), vec![1.0, 0.0, 1.0]);
+        assert_eq!(signals.push(&[3.0, 0.5], 1500), vec![2.0, 1.0, 2.5]);
+        assert_eq!(signals.push(&[5.0, 1.5], 2500), vec![4.0, 1.0, 3.5]);
+        // The same timestamp again keeps the last rate
+        assert_eq!(signals.push(&[5.0, 0.9], 2500)[1], 1.0);
+
+        // Unchanged channels keep their history; new ones start afresh
+        signals.set_channels(&channels);
+        assert_eq!(signals.push(&[7.0, 0.0], 3500)[0], 6.0);
+        signals.set_channels(&channels[..1]);
+        assert_eq!(signals.push(&[7.0, 0.0], 4500), vec![7.0]);
+    }
+}
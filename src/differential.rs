@@ -0,0 +1,397 @@
+use crate::benchmark::seed_parameters;
+use crate::neural_network::NeuralNetwork;
+use crate::training_plots::{self, Series, TRAIN_COLOR, VAL_COLOR};
+use crate::visualize::ImageFormat;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Serialize;
+use std::fmt::{self, Write};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Rows of the text report before epochs are skipped
+const MAX_TEXT_ROWS: usize = 20;
+
+/// Precision the parameters of one side are kept in between training steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum StoragePrecision {
+    F64,
+    /// Parameters rounded to `f32` after every step, as an `f32` backend would store them
+    F32,
+}
+
+impl fmt::Display for StoragePrecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StoragePrecision::F64 => "f64",
+            StoragePrecision::F32 => "f32",
+        })
+    }
+}
+
+impl StoragePrecision {
+    /// Round the weights and biases of `network` to this precision
+    pub fn apply(self, network: &mut NeuralNetwork) {
+        if self == StoragePrecision::F64 {
+            return;
+        }
+        for layer in 0..network.num_layers() - 1 {
+            let round = |values: &[f64]| -> Vec<f64> {
+                values.iter().map(|&value| value as f32 as f64).collect()
+            };
+            let weights = round(&network.get_layer_weights(layer));
+            let biases = round(network.get_layer_biases(layer));
+            let _ = network.set_layer_parameters(layer, &weights, &biases);
+        }
+    }
+}
+
+/// One of the two configurations of a differential test
+#[derive(Debug, Clone)]
+pub struct DifferentialSide {
+    pub label: String,
+    pub network: NeuralNetwork,
+    pub precision: StoragePrecision,
+}
+
+/// How far apart the two networks are after an epoch (epoch 0 is before training)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DivergencePoint {
+    pub epoch: usize,
+    /// Root mean square difference of their outputs over the dataset
+    pub output_rms: f64,
+    /// Largest absolute difference of one output
+    pub output_max: f64,
+    /// L2 norm of the difference of their parameters (`None` when their shapes differ)
+    pub weight_distance: Option<f64>,
+    /// Mean training error of the epoch on each side (`None` before training)
+    pub error_a: Option<f64>,
+    pub error_b: Option<f64>,
+}
+
+/// Divergence of two configurations trained on the same seeded data, epoch by epoch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DifferentialReport {
+    pub label_a: String,
+    pub label_b: String,
+    pub seed: u64,
+    /// Largest output difference still counted as the same learning dynamics
+    pub tolerance: f64,
+    pub points: Vec<DivergencePoint>,
+    /// First epoch whose largest output difference exceeds the tolerance
+    pub first_divergent_epoch: Option<usize>,
+}
+
+/// L2 norm of the difference of the parameters of `a` and `b`, when they have the same shape
+pub fn parameter_distance(a: &NeuralNetwork, b: &NeuralNetwork) -> Option<f64> {
+    if a.get_layers() != b.get_layers() {
+        return None;
+    }
+    let squared: f64 = (0..a.num_layers() - 1)
+        .map(|layer| {
+            let weights = a
+                .get_layer_weights(layer)
+                .into_iter()
+                .zip(b.get_layer_weights(layer))
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f64>();
+            let biases = a
+                .get_layer_biases(layer)
+                .iter()
+                .zip(b.get_layer_biases(layer))
+                .map(|(x, y)| (x - y).powi(2))
+                .sum::<f64>();
+            weights + biases
+        })
+        .sum();
+    Some(squared.sqrt())
+}
+
+fn measure(
+    epoch: usize,
+    a: &NeuralNetwork,
+    b: &NeuralNetwork,
+    inputs: &[Vec<f64>],
+    errors: Option<(f64, f64)>,
+) -> DivergencePoint {
+    let (outputs_a, outputs_b) = (a.forward_batch(inputs), b.forward_batch(inputs));
+    let (mut squared, mut max, mut values) = (0.0, 0.0_f64, 0);
+    for (x, y) in outputs_a.iter().flatten().zip(outputs_b.iter().flatten()) {
+        let difference = (x - y).abs();
+        squared += difference * difference;
+        // NaN on one side only counts as infinitely far apart
+        max = max.max(if difference.is_nan() && x.is_nan() != y.is_nan() {
+            f64::INFINITY
+        } else {
+            difference
+        });
+        values += 1;
+    }
+    DivergencePoint {
+        epoch,
+        output_rms: (squared / values.max(1) as f64).sqrt(),
+        output_max: max,
+        weight_distance: parameter_distance(a, b),
+        error_a: errors.map(|errors| errors.0),
+        error_b: errors.map(|errors| errors.1),
+    }
+}
+
+/// Train `a` and `b` side by side and record how far they drift apart
+///
+/// Both networks start from the same weights drawn from `seed` (when their
+/// shapes match) and see the samples in the same seeded order every epoch,
+/// so with identical configurations and precisions they never diverge; any
+/// divergence comes from the difference under test.
+pub fn run_differential(
+    mut a: DifferentialSide,
+    mut b: DifferentialSide,
+    inputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+    epochs: usize,
+    seed: u64,
+    tolerance: f64,
+) -> Result<DifferentialReport, String> {
+    if inputs.is_empty() || inputs.len() != targets.len() {
+        return Err(format!(
+            "Differential testing needs matching samples, got {} inputs and {} targets",
+            inputs.len(),
+            targets.len()
+        ));
+    }
+    for side in [&mut a, &mut b] {
+        seed_parameters(&mut side.network, &mut StdRng::seed_from_u64(seed));
+        side.precision.apply(&mut side.network);
+    }
+
+    let mut order: Vec<usize> = (0..inputs.len()).collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut points = vec![measure(0, &a.network, &b.network, inputs, None)];
+    for epoch in 1..=epochs {
+        order.shuffle(&mut rng);
+        let mut errors = (0.0, 0.0);
+        for &index in &order {
+            errors.0 += a.network.train(&inputs[index], &targets[index]);
+            a.precision.apply(&mut a.network);
+            errors.1 += b.network.train(&inputs[index], &targets[index]);
+            b.precision.apply(&mut b.network);
+        }
+        let samples = order.len() as f64;
+        points.push(measure(
+            epoch,
+            &a.network,
+            &b.network,
+            inputs,
+            Some((errors.0 / samples, errors.1 / samples)),
+        ));
+    }
+
+    Ok(DifferentialReport {
+        label_a: a.label,
+        label_b: b.label,
+        seed,
+        tolerance,
+        first_divergent_epoch: points
+            .iter()
+            .find(|point| point.output_max > tolerance)
+            .map(|point| point.epoch),
+        points,
+    })
+}
+
+impl DifferentialReport {
+    pub fn is_within_tolerance(&self) -> bool {
+        self.first_divergent_epoch.is_none()
+    }
+
+    /// `epoch,output_rms,output_max,weight_distance,error_a,error_b` rows
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("epoch,output_rms,output_max,weight_distance,error_a,error_b\n");
+        for point in &self.points {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{}",
+                point.epoch,
+                point.output_rms,
+                point.output_max,
+                point
+                    .weight_distance
+                    .map_or(String::new(), |d| d.to_string()),
+                point.error_a.map_or(String::new(), |e| e.to_string()),
+                point.error_b.map_or(String::new(), |e| e.to_string())
+            );
+        }
+        out
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "🔬 Differential test: A = {}, B = {} (seed {})\n",
+            self.label_a, self.label_b, self.seed
+        );
+        out.push_str("   epoch  output RMS    output max    weight dist.  error A     error B\n");
+        let step = self.points.len().div_ceil(MAX_TEXT_ROWS).max(1);
+        let last = self.points.len().saturating_sub(1);
+        for (index, point) in self.points.iter().enumerate() {
+            if index % step != 0 && index != last {
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "   {:>5}  {:>12.6e}  {:>12.6e}  {:>12}  {:>10}  {:>10}",
+                point.epoch,
+                point.output_rms,
+                point.output_max,
+                point
+                    .weight_distance
+                    .map_or("-".to_string(), |d| format!("{:.6e}", d)),
+                point
+                    .error_a
+                    .map_or("-".to_string(), |e| format!("{:.6}", e)),
+                point
+                    .error_b
+                    .map_or("-".to_string(), |e| format!("{:.6}", e))
+            );
+        }
+        match self.first_divergent_epoch {
+            Some(epoch) => {
+                let _ = writeln!(
+                    out,
+                    "❌ Outputs differ by more than {} from epoch {}",
+                    self.tolerance, epoch
+                );
+            }
+            None => {
+                let _ = writeln!(out, "✅ Outputs stay within {}", self.tolerance);
+            }
+        }
+        out
+    }
+
+    /// Write `output_divergence`, `weight_distance` and `error` charts into `dir`
+    pub fn write_plots(
+        &self,
+        dir: &Path,
+        format: ImageFormat,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        fs::create_dir_all(dir)?;
+        let series = |values: &dyn Fn(&DivergencePoint) -> Option<f64>| {
+            training_plots::points(self.points.iter().map(values))
+        };
+        let charts: [(&str, &str, Vec<Series>); 3] = [
+            (
+                "output_divergence",
+                "Output difference",
+                vec![
+                    ("RMS", TRAIN_COLOR, series(&|p| Some(p.output_rms))),
+                    ("max", VAL_COLOR, series(&|p| Some(p.output_max))),
+                ],
+            ),
+            (
+                "weight_distance",
+                "Parameter distance",
+                vec![("L2", TRAIN_COLOR, series(&|p| p.weight_distance))],
+            ),
+            (
+                "error",
+                "Training error",
+                vec![
+                    (&self.label_a, TRAIN_COLOR, series(&|p| p.error_a)),
+                    (&self.label_b, VAL_COLOR, series(&|p| p.error_b)),
+                ],
+            ),
+        ];
+        let mut written = Vec::new();
+        for (name, title, series) in &charts {
+            let path = dir.join(name).with_extension(match format {
+                ImageFormat::Svg => "svg",
+                ImageFormat::Png => "png",
+            });
+            training_plots::write_chart(&path, format, title, series)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(label: &str, precision: StoragePrecision) -> DifferentialSide {
+        DifferentialSide {
+            label: label.to_string(),
+            network: NeuralNetwork::with_layers(&[2, 4, 1], 0.1),
+            precision,
+        }
+    }
+
+    #[test]
+    fn test_differential_training() {
+        let inputs = vec![
+            vec![0.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+        ];
+        let targets = vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]];
+
+        // Identical sides never drift apart, whatever their random initialization
+        let same = run_differential(
+            side("a", StoragePrecision::F64),
+            side("b", StoragePrecision::F64),
+            &inputs,
+            &targets,
+            10,
+            7,
+            0.0,
+        )
+        .unwrap();
+        assert_eq!(same.points.len(), 11);
+        assert!(same.points.iter().all(|p| p.output_max == 0.0));
+        assert_eq!(same.points[10].weight_distance, Some(0.0));
+        assert!(same.is_within_tolerance());
+        assert!(same.to_text().contains("✅ Outputs stay within 0"));
+
+        // Rounding to f32 shows up right away, and stays small
+        let report = run_differential(
+            side("f64", StoragePrecision::F64),
+            side("f32", StoragePrecision::F32),
+            &inputs,
+            &targets,
+            10,
+            7,
+            1e-9,
+        )
+        .unwrap();
+        let last = report.points.last().unwrap();
+        assert!(last.output_rms > 0.0 && last.output_max < 1e-3);
+        assert_eq!(report.first_divergent_epoch, Some(0));
+        assert!(!report.is_within_tolerance());
+        assert_eq!(report.to_csv().lines().count(), 12);
+
+        // Different shapes have outputs to compare but no parameter distance
+        let mut wide = side("wide", StoragePrecision::F64);
+        wide.network = NeuralNetwork::with_layers(&[2, 8, 1], 0.1);
+        let report = run_differential(
+            side("a", StoragePrecision::F64),
+            wide,
+            &inputs,
+            &targets,
+            2,
+            7,
+            1.0,
+        )
+        .unwrap();
+        assert_eq!(report.points[0].weight_distance, None);
+        assert!(report.to_text().contains("   -  "));
+
+        let dir = std::env::temp_dir().join(format!("benny-differential-{}", std::process::id()));
+        let written = report.write_plots(&dir, ImageFormat::Svg).unwrap();
+        assert_eq!(written.len(), 3);
+        assert!(fs::read_to_string(&written[2]).unwrap().contains("wide"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,111 @@
+use crate::scaling::ValueRange;
+use serde::{Deserialize, Serialize};
+
+/// How a network and its channels are presented in the web interfaces
+///
+/// Set on `NeuralNetworkTarget` and `NeuralNetworkSource` and sent along with
+/// the network lists: the input UI labels and scales its sliders with it, the
+/// output monitor its bars, values and charts. Every field is optional;
+/// channels without a label are numbered and those without a range span
+/// `[0, 1]`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DisplayMetadata {
+    /// Accent color, as `#rgb`, `#rrggbb` or a CSS color name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Label of each channel, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    /// Unit of each channel, e.g. `"m/s"`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub units: Vec<String>,
+    /// Expected range of each channel
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ranges: Vec<ValueRange>,
+}
+
+impl DisplayMetadata {
+    /// Check the metadata of a network with `channels` inputs or outputs
+    pub fn validate(&self, channels: usize) -> Result<(), String> {
+        if let Some(color) = &self.color {
+            let valid = match color.strip_prefix('#') {
+                Some(hex) => {
+                    matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+                }
+                None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+            };
+            if !valid {
+                return Err(format!("Invalid color '{}'", color));
+            }
+        }
+        for (name, count) in [
+            ("labels", self.labels.len()),
+            ("units", self.units.len()),
+            ("ranges", self.ranges.len()),
+        ] {
+            if count > channels {
+                return Err(format!("{} {} for {} channels", count, name, channels));
+            }
+        }
+        if let Some(range) = self.ranges.iter().find(|range| {
+            !(range.min.is_finite() && range.max.is_finite() && range.min < range.max)
+        }) {
+            return Err(format!("Invalid range [{}, {}]", range.min, range.max));
+        }
+        Ok(())
+    }
+
+    /// Expected range of `channel`
+    pub fn range(&self, channel: usize) -> ValueRange {
+        self.ranges
+            .get(channel)
+            .copied()
+            .unwrap_or(ValueRange::UNIT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_metadata_is_checked_per_channel() {
+        let display: DisplayMetadata = serde_json::from_str(
+            r##"{"color":"#4caf50","labels":["Throttle","Steering"],"units":["%"],
+                "ranges":[{"min":0,"max":100},{"min":-1,"max":1}]}"##,
+        )
+        .unwrap();
+        assert!(display.validate(2).is_ok());
+        assert_eq!(display.range(1), ValueRange::BIPOLAR);
+        assert_eq!(display.range(2), ValueRange::UNIT);
+        assert!(display.validate(1).is_err());
+
+        // Unset fields are left out of the network lists
+        assert_eq!(
+            serde_json::to_string(&DisplayMetadata::default()).unwrap(),
+            "{}"
+        );
+
+        for color in ["teal", "#fff"] {
+            let display = DisplayMetadata {
+                color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(display.validate(0).is_ok());
+        }
+        for color in ["#12345", "red; background: url(x)"] {
+            let display = DisplayMetadata {
+                color: Some(color.to_string()),
+                ..Default::default()
+            };
+            assert!(display.validate(0).is_err());
+        }
+        let empty_range = DisplayMetadata {
+            ranges: vec![ValueRange::new(1.0, 1.0)],
+            ..Default::default()
+        };
+        assert!(empty_range.validate(1).is_err());
+    }
+}
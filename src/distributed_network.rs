@@ -1,10 +1,31 @@
+use crate::address::{self, AddressPreference, BindOptions};
+use crate::async_io::Compression;
+use crate::atomic_file;
+use crate::bandwidth::{BandwidthLimit, BandwidthTracker, Direction, PeerBandwidthStats};
+use crate::channels::ChannelTable;
+use crate::clock::{self, ClockSample, ClockSync};
+use crate::compatibility::{self, ShapePolicy};
+use crate::health::{ComponentHealth, HealthCheck, HealthReport, DEFAULT_QUEUE_THRESHOLD};
 use crate::neural_network::NeuralNetwork;
+use crate::model_metadata::LayerContribution;
+use crate::model_push::{self, ModelAssembler, ModelChunk};
+use crate::pacing::{Pacing, PeerPacing};
+use crate::priority::{Priority, PriorityLanes};
+use crate::protocol_compat;
+use crate::remote_config::ConfigUpdate;
+use crate::routing::LatencyRouter;
+use crate::scaling::{PeerRanges, SignalRanges, ValueRange};
+use crate::spikes::SpikeEvent;
+use crate::subscriptions::{DisconnectEvent, ForwardDataEvent, Subscriptions, WeightSyncEvent};
+use crate::transport::{self, NnpListener, NnpStream};
 use byteorder::{BigEndian, ByteOrder};
 use crc32fast::Hasher;
-use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::Path;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::{TcpListener, TcpStream};
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
@@ -17,28 +38,47 @@ pub type NetworkId = Uuid;
 /// [MAGIC][VERSION][MSG_TYPE][LENGTH][SEQUENCE][CHECKSUM][PAYLOAD]
 ///
 /// MAGIC: 4 bytes - "NNP\0" (0x4E4E5000)
-/// VERSION: 1 byte - Protocol version (currently 1)
+/// VERSION: 1 byte - Protocol version (low nibble, currently 2) and flags (high nibble)
 /// MSG_TYPE: 1 byte - Message type identifier
 /// LENGTH: 4 bytes - Payload length (big-endian)
 /// SEQUENCE: 8 bytes - Message sequence number (big-endian)
 /// CHECKSUM: 4 bytes - CRC32 of payload (big-endian)
 /// PAYLOAD: Variable length - Message data
 const PROTOCOL_MAGIC: [u8; 4] = [0x4E, 0x4E, 0x50, 0x00]; // "NNP\0"
-const PROTOCOL_VERSION: u8 = 1;
-const HEADER_SIZE: usize = 22; // 4 + 1 + 1 + 4 + 8 + 4
+/// Version spoken by this build; see `protocol_compat` for talking to older peers
+pub const PROTOCOL_VERSION: u8 = 2;
+pub const HEADER_SIZE: usize = 22; // 4 + 1 + 1 + 4 + 8 + 4
+const VERSION_MASK: u8 = 0x0F;
+
+/// Flags carried in the high nibble of the VERSION byte
+pub mod message_flags {
+    /// The receiver must answer with an `Ack` carrying the message sequence
+    pub const ACK_REQUIRED: u8 = 0x80;
+    /// The forward data repeats earlier outputs, served because fresh ones would have missed their deadline
+    pub const STALE: u8 = 0x40;
+}
 
 /// Message types for the neural network protocol
 #[repr(u8)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MessageType {
     Handshake = 0x01,
     HandshakeAck = 0x02,
+    Ack = 0x03,
     ForwardData = 0x10,
     BackwardData = 0x11,
     HebbianData = 0x12,
     WeightSync = 0x13,
+    SpikeEvents = 0x14,
     Heartbeat = 0x20,
     Disconnect = 0x21,
+    Info = 0x22,
+    InfoResponse = 0x23,
+    ConfigUpdate = 0x24,
+    ModelPush = 0x25,
+    ModelPushAck = 0x26,
+    Ping = 0x27,
+    Pong = 0x28,
     Error = 0xFF,
 }
 
@@ -47,12 +87,21 @@ impl From<u8> for MessageType {
         match value {
             0x01 => MessageType::Handshake,
             0x02 => MessageType::HandshakeAck,
+            0x03 => MessageType::Ack,
             0x10 => MessageType::ForwardData,
             0x11 => MessageType::BackwardData,
             0x12 => MessageType::HebbianData,
             0x13 => MessageType::WeightSync,
+            0x14 => MessageType::SpikeEvents,
             0x20 => MessageType::Heartbeat,
             0x21 => MessageType::Disconnect,
+            0x22 => MessageType::Info,
+            0x23 => MessageType::InfoResponse,
+            0x24 => MessageType::ConfigUpdate,
+            0x25 => MessageType::ModelPush,
+            0x26 => MessageType::ModelPushAck,
+            0x27 => MessageType::Ping,
+            0x28 => MessageType::Pong,
             _ => MessageType::Error,
         }
     }
@@ -64,6 +113,8 @@ pub struct NetworkMessage {
     pub msg_type: MessageType,
     pub sequence: u64,
     pub payload: MessagePayload,
+    /// Header flags (see `message_flags`)
+    pub flags: u8,
 }
 
 #[derive(Debug, Clone)]
@@ -74,16 +125,37 @@ pub enum MessagePayload {
         name: String,
         layers: Vec<u16>,
         capabilities: u32, // Bitfield for capabilities
+        /// Cluster the node belongs to (`None` is the default namespace)
+        namespace: Option<String>,
+        /// Names of the sender's forward data channels, indexed by their tags
+        channels: Vec<String>,
+        /// Value ranges the sender expects and produces
+        ranges: Option<SignalRanges>,
     },
     /// Acknowledgment of handshake
     HandshakeAck {
         network_id: NetworkId,
         accepted: bool,
+        /// Why the handshake was refused (only sent with `accepted == false`)
+        reason: Option<String>,
+        /// Value ranges the responder expects and produces
+        ranges: Option<SignalRanges>,
+    },
+    /// Acknowledgment of a message sent with `ACK_REQUIRED`
+    Ack {
+        sequence: u64,
+        /// When the acked message arrived and when the ack left, in the acker's
+        /// microseconds (only sent for heartbeats, for clock synchronization)
+        timestamps: Option<(u64, u64)>,
     },
     /// Forward propagation data (highly optimized)
     ForwardData {
         layer_id: u8,
         data: Vec<f32>, // Using f32 for better network performance
+        /// Send time in microseconds on the receiver's clock, as estimated by the sender
+        timestamp: Option<u64>,
+        /// Index into the channel table of the sender's handshake (`None` is untagged)
+        channel: Option<u8>,
     },
     /// Backpropagation gradients
     BackwardData { layer_id: u8, gradients: Vec<f32> },
@@ -92,6 +164,8 @@ pub enum MessagePayload {
         layer_id: u8,
         correlations: Vec<f32>,
         learning_rate: f32,
+        /// Send time in microseconds on the receiver's clock, as estimated by the sender
+        timestamp: Option<u64>,
     },
     /// Weight synchronization
     WeightSync {
@@ -99,10 +173,41 @@ pub enum MessagePayload {
         weights: Vec<f32>,
         biases: Vec<f32>,
     },
+    /// Sparse spike events, far smaller than dense activations for timing-based learning
+    SpikeEvents {
+        layer_id: u8,
+        /// Firing times in microseconds on the receiver's clock, as estimated by the sender
+        events: Vec<SpikeEvent>,
+    },
     /// Heartbeat
     Heartbeat { timestamp: u64 },
     /// Disconnect notification
     Disconnect { reason: String },
+    /// Request for the receiver's architecture and activity statistics
+    Info,
+    /// Reply to `Info`, written on the same connection
+    InfoResponse { info: PeerInfo },
+    /// Runtime settings change, only honoured by secure nodes (see `SecureDistributedNetwork`)
+    ConfigUpdate {
+        update: ConfigUpdate,
+        /// API token with the `admin` scope, for nodes that require one
+        token: Option<String>,
+    },
+    /// One chunk of a trained model to hot-swap into the receiver
+    ModelPush { chunk: ModelChunk },
+    /// Outcome of a model push, sent after its last chunk
+    ModelPushAck {
+        transfer_id: u64,
+        accepted: bool,
+        /// Model version that was active before the push
+        previous_version: u64,
+        /// Why the model was refused (only sent with `accepted == false`)
+        reason: Option<String>,
+    },
+    /// Health probe, answered without a handshake
+    Ping,
+    /// Reply to `Ping` with the receiver's readiness, written on the same connection
+    Pong { report: HealthReport },
     /// Error message
     Error { code: u16, message: String },
 }
@@ -117,6 +222,12 @@ pub mod capabilities {
     pub const MULTI_LAYER: u32 = 1 << 5;
     pub const REAL_TIME: u32 = 1 << 6;
     pub const COMPRESSION: u32 = 1 << 7;
+    pub const ACKNOWLEDGED_DELIVERY: u32 = 1 << 8;
+    pub const SPIKE_EVENTS: u32 = 1 << 9;
+    /// Sends or accepts `ConfigUpdate` messages (secure mode only)
+    pub const REMOTE_CONFIG: u32 = 1 << 10;
+    /// Accepts models pushed with `ModelPush` (off unless enabled on the node)
+    pub const MODEL_DEPLOYMENT: u32 = 1 << 11;
 }
 
 /// Information about a neural network node
@@ -131,11 +242,145 @@ pub struct NetworkInfo {
     pub status: NetworkStatus,
 }
 
+/// Architecture and activity of a node, as reported in an `InfoResponse`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    pub network_id: NetworkId,
+    pub name: String,
+    pub layers: Vec<u16>,
+    /// Weights and biases of the node's network
+    pub parameters: u64,
+    /// Hebbian learning mode of the node's network
+    pub learning_mode: String,
+    pub uptime_secs: u64,
+    /// Peers the node currently knows
+    pub peers: u32,
+    pub messages_sent: u64,
+    pub messages_received: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    /// L2 norm of the node's weights (`None` from nodes that predate it)
+    #[serde(default)]
+    pub weight_norm: Option<f32>,
+    /// Most recent training loss the node reported (see `DistributedNetwork::record_loss`)
+    #[serde(default)]
+    pub loss: Option<f32>,
+    /// Output activations of the node's most recent forward pass
+    #[serde(default)]
+    pub outputs: Vec<f32>,
+}
+
+impl PeerInfo {
+    fn write(&self, buffer: &mut Vec<u8>) {
+        buffer.extend_from_slice(self.network_id.as_bytes());
+        write_short_string(buffer, &self.name);
+
+        buffer.push(self.layers.len() as u8);
+        for &layer_size in &self.layers {
+            let mut layer_bytes = [0u8; 2];
+            BigEndian::write_u16(&mut layer_bytes, layer_size);
+            buffer.extend_from_slice(&layer_bytes);
+        }
+
+        write_timestamp(buffer, self.parameters);
+        write_short_string(buffer, &self.learning_mode);
+        write_timestamp(buffer, self.uptime_secs);
+
+        let mut peers_bytes = [0u8; 4];
+        BigEndian::write_u32(&mut peers_bytes, self.peers);
+        buffer.extend_from_slice(&peers_bytes);
+
+        for counter in [
+            self.messages_sent,
+            self.messages_received,
+            self.bytes_sent,
+            self.bytes_received,
+        ] {
+            write_timestamp(buffer, counter);
+        }
+
+        // Optional trailing activity; a missing loss is sent as NaN
+        buffer.extend_from_slice(&self.weight_norm.unwrap_or(f32::NAN).to_bits().to_be_bytes());
+        buffer.extend_from_slice(&self.loss.unwrap_or(f32::NAN).to_bits().to_be_bytes());
+        buffer.extend_from_slice(&(self.outputs.len() as u32).to_be_bytes());
+        for value in &self.outputs {
+            buffer.extend_from_slice(&value.to_bits().to_be_bytes());
+        }
+    }
+
+    fn from_payload(bytes: &[u8]) -> Result<Self, ProtocolError> {
+        let take = |offset: &mut usize, len: usize| -> Result<&[u8], ProtocolError> {
+            let field = bytes
+                .get(*offset..*offset + len)
+                .ok_or(ProtocolError::InvalidPayload)?;
+            *offset += len;
+            Ok(field)
+        };
+        let short_string = |offset: &mut usize| -> Result<String, ProtocolError> {
+            let len = take(offset, 1)?[0] as usize;
+            Ok(String::from_utf8_lossy(take(offset, len)?).to_string())
+        };
+
+        let mut offset = 0;
+        let network_id =
+            Uuid::from_slice(take(&mut offset, 16)?).map_err(|_| ProtocolError::InvalidPayload)?;
+        let name = short_string(&mut offset)?;
+        let layers_count = take(&mut offset, 1)?[0] as usize;
+        let layers = take(&mut offset, layers_count * 2)?
+            .chunks_exact(2)
+            .map(BigEndian::read_u16)
+            .collect();
+        let parameters = BigEndian::read_u64(take(&mut offset, 8)?);
+        let learning_mode = short_string(&mut offset)?;
+        let uptime_secs = BigEndian::read_u64(take(&mut offset, 8)?);
+        let peers = BigEndian::read_u32(take(&mut offset, 4)?);
+        let mut counters = [0u64; 4];
+        for counter in &mut counters {
+            *counter = BigEndian::read_u64(take(&mut offset, 8)?);
+        }
+
+        let (mut weight_norm, mut loss, mut outputs) = (None, None, Vec::new());
+        if offset < bytes.len() {
+            let float = |offset: &mut usize| -> Result<f32, ProtocolError> {
+                Ok(f32::from_bits(BigEndian::read_u32(take(offset, 4)?)))
+            };
+            weight_norm = Some(float(&mut offset)?).filter(|norm| !norm.is_nan());
+            loss = Some(float(&mut offset)?).filter(|loss| !loss.is_nan());
+            let count = BigEndian::read_u32(take(&mut offset, 4)?) as usize;
+            outputs = take(&mut offset, count * 4)?
+                .chunks_exact(4)
+                .map(|value| f32::from_bits(BigEndian::read_u32(value)))
+                .collect();
+        }
+        if offset != bytes.len() {
+            return Err(ProtocolError::InvalidPayload);
+        }
+
+        let [messages_sent, messages_received, bytes_sent, bytes_received] = counters;
+        Ok(PeerInfo {
+            network_id,
+            name,
+            layers,
+            parameters,
+            learning_mode,
+            uptime_secs,
+            peers,
+            messages_sent,
+            messages_received,
+            bytes_sent,
+            bytes_received,
+            weight_norm,
+            loss,
+            outputs,
+        })
+    }
+}
+
 /// Connection between two neural networks
 #[derive(Debug)]
 pub struct NetworkConnection {
     pub peer_id: NetworkId,
-    pub stream: Option<TcpStream>,
+    pub stream: Option<NnpStream>,
     pub capabilities: u32,
     pub last_heartbeat: u64,
     pub sequence_counter: u64,
@@ -145,7 +390,7 @@ pub struct NetworkConnection {
 
 impl Clone for NetworkConnection {
     fn clone(&self) -> Self {
-        // Create a new connection without the TcpStream
+        // Create a new connection without the stream
         // This is used only for reference purposes, not for actual communication
         Self {
             peer_id: self.peer_id,
@@ -170,8 +415,32 @@ pub enum NetworkStatus {
 
 /// Binary protocol implementation for neural network messages
 impl NetworkMessage {
+    /// Whether the sender expects an `Ack` for this message
+    pub fn requires_ack(&self) -> bool {
+        self.flags & message_flags::ACK_REQUIRED != 0
+    }
+
+    /// Whether the sender flagged the forward data as repeated, stale outputs
+    pub fn is_stale(&self) -> bool {
+        self.flags & message_flags::STALE != 0
+    }
+
     /// Serialize message to binary format
     pub fn to_bytes(&self) -> Vec<u8> {
+        self.encode(PROTOCOL_VERSION)
+    }
+
+    /// Serialize message for a peer speaking `version`, translating it first
+    pub fn to_bytes_for(&self, version: u8) -> Result<Vec<u8>, ProtocolError> {
+        protocol_compat::translate(self, version).map(|message| message.encode(version))
+    }
+
+    /// Protocol version advertised in a frame header
+    pub fn frame_version(header: &[u8]) -> u8 {
+        header[4] & VERSION_MASK
+    }
+
+    fn encode(&self, version: u8) -> Vec<u8> {
         let payload_bytes = self.payload.to_bytes();
         let payload_len = payload_bytes.len() as u32;
 
@@ -184,7 +453,7 @@ impl NetworkMessage {
 
         // Header: MAGIC + VERSION + MSG_TYPE + LENGTH + SEQUENCE + CHECKSUM
         buffer.extend_from_slice(&PROTOCOL_MAGIC);
-        buffer.push(PROTOCOL_VERSION);
+        buffer.push(version | (self.flags & !VERSION_MASK));
         buffer.push(self.msg_type as u8);
 
         let mut len_bytes = [0u8; 4];
@@ -216,10 +485,11 @@ impl NetworkMessage {
             return Err(ProtocolError::InvalidMagic);
         }
 
-        // Check version
-        if bytes[4] != PROTOCOL_VERSION {
+        // Check version; older peers omit the fields they do not know
+        if !protocol_compat::is_supported(Self::frame_version(bytes)) {
             return Err(ProtocolError::UnsupportedVersion);
         }
+        let flags = bytes[4] & !VERSION_MASK;
 
         let msg_type = MessageType::from(bytes[5]);
         let payload_len = BigEndian::read_u32(&bytes[6..10]) as usize;
@@ -247,11 +517,50 @@ impl NetworkMessage {
             msg_type,
             sequence,
             payload,
+            flags,
         })
     }
 }
 
 impl MessagePayload {
+    /// Message type carrying this payload
+    pub fn message_type(&self) -> MessageType {
+        match self {
+            MessagePayload::Handshake { .. } => MessageType::Handshake,
+            MessagePayload::HandshakeAck { .. } => MessageType::HandshakeAck,
+            MessagePayload::Ack { .. } => MessageType::Ack,
+            MessagePayload::ForwardData { .. } => MessageType::ForwardData,
+            MessagePayload::BackwardData { .. } => MessageType::BackwardData,
+            MessagePayload::HebbianData { .. } => MessageType::HebbianData,
+            MessagePayload::WeightSync { .. } => MessageType::WeightSync,
+            MessagePayload::SpikeEvents { .. } => MessageType::SpikeEvents,
+            MessagePayload::Heartbeat { .. } => MessageType::Heartbeat,
+            MessagePayload::Disconnect { .. } => MessageType::Disconnect,
+            MessagePayload::Info => MessageType::Info,
+            MessagePayload::InfoResponse { .. } => MessageType::InfoResponse,
+            MessagePayload::ConfigUpdate { .. } => MessageType::ConfigUpdate,
+            MessagePayload::ModelPush { .. } => MessageType::ModelPush,
+            MessagePayload::ModelPushAck { .. } => MessageType::ModelPushAck,
+            MessagePayload::Ping => MessageType::Ping,
+            MessagePayload::Pong { .. } => MessageType::Pong,
+            MessagePayload::Error { .. } => MessageType::Error,
+        }
+    }
+
+    /// Capability a peer needs to process this payload (0 for control messages)
+    pub fn required_capability(&self) -> u32 {
+        match self {
+            MessagePayload::ForwardData { .. } => capabilities::FORWARD_PROPAGATION,
+            MessagePayload::BackwardData { .. } => capabilities::BACKPROPAGATION,
+            MessagePayload::HebbianData { .. } => capabilities::HEBBIAN_LEARNING,
+            MessagePayload::WeightSync { .. } => capabilities::WEIGHT_SYNC,
+            MessagePayload::SpikeEvents { .. } => capabilities::SPIKE_EVENTS,
+            MessagePayload::ConfigUpdate { .. } => capabilities::REMOTE_CONFIG,
+            MessagePayload::ModelPush { .. } => capabilities::MODEL_DEPLOYMENT,
+            _ => 0,
+        }
+    }
+
     /// Serialize payload to bytes
     fn to_bytes(&self) -> Vec<u8> {
         let mut buffer = Vec::new();
@@ -262,6 +571,9 @@ impl MessagePayload {
                 name,
                 layers,
                 capabilities,
+                namespace,
+                channels,
+                ranges,
             } => {
                 buffer.extend_from_slice(network_id.as_bytes());
 
@@ -279,17 +591,66 @@ impl MessagePayload {
                 let mut cap_bytes = [0u8; 4];
                 BigEndian::write_u32(&mut cap_bytes, *capabilities);
                 buffer.extend_from_slice(&cap_bytes);
+
+                // Optional trailing namespace; older peers simply omit it
+                if namespace.is_some() || !channels.is_empty() || ranges.is_some() {
+                    write_short_string(&mut buffer, namespace.as_deref().unwrap_or(""));
+                }
+
+                // Optional channel table and value ranges, each after empty placeholders of the fields before
+                if !channels.is_empty() || ranges.is_some() {
+                    buffer.push(channels.len() as u8);
+                    for channel in channels {
+                        write_short_string(&mut buffer, channel);
+                    }
+                }
+                if let Some(ranges) = ranges {
+                    write_ranges(&mut buffer, ranges);
+                }
             }
 
             MessagePayload::HandshakeAck {
                 network_id,
                 accepted,
+                reason,
+                ranges,
             } => {
                 buffer.extend_from_slice(network_id.as_bytes());
                 buffer.push(if *accepted { 1 } else { 0 });
+
+                // Optional trailing reason; accepted handshakes keep the original 17 bytes
+                if reason.is_some() || ranges.is_some() {
+                    let reason = reason.as_deref().unwrap_or("");
+                    let reason_bytes = &reason.as_bytes()[..reason.len().min(u8::MAX as usize)];
+                    buffer.push(reason_bytes.len() as u8);
+                    buffer.extend_from_slice(reason_bytes);
+                }
+                if let Some(ranges) = ranges {
+                    write_ranges(&mut buffer, ranges);
+                }
+            }
+
+            MessagePayload::Ack {
+                sequence,
+                timestamps,
+            } => {
+                let mut seq_bytes = [0u8; 8];
+                BigEndian::write_u64(&mut seq_bytes, *sequence);
+                buffer.extend_from_slice(&seq_bytes);
+
+                // Optional trailing receive and transmit times
+                if let Some((received, replied)) = timestamps {
+                    write_timestamp(&mut buffer, *received);
+                    write_timestamp(&mut buffer, *replied);
+                }
             }
 
-            MessagePayload::ForwardData { layer_id, data } => {
+            MessagePayload::ForwardData {
+                layer_id,
+                data,
+                timestamp,
+                channel,
+            } => {
                 buffer.push(*layer_id);
 
                 let mut len_bytes = [0u8; 4];
@@ -301,6 +662,14 @@ impl MessagePayload {
                     BigEndian::write_u32(&mut value_bytes, value.to_bits());
                     buffer.extend_from_slice(&value_bytes);
                 }
+
+                // Optional trailing send time, then the channel tag (0 stands in for no send time)
+                if timestamp.is_some() || channel.is_some() {
+                    write_timestamp(&mut buffer, timestamp.unwrap_or(0));
+                }
+                if let Some(channel) = channel {
+                    buffer.push(*channel);
+                }
             }
 
             MessagePayload::BackwardData {
@@ -324,6 +693,7 @@ impl MessagePayload {
                 layer_id,
                 correlations,
                 learning_rate,
+                timestamp,
             } => {
                 buffer.push(*layer_id);
 
@@ -340,6 +710,11 @@ impl MessagePayload {
                     BigEndian::write_u32(&mut corr_bytes, correlation.to_bits());
                     buffer.extend_from_slice(&corr_bytes);
                 }
+
+                // Optional trailing send time
+                if let Some(timestamp) = timestamp {
+                    write_timestamp(&mut buffer, *timestamp);
+                }
             }
 
             MessagePayload::WeightSync {
@@ -370,6 +745,21 @@ impl MessagePayload {
                 }
             }
 
+            MessagePayload::SpikeEvents { layer_id, events } => {
+                buffer.push(*layer_id);
+
+                let mut len_bytes = [0u8; 4];
+                BigEndian::write_u32(&mut len_bytes, events.len() as u32);
+                buffer.extend_from_slice(&len_bytes);
+
+                for event in events {
+                    let mut neuron_bytes = [0u8; 4];
+                    BigEndian::write_u32(&mut neuron_bytes, event.neuron);
+                    buffer.extend_from_slice(&neuron_bytes);
+                    write_timestamp(&mut buffer, event.timestamp);
+                }
+            }
+
             MessagePayload::Heartbeat { timestamp } => {
                 let mut time_bytes = [0u8; 8];
                 BigEndian::write_u64(&mut time_bytes, *timestamp);
@@ -382,6 +772,66 @@ impl MessagePayload {
                 buffer.extend_from_slice(reason_bytes);
             }
 
+            MessagePayload::Info => {}
+
+            MessagePayload::InfoResponse { info } => info.write(&mut buffer),
+
+            MessagePayload::ConfigUpdate { update, token } => {
+                // Presence mask, then every field; absent fields are sent as zero
+                let mask = update.hebbian_rate.is_some() as u8
+                    | (update.online_learning.is_some() as u8) << 1
+                    | (update.decay_rate.is_some() as u8) << 2;
+                buffer.push(mask);
+
+                let mut rate_bytes = [0u8; 4];
+                BigEndian::write_u32(&mut rate_bytes, update.hebbian_rate.unwrap_or(0.0).to_bits());
+                buffer.extend_from_slice(&rate_bytes);
+                buffer.push(update.online_learning.unwrap_or(false) as u8);
+                BigEndian::write_u32(&mut rate_bytes, update.decay_rate.unwrap_or(0.0).to_bits());
+                buffer.extend_from_slice(&rate_bytes);
+
+                // Optional trailing token; updates without one keep the original 10 bytes
+                if let Some(token) = token {
+                    write_short_string(&mut buffer, token);
+                }
+            }
+
+            MessagePayload::ModelPush { chunk } => {
+                write_timestamp(&mut buffer, chunk.transfer_id);
+                write_timestamp(&mut buffer, chunk.version);
+                for value in [chunk.index, chunk.count, chunk.checksum, chunk.data.len() as u32] {
+                    let mut value_bytes = [0u8; 4];
+                    BigEndian::write_u32(&mut value_bytes, value);
+                    buffer.extend_from_slice(&value_bytes);
+                }
+                buffer.extend_from_slice(&chunk.data);
+            }
+
+            MessagePayload::ModelPushAck {
+                transfer_id,
+                accepted,
+                previous_version,
+                reason,
+            } => {
+                write_timestamp(&mut buffer, *transfer_id);
+                buffer.push(*accepted as u8);
+                write_timestamp(&mut buffer, *previous_version);
+                if let Some(reason) = reason {
+                    write_short_string(&mut buffer, reason);
+                }
+            }
+
+            MessagePayload::Ping => {}
+
+            MessagePayload::Pong { report } => {
+                buffer.push(report.components.len() as u8);
+                for component in &report.components {
+                    write_short_string(&mut buffer, &component.name);
+                    buffer.push(component.ready as u8);
+                    write_short_string(&mut buffer, &component.detail);
+                }
+            }
+
             MessagePayload::Error { code, message } => {
                 let mut code_bytes = [0u8; 2];
                 BigEndian::write_u16(&mut code_bytes, *code);
@@ -436,12 +886,53 @@ impl MessagePayload {
                     return Err(ProtocolError::InvalidPayload);
                 }
                 let capabilities = BigEndian::read_u32(&bytes[offset..offset + 4]);
+                offset += 4;
+
+                let mut namespace = if bytes.len() > offset {
+                    let namespace_len = bytes[offset] as usize;
+                    offset += 1;
+                    if bytes.len() < offset + namespace_len {
+                        return Err(ProtocolError::InvalidPayload);
+                    }
+                    let namespace =
+                        String::from_utf8_lossy(&bytes[offset..offset + namespace_len]).to_string();
+                    offset += namespace_len;
+                    Some(namespace)
+                } else {
+                    None
+                };
+
+                let mut channels = Vec::new();
+                let mut ranges = None;
+                if bytes.len() > offset {
+                    // An empty namespace only holds the place of the channel table
+                    if namespace.as_deref() == Some("") {
+                        namespace = None;
+                    }
+                    let count = bytes[offset] as usize;
+                    offset += 1;
+                    for _ in 0..count {
+                        let len = *bytes.get(offset).ok_or(ProtocolError::InvalidPayload)? as usize;
+                        offset += 1;
+                        if bytes.len() < offset + len {
+                            return Err(ProtocolError::InvalidPayload);
+                        }
+                        channels.push(String::from_utf8_lossy(&bytes[offset..offset + len]).to_string());
+                        offset += len;
+                    }
+                    if bytes.len() > offset {
+                        ranges = Some(read_ranges(&bytes[offset..])?);
+                    }
+                }
 
                 Ok(MessagePayload::Handshake {
                     network_id,
                     name,
                     layers,
                     capabilities,
+                    namespace,
+                    channels,
+                    ranges,
                 })
             }
 
@@ -452,10 +943,13 @@ impl MessagePayload {
 
                 let layer_id = bytes[0];
                 let data_len = BigEndian::read_u32(&bytes[1..5]) as usize;
-
-                if bytes.len() != 5 + data_len * 4 {
-                    return Err(ProtocolError::InvalidPayload);
-                }
+                let body_len = 5 + data_len * 4;
+                let (timestamp, channel) = if bytes.len() == body_len + 9 {
+                    let timestamp = BigEndian::read_u64(&bytes[body_len..body_len + 8]);
+                    ((timestamp != 0).then_some(timestamp), Some(bytes[body_len + 8]))
+                } else {
+                    (read_trailing_timestamp(bytes, body_len)?, None)
+                };
 
                 let mut data = Vec::with_capacity(data_len);
                 for i in 0..data_len {
@@ -464,18 +958,41 @@ impl MessagePayload {
                     data.push(f32::from_bits(bits));
                 }
 
-                Ok(MessagePayload::ForwardData { layer_id, data })
+                Ok(MessagePayload::ForwardData {
+                    layer_id,
+                    data,
+                    timestamp,
+                    channel,
+                })
             }
 
             MessageType::HandshakeAck => {
-                if bytes.len() != 17 {
+                if bytes.len() < 17 {
                     return Err(ProtocolError::InvalidPayload);
                 }
                 let network_id = Uuid::from_bytes(bytes[0..16].try_into().unwrap());
                 let accepted = bytes[16] != 0;
+                let (reason, ranges) = if bytes.len() > 17 {
+                    let reason_end = 18 + bytes[17] as usize;
+                    if bytes.len() < reason_end {
+                        return Err(ProtocolError::InvalidPayload);
+                    }
+                    let reason = String::from_utf8_lossy(&bytes[18..reason_end]).to_string();
+                    if bytes.len() == reason_end {
+                        (Some(reason), None)
+                    } else {
+                        // An empty reason only holds the place of the ranges
+                        let reason = (!reason.is_empty()).then_some(reason);
+                        (reason, Some(read_ranges(&bytes[reason_end..])?))
+                    }
+                } else {
+                    (None, None)
+                };
                 Ok(MessagePayload::HandshakeAck {
                     network_id,
                     accepted,
+                    reason,
+                    ranges,
                 })
             }
 
@@ -520,10 +1037,7 @@ impl MessagePayload {
                 let layer_id = bytes[0];
                 let learning_rate = f32::from_bits(BigEndian::read_u32(&bytes[1..5]));
                 let data_len = BigEndian::read_u32(&bytes[5..9]) as usize;
-
-                if bytes.len() != 9 + data_len * 4 {
-                    return Err(ProtocolError::InvalidPayload);
-                }
+                let timestamp = read_trailing_timestamp(bytes, 9 + data_len * 4)?;
 
                 let mut correlations = Vec::with_capacity(data_len);
                 for i in 0..data_len {
@@ -536,15 +1050,283 @@ impl MessagePayload {
                     layer_id,
                     correlations,
                     learning_rate,
+                    timestamp,
+                })
+            }
+
+            MessageType::Ack => {
+                let timestamps = match bytes.len() {
+                    8 => None,
+                    24 => Some((
+                        BigEndian::read_u64(&bytes[8..16]),
+                        BigEndian::read_u64(&bytes[16..24]),
+                    )),
+                    _ => return Err(ProtocolError::InvalidPayload),
+                };
+                let sequence = BigEndian::read_u64(&bytes[0..8]);
+                Ok(MessagePayload::Ack {
+                    sequence,
+                    timestamps,
+                })
+            }
+
+            MessageType::WeightSync => {
+                if bytes.len() < 5 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+
+                let layer_id = bytes[0];
+                let weights_len = BigEndian::read_u32(&bytes[1..5]) as usize;
+                let biases_offset = 5 + weights_len * 4;
+                if bytes.len() < biases_offset + 4 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                let biases_len =
+                    BigEndian::read_u32(&bytes[biases_offset..biases_offset + 4]) as usize;
+                if bytes.len() != biases_offset + 4 + biases_len * 4 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+
+                let read_f32s = |start: usize, count: usize| -> Vec<f32> {
+                    (0..count)
+                        .map(|i| {
+                            let at = start + i * 4;
+                            f32::from_bits(BigEndian::read_u32(&bytes[at..at + 4]))
+                        })
+                        .collect()
+                };
+
+                Ok(MessagePayload::WeightSync {
+                    layer_id,
+                    weights: read_f32s(5, weights_len),
+                    biases: read_f32s(biases_offset + 4, biases_len),
+                })
+            }
+
+            MessageType::SpikeEvents => {
+                if bytes.len() < 5 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+
+                let layer_id = bytes[0];
+                let events_len = BigEndian::read_u32(&bytes[1..5]) as usize;
+                if bytes.len() != 5 + events_len * 12 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+
+                let events = bytes[5..]
+                    .chunks_exact(12)
+                    .map(|chunk| SpikeEvent {
+                        neuron: BigEndian::read_u32(&chunk[0..4]),
+                        timestamp: BigEndian::read_u64(&chunk[4..12]),
+                    })
+                    .collect();
+
+                Ok(MessagePayload::SpikeEvents { layer_id, events })
+            }
+
+            MessageType::Disconnect => {
+                if bytes.is_empty() || bytes.len() != 1 + bytes[0] as usize {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                let reason = String::from_utf8_lossy(&bytes[1..]).to_string();
+                Ok(MessagePayload::Disconnect { reason })
+            }
+
+            MessageType::Info => {
+                if !bytes.is_empty() {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                Ok(MessagePayload::Info)
+            }
+
+            MessageType::InfoResponse => Ok(MessagePayload::InfoResponse {
+                info: PeerInfo::from_payload(bytes)?,
+            }),
+
+            MessageType::ConfigUpdate => {
+                if bytes.len() < 10 || bytes[0] & !0b111 != 0 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                let token = match &bytes[10..] {
+                    [] => None,
+                    [len, token @ ..] if token.len() == *len as usize => {
+                        Some(String::from_utf8_lossy(token).to_string())
+                    }
+                    _ => return Err(ProtocolError::InvalidPayload),
+                };
+                let mask = bytes[0];
+                let update = ConfigUpdate {
+                    hebbian_rate: (mask & 0b001 != 0)
+                        .then(|| f32::from_bits(BigEndian::read_u32(&bytes[1..5]))),
+                    online_learning: (mask & 0b010 != 0).then(|| bytes[5] != 0),
+                    decay_rate: (mask & 0b100 != 0)
+                        .then(|| f32::from_bits(BigEndian::read_u32(&bytes[6..10]))),
+                };
+                Ok(MessagePayload::ConfigUpdate { update, token })
+            }
+
+            MessageType::ModelPush => {
+                if bytes.len() < 32 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                let data_len = BigEndian::read_u32(&bytes[28..32]) as usize;
+                if bytes.len() != 32 + data_len {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                Ok(MessagePayload::ModelPush {
+                    chunk: ModelChunk {
+                        transfer_id: BigEndian::read_u64(&bytes[0..8]),
+                        version: BigEndian::read_u64(&bytes[8..16]),
+                        index: BigEndian::read_u32(&bytes[16..20]),
+                        count: BigEndian::read_u32(&bytes[20..24]),
+                        checksum: BigEndian::read_u32(&bytes[24..28]),
+                        data: bytes[32..].to_vec(),
+                    },
+                })
+            }
+
+            MessageType::ModelPushAck => {
+                if bytes.len() < 17 {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                let reason = if bytes.len() > 17 {
+                    if bytes.len() != 18 + bytes[17] as usize {
+                        return Err(ProtocolError::InvalidPayload);
+                    }
+                    Some(String::from_utf8_lossy(&bytes[18..]).to_string())
+                } else {
+                    None
+                };
+                Ok(MessagePayload::ModelPushAck {
+                    transfer_id: BigEndian::read_u64(&bytes[0..8]),
+                    accepted: bytes[8] != 0,
+                    previous_version: BigEndian::read_u64(&bytes[9..17]),
+                    reason,
+                })
+            }
+
+            MessageType::Ping => {
+                if !bytes.is_empty() {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                Ok(MessagePayload::Ping)
+            }
+
+            MessageType::Pong => Ok(MessagePayload::Pong {
+                report: read_health_report(bytes)?,
+            }),
+
+            MessageType::Error => {
+                if bytes.len() < 3 || bytes.len() != 3 + bytes[2] as usize {
+                    return Err(ProtocolError::InvalidPayload);
+                }
+                Ok(MessagePayload::Error {
+                    code: BigEndian::read_u16(&bytes[0..2]),
+                    message: String::from_utf8_lossy(&bytes[3..]).to_string(),
                 })
             }
+        }
+    }
+}
+
+fn write_timestamp(buffer: &mut Vec<u8>, micros: u64) {
+    let mut timestamp_bytes = [0u8; 8];
+    BigEndian::write_u64(&mut timestamp_bytes, micros);
+    buffer.extend_from_slice(&timestamp_bytes);
+}
 
-            // Add other message type deserializations...
-            _ => Err(ProtocolError::UnsupportedMessageType),
+/// Write input then output range as `min`/`max` f32 pairs, NaN for an undeclared range
+fn write_ranges(buffer: &mut Vec<u8>, ranges: &SignalRanges) {
+    for range in [ranges.input, ranges.output] {
+        let (min, max) = range.map_or((f32::NAN, f32::NAN), |r| (r.min as f32, r.max as f32));
+        for value in [min, max] {
+            let mut value_bytes = [0u8; 4];
+            BigEndian::write_u32(&mut value_bytes, value.to_bits());
+            buffer.extend_from_slice(&value_bytes);
         }
     }
 }
 
+fn read_ranges(bytes: &[u8]) -> Result<SignalRanges, ProtocolError> {
+    if bytes.len() != 16 {
+        return Err(ProtocolError::InvalidPayload);
+    }
+    let value = |i: usize| f32::from_bits(BigEndian::read_u32(&bytes[i * 4..i * 4 + 4])) as f64;
+    let range = |i: usize| {
+        let (min, max) = (value(i), value(i + 1));
+        (!min.is_nan() && !max.is_nan()).then(|| ValueRange::new(min, max))
+    };
+    Ok(SignalRanges {
+        input: range(0),
+        output: range(2),
+    })
+}
+
+fn read_health_report(bytes: &[u8]) -> Result<HealthReport, ProtocolError> {
+    let take = |offset: &mut usize, len: usize| -> Result<&[u8], ProtocolError> {
+        let field = bytes
+            .get(*offset..*offset + len)
+            .ok_or(ProtocolError::InvalidPayload)?;
+        *offset += len;
+        Ok(field)
+    };
+    let short_string = |offset: &mut usize| -> Result<String, ProtocolError> {
+        let len = take(offset, 1)?[0] as usize;
+        Ok(String::from_utf8_lossy(take(offset, len)?).to_string())
+    };
+
+    let mut offset = 0;
+    let mut report = HealthReport::new();
+    for _ in 0..take(&mut offset, 1)?[0] {
+        let name = short_string(&mut offset)?;
+        let ready = take(&mut offset, 1)?[0] != 0;
+        let detail = short_string(&mut offset)?;
+        report.push(ComponentHealth {
+            name,
+            ready,
+            detail,
+        });
+    }
+    if offset != bytes.len() {
+        return Err(ProtocolError::InvalidPayload);
+    }
+    Ok(report)
+}
+
+/// Write `text` behind a one-byte length
+///
+/// Longer text is cut at a character boundary so the frame stays well-formed;
+/// namespaces and channel names, which must arrive intact, are rejected before
+/// they get here.
+fn write_short_string(buffer: &mut Vec<u8>, text: &str) {
+    let mut len = text.len().min(u8::MAX as usize);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    buffer.push(len as u8);
+    buffer.extend_from_slice(&text.as_bytes()[..len]);
+}
+
+/// Check that `namespace` fits the one-byte length prefix it is sent with
+pub fn check_namespace(namespace: &str) -> Result<(), String> {
+    if namespace.len() > u8::MAX as usize {
+        return Err(format!("Namespace '{}' is longer than 255 bytes", namespace));
+    }
+    Ok(())
+}
+
+/// Read the optional timestamp after a payload body of `body_len` bytes
+fn read_trailing_timestamp(bytes: &[u8], body_len: usize) -> Result<Option<u64>, ProtocolError> {
+    if bytes.len() == body_len {
+        Ok(None)
+    } else if bytes.len() == body_len + 8 {
+        Ok(Some(BigEndian::read_u64(&bytes[body_len..])))
+    } else {
+        Err(ProtocolError::InvalidPayload)
+    }
+}
+
 /// Protocol errors
 #[derive(Debug)]
 pub enum ProtocolError {
@@ -555,6 +1337,13 @@ pub enum ProtocolError {
     InvalidPayload,
     UnsupportedMessageType,
     PeerNotFound,
+    AckTimeout,
+    /// The peer refused the handshake, with its reason when it gave one
+    HandshakeRejected(Option<String>),
+    /// The peer refused a pushed model
+    ModelRejected(String),
+    /// The peer speaks an older protocol version that lacks this message type
+    Untranslatable { msg_type: MessageType, version: u8 },
     IoError(std::io::Error),
 }
 
@@ -564,34 +1353,213 @@ impl From<std::io::Error> for ProtocolError {
     }
 }
 
-/// Distributed Neural Network Node with optimized TCP protocol
-#[derive(Clone)]
-pub struct DistributedNetwork {
-    pub id: NetworkId,
-    pub info: NetworkInfo,
-    pub network: Arc<Mutex<NeuralNetwork>>,
-    pub connections: Arc<Mutex<HashMap<NetworkId, NetworkConnection>>>,
-    pub message_sender: mpsc::UnboundedSender<NetworkMessage>,
-    pub sequence_counter: Arc<Mutex<u64>>,
+/// Acknowledged-delivery settings for outgoing data messages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AckPolicy {
+    /// Request an `Ack` for every data message and retransmit until one arrives
+    pub enabled: bool,
+    /// How long to wait for an `Ack` before retransmitting
+    pub timeout: Duration,
+    /// Retransmissions after the first attempt before giving up
+    pub max_retries: u32,
 }
 
-impl DistributedNetwork {
-    /// Create a new distributed neural network node
-    pub fn new(
-        name: String,
-        address: String,
-        port: u16,
-        network: NeuralNetwork,
-    ) -> (Self, mpsc::UnboundedReceiver<NetworkMessage>) {
-        let id = Uuid::new_v4();
-        let layers: Vec<u16> = network.get_layers().iter().map(|&x| x as u16).collect();
+impl Default for AckPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeout: Duration::from_millis(500),
+            max_retries: 5,
+        }
+    }
+}
 
-        let capabilities = capabilities::FORWARD_PROPAGATION
-            | capabilities::BACKPROPAGATION
-            | capabilities::HEBBIAN_LEARNING
-            | capabilities::CORRELATION_ANALYSIS
-            | capabilities::MULTI_LAYER
-            | capabilities::REAL_TIME;
+/// Bounded window of recently delivered (peer, sequence) pairs used to drop retransmissions
+#[derive(Debug, Default)]
+struct RecentDeliveries {
+    seen: HashSet<(NetworkId, u64)>,
+    order: VecDeque<(NetworkId, u64)>,
+}
+
+impl RecentDeliveries {
+    const CAPACITY: usize = 4096;
+
+    /// Whether message `sequence` from `peer` was already delivered
+    fn contains(&self, peer: NetworkId, sequence: u64) -> bool {
+        self.seen.contains(&(peer, sequence))
+    }
+
+    /// Forget `peer`'s deliveries when its handshake is numbered at or below one of them
+    ///
+    /// Sequence numbers only grow within a process, so such a handshake comes
+    /// from a restarted peer numbering its messages from the start again.
+    /// Returns whether anything was forgotten.
+    fn observe_handshake(&mut self, peer: NetworkId, sequence: u64) -> bool {
+        if !self.order.iter().any(|&(p, s)| p == peer && s >= sequence) {
+            return false;
+        }
+        self.seen.retain(|&(p, _)| p != peer);
+        self.order.retain(|&(p, _)| p != peer);
+        true
+    }
+
+    /// Record a delivery, returning `false` if it was already seen
+    fn insert(&mut self, peer: NetworkId, sequence: u64) -> bool {
+        if !self.seen.insert((peer, sequence)) {
+            return false;
+        }
+        self.order.push_back((peer, sequence));
+        if self.order.len() > Self::CAPACITY {
+            if let Some(oldest) = self.order.pop_front() {
+                self.seen.remove(&oldest);
+            }
+        }
+        true
+    }
+}
+
+/// Senders of `WeightSync` messages waiting in the inbox, oldest first
+///
+/// The inbox carries messages without their sender, so `handle_message`
+/// looks the sender up by layer and sequence number. Peers number their
+/// messages independently and may reuse a pair; the inbox keeps arrival
+/// order, so the oldest entry for a pair belongs to the message handled
+/// next. Entries for messages nobody handles age out past the capacity.
+#[derive(Debug, Default)]
+struct WeightSyncSenders {
+    pending: VecDeque<(u8, u64, NetworkId)>,
+}
+
+impl WeightSyncSenders {
+    const CAPACITY: usize = 1024;
+
+    fn insert(&mut self, layer_id: u8, sequence: u64, sender: NetworkId) {
+        self.pending.push_back((layer_id, sequence, sender));
+        if self.pending.len() > Self::CAPACITY {
+            self.pending.pop_front();
+        }
+    }
+
+    /// Sender of the oldest pending weight sync for `layer_id` numbered `sequence`
+    fn take(&mut self, layer_id: u8, sequence: u64) -> Option<NetworkId> {
+        let position = self
+            .pending
+            .iter()
+            .position(|&(layer, seq, _)| (layer, seq) == (layer_id, sequence))?;
+        self.pending.remove(position).map(|(_, _, sender)| sender)
+    }
+}
+
+/// A known peer as recorded in a `NodeSnapshot`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    pub peer_id: NetworkId,
+    pub address: String,
+    pub port: u16,
+    pub capabilities: u32,
+    pub last_heartbeat: u64,
+    pub sequence_counter: u64,
+}
+
+/// Persistent state of a `DistributedNetwork` node
+///
+/// Restoring a snapshot keeps the node's identity, continues its sequence
+/// numbers and reloads its learned weights and known peers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeSnapshot {
+    pub id: NetworkId,
+    pub name: String,
+    pub address: String,
+    pub port: u16,
+    pub sequence: u64,
+    pub network: NeuralNetwork,
+    pub peers: Vec<PeerRecord>,
+}
+
+/// Role of a node in a primary/standby pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NodeRole {
+    /// Serves its logical network (the default for every node)
+    #[default]
+    Primary,
+    /// Mirrors the weights `primary` streams to it and takes over once it falls silent
+    Standby { primary: NetworkId },
+}
+
+/// Distributed Neural Network Node with optimized TCP protocol
+#[derive(Clone)]
+pub struct DistributedNetwork {
+    pub id: NetworkId,
+    pub info: NetworkInfo,
+    pub network: Arc<Mutex<NeuralNetwork>>,
+    pub connections: Arc<Mutex<HashMap<NetworkId, NetworkConnection>>>,
+    pub message_sender: mpsc::UnboundedSender<NetworkMessage>,
+    pub sequence_counter: Arc<Mutex<u64>>,
+    pub bind_options: BindOptions,
+    pub ack_policy: AckPolicy,
+    pub bandwidth: BandwidthTracker,
+    pub router: LatencyRouter,
+    /// Clock offsets of peers, refreshed by every acknowledged heartbeat
+    pub clock: ClockSync,
+    /// Per-peer send queues that let control messages overtake bulk data
+    pub send_lanes: PriorityLanes,
+    /// Per-peer cadence of outgoing activations
+    pub pacing: PeerPacing,
+    /// Named forward data channels of this node and of its peers
+    pub channels: ChannelTable,
+    /// Value ranges declared with `set_signal_ranges`
+    signal_ranges: SignalRanges,
+    /// Value ranges announced by peers, used to rescale outgoing forward data
+    peer_ranges: PeerRanges,
+    /// Cluster this node belongs to; peers from other namespaces are rejected
+    pub namespace: Option<String>,
+    /// Foreign namespaces whose peers are accepted anyway
+    pub bridged_namespaces: Vec<String>,
+    /// Reaction to peers whose declared layers do not fit this network
+    pub shape_policy: ShapePolicy,
+    /// Protocol version this node speaks (see `set_protocol_version`)
+    pub protocol_version: u8,
+    /// Weight of a peer's parameters when merging a `WeightSync` (see `set_weight_sync_blend`)
+    weight_sync_blend: f64,
+    weight_sync_senders: Arc<Mutex<WeightSyncSenders>>,
+    recent_deliveries: Arc<Mutex<RecentDeliveries>>,
+    subscriptions: Subscriptions,
+    role: Arc<Mutex<NodeRole>>,
+    last_seen: Arc<Mutex<HashMap<NetworkId, Instant>>>,
+    started: Instant,
+    model_version: Arc<Mutex<u64>>,
+    model_transfers: Arc<Mutex<ModelAssembler>>,
+    peer_versions: Arc<Mutex<HashMap<NetworkId, u8>>>,
+    activity: Arc<Mutex<NodeActivity>>,
+    health_checks: Arc<Mutex<Vec<HealthCheck>>>,
+}
+
+/// Latest outputs and training loss of a node
+#[derive(Debug, Default)]
+struct NodeActivity {
+    outputs: Vec<f32>,
+    loss: Option<f32>,
+}
+
+impl DistributedNetwork {
+    /// Create a new distributed neural network node
+    pub fn new(
+        name: String,
+        address: String,
+        port: u16,
+        network: NeuralNetwork,
+    ) -> (Self, mpsc::UnboundedReceiver<NetworkMessage>) {
+        let id = Uuid::new_v4();
+        let layers: Vec<u16> = network.get_layers().iter().map(|&x| x as u16).collect();
+
+        let capabilities = capabilities::FORWARD_PROPAGATION
+            | capabilities::BACKPROPAGATION
+            | capabilities::HEBBIAN_LEARNING
+            | capabilities::CORRELATION_ANALYSIS
+            | capabilities::MULTI_LAYER
+            | capabilities::REAL_TIME
+            | capabilities::ACKNOWLEDGED_DELIVERY
+            | capabilities::SPIKE_EVENTS;
 
         let info = NetworkInfo {
             id,
@@ -612,11 +1580,433 @@ impl DistributedNetwork {
             connections: Arc::new(Mutex::new(HashMap::new())),
             message_sender: sender,
             sequence_counter: Arc::new(Mutex::new(0)),
+            bind_options: BindOptions::default(),
+            ack_policy: AckPolicy::default(),
+            bandwidth: BandwidthTracker::new(),
+            router: LatencyRouter::default(),
+            clock: ClockSync::default(),
+            send_lanes: PriorityLanes::default(),
+            pacing: PeerPacing::default(),
+            channels: ChannelTable::default(),
+            signal_ranges: SignalRanges::default(),
+            peer_ranges: PeerRanges::default(),
+            namespace: None,
+            bridged_namespaces: Vec::new(),
+            shape_policy: ShapePolicy::default(),
+            protocol_version: PROTOCOL_VERSION,
+            weight_sync_blend: 1.0,
+            weight_sync_senders: Arc::new(Mutex::new(WeightSyncSenders::default())),
+            recent_deliveries: Arc::new(Mutex::new(RecentDeliveries::default())),
+            subscriptions: Subscriptions::default(),
+            role: Arc::new(Mutex::new(NodeRole::default())),
+            last_seen: Arc::new(Mutex::new(HashMap::new())),
+            started: Instant::now(),
+            model_version: Arc::new(Mutex::new(0)),
+            model_transfers: Arc::new(Mutex::new(ModelAssembler::default())),
+            peer_versions: Arc::new(Mutex::new(HashMap::new())),
+            activity: Arc::new(Mutex::new(NodeActivity::default())),
+            health_checks: Arc::new(Mutex::new(Vec::new())),
         };
 
         (distributed_network, receiver)
     }
 
+    /// Call `callback` for every forward data message received from a peer
+    ///
+    /// Messages that arrived before the first subscription are replayed to it
+    /// (see `Subscriptions`). Unlike the receiver returned by `new`, the event
+    /// carries the sending peer and the data is already converted to `f64`.
+    pub fn on_forward_data<F>(&self, callback: F)
+    where
+        F: Fn(&ForwardDataEvent) + Send + Sync + 'static,
+    {
+        self.subscriptions.on_forward_data(callback);
+    }
+
+    /// Call `callback` for every weight sync message received from a peer
+    pub fn on_weight_sync<F>(&self, callback: F)
+    where
+        F: Fn(&WeightSyncEvent) + Send + Sync + 'static,
+    {
+        self.subscriptions.on_weight_sync(callback);
+    }
+
+    /// Call `callback` whenever a peer leaves with a `Disconnect`
+    ///
+    /// By then the peer is already gone from `connections` and the router, so
+    /// routed sends made from the callback go to a standby replica.
+    pub fn on_disconnect<F>(&self, callback: F)
+    where
+        F: Fn(&DisconnectEvent) + Send + Sync + 'static,
+    {
+        self.subscriptions.on_disconnect(callback);
+    }
+
+    /// Typed subscriptions of this node, e.g. to resize their queues
+    pub fn subscriptions(&self) -> &Subscriptions {
+        &self.subscriptions
+    }
+
+    /// Replace the node's identity, e.g. with one loaded from an identity file
+    pub fn set_id(&mut self, id: NetworkId) {
+        self.id = id;
+        self.info.id = id;
+    }
+
+    /// Capture the node's identity, sequence counter, weights and peer table
+    pub fn to_snapshot(&self) -> NodeSnapshot {
+        let mut peers: Vec<PeerRecord> = self
+            .connections
+            .lock()
+            .unwrap()
+            .values()
+            .map(|connection| PeerRecord {
+                peer_id: connection.peer_id,
+                address: connection.address.clone(),
+                port: connection.port,
+                capabilities: connection.capabilities,
+                last_heartbeat: connection.last_heartbeat,
+                sequence_counter: connection.sequence_counter,
+            })
+            .collect();
+        peers.sort_by_key(|peer| peer.peer_id);
+
+        NodeSnapshot {
+            id: self.id,
+            name: self.info.name.clone(),
+            address: self.info.address.clone(),
+            port: self.info.port,
+            sequence: *self.sequence_counter.lock().unwrap(),
+            network: self.network.lock().unwrap().clone(),
+            peers,
+        }
+    }
+
+    /// Recreate a node from a snapshot
+    pub fn from_snapshot(snapshot: NodeSnapshot) -> (Self, mpsc::UnboundedReceiver<NetworkMessage>) {
+        let (mut node, receiver) = Self::new(
+            snapshot.name,
+            snapshot.address,
+            snapshot.port,
+            snapshot.network,
+        );
+        node.set_id(snapshot.id);
+        *node.sequence_counter.lock().unwrap() = snapshot.sequence;
+
+        {
+            let mut connections = node.connections.lock().unwrap();
+            for peer in snapshot.peers {
+                connections.insert(
+                    peer.peer_id,
+                    NetworkConnection {
+                        peer_id: peer.peer_id,
+                        stream: None,
+                        capabilities: peer.capabilities,
+                        last_heartbeat: peer.last_heartbeat,
+                        sequence_counter: peer.sequence_counter,
+                        address: peer.address,
+                        port: peer.port,
+                    },
+                );
+            }
+        }
+
+        (node, receiver)
+    }
+
+    /// Persist the node state to a JSON file
+    ///
+    /// The file is written next to its destination, synced and renamed into
+    /// place, so a crash mid-write never leaves a truncated snapshot behind.
+    pub fn snapshot<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::to_string_pretty(&self.to_snapshot())?;
+        atomic_file::write_atomic(path, json.as_bytes())?;
+        Ok(())
+    }
+
+    /// Restore a node from a file written by `snapshot()` or `snapshot_async()`
+    pub fn restore<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<(Self, mpsc::UnboundedReceiver<NetworkMessage>), Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        let json = crate::async_io::decode(std::fs::read(path)?)?;
+        let snapshot: NodeSnapshot =
+            serde_json::from_slice(&json).map_err(|e| atomic_file::check_json(path, e))?;
+        Ok(Self::from_snapshot(snapshot))
+    }
+
+    /// `snapshot()` for async code: written through `tokio::fs` without blocking the runtime
+    pub async fn snapshot_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        compression: Compression,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let json = serde_json::to_vec_pretty(&self.to_snapshot())?;
+        crate::async_io::write_atomic(path.as_ref(), json, compression).await?;
+        Ok(())
+    }
+
+    /// `restore()` for async code, reading compressed or plain snapshots
+    pub async fn restore_async<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<
+        (Self, mpsc::UnboundedReceiver<NetworkMessage>),
+        Box<dyn std::error::Error + Send + Sync>,
+    > {
+        let path = path.as_ref();
+        let json = crate::async_io::read(path).await?;
+        let snapshot: NodeSnapshot =
+            serde_json::from_slice(&json).map_err(|e| atomic_file::check_json(path, e))?;
+        Ok(Self::from_snapshot(snapshot))
+    }
+
+    /// Join a namespace so the node only talks to peers of the same cluster
+    pub fn set_namespace(&mut self, namespace: Option<String>) -> Result<(), String> {
+        if let Some(namespace) = &namespace {
+            check_namespace(namespace)?;
+        }
+        self.namespace = namespace.filter(|ns| !ns.is_empty());
+        Ok(())
+    }
+
+    /// Accept peers from another namespace, bridging the two clusters
+    pub fn bridge_namespace(&mut self, namespace: &str) {
+        if !self.bridged_namespaces.iter().any(|ns| ns == namespace) {
+            self.bridged_namespaces.push(namespace.to_string());
+        }
+    }
+
+    /// Whether a peer announcing `namespace` may talk to this node
+    pub fn accepts_namespace(&self, namespace: Option<&str>) -> bool {
+        let namespace = namespace.filter(|ns| !ns.is_empty());
+        namespace == self.namespace.as_deref()
+            || namespace.is_some_and(|ns| self.bridged_namespaces.iter().any(|bridged| bridged == ns))
+    }
+
+    /// Let handshaken peers replace the active model with `push_model`
+    ///
+    /// Off by default, since a pushed model takes over everything the node
+    /// serves; enable it only on segments whose peers are trusted to deploy.
+    pub fn set_model_deployment(&mut self, enabled: bool) {
+        if enabled {
+            self.info.capabilities |= capabilities::MODEL_DEPLOYMENT;
+        } else {
+            self.info.capabilities &= !capabilities::MODEL_DEPLOYMENT;
+        }
+    }
+
+    /// Reject or adapt to peers whose declared layer sizes do not fit this network
+    pub fn set_shape_policy(&mut self, policy: ShapePolicy) {
+        self.shape_policy = policy;
+    }
+
+    /// Blend received `WeightSync` layers into the local ones instead of replacing them
+    ///
+    /// Each merged layer becomes `(1 - blend) * local + blend * received`; the
+    /// default of 1 adopts the peer's layer as is. A standby replica always
+    /// mirrors its primary exactly.
+    pub fn set_weight_sync_blend(&mut self, blend: f64) -> Result<(), String> {
+        if !(blend > 0.0 && blend <= 1.0) {
+            return Err(format!("Weight sync blend must be in (0, 1], got {}", blend));
+        }
+        self.weight_sync_blend = blend;
+        Ok(())
+    }
+
+    /// Peers whose weights were merged into each layer, oldest first
+    ///
+    /// Saved with the model's metadata; see `ModelMetadata::peer_shares` for
+    /// how much of each layer every peer still accounts for.
+    pub fn weight_provenance(&self) -> BTreeMap<usize, Vec<LayerContribution>> {
+        self.network.lock().unwrap().metadata().provenance.clone()
+    }
+
+    /// Speak an older protocol version during a rolling upgrade
+    ///
+    /// Upgraded nodes pinned to the previous version stay readable by nodes
+    /// that are not upgraded yet; once every node runs the new build the pin
+    /// is lifted node by node. Peers always settle on the older version of
+    /// the two, so unpinned nodes keep talking to pinned ones.
+    pub fn set_protocol_version(&mut self, version: u8) -> Result<(), String> {
+        if !protocol_compat::is_supported(version) {
+            return Err(format!(
+                "Protocol version {} is not supported (expected {}..={})",
+                version,
+                protocol_compat::MIN_PROTOCOL_VERSION,
+                PROTOCOL_VERSION
+            ));
+        }
+        self.protocol_version = version;
+        Ok(())
+    }
+
+    /// Protocol version agreed with a peer at its last handshake
+    pub fn peer_protocol_version(&self, peer_id: NetworkId) -> Option<u8> {
+        self.peer_versions.lock().unwrap().get(&peer_id).copied()
+    }
+
+    /// Remember the version agreed with a peer, warning when it is an older one
+    fn record_peer_version(&self, peer_id: NetworkId, version: u8) {
+        let previous = self.peer_versions.lock().unwrap().insert(peer_id, version);
+        if version < PROTOCOL_VERSION && previous != Some(version) {
+            println!(
+                "⚠️ Peer {} speaks protocol v{}; translating messages for it",
+                peer_id, version
+            );
+        }
+    }
+
+    /// Why a peer's handshake must be refused, if it must
+    fn handshake_refusal(&self, namespace: Option<&str>, layers: &[u16]) -> Option<String> {
+        if !self.accepts_namespace(namespace) {
+            return Some(format!(
+                "Namespace {:?} is not accepted (local namespace {:?})",
+                namespace, self.namespace
+            ));
+        }
+        match compatibility::check_layers(&self.info.layers, layers) {
+            Err(reason) if self.shape_policy == ShapePolicy::Reject => Some(reason),
+            Err(reason) => {
+                println!("↔️ Adapting to peer activations: {}", reason);
+                None
+            }
+            Ok(()) => None,
+        }
+    }
+
+    fn handshake_message(&self) -> NetworkMessage {
+        NetworkMessage {
+            msg_type: MessageType::Handshake,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::Handshake {
+                network_id: self.id,
+                name: self.info.name.clone(),
+                layers: self.info.layers.clone(),
+                capabilities: self.info.capabilities,
+                namespace: self.namespace.clone(),
+                channels: self.channels.local(),
+                ranges: Some(self.signal_ranges).filter(|ranges| !ranges.is_empty()),
+            },
+            flags: 0,
+        }
+    }
+
+    /// Ranges announced in handshakes
+    pub fn signal_ranges(&self) -> SignalRanges {
+        self.signal_ranges
+    }
+
+    /// Declare the range this node's inputs expect and, optionally, the range its outputs take
+    ///
+    /// Peers sending forward data rescale it from their output range to the
+    /// declared input range, and warn when they cannot.
+    pub fn set_signal_ranges(&mut self, ranges: SignalRanges) {
+        self.signal_ranges = ranges;
+    }
+
+    /// Ranges a peer announced in its handshake or handshake acknowledgment
+    pub fn peer_ranges(&self, peer_id: NetworkId) -> Option<SignalRanges> {
+        self.peer_ranges.get(peer_id)
+    }
+
+    /// Set how the server binds and how peer hostnames are resolved
+    pub fn set_bind_options(&mut self, bind_options: BindOptions) {
+        self.bind_options = bind_options;
+    }
+
+    /// Enable or tune acknowledged (at-least-once) delivery of data messages
+    pub fn set_ack_policy(&mut self, ack_policy: AckPolicy) {
+        self.ack_policy = ack_policy;
+    }
+
+    /// Bytes and messages exchanged with every peer, by message type
+    pub fn bandwidth_stats(&self) -> Vec<PeerBandwidthStats> {
+        self.bandwidth.snapshot()
+    }
+
+    /// Cap outgoing bandwidth for every peer (`None` removes the cap)
+    pub fn set_bandwidth_limit(&self, limit: Option<BandwidthLimit>) {
+        self.bandwidth.set_default_limit(limit);
+    }
+
+    /// Cap outgoing bandwidth for a single peer, overriding the default cap
+    pub fn set_peer_bandwidth_limit(&self, peer_id: NetworkId, limit: Option<BandwidthLimit>) {
+        self.bandwidth.set_peer_limit(peer_id, limit);
+    }
+
+    /// Pace forward data to every peer at a steady cadence (`None` sends as soon as possible)
+    pub fn set_pacing(&self, pacing: Option<Pacing>) {
+        self.pacing.set_default(pacing);
+    }
+
+    /// Pace forward data to a single peer, overriding the default cadence
+    pub fn set_peer_pacing(&self, peer_id: NetworkId, pacing: Option<Pacing>) {
+        self.pacing.set_peer(peer_id, pacing);
+    }
+
+    /// Architecture and activity statistics of this node, as sent in an `InfoResponse`
+    pub fn local_info(&self) -> PeerInfo {
+        let (parameters, learning_mode, weight_norm) = {
+            let network = self.network.lock().unwrap();
+            (
+                network.num_parameters() as u64,
+                format!("{:?}", network.get_learning_mode()),
+                network.weight_norm() as f32,
+            )
+        };
+        let activity = self.activity.lock().unwrap();
+        let traffic = self.bandwidth.snapshot();
+        let messages = |direction: Direction| -> u64 {
+            traffic
+                .iter()
+                .flat_map(|peer| &peer.by_type)
+                .filter(|(dir, _, _)| *dir == direction)
+                .map(|(_, _, counter)| counter.messages)
+                .sum()
+        };
+
+        PeerInfo {
+            network_id: self.id,
+            name: self.info.name.clone(),
+            layers: self.info.layers.clone(),
+            parameters,
+            learning_mode,
+            uptime_secs: self.started.elapsed().as_secs(),
+            peers: self.connections.lock().unwrap().len() as u32,
+            messages_sent: messages(Direction::Sent),
+            messages_received: messages(Direction::Received),
+            bytes_sent: traffic.iter().map(|peer| peer.bytes_sent).sum(),
+            bytes_received: traffic.iter().map(|peer| peer.bytes_received).sum(),
+            weight_norm: Some(weight_norm),
+            loss: activity.loss,
+            outputs: activity.outputs.clone(),
+        }
+    }
+
+    /// Remember the outputs of a forward pass, reported to `Info` requests
+    pub fn record_outputs(&self, outputs: &[f64]) {
+        self.activity.lock().unwrap().outputs = outputs.iter().map(|&x| x as f32).collect();
+    }
+
+    /// Remember a training loss, reported to `Info` requests
+    pub fn record_loss(&self, loss: f64) {
+        self.activity.lock().unwrap().loss = Some(loss as f32);
+    }
+
+    /// Replace the latency router, e.g. to change its probe interval or failure threshold
+    pub fn set_send_lanes(&mut self, send_lanes: PriorityLanes) {
+        self.send_lanes = send_lanes;
+    }
+
+    pub fn set_router(&mut self, router: LatencyRouter) {
+        self.router = router;
+    }
+
+    /// Register a peer as one of the redundant replicas of a logical network
+    pub fn add_route(&self, logical_network: &str, peer_id: NetworkId) {
+        self.router.add_route(logical_network, peer_id);
+    }
+
     /// Get next sequence number for message ordering
     fn next_sequence(&self) -> u64 {
         let mut counter = self.sequence_counter.lock().unwrap();
@@ -626,16 +2016,15 @@ impl DistributedNetwork {
 
     /// Start the TCP server for incoming connections
     pub async fn start_server(&self) -> Result<(), ProtocolError> {
-        let addr = format!("{}:{}", self.info.address, self.info.port);
-        let listener = TcpListener::bind(&addr).await?;
+        let addr = transport::display_address(&self.info.address, self.info.port);
+        let listener =
+            NnpListener::bind(&self.info.address, self.info.port, self.bind_options).await?;
 
         println!("🚀 Neural Network Protocol server listening on {}", addr);
         println!("📡 Network ID: {}", self.id);
         println!("🧠 Capabilities: 0x{:08X}", self.info.capabilities);
 
-        let connections = self.connections.clone();
-        let message_sender = self.message_sender.clone();
-        let network_id = self.id;
+        let node = self.clone();
 
         tokio::spawn(async move {
             loop {
@@ -643,18 +2032,10 @@ impl DistributedNetwork {
                     Ok((stream, peer_addr)) => {
                         println!("🔗 New connection from {}", peer_addr);
 
-                        let connections_clone = connections.clone();
-                        let sender_clone = message_sender.clone();
+                        let node = node.clone();
 
                         tokio::spawn(async move {
-                            if let Err(e) = Self::handle_connection(
-                                stream,
-                                connections_clone,
-                                sender_clone,
-                                network_id,
-                            )
-                            .await
-                            {
+                            if let Err(e) = node.handle_connection(stream).await {
                                 println!("❌ Connection error: {:?}", e);
                             }
                         });
@@ -669,14 +2050,10 @@ impl DistributedNetwork {
         Ok(())
     }
 
-    /// Handle incoming TCP connection
-    async fn handle_connection(
-        mut stream: TcpStream,
-        connections: Arc<Mutex<HashMap<NetworkId, NetworkConnection>>>,
-        message_sender: mpsc::UnboundedSender<NetworkMessage>,
-        our_network_id: NetworkId,
-    ) -> Result<(), ProtocolError> {
+    /// Handle incoming TCP or Unix socket connection
+    async fn handle_connection(self, mut stream: NnpStream) -> Result<(), ProtocolError> {
         let mut buffer = vec![0u8; 8192]; // 8KB buffer for incoming messages
+        let mut peer_id: Option<NetworkId> = None;
 
         loop {
             // Read message header first
@@ -708,14 +2085,88 @@ impl DistributedNetwork {
             stream
                 .read_exact(&mut buffer[HEADER_SIZE..total_len])
                 .await?;
+            let received_at = clock::now_micros();
 
             // Parse the complete message
             match NetworkMessage::from_bytes(&buffer[..total_len]) {
                 Ok(message) => {
                     println!("📥 Received message: {:?}", message.msg_type);
 
+                    // Replies are written in the version the peer speaks
+                    let peer_version = protocol_compat::negotiate(
+                        self.protocol_version,
+                        NetworkMessage::frame_version(&header_buf),
+                    );
+
                     // Handle handshake messages specially
                     if let MessagePayload::Handshake { network_id, .. } = &message.payload {
+                        peer_id = Some(*network_id);
+                        self.record_peer_version(*network_id, peer_version);
+                        let mut recent = self.recent_deliveries.lock().unwrap();
+                        if recent.observe_handshake(*network_id, message.sequence) {
+                            println!("🔄 Peer {} restarted its sequence numbers", network_id);
+                        }
+                    }
+                    let sender = peer_id.unwrap_or_default();
+                    self.bandwidth
+                        .record(sender, Direction::Received, message.msg_type, total_len);
+                    if peer_id.is_some() {
+                        self.last_seen.lock().unwrap().insert(sender, Instant::now());
+                    }
+
+                    // Health probes come from orchestrators, which never handshake
+                    if let MessagePayload::Ping = message.payload {
+                        let reply = NetworkMessage {
+                            msg_type: MessageType::Pong,
+                            sequence: self.next_sequence(),
+                            payload: MessagePayload::Pong {
+                                report: self.health(),
+                            },
+                            flags: 0,
+                        };
+                        let reply_bytes = reply.to_bytes_for(peer_version)?;
+                        stream.write_all(&reply_bytes).await?;
+                        self.bandwidth.record(
+                            sender,
+                            Direction::Sent,
+                            MessageType::Pong,
+                            reply_bytes.len(),
+                        );
+                        continue;
+                    }
+
+                    // In a namespace, only peers that identified themselves may send data
+                    if peer_id.is_none() && self.namespace.is_some() {
+                        println!("⛔ Dropping {:?} received before handshake", message.msg_type);
+                        continue;
+                    }
+
+                    if let MessagePayload::Handshake {
+                        network_id,
+                        namespace,
+                        layers,
+                        channels,
+                        ranges,
+                        ..
+                    } = &message.payload
+                    {
+                        if let Some(reason) = self.handshake_refusal(namespace.as_deref(), layers) {
+                            println!("⛔ Rejecting peer {}: {}", network_id, reason);
+                            let reject = NetworkMessage {
+                                msg_type: MessageType::HandshakeAck,
+                                sequence: 1,
+                                payload: MessagePayload::HandshakeAck {
+                                    network_id: self.id,
+                                    accepted: false,
+                                    reason: Some(reason),
+                                    ranges: None,
+                                },
+                                flags: 0,
+                            };
+                            stream.write_all(&reject.to_bytes_for(peer_version)?).await?;
+                            break;
+                        }
+
                         // Store connection info
                         let connection = NetworkConnection {
                             peer_id: *network_id,
@@ -731,29 +2182,204 @@ impl DistributedNetwork {
                         };
 
                         {
-                            let mut conns = connections.lock().unwrap();
+                            let mut conns = self.connections.lock().unwrap();
                             conns.insert(*network_id, connection);
                         }
+                        self.channels.set_peer(*network_id, channels.clone());
+                        self.peer_ranges.record(*network_id, ranges.unwrap_or_default());
 
                         // Send handshake acknowledgment
                         let ack_message = NetworkMessage {
                             msg_type: MessageType::HandshakeAck,
                             sequence: 1,
                             payload: MessagePayload::HandshakeAck {
-                                network_id: our_network_id,
+                                network_id: self.id,
                                 accepted: true,
+                                reason: None,
+                                ranges: Some(self.signal_ranges).filter(|ranges| !ranges.is_empty()),
                             },
+                            flags: 0,
                         };
 
-                        let ack_bytes = ack_message.to_bytes();
+                        let ack_bytes = ack_message.to_bytes_for(peer_version)?;
                         stream.write_all(&ack_bytes).await?;
+                        self.bandwidth.record(
+                            sender,
+                            Direction::Sent,
+                            MessageType::HandshakeAck,
+                            ack_bytes.len(),
+                        );
                         println!("📤 Sent handshake acknowledgment");
                     }
 
-                    // Forward message to main handler
-                    if message_sender.send(message).is_err() {
-                        println!("❌ Failed to forward message to handler");
-                        break;
+                    // Introspection requests are answered inline and not forwarded
+                    if let MessagePayload::Info = message.payload {
+                        let reply = NetworkMessage {
+                            msg_type: MessageType::InfoResponse,
+                            sequence: self.next_sequence(),
+                            payload: MessagePayload::InfoResponse {
+                                info: self.local_info(),
+                            },
+                            flags: 0,
+                        };
+                        let reply_bytes = reply.to_bytes_for(peer_version)?;
+                        stream.write_all(&reply_bytes).await?;
+                        self.bandwidth.record(
+                            sender,
+                            Direction::Sent,
+                            MessageType::InfoResponse,
+                            reply_bytes.len(),
+                        );
+                        continue;
+                    }
+
+                    // Model chunks are reassembled here; the last one is answered inline
+                    if let MessagePayload::ModelPush { chunk } = message.payload {
+                        let transfer_id = chunk.transfer_id;
+                        let deployable = self.info.capabilities & capabilities::MODEL_DEPLOYMENT != 0;
+                        let refusal = if !deployable {
+                            Some("Model deployment is not enabled on this node")
+                        } else if peer_id.is_none() {
+                            Some("Models are only accepted from peers that handshook")
+                        } else {
+                            None
+                        };
+                        let received = match refusal {
+                            Some(reason) => {
+                                println!("⛔ Refusing model chunk from {}: {}", sender, reason);
+                                (chunk.index + 1 == chunk.count).then(|| Err(reason.to_string()))
+                            }
+                            None => self.model_transfers.lock().unwrap().receive(sender, chunk),
+                        };
+                        let Some(received) = received else {
+                            continue;
+                        };
+                        let outcome = received.and_then(|(version, network)| {
+                            self.install_model(sender, version, network)
+                        });
+                        let reply = NetworkMessage {
+                            msg_type: MessageType::ModelPushAck,
+                            sequence: self.next_sequence(),
+                            payload: MessagePayload::ModelPushAck {
+                                transfer_id,
+                                accepted: outcome.is_ok(),
+                                previous_version: outcome
+                                    .as_ref()
+                                    .copied()
+                                    .unwrap_or_else(|_| self.model_version()),
+                                reason: outcome.err(),
+                            },
+                            flags: 0,
+                        };
+                        let reply_bytes = reply.to_bytes_for(peer_version)?;
+                        stream.write_all(&reply_bytes).await?;
+                        self.bandwidth.record(
+                            sender,
+                            Direction::Sent,
+                            MessageType::ModelPushAck,
+                            reply_bytes.len(),
+                        );
+                        continue;
+                    }
+
+                    if let MessagePayload::ConfigUpdate { update, .. } = &message.payload {
+                        println!(
+                            "⛔ Ignoring config update {:?} from {}: remote configuration requires secure mode",
+                            update, sender
+                        );
+                        continue;
+                    }
+
+                    // Acknowledged only once the message has been handed on
+                    let ack = message.requires_ack().then(|| NetworkMessage {
+                        msg_type: MessageType::Ack,
+                        sequence: self.next_sequence(),
+                        payload: MessagePayload::Ack {
+                            sequence: message.sequence,
+                            timestamps: matches!(message.payload, MessagePayload::Heartbeat { .. })
+                                .then(|| (received_at, clock::now_micros())),
+                        },
+                        flags: 0,
+                    });
+
+                    // Retransmissions of an already delivered message are acked but not re-processed
+                    let sequence = message.sequence;
+                    if let Some(ack) = &ack {
+                        if self.recent_deliveries.lock().unwrap().contains(sender, sequence) {
+                            println!("♻️ Dropping duplicate message {} from {}", sequence, sender);
+                            self.write_ack(&mut stream, sender, ack, peer_version).await?;
+                            continue;
+                        }
+                    }
+
+                    if let MessagePayload::Disconnect { reason } = &message.payload {
+                        println!("👋 Peer {} left: {}", sender, reason);
+                        self.forget_peer(sender);
+                    }
+                    let channel = match &message.payload {
+                        MessagePayload::ForwardData {
+                            channel: Some(tag), ..
+                        } => self.channels.resolve(sender, *tag),
+                        _ => None,
+                    };
+                    let subscribed =
+                        self.subscriptions.dispatch_on(sender, &message, channel.clone());
+                    if channel.is_some_and(|name| self.channels.is_diverted(&name)) {
+                        if let (Some(ack), true) = (&ack, subscribed) {
+                            self.recent_deliveries.lock().unwrap().insert(sender, sequence);
+                            self.write_ack(&mut stream, sender, ack, peer_version).await?;
+                        }
+                        continue;
+                    }
+
+                    // A standby mirrors its primary without waiting for the inbox to be consumed
+                    if let (
+                        NodeRole::Standby { primary },
+                        MessagePayload::WeightSync {
+                            layer_id,
+                            weights,
+                            biases,
+                        },
+                    ) = (self.role(), &message.payload)
+                    {
+                        if primary == sender {
+                            if let Err(e) =
+                                self.apply_weight_sync(Some(sender), *layer_id, weights, biases, 1.0)
+                            {
+                                println!("❌ Rejected replicated layer {}: {}", layer_id, e);
+                            }
+                        }
+                    }
+
+                    // Remember who sent each weight sync so `handle_message` can
+                    // credit the peer in the model's provenance
+                    if let MessagePayload::WeightSync { layer_id, .. } = &message.payload {
+                        if !self.message_sender.is_closed() {
+                            self.weight_sync_senders.lock().unwrap().insert(
+                                *layer_id,
+                                message.sequence,
+                                sender,
+                            );
+                        }
+                    }
+
+                    // Forward message to main handler; nodes that only use
+                    // subscriptions may have dropped the receiver. The ack
+                    // promises the message was taken in, so it is only sent
+                    // once the inbox or a subscription holds it; heartbeats
+                    // need no consumer, their ack is the reply.
+                    let heartbeat = matches!(message.payload, MessagePayload::Heartbeat { .. });
+                    let queued = self.message_sender.send(message).is_ok();
+                    if let Some(ack) = &ack {
+                        if queued || subscribed || heartbeat {
+                            self.recent_deliveries.lock().unwrap().insert(sender, sequence);
+                            self.write_ack(&mut stream, sender, ack, peer_version).await?;
+                        } else {
+                            println!(
+                                "⚠️ Not acknowledging message {} from {}: nothing takes it in",
+                                sequence, sender
+                            );
+                        }
                     }
                 }
                 Err(e) => {
@@ -766,46 +2392,67 @@ impl DistributedNetwork {
         Ok(())
     }
 
+    /// Write an acknowledgment on the stream the acknowledged message arrived on
+    async fn write_ack(
+        &self,
+        stream: &mut NnpStream,
+        sender: NetworkId,
+        ack: &NetworkMessage,
+        peer_version: u8,
+    ) -> Result<(), ProtocolError> {
+        let ack_bytes = ack.to_bytes_for(peer_version)?;
+        stream.write_all(&ack_bytes).await?;
+        self.bandwidth
+            .record(sender, Direction::Sent, MessageType::Ack, ack_bytes.len());
+        Ok(())
+    }
+
+    /// Refuse pushed models larger than `bytes` serialized bytes
+    ///
+    /// Abandons transfers in progress; the default is `model_push::DEFAULT_MAX_MODEL_SIZE`.
+    pub fn set_max_model_size(&self, bytes: usize) {
+        *self.model_transfers.lock().unwrap() = ModelAssembler::with_max_size(bytes);
+    }
+
     /// Connect to a remote neural network
     pub async fn connect_to(&self, address: &str, port: u16) -> Result<NetworkId, ProtocolError> {
-        let addr = format!("{}:{}", address, port);
+        let addr = transport::display_address(address, port);
         println!("🔗 Connecting to neural network at {}", addr);
 
-        let mut stream = TcpStream::connect(&addr).await?;
+        let mut stream = NnpStream::connect(address, port, self.bind_options.preference).await?;
 
         // Send handshake
-        let handshake = NetworkMessage {
-            msg_type: MessageType::Handshake,
-            sequence: self.next_sequence(),
-            payload: MessagePayload::Handshake {
-                network_id: self.id,
-                name: self.info.name.clone(),
-                layers: self.info.layers.clone(),
-                capabilities: self.info.capabilities,
-            },
-        };
+        let handshake = self.handshake_message();
 
-        let handshake_bytes = handshake.to_bytes();
+        let handshake_bytes = handshake.to_bytes_for(self.protocol_version)?;
         stream.write_all(&handshake_bytes).await?;
 
         // Wait for handshake acknowledgment
-        let mut header_buf = [0u8; HEADER_SIZE];
-        stream.read_exact(&mut header_buf).await?;
-
-        let payload_len = BigEndian::read_u32(&header_buf[6..10]) as usize;
-        let mut full_message = vec![0u8; HEADER_SIZE + payload_len];
-        full_message[..HEADER_SIZE].copy_from_slice(&header_buf);
-        stream.read_exact(&mut full_message[HEADER_SIZE..]).await?;
-
-        match NetworkMessage::from_bytes(&full_message) {
-            Ok(ack_message) => {
+        match Self::read_versioned_message(&mut stream).await {
+            Ok((ack_message, ack_len, ack_version)) => {
                 if let MessagePayload::HandshakeAck {
                     network_id,
                     accepted,
+                    reason,
+                    ranges,
                 } = ack_message.payload
                 {
+                    self.bandwidth.record(
+                        network_id,
+                        Direction::Sent,
+                        MessageType::Handshake,
+                        handshake_bytes.len(),
+                    );
+                    self.bandwidth
+                        .record(network_id, Direction::Received, ack_message.msg_type, ack_len);
+
                     if accepted {
                         println!("✅ Connected to network {}", network_id);
+                        self.peer_ranges.record(network_id, ranges.unwrap_or_default());
+                        self.record_peer_version(
+                            network_id,
+                            protocol_compat::negotiate(self.protocol_version, ack_version),
+                        );
 
                         // Store connection
                         let connection = NetworkConnection {
@@ -828,7 +2475,12 @@ impl DistributedNetwork {
 
                         return Ok(network_id);
                     } else {
-                        return Err(ProtocolError::InvalidPayload);
+                        println!(
+                            "⛔ Handshake rejected by network {}: {}",
+                            network_id,
+                            reason.as_deref().unwrap_or("no reason given")
+                        );
+                        return Err(ProtocolError::HandshakeRejected(reason));
                     }
                 }
             }
@@ -845,6 +2497,19 @@ impl DistributedNetwork {
         layer_id: u8,
         data: Vec<f64>,
     ) -> Result<(), ProtocolError> {
+        self.send_forward_data_flagged(peer_id, layer_id, data, 0).await
+    }
+
+    /// Send forward data with `message_flags` set, e.g. `STALE`
+    pub async fn send_forward_data_flagged(
+        &self,
+        peer_id: NetworkId,
+        layer_id: u8,
+        data: Vec<f64>,
+        flags: u8,
+    ) -> Result<(), ProtocolError> {
+        let mut data = data;
+        self.peer_ranges.fit(peer_id, &self.signal_ranges, &mut data);
         let data_f32: Vec<f32> = data.iter().map(|&x| x as f32).collect();
 
         let message = NetworkMessage {
@@ -853,7 +2518,348 @@ impl DistributedNetwork {
             payload: MessagePayload::ForwardData {
                 layer_id,
                 data: data_f32,
+                timestamp: Some(self.clock.to_peer_time(peer_id, clock::now_micros())),
+                channel: None,
+            },
+            flags,
+        };
+
+        self.send_message_to_peer(peer_id, message).await
+    }
+
+    /// Send forward data tagged with the named channel, so one connection can carry several streams
+    ///
+    /// The channel is added to this node's table on first use; receivers
+    /// learn the names from the handshake and report them in `ForwardDataEvent`.
+    pub async fn send_forward_data_on(
+        &self,
+        peer_id: NetworkId,
+        channel: &str,
+        layer_id: u8,
+        data: Vec<f64>,
+    ) -> Result<(), ProtocolError> {
+        let tag = self.channels.register(channel).map_err(|e| {
+            println!("❌ {}", e);
+            ProtocolError::InvalidPayload
+        })?;
+        let mut data = data;
+        self.peer_ranges.fit(peer_id, &self.signal_ranges, &mut data);
+
+        let message = NetworkMessage {
+            msg_type: MessageType::ForwardData,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::ForwardData {
+                layer_id,
+                data: data.iter().map(|&x| x as f32).collect(),
+                timestamp: Some(self.clock.to_peer_time(peer_id, clock::now_micros())),
+                channel: Some(tag),
             },
+            flags: 0,
+        };
+
+        self.send_message_to_peer(peer_id, message).await
+    }
+
+    /// Send forward propagation data to the fastest healthy replica of a logical network
+    ///
+    /// Replicas are tried in latency order; a failed delivery marks the peer
+    /// as failing and falls over to the next one. Returns the peer that
+    /// accepted the data.
+    pub async fn send_forward_data_routed(
+        &self,
+        logical_network: &str,
+        layer_id: u8,
+        data: Vec<f64>,
+    ) -> Result<NetworkId, ProtocolError> {
+        let candidates = self.router.candidates(logical_network);
+        if candidates.is_empty() {
+            return Err(ProtocolError::PeerNotFound);
+        }
+
+        let mut last_error = ProtocolError::PeerNotFound;
+        for peer_id in candidates {
+            match self.send_forward_data(peer_id, layer_id, data.clone()).await {
+                Ok(()) => return Ok(peer_id),
+                Err(e) => {
+                    println!("↪️ Route '{}' failing over from {}: {:?}", logical_network, peer_id, e);
+                    self.router.record_failure(peer_id);
+                    last_error = e;
+                }
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Measure the round-trip time to a peer with an acknowledged heartbeat
+    ///
+    /// Returns `None` when the peer speaks a protocol version without acks:
+    /// the heartbeat is delivered, but nothing was measured.
+    pub async fn ping_peer(&self, peer_id: NetworkId) -> Result<Option<Duration>, ProtocolError> {
+        let (address, port) = {
+            let connections = self.connections.lock().unwrap();
+            let connection = connections.get(&peer_id).ok_or(ProtocolError::PeerNotFound)?;
+            (connection.address.clone(), connection.port)
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let message = NetworkMessage {
+            msg_type: MessageType::Heartbeat,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::Heartbeat { timestamp },
+            flags: message_flags::ACK_REQUIRED,
+        };
+
+        self.deliver_to_peer(peer_id, &address, port, &message).await
+    }
+
+    /// Ask a peer for its architecture, parameter count, learning mode, uptime and traffic
+    ///
+    /// The request is a control message, so it overtakes queued bulk data;
+    /// the peer must answer within the ack policy timeout.
+    pub async fn query_peer_info(&self, peer_id: NetworkId) -> Result<PeerInfo, ProtocolError> {
+        let (address, port) = {
+            let connections = self.connections.lock().unwrap();
+            let connection = connections.get(&peer_id).ok_or(ProtocolError::PeerNotFound)?;
+            (connection.address.clone(), connection.port)
+        };
+
+        let _permit = self.send_lanes.acquire(peer_id, Priority::Control).await;
+        let (mut stream, version) = self.open_session(peer_id, &address, port).await?;
+        let request = NetworkMessage {
+            msg_type: MessageType::Info,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::Info,
+            flags: 0,
+        };
+        let request_bytes = request.to_bytes_for(version).inspect_err(|e| {
+            println!("⚠️ Cannot query peer {}: {:?}", peer_id, e);
+        })?;
+        stream.write_all(&request_bytes).await?;
+        self.bandwidth
+            .record(peer_id, Direction::Sent, MessageType::Info, request_bytes.len());
+
+        let (info, reply_len) =
+            tokio::time::timeout(self.ack_policy.timeout, read_info_response(&mut stream))
+                .await
+                .map_err(|_| ProtocolError::AckTimeout)??;
+        self.bandwidth
+            .record(peer_id, Direction::Received, MessageType::InfoResponse, reply_len);
+        Ok(info)
+    }
+
+    /// Report another component in `health`, e.g. a queue owned by a wrapping node
+    pub fn add_health_check<F>(&self, check: F)
+    where
+        F: Fn() -> ComponentHealth + Send + Sync + 'static,
+    {
+        self.health_checks.lock().unwrap().push(Arc::new(check));
+    }
+
+    /// Readiness of this node, as answered to `Ping`
+    ///
+    /// A standby replica is not ready until it takes over, and neither is a
+    /// node whose send queues hold more than `DEFAULT_QUEUE_THRESHOLD` data
+    /// messages. Registered health checks follow the built-in components.
+    pub fn health(&self) -> HealthReport {
+        let peers: Vec<NetworkId> = self.connections.lock().unwrap().keys().copied().collect();
+        let queued = peers.iter().map(|&peer| self.send_lanes.queued(peer).1).sum();
+
+        let mut report = HealthReport::new();
+        report.push(match self.role() {
+            NodeRole::Primary => ComponentHealth::new("role", true, "primary"),
+            NodeRole::Standby { primary } => {
+                ComponentHealth::new("role", false, format!("standby for {}", primary))
+            }
+        });
+        report.push(ComponentHealth::new(
+            "peers",
+            true,
+            format!("{} connected", peers.len()),
+        ));
+        report.push(ComponentHealth::queue(
+            "send_queue",
+            queued,
+            DEFAULT_QUEUE_THRESHOLD,
+        ));
+        for check in self.health_checks.lock().unwrap().iter() {
+            report.push(check());
+        }
+        report
+    }
+
+    /// Version of the model currently active on this node (0 until one is pushed)
+    pub fn model_version(&self) -> u64 {
+        *self.model_version.lock().unwrap()
+    }
+
+    /// Push a trained model to a peer, which hot-swaps it in as `version`
+    ///
+    /// The model travels in checksummed `ModelPush` chunks over one
+    /// connection, as bulk data behind queued control messages. The peer
+    /// refuses models unless it enabled `set_model_deployment`, as well as
+    /// models whose layers differ from its own and versions that are not
+    /// newer than its active one. Returns the peer's previous version.
+    pub async fn push_model(
+        &self,
+        peer_id: NetworkId,
+        network: &NeuralNetwork,
+        version: u64,
+    ) -> Result<u64, ProtocolError> {
+        let (address, port) = {
+            let connections = self.connections.lock().unwrap();
+            let connection = connections.get(&peer_id).ok_or(ProtocolError::PeerNotFound)?;
+            (connection.address.clone(), connection.port)
+        };
+        let transfer_id = self.next_sequence();
+        let chunks =
+            model_push::split_model(network, transfer_id, version).map_err(|e| {
+                println!("❌ {}", e);
+                ProtocolError::InvalidPayload
+            })?;
+
+        let _permit = self.send_lanes.acquire(peer_id, Priority::Data).await;
+        let (mut stream, protocol) = self.open_session(peer_id, &address, port).await?;
+        if !protocol_compat::supports_type(protocol, MessageType::ModelPush) {
+            println!("⚠️ Cannot push a model to peer {} speaking protocol v{}", peer_id, protocol);
+            return Err(ProtocolError::Untranslatable {
+                msg_type: MessageType::ModelPush,
+                version: protocol,
+            });
+        }
+        println!(
+            "🚚 Pushing model v{} to {} in {} chunks",
+            version,
+            peer_id,
+            chunks.len()
+        );
+        for chunk in chunks {
+            let message = NetworkMessage {
+                msg_type: MessageType::ModelPush,
+                sequence: self.next_sequence(),
+                payload: MessagePayload::ModelPush { chunk },
+                flags: 0,
+            };
+            let message_bytes = message.to_bytes_for(protocol)?;
+            self.bandwidth.throttle(peer_id, message_bytes.len()).await;
+            stream.write_all(&message_bytes).await?;
+            self.bandwidth
+                .record(peer_id, Direction::Sent, MessageType::ModelPush, message_bytes.len());
+        }
+
+        let wait_for_ack = async {
+            loop {
+                let (reply, reply_len) = Self::read_message(&mut stream).await?;
+                self.bandwidth
+                    .record(peer_id, Direction::Received, reply.msg_type, reply_len);
+                if let MessagePayload::ModelPushAck {
+                    transfer_id: acked,
+                    accepted,
+                    previous_version,
+                    reason,
+                } = reply.payload
+                {
+                    if acked != transfer_id {
+                        continue;
+                    }
+                    return if accepted {
+                        Ok(previous_version)
+                    } else {
+                        Err(ProtocolError::ModelRejected(
+                            reason.unwrap_or_else(|| "no reason given".to_string()),
+                        ))
+                    };
+                }
+            }
+        };
+        match tokio::time::timeout(self.ack_policy.timeout, wait_for_ack).await {
+            Ok(result) => result,
+            Err(_) => Err(ProtocolError::AckTimeout),
+        }
+    }
+
+    /// Swap in a pushed model, returning the version it replaced
+    fn install_model(
+        &self,
+        sender: NetworkId,
+        version: u64,
+        network: NeuralNetwork,
+    ) -> Result<u64, String> {
+        let mut current_version = self.model_version.lock().unwrap();
+        let mut current = self.network.lock().unwrap();
+        if network.get_layers() != current.get_layers() {
+            return Err(format!(
+                "Pushed model has layers {:?}, the active model {:?}",
+                network.get_layers(),
+                current.get_layers()
+            ));
+        }
+        if version <= *current_version {
+            return Err(format!(
+                "Pushed model v{} is not newer than the active v{}",
+                version, *current_version
+            ));
+        }
+
+        let mut network = network;
+        for layer in 0..network.num_layers() - 1 {
+            network
+                .metadata_mut()
+                .record_contribution(layer, &sender.to_string(), 1.0);
+        }
+        *current = network;
+        let previous = std::mem::replace(&mut *current_version, version);
+        println!("🔄 Activated model v{} from {} (was v{})", version, sender, previous);
+        Ok(previous)
+    }
+
+    /// Estimate the clock offset of a peer from `rounds` heartbeat exchanges
+    ///
+    /// Returns the peer's clock minus ours in microseconds. Forward and Hebbian
+    /// data sent afterwards carry timestamps converted to the peer's clock.
+    pub async fn sync_clock(&self, peer_id: NetworkId, rounds: usize) -> Result<i64, ProtocolError> {
+        for _ in 0..rounds.max(1) {
+            self.ping_peer(peer_id).await?;
+        }
+        self.clock.offset(peer_id).ok_or(ProtocolError::InvalidPayload)
+    }
+
+    /// Periodically ping every routed peer and feed the results into the router
+    pub fn start_latency_probes(&self) -> tokio::task::JoinHandle<()> {
+        let node = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(node.router.config().probe_interval);
+            loop {
+                interval.tick().await;
+                for peer_id in node.router.peers() {
+                    match node.ping_peer(peer_id).await {
+                        Ok(Some(rtt)) => node.router.record_rtt(peer_id, rtt),
+                        // Unacknowledged probes leave the peer unmeasured
+                        Ok(None) => {}
+                        Err(e) => {
+                            println!("💔 Heartbeat to {} failed: {:?}", peer_id, e);
+                            node.router.record_failure(peer_id);
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    /// Send an arbitrary payload to a connected network with a fresh sequence number
+    pub async fn send_payload(
+        &self,
+        peer_id: NetworkId,
+        payload: MessagePayload,
+    ) -> Result<(), ProtocolError> {
+        let message = NetworkMessage {
+            msg_type: payload.message_type(),
+            sequence: self.next_sequence(),
+            payload,
+            flags: 0,
         };
 
         self.send_message_to_peer(peer_id, message).await
@@ -876,20 +2882,267 @@ impl DistributedNetwork {
                 layer_id,
                 correlations: correlations_f32,
                 learning_rate: learning_rate as f32,
+                timestamp: Some(self.clock.to_peer_time(peer_id, clock::now_micros())),
+            },
+            flags: 0,
+        };
+
+        self.send_message_to_peer(peer_id, message).await
+    }
+
+    /// Send weights and biases of a layer to a connected network
+    pub async fn send_weight_sync(
+        &self,
+        peer_id: NetworkId,
+        layer_id: u8,
+        weights: Vec<f64>,
+        biases: Vec<f64>,
+    ) -> Result<(), ProtocolError> {
+        let message = NetworkMessage {
+            msg_type: MessageType::WeightSync,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::WeightSync {
+                layer_id,
+                weights: weights.iter().map(|&x| x as f32).collect(),
+                biases: biases.iter().map(|&x| x as f32).collect(),
             },
+            flags: 0,
+        };
+
+        self.send_message_to_peer(peer_id, message).await
+    }
+
+    /// Send spike events of a layer to a connected network
+    ///
+    /// `events` carry local timestamps; they are converted to the peer's clock
+    /// with the current offset estimate (see `sync_clock`).
+    pub async fn send_spike_events(
+        &self,
+        peer_id: NetworkId,
+        layer_id: u8,
+        events: Vec<SpikeEvent>,
+    ) -> Result<(), ProtocolError> {
+        let events = events
+            .into_iter()
+            .map(|event| SpikeEvent {
+                timestamp: self.clock.to_peer_time(peer_id, event.timestamp),
+                ..event
+            })
+            .collect();
+        let message = NetworkMessage {
+            msg_type: MessageType::SpikeEvents,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::SpikeEvents { layer_id, events },
+            flags: 0,
         };
 
         self.send_message_to_peer(peer_id, message).await
     }
 
+    /// Leave the cluster gracefully, handing this node's state to its peers
+    ///
+    /// With `handoff_weights`, every connected peer first receives a
+    /// `WeightSync` of each layer so a standby replica can take over the
+    /// learned state. Every peer then receives a `Disconnect` carrying `reason`,
+    /// removes this node from its routes and fails over to other replicas
+    /// before any activation is sent here. Returns the peers that were told;
+    /// the local connections and routes are cleared either way.
+    pub async fn farewell(&self, reason: &str, handoff_weights: bool) -> Vec<NetworkId> {
+        let peers: Vec<NetworkId> = self.connections.lock().unwrap().keys().copied().collect();
+        let layers = self.layer_parameters();
+
+        let mut notified = Vec::new();
+        for peer_id in peers {
+            let mut result = Ok(());
+            if handoff_weights {
+                for (layer, (weights, biases)) in layers.iter().enumerate() {
+                    result = self
+                        .send_weight_sync(peer_id, layer as u8, weights.clone(), biases.clone())
+                        .await;
+                    if result.is_err() {
+                        break;
+                    }
+                }
+            }
+            if result.is_ok() {
+                result = self
+                    .send_payload(
+                        peer_id,
+                        MessagePayload::Disconnect {
+                            reason: reason.to_string(),
+                        },
+                    )
+                    .await;
+            }
+            match result {
+                Ok(()) => notified.push(peer_id),
+                Err(e) => println!("❌ Farewell to {} failed: {:?}", peer_id, e),
+            }
+            self.forget_peer(peer_id);
+        }
+        notified
+    }
+
+    /// Weights and biases of every layer, in the layout `WeightSync` carries
+    fn layer_parameters(&self) -> Vec<(Vec<f64>, Vec<f64>)> {
+        let network = self.network.lock().unwrap();
+        (0..network.num_layers() - 1)
+            .map(|layer| {
+                (
+                    network.get_layer_weights(layer),
+                    network.get_layer_biases(layer).to_vec(),
+                )
+            })
+            .collect()
+    }
+
+    /// Merge one layer's parameters received in a `WeightSync` from `sender`
+    ///
+    /// The layer becomes `(1 - blend) * local + blend * received`, and the
+    /// merge is recorded in the model's provenance.
+    fn apply_weight_sync(
+        &self,
+        sender: Option<NetworkId>,
+        layer_id: u8,
+        weights: &[f32],
+        biases: &[f32],
+        blend: f64,
+    ) -> Result<(), String> {
+        let layer = layer_id as usize;
+        let mut network = self.network.lock().unwrap();
+        if layer + 1 >= network.num_layers() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        let mix = |local: &[f64], received: &[f32]| -> Vec<f64> {
+            if blend >= 1.0 || local.len() != received.len() {
+                return received.iter().map(|&x| x as f64).collect();
+            }
+            local
+                .iter()
+                .zip(received)
+                .map(|(&l, &r)| (1.0 - blend) * l + blend * r as f64)
+                .collect()
+        };
+        let weights = mix(&network.get_layer_weights(layer), weights);
+        let biases = mix(network.get_layer_biases(layer), biases);
+        network.set_layer_parameters(layer, &weights, &biases)?;
+        let peer = sender.map_or_else(|| "unknown".to_string(), |id| id.to_string());
+        network.metadata_mut().record_contribution(layer, &peer, blend);
+        Ok(())
+    }
+
+    /// Current role of this node
+    pub fn role(&self) -> NodeRole {
+        *self.role.lock().unwrap()
+    }
+
+    /// When the last message from `peer_id` arrived, if any did
+    pub fn last_seen(&self, peer_id: NetworkId) -> Option<Instant> {
+        self.last_seen.lock().unwrap().get(&peer_id).copied()
+    }
+
+    /// Stream this node's weights to a standby replica every `interval`
+    ///
+    /// Each round sends one `WeightSync` per layer; these double as heartbeats
+    /// for the replica's `start_standby`. Abort the handle to stop replicating.
+    pub fn start_replication(
+        &self,
+        replica: NetworkId,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        let node = self.clone();
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            loop {
+                ticks.tick().await;
+                for (layer, (weights, biases)) in node.layer_parameters().into_iter().enumerate() {
+                    if let Err(e) = node
+                        .send_weight_sync(replica, layer as u8, weights, biases)
+                        .await
+                    {
+                        println!("💔 Replication to {} failed: {:?}", replica, e);
+                        break;
+                    }
+                }
+            }
+        })
+    }
+
+    /// Act as a standby replica of `primary` until it falls silent
+    ///
+    /// Weight syncs from the primary are applied as they arrive. Once nothing
+    /// has been received from it for `timeout`, the node promotes itself (see
+    /// `promote`) and the returned task completes.
+    pub fn start_standby(&self, primary: NetworkId, timeout: Duration) -> tokio::task::JoinHandle<()> {
+        *self.role.lock().unwrap() = NodeRole::Standby { primary };
+        let node = self.clone();
+        tokio::spawn(async move {
+            let since = Instant::now();
+            loop {
+                tokio::time::sleep(timeout / 4).await;
+                if node.role() != (NodeRole::Standby { primary }) {
+                    return;
+                }
+                let last = node.last_seen(primary).map_or(since, |seen| seen.max(since));
+                if last.elapsed() >= timeout {
+                    println!("🚨 Primary {} silent for {:?}, promoting", primary, timeout);
+                    node.promote();
+                    return;
+                }
+            }
+        })
+    }
+
+    /// Take over as primary, dropping the former primary from connections and routes
+    pub fn promote(&self) {
+        let previous = std::mem::take(&mut *self.role.lock().unwrap());
+        if let NodeRole::Standby { primary } = previous {
+            self.forget_peer(primary);
+        }
+    }
+
+    /// Drop a peer from the connection table and every route
+    fn forget_peer(&self, peer_id: NetworkId) {
+        self.connections.lock().unwrap().remove(&peer_id);
+        self.router.remove_peer(peer_id);
+        self.clock.forget(peer_id);
+        self.channels.forget_peer(peer_id);
+        self.peer_ranges.forget(peer_id);
+    }
+
+    /// Read one complete NNP frame from a stream, returning it with its size in bytes
+    pub(crate) async fn read_message(stream: &mut NnpStream) -> Result<(NetworkMessage, usize), ProtocolError> {
+        Self::read_versioned_message(stream)
+            .await
+            .map(|(message, len, _)| (message, len))
+    }
+
+    /// Like `read_message`, also returning the protocol version the sender advertised
+    async fn read_versioned_message(
+        stream: &mut NnpStream,
+    ) -> Result<(NetworkMessage, usize, u8), ProtocolError> {
+        let mut header_buf = [0u8; HEADER_SIZE];
+        stream.read_exact(&mut header_buf).await?;
+
+        let payload_len = BigEndian::read_u32(&header_buf[6..10]) as usize;
+        let mut full_message = vec![0u8; HEADER_SIZE + payload_len];
+        full_message[..HEADER_SIZE].copy_from_slice(&header_buf);
+        stream.read_exact(&mut full_message[HEADER_SIZE..]).await?;
+
+        let message = NetworkMessage::from_bytes(&full_message)?;
+        let version = NetworkMessage::frame_version(&header_buf);
+        Ok((message, full_message.len(), version))
+    }
+
     /// Send a message to a specific peer
+    ///
+    /// With an enabled `AckPolicy` the message is flagged `ACK_REQUIRED` and
+    /// retransmitted until the peer acknowledges its sequence number.
     async fn send_message_to_peer(
         &self,
         peer_id: NetworkId,
-        message: NetworkMessage,
+        mut message: NetworkMessage,
     ) -> Result<(), ProtocolError> {
-        let message_bytes = message.to_bytes();
-
         // Get connection info for the peer
         let (address, port) = {
             let connections = self.connections.lock().unwrap();
@@ -900,57 +3153,176 @@ impl DistributedNetwork {
             }
         };
 
-        // Establish a new connection and send the message
-        // In a production system, you'd maintain persistent connections
-        match TcpStream::connect(format!("{}:{}", address, port)).await {
-            Ok(mut stream) => {
-                println!("📤 Sending {:?} message to {} ({}:{})", message.msg_type, peer_id, address, port);
-                
-                // First, send a handshake to establish the connection
-                let handshake = NetworkMessage {
-                    msg_type: MessageType::Handshake,
-                    sequence: self.next_sequence(),
-                    payload: MessagePayload::Handshake {
-                        network_id: self.id,
-                        name: self.info.name.clone(),
-                        layers: self.info.layers.clone(),
-                        capabilities: self.info.capabilities,
-                    },
-                };
-                
-                let handshake_bytes = handshake.to_bytes();
-                if let Err(e) = stream.write_all(&handshake_bytes).await {
-                    return Err(ProtocolError::IoError(e));
-                }
-                
-                // Read handshake acknowledgment
-                let mut ack_buffer = vec![0u8; 1024];
-                match stream.read(&mut ack_buffer).await {
-                    Ok(bytes_read) if bytes_read > 0 => {
-                        // Parse the acknowledgment (we'll assume it's valid for now)
-                        println!("🤝 Received handshake acknowledgment");
-                    }
-                    Ok(_) => {
-                        println!("⚠️ Empty handshake acknowledgment");
+        if !self.ack_policy.enabled {
+            return self
+                .deliver_to_peer(peer_id, &address, port, &message)
+                .await
+                .map(|_| ());
+        }
+
+        message.flags |= message_flags::ACK_REQUIRED;
+        let mut last_error = ProtocolError::AckTimeout;
+        for attempt in 0..=self.ack_policy.max_retries {
+            if attempt > 0 {
+                println!(
+                    "🔁 Retransmitting {:?} #{} to {} (attempt {})",
+                    message.msg_type, message.sequence, peer_id, attempt + 1
+                );
+            }
+            match self.deliver_to_peer(peer_id, &address, port, &message).await {
+                Ok(_) => return Ok(()),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    /// Connect to a peer and complete the handshake
+    ///
+    /// Returns the open stream and the protocol version agreed with the peer.
+    async fn open_session(
+        &self,
+        peer_id: NetworkId,
+        address: &str,
+        port: u16,
+    ) -> Result<(NnpStream, u8), ProtocolError> {
+        let mut stream = match NnpStream::connect(address, port, self.bind_options.preference).await {
+            Ok(stream) => stream,
+            Err(e) => {
+                println!("❌ Failed to connect to peer {} ({}:{}): {}", peer_id, address, port, e);
+                return Err(ProtocolError::IoError(e));
+            }
+        };
+
+        // First, send a handshake to establish the connection
+        let handshake = self.handshake_message();
+        let handshake_bytes = handshake.to_bytes_for(self.protocol_version)?;
+        stream.write_all(&handshake_bytes).await?;
+        self.bandwidth
+            .record(peer_id, Direction::Sent, MessageType::Handshake, handshake_bytes.len());
+
+        // Read handshake acknowledgment
+        let version = match Self::read_versioned_message(&mut stream).await {
+            Ok((ack, ack_len, ack_version)) => {
+                self.bandwidth
+                    .record(peer_id, Direction::Received, ack.msg_type, ack_len);
+                match ack.payload {
+                    MessagePayload::HandshakeAck {
+                        accepted: false,
+                        reason,
+                        ..
+                    } => {
+                        println!("⛔ Handshake rejected by peer {}", peer_id);
+                        return Err(ProtocolError::HandshakeRejected(reason));
                     }
-                    Err(e) => {
-                        println!("❌ Failed to read handshake acknowledgment: {}", e);
-                        return Err(ProtocolError::IoError(e));
+                    MessagePayload::HandshakeAck { ranges, .. } => {
+                        self.peer_ranges.record(peer_id, ranges.unwrap_or_default());
                     }
+                    _ => {}
                 }
-                
-                // Now send the actual message
-                if let Err(e) = stream.write_all(&message_bytes).await {
-                    return Err(ProtocolError::IoError(e));
-                }
-                
-                println!("✅ Successfully sent {:?} message to {}", message.msg_type, peer_id);
-                Ok(())
+                println!("🤝 Received handshake acknowledgment");
+                protocol_compat::negotiate(self.protocol_version, ack_version)
             }
             Err(e) => {
-                println!("❌ Failed to connect to peer {} ({}:{}): {}", peer_id, address, port, e);
-                Err(ProtocolError::IoError(e))
+                println!("❌ Failed to read handshake acknowledgment: {:?}", e);
+                return Err(e);
+            }
+        };
+        self.record_peer_version(peer_id, version);
+
+        Ok((stream, version))
+    }
+
+    /// Open a connection, handshake and write one message, waiting for its `Ack` if required
+    ///
+    /// The delivery first waits for a slot in the peer's send lanes, where
+    /// control messages overtake queued data; control messages are also
+    /// exempt from the bandwidth cap. Returns the time between writing the
+    /// message and receiving its `Ack`, or `None` for unacknowledged messages.
+    /// Peers on an older protocol version get the message translated, and
+    /// without an `Ack` when their version has none.
+    async fn deliver_to_peer(
+        &self,
+        peer_id: NetworkId,
+        address: &str,
+        port: u16,
+        message: &NetworkMessage,
+    ) -> Result<Option<Duration>, ProtocolError> {
+        let priority = Priority::of(message.msg_type);
+        let _permit = self.send_lanes.acquire(peer_id, priority).await;
+
+        // Establish a new connection and send the message
+        // In a production system, you'd maintain persistent connections
+        println!("📤 Sending {:?} message to {} ({}:{})", message.msg_type, peer_id, address, port);
+        let (mut stream, version) = self.open_session(peer_id, address, port).await?;
+        let translated = protocol_compat::translate(message, version).inspect_err(|_| {
+            println!(
+                "⚠️ Cannot send {:?} to peer {}: protocol v{} has no such message",
+                message.msg_type, peer_id, version
+            );
+        })?;
+        if message.requires_ack() && !translated.requires_ack() {
+            println!(
+                "⚠️ Peer {} speaks protocol v{} without acks; {:?} #{} goes out unacknowledged",
+                peer_id, version, message.msg_type, message.sequence
+            );
+        }
+        let message = &translated;
+        let message_bytes = message.encode(version);
+
+        // Now send the actual message, respecting the peer's cadence and bandwidth cap
+        if message.msg_type == MessageType::ForwardData {
+            self.pacing.wait(peer_id).await;
+        }
+        if priority == Priority::Data {
+            self.bandwidth.throttle(peer_id, message_bytes.len()).await;
+        }
+        let sent_at = Instant::now();
+        let sent_micros = clock::now_micros();
+        stream.write_all(&message_bytes).await?;
+        self.bandwidth
+            .record(peer_id, Direction::Sent, message.msg_type, message_bytes.len());
+
+        if message.requires_ack() {
+            let wait_for_ack = async {
+                loop {
+                    let (reply, reply_len) = Self::read_message(&mut stream).await?;
+                    self.bandwidth
+                        .record(peer_id, Direction::Received, reply.msg_type, reply_len);
+                    if let MessagePayload::Ack {
+                        sequence,
+                        timestamps,
+                    } = reply.payload
+                    {
+                        if sequence == message.sequence {
+                            if let Some((received, replied)) = timestamps {
+                                let sample = ClockSample::from_exchange(
+                                    sent_micros,
+                                    received,
+                                    replied,
+                                    clock::now_micros(),
+                                );
+                                self.clock.record(peer_id, sample);
+                            }
+                            return Ok::<(), ProtocolError>(());
+                        }
+                    }
+                }
+            };
+            match tokio::time::timeout(self.ack_policy.timeout, wait_for_ack).await {
+                Ok(result) => result?,
+                Err(_) => return Err(ProtocolError::AckTimeout),
             }
+            let rtt = sent_at.elapsed();
+            println!(
+                "✅ {:?} #{} acknowledged by {} in {:?}",
+                message.msg_type, message.sequence, peer_id, rtt
+            );
+            Ok(Some(rtt))
+        } else {
+            println!("✅ Successfully sent {:?} message to {}", message.msg_type, peer_id);
+            Ok(None)
         }
     }
     
@@ -967,7 +3339,9 @@ impl DistributedNetwork {
         
         // Iterate through all connections to find one matching the address and port
         for (peer_id, connection) in connections.iter() {
-            if connection.address == address && connection.port == port {
+            if address::strip_brackets(&connection.address) == address::strip_brackets(address)
+                && connection.port == port
+            {
                 return Some(*peer_id);
             }
         }
@@ -978,8 +3352,9 @@ impl DistributedNetwork {
 
     /// Process incoming network message
     pub async fn handle_message(&self, message: NetworkMessage) -> Result<(), ProtocolError> {
+        let sequence = message.sequence;
         match message.payload {
-            MessagePayload::ForwardData { layer_id, data } => {
+            MessagePayload::ForwardData { layer_id, data, .. } => {
                 println!(
                     "📥 Received forward data for layer {}: {} values",
                     layer_id,
@@ -991,8 +3366,14 @@ impl DistributedNetwork {
 
                 // Process through our network
                 let mut network = self.network.lock().unwrap();
+                let data_f64 = compatibility::fit_inputs(data_f64, network.input_size(), self.shape_policy)
+                    .map_err(|reason| {
+                        println!("❌ Dropping forward data: {}", reason);
+                        ProtocolError::InvalidPayload
+                    })?;
                 let (_, output) = network.forward(&data_f64);
                 drop(network);
+                self.record_outputs(&output);
 
                 println!("🧠 Processed data, output: {:?}", output);
             }
@@ -1001,6 +3382,7 @@ impl DistributedNetwork {
                 layer_id,
                 correlations,
                 learning_rate,
+                ..
             } => {
                 println!(
                     "🧬 Received Hebbian data for layer {}: {} correlations (rate: {})",
@@ -1012,6 +3394,31 @@ impl DistributedNetwork {
                 // Could integrate this correlation data into our own learning
             }
 
+            MessagePayload::WeightSync {
+                layer_id,
+                weights,
+                biases,
+            } => {
+                let sender = self
+                    .weight_sync_senders
+                    .lock()
+                    .unwrap()
+                    .take(layer_id, sequence);
+                let blend = self.weight_sync_blend;
+                match self.apply_weight_sync(sender, layer_id, &weights, &biases, blend) {
+                    Ok(()) => println!("⚖️ Applied weight sync for layer {}", layer_id),
+                    Err(e) => println!("❌ Rejected weight sync for layer {}: {}", layer_id, e),
+                }
+            }
+
+            MessagePayload::SpikeEvents { layer_id, events } => {
+                println!(
+                    "⚡ Received {} spike events for layer {}",
+                    events.len(),
+                    layer_id
+                );
+            }
+
             MessagePayload::Heartbeat { timestamp } => {
                 println!("💓 Heartbeat received: {}", timestamp);
             }
@@ -1024,3 +3431,1158 @@ impl DistributedNetwork {
         Ok(())
     }
 }
+
+/// Read replies until the `InfoResponse`, returning it with its size in bytes
+async fn read_info_response(stream: &mut NnpStream) -> Result<(PeerInfo, usize), ProtocolError> {
+    loop {
+        let (reply, reply_len) = DistributedNetwork::read_message(stream).await?;
+        if let MessagePayload::InfoResponse { info } = reply.payload {
+            return Ok((info, reply_len));
+        }
+    }
+}
+
+/// Probe the node at `address:port` with a `Ping`, returning its readiness
+pub async fn ping(
+    address: &str,
+    port: u16,
+    preference: AddressPreference,
+    timeout: Duration,
+) -> Result<HealthReport, ProtocolError> {
+    let request = NetworkMessage {
+        msg_type: MessageType::Ping,
+        sequence: 1,
+        payload: MessagePayload::Ping,
+        flags: 0,
+    };
+    let exchange = async {
+        let mut stream = NnpStream::connect(address, port, preference).await?;
+        stream.write_all(&request.to_bytes()).await?;
+        loop {
+            let (reply, _) = DistributedNetwork::read_message(&mut stream).await?;
+            if let MessagePayload::Pong { report } = reply.payload {
+                return Ok(report);
+            }
+        }
+    };
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| ProtocolError::AckTimeout)?
+}
+
+/// Ask the node at `address:port` for its `PeerInfo` without joining it as a peer
+///
+/// The request is sent without a handshake, so nodes that belong to a
+/// namespace ignore it; query those with `DistributedNetwork::query_peer_info`.
+pub async fn query_info(
+    address: &str,
+    port: u16,
+    preference: AddressPreference,
+    timeout: Duration,
+) -> Result<PeerInfo, ProtocolError> {
+    let request = NetworkMessage {
+        msg_type: MessageType::Info,
+        sequence: 1,
+        payload: MessagePayload::Info,
+        flags: 0,
+    };
+    let exchange = async {
+        let mut stream = NnpStream::connect(address, port, preference).await?;
+        stream.write_all(&request.to_bytes()).await?;
+        read_info_response(&mut stream).await.map(|(info, _)| info)
+    };
+    tokio::time::timeout(timeout, exchange)
+        .await
+        .map_err(|_| ProtocolError::AckTimeout)?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_ack_flag_roundtrip() {
+        let message = NetworkMessage {
+            msg_type: MessageType::ForwardData,
+            sequence: 42,
+            payload: MessagePayload::ForwardData {
+                layer_id: 0,
+                data: vec![0.25, 0.5],
+                timestamp: None,
+                channel: None,
+            },
+            flags: message_flags::ACK_REQUIRED,
+        };
+
+        let decoded = NetworkMessage::from_bytes(&message.to_bytes()).unwrap();
+        assert!(decoded.requires_ack());
+        assert_eq!(decoded.sequence, 42);
+
+        let ack = NetworkMessage {
+            msg_type: MessageType::Ack,
+            sequence: 1,
+            payload: MessagePayload::Ack {
+                sequence: 42,
+                timestamps: Some((100, 120)),
+            },
+            flags: 0,
+        };
+        let decoded = NetworkMessage::from_bytes(&ack.to_bytes()).unwrap();
+        assert!(!decoded.requires_ack());
+        assert!(matches!(
+            decoded.payload,
+            MessagePayload::Ack {
+                sequence: 42,
+                timestamps: Some((100, 120))
+            }
+        ));
+    }
+
+    #[test]
+    fn test_spike_events_roundtrip() {
+        let events = vec![
+            SpikeEvent {
+                neuron: 4,
+                timestamp: 1_700_000_000_000_000,
+            },
+            SpikeEvent {
+                neuron: 1,
+                timestamp: 1_700_000_000_000_250,
+            },
+        ];
+        let message = NetworkMessage {
+            msg_type: MessageType::SpikeEvents,
+            sequence: 3,
+            payload: MessagePayload::SpikeEvents {
+                layer_id: 1,
+                events: events.clone(),
+            },
+            flags: 0,
+        };
+
+        // 12 bytes per event instead of 4 per neuron of the layer
+        let bytes = message.to_bytes();
+        assert_eq!(bytes.len(), HEADER_SIZE + 5 + 2 * 12);
+        match NetworkMessage::from_bytes(&bytes).unwrap().payload {
+            MessagePayload::SpikeEvents {
+                layer_id,
+                events: decoded,
+            } => {
+                assert_eq!(layer_id, 1);
+                assert_eq!(decoded, events);
+            }
+            other => panic!("Unexpected payload: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_weight_sync_roundtrip() {
+        let message = NetworkMessage {
+            msg_type: MessageType::WeightSync,
+            sequence: 7,
+            payload: MessagePayload::WeightSync {
+                layer_id: 1,
+                weights: vec![0.1, -0.2, 0.3],
+                biases: vec![0.5],
+            },
+            flags: 0,
+        };
+
+        match NetworkMessage::from_bytes(&message.to_bytes()).unwrap().payload {
+            MessagePayload::WeightSync {
+                layer_id,
+                weights,
+                biases,
+            } => {
+                assert_eq!(layer_id, 1);
+                assert_eq!(weights, vec![0.1, -0.2, 0.3]);
+                assert_eq!(biases, vec![0.5]);
+            }
+            other => panic!("Unexpected payload: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_config_update_token_roundtrip() {
+        for token in [None, Some("benny_0badc0de_secret".to_string())] {
+            let message = NetworkMessage {
+                msg_type: MessageType::ConfigUpdate,
+                sequence: 3,
+                payload: MessagePayload::ConfigUpdate {
+                    update: ConfigUpdate {
+                        decay_rate: Some(0.01),
+                        ..ConfigUpdate::default()
+                    },
+                    token: token.clone(),
+                },
+                flags: 0,
+            };
+            match NetworkMessage::from_bytes(&message.to_bytes()).unwrap().payload {
+                MessagePayload::ConfigUpdate { update, token: decoded } => {
+                    assert_eq!(update.decay_rate, Some(0.01));
+                    assert_eq!(decoded, token);
+                }
+                other => panic!("Unexpected payload: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_duplicate_deliveries_are_detected() {
+        let mut recent = RecentDeliveries::default();
+        let peer = Uuid::new_v4();
+        assert!(recent.insert(peer, 1));
+        assert!(!recent.insert(peer, 1));
+        assert!(recent.insert(peer, 2));
+        assert!(recent.contains(peer, 1));
+
+        // Later sessions keep what was delivered, a restarted peer does not
+        let other = Uuid::new_v4();
+        assert!(recent.insert(other, 1));
+        assert!(!recent.observe_handshake(peer, 3));
+        assert!(recent.contains(peer, 2));
+        assert!(recent.observe_handshake(peer, 0));
+        assert!(!recent.contains(peer, 1));
+        assert!(recent.insert(peer, 1));
+        assert!(recent.contains(other, 1));
+    }
+
+    #[test]
+    fn test_weight_sync_senders_keep_colliding_sequences_apart() {
+        let mut senders = WeightSyncSenders::default();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        senders.insert(0, 7, first);
+        senders.insert(0, 7, second);
+        assert_eq!(senders.take(0, 7), Some(first));
+        assert_eq!(senders.take(0, 7), Some(second));
+        assert_eq!(senders.take(0, 7), None);
+
+        // Syncs nobody handles age out instead of piling up
+        for sequence in 0..2 * WeightSyncSenders::CAPACITY as u64 {
+            senders.insert(1, sequence, first);
+        }
+        assert_eq!(senders.pending.len(), WeightSyncSenders::CAPACITY);
+        assert_eq!(senders.take(1, 0), None);
+    }
+
+    #[tokio::test]
+    async fn test_acknowledged_forward_data() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (receiver, mut inbox) = DistributedNetwork::new(
+            "receiver".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        receiver.start_server().await.unwrap();
+
+        let (mut sender, _) = DistributedNetwork::new(
+            "sender".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        sender.set_ack_policy(AckPolicy {
+            enabled: true,
+            ..AckPolicy::default()
+        });
+
+        let peer_id = sender.connect_to("127.0.0.1", port).await.unwrap();
+        sender
+            .send_forward_data(peer_id, 1, vec![0.5, -0.5])
+            .await
+            .unwrap();
+
+        // Skip handshakes until the forward data arrives
+        loop {
+            let message = inbox.recv().await.unwrap();
+            if let MessagePayload::ForwardData { layer_id, data, .. } = message.payload {
+                assert_eq!(layer_id, 1);
+                assert_eq!(data, vec![0.5, -0.5]);
+                break;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acknowledged_weight_sync() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (receiver, mut inbox) = DistributedNetwork::new(
+            "receiver".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        receiver.start_server().await.unwrap();
+
+        let (mut sender, _) = DistributedNetwork::new(
+            "sender".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        sender.set_ack_policy(AckPolicy {
+            enabled: true,
+            ..AckPolicy::default()
+        });
+
+        let peer_id = sender.connect_to("127.0.0.1", port).await.unwrap();
+        sender
+            .send_weight_sync(peer_id, 0, vec![0.5, -0.5], vec![0.25])
+            .await
+            .unwrap();
+
+        // Skip handshakes until the weight sync arrives, then apply it
+        loop {
+            let message = inbox.recv().await.unwrap();
+            if let MessagePayload::WeightSync { .. } = message.payload {
+                receiver.handle_message(message).await.unwrap();
+                break;
+            }
+        }
+
+        let network = receiver.network.lock().unwrap();
+        assert_eq!(network.get_layer_weights(0), vec![0.5, -0.5]);
+        assert_eq!(network.get_layer_biases(0), &[0.25]);
+    }
+
+    #[tokio::test]
+    async fn test_blended_weight_sync_records_provenance() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let mut local = NeuralNetwork::with_layers(&[2, 1], 0.0);
+        local.set_layer_parameters(0, &[0.0, 0.0], &[0.0]).unwrap();
+        let (mut receiver, mut inbox) = DistributedNetwork::new(
+            "receiver".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            local,
+        );
+        assert!(receiver.set_weight_sync_blend(0.0).is_err());
+        receiver.set_weight_sync_blend(0.5).unwrap();
+        receiver.start_server().await.unwrap();
+
+        let (sender, _) = DistributedNetwork::new(
+            "sender".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        let peer_id = sender.connect_to("127.0.0.1", port).await.unwrap();
+        sender
+            .send_weight_sync(peer_id, 0, vec![0.5, -0.5], vec![0.25])
+            .await
+            .unwrap();
+        loop {
+            let message = inbox.recv().await.unwrap();
+            if let MessagePayload::WeightSync { .. } = message.payload {
+                receiver.handle_message(message).await.unwrap();
+                break;
+            }
+        }
+
+        {
+            let network = receiver.network.lock().unwrap();
+            assert_eq!(network.get_layer_weights(0), vec![0.25, -0.25]);
+            assert_eq!(network.get_layer_biases(0), &[0.125]);
+            let shares = network.metadata().peer_shares(0);
+            assert_eq!(shares[&sender.id.to_string()], 0.5);
+        }
+        let provenance = receiver.weight_provenance();
+        assert_eq!(provenance[&0].len(), 1);
+        assert_eq!(provenance[&0][0].blend, 0.5);
+        assert!(receiver.weight_sync_senders.lock().unwrap().pending.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_routed_forward_data_fails_over() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (replica, _inbox) = DistributedNetwork::new(
+            "replica".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        replica.start_server().await.unwrap();
+
+        let (sender, _) = DistributedNetwork::new(
+            "sender".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        let live = sender.connect_to("127.0.0.1", port).await.unwrap();
+        assert!(sender.ping_peer(live).await.unwrap().unwrap() > Duration::ZERO);
+
+        // A replica that looks fastest but is no longer listening
+        let dead = Uuid::new_v4();
+        sender.connections.lock().unwrap().insert(
+            dead,
+            NetworkConnection {
+                peer_id: dead,
+                stream: None,
+                capabilities: 0,
+                last_heartbeat: 0,
+                sequence_counter: 0,
+                address: "127.0.0.1".to_string(),
+                port: 1,
+            },
+        );
+        sender.add_route("classifier", dead);
+        sender.add_route("classifier", live);
+        sender.router.record_rtt(dead, Duration::from_micros(1));
+        sender.router.record_rtt(live, Duration::from_millis(10));
+
+        let used = sender
+            .send_forward_data_routed("classifier", 0, vec![0.1, 0.2])
+            .await
+            .unwrap();
+        assert_eq!(used, live);
+        assert_eq!(sender.router.routes()["classifier"][0].consecutive_failures, 1);
+    }
+
+    #[test]
+    fn test_snapshot_restore_roundtrip() {
+        let (node, _) = DistributedNetwork::new(
+            "node".to_string(),
+            "127.0.0.1".to_string(),
+            9000,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        node.network
+            .lock()
+            .unwrap()
+            .set_layer_parameters(0, &[0.5, -0.5], &[0.25])
+            .unwrap();
+        for _ in 0..3 {
+            node.next_sequence();
+        }
+        let peer = Uuid::new_v4();
+        node.connections.lock().unwrap().insert(
+            peer,
+            NetworkConnection {
+                peer_id: peer,
+                stream: None,
+                capabilities: capabilities::FORWARD_PROPAGATION,
+                last_heartbeat: 42,
+                sequence_counter: 7,
+                address: "::1".to_string(),
+                port: 9001,
+            },
+        );
+
+        let path = std::env::temp_dir().join(format!("benny-node-{}.json", Uuid::new_v4()));
+        node.snapshot(&path).unwrap();
+        let (restored, _) = DistributedNetwork::restore(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(restored.id, node.id);
+        assert_eq!(restored.info.id, node.id);
+        assert_eq!(restored.next_sequence(), 4);
+        assert_eq!(restored.find_peer_by_address("::1", 9001), Some(peer));
+        assert_eq!(restored.to_snapshot().peers, node.to_snapshot().peers);
+        assert_eq!(restored.network.lock().unwrap().get_layer_weights(0), vec![0.5, -0.5]);
+    }
+
+    #[test]
+    fn test_handshake_namespace_roundtrip() {
+        for namespace in [None, Some("cluster-a".to_string())] {
+            for channels in [Vec::new(), vec!["imu".to_string(), "lidar".to_string()]] {
+                let declared = SignalRanges {
+                    input: Some(ValueRange::new(0.0, 255.0)),
+                    output: None,
+                };
+                for ranges in [None, Some(declared)] {
+                    let message = NetworkMessage {
+                        msg_type: MessageType::Handshake,
+                        sequence: 1,
+                        payload: MessagePayload::Handshake {
+                            network_id: Uuid::new_v4(),
+                            name: "node".to_string(),
+                            layers: vec![2, 1],
+                            capabilities: capabilities::FORWARD_PROPAGATION,
+                            namespace: namespace.clone(),
+                            channels: channels.clone(),
+                            ranges,
+                        },
+                        flags: 0,
+                    };
+                    let decoded = NetworkMessage::from_bytes(&message.to_bytes()).unwrap();
+                    match decoded.payload {
+                        MessagePayload::Handshake {
+                            namespace: decoded_namespace,
+                            channels: decoded_channels,
+                            ranges: decoded_ranges,
+                            ..
+                        } => {
+                            assert_eq!(decoded_namespace, namespace);
+                            assert_eq!(decoded_channels, channels);
+                            assert_eq!(decoded_ranges, ranges);
+                        }
+                        other => panic!("Unexpected payload: {:?}", other),
+                    }
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_foreign_namespace_is_rejected_unless_bridged() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (mut server, _inbox) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        server.set_namespace(Some("cluster-a".to_string())).unwrap();
+        assert!(server.set_namespace(Some("x".repeat(256))).is_err());
+        server.bridge_namespace("cluster-c");
+        server.start_server().await.unwrap();
+
+        let client = |namespace: &str| {
+            let (mut node, _) = DistributedNetwork::new(
+                namespace.to_string(),
+                "127.0.0.1".to_string(),
+                0,
+                NeuralNetwork::with_layers(&[2, 1], 0.0),
+            );
+            node.set_namespace(Some(namespace.to_string())).unwrap();
+            node
+        };
+
+        assert!(client("cluster-a").connect_to("127.0.0.1", port).await.is_ok());
+        assert!(client("cluster-c").connect_to("127.0.0.1", port).await.is_ok());
+        assert!(matches!(
+            client("cluster-b").connect_to("127.0.0.1", port).await,
+            Err(ProtocolError::HandshakeRejected(Some(_)))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_subscriptions_receive_typed_messages() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        // The receiver is dropped right away; subscriptions keep working
+        let (server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        server.start_server().await.unwrap();
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 2], 0.0),
+        );
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        client
+            .send_forward_data(peer, 0, vec![0.5, 0.25])
+            .await
+            .unwrap();
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let forward_tx = tx.clone();
+        server.on_forward_data(move |event| {
+            let _ = forward_tx.send((event.sender, event.data.clone()));
+        });
+        server.on_weight_sync(move |event| {
+            let _ = tx.send((event.sender, event.weights.clone()));
+        });
+        client
+            .send_weight_sync(peer, 0, vec![1.0, -1.0], vec![0.0])
+            .await
+            .unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let forward = tokio::time::timeout(timeout, rx.recv()).await.unwrap();
+        assert_eq!(forward, Some((client.id, vec![0.5, 0.25])));
+        let sync = tokio::time::timeout(timeout, rx.recv()).await.unwrap();
+        assert_eq!(sync, Some((client.id, vec![1.0, -1.0])));
+    }
+
+    #[tokio::test]
+    async fn test_forward_data_channels_are_named() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        server.start_server().await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        server.on_forward_data(move |event| {
+            let _ = tx.send((event.channel.clone(), event.data.clone()));
+        });
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        client.send_forward_data_on(peer, "imu", 0, vec![0.5, 0.25]).await.unwrap();
+        client.send_forward_data_on(peer, "lidar", 0, vec![1.0, 0.0]).await.unwrap();
+        client.send_forward_data(peer, 0, vec![0.0, 0.0]).await.unwrap();
+
+        let timeout = Duration::from_secs(5);
+        let mut received = Vec::new();
+        for _ in 0..3 {
+            received.push(tokio::time::timeout(timeout, rx.recv()).await.unwrap().unwrap());
+        }
+        assert_eq!(
+            received,
+            vec![
+                (Some("imu".to_string()), vec![0.5, 0.25]),
+                (Some("lidar".to_string()), vec![1.0, 0.0]),
+                (None, vec![0.0, 0.0]),
+            ]
+        );
+        assert_eq!(client.channels.local(), vec!["imu", "lidar"]);
+    }
+
+    #[tokio::test]
+    async fn test_forward_data_is_rescaled_to_peer_input_range() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (mut server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        server.set_signal_ranges(SignalRanges {
+            input: Some(ValueRange::UNIT),
+            output: None,
+        });
+        server.start_server().await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        server.on_forward_data(move |event| {
+            let _ = tx.send(event.data.clone());
+        });
+
+        let (mut client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        client.set_signal_ranges(SignalRanges {
+            input: None,
+            output: Some(ValueRange::BIPOLAR),
+        });
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        assert_eq!(client.peer_ranges(peer).unwrap().input, Some(ValueRange::UNIT));
+        client.send_forward_data(peer, 0, vec![-1.0, 0.5]).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(received, vec![0.0, 0.75]);
+    }
+
+    #[tokio::test]
+    async fn test_clock_sync_stamps_forward_data() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        server.start_server().await.unwrap();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        server.on_forward_data(move |event| {
+            let _ = tx.send(event.timestamp);
+        });
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 2], 0.0),
+        );
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        assert_eq!(client.clock.offset(peer), None);
+
+        // Both nodes share this machine's clock, so the offset is within the round trip
+        let offset = client.sync_clock(peer, 3).await.unwrap();
+        let delay = client.clock.estimate(peer).unwrap().delay as i64;
+        assert!(offset.abs() <= delay.max(1_000), "offset {} with delay {}", offset, delay);
+
+        let before = clock::now_micros();
+        client.send_forward_data(peer, 0, vec![0.5, 0.25]).await.unwrap();
+        let timestamp = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap()
+            .expect("forward data should be timestamped");
+        let skew = (timestamp as i64 - before as i64).abs();
+        assert!(skew < 1_000_000, "timestamp {} µs away from send time", skew);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeats_overtake_saturating_forward_data() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[100, 1], 0.0),
+        );
+        server.start_server().await.unwrap();
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 100], 0.0),
+        );
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+
+        // ~440 byte frames at 4 KB/s: the backlog takes seconds to drain
+        client.set_peer_bandwidth_limit(peer, Some(BandwidthLimit::new(4_000)));
+        let delivered = Arc::new(Mutex::new(0));
+        let senders: Vec<_> = (0..30)
+            .map(|_| {
+                let (client, delivered) = (client.clone(), delivered.clone());
+                tokio::spawn(async move {
+                    client.send_forward_data(peer, 0, vec![0.5; 100]).await.unwrap();
+                    *delivered.lock().unwrap() += 1;
+                })
+            })
+            .collect();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(client.send_lanes.queued(peer).1 > 0);
+
+        for _ in 0..3 {
+            let started = Instant::now();
+            client.ping_peer(peer).await.unwrap();
+            assert!(
+                started.elapsed() < Duration::from_millis(500),
+                "heartbeat took {:?} behind bulk traffic",
+                started.elapsed()
+            );
+        }
+        assert!(*delivered.lock().unwrap() < 30);
+
+        for sender in senders {
+            sender.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_farewell_hands_off_to_standby() {
+        let free_port = || {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let node = |name: &str, port: u16| {
+            DistributedNetwork::new(
+                name.to_string(),
+                "127.0.0.1".to_string(),
+                port,
+                NeuralNetwork::with_layers(&[2, 2], 0.0),
+            )
+            .0
+        };
+        let (consumer_port, standby_port) = (free_port(), free_port());
+        let consumer = node("consumer", consumer_port);
+        let standby = node("standby", standby_port);
+        let primary = node("primary", 0);
+        consumer.start_server().await.unwrap();
+        standby.start_server().await.unwrap();
+
+        primary.connect_to("127.0.0.1", consumer_port).await.unwrap();
+        consumer.connect_to("127.0.0.1", standby_port).await.unwrap();
+        consumer.add_route("classifier", primary.id);
+        consumer.add_route("classifier", standby.id);
+
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let synced = Arc::new(Mutex::new(0));
+        let counter = synced.clone();
+        consumer.on_weight_sync(move |_| *counter.lock().unwrap() += 1);
+        consumer.on_disconnect(move |event| {
+            let _ = tx.send((event.sender, event.reason.clone()));
+        });
+
+        assert_eq!(
+            primary.farewell("maintenance", true).await,
+            vec![consumer.id]
+        );
+        assert!(primary.connections.lock().unwrap().is_empty());
+
+        let event = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap();
+        assert_eq!(event, Some((primary.id, "maintenance".to_string())));
+        assert_eq!(*synced.lock().unwrap(), 1);
+        assert_eq!(consumer.router.candidates("classifier"), vec![standby.id]);
+        assert_eq!(
+            consumer
+                .send_forward_data_routed("classifier", 0, vec![0.1, 0.2])
+                .await
+                .unwrap(),
+            standby.id
+        );
+    }
+
+    #[tokio::test]
+    async fn test_standby_mirrors_primary_and_promotes() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (standby, _) = DistributedNetwork::new(
+            "standby".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[3, 2], 0.0),
+        );
+        standby.start_server().await.unwrap();
+        let (primary, _) = DistributedNetwork::new(
+            "primary".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 2], 0.0),
+        );
+        let replica = primary.connect_to("127.0.0.1", port).await.unwrap();
+
+        let promotion = standby.start_standby(primary.id, Duration::from_millis(400));
+        assert_eq!(standby.role(), NodeRole::Standby { primary: primary.id });
+        let replication = primary.start_replication(replica, Duration::from_millis(50));
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let expected = primary.network.lock().unwrap().get_layer_weights(0);
+        let mirrored = standby.network.lock().unwrap().get_layer_weights(0);
+        for (a, b) in expected.iter().zip(&mirrored) {
+            assert!((a - b).abs() < 1e-6);
+        }
+        assert!(!promotion.is_finished());
+
+        // Losing the primary's heartbeat promotes the standby
+        replication.abort();
+        tokio::time::timeout(Duration::from_secs(5), promotion)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(standby.role(), NodeRole::Primary);
+        assert!(!standby.connections.lock().unwrap().contains_key(&primary.id));
+    }
+
+    #[tokio::test]
+    async fn test_mismatched_layer_shapes_follow_policy() {
+        for policy in [ShapePolicy::Reject, ShapePolicy::Adapt] {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            let port = listener.local_addr().unwrap().port();
+            drop(listener);
+
+            let (mut server, _inbox) = DistributedNetwork::new(
+                "server".to_string(),
+                "127.0.0.1".to_string(),
+                port,
+                NeuralNetwork::with_layers(&[3, 2], 0.0),
+            );
+            server.set_shape_policy(policy);
+            server.start_server().await.unwrap();
+
+            let (client, _) = DistributedNetwork::new(
+                "client".to_string(),
+                "127.0.0.1".to_string(),
+                0,
+                NeuralNetwork::with_layers(&[4, 5], 0.0),
+            );
+            let result = client.connect_to("127.0.0.1", port).await;
+            match policy {
+                ShapePolicy::Reject => match result {
+                    Err(ProtocolError::HandshakeRejected(Some(reason))) => {
+                        assert!(reason.contains("incompatible"), "{}", reason)
+                    }
+                    other => panic!("unexpected result {:?}", other.map(|_| ())),
+                },
+                ShapePolicy::Adapt => assert!(result.is_ok()),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_peer_info() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        server.start_server().await.unwrap();
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 4], 0.1),
+        );
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        client.send_forward_data(peer, 0, vec![0.5; 4]).await.unwrap();
+
+        let info = client.query_peer_info(peer).await.unwrap();
+        assert_eq!(info.network_id, server.id);
+        assert_eq!(info.name, "server");
+        assert_eq!(info.layers, vec![4, 8, 2]);
+        assert_eq!(info.parameters, (4 * 8 + 8 + 8 * 2 + 2) as u64);
+        assert_eq!(info.peers, 1);
+        assert!(info.messages_received >= 3);
+        assert!(info.bytes_received > 0);
+
+        // Without joining as a peer
+        let anonymous = query_info("127.0.0.1", port, AddressPreference::System, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(anonymous.layers, info.layers);
+        assert!(anonymous.messages_received > info.messages_received);
+    }
+
+    #[tokio::test]
+    async fn test_ping_reports_health() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (mut server, _) = DistributedNetwork::new(
+            "server".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[2, 1], 0.1),
+        );
+        // Probes are answered even where unidentified peers are dropped
+        server.namespace = Some("cluster".to_string());
+        let backlog = Arc::new(AtomicUsize::new(0));
+        let probed = Arc::clone(&backlog);
+        server.add_health_check(move || {
+            ComponentHealth::queue("backlog", probed.load(Ordering::Relaxed), 10)
+        });
+        server.start_server().await.unwrap();
+
+        let timeout = Duration::from_secs(2);
+        let report = ping("127.0.0.1", port, AddressPreference::System, timeout)
+            .await
+            .unwrap();
+        assert!(report.is_ready());
+        let names: Vec<&str> = report.components.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["role", "peers", "send_queue", "backlog"]);
+
+        backlog.store(11, Ordering::Relaxed);
+        let report = ping("127.0.0.1", port, AddressPreference::System, timeout)
+            .await
+            .unwrap();
+        assert_eq!(report, server.health());
+        assert_eq!(report.failing().next().unwrap().name, "backlog");
+    }
+
+    #[tokio::test]
+    async fn test_mixed_protocol_versions() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        // A node not upgraded yet, emulated by pinning it to the previous version
+        let (mut old, mut inbox) = DistributedNetwork::new(
+            "old".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[4, 2], 0.1),
+        );
+        old.set_protocol_version(1).unwrap();
+        assert!(old.set_protocol_version(PROTOCOL_VERSION + 1).is_err());
+        old.start_server().await.unwrap();
+
+        let (mut client, _) = DistributedNetwork::new(
+            "new".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 4], 0.1),
+        );
+        client.ack_policy.enabled = true;
+        let peer = client.connect_to("127.0.0.1", port).await.unwrap();
+        assert_eq!(client.peer_protocol_version(peer), Some(1));
+
+        // Acked delivery falls back to a plain send the old node can decode
+        client.send_forward_data(peer, 0, vec![0.5; 4]).await.unwrap();
+        let received = loop {
+            let message = inbox.recv().await.unwrap();
+            if message.msg_type == MessageType::ForwardData {
+                break message;
+            }
+        };
+        assert_eq!(received.flags, 0);
+        assert_eq!(old.peer_protocol_version(client.id), Some(1));
+
+        // Heartbeats to the old node go unacknowledged, so there is no round trip to report
+        assert_eq!(client.ping_peer(peer).await.unwrap(), None);
+
+        // Message types the old version lacks are refused before sending
+        assert!(matches!(
+            client.query_peer_info(peer).await,
+            Err(ProtocolError::Untranslatable {
+                msg_type: MessageType::Info,
+                version: 1
+            })
+        ));
+
+        // Frames for the old version carry its number and no flags
+        let bytes = received.to_bytes_for(1).unwrap();
+        assert_eq!(bytes[4], 1);
+        assert_eq!(NetworkMessage::from_bytes(&bytes).unwrap().sequence, received.sequence);
+    }
+
+    #[tokio::test]
+    async fn test_push_model_hot_swaps_weights() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (mut server, _) = DistributedNetwork::new(
+            "inference".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        server.set_model_deployment(true);
+        server.start_server().await.unwrap();
+
+        let (trainer, _) = DistributedNetwork::new(
+            "trainer".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        let peer = trainer.connect_to("127.0.0.1", port).await.unwrap();
+
+        let trained = NeuralNetwork::with_layers(&[4, 8, 2], 0.1);
+        let input = [0.1, 0.9, 0.4, 0.6];
+        assert_eq!(trainer.push_model(peer, &trained, 1).await.unwrap(), 0);
+        assert_eq!(server.model_version(), 1);
+        assert_eq!(
+            server.network.lock().unwrap().forward_static(&input).0,
+            trained.forward_static(&input).0
+        );
+
+        // Stale versions and other architectures are refused and leave the model alone
+        assert!(matches!(
+            trainer.push_model(peer, &trained, 1).await,
+            Err(ProtocolError::ModelRejected(reason)) if reason.contains("not newer")
+        ));
+        let wider = NeuralNetwork::with_layers(&[4, 16, 2], 0.1);
+        assert!(matches!(
+            trainer.push_model(peer, &wider, 2).await,
+            Err(ProtocolError::ModelRejected(reason)) if reason.contains("layers")
+        ));
+        assert_eq!(server.model_version(), 1);
+
+        let retrained = NeuralNetwork::with_layers(&[4, 8, 2], 0.1);
+        assert_eq!(trainer.push_model(peer, &retrained, 2).await.unwrap(), 1);
+        assert_eq!(
+            server.network.lock().unwrap().forward_static(&input).0,
+            retrained.forward_static(&input).0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_model_push_is_opt_in() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (server, _) = DistributedNetwork::new(
+            "inference".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        assert_eq!(server.info.capabilities & capabilities::MODEL_DEPLOYMENT, 0);
+        server.start_server().await.unwrap();
+
+        let (trainer, _) = DistributedNetwork::new(
+            "trainer".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        let peer = trainer.connect_to("127.0.0.1", port).await.unwrap();
+        let trained = NeuralNetwork::with_layers(&[4, 8, 2], 0.1);
+        assert!(matches!(
+            trainer.push_model(peer, &trained, 1).await,
+            Err(ProtocolError::ModelRejected(reason)) if reason.contains("not enabled")
+        ));
+
+        // Once enabled, chunks from a connection that never handshook are still refused
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let (mut server, _) = DistributedNetwork::new(
+            "inference".to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[4, 8, 2], 0.1),
+        );
+        server.set_model_deployment(true);
+        server.start_server().await.unwrap();
+
+        let mut stream = NnpStream::connect("127.0.0.1", port, AddressPreference::default())
+            .await
+            .unwrap();
+        for chunk in model_push::split_model(&trained, 1, 1).unwrap() {
+            let message = NetworkMessage {
+                msg_type: MessageType::ModelPush,
+                sequence: 1,
+                payload: MessagePayload::ModelPush { chunk },
+                flags: 0,
+            };
+            stream.write_all(&message.to_bytes()).await.unwrap();
+        }
+        let (reply, _) = DistributedNetwork::read_message(&mut stream).await.unwrap();
+        assert!(matches!(
+            reply.payload,
+            MessagePayload::ModelPushAck { accepted: false, reason: Some(reason), .. }
+                if reason.contains("handshook")
+        ));
+        assert_eq!(server.model_version(), 0);
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::{self, Write};
+use std::net::TcpListener;
+
+/// Dataset problems listed before the rest are summarized
+const MAX_SAMPLE_PROBLEMS: usize = 5;
+
+/// What a command would do, printed by `--dry-run` instead of doing it
+///
+/// Steps describe the resolved run (epochs, batches, ports, peers);
+/// problems are what would make it fail, so the dry run exits with an
+/// error when there are any.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionPlan {
+    pub command: String,
+    pub steps: Vec<(String, String)>,
+    pub problems: Vec<String>,
+}
+
+impl ExecutionPlan {
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            ..Self::default()
+        }
+    }
+
+    pub fn step(&mut self, label: &str, value: impl fmt::Display) {
+        self.steps.push((label.to_string(), value.to_string()));
+    }
+
+    /// Record the error of `result`, if any
+    pub fn check(&mut self, result: Result<(), String>) {
+        if let Err(problem) = result {
+            self.problems.push(problem);
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!("\n🧪 Dry run of `{}`, nothing was started\n", self.command);
+        let width = self.steps.iter().map(|(label, _)| label.len()).max();
+        for (label, value) in &self.steps {
+            let _ = writeln!(
+                out,
+                "   {:<width$}  {}",
+                format!("{}:", label),
+                value,
+                width = width.unwrap_or(0) + 1
+            );
+        }
+        for problem in &self.problems {
+            let _ = writeln!(out, "❌ {}", problem);
+        }
+        if self.is_ready() {
+            out.push_str("✅ Ready to run\n");
+        }
+        out
+    }
+
+    /// `Ok` when the run would start, for the command's exit status
+    pub fn into_result(self) -> Result<(), String> {
+        match self.problems.len() {
+            0 => Ok(()),
+            count => Err(format!(
+                "Dry run of `{}` found {} problem(s)",
+                self.command, count
+            )),
+        }
+    }
+}
+
+/// Problems with `inputs` and `targets` as training data of a network taking
+/// `input_size` values and producing `output_size`
+pub fn check_dataset(
+    inputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+    input_size: usize,
+    output_size: usize,
+) -> Vec<String> {
+    if inputs.is_empty() {
+        return vec!["The dataset has no samples".to_string()];
+    }
+    if inputs.len() != targets.len() {
+        return vec![format!(
+            "The dataset has {} inputs but {} targets",
+            inputs.len(),
+            targets.len()
+        )];
+    }
+    let mut problems: Vec<String> = inputs
+        .iter()
+        .zip(targets)
+        .enumerate()
+        .filter_map(|(index, (input, target))| {
+            if input.len() != input_size {
+                Some(format!(
+                    "Sample {} has {} inputs, the network takes {}",
+                    index,
+                    input.len(),
+                    input_size
+                ))
+            } else if target.len() != output_size {
+                Some(format!(
+                    "Sample {} has {} targets, the network produces {}",
+                    index,
+                    target.len(),
+                    output_size
+                ))
+            } else if !input.iter().chain(target).all(|value| value.is_finite()) {
+                Some(format!("Sample {} holds a NaN or infinite value", index))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if problems.len() > MAX_SAMPLE_PROBLEMS {
+        let more = problems.len() - MAX_SAMPLE_PROBLEMS;
+        problems.truncate(MAX_SAMPLE_PROBLEMS);
+        problems.push(format!("... and {} more malformed samples", more));
+    }
+    problems
+}
+
+/// Error when nothing could listen on `host`:`port` right now
+pub fn check_port(host: &str, port: u16) -> Result<(), String> {
+    TcpListener::bind((host, port))
+        .map(drop)
+        .map_err(|e| format!("Port {} on {} is not available: {}", port, host, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_and_checks() {
+        let inputs = vec![
+            vec![0.0, 1.0],
+            vec![1.0],
+            vec![f64::NAN, 0.0],
+            vec![1.0, 1.0],
+        ];
+        let targets = vec![vec![1.0], vec![0.0], vec![1.0], vec![0.0, 1.0]];
+        assert_eq!(
+            check_dataset(&inputs, &targets, 2, 1),
+            vec![
+                "Sample 1 has 1 inputs, the network takes 2",
+                "Sample 2 holds a NaN or infinite value",
+                "Sample 3 has 2 targets, the network produces 1",
+            ]
+        );
+        let many = vec![vec![0.0]; 8];
+        let problems = check_dataset(&many, &many, 2, 1);
+        assert_eq!(problems.len(), MAX_SAMPLE_PROBLEMS + 1);
+        assert_eq!(problems[5], "... and 3 more malformed samples");
+        assert!(check_dataset(&inputs[..1], &targets[..1], 2, 1).is_empty());
+
+        // A port in use is reported instead of failing later
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(check_port("127.0.0.1", port).is_err());
+        drop(listener);
+        assert!(check_port("127.0.0.1", port).is_ok());
+
+        let mut plan = ExecutionPlan::new("train");
+        plan.step("Epochs", 10);
+        plan.step("Batches per epoch", 4);
+        plan.check(Ok(()));
+        assert!(plan.is_ready());
+        assert!(plan.to_text().contains("   Epochs:             10\n"));
+        assert!(plan.to_text().ends_with("✅ Ready to run\n"));
+        plan.check(Err("Port 80 on 0.0.0.0 is not available".to_string()));
+        assert!(plan.to_text().contains("❌ Port 80"));
+        assert!(plan.into_result().is_err());
+    }
+}
@@ -0,0 +1,494 @@
+use crate::atomic_file;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Power iterations used to estimate the spectral radius of the reservoir
+const SPECTRAL_ITERATIONS: usize = 300;
+
+/// How an `EchoStateNetwork` builds its fixed random reservoir
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EchoStateConfig {
+    pub reservoir_size: usize,
+    /// Largest absolute eigenvalue of the recurrent weights; below 1 the
+    /// reservoir forgets its initial state (the echo state property)
+    #[serde(default = "default_spectral_radius")]
+    pub spectral_radius: f64,
+    /// Fraction of the new activation mixed into the state each step (1 = no leak)
+    #[serde(default = "default_leak_rate")]
+    pub leak_rate: f64,
+    /// Input weights are drawn from `[-input_scaling, input_scaling]`
+    #[serde(default = "default_input_scaling")]
+    pub input_scaling: f64,
+    /// Fraction of nonzero recurrent weights
+    #[serde(default = "default_connectivity")]
+    pub connectivity: f64,
+    /// Ridge regression penalty of `fit`
+    #[serde(default = "default_ridge")]
+    pub ridge: f64,
+    /// Readout learning rate of online `train` steps
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_spectral_radius() -> f64 {
+    0.9
+}
+
+fn default_leak_rate() -> f64 {
+    0.3
+}
+
+fn default_input_scaling() -> f64 {
+    1.0
+}
+
+fn default_connectivity() -> f64 {
+    0.1
+}
+
+fn default_ridge() -> f64 {
+    1e-6
+}
+
+fn default_learning_rate() -> f64 {
+    0.01
+}
+
+impl EchoStateConfig {
+    pub fn new(reservoir_size: usize) -> Self {
+        Self {
+            reservoir_size,
+            spectral_radius: default_spectral_radius(),
+            leak_rate: default_leak_rate(),
+            input_scaling: default_input_scaling(),
+            connectivity: default_connectivity(),
+            ridge: default_ridge(),
+            learning_rate: default_learning_rate(),
+            seed: 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reservoir_size == 0 {
+            return Err("Reservoir needs at least one neuron".to_string());
+        }
+        if !(self.leak_rate > 0.0 && self.leak_rate <= 1.0) {
+            return Err(format!("Leak rate {} must be in (0, 1]", self.leak_rate));
+        }
+        if !(self.connectivity > 0.0 && self.connectivity <= 1.0) {
+            return Err(format!(
+                "Connectivity {} must be in (0, 1]",
+                self.connectivity
+            ));
+        }
+        if self.spectral_radius < 0.0 || self.ridge < 0.0 {
+            return Err("Spectral radius and ridge penalty must not be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Reservoir computer: a fixed random recurrent network with a trained linear readout
+///
+/// Each step the leaky tanh reservoir state is updated from the input and
+/// its previous value; only the readout from `[1, input, state]` to the
+/// outputs learns, either in one shot with ridge regression (`fit`) or
+/// online with least-mean-squares steps (`train`). The state carries over
+/// between calls, so inputs are expected in time order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EchoStateNetwork {
+    config: EchoStateConfig,
+    input_weights: Vec<Vec<f64>>,     // input_weights[neuron][0] is the bias
+    reservoir_weights: Vec<Vec<f64>>, // reservoir_weights[to][from]
+    readout: Vec<Vec<f64>>,           // readout[output][feature]
+    state: Vec<f64>,
+}
+
+impl EchoStateNetwork {
+    pub fn new(inputs: usize, outputs: usize, config: EchoStateConfig) -> Result<Self, String> {
+        config.validate()?;
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let size = config.reservoir_size;
+        let input_weights = (0..size)
+            .map(|_| {
+                (0..=inputs)
+                    .map(|_| rng.gen_range(-1.0..=1.0) * config.input_scaling)
+                    .collect()
+            })
+            .collect();
+        let mut reservoir_weights: Vec<Vec<f64>> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| {
+                        if rng.gen::<f64>() < config.connectivity {
+                            rng.gen_range(-1.0..=1.0)
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let radius = spectral_radius(&reservoir_weights);
+        if radius > 0.0 {
+            let scale = config.spectral_radius / radius;
+            for weight in reservoir_weights.iter_mut().flatten() {
+                *weight *= scale;
+            }
+        }
+
+        Ok(Self {
+            readout: vec![vec![0.0; 1 + inputs + size]; outputs],
+            state: vec![0.0; size],
+            config,
+            input_weights,
+            reservoir_weights,
+        })
+    }
+
+    pub fn config(&self) -> &EchoStateConfig {
+        &self.config
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.input_weights.first().map_or(0, |row| row.len() - 1)
+    }
+
+    pub fn num_outputs(&self) -> usize {
+        self.readout.len()
+    }
+
+    pub fn reservoir_size(&self) -> usize {
+        self.state.len()
+    }
+
+    pub fn state(&self) -> &[f64] {
+        &self.state
+    }
+
+    /// Forget the input history, e.g. between independent sequences
+    pub fn reset_state(&mut self) {
+        self.state.iter_mut().for_each(|x| *x = 0.0);
+    }
+
+    /// Spectral radius of the recurrent weights, estimated by power iteration
+    pub fn spectral_radius(&self) -> f64 {
+        spectral_radius(&self.reservoir_weights)
+    }
+
+    /// Advance the reservoir by one input: `x = (1 - a) x + a tanh(W_in [1; u] + W x)`
+    pub fn update(&mut self, input: &[f64]) -> &[f64] {
+        assert_eq!(input.len(), self.num_inputs(), "Input size mismatch");
+        let leak = self.config.leak_rate;
+        let next: Vec<f64> = self
+            .input_weights
+            .iter()
+            .zip(&self.reservoir_weights)
+            .map(|(input_row, recurrent_row)| {
+                let drive = input_row[0]
+                    + input_row[1..]
+                        .iter()
+                        .zip(input)
+                        .map(|(w, u)| w * u)
+                        .sum::<f64>()
+                    + recurrent_row
+                        .iter()
+                        .zip(&self.state)
+                        .map(|(w, x)| w * x)
+                        .sum::<f64>();
+                drive.tanh()
+            })
+            .collect();
+        for (x, activation) in self.state.iter_mut().zip(next) {
+            *x = (1.0 - leak) * *x + leak * activation;
+        }
+        &self.state
+    }
+
+    /// Update the reservoir with `input` and read out the outputs
+    pub fn step(&mut self, input: &[f64]) -> Vec<f64> {
+        self.update(input);
+        self.read(&self.features(input))
+    }
+
+    /// Online readout training: one least-mean-squares step; returns the squared error
+    pub fn train(&mut self, input: &[f64], target: &[f64]) -> f64 {
+        assert_eq!(target.len(), self.num_outputs(), "Target size mismatch");
+        self.update(input);
+        let features = self.features(input);
+        let outputs = self.read(&features);
+        let rate = self.config.learning_rate;
+        let mut error = 0.0;
+        for ((row, output), target) in self.readout.iter_mut().zip(outputs).zip(target) {
+            let delta = target - output;
+            error += delta * delta;
+            for (weight, feature) in row.iter_mut().zip(&features) {
+                *weight += rate * delta * feature;
+            }
+        }
+        error
+    }
+
+    /// Fit the readout to a sequence with ridge regression; returns the mean squared error
+    ///
+    /// Starts from a reset state and ignores the first `washout` steps, while
+    /// the reservoir still reflects its initial state. Leaves the state after
+    /// the last input, so prediction can continue the sequence.
+    pub fn fit(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+        washout: usize,
+    ) -> Result<f64, String> {
+        if inputs.len() != targets.len() {
+            return Err(format!(
+                "Got {} inputs but {} targets",
+                inputs.len(),
+                targets.len()
+            ));
+        }
+        if washout >= inputs.len() {
+            return Err(format!(
+                "Washout of {} steps leaves none of the {} samples to fit",
+                washout,
+                inputs.len()
+            ));
+        }
+        if let Some(target) = targets.iter().find(|t| t.len() != self.num_outputs()) {
+            return Err(format!(
+                "Expected {} targets per step, got {}",
+                self.num_outputs(),
+                target.len()
+            ));
+        }
+
+        self.reset_state();
+        let mut features = Vec::with_capacity(inputs.len() - washout);
+        for (step, input) in inputs.iter().enumerate() {
+            if input.len() != self.num_inputs() {
+                return Err(format!(
+                    "Expected {} inputs per step, got {}",
+                    self.num_inputs(),
+                    input.len()
+                ));
+            }
+            self.update(input);
+            if step >= washout {
+                features.push(self.features(input));
+            }
+        }
+        let targets = &targets[washout..];
+
+        // (X^T X + ridge I) W_out^T = X^T Y
+        let n = features[0].len();
+        let mut gram = vec![vec![0.0; n]; n];
+        let mut projected = vec![vec![0.0; self.num_outputs()]; n];
+        for (row, target) in features.iter().zip(targets) {
+            for (i, &x) in row.iter().enumerate() {
+                for (sum, y) in gram[i].iter_mut().zip(row) {
+                    *sum += x * y;
+                }
+                for (sum, t) in projected[i].iter_mut().zip(target) {
+                    *sum += x * t;
+                }
+            }
+        }
+        for (i, gram_row) in gram.iter_mut().enumerate() {
+            gram_row[i] += self.config.ridge;
+        }
+        let solution = solve(gram, projected)
+            .ok_or("Readout regression is singular; increase the ridge penalty")?;
+        for (output, row) in self.readout.iter_mut().enumerate() {
+            for (feature, weight) in row.iter_mut().enumerate() {
+                *weight = solution[feature][output];
+            }
+        }
+
+        let error: f64 = features
+            .iter()
+            .zip(targets)
+            .map(|(row, target)| {
+                self.read(row)
+                    .iter()
+                    .zip(target)
+                    .map(|(o, t)| (o - t).powi(2))
+                    .sum::<f64>()
+            })
+            .sum();
+        Ok(error / (targets.len() * self.num_outputs()).max(1) as f64)
+    }
+
+    pub fn info(&self) -> String {
+        format!(
+            "Echo State Network: {} -> reservoir {} -> {} (leak: {}, spectral radius: {})",
+            self.num_inputs(),
+            self.reservoir_size(),
+            self.num_outputs(),
+            self.config.leak_rate,
+            self.config.spectral_radius
+        )
+    }
+
+    /// Save the network, including its current state, to a JSON file, replacing it atomically
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        atomic_file::write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| atomic_file::check_json(path, e).into())
+    }
+
+    /// Save the network to a binary file (more compact than JSON)
+    pub fn save_to_binary<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        atomic_file::write_atomic(path, &bincode::serialize(self)?)?;
+        Ok(())
+    }
+
+    pub fn load_from_binary<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
+        bincode::deserialize(&fs::read(path)?)
+            .map_err(|e| atomic_file::check_bincode(path, e).into())
+    }
+
+    fn features(&self, input: &[f64]) -> Vec<f64> {
+        let mut features = Vec::with_capacity(1 + input.len() + self.state.len());
+        features.push(1.0);
+        features.extend_from_slice(input);
+        features.extend_from_slice(&self.state);
+        features
+    }
+
+    fn read(&self, features: &[f64]) -> Vec<f64> {
+        self.readout
+            .iter()
+            .map(|row| row.iter().zip(features).map(|(w, f)| w * f).sum())
+            .collect()
+    }
+}
+
+/// Largest absolute eigenvalue of a square matrix
+///
+/// Averages the growth of a repeatedly multiplied vector, which also
+/// converges when the dominant eigenvalues form a complex pair.
+fn spectral_radius(matrix: &[Vec<f64>]) -> f64 {
+    let n = matrix.len();
+    let mut vector: Vec<f64> = (0..n).map(|i| 1.0 / (i + 1) as f64).collect();
+    let norm = |v: &[f64]| v.iter().map(|x| x * x).sum::<f64>().sqrt();
+    let mut log_growth = 0.0;
+    let mut counted = 0;
+    for iteration in 0..SPECTRAL_ITERATIONS {
+        let length = norm(&vector);
+        if length == 0.0 {
+            return 0.0;
+        }
+        let next: Vec<f64> = matrix
+            .iter()
+            .map(|row| row.iter().zip(&vector).map(|(w, x)| w * x).sum::<f64>() / length)
+            .collect();
+        // Skip the transient while the vector aligns with the dominant eigenvectors
+        if iteration >= SPECTRAL_ITERATIONS / 3 {
+            log_growth += norm(&next).ln();
+            counted += 1;
+        }
+        vector = next;
+    }
+    (log_growth / counted as f64).exp()
+}
+
+/// Solve `a x = b` for several right-hand sides by Gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = a.len();
+    for column in 0..n {
+        let pivot =
+            (column..n).max_by(|&i, &j| a[i][column].abs().total_cmp(&a[j][column].abs()))?;
+        if a[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(column, pivot);
+        b.swap(column, pivot);
+        let (upper_a, lower_a) = a.split_at_mut(column + 1);
+        let (upper_b, lower_b) = b.split_at_mut(column + 1);
+        let (pivot_a, pivot_b) = (&upper_a[column], &upper_b[column]);
+        for (row_a, row_b) in lower_a.iter_mut().zip(lower_b) {
+            let factor = row_a[column] / pivot_a[column];
+            if factor == 0.0 {
+                continue;
+            }
+            for (value, pivot) in row_a[column..].iter_mut().zip(&pivot_a[column..]) {
+                *value -= factor * pivot;
+            }
+            for (value, pivot) in row_b.iter_mut().zip(pivot_b) {
+                *value -= factor * pivot;
+            }
+        }
+    }
+    for row in (0..n).rev() {
+        for k in 0..b[row].len() {
+            let known: f64 = (row + 1..n).map(|j| a[row][j] * b[j][k]).sum();
+            b[row][k] = (b[row][k] - known) / a[row][row];
+        }
+    }
+    Some(b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(steps: usize) -> Vec<Vec<f64>> {
+        (0..steps).map(|t| vec![(t as f64 * 0.2).sin()]).collect()
+    }
+
+    #[test]
+    fn test_reservoir_is_scaled_and_deterministic() {
+        let config = EchoStateConfig {
+            seed: 3,
+            ..EchoStateConfig::new(50)
+        };
+        let esn = EchoStateNetwork::new(1, 1, config.clone()).unwrap();
+        assert!((esn.spectral_radius() - 0.9).abs() < 0.05);
+        assert_eq!(esn, EchoStateNetwork::new(1, 1, config).unwrap());
+
+        assert!(EchoStateNetwork::new(1, 1, EchoStateConfig::new(0)).is_err());
+        let leaky = EchoStateConfig {
+            leak_rate: 0.0,
+            ..EchoStateConfig::new(10)
+        };
+        assert!(EchoStateNetwork::new(1, 1, leaky).is_err());
+    }
+
+    #[test]
+    fn test_fit_predicts_next_sine_value() {
+        let series = sine(400);
+        let (inputs, targets) = (&series[..399], &series[1..]);
+        let mut esn = EchoStateNetwork::new(1, 1, EchoStateConfig::new(100)).unwrap();
+        let error = esn.fit(inputs, targets, 50).unwrap();
+        assert!(error < 1e-4, "training error {}", error);
+
+        // The state continues after the fitted sequence
+        let mut input = series[399].clone();
+        for t in 400..420 {
+            let predicted = esn.step(&input);
+            assert!((predicted[0] - (t as f64 * 0.2).sin()).abs() < 0.05);
+            input = vec![(t as f64 * 0.2).sin()];
+        }
+
+        let restored: EchoStateNetwork =
+            bincode::deserialize(&bincode::serialize(&esn).unwrap()).unwrap();
+        assert_eq!(restored, esn);
+        assert!(esn.fit(inputs, &targets[1..], 0).is_err());
+        assert!(esn.fit(inputs, targets, 399).is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// Learnable lookup table turning a categorical input column into a dense vector
+///
+/// The column carries the category index (`0..vocab_size`) as a number; the
+/// network replaces it with row `index` of `table` before the first layer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Embedding {
+    /// Position of the categorical column in the raw input
+    pub column: usize,
+    pub table: Vec<Vec<f64>>, // table[category][dimension]
+}
+
+impl Embedding {
+    /// Randomly initialized table of `vocab_size` vectors with `dim` components
+    pub fn new(column: usize, vocab_size: usize, dim: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            column,
+            table: (0..vocab_size)
+                .map(|_| (0..dim).map(|_| rng.gen_range(-0.5..0.5)).collect())
+                .collect(),
+        }
+    }
+
+    pub fn vocab_size(&self) -> usize {
+        self.table.len()
+    }
+
+    pub fn dim(&self) -> usize {
+        self.table.first().map_or(0, Vec::len)
+    }
+
+    /// Category index carried by an input value
+    pub fn category(&self, value: f64) -> usize {
+        let index = value.round();
+        assert!(
+            index >= 0.0 && (index as usize) < self.vocab_size(),
+            "Category {} out of range for column {} (vocabulary of {})",
+            value,
+            self.column,
+            self.vocab_size()
+        );
+        index as usize
+    }
+
+    pub fn lookup(&self, value: f64) -> &[f64] {
+        &self.table[self.category(value)]
+    }
+
+    /// Move the vector of `value`'s category by `rate * error`
+    pub fn update(&mut self, value: f64, error: &[f64], rate: f64) {
+        let category = self.category(value);
+        for (component, delta) in self.table[category].iter_mut().zip(error) {
+            *component += rate * delta;
+        }
+    }
+
+    /// Number of learnable parameters
+    pub fn num_parameters(&self) -> usize {
+        self.vocab_size() * self.dim()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup_and_update() {
+        let mut embedding = Embedding::new(2, 3, 4);
+        assert_eq!((embedding.vocab_size(), embedding.dim()), (3, 4));
+        assert_eq!(embedding.num_parameters(), 12);
+
+        let before = embedding.lookup(1.0).to_vec();
+        embedding.update(1.0, &[1.0, 0.0, -1.0, 0.0], 0.5);
+        let after = embedding.lookup(1.0);
+        assert!((after[0] - before[0] - 0.5).abs() < 1e-12);
+        assert!((after[2] - before[2] + 0.5).abs() < 1e-12);
+        assert_eq!(after[1], before[1]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn test_unknown_category_panics() {
+        Embedding::new(0, 2, 2).lookup(5.0);
+    }
+}
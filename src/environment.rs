@@ -0,0 +1,325 @@
+use crate::distributed_network::{DistributedNetwork, NetworkId};
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Result of one environment step
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    pub observation: Vec<f64>,
+    pub reward: f64,
+    /// The episode ended; the next step must follow a `reset`
+    pub done: bool,
+}
+
+/// Simulation environment with a Gym-style step/reset interface
+pub trait Environment {
+    fn observation_size(&self) -> usize;
+
+    fn action_size(&self) -> usize;
+
+    /// Start a new episode and return its first observation
+    fn reset(&mut self) -> Vec<f64>;
+
+    fn step(&mut self, action: &[f64]) -> Transition;
+}
+
+/// Chooses actions from observations
+pub trait Controller {
+    fn act(&mut self, observation: &[f64]) -> Result<Vec<f64>, String>;
+
+    /// Feedback on the last action, for controllers that learn from rewards
+    fn reward(&mut self, _reward: f64, _done: bool) {}
+}
+
+/// A local network acts with its outputs, adapting online if enabled
+impl Controller for NeuralNetwork {
+    fn act(&mut self, observation: &[f64]) -> Result<Vec<f64>, String> {
+        if observation.len() != self.input_size() {
+            return Err(format!(
+                "Network expects {} inputs, observation has {}",
+                self.input_size(),
+                observation.len()
+            ));
+        }
+        Ok(self.forward(observation).0)
+    }
+}
+
+/// Controller running on another node, reached over NNP
+///
+/// Observations are sent as forward data to `peer`, whose outputs must come
+/// back to `node` (e.g. a `server` listing `node` in `output_endpoints`).
+/// `act` blocks until they arrive, so drive the episode runner from a thread
+/// outside the async runtime, such as `tokio::task::spawn_blocking`.
+pub struct RemoteController {
+    node: DistributedNetwork,
+    peer: NetworkId,
+    runtime: tokio::runtime::Handle,
+    actions: mpsc::Receiver<Vec<f64>>,
+    timeout: Duration,
+}
+
+impl RemoteController {
+    /// Must be called inside a Tokio runtime, which sends the observations
+    pub fn new(node: DistributedNetwork, peer: NetworkId, timeout: Duration) -> Self {
+        let (sender, actions) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        node.on_forward_data(move |event| {
+            let _ = sender.lock().unwrap().send(event.data.clone());
+        });
+        Self {
+            node,
+            peer,
+            runtime: tokio::runtime::Handle::current(),
+            actions,
+            timeout,
+        }
+    }
+}
+
+impl Controller for RemoteController {
+    fn act(&mut self, observation: &[f64]) -> Result<Vec<f64>, String> {
+        // Outputs that arrived late for an earlier observation are stale
+        while self.actions.try_recv().is_ok() {}
+        self.runtime
+            .block_on(
+                self.node
+                    .send_forward_data(self.peer, 0, observation.to_vec()),
+            )
+            .map_err(|e| format!("Failed to send observation: {:?}", e))?;
+        self.actions
+            .recv_timeout(self.timeout)
+            .map_err(|_| format!("No action from {} within {:?}", self.peer, self.timeout))
+    }
+}
+
+/// Summary of one finished episode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeStats {
+    pub episode: usize,
+    pub steps: usize,
+    pub total_reward: f64,
+    /// The environment ended the episode (rather than the step limit)
+    pub done: bool,
+}
+
+/// Closed loop feeding an environment's observations to a controller and its outputs back as actions
+pub struct EnvironmentBridge<E: Environment, C: Controller> {
+    environment: E,
+    controller: C,
+    max_steps: usize,
+    episodes: Vec<EpisodeStats>,
+    reward_log: Option<PathBuf>,
+}
+
+impl<E: Environment, C: Controller> EnvironmentBridge<E, C> {
+    /// Episodes are cut off after `max_steps` steps
+    pub fn new(environment: E, controller: C, max_steps: usize) -> Self {
+        Self {
+            environment,
+            controller,
+            max_steps,
+            episodes: Vec::new(),
+            reward_log: None,
+        }
+    }
+
+    /// Append a CSV line (`episode,steps,total_reward,done`) per finished episode to `path`
+    pub fn set_reward_log(&mut self, path: PathBuf) {
+        self.reward_log = Some(path);
+    }
+
+    pub fn environment(&self) -> &E {
+        &self.environment
+    }
+
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// Every episode run so far
+    pub fn episodes(&self) -> &[EpisodeStats] {
+        &self.episodes
+    }
+
+    /// Mean total reward of the last `window` episodes
+    pub fn average_reward(&self, window: usize) -> Option<f64> {
+        let recent = &self.episodes[self.episodes.len().saturating_sub(window)..];
+        (!recent.is_empty())
+            .then(|| recent.iter().map(|e| e.total_reward).sum::<f64>() / recent.len() as f64)
+    }
+
+    pub fn run_episode(&mut self) -> Result<EpisodeStats, String> {
+        let mut observation = self.environment.reset();
+        let mut stats = EpisodeStats {
+            episode: self.episodes.len(),
+            steps: 0,
+            total_reward: 0.0,
+            done: false,
+        };
+
+        while stats.steps < self.max_steps && !stats.done {
+            let action = self.controller.act(&observation)?;
+            if action.len() != self.environment.action_size() {
+                return Err(format!(
+                    "Controller produced {} actions, environment takes {}",
+                    action.len(),
+                    self.environment.action_size()
+                ));
+            }
+            let transition = self.environment.step(&action);
+            self.controller.reward(transition.reward, transition.done);
+            stats.steps += 1;
+            stats.total_reward += transition.reward;
+            stats.done = transition.done;
+            observation = transition.observation;
+        }
+
+        if let Some(path) = &self.reward_log {
+            let mut log = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| format!("Failed to open reward log {}: {}", path.display(), e))?;
+            writeln!(
+                log,
+                "{},{},{},{}",
+                stats.episode, stats.steps, stats.total_reward, stats.done
+            )
+            .map_err(|e| format!("Failed to write reward log: {}", e))?;
+        }
+        self.episodes.push(stats.clone());
+        Ok(stats)
+    }
+
+    pub fn run(&mut self, episodes: usize) -> Result<Vec<EpisodeStats>, String> {
+        (0..episodes).map(|_| self.run_episode()).collect()
+    }
+}
+
+/// Point on a line that has to reach a target
+///
+/// Observes `[position, target]`; the single action in `[0, 1]` moves the
+/// point left (below 0.5) or right (above 0.5) by up to `speed`. Each step
+/// is rewarded with the negative distance to the target, and the episode
+/// ends once the point is within `tolerance` of it.
+#[derive(Debug, Clone)]
+pub struct LineWorld {
+    pub speed: f64,
+    pub tolerance: f64,
+    position: f64,
+    target: f64,
+    episode: usize,
+}
+
+impl LineWorld {
+    pub fn new() -> Self {
+        Self {
+            speed: 0.1,
+            tolerance: 0.05,
+            position: 0.0,
+            target: 0.0,
+            episode: 0,
+        }
+    }
+
+    fn observation(&self) -> Vec<f64> {
+        vec![self.position, self.target]
+    }
+}
+
+impl Default for LineWorld {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Environment for LineWorld {
+    fn observation_size(&self) -> usize {
+        2
+    }
+
+    fn action_size(&self) -> usize {
+        1
+    }
+
+    /// Targets alternate between the ends of the line, starting from the middle
+    fn reset(&mut self) -> Vec<f64> {
+        self.position = 0.0;
+        self.target = if self.episode.is_multiple_of(2) { 0.8 } else { -0.8 };
+        self.episode += 1;
+        self.observation()
+    }
+
+    fn step(&mut self, action: &[f64]) -> Transition {
+        let push = (action[0].clamp(0.0, 1.0) - 0.5) * 2.0;
+        self.position = (self.position + push * self.speed).clamp(-1.0, 1.0);
+        let distance = (self.target - self.position).abs();
+        Transition {
+            observation: self.observation(),
+            reward: -distance,
+            done: distance <= self.tolerance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pushes towards the target and counts the rewards it receives
+    struct Seeker {
+        rewards: usize,
+    }
+
+    impl Controller for Seeker {
+        fn act(&mut self, observation: &[f64]) -> Result<Vec<f64>, String> {
+            let towards = if observation[1] > observation[0] {
+                1.0
+            } else {
+                0.0
+            };
+            Ok(vec![towards])
+        }
+
+        fn reward(&mut self, _reward: f64, _done: bool) {
+            self.rewards += 1;
+        }
+    }
+
+    #[test]
+    fn test_episodes_reach_target_and_log_rewards() {
+        let log = std::env::temp_dir().join(format!("rewards-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&log);
+        let mut bridge = EnvironmentBridge::new(LineWorld::new(), Seeker { rewards: 0 }, 100);
+        bridge.set_reward_log(log.clone());
+
+        let episodes = bridge.run(2).unwrap();
+        for stats in &episodes {
+            assert!(stats.done);
+            assert_eq!(stats.steps, 8);
+            assert!(stats.total_reward < 0.0);
+        }
+        assert_eq!(bridge.controller().rewards, 16);
+        assert_eq!(bridge.average_reward(10), Some(episodes[0].total_reward));
+        let lines = std::fs::read_to_string(&log).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+        assert!(lines.starts_with("0,8,"));
+        std::fs::remove_file(&log).unwrap();
+
+        // A network with the wrong output size is rejected
+        let network = NeuralNetwork::with_layers(&[2, 3], 0.0);
+        let mut bridge = EnvironmentBridge::new(LineWorld::new(), network, 10);
+        assert!(bridge.run_episode().is_err());
+    }
+}
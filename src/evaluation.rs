@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Precision, recall and F1 of one class
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassMetrics {
+    pub class: usize,
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+    /// Samples whose target is this class
+    pub support: usize,
+}
+
+/// Regression and classification metrics of a model on a labelled set
+///
+/// Classes are read from the outputs the way training treats them: a single
+/// output is a binary label thresholded at 0.5, several outputs are one-hot
+/// and the largest one wins.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evaluation {
+    pub samples: usize,
+    pub mse: f64,
+    pub rmse: f64,
+    pub mae: f64,
+    pub accuracy: f64,
+    pub macro_f1: f64,
+    pub classes: Vec<ClassMetrics>,
+    /// `confusion[target][predicted]` sample counts
+    pub confusion: Vec<Vec<usize>>,
+}
+
+impl Evaluation {
+    /// Metrics of `(output, target)` pairs
+    pub fn compute<'a, I>(pairs: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a [f64], &'a [f64])>,
+    {
+        let mut samples = 0;
+        let (mut squared, mut absolute, mut values) = (0.0, 0.0, 0);
+        let mut confusion: Vec<Vec<usize>> = Vec::new();
+
+        for (output, target) in pairs {
+            samples += 1;
+            for (o, t) in output.iter().zip(target) {
+                squared += (o - t).powi(2);
+                absolute += (o - t).abs();
+                values += 1;
+            }
+
+            let classes = target.len().max(2);
+            if confusion.len() < classes {
+                confusion.resize(classes, Vec::new());
+            }
+            for row in &mut confusion {
+                row.resize(classes, 0);
+            }
+            confusion[class_of(target)][class_of(output).min(classes - 1)] += 1;
+        }
+
+        let mse = squared / values.max(1) as f64;
+        let correct: usize = (0..confusion.len()).map(|c| confusion[c][c]).sum();
+        let classes: Vec<ClassMetrics> = (0..confusion.len())
+            .map(|class| {
+                let true_positives = confusion[class][class] as f64;
+                let predicted: usize = confusion.iter().map(|row| row[class]).sum();
+                let support: usize = confusion[class].iter().sum();
+                let precision = ratio(true_positives, predicted as f64);
+                let recall = ratio(true_positives, support as f64);
+                ClassMetrics {
+                    class,
+                    precision,
+                    recall,
+                    f1: ratio(2.0 * precision * recall, precision + recall),
+                    support,
+                }
+            })
+            .collect();
+
+        Evaluation {
+            samples,
+            mse,
+            rmse: mse.sqrt(),
+            mae: absolute / values.max(1) as f64,
+            accuracy: ratio(correct as f64, samples as f64),
+            macro_f1: ratio(classes.iter().map(|c| c.f1).sum(), classes.len() as f64),
+            classes,
+            confusion,
+        }
+    }
+
+    /// `metric,class,value` rows; `class` is empty for whole-set metrics
+    ///
+    /// Confusion counts are `predicted_<p>` rows whose class is the target.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("metric,class,value\n");
+        for (metric, value) in [
+            ("samples", self.samples as f64),
+            ("mse", self.mse),
+            ("rmse", self.rmse),
+            ("mae", self.mae),
+            ("accuracy", self.accuracy),
+            ("macro_f1", self.macro_f1),
+        ] {
+            let _ = writeln!(out, "{},,{}", metric, value);
+        }
+        for class in &self.classes {
+            let _ = writeln!(out, "precision,{},{}", class.class, class.precision);
+            let _ = writeln!(out, "recall,{},{}", class.class, class.recall);
+            let _ = writeln!(out, "f1,{},{}", class.class, class.f1);
+            let _ = writeln!(out, "support,{},{}", class.class, class.support);
+        }
+        for (target, row) in self.confusion.iter().enumerate() {
+            for (predicted, count) in row.iter().enumerate() {
+                let _ = writeln!(out, "predicted_{},{},{}", predicted, target, count);
+            }
+        }
+        out
+    }
+
+    /// Human-readable report
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "📊 Evaluation on {} samples\n   MSE: {:.6}, RMSE: {:.6}, MAE: {:.6}\n   Accuracy: {:.2}%, macro F1: {:.4}\n",
+            self.samples,
+            self.mse,
+            self.rmse,
+            self.mae,
+            self.accuracy * 100.0,
+            self.macro_f1
+        );
+        out.push_str("   class  precision  recall      f1  support\n");
+        for class in &self.classes {
+            let _ = writeln!(
+                out,
+                "   {:>5}  {:>9.4}  {:>6.4}  {:>6.4}  {:>7}",
+                class.class, class.precision, class.recall, class.f1, class.support
+            );
+        }
+        out.push_str("   Confusion (rows: target, columns: predicted)\n");
+        for row in &self.confusion {
+            let cells: Vec<String> = row.iter().map(|count| format!("{:>6}", count)).collect();
+            let _ = writeln!(out, "   {}", cells.join(""));
+        }
+        out
+    }
+}
+
+fn class_of(values: &[f64]) -> usize {
+    match values {
+        [value] => (*value > 0.5) as usize,
+        _ => values
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(index, _)| index),
+    }
+}
+
+/// `numerator / denominator`, or 0 when nothing was counted
+fn ratio(numerator: f64, denominator: f64) -> f64 {
+    if denominator > 0.0 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_and_multiclass_metrics() {
+        let outputs = [vec![0.9], vec![0.2], vec![0.7], vec![0.4]];
+        let targets = [vec![1.0], vec![0.0], vec![0.0], vec![1.0]];
+        let binary = Evaluation::compute(
+            outputs
+                .iter()
+                .zip(&targets)
+                .map(|(o, t)| (o.as_slice(), t.as_slice())),
+        );
+        assert_eq!(binary.samples, 4);
+        assert_eq!(binary.confusion, vec![vec![1, 1], vec![1, 1]]);
+        assert!((binary.accuracy - 0.5).abs() < 1e-12);
+        assert!((binary.mse - (0.01 + 0.04 + 0.49 + 0.36) / 4.0).abs() < 1e-12);
+        assert!((binary.mae - (0.1 + 0.2 + 0.7 + 0.6) / 4.0).abs() < 1e-12);
+
+        let outputs = [
+            vec![0.8, 0.1, 0.1],
+            vec![0.1, 0.7, 0.2],
+            vec![0.6, 0.3, 0.1],
+        ];
+        let targets = [
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+            vec![0.0, 1.0, 0.0],
+        ];
+        let multi = Evaluation::compute(
+            outputs
+                .iter()
+                .zip(&targets)
+                .map(|(o, t)| (o.as_slice(), t.as_slice())),
+        );
+        assert_eq!(multi.classes.len(), 3);
+        assert_eq!(multi.confusion[1], vec![1, 1, 0]);
+        assert!((multi.classes[0].precision - 0.5).abs() < 1e-12);
+        assert!((multi.classes[1].recall - 0.5).abs() < 1e-12);
+        assert_eq!(multi.classes[2].support, 0);
+        assert_eq!(multi.classes[2].f1, 0.0);
+
+        assert!(multi.to_csv().contains("precision,0,0.5"));
+        assert!(multi.to_text().contains("Accuracy: 66.67%"));
+        let json = serde_json::to_string(&multi).unwrap();
+        let parsed: Evaluation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.confusion, multi.confusion);
+        assert_eq!(parsed.classes, multi.classes);
+    }
+}
@@ -0,0 +1,114 @@
+use hyper::{Body, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Liveness probe path: any answer means the process is up
+pub const LIVENESS_PATH: &str = "/healthz";
+/// Readiness probe path: 200 while every component is ready, 503 otherwise
+pub const READINESS_PATH: &str = "/readyz";
+
+/// Queued messages or samples above which a queue reports not ready
+pub const DEFAULT_QUEUE_THRESHOLD: usize = 1024;
+
+/// Status of one part of a service
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    pub ready: bool,
+    /// Short human-readable state, e.g. "3 peers" or "12 queued"
+    pub detail: String,
+}
+
+impl ComponentHealth {
+    pub fn new(name: &str, ready: bool, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            ready,
+            detail: detail.into(),
+        }
+    }
+
+    /// A queue that is ready while it holds at most `threshold` entries
+    pub fn queue(name: &str, queued: usize, threshold: usize) -> Self {
+        Self::new(
+            name,
+            queued <= threshold,
+            format!("{} queued (limit {})", queued, threshold),
+        )
+    }
+}
+
+/// Extra component a service reports on, evaluated on every probe
+pub type HealthCheck = Arc<dyn Fn() -> ComponentHealth + Send + Sync>;
+
+/// Readiness of a service, served on `/readyz` and in NNP `Pong` replies
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub components: Vec<ComponentHealth>,
+}
+
+impl HealthReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, component: ComponentHealth) {
+        self.components.push(component);
+    }
+
+    /// Ready when every component is; a report without components is ready
+    pub fn is_ready(&self) -> bool {
+        self.components.iter().all(|component| component.ready)
+    }
+
+    /// Components that are not ready
+    pub fn failing(&self) -> impl Iterator<Item = &ComponentHealth> {
+        self.components.iter().filter(|component| !component.ready)
+    }
+}
+
+/// Answer to `/healthz`
+pub fn liveness_response() -> Response<Body> {
+    Response::builder()
+        .header("content-type", "text/plain")
+        .body(Body::from("ok"))
+        .unwrap()
+}
+
+/// Answer to `/readyz`: the report as JSON, with 503 while it is not ready
+pub fn readiness_response(report: &HealthReport) -> Response<Body> {
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = serde_json::to_string(report).unwrap_or_else(|_| "{}".to_string());
+    Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_readiness_follows_components() {
+        let mut report = HealthReport::new();
+        assert!(report.is_ready());
+        report.push(ComponentHealth::new("listener", true, "bound"));
+        report.push(ComponentHealth::queue("send_queue", 3, 2));
+        assert!(!report.is_ready());
+        assert_eq!(report.failing().next().unwrap().name, "send_queue");
+        assert_eq!(
+            readiness_response(&report).status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        report.components[1] = ComponentHealth::queue("send_queue", 2, 2);
+        assert_eq!(readiness_response(&report).status(), StatusCode::OK);
+        assert_eq!(liveness_response().status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,152 @@
+use crate::activation::Activation;
+use serde::{Deserialize, Serialize};
+
+/// Fully connected recurrent network of bipolar units storing patterns as attractors
+///
+/// Patterns of `-1.0` / `+1.0` states are stored with the Hebbian outer
+/// product rule and recalled by updating one `Activation::Sign` unit at a time
+/// until the state stops changing. Every update lowers (or keeps) `energy`, so
+/// recall always settles.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HopfieldNetwork {
+    weights: Vec<Vec<f64>>, // weights[i][j], symmetric with a zero diagonal
+    patterns: usize,
+}
+
+impl HopfieldNetwork {
+    pub fn new(size: usize) -> Self {
+        Self {
+            weights: vec![vec![0.0; size]; size],
+            patterns: 0,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.weights.len()
+    }
+
+    /// Number of patterns stored so far
+    pub fn patterns(&self) -> usize {
+        self.patterns
+    }
+
+    /// Patterns that can be stored before recall degrades (about 0.138 per unit)
+    pub fn capacity(&self) -> usize {
+        (self.size() as f64 * 0.138) as usize
+    }
+
+    pub fn get_weight(&self, from: usize, to: usize) -> f64 {
+        self.weights[from][to]
+    }
+
+    /// Store a bipolar pattern: `w_ij += p_i * p_j / size` for every `i != j`
+    pub fn store(&mut self, pattern: &[f64]) -> Result<(), String> {
+        self.check_state(pattern)?;
+        let scale = 1.0 / self.size() as f64;
+        for (i, row) in self.weights.iter_mut().enumerate() {
+            for (j, weight) in row.iter_mut().enumerate() {
+                if i != j {
+                    *weight += pattern[i] * pattern[j] * scale;
+                }
+            }
+        }
+        self.patterns += 1;
+        Ok(())
+    }
+
+    /// `-1/2 * sum_ij w_ij s_i s_j`, lowest at stored patterns
+    pub fn energy(&self, state: &[f64]) -> f64 {
+        let total: f64 = self
+            .weights
+            .iter()
+            .zip(state)
+            .map(|(row, &s_i)| {
+                s_i * row
+                    .iter()
+                    .zip(state)
+                    .map(|(w, &s_j)| w * s_j)
+                    .sum::<f64>()
+            })
+            .sum();
+        -0.5 * total
+    }
+
+    /// Settle from `probe` with asynchronous updates in unit order
+    ///
+    /// Stops after a sweep that changes no unit or after `max_sweeps` sweeps.
+    /// Returns the final state and the number of sweeps run.
+    pub fn recall(&self, probe: &[f64], max_sweeps: usize) -> Result<(Vec<f64>, usize), String> {
+        self.check_state(probe)?;
+        let mut state = probe.to_vec();
+        for sweep in 1..=max_sweeps {
+            let mut changed = false;
+            for i in 0..state.len() {
+                let input: f64 = self.weights[i]
+                    .iter()
+                    .zip(&state)
+                    .map(|(w, s)| w * s)
+                    .sum();
+                let next = Activation::Sign.apply(input);
+                if next != state[i] {
+                    state[i] = next;
+                    changed = true;
+                }
+            }
+            if !changed {
+                return Ok((state, sweep));
+            }
+        }
+        Ok((state, max_sweeps))
+    }
+
+    fn check_state(&self, state: &[f64]) -> Result<(), String> {
+        if state.len() != self.size() {
+            return Err(format!(
+                "Expected {} states, got {}",
+                self.size(),
+                state.len()
+            ));
+        }
+        if state.iter().any(|&s| s != 1.0 && s != -1.0) {
+            return Err("Hopfield states must be -1.0 or 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recall_of_corrupted_patterns() {
+        let patterns: Vec<Vec<f64>> = [0b1111_0000_1111_0000u16, 0b1010_1010_0101_0101]
+            .iter()
+            .map(|bits| {
+                (0..16)
+                    .map(|i| if bits >> i & 1 == 1 { 1.0 } else { -1.0 })
+                    .collect()
+            })
+            .collect();
+        let mut network = HopfieldNetwork::new(16);
+        for pattern in &patterns {
+            network.store(pattern).unwrap();
+        }
+        assert_eq!(network.patterns(), 2);
+        assert_eq!(network.get_weight(3, 3), 0.0);
+        assert_eq!(network.get_weight(2, 5), network.get_weight(5, 2));
+
+        for pattern in &patterns {
+            let mut noisy = pattern.clone();
+            noisy[1] = -noisy[1];
+            noisy[10] = -noisy[10];
+            assert!(network.energy(&noisy) > network.energy(pattern));
+            let (recalled, sweeps) = network.recall(&noisy, 10).unwrap();
+            assert_eq!(&recalled, pattern);
+            assert!(sweeps <= 3);
+        }
+
+        assert!(network.store(&[0.5; 16]).is_err());
+        assert!(network.recall(&[1.0; 4], 10).is_err());
+    }
+}
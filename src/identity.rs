@@ -0,0 +1,77 @@
+use crate::distributed_network::NetworkId;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Where a node gets its `NetworkId` from
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IdentitySource {
+    /// A fresh UUID every run
+    #[default]
+    Random,
+    /// A fixed UUID supplied by configuration
+    Fixed { id: NetworkId },
+    /// A UUID stored in a file, generated and written on first use
+    File { path: PathBuf },
+}
+
+impl IdentitySource {
+    /// Resolve the identity, creating the identity file if needed
+    pub fn resolve(&self) -> std::io::Result<NetworkId> {
+        match self {
+            IdentitySource::Random => Ok(Uuid::new_v4()),
+            IdentitySource::Fixed { id } => Ok(*id),
+            IdentitySource::File { path } => load_or_create_identity(path),
+        }
+    }
+}
+
+/// Read the node UUID from `path`, or generate one and persist it there
+pub fn load_or_create_identity<P: AsRef<Path>>(path: P) -> std::io::Result<NetworkId> {
+    let path = path.as_ref();
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Uuid::parse_str(contents.trim()).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid identity in {}: {}", path.display(), e),
+            )
+        }),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let id = Uuid::new_v4();
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, format!("{}\n", id))?;
+            Ok(id)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_identity_is_stable() {
+        let dir = std::env::temp_dir().join(format!("benny-identity-{}", Uuid::new_v4()));
+        let source = IdentitySource::File {
+            path: dir.join("node.id"),
+        };
+
+        let first = source.resolve().unwrap();
+        assert_eq!(source.resolve().unwrap(), first);
+
+        std::fs::write(dir.join("node.id"), "not-a-uuid").unwrap();
+        assert!(source.resolve().is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fixed_identity() {
+        let id = Uuid::new_v4();
+        assert_eq!(IdentitySource::Fixed { id }.resolve().unwrap(), id);
+        assert_ne!(IdentitySource::Random.resolve().unwrap(), id);
+    }
+}
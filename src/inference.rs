@@ -0,0 +1,370 @@
+//! Inference on fixed weights with only `core` and `alloc`
+//!
+//! This module is all that remains of the crate when it is built without the
+//! default `std` feature, so trained networks can run on microcontrollers:
+//!
+//! ```text
+//! cargo build --lib --no-default-features
+//! ```
+//!
+//! Models are exported on the host with `NeuralNetwork::to_inference_model`
+//! and `InferenceModel::to_bytes`, and loaded on the device from flash with
+//! `InferenceModel::from_bytes`. Computation uses `f32`, which most
+//! microcontroller FPUs support natively, and its own `exp` so outputs do not
+//! depend on the target's math library.
+
+use crate::activation::Activation;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// First bytes of a serialized `InferenceModel`
+pub const MODEL_MAGIC: [u8; 4] = *b"BNYI";
+
+/// Serialization format version
+pub const MODEL_VERSION: u8 = 1;
+
+/// One dense layer: `outputs = activation(weights * inputs + biases)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferenceLayer {
+    inputs: usize,
+    activation: Activation,
+    weights: Vec<f32>, // weights[to * inputs + from], one contiguous row per output
+    biases: Vec<f32>,
+}
+
+impl InferenceLayer {
+    /// Layer from row-major weights (one row of `inputs` values per output)
+    pub fn new(
+        inputs: usize,
+        weights: Vec<f32>,
+        biases: Vec<f32>,
+        activation: Activation,
+    ) -> Result<Self, String> {
+        if weights.len() != inputs * biases.len() {
+            return Err(format!(
+                "Expected {} weights for {} inputs and {} outputs, got {}",
+                inputs * biases.len(),
+                inputs,
+                biases.len(),
+                weights.len()
+            ));
+        }
+        // The model format stores activations as fixed codes
+        #[cfg(feature = "std")]
+        if let Activation::Custom(custom) = activation {
+            return Err(format!(
+                "The inference core does not support custom activations ({:?})",
+                custom
+            ));
+        }
+        Ok(Self {
+            inputs,
+            activation,
+            weights,
+            biases,
+        })
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.biases.len()
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    /// Weights into output `to`, one per input
+    pub fn row(&self, to: usize) -> &[f32] {
+        &self.weights[to * self.inputs..(to + 1) * self.inputs]
+    }
+
+    pub fn biases(&self) -> &[f32] {
+        &self.biases
+    }
+
+    /// Write the layer's outputs for `inputs` into `outputs`
+    pub fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        for (to, (output, &bias)) in outputs.iter_mut().zip(&self.biases).enumerate() {
+            let sum = self
+                .row(to)
+                .iter()
+                .zip(inputs)
+                .fold(bias, |sum, (w, x)| sum + w * x);
+            *output = activate(self.activation, sum);
+        }
+    }
+}
+
+/// Feed-forward network reduced to what inference needs
+#[derive(Debug, Clone, PartialEq)]
+pub struct InferenceModel {
+    layers: Vec<InferenceLayer>,
+}
+
+impl InferenceModel {
+    pub fn new(layers: Vec<InferenceLayer>) -> Result<Self, String> {
+        if layers.is_empty() {
+            return Err("A model needs at least one layer".into());
+        }
+        for (index, pair) in layers.windows(2).enumerate() {
+            if pair[0].outputs() != pair[1].inputs() {
+                return Err(format!(
+                    "Layer {} has {} outputs but layer {} expects {} inputs",
+                    index,
+                    pair[0].outputs(),
+                    index + 1,
+                    pair[1].inputs()
+                ));
+            }
+        }
+        Ok(Self { layers })
+    }
+
+    pub fn layers(&self) -> &[InferenceLayer] {
+        &self.layers
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].inputs()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].outputs()
+    }
+
+    /// Output activations for `inputs`; missing inputs read as 0 and extra ones are ignored
+    pub fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut current: Vec<f32> = inputs.to_vec();
+        current.resize(self.input_size(), 0.0);
+        let mut next = Vec::new();
+        for layer in &self.layers {
+            next.clear();
+            next.resize(layer.outputs(), 0.0);
+            layer.forward_into(&current, &mut next);
+            core::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+
+    /// Compact little-endian encoding, suitable for embedding in firmware
+    ///
+    /// Layout: magic, version (u8), layer count (u32), then per layer its
+    /// input and output counts (u32), activation (u8), weights and biases (f32).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&MODEL_MAGIC);
+        bytes.push(MODEL_VERSION);
+        bytes.extend_from_slice(&(self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            bytes.extend_from_slice(&(layer.inputs() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(layer.outputs() as u32).to_le_bytes());
+            bytes.push(activation_code(layer.activation));
+            for value in layer.weights.iter().chain(&layer.biases) {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != MODEL_MAGIC {
+            return Err("Not a Benny inference model".into());
+        }
+        let version = reader.take(1)?[0];
+        if version != MODEL_VERSION {
+            return Err(format!("Unsupported model version {}", version));
+        }
+
+        let count = reader.u32()? as usize;
+        let mut layers = Vec::new();
+        for _ in 0..count {
+            let inputs = reader.u32()? as usize;
+            let outputs = reader.u32()? as usize;
+            let activation = activation_from_code(reader.take(1)?[0])?;
+            let weights = reader.f32s(inputs.checked_mul(outputs).ok_or("Model too large")?)?;
+            let biases = reader.f32s(outputs)?;
+            layers.push(InferenceLayer::new(inputs, weights, biases, activation)?);
+        }
+        if reader.offset != bytes.len() {
+            return Err(format!(
+                "{} trailing bytes after the model",
+                bytes.len() - reader.offset
+            ));
+        }
+        Self::new(layers)
+    }
+}
+
+pub(crate) struct Reader<'a> {
+    pub(crate) bytes: &'a [u8],
+    pub(crate) offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| format!("Model truncated at byte {}", self.offset))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn f32s(&mut self, count: usize) -> Result<Vec<f32>, String> {
+        let bytes = self.take(count.checked_mul(4).ok_or("Model too large")?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+            .collect())
+    }
+}
+
+pub(crate) fn activation_code(activation: Activation) -> u8 {
+    match activation {
+        Activation::Sigmoid => 0,
+        Activation::Tanh => 1,
+        Activation::Relu => 2,
+        Activation::Linear => 3,
+        Activation::Step => 4,
+        Activation::Sign => 5,
+        #[cfg(feature = "std")]
+        Activation::Custom(_) => unreachable!("InferenceLayer::new rejects custom activations"),
+    }
+}
+
+pub(crate) fn activation_from_code(code: u8) -> Result<Activation, String> {
+    match code {
+        0 => Ok(Activation::Sigmoid),
+        1 => Ok(Activation::Tanh),
+        2 => Ok(Activation::Relu),
+        3 => Ok(Activation::Linear),
+        4 => Ok(Activation::Step),
+        5 => Ok(Activation::Sign),
+        _ => Err(format!("Unknown activation code {}", code)),
+    }
+}
+
+/// `activation` applied to `x`, saturating like `Activation::apply`
+pub fn activate(activation: Activation, x: f32) -> f32 {
+    let x = if x.is_nan() { 0.0 } else { x };
+    match activation {
+        Activation::Sigmoid if x >= 0.0 => 1.0 / (1.0 + exp(-x)),
+        Activation::Sigmoid => {
+            let e = exp(x);
+            e / (1.0 + e)
+        }
+        // 1 - 2 / (e^2x + 1) stays finite for large |x|
+        Activation::Tanh => 1.0 - 2.0 / (exp(2.0 * x) + 1.0),
+        Activation::Relu => x.max(0.0),
+        Activation::Linear => x,
+        Activation::Step if x >= 0.0 => 1.0,
+        Activation::Step => 0.0,
+        Activation::Sign if x >= 0.0 => 1.0,
+        Activation::Sign => -1.0,
+        #[cfg(feature = "std")]
+        Activation::Custom(custom) => custom.apply(x as f64) as f32,
+    }
+}
+
+/// `e^x` to within a few ulps, without the standard library
+///
+/// Splits `x` into `n ln 2 + r` with `|r| <= ln 2 / 2`, evaluates `e^r` with a
+/// degree 7 Taylor polynomial and scales by `2^n` through the exponent bits.
+pub fn exp(x: f32) -> f32 {
+    if x.is_nan() {
+        return x;
+    }
+    if x > 88.72 {
+        return f32::INFINITY;
+    }
+    if x < -87.3 {
+        return 0.0;
+    }
+
+    let n = x * core::f32::consts::LOG2_E;
+    let n = if n >= 0.0 { n + 0.5 } else { n - 0.5 } as i32;
+    // ln 2 split in two so n * LN2_HI is exact
+    const LN2_HI: f32 = 0.693_145_75;
+    const LN2_LO: f32 = 1.428_606_8e-6;
+    let r = (x - n as f32 * LN2_HI) - n as f32 * LN2_LO;
+
+    let mut poly = 1.0 / 5040.0;
+    for coefficient in [
+        1.0 / 720.0,
+        1.0 / 120.0,
+        1.0 / 24.0,
+        1.0 / 6.0,
+        0.5,
+        1.0,
+        1.0,
+    ] {
+        poly = poly * r + coefficient;
+    }
+    // 2^128 has no f32 exponent, so near the overflow limit scale in two steps
+    let (n, scale) = if n > 127 { (n - 1, 2.0) } else { (n, 1.0) };
+    poly * scale * f32::from_bits(((n + 127) as u32) << 23)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exp_matches_std() {
+        for i in -870..=880 {
+            let x = i as f32 / 10.0;
+            let (ours, reference) = (exp(x), x.exp());
+            assert!(
+                (ours - reference).abs() <= reference * 2e-6,
+                "exp({}) = {} vs {}",
+                x,
+                ours,
+                reference
+            );
+        }
+        assert_eq!(exp(100.0), f32::INFINITY);
+        assert_eq!(exp(-100.0), 0.0);
+        assert_eq!(activate(Activation::Sigmoid, 1e30), 1.0);
+        assert_eq!(activate(Activation::Tanh, -1e30), -1.0);
+        assert_eq!(activate(Activation::Sigmoid, f32::NAN), 0.5);
+    }
+
+    #[test]
+    fn test_model_bytes_roundtrip() {
+        let hidden = InferenceLayer::new(
+            2,
+            vec![0.5, -1.0, 2.0, 0.25],
+            vec![0.1, -0.1],
+            Activation::Relu,
+        )
+        .unwrap();
+        let output = InferenceLayer::new(2, vec![1.0, -1.0], vec![0.0], Activation::Tanh).unwrap();
+        let model = InferenceModel::new(vec![hidden, output]).unwrap();
+
+        let bytes = model.to_bytes();
+        let restored = InferenceModel::from_bytes(&bytes).unwrap();
+        assert_eq!(restored, model);
+        assert_eq!(restored.forward(&[1.0, 0.5]), model.forward(&[1.0, 0.5]));
+        // relu(0.5 - 0.5 + 0.1) = 0.1, relu(2.0 + 0.125 - 0.1) = 2.025
+        let expected = (0.1f32 - 2.025).tanh();
+        assert!((model.forward(&[1.0, 0.5])[0] - expected).abs() < 1e-6);
+
+        assert!(InferenceModel::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(InferenceModel::from_bytes(b"nope").is_err());
+        assert!(InferenceLayer::new(3, vec![0.0; 4], vec![0.0; 2], Activation::Linear).is_err());
+        let wide = InferenceLayer::new(3, vec![0.0; 3], vec![0.0], Activation::Linear).unwrap();
+        assert!(InferenceModel::new(vec![model.layers()[0].clone(), wide]).is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+
+/// How values are mapped onto a network whose input size differs from the source's
+///
+/// Applied by `InputNode::send_data` and by `NetworkComposer` connections
+/// whose source and target index lists differ in length.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputAdapter {
+    /// Fail on any size mismatch
+    #[default]
+    Reject,
+    /// Drop surplus values; missing ones are zero
+    Truncate,
+    /// Fill missing values with a constant; surplus values are dropped
+    Pad { value: f64 },
+    /// Resample the values linearly onto the target size
+    Interpolate,
+    /// Trainable linear map from the source size to the target size
+    Projection(LinearProjection),
+}
+
+impl InputAdapter {
+    /// Map `data` onto `size` values
+    pub fn adapt(&self, data: &[f64], size: usize) -> Result<Vec<f64>, String> {
+        if data.len() == size && !matches!(self, InputAdapter::Projection(_)) {
+            return Ok(data.to_vec());
+        }
+        match self {
+            InputAdapter::Reject => Err(format!(
+                "Input size mismatch: expected {}, got {}",
+                size,
+                data.len()
+            )),
+            InputAdapter::Truncate => Ok(pad(data, size, 0.0)),
+            InputAdapter::Pad { value } => Ok(pad(data, size, *value)),
+            InputAdapter::Interpolate => Ok(interpolate(data, size)),
+            InputAdapter::Projection(projection) => projection.project(data, size),
+        }
+    }
+}
+
+impl std::str::FromStr for InputAdapter {
+    type Err = String;
+
+    /// Parse `reject`, `truncate`, `interpolate` or `pad[:value]`; projections are configured in files
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.to_ascii_lowercase();
+        match lower.split_once(':') {
+            Some(("pad", value)) => value
+                .parse()
+                .map(|value| InputAdapter::Pad { value })
+                .map_err(|_| format!("Invalid pad value '{}'", value)),
+            _ => match lower.as_str() {
+                "reject" => Ok(InputAdapter::Reject),
+                "truncate" => Ok(InputAdapter::Truncate),
+                "pad" => Ok(InputAdapter::Pad { value: 0.0 }),
+                "interpolate" => Ok(InputAdapter::Interpolate),
+                _ => Err(format!(
+                    "Unknown input adapter '{}' (expected reject, truncate, pad[:value] or interpolate)",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+fn pad(data: &[f64], size: usize, value: f64) -> Vec<f64> {
+    let mut values = data[..data.len().min(size)].to_vec();
+    values.resize(size, value);
+    values
+}
+
+fn interpolate(data: &[f64], size: usize) -> Vec<f64> {
+    (0..size)
+        .map(|i| {
+            interpolation_weights(data.len(), size, i)
+                .map(|(j, w)| data[j] * w)
+                .sum()
+        })
+        .collect()
+}
+
+/// Source indices and weights blended into output `i` when resampling `from` values to `to`
+fn interpolation_weights(from: usize, to: usize, i: usize) -> impl Iterator<Item = (usize, f64)> {
+    let position = if from < 2 || to < 2 {
+        0.0
+    } else {
+        i as f64 * (from - 1) as f64 / (to - 1) as f64
+    };
+    let lower = (position.floor() as usize).min(from.saturating_sub(1));
+    let upper = (lower + 1).min(from.saturating_sub(1));
+    let fraction = position - lower as f64;
+    let weights = if from == 0 {
+        vec![]
+    } else if upper == lower {
+        vec![(lower, 1.0)]
+    } else {
+        vec![(lower, 1.0 - fraction), (upper, fraction)]
+    };
+    weights.into_iter()
+}
+
+/// Linear projection `outputs = weights * inputs + biases`
+///
+/// Starts out as linear interpolation and is refined with `learn` from pairs
+/// of source values and the inputs the target network should have seen.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LinearProjection {
+    weights: Vec<Vec<f64>>, // [output][input]
+    biases: Vec<f64>,
+}
+
+impl LinearProjection {
+    pub fn new(inputs: usize, outputs: usize) -> Self {
+        let weights = (0..outputs)
+            .map(|i| {
+                let mut row = vec![0.0; inputs];
+                for (j, w) in interpolation_weights(inputs, outputs, i) {
+                    row[j] += w;
+                }
+                row
+            })
+            .collect();
+        Self {
+            weights,
+            biases: vec![0.0; outputs],
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.weights.first().map_or(0, Vec::len)
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.biases.len()
+    }
+
+    fn project(&self, data: &[f64], size: usize) -> Result<Vec<f64>, String> {
+        if data.len() != self.inputs() || size != self.outputs() {
+            return Err(format!(
+                "Projection maps {} values to {}, got {} values for {} inputs",
+                self.inputs(),
+                self.outputs(),
+                data.len(),
+                size
+            ));
+        }
+        Ok(self
+            .weights
+            .iter()
+            .zip(&self.biases)
+            .map(|(row, bias)| bias + row.iter().zip(data).map(|(w, x)| w * x).sum::<f64>())
+            .collect())
+    }
+
+    /// One least-mean-squares step towards mapping `data` onto `target`, returning the squared error before the step
+    pub fn learn(
+        &mut self,
+        data: &[f64],
+        target: &[f64],
+        learning_rate: f64,
+    ) -> Result<f64, String> {
+        let outputs = self.project(data, target.len())?;
+        let mut error = 0.0;
+        for ((row, bias), (output, target)) in self
+            .weights
+            .iter_mut()
+            .zip(&mut self.biases)
+            .zip(outputs.iter().zip(target))
+        {
+            let delta = target - output;
+            error += delta * delta;
+            for (w, x) in row.iter_mut().zip(data) {
+                *w += learning_rate * delta * x;
+            }
+            *bias += learning_rate * delta;
+        }
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_adapters() {
+        let data = [1.0, 2.0, 3.0];
+        assert!(InputAdapter::Reject.adapt(&data, 2).is_err());
+        assert_eq!(InputAdapter::Reject.adapt(&data, 3), Ok(data.to_vec()));
+        assert_eq!(InputAdapter::Truncate.adapt(&data, 2), Ok(vec![1.0, 2.0]));
+        assert_eq!(
+            InputAdapter::Truncate.adapt(&data, 4),
+            Ok(vec![1.0, 2.0, 3.0, 0.0])
+        );
+        assert_eq!(
+            InputAdapter::Pad { value: -1.0 }.adapt(&data, 5),
+            Ok(vec![1.0, 2.0, 3.0, -1.0, -1.0])
+        );
+        assert_eq!(
+            InputAdapter::Interpolate.adapt(&data, 5),
+            Ok(vec![1.0, 1.5, 2.0, 2.5, 3.0])
+        );
+        assert_eq!(
+            InputAdapter::Interpolate.adapt(&data, 2),
+            Ok(vec![1.0, 3.0])
+        );
+        assert_eq!(
+            InputAdapter::Interpolate.adapt(&[4.0], 2),
+            Ok(vec![4.0, 4.0])
+        );
+        assert_eq!(InputAdapter::Interpolate.adapt(&[], 2), Ok(vec![0.0, 0.0]));
+
+        assert_eq!("pad:0.5".parse(), Ok(InputAdapter::Pad { value: 0.5 }));
+        assert_eq!("Interpolate".parse(), Ok(InputAdapter::Interpolate));
+        assert!("projection".parse::<InputAdapter>().is_err());
+    }
+
+    #[test]
+    fn test_projection_learns_mapping() {
+        let mut projection = LinearProjection::new(2, 3);
+        let adapter = InputAdapter::Projection(projection.clone());
+        assert_eq!(adapter.adapt(&[2.0, 4.0], 3), Ok(vec![2.0, 3.0, 4.0]));
+        assert!(adapter.adapt(&[2.0, 4.0, 6.0], 3).is_err());
+
+        // Learn to swap and sum the inputs
+        let samples = [[1.0, 0.0], [0.0, 1.0], [0.5, 0.25], [0.3, 0.9]];
+        let mut error = f64::MAX;
+        for _ in 0..2000 {
+            error = 0.0;
+            for [a, b] in samples {
+                error += projection.learn(&[a, b], &[b, a, a + b], 0.2).unwrap();
+            }
+        }
+        assert!(error < 1e-6, "error {}", error);
+        let adapter = InputAdapter::Projection(projection);
+        let mapped = adapter.adapt(&[0.2, 0.7], 3).unwrap();
+        for (value, expected) in mapped.iter().zip([0.7, 0.2, 0.9]) {
+            assert!((value - expected).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,485 @@
+use crate::scaling::ValueRange;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Device a bridge reads controller events from
+///
+/// Both sources are character devices of the Linux kernel, read without a
+/// gamepad or MIDI library: other platforms have no device to point them at.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ControllerSource {
+    /// Joystick device as the Linux joydev driver exposes gamepads (`/dev/input/jsN`)
+    Gamepad {
+        #[serde(default = "default_gamepad")]
+        device: PathBuf,
+    },
+    /// Raw MIDI byte stream: an ALSA raw MIDI device (`/dev/snd/midiCxDy`) or a FIFO
+    Midi { device: PathBuf },
+}
+
+fn default_gamepad() -> PathBuf {
+    PathBuf::from("/dev/input/js0")
+}
+
+impl ControllerSource {
+    pub fn device(&self) -> &Path {
+        match self {
+            ControllerSource::Gamepad { device } | ControllerSource::Midi { device } => device,
+        }
+    }
+}
+
+/// Event of a gamepad or MIDI controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerEvent {
+    Axis {
+        number: u8,
+        value: i16,
+    },
+    Button {
+        number: u8,
+        pressed: bool,
+    },
+    ControlChange {
+        channel: u8,
+        controller: u8,
+        value: u8,
+    },
+    NoteOn {
+        channel: u8,
+        note: u8,
+        velocity: u8,
+    },
+    NoteOff {
+        channel: u8,
+        note: u8,
+    },
+}
+
+/// Decode one 8-byte `js_event` of the Linux joystick API
+///
+/// Events flagged as initial state are decoded like live ones, so the
+/// bridge starts from the pad's actual position.
+pub fn parse_joystick_event(bytes: [u8; 8]) -> Option<ControllerEvent> {
+    let value = i16::from_le_bytes([bytes[4], bytes[5]]);
+    let number = bytes[7];
+    match bytes[6] & 0x7f {
+        0x01 => Some(ControllerEvent::Button {
+            number,
+            pressed: value != 0,
+        }),
+        0x02 => Some(ControllerEvent::Axis { number, value }),
+        _ => None,
+    }
+}
+
+/// Incremental MIDI decoder keeping the running status
+#[derive(Debug, Clone, Default)]
+pub struct MidiParser {
+    status: Option<u8>,
+    data: Vec<u8>,
+}
+
+impl MidiParser {
+    /// Feed one byte, returning the event it completes
+    ///
+    /// Messages other than notes and control changes are skipped.
+    pub fn push(&mut self, byte: u8) -> Option<ControllerEvent> {
+        match byte {
+            // Real-time messages may appear anywhere and leave the running status alone
+            0xf8..=0xff => return None,
+            0x80..=0xef => {
+                self.status = Some(byte);
+                self.data.clear();
+                return None;
+            }
+            // System common and exclusive messages cancel the running status
+            0xf0..=0xf7 => {
+                self.status = None;
+                self.data.clear();
+                return None;
+            }
+            _ => {}
+        }
+        let status = self.status?;
+        self.data.push(byte);
+        let length = match status & 0xf0 {
+            0xc0 | 0xd0 => 1,
+            _ => 2,
+        };
+        if self.data.len() < length {
+            return None;
+        }
+        let data = std::mem::take(&mut self.data);
+        let channel = status & 0x0f;
+        match status & 0xf0 {
+            0x80 => Some(ControllerEvent::NoteOff {
+                channel,
+                note: data[0],
+            }),
+            0x90 if data[1] == 0 => Some(ControllerEvent::NoteOff {
+                channel,
+                note: data[0],
+            }),
+            0x90 => Some(ControllerEvent::NoteOn {
+                channel,
+                note: data[0],
+                velocity: data[1],
+            }),
+            0xb0 => Some(ControllerEvent::ControlChange {
+                channel,
+                controller: data[0],
+                value: data[1],
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Control of a controller that drives an input
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "control")]
+pub enum Control {
+    /// Gamepad axis, spread from one end of the range to the other
+    Axis { number: u8 },
+    /// Gamepad button: the range's max while pressed, its min otherwise
+    Button { number: u8 },
+    /// MIDI control change 0-127 over the range; any channel unless given
+    ControlChange {
+        #[serde(default)]
+        channel: Option<u8>,
+        controller: u8,
+    },
+    /// MIDI note: its velocity over the range while held, the range's min once released
+    Note {
+        #[serde(default)]
+        channel: Option<u8>,
+        note: u8,
+    },
+}
+
+impl Control {
+    /// Position of the control within `[0, 1]` after `event`, if the event concerns it
+    pub fn level(&self, event: &ControllerEvent) -> Option<f64> {
+        let on_channel = |wanted: &Option<u8>, channel: &u8| wanted.is_none_or(|c| c == *channel);
+        match (self, event) {
+            (Control::Axis { number }, ControllerEvent::Axis { number: n, value })
+                if number == n =>
+            {
+                Some((*value as f64 + 32767.0) / 65534.0)
+            }
+            (Control::Button { number }, ControllerEvent::Button { number: n, pressed })
+                if number == n =>
+            {
+                Some(if *pressed { 1.0 } else { 0.0 })
+            }
+            (
+                Control::ControlChange {
+                    channel: wanted,
+                    controller,
+                },
+                ControllerEvent::ControlChange {
+                    channel,
+                    controller: c,
+                    value,
+                },
+            ) if controller == c && on_channel(wanted, channel) => Some(*value as f64 / 127.0),
+            (
+                Control::Note {
+                    channel: wanted,
+                    note,
+                },
+                ControllerEvent::NoteOn {
+                    channel,
+                    note: n,
+                    velocity,
+                },
+            ) if note == n && on_channel(wanted, channel) => Some(*velocity as f64 / 127.0),
+            (
+                Control::Note {
+                    channel: wanted,
+                    note,
+                },
+                ControllerEvent::NoteOff { channel, note: n },
+            ) if note == n && on_channel(wanted, channel) => Some(0.0),
+            _ => None,
+        }
+    }
+
+    /// Position assumed until the first event: centered axes, everything else released
+    fn rest_level(&self) -> f64 {
+        match self {
+            Control::Axis { .. } => 0.5,
+            _ => 0.0,
+        }
+    }
+}
+
+/// One control driving one input of the target
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ControlMapping {
+    #[serde(flatten)]
+    pub control: Control,
+    /// Index of the input the control sets
+    pub input: usize,
+    /// Values the control spans
+    #[serde(default = "unit_range")]
+    pub range: ValueRange,
+    /// Run from the range's max to its min instead
+    #[serde(default)]
+    pub invert: bool,
+}
+
+fn unit_range() -> ValueRange {
+    ValueRange::UNIT
+}
+
+impl ControlMapping {
+    fn value(&self, level: f64) -> f64 {
+        let level = if self.invert { 1.0 - level } else { level };
+        self.range.min + level.clamp(0.0, 1.0) * (self.range.max - self.range.min)
+    }
+}
+
+/// Gamepad or MIDI controller feeding an InputServer target
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputBridgeConfig {
+    pub source: ControllerSource,
+    /// InputServer target the inputs are sent to
+    pub target_id: String,
+    pub mappings: Vec<ControlMapping>,
+    /// Samples sent per second, whether or not a control moved
+    #[serde(default = "default_rate_hz")]
+    pub rate_hz: f64,
+}
+
+fn default_rate_hz() -> f64 {
+    30.0
+}
+
+impl InputBridgeConfig {
+    /// Check the bridge against a target with `input_count` inputs
+    pub fn validate(&self, input_count: usize) -> Result<(), String> {
+        if !(self.rate_hz > 0.0 && self.rate_hz.is_finite()) {
+            return Err(format!("Bridge rate {} Hz must be positive", self.rate_hz));
+        }
+        if self.mappings.is_empty() {
+            return Err("An input bridge needs at least one mapping".to_string());
+        }
+        for mapping in &self.mappings {
+            if mapping.input >= input_count {
+                return Err(format!(
+                    "{:?} drives input {} of a target with {} inputs",
+                    mapping.control, mapping.input, input_count
+                ));
+            }
+            let range = mapping.range;
+            if !(range.min.is_finite() && range.max.is_finite() && range.min < range.max) {
+                return Err(format!(
+                    "Invalid range of {:?}: [{}, {}]",
+                    mapping.control, range.min, range.max
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Time between two samples
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate_hz)
+    }
+}
+
+/// Current inputs of a bridge, updated by every controller event
+#[derive(Debug, Clone)]
+pub struct InputBridge {
+    config: InputBridgeConfig,
+    inputs: Vec<f64>,
+}
+
+impl InputBridge {
+    /// Validate `config` for a target with `input_count` inputs; inputs no control drives stay 0
+    pub fn new(config: InputBridgeConfig, input_count: usize) -> Result<Self, String> {
+        config.validate(input_count)?;
+        let mut inputs = vec![0.0; input_count];
+        for mapping in &config.mappings {
+            inputs[mapping.input] = mapping.value(mapping.control.rest_level());
+        }
+        Ok(Self { config, inputs })
+    }
+
+    pub fn config(&self) -> &InputBridgeConfig {
+        &self.config
+    }
+
+    /// Move every input mapped to the control `event` concerns
+    pub fn apply(&mut self, event: &ControllerEvent) {
+        for mapping in &self.config.mappings {
+            if let Some(level) = mapping.control.level(event) {
+                self.inputs[mapping.input] = mapping.value(level);
+            }
+        }
+    }
+
+    pub fn inputs(&self) -> &[f64] {
+        &self.inputs
+    }
+}
+
+/// Read events from `source` on a dedicated thread until the receiver is dropped
+///
+/// A device that cannot be opened or goes away, e.g. an unplugged pad, is
+/// reopened every second.
+pub fn spawn_reader(source: ControllerSource) -> mpsc::UnboundedReceiver<ControllerEvent> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    std::thread::spawn(move || {
+        while !sender.is_closed() {
+            match File::open(source.device()) {
+                Ok(mut device) => {
+                    println!(
+                        "🎮 Reading controller events from {}",
+                        source.device().display()
+                    );
+                    let result = match source {
+                        ControllerSource::Gamepad { .. } => read_gamepad(&mut device, &sender),
+                        ControllerSource::Midi { .. } => read_midi(&mut device, &sender),
+                    };
+                    if let Err(e) = result {
+                        println!("❌ Controller {}: {}", source.device().display(), e);
+                    }
+                }
+                Err(e) => println!(
+                    "❌ Cannot open controller {}: {}",
+                    source.device().display(),
+                    e
+                ),
+            }
+            std::thread::sleep(Duration::from_secs(1));
+        }
+    });
+    receiver
+}
+
+fn read_gamepad(
+    device: &mut File,
+    sender: &mpsc::UnboundedSender<ControllerEvent>,
+) -> std::io::Result<()> {
+    let mut bytes = [0u8; 8];
+    loop {
+        device.read_exact(&mut bytes)?;
+        if let Some(event) = parse_joystick_event(bytes) {
+            if sender.send(event).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+fn read_midi(
+    device: &mut File,
+    sender: &mpsc::UnboundedSender<ControllerEvent>,
+) -> std::io::Result<()> {
+    let mut parser = MidiParser::default();
+    let mut buffer = [0u8; 64];
+    loop {
+        let read = device.read(&mut buffer)?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        for event in buffer[..read].iter().filter_map(|&byte| parser.push(byte)) {
+            if sender.send(event).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_controller_mapping() {
+        // Initial state of axis 1 at full deflection, then button 0 pressed
+        assert_eq!(
+            parse_joystick_event([0, 0, 0, 0, 0xff, 0x7f, 0x82, 1]),
+            Some(ControllerEvent::Axis {
+                number: 1,
+                value: 32767
+            })
+        );
+        assert_eq!(
+            parse_joystick_event([9, 0, 0, 0, 1, 0, 0x01, 0]),
+            Some(ControllerEvent::Button {
+                number: 0,
+                pressed: true
+            })
+        );
+
+        // Note on, a clock tick, two control changes in running status, then a note off
+        let mut parser = MidiParser::default();
+        let events: Vec<ControllerEvent> = [0x91, 60, 100, 0xf8, 0xb1, 7, 64, 7, 127, 0x91, 60, 0]
+            .into_iter()
+            .filter_map(|byte| parser.push(byte))
+            .collect();
+        let change = |value| ControllerEvent::ControlChange {
+            channel: 1,
+            controller: 7,
+            value,
+        };
+        assert_eq!(
+            events,
+            vec![
+                ControllerEvent::NoteOn {
+                    channel: 1,
+                    note: 60,
+                    velocity: 100
+                },
+                change(64),
+                change(127),
+                ControllerEvent::NoteOff {
+                    channel: 1,
+                    note: 60
+                },
+            ]
+        );
+
+        let config: InputBridgeConfig = serde_json::from_str(
+            r#"{
+                "source": {"type": "Midi", "device": "/dev/snd/midiC1D0"},
+                "target_id": "main",
+                "mappings": [
+                    {"control": "Axis", "number": 0, "input": 0, "range": {"min": -1, "max": 1}},
+                    {"control": "ControlChange", "channel": 1, "controller": 7, "input": 1, "invert": true},
+                    {"control": "Note", "note": 60, "input": 2}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.rate_hz, 30.0);
+        assert!(config.validate(2).unwrap_err().contains("input 2"));
+        let mut bridge = InputBridge::new(config, 4).unwrap();
+        assert_eq!(bridge.inputs(), &[0.0, 1.0, 0.0, 0.0]);
+        bridge.apply(&events[0]);
+        assert_eq!(bridge.inputs()[2], 100.0 / 127.0);
+        for event in &events[1..] {
+            bridge.apply(event);
+        }
+        bridge.apply(&ControllerEvent::Axis {
+            number: 0,
+            value: -32767,
+        });
+        // Control changes on other channels are ignored
+        bridge.apply(&ControllerEvent::ControlChange {
+            channel: 2,
+            controller: 7,
+            value: 0,
+        });
+        assert_eq!(bridge.inputs(), &[-1.0, 0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// What values one input of a network target takes, and how the web interface offers it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum InputConstraint {
+    /// Any value in `[min, max]`, on whole `step`s above `min` when given; shown as a slider
+    Continuous {
+        min: f64,
+        max: f64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        step: Option<f64>,
+    },
+    /// Either `off` or `on`; shown as a switch
+    Toggle {
+        #[serde(default)]
+        off: f64,
+        #[serde(default = "default_on")]
+        on: f64,
+    },
+    /// The value of one of the options; shown as a dropdown
+    Categorical { options: Vec<InputOption> },
+}
+
+fn default_on() -> f64 {
+    1.0
+}
+
+/// Choice of a categorical input
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputOption {
+    pub label: String,
+    pub value: f64,
+}
+
+/// Largest distance from a whole step still taken as on it, in steps
+const STEP_TOLERANCE: f64 = 1e-6;
+
+impl InputConstraint {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            InputConstraint::Continuous { min, max, step } => {
+                if !(min.is_finite() && max.is_finite() && min < max) {
+                    return Err(format!("Invalid range [{}, {}]", min, max));
+                }
+                if let Some(step) = step {
+                    if !(step.is_finite() && *step > 0.0) {
+                        return Err(format!("Step {} must be positive", step));
+                    }
+                }
+                Ok(())
+            }
+            InputConstraint::Toggle { off, on } => {
+                if !(off.is_finite() && on.is_finite()) || off == on {
+                    return Err(format!("Toggle values {} and {} must differ", off, on));
+                }
+                Ok(())
+            }
+            InputConstraint::Categorical { options } => {
+                if options.is_empty() {
+                    return Err("Categorical input without options".to_string());
+                }
+                let mut labels = HashSet::new();
+                let mut values = Vec::new();
+                for option in options {
+                    if !option.value.is_finite() {
+                        return Err(format!(
+                            "Option {} has value {}",
+                            option.label, option.value
+                        ));
+                    }
+                    if !labels.insert(option.label.as_str()) || values.contains(&option.value) {
+                        return Err(format!(
+                            "Option {} = {} repeats another option",
+                            option.label, option.value
+                        ));
+                    }
+                    values.push(option.value);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether `value` is one this input takes
+    pub fn check(&self, value: f64) -> Result<(), String> {
+        match self {
+            InputConstraint::Continuous { min, max, step } => {
+                if !(value >= *min && value <= *max) {
+                    return Err(format!("{} is outside [{}, {}]", value, min, max));
+                }
+                if let Some(step) = step {
+                    let steps = (value - min) / step;
+                    if (steps - steps.round()).abs() > STEP_TOLERANCE {
+                        return Err(format!(
+                            "{} is not {} plus a multiple of {}",
+                            value, min, step
+                        ));
+                    }
+                }
+                Ok(())
+            }
+            InputConstraint::Toggle { off, on } => {
+                if value == *off || value == *on {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "{} is neither {} (off) nor {} (on)",
+                        value, off, on
+                    ))
+                }
+            }
+            InputConstraint::Categorical { options } => {
+                if options.iter().any(|option| option.value == value) {
+                    Ok(())
+                } else {
+                    let values: Vec<String> = options
+                        .iter()
+                        .map(|option| format!("{} ({})", option.value, option.label))
+                        .collect();
+                    Err(format!("{} is not one of {}", value, values.join(", ")))
+                }
+            }
+        }
+    }
+}
+
+/// Check the constraints of a target with `input_count` inputs: none at all, or one per input
+pub fn validate_profile(constraints: &[InputConstraint], input_count: usize) -> Result<(), String> {
+    if !constraints.is_empty() && constraints.len() != input_count {
+        return Err(format!(
+            "{} input constraints for {} inputs",
+            constraints.len(),
+            input_count
+        ));
+    }
+    for (i, constraint) in constraints.iter().enumerate() {
+        constraint
+            .validate()
+            .map_err(|e| format!("Input {}: {}", i, e))?;
+    }
+    Ok(())
+}
+
+/// Check submitted `inputs` against a target's constraints; anything goes without constraints
+pub fn check_inputs(constraints: &[InputConstraint], inputs: &[f64]) -> Result<(), String> {
+    if constraints.is_empty() {
+        return Ok(());
+    }
+    if inputs.len() != constraints.len() {
+        return Err(format!(
+            "Expected {} inputs, got {}",
+            constraints.len(),
+            inputs.len()
+        ));
+    }
+    for (i, (constraint, value)) in constraints.iter().zip(inputs).enumerate() {
+        constraint
+            .check(*value)
+            .map_err(|e| format!("Input {}: {}", i, e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_constraints() {
+        let profile: Vec<InputConstraint> = serde_json::from_str(
+            r#"[
+                {"type": "Continuous", "min": -1, "max": 1, "step": 0.25},
+                {"type": "Toggle"},
+                {"type": "Categorical", "options": [
+                    {"label": "walk", "value": 0},
+                    {"label": "run", "value": 2}
+                ]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(profile[1], InputConstraint::Toggle { off: 0.0, on: 1.0 });
+        validate_profile(&profile, 3).unwrap();
+        assert!(validate_profile(&profile, 4).is_err());
+        assert!(validate_profile(&[], 4).is_ok());
+
+        check_inputs(&profile, &[0.75, 1.0, 2.0]).unwrap();
+        check_inputs(&[], &[5.0]).unwrap();
+        let error = |inputs: &[f64]| check_inputs(&profile, inputs).unwrap_err();
+        assert_eq!(error(&[1.5, 0.0, 0.0]), "Input 0: 1.5 is outside [-1, 1]");
+        assert!(error(&[0.3, 0.0, 0.0]).contains("multiple of 0.25"));
+        assert!(error(&[0.0, 0.5, 0.0]).starts_with("Input 1: 0.5 is neither"));
+        assert!(error(&[0.0, 0.0, 1.0]).contains("0 (walk), 2 (run)"));
+        assert!(error(&[f64::NAN, 0.0, 0.0]).contains("outside"));
+        assert_eq!(error(&[0.0]), "Expected 3 inputs, got 1");
+
+        let invalid = [
+            InputConstraint::Continuous {
+                min: 1.0,
+                max: 1.0,
+                step: None,
+            },
+            InputConstraint::Toggle { off: 1.0, on: 1.0 },
+            InputConstraint::Categorical {
+                options: Vec::new(),
+            },
+        ];
+        assert!(invalid
+            .iter()
+            .all(|constraint| constraint.validate().is_err()));
+    }
+}
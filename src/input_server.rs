@@ -1,15 +1,33 @@
-use crate::io_interface::{InputNode, IoNodeConfig};
-use futures_util::{SinkExt, StreamExt};
+use crate::address::{self, BindOptions, BoundPorts};
+use crate::audio_input::{self, AudioInputConfig, FeatureExtractor};
+use crate::broadcast::{self, Broadcaster, WebSocketClient};
+use crate::camera_input::{self, CameraInputConfig};
+use crate::closed_loop::{LoopConfig, LoopController};
+use crate::display::DisplayMetadata;
+use crate::distributed_network::PeerInfo;
+use crate::health::{self, ComponentHealth, HealthReport, DEFAULT_QUEUE_THRESHOLD};
+use crate::input_adapter::InputAdapter;
+use crate::input_bridge::{self, InputBridge, InputBridgeConfig};
+use crate::input_constraints::{self, InputConstraint};
+use crate::io_interface::{Delivery, InputNode, IoNodeConfig};
+use crate::output_server::OutputWebSocketMessage;
+use crate::reconnect::ConnectionStatus;
+use crate::reload::{self, ReloadReport};
+use crate::scripting::{ScriptConfig, ScriptTransform};
+use crate::session_recorder::{RecordingConfig, SessionRecorder};
+use futures_util::StreamExt;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Response, Server, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{RwLock, mpsc};
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{RwLock, watch};
+use tokio::time::MissedTickBehavior;
+use tokio_tungstenite::{accept_async, connect_async, tungstenite::Message};
 use uuid::Uuid;
 
 /// Configuration for the InputServer
@@ -27,10 +45,49 @@ pub struct InputServerConfig {
     pub cert_path: Option<String>,
     /// SSL key path (optional)
     pub key_path: Option<String>,
+    /// Address family preference and dual-stack behaviour for all listeners
+    #[serde(default)]
+    pub bind_options: BindOptions,
+    /// Record the session's inputs into a training dataset (optional)
+    #[serde(default)]
+    pub recording: Option<RecordingConfig>,
+    /// Feed observed outputs back to a target as its inputs (optional)
+    #[serde(default)]
+    pub closed_loop: Option<LoopConfig>,
+    /// Drive a target from a gamepad or MIDI controller (optional, Linux only)
+    #[serde(default)]
+    pub input_bridge: Option<InputBridgeConfig>,
+    /// Stream spectral features of live audio into a target (optional, needs an external
+    /// capture program or device file)
+    #[serde(default)]
+    pub audio_input: Option<AudioInputConfig>,
+    /// Stream downsampled camera frames into a target (optional)
+    #[serde(default)]
+    pub camera_input: Option<CameraInputConfig>,
+}
+
+impl InputServerConfig {
+    /// Loopback listeners on OS-assigned ports, for tests; see `InputServer::bound_ports`
+    pub fn ephemeral(neural_networks: Vec<NeuralNetworkTarget>) -> Self {
+        Self {
+            web_address: "127.0.0.1".to_string(),
+            web_port: 0,
+            websocket_port: 0,
+            neural_networks,
+            cert_path: None,
+            key_path: None,
+            bind_options: BindOptions::default(),
+            recording: None,
+            closed_loop: None,
+            input_bridge: None,
+            audio_input: None,
+            camera_input: None,
+        }
+    }
 }
 
 /// Configuration for a target neural network
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NeuralNetworkTarget {
     /// Unique identifier for this network
     pub id: String,
@@ -44,6 +101,18 @@ pub struct NeuralNetworkTarget {
     pub input_count: usize,
     /// Use TLS for connection
     pub use_tls: bool,
+    /// Mapping applied to submitted values whose count differs from `input_count`
+    #[serde(default)]
+    pub input_adapter: InputAdapter,
+    /// Script run on submitted values before they are sent (optional)
+    #[serde(default)]
+    pub preprocess: Option<ScriptConfig>,
+    /// Color, description, input labels, units and slider ranges shown by the web interface
+    #[serde(default)]
+    pub display: DisplayMetadata,
+    /// Type and allowed values of every input, checked before sending (optional)
+    #[serde(default)]
+    pub constraints: Vec<InputConstraint>,
 }
 
 /// WebSocket message types
@@ -67,6 +136,10 @@ pub enum WebSocketMessage {
     },
     /// Server sends status update
     StatusUpdate { network_id: String, status: String },
+    /// Client asks a network for its architecture and statistics
+    GetNetworkDetails { network_id: String },
+    /// Server responds with what the network reported about itself
+    NetworkDetails { network_id: String, info: PeerInfo },
     /// Error message
     Error { message: String },
 }
@@ -81,33 +154,134 @@ pub struct NetworkInfo {
     pub input_count: usize,
     pub connected: bool,
     pub use_tls: bool,
+    #[serde(default)]
+    pub display: DisplayMetadata,
+    #[serde(default)]
+    pub constraints: Vec<InputConstraint>,
+}
+
+/// What happens to client inputs around sending them: checks, preprocessing and recording
+#[derive(Clone, Default)]
+struct InputHooks {
+    constraints: Arc<Mutex<HashMap<String, Vec<InputConstraint>>>>,
+    preprocessors: Arc<Mutex<HashMap<String, Arc<ScriptTransform>>>>,
+    recorder: Arc<Mutex<Option<SessionRecorder>>>,
+}
+
+impl InputHooks {
+    /// Check submitted values against the target's input constraints
+    fn check(&self, network_id: &str, inputs: &[f64]) -> Result<(), String> {
+        match self.constraints.lock().unwrap().get(network_id) {
+            Some(constraints) => input_constraints::check_inputs(constraints, inputs),
+            None => Ok(()),
+        }
+    }
+
+    /// Run the target's preprocessing script, if it has one
+    fn preprocess(&self, network_id: &str, inputs: Vec<f64>) -> Result<Vec<f64>, String> {
+        let script = self.preprocessors.lock().unwrap().get(network_id).cloned();
+        match script {
+            Some(script) => script.apply(network_id, &inputs),
+            None => Ok(inputs),
+        }
+    }
+
+    fn record_input(&self, network_id: &str, inputs: &[f64]) {
+        if let Some(recorder) = self.recorder.lock().unwrap().as_mut() {
+            if let Err(e) = recorder.record_input(network_id, inputs) {
+                println!("❌ Failed to record sample: {}", e);
+            }
+        }
+    }
 }
 
 /// InputServer manages web interface and neural network connections
 pub struct InputServer {
-    config: InputServerConfig,
+    config: Arc<RwLock<InputServerConfig>>,
     input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
     network_status: Arc<RwLock<HashMap<String, bool>>>,
-    websocket_clients: Arc<RwLock<Vec<mpsc::UnboundedSender<WebSocketMessage>>>>,
+    broadcaster: Broadcaster,
+    hooks: InputHooks,
+    bound: watch::Sender<BoundPorts>,
 }
 
 impl InputServer {
     /// Create a new InputServer
     pub fn new(config: InputServerConfig) -> Self {
         Self {
-            config,
+            config: Arc::new(RwLock::new(config)),
             input_nodes: Arc::new(RwLock::new(HashMap::new())),
             network_status: Arc::new(RwLock::new(HashMap::new())),
-            websocket_clients: Arc::new(RwLock::new(Vec::new())),
+            broadcaster: Broadcaster::new(),
+            hooks: InputHooks::default(),
+            bound: watch::channel(BoundPorts::default()).0,
         }
     }
 
+    /// Ports the web and WebSocket listeners were bound to
+    ///
+    /// Resolves once `start` has bound both, so it is awaited alongside it.
+    pub async fn bound_ports(&self) -> BoundPorts {
+        let mut bound = self.bound.subscribe();
+        let ports = bound
+            .wait_for(|ports| ports.web.is_some() && ports.websocket.is_some())
+            .await
+            .map(|ports| *ports);
+        ports.unwrap_or_default()
+    }
+
+    /// Readiness of the server, as served on `/readyz`
+    pub async fn health(&self) -> HealthReport {
+        Self::health_report(&*self.config.read().await, &self.input_nodes).await
+    }
+
+    /// One component per target: its connection, and the samples waiting for it
+    async fn health_report(
+        config: &InputServerConfig,
+        input_nodes: &Arc<RwLock<HashMap<String, InputNode>>>,
+    ) -> HealthReport {
+        let nodes = input_nodes.read().await;
+        let mut report = HealthReport::new();
+        for target in &config.neural_networks {
+            let Some(node) = nodes.get(&target.id) else {
+                report.push(ComponentHealth::new(&target.id, false, "not connected"));
+                continue;
+            };
+            let status = node.connection_status();
+            report.push(ComponentHealth::new(
+                &target.id,
+                status == ConnectionStatus::Connected,
+                format!("{:?}", status),
+            ));
+            report.push(ComponentHealth::queue(
+                &format!("{}_queue", target.id),
+                node.pending_count().await,
+                DEFAULT_QUEUE_THRESHOLD,
+            ));
+        }
+        report
+    }
+
     /// Start the InputServer
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         println!("🚀 Starting InputServer...");
+        for target in &self.config.read().await.neural_networks {
+            target
+                .display
+                .validate(target.input_count)
+                .map_err(|e| format!("Display metadata of {}: {}", target.id, e))?;
+            input_constraints::validate_profile(&target.constraints, target.input_count)
+                .map_err(|e| format!("Input constraints of {}: {}", target.id, e))?;
+        }
+        self.broadcaster.spawn_fan_out();
+        self.start_recording().await?;
 
         // Initialize neural network connections
         self.initialize_networks().await?;
+        self.start_closed_loop().await?;
+        self.start_input_bridge().await?;
+        self.start_audio_input().await?;
+        self.start_camera_input().await?;
 
         // Start WebSocket server
         let websocket_server = self.start_websocket_server();
@@ -121,65 +295,428 @@ impl InputServer {
         Ok(())
     }
 
-    /// Initialize connections to neural networks
-    async fn initialize_networks(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut input_nodes = self.input_nodes.write().await;
-        let mut network_status = self.network_status.write().await;
-
-        for target in &self.config.neural_networks {
-            println!(
-                "🔗 Connecting to neural network: {} ({}:{})",
-                target.name, target.address, target.port
-            );
-
-            let node_config = IoNodeConfig {
-                node_id: Uuid::new_v4(),
-                name: format!("InputServer-{}", target.name),
-                listen_address: "127.0.0.1".to_string(),
-                listen_port: 0, // Let the system assign a port
-                target_address: Some(target.address.clone()),
-                target_port: Some(target.port),
-                use_tls: target.use_tls,
-                cert_path: self.config.cert_path.clone(),
-                key_path: self.config.key_path.clone(),
-		input_size: target.input_count.clone(),
-                data_transformation: None,
-            };
+    /// Open the recording dataset and follow the paired OutputServer, if configured
+    async fn start_recording(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(recording) = self.config.read().await.recording.clone() else {
+            return Ok(());
+        };
+        let recorder = SessionRecorder::new(recording.clone())?;
+        println!(
+            "⏺️ Recording inputs to {} ({} samples so far)",
+            recording.path.display(),
+            recorder.len()
+        );
+        *self.hooks.recorder.lock().unwrap() = Some(recorder);
+        if let Some(url) = recording.output_url {
+            let recorder = Arc::clone(&self.hooks.recorder);
+            tokio::spawn(Self::follow_outputs(url, move |_, outputs| {
+                if let Some(recorder) = recorder.lock().unwrap().as_mut() {
+                    if let Err(e) = recorder.record_output(&outputs) {
+                        println!("❌ Failed to record sample: {}", e);
+                    }
+                }
+            }));
+        }
+        Ok(())
+    }
+
+    /// Start feeding outputs back as inputs, if configured
+    async fn start_closed_loop(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(config) = self.config.read().await.closed_loop.clone() else {
+            return Ok(());
+        };
+        let controller = LoopController::new(config.clone())?;
+        println!(
+            "🔁 Closed loop: {} outputs from {} feed {} at up to {} Hz",
+            config.network_id, config.output_url, config.target_id, config.rate_hz
+        );
+        tokio::spawn(Self::run_closed_loop(controller, Arc::clone(&self.input_nodes)));
+        Ok(())
+    }
+
+    /// Start sending the inputs of a gamepad or MIDI controller, if configured
+    async fn start_input_bridge(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = self.config.read().await;
+        let Some(bridge) = config.input_bridge.clone() else {
+            return Ok(());
+        };
+        let target = config
+            .neural_networks
+            .iter()
+            .find(|target| target.id == bridge.target_id)
+            .ok_or_else(|| format!("Input bridge target {} is not configured", bridge.target_id))?;
+        let bridge = InputBridge::new(bridge, target.input_count)?;
+        println!(
+            "🎮 Input bridge: {} drives {} at {} Hz",
+            bridge.config().source.device().display(),
+            bridge.config().target_id,
+            bridge.config().rate_hz
+        );
+        tokio::spawn(Self::run_input_bridge(bridge, Arc::clone(&self.input_nodes)));
+        Ok(())
+    }
+
+    /// Apply controller events as they arrive and send the current inputs once per tick
+    async fn run_input_bridge(
+        mut bridge: InputBridge,
+        input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
+    ) {
+        let target_id = bridge.config().target_id.clone();
+        let mut events = input_bridge::spawn_reader(bridge.config().source.clone());
+        let mut ticker = tokio::time::interval(bridge.config().interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                event = events.recv() => match event {
+                    Some(event) => bridge.apply(&event),
+                    None => break,
+                },
+                _ = ticker.tick() => {
+                    let mut nodes = input_nodes.write().await;
+                    if let Some(node) = nodes.get_mut(&target_id) {
+                        if let Err(e) = node.send_data(bridge.inputs().to_vec()).await {
+                            println!("❌ Input bridge failed to feed {}: {:?}", target_id, e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Start streaming audio features, if configured
+    async fn start_audio_input(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = self.config.read().await;
+        let Some(audio) = config.audio_input.clone() else {
+            return Ok(());
+        };
+        let target = config
+            .neural_networks
+            .iter()
+            .find(|target| target.id == audio.target_id)
+            .ok_or_else(|| format!("Audio input target {} is not configured", audio.target_id))?;
+        if target.input_adapter == InputAdapter::Reject && audio.feature_count() != target.input_count {
+            return Err(format!(
+                "Audio input yields {} features but {} takes {} inputs; set an input adapter",
+                audio.feature_count(),
+                target.id,
+                target.input_count
+            )
+            .into());
+        }
+        let extractor = FeatureExtractor::new(audio.clone())?;
+        println!(
+            "🎤 Audio input: {} features {:.1} times per second feed {}",
+            audio.feature_count(),
+            audio.frame_rate(),
+            audio.target_id
+        );
+        tokio::spawn(Self::run_audio_input(extractor, Arc::clone(&self.input_nodes)));
+        Ok(())
+    }
+
+    /// Send every feature vector of the captured audio to the target as it is computed
+    async fn run_audio_input(
+        extractor: FeatureExtractor,
+        input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
+    ) {
+        let target_id = extractor.config().target_id.clone();
+        let mut frames = audio_input::spawn_reader(extractor);
+        while let Some(features) = frames.recv().await {
+            let mut nodes = input_nodes.write().await;
+            if let Some(node) = nodes.get_mut(&target_id) {
+                if let Err(e) = node.send_data(features).await {
+                    println!("❌ Audio input failed to feed {}: {:?}", target_id, e);
+                }
+            }
+        }
+    }
 
-            let (mut input_node, _receiver) = InputNode::new(node_config);
+    /// Start streaming camera frames, if configured
+    async fn start_camera_input(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = self.config.read().await;
+        let Some(camera) = config.camera_input.clone() else {
+            return Ok(());
+        };
+        camera.validate()?;
+        let target = config
+            .neural_networks
+            .iter()
+            .find(|target| target.id == camera.target_id)
+            .ok_or_else(|| format!("Camera input target {} is not configured", camera.target_id))?;
+        if target.input_adapter == InputAdapter::Reject && camera.input_count() != target.input_count {
+            return Err(format!(
+                "A {}x{} camera grid yields {} inputs but {} takes {}; set an input adapter",
+                camera.width,
+                camera.height,
+                camera.input_count(),
+                target.id,
+                target.input_count
+            )
+            .into());
+        }
+        println!(
+            "📷 Camera input: {}x{} grid at {} fps feeds {}",
+            camera.width, camera.height, camera.fps, camera.target_id
+        );
+        tokio::spawn(Self::run_camera_input(camera, Arc::clone(&self.input_nodes)));
+        Ok(())
+    }
 
-            match input_node.start().await {
-                Ok(_) => {
-                    println!("✅ Connected to {}", target.name);
-                    input_nodes.insert(target.id.clone(), input_node);
-                    network_status.insert(target.id.clone(), true);
+    /// Send the grid of the newest captured frame once per tick
+    async fn run_camera_input(
+        camera: CameraInputConfig,
+        input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
+    ) {
+        let mut frames = camera_input::spawn_reader(camera.clone());
+        let mut ticker = tokio::time::interval(camera.interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            let Some(grid) = frames.borrow_and_update().clone() else {
+                continue;
+            };
+            let mut nodes = input_nodes.write().await;
+            if let Some(node) = nodes.get_mut(&camera.target_id) {
+                if let Err(e) = node.send_data(grid).await {
+                    println!("❌ Camera input failed to feed {}: {:?}", camera.target_id, e);
                 }
+            }
+        }
+    }
+
+    /// Map the newest outputs of the followed network to inputs of the target, once per tick
+    ///
+    /// Runs until a safety limit stops the loop; outputs that arrive faster
+    /// than the loop rate are superseded by the newest one.
+    async fn run_closed_loop(
+        mut controller: LoopController,
+        input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
+    ) {
+        let config = controller.config().clone();
+        let (latest_tx, mut latest) = watch::channel(Vec::new());
+        let network_id = config.network_id.clone();
+        let follower = tokio::spawn(Self::follow_outputs(
+            config.output_url.clone(),
+            move |id, outputs| {
+                if id == network_id {
+                    latest_tx.send_replace(outputs);
+                }
+            },
+        ));
+        let mut ticker = tokio::time::interval(config.interval());
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        while latest.changed().await.is_ok() {
+            ticker.tick().await;
+            let outputs = latest.borrow_and_update().clone();
+            let inputs = match controller.step(&outputs).await {
+                Ok(inputs) => inputs,
                 Err(e) => {
-                    println!("❌ Failed to connect to {}: {:?}", target.name, e);
-                    network_status.insert(target.id.clone(), false);
+                    println!("🛑 Closed loop stopped: {}", e);
+                    break;
+                }
+            };
+            let mut nodes = input_nodes.write().await;
+            match nodes.get_mut(&config.target_id) {
+                Some(node) => {
+                    if let Err(e) = node.send_data(inputs).await {
+                        println!("❌ Closed loop failed to feed {}: {:?}", config.target_id, e);
+                    }
                 }
+                None => println!("❌ Closed loop target {} is not connected", config.target_id),
             }
         }
+        follower.abort();
+        controller.shutdown().await;
+    }
+
+    /// Hand every `OutputData` a paired OutputServer observes to `on_output`, reconnecting as needed
+    async fn follow_outputs<F>(url: String, mut on_output: F)
+    where
+        F: FnMut(String, Vec<f64>) + Send + 'static,
+    {
+        loop {
+            match connect_async(url.as_str()).await {
+                Ok((mut stream, _)) => {
+                    println!("🔗 Following outputs from {}", url);
+                    while let Some(Ok(message)) = stream.next().await {
+                        let Message::Text(text) = message else {
+                            continue;
+                        };
+                        let Ok(OutputWebSocketMessage::OutputData {
+                            network_id,
+                            outputs,
+                            ..
+                        }) = serde_json::from_str(&text)
+                        else {
+                            continue;
+                        };
+                        on_output(network_id, outputs);
+                    }
+                    println!("🔌 Lost outputs from {}, reconnecting", url);
+                }
+                Err(e) => println!("❌ Cannot follow outputs from {}: {}", url, e),
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+    }
+
+    /// Initialize connections to neural networks
+    async fn initialize_networks(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let config = self.config.read().await.clone();
+        for target in &config.neural_networks {
+            self.connect_target(&config, target).await;
+        }
 
         Ok(())
     }
 
+    /// Connect an input node to `target`, returning whether it succeeded
+    async fn connect_target(&self, config: &InputServerConfig, target: &NeuralNetworkTarget) -> bool {
+        println!(
+            "🔗 Connecting to neural network: {} ({}:{})",
+            target.name, target.address, target.port
+        );
+
+        // A target whose constraints are invalid or whose script does not compile is left unconnected
+        if let Err(e) = input_constraints::validate_profile(&target.constraints, target.input_count) {
+            println!("❌ Input constraints of {}: {}", target.name, e);
+            self.network_status.write().await.insert(target.id.clone(), false);
+            return false;
+        }
+        let preprocess = match target.preprocess.as_ref().map(ScriptTransform::new).transpose() {
+            Ok(preprocess) => preprocess,
+            Err(e) => {
+                println!("❌ Preprocessing script for {}: {}", target.name, e);
+                self.network_status.write().await.insert(target.id.clone(), false);
+                return false;
+            }
+        };
+        {
+            let mut preprocessors = self.hooks.preprocessors.lock().unwrap();
+            match preprocess {
+                Some(script) => preprocessors.insert(target.id.clone(), Arc::new(script)),
+                None => preprocessors.remove(&target.id),
+            };
+        }
+        self.hooks
+            .constraints
+            .lock()
+            .unwrap()
+            .insert(target.id.clone(), target.constraints.clone());
+
+        let node_config = IoNodeConfig {
+            node_id: Uuid::new_v4(),
+            name: format!("InputServer-{}", target.name),
+            listen_address: "127.0.0.1".to_string(),
+            listen_port: 0, // Let the system assign a port
+            target_address: Some(target.address.clone()),
+            target_port: Some(target.port),
+            use_tls: target.use_tls,
+            cert_path: config.cert_path.clone(),
+            key_path: config.key_path.clone(),
+            input_size: target.input_count,
+            data_transformation: None,
+            bind_options: config.bind_options,
+            input_adapter: target.input_adapter.clone(),
+            ..Default::default()
+        };
+
+        let (mut input_node, _receiver) = InputNode::new(node_config);
+
+        let connected = match input_node.start().await {
+            Ok(_) => {
+                println!("✅ Connected to {}", target.name);
+                self.input_nodes.write().await.insert(target.id.clone(), input_node);
+                true
+            }
+            Err(e) => {
+                println!("❌ Failed to connect to {}: {:?}", target.name, e);
+                false
+            }
+        };
+        self.network_status.write().await.insert(target.id.clone(), connected);
+        connected
+    }
+
+    /// Apply a new configuration without restarting
+    ///
+    /// Targets are matched by id: new ones are connected, removed ones are
+    /// left with a `Disconnect`, and changed ones are reconnected with their
+    /// new settings. The web and WebSocket listeners keep their addresses, so
+    /// changes to those are reported as needing a restart. Connected
+    /// dashboards receive the updated network list.
+    pub async fn reload(&self, config: InputServerConfig) -> ReloadReport {
+        let old = std::mem::replace(&mut *self.config.write().await, config.clone());
+        let mut report = ReloadReport::default();
+        for field in reload::changed_fields(&old, &config) {
+            match field.as_str() {
+                "neural_networks" => {}
+                "cert_path" | "key_path" => {
+                    report.applied(format!("New {} used for targets connected from now on", field))
+                }
+                _ => report.restart_required(field),
+            }
+        }
+
+        let diff = reload::diff_by_id(&old.neural_networks, &config.neural_networks, |t| &t.id);
+        for target in diff.removed.iter().chain(&diff.changed) {
+            if let Some(mut node) = self.input_nodes.write().await.remove(&target.id) {
+                node.disconnect().await;
+            }
+        }
+        for target in diff.removed {
+            self.network_status.write().await.remove(&target.id);
+            self.hooks.preprocessors.lock().unwrap().remove(&target.id);
+            self.hooks.constraints.lock().unwrap().remove(&target.id);
+            report.applied(format!("Removed target {}", target.id));
+        }
+        for (targets, verb) in [(diff.added, "Added"), (diff.changed, "Reconnected")] {
+            for target in targets {
+                let outcome = if self.connect_target(&config, target).await {
+                    "connected"
+                } else {
+                    "not reachable yet"
+                };
+                report.applied(format!(
+                    "{} target {} at {}:{} ({})",
+                    verb, target.id, target.address, target.port, outcome
+                ));
+            }
+        }
+
+        let networks = Self::get_network_info(&config, &self.network_status).await;
+        let _ = self
+            .broadcaster
+            .broadcast(&WebSocketMessage::NetworkList { networks });
+        report
+    }
+
     /// Start the WebSocket server
     async fn start_websocket_server(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = format!("{}:{}", self.config.web_address, self.config.websocket_port);
-        let listener = TcpListener::bind(&addr).await?;
+        let (web_address, websocket_port, bind_options) = {
+            let config = self.config.read().await;
+            (config.web_address.clone(), config.websocket_port, config.bind_options)
+        };
+        let listener =
+            address::bind_tcp_listener(&web_address, websocket_port, bind_options).await?;
+        let websocket_port = listener.local_addr()?.port();
+        self.bound.send_modify(|ports| ports.websocket = Some(websocket_port));
+        let addr = address::format_host_port(&web_address, websocket_port);
         println!("🌐 WebSocket server listening on {}", addr);
 
         let input_nodes = Arc::clone(&self.input_nodes);
         let network_status = Arc::clone(&self.network_status);
-        let websocket_clients = Arc::clone(&self.websocket_clients);
-        let config = self.config.clone();
+        let broadcaster = self.broadcaster.clone();
+        let config = Arc::clone(&self.config);
+        let hooks = self.hooks.clone();
 
         while let Ok((stream, addr)) = listener.accept().await {
             let input_nodes = Arc::clone(&input_nodes);
             let network_status = Arc::clone(&network_status);
-            let websocket_clients = Arc::clone(&websocket_clients);
-            let config = config.clone();
+            let broadcaster = broadcaster.clone();
+            let config = Arc::clone(&config);
+            let hooks = hooks.clone();
 
             tokio::spawn(async move {
                 if let Err(e) = Self::handle_websocket_connection(
@@ -187,8 +724,9 @@ impl InputServer {
                     addr,
                     input_nodes,
                     network_status,
-                    websocket_clients,
+                    broadcaster,
                     config,
+                    hooks,
                 )
                 .await
                 {
@@ -206,50 +744,41 @@ impl InputServer {
         addr: SocketAddr,
         input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
         network_status: Arc<RwLock<HashMap<String, bool>>>,
-        websocket_clients: Arc<RwLock<Vec<mpsc::UnboundedSender<WebSocketMessage>>>>,
-        config: InputServerConfig,
+        broadcaster: Broadcaster,
+        config: Arc<RwLock<InputServerConfig>>,
+        hooks: InputHooks,
     ) -> Result<(), Box<dyn std::error::Error>> {
         println!("🔌 New WebSocket connection from {}", addr);
 
         let ws_stream = accept_async(stream).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
-        let (tx, mut rx) = mpsc::unbounded_channel::<WebSocketMessage>();
-
-        // Add client to the list
-        {
-            let mut clients = websocket_clients.write().await;
-            clients.push(tx.clone());
-        }
+        // Add client to the broadcast list
+        let (client, mut frames) = broadcaster.connect();
 
         // Send initial network list
-        let networks = Self::get_network_info(&config, &network_status).await;
-        let network_list_msg = WebSocketMessage::NetworkList { networks };
-        if let Ok(msg_text) = serde_json::to_string(&network_list_msg) {
-            let _ = ws_sender.send(Message::Text(msg_text)).await;
-        }
+        let networks = Self::get_network_info(&*config.read().await, &network_status).await;
+        client.send(&WebSocketMessage::NetworkList { networks });
 
         // Handle outgoing messages
         let ws_sender_task = tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                if let Ok(msg_text) = serde_json::to_string(&message) {
-                    if ws_sender.send(Message::Text(msg_text)).await.is_err() {
-                        break;
-                    }
-                }
-            }
+            broadcast::write_frames(&mut ws_sender, &mut frames).await;
         });
 
         // Handle incoming messages
         let input_nodes_clone = Arc::clone(&input_nodes);
-        let tx_clone = tx.clone();
         let ws_receiver_task = tokio::spawn(async move {
             while let Some(msg) = ws_receiver.next().await {
                 match msg {
                     Ok(Message::Text(text)) => {
                         if let Ok(ws_msg) = serde_json::from_str::<WebSocketMessage>(&text) {
-                            Self::handle_websocket_message(ws_msg, &input_nodes_clone, &tx_clone)
-                                .await;
+                            Self::handle_websocket_message(
+                                ws_msg,
+                                &input_nodes_clone,
+                                &client,
+                                &hooks,
+                            )
+                            .await;
                         }
                     }
                     Ok(Message::Close(_)) => break,
@@ -265,11 +794,8 @@ impl InputServer {
             _ = ws_receiver_task => {},
         }
 
-        // Remove client from the list
-        {
-            let mut clients = websocket_clients.write().await;
-            clients.retain(|client| !client.is_closed());
-        }
+        // Remove client from the broadcast list
+        broadcaster.remove_closed();
 
         println!("🔌 WebSocket connection from {} closed", addr);
         Ok(())
@@ -279,21 +805,53 @@ impl InputServer {
     async fn handle_websocket_message(
         message: WebSocketMessage,
         input_nodes: &Arc<RwLock<HashMap<String, InputNode>>>,
-        tx: &mpsc::UnboundedSender<WebSocketMessage>,
+        client: &WebSocketClient,
+        hooks: &InputHooks,
     ) {
         match message {
             WebSocketMessage::ActivateInput { network_id, inputs } => {
+                if let Err(e) = hooks.check(&network_id, &inputs) {
+                    println!("❌ Rejected inputs for {}: {}", network_id, e);
+                    client.send(&WebSocketMessage::InputActivated {
+                        network_id,
+                        success: false,
+                        message: format!("Invalid inputs: {}", e),
+                    });
+                    return;
+                }
+                let inputs = match hooks.preprocess(&network_id, inputs) {
+                    Ok(inputs) => inputs,
+                    Err(e) => {
+                        println!("❌ Failed to preprocess inputs for {}: {}", network_id, e);
+                        client.send(&WebSocketMessage::InputActivated {
+                            network_id,
+                            success: false,
+                            message: format!("Preprocessing failed: {}", e),
+                        });
+                        return;
+                    }
+                };
                 let mut nodes = input_nodes.write().await;
                 if let Some(node) = nodes.get_mut(&network_id) {
                     match node.send_data(inputs.clone()).await {
-                        Ok(_) => {
+                        Ok(delivery) => {
+                            let message = match delivery {
+                                Delivery::Sent => {
+                                    format!("Successfully sent {} inputs", inputs.len())
+                                }
+                                Delivery::Buffered => format!(
+                                    "Target unreachable, {} inputs buffered until it reconnects",
+                                    inputs.len()
+                                ),
+                            };
                             let response = WebSocketMessage::InputActivated {
                                 network_id: network_id.clone(),
                                 success: true,
-                                message: format!("Successfully sent {} inputs", inputs.len()),
+                                message,
                             };
-                            let _ = tx.send(response);
+                            client.send(&response);
                             println!("📤 Sent inputs to {}: {:?}", network_id, inputs);
+                            hooks.record_input(&network_id, &inputs);
                         }
                         Err(e) => {
                             let response = WebSocketMessage::InputActivated {
@@ -301,7 +859,7 @@ impl InputServer {
                                 success: false,
                                 message: format!("Failed to send inputs: {:?}", e),
                             };
-                            let _ = tx.send(response);
+                            client.send(&response);
                             println!("❌ Failed to send inputs to {}: {:?}", network_id, e);
                         }
                     }
@@ -309,9 +867,25 @@ impl InputServer {
                     let response = WebSocketMessage::Error {
                         message: format!("Network {} not found", network_id),
                     };
-                    let _ = tx.send(response);
+                    client.send(&response);
                 }
             }
+            WebSocketMessage::GetNetworkDetails { network_id } => {
+                // Query without holding the lock; clones share the node's connections
+                let node = input_nodes.read().await.get(&network_id).cloned();
+                let response = match node {
+                    Some(node) => match node.target_info().await {
+                        Ok(info) => WebSocketMessage::NetworkDetails { network_id, info },
+                        Err(e) => WebSocketMessage::Error {
+                            message: format!("Failed to query {}: {}", network_id, e),
+                        },
+                    },
+                    None => WebSocketMessage::Error {
+                        message: format!("Network {} not found", network_id),
+                    },
+                };
+                client.send(&response);
+            }
             _ => {}
         }
     }
@@ -333,25 +907,44 @@ impl InputServer {
                 input_count: target.input_count,
                 connected: status.get(&target.id).copied().unwrap_or(false),
                 use_tls: target.use_tls,
+                display: target.display.clone(),
+                constraints: target.constraints.clone(),
             })
             .collect()
     }
 
     /// Start the HTTP server for serving the web interface
     async fn start_http_server(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = format!("{}:{}", self.config.web_address, self.config.web_port);
-        let websocket_port = self.config.websocket_port;
+        let (web_address, web_port, bind_options) = {
+            let config = self.config.read().await;
+            (config.web_address.clone(), config.web_port, config.bind_options)
+        };
+        let listener = address::bind_std_tcp_listener(&web_address, web_port, bind_options).await?;
+        let web_port = listener.local_addr()?.port();
+        self.bound.send_modify(|ports| ports.web = Some(web_port));
+        let addr = address::format_host_port(&web_address, web_port);
+        // The page connects to whichever port the WebSocket listener got
+        let websocket_port = self.bound_ports().await.websocket.unwrap_or_default();
+        let config = Arc::clone(&self.config);
+        let input_nodes = Arc::clone(&self.input_nodes);
 
         let make_svc = make_service_fn(move |_conn| {
             let websocket_port = websocket_port;
+            let config = Arc::clone(&config);
+            let input_nodes = Arc::clone(&input_nodes);
             async move {
                 Ok::<_, Infallible>(service_fn(move |req| {
-                    Self::handle_http_request(req, websocket_port)
+                    Self::handle_http_request(
+                        req,
+                        websocket_port,
+                        Arc::clone(&config),
+                        Arc::clone(&input_nodes),
+                    )
                 }))
             }
         });
 
-        let server = Server::bind(&addr.parse()?).serve(make_svc);
+        let server = Server::from_tcp(listener)?.serve(make_svc);
         println!("🌐 HTTP server listening on http://{}", addr);
 
         server.await?;
@@ -362,11 +955,18 @@ impl InputServer {
     async fn handle_http_request(
         req: Request<Body>,
         websocket_port: u16,
+        config: Arc<RwLock<InputServerConfig>>,
+        input_nodes: Arc<RwLock<HashMap<String, InputNode>>>,
     ) -> Result<Response<Body>, Infallible> {
         match req.uri().path() {
             "/" => Ok(Self::serve_index_html(websocket_port)),
             "/style.css" => Ok(Self::serve_css()),
             "/script.js" => Ok(Self::serve_js(websocket_port)),
+            health::LIVENESS_PATH => Ok(health::liveness_response()),
+            health::READINESS_PATH => {
+                let report = Self::health_report(&*config.read().await, &input_nodes).await;
+                Ok(health::readiness_response(&report))
+            }
             _ => Ok(Response::builder()
                 .status(StatusCode::NOT_FOUND)
                 .body(Body::from("Not Found"))
@@ -409,7 +1009,9 @@ impl InputServer {
                         <button id="send-inputs" class="btn-primary">Send Inputs</button>
                         <button id="reset-inputs" class="btn-secondary">Reset</button>
                         <button id="random-inputs" class="btn-secondary">Random</button>
+                        <button id="show-details" class="btn-secondary">Details</button>
                     </div>
+                    <div id="network-info"></div>
                 </div>
             </div>
             
@@ -548,6 +1150,12 @@ main {
     cursor: not-allowed;
 }
 
+.network-description {
+    font-size: 0.9rem;
+    color: #718096;
+    margin-top: 4px;
+}
+
 .network-name {
     font-weight: bold;
     font-size: 1.1rem;
@@ -560,6 +1168,21 @@ main {
     margin-top: 5px;
 }
 
+#network-info {
+    margin-top: 15px;
+    color: #4a5568;
+    font-size: 0.9rem;
+}
+
+#network-info dt {
+    font-weight: bold;
+    color: #2d3748;
+}
+
+#network-info dd {
+    margin: 0 0 5px 0;
+}
+
 .network-status {
     display: inline-block;
     padding: 2px 8px;
@@ -625,6 +1248,47 @@ main {
     border: none;
 }
 
+.switch {
+    -webkit-appearance: none;
+    appearance: none;
+    position: relative;
+    width: 40px;
+    height: 22px;
+    margin-right: auto;
+    border-radius: 11px;
+    background: #e2e8f0;
+    cursor: pointer;
+    transition: background 0.2s;
+}
+
+.switch::before {
+    content: '';
+    position: absolute;
+    top: 2px;
+    left: 2px;
+    width: 18px;
+    height: 18px;
+    border-radius: 50%;
+    background: white;
+    transition: left 0.2s;
+}
+
+.switch:checked {
+    background: #4299e1;
+}
+
+.switch:checked::before {
+    left: 20px;
+}
+
+.category {
+    flex: 1;
+    padding: 6px;
+    border: 1px solid #e2e8f0;
+    border-radius: 4px;
+    background: white;
+}
+
 .slider-value {
     min-width: 60px;
     text-align: center;
@@ -793,6 +1457,10 @@ class InputServerClient {{
                 this.addLogEntry(`📊 ${{message.status}} (Network: ${{message.network_id}})`, 'info');
                 break;
                 
+            case 'NetworkDetails':
+                this.showNetworkDetails(message.network_id, message.info);
+                break;
+                
             case 'Error':
                 this.addLogEntry(`❌ Error: ${{message.message}}`, 'error');
                 break;
@@ -826,9 +1494,13 @@ class InputServerClient {{
             const card = document.createElement('div');
             card.className = `network-card ${{network.connected ? '' : 'disconnected'}}`;
             card.dataset.networkId = network.id;
+            if (network.display?.color) {{
+                card.style.borderLeft = `6px solid ${{network.display.color}}`;
+            }}
             
             card.innerHTML = `
                 <div class="network-name">${{network.name}}</div>
+                ${{network.display?.description ? `<div class="network-description">${{network.display.description}}</div>` : ''}}
                 <div class="network-details">
                     ${{network.address}}:${{network.port}} | ${{network.input_count}} inputs
                     ${{network.use_tls ? '🔒 TLS' : '🔓 Plain'}}
@@ -860,17 +1532,51 @@ class InputServerClient {{
         this.addLogEntry(`Selected network: ${{network.name}}`, 'info');
     }}
     
+    requestNetworkDetails() {{
+        if (!this.selectedNetwork) {{
+            this.addLogEntry('No network selected', 'error');
+            return;
+        }}
+        
+        this.sendMessage({{
+            type: 'GetNetworkDetails',
+            network_id: this.selectedNetwork.id
+        }});
+    }}
+    
+    showNetworkDetails(networkId, info) {{
+        if (!this.selectedNetwork || this.selectedNetwork.id !== networkId) {{
+            return;
+        }}
+        
+        const rows = [
+            ['Name', info.name],
+            ['Architecture', info.layers.join(' → ')],
+            ['Parameters', info.parameters.toLocaleString()],
+            ['Learning mode', info.learning_mode],
+            ['Uptime', `${{info.uptime_secs}}s`],
+            ['Peers', info.peers],
+            ['Messages', `${{info.messages_sent}} sent / ${{info.messages_received}} received`],
+            ['Traffic', `${{info.bytes_sent}} B sent / ${{info.bytes_received}} B received`],
+        ];
+        document.getElementById('network-info').innerHTML =
+            '<dl>' + rows.map(([label, value]) => `<dt>${{label}}</dt><dd>${{value}}</dd>`).join('') + '</dl>';
+    }}
+    
     updateInputPanel() {{
         const selectedNetworkDiv = document.getElementById('selected-network');
         const inputControls = document.getElementById('input-controls');
         
         if (this.selectedNetwork) {{
+            const display = this.selectedNetwork.display || {{}};
             selectedNetworkDiv.innerHTML = `
-                <h3>${{this.selectedNetwork.name}}</h3>
+                <h3 style="color: ${{display.color || 'inherit'}}">${{this.selectedNetwork.name}}</h3>
+                ${{display.description ? `<p>${{display.description}}</p>` : ''}}
                 <p>${{this.selectedNetwork.input_count}} inputs | ${{this.selectedNetwork.address}}:${{this.selectedNetwork.port}}</p>
             `;
             
             this.createInputSliders();
+            document.getElementById('network-info').innerHTML = '';
             inputControls.style.display = 'block';
         }} else {{
             selectedNetworkDiv.innerHTML = '<p>Select a neural network to activate inputs</p>';
@@ -878,6 +1584,51 @@ class InputServerClient {{
         }}
     }}
     
+    // Type and allowed values of input `i`, when the network constrains its inputs
+    constraint(i) {{
+        return this.selectedNetwork.constraints?.[i];
+    }}
+    
+    // Expected range of input `i`, from its constraint or the network's display metadata
+    channelRange(i) {{
+        const constraint = this.constraint(i);
+        if (constraint?.type === 'Continuous') {{
+            return {{ min: constraint.min, max: constraint.max }};
+        }}
+        return this.selectedNetwork.display?.ranges?.[i] || {{ min: 0, max: 1 }};
+    }}
+    
+    // Value input `i` starts from and is reset to
+    defaultValue(i) {{
+        const constraint = this.constraint(i);
+        if (constraint?.type === 'Toggle') return constraint.off;
+        if (constraint?.type === 'Categorical') return constraint.options[0].value;
+        const range = this.channelRange(i);
+        return Math.min(range.max, Math.max(range.min, 0));
+    }}
+    
+    formatValue(i, value) {{
+        const unit = this.selectedNetwork.display?.units?.[i];
+        return unit ? `${{value.toFixed(2)}} ${{unit}}` : value.toFixed(2);
+    }}
+    
+    // Switch for toggles, dropdown for categorical inputs and slider for the rest
+    inputControl(i, start) {{
+        const constraint = this.constraint(i);
+        if (constraint?.type === 'Toggle') {{
+            return `<input type="checkbox" id="input-${{i}}" class="switch">`;
+        }}
+        if (constraint?.type === 'Categorical') {{
+            const options = constraint.options
+                .map((option, k) => `<option value="${{k}}">${{option.label}}</option>`)
+                .join('');
+            return `<select id="input-${{i}}" class="category">${{options}}</select>`;
+        }}
+        const range = this.channelRange(i);
+        const step = constraint?.step ?? (range.max - range.min) / 100;
+        return `<input type="range" id="input-${{i}}" class="slider" min="${{range.min}}" max="${{range.max}}" step="${{step}}" value="${{start}}">`;
+    }}
+    
     createInputSliders() {{
         const container = document.getElementById('input-sliders');
         container.innerHTML = '';
@@ -885,41 +1636,60 @@ class InputServerClient {{
         for (let i = 0; i < this.selectedNetwork.input_count; i++) {{
             const sliderDiv = document.createElement('div');
             sliderDiv.className = 'input-slider';
+            const label = this.selectedNetwork.display?.labels?.[i] || `Input ${{i + 1}}`;
+            const start = this.defaultValue(i);
             
             sliderDiv.innerHTML = `
-                <label for="input-${{i}}">Input ${{i + 1}}</label>
+                <label for="input-${{i}}">${{label}}</label>
                 <div class="slider-container">
-                    <input type="range" id="input-${{i}}" class="slider" min="0" max="1" step="0.01" value="0">
-                    <span class="slider-value" id="value-${{i}}">0.00</span>
+                    ${{this.inputControl(i, start)}}
+                    <span class="slider-value" id="value-${{i}}">${{this.formatValue(i, start)}}</span>
                 </div>
             `;
             
-            const slider = sliderDiv.querySelector('.slider');
-            const valueSpan = sliderDiv.querySelector('.slider-value');
+            container.appendChild(sliderDiv);
             
-            slider.addEventListener('input', (e) => {{
-                valueSpan.textContent = parseFloat(e.target.value).toFixed(2);
+            const valueSpan = sliderDiv.querySelector('.slider-value');
+            document.getElementById(`input-${{i}}`).addEventListener('input', () => {{
+                valueSpan.textContent = this.formatValue(i, this.inputValue(i));
             }});
-            
-            container.appendChild(sliderDiv);
         }}
     }}
     
+    // Current value of input `i`, read from whichever control it has
+    inputValue(i) {{
+        const control = document.getElementById(`input-${{i}}`);
+        const constraint = this.constraint(i);
+        if (constraint?.type === 'Toggle') return control.checked ? constraint.on : constraint.off;
+        if (constraint?.type === 'Categorical') return constraint.options[control.selectedIndex].value;
+        return parseFloat(control.value);
+    }}
+    
     getInputValues() {{
         const values = [];
         for (let i = 0; i < this.selectedNetwork.input_count; i++) {{
-            const slider = document.getElementById(`input-${{i}}`);
-            values.push(parseFloat(slider.value));
+            values.push(this.inputValue(i));
         }}
         return values;
     }}
     
     setInputValues(values) {{
         for (let i = 0; i < Math.min(values.length, this.selectedNetwork.input_count); i++) {{
-            const slider = document.getElementById(`input-${{i}}`);
+            const control = document.getElementById(`input-${{i}}`);
             const valueSpan = document.getElementById(`value-${{i}}`);
-            slider.value = values[i];
-            valueSpan.textContent = values[i].toFixed(2);
+            const constraint = this.constraint(i);
+            const distance = (allowed) => Math.abs(allowed - values[i]);
+            if (constraint?.type === 'Toggle') {{
+                control.checked = distance(constraint.on) < distance(constraint.off);
+            }} else if (constraint?.type === 'Categorical') {{
+                const nearest = constraint.options.reduce((best, option, k) =>
+                    distance(option.value) < distance(constraint.options[best].value) ? k : best, 0);
+                control.selectedIndex = nearest;
+            }} else {{
+                control.value = values[i];
+            }}
+            // Controls snap values to the nearest one the input allows
+            valueSpan.textContent = this.formatValue(i, this.inputValue(i));
         }}
     }}
     
@@ -942,15 +1712,25 @@ class InputServerClient {{
     resetInputs() {{
         if (!this.selectedNetwork) return;
         
-        const zeros = new Array(this.selectedNetwork.input_count).fill(0);
-        this.setInputValues(zeros);
-        this.addLogEntry('Reset all inputs to 0', 'info');
+        const defaults = Array.from({{length: this.selectedNetwork.input_count}}, (_, i) => this.defaultValue(i));
+        this.setInputValues(defaults);
+        this.addLogEntry('Reset all inputs to their defaults', 'info');
     }}
     
     randomizeInputs() {{
         if (!this.selectedNetwork) return;
         
-        const randomValues = Array.from({{length: this.selectedNetwork.input_count}}, () => Math.random());
+        const randomValues = Array.from({{length: this.selectedNetwork.input_count}}, (_, i) => {{
+            const constraint = this.constraint(i);
+            if (constraint?.type === 'Toggle') {{
+                return Math.random() < 0.5 ? constraint.off : constraint.on;
+            }}
+            if (constraint?.type === 'Categorical') {{
+                return constraint.options[Math.floor(Math.random() * constraint.options.length)].value;
+            }}
+            const range = this.channelRange(i);
+            return range.min + Math.random() * (range.max - range.min);
+        }});
         this.setInputValues(randomValues);
         this.addLogEntry('Randomized all inputs', 'info');
     }}
@@ -993,6 +1773,11 @@ class InputServerClient {{
             this.randomizeInputs();
         }});
         
+        // Details button
+        document.getElementById('show-details').addEventListener('click', () => {{
+            this.requestNetworkDetails();
+        }});
+        
         // Clear log button
         document.getElementById('clear-log').addEventListener('click', () => {{
             this.clearLog();
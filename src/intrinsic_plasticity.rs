@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+/// Smallest gain a neuron may adapt to, keeping its response monotonic
+const MIN_GAIN: f64 = 1e-3;
+
+/// Intrinsic plasticity of a sigmoid layer (Triesch, 2005)
+///
+/// Each neuron computes `sigmoid(gain * x + offset)` from its net input `x`
+/// and adapts `gain` and `offset` so that its outputs follow an exponential
+/// distribution with mean `target_mean`. Unlike bias homeostasis, which only
+/// shifts the average activity, this also spreads the outputs over the
+/// neuron's range so each one carries more information about its inputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IntrinsicPlasticity {
+    pub gain: Vec<f64>,
+    pub offset: Vec<f64>,
+    pub target_mean: f64,
+    pub rate: f64,
+}
+
+impl IntrinsicPlasticity {
+    /// Identity-initialized plasticity for a layer of `size` neurons
+    pub fn new(size: usize, target_mean: f64, rate: f64) -> Result<Self, String> {
+        if !(target_mean > 0.0 && target_mean < 1.0) {
+            return Err(format!(
+                "Target mean {} must be in (0, 1) for sigmoid outputs",
+                target_mean
+            ));
+        }
+        Ok(Self {
+            gain: vec![1.0; size],
+            offset: vec![0.0; size],
+            target_mean,
+            rate,
+        })
+    }
+
+    /// Scale and shift net inputs
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        inputs
+            .iter()
+            .zip(self.gain.iter().zip(&self.offset))
+            .map(|(x, (a, b))| a * x + b)
+            .collect()
+    }
+
+    /// One gradient step towards the target distribution, given each neuron's
+    /// net input and the output it produced
+    pub fn update(&mut self, inputs: &[f64], outputs: &[f64]) {
+        let mu = self.target_mean;
+        for ((a, b), (x, y)) in self
+            .gain
+            .iter_mut()
+            .zip(self.offset.iter_mut())
+            .zip(inputs.iter().zip(outputs))
+        {
+            let delta_b = self.rate * (1.0 - (2.0 + 1.0 / mu) * y + y * y / mu);
+            let delta_a = self.rate / *a + delta_b * x;
+            *b += delta_b;
+            *a = (*a + delta_a).max(MIN_GAIN);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::Activation;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    #[test]
+    fn test_outputs_approach_target_mean() {
+        let mut ip = IntrinsicPlasticity::new(1, 0.2, 0.01).unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let sample = |ip: &IntrinsicPlasticity, rng: &mut StdRng| {
+            let x = rng.gen_range(-1.0..1.0) * 3.0 + 2.0;
+            let y = Activation::Sigmoid.apply(ip.forward(&[x])[0]);
+            (x, y)
+        };
+
+        for _ in 0..20000 {
+            let (x, y) = sample(&ip, &mut rng);
+            ip.update(&[x], &[y]);
+        }
+        let mean = (0..2000).map(|_| sample(&ip, &mut rng).1).sum::<f64>() / 2000.0;
+        assert!((mean - 0.2).abs() < 0.05, "mean output {}", mean);
+        assert!(ip.gain[0] > 0.0);
+
+        assert!(IntrinsicPlasticity::new(1, 1.0, 0.01).is_err());
+    }
+}
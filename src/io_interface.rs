@@ -1,13 +1,34 @@
 use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+use crate::address::{AddressPreference, BindOptions};
+use crate::clock;
+use crate::curiosity::SharedCuriosity;
 use crate::distributed_network::{
-    DistributedNetwork, MessagePayload, MessageType, NetworkId, NetworkMessage,
+    DistributedNetwork, MessagePayload, MessageType, NetworkId, NetworkMessage, PeerInfo,
 };
+use crate::health::{ComponentHealth, DEFAULT_QUEUE_THRESHOLD};
+use crate::input_adapter::InputAdapter;
+use crate::live_evaluation::{LiveEvaluationConfig, LiveEvaluator, LiveMetrics};
+use crate::metrics::MetricsServer;
 use crate::neural_network::NeuralNetwork;
+use crate::osc::{self, OscInputs, OscSinkConfig, OscSourceConfig};
+use crate::pacing::{Pacer, Pacing};
+#[cfg(feature = "gpio")]
+use crate::pwm_sink::{PwmOutputs, PwmSinkConfig};
+use crate::reconnect::{
+    BufferPolicy, ConnectionStatus, DeliveryMetrics, DeliveryStats, PendingBuffer,
+    PushOutcome, ReconnectPolicy, StatusCallback,
+};
+use crate::resampling::{Resampler, Resampling};
+use crate::scaling::{SignalRanges, ValueRange};
+use crate::sim::{SimSourceConfig, VirtualActuator, VirtualSensors};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use crate::transport::NnpStream;
+use crate::watchdog::{Watchdog, WatchdogAlert, WatchdogConfig, WatchdogStats, WatchdogStatus};
 
 /// Unique identifier for I/O connections
 pub type IoConnectionId = Uuid;
@@ -26,6 +47,64 @@ pub struct IoNodeConfig {
     pub key_path: Option<String>,
     pub data_transformation: Option<String>,
     pub input_size: usize,
+    /// Address family preference and dual-stack behaviour for this node
+    #[serde(default)]
+    pub bind_options: BindOptions,
+    /// Reconnect behaviour when the target network becomes unreachable
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    /// Buffering of data produced while the target (or an output sink) is unreachable
+    #[serde(default)]
+    pub buffer_policy: BufferPolicy,
+    /// Mapping applied to data whose length differs from `input_size`
+    #[serde(default)]
+    pub input_adapter: InputAdapter,
+    /// Standby replicas of the target, tried in order when it becomes unreachable
+    #[serde(default)]
+    pub failover_targets: Vec<(String, u16)>,
+    /// Steady cadence for samples delivered to the external sink (unpaced when `None`)
+    #[serde(default)]
+    pub pacing: Option<Pacing>,
+    /// Serve sink samples at a fixed rate from timestamped, buffered outputs
+    #[serde(default)]
+    pub resampling: Option<Resampling>,
+    /// Range of the external data an `InputNode` receives, rescaled to the target's announced input range
+    #[serde(default)]
+    pub source_range: Option<ValueRange>,
+    /// Range an `OutputNode`'s sink expects (TCP sinks send `[0, 1]` as bytes), announced to senders
+    #[serde(default)]
+    pub sink_range: Option<ValueRange>,
+    /// Failsafe values an `OutputNode` sends its sink when fresh outputs stop arriving
+    #[serde(default)]
+    pub watchdog: Option<WatchdogConfig>,
+}
+
+impl Default for IoNodeConfig {
+    fn default() -> Self {
+        Self {
+            node_id: Uuid::new_v4(),
+            name: "io-node".to_string(),
+            listen_address: "127.0.0.1".to_string(),
+            listen_port: 0,
+            target_address: None,
+            target_port: None,
+            use_tls: false,
+            cert_path: None,
+            key_path: None,
+            data_transformation: None,
+            input_size: 1,
+            bind_options: BindOptions::default(),
+            reconnect: ReconnectPolicy::default(),
+            buffer_policy: BufferPolicy::default(),
+            input_adapter: InputAdapter::default(),
+            failover_targets: Vec::new(),
+            pacing: None,
+            resampling: None,
+            source_range: None,
+            sink_range: None,
+            watchdog: None,
+        }
+    }
 }
 
 /// Errors that can occur during I/O operations
@@ -43,6 +122,15 @@ pub enum IoError {
     ConfigError(String),
 }
 
+/// What became of a sample handed to `InputNode::send_data`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// The sample went out to the target (or to every connected network)
+    Sent,
+    /// The target is unreachable; the sample waits in the buffer until a reconnect replays it
+    Buffered,
+}
+
 /// Input node that acts as a data source in the distributed neural network
 ///
 /// This node receives data from external systems and sends it to the distributed
@@ -53,6 +141,41 @@ pub struct InputNode {
     distributed_network: DistributedNetwork,
     config: IoNodeConfig,
     is_running: bool,
+    link: Arc<TargetLink>,
+    curiosity: Option<SharedCuriosity>,
+}
+
+/// Connection state towards the configured target, shared between clones of an InputNode
+struct TargetLink {
+    buffer: tokio::sync::Mutex<PendingBuffer>,
+    status: std::sync::Mutex<ConnectionStatus>,
+    reconnecting: AtomicBool,
+    callbacks: std::sync::Mutex<Vec<StatusCallback>>,
+    /// Index of the target data is sent to: 0 is the configured target, then the failovers
+    active: AtomicUsize,
+}
+
+impl TargetLink {
+    fn new(buffer_policy: BufferPolicy) -> Self {
+        Self {
+            buffer: tokio::sync::Mutex::new(PendingBuffer::new(buffer_policy)),
+            status: std::sync::Mutex::new(ConnectionStatus::Disconnected),
+            reconnecting: AtomicBool::new(false),
+            callbacks: std::sync::Mutex::new(Vec::new()),
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    fn is_connected(&self) -> bool {
+        *self.status.lock().unwrap() == ConnectionStatus::Connected
+    }
+
+    fn set_status(&self, status: ConnectionStatus) {
+        *self.status.lock().unwrap() = status.clone();
+        for callback in self.callbacks.lock().unwrap().iter() {
+            callback(&status);
+        }
+    }
 }
 
 impl InputNode {
@@ -64,22 +187,47 @@ impl InputNode {
         let passthrough_network = NeuralNetwork::with_layers(&[config.input_size, config.input_size], 0.0);
         
         // Create a distributed network with the passthrough neural network
-        let (distributed_network, message_receiver) = DistributedNetwork::new(
+        let (mut distributed_network, message_receiver) = DistributedNetwork::new(
             config.name.clone(),
             config.listen_address.clone(),
             config.listen_port,
             passthrough_network,
         );
+        distributed_network.set_bind_options(config.bind_options);
+        distributed_network.set_signal_ranges(SignalRanges {
+            input: None,
+            output: config.source_range,
+        });
 
+        let link = Arc::new(TargetLink::new(config.buffer_policy.clone()));
+        let probed = Arc::clone(&link);
+        distributed_network.add_health_check(move || {
+            let status = probed.status.lock().unwrap().clone();
+            ComponentHealth::new(
+                "target",
+                status == ConnectionStatus::Connected,
+                format!("{:?}", status),
+            )
+        });
         let input_node = Self {
             distributed_network,
             config,
             is_running: false,
+            link,
+            curiosity: None,
         };
 
         (input_node, message_receiver)
     }
 
+    /// Score every sent sample as an observation of `curiosity`
+    ///
+    /// Share the module with the `OutputNode` of the same loop, which records
+    /// the actions and reports the novelty.
+    pub fn set_curiosity(&mut self, curiosity: SharedCuriosity) {
+        self.curiosity = Some(curiosity);
+    }
+
     /// Start the input node server
     pub async fn start(&mut self) -> Result<(), IoError> {
         // Start the distributed network server
@@ -100,39 +248,271 @@ impl InputNode {
                 .map_err(|e| {
                     IoError::ConnectionFailed(format!("Failed to connect to target: {:?}", e))
                 })?;
+            self.link.set_status(ConnectionStatus::Connected);
+        }
+
+        Ok(())
+    }
+
+    /// Leave the target with a `Disconnect` and stop sending to it
+    pub async fn disconnect(&mut self) {
+        self.distributed_network.farewell("Input node removed", false).await;
+        self.is_running = false;
+        self.link.set_status(ConnectionStatus::Disconnected);
+    }
+
+    /// Register a callback invoked whenever the target connection status changes
+    pub fn on_status_change<F>(&self, callback: F)
+    where
+        F: Fn(&ConnectionStatus) + Send + Sync + 'static,
+    {
+        self.link.callbacks.lock().unwrap().push(Arc::new(callback));
+    }
+
+    /// Current status of the connection to the configured target
+    pub fn connection_status(&self) -> ConnectionStatus {
+        self.link.status.lock().unwrap().clone()
+    }
+
+    /// Number of samples waiting to be delivered once the target is reachable again
+    pub async fn pending_count(&self) -> usize {
+        self.link.buffer.lock().await.len()
+    }
+
+    /// The configured target followed by its failover targets
+    fn targets(&self) -> Vec<(String, u16)> {
+        let primary = match (&self.config.target_address, self.config.target_port) {
+            (Some(addr), Some(port)) => Some((addr.clone(), port)),
+            _ => None,
+        };
+        primary
+            .into_iter()
+            .chain(self.config.failover_targets.iter().cloned())
+            .collect()
+    }
+
+    /// Target data is currently sent to, if any is configured
+    pub fn current_target(&self) -> Option<(String, u16)> {
+        let targets = self.targets();
+        let active = self.link.active.load(Ordering::SeqCst);
+        targets.get(active % targets.len().max(1)).cloned()
+    }
+
+    /// Architecture and activity statistics reported by the current target
+    pub async fn target_info(&self) -> Result<PeerInfo, IoError> {
+        let (addr, port) = self
+            .current_target()
+            .ok_or_else(|| IoError::ConfigError("No target configured".to_string()))?;
+        let peer_id = self
+            .distributed_network
+            .find_peer_by_address(&addr, port)
+            .ok_or_else(|| IoError::ConnectionFailed(format!("No peer found for {}:{}", addr, port)))?;
+
+        self.distributed_network
+            .query_peer_info(peer_id)
+            .await
+            .map_err(|e| IoError::NetworkError(format!("Failed to query target: {:?}", e)))
+    }
+
+    /// Make the target after the current one active
+    fn advance_target(&self) {
+        let count = self.targets().len().max(1);
+        let next = (self.link.active.load(Ordering::SeqCst) + 1) % count;
+        self.link.active.store(next, Ordering::SeqCst);
+    }
+
+    /// Deliver a sample to the first other target that accepts it, making that target active
+    async fn fail_over(&self, data: &[f64]) -> bool {
+        let count = self.targets().len();
+        for _ in 1..count {
+            self.advance_target();
+            let Some((addr, port)) = self.current_target() else {
+                return false;
+            };
+            if self.distributed_network.connect_to(&addr, port).await.is_err() {
+                continue;
+            }
+            if self.send_to_target(&addr, port, data.to_vec()).await.is_ok() {
+                println!("↪️ Failed over to {}:{}", addr, port);
+                self.link.set_status(ConnectionStatus::Connected);
+                return true;
+            }
+        }
+        self.advance_target();
+        false
+    }
+
+    /// Send one sample to the configured target peer
+    async fn send_to_target(&self, addr: &str, port: u16, data: Vec<f64>) -> Result<(), IoError> {
+        let peer_id = self
+            .distributed_network
+            .find_peer_by_address(addr, port)
+            .ok_or_else(|| IoError::ConnectionFailed(format!("No peer found for {}:{}", addr, port)))?;
+
+        if let Err(e) = self.distributed_network.send_forward_data(peer_id, 0u8, data).await {
+            // Forget the stale peer so that a reconnect registers a fresh one
+            self.distributed_network.connections.lock().unwrap().remove(&peer_id);
+            return Err(IoError::NetworkError(format!("Failed to send data: {:?}", e)));
         }
 
         Ok(())
     }
 
+    /// Buffer a sample after a failed send and make sure a reconnect is in progress
+    async fn handle_send_failure(
+        &self,
+        data: Vec<f64>,
+        error: IoError,
+    ) -> Result<Delivery, IoError> {
+        if self.link.is_connected() {
+            self.link.set_status(ConnectionStatus::Disconnected);
+        }
+
+        if !self.config.reconnect.enabled {
+            return Err(error);
+        }
+
+        let queued = Self::queue_sample(&mut *self.link.buffer.lock().await, data, error);
+        self.spawn_reconnect();
+        queued
+    }
+
+    /// Add a sample to the pending buffer, failing with `error` if the policy discards it
+    fn queue_sample(
+        buffer: &mut PendingBuffer,
+        data: Vec<f64>,
+        error: IoError,
+    ) -> Result<Delivery, IoError> {
+        let outcome = buffer
+            .push(data)
+            .map_err(|e| IoError::NetworkError(format!("Failed to buffer data: {}", e)))?;
+
+        if outcome.queued {
+            Ok(Delivery::Buffered)
+        } else {
+            Err(IoError::NetworkError(format!(
+                "Target unreachable, data dropped: {}",
+                error
+            )))
+        }
+    }
+
+    /// Start a background reconnect loop unless one is already running
+    fn spawn_reconnect(&self) {
+        if self.link.reconnecting.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let node = self.clone();
+        tokio::spawn(async move {
+            node.reconnect_loop().await;
+        });
+    }
+
+    /// Reconnect with backoff and replay the buffer, clearing `reconnecting` when done
+    ///
+    /// The flag is only cleared while the buffer lock is held, so `send_data`
+    /// never queues a sample after the final drain.
+    async fn reconnect_loop(&self) {
+        let policy = &self.config.reconnect;
+
+        let mut attempt = 0;
+        while policy.allows_attempt(attempt) {
+            let delay = policy.delay_for_attempt(attempt);
+            self.link
+                .set_status(ConnectionStatus::Reconnecting { attempt: attempt + 1, delay });
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+
+            // Each attempt goes to the next target, so a promoted standby is found
+            let Some((addr, port)) = self.current_target() else {
+                break;
+            };
+            if self.distributed_network.connect_to(&addr, port).await.is_err() {
+                self.advance_target();
+                continue;
+            }
+
+            // Replay buffered data before new samples are sent directly again
+            let mut buffer = self.link.buffer.lock().await;
+            let pending = match buffer.drain() {
+                Ok(pending) => pending,
+                Err(e) => {
+                    println!("❌ Failed to read buffered data: {}", e);
+                    Vec::new()
+                }
+            };
+
+            let mut replay = pending.into_iter();
+            let mut failed = false;
+            while let Some(sample) = replay.next() {
+                if self.send_to_target(&addr, port, sample.clone()).await.is_err() {
+                    let remaining: Vec<Vec<f64>> = std::iter::once(sample).chain(replay).collect();
+                    if let Err(e) = buffer.requeue(remaining) {
+                        println!("❌ Failed to requeue buffered data: {}", e);
+                    }
+                    failed = true;
+                    break;
+                }
+            }
+
+            if !failed {
+                self.link.reconnecting.store(false, Ordering::SeqCst);
+                self.link.set_status(ConnectionStatus::Connected);
+                return;
+            }
+        }
+
+        let _buffer = self.link.buffer.lock().await;
+        self.link.reconnecting.store(false, Ordering::SeqCst);
+        self.link.set_status(ConnectionStatus::GaveUp { attempts: attempt });
+    }
+
     /// Send data directly to connected neural network nodes via NNP
     /// This is a special implementation for InputNode that bypasses the neural network
-    pub async fn send_data(&self, data: Vec<f64>) -> Result<(), IoError> {
-        // Verify input size matches the configured size
-        if data.len() != self.config.input_size {
-            return Err(IoError::TransformationError(format!(
-                "Input size mismatch: expected {}, got {}",
-                self.config.input_size,
-                data.len()
-            )));
+    ///
+    /// With a target configured, a failed send tries the failover targets and
+    /// then, since reconnecting is on by default, buffers the sample and
+    /// returns `Ok(Delivery::Buffered)` while a reconnect runs in the
+    /// background; it is not broadcast to other networks. Only nodes without
+    /// a target broadcast to every connected network. Set
+    /// `reconnect.enabled = false` to get the send error instead.
+    pub async fn send_data(&self, data: Vec<f64>) -> Result<Delivery, IoError> {
+        // Fit the data to the configured size (rejects mismatches unless an adapter is set)
+        let data = self
+            .config
+            .input_adapter
+            .adapt(&data, self.config.input_size)
+            .map_err(IoError::TransformationError)?;
+        if let Some(curiosity) = &self.curiosity {
+            curiosity.lock().unwrap().observe(&data);
         }
         
-        // Try to send directly to a specific target if configured
-        if let (Some(addr), Some(port)) = (&self.config.target_address, &self.config.target_port) {
-            println!("🔍 Looking for peer with address: {}:{}", addr, port);
-            // Find the peer ID for the target neural network
-            if let Some(peer_id) = self.distributed_network.find_peer_by_address(addr, *port) {
-                println!("✅ Found peer {} for {}:{}", peer_id, addr, port);
-                // Send data directly to the target peer, bypassing neural network processing
-                return self.distributed_network
-                    .send_forward_data(peer_id, 0u8, data)
-                    .await
-                    .map_err(|e| IoError::NetworkError(format!("Failed to send data: {:?}", e)));
-            } else {
-                println!("❌ No peer found for {}:{}", addr, port);
+        if self.link.reconnecting.load(Ordering::SeqCst) {
+            // Re-check under the buffer lock: the reconnect loop may have replayed
+            // the buffer and cleared the flag while this call was waiting for it
+            let mut buffer = self.link.buffer.lock().await;
+            if self.link.reconnecting.load(Ordering::SeqCst) {
+                // Keep ordering: data waits behind the buffered samples until reconnected
+                let error = IoError::ConnectionFailed("Reconnecting to target".to_string());
+                return Self::queue_sample(&mut buffer, data, error);
             }
         }
-        
+
+        // Try to send directly to a specific target if configured
+        if let Some((addr, port)) = self.current_target() {
+            return match self.send_to_target(&addr, port, data.clone()).await {
+                Ok(()) => Ok(Delivery::Sent),
+                Err(e) => {
+                    println!("❌ Failed to send to {}:{}: {}", addr, port, e);
+                    if self.fail_over(&data).await {
+                        return Ok(Delivery::Sent);
+                    }
+                    self.handle_send_failure(data, e).await
+                }
+            };
+        }
+
         // If no specific target or target not found, broadcast to all connected networks
         let message = NetworkMessage {
             msg_type: MessageType::ForwardData,
@@ -140,7 +520,10 @@ impl InputNode {
             payload: MessagePayload::ForwardData {
                 layer_id: 0, // Always use layer 0 for input data
                 data: data.iter().map(|&x| x as f32).collect(),
+                timestamp: None,
+                channel: None,
             },
+            flags: 0,
         };
         
         self.distributed_network
@@ -148,7 +531,7 @@ impl InputNode {
             .await
             .map_err(|e| IoError::NetworkError(format!("Failed to send data: {:?}", e)))?;
 
-        Ok(())
+        Ok(Delivery::Sent)
     }
 
 
@@ -165,6 +548,12 @@ impl InputNode {
             ExternalSourceConfig::HttpEndpoint { url, poll_interval } => {
                 self.start_http_source(url, poll_interval).await?;
             }
+            ExternalSourceConfig::Osc(config) => {
+                self.start_osc_source(config).await?;
+            }
+            ExternalSourceConfig::Simulated(config) => {
+                self.start_simulated_source(config)?;
+            }
             ExternalSourceConfig::Custom { handler } => {
                 let (tx, mut rx) = mpsc::channel(100);
                 handler(tx).await?;
@@ -185,11 +574,11 @@ impl InputNode {
     }
 
     async fn start_tcp_source(&self, address: String, port: u16) -> Result<(), IoError> {
-        let addr = format!("{}:{}", address, port);
         let input_node = self.clone();
+        let preference = self.config.bind_options.preference;
 
         tokio::spawn(async move {
-            if let Ok(mut stream) = TcpStream::connect(&addr).await {
+            if let Ok(mut stream) = NnpStream::connect(&address, port, preference).await {
                 let mut buffer = vec![0u8; 1024];
 
                 while let Ok(n) = stream.read(&mut buffer).await {
@@ -234,6 +623,67 @@ impl InputNode {
 
         Ok(())
     }
+
+    /// Receive OSC packets and send the mapped inputs once per packet
+    async fn start_osc_source(&self, config: OscSourceConfig) -> Result<(), IoError> {
+        let socket = tokio::net::UdpSocket::bind((config.bind_address.as_str(), config.port))
+            .await
+            .map_err(|e| IoError::ConnectionFailed(e.to_string()))?;
+        let input_node = self.clone();
+        let mut inputs = OscInputs::new(config.mappings, self.config.input_size);
+
+        tokio::spawn(async move {
+            let mut buffer = vec![0u8; 65536];
+            loop {
+                let n = match socket.recv(&mut buffer).await {
+                    Ok(n) => n,
+                    Err(e) => {
+                        eprintln!("Failed to receive OSC packet: {}", e);
+                        break;
+                    }
+                };
+                let messages = match osc::decode_packet(&buffer[..n]) {
+                    Ok(messages) => messages,
+                    Err(e) => {
+                        eprintln!("Ignoring malformed OSC packet: {}", e);
+                        continue;
+                    }
+                };
+                let mut matched = false;
+                for message in &messages {
+                    matched |= inputs.apply(message);
+                }
+                if matched {
+                    if let Err(e) = input_node.send_data(inputs.values().to_vec()).await {
+                        eprintln!("Failed to send OSC data: {:?}", e);
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Send one sample of the virtual sensors per tick until the configured count is reached
+    fn start_simulated_source(&self, config: SimSourceConfig) -> Result<(), IoError> {
+        let mut sensors = VirtualSensors::new(config).map_err(IoError::ConfigError)?;
+        let input_node = self.clone();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sensors.config().interval());
+
+            while !sensors.is_finished() {
+                interval.tick().await;
+                if let Err(e) = input_node.send_data(sensors.next_sample()).await {
+                    eprintln!("Failed to send simulated data: {:?}", e);
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
 }
 
 /// Output node that receives data from the distributed neural network
@@ -243,9 +693,153 @@ impl InputNode {
 #[derive(Clone)]
 pub struct OutputNode {
     distributed_network: DistributedNetwork,
-    #[allow(dead_code)]
     config: IoNodeConfig,
     is_running: bool,
+    metrics: Arc<DeliveryMetrics>,
+    queue_len: Arc<AtomicUsize>,
+    curiosity: Option<SharedCuriosity>,
+    evaluator: Option<LiveEvaluator>,
+    watchdog: Arc<WatchdogStatus>,
+}
+
+/// Watchdog of a running sink, raising its alerts on behalf of the node
+struct SinkWatchdog {
+    watchdog: Watchdog,
+    status: Arc<WatchdogStatus>,
+    node: String,
+}
+
+impl SinkWatchdog {
+    fn deadline(&self) -> Option<tokio::time::Instant> {
+        self.watchdog.deadline().map(Into::into)
+    }
+
+    fn is_tripped(&self) -> bool {
+        self.watchdog.is_tripped()
+    }
+
+    /// Whether an output is passed on to the sink; fresh ones feed the watchdog
+    fn observe(&mut self, stale: bool) -> bool {
+        if !self.watchdog.accepts(stale) {
+            return false;
+        }
+        if !stale {
+            if let Some(alert) = self.watchdog.fresh(std::time::Instant::now()) {
+                self.raise(alert);
+            }
+        }
+        true
+    }
+
+    /// Failsafe values that are due now
+    fn expire(&mut self) -> Vec<f64> {
+        let (failsafe, alert) = self.watchdog.expire(std::time::Instant::now());
+        if let Some(alert) = alert {
+            self.raise(alert);
+        }
+        failsafe
+    }
+
+    fn raise(&self, alert: WatchdogAlert) {
+        self.status.record(&alert);
+        alert.raise(&self.node, self.watchdog.config().webhook.as_deref());
+    }
+}
+
+/// External sink that queued output samples are delivered to
+enum SinkTarget {
+    Tcp {
+        address: String,
+        port: u16,
+        preference: AddressPreference,
+        stream: Option<NnpStream>,
+    },
+    Http {
+        url: String,
+        client: hyper::Client<hyper::client::HttpConnector>,
+    },
+    Osc {
+        config: OscSinkConfig,
+        socket: Option<tokio::net::UdpSocket>,
+    },
+    Simulated(VirtualActuator),
+}
+
+impl SinkTarget {
+    /// Deliver one sample, reconnecting TCP sinks if necessary
+    async fn deliver(&mut self, data: &[f64]) -> Result<(), IoError> {
+        match self {
+            SinkTarget::Tcp {
+                address,
+                port,
+                preference,
+                stream,
+            } => {
+                if stream.is_none() {
+                    let connected = NnpStream::connect(address, *port, *preference)
+                        .await
+                        .map_err(|e| IoError::ConnectionFailed(e.to_string()))?;
+                    *stream = Some(connected);
+                }
+
+                // Transform neural network output to bytes
+                let bytes: Vec<u8> = data
+                    .iter()
+                    .map(|&x| (x * 255.0).clamp(0.0, 255.0) as u8)
+                    .collect();
+
+                let connection = stream.as_mut().expect("stream connected above");
+                if let Err(e) = connection.write_all(&bytes).await {
+                    *stream = None;
+                    return Err(IoError::NetworkError(e.to_string()));
+                }
+                Ok(())
+            }
+            SinkTarget::Http { url, client } => {
+                let body = serde_json::json!({ "outputs": data }).to_string();
+                let request = hyper::Request::post(url.as_str())
+                    .header("content-type", "application/json")
+                    .body(hyper::Body::from(body))
+                    .map_err(|e| IoError::ConfigError(e.to_string()))?;
+                let response = client
+                    .request(request)
+                    .await
+                    .map_err(|e| IoError::ConnectionFailed(e.to_string()))?;
+                if !response.status().is_success() {
+                    return Err(IoError::NetworkError(format!(
+                        "Sink responded with {}",
+                        response.status()
+                    )));
+                }
+                Ok(())
+            }
+            SinkTarget::Osc { config, socket } => {
+                if socket.is_none() {
+                    let connected = tokio::net::UdpSocket::bind(("0.0.0.0", 0))
+                        .await
+                        .map_err(|e| IoError::NetworkError(e.to_string()))?;
+                    connected
+                        .connect((config.address.as_str(), config.port))
+                        .await
+                        .map_err(|e| IoError::ConnectionFailed(e.to_string()))?;
+                    *socket = Some(connected);
+                }
+
+                let connection = socket.as_ref().expect("socket connected above");
+                for message in config.messages(data) {
+                    if let Err(e) = connection.send(&message.encode()).await {
+                        *socket = None;
+                        return Err(IoError::NetworkError(e.to_string()));
+                    }
+                }
+                Ok(())
+            }
+            SinkTarget::Simulated(actuator) => {
+                actuator.record(data);
+                Ok(())
+            }
+        }
+    }
 }
 
 impl OutputNode {
@@ -254,17 +848,49 @@ impl OutputNode {
         // Create a dummy neural network for the distributed node
         let dummy_network = NeuralNetwork::new(4, 2, 1, 0.1);
 
-        let (distributed_network, message_receiver) = DistributedNetwork::new(
+        let (mut distributed_network, message_receiver) = DistributedNetwork::new(
             config.name.clone(),
             config.listen_address.clone(),
             config.listen_port,
             dummy_network,
         );
+        distributed_network.set_bind_options(config.bind_options);
+        distributed_network.set_signal_ranges(SignalRanges {
+            input: config.sink_range,
+            output: None,
+        });
+        let queue_len = Arc::new(AtomicUsize::new(0));
+        let probed = Arc::clone(&queue_len);
+        distributed_network.add_health_check(move || {
+            ComponentHealth::queue(
+                "sink_queue",
+                probed.load(Ordering::Relaxed),
+                DEFAULT_QUEUE_THRESHOLD,
+            )
+        });
+        let watchdog = Arc::new(WatchdogStatus::default());
+        if config.watchdog.is_some() {
+            let probed = Arc::clone(&watchdog);
+            distributed_network.add_health_check(move || {
+                let stats = probed.snapshot();
+                let state = if stats.tripped { "failsafe" } else { "armed" };
+                ComponentHealth::new(
+                    "watchdog",
+                    !stats.tripped,
+                    format!("{}, tripped {} times", state, stats.trips),
+                )
+            });
+        }
 
         let output_node = Self {
             distributed_network,
             config,
             is_running: false,
+            metrics: Arc::new(DeliveryMetrics::default()),
+            queue_len,
+            curiosity: None,
+            evaluator: None,
+            watchdog,
         };
 
         (output_node, message_receiver)
@@ -288,6 +914,9 @@ impl OutputNode {
         message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
         sink_config: ExternalSinkConfig,
     ) -> Result<(), IoError> {
+        if let Some(watchdog) = &self.config.watchdog {
+            watchdog.validate().map_err(IoError::ConfigError)?;
+        }
         match sink_config {
             ExternalSinkConfig::TcpSocket { address, port } => {
                 self.start_tcp_sink_handler(message_receiver, address, port)
@@ -296,6 +925,16 @@ impl OutputNode {
             ExternalSinkConfig::HttpEndpoint { url } => {
                 self.start_http_sink_handler(message_receiver, url).await?;
             }
+            ExternalSinkConfig::Osc(config) => {
+                self.start_osc_sink_handler(message_receiver, config);
+            }
+            ExternalSinkConfig::Simulated(actuator) => {
+                self.spawn_sink_queue(message_receiver, SinkTarget::Simulated(actuator));
+            }
+            #[cfg(feature = "gpio")]
+            ExternalSinkConfig::Pwm(config) => {
+                self.start_pwm_sink_handler(message_receiver, config)?;
+            }
             ExternalSinkConfig::Custom { handler } => {
                 let (tx, rx) = mpsc::channel(100);
                 self.start_nnp_receiver(message_receiver, tx).await?;
@@ -306,50 +945,302 @@ impl OutputNode {
         Ok(())
     }
 
+    /// Append the novelty of `curiosity` to every delivered sample as an extra channel
+    ///
+    /// Each output is recorded as the action taken after the latest
+    /// observation of the `InputNode` sharing the module, and the sink
+    /// receives its values followed by the current novelty score.
+    pub fn set_curiosity(&mut self, curiosity: SharedCuriosity) {
+        self.curiosity = Some(curiosity);
+    }
+
+    /// Delivery counters for the external sink queue
+    pub fn delivery_metrics(&self) -> DeliveryStats {
+        self.metrics.snapshot(self.queue_len.load(Ordering::Relaxed))
+    }
+
+    /// Watchdog of a sink about to start, when one is configured
+    fn sink_watchdog(&self) -> Option<SinkWatchdog> {
+        let config = self.config.watchdog.clone()?;
+        Some(SinkWatchdog {
+            watchdog: Watchdog::new(config, std::time::Instant::now()),
+            status: Arc::clone(&self.watchdog),
+            node: self.config.name.clone(),
+        })
+    }
+
+    /// Whether the watchdog is holding the sink at its failsafe values, and how often it tripped
+    pub fn watchdog_stats(&self) -> WatchdogStats {
+        self.watchdog.snapshot()
+    }
+
+    /// Score every output received against the labels arriving on `config.label_channel`
+    ///
+    /// Labels come from any peer sending on that channel (see
+    /// `DistributedNetwork::send_forward_data_on`) and are not delivered to
+    /// the sink. Outputs on other channels count as predictions.
+    pub fn set_live_evaluation(&mut self, config: LiveEvaluationConfig) {
+        let evaluator = LiveEvaluator::new(config);
+        evaluator.listen(&self.distributed_network);
+        let predictions = evaluator.clone();
+        self.distributed_network.on_forward_data(move |event| {
+            if event.channel.as_deref() != Some(predictions.config().label_channel.as_str()) {
+                predictions.record_prediction(&event.data);
+            }
+        });
+        self.evaluator = Some(evaluator);
+    }
+
+    /// Rolling accuracy and error against the live labels, once evaluation is set up
+    pub fn live_metrics(&self) -> Option<LiveMetrics> {
+        self.evaluator.as_ref().map(LiveEvaluator::snapshot)
+    }
+
+    /// Metrics endpoint of this node, including the live evaluation if any
+    pub fn metrics_server(&self) -> MetricsServer {
+        let server = MetricsServer::new(self.distributed_network.clone());
+        match self.evaluator.clone() {
+            Some(evaluator) => server.with_live_evaluation(evaluator),
+            None => server,
+        }
+    }
+
     async fn start_tcp_sink_handler(
         &self,
-        mut message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+        message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
         address: String,
         port: u16,
     ) -> Result<(), IoError> {
-        let addr = format!("{}:{}", address, port);
+        let target = SinkTarget::Tcp {
+            address,
+            port,
+            preference: self.config.bind_options.preference,
+            stream: None,
+        };
+        self.spawn_sink_queue(message_receiver, target);
+
+        Ok(())
+    }
+
+    async fn start_http_sink_handler(
+        &self,
+        message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+        url: String,
+    ) -> Result<(), IoError> {
+        let target = SinkTarget::Http {
+            url,
+            client: hyper::Client::new(),
+        };
+        self.spawn_sink_queue(message_receiver, target);
+
+        Ok(())
+    }
+
+    fn start_osc_sink_handler(
+        &self,
+        message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+        config: OscSinkConfig,
+    ) {
+        let target = SinkTarget::Osc {
+            config,
+            socket: None,
+        };
+        self.spawn_sink_queue(message_receiver, target);
+    }
+
+    /// Drive PWM channels with each output as it arrives
+    ///
+    /// Every channel goes to its failsafe duty once no output arrived for
+    /// `failsafe_after_ms`, and for good when the message stream closes.
+    #[cfg(feature = "gpio")]
+    fn start_pwm_sink_handler(
+        &self,
+        mut message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+        config: PwmSinkConfig,
+    ) -> Result<(), IoError> {
+        let mut outputs = PwmOutputs::open(config).map_err(IoError::ConfigError)?;
+        let metrics = Arc::clone(&self.metrics);
+        let curiosity = self.curiosity.clone();
 
         tokio::spawn(async move {
-            if let Ok(mut stream) = TcpStream::connect(&addr).await {
-                while let Some(message) = message_receiver.recv().await {
-                    if let MessagePayload::ForwardData { data, .. } = message.payload {
-                        // Transform neural network output to bytes
-                        let bytes: Vec<u8> = data
-                            .iter()
-                            .map(|&x| (x * 255.0).clamp(0.0, 255.0) as u8)
-                            .collect();
-
-                        if stream.write_all(&bytes).await.is_err() {
-                            break;
+            let timeout = outputs.config().failsafe_after();
+            // Channels start at their failsafe duty
+            let mut failsafe = true;
+            let mut deadline = tokio::time::Instant::now() + timeout;
+            loop {
+                tokio::select! {
+                    message = message_receiver.recv() => {
+                        let Some(message) = message else { break };
+                        let MessagePayload::ForwardData { data, .. } = message.payload else {
+                            continue;
+                        };
+                        let mut data: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+                        if let Some(curiosity) = &curiosity {
+                            curiosity.lock().unwrap().annotate(&mut data);
+                        }
+                        match outputs.apply(&data) {
+                            Ok(()) => metrics.record_delivered(),
+                            Err(e) => {
+                                println!("❌ PWM sink: {}", e);
+                                metrics.record_failure();
+                            }
                         }
+                        failsafe = false;
+                        deadline = tokio::time::Instant::now() + timeout;
+                    }
+                    _ = tokio::time::sleep_until(deadline), if !failsafe => {
+                        println!("⚠️ No outputs for {:?}, PWM channels in failsafe", timeout);
+                        if let Err(e) = outputs.failsafe() {
+                            println!("❌ PWM sink: {}", e);
+                        }
+                        failsafe = true;
                     }
                 }
             }
+            // Dropping the outputs leaves every channel at its failsafe duty
         });
 
         Ok(())
     }
 
-    async fn start_http_sink_handler(
+    /// Deliver incoming outputs to the sink in order, queueing them while it is unreachable
+    ///
+    /// With `resampling` configured, outputs are buffered by timestamp and the
+    /// sink receives one value per tick instead of one per message.
+    fn spawn_sink_queue(
         &self,
         mut message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
-        url: String,
-    ) -> Result<(), IoError> {
+        mut target: SinkTarget,
+    ) {
+        let policy = self.config.reconnect.clone();
+        let mut queue = PendingBuffer::new(self.config.buffer_policy.clone());
+        let metrics = Arc::clone(&self.metrics);
+        let queue_len = Arc::clone(&self.queue_len);
+        let curiosity = self.curiosity.clone();
+        let mut pacer = self.config.pacing.map(Pacer::new);
+        let mut resampler = self.config.resampling.map(Resampler::new);
+        let mut watchdog = self.sink_watchdog();
+
         tokio::spawn(async move {
-            while let Some(message) = message_receiver.recv().await {
-                if let MessagePayload::ForwardData { data, .. } = message.payload {
-                    let data_f64: Vec<f64> = data.iter().map(|&x| x as f64).collect();
-                    println!("Would POST to {}: {:?}", url, data_f64);
+            let mut attempt = 0;
+            let mut retry_at: Option<tokio::time::Instant> = None;
+            let mut ticker = resampler.as_ref().map(|r| {
+                let mut ticker = tokio::time::interval(r.config().period());
+                ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                ticker
+            });
+
+            loop {
+                let watchdog_at = watchdog.as_ref().and_then(SinkWatchdog::deadline);
+                let sample = tokio::select! {
+                    message = message_receiver.recv() => {
+                        let Some(message) = message else { break };
+                        let stale = message.is_stale();
+                        let MessagePayload::ForwardData { data, timestamp, .. } = message.payload else {
+                            continue;
+                        };
+                        if let Some(watchdog) = &mut watchdog {
+                            if !watchdog.observe(stale) {
+                                continue;
+                            }
+                        }
+                        let mut data: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+                        if let Some(curiosity) = &curiosity {
+                            curiosity.lock().unwrap().annotate(&mut data);
+                        }
+                        match &mut resampler {
+                            // Held back until a tick asks for a value
+                            Some(resampler) => {
+                                resampler.push(timestamp.unwrap_or_else(clock::now_micros), data);
+                                continue;
+                            }
+                            None => Some(data),
+                        }
+                    }
+                    _ = async { ticker.as_mut().unwrap().tick().await }, if ticker.is_some() => {
+                        // Held outputs must not override the failsafe values
+                        if watchdog.as_ref().is_some_and(SinkWatchdog::is_tripped) {
+                            None
+                        } else {
+                            resampler.as_mut().and_then(|r| r.tick(clock::now_micros()))
+                        }
+                    }
+                    _ = async { tokio::time::sleep_until(watchdog_at.unwrap()).await }, if watchdog_at.is_some() => {
+                        watchdog.as_mut().map(SinkWatchdog::expire)
+                    }
+                    _ = async { tokio::time::sleep_until(retry_at.unwrap()).await }, if retry_at.is_some() => {
+                        metrics.record_retry();
+                        let pending = queue.drain().unwrap_or_else(|e| {
+                            println!("❌ Failed to read queued sink data: {}", e);
+                            Vec::new()
+                        });
+
+                        let mut remaining = pending.into_iter();
+                        let mut undelivered = Vec::new();
+                        for sample in remaining.by_ref() {
+                            if let Some(pacer) = &mut pacer {
+                                pacer.wait().await;
+                            }
+                            if let Err(e) = target.deliver(&sample).await {
+                                println!("❌ Sink retry failed: {}", e);
+                                metrics.record_failure();
+                                undelivered.push(sample);
+                                break;
+                            }
+                            metrics.record_delivered();
+                        }
+                        undelivered.extend(remaining);
+
+                        if undelivered.is_empty() {
+                            attempt = 0;
+                            retry_at = None;
+                        } else {
+                            match queue.requeue(undelivered) {
+                                Ok(discarded) => metrics.record_dropped(discarded),
+                                Err(e) => println!("❌ Failed to requeue sink data: {}", e),
+                            }
+                            attempt += 1;
+                            retry_at = policy
+                                .allows_attempt(attempt)
+                                .then(|| tokio::time::Instant::now() + policy.delay_for_attempt(attempt));
+                        }
+                        None
+                    }
+                };
+
+                if let Some(data) = sample {
+                    // Deliver straight away only when nothing is waiting ahead of this sample
+                    if retry_at.is_none() && queue.is_empty() {
+                        if let Some(pacer) = &mut pacer {
+                            pacer.wait().await;
+                        }
+                        match target.deliver(&data).await {
+                            Ok(()) => {
+                                metrics.record_delivered();
+                                continue;
+                            }
+                            Err(e) => {
+                                println!("❌ Sink delivery failed: {}", e);
+                                metrics.record_failure();
+                                retry_at = Some(tokio::time::Instant::now() + policy.delay_for_attempt(attempt));
+                            }
+                        }
+                    }
+
+                    match queue.push(data) {
+                        Ok(outcome) => metrics.record_queued(outcome),
+                        Err(e) => {
+                            println!("❌ Failed to queue sink data: {}", e);
+                            metrics.record_queued(PushOutcome::default());
+                        }
+                    }
+                    if retry_at.is_none() {
+                        retry_at = Some(tokio::time::Instant::now());
+                    }
                 }
+                queue_len.store(queue.len(), Ordering::Relaxed);
             }
         });
-
-        Ok(())
     }
 
     async fn start_nnp_receiver(
@@ -357,14 +1248,40 @@ impl OutputNode {
         mut message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
         sender: mpsc::Sender<Vec<f64>>,
     ) -> Result<(), IoError> {
+        let curiosity = self.curiosity.clone();
+        let mut pacer = self.config.pacing.map(Pacer::new);
+        let mut watchdog = self.sink_watchdog();
         tokio::spawn(async move {
-            while let Some(message) = message_receiver.recv().await {
-                if let MessagePayload::ForwardData { data, .. } = message.payload {
-                    let data_f64: Vec<f64> = data.iter().map(|&x| x as f64).collect();
-
-                    if sender.send(data_f64).await.is_err() {
-                        break;
+            loop {
+                let watchdog_at = watchdog.as_ref().and_then(SinkWatchdog::deadline);
+                let data = tokio::select! {
+                    message = message_receiver.recv() => {
+                        let Some(message) = message else { break };
+                        let stale = message.is_stale();
+                        let MessagePayload::ForwardData { data, .. } = message.payload else {
+                            continue;
+                        };
+                        if let Some(watchdog) = &mut watchdog {
+                            if !watchdog.observe(stale) {
+                                continue;
+                            }
+                        }
+                        let mut data_f64: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+                        if let Some(curiosity) = &curiosity {
+                            curiosity.lock().unwrap().annotate(&mut data_f64);
+                        }
+                        data_f64
+                    }
+                    _ = async { tokio::time::sleep_until(watchdog_at.unwrap()).await }, if watchdog_at.is_some() => {
+                        watchdog.as_mut().expect("deadline set by the watchdog").expire()
                     }
+                };
+                if let Some(pacer) = &mut pacer {
+                    pacer.wait().await;
+                }
+
+                if sender.send(data).await.is_err() {
+                    break;
                 }
             }
         });
@@ -390,6 +1307,10 @@ pub type CustomSinkHandler =
 pub enum ExternalSourceConfig {
     TcpSocket { address: String, port: u16 },
     HttpEndpoint { url: String, poll_interval: u64 },
+    /// OSC messages over UDP, mapped onto inputs by address pattern
+    Osc(OscSourceConfig),
+    /// Virtual sensors, for demos and tests without hardware
+    Simulated(SimSourceConfig),
     Custom { handler: CustomSourceHandler },
 }
 
@@ -398,6 +1319,13 @@ pub enum ExternalSourceConfig {
 pub enum ExternalSinkConfig {
     TcpSocket { address: String, port: u16 },
     HttpEndpoint { url: String },
+    /// OSC messages over UDP, one per output mapping
+    Osc(OscSinkConfig),
+    /// A virtual actuator recording every output it receives
+    Simulated(VirtualActuator),
+    /// Actuators on Linux sysfs PWM channels
+    #[cfg(feature = "gpio")]
+    Pwm(PwmSinkConfig),
     Custom { handler: CustomSinkHandler },
 }
 
@@ -407,3 +1335,176 @@ pub enum ExternalSinkConfig {
 /// by leveraging the SecureDistributedNetwork infrastructure.
 pub type SecureInputNode = InputNode;
 pub type SecureOutputNode = OutputNode;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn free_port() -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    fn target_node(
+        name: &str,
+        port: u16,
+    ) -> (DistributedNetwork, mpsc::UnboundedReceiver<NetworkMessage>) {
+        DistributedNetwork::new(
+            name.to_string(),
+            "127.0.0.1".to_string(),
+            port,
+            NeuralNetwork::with_layers(&[1, 1], 0.0),
+        )
+    }
+
+    fn fast_reconnect() -> ReconnectPolicy {
+        ReconnectPolicy {
+            initial_delay_ms: 10,
+            max_delay_ms: 20,
+            jitter: 0.0,
+            ..ReconnectPolicy::default()
+        }
+    }
+
+    /// Collect the single values of `count` forward data messages
+    async fn receive_samples(
+        inbox: &mut mpsc::UnboundedReceiver<NetworkMessage>,
+        count: usize,
+    ) -> Vec<f32> {
+        let mut values = Vec::new();
+        while values.len() < count {
+            let message = tokio::time::timeout(Duration::from_secs(5), inbox.recv())
+                .await
+                .expect("timed out waiting for forward data")
+                .unwrap();
+            if let MessagePayload::ForwardData { data, .. } = message.payload {
+                values.extend(data);
+            }
+        }
+        values
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_send_during_replay_is_not_stranded() {
+        let port = free_port();
+        let (node, _) = InputNode::new(IoNodeConfig {
+            target_address: Some("127.0.0.1".to_string()),
+            target_port: Some(port),
+            reconnect: fast_reconnect(),
+            ..IoNodeConfig::default()
+        });
+
+        // The target is down: these samples are buffered and a reconnect starts
+        for i in 0..20 {
+            assert_eq!(node.send_data(vec![i as f64]).await.unwrap(), Delivery::Buffered);
+        }
+        assert_eq!(node.pending_count().await, 20);
+
+        let (target, mut inbox) = target_node("target", port);
+        target.start_server().await.unwrap();
+
+        // Keep sending from several tasks while the reconnect loop replays the buffer
+        let senders: Vec<_> = (0..4)
+            .map(|task| {
+                let sender = node.clone();
+                tokio::spawn(async move {
+                    for i in (20 + task..200).step_by(4) {
+                        sender.send_data(vec![i as f64]).await.unwrap();
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+        for sending in senders {
+            sending.await.unwrap();
+        }
+
+        let mut values = receive_samples(&mut inbox, 200).await;
+        assert_eq!(node.pending_count().await, 0);
+        assert_eq!(node.connection_status(), ConnectionStatus::Connected);
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(values, (0..200).map(|i| i as f32).collect::<Vec<_>>());
+    }
+
+    /// Run a target on its own runtime, which is dropped (closing the listener) when `kill` is sent
+    fn spawn_killable_target(port: u16) -> std::sync::mpsc::Sender<()> {
+        let (kill, killed) = std::sync::mpsc::channel();
+        let (ready, is_ready) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let (target, _inbox) = target_node("primary", port);
+            runtime.block_on(target.start_server()).unwrap();
+            ready.send(()).unwrap();
+            let _ = killed.recv();
+            drop(runtime);
+        });
+        is_ready.recv().unwrap();
+        kill
+    }
+
+    #[tokio::test]
+    async fn test_send_fails_over_when_primary_dies() {
+        let (primary_port, standby_port) = (free_port(), free_port());
+        let kill_primary = spawn_killable_target(primary_port);
+        let (standby, mut inbox) = target_node("standby", standby_port);
+        standby.start_server().await.unwrap();
+
+        let (mut node, _) = InputNode::new(IoNodeConfig {
+            target_address: Some("127.0.0.1".to_string()),
+            target_port: Some(primary_port),
+            failover_targets: vec![("127.0.0.1".to_string(), standby_port)],
+            reconnect: fast_reconnect(),
+            ..IoNodeConfig::default()
+        });
+        node.start().await.unwrap();
+
+        kill_primary.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(node.send_data(vec![1.0]).await.unwrap(), Delivery::Sent);
+
+        assert_eq!(receive_samples(&mut inbox, 1).await, vec![1.0]);
+        assert_eq!(node.current_target(), Some(("127.0.0.1".to_string(), standby_port)));
+        assert_eq!(node.pending_count().await, 0);
+    }
+
+    #[tokio::test]
+    async fn test_failover_carries_buffered_samples() {
+        let (primary_port, standby_port) = (free_port(), free_port());
+        let kill_primary = spawn_killable_target(primary_port);
+
+        let (mut node, _) = InputNode::new(IoNodeConfig {
+            target_address: Some("127.0.0.1".to_string()),
+            target_port: Some(primary_port),
+            failover_targets: vec![("127.0.0.1".to_string(), standby_port)],
+            reconnect: fast_reconnect(),
+            ..IoNodeConfig::default()
+        });
+        node.start().await.unwrap();
+        assert_eq!(node.current_target(), Some(("127.0.0.1".to_string(), primary_port)));
+
+        // The primary dies while the standby is still down: samples wait in the buffer
+        kill_primary.send(()).unwrap();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        for i in 0..3 {
+            node.send_data(vec![i as f64]).await.unwrap();
+        }
+        assert_eq!(node.pending_count().await, 3);
+
+        // Once the standby is up, the buffer is replayed to it ahead of new samples
+        let (standby, mut inbox) = target_node("standby", standby_port);
+        standby.start_server().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while node.connection_status() != ConnectionStatus::Connected {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("never failed over to the standby");
+        assert_eq!(node.current_target(), Some(("127.0.0.1".to_string(), standby_port)));
+
+        assert_eq!(node.send_data(vec![3.0]).await.unwrap(), Delivery::Sent);
+        assert_eq!(receive_samples(&mut inbox, 4).await, vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(node.pending_count().await, 0);
+    }
+}
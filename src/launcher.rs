@@ -0,0 +1,479 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+
+/// First port handed out to components that do not fix their own
+pub const DEFAULT_BASE_PORT: u16 = 9100;
+
+/// Pause between starting two processes so listeners are bound before their clients start
+const START_DELAY: Duration = Duration::from_millis(300);
+
+/// Local processes started together by `benny up`
+///
+/// Relative paths are resolved against the directory of the cluster file.
+///
+/// ```toml
+/// [[node]]
+/// name = "hidden"
+/// config = "network_config.toml"
+/// outputs = ["output"]
+///
+/// [input_server]
+/// target = "hidden"
+/// input_size = 4
+///
+/// [output_server]
+/// output_size = 2
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterConfig {
+    #[serde(default = "default_base_port")]
+    pub base_port: u16,
+    #[serde(default, rename = "node")]
+    pub nodes: Vec<NodeSpec>,
+    #[serde(default)]
+    pub input_server: Option<InputServerSpec>,
+    #[serde(default)]
+    pub output_server: Option<OutputServerSpec>,
+}
+
+/// A `benny server` process
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NodeSpec {
+    pub name: String,
+    pub config: PathBuf,
+    #[serde(default)]
+    pub model: Option<PathBuf>,
+    /// NNP port; assigned from `base_port` when absent
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// Names of the nodes (or the output server) this node forwards its outputs to
+    #[serde(default)]
+    pub outputs: Vec<String>,
+    /// Extra `benny server` arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// The `input_server` process, feeding one node
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputServerSpec {
+    #[serde(default = "default_input_name")]
+    pub name: String,
+    /// Name of the node inputs are sent to
+    pub target: String,
+    pub input_size: usize,
+    #[serde(default)]
+    pub web_port: Option<u16>,
+    #[serde(default)]
+    pub websocket_port: Option<u16>,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// The `output_server` process, displaying what nodes forward to it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OutputServerSpec {
+    #[serde(default = "default_output_name")]
+    pub name: String,
+    pub output_size: usize,
+    #[serde(default)]
+    pub listen_port: Option<u16>,
+    #[serde(default)]
+    pub web_port: Option<u16>,
+    #[serde(default)]
+    pub websocket_port: Option<u16>,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+fn default_base_port() -> u16 {
+    DEFAULT_BASE_PORT
+}
+
+fn default_input_name() -> String {
+    "input".to_string()
+}
+
+fn default_output_name() -> String {
+    "output".to_string()
+}
+
+/// One process of a launch plan
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessSpec {
+    pub name: String,
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// Where to reach it, printed at startup
+    pub endpoints: Vec<String>,
+    /// Ports it listens on, by purpose (`nnp`, `web`, `websocket`)
+    pub ports: Vec<(&'static str, u16)>,
+}
+
+impl ClusterConfig {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let content = std::fs::read_to_string(path)?;
+        let mut config: ClusterConfig = toml::from_str(&content)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        for node in &mut config.nodes {
+            node.config = base.join(&node.config);
+            node.model = node.model.as_ref().map(|model| base.join(model));
+        }
+        Ok(config)
+    }
+
+    /// Processes to start, in start order, with every port assigned and wired
+    ///
+    /// Components are started downstream first: the output server, then each
+    /// node after the nodes it forwards to, then the input server. Binaries
+    /// are looked up in `bin_dir`.
+    pub fn plan(&self, bin_dir: &Path) -> Result<Vec<ProcessSpec>, String> {
+        let mut names: Vec<&str> = self.nodes.iter().map(|node| node.name.as_str()).collect();
+        names.extend(self.input_server.iter().map(|input| input.name.as_str()));
+        names.extend(self.output_server.iter().map(|output| output.name.as_str()));
+        for (index, name) in names.iter().enumerate() {
+            if names[..index].contains(name) {
+                return Err(format!("Component name '{}' is used twice", name));
+            }
+        }
+
+        let mut ports = PortAllocator::new(self.base_port, self.fixed_ports());
+        let node_ports: HashMap<&str, u16> = self
+            .nodes
+            .iter()
+            .map(|node| {
+                (
+                    node.name.as_str(),
+                    node.port.unwrap_or_else(|| ports.next()),
+                )
+            })
+            .collect();
+        let endpoint = |name: &str, port: u16| format!("{} 127.0.0.1:{}", name, port);
+        let mut plan = Vec::new();
+
+        let mut output_port = None;
+        if let Some(output) = &self.output_server {
+            let listen_port = output.listen_port.unwrap_or_else(|| ports.next());
+            let web_port = output.web_port.unwrap_or_else(|| ports.next());
+            let websocket_port = output.websocket_port.unwrap_or_else(|| ports.next());
+            output_port = Some((output.name.as_str(), listen_port));
+            plan.push(ProcessSpec {
+                name: output.name.clone(),
+                program: bin_dir.join(binary("output_server")),
+                args: [
+                    "--listen-host",
+                    "127.0.0.1",
+                    "--listen-port",
+                    &listen_port.to_string(),
+                    "--web-host",
+                    "127.0.0.1",
+                    "--web-port",
+                    &web_port.to_string(),
+                    "--websocket-port",
+                    &websocket_port.to_string(),
+                    "--output-size",
+                    &output.output_size.to_string(),
+                ]
+                .into_iter()
+                .map(str::to_string)
+                .chain(output.args.iter().cloned())
+                .collect(),
+                endpoints: vec![
+                    endpoint("NNP", listen_port),
+                    format!("web http://127.0.0.1:{}", web_port),
+                ],
+                ports: vec![
+                    ("nnp", listen_port),
+                    ("web", web_port),
+                    ("websocket", websocket_port),
+                ],
+            });
+        }
+
+        let resolve = |name: &str| {
+            node_ports
+                .get(name)
+                .copied()
+                .or_else(|| {
+                    output_port
+                        .filter(|(output, _)| *output == name)
+                        .map(|(_, port)| port)
+                })
+                .ok_or_else(|| format!("Unknown component '{}'", name))
+        };
+        for node in self.start_order() {
+            let port = node_ports[node.name.as_str()];
+            let mut args = vec![
+                "server".to_string(),
+                "--config".to_string(),
+                node.config.display().to_string(),
+                "--port".to_string(),
+                port.to_string(),
+            ];
+            if let Some(model) = &node.model {
+                args.extend(["--model".to_string(), model.display().to_string()]);
+            }
+            for output in &node.outputs {
+                let output_port = resolve(output).map_err(|e| format!("{}: {}", node.name, e))?;
+                args.extend([
+                    "--outputs".to_string(),
+                    format!("127.0.0.1:{}", output_port),
+                ]);
+            }
+            args.extend(node.args.iter().cloned());
+            plan.push(ProcessSpec {
+                name: node.name.clone(),
+                program: bin_dir.join(binary("neural_network")),
+                args,
+                endpoints: vec![endpoint("NNP", port)],
+                ports: vec![("nnp", port)],
+            });
+        }
+
+        if let Some(input) = &self.input_server {
+            let target_port =
+                resolve(&input.target).map_err(|e| format!("{}: {}", input.name, e))?;
+            let web_port = input.web_port.unwrap_or_else(|| ports.next());
+            let websocket_port = input.websocket_port.unwrap_or_else(|| ports.next());
+            plan.push(ProcessSpec {
+                name: input.name.clone(),
+                program: bin_dir.join(binary("input_server")),
+                args: [
+                    "--network-host",
+                    "127.0.0.1",
+                    "--network-port",
+                    &target_port.to_string(),
+                    "--web-host",
+                    "127.0.0.1",
+                    "--web-port",
+                    &web_port.to_string(),
+                    "--websocket-port",
+                    &websocket_port.to_string(),
+                    "--input-size",
+                    &input.input_size.to_string(),
+                ]
+                .into_iter()
+                .map(str::to_string)
+                .chain(input.args.iter().cloned())
+                .collect(),
+                endpoints: vec![format!("web http://127.0.0.1:{}", web_port)],
+                ports: vec![("web", web_port), ("websocket", websocket_port)],
+            });
+        }
+
+        Ok(plan)
+    }
+
+    fn fixed_ports(&self) -> Vec<u16> {
+        let mut fixed: Vec<u16> = self.nodes.iter().filter_map(|node| node.port).collect();
+        if let Some(output) = &self.output_server {
+            fixed.extend(
+                [output.listen_port, output.web_port, output.websocket_port]
+                    .into_iter()
+                    .flatten(),
+            );
+        }
+        if let Some(input) = &self.input_server {
+            fixed.extend([input.web_port, input.websocket_port].into_iter().flatten());
+        }
+        fixed
+    }
+
+    /// Nodes ordered so each starts after the nodes it forwards to
+    fn start_order(&self) -> Vec<&NodeSpec> {
+        let mut order: Vec<&NodeSpec> = Vec::new();
+        let mut pending: Vec<&NodeSpec> = self.nodes.iter().collect();
+        while !pending.is_empty() {
+            let started = |name: &String| {
+                order.iter().any(|node| &node.name == name)
+                    || !self.nodes.iter().any(|node| &node.name == name)
+            };
+            match pending
+                .iter()
+                .position(|node| node.outputs.iter().all(started))
+            {
+                Some(index) => order.push(pending.remove(index)),
+                // A cycle: start the rest in file order, peers reconnect once all are up
+                None => order.append(&mut pending),
+            }
+        }
+        order
+    }
+}
+
+/// Hands out ports from a base, skipping ones fixed in the cluster file
+struct PortAllocator {
+    next: u16,
+    fixed: Vec<u16>,
+}
+
+impl PortAllocator {
+    fn new(base: u16, fixed: Vec<u16>) -> Self {
+        Self { next: base, fixed }
+    }
+
+    fn next(&mut self) -> u16 {
+        while self.fixed.contains(&self.next) {
+            self.next += 1;
+        }
+        self.next += 1;
+        self.next - 1
+    }
+}
+
+fn binary(name: &str) -> String {
+    format!("{}{}", name, std::env::consts::EXE_SUFFIX)
+}
+
+/// Start every process of `plan`, prefix their output with their names and
+/// stop them all on Ctrl-C
+///
+/// Returns once every process has exited.
+pub async fn run(plan: Vec<ProcessSpec>) -> Result<(), Box<dyn std::error::Error>> {
+    let width = plan
+        .iter()
+        .map(|process| process.name.len())
+        .max()
+        .unwrap_or(0);
+    let (stop, stopped) = watch::channel(false);
+    let mut running = JoinSet::new();
+
+    for process in plan {
+        let prefix = format!("[{:>width$}]", process.name, width = width);
+        let mut child = Command::new(&process.program)
+            .args(&process.args)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                format!(
+                    "Failed to start {} ({}): {}",
+                    process.name,
+                    process.program.display(),
+                    e
+                )
+            })?;
+        println!("{} ▶️ started: {}", prefix, process.endpoints.join(", "));
+
+        if let Some(stdout) = child.stdout.take() {
+            tokio::spawn(forward_lines(BufReader::new(stdout), prefix.clone()));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            tokio::spawn(forward_lines(BufReader::new(stderr), prefix.clone()));
+        }
+
+        let mut stopped = stopped.clone();
+        running.spawn(async move {
+            tokio::select! {
+                status = child.wait() => match status {
+                    Ok(status) => println!("{} ⏹️ exited: {}", prefix, status),
+                    Err(e) => println!("{} ❌ lost track of the process: {}", prefix, e),
+                },
+                _ = stopped.changed() => {
+                    let _ = child.kill().await;
+                    println!("{} ⏹️ stopped", prefix);
+                }
+            }
+        });
+        tokio::time::sleep(START_DELAY).await;
+    }
+
+    println!("✅ All processes started; press Ctrl-C to stop them");
+    loop {
+        tokio::select! {
+            finished = running.join_next() => {
+                if finished.is_none() {
+                    break;
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("🛑 Stopping {} processes", running.len());
+                let _ = stop.send(true);
+                while running.join_next().await.is_some() {}
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn forward_lines<R: AsyncBufRead + Unpin>(reader: R, prefix: String) {
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        println!("{} {}", prefix, line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_assigns_and_wires_ports() {
+        let config: ClusterConfig = toml::from_str(
+            r#"
+            base_port = 9000
+
+            [[node]]
+            name = "hidden"
+            config = "hidden.toml"
+            outputs = ["head"]
+
+            [[node]]
+            name = "head"
+            config = "head.toml"
+            port = 9001
+            outputs = ["output"]
+
+            [input_server]
+            target = "hidden"
+            input_size = 4
+
+            [output_server]
+            output_size = 2
+            "#,
+        )
+        .unwrap();
+        let plan = config.plan(Path::new("bin")).unwrap();
+        let names: Vec<&str> = plan.iter().map(|process| process.name.as_str()).collect();
+        assert_eq!(names, vec!["output", "head", "hidden", "input"]);
+
+        // hidden takes 9000, head fixed 9001, then the output server 9002..=9004
+        assert!(plan[0]
+            .args
+            .windows(2)
+            .any(|pair| pair == ["--listen-port", "9002"]));
+        assert!(plan[1]
+            .args
+            .windows(2)
+            .any(|pair| pair == ["--outputs", "127.0.0.1:9002"]));
+        assert!(plan[2]
+            .args
+            .windows(2)
+            .any(|pair| pair == ["--outputs", "127.0.0.1:9001"]));
+        assert!(plan[3]
+            .args
+            .windows(2)
+            .any(|pair| pair == ["--network-port", "9000"]));
+        assert_eq!(
+            plan[1].program,
+            Path::new("bin").join(binary("neural_network"))
+        );
+
+        let mut broken = config.clone();
+        broken.nodes[0].outputs.push("nowhere".to_string());
+        assert!(broken.plan(Path::new("bin")).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+use crate::activation::Activation;
+use crate::convolution::PoolKind;
+use crate::rbm::RbmTraining;
+use serde::{Deserialize, Serialize};
+
+/// One entry of a heterogeneous layer stack
+///
+/// A stack starts with `Input`; `Dense`, `Conv` and `Pool` each add a layer,
+/// while `Dropout`, `KWinners`, `LayerNorm`, `BatchNorm` and `Rbm` modify the
+/// layer before them.
+/// Convolution and pooling shapes are inferred from the previous layer:
+/// `Input` may declare `shape = [channels, height, width]`, `Dense` layers
+/// are treated as a single 1 x n signal.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum LayerSpec {
+    Input {
+        n: usize,
+        #[serde(default)]
+        shape: Option<(usize, usize, usize)>,
+    },
+    Dense {
+        n: usize,
+        #[serde(default)]
+        activation: Activation,
+    },
+    Conv {
+        out_channels: usize,
+        /// Kernel height and width; use height 1 for 1D signals
+        kernel: (usize, usize),
+        #[serde(default = "default_stride")]
+        stride: usize,
+        #[serde(default)]
+        padding: usize,
+        #[serde(default)]
+        activation: Activation,
+    },
+    Pool {
+        kind: PoolKind,
+        kernel: (usize, usize),
+        stride: usize,
+    },
+    Dropout {
+        rate: f64,
+    },
+    /// Keep only the `k` most active outputs of the previous hidden layer
+    KWinners {
+        k: usize,
+    },
+    LayerNorm,
+    BatchNorm,
+    /// Pre-train the previous sigmoid Dense layer as a restricted Boltzmann
+    /// machine before backpropagation (see `rbm::pretrain_layers`)
+    Rbm(RbmTraining),
+}
+
+fn default_stride() -> usize {
+    1
+}
+
+impl LayerSpec {
+    /// The simple architecture form: an input layer followed by sigmoid dense layers
+    pub fn from_sizes(sizes: &[usize]) -> Vec<LayerSpec> {
+        sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &n)| {
+                if i == 0 {
+                    LayerSpec::Input { n, shape: None }
+                } else {
+                    LayerSpec::Dense {
+                        n,
+                        activation: Activation::Sigmoid,
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Weight layers marked for RBM pre-training, in stack order
+    pub fn rbm_layers(specs: &[LayerSpec]) -> Vec<(usize, RbmTraining)> {
+        let mut layers = 0;
+        let mut marked = Vec::new();
+        for spec in specs {
+            match spec {
+                LayerSpec::Dense { .. } | LayerSpec::Conv { .. } | LayerSpec::Pool { .. } => {
+                    layers += 1
+                }
+                LayerSpec::Rbm(training) if layers > 0 => marked.push((layers - 1, *training)),
+                _ => {}
+            }
+        }
+        marked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specs_from_toml() {
+        #[derive(Deserialize)]
+        struct Stack {
+            layers: Vec<LayerSpec>,
+        }
+
+        let stack: Stack = toml::from_str(
+            r#"
+            [[layers]]
+            type = "Input"
+            n = 16
+            shape = [1, 4, 4]
+
+            [[layers]]
+            type = "Conv"
+            out_channels = 2
+            kernel = [2, 2]
+
+            [[layers]]
+            type = "BatchNorm"
+
+            [[layers]]
+            type = "Dense"
+            n = 3
+            activation = "Tanh"
+
+            [[layers]]
+            type = "Rbm"
+            epochs = 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            stack.layers,
+            vec![
+                LayerSpec::Input {
+                    n: 16,
+                    shape: Some((1, 4, 4))
+                },
+                LayerSpec::Conv {
+                    out_channels: 2,
+                    kernel: (2, 2),
+                    stride: 1,
+                    padding: 0,
+                    activation: Activation::Sigmoid
+                },
+                LayerSpec::BatchNorm,
+                LayerSpec::Dense {
+                    n: 3,
+                    activation: Activation::Tanh
+                },
+                LayerSpec::Rbm(RbmTraining {
+                    epochs: 5,
+                    ..RbmTraining::default()
+                }),
+            ]
+        );
+        assert_eq!(
+            LayerSpec::rbm_layers(&stack.layers),
+            vec![(1, RbmTraining { epochs: 5, ..RbmTraining::default() })]
+        );
+        assert_eq!(LayerSpec::from_sizes(&[2, 3]).len(), 2);
+    }
+}
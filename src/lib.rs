@@ -1,29 +1,300 @@
-pub mod cli;
-pub mod distributed_network;
-pub mod input_server;
-pub mod io_interface;
-pub mod network_composer;
-pub mod neural_network;
-pub mod output_server;
-pub mod runner;
-pub mod secure_network;
-pub mod server;
+#![cfg_attr(not(feature = "std"), no_std)]
 
-pub use distributed_network::{
-    DistributedNetwork, MessagePayload, MessageType, NetworkMessage, ProtocolError, capabilities,
-};
-pub use input_server::{
-    InputServer, InputServerConfig, NetworkInfo, NeuralNetworkTarget, WebSocketMessage,
-};
-pub use output_server::{
-    OutputServer, OutputServerConfig, OutputNetworkInfo, NeuralNetworkSource, OutputWebSocketMessage,
-};
-pub use io_interface::{
-    ExternalSinkConfig, ExternalSourceConfig, InputNode, IoConnectionId, IoError, IoNodeConfig,
-    OutputNode, SecureInputNode, SecureOutputNode,
-};
-pub use network_composer::{NetworkComposer, NetworkConnection};
-pub use neural_network::{HebbianLearningMode, NeuralNetwork};
-pub use secure_network::{
-    NetworkCertificate, SecureDistributedNetwork, SecureNetworkError, TlsConfig,
-};
+extern crate alloc;
+
+/// Declare items that need the standard library
+macro_rules! with_std {
+    ($($item:item)*) => {
+        $(
+            #[cfg(feature = "std")]
+            $item
+        )*
+    };
+}
+
+pub mod activation;
+pub mod inference;
+pub mod quantized;
+pub mod small_network;
+
+pub use activation::Activation;
+pub use inference::{InferenceLayer, InferenceModel};
+pub use quantized::{AccuracyReport, Precision, QuantizedLayer, QuantizedModel};
+pub use small_network::SmallNetwork;
+
+with_std! {
+    pub mod activation_export;
+    pub mod activation_registry;
+    pub mod adaptive_rates;
+    pub mod address;
+    pub mod alerts;
+    pub mod arena;
+    pub mod async_io;
+    pub mod atomic_file;
+    pub mod audio_input;
+    pub mod augmentation;
+    pub mod bandwidth;
+    pub mod batch_tuning;
+    pub mod benchmark;
+    pub mod bridge;
+    pub mod broadcast;
+    pub mod camera_input;
+    pub mod channels;
+    pub mod checkpoint;
+    pub mod cli;
+    pub mod closed_loop;
+    pub mod clock;
+    pub mod compatibility;
+    pub mod config_overrides;
+    pub mod config_schema;
+    pub mod convolution;
+    pub mod cost;
+    pub mod curiosity;
+    pub mod datasets;
+    pub mod dashboard;
+    pub mod debug;
+    pub mod decision_boundary;
+    pub mod decimation;
+    pub mod deployment_plan;
+    pub mod derived_signals;
+    pub mod differential;
+    pub mod display;
+    pub mod distributed_network;
+    pub mod dry_run;
+    pub mod echo_state;
+    pub mod embedding;
+    pub mod environment;
+    pub mod evaluation;
+    pub mod feature_importance;
+    pub mod health;
+    pub mod hopfield;
+    pub mod identity;
+    pub mod input_adapter;
+    pub mod input_bridge;
+    pub mod input_constraints;
+    pub mod input_server;
+    pub mod intrinsic_plasticity;
+    pub mod io_interface;
+    pub mod launcher;
+    pub mod layer_spec;
+    pub mod live_evaluation;
+    pub mod lottery;
+    pub mod metrics;
+    pub mod mmap_model;
+    pub mod model_metadata;
+    pub mod model_push;
+    pub mod network_composer;
+    pub mod neural_network;
+    pub mod nnp_tool;
+    pub mod normalization;
+    pub mod notebook;
+    pub mod osc;
+    pub mod output_alerts;
+    pub mod output_history;
+    pub mod output_frame;
+    pub mod output_server;
+    pub mod output_sink;
+    pub mod pacing;
+    pub mod population;
+    pub mod priority;
+    pub mod profiling;
+    pub mod projection;
+    pub mod protocol_compat;
+    pub mod protocol_conformance;
+    pub mod protocol_dump;
+    pub mod protocol_spec;
+    pub mod pruning;
+    pub mod python_client;
+    pub mod rbm;
+    pub mod realtime;
+    pub mod reconnect;
+    pub mod recurrent;
+    pub mod reload;
+    pub mod repl;
+    pub mod remote_config;
+    pub mod resampling;
+    pub mod resources;
+    pub mod retraining;
+    pub mod robust_hebbian;
+    pub mod routing;
+    pub mod runner;
+    pub mod runtime;
+    pub mod scaling;
+    pub mod scripting;
+    pub mod secure_network;
+    pub mod sequence;
+    pub mod server;
+    pub mod session_recorder;
+    pub mod shadow_capture;
+    pub mod sim;
+    pub mod soak;
+    pub mod spikes;
+    pub mod split;
+    pub mod storage;
+    pub mod sparsity;
+    pub mod spsa;
+    pub mod subscriptions;
+    pub mod swa;
+    pub mod sweep;
+    pub mod synaptic_tagging;
+    pub mod telemetry;
+    pub mod testing;
+    pub mod thresholds;
+    pub mod tokens;
+    pub mod top;
+    pub mod training_plots;
+    pub mod transport;
+    pub mod visualize;
+    pub mod watchdog;
+    pub mod weight_animation;
+    pub mod weight_ensemble;
+    pub mod ws_session;
+
+    pub use activation_export::HistoryFormat;
+    pub use activation_registry::CustomActivation;
+    pub use adaptive_rates::AdaptiveRates;
+    pub use address::{AddressPreference, BindOptions, BoundPorts};
+    pub use alerts::{AlertConfig, Alerter, TrainingAlert, WebhookConfig, WebhookKind};
+    pub use arena::{AgentLearning, Arena, ArenaRound, Topology};
+    pub use async_io::Compression;
+    pub use atomic_file::{LoadError, TruncatedFile};
+    pub use audio_input::{AudioFeatures, AudioInputConfig, AudioSource, FeatureExtractor};
+    pub use augmentation::{AugmentationConfig, Augmenter};
+    pub use bandwidth::{BandwidthLimit, BandwidthTracker, PeerBandwidthStats};
+    pub use batch_tuning::BatchTuner;
+    pub use benchmark::{compare_modes, experiment_seed, ModeBenchmark};
+    pub use bridge::{BridgeConfig, BridgeNode, BridgePolicy, BridgeStats};
+    pub use broadcast::Broadcaster;
+    pub use camera_input::{CameraInputConfig, FrameSource, JpegSplitter, RegionOfInterest};
+    pub use channels::ChannelTable;
+    pub use checkpoint::{CheckpointStore, WeightDelta};
+    pub use closed_loop::{LoopConfig, LoopController, LoopMapping, SafetyLimits};
+    pub use clock::{ClockSample, ClockSync};
+    pub use compatibility::ShapePolicy;
+    pub use config_overrides::{ConfigOverride, ConfigOverrides, OverrideSource};
+    pub use config_schema::{ConfigIssue, ConfigReport, Severity};
+    pub use convolution::{ConvLayer, ConvSpec, PoolKind, PoolSpec};
+    pub use cost::{CallCost, CostReport, OpCost};
+    pub use curiosity::{Curiosity, SharedCuriosity};
+    pub use dashboard::{ChartType, Dashboard, DashboardPanel, DashboardStore};
+    pub use debug::{grad_check, GradCheck};
+    pub use decision_boundary::{BoundaryAnimation, DecisionGrid};
+    pub use decimation::{DecimatedSample, Decimation, Decimator};
+    pub use derived_signals::{DerivedChannel, DerivedSignal, DerivedSignals};
+    pub use deployment_plan::{DataFlowEdge, DeploymentPlan, NodeShape, PlannedComponent};
+    pub use differential::{DifferentialReport, DifferentialSide, DivergencePoint, StoragePrecision};
+    pub use display::DisplayMetadata;
+    pub use distributed_network::{
+        AckPolicy, DistributedNetwork, MessagePayload, MessageType, NetworkMessage, NodeRole,
+        NodeSnapshot, PeerInfo, PeerRecord, ProtocolError, capabilities, message_flags,
+    };
+    pub use dry_run::ExecutionPlan;
+    pub use echo_state::{EchoStateConfig, EchoStateNetwork};
+    pub use embedding::Embedding;
+    pub use environment::{
+        Controller, Environment, EnvironmentBridge, EpisodeStats, LineWorld, RemoteController,
+        Transition,
+    };
+    pub use evaluation::{ClassMetrics, Evaluation};
+    pub use feature_importance::{FeatureImportance, ImportanceReport};
+    pub use health::{ComponentHealth, HealthReport};
+    pub use hopfield::HopfieldNetwork;
+    pub use identity::IdentitySource;
+    pub use input_adapter::{InputAdapter, LinearProjection};
+    pub use input_bridge::{
+        ControlMapping, ControllerEvent, ControllerSource, InputBridge, InputBridgeConfig,
+    };
+    pub use input_constraints::{InputConstraint, InputOption};
+    pub use intrinsic_plasticity::IntrinsicPlasticity;
+    pub use input_server::{
+        InputServer, InputServerConfig, NetworkInfo, NeuralNetworkTarget, WebSocketMessage,
+    };
+    pub use output_alerts::{AlertLevel, AlertTracker, OutputAlert, OutputThreshold, ThresholdStore};
+    pub use output_history::{HistoryConfig, HistoryQuery, HistorySample, OutputHistory, RetentionPolicy};
+    pub use output_sink::{OutputSink, SinkConfig, SinkSample, SinkTarget};
+    pub use output_frame::OutputFrame;
+    pub use output_server::{
+        DashboardSession, OutputServer, OutputServerConfig, OutputNetworkInfo, NeuralNetworkSource,
+        OutputWebSocketMessage, StreamFormat,
+    };
+    pub use ws_session::{SessionConfig, SessionStore};
+    pub use io_interface::{
+        Delivery, ExternalSinkConfig, ExternalSourceConfig, InputNode, IoConnectionId, IoError,
+        IoNodeConfig, OutputNode, SecureInputNode, SecureOutputNode,
+    };
+    pub use launcher::ClusterConfig;
+    pub use layer_spec::LayerSpec;
+    pub use live_evaluation::{LiveEvaluationConfig, LiveEvaluator, LiveMetrics};
+    pub use lottery::{LotteryConfig, LotteryReport, WeightMask};
+    pub use mmap_model::MappedModel;
+    pub use model_metadata::{LayerContribution, ModelMetadata};
+    pub use model_push::{ModelAssembler, ModelChunk};
+    pub use network_composer::{NetworkComposer, NetworkConnection};
+    pub use neural_network::{HebbianLearningMode, MergeStrategy, NeuralNetwork, SkipConnection};
+    pub use nnp_tool::SendKind;
+    pub use normalization::{BatchNorm, LayerNorm};
+    pub use notebook::TrainingReport;
+    pub use osc::{
+        OscArg, OscInputMapping, OscMessage, OscOutputMapping, OscSinkConfig, OscSourceConfig,
+    };
+    pub use pacing::{Pacer, Pacing, PeerPacing};
+    pub use population::{PopulationCode, PopulationCoder, PopulationColumn};
+    pub use priority::{Priority, PriorityLanes};
+    pub use profiling::{Phase, TimingReport};
+    pub use projection::{Pca, ProjectionMethod};
+    pub use pruning::{PruneConfig, PruneReport};
+    pub use transport::{NnpListener, NnpStream};
+    pub use rbm::{Rbm, RbmTraining};
+    pub use realtime::{LatencyBudget, Prediction, RealtimePredictor, RealtimeStats};
+    pub use remote_config::{ConfigAuditEntry, ConfigUpdate};
+    pub use resampling::{ResampleMode, Resampler, Resampling};
+    pub use resources::{ResourceMonitor, ResourceSample, ResourceUsage};
+    pub use retraining::{CronSchedule, DatasetSnapshot, PromotionDecision, PromotionMetric};
+    pub use robust_hebbian::RobustHebbian;
+    pub use routing::{LatencyRouter, RouteEntry, RoutingConfig};
+    pub use runtime::{
+        BennyRuntime, OnlineTrainingConfig, RuntimeBuilder, RuntimeHandle, TrainingStats,
+    };
+    pub use reconnect::{
+        BufferPolicy, ConnectionStatus, DeliveryStats, OverflowPolicy, ReconnectPolicy,
+    };
+    pub use recurrent::{BpttConfig, RecurrentNetwork};
+    pub use reload::ReloadReport;
+    pub use scaling::{PeerRanges, RangeFit, SignalRanges, ValueRange};
+    pub use scripting::{ScriptConfig, ScriptTransform};
+    pub use secure_network::{
+        NetworkCertificate, SecureDistributedNetwork, SecureNetworkError, TlsConfig,
+    };
+    pub use sequence::{Episode, SequenceData};
+    pub use session_recorder::{RecordingConfig, SessionRecorder};
+    pub use shadow_capture::{CaptureConfig, CaptureFilter, ShadowCapture};
+    pub use sim::{
+        ActuatorCommand, SimSourceConfig, VirtualActuator, VirtualSensor, VirtualSensors,
+    };
+    pub use soak::{soak, SoakConfig, SoakReport, SoakSample};
+    pub use sparsity::Sparseness;
+    pub use spikes::{SpikeEvent, StdpParams};
+    pub use spsa::{Spsa, SpsaConfig};
+    pub use split::{DataSplit, SplitConfig};
+    pub use storage::{Location, S3Config, StagedFile, StorageError};
+    pub use subscriptions::{DisconnectEvent, ForwardDataEvent, Subscriptions, WeightSyncEvent};
+    pub use swa::{SwaConfig, WeightAverage};
+    pub use sweep::{SweepConfig, SweepOptions, SweepReport, SweepResult, SweepRun};
+    pub use synaptic_tagging::SynapticTagging;
+    pub use telemetry::{EpochRecord, ResourceRecord, RunParams, RunStore, RunSummary};
+    pub use testing::WeightStats;
+    pub use thresholds::{ThresholdPoint, ThresholdReport, ThresholdSweep};
+    pub use tokens::{ApiToken, Scope, TokenGuard, TokenStore};
+    pub use training_plots::TrainingCurves;
+    pub use visualize::{ImageFormat, VisualizeOptions};
+    pub use watchdog::{Watchdog, WatchdogAlert, WatchdogConfig, WatchdogStats};
+    pub use weight_animation::WeightAnimation;
+    pub use weight_ensemble::WeightEnsemble;
+}
+
+#[cfg(feature = "arrow")]
+pub mod arrow_batch;
+#[cfg(feature = "polars")]
+pub mod polars_frame;
+#[cfg(feature = "gpio")]
+pub mod pwm_sink;
@@ -0,0 +1,286 @@
+use crate::distributed_network::DistributedNetwork;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+
+/// Channel ground-truth labels are expected on unless configured otherwise
+pub const DEFAULT_LABEL_CHANNEL: &str = "labels";
+
+/// How deployed predictions are matched with the labels that arrive later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LiveEvaluationConfig {
+    /// Forward data channel carrying the ground truth
+    pub label_channel: String,
+    /// Most recent labelled predictions the rolling metrics cover
+    pub window: usize,
+    /// Predictions (or labels) kept waiting for their counterpart; older ones are dropped
+    pub max_pending: usize,
+}
+
+impl Default for LiveEvaluationConfig {
+    fn default() -> Self {
+        Self {
+            label_channel: DEFAULT_LABEL_CHANNEL.to_string(),
+            window: 1000,
+            max_pending: 1000,
+        }
+    }
+}
+
+/// Rolling error of a deployed model against its live labels
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LiveMetrics {
+    /// Labelled predictions in the window
+    pub samples: usize,
+    /// Labelled predictions since the start
+    pub labelled_total: u64,
+    /// Share of the window whose class (thresholded at 0.5, or the largest output) was right
+    pub accuracy: f64,
+    pub mse: f64,
+    pub mae: f64,
+    pub pending_predictions: usize,
+    pub pending_labels: usize,
+    /// Predictions and labels dropped before their counterpart arrived
+    pub dropped_total: u64,
+}
+
+/// Error of one labelled prediction
+#[derive(Debug, Clone, Copy)]
+struct Outcome {
+    squared: f64,
+    absolute: f64,
+    correct: bool,
+}
+
+impl Outcome {
+    fn of(prediction: &[f64], label: &[f64]) -> Self {
+        let pairs = prediction.len().min(label.len()).max(1) as f64;
+        let (squared, absolute) = prediction
+            .iter()
+            .zip(label)
+            .fold((0.0, 0.0), |(squared, absolute), (p, l)| {
+                (squared + (p - l).powi(2), absolute + (p - l).abs())
+            });
+        let correct = match (prediction, label) {
+            ([p], [l]) => (*p >= 0.5) == (*l >= 0.5),
+            _ => crate::projection::class_of(prediction) == crate::projection::class_of(label),
+        };
+        Self {
+            squared: squared / pairs,
+            absolute: absolute / pairs,
+            correct,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    predictions: VecDeque<Vec<f64>>,
+    labels: VecDeque<Vec<f64>>,
+    window: VecDeque<Outcome>,
+    labelled_total: u64,
+    dropped_total: u64,
+}
+
+/// Continuous evaluation of a deployed model against a labelled live feed
+///
+/// Predictions and labels are matched in arrival order: each label belongs
+/// to the oldest prediction still waiting for one, so the labelling source
+/// must emit one label per prediction, in the same order, however late.
+/// Clones share their state.
+#[derive(Debug, Clone)]
+pub struct LiveEvaluator {
+    config: LiveEvaluationConfig,
+    state: Arc<Mutex<State>>,
+}
+
+impl LiveEvaluator {
+    pub fn new(config: LiveEvaluationConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(State::default())),
+        }
+    }
+
+    pub fn config(&self) -> &LiveEvaluationConfig {
+        &self.config
+    }
+
+    /// Record the labels `network` receives on the label channel
+    ///
+    /// The channel is diverted, so labels never reach the node's message
+    /// loop as activations.
+    pub fn listen(&self, network: &DistributedNetwork) {
+        network.channels.divert(&self.config.label_channel);
+        let evaluator = self.clone();
+        network.on_forward_data(move |event| {
+            if event.channel.as_deref() == Some(evaluator.config.label_channel.as_str()) {
+                evaluator.record_label(&event.data);
+            }
+        });
+    }
+
+    pub fn record_prediction(&self, outputs: &[f64]) {
+        let mut state = self.state.lock().unwrap();
+        match state.labels.pop_front() {
+            Some(label) => self.score(&mut state, outputs, &label),
+            None => {
+                state.predictions.push_back(outputs.to_vec());
+                if state.predictions.len() > self.config.max_pending {
+                    state.predictions.pop_front();
+                    state.dropped_total += 1;
+                }
+            }
+        }
+    }
+
+    pub fn record_label(&self, targets: &[f64]) {
+        let mut state = self.state.lock().unwrap();
+        match state.predictions.pop_front() {
+            Some(prediction) => self.score(&mut state, &prediction, targets),
+            None => {
+                state.labels.push_back(targets.to_vec());
+                if state.labels.len() > self.config.max_pending {
+                    state.labels.pop_front();
+                    state.dropped_total += 1;
+                }
+            }
+        }
+    }
+
+    fn score(&self, state: &mut State, prediction: &[f64], label: &[f64]) {
+        state.window.push_back(Outcome::of(prediction, label));
+        if state.window.len() > self.config.window.max(1) {
+            state.window.pop_front();
+        }
+        state.labelled_total += 1;
+    }
+
+    pub fn snapshot(&self) -> LiveMetrics {
+        let state = self.state.lock().unwrap();
+        let samples = state.window.len();
+        let mean = |value: fn(&Outcome) -> f64| {
+            state.window.iter().map(value).sum::<f64>() / samples.max(1) as f64
+        };
+        LiveMetrics {
+            samples,
+            labelled_total: state.labelled_total,
+            accuracy: mean(|o| o.correct as u8 as f64),
+            mse: mean(|o| o.squared),
+            mae: mean(|o| o.absolute),
+            pending_predictions: state.predictions.len(),
+            pending_labels: state.labels.len(),
+            dropped_total: state.dropped_total,
+        }
+    }
+
+    /// Rolling metrics in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        let metrics = self.snapshot();
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: f64| {
+            let _ = writeln!(out, "# HELP benny_live_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE benny_live_{} {}", name, kind);
+            let _ = writeln!(out, "benny_live_{} {}", name, value);
+        };
+        metric(
+            "accuracy",
+            "gauge",
+            "Accuracy over the recent labelled predictions",
+            metrics.accuracy,
+        );
+        metric(
+            "mse",
+            "gauge",
+            "Mean squared error over the recent labelled predictions",
+            metrics.mse,
+        );
+        metric(
+            "mae",
+            "gauge",
+            "Mean absolute error over the recent labelled predictions",
+            metrics.mae,
+        );
+        metric(
+            "window_samples",
+            "gauge",
+            "Labelled predictions the rolling metrics cover",
+            metrics.samples as f64,
+        );
+        metric(
+            "labelled_total",
+            "counter",
+            "Predictions matched with a label",
+            metrics.labelled_total as f64,
+        );
+        metric(
+            "pending_predictions",
+            "gauge",
+            "Predictions waiting for their label",
+            metrics.pending_predictions as f64,
+        );
+        metric(
+            "pending_labels",
+            "gauge",
+            "Labels waiting for their prediction",
+            metrics.pending_labels as f64,
+        );
+        metric(
+            "dropped_total",
+            "counter",
+            "Predictions and labels dropped before their counterpart arrived",
+            metrics.dropped_total as f64,
+        );
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_evaluation() {
+        let evaluator = LiveEvaluator::new(LiveEvaluationConfig {
+            window: 3,
+            max_pending: 2,
+            ..LiveEvaluationConfig::default()
+        });
+        // Labels lag behind their predictions, then overtake them
+        evaluator.record_prediction(&[0.9]);
+        evaluator.record_prediction(&[0.2]);
+        assert_eq!(evaluator.snapshot().pending_predictions, 2);
+        evaluator.record_label(&[1.0]);
+        evaluator.record_label(&[1.0]);
+        evaluator.record_label(&[0.0]);
+        evaluator.record_prediction(&[0.4]);
+
+        let metrics = evaluator.snapshot();
+        assert_eq!(metrics.samples, 3);
+        assert_eq!(metrics.labelled_total, 3);
+        assert!((metrics.accuracy - 2.0 / 3.0).abs() < 1e-12);
+        assert!((metrics.mse - (0.01 + 0.64 + 0.16) / 3.0).abs() < 1e-12);
+        assert_eq!(
+            (metrics.pending_predictions, metrics.pending_labels),
+            (0, 0)
+        );
+
+        // The window keeps the latest outcomes, the pending queues the latest samples
+        evaluator.record_label(&[0.0]);
+        evaluator.record_prediction(&[0.0]);
+        evaluator.record_prediction(&[0.8]);
+        evaluator.record_label(&[1.0]);
+        assert_eq!(evaluator.snapshot().samples, 3);
+        assert_eq!(evaluator.snapshot().accuracy, 1.0);
+        for _ in 0..3 {
+            evaluator.record_label(&[1.0, 0.0]);
+        }
+        let metrics = evaluator.snapshot();
+        assert_eq!((metrics.pending_labels, metrics.dropped_total), (2, 1));
+
+        assert!(evaluator
+            .to_prometheus()
+            .contains("benny_live_labelled_total 5\n"));
+    }
+}
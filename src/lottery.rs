@@ -0,0 +1,348 @@
+use crate::evaluation::Evaluation;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Schedule of an iterative magnitude pruning run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotteryConfig {
+    /// Pruning rounds after the dense run
+    pub rounds: usize,
+    /// Share of the remaining weights pruned each round
+    pub prune_fraction: f64,
+    /// Training epochs of every round
+    pub epochs: usize,
+    /// Epoch whose weights every round restarts from; 0 rewinds to the initialization
+    pub rewind_epoch: usize,
+}
+
+impl Default for LotteryConfig {
+    fn default() -> Self {
+        Self {
+            rounds: 5,
+            prune_fraction: 0.2,
+            epochs: 100,
+            rewind_epoch: 0,
+        }
+    }
+}
+
+impl LotteryConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.prune_fraction > 0.0 && self.prune_fraction < 1.0) {
+            return Err(format!(
+                "Prune fraction {} must be between 0 and 1",
+                self.prune_fraction
+            ));
+        }
+        if self.rewind_epoch > self.epochs {
+            return Err(format!(
+                "Cannot rewind to epoch {} of {}-epoch rounds",
+                self.rewind_epoch, self.epochs
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Which weights of every layer are still connected
+///
+/// Biases, normalization and embedding parameters are never masked.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightMask {
+    /// `layers[layer]`, flattened like `NeuralNetwork::get_layer_weights`
+    pub layers: Vec<Vec<bool>>,
+}
+
+impl WeightMask {
+    /// Every weight of `network` connected
+    pub fn dense(network: &NeuralNetwork) -> Self {
+        Self {
+            layers: (0..network.num_layers() - 1)
+                .map(|layer| vec![true; network.get_layer_weights(layer).len()])
+                .collect(),
+        }
+    }
+
+    pub fn kept(&self) -> usize {
+        self.layers.iter().flatten().filter(|&&kept| kept).count()
+    }
+
+    /// Share of the weights still connected
+    pub fn density(&self) -> f64 {
+        let total: usize = self.layers.iter().map(Vec::len).sum();
+        self.kept() as f64 / total.max(1) as f64
+    }
+
+    /// Zero every disconnected weight of `network`
+    pub fn apply(&self, network: &mut NeuralNetwork) -> Result<(), String> {
+        for (layer, mask) in self.layers.iter().enumerate() {
+            let mut weights = network.get_layer_weights(layer);
+            if mask.iter().all(|&kept| kept) {
+                continue;
+            }
+            for (weight, &kept) in weights.iter_mut().zip(mask) {
+                if !kept {
+                    *weight = 0.0;
+                }
+            }
+            let biases = network.get_layer_biases(layer).to_vec();
+            network.set_layer_parameters(layer, &weights, &biases)?;
+        }
+        Ok(())
+    }
+
+    /// Disconnect the `fraction` of connected weights smallest in magnitude in `network`, across all layers
+    pub fn prune_smallest(&mut self, network: &NeuralNetwork, fraction: f64) {
+        let mut magnitudes: Vec<(f64, usize, usize)> = self
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer, mask)| {
+                network
+                    .get_layer_weights(layer)
+                    .into_iter()
+                    .zip(mask.clone())
+                    .enumerate()
+                    .filter(|(_, (_, kept))| *kept)
+                    .map(move |(index, (weight, _))| (weight.abs(), layer, index))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        magnitudes.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let count = (magnitudes.len() as f64 * fraction).round() as usize;
+        for &(_, layer, index) in magnitudes.iter().take(count) {
+            self.layers[layer][index] = false;
+        }
+    }
+}
+
+/// Validation quality of the network trained in one round
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotteryRound {
+    /// 0 for the dense run
+    pub round: usize,
+    pub weights: usize,
+    /// Share of the weights still connected
+    pub density: f64,
+    pub train_mse: f64,
+    pub validation_mse: f64,
+    pub validation_accuracy: f64,
+}
+
+/// Accuracy against sparsity over the rounds of a lottery ticket search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LotteryReport {
+    pub prune_fraction: f64,
+    pub rewind_epoch: usize,
+    pub rounds: Vec<LotteryRound>,
+}
+
+impl LotteryReport {
+    /// `round,weights,density,sparsity,train_mse,validation_mse,validation_accuracy` rows
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "round,weights,density,sparsity,train_mse,validation_mse,validation_accuracy\n",
+        );
+        for round in &self.rounds {
+            let _ = writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                round.round,
+                round.weights,
+                round.density,
+                1.0 - round.density,
+                round.train_mse,
+                round.validation_mse,
+                round.validation_accuracy
+            );
+        }
+        out
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "🎟️  Iterative magnitude pruning: {:.0}% of the remaining weights per round, rewinding to epoch {}\n",
+            self.prune_fraction * 100.0,
+            self.rewind_epoch
+        );
+        out.push_str("   round  weights  sparsity  train MSE  val MSE   val accuracy\n");
+        for round in &self.rounds {
+            let _ = writeln!(
+                out,
+                "   {:>5}  {:>7}  {:>7.1}%  {:>9.6}  {:>8.6}  {:>11.2}%",
+                round.round,
+                round.weights,
+                (1.0 - round.density) * 100.0,
+                round.train_mse,
+                round.validation_mse,
+                round.validation_accuracy * 100.0
+            );
+        }
+        out
+    }
+}
+
+fn mse(network: &NeuralNetwork, inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> f64 {
+    let outputs = network.forward_batch(inputs);
+    Evaluation::compute(
+        outputs
+            .iter()
+            .zip(targets)
+            .map(|(output, target)| (output.as_slice(), target.as_slice())),
+    )
+    .mse
+}
+
+/// Train `network` for `epochs` with `train`, zeroing the disconnected weights after every sample
+fn train_masked(
+    network: &mut NeuralNetwork,
+    mask: &WeightMask,
+    inputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+    epochs: usize,
+) -> Result<(), String> {
+    for _ in 0..epochs {
+        for (input, target) in inputs.iter().zip(targets) {
+            network.train(input, target);
+            mask.apply(network)?;
+        }
+    }
+    Ok(())
+}
+
+/// Search for a sparse subnetwork that trains as well as the dense one (lottery ticket hypothesis)
+///
+/// The dense network is trained first. Every following round disconnects
+/// the `prune_fraction` of the remaining weights that ended up smallest,
+/// rewinds the others to their values at `rewind_epoch` of the dense run
+/// (Frankle & Carbin; late rewinding for deeper networks) and trains again
+/// with the disconnected weights held at zero. Returns the network and mask
+/// of the last round with the validation results of every round; its
+/// disconnected weights are exact zeros.
+pub fn find_ticket(
+    network: &NeuralNetwork,
+    train: (&[Vec<f64>], &[Vec<f64>]),
+    validation: (&[Vec<f64>], &[Vec<f64>]),
+    config: &LotteryConfig,
+    mut on_round: impl FnMut(&LotteryRound),
+) -> Result<(NeuralNetwork, WeightMask, LotteryReport), String> {
+    config.validate()?;
+    let (train_inputs, train_targets) = train;
+    let (validation_inputs, validation_targets) = validation;
+    let mut mask = WeightMask::dense(network);
+
+    let mut rewind = network.clone();
+    train_masked(
+        &mut rewind,
+        &mask,
+        train_inputs,
+        train_targets,
+        config.rewind_epoch,
+    )?;
+    let mut trained = rewind.clone();
+    let mut rounds = Vec::new();
+    for round in 0..=config.rounds {
+        if round > 0 {
+            mask.prune_smallest(&trained, config.prune_fraction);
+            trained = rewind.clone();
+            mask.apply(&mut trained)?;
+            train_masked(
+                &mut trained,
+                &mask,
+                train_inputs,
+                train_targets,
+                config.epochs,
+            )?;
+        } else {
+            train_masked(
+                &mut trained,
+                &mask,
+                train_inputs,
+                train_targets,
+                config.epochs - config.rewind_epoch,
+            )?;
+        }
+
+        let outputs = trained.forward_batch(validation_inputs);
+        let evaluation = Evaluation::compute(
+            outputs
+                .iter()
+                .zip(validation_targets)
+                .map(|(output, target)| (output.as_slice(), target.as_slice())),
+        );
+        let result = LotteryRound {
+            round,
+            weights: mask.kept(),
+            density: mask.density(),
+            train_mse: mse(&trained, train_inputs, train_targets),
+            validation_mse: evaluation.mse,
+            validation_accuracy: evaluation.accuracy,
+        };
+        on_round(&result);
+        rounds.push(result);
+    }
+
+    let report = LotteryReport {
+        prune_fraction: config.prune_fraction,
+        rewind_epoch: config.rewind_epoch,
+        rounds,
+    };
+    Ok((trained, mask, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_ticket() {
+        let mut network = NeuralNetwork::with_layers(&[2, 8, 1], 0.0);
+        network.set_backprop_enabled(true, 0.5);
+        let inputs: Vec<Vec<f64>> = (0..16)
+            .map(|i| vec![(i % 4) as f64 / 3.0, (i / 4) as f64 / 3.0])
+            .collect();
+        let targets: Vec<Vec<f64>> = inputs
+            .iter()
+            .map(|x| vec![(x[0] > 0.5) as u8 as f64])
+            .collect();
+
+        let config = LotteryConfig {
+            rounds: 3,
+            prune_fraction: 0.5,
+            epochs: 20,
+            rewind_epoch: 2,
+        };
+        let mut seen = 0;
+        let (ticket, mask, report) = find_ticket(
+            &network,
+            (&inputs, &targets),
+            (&inputs, &targets),
+            &config,
+            |_| seen += 1,
+        )
+        .unwrap();
+        assert_eq!(seen, 4);
+        // 24 weights halved three times
+        let weights: Vec<usize> = report.rounds.iter().map(|r| r.weights).collect();
+        assert_eq!(weights, vec![24, 12, 6, 3]);
+        assert_eq!(mask.kept(), 3);
+        assert_eq!(mask.density(), 0.125);
+        let zeros = (0..2)
+            .flat_map(|layer| ticket.get_layer_weights(layer))
+            .filter(|&w| w == 0.0)
+            .count();
+        assert_eq!(zeros, 21);
+
+        assert!(report.to_csv().starts_with(
+            "round,weights,density,sparsity,train_mse,validation_mse,validation_accuracy\n0,24,1,0,"
+        ));
+        assert!(report.to_text().contains("87.5%"));
+        assert!(LotteryConfig {
+            prune_fraction: 1.0,
+            ..LotteryConfig::default()
+        }
+        .validate()
+        .is_err());
+    }
+}
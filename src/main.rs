@@ -4,47 +4,64 @@ use neural_network::runner::*;
 
 fn main() {
     let cli = Cli::parse();
+    neural_network::config_overrides::set_cli_overrides(cli.set);
 
     let result = match cli.command {
-        Commands::Train {
-            config,
-            data,
-            output,
-            epochs,
-            verbose,
-        } => run_training(config, data, output, epochs, verbose),
+        Commands::Train(args) => run_training(args),
         Commands::Predict {
             config,
             input,
             model,
             format,
-        } => run_prediction(config, input, model, format),
+            mc_samples,
+            ensemble,
+            ensemble_noise,
+        } => run_prediction(
+            config,
+            input,
+            model,
+            format,
+            mc_samples,
+            ensemble.map(|members| (members, ensemble_noise)),
+        ),
+        Commands::Evaluate {
+            model,
+            data,
+            config,
+            format,
+            output,
+        } => run_evaluation(model, data, config, format, output),
         Commands::InitConfig {
             output,
             network_type,
         } => create_sample_config(output, network_type),
-        Commands::Interactive { config } => run_interactive_mode(config),
-        Commands::Benchmark { config, iterations } => run_benchmark(config, iterations),
-        Commands::Server {
+        Commands::Config { command } => run_config(command),
+        Commands::Interactive { config, script } => run_interactive_mode(config, script),
+        Commands::Benchmark {
             config,
-            model,
-            port,
-            cert,
-            key,
-            outputs,
-            daemon,
-            hebbian_learning,
-        } => run_server(
-            config,
-            model,
-            port,
-            cert,
-            key,
-            outputs,
-            daemon,
-            hebbian_learning,
-        ),
+            iterations,
+            seed,
+            compare_modes,
+        } => run_benchmark(config, iterations, seed, compare_modes),
+        Commands::Server(args) => run_server(*args),
+        Commands::Soak(args) => run_soak(args),
+        Commands::Retrain(args) => run_retrain(args),
+        Commands::Lottery(args) => run_lottery(args),
+        Commands::Sweep(args) => run_sweep(args),
+        Commands::Difftest(args) => run_difftest(args),
         Commands::Demo { demo_type } => run_demo(demo_type),
+        Commands::Protocol { command } => run_protocol(command),
+        Commands::Checkpoint { command } => run_checkpoint(command),
+        Commands::Model { command } => run_model(command),
+        Commands::Analyze { command } => run_analyze(command),
+        Commands::Data { command } => run_data(command),
+        Commands::Token { command } => run_token(command),
+        Commands::Runs { command } => run_runs(command),
+        Commands::Top { nodes, interval_ms } => run_top(nodes, interval_ms),
+        Commands::Up { file } => run_up(file),
+        Commands::Plan { file, rate, output } => run_plan(file, rate, output),
+        Commands::Completions { shell } => run_completions(shell),
+        Commands::Examples { workflow } => run_examples(workflow),
     };
 
     if let Err(e) = result {
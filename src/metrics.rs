@@ -0,0 +1,332 @@
+use crate::address::{self, BindOptions};
+use crate::distributed_network::DistributedNetwork;
+use crate::health;
+use crate::live_evaluation::LiveEvaluator;
+use crate::mmap_model::MappedModel;
+use crate::remote_config::ConfigUpdate;
+use crate::resources;
+use crate::tokens::{self, Scope, TokenGuard};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::Arc;
+
+/// HTTP endpoint exposing node metrics
+///
+/// Routes:
+/// - `/metrics`: Prometheus text exposition, including layer timings when profiling is enabled
+///   and the memory, CPU and thread usage of the process
+/// - `/bandwidth`: per-peer bandwidth usage as JSON
+/// - `/evaluation`: rolling metrics against live labels as JSON, when
+///   evaluating (see `with_live_evaluation`); also included in `/metrics`
+/// - `/healthz`, `/readyz`: liveness and readiness probes (see `DistributedNetwork::health`)
+///
+/// With API tokens (see `with_tokens`) the metrics routes need the `metrics`
+/// scope and a small REST API is served as well; probes stay open:
+/// - `POST /predict` (`predict`): `{"inputs": [...]}` -> `{"outputs": [...]}`, plus
+///   `"decisions"` when the model carries tuned decision thresholds; with
+///   `"samples": n` the outputs are the mean of `n` passes with dropout on, and
+///   their `"variance"` is included
+/// - `POST /train` (`train`): `{"inputs": [...], "targets": [...]}` -> `{"error": ...}`
+/// - `POST /admin/config` (`admin`): a `ConfigUpdate` -> `{"previous": ...}`
+///
+/// A node serving a memory-mapped model (see `with_mapped_model`) predicts
+/// from the mapping and refuses `/train` and `"samples"`.
+pub struct MetricsServer {
+    network: DistributedNetwork,
+    tokens: Option<TokenGuard>,
+    evaluation: Option<LiveEvaluator>,
+    mapped: Option<Arc<MappedModel>>,
+}
+
+#[derive(Deserialize)]
+struct PredictRequest {
+    inputs: Vec<f64>,
+    /// Dropout passes to average, reporting their variance (MC dropout)
+    #[serde(default)]
+    samples: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct TrainRequest {
+    inputs: Vec<f64>,
+    targets: Vec<f64>,
+}
+
+impl MetricsServer {
+    pub fn new(network: DistributedNetwork) -> Self {
+        Self {
+            network,
+            tokens: None,
+            evaluation: None,
+            mapped: None,
+        }
+    }
+
+    /// Require API tokens from `guard` and serve the REST API
+    pub fn with_tokens(mut self, guard: TokenGuard) -> Self {
+        self.tokens = Some(guard);
+        self
+    }
+
+    /// Report the rolling metrics of `evaluator` alongside the node's own
+    pub fn with_live_evaluation(mut self, evaluator: LiveEvaluator) -> Self {
+        self.evaluation = Some(evaluator);
+        self
+    }
+
+    /// Predict from `model`, which the node serves in place of its own weights
+    pub fn with_mapped_model(mut self, model: Arc<MappedModel>) -> Self {
+        self.mapped = Some(model);
+        self
+    }
+
+    /// Bind `address:port` and serve metrics until the server fails
+    pub async fn serve(
+        self,
+        address: &str,
+        port: u16,
+        bind_options: BindOptions,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let listener = address::bind_std_tcp_listener(address, port, bind_options).await?;
+        let local_addr = listener.local_addr()?;
+        resources::start_process_monitor();
+        let (network, tokens, evaluation, mapped) =
+            (self.network, self.tokens, self.evaluation, self.mapped);
+
+        let make_svc = make_service_fn(move |_conn| {
+            let (network, tokens, evaluation, mapped) = (
+                network.clone(),
+                tokens.clone(),
+                evaluation.clone(),
+                mapped.clone(),
+            );
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let (network, tokens, evaluation, mapped) = (
+                        network.clone(),
+                        tokens.clone(),
+                        evaluation.clone(),
+                        mapped.clone(),
+                    );
+                    async move {
+                        Ok::<_, Infallible>(
+                            Self::handle_request(
+                                req,
+                                &network,
+                                tokens,
+                                evaluation.as_ref(),
+                                mapped.as_deref(),
+                            )
+                            .await,
+                        )
+                    }
+                }))
+            }
+        });
+
+        log::info!(
+            "📈 Metrics endpoint listening on http://{}/metrics",
+            local_addr
+        );
+        Server::from_tcp(listener)?.serve(make_svc).await?;
+        Ok(())
+    }
+
+    async fn handle_request(
+        req: Request<Body>,
+        network: &DistributedNetwork,
+        tokens: Option<TokenGuard>,
+        evaluation: Option<&LiveEvaluator>,
+        mapped: Option<&MappedModel>,
+    ) -> Response<Body> {
+        let scope = match req.uri().path() {
+            health::LIVENESS_PATH => return health::liveness_response(),
+            health::READINESS_PATH => return health::readiness_response(&network.health()),
+            "/metrics" | "/bandwidth" => Scope::Metrics,
+            "/evaluation" if evaluation.is_some() => Scope::Metrics,
+            "/predict" => Scope::Predict,
+            "/train" => Scope::Train,
+            "/admin/config" => Scope::Admin,
+            _ => return Self::plain(StatusCode::NOT_FOUND, "Not Found"),
+        };
+        match &tokens {
+            Some(guard) => {
+                if let Err(e) = guard.authorize(tokens::bearer_token(&req), scope) {
+                    return tokens::denied_response(&e);
+                }
+            }
+            // The REST API only exists behind tokens
+            None if scope != Scope::Metrics => {
+                return Self::plain(StatusCode::NOT_FOUND, "Not Found")
+            }
+            None => {}
+        }
+
+        let path = req.uri().path().to_string();
+        let reply = match path.as_str() {
+            "/metrics" => {
+                let mut body = Self::render_prometheus(network);
+                if let Some(evaluator) = evaluation {
+                    body.push_str(&evaluator.to_prometheus());
+                }
+                return Response::builder()
+                    .header("content-type", "text/plain; version=0.0.4")
+                    .body(Body::from(body))
+                    .unwrap();
+            }
+            "/bandwidth" => {
+                serde_json::to_value(network.bandwidth_stats()).map_err(|e| e.to_string())
+            }
+            "/evaluation" => serde_json::to_value(evaluation.map(LiveEvaluator::snapshot))
+                .map_err(|e| e.to_string()),
+            _ if req.method() != Method::POST => {
+                return Self::plain(StatusCode::METHOD_NOT_ALLOWED, "Use POST")
+            }
+            "/predict" => Self::read_json::<PredictRequest>(req)
+                .await
+                .and_then(|request| {
+                    let model = network.network.lock().unwrap();
+                    Self::check_width("inputs", &request.inputs, model.get_layers()[0])?;
+                    let (outputs, variance) = match request.samples {
+                        Some(_) if mapped.is_some() => {
+                            return Err("\"samples\" needs a model loaded into memory".to_string())
+                        }
+                        Some(_) if !model.has_dropout() => {
+                            return Err("\"samples\" needs a model with dropout".to_string())
+                        }
+                        Some(samples) => {
+                            let (mean, variance) =
+                                model.predict_with_uncertainty(&request.inputs, samples);
+                            (mean, Some(variance))
+                        }
+                        None => match mapped {
+                            Some(mapped) => (mapped.forward(&request.inputs), None),
+                            // Predict-scoped callers must not adapt an online-learning model
+                            None => (model.forward_static(&request.inputs).0, None),
+                        },
+                    };
+                    let mut reply = serde_json::json!({ "outputs": outputs });
+                    if let Some(decisions) = model.metadata().decisions(&outputs) {
+                        reply["decisions"] = serde_json::json!(decisions);
+                    }
+                    if let Some(variance) = variance {
+                        reply["variance"] = serde_json::json!(variance);
+                    }
+                    Ok(reply)
+                }),
+            "/train" if mapped.is_some() => {
+                Err("The served model is memory-mapped and read-only".to_string())
+            }
+            "/train" => Self::read_json::<TrainRequest>(req)
+                .await
+                .and_then(|request| {
+                    let mut model = network.network.lock().unwrap();
+                    let outputs = *model.get_layers().last().unwrap();
+                    Self::check_width("inputs", &request.inputs, model.get_layers()[0])?;
+                    Self::check_width("targets", &request.targets, outputs)?;
+                    let error = model.train(&request.inputs, &request.targets);
+                    Ok(serde_json::json!({ "error": error }))
+                }),
+            _ => Self::read_json::<ConfigUpdate>(req)
+                .await
+                .and_then(|update| {
+                    update.validate()?;
+                    let previous = update.apply(&mut network.network.lock().unwrap());
+                    Ok(serde_json::json!({ "previous": previous }))
+                }),
+        };
+        match reply {
+            Ok(body) => Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap(),
+            Err(e) => Self::plain(StatusCode::BAD_REQUEST, &e),
+        }
+    }
+
+    async fn read_json<T: serde::de::DeserializeOwned>(req: Request<Body>) -> Result<T, String> {
+        let body = hyper::body::to_bytes(req.into_body())
+            .await
+            .map_err(|e| e.to_string())?;
+        serde_json::from_slice(&body).map_err(|e| e.to_string())
+    }
+
+    fn check_width(name: &str, values: &[f64], expected: usize) -> Result<(), String> {
+        if values.len() == expected {
+            return Ok(());
+        }
+        Err(format!(
+            "Expected {} {}, got {}",
+            expected,
+            name,
+            values.len()
+        ))
+    }
+
+    fn plain(status: StatusCode, text: &str) -> Response<Body> {
+        Response::builder()
+            .status(status)
+            .body(Body::from(text.to_string()))
+            .unwrap()
+    }
+
+    /// Render all node metrics in the Prometheus text format
+    pub fn render_prometheus(network: &DistributedNetwork) -> String {
+        let mut out = String::new();
+        let peers = network.connections.lock().unwrap().len();
+
+        out.push_str("# HELP benny_connected_peers Peers known to this node\n");
+        out.push_str("# TYPE benny_connected_peers gauge\n");
+        let _ = writeln!(out, "benny_connected_peers {}", peers);
+
+        out.push_str(&network.bandwidth.to_prometheus());
+        if let Some(report) = network.network.lock().unwrap().timing_report() {
+            out.push_str(&report.to_prometheus());
+        }
+        out.push_str(&resources::process_usage().to_prometheus());
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_network::{HebbianLearningMode, NeuralNetwork};
+    use crate::tokens::TokenStore;
+
+    #[tokio::test]
+    async fn test_predict_leaves_online_learning_weights_unchanged() {
+        let model =
+            NeuralNetwork::with_online_learning(&[2, 3, 1], 0.5, HebbianLearningMode::Classic);
+        let (network, _) = DistributedNetwork::new(
+            "metrics-test".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            model,
+        );
+        let weights = network.network.lock().unwrap().get_layer_weights(0);
+
+        let mut store = TokenStore::default();
+        let (_, secret) = store.issue("client", &[Scope::Predict]).unwrap();
+        let path = std::env::temp_dir()
+            .join(format!("benny-metrics-tokens-{}.json", std::process::id()));
+        store.save(&path).unwrap();
+        let guard = TokenGuard::open(&path).unwrap();
+
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/predict")
+            .header(hyper::header::AUTHORIZATION, format!("Bearer {}", secret))
+            .body(Body::from(r#"{"inputs": [1.0, 0.5]}"#))
+            .unwrap();
+        let response =
+            MetricsServer::handle_request(request, &network, Some(guard), None, None).await;
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(network.network.lock().unwrap().get_layer_weights(0), weights);
+    }
+}
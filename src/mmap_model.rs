@@ -0,0 +1,338 @@
+use crate::atomic_file::{self, TruncatedFile};
+use crate::neural_network::NeuralNetwork;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// Magic bytes opening a memory-mappable model file (last byte is the version)
+const MAGIC: &[u8; 8] = b"BNYMMAP\x01";
+
+/// Write `skeleton` (a network without weights) followed by the raw parameters
+///
+/// Layout: magic, header length (u64 LE), bincode header, zero padding to an
+/// 8-byte boundary, then for every layer its weights in `[from][to]` order and
+/// its biases, as little-endian f64.
+pub(crate) fn write<P: AsRef<Path>>(
+    skeleton: &NeuralNetwork,
+    weights: &[Vec<Vec<f64>>],
+    biases: &[Vec<f64>],
+    path: P,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let header = bincode::serialize(skeleton)?;
+    // Renaming over a mapped file is safe: open mappings keep the old contents
+    atomic_file::write_atomic_with(path, |out| {
+        out.write_all(MAGIC)?;
+        out.write_all(&(header.len() as u64).to_le_bytes())?;
+        out.write_all(&header)?;
+        out.write_all(&vec![0u8; padding(header.len())])?;
+        for (layer_weights, layer_biases) in weights.iter().zip(biases) {
+            for value in layer_weights.iter().flatten().chain(layer_biases) {
+                out.write_all(&value.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Zero bytes after the header so the parameters start 8-byte aligned
+fn padding(header_len: usize) -> usize {
+    (8 - (MAGIC.len() + 8 + header_len) % 8) % 8
+}
+
+/// A model served straight from a memory-mapped file
+///
+/// Opening only parses the small header; weights stay in the page cache and
+/// are paged in lazily by the first forward passes that touch them. `lock`
+/// pins them in RAM for latency-sensitive serving. Files are written with
+/// `NeuralNetwork::save_to_mmap`.
+pub struct MappedModel {
+    network: NeuralNetwork,      // header: every setting except weights and biases
+    region: Region,              // the whole file
+    layers: Vec<(usize, usize)>, // (first value, value count) of each layer, in f64 units
+}
+
+impl MappedModel {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        if cfg!(target_endian = "big") {
+            return Err("Memory-mapped models require a little-endian host".into());
+        }
+        let path = path.as_ref();
+        let region = Region::map(&fs::File::open(path)?)?;
+        let bytes = region.bytes();
+        if bytes.len() < MAGIC.len() + 8 || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("Not a memory-mapped model file".into());
+        }
+        let mut len = [0u8; 8];
+        len.copy_from_slice(&bytes[MAGIC.len()..MAGIC.len() + 8]);
+        let header_len = u64::from_le_bytes(len) as usize;
+        let header_start = MAGIC.len() + 8;
+        let header_end = header_start
+            .checked_add(header_len)
+            .filter(|&end| end <= bytes.len())
+            .ok_or_else(|| TruncatedFile::new(path))?;
+        let network: NeuralNetwork = bincode::deserialize(&bytes[header_start..header_end])?;
+
+        let mut offset = (header_end + padding(header_len)) / 8;
+        let mut layers = Vec::new();
+        for pair in network.get_layers().windows(2) {
+            let count = pair[0] * pair[1] + pair[1];
+            layers.push((offset, count));
+            offset += count;
+        }
+        if offset * 8 > bytes.len() {
+            return Err(TruncatedFile::new(path).into());
+        }
+
+        Ok(Self {
+            network,
+            region,
+            layers,
+        })
+    }
+
+    /// Weights (`[from][to]`, flattened) and biases of weight layer `layer`
+    fn layer(&self, layer: usize) -> (&[f64], &[f64]) {
+        let (start, count) = self.layers[layer];
+        let values = &self.region.values()[start..start + count];
+        values.split_at(count - self.network.get_layers()[layer + 1])
+    }
+
+    /// Output of the network for `inputs`, reading weights from the mapping
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        let mut activations = vec![self.network.embed_inputs(inputs)];
+        for layer in 0..self.layers.len() {
+            let (weights, biases) = self.layer(layer);
+            let mut sums = biases.to_vec();
+            for (x, row) in activations[layer].iter().zip(weights.chunks(biases.len())) {
+                for (sum, w) in sums.iter_mut().zip(row) {
+                    *sum += x * w;
+                }
+            }
+            let next = self.network.activate(layer, sums, &activations);
+            activations.push(next);
+        }
+        activations.pop().unwrap_or_default()
+    }
+
+    /// Layer sizes of the model
+    pub fn get_layers(&self) -> &[usize] {
+        self.network.get_layers()
+    }
+
+    /// The model's settings without its weights and biases
+    ///
+    /// For code that needs the architecture or metadata of a model served
+    /// from the mapping; running it panics.
+    pub fn skeleton(&self) -> NeuralNetwork {
+        self.network.clone()
+    }
+
+    /// Copy the weights out of the mapping into a regular, trainable network
+    pub fn to_network(&self) -> NeuralNetwork {
+        let sizes = self.network.get_layers();
+        let mut weights = Vec::with_capacity(self.layers.len());
+        let mut biases = Vec::with_capacity(self.layers.len());
+        for layer in 0..self.layers.len() {
+            let (layer_weights, layer_biases) = self.layer(layer);
+            weights.push(
+                layer_weights
+                    .chunks(sizes[layer + 1])
+                    .map(<[f64]>::to_vec)
+                    .collect(),
+            );
+            biases.push(layer_biases.to_vec());
+        }
+        self.network.clone().with_parameters(weights, biases)
+    }
+
+    /// Pin the model's pages in RAM so forward passes never wait on page faults
+    ///
+    /// Fails when the process may not lock that much memory (`RLIMIT_MEMLOCK`).
+    pub fn lock(&self) -> io::Result<()> {
+        self.region.lock()
+    }
+}
+
+/// Read-only view of a file: mapped on Unix, read into memory elsewhere
+struct Region {
+    #[cfg(unix)]
+    ptr: *mut libc::c_void,
+    #[cfg(unix)]
+    len: usize,
+    #[cfg(not(unix))]
+    data: Vec<u64>, // u64 storage keeps the bytes 8-byte aligned
+}
+
+// The mapping is private and never written, so sharing it across threads is sound
+unsafe impl Send for Region {}
+unsafe impl Sync for Region {}
+
+impl Region {
+    #[cfg(unix)]
+    fn map(file: &fs::File) -> io::Result<Self> {
+        use std::os::unix::io::AsRawFd;
+
+        let len = file.metadata()?.len() as usize;
+        if len == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Empty model file",
+            ));
+        }
+        // SAFETY: a fresh read-only private mapping of a file we hold open
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                len,
+                libc::PROT_READ,
+                libc::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { ptr, len })
+    }
+
+    #[cfg(not(unix))]
+    fn map(file: &fs::File) -> io::Result<Self> {
+        use std::io::Read;
+
+        let mut bytes = Vec::new();
+        (&*file).read_to_end(&mut bytes)?;
+        let mut data = vec![0u64; bytes.len().div_ceil(8)];
+        for (word, chunk) in data.iter_mut().zip(bytes.chunks(8)) {
+            let mut buf = [0u8; 8];
+            buf[..chunk.len()].copy_from_slice(chunk);
+            *word = u64::from_ne_bytes(buf);
+        }
+        Ok(Self { data })
+    }
+
+    fn bytes(&self) -> &[u8] {
+        #[cfg(unix)]
+        // SAFETY: the mapping covers `len` readable bytes until `drop`
+        unsafe {
+            std::slice::from_raw_parts(self.ptr as *const u8, self.len)
+        }
+        #[cfg(not(unix))]
+        // SAFETY: reinterpreting initialized u64 storage as bytes
+        unsafe {
+            std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.data.len() * 8)
+        }
+    }
+
+    /// The region as f64 values; trailing bytes that do not fill a value are ignored
+    fn values(&self) -> &[f64] {
+        let bytes = self.bytes();
+        // SAFETY: the base is page (or u64) aligned and any bit pattern is a valid f64
+        unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const f64, bytes.len() / 8) }
+    }
+
+    #[cfg(unix)]
+    fn lock(&self) -> io::Result<()> {
+        // SAFETY: locking pages of a live mapping
+        if unsafe { libc::mlock(self.ptr, self.len) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    fn lock(&self) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Page locking is only available on Unix",
+        ))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Region {
+    fn drop(&mut self) {
+        // SAFETY: unmapping the region created in `map`, which is not used afterwards
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// Lock all current and future memory of the process into RAM
+///
+/// Used when serving a model loaded into regular memory, so its weights
+/// cannot be swapped out; mapped models lock just their pages with
+/// `MappedModel::lock`.
+pub fn lock_process_memory() -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        // SAFETY: mlockall has no memory-safety preconditions
+        if unsafe { libc::mlockall(libc::MCL_CURRENT | libc::MCL_FUTURE) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Page locking is only available on Unix",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::Activation;
+
+    #[test]
+    fn test_mapped_forward_matches_network() {
+        let path = std::env::temp_dir().join(format!("benny-model-{}.mmap", uuid::Uuid::new_v4()));
+        let mut nn = NeuralNetwork::with_layers(&[3, 5, 4, 2], 0.1);
+        nn.set_layer_norm(0, true);
+        nn.set_activation(1, Activation::Tanh);
+        nn.add_embedding(1, 3, 2).unwrap();
+        nn.add_skip_connection(0, 2).unwrap();
+        nn.save_to_mmap(&path).unwrap();
+
+        let model = MappedModel::open(&path).unwrap();
+        assert_eq!(model.get_layers(), nn.get_layers());
+        for inputs in [[0.2, 1.0, -0.4], [0.9, 2.0, 0.1]] {
+            assert_eq!(model.forward(&inputs), nn.forward_static(&inputs).0);
+        }
+
+        let restored = model.to_network();
+        for layer in 0..3 {
+            assert_eq!(
+                restored.get_layer_weights(layer),
+                nn.get_layer_weights(layer)
+            );
+            assert_eq!(restored.get_layer_biases(layer), nn.get_layer_biases(layer));
+        }
+        drop(model);
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_rejects_other_formats() {
+        let path = std::env::temp_dir().join(format!("benny-model-{}.bin", uuid::Uuid::new_v4()));
+        let nn = NeuralNetwork::with_layers(&[2, 2], 0.1);
+        nn.save_to_binary(&path).unwrap();
+        assert!(MappedModel::open(&path).is_err());
+
+        nn.save_to_mmap(&path).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        fs::write(&path, &bytes[..bytes.len() - 8]).unwrap();
+        let error = MappedModel::open(&path).err().unwrap();
+        assert!(error.is::<TruncatedFile>());
+
+        // A header length running past the end of the address space
+        let mut bytes = bytes;
+        bytes[MAGIC.len()..MAGIC.len() + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let error = MappedModel::open(&path).err().unwrap();
+        assert!(error.is::<TruncatedFile>());
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,229 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+use std::time::Duration;
+
+/// Provenance saved inside a model file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelMetadata {
+    /// Data the model was trained on
+    pub dataset: Option<String>,
+    /// Commit of the working directory the training ran in
+    pub git_hash: Option<String>,
+    /// RFC 3339 time the training finished
+    pub created_at: Option<String>,
+    pub training_seconds: Option<f64>,
+    /// Final errors and accuracies by name
+    pub metrics: BTreeMap<String, f64>,
+    /// Free-form `key=value` labels
+    pub tags: BTreeMap<String, String>,
+    /// Peer weights merged into each layer, oldest first
+    #[serde(default)]
+    pub provenance: BTreeMap<usize, Vec<LayerContribution>>,
+    /// Decision threshold of each output, tuned on validation data
+    #[serde(default)]
+    pub thresholds: Vec<f64>,
+}
+
+/// Contributions kept per layer; older ones are dropped
+pub const MAX_CONTRIBUTIONS_PER_LAYER: usize = 32;
+
+/// Weights of one peer merged into a layer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerContribution {
+    pub peer: String,
+    /// RFC 3339 time of the merge
+    pub merged_at: String,
+    /// Weight of the peer's parameters in the merge; 1 replaces the layer
+    pub blend: f64,
+}
+
+impl ModelMetadata {
+    /// Metadata of a model trained on `dataset` from the current directory
+    pub fn for_dataset(dataset: &Path) -> Self {
+        Self {
+            dataset: Some(dataset.display().to_string()),
+            git_hash: current_git_hash(),
+            ..Self::default()
+        }
+    }
+
+    /// Stamp the end of a training run that took `duration`
+    pub fn finish_training(&mut self, duration: Duration) {
+        self.created_at = Some(chrono::Utc::now().to_rfc3339());
+        self.training_seconds = Some(duration.as_secs_f64());
+    }
+
+    /// Note that `peer`'s weights were merged into `layer` with `blend`
+    ///
+    /// A blend of 1 replaces the layer, so earlier contributions are forgotten.
+    pub fn record_contribution(&mut self, layer: usize, peer: &str, blend: f64) {
+        let contributions = self.provenance.entry(layer).or_default();
+        if blend >= 1.0 {
+            contributions.clear();
+        }
+        contributions.push(LayerContribution {
+            peer: peer.to_string(),
+            merged_at: chrono::Utc::now().to_rfc3339(),
+            blend,
+        });
+        if contributions.len() > MAX_CONTRIBUTIONS_PER_LAYER {
+            contributions.remove(0);
+        }
+    }
+
+    /// Share of `layer`'s current parameters that came from each peer
+    ///
+    /// Every merge scales down what was there before by `1 - blend`; whatever
+    /// the shares leave to 1 is the node's own (or forgotten) contribution.
+    pub fn peer_shares(&self, layer: usize) -> BTreeMap<String, f64> {
+        let mut shares = BTreeMap::new();
+        let mut remaining = 1.0;
+        for contribution in self.provenance.get(&layer).into_iter().flatten().rev() {
+            *shares.entry(contribution.peer.clone()).or_insert(0.0) +=
+                remaining * contribution.blend;
+            remaining *= 1.0 - contribution.blend;
+        }
+        shares
+    }
+
+    /// Threshold above which `output` counts as positive: the tuned one, or 0.5
+    pub fn threshold(&self, output: usize) -> f64 {
+        self.thresholds
+            .get(output)
+            .copied()
+            .unwrap_or(crate::thresholds::DEFAULT_THRESHOLD)
+    }
+
+    /// Whether each of `outputs` clears its threshold, when thresholds were tuned
+    pub fn decisions(&self, outputs: &[f64]) -> Option<Vec<bool>> {
+        if self.thresholds.is_empty() {
+            return None;
+        }
+        Some(
+            outputs
+                .iter()
+                .enumerate()
+                .map(|(output, &value)| value >= self.threshold(output))
+                .collect(),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn record(&mut self, metric: &str, value: f64) {
+        self.metrics.insert(metric.to_string(), value);
+    }
+
+    /// Add a tag written as `key=value`
+    pub fn add_tag(&mut self, tag: &str) -> Result<(), String> {
+        match tag.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                self.tags
+                    .insert(key.trim().to_string(), value.trim().to_string());
+                Ok(())
+            }
+            _ => Err(format!("Tag '{}' is not of the form key=value", tag)),
+        }
+    }
+
+    /// One `name: value` line per field that is set
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        if let Some(dataset) = &self.dataset {
+            lines.push(format!("Dataset: {}", dataset));
+        }
+        if let Some(hash) = &self.git_hash {
+            lines.push(format!("Git commit: {}", hash));
+        }
+        if let Some(created_at) = &self.created_at {
+            lines.push(format!("Created: {}", created_at));
+        }
+        if let Some(seconds) = self.training_seconds {
+            lines.push(format!("Training time: {:.2}s", seconds));
+        }
+        for (metric, value) in &self.metrics {
+            lines.push(format!("Metric {}: {:.6}", metric, value));
+        }
+        for (key, value) in &self.tags {
+            lines.push(format!("Tag {}: {}", key, value));
+        }
+        if !self.thresholds.is_empty() {
+            let thresholds: Vec<String> = self
+                .thresholds
+                .iter()
+                .map(|t| format!("{:.3}", t))
+                .collect();
+            lines.push(format!("Decision thresholds: {}", thresholds.join(", ")));
+        }
+        for &layer in self.provenance.keys() {
+            let shares: Vec<String> = self
+                .peer_shares(layer)
+                .iter()
+                .map(|(peer, share)| format!("{} {:.1}%", peer, share * 100.0))
+                .collect();
+            lines.push(format!(
+                "Layer {} merged from: {}",
+                layer,
+                shares.join(", ")
+            ));
+        }
+        lines
+    }
+}
+
+/// `HEAD` of the git repository around the current directory, if any
+fn current_git_hash() -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let hash = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!hash.is_empty()).then_some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_network::NeuralNetwork;
+
+    #[test]
+    fn test_metadata_and_provenance_survive_json_and_binary() {
+        let mut metadata = ModelMetadata::for_dataset(Path::new("xor.csv"));
+        metadata.finish_training(Duration::from_secs(3));
+        metadata.record("val_accuracy", 0.75);
+        metadata.add_tag("owner = lab").unwrap();
+        assert!(metadata.add_tag("no-value").is_err());
+        assert!(metadata.lines().contains(&"Tag owner: lab".to_string()));
+        metadata.record_contribution(0, "a", 0.5);
+        metadata.record_contribution(0, "b", 0.5);
+        assert_eq!(metadata.peer_shares(0)["a"], 0.25);
+        assert_eq!(metadata.peer_shares(0)["b"], 0.5);
+        metadata.record_contribution(0, "c", 1.0);
+        assert_eq!(metadata.provenance[&0].len(), 1);
+        assert!(metadata
+            .lines()
+            .contains(&"Layer 0 merged from: c 100.0%".to_string()));
+        assert_eq!(metadata.decisions(&[0.4]), None);
+        metadata.thresholds = vec![0.3];
+        assert_eq!(metadata.decisions(&[0.4, 0.4]), Some(vec![true, false]));
+
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        assert!(network.metadata().is_empty());
+        network.set_metadata(metadata.clone());
+
+        let dir = std::env::temp_dir().join(format!("benny-metadata-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        network.save_to_file(dir.join("model.json")).unwrap();
+        network.save_to_binary(dir.join("model.bin")).unwrap();
+        let json = NeuralNetwork::load_from_file(dir.join("model.json")).unwrap();
+        let binary = NeuralNetwork::load_from_binary(dir.join("model.bin")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(json.metadata(), &metadata);
+        assert_eq!(binary.metadata(), &metadata);
+    }
+}
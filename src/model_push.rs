@@ -0,0 +1,246 @@
+use crate::distributed_network::NetworkId;
+use crate::neural_network::NeuralNetwork;
+use bincode::Options;
+use crc32fast::Hasher;
+use std::collections::HashMap;
+
+/// Largest model slice carried by one `ModelPush` message
+pub const MODEL_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest serialized model a `ModelAssembler` accepts unless configured otherwise
+pub const DEFAULT_MAX_MODEL_SIZE: usize = 256 * 1024 * 1024;
+
+/// One slice of a model pushed to an inference node
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelChunk {
+    /// Identifies the push this chunk belongs to
+    pub transfer_id: u64,
+    /// Version the model becomes active as
+    pub version: u64,
+    pub index: u32,
+    pub count: u32,
+    /// CRC32 of the complete serialized model
+    pub checksum: u32,
+    pub data: Vec<u8>,
+}
+
+/// Serialize `network` and cut it into `ModelPush` chunks
+pub fn split_model(
+    network: &NeuralNetwork,
+    transfer_id: u64,
+    version: u64,
+) -> Result<Vec<ModelChunk>, String> {
+    let bytes =
+        bincode::serialize(network).map_err(|e| format!("Failed to serialize model: {}", e))?;
+    let mut hasher = Hasher::new();
+    hasher.update(&bytes);
+    let checksum = hasher.finalize();
+
+    let count = bytes.len().div_ceil(MODEL_CHUNK_SIZE) as u32;
+    Ok(bytes
+        .chunks(MODEL_CHUNK_SIZE)
+        .enumerate()
+        .map(|(index, data)| ModelChunk {
+            transfer_id,
+            version,
+            index: index as u32,
+            count,
+            checksum,
+            data: data.to_vec(),
+        })
+        .collect())
+}
+
+#[derive(Debug)]
+struct Transfer {
+    transfer_id: u64,
+    version: u64,
+    checksum: u32,
+    chunks: Vec<Option<Vec<u8>>>,
+    received: usize,
+}
+
+/// Reassembles pushed models, one transfer in progress per peer
+///
+/// A new transfer from a peer abandons its unfinished one, and transfers
+/// announcing more than the maximum model size are refused before anything is
+/// allocated, so the memory held is bounded by the number of peers pushing at once.
+#[derive(Debug)]
+pub struct ModelAssembler {
+    transfers: HashMap<NetworkId, Transfer>,
+    max_size: usize,
+}
+
+impl Default for ModelAssembler {
+    fn default() -> Self {
+        Self::with_max_size(DEFAULT_MAX_MODEL_SIZE)
+    }
+}
+
+impl ModelAssembler {
+    /// Assembler refusing models larger than `max_size` serialized bytes
+    pub fn with_max_size(max_size: usize) -> Self {
+        Self {
+            transfers: HashMap::new(),
+            max_size,
+        }
+    }
+
+    /// Store a chunk, returning the model version and network once the last one arrives
+    ///
+    /// Errors abandon the transfer: inconsistent chunk headers, a model larger
+    /// than the maximum size, a checksum mismatch or bytes that do not decode
+    /// as a network.
+    pub fn receive(
+        &mut self,
+        peer: NetworkId,
+        chunk: ModelChunk,
+    ) -> Option<Result<(u64, NeuralNetwork), String>> {
+        if chunk.count == 0 || chunk.index >= chunk.count || chunk.data.len() > MODEL_CHUNK_SIZE {
+            self.transfers.remove(&peer);
+            return Some(Err(format!(
+                "Invalid model chunk {} of {} ({} bytes)",
+                chunk.index,
+                chunk.count,
+                chunk.data.len()
+            )));
+        }
+        if chunk.count as usize > self.max_size.div_ceil(MODEL_CHUNK_SIZE) {
+            self.transfers.remove(&peer);
+            return Some(Err(format!(
+                "Model of {} chunks exceeds the {} byte limit",
+                chunk.count, self.max_size
+            )));
+        }
+
+        if self
+            .transfers
+            .get(&peer)
+            .is_none_or(|transfer| transfer.transfer_id != chunk.transfer_id)
+        {
+            self.transfers.insert(
+                peer,
+                Transfer {
+                    transfer_id: chunk.transfer_id,
+                    version: chunk.version,
+                    checksum: chunk.checksum,
+                    chunks: vec![None; chunk.count as usize],
+                    received: 0,
+                },
+            );
+        }
+        let transfer = self.transfers.get_mut(&peer)?;
+
+        if transfer.version != chunk.version
+            || transfer.checksum != chunk.checksum
+            || transfer.chunks.len() != chunk.count as usize
+        {
+            self.transfers.remove(&peer);
+            return Some(Err(format!(
+                "Model chunk {} does not match transfer {}",
+                chunk.index, chunk.transfer_id
+            )));
+        }
+
+        let slot = &mut transfer.chunks[chunk.index as usize];
+        if slot.is_none() {
+            transfer.received += 1;
+        }
+        *slot = Some(chunk.data);
+        if transfer.received < transfer.chunks.len() {
+            return None;
+        }
+
+        let transfer = self.transfers.remove(&peer)?;
+        let bytes: Vec<u8> = transfer.chunks.into_iter().flatten().flatten().collect();
+        let mut hasher = Hasher::new();
+        hasher.update(&bytes);
+        if hasher.finalize() != transfer.checksum {
+            return Some(Err(format!(
+                "Model checksum mismatch for transfer {}",
+                transfer.transfer_id
+            )));
+        }
+        // bincode only enforces the limit when reading, not when decoding a slice
+        Some(
+            bincode::DefaultOptions::new()
+                .with_fixint_encoding()
+                .allow_trailing_bytes()
+                .with_limit(self.max_size as u64)
+                .deserialize_from(bytes.as_slice())
+                .map(|network| (transfer.version, network))
+                .map_err(|e| format!("Failed to decode pushed model: {}", e)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_model_chunks_reassemble() {
+        let network = NeuralNetwork::with_layers(&[64, 300, 10], 0.1);
+        let chunks = split_model(&network, 7, 3).unwrap();
+        assert!(chunks.len() > 1);
+
+        // Out of order, with a duplicate
+        let peer = NetworkId::new_v4();
+        let mut assembler = ModelAssembler::default();
+        let mut result = None;
+        for chunk in std::iter::once(&chunks[1]).chain(chunks.iter().rev()) {
+            if let Some(done) = assembler.receive(peer, chunk.clone()) {
+                result = Some(done);
+            }
+        }
+        let (version, received) = result.unwrap().unwrap();
+        assert_eq!(version, 3);
+        assert_eq!(received.get_layers(), network.get_layers());
+        let input = vec![0.5; 64];
+        assert_eq!(
+            received.forward_static(&input).0,
+            network.forward_static(&input).0
+        );
+
+        // A corrupted chunk fails the model checksum
+        let mut corrupted = chunks.clone();
+        corrupted[0].data[0] ^= 0xFF;
+        let outcome = corrupted
+            .into_iter()
+            .filter_map(|chunk| assembler.receive(peer, chunk))
+            .next()
+            .unwrap();
+        assert!(outcome.unwrap_err().contains("checksum"));
+    }
+
+    #[test]
+    fn test_oversized_models_are_refused() {
+        let peer = NetworkId::new_v4();
+        let mut assembler = ModelAssembler::default();
+        let huge = ModelChunk {
+            transfer_id: 1,
+            version: 1,
+            index: 0,
+            count: u32::MAX,
+            checksum: 0,
+            data: vec![0; 16],
+        };
+        assert!(assembler
+            .receive(peer, huge)
+            .unwrap()
+            .unwrap_err()
+            .contains("limit"));
+
+        // Within the chunk limit, but the model decodes to more than the limit allows
+        let network = NeuralNetwork::with_layers(&[64, 300, 10], 0.1);
+        let chunks = split_model(&network, 2, 1).unwrap();
+        let mut assembler =
+            ModelAssembler::with_max_size((chunks.len() - 1) * MODEL_CHUNK_SIZE + 1);
+        let outcome = chunks
+            .into_iter()
+            .filter_map(|chunk| assembler.receive(peer, chunk))
+            .next()
+            .unwrap();
+        assert!(outcome.unwrap_err().contains("decode"));
+    }
+}
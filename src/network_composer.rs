@@ -1,3 +1,5 @@
+use crate::echo_state::EchoStateNetwork;
+use crate::input_adapter::InputAdapter;
 use crate::neural_network::NeuralNetwork;
 use std::collections::HashMap;
 
@@ -8,14 +10,22 @@ pub struct NetworkConnection {
     pub target_network: String,
     pub source_outputs: Vec<usize>, // Which outputs from source
     pub target_inputs: Vec<usize>,  // Which inputs to target
+    /// Maps the selected source outputs onto the target inputs when their counts differ
+    pub adapter: InputAdapter,
 }
 
 /// A composer that manages multiple neural networks and their connections
+///
+/// Echo state network reservoirs take part like any other network; they keep
+/// their state between `forward` calls, so a composition can feed them a time
+/// series step by step.
 #[derive(Debug)]
 pub struct NetworkComposer {
     networks: HashMap<String, NeuralNetwork>,
+    reservoirs: HashMap<String, EchoStateNetwork>,
     connections: Vec<NetworkConnection>,
     execution_order: Vec<String>,
+    input_adapters: HashMap<String, InputAdapter>, // For external inputs of another size
 }
 
 impl NetworkComposer {
@@ -23,14 +33,16 @@ impl NetworkComposer {
     pub fn new() -> Self {
         Self {
             networks: HashMap::new(),
+            reservoirs: HashMap::new(),
             connections: Vec::new(),
             execution_order: Vec::new(),
+            input_adapters: HashMap::new(),
         }
     }
 
     /// Add a neural network to the composer
     pub fn add_network(&mut self, name: String, network: NeuralNetwork) -> Result<(), String> {
-        if self.networks.contains_key(&name) {
+        if self.contains(&name) {
             return Err(format!("Network '{}' already exists", name));
         }
 
@@ -39,11 +51,23 @@ impl NetworkComposer {
         Ok(())
     }
 
+    /// Add an echo state network to the composer
+    pub fn add_reservoir(&mut self, name: String, reservoir: EchoStateNetwork) -> Result<(), String> {
+        if self.contains(&name) {
+            return Err(format!("Network '{}' already exists", name));
+        }
+
+        self.reservoirs.insert(name.clone(), reservoir);
+        self.update_execution_order();
+        Ok(())
+    }
+
     /// Remove a neural network from the composer
     pub fn remove_network(&mut self, name: &str) -> Result<NeuralNetwork, String> {
         // Remove all connections involving this network
         self.connections
             .retain(|conn| conn.source_network != name && conn.target_network != name);
+        self.input_adapters.remove(name);
 
         self.update_execution_order();
 
@@ -52,6 +76,19 @@ impl NetworkComposer {
             .ok_or_else(|| format!("Network '{}' not found", name))
     }
 
+    /// Remove an echo state network from the composer
+    pub fn remove_reservoir(&mut self, name: &str) -> Result<EchoStateNetwork, String> {
+        let reservoir = self
+            .reservoirs
+            .remove(name)
+            .ok_or_else(|| format!("Network '{}' not found", name))?;
+        self.connections
+            .retain(|conn| conn.source_network != name && conn.target_network != name);
+        self.input_adapters.remove(name);
+        self.update_execution_order();
+        Ok(reservoir)
+    }
+
     /// Connect outputs of one network to inputs of another
     pub fn connect_networks(
         &mut self,
@@ -59,21 +96,37 @@ impl NetworkComposer {
         target_name: &str,
         source_outputs: Vec<usize>,
         target_inputs: Vec<usize>,
+    ) -> Result<(), String> {
+        self.connect_networks_with_adapter(
+            source_name,
+            target_name,
+            source_outputs,
+            target_inputs,
+            InputAdapter::Reject,
+        )
+    }
+
+    /// Connect outputs of one network to inputs of another, mapping between
+    /// differently sized index lists with `adapter`
+    pub fn connect_networks_with_adapter(
+        &mut self,
+        source_name: &str,
+        target_name: &str,
+        source_outputs: Vec<usize>,
+        target_inputs: Vec<usize>,
+        adapter: InputAdapter,
     ) -> Result<(), String> {
         // Validate networks exist
-        let source_net = self
-            .networks
-            .get(source_name)
+        let (_, source_output_size) = self
+            .io_sizes(source_name)
             .ok_or_else(|| format!("Source network '{}' not found", source_name))?;
-        let target_net = self
-            .networks
-            .get(target_name)
+        let (target_input_size, _) = self
+            .io_sizes(target_name)
             .ok_or_else(|| format!("Target network '{}' not found", target_name))?;
 
         // Validate output indices
-        let source_output_size = source_net.get_layers().last().unwrap();
         for &output_idx in &source_outputs {
-            if output_idx >= *source_output_size {
+            if output_idx >= source_output_size {
                 return Err(format!(
                     "Source output index {} out of range (max: {})",
                     output_idx,
@@ -83,9 +136,8 @@ impl NetworkComposer {
         }
 
         // Validate input indices
-        let target_input_size = target_net.get_layers().first().unwrap();
         for &input_idx in &target_inputs {
-            if input_idx >= *target_input_size {
+            if input_idx >= target_input_size {
                 return Err(format!(
                     "Target input index {} out of range (max: {})",
                     input_idx,
@@ -94,12 +146,14 @@ impl NetworkComposer {
             }
         }
 
-        // Validate connection sizes match
-        if source_outputs.len() != target_inputs.len() {
+        // Validate connection sizes match (or can be adapted)
+        let probe = vec![0.0; source_outputs.len()];
+        if let Err(e) = adapter.adapt(&probe, target_inputs.len()) {
             return Err(format!(
-                "Connection size mismatch: {} outputs -> {} inputs",
+                "Connection size mismatch: {} outputs -> {} inputs ({})",
                 source_outputs.len(),
-                target_inputs.len()
+                target_inputs.len(),
+                e
             ));
         }
 
@@ -114,6 +168,7 @@ impl NetworkComposer {
             target_network: target_name.to_string(),
             source_outputs,
             target_inputs,
+            adapter,
         };
 
         self.connections.push(connection);
@@ -130,7 +185,7 @@ impl NetworkComposer {
 
         // Initialize with external inputs
         for (network_name, input_values) in inputs {
-            if !self.networks.contains_key(network_name) {
+            if !self.contains(network_name) {
                 return Err(format!("Input network '{}' not found", network_name));
             }
             network_outputs.insert(network_name.clone(), input_values.clone());
@@ -138,37 +193,36 @@ impl NetworkComposer {
 
         // Execute networks in topological order
         for network_name in &self.execution_order {
-            let network = self.networks.get_mut(network_name).unwrap();
+            let (input_size, _) = self.io_sizes(network_name).unwrap();
 
             // Prepare inputs for this network
-            let mut network_inputs = vec![0.0; network.get_layers()[0]];
+            let mut network_inputs = vec![0.0; input_size];
 
             // Use external inputs if provided
             if let Some(external_inputs) = inputs.get(network_name) {
-                if external_inputs.len() != network_inputs.len() {
-                    return Err(format!(
-                        "Input size mismatch for network '{}': expected {}, got {}",
-                        network_name,
-                        network_inputs.len(),
-                        external_inputs.len()
-                    ));
-                }
-                network_inputs = external_inputs.clone();
+                network_inputs = self
+                    .input_adapters
+                    .get(network_name)
+                    .unwrap_or(&InputAdapter::Reject)
+                    .adapt(external_inputs, network_inputs.len())
+                    .map_err(|e| format!("Network '{}': {}", network_name, e))?;
             }
 
             // Apply connections from other networks
             for connection in &self.connections {
                 if connection.target_network == *network_name {
                     if let Some(source_outputs) = network_outputs.get(&connection.source_network) {
-                        for (&source_idx, &target_idx) in connection
+                        let selected: Vec<f64> = connection
                             .source_outputs
                             .iter()
-                            .zip(connection.target_inputs.iter())
-                        {
-                            if source_idx < source_outputs.len()
-                                && target_idx < network_inputs.len()
-                            {
-                                network_inputs[target_idx] = source_outputs[source_idx];
+                            .map(|&source_idx| source_outputs.get(source_idx).copied().unwrap_or(0.0))
+                            .collect();
+                        let mapped = connection
+                            .adapter
+                            .adapt(&selected, connection.target_inputs.len())?;
+                        for (&target_idx, value) in connection.target_inputs.iter().zip(mapped) {
+                            if target_idx < network_inputs.len() {
+                                network_inputs[target_idx] = value;
                             }
                         }
                     }
@@ -176,7 +230,10 @@ impl NetworkComposer {
             }
 
             // Forward propagation through this network
-            let outputs = network.predict(&network_inputs);
+            let outputs = match self.networks.get_mut(network_name) {
+                Some(network) => network.predict(&network_inputs),
+                None => self.reservoirs.get_mut(network_name).unwrap().step(&network_inputs),
+            };
             network_outputs.insert(network_name.clone(), outputs);
         }
 
@@ -190,6 +247,9 @@ impl NetworkComposer {
         inputs: &[f64],
         targets: &[f64],
     ) -> Result<f64, String> {
+        if let Some(reservoir) = self.reservoirs.get_mut(network_name) {
+            return Ok(reservoir.train(inputs, targets));
+        }
         let network = self
             .networks
             .get_mut(network_name)
@@ -234,9 +294,19 @@ impl NetworkComposer {
         self.networks.get_mut(name)
     }
 
-    /// Get all network names
+    /// Get a reference to a specific echo state network
+    pub fn get_reservoir(&self, name: &str) -> Option<&EchoStateNetwork> {
+        self.reservoirs.get(name)
+    }
+
+    /// Get a mutable reference to a specific echo state network
+    pub fn get_reservoir_mut(&mut self, name: &str) -> Option<&mut EchoStateNetwork> {
+        self.reservoirs.get_mut(name)
+    }
+
+    /// Get all network names, reservoirs included
     pub fn get_network_names(&self) -> Vec<&String> {
-        self.networks.keys().collect()
+        self.networks.keys().chain(self.reservoirs.keys()).collect()
     }
 
     /// Get all connections
@@ -244,6 +314,20 @@ impl NetworkComposer {
         &self.connections
     }
 
+    /// Map external inputs of another size onto the inputs of network `name`
+    pub fn set_input_adapter(&mut self, name: &str, adapter: InputAdapter) -> Result<(), String> {
+        if !self.contains(name) {
+            return Err(format!("Network '{}' not found", name));
+        }
+        self.input_adapters.insert(name.to_string(), adapter);
+        Ok(())
+    }
+
+    /// Mutable access to the adapter of a connection, e.g. to train a projection
+    pub fn connection_adapter_mut(&mut self, index: usize) -> Option<&mut InputAdapter> {
+        self.connections.get_mut(index).map(|connection| &mut connection.adapter)
+    }
+
     /// Get execution order
     pub fn get_execution_order(&self) -> &[String] {
         &self.execution_order
@@ -253,13 +337,15 @@ impl NetworkComposer {
     pub fn info(&self) -> String {
         let mut info = format!(
             "Network Composition ({} networks, {} connections):\n",
-            self.networks.len(),
+            self.networks.len() + self.reservoirs.len(),
             self.connections.len()
         );
 
         for network_name in &self.execution_order {
             if let Some(network) = self.networks.get(network_name) {
                 info.push_str(&format!("  {}: {}\n", network_name, network.info()));
+            } else if let Some(reservoir) = self.reservoirs.get(network_name) {
+                info.push_str(&format!("  {}: {}\n", network_name, reservoir.info()));
             }
         }
 
@@ -279,6 +365,24 @@ impl NetworkComposer {
         info
     }
 
+    fn contains(&self, name: &str) -> bool {
+        self.networks.contains_key(name) || self.reservoirs.contains_key(name)
+    }
+
+    /// Input and output sizes of a network or reservoir
+    fn io_sizes(&self, name: &str) -> Option<(usize, usize)> {
+        match self.networks.get(name) {
+            Some(network) => {
+                let layers = network.get_layers();
+                Some((layers[0], layers[layers.len() - 1]))
+            }
+            None => self
+                .reservoirs
+                .get(name)
+                .map(|reservoir| (reservoir.num_inputs(), reservoir.num_outputs())),
+        }
+    }
+
     /// Simple cycle detection (could be improved with proper graph algorithms)
     fn would_create_cycle(&self, source: &str, target: &str) -> bool {
         // Simple check: if target can reach source through existing connections,
@@ -310,7 +414,7 @@ impl NetworkComposer {
         let mut visited = std::collections::HashSet::new();
         let mut temp_visited = std::collections::HashSet::new();
 
-        for network_name in self.networks.keys() {
+        for network_name in self.networks.keys().chain(self.reservoirs.keys()) {
             if !visited.contains(network_name) {
                 self.topological_sort(network_name, &mut visited, &mut temp_visited, &mut order);
             }
@@ -360,6 +464,7 @@ impl Default for NetworkComposer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::echo_state::EchoStateConfig;
 
     #[test]
     fn test_composer_creation() {
@@ -424,6 +529,73 @@ mod tests {
         ); // Size mismatch
     }
 
+    #[test]
+    fn test_adapted_connections_and_inputs() {
+        let mut composer = NetworkComposer::new();
+        composer
+            .add_network("source".to_string(), NeuralNetwork::with_layers(&[2, 2], 0.1))
+            .unwrap();
+        composer
+            .add_network("target".to_string(), NeuralNetwork::with_layers(&[3, 1], 0.1))
+            .unwrap();
+
+        assert!(composer
+            .connect_networks_with_adapter(
+                "source",
+                "target",
+                vec![0, 1],
+                vec![0, 1, 2],
+                InputAdapter::Interpolate
+            )
+            .is_ok());
+        assert!(matches!(
+            composer.connection_adapter_mut(0),
+            Some(InputAdapter::Interpolate)
+        ));
+
+        let mut inputs = HashMap::new();
+        inputs.insert("source".to_string(), vec![0.5, 0.5, 0.5, 0.5]);
+        assert!(composer.forward(&inputs).is_err());
+        composer
+            .set_input_adapter("source", InputAdapter::Truncate)
+            .unwrap();
+        assert!(composer.forward(&inputs).is_ok());
+        assert!(composer
+            .set_input_adapter("missing", InputAdapter::Truncate)
+            .is_err());
+    }
+
+    #[test]
+    fn test_reservoir_feeds_network() {
+        let mut composer = NetworkComposer::new();
+        let esn = EchoStateNetwork::new(1, 2, EchoStateConfig::new(20)).unwrap();
+        composer.add_reservoir("esn".to_string(), esn).unwrap();
+        composer
+            .add_network("net".to_string(), NeuralNetwork::with_layers(&[2, 1], 0.1))
+            .unwrap();
+        assert!(composer
+            .add_network("esn".to_string(), NeuralNetwork::with_layers(&[1, 1], 0.1))
+            .is_err());
+        assert!(composer
+            .connect_networks("esn", "net", vec![0, 2], vec![0, 1])
+            .is_err());
+        composer
+            .connect_networks("esn", "net", vec![0, 1], vec![0, 1])
+            .unwrap();
+        assert_eq!(composer.get_execution_order(), &["esn", "net"]);
+
+        let mut inputs = HashMap::new();
+        inputs.insert("esn".to_string(), vec![1.0]);
+        let outputs = composer.forward(&inputs).unwrap();
+        assert_eq!(outputs["esn"].len(), 2);
+        assert!(outputs.contains_key("net"));
+        assert!(composer.get_reservoir("esn").unwrap().state()[0] != 0.0);
+
+        composer.train_network("esn", &[1.0], &[0.5, -0.5]).unwrap();
+        assert!(composer.remove_reservoir("esn").is_ok());
+        assert!(composer.get_connections().is_empty());
+    }
+
     #[test]
     fn test_forward_propagation() {
         let mut composer = NetworkComposer::new();
@@ -1,8 +1,29 @@
+use crate::activation::Activation;
+use crate::activation_export::HistoryFormat;
+use crate::adaptive_rates::AdaptiveRates;
+use crate::atomic_file;
+use crate::convolution::{ConvLayer, ConvSpec, PoolSpec};
+use crate::cost::{CostMeter, CostReport, OpCost};
+use crate::embedding::Embedding;
+use crate::inference::{InferenceLayer, InferenceModel};
+use crate::intrinsic_plasticity::IntrinsicPlasticity;
+use crate::layer_spec::LayerSpec;
+use crate::model_metadata::ModelMetadata;
+use crate::normalization::{BatchNorm, LayerNorm};
+use crate::profiling::{Phase, Profiler, TimingReport};
+use crate::quantized::{AccuracyReport, Precision, QuantizedModel};
+use crate::robust_hebbian::RobustHebbian;
+use crate::sparsity::{self, Sparseness};
+use crate::spikes::{self, SpikeEvent, StdpParams};
+use crate::synaptic_tagging::SynapticTagging;
+use futures_util::stream::{Stream, StreamExt};
 use rand::Rng;
 use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::ops::Range;
 use std::path::Path;
+use std::time::Instant;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NeuralNetwork {
@@ -24,6 +45,81 @@ pub struct NeuralNetwork {
     use_backprop: bool,                 // Whether to supplement with backpropagation
     backprop_rate: f64,                 // Learning rate for backprop (when enabled)
     online_learning: bool,              // Whether to continuously adapt during forward passes
+
+    // Normalization
+    #[serde(default)]
+    layer_norms: Vec<Option<LayerNorm>>, // layer_norms[layer], applied to pre-activations
+    #[serde(default)]
+    batch_norms: Vec<Option<BatchNorm>>, // batch_norms[layer], exclusive with layer_norms[layer]
+
+    // Residual paths between non-adjacent layers
+    #[serde(default)]
+    skip_connections: Vec<SkipConnection>,
+
+    // Convolutional layers, tied into weights[layer] after every update
+    #[serde(default)]
+    conv_layers: Vec<Option<ConvLayer>>, // conv_layers[layer]
+    #[serde(default)]
+    pool_layers: Vec<Option<PoolSpec>>, // pool_layers[layer], replaces weights and activation
+
+    // Decoder weights kept at the transpose of their mirror encoder layer
+    #[serde(default)]
+    tied_weights: bool,
+
+    // Categorical input columns, sorted by column
+    #[serde(default)]
+    embeddings: Vec<Embedding>,
+
+    // Per-layer activation function and training-time dropout rate
+    #[serde(default)]
+    activation_functions: Vec<Activation>, // activation_functions[layer], sigmoid if absent
+    #[serde(default)]
+    dropout: Vec<f64>, // dropout[layer], fraction of outputs dropped while training
+    #[serde(default)]
+    k_winners: Vec<Option<usize>>, // k_winners[layer], outputs kept active per pass
+
+    // Homeostatic activity targets overriding target_activity
+    #[serde(default)]
+    target_activities: Vec<Option<Vec<f64>>>, // target_activities[layer][neuron]
+
+    // Adaptive activation gain and offset per neuron
+    #[serde(default)]
+    intrinsic_plasticity: Vec<Option<IntrinsicPlasticity>>, // intrinsic_plasticity[layer]
+
+    // Reduced decay for weights with recent large updates
+    #[serde(default)]
+    synaptic_tagging: Option<SynapticTagging>,
+
+    // Winsorizing, median correlation and update caps against outlier activations
+    #[serde(default)]
+    robust_hebbian: Option<RobustHebbian>,
+
+    // Per-connection Hebbian gains from the variance of recent updates
+    #[serde(default)]
+    adaptive_rates: Option<AdaptiveRates>,
+
+    // Provenance of the saved model (dataset, commit, metrics, tags)
+    #[serde(default)]
+    metadata: ModelMetadata,
+
+    // Operation counters behind cost_report, never persisted
+    #[serde(skip)]
+    cost_meter: Option<CostMeter>,
+
+    // Layer and phase timers behind timing_report, never persisted
+    #[serde(skip)]
+    profiler: Option<Profiler>,
+}
+
+/// Projection from one layer's activations into the weighted sums of a later layer
+///
+/// Layers are indexed like `get_layers` (0 = input layer); `to` is at least two
+/// layers after `from`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkipConnection {
+    pub from: usize,
+    pub to: usize,
+    pub weights: Vec<Vec<f64>>, // weights[from_neuron][to_neuron]
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -42,6 +138,15 @@ pub enum HebbianLearningMode {
     Hybrid,
 }
 
+/// How `NeuralNetwork::merge` combines two trained networks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// Place the hidden units of both side by side, block-diagonally connected
+    Concatenate,
+    /// Average the weights and biases of identically shaped networks
+    Average,
+}
+
 impl NeuralNetwork {
     /// Creates a new Hebbian neural network with the specified architecture
     /// Uses Classic Hebbian learning by default
@@ -185,6 +290,24 @@ impl NeuralNetwork {
             use_backprop,
             backprop_rate,
             online_learning: false, // Default to false for backward compatibility
+            layer_norms: Vec::new(),
+            batch_norms: Vec::new(),
+            skip_connections: Vec::new(),
+            conv_layers: Vec::new(),
+            pool_layers: Vec::new(),
+            tied_weights: false,
+            embeddings: Vec::new(),
+            activation_functions: Vec::new(),
+            dropout: Vec::new(),
+            k_winners: Vec::new(),
+            target_activities: Vec::new(),
+            intrinsic_plasticity: Vec::new(),
+            synaptic_tagging: None,
+            robust_hebbian: None,
+            adaptive_rates: None,
+            metadata: ModelMetadata::default(),
+            cost_meter: None,
+            profiler: None,
         }
     }
 
@@ -220,6 +343,59 @@ impl NeuralNetwork {
         self.target_activity = target_activity;
     }
 
+    /// Set the homeostatic activity target of every neuron fed by weight layer `layer`
+    ///
+    /// Sparse output layers and denser hidden layers can thus be regulated
+    /// differently; layers without a target use the global `target_activity`.
+    pub fn set_layer_target_activity(&mut self, layer: usize, target: f64) -> Result<(), String> {
+        let size = self.target_layer_size(layer)?;
+        self.set_neuron_target_activities(layer, &vec![target; size])
+    }
+
+    /// Set one homeostatic activity target per neuron fed by weight layer `layer`
+    pub fn set_neuron_target_activities(
+        &mut self,
+        layer: usize,
+        targets: &[f64],
+    ) -> Result<(), String> {
+        let size = self.target_layer_size(layer)?;
+        if targets.len() != size {
+            return Err(format!(
+                "Layer {} has {} neurons, got {} activity targets",
+                layer,
+                size,
+                targets.len()
+            ));
+        }
+        self.target_activities.resize(self.weights.len(), None);
+        self.target_activities[layer] = Some(targets.to_vec());
+        Ok(())
+    }
+
+    /// Homeostatic activity target of `neuron` in the layer fed by weight layer `layer`
+    pub fn get_target_activity(&self, layer: usize, neuron: usize) -> f64 {
+        self.target_activities
+            .get(layer)
+            .and_then(|targets| targets.as_ref())
+            .and_then(|targets| targets.get(neuron))
+            .copied()
+            .unwrap_or(self.target_activity)
+    }
+
+    /// Activity targets of all neurons fed by weight layer `layer`
+    fn layer_target_activities(&self, layer: usize) -> Vec<f64> {
+        (0..self.layers[layer + 1])
+            .map(|neuron| self.get_target_activity(layer, neuron))
+            .collect()
+    }
+
+    fn target_layer_size(&self, layer: usize) -> Result<usize, String> {
+        if layer >= self.weights.len() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        Ok(self.layers[layer + 1])
+    }
+
     /// Enable or disable backpropagation supplementation
     pub fn set_backprop_enabled(&mut self, enabled: bool, rate: f64) {
         self.use_backprop = enabled;
@@ -251,204 +427,1137 @@ impl NeuralNetwork {
         network
     }
 
-    /// Sigmoid activation function
-    fn sigmoid(x: f64) -> f64 {
-        1.0 / (1.0 + (-x).exp())
+    /// Enable or disable layer normalization of a layer's pre-activations
+    ///
+    /// `layer` indexes weight layers like `get_layer_weights` (0 = first hidden layer).
+    /// Enabling resets the learnable scale and shift to the identity and
+    /// replaces batch normalization on that layer.
+    pub fn set_layer_norm(&mut self, layer: usize, enabled: bool) {
+        assert!(layer < self.weights.len(), "Layer {} does not exist", layer);
+        assert!(
+            !enabled || self.get_pool_layer(layer).is_none(),
+            "Pooling layer {} cannot be normalized",
+            layer
+        );
+        self.layer_norms.resize(self.weights.len(), None);
+        self.layer_norms[layer] = enabled.then(|| LayerNorm::new(self.layers[layer + 1]));
+        if enabled {
+            self.set_batch_norm(layer, false);
+        }
     }
 
-    /// Derivative of sigmoid function
-    fn sigmoid_derivative(x: f64) -> f64 {
-        x * (1.0 - x)
+    /// Layer normalization of a layer, if enabled
+    pub fn get_layer_norm(&self, layer: usize) -> Option<&LayerNorm> {
+        self.layer_norms.get(layer).and_then(Option::as_ref)
     }
 
-    /// Forward propagation through the network
-    /// If online learning is enabled, weights are continuously adapted during forward pass
-    pub fn forward(&mut self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
-        if self.online_learning {
-            self.forward_with_online_learning(inputs)
-        } else {
-            self.forward_static(inputs)
+    /// Enable or disable batch normalization of a layer's pre-activations
+    ///
+    /// Batch statistics are used by `train_batch`; single-sample passes such as
+    /// `predict_static` use the running statistics. Enabling replaces layer
+    /// normalization on that layer.
+    pub fn set_batch_norm(&mut self, layer: usize, enabled: bool) {
+        assert!(layer < self.weights.len(), "Layer {} does not exist", layer);
+        assert!(
+            !enabled || self.get_pool_layer(layer).is_none(),
+            "Pooling layer {} cannot be normalized",
+            layer
+        );
+        self.batch_norms.resize(self.weights.len(), None);
+        self.batch_norms[layer] = enabled.then(|| BatchNorm::new(self.layers[layer + 1]));
+        if enabled {
+            self.set_layer_norm(layer, false);
         }
     }
 
-    /// Forward propagation without weight updates (traditional inference)
-    pub fn forward_static(&self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
-        assert_eq!(inputs.len(), self.layers[0], "Input size mismatch");
+    /// Batch normalization of a layer, if enabled
+    pub fn get_batch_norm(&self, layer: usize) -> Option<&BatchNorm> {
+        self.batch_norms.get(layer).and_then(Option::as_ref)
+    }
 
-        let mut activations = vec![inputs.to_vec()];
+    /// Declare raw input `column` categorical with an embedding of `dim` components
+    ///
+    /// The column then carries a category index in `0..vocab_size`. The input
+    /// layer widens by `dim - 1` neurons, so `get_layers()[0]` counts embedded
+    /// components while `input_size()` stays the raw column count. Embeddings
+    /// are learned by backpropagation.
+    pub fn add_embedding(
+        &mut self,
+        column: usize,
+        vocab_size: usize,
+        dim: usize,
+    ) -> Result<(), String> {
+        if column >= self.input_size() {
+            return Err(format!(
+                "Column {} does not exist in {} inputs",
+                column,
+                self.input_size()
+            ));
+        }
+        if vocab_size == 0 || dim == 0 {
+            return Err("Embedding vocabulary and dimension must be non-zero".to_string());
+        }
+        if self.embeddings.iter().any(|e| e.column == column) {
+            return Err(format!("Column {} already has an embedding", column));
+        }
+        if self.tied_weights && dim > 1 {
+            return Err("Embeddings would widen the input layer of tied weights".to_string());
+        }
+        if self.get_conv_layer(0).is_some()
+            || self.get_pool_layer(0).is_some()
+            || self.skip_connections.iter().any(|skip| skip.from == 0)
+        {
+            return Err(
+                "Add embeddings before convolution, pooling or skip connections on the input layer"
+                    .to_string(),
+            );
+        }
 
-        // Forward propagate through each layer
-        for layer_idx in 0..self.weights.len() {
-            let current_layer = &activations[layer_idx];
-            let mut next_layer = vec![0.0; self.layers[layer_idx + 1]];
+        // Replace the column's input neuron with `dim` freshly initialized ones
+        let position = self.embedded_offset(column);
+        let mut rng = rand::thread_rng();
+        let new_rows: Vec<Vec<f64>> = (0..dim)
+            .map(|_| {
+                (0..self.layers[1])
+                    .map(|_| rng.gen_range(-1.0..1.0))
+                    .collect()
+            })
+            .collect();
+        self.weights[0].splice(position..=position, new_rows);
+        let history = vec![vec![0.0; self.history_size]; dim];
+        self.activation_history[0].splice(position..=position, history);
+        self.layers[0] += dim - 1;
+
+        self.embeddings
+            .push(Embedding::new(column, vocab_size, dim));
+        self.embeddings.sort_by_key(|e| e.column);
+        Ok(())
+    }
 
-            // Calculate weighted sum + bias for each neuron in next layer
-            for to_neuron in 0..next_layer.len() {
-                let mut sum = self.biases[layer_idx][to_neuron];
-                for from_neuron in 0..current_layer.len() {
-                    sum += current_layer[from_neuron]
-                        * self.weights[layer_idx][from_neuron][to_neuron];
-                }
-                next_layer[to_neuron] = Self::sigmoid(sum);
-            }
+    /// Embeddings of categorical input columns
+    pub fn embeddings(&self) -> &[Embedding] {
+        &self.embeddings
+    }
 
-            activations.push(next_layer);
+    /// Number of raw input values, counting each categorical column once
+    pub fn input_size(&self) -> usize {
+        self.layers[0] + self.embeddings.len()
+            - self.embeddings.iter().map(Embedding::dim).sum::<usize>()
+    }
+
+    /// Position of raw input `column` in the embedded input layer
+    fn embedded_offset(&self, column: usize) -> usize {
+        column
+            + self
+                .embeddings
+                .iter()
+                .filter(|e| e.column < column)
+                .map(|e| e.dim() - 1)
+                .sum::<usize>()
+    }
+
+    /// Replace categorical inputs with their embedding vectors
+    pub(crate) fn embed_inputs(&self, inputs: &[f64]) -> Vec<f64> {
+        assert_eq!(inputs.len(), self.input_size(), "Input size mismatch");
+
+        let mut embedded = Vec::with_capacity(self.layers[0]);
+        let mut start = 0;
+        for embedding in &self.embeddings {
+            embedded.extend_from_slice(&inputs[start..embedding.column]);
+            embedded.extend_from_slice(embedding.lookup(inputs[embedding.column]));
+            start = embedding.column + 1;
         }
+        embedded.extend_from_slice(&inputs[start..]);
+        embedded
+    }
 
-        // Return (output, hidden) - output is the final layer activations
-        let output = activations.last().unwrap().clone();
-        let hidden = if activations.len() > 2 {
-            activations[1].clone()
-        } else {
-            vec![]
-        };
+    /// Error reaching the (embedded) input layer
+    fn input_error(&self, activations: &[Vec<f64>], layer_errors: &[Vec<f64>]) -> Vec<f64> {
+        let pooled = self
+            .get_pool_layer(0)
+            .map(|pool| pool.backward(&activations[0], &layer_errors[1]));
+        (0..self.layers[0])
+            .map(|neuron| {
+                let forward_error = match &pooled {
+                    Some(pooled) => pooled[neuron],
+                    None => (0..self.layers[1])
+                        .map(|next| layer_errors[1][next] * self.weights[0][neuron][next])
+                        .sum::<f64>(),
+                };
+                forward_error + self.skip_error(0, neuron, layer_errors)
+            })
+            .collect()
+    }
 
-        (output, hidden)
+    /// Move the embedding vectors used by `inputs` along the input-layer error
+    fn update_embeddings(&mut self, inputs: &[f64], input_error: &[f64], rate: f64) {
+        let mut widened = 0;
+        for embedding in &mut self.embeddings {
+            let offset = embedding.column + widened;
+            let dim = embedding.dim();
+            embedding.update(
+                inputs[embedding.column],
+                &input_error[offset..offset + dim],
+                rate,
+            );
+            widened += dim - 1;
+        }
     }
 
-    /// Forward propagation with continuous Hebbian learning (online learning)
-    /// This is the biologically realistic mode where neurons adapt during every activation
-    pub fn forward_with_online_learning(&mut self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
-        assert_eq!(inputs.len(), self.layers[0], "Input size mismatch");
+    /// Make weight layer `layer` a convolution (0 = input to first hidden layer)
+    ///
+    /// The spec must map the layer's input size to its output size; the
+    /// layer's weights and biases are reinitialized from fresh kernels.
+    pub fn set_conv_layer(&mut self, layer: usize, spec: ConvSpec) -> Result<(), String> {
+        if layer >= self.weights.len() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        if self.tied_weights {
+            return Err("Tied weights only support dense layers".to_string());
+        }
+        spec.validate(self.layers[layer], self.layers[layer + 1])?;
+
+        let conv = ConvLayer::new(spec);
+        (self.weights[layer], self.biases[layer]) = conv.unroll();
+        self.conv_layers.resize(self.weights.len(), None);
+        self.conv_layers[layer] = Some(conv);
+        if let Some(pool) = self.pool_layers.get_mut(layer) {
+            *pool = None;
+        }
+        Ok(())
+    }
 
-        let mut activations = vec![inputs.to_vec()];
+    /// Make weight layer `layer` a max or average pooling layer
+    ///
+    /// Pooling layers have no weights, activation function or normalization;
+    /// skip connections cannot target them.
+    pub fn set_pool_layer(&mut self, layer: usize, spec: PoolSpec) -> Result<(), String> {
+        if layer >= self.weights.len() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        if self.tied_weights {
+            return Err("Tied weights only support dense layers".to_string());
+        }
+        spec.validate(self.layers[layer], self.layers[layer + 1])?;
+        if self
+            .skip_connections
+            .iter()
+            .any(|skip| skip.to == layer + 1)
+        {
+            return Err(format!(
+                "Layer {} is the target of a skip connection",
+                layer + 1
+            ));
+        }
 
-        // Store input activations in history for Hebbian learning
-        self.store_activations(0, inputs);
+        self.set_layer_norm(layer, false);
+        self.set_batch_norm(layer, false);
+        if let Some(conv) = self.conv_layers.get_mut(layer) {
+            *conv = None;
+        }
+        if let Some(ip) = self.intrinsic_plasticity.get_mut(layer) {
+            *ip = None;
+        }
+        self.pool_layers.resize(self.weights.len(), None);
+        self.pool_layers[layer] = Some(spec);
+        self.clear_pool_weights();
+        Ok(())
+    }
 
-        // Forward propagate through each layer with online adaptation
-        for layer_idx in 0..self.weights.len() {
-            let current_layer = &activations[layer_idx];
-            let mut next_layer = vec![0.0; self.layers[layer_idx + 1]];
+    /// Pooling of a weight layer, if it is one
+    pub fn get_pool_layer(&self, layer: usize) -> Option<&PoolSpec> {
+        self.pool_layers.get(layer).and_then(Option::as_ref)
+    }
 
-            // Calculate weighted sum + bias for each neuron in next layer
-            for to_neuron in 0..next_layer.len() {
-                let mut sum = self.biases[layer_idx][to_neuron];
-                for from_neuron in 0..current_layer.len() {
-                    sum += current_layer[from_neuron]
-                        * self.weights[layer_idx][from_neuron][to_neuron];
+    /// Keep the unused weights and biases of pooling layers at zero
+    fn clear_pool_weights(&mut self) {
+        for (layer, pool) in self.pool_layers.iter().enumerate() {
+            if pool.is_some() {
+                for row in &mut self.weights[layer] {
+                    row.fill(0.0);
                 }
-                next_layer[to_neuron] = Self::sigmoid(sum);
+                self.biases[layer].fill(0.0);
             }
+        }
+    }
 
-            activations.push(next_layer.clone());
-
-            // Store activations for this layer
-            self.store_activations(layer_idx + 1, &next_layer);
+    /// Derivative of layer `layer`'s output (0 = input layer) with respect to its
+    /// input, given an output `activation` from a training forward pass
+    fn activation_derivative(&self, layer: usize, activation: f64) -> f64 {
+        let weight_layer = layer - 1;
 
-            // Apply online Hebbian learning to the connection we just used
-            self.apply_online_hebbian_to_layer(layer_idx, &activations);
+        // Outputs silenced by k-winners-take-all pass no error
+        if activation == 0.0 && self.get_k_winners(weight_layer).is_some() {
+            return 0.0;
         }
 
-        // Apply homeostatic regulation to maintain network stability
-        self.apply_online_homeostatic_regulation(&activations);
-
-        // Return (output, hidden) - output is the final layer activations
-        let output = activations.last().unwrap().clone();
-        let hidden = if activations.len() > 2 {
-            activations[1].clone()
+        // Undo inverted dropout: dropped units pass no error, kept ones were scaled
+        let rate = self.get_dropout(weight_layer);
+        let (activation, scale) = if rate > 0.0 {
+            if activation == 0.0 {
+                return 0.0;
+            }
+            let scale = 1.0 / (1.0 - rate);
+            (activation / scale, scale)
         } else {
-            vec![]
+            (activation, 1.0)
         };
 
-        (output, hidden)
+        if self.get_pool_layer(weight_layer).is_some() {
+            scale
+        } else {
+            scale * self.get_activation(weight_layer).derivative(activation)
+        }
     }
 
-    /// Forward propagation returning all layer activations (optimized for multi-core)
-    pub fn forward_all_layers(&self, inputs: &[f64]) -> Vec<Vec<f64>> {
-        assert_eq!(inputs.len(), self.layers[0], "Input size mismatch");
-
-        let mut activations = vec![inputs.to_vec()];
+    /// Derivative of `neuron`'s output in layer `layer` (0 = input layer) with
+    /// respect to its net input, including its intrinsic gain
+    fn neuron_derivative(&self, layer: usize, neuron: usize, activation: f64) -> f64 {
+        let gain = self
+            .get_intrinsic_plasticity(layer - 1)
+            .map_or(1.0, |ip| ip.gain[neuron]);
+        gain * self.activation_derivative(layer, activation)
+    }
 
-        // Forward propagate through each layer
-        for layer_idx in 0..self.weights.len() {
-            let current_layer = &activations[layer_idx];
+    /// Set the activation function of weight layer `layer` (sigmoid by default)
+    pub fn set_activation(&mut self, layer: usize, activation: Activation) {
+        assert!(layer < self.weights.len(), "Layer {} does not exist", layer);
+        self.activation_functions
+            .resize(self.weights.len(), Activation::Sigmoid);
+        self.activation_functions[layer] = activation;
+    }
 
-            // Parallel computation of next layer activations
-            let next_layer: Vec<f64> = (0..self.layers[layer_idx + 1])
-                .into_par_iter()
-                .map(|to_neuron| {
-                    // Calculate weighted sum + bias for this neuron
-                    let mut sum = self.biases[layer_idx][to_neuron];
+    /// Activation function of weight layer `layer`
+    pub fn get_activation(&self, layer: usize) -> Activation {
+        self.activation_functions
+            .get(layer)
+            .copied()
+            .unwrap_or_default()
+    }
 
-                    // Vectorized inner product using parallel iterator
-                    sum += current_layer
-                        .par_iter()
-                        .enumerate()
-                        .map(|(from_neuron, &activation)| {
-                            activation * self.weights[layer_idx][from_neuron][to_neuron]
-                        })
-                        .sum::<f64>();
+    /// Drop a fraction `rate` of weight layer `layer`'s outputs during training
+    ///
+    /// Dropout is inverted: kept outputs are scaled by `1 / (1 - rate)` while
+    /// training, so inference passes use the layer unchanged.
+    pub fn set_dropout(&mut self, layer: usize, rate: f64) {
+        assert!(layer < self.weights.len(), "Layer {} does not exist", layer);
+        assert!((0.0..1.0).contains(&rate), "Dropout rate must be in [0, 1)");
+        self.dropout.resize(self.weights.len(), 0.0);
+        self.dropout[layer] = rate;
+    }
 
-                    Self::sigmoid(sum)
-                })
-                .collect();
+    /// Dropout rate of weight layer `layer`
+    pub fn get_dropout(&self, layer: usize) -> f64 {
+        self.dropout.get(layer).copied().unwrap_or(0.0)
+    }
 
-            activations.push(next_layer);
+    /// Keep only the `k` most active outputs of weight layer `layer`, zeroing the rest
+    ///
+    /// Applies to every forward pass, so competitive learning only adapts the
+    /// winners' incoming weights and the layer produces a sparse code. Only
+    /// hidden layers can be constrained; `None` removes the constraint.
+    pub fn set_k_winners(&mut self, layer: usize, k: Option<usize>) -> Result<(), String> {
+        if layer + 1 >= self.weights.len() {
+            return Err(format!("Layer {} is not a hidden layer", layer));
         }
+        if k == Some(0) {
+            return Err("k-winners-take-all needs at least one winner".to_string());
+        }
+        self.k_winners.resize(self.weights.len(), None);
+        self.k_winners[layer] = k;
+        Ok(())
+    }
 
-        activations
+    /// Number of winners kept active in weight layer `layer`, if constrained
+    pub fn get_k_winners(&self, layer: usize) -> Option<usize> {
+        self.k_winners.get(layer).copied().flatten()
     }
 
-    /// Train the network using Hebbian learning (primary method)
-    /// Optionally supplements with backpropagation if enabled
-    pub fn train(&mut self, inputs: &[f64], targets: &[f64]) -> f64 {
-        assert_eq!(
-            targets.len(),
-            self.layers[self.layers.len() - 1],
-            "Target size mismatch"
-        );
+    /// Apply the layer's k-winners-take-all constraint to its outputs
+    fn apply_k_winners(&self, layer: usize, outputs: &mut [f64]) {
+        if let Some(k) = self.get_k_winners(layer) {
+            sparsity::k_winners_take_all(outputs, k);
+        }
+    }
 
-        // Forward pass and store activations in history
-        let activations = self.forward_with_history(inputs);
+    /// Population sparseness of every non-input layer's response to `inputs`
+    pub fn layer_sparseness(&self, inputs: &[f64]) -> Vec<Sparseness> {
+        self.forward_all_layers(inputs)
+            .iter()
+            .skip(1)
+            .map(|outputs| Sparseness::of(outputs))
+            .collect()
+    }
 
-        // Calculate error for monitoring
-        let output = &activations[activations.len() - 1];
-        let mut total_error = 0.0;
-        for i in 0..output.len() {
-            let error = targets[i] - output[i];
-            total_error += error.powi(2);
+    /// Apply training-time dropout to a layer's outputs
+    fn apply_dropout(&self, layer: usize, outputs: &mut [f64]) {
+        let rate = self.get_dropout(layer);
+        if rate <= 0.0 {
+            return;
         }
-        total_error /= 2.0;
+        let mut rng = rand::thread_rng();
+        let scale = 1.0 / (1.0 - rate);
+        for output in outputs {
+            *output = if rng.gen::<f64>() < rate {
+                0.0
+            } else {
+                *output * scale
+            };
+        }
+    }
 
-        // Primary Hebbian learning
-        self.apply_hebbian_learning(&activations);
+    /// Build a network from a heterogeneous layer stack
+    ///
+    /// See `LayerSpec` for how entries compose; `LayerSpec::from_sizes` gives
+    /// the stack equivalent to `with_layers_and_mode`.
+    pub fn from_layer_specs(
+        specs: &[LayerSpec],
+        hebbian_rate: f64,
+        mode: HebbianLearningMode,
+    ) -> Result<Self, String> {
+        enum Op {
+            Activation(Activation),
+            Conv(ConvSpec),
+            Pool(PoolSpec),
+            Dropout(f64),
+            KWinners(usize),
+            LayerNorm,
+            BatchNorm,
+        }
 
-        // Apply homeostatic regulation
-        self.apply_homeostatic_regulation(&activations);
+        let (input, rest) = match specs.split_first() {
+            Some((LayerSpec::Input { n, shape }, rest)) => ((*n, *shape), rest),
+            _ => return Err("Layer stack must start with an Input layer".to_string()),
+        };
+        let (n, shape) = input;
+        let mut shape = shape.unwrap_or((1, 1, n));
+        if shape.0 * shape.1 * shape.2 != n {
+            return Err(format!(
+                "Input shape {:?} does not hold {} values",
+                shape, n
+            ));
+        }
 
-        // Optional backpropagation supplementation
-        if self.use_backprop {
-            self.apply_backpropagation(&activations, targets);
+        let mut sizes = vec![n];
+        let mut ops: Vec<(usize, Op)> = Vec::new();
+        for spec in rest {
+            let layer = sizes.len() - 1;
+            match *spec {
+                LayerSpec::Input { .. } => {
+                    return Err("Only the first layer can be an Input layer".to_string());
+                }
+                LayerSpec::Dense { n, activation } => {
+                    ops.push((layer, Op::Activation(activation)));
+                    sizes.push(n);
+                    shape = (1, 1, n);
+                }
+                LayerSpec::Conv {
+                    out_channels,
+                    kernel,
+                    stride,
+                    padding,
+                    activation,
+                } => {
+                    let conv = ConvSpec::conv2d(
+                        shape.0,
+                        (shape.1, shape.2),
+                        out_channels,
+                        kernel,
+                        stride,
+                        padding,
+                    );
+                    ops.push((layer, Op::Activation(activation)));
+                    ops.push((layer, Op::Conv(conv)));
+                    sizes.push(conv.output_len());
+                    shape = (out_channels, conv.out_height(), conv.out_width());
+                }
+                LayerSpec::Pool {
+                    kind,
+                    kernel,
+                    stride,
+                } => {
+                    let pool = PoolSpec::pool2d(kind, shape.0, (shape.1, shape.2), kernel, stride);
+                    ops.push((layer, Op::Pool(pool)));
+                    sizes.push(pool.output_len());
+                    shape = (shape.0, pool.out_height(), pool.out_width());
+                }
+                LayerSpec::Dropout { .. }
+                | LayerSpec::KWinners { .. }
+                | LayerSpec::LayerNorm
+                | LayerSpec::BatchNorm
+                | LayerSpec::Rbm(_)
+                    if layer == 0 =>
+                {
+                    return Err(format!(
+                        "{:?} must follow a Dense, Conv or Pool layer",
+                        spec
+                    ));
+                }
+                LayerSpec::Dropout { rate } => {
+                    if !(0.0..1.0).contains(&rate) {
+                        return Err(format!("Dropout rate {} must be in [0, 1)", rate));
+                    }
+                    ops.push((layer - 1, Op::Dropout(rate)));
+                }
+                LayerSpec::KWinners { k } => ops.push((layer - 1, Op::KWinners(k))),
+                LayerSpec::LayerNorm => ops.push((layer - 1, Op::LayerNorm)),
+                LayerSpec::BatchNorm => ops.push((layer - 1, Op::BatchNorm)),
+                LayerSpec::Rbm(_) => {
+                    let sigmoid_dense = ops.iter().filter(|(l, _)| *l == layer - 1).all(
+                        |(_, op)| match op {
+                            Op::Activation(activation) => *activation == Activation::Sigmoid,
+                            Op::Conv(_) | Op::Pool(_) => false,
+                            _ => true,
+                        },
+                    );
+                    if !sigmoid_dense {
+                        return Err(
+                            "Rbm pre-training must follow a sigmoid Dense layer".to_string()
+                        );
+                    }
+                }
+            }
+        }
+        if sizes.len() < 2 {
+            return Err("Layer stack needs at least one layer after the input".to_string());
         }
 
-        total_error
+        let mut network = Self::with_layers_and_mode(&sizes, hebbian_rate, mode);
+        for (layer, op) in ops {
+            match op {
+                Op::Activation(activation) => network.set_activation(layer, activation),
+                Op::Conv(conv) => network.set_conv_layer(layer, conv)?,
+                Op::Pool(pool) => network.set_pool_layer(layer, pool)?,
+                Op::Dropout(rate) => network.set_dropout(layer, rate),
+                Op::KWinners(k) => network.set_k_winners(layer, Some(k))?,
+                Op::LayerNorm | Op::BatchNorm if network.get_pool_layer(layer).is_some() => {
+                    return Err(format!("Pooling layer {} cannot be normalized", layer));
+                }
+                Op::LayerNorm => network.set_layer_norm(layer, true),
+                Op::BatchNorm => network.set_batch_norm(layer, true),
+            }
+        }
+        Ok(network)
     }
 
-    /// Train the network using only Hebbian learning (unsupervised)
-    pub fn train_unsupervised(&mut self, inputs: &[f64]) {
-        // Forward pass and store activations in history
-        let activations = self.forward_with_history(inputs);
-
-        // Apply Hebbian learning
-        self.apply_hebbian_learning(&activations);
+    /// Convolution of a weight layer, if it is one
+    pub fn get_conv_layer(&self, layer: usize) -> Option<&ConvLayer> {
+        self.conv_layers.get(layer).and_then(Option::as_ref)
+    }
 
-        // Apply homeostatic regulation
-        self.apply_homeostatic_regulation(&activations);
+    /// Fold dense updates of convolutional layers back into their shared kernels,
+    /// discard updates to pooling layers and re-tie tied weights
+    fn sync_conv_layers(&mut self) {
+        for (layer, conv) in self.conv_layers.iter_mut().enumerate() {
+            if let Some(conv) = conv {
+                conv.absorb(&self.weights[layer], &self.biases[layer]);
+                (self.weights[layer], self.biases[layer]) = conv.unroll();
+            }
+        }
+        self.clear_pool_weights();
+        self.sync_tied_weights();
     }
 
-    /// Forward propagation with activation history storage for Hebbian learning
-    pub fn forward_with_history(&mut self, inputs: &[f64]) -> Vec<Vec<f64>> {
-        assert_eq!(inputs.len(), self.layers[0], "Input size mismatch");
+    /// Tie each decoder weight layer to the transpose of its mirror encoder layer
+    ///
+    /// Needs a symmetric autoencoder of dense layers around a middle layer,
+    /// such as `[8, 4, 2, 4, 8]`. Tying overwrites the decoder weights with the
+    /// transposed encoder weights; after every learning step the updates both
+    /// halves received are averaged into the shared weights. Biases stay
+    /// separate.
+    pub fn set_tied_weights(&mut self, tied: bool) -> Result<(), String> {
+        if tied {
+            if self.layers.len().is_multiple_of(2) || !self.layers.iter().eq(self.layers.iter().rev()) {
+                return Err(format!(
+                    "Tied weights need a symmetric architecture around a middle layer, not {:?}",
+                    self.layers
+                ));
+            }
+            if self.conv_layers.iter().any(Option::is_some)
+                || self.pool_layers.iter().any(Option::is_some)
+            {
+                return Err("Tied weights only support dense layers".to_string());
+            }
+            let last = self.weights.len() - 1;
+            for layer in 0..self.weights.len() / 2 {
+                let (encoder, decoder) = self.weights.split_at_mut(last - layer);
+                for (from, row) in encoder[layer].iter().enumerate() {
+                    for (to, &weight) in row.iter().enumerate() {
+                        decoder[0][to][from] = weight;
+                    }
+                }
+            }
+        }
+        self.tied_weights = tied;
+        Ok(())
+    }
 
-        let mut activations = vec![inputs.to_vec()];
+    /// Whether decoder weights are tied to the encoder weights
+    pub fn has_tied_weights(&self) -> bool {
+        self.tied_weights
+    }
 
-        // Store input activations in history
-        self.store_activations(0, inputs);
+    /// Average the separate updates of tied encoder and decoder weights into both
+    fn sync_tied_weights(&mut self) {
+        if !self.tied_weights {
+            return;
+        }
+        let last = self.weights.len() - 1;
+        for layer in 0..self.weights.len() / 2 {
+            let (encoder, decoder) = self.weights.split_at_mut(last - layer);
+            for (from, row) in encoder[layer].iter_mut().enumerate() {
+                for (to, weight) in row.iter_mut().enumerate() {
+                    let shared = (*weight + decoder[0][to][from]) / 2.0;
+                    *weight = shared;
+                    decoder[0][to][from] = shared;
+                }
+            }
+        }
+    }
 
-        // Forward propagate through each layer using parallel processing
-        for layer_idx in 0..self.weights.len() {
-            let current_layer = &activations[layer_idx];
+    /// Add a skip connection from layer `from` to layer `to` (0 = input layer)
+    ///
+    /// The source activations are projected through a new weight matrix and
+    /// added to the target layer's weighted sums, before normalization.
+    pub fn add_skip_connection(&mut self, from: usize, to: usize) -> Result<(), String> {
+        if to >= self.layers.len() || from + 2 > to {
+            return Err(format!(
+                "Skip connection {} -> {} must jump forward over at least one of {} layers",
+                from,
+                to,
+                self.layers.len()
+            ));
+        }
+        if self
+            .skip_connections
+            .iter()
+            .any(|skip| skip.from == from && skip.to == to)
+        {
+            return Err(format!("Skip connection {} -> {} already exists", from, to));
+        }
+        if self.get_pool_layer(to - 1).is_some() {
+            return Err(format!(
+                "Skip connection cannot target pooling layer {}",
+                to
+            ));
+        }
+
+        let mut rng = rand::thread_rng();
+        let weights = (0..self.layers[from])
+            .map(|_| {
+                (0..self.layers[to])
+                    .map(|_| rng.gen_range(-1.0..1.0))
+                    .collect()
+            })
+            .collect();
+        self.skip_connections
+            .push(SkipConnection { from, to, weights });
+        Ok(())
+    }
+
+    /// Skip connections declared on this network
+    pub fn skip_connections(&self) -> &[SkipConnection] {
+        &self.skip_connections
+    }
+
+    /// Add the projected skip inputs arriving at weight layer `layer_idx`
+    fn add_skip_inputs(&self, layer_idx: usize, sums: &mut [f64], activations: &[Vec<f64>]) {
+        for skip in self
+            .skip_connections
+            .iter()
+            .filter(|skip| skip.to == layer_idx + 1)
+        {
+            for (from_neuron, row) in skip.weights.iter().enumerate() {
+                let activation = activations[skip.from][from_neuron];
+                for (sum, weight) in sums.iter_mut().zip(row) {
+                    *sum += activation * weight;
+                }
+            }
+        }
+    }
+
+    /// Error reaching `neuron` of `layer` through skip connections leaving it
+    fn skip_error(&self, layer: usize, neuron: usize, layer_errors: &[Vec<f64>]) -> f64 {
+        self.skip_connections
+            .iter()
+            .filter(|skip| skip.from == layer)
+            .map(|skip| {
+                skip.weights[neuron]
+                    .iter()
+                    .zip(&layer_errors[skip.to])
+                    .map(|(weight, error)| weight * error)
+                    .sum::<f64>()
+            })
+            .sum()
+    }
+
+    /// Backprop update of the skip weights: Δw = scale * error[to] * activation[from]
+    fn update_skip_weights(
+        &mut self,
+        activations: &[Vec<f64>],
+        layer_errors: &[Vec<f64>],
+        scale: f64,
+    ) {
+        for skip in &mut self.skip_connections {
+            for (from_neuron, row) in skip.weights.iter_mut().enumerate() {
+                let activation = activations[skip.from][from_neuron];
+                for (weight, error) in row.iter_mut().zip(&layer_errors[skip.to]) {
+                    *weight += scale * error * activation;
+                }
+            }
+        }
+    }
+
+    /// Weighted sums (before normalization and activation) of a layer,
+    /// including skip inputs; `activations` holds every layer up to `layer_idx`
+    fn pre_activations(&self, layer_idx: usize, activations: &[Vec<f64>]) -> Vec<f64> {
+        let inputs = &activations[layer_idx];
+        let mut sums: Vec<f64> = (0..self.layers[layer_idx + 1])
+            .map(|to_neuron| {
+                self.biases[layer_idx][to_neuron]
+                    + inputs
+                        .iter()
+                        .enumerate()
+                        .map(|(from_neuron, &x)| {
+                            x * self.weights[layer_idx][from_neuron][to_neuron]
+                        })
+                        .sum::<f64>()
+            })
+            .collect();
+        self.add_skip_inputs(layer_idx, &mut sums, activations);
+        sums
+    }
+
+    /// Apply the layer's optional normalization to one sample
+    fn normalize(&self, layer_idx: usize, pre_activations: Vec<f64>) -> Vec<f64> {
+        if let Some(norm) = self.get_layer_norm(layer_idx) {
+            norm.forward(&pre_activations)
+        } else if let Some(norm) = self.get_batch_norm(layer_idx) {
+            norm.forward(&pre_activations)
+        } else {
+            pre_activations
+        }
+    }
+
+    /// Add skip inputs, then apply the layer's optional normalization and the sigmoid activation
+    ///
+    /// Pooling layers ignore `pre_activations` and pool the previous layer instead.
+    pub(crate) fn activate(
+        &self,
+        layer_idx: usize,
+        mut pre_activations: Vec<f64>,
+        activations: &[Vec<f64>],
+    ) -> Vec<f64> {
+        if let Some(pool) = self.get_pool_layer(layer_idx) {
+            return pool.forward(&activations[layer_idx]);
+        }
+        self.add_skip_inputs(layer_idx, &mut pre_activations, activations);
+        let net_inputs = self.net_inputs(layer_idx, pre_activations);
+        let net_inputs = match self.get_intrinsic_plasticity(layer_idx) {
+            Some(ip) => ip.forward(&net_inputs),
+            None => net_inputs,
+        };
+        let activation = self.get_activation(layer_idx);
+        let mut outputs: Vec<f64> = net_inputs
+            .into_iter()
+            .map(|x| activation.apply(x))
+            .collect();
+        self.apply_k_winners(layer_idx, &mut outputs);
+        outputs
+    }
+
+    /// Saturate and normalize weighted sums (skip inputs included) into the
+    /// net inputs that intrinsic plasticity and the activation function see
+    fn net_inputs(&self, layer_idx: usize, mut pre_activations: Vec<f64>) -> Vec<f64> {
+        for x in &mut pre_activations {
+            *x = Activation::saturate(*x);
+        }
+        self.normalize(layer_idx, pre_activations)
+    }
+
+    /// Enable intrinsic plasticity on weight layer `layer` (0 = first hidden layer)
+    ///
+    /// Each neuron of the layer learns an activation gain and offset during
+    /// `train`, `train_unsupervised` and online passes so that its outputs
+    /// approach an exponential distribution with mean `target_mean`. Only
+    /// sigmoid layers adapt; gains are reset to 1 when (re-)enabled.
+    pub fn set_intrinsic_plasticity(
+        &mut self,
+        layer: usize,
+        target_mean: f64,
+        rate: f64,
+    ) -> Result<(), String> {
+        if layer >= self.weights.len() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        if self.get_pool_layer(layer).is_some() {
+            return Err(format!(
+                "Pooling layer {} has no activation to adapt",
+                layer
+            ));
+        }
+        let ip = IntrinsicPlasticity::new(self.layers[layer + 1], target_mean, rate)?;
+        self.intrinsic_plasticity.resize(self.weights.len(), None);
+        self.intrinsic_plasticity[layer] = Some(ip);
+        Ok(())
+    }
+
+    /// Disable intrinsic plasticity on a layer, restoring unit gain and zero offset
+    pub fn clear_intrinsic_plasticity(&mut self, layer: usize) {
+        if let Some(ip) = self.intrinsic_plasticity.get_mut(layer) {
+            *ip = None;
+        }
+    }
+
+    /// Intrinsic plasticity of a weight layer, if enabled
+    pub fn get_intrinsic_plasticity(&self, layer: usize) -> Option<&IntrinsicPlasticity> {
+        self.intrinsic_plasticity
+            .get(layer)
+            .and_then(Option::as_ref)
+    }
+
+    /// Adapt the gains of weight layer `layer_idx` to the pass in `activations`
+    ///
+    /// Net inputs are recomputed from the layer below, so this must run
+    /// before that layer's weights change.
+    fn update_intrinsic_plasticity(&mut self, layer_idx: usize, activations: &[Vec<f64>]) {
+        if self.get_intrinsic_plasticity(layer_idx).is_none()
+            || self.get_activation(layer_idx) != Activation::Sigmoid
+        {
+            return;
+        }
+        let net_inputs = self.net_inputs(layer_idx, self.pre_activations(layer_idx, activations));
+        let Some(ip) = self.intrinsic_plasticity[layer_idx].as_mut() else {
+            return;
+        };
+        let outputs: Vec<f64> = ip
+            .forward(&net_inputs)
+            .into_iter()
+            .map(|x| Activation::Sigmoid.apply(x))
+            .collect();
+        ip.update(&net_inputs, &outputs);
+    }
+
+    /// Forward propagation through the network
+    /// If online learning is enabled, weights are continuously adapted during forward pass
+    pub fn forward(&mut self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        if self.online_learning {
+            self.forward_with_online_learning(inputs)
+        } else {
+            self.forward_static(inputs)
+        }
+    }
+
+    /// Forward propagation without weight updates (traditional inference)
+    pub fn forward_static(&self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        self.count_forward(|| self.forward_cost());
+        let mut activations = vec![self.embed_inputs(inputs)];
+
+        // Forward propagate through each layer
+        for layer_idx in 0..self.weights.len() {
+            let started = self.profile_start();
+            let current_layer = &activations[layer_idx];
+            let mut next_layer = vec![0.0; self.layers[layer_idx + 1]];
+
+            // Calculate weighted sum + bias for each neuron in next layer
+            for to_neuron in 0..next_layer.len() {
+                let mut sum = self.biases[layer_idx][to_neuron];
+                for from_neuron in 0..current_layer.len() {
+                    sum += current_layer[from_neuron]
+                        * self.weights[layer_idx][from_neuron][to_neuron];
+                }
+                next_layer[to_neuron] = sum;
+            }
+            let next_layer = self.activate(layer_idx, next_layer, &activations);
+            self.profile_layer(layer_idx, started);
+
+            activations.push(next_layer);
+        }
+
+        // Return (output, hidden) - output is the final layer activations
+        let output = activations.last().unwrap().clone();
+        let hidden = if activations.len() > 2 {
+            activations[1].clone()
+        } else {
+            vec![]
+        };
+
+        (output, hidden)
+    }
+
+    /// Run every input of an async stream through `forward_with_online_learning`
+    ///
+    /// Yields the outputs in input order, learning from each input before the
+    /// next one is pulled. The network stays borrowed until the returned stream
+    /// is dropped.
+    pub fn process_stream<'a, S>(&'a mut self, inputs: S) -> impl Stream<Item = Vec<f64>> + 'a
+    where
+        S: Stream<Item = Vec<f64>> + 'a,
+    {
+        inputs.map(move |inputs| self.forward_with_online_learning(&inputs).0)
+    }
+
+    /// Forward propagation with continuous Hebbian learning (online learning)
+    /// This is the biologically realistic mode where neurons adapt during every activation
+    pub fn forward_with_online_learning(&mut self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        self.count_forward(|| self.forward_cost() + self.update_cost(false));
+        let mut activations = vec![self.embed_inputs(inputs)];
+
+        // Store input activations in history for Hebbian learning
+        self.store_activations(0, &activations[0].clone());
+
+        // Forward propagate through each layer with online adaptation
+        for layer_idx in 0..self.weights.len() {
+            let started = self.profile_start();
+            let current_layer = &activations[layer_idx];
+            let mut next_layer = vec![0.0; self.layers[layer_idx + 1]];
+
+            // Calculate weighted sum + bias for each neuron in next layer
+            for to_neuron in 0..next_layer.len() {
+                let mut sum = self.biases[layer_idx][to_neuron];
+                for from_neuron in 0..current_layer.len() {
+                    sum += current_layer[from_neuron]
+                        * self.weights[layer_idx][from_neuron][to_neuron];
+                }
+                next_layer[to_neuron] = sum;
+            }
+            let next_layer = self.activate(layer_idx, next_layer, &activations);
+            self.profile_layer(layer_idx, started);
+
+            activations.push(next_layer.clone());
+
+            // Store activations for this layer
+            self.store_activations(layer_idx + 1, &next_layer);
+
+            // Adapt the layer's gains, then the connection we just used
+            let started = self.profile_start();
+            self.update_intrinsic_plasticity(layer_idx, &activations);
+            self.apply_online_hebbian_to_layer(layer_idx, &activations);
+            self.profile_phase(Phase::Hebbian, started);
+        }
+
+        // Skip connections adapt once every layer they join has fired
+        let started = self.profile_start();
+        self.apply_skip_hebbian(&activations, self.hebbian_rate * 0.1);
+        self.profile_phase(Phase::Hebbian, started);
+
+        // Apply homeostatic regulation to maintain network stability
+        let started = self.profile_start();
+        self.apply_online_homeostatic_regulation(&activations);
+        self.profile_phase(Phase::Homeostasis, started);
+        self.sync_conv_layers();
+
+        // Return (output, hidden) - output is the final layer activations
+        let output = activations.last().unwrap().clone();
+        let hidden = if activations.len() > 2 {
+            activations[1].clone()
+        } else {
+            vec![]
+        };
+
+        (output, hidden)
+    }
+
+    /// Forward propagation returning all layer activations (optimized for multi-core)
+    pub fn forward_all_layers(&self, inputs: &[f64]) -> Vec<Vec<f64>> {
+        self.count_forward(|| self.forward_cost());
+        self.all_layer_activations(inputs)
+    }
+
+    fn all_layer_activations(&self, inputs: &[f64]) -> Vec<Vec<f64>> {
+        let mut activations = vec![self.embed_inputs(inputs)];
+
+        // Forward propagate through each layer
+        for layer_idx in 0..self.weights.len() {
+            let started = self.profile_start();
+            let current_layer = &activations[layer_idx];
+
+            // Parallel computation of next layer activations
+            let next_layer: Vec<f64> = (0..self.layers[layer_idx + 1])
+                .into_par_iter()
+                .map(|to_neuron| {
+                    // Calculate weighted sum + bias for this neuron
+                    let mut sum = self.biases[layer_idx][to_neuron];
+
+                    // Vectorized inner product using parallel iterator
+                    sum += current_layer
+                        .par_iter()
+                        .enumerate()
+                        .map(|(from_neuron, &activation)| {
+                            activation * self.weights[layer_idx][from_neuron][to_neuron]
+                        })
+                        .sum::<f64>();
+
+                    sum
+                })
+                .collect();
+            let next_layer = self.activate(layer_idx, next_layer, &activations);
+            self.profile_layer(layer_idx, started);
+
+            activations.push(next_layer);
+        }
+
+        activations
+    }
+
+    /// Train the network using Hebbian learning (primary method)
+    /// Optionally supplements with backpropagation if enabled
+    pub fn train(&mut self, inputs: &[f64], targets: &[f64]) -> f64 {
+        assert_eq!(
+            targets.len(),
+            self.layers[self.layers.len() - 1],
+            "Target size mismatch"
+        );
+        self.count_train(1, || self.train_cost());
+
+        // Forward pass and store activations in history
+        let activations = self.forward_with_history(inputs);
+
+        // Calculate error for monitoring
+        let output = &activations[activations.len() - 1];
+        let mut total_error = 0.0;
+        for i in 0..output.len() {
+            let error = targets[i] - output[i];
+            total_error += error.powi(2);
+        }
+        total_error /= 2.0;
+
+        // Intrinsic plasticity sees the net inputs of this pass, so it runs before weights change
+        let started = self.profile_start();
+        for layer_idx in 0..self.weights.len() {
+            self.update_intrinsic_plasticity(layer_idx, &activations);
+        }
+
+        // Primary Hebbian learning
+        self.apply_hebbian_learning(&activations);
+        self.profile_phase(Phase::Hebbian, started);
+
+        // Apply homeostatic regulation
+        let started = self.profile_start();
+        self.apply_homeostatic_regulation(&activations);
+        self.profile_phase(Phase::Homeostasis, started);
+
+        // Optional backpropagation supplementation
+        if self.use_backprop {
+            let started = self.profile_start();
+            let input_error = self.apply_backpropagation(&activations, targets);
+            self.update_embeddings(inputs, &input_error, self.backprop_rate);
+            self.profile_phase(Phase::Backprop, started);
+        }
+        self.sync_conv_layers();
+
+        total_error
+    }
+
+    /// One backpropagation step without Hebbian learning, homeostasis or kernel tying
+    ///
+    /// Each parameter moves by `backprop_rate` times the negative gradient of the
+    /// squared error, which lets `debug::grad_check` read gradients off the update.
+    pub(crate) fn backprop_step(&mut self, inputs: &[f64], targets: &[f64]) {
+        let activations = self.forward_with_history(inputs);
+        let input_error = self.apply_backpropagation(&activations, targets);
+        self.update_embeddings(inputs, &input_error, self.backprop_rate);
+    }
+
+    /// Parameters trained by backpropagation, in a fixed order
+    ///
+    /// Weights and biases of every non-pooling layer, then normalization scales
+    /// and shifts, skip connection weights and embedding tables.
+    pub(crate) fn backprop_parameters_mut(&mut self) -> Vec<&mut f64> {
+        let mut parameters: Vec<&mut f64> = Vec::new();
+        for (layer, (weights, biases)) in self
+            .weights
+            .iter_mut()
+            .zip(self.biases.iter_mut())
+            .enumerate()
+        {
+            if matches!(self.pool_layers.get(layer), Some(Some(_))) {
+                continue;
+            }
+            parameters.extend(weights.iter_mut().flatten());
+            parameters.extend(biases.iter_mut());
+        }
+        for norm in self.layer_norms.iter_mut().flatten() {
+            parameters.extend(norm.gamma.iter_mut().chain(norm.beta.iter_mut()));
+        }
+        for norm in self.batch_norms.iter_mut().flatten() {
+            parameters.extend(norm.gamma.iter_mut().chain(norm.beta.iter_mut()));
+        }
+        for skip in &mut self.skip_connections {
+            parameters.extend(skip.weights.iter_mut().flatten());
+        }
+        for embedding in &mut self.embeddings {
+            parameters.extend(embedding.table.iter_mut().flatten());
+        }
+        parameters
+    }
+
+    /// Every learned value of the network, in a fixed order
+    ///
+    /// Unlike `backprop_parameters_mut` this also covers pooling layers,
+    /// convolution kernels and batch normalization running statistics, so that
+    /// restoring these values restores the network's learned state.
+    pub(crate) fn state_parameters_mut(&mut self) -> Vec<&mut f64> {
+        let mut parameters: Vec<&mut f64> = Vec::new();
+        for (weights, biases) in self.weights.iter_mut().zip(self.biases.iter_mut()) {
+            parameters.extend(weights.iter_mut().flatten());
+            parameters.extend(biases.iter_mut());
+        }
+        for norm in self.layer_norms.iter_mut().flatten() {
+            parameters.extend(norm.gamma.iter_mut().chain(norm.beta.iter_mut()));
+        }
+        for norm in self.batch_norms.iter_mut().flatten() {
+            parameters.extend(norm.gamma.iter_mut().chain(norm.beta.iter_mut()));
+            parameters.extend(norm.running_mean.iter_mut());
+            parameters.extend(norm.running_var.iter_mut());
+        }
+        for skip in &mut self.skip_connections {
+            parameters.extend(skip.weights.iter_mut().flatten());
+        }
+        for conv in self.conv_layers.iter_mut().flatten() {
+            parameters.extend(conv.kernels.iter_mut().chain(conv.biases.iter_mut()));
+        }
+        for embedding in &mut self.embeddings {
+            parameters.extend(embedding.table.iter_mut().flatten());
+        }
+        for ip in self.intrinsic_plasticity.iter_mut().flatten() {
+            parameters.extend(ip.gain.iter_mut().chain(ip.offset.iter_mut()));
+        }
+        parameters
+    }
+
+    /// Train the network using only Hebbian learning (unsupervised)
+    pub fn train_unsupervised(&mut self, inputs: &[f64]) {
+        self.count_train(1, || self.forward_cost() + self.update_cost(false));
+        // Forward pass and store activations in history
+        let activations = self.forward_with_history(inputs);
+        let started = self.profile_start();
+        for layer_idx in 0..self.weights.len() {
+            self.update_intrinsic_plasticity(layer_idx, &activations);
+        }
+
+        // Apply Hebbian learning
+        self.apply_hebbian_learning(&activations);
+        self.profile_phase(Phase::Hebbian, started);
+
+        // Apply homeostatic regulation
+        let started = self.profile_start();
+        self.apply_homeostatic_regulation(&activations);
+        self.profile_phase(Phase::Homeostasis, started);
+        self.sync_conv_layers();
+    }
+
+    /// Forward propagation with activation history storage for Hebbian learning
+    pub fn forward_with_history(&mut self, inputs: &[f64]) -> Vec<Vec<f64>> {
+        let mut activations = vec![self.embed_inputs(inputs)];
+
+        // Store input activations in history
+        self.store_activations(0, &activations[0].clone());
+
+        // Forward propagate through each layer using parallel processing
+        for layer_idx in 0..self.weights.len() {
+            let started = self.profile_start();
+            let current_layer = &activations[layer_idx];
 
             // Parallel computation of next layer activations
             let next_layer: Vec<f64> = (0..self.layers[layer_idx + 1])
@@ -466,9 +1575,12 @@ impl NeuralNetwork {
                         })
                         .sum::<f64>();
 
-                    Self::sigmoid(sum)
+                    sum
                 })
                 .collect();
+            let mut next_layer = self.activate(layer_idx, next_layer, &activations);
+            self.apply_dropout(layer_idx, &mut next_layer);
+            self.profile_layer(layer_idx, started);
 
             // Store activations in history
             self.store_activations(layer_idx + 1, &next_layer);
@@ -489,6 +1601,14 @@ impl NeuralNetwork {
 
     /// Apply Hebbian learning based on the selected learning mode
     fn apply_hebbian_learning(&mut self, activations: &[Vec<f64>]) {
+        let winsorized: Option<Vec<Vec<f64>>> = activations
+            .iter()
+            .enumerate()
+            .map(|(layer_idx, layer)| self.winsorized(layer_idx, layer))
+            .collect();
+        let activations = winsorized.as_deref().unwrap_or(activations);
+        let before = self.shapes_hebbian_updates().then(|| self.weights.clone());
+
         match self.learning_mode {
             HebbianLearningMode::Classic => self.apply_classic_hebbian(activations),
             HebbianLearningMode::Competitive => self.apply_competitive_learning(activations),
@@ -501,6 +1621,90 @@ impl NeuralNetwork {
                 self.apply_oja_rule(activations);
             }
         }
+        if let Some(before) = before {
+            for (layer_idx, before) in before.iter().enumerate() {
+                self.shape_hebbian_updates(layer_idx, before);
+            }
+        }
+        self.apply_skip_hebbian(activations, self.hebbian_rate);
+    }
+
+    /// `activations` of layer `layer_idx` clipped to their recent history, when winsorizing
+    fn winsorized(&self, layer_idx: usize, activations: &[f64]) -> Option<Vec<f64>> {
+        let robust = self
+            .robust_hebbian
+            .as_ref()
+            .filter(|robust| robust.winsorize.is_some())?;
+        let history = &self.activation_history[layer_idx];
+        Some(
+            activations
+                .iter()
+                .zip(history)
+                .map(|(&activation, history)| robust.winsorize(activation, history))
+                .collect(),
+        )
+    }
+
+    /// Whether Hebbian updates are rescaled or capped after their rule ran
+    fn shapes_hebbian_updates(&self) -> bool {
+        self.adaptive_rates.is_some() || self.robust_hebbian.is_some()
+    }
+
+    /// Rescale, then cap, the Hebbian updates of weight layer `layer_idx` since `before`
+    fn shape_hebbian_updates(&mut self, layer_idx: usize, before: &[Vec<f64>]) {
+        if let Some(rates) = self.adaptive_rates.as_mut() {
+            rates.apply(layer_idx, before, &mut self.weights[layer_idx]);
+        }
+        if let Some(robust) = self.robust_hebbian.as_mut() {
+            robust.cap(layer_idx, before, &mut self.weights[layer_idx]);
+        }
+    }
+
+    /// Apply the selected Hebbian rule to skip connection weights
+    fn apply_skip_hebbian(&mut self, activations: &[Vec<f64>], rate: f64) {
+        let mode = self.learning_mode;
+        let anti_hebbian_rate = self.anti_hebbian_rate;
+        let thresholds: Vec<Vec<f64>> = self
+            .skip_connections
+            .iter()
+            .map(|skip| {
+                let targets = self.layer_target_activities(skip.to - 1);
+                targets.iter().map(|t| t * t).collect()
+            })
+            .collect();
+
+        for (skip, thresholds) in self.skip_connections.iter_mut().zip(&thresholds) {
+            let from_layer = &activations[skip.from];
+            let to_layer = &activations[skip.to];
+            let winner = to_layer
+                .iter()
+                .enumerate()
+                .max_by(|a, b| a.1.partial_cmp(b.1).unwrap())
+                .map(|(idx, _)| idx)
+                .unwrap_or(0);
+
+            for (from_neuron, row) in skip.weights.iter_mut().enumerate() {
+                let pre = from_layer[from_neuron];
+                for (to_neuron, weight) in row.iter_mut().enumerate() {
+                    let post = to_layer[to_neuron];
+                    let classic = rate * pre * post;
+                    let competitive = if to_neuron == winner {
+                        rate * pre
+                    } else {
+                        -anti_hebbian_rate * pre * 0.1
+                    };
+                    let oja = rate * post * (pre - post * *weight);
+                    *weight += match mode {
+                        HebbianLearningMode::Classic => classic,
+                        HebbianLearningMode::Competitive => competitive,
+                        HebbianLearningMode::Oja => oja,
+                        HebbianLearningMode::BCM => classic * (post - thresholds[to_neuron]),
+                        HebbianLearningMode::AntiHebbian => -anti_hebbian_rate * pre * post,
+                        HebbianLearningMode::Hybrid => classic + competitive + oja,
+                    };
+                }
+            }
+        }
     }
 
     /// Classic Hebbian learning: "neurons that fire together, wire together"
@@ -593,6 +1797,7 @@ impl NeuralNetwork {
         for layer_idx in 0..self.weights.len() {
             let from_layer = &activations[layer_idx];
             let to_layer = &activations[layer_idx + 1];
+            let targets = self.layer_target_activities(layer_idx);
 
             self.weights[layer_idx].par_iter_mut().enumerate().for_each(
                 |(from_neuron, weight_row)| {
@@ -604,7 +1809,7 @@ impl NeuralNetwork {
                             let post_activity = to_layer[to_neuron];
 
                             // BCM threshold (sliding average of post-synaptic activity squared)
-                            let threshold = self.target_activity * self.target_activity;
+                            let threshold = targets[to_neuron] * targets[to_neuron];
 
                             // BCM rule: Δw = η * pre * post * (post - threshold)
                             let weight_update = self.hebbian_rate
@@ -655,7 +1860,8 @@ impl NeuralNetwork {
             // Adjust biases to maintain target activity
             let bias_layer_idx = layer_idx - 1;
             for neuron_idx in 0..layer_activations.len() {
-                let activity_error = self.target_activity - layer_activations[neuron_idx];
+                let activity_error = self.get_target_activity(bias_layer_idx, neuron_idx)
+                    - layer_activations[neuron_idx];
                 let bias_update = self.homeostatic_rate * activity_error;
                 self.biases[bias_layer_idx][neuron_idx] += bias_update;
             }
@@ -663,7 +1869,10 @@ impl NeuralNetwork {
     }
 
     /// Apply backpropagation as supplementary learning (when enabled)
-    fn apply_backpropagation(&mut self, activations: &[Vec<f64>], targets: &[f64]) {
+    ///
+    /// Returns the error reaching the input layer when the network has
+    /// embeddings to learn, otherwise an empty vector.
+    fn apply_backpropagation(&mut self, activations: &[Vec<f64>], targets: &[f64]) -> Vec<f64> {
         // Standard backpropagation implementation
         let mut layer_errors = vec![vec![]; self.layers.len()];
 
@@ -672,27 +1881,44 @@ impl NeuralNetwork {
         layer_errors[output_layer_idx] = vec![0.0; self.layers[output_layer_idx]];
         for i in 0..self.layers[output_layer_idx] {
             let error = targets[i] - activations[output_layer_idx][i];
-            layer_errors[output_layer_idx][i] =
-                error * Self::sigmoid_derivative(activations[output_layer_idx][i]);
+            layer_errors[output_layer_idx][i] = error
+                * self.neuron_derivative(output_layer_idx, i, activations[output_layer_idx][i]);
         }
+        self.backprop_through_normalization(output_layer_idx, activations, &mut layer_errors);
 
         // Backpropagate errors through hidden layers
         for layer_idx in (1..self.layers.len() - 1).rev() {
+            // Pooling layers route their error back instead of weighting it
+            let pooled = self
+                .get_pool_layer(layer_idx)
+                .map(|pool| pool.backward(&activations[layer_idx], &layer_errors[layer_idx + 1]));
             layer_errors[layer_idx] = (0..self.layers[layer_idx])
                 .into_par_iter()
                 .map(|neuron| {
-                    let error: f64 = (0..self.layers[layer_idx + 1])
-                        .into_par_iter()
-                        .map(|next_neuron| {
-                            layer_errors[layer_idx + 1][next_neuron]
-                                * self.weights[layer_idx][neuron][next_neuron]
-                        })
-                        .sum();
-                    error * Self::sigmoid_derivative(activations[layer_idx][neuron])
+                    let forward_error = match &pooled {
+                        Some(pooled) => pooled[neuron],
+                        None => (0..self.layers[layer_idx + 1])
+                            .into_par_iter()
+                            .map(|next_neuron| {
+                                layer_errors[layer_idx + 1][next_neuron]
+                                    * self.weights[layer_idx][neuron][next_neuron]
+                            })
+                            .sum::<f64>(),
+                    };
+                    let error = forward_error + self.skip_error(layer_idx, neuron, &layer_errors);
+                    error
+                        * self.neuron_derivative(layer_idx, neuron, activations[layer_idx][neuron])
                 })
                 .collect();
+            self.backprop_through_normalization(layer_idx, activations, &mut layer_errors);
         }
 
+        let input_error = if self.embeddings.is_empty() {
+            Vec::new()
+        } else {
+            self.input_error(activations, &layer_errors)
+        };
+
         // Update weights and biases with backprop
         for layer_idx in 0..self.weights.len() {
             self.weights[layer_idx].par_iter_mut().enumerate().for_each(
@@ -716,20 +1942,100 @@ impl NeuralNetwork {
                     *bias += self.backprop_rate * layer_errors[layer_idx + 1][neuron];
                 });
         }
+        self.update_skip_weights(activations, &layer_errors, self.backprop_rate);
+        input_error
+    }
+
+    /// Turn the error at a normalized layer's output into the error at its weighted sums,
+    /// updating the normalization's scale and shift along the way
+    fn backprop_through_normalization(
+        &mut self,
+        layer_idx: usize,
+        activations: &[Vec<f64>],
+        layer_errors: &mut [Vec<f64>],
+    ) {
+        let weight_layer = layer_idx - 1;
+        if self.get_layer_norm(weight_layer).is_none()
+            && self.get_batch_norm(weight_layer).is_none()
+        {
+            return;
+        }
+        let pre_activations = self.pre_activations(weight_layer, activations);
+        let rate = self.backprop_rate;
+        if let Some(norm) = self
+            .layer_norms
+            .get_mut(weight_layer)
+            .and_then(Option::as_mut)
+        {
+            layer_errors[layer_idx] =
+                norm.backward(&pre_activations, &layer_errors[layer_idx], rate);
+        } else if let Some(norm) = self
+            .batch_norms
+            .get_mut(weight_layer)
+            .and_then(Option::as_mut)
+        {
+            layer_errors[layer_idx] =
+                norm.backward(&pre_activations, &layer_errors[layer_idx], rate);
+        }
     }
 
     /// Apply Hebbian learning rule: "neurons that fire together, wire together"
     pub fn hebbian_update(&mut self, inputs: &[f64]) {
+        self.count_train(1, || self.forward_cost() + self.update_cost(false));
         // Forward pass with history storage
         let _activations = self.forward_with_history(inputs);
 
         // Apply Hebbian updates to all layer connections
+        let before = (self.synaptic_tagging.is_some() || self.shapes_hebbian_updates())
+            .then(|| self.weights.clone());
         for layer_idx in 0..self.weights.len() {
             self.apply_hebbian_to_layer(layer_idx);
         }
+        if let Some(before) = &before {
+            for (layer_idx, before) in before.iter().enumerate() {
+                self.shape_hebbian_updates(layer_idx, before);
+            }
+        }
+        if let (Some(tagging), Some(before)) = (self.synaptic_tagging.as_mut(), before) {
+            for (layer_idx, (before, after)) in before.iter().zip(&self.weights).enumerate() {
+                tagging.observe(layer_idx, before, after);
+            }
+        }
 
         // Apply weight decay to prevent unbounded growth
         self.apply_weight_decay();
+        self.sync_conv_layers();
+    }
+
+    /// Spike-timing-dependent update of weight layer `layer`
+    ///
+    /// `pre_spikes` index neurons of layer `layer` and `post_spikes` neurons of
+    /// layer `layer + 1`; either side may come from another node, as long as
+    /// both use the same clock.
+    pub fn apply_stdp(
+        &mut self,
+        layer: usize,
+        pre_spikes: &[SpikeEvent],
+        post_spikes: &[SpikeEvent],
+        params: &StdpParams,
+    ) -> Result<(), String> {
+        if layer >= self.weights.len() {
+            return Err(format!(
+                "Weight layer {} out of range (network has {})",
+                layer,
+                self.weights.len()
+            ));
+        }
+        let before = self
+            .synaptic_tagging
+            .as_ref()
+            .map(|_| self.weights[layer].clone());
+        spikes::stdp_update(&mut self.weights[layer], pre_spikes, post_spikes, params);
+        if let (Some(tagging), Some(before)) = (self.synaptic_tagging.as_mut(), before) {
+            tagging.observe(layer, &before, &self.weights[layer]);
+        }
+        self.sync_conv_layers();
+        Ok(())
     }
 
     /// Apply Hebbian learning to a specific layer
@@ -761,6 +2067,9 @@ impl NeuralNetwork {
     ) -> f64 {
         let history1 = &self.activation_history[layer1][neuron1];
         let history2 = &self.activation_history[layer2][neuron2];
+        if let Some(robust) = &self.robust_hebbian {
+            return robust.correlation(history1, history2);
+        }
 
         // Calculate mean activations
         let mean1: f64 = history1.iter().sum::<f64>() / history1.len() as f64;
@@ -787,99 +2096,616 @@ impl NeuralNetwork {
         }
     }
 
-    /// Apply weight decay to prevent unbounded weight growth
-    fn apply_weight_decay(&mut self) {
-        for layer_weights in &mut self.weights {
-            for neuron_weights in layer_weights {
-                for weight in neuron_weights {
-                    *weight *= 1.0 - self.decay_rate;
+    /// Apply weight decay to prevent unbounded weight growth
+    fn apply_weight_decay(&mut self) {
+        for (layer_idx, layer_weights) in self.weights.iter_mut().enumerate() {
+            if let Some(tagging) = self.synaptic_tagging.as_mut() {
+                tagging.decay(layer_idx, layer_weights, self.decay_rate);
+                continue;
+            }
+            for neuron_weights in layer_weights {
+                for weight in neuron_weights {
+                    *weight *= 1.0 - self.decay_rate;
+                }
+            }
+        }
+    }
+
+    /// Train using pure Hebbian learning (unsupervised)
+    pub fn train_hebbian(&mut self, inputs: &[f64]) {
+        self.hebbian_update(inputs);
+    }
+
+    /// Train using hybrid approach: backpropagation + Hebbian learning
+    pub fn train_hybrid(&mut self, inputs: &[f64], targets: &[f64]) -> f64 {
+        // First apply backpropagation
+        let error = self.train(inputs, targets);
+
+        // Then apply Hebbian learning
+        self.hebbian_update(inputs);
+
+        error
+    }
+
+    /// Get average activation for a neuron over its history
+    pub fn get_average_activation(&self, layer: usize, neuron: usize) -> f64 {
+        let history = &self.activation_history[layer][neuron];
+        history.iter().sum::<f64>() / history.len() as f64
+    }
+
+    /// Get correlation between two neurons
+    pub fn get_neuron_correlation(
+        &self,
+        layer1: usize,
+        neuron1: usize,
+        layer2: usize,
+        neuron2: usize,
+    ) -> f64 {
+        self.calculate_correlation(layer1, neuron1, layer2, neuron2)
+    }
+
+    /// Make a prediction using the trained network
+    /// Note: If online learning is enabled, this will adapt weights during prediction
+    pub fn predict(&mut self, inputs: &[f64]) -> Vec<f64> {
+        let (_, output) = self.forward(inputs);
+        output
+    }
+
+    /// Make a prediction without any weight updates (pure inference)
+    /// This is useful when you want to test the network without adaptation
+    pub fn predict_static(&self, inputs: &[f64]) -> Vec<f64> {
+        let (_, output) = self.forward_static(inputs);
+        output
+    }
+
+    /// Whether any layer drops units while training
+    pub fn has_dropout(&self) -> bool {
+        self.dropout.iter().any(|&rate| rate > 0.0)
+    }
+
+    /// One inference pass with training-time dropout applied, without weight updates
+    ///
+    /// Each call drops a different random subset of units, so the spread of
+    /// repeated calls reflects how sure the network is (Monte Carlo dropout).
+    pub fn forward_with_dropout(&self, inputs: &[f64]) -> Vec<f64> {
+        let mut activations = vec![self.embed_inputs(inputs)];
+        for layer_idx in 0..self.weights.len() {
+            let current_layer = &activations[layer_idx];
+            let next_layer: Vec<f64> = (0..self.layers[layer_idx + 1])
+                .map(|to_neuron| {
+                    current_layer.iter().enumerate().fold(
+                        self.biases[layer_idx][to_neuron],
+                        |sum, (from_neuron, &activation)| {
+                            sum + activation * self.weights[layer_idx][from_neuron][to_neuron]
+                        },
+                    )
+                })
+                .collect();
+            let mut next_layer = self.activate(layer_idx, next_layer, &activations);
+            self.apply_dropout(layer_idx, &mut next_layer);
+            activations.push(next_layer);
+        }
+        activations.pop().unwrap()
+    }
+
+    /// Mean and variance of every output over `n_samples` passes of `forward_with_dropout`
+    ///
+    /// The variance is zero for a network without dropout; see `has_dropout`.
+    pub fn predict_with_uncertainty(
+        &self,
+        inputs: &[f64],
+        n_samples: usize,
+    ) -> (Vec<f64>, Vec<f64>) {
+        output_moments((0..n_samples.max(1)).map(|_| self.forward_with_dropout(inputs)))
+    }
+
+    /// Get network architecture information
+    pub fn info(&self) -> String {
+        let layer_info = self
+            .layers
+            .iter()
+            .map(|&size| size.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+
+        format!(
+            "Neural Network: {} (Hebbian rate: {}, mode: {:?})",
+            layer_info, self.hebbian_rate, self.learning_mode
+        )
+    }
+
+    /// Get the layer sizes
+    pub fn get_layers(&self) -> &[usize] {
+        &self.layers
+    }
+
+    /// Get the current Hebbian learning mode
+    pub fn get_learning_mode(&self) -> &HebbianLearningMode {
+        &self.learning_mode
+    }
+
+    /// Switch to a different Hebbian learning mode
+    pub fn set_learning_mode(&mut self, mode: HebbianLearningMode) {
+        self.learning_mode = mode;
+    }
+
+    /// Get a specific weight value for inspection
+    pub fn get_weight(&self, layer: usize, from_neuron: usize, to_neuron: usize) -> f64 {
+        self.weights[layer][from_neuron][to_neuron]
+    }
+
+    /// Get the weights of a layer flattened in `[from_neuron][to_neuron]` order
+    pub fn get_layer_weights(&self, layer: usize) -> Vec<f64> {
+        self.weights[layer].iter().flatten().copied().collect()
+    }
+
+    /// Get the biases of a layer
+    pub fn get_layer_biases(&self, layer: usize) -> &[f64] {
+        &self.biases[layer]
+    }
+
+    /// L2 norm of all layer weights (biases excluded)
+    pub fn weight_norm(&self) -> f64 {
+        self.weights
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|w| w * w)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Replace the weights and biases of a layer (weights flattened as in `get_layer_weights`)
+    pub fn set_layer_parameters(
+        &mut self,
+        layer: usize,
+        weights: &[f64],
+        biases: &[f64],
+    ) -> Result<(), String> {
+        if layer >= self.weights.len() {
+            return Err(format!("Layer {} does not exist", layer));
+        }
+        let (from, to) = (self.layers[layer], self.layers[layer + 1]);
+        if weights.len() != from * to || biases.len() != to {
+            return Err(format!(
+                "Layer {} expects {} weights and {} biases, got {} and {}",
+                layer,
+                from * to,
+                to,
+                weights.len(),
+                biases.len()
+            ));
+        }
+
+        for (row, chunk) in self.weights[layer].iter_mut().zip(weights.chunks(to)) {
+            row.copy_from_slice(chunk);
+        }
+        self.biases[layer].copy_from_slice(biases);
+        Ok(())
+    }
+
+    /// Initialize dense weights and biases from a trained network with a smaller or equal architecture
+    ///
+    /// Follows Net2Net: a widened hidden layer fills its extra neurons with
+    /// copies of existing ones (neuron `i` copies neuron `i % old_size`) and
+    /// splits their outgoing weights among the copies, and hidden layers beyond
+    /// `other`'s depth are inserted before the output layer as identity
+    /// mappings. Extra inputs start with zero weights and extra outputs keep
+    /// their fresh initialization. The function is preserved exactly for linear
+    /// and ReLU layers; with saturating activations it is a close starting point.
+    pub fn warm_start_from(&mut self, other: &NeuralNetwork) -> Result<(), String> {
+        let (old, new) = (&other.layers, &self.layers);
+        if new.len() < old.len() {
+            return Err(format!(
+                "Cannot warm-start {} layers from a deeper network of {} layers",
+                new.len(),
+                old.len()
+            ));
+        }
+        let has_conv = |nn: &NeuralNetwork| {
+            nn.conv_layers.iter().any(Option::is_some) || nn.pool_layers.iter().any(Option::is_some)
+        };
+        if has_conv(self) || has_conv(other) {
+            return Err("Warm starts only support dense layers".to_string());
+        }
+
+        // Old layer each new layer takes its neurons from, None for inserted layers
+        let last = new.len() - 1;
+        let roles: Vec<Option<usize>> = (0..new.len())
+            .map(|layer| match layer {
+                layer if layer == last => Some(old.len() - 1),
+                layer if layer < old.len() - 1 => Some(layer),
+                _ => None,
+            })
+            .collect();
+
+        // origins[layer][neuron]: old neuron it copies, None for new inputs and outputs
+        let mut origins: Vec<Vec<Option<usize>>> = Vec::with_capacity(new.len());
+        for (layer, &size) in new.iter().enumerate() {
+            let origin = match roles[layer] {
+                Some(role) if layer == 0 || layer == last => (0..size)
+                    .map(|neuron| (neuron < old[role]).then_some(neuron))
+                    .collect(),
+                Some(role) => (0..size).map(|neuron| Some(neuron % old[role])).collect(),
+                None => {
+                    let previous: &Vec<Option<usize>> = &origins[layer - 1];
+                    (0..size).map(|neuron| previous[neuron % previous.len()]).collect()
+                }
+            };
+            origins.push(origin);
+        }
+
+        for layer in 0..self.weights.len() {
+            let (from, to) = (&origins[layer], &origins[layer + 1]);
+            let Some(role) = roles[layer + 1] else {
+                // Inserted layer: each neuron passes on one neuron of the layer below
+                for (from_neuron, row) in self.weights[layer].iter_mut().enumerate() {
+                    for (to_neuron, weight) in row.iter_mut().enumerate() {
+                        let copied = to_neuron % from.len() == from_neuron;
+                        *weight = if copied { 1.0 } else { 0.0 };
+                    }
+                }
+                self.biases[layer].fill(0.0);
+                continue;
+            };
+
+            for (from_neuron, origin) in from.iter().enumerate() {
+                let copies = from.iter().filter(|&other| other == origin).count() as f64;
+                for (to_neuron, target) in to.iter().enumerate() {
+                    let Some(target) = target else { continue };
+                    self.weights[layer][from_neuron][to_neuron] = match origin {
+                        Some(origin) => other.weights[role - 1][*origin][*target] / copies,
+                        None => 0.0,
+                    };
+                }
+            }
+            for (bias, target) in self.biases[layer].iter_mut().zip(to) {
+                if let Some(target) = target {
+                    *bias = other.biases[role - 1][*target];
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Combine this network with `other`, for instance specialists trained on separate data
+    ///
+    /// Both need the same depth, inputs, outputs and per-layer activations.
+    /// `Concatenate` gives each hidden layer the units of both parents: the
+    /// inputs feed both halves, each parent's units only feed its own units in
+    /// the next layer, and the output layer receives half of each parent's
+    /// contribution, so the merged network starts out averaging the parents'
+    /// output pre-activations. `Average` requires identical shapes and averages
+    /// every weight and bias, skip connections and embeddings included.
+    /// Learning settings, normalization and the like come from `self`; the
+    /// result has fresh activation history and metadata, ready to be fine-tuned.
+    pub fn merge(
+        &self,
+        other: &NeuralNetwork,
+        strategy: MergeStrategy,
+    ) -> Result<NeuralNetwork, String> {
+        let has_conv = |nn: &NeuralNetwork| {
+            nn.conv_layers.iter().any(Option::is_some) || nn.pool_layers.iter().any(Option::is_some)
+        };
+        if has_conv(self) || has_conv(other) {
+            return Err("Merging only supports dense layers".to_string());
+        }
+        let depth = self.layers.len();
+        if other.layers.len() != depth
+            || self.input_size() != other.input_size()
+            || self.layers[depth - 1] != other.layers[depth - 1]
+        {
+            return Err(format!(
+                "Cannot merge layers {:?} with {:?}: depth, inputs and outputs must match",
+                self.layers, other.layers
+            ));
+        }
+        if let Some(layer) =
+            (0..depth - 1).find(|&layer| self.get_activation(layer) != other.get_activation(layer))
+        {
+            return Err(format!(
+                "Cannot merge layer {} with {:?} activation into one with {:?}",
+                layer + 1,
+                other.get_activation(layer),
+                self.get_activation(layer)
+            ));
+        }
+
+        let average = |a: &[f64], b: &[f64]| -> Vec<f64> {
+            a.iter().zip(b).map(|(a, b)| (a + b) / 2.0).collect()
+        };
+        let mut merged = self.clone();
+        match strategy {
+            MergeStrategy::Average => {
+                if self.layers != other.layers {
+                    return Err(format!(
+                        "Averaging needs identical layers, not {:?} and {:?}",
+                        self.layers, other.layers
+                    ));
+                }
+                let skips = |nn: &NeuralNetwork| -> Vec<(usize, usize)> {
+                    nn.skip_connections.iter().map(|s| (s.from, s.to)).collect()
+                };
+                if skips(self) != skips(other) {
+                    return Err("Averaging needs the same skip connections".to_string());
+                }
+                let tables = |nn: &NeuralNetwork| -> Vec<(usize, usize, usize)> {
+                    nn.embeddings
+                        .iter()
+                        .map(|e| (e.column, e.table.len(), e.table.first().map_or(0, Vec::len)))
+                        .collect()
+                };
+                if tables(self) != tables(other) {
+                    return Err("Averaging needs the same embeddings".to_string());
+                }
+
+                for (merged, other) in merged.weights.iter_mut().zip(&other.weights) {
+                    for (row, other) in merged.iter_mut().zip(other) {
+                        *row = average(row, other);
+                    }
+                }
+                for (biases, other) in merged.biases.iter_mut().zip(&other.biases) {
+                    *biases = average(biases, other);
+                }
+                for (skip, other) in merged.skip_connections.iter_mut().zip(&other.skip_connections) {
+                    for (row, other) in skip.weights.iter_mut().zip(&other.weights) {
+                        *row = average(row, other);
+                    }
+                }
+                for (embedding, other) in merged.embeddings.iter_mut().zip(&other.embeddings) {
+                    for (row, other) in embedding.table.iter_mut().zip(&other.table) {
+                        *row = average(row, other);
+                    }
+                }
+            }
+            MergeStrategy::Concatenate => {
+                for nn in [self, other] {
+                    let per_neuron = nn.layer_norms.iter().any(Option::is_some)
+                        || nn.batch_norms.iter().any(Option::is_some)
+                        || nn.target_activities.iter().any(Option::is_some)
+                        || nn.intrinsic_plasticity.iter().any(Option::is_some);
+                    if !nn.skip_connections.is_empty() || !nn.embeddings.is_empty() || per_neuron {
+                        return Err("Concatenating supports neither skip connections, embeddings, \
+                                    normalization nor per-neuron activity state"
+                            .to_string());
+                    }
+                }
+
+                // Hidden layers hold both parents' units, inputs and outputs are shared
+                let last = depth - 1;
+                let shared = |layer: usize| layer == 0 || layer == last;
+                merged.layers = (0..depth)
+                    .map(|layer| match shared(layer) {
+                        true => self.layers[layer],
+                        false => self.layers[layer] + other.layers[layer],
+                    })
+                    .collect();
+                for layer in 0..last {
+                    let (a, b) = (&self.weights[layer], &other.weights[layer]);
+                    merged.weights[layer] = match (shared(layer), shared(layer + 1)) {
+                        (true, true) => a.iter().zip(b).map(|(a, b)| average(a, b)).collect(),
+                        (true, false) => a.iter().zip(b).map(|(a, b)| [&a[..], b].concat()).collect(),
+                        (false, true) => a
+                            .iter()
+                            .chain(b)
+                            .map(|row| row.iter().map(|w| w / 2.0).collect())
+                            .collect(),
+                        (false, false) => {
+                            let (a_width, b_width) = (self.layers[layer + 1], other.layers[layer + 1]);
+                            let a_rows = a.iter().map(|row| [&row[..], &vec![0.0; b_width]].concat());
+                            let b_rows = b.iter().map(|row| [&vec![0.0; a_width], &row[..]].concat());
+                            a_rows.chain(b_rows).collect()
+                        }
+                    };
+                    let (a, b) = (&self.biases[layer], &other.biases[layer]);
+                    merged.biases[layer] = match shared(layer + 1) {
+                        true => average(a, b),
+                        false => [&a[..], b].concat(),
+                    };
+                }
+                for (layer, k) in merged.k_winners.iter_mut().enumerate() {
+                    *k = match (*k, other.k_winners.get(layer).copied().flatten()) {
+                        (Some(a), Some(b)) => Some(a + b),
+                        (k, _) => k,
+                    };
                 }
             }
         }
+        merged.activation_history = merged
+            .layers
+            .iter()
+            .map(|&size| vec![vec![0.0; merged.history_size]; size])
+            .collect();
+        merged.metadata = ModelMetadata::default();
+        Ok(merged)
     }
 
-    /// Train using pure Hebbian learning (unsupervised)
-    pub fn train_hebbian(&mut self, inputs: &[f64]) {
-        self.hebbian_update(inputs);
-    }
-
-    /// Train using hybrid approach: backpropagation + Hebbian learning
-    pub fn train_hybrid(&mut self, inputs: &[f64], targets: &[f64]) -> f64 {
-        // First apply backpropagation
-        let error = self.train(inputs, targets);
-
-        // Then apply Hebbian learning
-        self.hebbian_update(inputs);
-
-        error
-    }
-
-    /// Get average activation for a neuron over its history
-    pub fn get_average_activation(&self, layer: usize, neuron: usize) -> f64 {
-        let history = &self.activation_history[layer][neuron];
-        history.iter().sum::<f64>() / history.len() as f64
-    }
-
-    /// Get correlation between two neurons
-    pub fn get_neuron_correlation(
-        &self,
-        layer1: usize,
-        neuron1: usize,
-        layer2: usize,
-        neuron2: usize,
-    ) -> f64 {
-        self.calculate_correlation(layer1, neuron1, layer2, neuron2)
-    }
-
-    /// Make a prediction using the trained network
-    /// Note: If online learning is enabled, this will adapt weights during prediction
-    pub fn predict(&mut self, inputs: &[f64]) -> Vec<f64> {
-        let (_, output) = self.forward(inputs);
-        output
-    }
+    /// Copy weight layers `range` out as a standalone network, for instance the trained feature layers
+    ///
+    /// Layers are indexed like `get_layer_weights`, so `extract_layers(0..2)`
+    /// of a `[4, 8, 6, 2]` network is a `[4, 8, 6]` network computing the
+    /// second hidden layer. Each kept layer brings its activation, dropout,
+    /// normalization, convolution and homeostatic settings, and skip
+    /// connections running entirely inside the slice are kept; embeddings only
+    /// when the slice starts at the input. Learning settings come along, but
+    /// the per-layer statistics of synaptic tagging, robust Hebbian updates
+    /// and adaptive rates start afresh, as do activation history and metadata.
+    pub fn extract_layers(&self, range: Range<usize>) -> Result<NeuralNetwork, String> {
+        if range.is_empty() || range.end > self.weights.len() {
+            return Err(format!(
+                "Cannot extract layers {:?} from a network with {} weight layers",
+                range,
+                self.weights.len()
+            ));
+        }
+        fn slice<T: Clone>(items: &[T], range: &Range<usize>) -> Vec<T> {
+            items
+                .iter()
+                .skip(range.start)
+                .take(range.len())
+                .cloned()
+                .collect()
+        }
 
-    /// Make a prediction without any weight updates (pure inference)
-    /// This is useful when you want to test the network without adaptation
-    pub fn predict_static(&self, inputs: &[f64]) -> Vec<f64> {
-        let (_, output) = self.forward_static(inputs);
-        output
-    }
+        let mut extracted = self.clone();
+        extracted.layers = self.layers[range.start..=range.end].to_vec();
+        extracted.weights = slice(&self.weights, &range);
+        extracted.biases = slice(&self.biases, &range);
+        extracted.layer_norms = slice(&self.layer_norms, &range);
+        extracted.batch_norms = slice(&self.batch_norms, &range);
+        extracted.conv_layers = slice(&self.conv_layers, &range);
+        extracted.pool_layers = slice(&self.pool_layers, &range);
+        extracted.activation_functions = slice(&self.activation_functions, &range);
+        extracted.dropout = slice(&self.dropout, &range);
+        extracted.k_winners = slice(&self.k_winners, &range);
+        extracted.target_activities = slice(&self.target_activities, &range);
+        extracted.intrinsic_plasticity = slice(&self.intrinsic_plasticity, &range);
+        extracted.skip_connections = self
+            .skip_connections
+            .iter()
+            .filter(|skip| skip.from >= range.start && skip.to <= range.end)
+            .map(|skip| SkipConnection {
+                from: skip.from - range.start,
+                to: skip.to - range.start,
+                weights: skip.weights.clone(),
+            })
+            .collect();
+        if range.start > 0 {
+            extracted.embeddings.clear();
+        }
+        extracted.tied_weights = self.tied_weights && range == (0..self.weights.len());
 
-    /// Get network architecture information
-    pub fn info(&self) -> String {
-        let layer_info = self
+        extracted.reset_update_statistics();
+        extracted.activation_history = extracted
             .layers
             .iter()
-            .map(|&size| size.to_string())
-            .collect::<Vec<_>>()
-            .join(" -> ");
-
-        format!(
-            "Neural Network: {} (Hebbian rate: {}, mode: {:?})",
-            layer_info, self.hebbian_rate, self.learning_mode
-        )
+            .map(|&size| vec![vec![0.0; extracted.history_size]; size])
+            .collect();
+        extracted.metadata = ModelMetadata::default();
+        Ok(extracted)
     }
 
-    /// Get the layer sizes
-    pub fn get_layers(&self) -> &[usize] {
-        &self.layers
+    /// Restart the per-weight statistics of synaptic tagging, robust Hebbian updates and adaptive rates
+    fn reset_update_statistics(&mut self) {
+        self.synaptic_tagging = self.synaptic_tagging.as_ref().and_then(|tagging| {
+            SynapticTagging::new(
+                tagging.threshold,
+                tagging.protection,
+                tagging.duration,
+                tagging.smoothing,
+            )
+            .ok()
+        });
+        self.robust_hebbian = self.robust_hebbian.as_ref().and_then(|robust| {
+            RobustHebbian::new(
+                robust.winsorize,
+                robust.median_correlation,
+                robust.mad_cap,
+                robust.smoothing,
+            )
+            .ok()
+        });
+        self.adaptive_rates = self.adaptive_rates.as_ref().and_then(|rates| {
+            AdaptiveRates::new(rates.target, rates.max_gain, rates.smoothing).ok()
+        });
     }
 
-    /// Get the current Hebbian learning mode
-    pub fn get_learning_mode(&self) -> &HebbianLearningMode {
-        &self.learning_mode
-    }
+    /// Keep only the neurons `keep` of hidden layer `layer`, removing the others with their connections
+    ///
+    /// Layers are indexed like `get_layers`. The incoming and outgoing
+    /// weights, biases, normalization, intrinsic plasticity, activity
+    /// targets, skip connections and activation history of the kept neurons
+    /// are carried over, and `k_winners` is capped at the new width; the
+    /// per-weight statistics of synaptic tagging, robust Hebbian updates and
+    /// adaptive rates start afresh. Convolution, pooling and tied layers
+    /// cannot be narrowed.
+    pub fn remove_neurons(&mut self, layer: usize, keep: &[usize]) -> Result<(), String> {
+        if layer == 0 || layer + 1 >= self.layers.len() {
+            return Err(format!("Layer {} is not a hidden layer", layer));
+        }
+        let size = self.layers[layer];
+        if keep.is_empty() || keep.iter().any(|&neuron| neuron >= size) {
+            return Err(format!(
+                "Layer {} has {} neurons, cannot keep {:?}",
+                layer, size, keep
+            ));
+        }
+        let structured = |weight_layer: usize| {
+            self.get_conv_layer(weight_layer).is_some()
+                || self.get_pool_layer(weight_layer).is_some()
+        };
+        if structured(layer - 1) || structured(layer) || self.tied_weights {
+            return Err(format!(
+                "Layer {} is convolutional, pooled or tied and cannot be narrowed",
+                layer
+            ));
+        }
+        fn select(values: &[f64], keep: &[usize]) -> Vec<f64> {
+            keep.iter().map(|&neuron| values[neuron]).collect()
+        }
 
-    /// Get a specific weight value for inspection
-    pub fn get_weight(&self, layer: usize, from_neuron: usize, to_neuron: usize) -> f64 {
-        self.weights[layer][from_neuron][to_neuron]
+        // Weight layer `layer - 1` feeds the neurons, weight layer `layer` reads them
+        let fed = layer - 1;
+        for row in &mut self.weights[fed] {
+            *row = select(row, keep);
+        }
+        self.biases[fed] = select(&self.biases[fed], keep);
+        self.weights[layer] = keep
+            .iter()
+            .map(|&neuron| self.weights[layer][neuron].clone())
+            .collect();
+        if let Some(Some(norm)) = self.layer_norms.get_mut(fed) {
+            norm.gamma = select(&norm.gamma, keep);
+            norm.beta = select(&norm.beta, keep);
+        }
+        if let Some(Some(norm)) = self.batch_norms.get_mut(fed) {
+            norm.gamma = select(&norm.gamma, keep);
+            norm.beta = select(&norm.beta, keep);
+            norm.running_mean = select(&norm.running_mean, keep);
+            norm.running_var = select(&norm.running_var, keep);
+        }
+        if let Some(Some(ip)) = self.intrinsic_plasticity.get_mut(fed) {
+            ip.gain = select(&ip.gain, keep);
+            ip.offset = select(&ip.offset, keep);
+        }
+        if let Some(Some(targets)) = self.target_activities.get_mut(fed) {
+            *targets = select(targets, keep);
+        }
+        if let Some(Some(k)) = self.k_winners.get_mut(fed) {
+            *k = (*k).min(keep.len());
+        }
+        for skip in &mut self.skip_connections {
+            if skip.from == layer {
+                skip.weights = keep
+                    .iter()
+                    .map(|&neuron| skip.weights[neuron].clone())
+                    .collect();
+            }
+            if skip.to == layer {
+                for row in &mut skip.weights {
+                    *row = select(row, keep);
+                }
+            }
+        }
+        self.activation_history[layer] = keep
+            .iter()
+            .map(|&neuron| self.activation_history[layer][neuron].clone())
+            .collect();
+        self.layers[layer] = keep.len();
+        self.reset_update_statistics();
+        Ok(())
     }
 
     /// Apply online Hebbian learning to a specific layer during forward pass
     /// This is called during forward propagation when online learning is enabled
     fn apply_online_hebbian_to_layer(&mut self, layer_idx: usize, activations: &[Vec<f64>]) {
-        let from_layer = &activations[layer_idx];
-        let to_layer = &activations[layer_idx + 1];
+        let winsorized_from = self.winsorized(layer_idx, &activations[layer_idx]);
+        let winsorized_to = self.winsorized(layer_idx + 1, &activations[layer_idx + 1]);
+        let from_layer = winsorized_from.as_deref().unwrap_or(&activations[layer_idx]);
+        let to_layer = winsorized_to.as_deref().unwrap_or(&activations[layer_idx + 1]);
+
+        let before = (self.synaptic_tagging.is_some() || self.shapes_hebbian_updates())
+            .then(|| self.weights[layer_idx].clone());
 
         // Apply the selected Hebbian learning rule with reduced learning rate for stability
         let online_rate = self.hebbian_rate * 0.1; // Reduce rate for online learning stability
@@ -917,6 +2743,13 @@ impl NeuralNetwork {
             }
         }
 
+        if let Some(before) = &before {
+            self.shape_hebbian_updates(layer_idx, before);
+        }
+        if let (Some(tagging), Some(before)) = (self.synaptic_tagging.as_mut(), before) {
+            tagging.observe(layer_idx, &before, &self.weights[layer_idx]);
+        }
+
         // Apply light weight decay to prevent runaway growth
         self.apply_online_weight_decay(layer_idx);
     }
@@ -1002,7 +2835,7 @@ impl NeuralNetwork {
                 let post_activity = to_layer[to_neuron];
 
                 // BCM threshold (simplified - using target activity)
-                let threshold = self.target_activity;
+                let threshold = self.get_target_activity(layer_idx, to_neuron);
                 let bcm_factor = post_activity * (post_activity - threshold);
 
                 let weight_update = rate * bcm_factor * pre_activity;
@@ -1033,7 +2866,12 @@ impl NeuralNetwork {
 
     /// Apply light weight decay to a specific layer during online learning
     fn apply_online_weight_decay(&mut self, layer_idx: usize) {
-        let decay = 1.0 - (self.decay_rate * 0.1); // Lighter decay for online learning
+        let rate = self.decay_rate * 0.1; // Lighter decay for online learning
+        if let Some(tagging) = self.synaptic_tagging.as_mut() {
+            tagging.decay(layer_idx, &mut self.weights[layer_idx], rate);
+            return;
+        }
+        let decay = 1.0 - rate;
         for neuron_weights in &mut self.weights[layer_idx] {
             for weight in neuron_weights {
                 *weight *= decay;
@@ -1050,7 +2888,8 @@ impl NeuralNetwork {
 
             for neuron_idx in 0..layer_activations.len() {
                 let current_activity = layer_activations[neuron_idx];
-                let activity_error = self.target_activity - current_activity;
+                let activity_error =
+                    self.get_target_activity(layer_idx - 1, neuron_idx) - current_activity;
 
                 // Adjust bias to regulate activity level
                 if layer_idx > 0 {
@@ -1078,21 +2917,260 @@ impl NeuralNetwork {
     pub fn num_parameters(&self) -> usize {
         let mut total = 0;
 
-        // Count weights
-        for layer_weights in &self.weights {
-            for neuron_weights in layer_weights {
-                total += neuron_weights.len();
+        for layer in 0..self.weights.len() {
+            // Convolutions share their kernels and biases across positions
+            if let Some(conv) = self.get_conv_layer(layer) {
+                total += conv.num_parameters();
+                continue;
+            }
+            if self.get_pool_layer(layer).is_some() {
+                continue;
+            }
+
+            // Count weights, tied decoder layers reuse the encoder's
+            if !(self.tied_weights && layer >= self.weights.len() / 2) {
+                for neuron_weights in &self.weights[layer] {
+                    total += neuron_weights.len();
+                }
             }
-        }
 
-        // Count biases
-        for layer_biases in &self.biases {
-            total += layer_biases.len();
+            // Count biases
+            total += self.biases[layer].len();
         }
 
+        // Count embedding tables
+        total += self
+            .embeddings
+            .iter()
+            .map(Embedding::num_parameters)
+            .sum::<usize>();
+
+        // Count skip connection weights
+        total += self
+            .skip_connections
+            .iter()
+            .map(|skip| skip.weights.iter().map(Vec::len).sum::<usize>())
+            .sum::<usize>();
+
+        // Count normalization scale and shift
+        total += self
+            .layer_norms
+            .iter()
+            .flatten()
+            .map(LayerNorm::num_parameters)
+            .sum::<usize>();
+        total += self
+            .batch_norms
+            .iter()
+            .flatten()
+            .map(BatchNorm::num_parameters)
+            .sum::<usize>();
+
         total
     }
 
+    /// Copy of the trained weights for the `std`-free inference core
+    ///
+    /// Only plain feed-forward networks convert: skip connections,
+    /// normalization, embeddings, pooling, k-winners-take-all and intrinsic
+    /// plasticity have no counterpart in `InferenceModel`. Convolutions are
+    /// exported as their unrolled dense weights.
+    pub fn to_inference_model(&self) -> Result<InferenceModel, String> {
+        let unsupported = [
+            (!self.skip_connections.is_empty(), "skip connections"),
+            (!self.embeddings.is_empty(), "embeddings"),
+            (
+                self.layer_norms.iter().any(Option::is_some),
+                "layer normalization",
+            ),
+            (
+                self.batch_norms.iter().any(Option::is_some),
+                "batch normalization",
+            ),
+            (
+                self.pool_layers.iter().any(Option::is_some),
+                "pooling layers",
+            ),
+            (
+                self.k_winners.iter().any(Option::is_some),
+                "k-winners-take-all",
+            ),
+            (
+                self.intrinsic_plasticity.iter().any(Option::is_some),
+                "intrinsic plasticity",
+            ),
+        ];
+        if let Some((_, feature)) = unsupported.iter().find(|(used, _)| *used) {
+            return Err(format!("The inference core does not support {}", feature));
+        }
+
+        let layers = (0..self.weights.len())
+            .map(|layer| {
+                let inputs = self.layers[layer];
+                let weights = (0..self.layers[layer + 1])
+                    .flat_map(|to| (0..inputs).map(move |from| (from, to)))
+                    .map(|(from, to)| self.weights[layer][from][to] as f32)
+                    .collect();
+                let biases = self.biases[layer].iter().map(|&b| b as f32).collect();
+                InferenceLayer::new(inputs, weights, biases, self.get_activation(layer))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        InferenceModel::new(layers)
+    }
+
+    /// `to_inference_model` with weights quantized to `precision`
+    pub fn to_quantized_model(&self, precision: Precision) -> Result<QuantizedModel, String> {
+        Ok(QuantizedModel::quantize(
+            &self.to_inference_model()?,
+            precision,
+        ))
+    }
+
+    /// Compare `model` on `samples` against this network's f64 `forward_static`
+    pub fn quantization_accuracy(
+        &self,
+        model: &QuantizedModel,
+        samples: &[Vec<f64>],
+    ) -> AccuracyReport {
+        let mut report = AccuracyReport::default();
+        for inputs in samples {
+            let reference = self.forward_static(inputs).0;
+            let inputs: Vec<f32> = inputs.iter().map(|&x| x as f32).collect();
+            report.record(&reference, &model.forward(&inputs));
+        }
+        report
+    }
+
+    /// Count the work of `forward`, `train` and related calls from now on
+    ///
+    /// Counting is cheap but off by default; disabling drops the counters.
+    /// `forward_with_history` and other building blocks are only counted
+    /// through the calls that use them.
+    pub fn set_cost_accounting(&mut self, enabled: bool) {
+        match (enabled, &self.cost_meter) {
+            (true, None) => self.cost_meter = Some(CostMeter::default()),
+            (false, _) => self.cost_meter = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Work counted since cost accounting was enabled or reset, if it is enabled
+    pub fn cost_report(&self) -> Option<CostReport> {
+        self.cost_meter.as_ref().map(CostMeter::report)
+    }
+
+    /// Zero the cost counters, keeping accounting enabled if it was
+    pub fn reset_cost_report(&mut self) {
+        if self.cost_meter.is_some() {
+            self.cost_meter = Some(CostMeter::default());
+        }
+    }
+
+    /// Arithmetic work of one inference pass
+    ///
+    /// Every weight layer computes its dense weighted sums, including
+    /// convolution and pooling layers, whose weights are unrolled.
+    pub fn forward_cost(&self) -> OpCost {
+        let mut cost = OpCost::default();
+        for layer in 0..self.weights.len() {
+            let inputs = self.layers[layer] as u64;
+            let outputs = self.layers[layer + 1] as u64;
+            cost.macs += inputs * outputs;
+            cost.activations += outputs;
+            if self.get_pool_layer(layer).is_some() {
+                cost.flops += inputs;
+                continue;
+            }
+
+            let mut per_neuron = 1 + self.get_activation(layer).estimated_flops();
+            if self.get_layer_norm(layer).is_some() {
+                per_neuron += 7;
+            }
+            if self.get_batch_norm(layer).is_some() {
+                per_neuron += 4;
+            }
+            if self.get_intrinsic_plasticity(layer).is_some() {
+                per_neuron += 2;
+            }
+            cost.flops += outputs * per_neuron;
+        }
+        for skip in &self.skip_connections {
+            cost.macs += (self.layers[skip.from] * self.layers[skip.to]) as u64;
+        }
+        cost.flops += 2 * cost.macs;
+        cost
+    }
+
+    /// Arithmetic work of one `train` call: a forward pass plus the weight updates
+    pub fn train_cost(&self) -> OpCost {
+        self.forward_cost() + self.update_cost(self.use_backprop)
+    }
+
+    /// One MAC per weight for the Hebbian update, two more with backpropagation
+    fn update_cost(&self, backprop: bool) -> OpCost {
+        let mut weights: u64 = self.layers.windows(2).map(|w| (w[0] * w[1]) as u64).sum();
+        for skip in &self.skip_connections {
+            weights += (self.layers[skip.from] * self.layers[skip.to]) as u64;
+        }
+        let macs = if backprop { 3 * weights } else { weights };
+        OpCost {
+            macs,
+            activations: 0,
+            flops: 2 * macs,
+        }
+    }
+
+    fn count_forward(&self, cost: impl FnOnce() -> OpCost) {
+        if let Some(meter) = &self.cost_meter {
+            meter.record_forward(1, cost());
+        }
+    }
+
+    fn count_train(&self, calls: u64, cost: impl FnOnce() -> OpCost) {
+        if let Some(meter) = &self.cost_meter {
+            meter.record_train(calls, cost());
+        }
+    }
+
+    /// Time every weight layer and learning phase of `forward`, `train` and related calls
+    ///
+    /// Off by default since every layer reads the clock; disabling drops the timers.
+    pub fn set_profiling(&mut self, enabled: bool) {
+        match (enabled, &self.profiler) {
+            (true, None) => self.profiler = Some(Profiler::new(self.weights.len())),
+            (false, _) => self.profiler = None,
+            (true, Some(_)) => {}
+        }
+    }
+
+    /// Time spent per layer and phase since profiling was enabled or reset, if it is enabled
+    pub fn timing_report(&self) -> Option<TimingReport> {
+        self.profiler.as_ref().map(Profiler::report)
+    }
+
+    /// Zero the timers, keeping profiling enabled if it was
+    pub fn reset_timing_report(&mut self) {
+        if self.profiler.is_some() {
+            self.profiler = Some(Profiler::new(self.weights.len()));
+        }
+    }
+
+    fn profile_start(&self) -> Option<Instant> {
+        self.profiler.as_ref().map(|_| Instant::now())
+    }
+
+    fn profile_layer(&self, layer: usize, started: Option<Instant>) {
+        if let (Some(profiler), Some(started)) = (&self.profiler, started) {
+            profiler.record_layer(layer, started.elapsed());
+        }
+    }
+
+    fn profile_phase(&self, phase: Phase, started: Option<Instant>) {
+        if let (Some(profiler), Some(started)) = (&self.profiler, started) {
+            profiler.record_phase(phase, started.elapsed());
+        }
+    }
+
     /// Get Hebbian learning rate
     pub fn get_hebbian_rate(&self) -> f64 {
         self.hebbian_rate
@@ -1118,18 +3196,97 @@ impl NeuralNetwork {
         self.decay_rate = rate;
     }
 
+    /// Protect weights with recent large updates from part of the weight decay
+    ///
+    /// Applies to the decay of `hebbian_update` and of online learning.
+    pub fn set_synaptic_tagging(&mut self, tagging: SynapticTagging) {
+        self.synaptic_tagging = Some(tagging);
+    }
+
+    /// Disable synaptic tagging, decaying every weight at the full rate again
+    pub fn clear_synaptic_tagging(&mut self) {
+        self.synaptic_tagging = None;
+    }
+
+    /// Synaptic tagging state, if enabled
+    pub fn get_synaptic_tagging(&self) -> Option<&SynapticTagging> {
+        self.synaptic_tagging.as_ref()
+    }
+
+    /// Guard Hebbian updates against outlier activations
+    ///
+    /// Applies to `train`, `train_unsupervised`, `hebbian_update` and online
+    /// learning; skip connections see winsorized activations but are not capped.
+    pub fn set_robust_hebbian(&mut self, robust: RobustHebbian) {
+        self.robust_hebbian = Some(robust);
+    }
+
+    /// Disable robust updates, learning from raw activations again
+    pub fn clear_robust_hebbian(&mut self) {
+        self.robust_hebbian = None;
+    }
+
+    /// Robust update settings and running update scales, if enabled
+    pub fn get_robust_hebbian(&self) -> Option<&RobustHebbian> {
+        self.robust_hebbian.as_ref()
+    }
+
+    /// Scale each connection's Hebbian updates by the variance of its recent ones
+    ///
+    /// Applies wherever robust updates do, before their caps.
+    pub fn set_adaptive_rates(&mut self, rates: AdaptiveRates) {
+        self.adaptive_rates = Some(rates);
+    }
+
+    /// Disable adaptive rates, giving every connection the plain Hebbian rate again
+    pub fn clear_adaptive_rates(&mut self) {
+        self.adaptive_rates = None;
+    }
+
+    /// Adaptive rate state, if enabled
+    pub fn get_adaptive_rates(&self) -> Option<&AdaptiveRates> {
+        self.adaptive_rates.as_ref()
+    }
+
+    /// Provenance saved with the model
+    pub fn metadata(&self) -> &ModelMetadata {
+        &self.metadata
+    }
+
+    /// Replace the provenance saved with the model
+    pub fn set_metadata(&mut self, metadata: ModelMetadata) {
+        self.metadata = metadata;
+    }
+
+    pub fn metadata_mut(&mut self) -> &mut ModelMetadata {
+        &mut self.metadata
+    }
+
     /// Parallel batch training - train on multiple samples simultaneously
+    ///
+    /// Networks with batch normalization take a true batch step instead: the
+    /// batch is normalized with its own statistics and backpropagation applies
+    /// the batch-averaged update once.
     pub fn train_batch(&mut self, batch: &[(Vec<f64>, Vec<f64>)]) -> f64 {
         if batch.is_empty() {
             return 0.0;
         }
+        if self.batch_norms.iter().any(Option::is_some) {
+            self.count_train(batch.len() as u64, || {
+                self.train_cost() * batch.len() as u64
+            });
+            return self.train_batch_normalized(batch);
+        }
+
+        // The error pass below comes on top of the passes of `train`
+        self.count_train(0, || self.forward_cost() * batch.len() as u64);
 
         // Process batch in parallel and collect errors
         let total_error: f64 = batch
             .par_iter()
             .map(|(inputs, targets)| {
                 // Each thread gets its own copy of the network for forward pass
-                let activations = self.forward_all_layers(inputs);
+                let activations = self.all_layer_activations(inputs);
 
                 // Calculate error for this sample
                 let output = &activations[activations.len() - 1];
@@ -1140,15 +3297,212 @@ impl NeuralNetwork {
                 }
                 sample_error / 2.0
             })
-            .sum();
+            .sum();
+
+        // Sequential weight updates (to avoid race conditions)
+        // In practice, you'd accumulate gradients and apply them once
+        for (inputs, targets) in batch {
+            self.train(inputs, targets);
+        }
+
+        total_error / batch.len() as f64
+    }
+
+    /// Batch training step for networks with batch normalization
+    fn train_batch_normalized(&mut self, batch: &[(Vec<f64>, Vec<f64>)]) -> f64 {
+        // Forward the whole batch layer by layer; activations[sample][layer]
+        let mut activations: Vec<Vec<Vec<f64>>> = batch
+            .iter()
+            .map(|(inputs, _)| vec![self.embed_inputs(inputs)])
+            .collect();
+        let mut batch_pre_activations = vec![None; self.weights.len()];
+
+        for (layer_idx, batch_sums) in batch_pre_activations.iter_mut().enumerate() {
+            if let Some(pool) = self.get_pool_layer(layer_idx) {
+                for sample in activations.iter_mut() {
+                    let mut pooled = pool.forward(&sample[layer_idx]);
+                    self.apply_dropout(layer_idx, &mut pooled);
+                    sample.push(pooled);
+                }
+                continue;
+            }
+
+            let sums: Vec<Vec<f64>> = activations
+                .par_iter()
+                .map(|sample| self.pre_activations(layer_idx, sample))
+                .collect();
+
+            let normalized = match self.batch_norms.get_mut(layer_idx).and_then(Option::as_mut) {
+                Some(norm) => {
+                    let normalized = norm.forward_batch(&sums);
+                    *batch_sums = Some(sums);
+                    normalized
+                }
+                None => sums
+                    .into_iter()
+                    .map(|sum| self.normalize(layer_idx, sum))
+                    .collect(),
+            };
+
+            let activation = self.get_activation(layer_idx);
+            for (sample, values) in activations.iter_mut().zip(normalized) {
+                let mut outputs: Vec<f64> =
+                    values.into_iter().map(|x| activation.apply(x)).collect();
+                self.apply_k_winners(layer_idx, &mut outputs);
+                self.apply_dropout(layer_idx, &mut outputs);
+                sample.push(outputs);
+            }
+        }
+
+        let total_error: f64 = activations
+            .iter()
+            .zip(batch)
+            .map(|(sample, (_, targets))| {
+                let output = &sample[sample.len() - 1];
+                output
+                    .iter()
+                    .zip(targets)
+                    .map(|(o, t)| (t - o).powi(2))
+                    .sum::<f64>()
+                    / 2.0
+            })
+            .sum();
+
+        // Hebbian learning and homeostasis see each sample as in `train`
+        for sample in &activations {
+            for (layer_idx, layer_activations) in sample.iter().enumerate() {
+                self.store_activations(layer_idx, layer_activations);
+            }
+            self.apply_hebbian_learning(sample);
+            self.apply_homeostatic_regulation(sample);
+        }
+
+        if self.use_backprop {
+            let targets: Vec<&[f64]> = batch.iter().map(|(_, t)| t.as_slice()).collect();
+            let input_errors =
+                self.apply_batch_backpropagation(&activations, &targets, &batch_pre_activations);
+            let scale = self.backprop_rate / batch.len() as f64;
+            for ((inputs, _), input_error) in batch.iter().zip(input_errors) {
+                self.update_embeddings(inputs, &input_error, scale);
+            }
+        }
+        self.sync_conv_layers();
+
+        total_error / batch.len() as f64
+    }
+
+    /// Backpropagation averaged over a batch, differentiating through batch statistics
+    ///
+    /// Returns each sample's input-layer error when the network has embeddings.
+    fn apply_batch_backpropagation(
+        &mut self,
+        activations: &[Vec<Vec<f64>>],
+        targets: &[&[f64]],
+        batch_pre_activations: &[Option<Vec<Vec<f64>>>],
+    ) -> Vec<Vec<f64>> {
+        let output_layer_idx = self.layers.len() - 1;
+        let rate = self.backprop_rate;
+
+        // errors[sample][layer]
+        let mut errors: Vec<Vec<Vec<f64>>> = activations
+            .iter()
+            .zip(targets)
+            .map(|(sample, targets)| {
+                let mut layer_errors = vec![vec![]; self.layers.len()];
+                layer_errors[output_layer_idx] = sample[output_layer_idx]
+                    .iter()
+                    .zip(targets.iter())
+                    .enumerate()
+                    .map(|(i, (&a, &t))| (t - a) * self.neuron_derivative(output_layer_idx, i, a))
+                    .collect();
+                layer_errors
+            })
+            .collect();
+
+        for layer_idx in (1..self.layers.len()).rev() {
+            let weight_layer = layer_idx - 1;
+
+            // Through this layer's normalization
+            if let (Some(norm), Some(sums)) = (
+                self.batch_norms
+                    .get_mut(weight_layer)
+                    .and_then(Option::as_mut),
+                &batch_pre_activations[weight_layer],
+            ) {
+                let grads: Vec<Vec<f64>> = errors.iter().map(|e| e[layer_idx].clone()).collect();
+                for (sample, grad) in errors
+                    .iter_mut()
+                    .zip(norm.backward_batch(sums, &grads, rate))
+                {
+                    sample[layer_idx] = grad;
+                }
+            } else {
+                for (sample, layer_errors) in activations.iter().zip(errors.iter_mut()) {
+                    self.backprop_through_normalization(layer_idx, sample, layer_errors);
+                }
+            }
 
-        // Sequential weight updates (to avoid race conditions)
-        // In practice, you'd accumulate gradients and apply them once
-        for (inputs, targets) in batch {
-            self.train(inputs, targets);
+            // Into the previous hidden layer
+            if layer_idx > 1 {
+                let pool = self.get_pool_layer(weight_layer);
+                for (sample, layer_errors) in activations.iter().zip(errors.iter_mut()) {
+                    let pooled = pool
+                        .map(|pool| pool.backward(&sample[weight_layer], &layer_errors[layer_idx]));
+                    layer_errors[weight_layer] = (0..self.layers[weight_layer])
+                        .map(|neuron| {
+                            let forward_error = match &pooled {
+                                Some(pooled) => pooled[neuron],
+                                None => (0..self.layers[layer_idx])
+                                    .map(|next| {
+                                        layer_errors[layer_idx][next]
+                                            * self.weights[weight_layer][neuron][next]
+                                    })
+                                    .sum::<f64>(),
+                            };
+                            let error =
+                                forward_error + self.skip_error(weight_layer, neuron, layer_errors);
+                            error
+                                * self.neuron_derivative(
+                                    weight_layer,
+                                    neuron,
+                                    sample[weight_layer][neuron],
+                                )
+                        })
+                        .collect();
+                }
+            }
         }
 
-        total_error / batch.len() as f64
+        let input_errors: Vec<Vec<f64>> = if self.embeddings.is_empty() {
+            Vec::new()
+        } else {
+            activations
+                .iter()
+                .zip(&errors)
+                .map(|(sample, layer_errors)| self.input_error(sample, layer_errors))
+                .collect()
+        };
+
+        // Batch-averaged weight and bias updates
+        let scale = rate / activations.len() as f64;
+        for layer_idx in 0..self.weights.len() {
+            for (sample, layer_errors) in activations.iter().zip(&errors) {
+                for (from_neuron, weight_row) in self.weights[layer_idx].iter_mut().enumerate() {
+                    for (to_neuron, weight) in weight_row.iter_mut().enumerate() {
+                        *weight += scale
+                            * layer_errors[layer_idx + 1][to_neuron]
+                            * sample[layer_idx][from_neuron];
+                    }
+                }
+                for (neuron, bias) in self.biases[layer_idx].iter_mut().enumerate() {
+                    *bias += scale * layer_errors[layer_idx + 1][neuron];
+                }
+            }
+        }
+        for (sample, layer_errors) in activations.iter().zip(&errors) {
+            self.update_skip_weights(sample, layer_errors, scale);
+        }
+        input_errors
     }
 
     /// Parallel batch forward propagation
@@ -1176,8 +3530,34 @@ impl NeuralNetwork {
         }
     }
 
+    /// Export the activation history of every neuron to a CSV or Parquet file
+    ///
+    /// Each remembered activation becomes one `layer, neuron, time, activation`
+    /// row, with time 0 the oldest step, ready for pandas or Polars.
+    ///
+    /// # Example
+    /// ```
+    /// use neural_network::{HistoryFormat, NeuralNetwork};
+    ///
+    /// let mut nn = NeuralNetwork::new(2, 3, 1, 0.1);
+    /// nn.forward_with_history(&[0.5, 0.8]);
+    /// let path = std::env::temp_dir().join("activations.parquet");
+    /// nn.export_activation_history(&path, HistoryFormat::Parquet)
+    ///     .expect("Failed to export activation history");
+    /// ```
+    pub fn export_activation_history<P: AsRef<Path>>(
+        &self,
+        path: P,
+        format: HistoryFormat,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        crate::activation_export::write(&self.activation_history, path, format)
+    }
+
     /// Save the neural network state to a JSON file
     ///
+    /// The file is replaced atomically (see `atomic_file::write_atomic`), so a
+    /// crash mid-save leaves the previous version in place.
+    ///
     /// # Arguments
     /// * `path` - The file path where the network state will be saved
     ///
@@ -1193,12 +3573,14 @@ impl NeuralNetwork {
     /// ```
     pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
         let json = serde_json::to_string_pretty(self)?;
-        fs::write(path, json)?;
+        atomic_file::write_atomic(path, json.as_bytes())?;
         Ok(())
     }
 
     /// Load a neural network state from a JSON file
     ///
+    /// A file that ends early is reported as `LoadError::Truncated`.
+    ///
     /// # Arguments
     /// * `path` - The file path from which to load the network state
     ///
@@ -1213,13 +3595,17 @@ impl NeuralNetwork {
     ///     .expect("Failed to load network");
     /// ```
     pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
         let json = fs::read_to_string(path)?;
-        let network = serde_json::from_str(&json)?;
+        let network =
+            serde_json::from_str(&json).map_err(|e| atomic_file::check_json(path, e))?;
         Ok(network)
     }
 
     /// Save the neural network state to a binary file (more compact than JSON)
     ///
+    /// Replaced atomically like `save_to_file`.
+    ///
     /// # Arguments
     /// * `path` - The file path where the network state will be saved
     ///
@@ -1238,12 +3624,40 @@ impl NeuralNetwork {
         path: P,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let encoded = bincode::serialize(self)?;
-        fs::write(path, encoded)?;
+        atomic_file::write_atomic(path, &encoded)?;
         Ok(())
     }
 
+    /// Save the network in the memory-mappable format read by `MappedModel`
+    ///
+    /// Weights and biases are stored as raw little-endian floats after a small
+    /// header, so loading them needs no deserialization.
+    pub fn save_to_mmap<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        let skeleton = Self {
+            weights: Vec::new(),
+            biases: Vec::new(),
+            ..self.clone()
+        };
+        crate::mmap_model::write(&skeleton, &self.weights, &self.biases, path)
+    }
+
+    /// Attach weights and biases to a network saved without them
+    pub(crate) fn with_parameters(
+        self,
+        weights: Vec<Vec<Vec<f64>>>,
+        biases: Vec<Vec<f64>>,
+    ) -> Self {
+        Self {
+            weights,
+            biases,
+            ..self
+        }
+    }
+
     /// Load a neural network state from a binary file
     ///
+    /// A file that ends early is reported as `LoadError::Truncated`.
+    ///
     /// # Arguments
     /// * `path` - The file path from which to load the network state
     ///
@@ -1258,11 +3672,56 @@ impl NeuralNetwork {
     ///     .expect("Failed to load network");
     /// ```
     pub fn load_from_binary<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref();
         let data = fs::read(path)?;
-        let network = bincode::deserialize(&data)?;
+        let network =
+            bincode::deserialize(&data).map_err(|e| atomic_file::check_bincode(path, e))?;
         Ok(network)
     }
 
+    /// Save the network from async code without blocking the runtime
+    ///
+    /// Writes bincode for `.bin` and `.bin.gz` paths and JSON otherwise,
+    /// atomically and optionally compressed (see `async_io::write_atomic`).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use neural_network::{Compression, NeuralNetwork};
+    ///
+    /// # async fn checkpoint(nn: &NeuralNetwork) {
+    /// nn.save_async("checkpoint.json.gz", Compression::Gzip)
+    ///     .await
+    ///     .expect("Failed to save network");
+    /// # }
+    /// ```
+    pub async fn save_async<P: AsRef<Path>>(
+        &self,
+        path: P,
+        compression: crate::async_io::Compression,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let bytes = if crate::async_io::is_binary(path) {
+            bincode::serialize(self)?
+        } else {
+            serde_json::to_vec_pretty(self)?
+        };
+        crate::async_io::write_atomic(path, bytes, compression).await?;
+        Ok(())
+    }
+
+    /// Load a network saved by `save_async`, `save_to_file` or `save_to_binary` without blocking the runtime
+    pub async fn load_async<P: AsRef<Path>>(
+        path: P,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let path = path.as_ref();
+        let bytes = crate::async_io::read(path).await?;
+        Ok(if crate::async_io::is_binary(path) {
+            bincode::deserialize(&bytes).map_err(|e| atomic_file::check_bincode(path, e))?
+        } else {
+            serde_json::from_slice(&bytes).map_err(|e| atomic_file::check_json(path, e))?
+        })
+    }
+
     /// Export network metadata and architecture information
     ///
     /// # Returns
@@ -1308,9 +3767,28 @@ impl NeuralNetwork {
     }
 }
 
+/// Mean and (population) variance of every output over a set of forward passes
+pub(crate) fn output_moments<I>(samples: I) -> (Vec<f64>, Vec<f64>)
+where
+    I: IntoIterator<Item = Vec<f64>>,
+{
+    let samples: Vec<Vec<f64>> = samples.into_iter().collect();
+    let count = samples.len().max(1) as f64;
+    let width = samples.first().map_or(0, Vec::len);
+    let mean: Vec<f64> = (0..width)
+        .map(|i| samples.iter().map(|s| s[i]).sum::<f64>() / count)
+        .collect();
+    let variance = (0..width)
+        .map(|i| samples.iter().map(|s| (s[i] - mean[i]).powi(2)).sum::<f64>() / count)
+        .collect();
+    (mean, variance)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::convolution::PoolKind;
+    use crate::network_composer::NetworkComposer;
 
     #[test]
     fn test_neural_network_creation() {
@@ -1388,6 +3866,236 @@ mod tests {
         assert_eq!(nn.num_parameters(), 13);
     }
 
+    #[test]
+    fn test_cost_accounting() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let pass = nn.forward_cost();
+        assert_eq!(pass.macs, 9);
+        assert_eq!(pass.activations, 4);
+        assert_eq!(
+            pass.flops,
+            2 * 9 + 4 * (1 + Activation::Sigmoid.estimated_flops())
+        );
+
+        assert_eq!(nn.cost_report(), None);
+        nn.set_cost_accounting(true);
+        nn.forward_static(&[0.1, 0.2]);
+        nn.forward_static(&[0.3, 0.4]);
+        nn.train(&[0.1, 0.2], &[1.0]);
+        let report = nn.cost_report().unwrap();
+        assert_eq!(report.forward.calls, 2);
+        assert_eq!(report.forward.per_call(), pass);
+        assert_eq!(report.train.calls, 1);
+        assert_eq!(report.train.total, nn.train_cost());
+
+        // Batches pay for their extra error pass
+        nn.reset_cost_report();
+        let batch = vec![(vec![0.1, 0.2], vec![1.0]); 2];
+        nn.train_batch(&batch);
+        let report = nn.cost_report().unwrap();
+        assert_eq!(report.train.calls, 2);
+        assert_eq!(report.train.total, (nn.train_cost() + pass) * 2);
+        assert_eq!(report.forward.calls, 0);
+
+        nn.set_cost_accounting(false);
+        assert_eq!(nn.cost_report(), None);
+    }
+
+    #[test]
+    fn test_warm_start_preserves_function() {
+        let mut old = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        old.set_activation(0, Activation::Relu);
+        let inputs = [0.3, 0.8];
+        let expected = old.predict_static(&inputs);
+
+        let mut wider = NeuralNetwork::with_layers(&[2, 5, 1], 0.1);
+        wider.set_activation(0, Activation::Relu);
+        wider.warm_start_from(&old).unwrap();
+        assert!((wider.predict_static(&inputs)[0] - expected[0]).abs() < 1e-12);
+
+        let mut deeper = NeuralNetwork::with_layers(&[2, 4, 4, 1], 0.1);
+        deeper.set_activation(0, Activation::Relu);
+        deeper.set_activation(1, Activation::Relu);
+        deeper.warm_start_from(&old).unwrap();
+        assert!((deeper.predict_static(&inputs)[0] - expected[0]).abs() < 1e-12);
+
+        // New inputs are ignored until trained
+        let mut more_inputs = NeuralNetwork::with_layers(&[3, 3, 1], 0.1);
+        more_inputs.set_activation(0, Activation::Relu);
+        more_inputs.warm_start_from(&old).unwrap();
+        let output = more_inputs.predict_static(&[0.3, 0.8, 0.9]);
+        assert!((output[0] - expected[0]).abs() < 1e-12);
+
+        assert!(old.warm_start_from(&deeper).is_err());
+    }
+
+    #[test]
+    fn test_merge_networks() {
+        let build = |hidden: usize| {
+            let mut nn = NeuralNetwork::with_layers(&[2, hidden, 1], 0.1);
+            nn.set_activation(0, Activation::Relu);
+            nn.set_activation(1, Activation::Linear);
+            nn
+        };
+        let (a, b) = (build(3), build(4));
+        let inputs = [0.3, 0.8];
+        let output = |nn: &NeuralNetwork| nn.forward_static(&inputs).0[0];
+        let mean = (output(&a) + output(&b)) / 2.0;
+
+        let stacked = a.merge(&b, MergeStrategy::Concatenate).unwrap();
+        assert_eq!(stacked.get_layers(), &[2, 7, 1]);
+        assert!((output(&stacked) - mean).abs() < 1e-12);
+
+        let other = build(3);
+        let averaged = a.merge(&other, MergeStrategy::Average).unwrap();
+        assert_eq!(averaged.get_layers(), &[2, 3, 1]);
+        let weight = (a.get_weight(0, 1, 2) + other.get_weight(0, 1, 2)) / 2.0;
+        assert!((averaged.get_weight(0, 1, 2) - weight).abs() < 1e-12);
+        let bias = (a.get_layer_biases(1)[0] + other.get_layer_biases(1)[0]) / 2.0;
+        assert!((averaged.get_layer_biases(1)[0] - bias).abs() < 1e-12);
+
+        assert!(a.merge(&b, MergeStrategy::Average).is_err());
+        assert!(a
+            .merge(&NeuralNetwork::with_layers(&[3, 3, 1], 0.1), MergeStrategy::Concatenate)
+            .is_err());
+        assert!(a
+            .merge(&NeuralNetwork::with_layers(&[2, 3, 1], 0.1), MergeStrategy::Average)
+            .is_err());
+    }
+
+    #[test]
+    fn test_extract_layers() {
+        let mut nn = NeuralNetwork::with_layers(&[4, 8, 6, 2], 0.1);
+        nn.set_activation(1, Activation::Relu);
+        let inputs = [0.1, 0.9, 0.4, 0.7];
+
+        let features = nn.extract_layers(0..2).unwrap();
+        assert_eq!(features.get_layers(), &[4, 8, 6]);
+        let head = nn.extract_layers(2..3).unwrap();
+        assert_eq!(head.get_layers(), &[6, 2]);
+        assert_eq!(head.get_layer_weights(0), nn.get_layer_weights(2));
+        assert_eq!(features.get_activation(1), Activation::Relu);
+
+        // Chaining the slices computes the whole network
+        let hidden = features.forward_static(&inputs).0;
+        assert_eq!(head.forward_static(&hidden).0, nn.forward_static(&inputs).0);
+
+        let mut composer = NetworkComposer::new();
+        composer
+            .add_network("features".to_string(), features)
+            .unwrap();
+        composer.add_network("head".to_string(), head).unwrap();
+        composer
+            .connect_networks("features", "head", (0..6).collect(), (0..6).collect())
+            .unwrap();
+
+        assert!(nn.extract_layers(1..1).is_err());
+        assert!(nn.extract_layers(2..4).is_err());
+    }
+
+    #[test]
+    fn test_timing_profile() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        assert_eq!(nn.timing_report(), None);
+
+        nn.set_profiling(true);
+        nn.set_backprop_enabled(true, 0.1);
+        nn.forward_static(&[0.1, 0.2]);
+        nn.train(&[0.1, 0.2], &[1.0]);
+        let report = nn.timing_report().unwrap();
+        assert_eq!(report.layer_ns.len(), 2);
+        assert!(report.layer_ns.iter().all(|&ns| ns > 0));
+        assert!(report.phase(Phase::Backprop) > std::time::Duration::ZERO);
+        let layers: u64 = report.layer_ns.iter().sum();
+        assert_eq!(report.phase(Phase::Forward).as_nanos() as u64, layers);
+
+        nn.reset_timing_report();
+        assert_eq!(nn.timing_report().unwrap().total(), std::time::Duration::ZERO);
+        nn.set_profiling(false);
+        assert_eq!(nn.timing_report(), None);
+    }
+
+    #[test]
+    fn test_inference_model_matches_forward_static() {
+        let mut nn = NeuralNetwork::with_layers(&[3, 4, 2], 0.1);
+        nn.set_activation(0, Activation::Tanh);
+        let model = nn.to_inference_model().unwrap();
+        assert_eq!((model.input_size(), model.output_size()), (3, 2));
+
+        let inputs = [0.2, -0.7, 0.9];
+        let expected = nn.forward_static(&inputs).0;
+        let inputs_f32: Vec<f32> = inputs.iter().map(|&x| x as f32).collect();
+        for (ours, reference) in model.forward(&inputs_f32).iter().zip(&expected) {
+            assert!((*ours as f64 - reference).abs() < 1e-5);
+        }
+
+        nn.set_layer_norm(0, true);
+        assert!(nn.to_inference_model().is_err());
+    }
+
+    #[test]
+    fn test_custom_activation_roundtrip() {
+        fn softplus(x: f64) -> f64 {
+            x.max(0.0) + (-x.abs()).exp().ln_1p()
+        }
+        fn softplus_derivative(output: f64) -> f64 {
+            1.0 - (-output).exp()
+        }
+        let custom = crate::activation_registry::register(
+            "nn_test_softplus",
+            1,
+            softplus,
+            softplus_derivative,
+        )
+        .unwrap();
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        nn.set_activation(0, custom);
+        nn.set_backprop_enabled(true, 0.1);
+        nn.train(&[0.3, -0.2], &[0.5]);
+        assert!(nn.to_inference_model().is_err());
+
+        let inputs = [0.4, 0.9];
+        let filename = "test_custom_activation_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename);
+        let renamed = fs::read_to_string(filename)
+            .unwrap()
+            .replace("nn_test_softplus@1", "nn_test_softplus@2");
+        fs::write(filename, renamed).unwrap();
+        let unregistered = NeuralNetwork::load_from_file(filename);
+        let _ = fs::remove_file(filename);
+
+        let loaded = loaded.expect("Failed to load network");
+        assert_eq!(loaded.get_activation(0), custom);
+        assert_eq!(loaded.predict_static(&inputs), nn.predict_static(&inputs));
+        let error = unregistered.unwrap_err().to_string();
+        assert!(error.contains("nn_test_softplus@2 is not registered"), "{}", error);
+    }
+
+    #[test]
+    fn test_quantized_model_accuracy() {
+        let mut nn = NeuralNetwork::with_layers(&[3, 6, 2], 0.1);
+        nn.set_activation(0, Activation::Tanh);
+        let samples = vec![
+            vec![0.2, -0.7, 0.9],
+            vec![1.0, 0.5, -0.3],
+            vec![0.0, 0.1, 0.4],
+        ];
+
+        let half = nn.to_quantized_model(Precision::Half).unwrap();
+        let half_report = nn.quantization_accuracy(&half, &samples);
+        assert_eq!(half_report.samples, 3);
+        assert!(half_report.max_abs_error < 1e-2);
+
+        let int8 = nn.to_quantized_model(Precision::Int8).unwrap();
+        let int8_report = nn.quantization_accuracy(&int8, &samples);
+        assert!(int8_report.max_abs_error < 0.05);
+        assert!(half_report.mean_abs_error() <= int8_report.mean_abs_error());
+
+        nn.set_layer_norm(0, true);
+        assert!(nn.to_quantized_model(Precision::Int8).is_err());
+    }
+
     #[test]
     fn test_training_compatibility() {
         let mut nn = NeuralNetwork::new(2, 3, 1, 0.5);
@@ -1428,6 +4136,37 @@ mod tests {
         assert_eq!(updated_history[19], 0.5);
     }
 
+    #[test]
+    fn test_export_activation_history() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut nn = NeuralNetwork::with_layers(&[2, 2, 1], 0.05);
+        nn.forward_with_history(&[0.5, 0.8]);
+        let dir = std::env::temp_dir();
+        let csv_path = dir.join(format!("benny-activations-{}.csv", std::process::id()));
+        let parquet_path = dir.join(format!("benny-activations-{}.parquet", std::process::id()));
+
+        nn.export_activation_history(&csv_path, HistoryFormat::Csv)
+            .unwrap();
+        let csv = std::fs::read_to_string(&csv_path).unwrap();
+        std::fs::remove_file(&csv_path).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "layer,neuron,time,activation");
+        // 5 neurons remembering 20 steps each
+        assert_eq!(lines.len(), 1 + 5 * 20);
+        assert_eq!(lines[20], "0,0,19,0.5");
+
+        nn.export_activation_history(&parquet_path, HistoryFormat::Parquet)
+            .unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(&parquet_path).unwrap()).unwrap();
+        std::fs::remove_file(&parquet_path).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows(), 5 * 20);
+        assert_eq!(reader.metadata().file_metadata().schema_descr().num_columns(), 4);
+
+        assert_eq!(HistoryFormat::from_path("run.PARQUET"), Some(HistoryFormat::Parquet));
+        assert!("xlsx".parse::<HistoryFormat>().is_err());
+    }
+
     #[test]
     fn test_hebbian_learning() {
         let mut nn = NeuralNetwork::with_layers(&[2, 2, 1], 0.1);
@@ -1445,6 +4184,238 @@ mod tests {
         assert_ne!(initial_weight, final_weight);
     }
 
+    #[test]
+    fn test_per_layer_target_activity() {
+        let mut base = NeuralNetwork::with_layers(&[2, 3, 2], 0.0);
+        base.configure_hebbian(0.0, 0.0, 0.1, 0.5);
+        base.set_decay_rate(0.0);
+
+        let mut targeted = base.clone();
+        targeted.set_layer_target_activity(1, 0.9).unwrap();
+        targeted
+            .set_neuron_target_activities(0, &[0.5, 0.1, 0.5])
+            .unwrap();
+        assert_eq!(targeted.get_target_activity(1, 1), 0.9);
+        assert_eq!(targeted.get_target_activity(0, 1), 0.1);
+        assert!(targeted.set_neuron_target_activities(0, &[0.5]).is_err());
+        assert!(targeted.set_layer_target_activity(2, 0.5).is_err());
+
+        // Offline and online regulation move biases towards the per-neuron targets
+        for (online, rate) in [(false, 0.1), (true, 0.01)] {
+            let mut a = base.clone();
+            let mut b = targeted.clone();
+            if online {
+                a.forward_with_online_learning(&[0.3, 0.7]);
+                b.forward_with_online_learning(&[0.3, 0.7]);
+            } else {
+                a.train(&[0.3, 0.7], &[0.0, 0.0]);
+                b.train(&[0.3, 0.7], &[0.0, 0.0]);
+            }
+            let shift =
+                |layer: usize, neuron: usize| b.biases[layer][neuron] - a.biases[layer][neuron];
+            assert!((shift(1, 0) - rate * 0.4).abs() < 1e-12);
+            assert!((shift(0, 1) + rate * 0.4).abs() < 1e-12);
+            assert!(shift(0, 0).abs() < 1e-12);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_stream() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let mut expected = nn.clone();
+        let inputs = vec![vec![0.1, 0.9], vec![0.8, 0.2], vec![0.5, 0.5]];
+
+        let outputs: Vec<Vec<f64>> = nn
+            .process_stream(futures_util::stream::iter(inputs.clone()))
+            .collect()
+            .await;
+        for (input, output) in inputs.iter().zip(&outputs) {
+            assert_eq!(*output, expected.forward_with_online_learning(input).0);
+        }
+        assert_eq!(nn.get_layer_weights(0), expected.get_layer_weights(0));
+    }
+
+    #[test]
+    fn test_k_winners_sparse_code() {
+        let mut nn =
+            NeuralNetwork::with_layers_and_mode(&[4, 8, 2], 0.1, HebbianLearningMode::Competitive);
+        let inputs = [0.9, 0.1, 0.4, 0.7];
+        let dense = nn.layer_sparseness(&inputs);
+        assert_eq!(dense.len(), 2);
+        assert_eq!(dense[0].active_fraction, 1.0);
+
+        assert!(nn.set_k_winners(1, Some(1)).is_err());
+        assert!(nn.set_k_winners(0, Some(0)).is_err());
+        nn.set_k_winners(0, Some(2)).unwrap();
+        for _ in 0..20 {
+            nn.forward_with_online_learning(&inputs);
+        }
+        let hidden = &nn.forward_all_layers(&inputs)[1];
+        assert_eq!(hidden.iter().filter(|&&x| x != 0.0).count(), 2);
+        let sparse = nn.layer_sparseness(&inputs);
+        assert_eq!(sparse[0].active_fraction, 0.25);
+        assert!(sparse[0].hoyer > dense[0].hoyer);
+        assert!(sparse[0].gini > dense[0].gini);
+
+        nn.set_k_winners(0, None).unwrap();
+        assert_eq!(nn.layer_sparseness(&inputs)[0].active_fraction, 1.0);
+    }
+
+    #[test]
+    fn test_stdp_from_remote_spikes() {
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.0);
+        let initial = network.weights.clone();
+        let pre = spikes::encode_spikes(&[0.9, 0.1], 0.5, 1_000);
+        let post = [SpikeEvent {
+            neuron: 2,
+            timestamp: 3_000,
+        }];
+        network
+            .apply_stdp(0, &pre, &post, &StdpParams::default())
+            .unwrap();
+        assert!(network.weights[0][0][2] > initial[0][0][2]);
+        network.weights[0][0][2] = initial[0][0][2];
+        assert_eq!(network.weights, initial);
+        assert!(network
+            .apply_stdp(2, &pre, &post, &StdpParams::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_synaptic_tagging_retains_learned_weights() {
+        let mut plain = NeuralNetwork::with_layers(&[2, 2], 0.5);
+        plain.set_decay_rate(0.5);
+        plain.set_online_learning(true);
+        let mut tagged = plain.clone();
+        tagged.set_synaptic_tagging(SynapticTagging::new(1e-3, 0.0, 100, 0.5).unwrap());
+
+        for _ in 0..10 {
+            plain.forward(&[0.9, 0.8]);
+            tagged.forward(&[0.9, 0.8]);
+        }
+        assert!(tagged.get_synaptic_tagging().unwrap().tagged_count() > 0);
+
+        // Without learning, only decay acts on the weights
+        plain.set_hebbian_rate(0.0);
+        tagged.set_hebbian_rate(0.0);
+        let learned = tagged.weights.clone();
+        for _ in 0..50 {
+            plain.forward(&[0.9, 0.8]);
+            tagged.forward(&[0.9, 0.8]);
+        }
+        assert!(tagged.weight_norm() > plain.weight_norm());
+        let tagging = tagged.get_synaptic_tagging().unwrap();
+        for (from, row) in learned[0].iter().enumerate() {
+            for (to, &weight) in row.iter().enumerate() {
+                if tagging.is_tagged(0, from, to) {
+                    assert_eq!(tagged.weights[0][from][to], weight);
+                }
+            }
+        }
+
+        tagged.clear_synaptic_tagging();
+        assert!(tagged.get_synaptic_tagging().is_none());
+    }
+
+    #[test]
+    fn test_robust_hebbian_resists_outliers() {
+        let mut plain = NeuralNetwork::with_layers(&[3, 2], 0.5);
+        plain
+            .set_layer_parameters(0, &[0.1, 0.2, 0.3, 0.1, 0.2, 0.3], &[0.0, 0.0])
+            .unwrap();
+        plain.set_decay_rate(0.0);
+        plain.set_online_learning(true);
+        let mut robust = plain.clone();
+        robust.set_robust_hebbian(RobustHebbian::new(Some(0.1), true, Some(3.0), 0.5).unwrap());
+
+        for step in 0..30 {
+            let noise = (step % 3) as f64 * 0.05;
+            plain.forward(&[0.5 + noise, 0.4, 0.6 - noise]);
+            robust.forward(&[0.5 + noise, 0.4, 0.6 - noise]);
+        }
+        let (plain_before, robust_before) = (plain.weights.clone(), robust.weights.clone());
+        plain.forward(&[500.0, 0.4, 0.6]);
+        robust.forward(&[500.0, 0.4, 0.6]);
+
+        let largest_change = |before: &[Vec<Vec<f64>>], after: &[Vec<Vec<f64>>]| {
+            before
+                .iter()
+                .flatten()
+                .flatten()
+                .zip(after.iter().flatten().flatten())
+                .map(|(old, new)| (new - old).abs())
+                .fold(0.0, f64::max)
+        };
+        let plain_change = largest_change(&plain_before, &plain.weights);
+        let robust_change = largest_change(&robust_before, &robust.weights);
+        assert!(robust_change * 100.0 < plain_change);
+        assert!(robust.get_robust_hebbian().unwrap().update_scale(0).is_some());
+
+        // Correlation-driven updates stay bounded too
+        robust.hebbian_update(&[500.0, 0.4, 0.6]);
+        assert!(robust.weights.iter().flatten().flatten().all(|w| w.abs() < 10.0));
+
+        robust.clear_robust_hebbian();
+        assert!(robust.get_robust_hebbian().is_none());
+    }
+
+    #[test]
+    fn test_adaptive_rates_favour_quiet_connections() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 1], 0.1);
+        nn.set_layer_parameters(0, &[0.5, 0.5], &[0.0]).unwrap();
+        nn.set_decay_rate(0.0);
+        nn.set_online_learning(true);
+        nn.set_adaptive_rates(AdaptiveRates::new(1e-3, 10.0, 0.05).unwrap());
+
+        // Input 0 fires constantly, input 1 only at the end
+        for _ in 0..50 {
+            nn.forward(&[0.9, 0.0]);
+        }
+        let rates = nn.get_adaptive_rates().unwrap();
+        assert!(rates.gain(0, 0, 0) < 1.0);
+        assert_eq!(rates.gain(0, 1, 0), 10.0);
+
+        let before = nn.weights.clone();
+        nn.forward(&[0.9, 0.9]);
+        let busy = nn.weights[0][0][0] - before[0][0][0];
+        let quiet = nn.weights[0][1][0] - before[0][1][0];
+        assert!(quiet > busy * 3.0);
+
+        nn.clear_adaptive_rates();
+        assert!(nn.get_adaptive_rates().is_none());
+    }
+
+    #[test]
+    fn test_intrinsic_plasticity_adapts_gain() {
+        let inputs = [0.2, 0.9, 0.4];
+        let mut nn = NeuralNetwork::with_layers(&[3, 4, 2], 0.0);
+        let plain = nn.clone();
+        nn.set_intrinsic_plasticity(0, 0.2, 0.05).unwrap();
+        assert!(nn.set_intrinsic_plasticity(2, 0.2, 0.05).is_err());
+        assert!(nn.set_intrinsic_plasticity(0, 1.5, 0.05).is_err());
+        assert_eq!(
+            nn.forward_static(&inputs).0,
+            plain.forward_static(&inputs).0
+        );
+
+        for _ in 0..50 {
+            nn.train_unsupervised(&inputs);
+        }
+        let ip = nn.get_intrinsic_plasticity(0).unwrap();
+        assert!(ip.gain.iter().all(|&g| g > 0.0 && g != 1.0));
+
+        let restored: NeuralNetwork =
+            bincode::deserialize(&bincode::serialize(&nn).unwrap()).unwrap();
+        assert_eq!(restored.get_intrinsic_plasticity(0), Some(ip));
+        assert_eq!(
+            restored.forward_static(&inputs).0,
+            nn.forward_static(&inputs).0
+        );
+
+        nn.clear_intrinsic_plasticity(0);
+        assert!(nn.get_intrinsic_plasticity(0).is_none());
+    }
+
     #[test]
     fn test_neuron_correlation() {
         let mut nn = NeuralNetwork::with_layers(&[2, 2, 1], 0.1);
@@ -1670,4 +4641,481 @@ mod tests {
         // Clean up
         let _ = fs::remove_file(filename);
     }
+
+    #[test]
+    fn test_layer_norm_forward_and_serialization() {
+        let mut nn = NeuralNetwork::with_layers(&[3, 6, 4, 2], 0.05);
+        nn.set_layer_norm(0, true);
+        nn.set_layer_norm(1, true);
+        assert!(nn.get_layer_norm(0).is_some());
+        assert!(nn.get_layer_norm(2).is_none());
+        assert_eq!(
+            nn.num_parameters(),
+            3 * 6 + 6 + 6 * 4 + 4 + 4 * 2 + 2 + 2 * (6 + 4)
+        );
+
+        let inputs = vec![5.0, -3.0, 8.0];
+        let output = nn.predict_static(&inputs);
+        assert!(output.iter().all(|&o| o > 0.0 && o < 1.0));
+
+        let filename = "test_layer_norm_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename).expect("Failed to load network");
+        let _ = fs::remove_file(filename);
+        assert_eq!(loaded.get_layer_norm(1), nn.get_layer_norm(1));
+        assert_eq!(loaded.predict_static(&inputs), output);
+    }
+
+    #[test]
+    fn test_layer_norm_backprop_reduces_error() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 4, 4, 1], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+        nn.set_layer_norm(0, true);
+        nn.set_layer_norm(1, true);
+
+        let inputs = vec![0.9, 0.1];
+        let initial = nn.train(&inputs, &[1.0]);
+        let mut last = initial;
+        for _ in 0..200 {
+            last = nn.train(&inputs, &[1.0]);
+        }
+        assert!(
+            last < initial,
+            "error did not decrease: {} -> {}",
+            initial,
+            last
+        );
+        assert_ne!(nn.get_layer_norm(1).unwrap().beta, vec![0.0; 4]);
+    }
+
+    #[test]
+    fn test_batch_norm_training_and_inference() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 4, 1], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+        nn.set_batch_norm(0, true);
+        assert!(nn.get_batch_norm(0).is_some());
+
+        nn.set_layer_norm(0, true);
+        assert!(nn.get_batch_norm(0).is_none());
+        nn.set_batch_norm(0, true);
+        assert!(nn.get_layer_norm(0).is_none());
+
+        let batch = vec![
+            (vec![0.0, 0.0], vec![0.0]),
+            (vec![0.0, 1.0], vec![1.0]),
+            (vec![1.0, 0.0], vec![1.0]),
+            (vec![1.0, 1.0], vec![1.0]),
+        ];
+        let initial = nn.train_batch(&batch);
+        let mut last = initial;
+        for _ in 0..300 {
+            last = nn.train_batch(&batch);
+        }
+        assert!(
+            last < initial,
+            "error did not decrease: {} -> {}",
+            initial,
+            last
+        );
+
+        let norm = nn.get_batch_norm(0).unwrap();
+        assert!(norm.running_mean.iter().any(|&m| m != 0.0));
+
+        let filename = "test_batch_norm_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename).expect("Failed to load network");
+        let _ = fs::remove_file(filename);
+        let loaded_norm = loaded.get_batch_norm(0).unwrap();
+        for (orig, loaded) in norm.running_var.iter().zip(&loaded_norm.running_var) {
+            assert!((orig - loaded).abs() < 1e-10);
+        }
+        for (inputs, _) in &batch {
+            let expected = nn.predict_static(inputs);
+            for (orig, loaded) in expected.iter().zip(loaded.predict_static(inputs)) {
+                assert!((orig - loaded).abs() < 1e-10);
+            }
+        }
+    }
+
+    #[test]
+    fn test_skip_connection_forward_and_validation() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 3, 1], 0.05);
+        assert!(nn.add_skip_connection(0, 1).is_err());
+        assert!(nn.add_skip_connection(1, 4).is_err());
+        let before = nn.num_parameters();
+        let inputs = vec![0.4, 0.9];
+        let (without_skip, _) = nn.forward_static(&inputs);
+
+        nn.add_skip_connection(0, 3).unwrap();
+        assert!(nn.add_skip_connection(0, 3).is_err());
+        assert_eq!(nn.num_parameters(), before + 2);
+
+        // Zero skip weights leave the output unchanged; non-zero weights change it
+        nn.skip_connections[0].weights = vec![vec![0.0]; 2];
+        let (zeroed, _) = nn.forward_static(&inputs);
+        assert!((zeroed[0] - without_skip[0]).abs() < 1e-12);
+        nn.skip_connections[0].weights = vec![vec![2.0]; 2];
+        let (with_skip, _) = nn.forward_static(&inputs);
+        assert!(with_skip[0] > without_skip[0]);
+        // forward_all_layers sums in parallel, so only agrees up to rounding
+        assert!((nn.forward_all_layers(&inputs)[3][0] - with_skip[0]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_skip_connection_learning() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 3, 3, 1], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+        nn.add_skip_connection(0, 3).unwrap();
+        nn.add_skip_connection(1, 3).unwrap();
+        let initial_skip = nn.skip_connections()[0].clone();
+
+        let inputs = vec![0.9, 0.2];
+        let initial = nn.train(&inputs, &[0.05]);
+        let mut last = initial;
+        for _ in 0..200 {
+            last = nn.train(&inputs, &[0.05]);
+        }
+        assert!(
+            last < initial,
+            "error did not decrease: {} -> {}",
+            initial,
+            last
+        );
+        assert_ne!(nn.skip_connections()[0], initial_skip);
+
+        // Hebbian updates reach the skip weights too
+        let mut hebbian = NeuralNetwork::with_layers(&[2, 2, 2], 0.1);
+        hebbian.add_skip_connection(0, 2).unwrap();
+        let before = hebbian.skip_connections()[0].clone();
+        hebbian.train_unsupervised(&[1.0, 1.0]);
+        assert_ne!(hebbian.skip_connections()[0], before);
+    }
+
+    #[test]
+    fn test_conv_layer_stays_tied_through_learning() {
+        // 1 channel 4x4 image -> 2 feature maps of 3x3 -> 1 output
+        let spec = ConvSpec::conv2d(1, (4, 4), 2, (2, 2), 1, 0);
+        let mut nn = NeuralNetwork::with_layers(&[16, 18, 1], 0.05);
+        assert!(nn
+            .set_conv_layer(0, ConvSpec::conv2d(1, (4, 4), 1, (2, 2), 1, 0))
+            .is_err());
+        nn.set_conv_layer(0, spec).unwrap();
+        assert_eq!(nn.num_parameters(), (2 * 4 + 2) + (18 + 1));
+        nn.set_backprop_enabled(true, 0.3);
+
+        let image: Vec<f64> = (0..16).map(|i| (i % 5) as f64 / 4.0).collect();
+        for _ in 0..10 {
+            nn.train(&image, &[1.0]);
+            nn.train_unsupervised(&image);
+        }
+
+        let conv = nn.get_conv_layer(0).unwrap().clone();
+        let (weights, biases) = conv.unroll();
+        assert_eq!(nn.weights[0], weights);
+        assert_eq!(nn.biases[0], biases);
+
+        let filename = "test_conv_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename).expect("Failed to load network");
+        let _ = fs::remove_file(filename);
+        assert_eq!(loaded.get_conv_layer(0).unwrap().spec, spec);
+    }
+
+    #[test]
+    fn test_tied_autoencoder_weights() {
+        let mut nn = NeuralNetwork::with_layers(&[4, 2, 4], 0.05);
+        assert!(NeuralNetwork::with_layers(&[4, 2, 3], 0.05)
+            .set_tied_weights(true)
+            .is_err());
+        assert!(NeuralNetwork::with_layers(&[4, 2, 2, 4], 0.05)
+            .set_tied_weights(true)
+            .is_err());
+        nn.set_tied_weights(true).unwrap();
+        assert_eq!(nn.num_parameters(), 4 * 2 + 2 + 4);
+        nn.set_backprop_enabled(true, 0.3);
+
+        let transposed = |nn: &NeuralNetwork| {
+            (0..4).all(|from| (0..2).all(|to| nn.weights[0][from][to] == nn.weights[1][to][from]))
+        };
+        assert!(transposed(&nn));
+        let before = nn.weights[0].clone();
+        let sample = [0.9, 0.1, 0.8, 0.2];
+        for _ in 0..10 {
+            nn.train(&sample, &sample);
+            nn.train_unsupervised(&sample);
+        }
+        assert_ne!(nn.weights[0], before);
+        assert!(transposed(&nn));
+        assert!(nn.add_embedding(0, 5, 3).is_err());
+
+        let filename = "test_tied_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename).expect("Failed to load network");
+        let _ = fs::remove_file(filename);
+        assert!(loaded.has_tied_weights());
+    }
+
+    #[test]
+    fn test_pool_layer_forward_backprop_and_serialization() {
+        // 1x4x4 image -> 2 conv maps of 3x3 -> max pool 2x2 stride 1 -> 2x2x2 -> 1 output
+        let mut nn = NeuralNetwork::with_layers(&[16, 18, 8, 1], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+        nn.set_conv_layer(0, ConvSpec::conv2d(1, (4, 4), 2, (2, 2), 1, 0))
+            .unwrap();
+        let pool = PoolSpec::pool2d(PoolKind::Max, 2, (3, 3), (2, 2), 1);
+        assert!(nn
+            .set_pool_layer(1, PoolSpec { stride: 2, ..pool })
+            .is_err());
+        nn.set_pool_layer(1, pool).unwrap();
+        assert!(nn.add_skip_connection(0, 2).is_err());
+        assert_eq!(nn.num_parameters(), (2 * 4 + 2) + (8 + 1));
+
+        let image: Vec<f64> = (0..16).map(|i| ((i * 7) % 11) as f64 / 10.0).collect();
+        let activations = nn.forward_all_layers(&image);
+        assert_eq!(activations[2], pool.forward(&activations[1]));
+
+        let initial = nn.train(&image, &[0.9]);
+        let mut last = initial;
+        for _ in 0..100 {
+            last = nn.train(&image, &[0.9]);
+        }
+        assert!(
+            last < initial,
+            "error did not decrease: {} -> {}",
+            initial,
+            last
+        );
+        assert!(nn.weights[1].iter().flatten().all(|&w| w == 0.0));
+
+        let filename = "test_pool_network.json";
+        nn.save_to_file(filename).expect("Failed to save network");
+        let loaded = NeuralNetwork::load_from_file(filename).expect("Failed to load network");
+        let _ = fs::remove_file(filename);
+        assert_eq!(loaded.get_pool_layer(1), Some(&pool));
+        let expected = nn.predict_static(&image);
+        for (orig, loaded) in expected.iter().zip(loaded.predict_static(&image)) {
+            assert!((orig - loaded).abs() < 1e-10);
+        }
+    }
+
+    #[test]
+    fn test_embedding_widens_input_and_learns() {
+        let mut nn = NeuralNetwork::with_layers(&[3, 4, 1], 0.0);
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+        assert!(nn.add_embedding(3, 5, 2).is_err());
+        nn.add_embedding(1, 5, 3).unwrap();
+        assert!(nn.add_embedding(1, 5, 3).is_err());
+        assert_eq!(nn.get_layers()[0], 5);
+        assert_eq!(nn.input_size(), 3);
+        assert_eq!(nn.weights[0].len(), 5);
+        assert_eq!(nn.num_parameters(), 5 * 4 + 4 + 4 + 1 + 5 * 3);
+
+        let inputs = vec![0.3, 2.0, 0.7];
+        let embedded = nn.forward_all_layers(&inputs)[0].clone();
+        assert_eq!(embedded[0], 0.3);
+        assert_eq!(&embedded[1..4], nn.embeddings()[0].lookup(2.0));
+        assert_eq!(embedded[4], 0.7);
+
+        let before = nn.embeddings()[0].clone();
+        for _ in 0..20 {
+            nn.train(&inputs, &[1.0]);
+        }
+        let after = &nn.embeddings()[0];
+        assert_ne!(after.table[2], before.table[2]);
+        assert_eq!(after.table[0], before.table[0]);
+    }
+
+    #[test]
+    fn test_layer_specs_build_heterogeneous_stack() {
+        let specs = vec![
+            LayerSpec::Input {
+                n: 16,
+                shape: Some((1, 4, 4)),
+            },
+            LayerSpec::Conv {
+                out_channels: 2,
+                kernel: (3, 3),
+                stride: 1,
+                padding: 1,
+                activation: Activation::Relu,
+            },
+            LayerSpec::Pool {
+                kind: PoolKind::Max,
+                kernel: (2, 2),
+                stride: 2,
+            },
+            LayerSpec::Dense {
+                n: 3,
+                activation: Activation::Tanh,
+            },
+            LayerSpec::LayerNorm,
+            LayerSpec::Dropout { rate: 0.2 },
+            LayerSpec::KWinners { k: 2 },
+            LayerSpec::Dense {
+                n: 1,
+                activation: Activation::Sigmoid,
+            },
+        ];
+        let nn =
+            NeuralNetwork::from_layer_specs(&specs, 0.1, HebbianLearningMode::Classic).unwrap();
+        assert_eq!(nn.get_layers(), &vec![16, 32, 8, 3, 1]);
+        assert!(nn.get_conv_layer(0).is_some());
+        assert!(nn.get_pool_layer(1).is_some());
+        assert_eq!(nn.get_activation(0), Activation::Relu);
+        assert_eq!(nn.get_activation(2), Activation::Tanh);
+        assert!(nn.get_layer_norm(2).is_some());
+        assert_eq!(nn.get_dropout(2), 0.2);
+        assert_eq!(nn.get_dropout(3), 0.0);
+        assert_eq!(nn.get_k_winners(2), Some(2));
+
+        let simple = NeuralNetwork::from_layer_specs(
+            &LayerSpec::from_sizes(&[2, 4, 1]),
+            0.1,
+            HebbianLearningMode::Classic,
+        )
+        .unwrap();
+        assert_eq!(simple.get_layers(), &vec![2, 4, 1]);
+        assert_eq!(simple.get_activation(1), Activation::Sigmoid);
+
+        let dense = LayerSpec::Dense {
+            n: 2,
+            activation: Activation::Sigmoid,
+        };
+        let invalid = [
+            vec![dense.clone()],
+            vec![LayerSpec::Input { n: 2, shape: None }],
+            vec![
+                LayerSpec::Input { n: 2, shape: None },
+                LayerSpec::Dropout { rate: 0.5 },
+                dense.clone(),
+            ],
+            vec![
+                LayerSpec::Input {
+                    n: 3,
+                    shape: Some((1, 2, 2)),
+                },
+                dense,
+            ],
+        ];
+        for specs in invalid {
+            assert!(
+                NeuralNetwork::from_layer_specs(&specs, 0.1, HebbianLearningMode::Classic).is_err(),
+                "{:?}",
+                specs
+            );
+        }
+    }
+
+    #[test]
+    fn test_predict_with_uncertainty() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 16, 1], 0.0);
+        assert!(!nn.has_dropout());
+        let (mean, variance) = nn.predict_with_uncertainty(&[0.3, 0.7], 20);
+        assert!((mean[0] - nn.forward_static(&[0.3, 0.7]).0[0]).abs() < 1e-12);
+        assert!(variance[0] < 1e-20);
+
+        nn.set_dropout(0, 0.5);
+        assert!(nn.has_dropout());
+        let (mean, variance) = nn.predict_with_uncertainty(&[0.3, 0.7], 200);
+        assert_eq!(mean.len(), 1);
+        assert!(variance[0] > 0.0);
+        // Deterministic inference is unaffected
+        assert_eq!(nn.forward_static(&[0.3, 0.7]), nn.forward_static(&[0.3, 0.7]));
+    }
+
+    #[test]
+    fn test_tanh_layer_learns() {
+        let specs = vec![
+            LayerSpec::Input { n: 2, shape: None },
+            LayerSpec::Dense {
+                n: 4,
+                activation: Activation::Tanh,
+            },
+            LayerSpec::Dense {
+                n: 1,
+                activation: Activation::Sigmoid,
+            },
+        ];
+        let mut nn =
+            NeuralNetwork::from_layer_specs(&specs, 0.0, HebbianLearningMode::Classic).unwrap();
+        nn.set_hebbian_rate(0.0);
+        nn.set_decay_rate(0.0);
+        nn.set_backprop_enabled(true, 0.5);
+
+        let data = [([0.0, 1.0], 1.0), ([1.0, 0.0], 0.0)];
+        let error = |nn: &NeuralNetwork| -> f64 {
+            data.iter()
+                .map(|(x, y)| (nn.forward_static(x).0[0] - y).powi(2))
+                .sum()
+        };
+        let before = error(&nn);
+        for _ in 0..200 {
+            for (x, y) in &data {
+                nn.train(x, &[*y]);
+            }
+        }
+        assert!(error(&nn) < before);
+        let hidden = nn.forward_all_layers(&[0.0, 1.0])[1].clone();
+        assert!(hidden.iter().all(|h| (-1.0..=1.0).contains(h)));
+    }
+
+    #[test]
+    fn test_extreme_weights_do_not_produce_nan() {
+        for mode in [
+            HebbianLearningMode::Classic,
+            HebbianLearningMode::Competitive,
+            HebbianLearningMode::Oja,
+            HebbianLearningMode::BCM,
+            HebbianLearningMode::AntiHebbian,
+            HebbianLearningMode::Hybrid,
+        ] {
+            let mut nn = NeuralNetwork::with_layers_and_mode(&[4, 3, 1], 0.1, mode);
+            nn.set_backprop_enabled(true, 0.1);
+            nn.set_layer_parameters(
+                0,
+                &[
+                    f64::MAX,
+                    -f64::MAX,
+                    1e300,
+                    f64::MAX,
+                    f64::MAX,
+                    -1e300,
+                    -f64::MAX,
+                    -f64::MAX,
+                    1e300,
+                    -f64::MAX,
+                    f64::MAX,
+                    -1e300,
+                ],
+                &[1e300, -1e300, 0.0],
+            )
+            .unwrap();
+            nn.set_layer_parameters(1, &[1e200, -1e200, 1e200], &[0.0])
+                .unwrap();
+
+            for _ in 0..5 {
+                nn.train(&[1.0, 1.0, 1.0, 1.0], &[1.0]);
+            }
+            let output = nn.forward_static(&[1.0, 1.0, 1.0, 1.0]).0;
+            assert!(output.iter().all(|y| y.is_finite()), "{:?}", mode);
+            for layer in 0..2 {
+                assert!(
+                    nn.get_layer_weights(layer).iter().all(|w| !w.is_nan()),
+                    "{:?} layer {}",
+                    mode,
+                    layer
+                );
+            }
+        }
+    }
 }
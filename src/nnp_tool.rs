@@ -0,0 +1,276 @@
+use crate::address::{AddressPreference, BindOptions};
+use crate::distributed_network::{
+    capabilities, message_flags, DistributedNetwork, MessagePayload, MessageType, NetworkId,
+    NetworkMessage, ProtocolError,
+};
+use crate::transport::{NnpListener, NnpStream};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+/// Message types that can be built by hand for `benny nnp send`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum SendKind {
+    Forward,
+    Backward,
+    Heartbeat,
+    Info,
+    Ping,
+    Disconnect,
+}
+
+impl SendKind {
+    fn carries_data(self) -> bool {
+        matches!(self, SendKind::Forward | SendKind::Backward)
+    }
+}
+
+/// Build a message of `kind`, with `data` as the forward activations or backward gradients of `layer`
+pub fn build_message(
+    kind: SendKind,
+    layer: u8,
+    data: &[f64],
+    sequence: u64,
+) -> Result<NetworkMessage, String> {
+    if kind.carries_data() && data.is_empty() {
+        return Err(format!("{:?} messages need --data", kind));
+    }
+    if !kind.carries_data() && !data.is_empty() {
+        return Err(format!("{:?} messages carry no data", kind));
+    }
+    let values = data.iter().map(|&value| value as f32).collect();
+    let (msg_type, payload) = match kind {
+        SendKind::Forward => (
+            MessageType::ForwardData,
+            MessagePayload::ForwardData {
+                layer_id: layer,
+                data: values,
+                timestamp: None,
+                channel: None,
+            },
+        ),
+        SendKind::Backward => (
+            MessageType::BackwardData,
+            MessagePayload::BackwardData {
+                layer_id: layer,
+                gradients: values,
+            },
+        ),
+        SendKind::Heartbeat => (
+            MessageType::Heartbeat,
+            MessagePayload::Heartbeat {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            },
+        ),
+        SendKind::Info => (MessageType::Info, MessagePayload::Info),
+        SendKind::Ping => (MessageType::Ping, MessagePayload::Ping),
+        SendKind::Disconnect => (
+            MessageType::Disconnect,
+            MessagePayload::Disconnect {
+                reason: "benny nnp send".to_string(),
+            },
+        ),
+    };
+    Ok(NetworkMessage {
+        msg_type,
+        sequence,
+        payload,
+        flags: 0,
+    })
+}
+
+/// Handshake introducing a hand-driven sender with one layer of `inputs` values
+pub fn handshake(id: NetworkId, inputs: usize, namespace: Option<String>) -> NetworkMessage {
+    NetworkMessage {
+        msg_type: MessageType::Handshake,
+        sequence: 0,
+        payload: MessagePayload::Handshake {
+            network_id: id,
+            name: "benny-nnp".to_string(),
+            layers: vec![inputs as u16],
+            capabilities: capabilities::FORWARD_PROPAGATION,
+            namespace,
+            channels: Vec::new(),
+            ranges: None,
+        },
+        flags: 0,
+    }
+}
+
+/// One line describing a decoded message: type, sequence, flags and payload
+pub fn describe(message: &NetworkMessage) -> String {
+    let mut flags = Vec::new();
+    if message.flags & message_flags::ACK_REQUIRED != 0 {
+        flags.push("ack-required");
+    }
+    if message.flags & message_flags::STALE != 0 {
+        flags.push("stale");
+    }
+    let flags = if flags.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", flags.join(", "))
+    };
+    format!(
+        "{:?} #{}{} {:?}",
+        message.msg_type, message.sequence, flags, message.payload
+    )
+}
+
+/// What a listener answers to `message`, so that real nodes keep talking to it
+///
+/// Handshakes are accepted and messages flagged `ACK_REQUIRED` are
+/// acknowledged; everything else is only printed.
+pub fn reply(message: &NetworkMessage, id: NetworkId) -> Option<NetworkMessage> {
+    let (msg_type, payload) = match &message.payload {
+        MessagePayload::Handshake { .. } => (
+            MessageType::HandshakeAck,
+            MessagePayload::HandshakeAck {
+                network_id: id,
+                accepted: true,
+                reason: None,
+                ranges: None,
+            },
+        ),
+        _ if message.flags & message_flags::ACK_REQUIRED != 0 => (
+            MessageType::Ack,
+            MessagePayload::Ack {
+                sequence: message.sequence,
+                timestamps: None,
+            },
+        ),
+        _ => return None,
+    };
+    Some(NetworkMessage {
+        msg_type,
+        sequence: message.sequence,
+        payload,
+        flags: 0,
+    })
+}
+
+/// Write `messages` to the node at `address:port` in order, then pass every reply to `on_reply`
+///
+/// Replies are read until the node closes the connection or stays silent
+/// for `wait`. Returns the number of replies.
+pub async fn send(
+    address: &str,
+    port: u16,
+    preference: AddressPreference,
+    messages: &[NetworkMessage],
+    wait: Duration,
+    mut on_reply: impl FnMut(&NetworkMessage),
+) -> Result<usize, ProtocolError> {
+    let mut stream = NnpStream::connect(address, port, preference).await?;
+    for message in messages {
+        stream.write_all(&message.to_bytes()).await?;
+    }
+    let mut replies = 0;
+    loop {
+        match tokio::time::timeout(wait, DistributedNetwork::read_message(&mut stream)).await {
+            Err(_) => break,
+            Ok(Err(ProtocolError::IoError(e))) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break
+            }
+            Ok(Err(e)) => return Err(e),
+            Ok(Ok((reply, _))) => {
+                replies += 1;
+                on_reply(&reply);
+            }
+        }
+    }
+    Ok(replies)
+}
+
+/// Accept NNP connections on `listener` and pass every message to `on_message` with its sender
+///
+/// Answers as `reply` describes. Runs until the listener fails.
+pub async fn listen(
+    listener: NnpListener,
+    id: NetworkId,
+    on_message: impl Fn(&str, &NetworkMessage) + Send + Sync + 'static,
+) -> Result<(), ProtocolError> {
+    let on_message = Arc::new(on_message);
+    loop {
+        let (mut stream, peer) = listener.accept().await?;
+        println!("🔗 Connection from {}", peer);
+        let on_message = Arc::clone(&on_message);
+        tokio::spawn(async move {
+            loop {
+                match DistributedNetwork::read_message(&mut stream).await {
+                    Ok((message, _)) => {
+                        on_message(&peer, &message);
+                        if let Some(answer) = reply(&message, id) {
+                            if let Err(e) = stream.write_all(&answer.to_bytes()).await {
+                                println!("❌ Failed to answer {}: {}", peer, e);
+                                break;
+                            }
+                        }
+                    }
+                    Err(ProtocolError::IoError(e))
+                        if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                    {
+                        println!("🔌 {} disconnected", peer);
+                        break;
+                    }
+                    Err(e) => {
+                        println!("❌ Dropping {}: {:?}", peer, e);
+                        break;
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Bind a listener for `listen` on a TCP host/port or Unix socket path
+pub async fn bind(address: &str, port: u16) -> std::io::Result<NnpListener> {
+    NnpListener::bind(address, port, BindOptions::default()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_nnp_tool_messages() {
+        let message = build_message(SendKind::Forward, 1, &[0.1, -0.2, 0.3], 7).unwrap();
+        let decoded = NetworkMessage::from_bytes(&message.to_bytes()).unwrap();
+        match &decoded.payload {
+            MessagePayload::ForwardData { layer_id, data, .. } => {
+                assert_eq!(*layer_id, 1);
+                assert_eq!(data, &vec![0.1f32, -0.2, 0.3]);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+        assert!(describe(&decoded).starts_with("ForwardData #7 ForwardData"));
+        assert!(build_message(SendKind::Forward, 0, &[], 1)
+            .unwrap_err()
+            .contains("need --data"));
+        assert!(build_message(SendKind::Ping, 0, &[1.0], 1).is_err());
+
+        // Listeners accept handshakes and acknowledge what asks for it, nothing else
+        let id = Uuid::new_v4();
+        let mut heartbeat = build_message(SendKind::Heartbeat, 0, &[], 3).unwrap();
+        assert!(reply(&heartbeat, id).is_none());
+        heartbeat.flags = message_flags::ACK_REQUIRED;
+        assert!(describe(&heartbeat).contains("[ack-required]"));
+        assert!(matches!(
+            reply(&heartbeat, id).unwrap().payload,
+            MessagePayload::Ack { sequence: 3, .. }
+        ));
+        let ack = reply(&handshake(Uuid::new_v4(), 3, None), id).unwrap();
+        assert!(matches!(
+            ack.payload,
+            MessagePayload::HandshakeAck {
+                network_id,
+                accepted: true,
+                ..
+            } if network_id == id
+        ));
+    }
+}
@@ -0,0 +1,308 @@
+use serde::{Deserialize, Serialize};
+
+/// Layer normalization applied to a layer's pre-activations
+///
+/// Each sample is normalized across the neurons of the layer to zero mean and
+/// unit variance, then scaled by `gamma` and shifted by `beta` (both learnable).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerNorm {
+    pub gamma: Vec<f64>,
+    pub beta: Vec<f64>,
+    pub epsilon: f64,
+}
+
+impl LayerNorm {
+    /// Identity-initialized normalization for a layer of `size` neurons
+    pub fn new(size: usize) -> Self {
+        Self {
+            gamma: vec![1.0; size],
+            beta: vec![0.0; size],
+            epsilon: 1e-5,
+        }
+    }
+
+    /// Normalized values and the inverse standard deviation used
+    fn standardize(&self, inputs: &[f64]) -> (Vec<f64>, f64) {
+        let n = inputs.len().max(1) as f64;
+        let mean = inputs.iter().sum::<f64>() / n;
+        let variance = inputs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n;
+        let inv_std = 1.0 / (variance + self.epsilon).sqrt();
+        (
+            inputs.iter().map(|x| (x - mean) * inv_std).collect(),
+            inv_std,
+        )
+    }
+
+    /// Normalize, scale and shift one sample
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        let (normalized, _) = self.standardize(inputs);
+        normalized
+            .iter()
+            .zip(self.gamma.iter().zip(&self.beta))
+            .map(|(x, (g, b))| g * x + b)
+            .collect()
+    }
+
+    /// Propagate `grad_output` (w.r.t. the normalized output) back to the inputs
+    ///
+    /// `gamma` and `beta` are updated in place with `rate`, following the
+    /// network's convention of adding `rate * delta * input`.
+    pub fn backward(&mut self, inputs: &[f64], grad_output: &[f64], rate: f64) -> Vec<f64> {
+        let n = inputs.len().max(1) as f64;
+        let (normalized, inv_std) = self.standardize(inputs);
+
+        let grad_normalized: Vec<f64> = grad_output
+            .iter()
+            .zip(&self.gamma)
+            .map(|(g, gamma)| g * gamma)
+            .collect();
+        let mean_grad = grad_normalized.iter().sum::<f64>() / n;
+        let mean_grad_x = grad_normalized
+            .iter()
+            .zip(&normalized)
+            .map(|(g, x)| g * x)
+            .sum::<f64>()
+            / n;
+
+        let grad_inputs = grad_normalized
+            .iter()
+            .zip(&normalized)
+            .map(|(g, x)| inv_std * (g - mean_grad - x * mean_grad_x))
+            .collect();
+
+        for (i, (&g, &x)) in grad_output.iter().zip(&normalized).enumerate() {
+            self.gamma[i] += rate * g * x;
+            self.beta[i] += rate * g;
+        }
+
+        grad_inputs
+    }
+
+    /// Number of learnable parameters
+    pub fn num_parameters(&self) -> usize {
+        self.gamma.len() + self.beta.len()
+    }
+}
+
+/// Batch normalization applied to a layer's pre-activations
+///
+/// During batch training each neuron is normalized with the mean and variance
+/// of the current batch, and exponential running averages of those statistics
+/// are kept for inference on single samples.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BatchNorm {
+    pub gamma: Vec<f64>,
+    pub beta: Vec<f64>,
+    pub running_mean: Vec<f64>,
+    pub running_var: Vec<f64>,
+    /// Weight of the newest batch in the running statistics
+    pub momentum: f64,
+    pub epsilon: f64,
+}
+
+impl BatchNorm {
+    /// Identity-initialized normalization for a layer of `size` neurons
+    pub fn new(size: usize) -> Self {
+        Self {
+            gamma: vec![1.0; size],
+            beta: vec![0.0; size],
+            running_mean: vec![0.0; size],
+            running_var: vec![1.0; size],
+            momentum: 0.1,
+            epsilon: 1e-5,
+        }
+    }
+
+    /// Per-neuron mean and (biased) variance over a batch
+    pub fn batch_statistics(batch: &[Vec<f64>]) -> (Vec<f64>, Vec<f64>) {
+        let size = batch.first().map_or(0, Vec::len);
+        let n = batch.len().max(1) as f64;
+        let mut mean = vec![0.0; size];
+        let mut variance = vec![0.0; size];
+        for sample in batch {
+            for (m, x) in mean.iter_mut().zip(sample) {
+                *m += x / n;
+            }
+        }
+        for sample in batch {
+            for ((v, x), m) in variance.iter_mut().zip(sample).zip(&mean) {
+                *v += (x - m).powi(2) / n;
+            }
+        }
+        (mean, variance)
+    }
+
+    fn normalize(&self, inputs: &[f64], mean: &[f64], variance: &[f64]) -> Vec<f64> {
+        (0..inputs.len())
+            .map(|i| {
+                let x_hat = (inputs[i] - mean[i]) / (variance[i] + self.epsilon).sqrt();
+                self.gamma[i] * x_hat + self.beta[i]
+            })
+            .collect()
+    }
+
+    /// Normalize one sample with the running statistics (inference)
+    pub fn forward(&self, inputs: &[f64]) -> Vec<f64> {
+        self.normalize(inputs, &self.running_mean, &self.running_var)
+    }
+
+    /// Normalize a batch with its own statistics and fold them into the running averages
+    pub fn forward_batch(&mut self, batch: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let (mean, variance) = Self::batch_statistics(batch);
+        for i in 0..self.running_mean.len() {
+            self.running_mean[i] += self.momentum * (mean[i] - self.running_mean[i]);
+            self.running_var[i] += self.momentum * (variance[i] - self.running_var[i]);
+        }
+        batch
+            .iter()
+            .map(|sample| self.normalize(sample, &mean, &variance))
+            .collect()
+    }
+
+    /// Propagate `grad_output` back through the inference transform of one sample
+    ///
+    /// The running statistics are constants here, so the layer is a per-neuron
+    /// affine map; `gamma` and `beta` are updated in place with `rate`.
+    pub fn backward(&mut self, inputs: &[f64], grad_output: &[f64], rate: f64) -> Vec<f64> {
+        let mut grad_inputs = Vec::with_capacity(inputs.len());
+        for i in 0..inputs.len() {
+            let inv_std = 1.0 / (self.running_var[i] + self.epsilon).sqrt();
+            let x_hat = (inputs[i] - self.running_mean[i]) * inv_std;
+            grad_inputs.push(grad_output[i] * self.gamma[i] * inv_std);
+            self.gamma[i] += rate * grad_output[i] * x_hat;
+            self.beta[i] += rate * grad_output[i];
+        }
+        grad_inputs
+    }
+
+    /// Propagate per-sample `grad_outputs` back through a batch-statistics forward pass
+    ///
+    /// `gamma` and `beta` are updated with the batch-averaged deltas.
+    pub fn backward_batch(
+        &mut self,
+        batch: &[Vec<f64>],
+        grad_outputs: &[Vec<f64>],
+        rate: f64,
+    ) -> Vec<Vec<f64>> {
+        let n = batch.len().max(1) as f64;
+        let (mean, variance) = Self::batch_statistics(batch);
+        let mut grad_inputs = vec![vec![0.0; mean.len()]; batch.len()];
+
+        for i in 0..mean.len() {
+            let inv_std = 1.0 / (variance[i] + self.epsilon).sqrt();
+            let x_hat: Vec<f64> = batch.iter().map(|s| (s[i] - mean[i]) * inv_std).collect();
+            let grads: Vec<f64> = grad_outputs.iter().map(|g| g[i]).collect();
+
+            let mean_grad = grads.iter().sum::<f64>() / n;
+            let mean_grad_x = grads.iter().zip(&x_hat).map(|(g, x)| g * x).sum::<f64>() / n;
+            for (sample, (g, x)) in grads.iter().zip(&x_hat).enumerate() {
+                grad_inputs[sample][i] =
+                    self.gamma[i] * inv_std * (g - mean_grad - x * mean_grad_x);
+            }
+
+            self.gamma[i] += rate * mean_grad_x;
+            self.beta[i] += rate * mean_grad;
+        }
+
+        grad_inputs
+    }
+
+    /// Number of learnable parameters (running statistics are not learned)
+    pub fn num_parameters(&self) -> usize {
+        self.gamma.len() + self.beta.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_standardizes() {
+        let norm = LayerNorm::new(4);
+        let output = norm.forward(&[1.0, 2.0, 3.0, 4.0]);
+        let mean = output.iter().sum::<f64>() / 4.0;
+        let variance = output.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / 4.0;
+        assert!(mean.abs() < 1e-9);
+        assert!((variance - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_backward_matches_numerical_gradient() {
+        let mut norm = LayerNorm::new(3);
+        norm.gamma = vec![0.5, 1.5, -1.0];
+        let inputs = [0.2, -0.7, 1.3];
+        let upstream = [0.3, -0.1, 0.8];
+        let loss = |norm: &LayerNorm, x: &[f64]| -> f64 {
+            norm.forward(x)
+                .iter()
+                .zip(&upstream)
+                .map(|(y, g)| y * g)
+                .sum()
+        };
+
+        let analytic = norm.clone().backward(&inputs, &upstream, 0.0);
+        for i in 0..inputs.len() {
+            let h = 1e-6;
+            let mut plus = inputs;
+            let mut minus = inputs;
+            plus[i] += h;
+            minus[i] -= h;
+            let numeric = (loss(&norm, &plus) - loss(&norm, &minus)) / (2.0 * h);
+            assert!((numeric - analytic[i]).abs() < 1e-5, "input {}", i);
+        }
+    }
+
+    #[test]
+    fn test_batch_norm_running_statistics() {
+        let mut norm = BatchNorm::new(2);
+        let batch = vec![vec![1.0, 10.0], vec![3.0, 30.0]];
+        for _ in 0..200 {
+            let output = norm.forward_batch(&batch);
+            assert!((output[0][0] + output[1][0]).abs() < 1e-9);
+        }
+        assert!((norm.running_mean[0] - 2.0).abs() < 1e-6);
+        assert!((norm.running_var[1] - 100.0).abs() < 1e-4);
+
+        let inference = norm.forward(&[2.0, 20.0]);
+        assert!(inference.iter().all(|x| x.abs() < 1e-6));
+    }
+
+    #[test]
+    fn test_batch_norm_backward_matches_numerical_gradient() {
+        let mut norm = BatchNorm::new(2);
+        norm.gamma = vec![0.7, -1.2];
+        norm.beta = vec![0.1, 0.4];
+        let batch = vec![vec![0.2, -0.5], vec![1.1, 0.3], vec![-0.4, 0.9]];
+        let upstream = vec![vec![0.3, -0.2], vec![-0.6, 0.5], vec![0.1, 0.8]];
+        let loss = |batch: &[Vec<f64>]| -> f64 {
+            let (mean, variance) = BatchNorm::batch_statistics(batch);
+            batch
+                .iter()
+                .zip(&upstream)
+                .map(|(s, g)| {
+                    let y = norm.normalize(s, &mean, &variance);
+                    y.iter().zip(g).map(|(y, g)| y * g).sum::<f64>()
+                })
+                .sum()
+        };
+
+        let analytic = norm.clone().backward_batch(&batch, &upstream, 0.0);
+        for sample in 0..batch.len() {
+            for i in 0..2 {
+                let h = 1e-6;
+                let mut plus = batch.clone();
+                let mut minus = batch.clone();
+                plus[sample][i] += h;
+                minus[sample][i] -= h;
+                let numeric = (loss(&plus) - loss(&minus)) / (2.0 * h);
+                assert!(
+                    (numeric - analytic[sample][i]).abs() < 1e-5,
+                    "sample {} neuron {}",
+                    sample,
+                    i
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use crate::network_composer::NetworkComposer;
+use crate::neural_network::NeuralNetwork;
+use crate::telemetry::EpochRecord;
+use std::fmt::Write as _;
+
+const HISTOGRAM_BINS: usize = 20;
+const CHART_WIDTH: f64 = 320.0;
+const CHART_HEIGHT: f64 = 120.0;
+
+const TABLE_STYLE: &str = "border-collapse: collapse; font-family: sans-serif; font-size: 13px;";
+const CELL_STYLE: &str = "border: 1px solid #ddd; padding: 2px 8px; text-align: left;";
+
+/// Print `html` the way evcxr picks it up as rich output
+fn display_html(html: &str) {
+    println!("EVCXR_BEGIN_CONTENT text/html\n{}\nEVCXR_END_CONTENT", html);
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A table with a header row; cells are escaped
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut html = format!("<table style=\"{}\"><tr>", TABLE_STYLE);
+    for header in headers {
+        let _ = write!(html, "<th style=\"{}\">{}</th>", CELL_STYLE, escape(header));
+    }
+    html.push_str("</tr>");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            let _ = write!(html, "<td style=\"{}\">{}</td>", CELL_STYLE, escape(cell));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</table>");
+    html
+}
+
+/// Inline SVG histogram of `values`, with the range under it
+fn histogram_svg(values: &[f64]) -> String {
+    let finite: Vec<f64> = values.iter().copied().filter(|v| v.is_finite()).collect();
+    let (min, max) = finite
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+    let mut counts = [0usize; HISTOGRAM_BINS];
+    for &value in &finite {
+        let position = if max > min {
+            (value - min) / (max - min)
+        } else {
+            0.5
+        };
+        counts[((position * HISTOGRAM_BINS as f64) as usize).min(HISTOGRAM_BINS - 1)] += 1;
+    }
+    let tallest = counts.iter().copied().max().unwrap_or(0).max(1) as f64;
+    let bar_width = CHART_WIDTH / HISTOGRAM_BINS as f64;
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        CHART_WIDTH,
+        CHART_HEIGHT + 16.0
+    );
+    for (bin, &count) in counts.iter().enumerate() {
+        let height = count as f64 / tallest * CHART_HEIGHT;
+        let _ = write!(
+            svg,
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" fill=\"#4a90d9\"/>",
+            bin as f64 * bar_width,
+            CHART_HEIGHT - height,
+            bar_width - 1.0,
+            height
+        );
+    }
+    if !finite.is_empty() {
+        let _ = write!(
+            svg,
+            "<text x=\"0\" y=\"{0}\" font-size=\"11\">{1:.3}</text>\
+             <text x=\"{2}\" y=\"{0}\" font-size=\"11\" text-anchor=\"end\">{3:.3}</text>",
+            CHART_HEIGHT + 13.0,
+            min,
+            CHART_WIDTH,
+            max
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Inline SVG line chart of `(color, values)` series sharing one scale; gaps are skipped
+fn line_chart_svg(series: &[(&str, Vec<Option<f64>>)]) -> String {
+    let points = || {
+        series
+            .iter()
+            .flat_map(|(_, values)| values.iter().flatten().copied())
+            .filter(|v| v.is_finite())
+    };
+    let min = points().fold(f64::INFINITY, f64::min);
+    let max = points().fold(f64::NEG_INFINITY, f64::max);
+    let length = series
+        .iter()
+        .map(|(_, values)| values.len())
+        .max()
+        .unwrap_or(0);
+    let x = |i: usize| i as f64 * CHART_WIDTH / (length.max(2) - 1) as f64;
+    let y = |v: f64| {
+        if max > min {
+            CHART_HEIGHT - (v - min) / (max - min) * CHART_HEIGHT
+        } else {
+            CHART_HEIGHT / 2.0
+        }
+    };
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        CHART_WIDTH,
+        CHART_HEIGHT + 16.0
+    );
+    for (color, values) in series {
+        let path: Vec<String> = values
+            .iter()
+            .enumerate()
+            .filter_map(|(i, v)| v.filter(|v| v.is_finite()).map(|v| (i, v)))
+            .map(|(i, v)| format!("{:.1},{:.1}", x(i), y(v)))
+            .collect();
+        if !path.is_empty() {
+            let _ = write!(
+                svg,
+                "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>",
+                path.join(" "),
+                color
+            );
+        }
+    }
+    if min.is_finite() {
+        let _ = write!(
+            svg,
+            "<text x=\"0\" y=\"{}\" font-size=\"11\">min {:.6} · max {:.6}</text>",
+            CHART_HEIGHT + 13.0,
+            min,
+            max
+        );
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+impl NeuralNetwork {
+    /// Summary table and per-layer weight histograms, as HTML
+    pub fn to_html(&self) -> String {
+        let layers = self.get_layers();
+        let mut rows = vec![vec![
+            "0 (input)".to_string(),
+            self.input_size().to_string(),
+            "–".to_string(),
+            "–".to_string(),
+        ]];
+        for layer in 0..layers.len() - 1 {
+            let weights = self.get_layer_weights(layer);
+            let mean = weights.iter().sum::<f64>() / weights.len().max(1) as f64;
+            let spread = (weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>()
+                / weights.len().max(1) as f64)
+                .sqrt();
+            rows.push(vec![
+                (layer + 1).to_string(),
+                layers[layer + 1].to_string(),
+                format!("{:?}", self.get_activation(layer)),
+                format!("{:.4} ± {:.4}", mean, spread),
+            ]);
+        }
+
+        let mut html = format!(
+            "<div><b>NeuralNetwork</b> {} · {} parameters · {:?} learning<br>",
+            layers
+                .iter()
+                .map(|size| size.to_string())
+                .collect::<Vec<_>>()
+                .join("-"),
+            self.num_parameters(),
+            self.get_learning_mode()
+        );
+        html.push_str(&table(
+            &["Layer", "Neurons", "Activation", "Weights"],
+            &rows,
+        ));
+        html.push_str("<div style=\"display: flex; flex-wrap: wrap; gap: 12px;\">");
+        for layer in 0..layers.len() - 1 {
+            let _ = write!(
+                html,
+                "<figure style=\"margin: 4px;\">{}<figcaption style=\"font-size: 12px;\">Weights {} → {}</figcaption></figure>",
+                histogram_svg(&self.get_layer_weights(layer)),
+                layer,
+                layer + 1
+            );
+        }
+        html.push_str("</div></div>");
+        html
+    }
+
+    /// Rich output for evcxr notebooks; see `to_html`
+    pub fn evcxr_display(&self) {
+        display_html(&self.to_html());
+    }
+}
+
+impl NetworkComposer {
+    /// Networks in execution order and their connections, as HTML
+    pub fn to_html(&self) -> String {
+        let networks: Vec<Vec<String>> = self
+            .get_execution_order()
+            .iter()
+            .enumerate()
+            .map(|(step, name)| {
+                let (kind, info) = match (self.get_network(name), self.get_reservoir(name)) {
+                    (Some(network), _) => ("network", network.info()),
+                    (None, Some(reservoir)) => ("reservoir", reservoir.info()),
+                    (None, None) => ("", String::new()),
+                };
+                vec![step.to_string(), name.clone(), kind.to_string(), info]
+            })
+            .collect();
+        let connections: Vec<Vec<String>> = self
+            .get_connections()
+            .iter()
+            .map(|connection| {
+                vec![
+                    connection.source_network.clone(),
+                    format!("{:?}", connection.source_outputs),
+                    connection.target_network.clone(),
+                    format!("{:?}", connection.target_inputs),
+                ]
+            })
+            .collect();
+
+        let mut html = format!(
+            "<div><b>NetworkComposer</b> {} networks · {} connections<br>",
+            networks.len(),
+            connections.len()
+        );
+        html.push_str(&table(&["Step", "Name", "Kind", "Details"], &networks));
+        if !connections.is_empty() {
+            html.push_str("<br>");
+            html.push_str(&table(&["From", "Outputs", "To", "Inputs"], &connections));
+        }
+        html.push_str("</div>");
+        html
+    }
+
+    /// Rich output for evcxr notebooks; see `to_html`
+    pub fn evcxr_display(&self) {
+        display_html(&self.to_html());
+    }
+}
+
+/// Errors of a training session, epoch by epoch, for display in notebooks
+///
+/// Filled with `record` while training, or from the epochs kept by a
+/// `RunStore`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingReport {
+    pub train_errors: Vec<f64>,
+    /// Validation error of each epoch, when it was validated
+    pub val_errors: Vec<Option<f64>>,
+}
+
+impl TrainingReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the errors of the next epoch
+    pub fn record(&mut self, train_error: f64, val_error: Option<f64>) {
+        self.train_errors.push(train_error);
+        self.val_errors.push(val_error);
+    }
+
+    pub fn from_epochs(epochs: &[EpochRecord]) -> Self {
+        let mut report = Self::new();
+        for epoch in epochs {
+            report.record(epoch.train_error, epoch.val_error);
+        }
+        report
+    }
+
+    /// Epoch and value of the lowest validation error
+    pub fn best_val_error(&self) -> Option<(usize, f64)> {
+        self.val_errors
+            .iter()
+            .enumerate()
+            .filter_map(|(epoch, error)| error.map(|error| (epoch, error)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Loss curves and final errors, as HTML
+    pub fn to_html(&self) -> String {
+        let mut rows = vec![vec![
+            "Epochs".to_string(),
+            self.train_errors.len().to_string(),
+        ]];
+        if let Some(last) = self.train_errors.last() {
+            rows.push(vec![
+                "Final train error".to_string(),
+                format!("{:.6}", last),
+            ]);
+        }
+        if let Some((epoch, error)) = self.best_val_error() {
+            rows.push(vec![
+                "Best validation error".to_string(),
+                format!("{:.6} (epoch {})", error, epoch),
+            ]);
+        }
+
+        let train = self.train_errors.iter().map(|&e| Some(e)).collect();
+        let mut series = vec![("#4a90d9", train)];
+        if self.val_errors.iter().any(Option::is_some) {
+            series.push(("#e67e22", self.val_errors.clone()));
+        }
+        format!(
+            "<div><b>Training</b> <span style=\"color: #4a90d9;\">train</span> \
+             <span style=\"color: #e67e22;\">validation</span><br>{}{}</div>",
+            line_chart_svg(&series),
+            table(&["", ""], &rows)
+        )
+    }
+
+    /// Rich output for evcxr notebooks; see `to_html`
+    pub fn evcxr_display(&self) {
+        display_html(&self.to_html());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notebook_html() {
+        let nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let html = nn.to_html();
+        assert!(html.contains("2-3-1"));
+        // One histogram per weight layer
+        assert_eq!(html.matches("<svg").count(), 2);
+
+        let mut composer = NetworkComposer::new();
+        composer.add_network("a<b".to_string(), nn.clone()).unwrap();
+        composer.add_network("c".to_string(), nn).unwrap();
+        composer
+            .connect_networks("a<b", "c", vec![0], vec![0])
+            .unwrap();
+        let html = composer.to_html();
+        assert!(html.contains("a&lt;b"));
+        assert!(html.contains("[0]"));
+
+        let mut report = TrainingReport::new();
+        report.record(0.5, None);
+        report.record(0.3, Some(0.4));
+        report.record(0.2, Some(0.35));
+        assert_eq!(report.best_val_error(), Some((2, 0.35)));
+        let html = report.to_html();
+        assert_eq!(html.matches("<polyline").count(), 2);
+        assert!(html.contains("0.200000"));
+    }
+}
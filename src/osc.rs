@@ -0,0 +1,422 @@
+use serde::{Deserialize, Serialize};
+
+/// Argument of an OSC message
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Blob(Vec<u8>),
+    Bool(bool),
+    Nil,
+}
+
+impl OscArg {
+    /// Numeric value of the argument; booleans count as 0 and 1
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            OscArg::Int(v) => Some(*v as f64),
+            OscArg::Long(v) => Some(*v as f64),
+            OscArg::Float(v) => Some(*v as f64),
+            OscArg::Double(v) => Some(*v),
+            OscArg::Bool(v) => Some(if *v { 1.0 } else { 0.0 }),
+            OscArg::String(_) | OscArg::Blob(_) | OscArg::Nil => None,
+        }
+    }
+}
+
+/// OSC 1.0 message
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+impl OscMessage {
+    /// Message of `values` as float32 arguments, the type every OSC environment reads
+    pub fn floats(address: &str, values: &[f64]) -> Self {
+        Self {
+            address: address.to_string(),
+            args: values.iter().map(|&v| OscArg::Float(v as f32)).collect(),
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_string(&mut bytes, &self.address);
+        let mut tags = String::from(",");
+        let mut data = Vec::new();
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(v) => {
+                    tags.push('i');
+                    data.extend_from_slice(&v.to_be_bytes());
+                }
+                OscArg::Long(v) => {
+                    tags.push('h');
+                    data.extend_from_slice(&v.to_be_bytes());
+                }
+                OscArg::Float(v) => {
+                    tags.push('f');
+                    data.extend_from_slice(&v.to_be_bytes());
+                }
+                OscArg::Double(v) => {
+                    tags.push('d');
+                    data.extend_from_slice(&v.to_be_bytes());
+                }
+                OscArg::String(v) => {
+                    tags.push('s');
+                    write_string(&mut data, v);
+                }
+                OscArg::Blob(v) => {
+                    tags.push('b');
+                    data.extend_from_slice(&(v.len() as u32).to_be_bytes());
+                    data.extend_from_slice(v);
+                    pad(&mut data);
+                }
+                OscArg::Bool(v) => tags.push(if *v { 'T' } else { 'F' }),
+                OscArg::Nil => tags.push('N'),
+            }
+        }
+        write_string(&mut bytes, &tags);
+        bytes.extend(data);
+        bytes
+    }
+}
+
+/// Null-terminated string padded to a multiple of four bytes
+fn write_string(bytes: &mut Vec<u8>, value: &str) {
+    bytes.extend_from_slice(value.as_bytes());
+    bytes.push(0);
+    pad(bytes);
+}
+
+fn pad(bytes: &mut Vec<u8>) {
+    while !bytes.len().is_multiple_of(4) {
+        bytes.push(0);
+    }
+}
+
+/// Reads the aligned fields of one OSC packet
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let end = self.position + count;
+        let bytes = self
+            .bytes
+            .get(self.position..end)
+            .ok_or_else(|| format!("OSC packet ends at byte {}", self.bytes.len()))?;
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().expect("took N bytes"))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.bytes[self.position.min(self.bytes.len())..];
+        let length = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or("Unterminated OSC string")?;
+        let value = std::str::from_utf8(&rest[..length])
+            .map_err(|e| format!("Invalid OSC string: {}", e))?
+            .to_string();
+        self.take((length + 4) / 4 * 4)?;
+        Ok(value)
+    }
+
+    fn is_done(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+}
+
+/// Decode a packet into its messages, flattening nested bundles
+///
+/// Time tags are ignored: bundled messages are taken as arriving now.
+pub fn decode_packet(bytes: &[u8]) -> Result<Vec<OscMessage>, String> {
+    let mut reader = Reader { bytes, position: 0 };
+    if bytes.starts_with(b"#bundle\0") {
+        reader.take(16)?;
+        let mut messages = Vec::new();
+        while !reader.is_done() {
+            let size = u32::from_be_bytes(reader.array()?) as usize;
+            messages.extend(decode_packet(reader.take(size)?)?);
+        }
+        return Ok(messages);
+    }
+
+    let address = reader.string()?;
+    if !address.starts_with('/') {
+        return Err(format!("OSC address '{}' does not start with '/'", address));
+    }
+    // Very old senders omit the type tags; such a message has no arguments we can read
+    let tags = if reader.is_done() {
+        String::from(",")
+    } else {
+        reader.string()?
+    };
+    let mut args = Vec::new();
+    for tag in tags.chars().skip(1) {
+        args.push(match tag {
+            'i' => OscArg::Int(i32::from_be_bytes(reader.array()?)),
+            'h' => OscArg::Long(i64::from_be_bytes(reader.array()?)),
+            'f' => OscArg::Float(f32::from_be_bytes(reader.array()?)),
+            'd' => OscArg::Double(f64::from_be_bytes(reader.array()?)),
+            's' | 'S' => OscArg::String(reader.string()?),
+            'b' => {
+                let length = u32::from_be_bytes(reader.array()?) as usize;
+                let blob = reader.take(length)?.to_vec();
+                reader.take((4 - length % 4) % 4)?;
+                OscArg::Blob(blob)
+            }
+            'T' => OscArg::Bool(true),
+            'F' => OscArg::Bool(false),
+            'N' | 'I' => OscArg::Nil,
+            other => return Err(format!("Unsupported OSC type tag '{}'", other)),
+        });
+    }
+    Ok(vec![OscMessage { address, args }])
+}
+
+/// Whether OSC address `pattern` matches `address`
+///
+/// Supports `?` and `*` within one path segment, character classes such as
+/// `[a-c]` and `[!0-9]`, and alternatives such as `{left,right}`.
+pub fn pattern_matches(pattern: &str, address: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let address: Vec<char> = address.chars().collect();
+    matches_from(&pattern, &address)
+}
+
+fn matches_from(pattern: &[char], address: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return address.is_empty();
+    };
+    match first {
+        '*' => (0..=address.len())
+            .take_while(|&i| i == 0 || address[i - 1] != '/')
+            .any(|i| matches_from(rest, &address[i..])),
+        '?' => address.first().is_some_and(|&c| c != '/') && matches_from(rest, &address[1..]),
+        '[' => {
+            let Some(close) = rest.iter().position(|&c| c == ']') else {
+                return false;
+            };
+            let (negated, class) = match rest[..close].split_first() {
+                Some(('!', class)) => (true, class),
+                _ => (false, &rest[..close]),
+            };
+            let Some(&c) = address.first() else {
+                return false;
+            };
+            let mut found = false;
+            let mut i = 0;
+            while i < class.len() {
+                if i + 2 < class.len() && class[i + 1] == '-' {
+                    found |= class[i] <= c && c <= class[i + 2];
+                    i += 3;
+                } else {
+                    found |= class[i] == c;
+                    i += 1;
+                }
+            }
+            found != negated && c != '/' && matches_from(&rest[close + 1..], &address[1..])
+        }
+        '{' => {
+            let Some(close) = rest.iter().position(|&c| c == '}') else {
+                return false;
+            };
+            let after = &rest[close + 1..];
+            rest[..close].split(|&c| c == ',').any(|alternative| {
+                address.starts_with(alternative)
+                    && matches_from(after, &address[alternative.len()..])
+            })
+        }
+        c => address.first() == Some(&c) && matches_from(rest, &address[1..]),
+    }
+}
+
+/// Incoming messages whose address matches `pattern` set inputs from `input` on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscInputMapping {
+    pub pattern: String,
+    /// Input receiving the first numeric argument; further arguments set the following inputs
+    pub input: usize,
+}
+
+/// Vectors received as OSC over UDP, e.g. from SuperCollider, TouchDesigner or Max/MSP
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscSourceConfig {
+    /// Address to receive packets on
+    pub bind_address: String,
+    pub port: u16,
+    /// Messages matching no mapping are ignored; all numeric arguments go to input 0 on when empty
+    #[serde(default)]
+    pub mappings: Vec<OscInputMapping>,
+}
+
+/// Latest inputs assembled from OSC messages
+#[derive(Debug, Clone)]
+pub struct OscInputs {
+    mappings: Vec<OscInputMapping>,
+    values: Vec<f64>,
+}
+
+impl OscInputs {
+    pub fn new(mappings: Vec<OscInputMapping>, input_count: usize) -> Self {
+        Self {
+            mappings,
+            values: vec![0.0; input_count],
+        }
+    }
+
+    /// Set the inputs `message` is mapped to, returning whether any mapping matched
+    ///
+    /// Arguments beyond the last input are dropped.
+    pub fn apply(&mut self, message: &OscMessage) -> bool {
+        let numbers: Vec<f64> = message.args.iter().filter_map(OscArg::as_f64).collect();
+        let starts: Vec<usize> = if self.mappings.is_empty() {
+            vec![0]
+        } else {
+            self.mappings
+                .iter()
+                .filter(|mapping| pattern_matches(&mapping.pattern, &message.address))
+                .map(|mapping| mapping.input)
+                .collect()
+        };
+        for &start in &starts {
+            for (value, number) in self.values.iter_mut().skip(start).zip(&numbers) {
+                *value = *number;
+            }
+        }
+        !starts.is_empty()
+    }
+
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+}
+
+/// Outputs sent to address `address`: the listed outputs, or all of them
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscOutputMapping {
+    pub address: String,
+    #[serde(default)]
+    pub outputs: Option<Vec<usize>>,
+}
+
+/// Outputs sent as OSC over UDP
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OscSinkConfig {
+    pub address: String,
+    pub port: u16,
+    /// One message per mapping and output sample; `/benny/outputs` with every output when empty
+    #[serde(default)]
+    pub mappings: Vec<OscOutputMapping>,
+}
+
+impl OscSinkConfig {
+    /// Messages carrying one output sample
+    ///
+    /// Outputs a sample lacks are left out of their message.
+    pub fn messages(&self, outputs: &[f64]) -> Vec<OscMessage> {
+        if self.mappings.is_empty() {
+            return vec![OscMessage::floats("/benny/outputs", outputs)];
+        }
+        self.mappings
+            .iter()
+            .map(|mapping| match &mapping.outputs {
+                Some(indices) => {
+                    let values: Vec<f64> = indices
+                        .iter()
+                        .filter_map(|&i| outputs.get(i).copied())
+                        .collect();
+                    OscMessage::floats(&mapping.address, &values)
+                }
+                None => OscMessage::floats(&mapping.address, outputs),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc_codec_and_mapping() {
+        let message = OscMessage {
+            address: "/xy/1".to_string(),
+            args: vec![
+                OscArg::Float(0.25),
+                OscArg::Int(3),
+                OscArg::String("go".to_string()),
+                OscArg::Blob(vec![1, 2, 3]),
+                OscArg::Bool(true),
+            ],
+        };
+        let encoded = message.encode();
+        assert_eq!(&encoded[..12], b"/xy/1\0\0\0,fis");
+        assert_eq!(encoded.len() % 4, 0);
+        assert_eq!(decode_packet(&encoded).unwrap(), vec![message.clone()]);
+
+        // A bundle carrying the message and a nested bundle with another one
+        let fader = OscMessage::floats("/fader3", &[0.5]);
+        let bundle = |parts: &[Vec<u8>]| {
+            let mut bytes = b"#bundle\0\0\0\0\0\0\0\0\x01".to_vec();
+            for part in parts {
+                bytes.extend_from_slice(&(part.len() as u32).to_be_bytes());
+                bytes.extend_from_slice(part);
+            }
+            bytes
+        };
+        let packet = bundle(&[encoded.clone(), bundle(&[fader.encode()])]);
+        assert_eq!(
+            decode_packet(&packet).unwrap(),
+            vec![message.clone(), fader.clone()]
+        );
+        assert!(decode_packet(&encoded[..10]).is_err());
+
+        assert!(pattern_matches("/xy/*", "/xy/1"));
+        assert!(!pattern_matches("/*", "/xy/1"));
+        assert!(pattern_matches("/fader[1-4]", "/fader3"));
+        assert!(!pattern_matches("/fader[!1-4]", "/fader3"));
+        assert!(pattern_matches("/{left,right}/gain?", "/right/gain2"));
+        assert!(!pattern_matches("/{left,right}/gain", "/center/gain"));
+
+        // Numeric arguments fill consecutive inputs; strings are skipped
+        let mut inputs = OscInputs::new(
+            vec![
+                OscInputMapping {
+                    pattern: "/xy/*".to_string(),
+                    input: 2,
+                },
+                OscInputMapping {
+                    pattern: "/fader[0-9]".to_string(),
+                    input: 0,
+                },
+            ],
+            4,
+        );
+        assert!(inputs.apply(&message));
+        assert!(inputs.apply(&fader));
+        assert!(!inputs.apply(&OscMessage::floats("/other", &[9.0])));
+        assert_eq!(inputs.values(), &[0.5, 0.0, 0.25, 3.0]);
+
+        let sink: OscSinkConfig = serde_json::from_str(
+            r#"{"address": "127.0.0.1", "port": 57120, "mappings": [{"address": "/pair", "outputs": [1, 0, 7]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            sink.messages(&[0.5, 1.0]),
+            vec![OscMessage::floats("/pair", &[1.0, 0.5])]
+        );
+    }
+}
@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// Severity of an output relative to its thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum AlertLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Warning and critical limits of one output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputThreshold {
+    pub output: usize,
+    #[serde(default)]
+    pub warning: Option<f64>,
+    #[serde(default)]
+    pub critical: Option<f64>,
+    /// Alert when the value falls below the limits instead of rising above them
+    #[serde(default)]
+    pub below: bool,
+}
+
+impl OutputThreshold {
+    pub fn validate(&self) -> Result<(), String> {
+        for limit in [self.warning, self.critical].into_iter().flatten() {
+            if !limit.is_finite() {
+                return Err(format!(
+                    "Threshold {} of output {} is not a number",
+                    limit, self.output
+                ));
+            }
+        }
+        if let (Some(warning), Some(critical)) = (self.warning, self.critical) {
+            if self.beyond(warning, critical) {
+                return Err(format!(
+                    "Critical threshold {} of output {} is less severe than its warning {}",
+                    critical, self.output, warning
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `value` is past `limit` in this threshold's direction
+    fn beyond(&self, value: f64, limit: f64) -> bool {
+        if self.below {
+            value < limit
+        } else {
+            value > limit
+        }
+    }
+
+    /// Level of `value`, with the limit it crossed
+    pub fn level(&self, value: f64) -> (AlertLevel, Option<f64>) {
+        match (self.critical, self.warning) {
+            (Some(critical), _) if self.beyond(value, critical) => {
+                (AlertLevel::Critical, Some(critical))
+            }
+            (_, Some(warning)) if self.beyond(value, warning) => {
+                (AlertLevel::Warning, Some(warning))
+            }
+            _ => (AlertLevel::Normal, None),
+        }
+    }
+}
+
+/// A change in an output's alert level
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OutputAlert {
+    pub network_id: String,
+    pub output: usize,
+    pub level: AlertLevel,
+    /// The level the output was at before
+    pub previous: AlertLevel,
+    pub value: f64,
+    /// The limit crossed; none when the output returned to normal
+    pub threshold: Option<f64>,
+}
+
+impl OutputAlert {
+    /// One-line human-readable description
+    pub fn message(&self) -> String {
+        match (self.level, self.threshold) {
+            (AlertLevel::Normal, _) => format!(
+                "✅ {} output {} back to normal at {:.3}",
+                self.network_id, self.output, self.value
+            ),
+            (level, threshold) => format!(
+                "{} {} output {} at {:.3} crossed {:?} threshold {}",
+                if level == AlertLevel::Critical {
+                    "🚨"
+                } else {
+                    "⚠️"
+                },
+                self.network_id,
+                self.output,
+                self.value,
+                level,
+                threshold.map_or_else(String::new, |t| t.to_string())
+            ),
+        }
+    }
+}
+
+/// Alert thresholds by network, kept in a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdStore {
+    thresholds: BTreeMap<String, Vec<OutputThreshold>>,
+}
+
+impl ThresholdStore {
+    /// Read a threshold file; a missing file holds no thresholds
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid threshold file {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, network_id: &str) -> &[OutputThreshold] {
+        self.thresholds.get(network_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Networks with thresholds, and their thresholds
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<OutputThreshold>)> {
+        self.thresholds.iter()
+    }
+
+    /// Replace a network's thresholds; an empty list removes them
+    pub fn set(
+        &mut self,
+        network_id: &str,
+        thresholds: Vec<OutputThreshold>,
+    ) -> Result<(), String> {
+        for threshold in &thresholds {
+            threshold.validate()?;
+        }
+        if thresholds.is_empty() {
+            self.thresholds.remove(network_id);
+        } else {
+            self.thresholds.insert(network_id.to_string(), thresholds);
+        }
+        Ok(())
+    }
+}
+
+/// Current alert level of each output of one network
+///
+/// `evaluate` is called with every sample and reports only changes, so a
+/// value that stays above its threshold raises one alert, not one per sample.
+#[derive(Debug, Clone, Default)]
+pub struct AlertTracker {
+    levels: HashMap<usize, AlertLevel>,
+}
+
+impl AlertTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn level(&self, output: usize) -> AlertLevel {
+        self.levels.get(&output).copied().unwrap_or_default()
+    }
+
+    /// Alerts for the outputs of `network_id` whose level changed with this sample
+    pub fn evaluate(
+        &mut self,
+        network_id: &str,
+        outputs: &[f64],
+        thresholds: &[OutputThreshold],
+    ) -> Vec<OutputAlert> {
+        // Outputs whose thresholds were removed return to normal silently
+        self.levels
+            .retain(|output, _| thresholds.iter().any(|t| t.output == *output));
+        let mut alerts = Vec::new();
+        for threshold in thresholds {
+            let Some(&value) = outputs.get(threshold.output) else {
+                continue;
+            };
+            let (level, crossed) = threshold.level(value);
+            let previous = self
+                .levels
+                .insert(threshold.output, level)
+                .unwrap_or_default();
+            if level != previous {
+                alerts.push(OutputAlert {
+                    network_id: network_id.to_string(),
+                    output: threshold.output,
+                    level,
+                    previous,
+                    value,
+                    threshold: crossed,
+                });
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alerts_fire_on_level_changes() {
+        let thresholds = vec![
+            OutputThreshold {
+                output: 0,
+                warning: Some(0.7),
+                critical: Some(0.9),
+                below: false,
+            },
+            OutputThreshold {
+                output: 1,
+                warning: None,
+                critical: Some(0.1),
+                below: true,
+            },
+        ];
+        let mut tracker = AlertTracker::new();
+        assert!(tracker
+            .evaluate("main-network", &[0.5, 0.5], &thresholds)
+            .is_empty());
+        let alerts = tracker.evaluate("main-network", &[0.8, 0.05], &thresholds);
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].level, AlertLevel::Warning);
+        assert_eq!(alerts[0].threshold, Some(0.7));
+        assert_eq!(alerts[1].level, AlertLevel::Critical);
+        // Staying above a threshold does not repeat the alert
+        assert!(tracker
+            .evaluate("main-network", &[0.85, 0.0], &thresholds)
+            .is_empty());
+        let alerts = tracker.evaluate("main-network", &[0.95, 0.5], &thresholds);
+        assert_eq!(
+            alerts
+                .iter()
+                .map(|a| (a.level, a.previous))
+                .collect::<Vec<_>>(),
+            vec![
+                (AlertLevel::Critical, AlertLevel::Warning),
+                (AlertLevel::Normal, AlertLevel::Critical)
+            ]
+        );
+        assert_eq!(tracker.level(0), AlertLevel::Critical);
+
+        let mut store = ThresholdStore::default();
+        let inverted = OutputThreshold {
+            output: 0,
+            warning: Some(0.9),
+            critical: Some(0.7),
+            below: false,
+        };
+        assert!(store.set("main-network", vec![inverted]).is_err());
+        store.set("main-network", thresholds.clone()).unwrap();
+        let path =
+            std::env::temp_dir().join(format!("benny-thresholds-{}.json", std::process::id()));
+        store.save(&path).unwrap();
+        let mut loaded = ThresholdStore::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get("main-network"), thresholds.as_slice());
+        loaded.set("main-network", Vec::new()).unwrap();
+        assert!(loaded.get("main-network").is_empty());
+        assert_eq!(
+            ThresholdStore::load(&path).unwrap(),
+            ThresholdStore::default()
+        );
+    }
+}
@@ -0,0 +1,187 @@
+/// Frame kind of an output sample
+pub const KIND_OUTPUT: u8 = 1;
+/// Flag set when the frame carries per-output min and max arrays after the outputs
+pub const FLAG_ENVELOPE: u8 = 1;
+/// Flag set when the frame ends with the values of derived channels
+pub const FLAG_DERIVED: u8 = 2;
+/// Bytes before the network id length: kind, flags, output count (u16) and timestamp (u64)
+pub const HEADER_SIZE: usize = 12;
+
+/// Output sample in the binary WebSocket format
+///
+/// Layout, all little-endian:
+///
+/// | bytes | field                                      |
+/// |-------|--------------------------------------------|
+/// | 1     | kind (`KIND_OUTPUT`)                       |
+/// | 1     | flags (`FLAG_ENVELOPE`, `FLAG_DERIVED`)    |
+/// | 2     | output count `n`                           |
+/// | 8     | timestamp, ms since the Unix epoch         |
+/// | 1     | network id length `k`                      |
+/// | k     | network id, UTF-8                          |
+/// | 4·n   | outputs as f32                             |
+/// | 8·n   | min then max as f32, with `FLAG_ENVELOPE`  |
+/// | 2     | derived count `m`, with `FLAG_DERIVED`     |
+/// | 4·m   | derived values as f32, with `FLAG_DERIVED` |
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFrame {
+    pub network_id: String,
+    pub timestamp: u64,
+    pub outputs: Vec<f32>,
+    pub envelope: Option<(Vec<f32>, Vec<f32>)>,
+    pub derived: Vec<f32>,
+}
+
+impl OutputFrame {
+    pub fn new(network_id: &str, timestamp: u64, outputs: &[f64]) -> Self {
+        Self {
+            network_id: network_id.to_string(),
+            timestamp,
+            outputs: outputs.iter().map(|&v| v as f32).collect(),
+            envelope: None,
+            derived: Vec::new(),
+        }
+    }
+
+    pub fn with_envelope(mut self, min: &[f64], max: &[f64]) -> Self {
+        let to_f32 = |values: &[f64]| values.iter().map(|&v| v as f32).collect();
+        self.envelope = Some((to_f32(min), to_f32(max)));
+        self
+    }
+
+    pub fn with_derived(mut self, derived: &[f64]) -> Self {
+        self.derived = derived.iter().map(|&v| v as f32).collect();
+        self
+    }
+
+    /// Network ids longer than 255 bytes are truncated at a character boundary
+    pub fn encode(&self) -> Vec<u8> {
+        let mut id_len = self.network_id.len().min(u8::MAX as usize);
+        while !self.network_id.is_char_boundary(id_len) {
+            id_len -= 1;
+        }
+        let arrays = if self.envelope.is_some() { 3 } else { 1 };
+        let mut bytes = Vec::with_capacity(
+            HEADER_SIZE + 1 + id_len + 4 * arrays * self.outputs.len() + 2 + 4 * self.derived.len(),
+        );
+        bytes.push(KIND_OUTPUT);
+        let mut flags = 0;
+        if self.envelope.is_some() {
+            flags |= FLAG_ENVELOPE;
+        }
+        if !self.derived.is_empty() {
+            flags |= FLAG_DERIVED;
+        }
+        bytes.push(flags);
+        bytes.extend_from_slice(&(self.outputs.len() as u16).to_le_bytes());
+        bytes.extend_from_slice(&self.timestamp.to_le_bytes());
+        bytes.push(id_len as u8);
+        bytes.extend_from_slice(&self.network_id.as_bytes()[..id_len]);
+        let mut values: Vec<&f32> = self.outputs.iter().collect();
+        if let Some((min, max)) = &self.envelope {
+            values.extend(min.iter().chain(max));
+        }
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        if !self.derived.is_empty() {
+            bytes.extend_from_slice(&(self.derived.len() as u16).to_le_bytes());
+            for value in &self.derived {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_SIZE + 1 {
+            return Err(format!(
+                "Frame of {} bytes is shorter than its header",
+                bytes.len()
+            ));
+        }
+        if bytes[0] != KIND_OUTPUT {
+            return Err(format!("Unknown frame kind {}", bytes[0]));
+        }
+        let envelope = bytes[1] & FLAG_ENVELOPE != 0;
+        let count = u16::from_le_bytes([bytes[2], bytes[3]]) as usize;
+        let timestamp = u64::from_le_bytes(bytes[4..12].try_into().unwrap());
+        let id_end = HEADER_SIZE + 1 + bytes[HEADER_SIZE] as usize;
+        let arrays = if envelope { 3 } else { 1 };
+        let arrays_end = id_end + 4 * arrays * count;
+        let (derived_start, derived_count) = if bytes[1] & FLAG_DERIVED == 0 {
+            (arrays_end, 0)
+        } else if bytes.len() >= arrays_end + 2 {
+            let count = u16::from_le_bytes([bytes[arrays_end], bytes[arrays_end + 1]]);
+            (arrays_end + 2, count as usize)
+        } else {
+            return Err(format!(
+                "Frame of {} bytes lacks its derived count",
+                bytes.len()
+            ));
+        };
+        if bytes.len() != derived_start + 4 * derived_count {
+            return Err(format!(
+                "Frame of {} bytes does not hold {} outputs and {} derived values",
+                bytes.len(),
+                count,
+                derived_count
+            ));
+        }
+        let network_id = std::str::from_utf8(&bytes[HEADER_SIZE + 1..id_end])
+            .map_err(|e| format!("Invalid network id: {}", e))?
+            .to_string();
+        let array = |index: usize| -> Vec<f32> {
+            bytes[id_end + 4 * count * index..id_end + 4 * count * (index + 1)]
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect()
+        };
+        Ok(Self {
+            network_id,
+            timestamp,
+            outputs: array(0),
+            envelope: envelope.then(|| (array(1), array(2))),
+            derived: bytes[derived_start..]
+                .chunks_exact(4)
+                .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_roundtrip() {
+        let frame = OutputFrame::new("main-network", 1_700_000_000_123, &[0.25, -1.5]);
+        let bytes = frame.encode();
+        assert_eq!(bytes.len(), HEADER_SIZE + 1 + 12 + 8);
+        assert_eq!(&bytes[2..4], &[2, 0]);
+        assert_eq!(OutputFrame::decode(&bytes).unwrap(), frame);
+
+        let frame = frame.with_envelope(&[0.0, -2.0], &[0.5, 1.0]);
+        let bytes = frame.encode();
+        assert_eq!(bytes[1], FLAG_ENVELOPE);
+        assert_eq!(OutputFrame::decode(&bytes).unwrap(), frame);
+
+        let frame = frame.with_derived(&[0.75]);
+        let bytes = frame.encode();
+        assert_eq!(bytes[1], FLAG_ENVELOPE | FLAG_DERIVED);
+        assert_eq!(&bytes[bytes.len() - 6..bytes.len() - 4], &[1, 0]);
+        assert_eq!(OutputFrame::decode(&bytes).unwrap(), frame);
+
+        assert!(OutputFrame::decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(OutputFrame::decode(&[2; 20]).is_err());
+        let long = OutputFrame::new(&"é".repeat(200), 0, &[]);
+        assert_eq!(
+            OutputFrame::decode(&long.encode())
+                .unwrap()
+                .network_id
+                .len(),
+            254
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS outputs (
+        network_id TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        outputs BLOB NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS outputs_by_network ON outputs (network_id, timestamp);
+";
+
+/// Samples returned by a query that does not set a limit
+pub const DEFAULT_QUERY_LIMIT: usize = 1000;
+
+/// Most samples a single query may return
+pub const MAX_QUERY_LIMIT: usize = 100_000;
+
+/// How long recorded outputs are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct RetentionPolicy {
+    /// Drop samples older than this many seconds
+    #[serde(default)]
+    pub max_age_secs: Option<u64>,
+    /// Keep at most this many samples per network, dropping the oldest
+    #[serde(default)]
+    pub max_samples: Option<u64>,
+}
+
+/// Where an OutputServer keeps the outputs it receives
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// SQLite database, created if missing
+    pub path: PathBuf,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+}
+
+impl HistoryConfig {
+    /// Keep everything in the database at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            retention: RetentionPolicy::default(),
+        }
+    }
+}
+
+/// One recorded output sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistorySample {
+    pub network_id: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub outputs: Vec<f64>,
+}
+
+/// A window of recorded outputs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryQuery {
+    /// Every network when unset
+    pub network_id: Option<String>,
+    /// Earliest timestamp, inclusive
+    pub from: Option<u64>,
+    /// Latest timestamp, inclusive
+    pub to: Option<u64>,
+    /// Most samples returned; the latest of the window are kept
+    pub limit: usize,
+}
+
+impl Default for HistoryQuery {
+    fn default() -> Self {
+        Self {
+            network_id: None,
+            from: None,
+            to: None,
+            limit: DEFAULT_QUERY_LIMIT,
+        }
+    }
+}
+
+impl HistoryQuery {
+    /// Read a URL query string such as `network_id=main-network&from=1700000000000&limit=500`
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value)?;
+            let number = |value: &str| {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid {} '{}'", key, value))
+            };
+            match key {
+                "network_id" => parsed.network_id = Some(value),
+                "from" => parsed.from = Some(number(&value)?),
+                "to" => parsed.to = Some(number(&value)?),
+                "limit" => parsed.limit = number(&value)? as usize,
+                _ => return Err(format!("Unknown history parameter '{}'", key)),
+            }
+        }
+        if parsed.limit == 0 || parsed.limit > MAX_QUERY_LIMIT {
+            return Err(format!("Limit must be between 1 and {}", MAX_QUERY_LIMIT));
+        }
+        if let (Some(from), Some(to)) = (parsed.from, parsed.to) {
+            if from > to {
+                return Err(format!("from {} is after to {}", from, to));
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+fn percent_decode(value: &str) -> Result<String, String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = value
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| format!("Invalid escape in '{}'", value))?;
+                decoded.push(hex);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| format!("Invalid UTF-8 in '{}'", value))
+}
+
+/// Outputs received by an OutputServer, kept in a SQLite database
+///
+/// Samples are stored as little-endian `f64`s so values that are not finite
+/// survive the round trip.
+pub struct OutputHistory {
+    conn: Connection,
+    retention: RetentionPolicy,
+}
+
+impl OutputHistory {
+    /// Open (creating if needed) the database of `config`
+    pub fn open(config: &HistoryConfig) -> Result<Self, rusqlite::Error> {
+        let conn = Connection::open(&config.path)?;
+        // One transaction per sample; WAL keeps that from syncing on every write
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.pragma_update(None, "synchronous", "NORMAL")?;
+        Self::with_connection(conn, config.retention)
+    }
+
+    /// History that lives only as long as the process
+    pub fn in_memory(retention: RetentionPolicy) -> Result<Self, rusqlite::Error> {
+        Self::with_connection(Connection::open_in_memory()?, retention)
+    }
+
+    fn with_connection(
+        conn: Connection,
+        retention: RetentionPolicy,
+    ) -> Result<Self, rusqlite::Error> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn, retention })
+    }
+
+    pub fn retention(&self) -> RetentionPolicy {
+        self.retention
+    }
+
+    pub fn record(
+        &self,
+        network_id: &str,
+        timestamp: u64,
+        outputs: &[f64],
+    ) -> Result<(), rusqlite::Error> {
+        let blob: Vec<u8> = outputs.iter().flat_map(|v| v.to_le_bytes()).collect();
+        self.conn.execute(
+            "INSERT INTO outputs (network_id, timestamp, outputs) VALUES (?1, ?2, ?3)",
+            params![network_id, timestamp as i64, blob],
+        )?;
+        Ok(())
+    }
+
+    /// Samples in the window of `query`, oldest first
+    pub fn query(&self, query: &HistoryQuery) -> Result<Vec<HistorySample>, rusqlite::Error> {
+        let mut conditions = Vec::new();
+        let mut values = Vec::new();
+        if let Some(network_id) = &query.network_id {
+            conditions.push("network_id = ?");
+            values.push(Value::Text(network_id.clone()));
+        }
+        if let Some(from) = query.from {
+            conditions.push("timestamp >= ?");
+            values.push(Value::Integer(from as i64));
+        }
+        if let Some(to) = query.to {
+            conditions.push("timestamp <= ?");
+            values.push(Value::Integer(to as i64));
+        }
+        values.push(Value::Integer(query.limit as i64));
+        let condition = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!("WHERE {}", conditions.join(" AND "))
+        };
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT network_id, timestamp, outputs FROM outputs {} \
+             ORDER BY timestamp DESC, rowid DESC LIMIT ?",
+            condition
+        ))?;
+        let samples = statement.query_map(params_from_iter(values), |row| {
+            let blob: Vec<u8> = row.get(2)?;
+            Ok(HistorySample {
+                network_id: row.get(0)?,
+                timestamp: row.get::<_, i64>(1)? as u64,
+                outputs: blob
+                    .chunks_exact(8)
+                    .map(|bytes| f64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect(),
+            })
+        })?;
+        let mut samples = samples.collect::<Result<Vec<_>, _>>()?;
+        samples.reverse();
+        Ok(samples)
+    }
+
+    /// Apply the retention policy as of `now` (milliseconds since the Unix epoch), returning the samples dropped
+    pub fn prune(&self, now: u64) -> Result<usize, rusqlite::Error> {
+        let mut dropped = 0;
+        if let Some(max_age_secs) = self.retention.max_age_secs {
+            let cutoff = now.saturating_sub(max_age_secs.saturating_mul(1000));
+            dropped += self
+                .conn
+                .execute("DELETE FROM outputs WHERE timestamp < ?1", [cutoff as i64])?;
+        }
+        if let Some(max_samples) = self.retention.max_samples {
+            dropped += self.conn.execute(
+                "DELETE FROM outputs WHERE rowid IN (\
+                 SELECT rowid FROM (SELECT rowid, ROW_NUMBER() OVER (\
+                 PARTITION BY network_id ORDER BY timestamp DESC, rowid DESC) AS n FROM outputs) \
+                 WHERE n > ?1)",
+                [max_samples as i64],
+            )?;
+        }
+        Ok(dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_windows_and_retention() {
+        let retention = RetentionPolicy {
+            max_age_secs: Some(60),
+            max_samples: Some(3),
+        };
+        let history = OutputHistory::in_memory(retention).unwrap();
+        for (i, timestamp) in [1_000, 2_000, 3_000, 4_000].into_iter().enumerate() {
+            history
+                .record("main-network", timestamp, &[i as f64, 0.5])
+                .unwrap();
+        }
+        history.record("other", 2_500, &[f64::NAN]).unwrap();
+
+        let window = history
+            .query(&HistoryQuery::parse("network_id=main-network&from=2000&to=3000").unwrap())
+            .unwrap();
+        assert_eq!(
+            window.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+            vec![2_000, 3_000]
+        );
+        assert_eq!(window[0].outputs, vec![1.0, 0.5]);
+        // A limit keeps the latest samples of the window
+        let latest = history
+            .query(&HistoryQuery {
+                limit: 2,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(
+            latest.iter().map(|s| s.timestamp).collect::<Vec<_>>(),
+            vec![3_000, 4_000]
+        );
+        let other = history
+            .query(&HistoryQuery::parse("network_id=other").unwrap())
+            .unwrap();
+        assert!(other[0].outputs[0].is_nan());
+
+        // One main-network sample over the cap, then everything older than a minute
+        assert_eq!(history.prune(4_000).unwrap(), 1);
+        assert_eq!(history.prune(63_500).unwrap(), 3);
+        let remaining = history.query(&HistoryQuery::default()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].timestamp, 4_000);
+
+        let query = HistoryQuery::parse("network_id=main%20network&limit=5").unwrap();
+        assert_eq!(query.network_id.as_deref(), Some("main network"));
+        assert_eq!(query.limit, 5);
+        assert!(HistoryQuery::parse("from=5&to=1").is_err());
+        assert!(HistoryQuery::parse("limit=0").is_err());
+        assert!(HistoryQuery::parse("since=1").is_err());
+    }
+}
@@ -1,18 +1,37 @@
-use tokio::net::{TcpListener, TcpStream};
+use tokio::net::TcpStream;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use futures_util::{SinkExt, StreamExt};
+use futures_util::StreamExt;
 use hyper::service::{make_service_fn, service_fn};
 use hyper::{Body, Request, Response, Server, StatusCode};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::convert::Infallible;
 use std::net::SocketAddr;
-use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Instant;
+use tokio::sync::{Notify, RwLock, watch};
 use tokio_tungstenite::{accept_async, tungstenite::Message};
 
 // Import NNP protocol components
-use crate::distributed_network::{MessageType, MessagePayload, NetworkMessage};
+use crate::address::{self, BindOptions, BoundPorts};
+use crate::broadcast::{self, Broadcaster, WebSocketClient};
+use crate::transport::{self, NnpListener, NnpStream};
+use crate::ws_session::{SessionConfig, SessionStore};
+use crate::compatibility;
+use crate::dashboard::{Dashboard, DashboardStore};
+use crate::decimation::{Decimation, Decimator};
+use crate::derived_signals::{self, DerivedChannel, DerivedSignals};
+use crate::display::DisplayMetadata;
+use crate::distributed_network::{self, MessageType, MessagePayload, NetworkMessage, PeerInfo};
+use crate::health::{self, ComponentHealth, HealthReport};
+use crate::output_alerts::{AlertTracker, OutputAlert, OutputThreshold, ThresholdStore};
+use crate::output_frame::OutputFrame;
+use crate::output_history::{HistoryConfig, HistoryQuery, OutputHistory};
+use crate::output_sink::{OutputSink, SinkConfig};
+use crate::reload::{self, ReloadReport};
+use crate::scripting::{ScriptConfig, ScriptTransform};
 use byteorder::{BigEndian, ByteOrder};
 use uuid::Uuid;
 
@@ -36,10 +55,54 @@ pub struct OutputServerConfig {
     pub cert_path: Option<String>,
     /// SSL key path (optional)
     pub key_path: Option<String>,
+    /// Address family preference and dual-stack behaviour for all listeners
+    #[serde(default)]
+    pub bind_options: BindOptions,
+    /// JSON file the dashboards saved from the web UI are kept in; without one they last until restart
+    #[serde(default)]
+    pub dashboard_file: Option<String>,
+    /// JSON file the alert thresholds set from the web UI are kept in; without one they last until restart
+    #[serde(default)]
+    pub threshold_file: Option<String>,
+    /// SQLite database every received output is kept in; without one only live data is shown
+    #[serde(default)]
+    pub history: Option<HistoryConfig>,
+    /// Time-series databases the received outputs are forwarded to
+    #[serde(default)]
+    pub sinks: Vec<SinkConfig>,
+    /// How long dashboard sessions can be resumed after a disconnect, and the outputs replayed on resume
+    #[serde(default)]
+    pub sessions: SessionConfig,
+}
+
+impl OutputServerConfig {
+    /// Loopback web listeners on OS-assigned ports, for tests; see `OutputServer::bound_ports`
+    ///
+    /// The NNP listener still uses the first source's address, which may set port 0 too.
+    pub fn ephemeral(
+        expected_output_size: usize,
+        neural_networks: Vec<NeuralNetworkSource>,
+    ) -> Self {
+        Self {
+            web_address: "127.0.0.1".to_string(),
+            web_port: 0,
+            websocket_port: 0,
+            expected_output_size,
+            neural_networks,
+            cert_path: None,
+            key_path: None,
+            bind_options: BindOptions::default(),
+            dashboard_file: None,
+            threshold_file: None,
+            history: None,
+            sinks: Vec::new(),
+            sessions: SessionConfig::default(),
+        }
+    }
 }
 
 /// Configuration for a source neural network
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct NeuralNetworkSource {
     /// Unique identifier for this network
     pub id: String,
@@ -53,6 +116,21 @@ pub struct NeuralNetworkSource {
     pub output_count: usize,
     /// Use TLS for connection
     pub use_tls: bool,
+    /// NNP endpoint (`host:port`) of the producing node, queried for its details
+    #[serde(default)]
+    pub info_endpoint: Option<String>,
+    /// How outputs are thinned out before reaching dashboards; adjustable live
+    #[serde(default)]
+    pub decimation: Decimation,
+    /// Channels computed from every raw sample and streamed next to the outputs
+    #[serde(default)]
+    pub derived: Vec<DerivedChannel>,
+    /// Script run on received outputs before decimation (optional)
+    #[serde(default)]
+    pub postprocess: Option<ScriptConfig>,
+    /// Color, description, output labels, units and ranges shown by the monitor
+    #[serde(default)]
+    pub display: DisplayMetadata,
 }
 
 /// WebSocket message types for output display
@@ -68,13 +146,94 @@ pub enum OutputWebSocketMessage {
         network_id: String,
         outputs: Vec<f64>,
         timestamp: u64,
+        /// Per-output minimum since the previous sample, under envelope decimation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        min: Option<Vec<f64>>,
+        /// Per-output maximum since the previous sample, under envelope decimation
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        max: Option<Vec<f64>>,
+        /// Values of the source's derived channels, in configured order
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        derived: Vec<f64>,
     },
     /// Server sends status update
     StatusUpdate { network_id: String, status: String },
+    /// Client asks a network for its architecture and statistics
+    GetNetworkDetails { network_id: String },
+    /// Server responds with what the network reported about itself
+    NetworkDetails { network_id: String, info: PeerInfo },
+    /// Client changes how a network's outputs are decimated
+    SetDecimation { network_id: String, decimation: Decimation },
+    /// Server announces a network's new decimation to every client
+    DecimationChanged { network_id: String, decimation: Decimation },
+    /// Client requests the names of the saved dashboards
+    ListDashboards,
+    /// Server sends the names of the saved dashboards, on connection and after every change
+    DashboardList { names: Vec<String> },
+    /// Client requests a saved dashboard
+    LoadDashboard { name: String },
+    /// Server responds with the requested dashboard
+    DashboardLoaded { dashboard: Dashboard },
+    /// Client saves a dashboard, replacing any with the same name
+    SaveDashboard { dashboard: Dashboard },
+    /// Client deletes a saved dashboard
+    DeleteDashboard { name: String },
+    /// Client chooses how it receives `OutputData`; everything else stays JSON
+    SetStreamFormat { format: StreamFormat },
+    /// Server confirms the format this client now receives output data in
+    StreamFormatChanged { format: StreamFormat },
+    /// Client replaces a network's alert thresholds; an empty list removes them
+    SetThresholds { network_id: String, thresholds: Vec<OutputThreshold> },
+    /// Server sends a network's alert thresholds, on connection and after every change
+    ThresholdsChanged { network_id: String, thresholds: Vec<OutputThreshold> },
+    /// Server announces that an output crossed into another alert level
+    Alert { alert: OutputAlert },
+    /// Server issues the session token of a new connection
+    SessionStarted { token: String },
+    /// Client takes up the session of a previous connection, replaying outputs newer than `since`
+    Resume {
+        token: String,
+        #[serde(default)]
+        since: Option<u64>,
+    },
+    /// Server restores a resumed session; the replayed `OutputData` follows
+    SessionResumed { token: String, session: DashboardSession, replayed: usize },
+    /// Server cannot resume the session; the client keeps the one it was issued
+    SessionExpired { token: String },
+    /// Client reports what it shows, to be restored when it resumes
+    SetView {
+        #[serde(default)]
+        selected_network: Option<String>,
+        #[serde(default)]
+        dashboard: Option<String>,
+    },
     /// Error message
     Error { message: String },
 }
 
+/// Encoding of `OutputData` for one WebSocket client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StreamFormat {
+    /// `OutputData` JSON text frames
+    #[default]
+    Json,
+    /// Binary frames in the `OutputFrame` layout
+    Binary,
+}
+
+/// What a dashboard client was showing, kept for it to resume
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DashboardSession {
+    /// Network whose details were open
+    pub selected_network: Option<String>,
+    /// Loaded dashboard; `None` shows every network
+    pub dashboard: Option<String>,
+    pub stream_format: StreamFormat,
+}
+
+/// Dashboard sessions, with the recent `OutputData` replayed to resuming clients
+pub type DashboardSessions = SessionStore<DashboardSession, OutputWebSocketMessage>;
+
 /// Network information for the web interface
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputNetworkInfo {
@@ -85,28 +244,144 @@ pub struct OutputNetworkInfo {
     pub output_count: usize,
     pub connected: bool,
     pub use_tls: bool,
+    #[serde(default)]
+    pub decimation: Decimation,
+    #[serde(default)]
+    pub display: DisplayMetadata,
+    /// Channels derived from the outputs, streamed after them
+    #[serde(default)]
+    pub derived: Vec<DerivedChannel>,
+}
+
+/// Readiness state shared by the listeners of an OutputServer
+#[derive(Debug, Default)]
+struct OutputHealth {
+    listening: AtomicBool,
+    producers: AtomicUsize,
+}
+
+impl OutputHealth {
+    fn report(&self) -> HealthReport {
+        let listening = self.listening.load(Ordering::Relaxed);
+        let mut report = HealthReport::new();
+        report.push(ComponentHealth::new(
+            "nnp_listener",
+            listening,
+            if listening { "listening" } else { "not bound" },
+        ));
+        report.push(ComponentHealth::new(
+            "producers",
+            true,
+            format!("{} connected", self.producers.load(Ordering::Relaxed)),
+        ));
+        report
+    }
+}
+
+/// Where the NNP connections keep what they learn from each sample
+#[derive(Clone)]
+struct SampleStores {
+    thresholds: Arc<RwLock<ThresholdStore>>,
+    history: Arc<Mutex<Option<OutputHistory>>>,
+    sinks: Arc<RwLock<Vec<OutputSink>>>,
+    sessions: Arc<Mutex<DashboardSessions>>,
 }
 
 /// OutputServer manages web interface and neural network output display
 pub struct OutputServer {
-    config: OutputServerConfig,
+    config: Arc<RwLock<OutputServerConfig>>,
     network_status: Arc<RwLock<HashMap<String, bool>>>,
-    websocket_clients: Arc<RwLock<Vec<mpsc::UnboundedSender<OutputWebSocketMessage>>>>,
+    broadcaster: Broadcaster,
+    dashboards: Arc<RwLock<DashboardStore>>,
+    thresholds: Arc<RwLock<ThresholdStore>>,
+    history: Arc<Mutex<Option<OutputHistory>>>,
+    sinks: Arc<RwLock<Vec<OutputSink>>>,
+    sessions: Arc<Mutex<DashboardSessions>>,
+    health: Arc<OutputHealth>,
+    /// Listener bound by `reload` for the NNP accept loop to switch to
+    moved_listener: Mutex<Option<NnpListener>>,
+    listener_moved: Notify,
+    bound: watch::Sender<BoundPorts>,
 }
 
 impl OutputServer {
     /// Create a new OutputServer
     pub fn new(config: OutputServerConfig) -> Self {
+        let sessions = SessionStore::new(config.sessions);
         Self {
-            config,
+            config: Arc::new(RwLock::new(config)),
             network_status: Arc::new(RwLock::new(HashMap::new())),
-            websocket_clients: Arc::new(RwLock::new(Vec::new())),
+            broadcaster: Broadcaster::new(),
+            dashboards: Arc::new(RwLock::new(DashboardStore::default())),
+            thresholds: Arc::new(RwLock::new(ThresholdStore::default())),
+            history: Arc::new(Mutex::new(None)),
+            sinks: Arc::new(RwLock::new(Vec::new())),
+            sessions: Arc::new(Mutex::new(sessions)),
+            health: Arc::new(OutputHealth::default()),
+            moved_listener: Mutex::new(None),
+            listener_moved: Notify::new(),
+            bound: watch::channel(BoundPorts::default()).0,
         }
     }
 
+    /// Ports the NNP, web and WebSocket listeners were bound to
+    ///
+    /// Resolves once `start` has bound all three, so it is awaited alongside it.
+    pub async fn bound_ports(&self) -> BoundPorts {
+        let mut bound = self.bound.subscribe();
+        let ports = bound
+            .wait_for(|ports| {
+                ports.nnp.is_some() && ports.web.is_some() && ports.websocket.is_some()
+            })
+            .await
+            .map(|ports| *ports);
+        ports.unwrap_or_default()
+    }
+
+    /// Readiness of the server, as served on `/readyz` and answered to NNP `Ping`
+    pub fn health(&self) -> HealthReport {
+        self.health.report()
+    }
+
+    /// Change how the outputs of source `network_id` are decimated, effective from the next sample
+    ///
+    /// Connected dashboards are told about the change.
+    pub async fn set_decimation(&self, network_id: &str, decimation: Decimation) -> Result<(), String> {
+        Self::update_decimation(&self.config, &self.broadcaster, network_id, decimation).await
+    }
+
     /// Start the OutputServer
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
         println!("🚀 Starting OutputServer...");
+        for source in &self.config.read().await.neural_networks {
+            source
+                .display
+                .validate(source.output_count)
+                .map_err(|e| format!("Display metadata of {}: {}", source.id, e))?;
+            derived_signals::validate(&source.derived, source.output_count)
+                .map_err(|e| format!("Derived channels of {}: {}", source.id, e))?;
+        }
+        self.broadcaster.spawn_fan_out();
+        if let Some(path) = self.config.read().await.dashboard_file.clone() {
+            let store = DashboardStore::load(Path::new(&path))?;
+            println!("📐 Loaded {} dashboards from {}", store.names().len(), path);
+            *self.dashboards.write().await = store;
+        }
+        if let Some(path) = self.config.read().await.threshold_file.clone() {
+            let store = ThresholdStore::load(Path::new(&path))?;
+            println!("🚨 Loaded alert thresholds for {} networks from {}", store.iter().count(), path);
+            *self.thresholds.write().await = store;
+        }
+        if let Some(history) = self.config.read().await.history.clone() {
+            let store = OutputHistory::open(&history)
+                .map_err(|e| format!("Cannot open history {}: {}", history.path.display(), e))?;
+            println!("🗄️ Recording outputs to {}", history.path.display());
+            *self.history.lock().unwrap() = Some(store);
+            self.spawn_history_pruning();
+        }
+        for sink in self.config.read().await.sinks.clone() {
+            self.sinks.write().await.push(OutputSink::spawn(sink)?);
+        }
 
         // Start TCP server for neural network connections
         let tcp_server = self.start_tcp_server();
@@ -123,29 +398,148 @@ impl OutputServer {
         Ok(())
     }
 
+    /// Apply a new configuration without restarting
+    ///
+    /// Sources are matched by id and connected dashboards receive the updated
+    /// network list. When the first source's listen address moves, the NNP
+    /// listener is rebound there while producers already connected stay on
+    /// the old socket. A new expected output size applies to connections made
+    /// from now on. The web and WebSocket listeners need a restart to move.
+    pub async fn reload(&self, config: OutputServerConfig) -> ReloadReport {
+        let old = std::mem::replace(&mut *self.config.write().await, config.clone());
+        let mut report = ReloadReport::default();
+        for field in reload::changed_fields(&old, &config) {
+            match field.as_str() {
+                "neural_networks" | "bind_options" => {}
+                "expected_output_size" => report.applied(format!(
+                    "Expected output size is now {} for new connections",
+                    config.expected_output_size
+                )),
+                _ => report.restart_required(field),
+            }
+        }
+
+        let diff = reload::diff_by_id(&old.neural_networks, &config.neural_networks, |s| &s.id);
+        for source in diff.removed {
+            self.network_status.write().await.remove(&source.id);
+            report.applied(format!("Removed source {}", source.id));
+        }
+        for source in diff.added {
+            report.applied(format!("Added source {}", source.id));
+        }
+        for source in diff.changed {
+            report.applied(format!("Updated source {}", source.id));
+        }
+
+        let listen = |config: &OutputServerConfig| {
+            config
+                .neural_networks
+                .first()
+                .map(|source| (source.listen_address.clone(), source.listen_port, config.bind_options))
+        };
+        if let Some((host, port, bind_options)) = listen(&config).filter(|new| listen(&old).as_ref() != Some(new)) {
+            let addr = transport::display_address(&host, port);
+            match NnpListener::bind(&host, port, bind_options).await {
+                Ok(listener) => {
+                    *self.moved_listener.lock().unwrap() = Some(listener);
+                    self.listener_moved.notify_one();
+                    report.applied(format!("NNP listener moved to {}", addr));
+                }
+                Err(e) => report.restart_required(format!(
+                    "NNP listen address (cannot bind {}: {})",
+                    addr, e
+                )),
+            }
+        }
+
+        let networks = Self::network_list(&config);
+        let _ = self
+            .broadcaster
+            .broadcast(&OutputWebSocketMessage::NetworkList { networks });
+        report
+    }
+
+    /// Apply the history's retention policy now and then every minute
+    fn spawn_history_pruning(&self) {
+        let history = Arc::clone(&self.history);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+            loop {
+                ticker.tick().await;
+                let now = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap()
+                    .as_millis() as u64;
+                if let Some(history) = history.lock().unwrap().as_ref() {
+                    match history.prune(now) {
+                        Ok(0) => {}
+                        Ok(dropped) => println!("🗄️ Dropped {} samples past the history retention", dropped),
+                        Err(e) => println!("❌ Failed to prune output history: {}", e),
+                    }
+                }
+            }
+        });
+    }
+
     /// Start TCP server for neural network connections
     async fn start_tcp_server(&self) -> Result<(), Box<dyn std::error::Error>> {
         // Use the first neural network source for the TCP server configuration
-        let source = &self.config.neural_networks[0];
-        let addr = format!("{}:{}", source.listen_address, source.listen_port);
-        let listener = TcpListener::bind(&addr).await?;
-        println!("🔗 TCP server listening on {} for neural network connections", addr);
-
-        let websocket_clients = Arc::clone(&self.websocket_clients);
-        let expected_output_size = self.config.expected_output_size;
+        let (source, bind_options) = {
+            let config = self.config.read().await;
+            (config.neural_networks[0].clone(), config.bind_options)
+        };
+        let mut listener =
+            NnpListener::bind(&source.listen_address, source.listen_port, bind_options).await?;
+        let nnp_port = listener.local_port();
+        self.bound.send_modify(|ports| ports.nnp = Some(nnp_port.unwrap_or(0)));
+        let addr = transport::display_address(
+            &source.listen_address,
+            nnp_port.unwrap_or(source.listen_port),
+        );
+        println!("🔗 NNP server listening on {} for neural network connections", addr);
+        self.health.listening.store(true, Ordering::Relaxed);
+
+        let broadcaster = self.broadcaster.clone();
+        let health = Arc::clone(&self.health);
+        let config = Arc::clone(&self.config);
+        let stores = SampleStores {
+            thresholds: Arc::clone(&self.thresholds),
+            history: Arc::clone(&self.history),
+            sinks: Arc::clone(&self.sinks),
+            sessions: Arc::clone(&self.sessions),
+        };
 
         loop {
-            match listener.accept().await {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = self.listener_moved.notified() => {
+                    if let Some(moved) = self.moved_listener.lock().unwrap().take() {
+                        listener = moved;
+                        let nnp_port = listener.local_port().unwrap_or(0);
+                        self.bound.send_modify(|ports| ports.nnp = Some(nnp_port));
+                        println!("🔗 NNP server switched to its new listener");
+                    }
+                    continue;
+                }
+            };
+            let expected_output_size = self.config.read().await.expected_output_size;
+            match accepted {
                 Ok((stream, addr)) => {
                     println!("🔗 New neural network connection from {}", addr);
-                    let websocket_clients = Arc::clone(&websocket_clients);
+                    let broadcaster = broadcaster.clone();
+                    let health = Arc::clone(&health);
+                    let config = Arc::clone(&config);
+                    let stores = stores.clone();
                     
                     tokio::spawn(async move {
                         if let Err(e) = Self::handle_neural_network_connection(
                             stream, 
                             "main-network".to_string(), // Use consistent network ID
-                            websocket_clients,
+                            broadcaster,
+                            config,
+                            stores,
                             expected_output_size,
+                            health,
                         ).await {
                             println!("❌ Error handling connection from {}: {:?}", addr, e);
                         }
@@ -160,18 +554,35 @@ impl OutputServer {
 
     /// Handle a connection from a neural network using NNP protocol
     async fn handle_neural_network_connection(
-        mut stream: TcpStream,
+        mut stream: NnpStream,
         network_id: String,
-        websocket_clients: Arc<RwLock<Vec<mpsc::UnboundedSender<OutputWebSocketMessage>>>>,
+        broadcaster: Broadcaster,
+        config: Arc<RwLock<OutputServerConfig>>,
+        stores: SampleStores,
         expected_output_size: usize,
+        health: Arc<OutputHealth>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         println!("📡 Started handling NNP connection from: {}", network_id);
+        let postprocess = Self::source_postprocess(&*config.read().await, &network_id)
+            .map_err(|e| format!("Postprocessing script for {}: {}", network_id, e))?;
         
         // Handle initial handshake
-        if let Err(e) = Self::handle_nnp_handshake(&mut stream, &network_id).await {
-            println!("❌ Handshake failed with {}: {:?}", network_id, e);
-            return Err(format!("Handshake failed: {:?}", e).into());
+        match Self::handle_nnp_handshake(&mut stream, &network_id, expected_output_size, &health).await {
+            Ok(true) => {}
+            Ok(false) => return Ok(()),
+            Err(e) => {
+                println!("❌ Handshake failed with {}: {:?}", network_id, e);
+                return Err(format!("Handshake failed: {:?}", e).into());
+            }
         }
+        health.producers.fetch_add(1, Ordering::Relaxed);
+        let mut decimator =
+            Decimator::new(Self::source_decimation(&*config.read().await, &network_id));
+        let mut derived = DerivedSignals::new(&Self::source_derived(&*config.read().await, &network_id));
+        let mut alerts = AlertTracker::new();
+        let display = Self::find_source(&*config.read().await, &network_id)
+            .map(|source| source.display.clone())
+            .unwrap_or_default();
         
         // Send NetworkList to all WebSocket clients after successful handshake
         let network_info = OutputNetworkInfo {
@@ -182,51 +593,100 @@ impl OutputServer {
             output_count: expected_output_size,
             connected: true,
             use_tls: false,
+            decimation: decimator.decimation().clone(),
+            display,
+            derived: derived.channels().to_vec(),
         };
         let network_list_msg = OutputWebSocketMessage::NetworkList { 
             networks: vec![network_info] 
         };
         
-        let clients = websocket_clients.read().await;
-        for client in clients.iter() {
-            let _ = client.send(network_list_msg.clone());
-        }
-        drop(clients);
-        println!("📋 Sent NetworkList to {} WebSocket clients", websocket_clients.read().await.len());
+        let _ = broadcaster.broadcast(&network_list_msg);
+        println!("📋 Sent NetworkList to {} WebSocket clients", broadcaster.client_count());
         
         loop {
             match Self::read_nnp_message(&mut stream).await {
                 Ok(Some(message)) => {
+                    if message.requires_ack() {
+                        let ack = NetworkMessage {
+                            msg_type: MessageType::Ack,
+                            sequence: message.sequence,
+                            payload: MessagePayload::Ack {
+                                sequence: message.sequence,
+                                timestamps: None,
+                            },
+                            flags: 0,
+                        };
+                        if let Err(e) = Self::send_nnp_message(&mut stream, ack).await {
+                            println!("❌ Failed to acknowledge message from {}: {:?}", network_id, e);
+                        }
+                    }
+
                     match message.payload {
-                        MessagePayload::ForwardData { layer_id: _, data } => {
+                        MessagePayload::ForwardData { data, .. } => {
                             // Convert f32 data to f64 for consistency
                             let outputs: Vec<f64> = data.iter().map(|&x| x as f64).collect();
                             
                             if outputs.len() == expected_output_size {
+                                let outputs = match &postprocess {
+                                    Some(script) => match script.apply(&network_id, &outputs) {
+                                        Ok(outputs) => outputs,
+                                        Err(e) => {
+                                            println!("❌ Failed to postprocess output from {}: {}", network_id, e);
+                                            continue;
+                                        }
+                                    },
+                                    None => outputs,
+                                };
                                 let timestamp = std::time::SystemTime::now()
                                     .duration_since(std::time::UNIX_EPOCH)
                                     .unwrap()
                                     .as_millis() as u64;
+                                // Every sample is recorded and checked, including those decimation drops
+                                if let Some(history) = stores.history.lock().unwrap().as_ref() {
+                                    if let Err(e) = history.record(&network_id, timestamp, &outputs) {
+                                        println!("❌ Failed to record output from {}: {}", network_id, e);
+                                    }
+                                }
+                                for sink in stores.sinks.read().await.iter() {
+                                    sink.send(&network_id, timestamp, &outputs);
+                                }
+                                let changes = alerts.evaluate(&network_id, &outputs, stores.thresholds.read().await.get(&network_id));
+                                for alert in changes {
+                                    println!("{}", alert.message());
+                                    let _ = broadcaster.broadcast(&OutputWebSocketMessage::Alert { alert });
+                                }
+                                // Picks up changes made from the UI or by a reload
+                                decimator.set_decimation(&Self::source_decimation(&*config.read().await, &network_id));
+                                derived.set_channels(&Self::source_derived(&*config.read().await, &network_id));
+                                // Derived channels see every sample and are decimated along with the outputs
+                                let derived_values = derived.push(&outputs, timestamp);
+                                let Some(sample) = decimator.push(&[outputs.as_slice(), &derived_values].concat()) else {
+                                    continue;
+                                };
 
+                                let mut sample_outputs = sample.outputs;
+                                let derived_values = sample_outputs.split_off(outputs.len());
+                                let (min, max) = sample.envelope.unzip();
+                                let raw = |mut values: Vec<f64>| {
+                                    values.truncate(outputs.len());
+                                    values
+                                };
                                 let output_message = OutputWebSocketMessage::OutputData {
                                     network_id: network_id.clone(),
-                                    outputs: outputs.clone(),
+                                    outputs: sample_outputs,
                                     timestamp,
+                                    min: min.map(raw),
+                                    max: max.map(raw),
+                                    derived: derived_values,
                                 };
+                                // Kept for clients that resume their session after missing it
+                                stores.sessions.lock().unwrap().record(&network_id, timestamp, output_message.clone());
 
-                                // Broadcast to all connected WebSocket clients
-                                let clients = websocket_clients.read().await;
-                                println!("🔌 Broadcasting to {} WebSocket clients", clients.len());
-                                for (i, client) in clients.iter().enumerate() {
-                                    match client.send(output_message.clone()) {
-                                        Ok(_) => {
-                                            println!("✅ Sent to WebSocket client {}", i);
-                                            if let Ok(json_str) = serde_json::to_string(&output_message) {
-                                                println!("📤 WebSocket message: {}", json_str);
-                                            }
-                                        },
-                                        Err(e) => println!("❌ Failed to send to WebSocket client {}: {:?}", i, e),
-                                    }
+                                // Serialized once here; the fan-out task delivers it to every client
+                                match broadcaster.broadcast_binary(&output_message, || Self::encode_output(&output_message)) {
+                                    Ok(()) => println!("🔌 Broadcasting to {} WebSocket clients", broadcaster.client_count()),
+                                    Err(e) => println!("❌ Failed to serialize output from {}: {:?}", network_id, e),
                                 }
 
                                 println!("📊 Received output data from {} via NNP: {:?}", network_id, outputs);
@@ -254,19 +714,38 @@ impl OutputServer {
             }
         }
         
+        health.producers.fetch_sub(1, Ordering::Relaxed);
         println!("📡 Connection handler for {} ended", network_id);
         Ok(())
     }
 
-    /// Handle NNP handshake
-    async fn handle_nnp_handshake(stream: &mut TcpStream, network_id: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// Handle NNP handshake, refusing producers whose output layer does not match `expected_output_size`
+    ///
+    /// Returns `false` when the connection was a health probe, which is answered with a `Pong`.
+    async fn handle_nnp_handshake(stream: &mut NnpStream, network_id: &str, expected_output_size: usize, health: &OutputHealth) -> Result<bool, Box<dyn std::error::Error>> {
         // Wait for handshake message
         match Self::read_nnp_message(stream).await {
             Ok(Some(message)) => {
                 match message.payload {
-                    MessagePayload::Handshake { network_id: peer_id, name, layers, capabilities } => {
+                    MessagePayload::Handshake { network_id: peer_id, name, layers, capabilities, .. } => {
                         println!("🤝 Received handshake from '{}' (ID: {})", name, peer_id);
                         println!("   Layers: {:?}, Capabilities: 0x{:08X}", layers, capabilities);
+
+                        if let Err(reason) = compatibility::check_output_size(expected_output_size, &layers) {
+                            let reject = NetworkMessage {
+                                msg_type: MessageType::HandshakeAck,
+                                sequence: 1,
+                                payload: MessagePayload::HandshakeAck {
+                                    network_id: Uuid::new_v4(),
+                                    accepted: false,
+                                    reason: Some(reason.clone()),
+                                    ranges: None,
+                                },
+                                flags: 0,
+                            };
+                            let _ = Self::send_nnp_message(stream, reject).await;
+                            return Err(format!("Rejected '{}': {}", name, reason).into());
+                        }
                         
                         // Send handshake acknowledgment
                         let ack_message = NetworkMessage {
@@ -275,14 +754,29 @@ impl OutputServer {
                             payload: MessagePayload::HandshakeAck {
                                 network_id: Uuid::new_v4(), // Our ID
                                 accepted: true,
+                                reason: None,
+                                ranges: None,
                             },
+                            flags: 0,
                         };
                         
                         if let Err(e) = Self::send_nnp_message(stream, ack_message).await {
                             return Err(format!("Failed to send handshake ack: {:?}", e).into());
                         }
                         println!("📤 Sent handshake acknowledgment to {}", network_id);
-                        Ok(())
+                        Ok(true)
+                    }
+                    MessagePayload::Ping => {
+                        let pong = NetworkMessage {
+                            msg_type: MessageType::Pong,
+                            sequence: 1,
+                            payload: MessagePayload::Pong { report: health.report() },
+                            flags: 0,
+                        };
+                        if let Err(e) = Self::send_nnp_message(stream, pong).await {
+                            return Err(format!("Failed to answer health probe: {:?}", e).into());
+                        }
+                        Ok(false)
                     }
                     _ => {
                         Err("Expected handshake message".into())
@@ -299,7 +793,7 @@ impl OutputServer {
     }
 
     /// Read an NNP message from the stream
-    async fn read_nnp_message(stream: &mut TcpStream) -> Result<Option<NetworkMessage>, Box<dyn std::error::Error + Send + Sync>> {
+    async fn read_nnp_message(stream: &mut NnpStream) -> Result<Option<NetworkMessage>, Box<dyn std::error::Error + Send + Sync>> {
         // Read header first
         let mut header = vec![0u8; HEADER_SIZE];
         match stream.read_exact(&mut header).await {
@@ -324,7 +818,7 @@ impl OutputServer {
     }
 
     /// Send an NNP message to the stream
-    async fn send_nnp_message(stream: &mut TcpStream, message: NetworkMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    async fn send_nnp_message(stream: &mut NnpStream, message: NetworkMessage) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         // Use the distributed network's serialization method
         let message_bytes = message.to_bytes();
         stream.write_all(&message_bytes).await?;
@@ -333,25 +827,38 @@ impl OutputServer {
 
     /// Start the WebSocket server
     async fn start_websocket_server(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = format!("{}:{}", self.config.web_address, self.config.websocket_port);
-        let listener = TcpListener::bind(&addr).await?;
+        let (web_address, websocket_port, bind_options) = {
+            let config = self.config.read().await;
+            (config.web_address.clone(), config.websocket_port, config.bind_options)
+        };
+        let listener =
+            address::bind_tcp_listener(&web_address, websocket_port, bind_options).await?;
+        let websocket_port = listener.local_addr()?.port();
+        self.bound.send_modify(|ports| ports.websocket = Some(websocket_port));
+        let addr = address::format_host_port(&web_address, websocket_port);
         println!("🌐 WebSocket server listening on {}", addr);
 
-        let network_status = Arc::clone(&self.network_status);
-        let websocket_clients = Arc::clone(&self.websocket_clients);
-        let config = self.config.clone();
+        let sessions = Arc::clone(&self.sessions);
+        let broadcaster = self.broadcaster.clone();
+        let dashboards = Arc::clone(&self.dashboards);
+        let thresholds = Arc::clone(&self.thresholds);
+        let config = Arc::clone(&self.config);
 
         while let Ok((stream, addr)) = listener.accept().await {
-            let network_status = Arc::clone(&network_status);
-            let websocket_clients = Arc::clone(&websocket_clients);
-            let config = config.clone();
+            let sessions = Arc::clone(&sessions);
+            let broadcaster = broadcaster.clone();
+            let dashboards = Arc::clone(&dashboards);
+            let thresholds = Arc::clone(&thresholds);
+            let config = Arc::clone(&config);
 
             tokio::spawn(async move {
                 if let Err(e) = Self::handle_websocket_connection(
                     stream,
                     addr,
-                    network_status,
-                    websocket_clients,
+                    sessions,
+                    broadcaster,
+                    dashboards,
+                    thresholds,
                     config,
                 )
                 .await
@@ -368,61 +875,54 @@ impl OutputServer {
     async fn handle_websocket_connection(
         stream: TcpStream,
         addr: SocketAddr,
-        _network_status: Arc<RwLock<HashMap<String, bool>>>,
-        websocket_clients: Arc<RwLock<Vec<mpsc::UnboundedSender<OutputWebSocketMessage>>>>,
-        config: OutputServerConfig,
+        sessions: Arc<Mutex<DashboardSessions>>,
+        broadcaster: Broadcaster,
+        dashboards: Arc<RwLock<DashboardStore>>,
+        thresholds: Arc<RwLock<ThresholdStore>>,
+        config: Arc<RwLock<OutputServerConfig>>,
     ) -> Result<(), Box<dyn std::error::Error>> {
         println!("🔌 New WebSocket connection from {}", addr);
 
         let ws_stream = accept_async(stream).await?;
         let (mut ws_sender, mut ws_receiver) = ws_stream.split();
 
-        let (tx, mut rx) = mpsc::unbounded_channel::<OutputWebSocketMessage>();
-
-        // Add client to the list
-        {
-            let mut clients = websocket_clients.write().await;
-            clients.push(tx.clone());
-        }
+        // Add client to the broadcast list
+        let (client, mut frames) = broadcaster.connect();
+        let mut token = sessions.lock().unwrap().open(Instant::now());
+        client.send(&OutputWebSocketMessage::SessionStarted { token: token.clone() });
 
         // Send initial network list
-        let networks = if let Some(network) = config.neural_networks.first() {
-            vec![OutputNetworkInfo {
-                id: network.id.clone(),
-                name: network.name.clone(),
-                listen_address: network.listen_address.clone(),
-                listen_port: network.listen_port,
-                output_count: network.output_count,
-                connected: true, // Assume connected for simplicity
-                use_tls: network.use_tls,
-            }]
-        } else {
-            vec![]
-        };
-        let network_list_msg = OutputWebSocketMessage::NetworkList { networks };
-        if let Ok(msg_text) = serde_json::to_string(&network_list_msg) {
-            let _ = ws_sender.send(Message::Text(msg_text)).await;
+        let networks = Self::network_list(&*config.read().await);
+        client.send(&OutputWebSocketMessage::NetworkList { networks });
+        let names = dashboards.read().await.names();
+        client.send(&OutputWebSocketMessage::DashboardList { names });
+        for (network_id, list) in thresholds.read().await.iter() {
+            client.send(&OutputWebSocketMessage::ThresholdsChanged {
+                network_id: network_id.clone(),
+                thresholds: list.clone(),
+            });
         }
 
         // Handle outgoing messages
         let ws_sender_task = tokio::spawn(async move {
-            while let Some(message) = rx.recv().await {
-                if let Ok(msg_text) = serde_json::to_string(&message) {
-                    if ws_sender.send(Message::Text(msg_text)).await.is_err() {
-                        break;
-                    }
-                }
-            }
+            broadcast::write_frames(&mut ws_sender, &mut frames).await;
         });
 
         // Handle incoming messages
-        let tx_clone = tx.clone();
+        let broadcaster_handle = broadcaster.clone();
         let ws_receiver_task = tokio::spawn(async move {
             while let Some(msg) = ws_receiver.next().await {
                 match msg {
                     Ok(Message::Text(text)) => {
-                        if let Ok(ws_msg) = serde_json::from_str::<OutputWebSocketMessage>(&text) {
-                            Self::handle_websocket_message(ws_msg, &tx_clone).await;
+                        match serde_json::from_str::<OutputWebSocketMessage>(&text) {
+                            Ok(OutputWebSocketMessage::Resume { token: previous, since }) => {
+                                Self::resume_session(&client, &sessions, &mut token, &previous, since);
+                            }
+                            Ok(ws_msg) => {
+                                Self::record_session(&sessions, &token, &ws_msg);
+                                Self::handle_websocket_message(ws_msg, &client, &broadcaster, &dashboards, &thresholds, &config).await;
+                            }
+                            Err(_) => {}
                         }
                     }
                     Ok(Message::Close(_)) => break,
@@ -430,6 +930,7 @@ impl OutputServer {
                     _ => {}
                 }
             }
+            sessions.lock().unwrap().close(&token, Instant::now());
         });
 
         // Wait for either task to complete
@@ -438,30 +939,319 @@ impl OutputServer {
             _ = ws_receiver_task => {},
         }
 
-        // Remove client from the list
-        {
-            let mut clients = websocket_clients.write().await;
-            clients.retain(|client| !client.is_closed());
-        }
+        // Remove client from the broadcast list
+        broadcaster_handle.remove_closed();
 
         println!("🔌 WebSocket connection from {} closed", addr);
         Ok(())
     }
 
+    /// Move a connection onto the session `previous` and replay the outputs it missed
+    ///
+    /// The connection keeps the session it was issued when `previous` is unknown or expired.
+    fn resume_session(
+        client: &WebSocketClient,
+        sessions: &Mutex<DashboardSessions>,
+        token: &mut String,
+        previous: &str,
+        since: Option<u64>,
+    ) {
+        let mut sessions = sessions.lock().unwrap();
+        if previous == token.as_str() {
+            return;
+        }
+        let Some(session) = sessions.resume(previous, Instant::now()) else {
+            client.send(&OutputWebSocketMessage::SessionExpired { token: previous.to_string() });
+            return;
+        };
+        sessions.discard(token);
+        *token = previous.to_string();
+        client.set_binary(session.stream_format == StreamFormat::Binary);
+        let replay = sessions.samples_since(since);
+        drop(sessions);
+
+        client.send(&OutputWebSocketMessage::SessionResumed {
+            token: token.clone(),
+            session,
+            replayed: replay.len(),
+        });
+        for message in &replay {
+            client.send(message);
+        }
+        println!("🔁 Resumed dashboard session, replaying {} outputs", replay.len());
+    }
+
+    /// Keep what a client message changes about its view in its session
+    fn record_session(sessions: &Mutex<DashboardSessions>, token: &str, message: &OutputWebSocketMessage) {
+        sessions.lock().unwrap().update(token, |session| match message {
+            OutputWebSocketMessage::SetView { selected_network, dashboard } => {
+                session.selected_network = selected_network.clone();
+                session.dashboard = dashboard.clone();
+            }
+            OutputWebSocketMessage::SetStreamFormat { format } => session.stream_format = *format,
+            _ => {}
+        });
+    }
+
     /// Handle WebSocket messages
     async fn handle_websocket_message(
         message: OutputWebSocketMessage,
-        _tx: &mpsc::UnboundedSender<OutputWebSocketMessage>,
+        client: &WebSocketClient,
+        broadcaster: &Broadcaster,
+        dashboards: &RwLock<DashboardStore>,
+        thresholds: &RwLock<ThresholdStore>,
+        config: &Arc<RwLock<OutputServerConfig>>,
     ) {
         match message {
             OutputWebSocketMessage::GetNetworks => {
                 // This would be handled by sending the network list on connection
                 // For now, we don't need to handle this specifically
             }
+            OutputWebSocketMessage::GetNetworkDetails { network_id } => {
+                let config = config.read().await.clone();
+                let response = match Self::query_network_details(&config, &network_id).await {
+                    Ok(info) => OutputWebSocketMessage::NetworkDetails { network_id, info },
+                    Err(message) => OutputWebSocketMessage::Error { message },
+                };
+                client.send(&response);
+            }
+            OutputWebSocketMessage::SetDecimation { network_id, decimation } => {
+                // Success is announced to every client by `update_decimation`
+                if let Err(message) =
+                    Self::update_decimation(config, broadcaster, &network_id, decimation).await
+                {
+                    client.send(&OutputWebSocketMessage::Error { message });
+                }
+            }
+            OutputWebSocketMessage::SetStreamFormat { format } => {
+                client.set_binary(format == StreamFormat::Binary);
+                client.send(&OutputWebSocketMessage::StreamFormatChanged { format });
+            }
+            OutputWebSocketMessage::ListDashboards => {
+                let names = dashboards.read().await.names();
+                client.send(&OutputWebSocketMessage::DashboardList { names });
+            }
+            OutputWebSocketMessage::LoadDashboard { name } => {
+                let response = match dashboards.read().await.get(&name) {
+                    Some(dashboard) => OutputWebSocketMessage::DashboardLoaded {
+                        dashboard: dashboard.clone(),
+                    },
+                    None => OutputWebSocketMessage::Error {
+                        message: format!("Dashboard {} not found", name),
+                    },
+                };
+                client.send(&response);
+            }
+            OutputWebSocketMessage::SaveDashboard { dashboard } => {
+                let name = dashboard.name.clone();
+                let result = Self::update_dashboards(config, dashboards, broadcaster, |store| {
+                    store.insert(dashboard)
+                })
+                .await;
+                match result {
+                    Ok(()) => println!("📐 Saved dashboard {}", name),
+                    Err(message) => {
+                        client.send(&OutputWebSocketMessage::Error { message });
+                    }
+                }
+            }
+            OutputWebSocketMessage::DeleteDashboard { name } => {
+                let result = Self::update_dashboards(config, dashboards, broadcaster, |store| {
+                    store
+                        .remove(&name)
+                        .map(|_| ())
+                        .ok_or_else(|| format!("Dashboard {} not found", name))
+                })
+                .await;
+                match result {
+                    Ok(()) => println!("📐 Deleted dashboard {}", name),
+                    Err(message) => {
+                        client.send(&OutputWebSocketMessage::Error { message });
+                    }
+                }
+            }
+            OutputWebSocketMessage::SetThresholds { network_id, thresholds: list } => {
+                // Success is announced to every client by `update_thresholds`
+                if let Err(message) =
+                    Self::update_thresholds(config, thresholds, broadcaster, network_id, list).await
+                {
+                    client.send(&OutputWebSocketMessage::Error { message });
+                }
+            }
             _ => {}
         }
     }
 
+    /// Apply `change` to the saved dashboards, write them to the dashboard file and announce the new list
+    async fn update_dashboards(
+        config: &RwLock<OutputServerConfig>,
+        dashboards: &RwLock<DashboardStore>,
+        broadcaster: &Broadcaster,
+        change: impl FnOnce(&mut DashboardStore) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let dashboard_file = config.read().await.dashboard_file.clone();
+        let names = {
+            let mut store = dashboards.write().await;
+            let mut updated = store.clone();
+            change(&mut updated)?;
+            if let Some(path) = dashboard_file {
+                updated.save(Path::new(&path))?;
+            }
+            *store = updated;
+            store.names()
+        };
+        let _ = broadcaster.broadcast(&OutputWebSocketMessage::DashboardList { names });
+        Ok(())
+    }
+
+    /// Replace a network's alert thresholds, write them to the threshold file and announce them
+    async fn update_thresholds(
+        config: &RwLock<OutputServerConfig>,
+        thresholds: &RwLock<ThresholdStore>,
+        broadcaster: &Broadcaster,
+        network_id: String,
+        list: Vec<OutputThreshold>,
+    ) -> Result<(), String> {
+        let threshold_file = config.read().await.threshold_file.clone();
+        {
+            let mut store = thresholds.write().await;
+            let mut updated = store.clone();
+            updated.set(&network_id, list.clone())?;
+            if let Some(path) = threshold_file {
+                updated.save(Path::new(&path))?;
+            }
+            *store = updated;
+        }
+        println!("🚨 Alert thresholds of {} set: {:?}", network_id, list);
+        let _ = broadcaster.broadcast(&OutputWebSocketMessage::ThresholdsChanged {
+            network_id,
+            thresholds: list,
+        });
+        Ok(())
+    }
+
+    /// `OutputData` in the binary frame layout for clients that negotiated it
+    fn encode_output(message: &OutputWebSocketMessage) -> Vec<u8> {
+        let OutputWebSocketMessage::OutputData { network_id, outputs, timestamp, min, max, derived } = message else {
+            return Vec::new();
+        };
+        let frame = OutputFrame::new(network_id, *timestamp, outputs).with_derived(derived);
+        match (min, max) {
+            (Some(min), Some(max)) => frame.with_envelope(min, max).encode(),
+            _ => frame.encode(),
+        }
+    }
+
+    /// Store a source's new decimation in the config and announce it to every client
+    async fn update_decimation(
+        config: &RwLock<OutputServerConfig>,
+        broadcaster: &Broadcaster,
+        network_id: &str,
+        decimation: Decimation,
+    ) -> Result<(), String> {
+        decimation.validate()?;
+        {
+            let mut config = config.write().await;
+            let source = config
+                .neural_networks
+                .iter_mut()
+                .find(|source| source.id == network_id)
+                .ok_or_else(|| format!("Network {} not found", network_id))?;
+            source.decimation = decimation.clone();
+        }
+        println!("🎚️ Decimation of {} set to {:?}", network_id, decimation);
+        let _ = broadcaster.broadcast(&OutputWebSocketMessage::DecimationChanged {
+            network_id: network_id.to_string(),
+            decimation,
+        });
+        Ok(())
+    }
+
+    /// Decimation of the source `network_id`, or of the source the NNP listener serves
+    fn source_decimation(config: &OutputServerConfig, network_id: &str) -> Decimation {
+        Self::find_source(config, network_id)
+            .map(|source| source.decimation.clone())
+            .unwrap_or_default()
+    }
+
+    /// Derived channels of the source `network_id` belongs to
+    fn source_derived(config: &OutputServerConfig, network_id: &str) -> Vec<DerivedChannel> {
+        Self::find_source(config, network_id)
+            .map(|source| source.derived.clone())
+            .unwrap_or_default()
+    }
+
+    /// Compiled postprocessing script of the source `network_id` belongs to, if it has one
+    fn source_postprocess(
+        config: &OutputServerConfig,
+        network_id: &str,
+    ) -> Result<Option<ScriptTransform>, String> {
+        Self::find_source(config, network_id)
+            .and_then(|source| source.postprocess.as_ref())
+            .map(ScriptTransform::new)
+            .transpose()
+    }
+
+    /// The source `network_id` belongs to, falling back to the first one
+    fn find_source<'a>(
+        config: &'a OutputServerConfig,
+        network_id: &str,
+    ) -> Option<&'a NeuralNetworkSource> {
+        config
+            .neural_networks
+            .iter()
+            .find(|source| source.id == network_id)
+            .or_else(|| config.neural_networks.first())
+    }
+
+    /// Ask the producing node of a source for its `PeerInfo`
+    async fn query_network_details(
+        config: &OutputServerConfig,
+        network_id: &str,
+    ) -> Result<PeerInfo, String> {
+        let source = config
+            .neural_networks
+            .iter()
+            .find(|source| source.id == network_id)
+            .ok_or_else(|| format!("Network {} not found", network_id))?;
+        let endpoint = source
+            .info_endpoint
+            .as_deref()
+            .ok_or_else(|| format!("No info endpoint configured for {}", source.name))?;
+        let (host, port) =
+            address::parse_host_port(endpoint, source.listen_port).map_err(|e| e.to_string())?;
+
+        distributed_network::query_info(
+            &host,
+            port,
+            config.bind_options.preference,
+            std::time::Duration::from_secs(2),
+        )
+        .await
+        .map_err(|e| format!("Failed to query {}: {:?}", endpoint, e))
+    }
+
+    /// Network list sent to dashboards: the source the NNP listener serves
+    fn network_list(config: &OutputServerConfig) -> Vec<OutputNetworkInfo> {
+        config
+            .neural_networks
+            .first()
+            .map(|network| OutputNetworkInfo {
+                id: network.id.clone(),
+                name: network.name.clone(),
+                listen_address: network.listen_address.clone(),
+                listen_port: network.listen_port,
+                output_count: network.output_count,
+                connected: true, // Assume connected for simplicity
+                use_tls: network.use_tls,
+                decimation: network.decimation.clone(),
+                display: network.display.clone(),
+                derived: network.derived.clone(),
+            })
+            .into_iter()
+            .collect()
+    }
+
     /// Get network information
     async fn get_network_info(
         config: &OutputServerConfig,
@@ -479,6 +1269,9 @@ impl OutputServer {
                 output_count: source.output_count,
                 connected: status.get(&source.id).copied().unwrap_or(false),
                 use_tls: source.use_tls,
+                decimation: source.decimation.clone(),
+                display: source.display.clone(),
+                derived: source.derived.clone(),
             })
             .collect()
     }
@@ -487,19 +1280,37 @@ impl OutputServer {
 
     /// Start the HTTP server for serving the web interface
     async fn start_http_server(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let addr = format!("{}:{}", self.config.web_address, self.config.web_port);
-        let websocket_port = self.config.websocket_port;
+        let (web_address, web_port, bind_options) = {
+            let config = self.config.read().await;
+            (config.web_address.clone(), config.web_port, config.bind_options)
+        };
+        let listener = address::bind_std_tcp_listener(&web_address, web_port, bind_options).await?;
+        let web_port = listener.local_addr()?.port();
+        self.bound.send_modify(|ports| ports.web = Some(web_port));
+        let addr = address::format_host_port(&web_address, web_port);
+        // The page connects to whichever port the WebSocket listener got
+        let websocket_port = self
+            .bound
+            .subscribe()
+            .wait_for(|ports| ports.websocket.is_some())
+            .await?
+            .websocket
+            .unwrap_or_default();
+        let health = Arc::clone(&self.health);
+        let history = Arc::clone(&self.history);
 
         let make_svc = make_service_fn(move |_conn| {
             let websocket_port = websocket_port;
+            let health = Arc::clone(&health);
+            let history = Arc::clone(&history);
             async move {
                 Ok::<_, Infallible>(service_fn(move |req| {
-                    Self::handle_http_request(req, websocket_port)
+                    Self::handle_http_request(req, websocket_port, Arc::clone(&health), Arc::clone(&history))
                 }))
             }
         });
 
-        let server = Server::bind(&addr.parse()?).serve(make_svc);
+        let server = Server::from_tcp(listener)?.serve(make_svc);
         println!("🌐 HTTP server listening on http://{}", addr);
 
         server.await?;
@@ -512,11 +1323,16 @@ impl OutputServer {
     async fn handle_http_request(
         req: Request<Body>,
         websocket_port: u16,
+        readiness: Arc<OutputHealth>,
+        history: Arc<Mutex<Option<OutputHistory>>>,
     ) -> Result<Response<Body>, Infallible> {
         match req.uri().path() {
             "/" => Ok(Self::serve_index_html(websocket_port)),
             "/style.css" => Ok(Self::serve_css()),
             "/script.js" => Ok(Self::serve_js(websocket_port)),
+            "/api/history" => Ok(Self::serve_history(req.uri().query().unwrap_or(""), &history)),
+            health::LIVENESS_PATH => Ok(health::liveness_response()),
+            health::READINESS_PATH => Ok(health::readiness_response(&readiness.report())),
             _ => Ok(Response::builder()
                 .status(StatusCode::NOT_FOUND)
                 .body(Body::from("Not Found"))
@@ -524,6 +1340,29 @@ impl OutputServer {
         }
     }
 
+    /// Answer a history query with the matching samples as JSON, oldest first
+    fn serve_history(query: &str, history: &Mutex<Option<OutputHistory>>) -> Response<Body> {
+        let respond = |status: StatusCode, body: String| {
+            Response::builder()
+                .status(status)
+                .header("content-type", if status == StatusCode::OK { "application/json" } else { "text/plain" })
+                .body(Body::from(body))
+                .unwrap()
+        };
+        let query = match HistoryQuery::parse(query) {
+            Ok(query) => query,
+            Err(e) => return respond(StatusCode::BAD_REQUEST, e),
+        };
+        let samples = match history.lock().unwrap().as_ref() {
+            Some(history) => history.query(&query),
+            None => return respond(StatusCode::NOT_FOUND, "History is not enabled".to_string()),
+        };
+        match samples.map_err(|e| e.to_string()).and_then(|samples| serde_json::to_string(&samples).map_err(|e| e.to_string())) {
+            Ok(json) => respond(StatusCode::OK, json),
+            Err(e) => respond(StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    }
+
     /// Serve the main HTML page
     fn serve_index_html(websocket_port: u16) -> Response<Body> {
         let html = format!(
@@ -539,7 +1378,13 @@ impl OutputServer {
     <div class="container">
         <header>
             <h1>📊 Neural Network Output Monitor</h1>
-            <div id="connection-status" class="status disconnected">Disconnected</div>
+            <div class="header-controls">
+                <select id="stream-format" title="Encoding of output data">
+                    <option value="Binary">Binary stream</option>
+                    <option value="Json">JSON stream</option>
+                </select>
+                <div id="connection-status" class="status disconnected">Disconnected</div>
+            </div>
         </header>
         
         <main>
@@ -548,6 +1393,21 @@ impl OutputServer {
                 <div id="networks-list"></div>
             </div>
             
+            <div id="dashboard-panel">
+                <h2>Dashboards</h2>
+                <div class="dashboard-controls">
+                    <select id="dashboard-select">
+                        <option value="">All networks</option>
+                    </select>
+                    <button id="load-dashboard" class="btn-secondary">Load</button>
+                    <button id="delete-dashboard" class="btn-secondary">Delete</button>
+                </div>
+                <div class="dashboard-controls">
+                    <input id="dashboard-name" type="text" placeholder="Dashboard name">
+                    <button id="save-dashboard" class="btn-secondary">Save Layout</button>
+                </div>
+            </div>
+            
             <div id="output-panel">
                 <h2>Real-time Output Display</h2>
                 <div id="output-visualizations"></div>
@@ -616,6 +1476,18 @@ header h1 {
     font-size: 2.2em;
 }
 
+.header-controls {
+    display: flex;
+    gap: 10px;
+    align-items: center;
+}
+
+.header-controls select {
+    padding: 6px;
+    border: 1px solid #ddd;
+    border-radius: 5px;
+}
+
 .status {
     padding: 8px 16px;
     border-radius: 20px;
@@ -640,7 +1512,7 @@ main {
     margin-bottom: 20px;
 }
 
-#networks-container, #output-panel {
+#networks-container, #dashboard-panel, #output-panel {
     background: rgba(255, 255, 255, 0.95);
     padding: 20px;
     border-radius: 10px;
@@ -685,6 +1557,24 @@ h2 {
     color: #666;
 }
 
+.network-info {
+    font-size: 0.85em;
+    color: #444;
+    margin-top: 8px;
+}
+
+.decimation-controls {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 6px;
+    align-items: center;
+    margin-top: 8px;
+}
+
+.decimation-value {
+    width: 80px;
+}
+
 .network-status {
     display: inline-block;
     padding: 2px 8px;
@@ -731,6 +1621,7 @@ h2 {
 }
 
 .output-bars {
+    position: relative;
     display: flex;
     gap: 8px;
     height: 120px;
@@ -751,6 +1642,99 @@ h2 {
     min-width: 20px;
 }
 
+.output-bar.derived {
+    opacity: 0.6;
+}
+
+.threshold-line {
+    position: absolute;
+    left: 0;
+    right: 0;
+    border-top: 2px dashed #e91e63;
+    pointer-events: none;
+}
+
+.output-chart {
+    width: 100%;
+    height: 120px;
+    margin: 15px 0;
+    background: white;
+    border-radius: 5px;
+}
+
+.dashboard-controls, .panel-settings {
+    display: flex;
+    flex-wrap: wrap;
+    gap: 8px;
+    align-items: center;
+    margin-bottom: 10px;
+}
+
+.dashboard-controls input, .dashboard-controls select, .panel-settings input, .panel-settings select {
+    padding: 6px;
+    border: 1px solid #ddd;
+    border-radius: 5px;
+}
+
+.output-value.above-threshold {
+    border-color: #e91e63;
+    background: #fce4ec;
+}
+
+.output-value.alert-warning {
+    border-color: #FF9800;
+    background: #fff3e0;
+}
+
+.output-value.alert-critical {
+    border-color: #f44336;
+    background: #ffebee;
+}
+
+.output-visualization.alert-warning {
+    box-shadow: 0 0 0 2px #FF9800;
+}
+
+.output-visualization.alert-critical {
+    box-shadow: 0 0 0 3px #f44336;
+}
+
+.alert-settings, .history-settings {
+    margin-top: 10px;
+    font-size: 0.9em;
+}
+
+.history-row {
+    display: flex;
+    gap: 8px;
+    align-items: center;
+    margin: 5px 0;
+}
+
+.history-row input {
+    padding: 4px;
+    border: 1px solid #ddd;
+    border-radius: 5px;
+}
+
+.alert-row {
+    display: flex;
+    gap: 8px;
+    align-items: center;
+    margin: 5px 0;
+}
+
+.alert-row span {
+    min-width: 80px;
+}
+
+.alert-row input, .alert-row select {
+    width: 90px;
+    padding: 4px;
+    border: 1px solid #ddd;
+    border-radius: 5px;
+}
+
 .output-bar-label {
     position: absolute;
     bottom: -25px;
@@ -776,6 +1760,10 @@ h2 {
     border: 1px solid #ddd;
 }
 
+.output-value.derived {
+    border-style: dashed;
+}
+
 .output-value-label {
     font-size: 0.8em;
     color: #666;
@@ -897,6 +1885,17 @@ class OutputMonitor {{
         this.reconnectAttempts = 0;
         this.maxReconnectAttempts = 5;
         this.outputData = new Map(); // Store latest output data for each network
+        this.history = new Map(); // Recent outputs per network, for line charts
+        this.historyLength = 100;
+        this.replay = new Map(); // Recorded window shown instead of live data, per network
+        this.panels = new Map(); // Visualization settings per network, in display order
+        this.dashboardName = null; // Loaded dashboard; null shows every network
+        this.thresholds = new Map(); // Alert thresholds per network, as stored by the server
+        this.alertLevels = new Map(); // Current alert level per `network:output`
+        this.sessionToken = sessionStorage.getItem('bennySession'); // Session resumed after a reconnect or reload
+        this.issuedToken = null; // Session of the current connection, kept when the resume fails
+        this.lastTimestamp = null; // Newest output received, so a resume replays only what was missed
+        this.selectedNetwork = null; // Network whose details are shown
         
         this.init();
     }}
@@ -917,15 +1916,30 @@ class OutputMonitor {{
         
         try {{
             this.ws = new WebSocket(wsUrl);
+            this.ws.binaryType = 'arraybuffer';
             
             this.ws.onopen = () => {{
                 this.log('WebSocket connected');
                 this.connected = true;
                 this.reconnectAttempts = 0;
                 this.updateConnectionStatus();
+                if (this.sessionToken) {{
+                    this.sendMessage({{ type: 'Resume', token: this.sessionToken, since: this.lastTimestamp }}, 'resume session');
+                }} else {{
+                    this.requestStreamFormat();
+                }}
             }};
             
             this.ws.onmessage = (event) => {{
+                if (event.data instanceof ArrayBuffer) {{
+                    const frame = this.decodeOutputFrame(event.data);
+                    if (frame) {{
+                        this.updateOutputData(frame.networkId, frame.outputs, frame.timestamp, frame.min, frame.max, frame.derived);
+                    }} else {{
+                        this.log('Ignored an unknown binary frame');
+                    }}
+                    return;
+                }}
                 try {{
                     console.log('WebSocket message received:', event.data);
                     const message = JSON.parse(event.data);
@@ -976,11 +1990,45 @@ class OutputMonitor {{
                 break;
             case 'OutputData':
                 console.log('Processing OutputData:', message.network_id, message.outputs, message.timestamp);
-                this.updateOutputData(message.network_id, message.outputs, message.timestamp);
+                this.updateOutputData(message.network_id, message.outputs, message.timestamp, message.min, message.max, message.derived);
+                break;
+            case 'DecimationChanged':
+                this.updateDecimation(message.network_id, message.decimation);
+                break;
+            case 'StreamFormatChanged':
+                this.log(`Receiving output data as ${{message.format === 'Binary' ? 'binary frames' : 'JSON'}}`);
+                break;
+            case 'DashboardList':
+                this.updateDashboardList(message.names);
+                break;
+            case 'DashboardLoaded':
+                this.applyDashboard(message.dashboard);
+                break;
+            case 'ThresholdsChanged':
+                this.updateThresholds(message.network_id, message.thresholds);
+                break;
+            case 'Alert':
+                this.showAlert(message.alert);
                 break;
             case 'StatusUpdate':
                 this.updateNetworkStatus(message.network_id, message.status);
                 break;
+            case 'NetworkDetails':
+                this.showNetworkDetails(message.network_id, message.info);
+                break;
+            case 'SessionStarted':
+                this.issuedToken = message.token;
+                if (!this.sessionToken) this.setSessionToken(message.token);
+                break;
+            case 'SessionResumed':
+                this.restoreSession(message.session, message.replayed);
+                break;
+            case 'SessionExpired':
+                this.log('Previous session expired; starting a new one');
+                this.setSessionToken(this.issuedToken);
+                this.requestStreamFormat();
+                this.reportView();
+                break;
             case 'Error':
                 this.log(`Error: ${{message.message}}`);
                 break;
@@ -989,25 +2037,115 @@ class OutputMonitor {{
         }}
     }}
 
+    setSessionToken(token) {{
+        this.sessionToken = token;
+        sessionStorage.setItem('bennySession', token);
+    }}
+
+    // Bring back the dashboard, details and stream format of a resumed session; missed outputs arrive next
+    restoreSession(session, replayed) {{
+        const format = document.getElementById('stream-format');
+        if (format) format.value = session.stream_format;
+        if (session.dashboard && session.dashboard !== this.dashboardName) {{
+            this.sendMessage({{ type: 'LoadDashboard', name: session.dashboard }}, 'load dashboard');
+        }} else if (!session.dashboard && this.dashboardName !== null) {{
+            this.showAllNetworks();
+        }}
+        if (session.selected_network) this.requestNetworkDetails(session.selected_network);
+        this.log(`Resumed session, replaying ${{replayed}} outputs`);
+    }}
+
+    // Tell the server what is shown, so it can be restored after a reload
+    reportView() {{
+        this.sendMessage({{ type: 'SetView', selected_network: this.selectedNetwork, dashboard: this.dashboardName }}, 'save view');
+    }}
+
     updateNetworkList(networks) {{
         this.networks.clear();
         networks.forEach(network => {{
             this.networks.set(network.id, network);
+            if (this.dashboardName === null && !this.panels.has(network.id)) {{
+                this.panels.set(network.id, this.defaultPanel(network.id));
+            }}
+            this.seedHistory(network.id);
         }});
         this.renderNetworkList();
         this.renderOutputVisualizations();
     }}
 
-    updateOutputData(networkId, outputs, timestamp) {{
+    // Start the line chart from the latest recorded samples, so a restarted monitor picks up where it left off
+    seedHistory(networkId) {{
+        if (this.history.has(networkId)) return;
+        this.history.set(networkId, []);
+        fetch(`/api/history?network_id=${{encodeURIComponent(networkId)}}&limit=${{this.historyLength}}`)
+            .then(response => response.ok ? response.json() : [])
+            .then(samples => {{
+                if (samples.length === 0 || this.outputData.has(networkId)) return;
+                const last = samples[samples.length - 1];
+                this.history.set(networkId, samples.map(sample => sample.outputs));
+                this.outputData.set(networkId, {{ outputs: last.outputs, timestamp: last.timestamp, lastUpdate: new Date(last.timestamp) }});
+                this.updateOutputVisualization(networkId);
+            }})
+            .catch(() => {{}});
+    }}
+
+    loadHistoryWindow(networkId, vizDiv) {{
+        const fromInput = vizDiv.querySelector('.history-from').value;
+        const toInput = vizDiv.querySelector('.history-to').value;
+        const params = new URLSearchParams({{ network_id: networkId, limit: '5000' }});
+        if (fromInput) params.set('from', new Date(fromInput).getTime());
+        if (toInput) params.set('to', new Date(toInput).getTime());
+        fetch(`/api/history?${{params}}`)
+            .then(async response => {{
+                if (!response.ok) throw new Error(await response.text());
+                return response.json();
+            }})
+            .then(samples => {{
+                if (samples.length === 0) {{
+                    this.log(`No recorded outputs of ${{networkId}} in that window`);
+                    return;
+                }}
+                const first = samples[0];
+                const last = samples[samples.length - 1];
+                this.replay.set(networkId, {{ from: first.timestamp, to: last.timestamp, count: samples.length, fromInput, toInput }});
+                this.history.set(networkId, samples.map(sample => sample.outputs));
+                this.outputData.set(networkId, {{ outputs: last.outputs, timestamp: last.timestamp, lastUpdate: new Date(last.timestamp) }});
+                this.updateOutputVisualization(networkId);
+                this.log(`Showing ${{samples.length}} recorded outputs of ${{networkId}}; live data is paused`);
+            }})
+            .catch(e => this.log(`Cannot load history of ${{networkId}}: ${{e.message}}`));
+    }}
+
+    resumeLive(networkId) {{
+        if (!this.replay.delete(networkId)) return;
+        this.history.set(networkId, []);
+        this.outputData.delete(networkId);
+        this.renderOutputVisualizations();
+        this.panels.forEach((_, id) => this.updateOutputVisualization(id));
+        this.log(`Showing live outputs of ${{networkId}} again`);
+    }}
+
+    // Derived channel values are kept after the outputs, so they are drawn as channels `output_count` and up
+    updateOutputData(networkId, outputs, timestamp, min, max, derived) {{
+        // A loaded history window stays on screen until Live is pressed
+        if (this.replay.has(networkId)) return;
         console.log('updateOutputData called with:', networkId, outputs, timestamp);
         console.log('Current networks:', this.networks);
         console.log('Current outputData before update:', this.outputData);
         
+        this.lastTimestamp = Math.max(this.lastTimestamp ?? 0, timestamp);
+        if (derived?.length) outputs = outputs.concat(derived);
         this.outputData.set(networkId, {{
             outputs: outputs,
+            min: min,
+            max: max,
             timestamp: timestamp,
             lastUpdate: new Date()
         }});
+        const history = this.history.get(networkId) || [];
+        history.push(outputs);
+        if (history.length > this.historyLength) history.shift();
+        this.history.set(networkId, history);
         
         console.log('Current outputData after update:', this.outputData);
         
@@ -1015,6 +2153,255 @@ class OutputMonitor {{
         this.log(`Received output data from ${{this.networks.get(networkId)?.name || networkId}}: [${{outputs.map(v => v.toFixed(3)).join(', ')}}]`);
     }}
 
+    requestStreamFormat() {{
+        const format = document.getElementById('stream-format')?.value || 'Json';
+        this.sendMessage({{ type: 'SetStreamFormat', format: format }}, 'change stream format');
+    }}
+
+    // Layout: kind u8, flags u8, count u16, timestamp u64, id length u8, id, then f32 arrays (little-endian)
+    decodeOutputFrame(buffer) {{
+        const view = new DataView(buffer);
+        if (buffer.byteLength < 13 || view.getUint8(0) !== 1) return null;
+        const envelope = (view.getUint8(1) & 1) !== 0;
+        const hasDerived = (view.getUint8(1) & 2) !== 0;
+        const count = view.getUint16(2, true);
+        const timestamp = Number(view.getBigUint64(4, true));
+        const idLength = view.getUint8(12);
+        const networkId = new TextDecoder().decode(new Uint8Array(buffer, 13, idLength));
+        let offset = 13 + idLength;
+        const arraysEnd = offset + 4 * count * (envelope ? 3 : 1);
+        if (hasDerived && buffer.byteLength < arraysEnd + 2) return null;
+        const derivedCount = hasDerived ? view.getUint16(arraysEnd, true) : 0;
+        if (buffer.byteLength !== arraysEnd + (hasDerived ? 2 + 4 * derivedCount : 0)) return null;
+        const array = (length = count) => Array.from({{length}}, () => {{
+            const value = view.getFloat32(offset, true);
+            offset += 4;
+            return value;
+        }});
+        const outputs = array();
+        const min = envelope ? array() : undefined;
+        const max = envelope ? array() : undefined;
+        offset += hasDerived ? 2 : 0;
+        const derived = array(derivedCount);
+        return {{ networkId, timestamp, outputs, min, max, derived }};
+    }}
+
+    defaultPanel(networkId) {{
+        return {{ network_id: networkId, outputs: [], chart: 'Bars', thresholds: [] }};
+    }}
+
+    updateDashboardList(names) {{
+        const select = document.getElementById('dashboard-select');
+        if (!select) return;
+        const selected = select.value;
+        select.innerHTML = '<option value="">All networks</option>';
+        names.forEach(name => {{
+            const option = document.createElement('option');
+            option.value = name;
+            option.textContent = name;
+            select.appendChild(option);
+        }});
+        select.value = names.includes(selected) ? selected : '';
+    }}
+
+    applyDashboard(dashboard) {{
+        this.dashboardName = dashboard.name;
+        this.panels = new Map(dashboard.panels.map(panel => [panel.network_id, panel]));
+        document.getElementById('dashboard-select').value = dashboard.name;
+        document.getElementById('dashboard-name').value = dashboard.name;
+        this.renderOutputVisualizations();
+        this.panels.forEach((_, networkId) => this.updateOutputVisualization(networkId));
+        this.reportView();
+        this.log(`Loaded dashboard ${{dashboard.name}}`);
+    }}
+
+    showAllNetworks() {{
+        this.dashboardName = null;
+        this.panels = new Map(Array.from(this.networks.keys()).map(id => [id, this.defaultPanel(id)]));
+        this.renderOutputVisualizations();
+        this.panels.forEach((_, networkId) => this.updateOutputVisualization(networkId));
+        this.reportView();
+    }}
+
+    sendMessage(message, action) {{
+        if (this.ws && this.ws.readyState === WebSocket.OPEN) {{
+            this.ws.send(JSON.stringify(message));
+        }} else {{
+            this.log(`Cannot ${{action}}: WebSocket not connected`);
+        }}
+    }}
+
+    loadDashboard() {{
+        const name = document.getElementById('dashboard-select').value;
+        if (name === '') {{
+            this.showAllNetworks();
+        }} else {{
+            this.sendMessage({{ type: 'LoadDashboard', name: name }}, 'load dashboard');
+        }}
+    }}
+
+    saveDashboard() {{
+        const name = document.getElementById('dashboard-name').value.trim();
+        if (name === '') {{
+            this.log('Enter a name to save the dashboard under');
+            return;
+        }}
+        this.dashboardName = name;
+        const dashboard = {{ name: name, panels: Array.from(this.panels.values()) }};
+        this.sendMessage({{ type: 'SaveDashboard', dashboard: dashboard }}, 'save dashboard');
+    }}
+
+    deleteDashboard() {{
+        const name = document.getElementById('dashboard-select').value;
+        if (name === '') return;
+        this.sendMessage({{ type: 'DeleteDashboard', name: name }}, 'delete dashboard');
+        if (this.dashboardName === name) this.showAllNetworks();
+    }}
+
+    parseList(text, parse) {{
+        return text.split(',').map(part => parse(part.trim())).filter(value => !Number.isNaN(value));
+    }}
+
+    // Outputs then derived channels
+    channelCount(network) {{
+        return network.output_count + (network.derived?.length || 0);
+    }}
+
+    panelOutputs(panel, network) {{
+        const all = Array.from({{length: this.channelCount(network)}}, (_, i) => i);
+        return panel.outputs.length ? panel.outputs.filter(i => i < this.channelCount(network)) : all;
+    }}
+
+    // Derived channel shown as channel `i`, if it is one
+    derivedChannel(network, i) {{
+        return i >= network.output_count ? network.derived?.[i - network.output_count] : undefined;
+    }}
+
+    // Label and expected range of channel `i`, from the network's display metadata or derived channels
+    channelLabel(network, i) {{
+        return this.derivedChannel(network, i)?.name || network.display?.labels?.[i] || `Output ${{i}}`;
+    }}
+
+    channelRange(network, i) {{
+        const derived = this.derivedChannel(network, i);
+        if (derived) {{
+            if (derived.range) return derived.range;
+            // Moving averages stay within their output's range; differences and rates are centered on 0
+            return derived.type === 'MovingAverage' ? this.channelRange(network, derived.output) : {{ min: -1, max: 1 }};
+        }}
+        return network.display?.ranges?.[i] || {{ min: 0, max: 1 }};
+    }}
+
+    // Position of `value` within the expected range of output `i`, clamped to [0, 1]
+    normalize(network, i, value) {{
+        const range = this.channelRange(network, i);
+        return Math.min(1, Math.max(0, (value - range.min) / (range.max - range.min)));
+    }}
+
+    formatValue(network, i, value) {{
+        const unit = network.display?.units?.[i];
+        return unit ? `${{value.toFixed(3)}} ${{unit}}` : value.toFixed(3);
+    }}
+
+    updateThresholds(networkId, thresholds) {{
+        this.thresholds.set(networkId, thresholds);
+        // Outputs without thresholds no longer alert
+        this.alertLevels.forEach((_, key) => {{
+            const [id, output] = key.split(':');
+            if (id === networkId && !thresholds.some(t => t.output === Number(output))) {{
+                this.alertLevels.delete(key);
+            }}
+        }});
+        this.renderOutputVisualizations();
+        this.panels.forEach((_, id) => this.updateOutputVisualization(id));
+    }}
+
+    showAlert(alert) {{
+        this.alertLevels.set(`${{alert.network_id}}:${{alert.output}}`, alert.level);
+        this.applyAlertLevels(alert.network_id);
+        const network = this.networks.get(alert.network_id);
+        const label = `${{network ? this.channelLabel(network, alert.output) : `Output ${{alert.output}}`}} of ${{network?.name || alert.network_id}}`;
+        this.log(alert.level === 'Normal'
+            ? `✅ ${{label}} back to normal at ${{alert.value.toFixed(3)}}`
+            : `${{alert.level === 'Critical' ? '🚨' : '⚠️'}} ${{label}} at ${{alert.value.toFixed(3)}} crossed the ${{alert.level.toLowerCase()}} threshold ${{alert.threshold}}`);
+    }}
+
+    applyAlertLevels(networkId) {{
+        const network = this.networks.get(networkId);
+        if (!network) return;
+        let warning = false;
+        let critical = false;
+        for (let i = 0; i < network.output_count; i++) {{
+            const level = this.alertLevels.get(`${{networkId}}:${{i}}`) || 'Normal';
+            const element = document.getElementById(`output-${{networkId}}-${{i}}`);
+            if (element) {{
+                element.classList.toggle('alert-warning', level === 'Warning');
+                element.classList.toggle('alert-critical', level === 'Critical');
+            }}
+            warning = warning || level === 'Warning';
+            critical = critical || level === 'Critical';
+        }}
+        const vizElement = document.getElementById(`viz-${{networkId}}`);
+        if (vizElement) {{
+            vizElement.classList.toggle('alert-critical', critical);
+            vizElement.classList.toggle('alert-warning', warning && !critical);
+        }}
+    }}
+
+    saveThresholds(network, vizDiv) {{
+        const rows = Array.from(vizDiv.querySelectorAll('.alert-row'));
+        const shown = rows.map(row => Number(row.dataset.output));
+        const parse = input => input.value === '' ? null : parseFloat(input.value);
+        // Thresholds of outputs this panel hides are kept
+        const thresholds = (this.thresholds.get(network.id) || []).filter(t => !shown.includes(t.output));
+        rows.forEach(row => {{
+            const warning = parse(row.querySelector('.alert-warning-input'));
+            const critical = parse(row.querySelector('.alert-critical-input'));
+            if (warning === null && critical === null) return;
+            thresholds.push({{
+                output: Number(row.dataset.output),
+                warning,
+                critical,
+                below: row.querySelector('.alert-direction').value === 'below',
+            }});
+        }});
+        if (this.ws && this.ws.readyState === WebSocket.OPEN) {{
+            this.ws.send(JSON.stringify({{ type: 'SetThresholds', network_id: network.id, thresholds }}));
+        }} else {{
+            this.log('Cannot save thresholds: WebSocket not connected');
+        }}
+    }}
+
+    updateDecimation(networkId, decimation) {{
+        const network = this.networks.get(networkId);
+        if (network) {{
+            network.decimation = decimation;
+            this.renderNetworkList();
+        }}
+        this.log(`Decimation of ${{network?.name || networkId}} set to ${{this.describeDecimation(decimation)}}`);
+    }}
+
+    describeDecimation(decimation) {{
+        switch (decimation?.type) {{
+            case 'EveryNth': return `every ${{decimation.n}} samples`;
+            case 'Envelope': return `min/max envelope of ${{decimation.interval}} samples`;
+            case 'Smoothing': return `smoothing ${{decimation.alpha}} every ${{decimation.every}} samples`;
+            default: return 'off';
+        }}
+    }}
+
+    requestDecimation(networkId, type, value) {{
+        const decimation = {{ type: type }};
+        if (type === 'EveryNth') decimation.n = parseInt(value, 10);
+        if (type === 'Envelope') decimation.interval = parseInt(value, 10);
+        if (type === 'Smoothing') decimation.alpha = parseFloat(value);
+        if (this.ws && this.ws.readyState === WebSocket.OPEN) {{
+            this.ws.send(JSON.stringify({{ type: 'SetDecimation', network_id: networkId, decimation: decimation }}));
+        }} else {{
+            this.log('Cannot change decimation: WebSocket not connected');
+        }}
+    }}
+
     updateNetworkStatus(networkId, status) {{
         const network = this.networks.get(networkId);
         if (network) {{
@@ -1049,7 +2436,8 @@ class OutputMonitor {{
             networkDiv.className = `network-item ${{network.connected ? 'connected' : 'disconnected'}}`;
             
             networkDiv.innerHTML = `
-                <div class="network-name">${{network.name}}</div>
+                <div class="network-name" style="color: ${{network.display?.color || 'inherit'}}">${{network.name}}</div>
+                ${{network.display?.description ? `<div class="network-details">${{network.display.description}}</div>` : ''}}
                 <div class="network-details">
                     ${{network.listen_address}}:${{network.listen_port}} | 
                     ${{network.output_count}} outputs | 
@@ -1058,49 +2446,194 @@ class OutputMonitor {{
                 <div class="network-status ${{network.connected ? 'connected' : 'disconnected'}}">
                     ${{network.connected ? 'Connected' : 'Disconnected'}}
                 </div>
+                <button class="btn-secondary details-btn">Details</button>
+                <div class="network-info" id="info-${{network.id}}"></div>
+                <div class="decimation-controls">
+                    <select class="decimation-type">
+                        <option value="Off">No decimation</option>
+                        <option value="EveryNth">Every Nth sample</option>
+                        <option value="Envelope">Min/max envelope</option>
+                        <option value="Smoothing">Exponential smoothing</option>
+                    </select>
+                    <input class="decimation-value" type="number" min="0" step="any">
+                    <button class="btn-secondary decimation-btn">Apply</button>
+                    <div class="network-details">Decimation: ${{this.describeDecimation(network.decimation)}}</div>
+                </div>
             `;
             
+            const decimation = network.decimation || {{ type: 'Off' }};
+            networkDiv.querySelector('.decimation-type').value = decimation.type;
+            networkDiv.querySelector('.decimation-value').value =
+                decimation.n ?? decimation.interval ?? decimation.alpha ?? '';
+            networkDiv.querySelector('.details-btn').addEventListener('click', () => {{
+                this.requestNetworkDetails(network.id);
+            }});
+            networkDiv.querySelector('.decimation-btn').addEventListener('click', () => {{
+                this.requestDecimation(
+                    network.id,
+                    networkDiv.querySelector('.decimation-type').value,
+                    networkDiv.querySelector('.decimation-value').value
+                );
+            }});
             container.appendChild(networkDiv);
         }});
     }}
 
+    requestNetworkDetails(networkId) {{
+        if (this.selectedNetwork !== networkId) {{
+            this.selectedNetwork = networkId;
+            this.reportView();
+        }}
+        if (this.ws && this.ws.readyState === WebSocket.OPEN) {{
+            this.ws.send(JSON.stringify({{ type: 'GetNetworkDetails', network_id: networkId }}));
+        }} else {{
+            this.log('Cannot request details: WebSocket not connected');
+        }}
+    }}
+
+    showNetworkDetails(networkId, info) {{
+        const infoElement = document.getElementById(`info-${{networkId}}`);
+        if (!infoElement) return;
+        
+        infoElement.innerHTML = `
+            ${{info.name}} | ${{info.layers.join(' → ')}} | ${{info.parameters.toLocaleString()}} parameters<br>
+            ${{info.learning_mode}} learning | up ${{info.uptime_secs}}s | ${{info.peers}} peers<br>
+            ${{info.messages_sent}} messages sent, ${{info.messages_received}} received
+            (${{info.bytes_sent}} / ${{info.bytes_received}} bytes)
+        `;
+    }}
+
     renderOutputVisualizations() {{
         const container = document.getElementById('output-visualizations');
         container.innerHTML = '';
         
-        if (this.networks.size === 0) {{
+        const panels = Array.from(this.panels.values()).filter(panel => this.networks.has(panel.network_id));
+        if (panels.length === 0) {{
             container.innerHTML = '<div class="no-data">No neural networks to monitor</div>';
             return;
         }}
         
-        this.networks.forEach(network => {{
+        panels.forEach(panel => {{
+            const network = this.networks.get(panel.network_id);
+            const outputs = this.panelOutputs(panel, network);
+            const color = network.display?.color;
             const vizDiv = document.createElement('div');
             vizDiv.className = 'output-visualization';
             vizDiv.id = `viz-${{network.id}}`;
             
             vizDiv.innerHTML = `
                 <div class="output-header">
-                    <div class="output-title">${{network.name}}</div>
+                    <div class="output-title" style="color: ${{color || 'inherit'}}">${{network.name}}</div>
                     <div class="output-timestamp" id="timestamp-${{network.id}}">No data yet</div>
                 </div>
+                <div class="panel-settings">
+                    <input class="panel-outputs" type="text" placeholder="Outputs (all)" value="${{panel.outputs.join(', ')}}">
+                    <select class="panel-chart">
+                        <option value="Bars">Bars</option>
+                        <option value="Line">Line</option>
+                        <option value="Values">Values</option>
+                    </select>
+                    <input class="panel-thresholds" type="text" placeholder="Thresholds" value="${{panel.thresholds.join(', ')}}">
+                    <button class="btn-secondary panel-apply">Apply</button>
+                </div>
+                ${{panel.chart === 'Bars' ? `
                 <div class="output-bars" id="bars-${{network.id}}">
-                    ${{Array.from({{length: network.output_count}}, (_, i) => `
-                        <div class="output-bar" id="bar-${{network.id}}-${{i}}" style="height: 3px;">
-                            <div class="output-bar-label">O${{i}}</div>
+                    ${{outputs.map(i => `
+                        <div class="output-bar${{i >= network.output_count ? ' derived' : ''}}" id="bar-${{network.id}}-${{i}}" style="height: 3px;${{color ? ` background: ${{color}};` : ''}}">
+                            <div class="output-bar-label">${{this.derivedChannel(network, i)?.name || network.display?.labels?.[i] || `O${{i}}`}}</div>
                         </div>
                     `).join('')}}
-                </div>
+                    ${{panel.thresholds.map(t => `
+                        <div class="threshold-line" style="bottom: ${{this.normalize(network, outputs[0] ?? 0, t) * 100}}%;"></div>
+                    `).join('')}}
+                </div>` : ''}}
+                ${{panel.chart === 'Line' ? `
+                <canvas class="output-chart" id="chart-${{network.id}}" width="800" height="120"></canvas>` : ''}}
                 <div class="output-values" id="values-${{network.id}}">
-                    ${{Array.from({{length: network.output_count}}, (_, i) => `
-                        <div class="output-value">
-                            <div class="output-value-label">Output ${{i}}</div>
+                    ${{outputs.map(i => `
+                        <div class="output-value${{i >= network.output_count ? ' derived' : ''}}" id="output-${{network.id}}-${{i}}">
+                            <div class="output-value-label">${{this.channelLabel(network, i)}}</div>
                             <div class="output-value-number" id="value-${{network.id}}-${{i}}">0.000</div>
                         </div>
                     `).join('')}}
                 </div>
+                <details class="alert-settings">
+                    <summary>Alert thresholds</summary>
+                    ${{outputs.filter(i => i < network.output_count).map(i => {{
+                        const threshold = (this.thresholds.get(network.id) || []).find(t => t.output === i);
+                        return `
+                        <div class="alert-row" data-output="${{i}}">
+                            <span>${{this.channelLabel(network, i)}}</span>
+                            <input class="alert-warning-input" type="number" step="any" placeholder="Warning" value="${{threshold?.warning ?? ''}}">
+                            <input class="alert-critical-input" type="number" step="any" placeholder="Critical" value="${{threshold?.critical ?? ''}}">
+                            <select class="alert-direction">
+                                <option value="above" ${{threshold?.below ? '' : 'selected'}}>above</option>
+                                <option value="below" ${{threshold?.below ? 'selected' : ''}}>below</option>
+                            </select>
+                        </div>`;
+                    }}).join('')}}
+                    <button class="btn-secondary alert-save">Save thresholds</button>
+                </details>
+                <details class="history-settings">
+                    <summary>History</summary>
+                    <div class="history-row">
+                        <input class="history-from" type="datetime-local" step="1" title="From" value="${{this.replay.get(network.id)?.fromInput || ''}}">
+                        <input class="history-to" type="datetime-local" step="1" title="To" value="${{this.replay.get(network.id)?.toInput || ''}}">
+                        <button class="btn-secondary history-load">Load</button>
+                        <button class="btn-secondary history-live">Live</button>
+                    </div>
+                </details>
             `;
             
+            vizDiv.querySelector('.panel-chart').value = panel.chart;
+            vizDiv.querySelector('.alert-save').addEventListener('click', () => this.saveThresholds(network, vizDiv));
+            vizDiv.querySelector('.history-load').addEventListener('click', () => this.loadHistoryWindow(network.id, vizDiv));
+            vizDiv.querySelector('.history-live').addEventListener('click', () => this.resumeLive(network.id));
+            vizDiv.querySelector('.panel-apply').addEventListener('click', () => {{
+                panel.outputs = this.parseList(vizDiv.querySelector('.panel-outputs').value, v => parseInt(v, 10));
+                panel.chart = vizDiv.querySelector('.panel-chart').value;
+                panel.thresholds = this.parseList(vizDiv.querySelector('.panel-thresholds').value, parseFloat);
+                this.renderOutputVisualizations();
+                this.panels.forEach((_, networkId) => this.updateOutputVisualization(networkId));
+            }});
             container.appendChild(vizDiv);
+            this.applyAlertLevels(network.id);
+        }});
+    }}
+
+    drawLineChart(networkId, panel, outputs) {{
+        const canvas = document.getElementById(`chart-${{networkId}}`);
+        const history = this.history.get(networkId) || [];
+        const network = this.networks.get(networkId);
+        if (!canvas || !network || history.length === 0) return;
+        
+        const ctx = canvas.getContext('2d');
+        const width = canvas.width;
+        const height = canvas.height;
+        // Each output is drawn against its own expected range; thresholds against the first one's
+        const y = (value, output = outputs[0] ?? 0) => height - this.normalize(network, output, value) * height;
+        const x = index => index * width / Math.max(1, Math.max(this.historyLength, history.length) - 1);
+        ctx.clearRect(0, 0, width, height);
+        
+        ctx.setLineDash([6, 4]);
+        ctx.strokeStyle = '#e91e63';
+        panel.thresholds.forEach(t => {{
+            ctx.beginPath();
+            ctx.moveTo(0, y(t));
+            ctx.lineTo(width, y(t));
+            ctx.stroke();
+        }});
+        
+        ctx.setLineDash([]);
+        ctx.lineWidth = 2;
+        outputs.forEach((output, n) => {{
+            ctx.strokeStyle = `hsl(${{(230 + n * 67) % 360}}, 60%, 50%)`;
+            ctx.beginPath();
+            history.forEach((sample, index) => {{
+                const point = [x(index), y(sample[output] ?? 0, output)];
+                index === 0 ? ctx.moveTo(...point) : ctx.lineTo(...point);
+            }});
+            ctx.stroke();
         }});
     }}
 
@@ -1108,11 +2641,12 @@ class OutputMonitor {{
         console.log('updateOutputVisualization called for:', networkId);
         const data = this.outputData.get(networkId);
         const network = this.networks.get(networkId);
+        const panel = this.panels.get(networkId);
         
         console.log('Data found:', data);
         console.log('Network found:', network);
         
-        if (!data || !network) {{
+        if (!data || !network || !panel) {{
             console.log('Missing data or network, returning early');
             return;
         }}
@@ -1120,26 +2654,42 @@ class OutputMonitor {{
         // Update timestamp
         const timestampElement = document.getElementById(`timestamp-${{networkId}}`);
         if (timestampElement) {{
-            timestampElement.textContent = `Last update: ${{data.lastUpdate.toLocaleTimeString()}}`;
+            const replay = this.replay.get(networkId);
+            timestampElement.textContent = replay
+                ? `History: ${{replay.count}} samples, ${{new Date(replay.from).toLocaleString()}} – ${{new Date(replay.to).toLocaleString()}}`
+                : `Last update: ${{data.lastUpdate.toLocaleTimeString()}}`;
+        }}
+        
+        if (panel.chart === 'Line') {{
+            this.drawLineChart(networkId, panel, this.panelOutputs(panel, network));
         }}
+        const highest = panel.thresholds.length ? Math.max(...panel.thresholds) : null;
         
         // Update bars and values
         data.outputs.forEach((value, index) => {{
+            const outputElement = document.getElementById(`output-${{networkId}}-${{index}}`);
+            if (outputElement) {{
+                outputElement.classList.toggle('above-threshold', highest !== null && value > highest);
+            }}
+
             const barElement = document.getElementById(`bar-${{networkId}}-${{index}}`);
             const valueElement = document.getElementById(`value-${{networkId}}-${{index}}`);
             
+            const position = this.normalize(network, index, value);
             if (barElement) {{
-                const height = Math.max(3, value * 100); // Minimum 3px height
+                const height = Math.max(3, position * 100); // Minimum 3px height
                 barElement.style.height = `${{height}}%`;
             }}
             
             if (valueElement) {{
-                valueElement.textContent = value.toFixed(3);
+                valueElement.textContent = data.min && data.max && index < data.min.length
+                    ? `${{this.formatValue(network, index, value)}} (${{data.min[index].toFixed(2)}}…${{data.max[index].toFixed(2)}})`
+                    : this.formatValue(network, index, value);
                 
-                // Color coding based on value
-                if (value > 0.7) {{
+                // Color coding based on where the value sits in its expected range
+                if (position > 0.7) {{
                     valueElement.style.color = '#4CAF50'; // Green for high values
-                }} else if (value > 0.3) {{
+                }} else if (position > 0.3) {{
                     valueElement.style.color = '#FF9800'; // Orange for medium values
                 }} else {{
                     valueElement.style.color = '#666'; // Gray for low values
@@ -1155,6 +2705,11 @@ class OutputMonitor {{
                 this.clearLog();
             }});
         }}
+        
+        document.getElementById('stream-format')?.addEventListener('change', () => this.requestStreamFormat());
+        document.getElementById('load-dashboard')?.addEventListener('click', () => this.loadDashboard());
+        document.getElementById('save-dashboard')?.addEventListener('click', () => this.saveDashboard());
+        document.getElementById('delete-dashboard')?.addEventListener('click', () => this.deleteDashboard());
     }}
 
     log(message) {{
@@ -1199,3 +2754,203 @@ document.addEventListener('DOMContentLoaded', () => {{
             .unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input_server::{
+        InputServer, InputServerConfig, NeuralNetworkTarget, WebSocketMessage,
+    };
+    use crate::neural_network::NeuralNetwork;
+    use crate::server::{NetworkServer, ServerConfig};
+    use futures_util::SinkExt;
+    use std::time::Duration;
+    use tokio_tungstenite::connect_async;
+
+    fn free_port() -> u16 {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn test_slider_input_reaches_output_dashboard() {
+        let output = OutputServer::new(OutputServerConfig::ephemeral(
+            2,
+            vec![NeuralNetworkSource {
+                id: "main-network".to_string(),
+                name: "Main".to_string(),
+                listen_address: "127.0.0.1".to_string(),
+                listen_port: 0,
+                output_count: 2,
+                use_tls: false,
+                info_endpoint: None,
+                decimation: Decimation::Off,
+                derived: vec![DerivedChannel {
+                    name: "gap".to_string(),
+                    signal: crate::derived_signals::DerivedSignal::Difference { a: 0, b: 1 },
+                    range: None,
+                }],
+                postprocess: None,
+                display: Default::default(),
+            }],
+        ));
+        let node_port = free_port();
+        let input = InputServer::new(InputServerConfig::ephemeral(vec![NeuralNetworkTarget {
+            id: "main-network".to_string(),
+            name: "Main".to_string(),
+            address: "127.0.0.1".to_string(),
+            port: node_port,
+            input_count: 4,
+            use_tls: false,
+            input_adapter: Default::default(),
+            preprocess: None,
+            display: Default::default(),
+            constraints: Vec::new(),
+        }]));
+
+        let scenario = async {
+            let nnp_port = output.bound_ports().await.nnp.unwrap();
+            let node = NetworkServer::new(
+                NeuralNetwork::with_layers(&[4, 3, 2], 0.1),
+                ServerConfig {
+                    name: "node".to_string(),
+                    address: "127.0.0.1".to_string(),
+                    port: node_port,
+                    cert_path: None,
+                    key_path: None,
+                    output_endpoints: vec![format!("127.0.0.1:{}", nnp_port)],
+                    hebbian_learning: false,
+                    daemon_mode: false,
+                    metrics_port: None,
+                    bandwidth_limit: None,
+                    state_file: None,
+                    state_compression: Default::default(),
+                    model_file: None,
+                    identity: Default::default(),
+                    namespace: None,
+                    bridged_namespaces: Vec::new(),
+                    shape_policy: Default::default(),
+                    batch_window: None,
+                    max_batch_size: 1,
+                    protocol_version: None,
+                    input_range: None,
+                    config_file: None,
+                    token_file: None,
+                    live_evaluation: None,
+                    capture: None,
+                    latency_budget: None,
+                },
+            )
+            .unwrap();
+            tokio::spawn(async move {
+                let _ = node.start().await;
+            });
+            while TcpStream::connect(("127.0.0.1", node_port)).await.is_err() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+
+            // The input server connects to the node as it starts
+            let input_run = input.start();
+            let dashboard = async {
+                let ports = output.bound_ports().await;
+                let url = format!("ws://127.0.0.1:{}", ports.websocket.unwrap());
+                let (mut browser, _) = connect_async(url).await.unwrap();
+
+                let ports = input.bound_ports().await;
+                let url = format!("ws://127.0.0.1:{}", ports.websocket.unwrap());
+                let (mut slider, _) = connect_async(url).await.unwrap();
+                let activate = WebSocketMessage::ActivateInput {
+                    network_id: "main-network".to_string(),
+                    inputs: vec![0.1, 0.9, 0.4, 0.6],
+                };
+                slider
+                    .send(Message::Text(serde_json::to_string(&activate).unwrap()))
+                    .await
+                    .unwrap();
+
+                let mut token = None;
+                let mut outputs = None;
+                while let Some(Ok(message)) = browser.next().await {
+                    let Message::Text(text) = message else {
+                        continue;
+                    };
+                    match serde_json::from_str(&text) {
+                        Ok(OutputWebSocketMessage::SessionStarted { token: issued }) => {
+                            token = Some(issued);
+                        }
+                        Ok(OutputWebSocketMessage::OutputData { outputs: data, derived, .. }) => {
+                            // Derived channels are computed on the server and streamed with the outputs
+                            assert_eq!(derived.len(), 1);
+                            assert!((derived[0] - (data[0] - data[1])).abs() < 1e-9);
+                            outputs = Some(data);
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+                let token = token.expect("no session token issued");
+                let outputs = outputs.expect("dashboard connection closed without output data");
+
+                // A reconnecting dashboard gets its view and the outputs it has seen back
+                let view = OutputWebSocketMessage::SetView {
+                    selected_network: Some("main-network".to_string()),
+                    dashboard: None,
+                };
+                // Messages are handled in order, so the view is kept once the list arrives
+                for message in [view, OutputWebSocketMessage::ListDashboards] {
+                    browser
+                        .send(Message::Text(serde_json::to_string(&message).unwrap()))
+                        .await
+                        .unwrap();
+                }
+                while let Some(Ok(message)) = browser.next().await {
+                    if let Message::Text(text) = message {
+                        if text.contains("\"DashboardList\"") {
+                            break;
+                        }
+                    }
+                }
+                browser.close(None).await.unwrap();
+                let ports = output.bound_ports().await;
+                let url = format!("ws://127.0.0.1:{}", ports.websocket.unwrap());
+                let (mut browser, _) = connect_async(url).await.unwrap();
+                let resume = OutputWebSocketMessage::Resume {
+                    token: token.clone(),
+                    since: None,
+                };
+                browser
+                    .send(Message::Text(serde_json::to_string(&resume).unwrap()))
+                    .await
+                    .unwrap();
+                while let Some(Ok(message)) = browser.next().await {
+                    let Message::Text(text) = message else {
+                        continue;
+                    };
+                    if let Ok(OutputWebSocketMessage::SessionResumed {
+                        token: resumed,
+                        session,
+                        replayed,
+                    }) = serde_json::from_str(&text)
+                    {
+                        assert_eq!(resumed, token);
+                        assert_eq!(session.selected_network.as_deref(), Some("main-network"));
+                        assert!(replayed >= 1);
+                        return outputs;
+                    }
+                }
+                panic!("dashboard session was not resumed");
+            };
+            tokio::select! {
+                result = input_run => panic!("input server stopped: {:?}", result.err()),
+                outputs = dashboard => outputs,
+            }
+        };
+
+        let outputs = tokio::select! {
+            result = output.start() => panic!("output server stopped: {:?}", result.err()),
+            outputs = tokio::time::timeout(Duration::from_secs(10), scenario) => outputs.unwrap(),
+        };
+        assert_eq!(outputs.len(), 2);
+        assert!(outputs.iter().all(|value| (0.0..=1.0).contains(value)));
+    }
+}
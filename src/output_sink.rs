@@ -0,0 +1,480 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+/// How long a single HTTP write may take
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Samples queued between the OutputServer and a sink before new ones are dropped
+const CHANNEL_CAPACITY: usize = 10_000;
+
+/// Time-series database the outputs are forwarded to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SinkTarget {
+    /// HTTP endpoint taking InfluxDB line protocol
+    ///
+    /// The full write URL, e.g. `http://influx:8086/write?db=benny` for
+    /// InfluxDB 1.x or `http://influx:8086/api/v2/write?org=o&bucket=b` for 2.x;
+    /// VictoriaMetrics and Telegraf's HTTP listener work the same way.
+    LineProtocolHttp {
+        url: String,
+        /// Sent as `Authorization: Token <token>`
+        #[serde(default)]
+        token: Option<String>,
+    },
+    /// Raw line protocol over TCP, as taken by Telegraf's socket listener or QuestDB
+    LineProtocolTcp { address: String },
+    /// A PostgreSQL or TimescaleDB table with one row per output value
+    ///
+    /// The table `(time TIMESTAMPTZ, network_id TEXT, output INTEGER, value
+    /// DOUBLE PRECISION)` is created if missing; turn it into a hypertable with
+    /// `create_hypertable` to use TimescaleDB.
+    Postgres {
+        /// libpq-style connection string, e.g. `host=db user=benny dbname=metrics`
+        connection: String,
+        #[serde(default = "default_table")]
+        table: String,
+    },
+}
+
+/// One time-series export of an OutputServer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SinkConfig {
+    pub target: SinkTarget,
+    /// Networks forwarded; every network when empty
+    #[serde(default)]
+    pub networks: Vec<String>,
+    /// Line protocol measurement the outputs are written as
+    #[serde(default = "default_measurement")]
+    pub measurement: String,
+    /// Samples per write
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Longest a sample waits for its batch to fill, in milliseconds
+    #[serde(default = "default_flush_interval_ms")]
+    pub flush_interval_ms: u64,
+    /// Samples kept for retrying while the database is unreachable; older ones are dropped
+    #[serde(default = "default_max_buffered")]
+    pub max_buffered: usize,
+}
+
+fn default_table() -> String {
+    "network_outputs".to_string()
+}
+
+fn default_measurement() -> String {
+    "network_outputs".to_string()
+}
+
+fn default_batch_size() -> usize {
+    100
+}
+
+fn default_flush_interval_ms() -> u64 {
+    1000
+}
+
+fn default_max_buffered() -> usize {
+    10_000
+}
+
+impl SinkConfig {
+    pub fn new(target: SinkTarget) -> Self {
+        Self {
+            target,
+            networks: Vec::new(),
+            measurement: default_measurement(),
+            batch_size: default_batch_size(),
+            flush_interval_ms: default_flush_interval_ms(),
+            max_buffered: default_max_buffered(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.batch_size == 0 || self.max_buffered < self.batch_size {
+            return Err(format!(
+                "Sink batch size {} must be positive and at most max_buffered {}",
+                self.batch_size, self.max_buffered
+            ));
+        }
+        if self.measurement.is_empty() {
+            return Err("Sink measurement cannot be empty".to_string());
+        }
+        match &self.target {
+            SinkTarget::LineProtocolHttp { url, .. } if !url.starts_with("http") => {
+                Err(format!("Line protocol URL '{}' is not http(s)", url))
+            }
+            SinkTarget::Postgres { table, .. } => {
+                // The table name is spliced into SQL, so only plain identifiers are allowed
+                let valid = table.split('.').all(|part| {
+                    part.chars()
+                        .next()
+                        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                        && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                });
+                if valid {
+                    Ok(())
+                } else {
+                    Err(format!("Invalid table name '{}'", table))
+                }
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the outputs of `network_id` go to this sink
+    pub fn forwards(&self, network_id: &str) -> bool {
+        self.networks.is_empty() || self.networks.iter().any(|id| id == network_id)
+    }
+
+    fn describe(&self) -> String {
+        match &self.target {
+            SinkTarget::LineProtocolHttp { url, .. } => url.clone(),
+            SinkTarget::LineProtocolTcp { address } => format!("tcp://{}", address),
+            SinkTarget::Postgres { table, .. } => format!("postgres table {}", table),
+        }
+    }
+}
+
+/// One output sample on its way to a sink
+#[derive(Debug, Clone, PartialEq)]
+pub struct SinkSample {
+    pub network_id: String,
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub outputs: Vec<f64>,
+}
+
+impl SinkSample {
+    /// The sample as one line of InfluxDB line protocol, with fields `output_0`, `output_1`, …
+    ///
+    /// Values that are not finite cannot be written and are left out; a sample
+    /// with none left has no line.
+    pub fn line_protocol(&self, measurement: &str) -> Option<String> {
+        let mut line = format!(
+            "{},network_id={} ",
+            escape(measurement, &[',', ' ']),
+            escape(&self.network_id, &[',', '=', ' '])
+        );
+        let mut fields = 0;
+        for (i, value) in self.outputs.iter().enumerate() {
+            if value.is_finite() {
+                if fields > 0 {
+                    line.push(',');
+                }
+                // `{:?}` keeps a decimal point, so whole numbers stay floats
+                let _ = write!(line, "output_{}={:?}", i, value);
+                fields += 1;
+            }
+        }
+        if fields == 0 {
+            return None;
+        }
+        let _ = write!(line, " {}", self.timestamp as u128 * 1_000_000);
+        Some(line)
+    }
+}
+
+fn escape(value: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Sending side of a running sink
+///
+/// Samples are queued and written in batches by a background task, so a slow
+/// or unreachable database never holds up the OutputServer. When the queue is
+/// full new samples are dropped.
+#[derive(Clone)]
+pub struct OutputSink {
+    config: SinkConfig,
+    sender: mpsc::Sender<SinkSample>,
+}
+
+impl OutputSink {
+    /// Validate `config` and start its writer task
+    pub fn spawn(config: SinkConfig) -> Result<Self, String> {
+        config.validate()?;
+        let (sender, receiver) = mpsc::channel(CHANNEL_CAPACITY);
+        tokio::spawn(run_sink(config.clone(), receiver));
+        Ok(Self { config, sender })
+    }
+
+    pub fn config(&self) -> &SinkConfig {
+        &self.config
+    }
+
+    /// Queue a sample if this sink forwards its network
+    pub fn send(&self, network_id: &str, timestamp: u64, outputs: &[f64]) {
+        if self.config.forwards(network_id) {
+            let _ = self.sender.try_send(SinkSample {
+                network_id: network_id.to_string(),
+                timestamp,
+                outputs: outputs.to_vec(),
+            });
+        }
+    }
+}
+
+/// Connection to a sink's database, made on the first write and after failures
+enum SinkWriter {
+    Http,
+    Tcp(Option<TcpStream>),
+    Postgres(Option<tokio_postgres::Client>),
+}
+
+impl SinkWriter {
+    fn new(target: &SinkTarget) -> Self {
+        match target {
+            SinkTarget::LineProtocolHttp { .. } => SinkWriter::Http,
+            SinkTarget::LineProtocolTcp { .. } => SinkWriter::Tcp(None),
+            SinkTarget::Postgres { .. } => SinkWriter::Postgres(None),
+        }
+    }
+
+    async fn write(&mut self, config: &SinkConfig, batch: &[SinkSample]) -> Result<(), String> {
+        match (self, &config.target) {
+            (SinkWriter::Http, SinkTarget::LineProtocolHttp { url, token }) => {
+                let body = lines(config, batch);
+                let (url, token) = (url.clone(), token.clone());
+                tokio::task::spawn_blocking(move || {
+                    let mut request = ureq::post(&url).timeout(WRITE_TIMEOUT);
+                    if let Some(token) = token {
+                        request = request.set("Authorization", &format!("Token {}", token));
+                    }
+                    request
+                        .send_string(&body)
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            }
+            (SinkWriter::Tcp(stream), SinkTarget::LineProtocolTcp { address }) => {
+                if stream.is_none() {
+                    *stream = Some(
+                        TcpStream::connect(address)
+                            .await
+                            .map_err(|e| format!("Cannot connect: {}", e))?,
+                    );
+                }
+                let written = match stream.as_mut() {
+                    Some(stream) => stream.write_all(lines(config, batch).as_bytes()).await,
+                    None => Ok(()),
+                };
+                written.map_err(|e| {
+                    *stream = None;
+                    e.to_string()
+                })
+            }
+            (SinkWriter::Postgres(client), SinkTarget::Postgres { connection, table }) => {
+                if client.as_ref().is_none_or(|client| client.is_closed()) {
+                    *client = Some(connect_postgres(connection, table).await?);
+                }
+                let Some(connected) = client.as_ref() else {
+                    return Ok(());
+                };
+                let mut times = Vec::new();
+                let mut networks = Vec::new();
+                let mut outputs = Vec::new();
+                let mut values = Vec::new();
+                for sample in batch {
+                    let time = UNIX_EPOCH + Duration::from_millis(sample.timestamp);
+                    for (i, value) in sample.outputs.iter().enumerate() {
+                        times.push(time);
+                        networks.push(sample.network_id.as_str());
+                        outputs.push(i as i32);
+                        values.push(*value);
+                    }
+                }
+                let insert = format!(
+                    "INSERT INTO {} (time, network_id, output, value) \
+                     SELECT * FROM UNNEST($1::timestamptz[], $2::text[], $3::int[], $4::float8[])",
+                    table
+                );
+                connected
+                    .execute(&insert, &[&times, &networks, &outputs, &values])
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| {
+                        *client = None;
+                        e.to_string()
+                    })
+            }
+            _ => Err("Sink writer does not match its target".to_string()),
+        }
+    }
+}
+
+fn lines(config: &SinkConfig, batch: &[SinkSample]) -> String {
+    let mut body = String::new();
+    for line in batch
+        .iter()
+        .filter_map(|sample| sample.line_protocol(&config.measurement))
+    {
+        body.push_str(&line);
+        body.push('\n');
+    }
+    body
+}
+
+async fn connect_postgres(connection: &str, table: &str) -> Result<tokio_postgres::Client, String> {
+    let (client, driver) = tokio_postgres::connect(connection, tokio_postgres::NoTls)
+        .await
+        .map_err(|e| format!("Cannot connect: {}", e))?;
+    tokio::spawn(async move {
+        if let Err(e) = driver.await {
+            println!("❌ Postgres sink connection closed: {}", e);
+        }
+    });
+    client
+        .batch_execute(&format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+             time TIMESTAMPTZ NOT NULL, network_id TEXT NOT NULL, \
+             output INTEGER NOT NULL, value DOUBLE PRECISION NOT NULL)",
+            table
+        ))
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", table, e))?;
+    Ok(client)
+}
+
+/// Batch queued samples and write them until every `OutputSink` is dropped
+async fn run_sink(config: SinkConfig, mut receiver: mpsc::Receiver<SinkSample>) {
+    let mut writer = SinkWriter::new(&config.target);
+    let mut pending: VecDeque<SinkSample> = VecDeque::new();
+    let mut dropped = 0usize;
+    let mut failing = false;
+    let mut ticker = tokio::time::interval(Duration::from_millis(config.flush_interval_ms.max(1)));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    println!("📤 Forwarding outputs to {}", config.describe());
+
+    loop {
+        let open = tokio::select! {
+            sample = receiver.recv() => match sample {
+                Some(sample) => {
+                    pending.push_back(sample);
+                    if pending.len() > config.max_buffered {
+                        pending.pop_front();
+                        dropped += 1;
+                    }
+                    // Full batches go out at once, unless the database is down
+                    if pending.len() < config.batch_size || failing {
+                        continue;
+                    }
+                    true
+                }
+                None => false,
+            },
+            _ = ticker.tick() => true,
+        };
+
+        while !pending.is_empty() {
+            let count = pending.len().min(config.batch_size);
+            let batch: Vec<SinkSample> = pending.range(..count).cloned().collect();
+            match writer.write(&config, &batch).await {
+                Ok(()) => {
+                    pending.drain(..count);
+                    if failing {
+                        println!("📤 Writing to {} again", config.describe());
+                        failing = false;
+                    }
+                }
+                Err(e) => {
+                    if !failing {
+                        println!("❌ Failed to write to {}: {}", config.describe(), e);
+                        failing = true;
+                    }
+                    break;
+                }
+            }
+        }
+        if dropped > 0 {
+            println!(
+                "⚠️ Dropped {} samples bound for {}",
+                dropped,
+                config.describe()
+            );
+            dropped = 0;
+        }
+        if !open {
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncBufReadExt;
+
+    #[tokio::test]
+    async fn test_outputs_are_forwarded_as_line_protocol() {
+        let sample = SinkSample {
+            network_id: "main network".to_string(),
+            timestamp: 1_700_000_000_123,
+            outputs: vec![0.5, f64::NAN, 1.0],
+        };
+        assert_eq!(
+            sample.line_protocol("nn outputs").unwrap(),
+            r"nn\ outputs,network_id=main\ network output_0=0.5,output_2=1.0 1700000000123000000"
+        );
+        let empty = SinkSample {
+            outputs: vec![f64::INFINITY],
+            ..sample.clone()
+        };
+        assert!(empty.line_protocol("nn").is_none());
+
+        let mut bad_table = SinkConfig::new(SinkTarget::Postgres {
+            connection: "host=localhost".to_string(),
+            table: "outputs; DROP TABLE runs".to_string(),
+        });
+        assert!(bad_table.validate().is_err());
+        bad_table.target = SinkTarget::Postgres {
+            connection: "host=localhost".to_string(),
+            table: "metrics.network_outputs".to_string(),
+        };
+        assert!(bad_table.validate().is_ok());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut config = SinkConfig::new(SinkTarget::LineProtocolTcp {
+            address: listener.local_addr().unwrap().to_string(),
+        });
+        config.networks = vec!["main-network".to_string()];
+        config.batch_size = 2;
+        let sink = OutputSink::spawn(config).unwrap();
+        sink.send("other-network", 1, &[0.0]);
+        sink.send("main-network", 2, &[0.25]);
+        sink.send("main-network", 3, &[0.75]);
+
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut lines = tokio::io::BufReader::new(stream).lines();
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            let line = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+                .await
+                .unwrap()
+                .unwrap()
+                .unwrap();
+            received.push(line);
+        }
+        assert_eq!(
+            received,
+            vec![
+                "network_outputs,network_id=main-network output_0=0.25 2000000",
+                "network_outputs,network_id=main-network output_0=0.75 3000000",
+            ]
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use crate::distributed_network::NetworkId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Steady delivery cadence for a stream of samples
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pacing {
+    /// Target deliveries per second
+    pub rate_hz: f64,
+    /// Samples that may go out back to back after an idle period
+    #[serde(default = "default_max_burst")]
+    pub max_burst: u32,
+}
+
+fn default_max_burst() -> u32 {
+    1
+}
+
+impl Pacing {
+    /// Evenly spaced deliveries at `rate_hz`, without bursts
+    pub fn new(rate_hz: f64) -> Self {
+        Self {
+            rate_hz,
+            max_burst: default_max_burst(),
+        }
+    }
+
+    /// Time between two paced deliveries
+    pub fn interval(&self) -> Duration {
+        if self.rate_hz > 0.0 {
+            Duration::from_secs_f64(1.0 / self.rate_hz)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Token bucket that spaces out deliveries of one stream
+///
+/// Samples arriving faster than the target rate are held back until their
+/// slot, so a burst from the network leaves at the configured cadence. Slots
+/// are reserved on arrival: concurrent callers queue up one interval apart.
+#[derive(Debug, Clone)]
+pub struct Pacer {
+    pacing: Pacing,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Pacer {
+    pub fn new(pacing: Pacing) -> Self {
+        Self {
+            pacing,
+            tokens: pacing.max_burst.max(1) as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        self.pacing
+    }
+
+    /// Reserve the next delivery slot, returning how long the caller must wait for it
+    pub fn reserve(&mut self) -> Duration {
+        if self.pacing.rate_hz <= 0.0 {
+            return Duration::ZERO;
+        }
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens =
+            (self.tokens + elapsed * self.pacing.rate_hz).min(self.pacing.max_burst.max(1) as f64);
+        self.last_refill = now;
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.pacing.rate_hz)
+        }
+    }
+
+    /// Wait for the next delivery slot
+    pub async fn wait(&mut self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Per-peer pacers shared by all clones of a node
+#[derive(Debug, Clone, Default)]
+pub struct PeerPacing {
+    default_pacing: Arc<Mutex<Option<Pacing>>>,
+    peer_pacing: Arc<Mutex<HashMap<NetworkId, Pacing>>>,
+    pacers: Arc<Mutex<HashMap<NetworkId, Pacer>>>,
+}
+
+impl PeerPacing {
+    /// Set the cadence applied to peers without an explicit one
+    pub fn set_default(&self, pacing: Option<Pacing>) {
+        *self.default_pacing.lock().unwrap() = pacing;
+    }
+
+    /// Set or clear the cadence for a single peer
+    pub fn set_peer(&self, peer: NetworkId, pacing: Option<Pacing>) {
+        let mut peers = self.peer_pacing.lock().unwrap();
+        match pacing {
+            Some(pacing) => peers.insert(peer, pacing),
+            None => peers.remove(&peer),
+        };
+    }
+
+    pub fn pacing_for(&self, peer: NetworkId) -> Option<Pacing> {
+        self.peer_pacing
+            .lock()
+            .unwrap()
+            .get(&peer)
+            .copied()
+            .or(*self.default_pacing.lock().unwrap())
+    }
+
+    /// Reserve the next slot for `peer`, returning how long the caller must wait
+    pub fn reserve(&self, peer: NetworkId) -> Duration {
+        let Some(pacing) = self.pacing_for(peer) else {
+            return Duration::ZERO;
+        };
+        let mut pacers = self.pacers.lock().unwrap();
+        let pacer = pacers.entry(peer).or_insert_with(|| Pacer::new(pacing));
+        // A changed cadence starts a fresh schedule
+        if pacer.pacing() != pacing {
+            *pacer = Pacer::new(pacing);
+        }
+        pacer.reserve()
+    }
+
+    /// Wait until the next paced delivery to `peer` is due
+    pub async fn wait(&self, peer: NetworkId) {
+        let wait = self.reserve(peer);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_burst_then_steady_cadence() {
+        let mut pacer = Pacer::new(Pacing {
+            rate_hz: 100.0,
+            max_burst: 2,
+        });
+        assert_eq!(pacer.reserve(), Duration::ZERO);
+        assert_eq!(pacer.reserve(), Duration::ZERO);
+
+        // Further samples queue up one 10 ms interval apart
+        let third = pacer.reserve();
+        let fourth = pacer.reserve();
+        assert!(third > Duration::from_millis(8) && third <= Duration::from_millis(10));
+        assert!(fourth > Duration::from_millis(18) && fourth <= Duration::from_millis(20));
+        assert_eq!(Pacing::new(50.0).interval(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_peer_overrides_default() {
+        let pacing = PeerPacing::default();
+        let (fast, slow, free) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        pacing.set_default(Some(Pacing::new(1000.0)));
+        pacing.set_peer(slow, Some(Pacing::new(10.0)));
+
+        assert_eq!(pacing.reserve(slow), Duration::ZERO);
+        assert!(pacing.reserve(slow) > Duration::from_millis(90));
+        assert_eq!(pacing.reserve(fast), Duration::ZERO);
+        assert!(pacing.reserve(fast) <= Duration::from_millis(1));
+
+        pacing.set_default(None);
+        assert_eq!(pacing.pacing_for(free), None);
+        assert_eq!(pacing.reserve(free), Duration::ZERO);
+    }
+}
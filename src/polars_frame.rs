@@ -0,0 +1,132 @@
+use crate::neural_network::NeuralNetwork;
+use polars::prelude::*;
+
+/// Rows of `columns` of `df`, as samples of `expected` values
+fn samples(df: &DataFrame, columns: &[&str], expected: usize) -> PolarsResult<Vec<Vec<f64>>> {
+    if columns.len() != expected {
+        return Err(PolarsError::ShapeMismatch(
+            format!(
+                "{} columns given for a layer of {} neurons",
+                columns.len(),
+                expected
+            )
+            .into(),
+        ));
+    }
+    let mut rows = vec![Vec::with_capacity(expected); df.height()];
+    for name in columns {
+        let column = df.column(name)?.cast(&DataType::Float64)?;
+        let values = column.as_materialized_series().f64()?;
+        if values.null_count() > 0 {
+            return Err(PolarsError::ComputeError(
+                format!("Column '{}' has {} nulls", name, values.null_count()).into(),
+            ));
+        }
+        for (row, value) in rows.iter_mut().zip(values.into_no_null_iter()) {
+            row.push(value);
+        }
+    }
+    Ok(rows)
+}
+
+impl NeuralNetwork {
+    /// Predict every row of `df` from `input_cols`, given in input order
+    ///
+    /// Numeric columns of any type are accepted; nulls are an error. A network
+    /// with one output returns a `Float64` series named `prediction`, one with
+    /// several a series of lists holding each row's outputs.
+    ///
+    /// # Example
+    /// ```
+    /// use neural_network::NeuralNetwork;
+    /// use polars::prelude::*;
+    ///
+    /// let df = df!("x" => [0.0, 1.0], "y" => [1.0, 0.0]).unwrap();
+    /// let nn = NeuralNetwork::new(2, 3, 1, 0.1);
+    /// let predictions = nn.predict_df(&df, &["x", "y"]).unwrap();
+    /// assert_eq!(predictions.len(), 2);
+    /// ```
+    pub fn predict_df(&self, df: &DataFrame, input_cols: &[&str]) -> PolarsResult<Series> {
+        let inputs = samples(df, input_cols, self.input_size())?;
+        let outputs = self.forward_batch(&inputs);
+        if self.get_layers().last() == Some(&1) {
+            let values: Vec<f64> = outputs.into_iter().map(|output| output[0]).collect();
+            Ok(Series::new("prediction".into(), values))
+        } else {
+            let rows: Vec<Series> = outputs
+                .into_iter()
+                .map(|output| Series::new(PlSmallStr::EMPTY, output))
+                .collect();
+            Ok(Series::new("prediction".into(), rows))
+        }
+    }
+
+    /// Train on every row of `df` for `epochs` epochs, returning the mean error of the last one
+    ///
+    /// Rows are trained in order with `train`, `input_cols` and `target_cols`
+    /// given in input and output order.
+    pub fn train_df(
+        &mut self,
+        df: &DataFrame,
+        input_cols: &[&str],
+        target_cols: &[&str],
+        epochs: usize,
+    ) -> PolarsResult<f64> {
+        let inputs = samples(df, input_cols, self.input_size())?;
+        let output_size = *self.get_layers().last().unwrap_or(&0);
+        let targets = samples(df, target_cols, output_size)?;
+        let mut error = 0.0;
+        for _ in 0..epochs {
+            let total: f64 = inputs
+                .iter()
+                .zip(&targets)
+                .map(|(inputs, targets)| self.train(inputs, targets))
+                .sum();
+            error = total / inputs.len().max(1) as f64;
+        }
+        Ok(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dataframes_train_and_predict() {
+        let df = df!(
+            "a" => [0i32, 0, 1, 1],
+            "b" => [0.0f32, 1.0, 0.0, 1.0],
+            "or" => [0.0, 1.0, 1.0, 1.0],
+            "and" => [0.0, 0.0, 0.0, 1.0],
+        )
+        .unwrap();
+
+        let mut nn = NeuralNetwork::with_layers(&[2, 4, 1], 0.5);
+        let untrained = nn.predict_df(&df, &["a", "b"]).unwrap();
+        let error = nn.train_df(&df, &["a", "b"], &["or"], 5).unwrap();
+        assert!(error.is_finite());
+
+        let predictions = nn.predict_df(&df, &["a", "b"]).unwrap();
+        assert_eq!(predictions.dtype(), &DataType::Float64);
+        assert_ne!(predictions, untrained);
+        let expected = nn.forward_batch(&[
+            vec![0.0, 0.0],
+            vec![0.0, 1.0],
+            vec![1.0, 0.0],
+            vec![1.0, 1.0],
+        ]);
+        let values: Vec<f64> = predictions.f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(values, expected.concat());
+
+        let both = NeuralNetwork::with_layers(&[2, 3, 2], 0.1);
+        let predictions = both.predict_df(&df, &["a", "b"]).unwrap();
+        assert_eq!(predictions.len(), 4);
+        assert!(matches!(predictions.dtype(), DataType::List(_)));
+
+        assert!(nn.train_df(&df, &["a", "b"], &["or", "and"], 1).is_err());
+        assert!(nn.predict_df(&df, &["a", "missing"]).is_err());
+        let with_null = df!("a" => [Some(1.0), None], "b" => [0.0, 1.0]).unwrap();
+        assert!(nn.predict_df(&with_null, &["a", "b"]).is_err());
+    }
+}
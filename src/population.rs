@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+/// Smallest activity used when fitting tuning curves, so silent neurons stay finite in log space
+const MIN_ACTIVITY: f64 = 1e-12;
+
+/// Gaussian tuning curves spreading one scalar over `neurons` neurons
+///
+/// Neuron `k` prefers the value `min + k * (max - min) / (neurons - 1)` and
+/// responds with `exp(-(x - preferred)² / (2 width²))`. Values outside
+/// `[min, max]` are clamped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PopulationCode {
+    pub neurons: usize,
+    pub min: f64,
+    pub max: f64,
+    /// Tuning curve standard deviation (the spacing of preferred values when unset)
+    #[serde(default)]
+    pub width: Option<f64>,
+}
+
+impl PopulationCode {
+    pub fn new(neurons: usize, min: f64, max: f64) -> Result<Self, String> {
+        let code = Self {
+            neurons,
+            min,
+            max,
+            width: None,
+        };
+        code.validate()?;
+        Ok(code)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.neurons < 2 {
+            return Err(format!(
+                "Population codes need at least 2 neurons, got {}",
+                self.neurons
+            ));
+        }
+        if self.min.is_nan() || self.max.is_nan() || self.min >= self.max {
+            return Err(format!(
+                "Population code range [{}, {}] is empty",
+                self.min, self.max
+            ));
+        }
+        if self.width.is_some_and(|width| width.is_nan() || width <= 0.0) {
+            return Err("Population code width must be positive".to_string());
+        }
+        Ok(())
+    }
+
+    fn spacing(&self) -> f64 {
+        (self.max - self.min) / (self.neurons - 1) as f64
+    }
+
+    /// Value neuron `neuron` responds to most
+    pub fn preferred(&self, neuron: usize) -> f64 {
+        self.min + neuron as f64 * self.spacing()
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width.unwrap_or_else(|| self.spacing())
+    }
+
+    pub fn encode(&self, value: f64) -> Vec<f64> {
+        let value = value.clamp(self.min, self.max);
+        let variance = 2.0 * self.width().powi(2);
+        (0..self.neurons)
+            .map(|neuron| (-(value - self.preferred(neuron)).powi(2) / variance).exp())
+            .collect()
+    }
+
+    /// Scalar best explained by the activities of a population
+    ///
+    /// Fits the tuning curves of the most active neuron and its stronger
+    /// neighbour, which recovers encoded values exactly and degrades
+    /// gracefully for noisy network outputs.
+    pub fn decode(&self, activities: &[f64]) -> f64 {
+        assert_eq!(activities.len(), self.neurons, "Population size mismatch");
+        let peak = activities
+            .iter()
+            .enumerate()
+            .fold(0, |best, (neuron, &a)| if a > activities[best] { neuron } else { best });
+        let neighbour = match (peak.checked_sub(1), activities.get(peak + 1)) {
+            (Some(left), Some(&right)) if activities[left] >= right => left,
+            (Some(left), None) => left,
+            _ => peak + 1,
+        };
+        let (low, high) = (peak.min(neighbour), peak.max(neighbour));
+
+        // ln a_low - ln a_high = ((c_low + c_high) / 2 - x) * spacing / width²
+        let log_ratio = activities[low].max(MIN_ACTIVITY).ln()
+            - activities[high].max(MIN_ACTIVITY).ln();
+        let midpoint = (self.preferred(low) + self.preferred(high)) / 2.0;
+        let value = midpoint - log_ratio * self.width().powi(2) / self.spacing();
+        value.clamp(self.min, self.max)
+    }
+}
+
+/// Population code of one raw column (`[[population_inputs]]` / `[[population_outputs]]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PopulationColumn {
+    pub column: usize,
+    #[serde(flatten)]
+    pub code: PopulationCode,
+}
+
+/// Replaces selected columns of a vector with their population codes, and back
+///
+/// Used as an input stage (raw inputs to network inputs) or an output stage
+/// (raw targets to network outputs, decoded again after inference). Columns
+/// without a code pass through unchanged.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct PopulationCoder {
+    columns: Vec<PopulationColumn>, // sorted by column
+}
+
+impl PopulationCoder {
+    pub fn new(mut columns: Vec<PopulationColumn>) -> Result<Self, String> {
+        columns.sort_by_key(|c| c.column);
+        for (i, column) in columns.iter().enumerate() {
+            column
+                .code
+                .validate()
+                .map_err(|e| format!("Column {}: {}", column.column, e))?;
+            if i > 0 && columns[i - 1].column == column.column {
+                return Err(format!(
+                    "Column {} has more than one population code",
+                    column.column
+                ));
+            }
+        }
+        Ok(Self { columns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Number of encoded values for `raw` raw values
+    pub fn encoded_size(&self, raw: usize) -> usize {
+        raw + self
+            .columns
+            .iter()
+            .filter(|c| c.column < raw)
+            .map(|c| c.code.neurons - 1)
+            .sum::<usize>()
+    }
+
+    pub fn encode(&self, values: &[f64]) -> Vec<f64> {
+        let mut encoded = Vec::with_capacity(self.encoded_size(values.len()));
+        let mut start = 0;
+        for column in &self.columns {
+            assert!(
+                column.column < values.len(),
+                "Population column {} out of range for {} values",
+                column.column,
+                values.len()
+            );
+            encoded.extend_from_slice(&values[start..column.column]);
+            encoded.extend(column.code.encode(values[column.column]));
+            start = column.column + 1;
+        }
+        encoded.extend_from_slice(&values[start..]);
+        encoded
+    }
+
+    pub fn decode(&self, encoded: &[f64]) -> Vec<f64> {
+        let mut decoded = Vec::new();
+        let mut position = 0;
+        for column in &self.columns {
+            let plain = column.column - decoded.len();
+            let end = position + plain + column.code.neurons;
+            assert!(
+                end <= encoded.len(),
+                "Population column {} out of range for {} encoded values",
+                column.column,
+                encoded.len()
+            );
+            decoded.extend_from_slice(&encoded[position..position + plain]);
+            decoded.push(column.code.decode(&encoded[position + plain..end]));
+            position = end;
+        }
+        decoded.extend_from_slice(&encoded[position..]);
+        decoded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_round_trip() {
+        let code = PopulationCode::new(8, -1.0, 1.0).unwrap();
+        for value in [-1.0, -0.37, 0.0, 0.5, 0.93, 1.0] {
+            let activities = code.encode(value);
+            assert_eq!(activities.len(), 8);
+            assert!((code.decode(&activities) - value).abs() < 1e-9);
+        }
+        assert_eq!(code.decode(&code.encode(5.0)), 1.0);
+
+        // Noisy activities still land near the encoded value
+        let noisy: Vec<f64> = code.encode(0.3).iter().map(|a| a * 0.9 + 0.02).collect();
+        assert!((code.decode(&noisy) - 0.3).abs() < 0.1);
+
+        assert!(PopulationCode::new(1, 0.0, 1.0).is_err());
+        assert!(PopulationCode::new(4, 1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_coder_replaces_columns() {
+        let column = |column, neurons| PopulationColumn {
+            column,
+            code: PopulationCode::new(neurons, 0.0, 1.0).unwrap(),
+        };
+        let coder = PopulationCoder::new(vec![column(2, 3), column(0, 4)]).unwrap();
+        assert_eq!(coder.encoded_size(3), 8);
+
+        let encoded = coder.encode(&[0.25, 7.0, 1.0]);
+        assert_eq!(encoded.len(), 8);
+        assert_eq!(encoded[4], 7.0);
+        let decoded = coder.decode(&encoded);
+        assert!((decoded[0] - 0.25).abs() < 1e-9);
+        assert_eq!(decoded[1], 7.0);
+        assert!((decoded[2] - 1.0).abs() < 1e-9);
+
+        assert!(PopulationCoder::new(vec![column(1, 3), column(1, 5)]).is_err());
+    }
+}
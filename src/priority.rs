@@ -0,0 +1,231 @@
+use crate::distributed_network::{MessageType, NetworkId};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+
+/// Deliveries to one peer that may be in flight at the same time
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 4;
+
+/// Lane a message waits in before it may be sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Connection upkeep: handshakes, acks, heartbeats, introspection, health probes, configuration, disconnects and errors
+    Control,
+    /// Activations, learning signals and weights
+    Data,
+}
+
+impl Priority {
+    pub fn of(msg_type: MessageType) -> Self {
+        match msg_type {
+            MessageType::Handshake
+            | MessageType::HandshakeAck
+            | MessageType::Ack
+            | MessageType::Heartbeat
+            | MessageType::Disconnect
+            | MessageType::Info
+            | MessageType::InfoResponse
+            | MessageType::ConfigUpdate
+            | MessageType::ModelPushAck
+            | MessageType::Ping
+            | MessageType::Pong
+            | MessageType::Error => Priority::Control,
+            MessageType::ForwardData
+            | MessageType::BackwardData
+            | MessageType::HebbianData
+            | MessageType::WeightSync
+            | MessageType::SpikeEvents
+            | MessageType::ModelPush => Priority::Data,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct PeerLanes {
+    in_flight: usize,
+    control: VecDeque<oneshot::Sender<()>>,
+    data: VecDeque<oneshot::Sender<()>>,
+}
+
+impl PeerLanes {
+    fn lane(&mut self, priority: Priority) -> &mut VecDeque<oneshot::Sender<()>> {
+        match priority {
+            Priority::Control => &mut self.control,
+            Priority::Data => &mut self.data,
+        }
+    }
+}
+
+/// Per-peer send queues in which control messages overtake queued data
+///
+/// At most `max_in_flight` deliveries to a peer run at once; the rest wait
+/// for a free slot, and a freed slot goes to the oldest waiting control
+/// message before any data message. A heartbeat therefore waits for at most
+/// one delivery in progress, never for a backlog of bulk traffic.
+#[derive(Debug, Clone)]
+pub struct PriorityLanes {
+    peers: Arc<Mutex<HashMap<NetworkId, PeerLanes>>>,
+    max_in_flight: usize,
+}
+
+impl Default for PriorityLanes {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IN_FLIGHT)
+    }
+}
+
+impl PriorityLanes {
+    pub fn new(max_in_flight: usize) -> Self {
+        Self {
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            max_in_flight: max_in_flight.max(1),
+        }
+    }
+
+    /// Wait for a send slot to `peer`, held until the returned permit is dropped
+    pub async fn acquire(&self, peer: NetworkId, priority: Priority) -> LanePermit {
+        let receiver = {
+            let mut peers = self.peers.lock().unwrap();
+            let lanes = peers.entry(peer).or_default();
+            if lanes.in_flight < self.max_in_flight {
+                lanes.in_flight += 1;
+                return self.permit(peer);
+            }
+            let (sender, receiver) = oneshot::channel();
+            lanes.lane(priority).push_back(sender);
+            receiver
+        };
+
+        let mut waiting = Waiting {
+            lanes: self.clone(),
+            peer,
+            receiver,
+            granted: false,
+        };
+        // Senders are only dropped when their slot is handed over
+        let _ = (&mut waiting.receiver).await;
+        waiting.granted = true;
+        self.permit(peer)
+    }
+
+    fn permit(&self, peer: NetworkId) -> LanePermit {
+        LanePermit {
+            lanes: self.clone(),
+            peer,
+        }
+    }
+
+    /// Messages waiting for a slot to `peer`, as (control, data)
+    pub fn queued(&self, peer: NetworkId) -> (usize, usize) {
+        self.peers
+            .lock()
+            .unwrap()
+            .get(&peer)
+            .map_or((0, 0), |lanes| (lanes.control.len(), lanes.data.len()))
+    }
+
+    /// Pass a freed slot to the next waiter, control first
+    fn release(&self, peer: NetworkId) {
+        let mut peers = self.peers.lock().unwrap();
+        let Some(lanes) = peers.get_mut(&peer) else {
+            return;
+        };
+        while let Some(next) = lanes.control.pop_front().or_else(|| lanes.data.pop_front()) {
+            // A waiter that gave up has dropped its receiver; try the next one
+            if next.send(()).is_ok() {
+                return;
+            }
+        }
+        lanes.in_flight -= 1;
+        if lanes.in_flight == 0 {
+            peers.remove(&peer);
+        }
+    }
+}
+
+/// A send slot to one peer, released on drop
+#[derive(Debug)]
+pub struct LanePermit {
+    lanes: PriorityLanes,
+    peer: NetworkId,
+}
+
+impl Drop for LanePermit {
+    fn drop(&mut self) {
+        self.lanes.release(self.peer);
+    }
+}
+
+/// A queued `acquire`; if it is cancelled just after being handed a slot, the slot is passed on
+struct Waiting {
+    lanes: PriorityLanes,
+    peer: NetworkId,
+    receiver: oneshot::Receiver<()>,
+    granted: bool,
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        if self.granted {
+            return;
+        }
+        self.receiver.close();
+        if self.receiver.try_recv().is_ok() {
+            self.lanes.release(self.peer);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_control_overtakes_queued_data() {
+        let lanes = PriorityLanes::new(1);
+        let peer = NetworkId::new_v4();
+        let busy = lanes.acquire(peer, Priority::Data).await;
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        let mut waiters = Vec::new();
+        for (name, priority) in [
+            ("data 1", Priority::Data),
+            ("data 2", Priority::Data),
+            ("heartbeat", Priority::Control),
+        ] {
+            let (lanes, order) = (lanes.clone(), order.clone());
+            waiters.push(tokio::spawn(async move {
+                let _permit = lanes.acquire(peer, priority).await;
+                order.lock().unwrap().push(name);
+            }));
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(lanes.queued(peer), (1, 2));
+
+        // A cancelled waiter gives its place up
+        let cancelled = tokio::spawn({
+            let lanes = lanes.clone();
+            async move {
+                let _permit = lanes.acquire(peer, Priority::Control).await;
+                unreachable!("cancelled before its turn");
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        cancelled.abort();
+        let _ = cancelled.await;
+
+        drop(busy);
+        for waiter in waiters {
+            waiter.await.unwrap();
+        }
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["heartbeat", "data 1", "data 2"]
+        );
+        assert_eq!(lanes.queued(peer), (0, 0));
+        assert!(lanes.peers.lock().unwrap().is_empty());
+        assert_eq!(Priority::of(MessageType::Ack), Priority::Control);
+        assert_eq!(Priority::of(MessageType::WeightSync), Priority::Data);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Stage of a forward or training pass that time is attributed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Weighted sums and activations of every layer
+    Forward,
+    /// Hebbian weight updates, including intrinsic plasticity
+    Hebbian,
+    /// Homeostatic regulation
+    Homeostasis,
+    /// Backpropagation and embedding updates
+    Backprop,
+}
+
+impl Phase {
+    pub const ALL: [Phase; 4] = [
+        Phase::Forward,
+        Phase::Hebbian,
+        Phase::Homeostasis,
+        Phase::Backprop,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Forward => "forward",
+            Phase::Hebbian => "hebbian",
+            Phase::Homeostasis => "homeostasis",
+            Phase::Backprop => "backprop",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Wall-clock time spent per layer and per phase since profiling was enabled or reset
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimingReport {
+    /// Forward time of each weight layer (layer `i` feeds layer `i + 1`), in nanoseconds
+    pub layer_ns: Vec<u64>,
+    /// Time of each phase in `Phase::ALL` order, in nanoseconds
+    pub phase_ns: [u64; 4],
+}
+
+impl TimingReport {
+    pub fn phase(&self, phase: Phase) -> Duration {
+        Duration::from_nanos(self.phase_ns[phase.index()])
+    }
+
+    pub fn layer(&self, layer: usize) -> Duration {
+        Duration::from_nanos(self.layer_ns.get(layer).copied().unwrap_or(0))
+    }
+
+    /// Time across all phases
+    pub fn total(&self) -> Duration {
+        Duration::from_nanos(self.phase_ns.iter().sum())
+    }
+
+    /// Prometheus counters of the layer and phase times
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP benny_layer_forward_seconds_total Forward time per weight layer\n");
+        out.push_str("# TYPE benny_layer_forward_seconds_total counter\n");
+        for (layer, &ns) in self.layer_ns.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "benny_layer_forward_seconds_total{{layer=\"{}\"}} {}",
+                layer,
+                ns as f64 / 1e9
+            );
+        }
+        out.push_str("# HELP benny_phase_seconds_total Time per forward/learning phase\n");
+        out.push_str("# TYPE benny_phase_seconds_total counter\n");
+        for phase in Phase::ALL {
+            let _ = writeln!(
+                out,
+                "benny_phase_seconds_total{{phase=\"{}\"}} {}",
+                phase.name(),
+                self.phase(phase).as_secs_f64()
+            );
+        }
+        out
+    }
+}
+
+/// Thread-safe timers behind `TimingReport`, so `&self` inference can be timed
+#[derive(Debug)]
+pub(crate) struct Profiler {
+    layers: Vec<AtomicU64>,
+    phases: [AtomicU64; 4],
+}
+
+impl Clone for Profiler {
+    fn clone(&self) -> Self {
+        let load = |counter: &AtomicU64| AtomicU64::new(counter.load(Ordering::Relaxed));
+        Self {
+            layers: self.layers.iter().map(load).collect(),
+            phases: std::array::from_fn(|i| load(&self.phases[i])),
+        }
+    }
+}
+
+impl Profiler {
+    pub(crate) fn new(weight_layers: usize) -> Self {
+        Self {
+            layers: (0..weight_layers).map(|_| AtomicU64::new(0)).collect(),
+            phases: Default::default(),
+        }
+    }
+
+    /// Forward time of one layer, which also counts towards `Phase::Forward`
+    pub(crate) fn record_layer(&self, layer: usize, elapsed: Duration) {
+        let ns = elapsed.as_nanos() as u64;
+        if let Some(counter) = self.layers.get(layer) {
+            counter.fetch_add(ns, Ordering::Relaxed);
+        }
+        self.phases[Phase::Forward.index()].fetch_add(ns, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_phase(&self, phase: Phase, elapsed: Duration) {
+        self.phases[phase.index()].fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn report(&self) -> TimingReport {
+        TimingReport {
+            layer_ns: self
+                .layers
+                .iter()
+                .map(|counter| counter.load(Ordering::Relaxed))
+                .collect(),
+            phase_ns: std::array::from_fn(|i| self.phases[i].load(Ordering::Relaxed)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiler_attributes_layers_to_forward() {
+        let profiler = Profiler::new(2);
+        profiler.record_layer(0, Duration::from_micros(3));
+        profiler.record_layer(1, Duration::from_micros(5));
+        profiler.record_layer(7, Duration::from_micros(1));
+        profiler.record_phase(Phase::Hebbian, Duration::from_micros(4));
+
+        let report = profiler.clone().report();
+        assert_eq!(report.layer(1), Duration::from_micros(5));
+        assert_eq!(report.layer(7), Duration::ZERO);
+        assert_eq!(report.phase(Phase::Forward), Duration::from_micros(9));
+        assert_eq!(report.total(), Duration::from_micros(13));
+
+        let text = report.to_prometheus();
+        assert!(text.contains("benny_layer_forward_seconds_total{layer=\"0\"} 0.000003"));
+        assert!(text.contains("benny_phase_seconds_total{phase=\"hebbian\"} 0.000004"));
+    }
+}
@@ -0,0 +1,414 @@
+use crate::neural_network::NeuralNetwork;
+use crate::visualize::ImageFormat;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+const POWER_ITERATIONS: usize = 200;
+const PLOT_SIZE: (u32, u32) = (800, 800);
+
+/// How activations are brought down to two dimensions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProjectionMethod {
+    /// Principal component analysis: linear, fast, keeps global structure
+    Pca,
+    /// t-SNE: nonlinear, keeps neighbourhoods, quadratic in the sample count
+    #[cfg(feature = "tsne")]
+    Tsne,
+}
+
+/// Activations of layer `layer` (0 is the input layer) for every input
+pub fn layer_activations(
+    network: &NeuralNetwork,
+    inputs: &[Vec<f64>],
+    layer: usize,
+) -> Result<Vec<Vec<f64>>, String> {
+    let layers = network.get_layers();
+    if layer >= layers.len() {
+        return Err(format!(
+            "Layer {} out of range: the network has layers 0 to {}",
+            layer,
+            layers.len() - 1
+        ));
+    }
+    Ok(inputs
+        .iter()
+        .map(|input| network.forward_all_layers(input).swap_remove(layer))
+        .collect())
+}
+
+/// Class of a target: the index of its largest value, or whether a single value is at least 0.5
+pub fn class_of(target: &[f64]) -> usize {
+    if target.len() == 1 {
+        return (target[0] >= 0.5) as usize;
+    }
+    target
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map_or(0, |(class, _)| class)
+}
+
+/// Principal components of a set of points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pca {
+    pub mean: Vec<f64>,
+    /// Unit directions of largest variance, largest first
+    pub components: Vec<Vec<f64>>,
+    /// Share of the total variance along each component
+    pub explained_variance: Vec<f64>,
+}
+
+impl Pca {
+    /// Find the first `k` components by power iteration on the covariance matrix
+    pub fn fit(points: &[Vec<f64>], k: usize) -> Self {
+        let dims = points.first().map_or(0, Vec::len);
+        let count = points.len().max(1) as f64;
+        let mean: Vec<f64> = (0..dims)
+            .map(|d| points.iter().map(|p| p[d]).sum::<f64>() / count)
+            .collect();
+        let mut covariance = vec![vec![0.0; dims]; dims];
+        for point in points {
+            for i in 0..dims {
+                let centered = point[i] - mean[i];
+                for (j, entry) in covariance[i].iter_mut().enumerate() {
+                    *entry += centered * (point[j] - mean[j]) / count;
+                }
+            }
+        }
+        let total: f64 = (0..dims).map(|d| covariance[d][d]).sum();
+
+        let mut components = Vec::new();
+        let mut explained_variance = Vec::new();
+        for component in 0..k.min(dims) {
+            // A start that is not orthogonal to any particular axis
+            let mut vector: Vec<f64> = (0..dims)
+                .map(|d| 1.0 + ((d + component) % 7) as f64 * 0.1)
+                .collect();
+            let mut eigenvalue = 0.0;
+            for _ in 0..POWER_ITERATIONS {
+                let next: Vec<f64> = covariance
+                    .iter()
+                    .map(|row| row.iter().zip(&vector).map(|(c, v)| c * v).sum())
+                    .collect();
+                let norm = next.iter().map(|v| v * v).sum::<f64>().sqrt();
+                if norm < 1e-12 {
+                    break;
+                }
+                eigenvalue = norm;
+                vector = next.into_iter().map(|v| v / norm).collect();
+            }
+            // Deflate so the next iteration finds the following component
+            for i in 0..dims {
+                for j in 0..dims {
+                    covariance[i][j] -= eigenvalue * vector[i] * vector[j];
+                }
+            }
+            explained_variance.push(if total > 0.0 { eigenvalue / total } else { 0.0 });
+            components.push(vector);
+        }
+        Self {
+            mean,
+            components,
+            explained_variance,
+        }
+    }
+
+    /// Coordinates of `point` along the components
+    pub fn transform(&self, point: &[f64]) -> Vec<f64> {
+        self.components
+            .iter()
+            .map(|component| {
+                component
+                    .iter()
+                    .zip(point.iter().zip(&self.mean))
+                    .map(|(c, (p, m))| c * (p - m))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Fit two components to `points` and project them
+    pub fn project(points: &[Vec<f64>]) -> (Self, Vec<(f64, f64)>) {
+        let pca = Self::fit(points, 2);
+        let projected = points
+            .iter()
+            .map(|point| {
+                let coordinates = pca.transform(point);
+                (
+                    coordinates.first().copied().unwrap_or(0.0),
+                    coordinates.get(1).copied().unwrap_or(0.0),
+                )
+            })
+            .collect();
+        (pca, projected)
+    }
+}
+
+/// Exact t-SNE embedding of `points` in two dimensions
+///
+/// Each point's neighbourhood is sized to `perplexity` effective neighbours;
+/// the embedding starts from a small random layout drawn from `seed` and is
+/// optimised for `iterations` steps, the first 250 with early exaggeration.
+/// Time and memory grow with the square of the point count, so keep to a few
+/// thousand samples.
+#[cfg(feature = "tsne")]
+pub fn tsne(points: &[Vec<f64>], perplexity: f64, iterations: usize, seed: u64) -> Vec<(f64, f64)> {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let n = points.len();
+    if n < 2 {
+        return vec![(0.0, 0.0); n];
+    }
+    let distances: Vec<Vec<f64>> = points
+        .iter()
+        .map(|a| {
+            points
+                .iter()
+                .map(|b| a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum())
+                .collect()
+        })
+        .collect();
+
+    // Conditional probabilities with a bandwidth per point matching the perplexity
+    let target_entropy = perplexity.clamp(1.0, (n - 1) as f64).ln();
+    let mut p = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        let (mut beta, mut low, mut high) = (1.0, 0.0, f64::INFINITY);
+        for _ in 0..64 {
+            let mut sum = 0.0;
+            let mut weighted = 0.0;
+            for j in (0..n).filter(|&j| j != i) {
+                p[i][j] = (-beta * distances[i][j]).exp();
+                sum += p[i][j];
+                weighted += distances[i][j] * p[i][j];
+            }
+            let sum = sum.max(f64::MIN_POSITIVE);
+            let entropy = sum.ln() + beta * weighted / sum;
+            for probability in &mut p[i] {
+                *probability /= sum;
+            }
+            if (entropy - target_entropy).abs() < 1e-5 {
+                break;
+            }
+            if entropy > target_entropy {
+                low = beta;
+                beta = if high.is_finite() {
+                    (beta + high) / 2.0
+                } else {
+                    beta * 2.0
+                };
+            } else {
+                high = beta;
+                beta = (beta + low) / 2.0;
+            }
+        }
+    }
+    let symmetric: Vec<Vec<f64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| ((p[i][j] + p[j][i]) / (2.0 * n as f64)).max(1e-12))
+                .collect()
+        })
+        .collect();
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut y: Vec<[f64; 2]> = (0..n)
+        .map(|_| {
+            [
+                crate::datasets::gaussian(&mut rng) * 1e-4,
+                crate::datasets::gaussian(&mut rng) * 1e-4,
+            ]
+        })
+        .collect();
+    let mut velocity = vec![[0.0; 2]; n];
+    let mut gains = vec![[1.0f64; 2]; n];
+    let learning_rate = (n as f64 / 12.0).max(50.0);
+    for iteration in 0..iterations {
+        let exaggeration = if iteration < 250 { 12.0 } else { 1.0 };
+        let momentum = if iteration < 250 { 0.5 } else { 0.8 };
+
+        let mut kernel = vec![vec![0.0; n]; n];
+        let mut total = 0.0;
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let distance = (y[i][0] - y[j][0]).powi(2) + (y[i][1] - y[j][1]).powi(2);
+                let value = 1.0 / (1.0 + distance);
+                kernel[i][j] = value;
+                kernel[j][i] = value;
+                total += 2.0 * value;
+            }
+        }
+        for i in 0..n {
+            let mut gradient = [0.0; 2];
+            for j in (0..n).filter(|&j| j != i) {
+                let q = (kernel[i][j] / total).max(1e-12);
+                let force = 4.0 * (exaggeration * symmetric[i][j] - q) * kernel[i][j];
+                gradient[0] += force * (y[i][0] - y[j][0]);
+                gradient[1] += force * (y[i][1] - y[j][1]);
+            }
+            for d in 0..2 {
+                gains[i][d] = if (gradient[d] > 0.0) != (velocity[i][d] > 0.0) {
+                    gains[i][d] + 0.2
+                } else {
+                    (gains[i][d] * 0.8).max(0.01)
+                };
+                velocity[i][d] =
+                    momentum * velocity[i][d] - learning_rate * gains[i][d] * gradient[d];
+            }
+        }
+        for (point, step) in y.iter_mut().zip(&velocity) {
+            point[0] += step[0];
+            point[1] += step[1];
+        }
+    }
+    y.into_iter().map(|[a, b]| (a, b)).collect()
+}
+
+/// Scatter `points` coloured by `classes` into an SVG or PNG image, following the extension of `path`
+///
+/// As with `visualize::render`, only SVG images carry axis labels and a legend.
+pub fn write_scatter<P: AsRef<Path>>(
+    path: P,
+    title: &str,
+    points: &[(f64, f64)],
+    classes: &[usize],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    match ImageFormat::from_path(path) {
+        Some(ImageFormat::Svg) => {
+            let area = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw_scatter(&area, title, points, classes, true)?;
+        }
+        Some(ImageFormat::Png) => {
+            let area = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw_scatter(&area, title, points, classes, false)?;
+        }
+        None => {
+            return Err(format!(
+                "Unknown image format for '{}' (expected .svg or .png)",
+                path.display()
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+fn draw_scatter<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    title: &str,
+    points: &[(f64, f64)],
+    classes: &[usize],
+    labels: bool,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    area.fill(&WHITE)?;
+    let range = |coordinate: fn(&(f64, f64)) -> f64| {
+        let (low, high) = points
+            .iter()
+            .map(coordinate)
+            .filter(|v| v.is_finite())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+                (lo.min(v), hi.max(v))
+            });
+        if !low.is_finite() {
+            return -1.0..1.0;
+        }
+        let pad = ((high - low) * 0.05).max(1e-9);
+        (low - pad)..(high + pad)
+    };
+
+    let mut builder = ChartBuilder::on(area);
+    builder.margin(20);
+    if labels {
+        builder
+            .caption(title, ("sans-serif", 20))
+            .x_label_area_size(40)
+            .y_label_area_size(60);
+    }
+    let mut chart = builder.build_cartesian_2d(range(|p| p.0), range(|p| p.1))?;
+    if labels {
+        chart.configure_mesh().draw()?;
+    }
+    let class_count = classes.iter().max().map_or(0, |&c| c + 1);
+    for class in 0..class_count {
+        let color = Palette99::pick(class).to_rgba();
+        let drawn = chart.draw_series(
+            points
+                .iter()
+                .zip(classes)
+                .filter(|(_, &c)| c == class)
+                .map(|(&point, _)| Circle::new(point, 3, color.filled())),
+        )?;
+        if labels {
+            drawn
+                .label(format!("class {}", class))
+                .legend(move |(x, y)| Circle::new((x + 10, y), 4, color.filled()));
+        }
+    }
+    if labels && class_count > 1 {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    area.present()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_hidden_activations() {
+        // Points spread along (1, 1, 0) with a little noise along z
+        let points: Vec<Vec<f64>> = (0..20)
+            .map(|i| {
+                let t = i as f64 - 10.0;
+                vec![t, t, (i % 2) as f64 * 0.1]
+            })
+            .collect();
+        let (pca, projected) = Pca::project(&points);
+        let first = &pca.components[0];
+        assert!((first[0].abs() - 0.5f64.sqrt()).abs() < 1e-2);
+        assert!(first[2].abs() < 1e-2);
+        assert!(pca.explained_variance[0] > 0.99);
+        assert!((projected[19].0 - projected[0].0).abs() > 25.0);
+
+        let network = NeuralNetwork::with_layers(&[2, 4, 1], 0.1);
+        let inputs = vec![vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]];
+        let hidden = layer_activations(&network, &inputs, 1).unwrap();
+        assert_eq!(hidden.len(), 3);
+        assert_eq!(hidden[0].len(), 4);
+        assert!(layer_activations(&network, &inputs, 3).is_err());
+        assert_eq!(class_of(&[0.7]), 1);
+        assert_eq!(class_of(&[0.1, 0.8, 0.3]), 1);
+
+        let path =
+            std::env::temp_dir().join(format!("benny-projection-{}.svg", std::process::id()));
+        let (_, projected) = Pca::project(&hidden);
+        write_scatter(&path, "Layer 1", &projected, &[0, 1, 1]).unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(svg.contains("class 1"));
+
+        #[cfg(feature = "tsne")]
+        {
+            let clusters: Vec<Vec<f64>> = (0..30)
+                .map(|i| {
+                    vec![
+                        (i / 15) as f64 * 10.0 + (i % 5) as f64 * 0.1,
+                        (i % 3) as f64 * 0.1,
+                    ]
+                })
+                .collect();
+            let embedded = tsne(&clusters, 5.0, 300, 7);
+            let distance =
+                |a: (f64, f64), b: (f64, f64)| ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt();
+            // Neighbours within a cluster stay closer than points of the other one
+            assert!(distance(embedded[0], embedded[1]) < distance(embedded[0], embedded[29]));
+        }
+    }
+}
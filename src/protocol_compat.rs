@@ -0,0 +1,182 @@
+use crate::distributed_network::{
+    MessagePayload, MessageType, NetworkMessage, ProtocolError, PROTOCOL_VERSION,
+};
+
+/// Oldest protocol version this build still talks to
+///
+/// Adjacent versions may be mixed in one cluster while it is upgraded: each
+/// frame advertises its sender's version in the low nibble of the VERSION
+/// byte, both sides settle on the older of the two, and `translate` rewrites
+/// outgoing messages for peers that are still on the previous version.
+pub const MIN_PROTOCOL_VERSION: u8 = 1;
+
+/// Whether frames of `version` can be decoded and produced
+pub fn is_supported(version: u8) -> bool {
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION).contains(&version)
+}
+
+/// Version two nodes speak to each other: the older of their own
+pub fn negotiate(local: u8, remote: u8) -> u8 {
+    local.min(remote)
+}
+
+/// Whether a peer speaking `version` knows `msg_type`
+pub fn supports_type(version: u8, msg_type: MessageType) -> bool {
+    match msg_type {
+        MessageType::Handshake
+        | MessageType::HandshakeAck
+        | MessageType::ForwardData
+        | MessageType::BackwardData
+        | MessageType::HebbianData
+        | MessageType::WeightSync
+        | MessageType::Heartbeat
+        | MessageType::Disconnect
+        | MessageType::Error => true,
+        MessageType::Ack
+        | MessageType::SpikeEvents
+        | MessageType::Info
+        | MessageType::InfoResponse
+        | MessageType::ConfigUpdate
+        | MessageType::ModelPush
+        | MessageType::ModelPushAck
+        | MessageType::Ping
+        | MessageType::Pong => version >= 2,
+    }
+}
+
+/// Rewrite `message` into a form peers speaking `version` decode
+///
+/// Version 1 predates header flags and the optional trailing fields, so the
+/// handshake namespace, channel table and value ranges, the rejection
+/// reason, the send timestamps and channel tags are dropped, and
+/// `ACK_REQUIRED` is cleared. Message types the older version does not know
+/// cannot be translated.
+pub fn translate(message: &NetworkMessage, version: u8) -> Result<NetworkMessage, ProtocolError> {
+    if !is_supported(version) {
+        return Err(ProtocolError::UnsupportedVersion);
+    }
+    if !supports_type(version, message.msg_type) {
+        return Err(ProtocolError::Untranslatable {
+            msg_type: message.msg_type,
+            version,
+        });
+    }
+    if version >= 2 {
+        return Ok(message.clone());
+    }
+
+    let payload = match &message.payload {
+        MessagePayload::Handshake {
+            network_id,
+            name,
+            layers,
+            capabilities,
+            ..
+        } => MessagePayload::Handshake {
+            network_id: *network_id,
+            name: name.clone(),
+            layers: layers.clone(),
+            capabilities: *capabilities,
+            namespace: None,
+            channels: Vec::new(),
+            ranges: None,
+        },
+        MessagePayload::HandshakeAck {
+            network_id,
+            accepted,
+            ..
+        } => MessagePayload::HandshakeAck {
+            network_id: *network_id,
+            accepted: *accepted,
+            reason: None,
+            ranges: None,
+        },
+        MessagePayload::ForwardData { layer_id, data, .. } => MessagePayload::ForwardData {
+            layer_id: *layer_id,
+            data: data.clone(),
+            timestamp: None,
+            channel: None,
+        },
+        MessagePayload::HebbianData {
+            layer_id,
+            correlations,
+            learning_rate,
+            ..
+        } => MessagePayload::HebbianData {
+            layer_id: *layer_id,
+            correlations: correlations.clone(),
+            learning_rate: *learning_rate,
+            timestamp: None,
+        },
+        payload => payload.clone(),
+    };
+    Ok(NetworkMessage {
+        msg_type: message.msg_type,
+        sequence: message.sequence,
+        payload,
+        flags: 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributed_network::message_flags;
+
+    #[test]
+    fn test_translate_to_previous_version() {
+        assert!(is_supported(1) && is_supported(PROTOCOL_VERSION));
+        assert!(!is_supported(0) && !is_supported(PROTOCOL_VERSION + 1));
+        assert_eq!(negotiate(PROTOCOL_VERSION, 1), 1);
+
+        let forward = NetworkMessage {
+            msg_type: MessageType::ForwardData,
+            sequence: 9,
+            payload: MessagePayload::ForwardData {
+                layer_id: 0,
+                data: vec![0.5, -1.0],
+                timestamp: Some(1234),
+                channel: Some(2),
+            },
+            flags: message_flags::ACK_REQUIRED,
+        };
+        let old = translate(&forward, 1).unwrap();
+        assert_eq!(old.flags, 0);
+        assert_eq!(old.sequence, 9);
+        match old.payload {
+            MessagePayload::ForwardData {
+                data,
+                timestamp,
+                channel,
+                ..
+            } => {
+                assert_eq!(data, vec![0.5, -1.0]);
+                assert_eq!(timestamp, None);
+                assert_eq!(channel, None);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        // The current version passes messages through untouched
+        let current = translate(&forward, PROTOCOL_VERSION).unwrap();
+        assert_eq!(current.flags, message_flags::ACK_REQUIRED);
+
+        let info = NetworkMessage {
+            msg_type: MessageType::Info,
+            sequence: 1,
+            payload: MessagePayload::Info,
+            flags: 0,
+        };
+        assert!(matches!(
+            translate(&info, 1),
+            Err(ProtocolError::Untranslatable {
+                msg_type: MessageType::Info,
+                version: 1
+            })
+        ));
+        assert!(matches!(
+            translate(&info, 0),
+            Err(ProtocolError::UnsupportedVersion)
+        ));
+    }
+}
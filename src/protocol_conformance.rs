@@ -0,0 +1,552 @@
+use crate::distributed_network::{
+    message_flags, MessagePayload, NetworkMessage, PeerInfo, HEADER_SIZE, PROTOCOL_VERSION,
+};
+use crate::health::{ComponentHealth, HealthReport};
+use crate::model_push::ModelChunk;
+use crate::protocol_spec::{self, ALL_MESSAGE_TYPES};
+use crate::remote_config::ConfigUpdate;
+use crate::scaling::{SignalRanges, ValueRange};
+use crate::spikes::SpikeEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Explains the fixture files to implementers of other languages
+const FIXTURES_README: &str = "\
+# NNP conformance fixtures
+
+Generated by `benny protocol fixtures` from the Rust reference implementation.
+
+Every `<name>.bin` is one complete NNP frame (header and payload) and
+`<name>.json` holds what it decodes to: the message `type` and its `code`,
+the `version` it is encoded for, and the `header` and `payload` fields named
+as in `benny protocol spec`. Integers and floats are JSON numbers (floats are
+the exact `f32` values; NaN and infinities are the strings `NaN`, `inf` and
+`-inf`), UUIDs are hyphenated, raw bytes are lowercase hex and optional
+trailing fields the frame omits are left out.
+
+An implementation conforms when it decodes every `.bin` into the fields of
+its `.json`, and encodes those fields back into the same bytes. To check an
+encoder, write its frames as `.bin` files next to copies of the `.json`
+files and run `benny protocol conformance <dir>`.
+";
+
+/// A canonical NNP message, shipped as a binary frame and its decoded fields
+#[derive(Debug, Clone)]
+pub struct Fixture {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Protocol version the frame is encoded for
+    pub version: u8,
+    pub message: NetworkMessage,
+}
+
+/// Decoded fields of a fixture, as stored in its `.json` file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExpectedFrame {
+    pub description: String,
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    pub code: u8,
+    pub version: u8,
+    pub header: Map<String, Value>,
+    pub payload: Map<String, Value>,
+}
+
+impl Fixture {
+    fn new(name: &'static str, description: &'static str, payload: MessagePayload) -> Self {
+        Self {
+            name,
+            description,
+            version: PROTOCOL_VERSION,
+            message: NetworkMessage {
+                msg_type: payload.message_type(),
+                sequence: 1,
+                payload,
+                flags: 0,
+            },
+        }
+    }
+
+    pub fn bytes(&self) -> Result<Vec<u8>, String> {
+        self.message
+            .to_bytes_for(self.version)
+            .map_err(|e| format!("{}: {:?}", self.name, e))
+    }
+
+    /// Fields the frame decodes to, read off its bytes with the layouts of `benny protocol spec`
+    pub fn expected(&self) -> Result<ExpectedFrame, String> {
+        let bytes = self.bytes()?;
+        Ok(ExpectedFrame {
+            description: self.description.to_string(),
+            msg_type: format!("{:?}", self.message.msg_type),
+            code: self.message.msg_type as u8,
+            version: self.version,
+            header: protocol_spec::decode_header(&bytes)?,
+            payload: protocol_spec::decode_payload(self.message.msg_type, &bytes[HEADER_SIZE..])?,
+        })
+    }
+}
+
+fn node_id(n: u128) -> Uuid {
+    Uuid::from_u128(0x6e6e7000_0000_4000_8000_000000000000 | n)
+}
+
+/// The canonical messages: every message type, with and without its optional fields
+pub fn canonical_fixtures() -> Vec<Fixture> {
+    let mut fixtures = vec![
+        Fixture::new(
+            "handshake",
+            "Handshake with the required fields only",
+            MessagePayload::Handshake {
+                network_id: node_id(1),
+                name: "node-a".to_string(),
+                layers: vec![4, 8, 2],
+                capabilities: 0x0F,
+                namespace: None,
+                channels: Vec::new(),
+                ranges: None,
+            },
+        ),
+        Fixture::new(
+            "handshake_full",
+            "Handshake with a namespace, a channel table and declared value ranges",
+            MessagePayload::Handshake {
+                network_id: node_id(1),
+                name: "node-a".to_string(),
+                layers: vec![2],
+                capabilities: 0x0F,
+                namespace: Some("lab".to_string()),
+                channels: vec!["imu".to_string(), "lidar".to_string()],
+                ranges: Some(SignalRanges {
+                    input: Some(ValueRange::UNIT),
+                    output: None,
+                }),
+            },
+        ),
+        Fixture::new(
+            "handshake_ack",
+            "Accepted handshake",
+            MessagePayload::HandshakeAck {
+                network_id: node_id(2),
+                accepted: true,
+                reason: None,
+                ranges: None,
+            },
+        ),
+        Fixture::new(
+            "handshake_ack_refused",
+            "Refused handshake with its reason",
+            MessagePayload::HandshakeAck {
+                network_id: node_id(2),
+                accepted: false,
+                reason: Some("incompatible layer shapes".to_string()),
+                ranges: None,
+            },
+        ),
+        Fixture::new(
+            "ack",
+            "Acknowledgment of a data message",
+            MessagePayload::Ack {
+                sequence: 41,
+                timestamps: None,
+            },
+        ),
+        Fixture::new(
+            "ack_heartbeat",
+            "Acknowledgment of a heartbeat with the acker's receive and reply times",
+            MessagePayload::Ack {
+                sequence: 42,
+                timestamps: Some((1_000_000, 1_000_250)),
+            },
+        ),
+        Fixture::new(
+            "forward_data",
+            "Untagged, unstamped activations",
+            MessagePayload::ForwardData {
+                layer_id: 1,
+                data: vec![0.5, -0.25, 0.1],
+                timestamp: None,
+                channel: None,
+            },
+        ),
+        Fixture::new(
+            "forward_data_tagged",
+            "Activations with a send timestamp and a channel tag",
+            MessagePayload::ForwardData {
+                layer_id: 0,
+                data: vec![1.0],
+                timestamp: Some(1_700_000_000_000_000),
+                channel: Some(1),
+            },
+        ),
+        Fixture::new(
+            "backward_data",
+            "Gradients for a layer",
+            MessagePayload::BackwardData {
+                layer_id: 2,
+                gradients: vec![0.01, -0.02],
+            },
+        ),
+        Fixture::new(
+            "hebbian_data",
+            "Hebbian correlations with a timestamp",
+            MessagePayload::HebbianData {
+                layer_id: 0,
+                correlations: vec![0.1, 0.2, 0.3],
+                learning_rate: 0.01,
+                timestamp: Some(7),
+            },
+        ),
+        Fixture::new(
+            "weight_sync",
+            "Weights and biases of a layer",
+            MessagePayload::WeightSync {
+                layer_id: 0,
+                weights: vec![1.0, 2.0, -3.5, 0.0],
+                biases: vec![0.5, f32::NAN],
+            },
+        ),
+        Fixture::new(
+            "spike_events",
+            "Sparse spikes with their firing times",
+            MessagePayload::SpikeEvents {
+                layer_id: 2,
+                events: vec![
+                    SpikeEvent {
+                        neuron: 3,
+                        timestamp: 1_000,
+                    },
+                    SpikeEvent {
+                        neuron: 0,
+                        timestamp: 1_250,
+                    },
+                ],
+            },
+        ),
+        Fixture::new(
+            "disconnect",
+            "Disconnect notification",
+            MessagePayload::Disconnect {
+                reason: "shutting down".to_string(),
+            },
+        ),
+        Fixture::new(
+            "info",
+            "Request for the receiver's info",
+            MessagePayload::Info,
+        ),
+        Fixture::new(
+            "info_response",
+            "Architecture and activity statistics",
+            MessagePayload::InfoResponse {
+                info: PeerInfo {
+                    network_id: node_id(3),
+                    name: "node-b".to_string(),
+                    layers: vec![4, 8, 2],
+                    parameters: 58,
+                    learning_mode: "Oja".to_string(),
+                    uptime_secs: 3_600,
+                    peers: 2,
+                    messages_sent: 10,
+                    messages_received: 12,
+                    bytes_sent: 1_024,
+                    bytes_received: 2_048,
+                    weight_norm: Some(3.5),
+                    loss: None,
+                    outputs: vec![0.25, 0.75],
+                },
+            },
+        ),
+        Fixture::new(
+            "config_update",
+            "Runtime settings change without a token",
+            MessagePayload::ConfigUpdate {
+                update: ConfigUpdate {
+                    hebbian_rate: Some(0.05),
+                    online_learning: None,
+                    decay_rate: Some(0.001),
+                },
+                token: None,
+            },
+        ),
+        Fixture::new(
+            "config_update_token",
+            "Runtime settings change carrying an admin token",
+            MessagePayload::ConfigUpdate {
+                update: ConfigUpdate {
+                    online_learning: Some(true),
+                    ..ConfigUpdate::default()
+                },
+                token: Some("benny_0badc0de_secret".to_string()),
+            },
+        ),
+        Fixture::new(
+            "model_push",
+            "One chunk of a model push",
+            MessagePayload::ModelPush {
+                chunk: ModelChunk {
+                    transfer_id: 4,
+                    version: 2,
+                    index: 0,
+                    count: 1,
+                    checksum: 0xDEAD_BEEF,
+                    data: vec![1, 2, 3, 255],
+                },
+            },
+        ),
+        Fixture::new(
+            "model_push_ack_refused",
+            "Refused model push with its reason",
+            MessagePayload::ModelPushAck {
+                transfer_id: 4,
+                accepted: false,
+                previous_version: 1,
+                reason: Some("stale version".to_string()),
+            },
+        ),
+        Fixture::new("ping", "Readiness probe", MessagePayload::Ping),
+        Fixture::new(
+            "pong",
+            "Health report of a node that is not ready",
+            MessagePayload::Pong {
+                report: HealthReport {
+                    components: vec![
+                        ComponentHealth::new("peers", true, "2 connected"),
+                        ComponentHealth::new("model", false, "loading"),
+                    ],
+                },
+            },
+        ),
+        Fixture::new(
+            "error",
+            "Error report",
+            MessagePayload::Error {
+                code: 3,
+                message: "unknown layer".to_string(),
+            },
+        ),
+    ];
+
+    let mut heartbeat = Fixture::new(
+        "heartbeat_ack_required",
+        "Heartbeat with the ACK_REQUIRED flag in the version byte",
+        MessagePayload::Heartbeat { timestamp: 42 },
+    );
+    heartbeat.message.flags = message_flags::ACK_REQUIRED;
+    heartbeat.message.sequence = 9;
+    fixtures.push(heartbeat);
+
+    let mut legacy = Fixture::new(
+        "forward_data_v1",
+        "Activations encoded for a version 1 peer, without the optional fields",
+        MessagePayload::ForwardData {
+            layer_id: 1,
+            data: vec![0.5],
+            timestamp: None,
+            channel: None,
+        },
+    );
+    legacy.version = 1;
+    fixtures.push(legacy);
+    fixtures
+}
+
+/// Write every canonical fixture into `dir`, returning how many were written
+pub fn write_fixtures(dir: &Path) -> Result<usize, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let write = |name: String, content: &[u8]| {
+        let path = dir.join(name);
+        fs::write(&path, content).map_err(|e| format!("{}: {}", path.display(), e))
+    };
+    let fixtures = canonical_fixtures();
+    for fixture in &fixtures {
+        let json = serde_json::to_string_pretty(&fixture.expected()?).map_err(|e| e.to_string())?;
+        write(format!("{}.bin", fixture.name), &fixture.bytes()?)?;
+        write(
+            format!("{}.json", fixture.name),
+            format!("{}\n", json).as_bytes(),
+        )?;
+    }
+    write("README.md".to_string(), FIXTURES_README.as_bytes())?;
+    Ok(fixtures.len())
+}
+
+/// Differences between `expected` and `actual` fields, prefixed by `section`
+fn compare_fields(
+    section: &str,
+    expected: &Map<String, Value>,
+    actual: &Map<String, Value>,
+    failures: &mut Vec<String>,
+) {
+    for (name, value) in expected {
+        match actual.get(name) {
+            Some(actual) if actual == value => {}
+            Some(actual) => failures.push(format!(
+                "{}.{}: expected {}, got {}",
+                section, name, value, actual
+            )),
+            None => failures.push(format!("{}.{}: missing", section, name)),
+        }
+    }
+    for name in actual.keys().filter(|name| !expected.contains_key(*name)) {
+        failures.push(format!("{}.{}: not expected", section, name));
+    }
+}
+
+/// Every way `frame` falls short of `expected`; empty when it conforms
+///
+/// The frame must decode, with the layouts of the specification, to the
+/// expected header and payload fields, be accepted by the reference decoder
+/// and come out of the reference encoder byte for byte.
+pub fn check_frame(frame: &[u8], expected: &ExpectedFrame) -> Vec<String> {
+    let mut failures = Vec::new();
+    match protocol_spec::decode_header(frame) {
+        Ok(header) => compare_fields("header", &expected.header, &header, &mut failures),
+        Err(e) => return vec![e],
+    }
+    let Some(msg_type) = ALL_MESSAGE_TYPES
+        .into_iter()
+        .find(|msg_type| *msg_type as u8 == frame[5])
+    else {
+        failures.push(format!("Unknown message type 0x{:02X}", frame[5]));
+        return failures;
+    };
+    if msg_type as u8 != expected.code {
+        failures.push(format!(
+            "Message type 0x{:02X} ({:?}) instead of 0x{:02X} ({})",
+            msg_type as u8, msg_type, expected.code, expected.msg_type
+        ));
+        return failures;
+    }
+    match protocol_spec::decode_payload(msg_type, &frame[HEADER_SIZE..]) {
+        Ok(payload) => compare_fields("payload", &expected.payload, &payload, &mut failures),
+        Err(e) => failures.push(e),
+    }
+
+    match NetworkMessage::from_bytes(frame) {
+        Ok(message) => match message.to_bytes_for(NetworkMessage::frame_version(frame)) {
+            Ok(encoded) if encoded == frame => {}
+            Ok(encoded) => {
+                let offset = encoded
+                    .iter()
+                    .zip(frame)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or(encoded.len().min(frame.len()));
+                failures.push(format!(
+                    "The reference encoder writes {} bytes that differ from byte {} on",
+                    encoded.len(),
+                    offset
+                ));
+            }
+            Err(e) => failures.push(format!("The reference encoder refuses it: {:?}", e)),
+        },
+        Err(e) => failures.push(format!("The reference decoder rejects it: {:?}", e)),
+    }
+    failures
+}
+
+/// Outcome of one fixture of a conformance run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConformanceResult {
+    pub name: String,
+    pub failures: Vec<String>,
+}
+
+impl ConformanceResult {
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Check every `<name>.bin` frame of `dir` against its `<name>.json`, in name order
+pub fn check_dir(dir: &Path) -> Result<Vec<ConformanceResult>, String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map_err(|e| format!("{}: {}", dir.display(), e))?
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            (path.extension()? == "json").then(|| path.file_stem()?.to_str().map(String::from))?
+        })
+        .collect();
+    if names.is_empty() {
+        return Err(format!("{} holds no fixtures", dir.display()));
+    }
+    names.sort();
+    let mut results = Vec::new();
+    for name in names {
+        let json_path = dir.join(format!("{}.json", name));
+        let bin_path = dir.join(format!("{}.bin", name));
+        let failures = match (fs::read_to_string(&json_path), fs::read(&bin_path)) {
+            (Ok(json), Ok(frame)) => match serde_json::from_str::<ExpectedFrame>(&json) {
+                Ok(expected) => check_frame(&frame, &expected),
+                Err(e) => vec![format!("{}: {}", json_path.display(), e)],
+            },
+            (Err(e), _) => vec![format!("{}: {}", json_path.display(), e)],
+            (_, Err(e)) => vec![format!("{}: {}", bin_path.display(), e)],
+        };
+        results.push(ConformanceResult { name, failures });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shipped_fixtures_conform() {
+        let fixtures = canonical_fixtures();
+        for msg_type in ALL_MESSAGE_TYPES {
+            assert!(
+                fixtures.iter().any(|f| f.message.msg_type == msg_type),
+                "no fixture for {:?}",
+                msg_type
+            );
+        }
+        let expected = fixtures[6].expected().unwrap();
+        assert_eq!(expected.msg_type, "ForwardData");
+        assert_eq!(expected.header["magic"], "4e4e5000");
+        assert_eq!(
+            expected.payload["data"],
+            serde_json::json!([0.5, -0.25, 0.10000000149011612])
+        );
+        assert!(!expected.payload.contains_key("timestamp"));
+
+        // The checked-in fixtures are the ones this build writes, and all conform
+        let shipped = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/nnp");
+        for fixture in &fixtures {
+            let frame = fs::read(shipped.join(format!("{}.bin", fixture.name))).unwrap();
+            assert_eq!(frame, fixture.bytes().unwrap(), "{} drifted", fixture.name);
+            let json = fs::read_to_string(shipped.join(format!("{}.json", fixture.name))).unwrap();
+            let stored: ExpectedFrame = serde_json::from_str(&json).unwrap();
+            assert_eq!(
+                stored,
+                fixture.expected().unwrap(),
+                "{} drifted",
+                fixture.name
+            );
+        }
+        let results = check_dir(&shipped).unwrap();
+        assert_eq!(results.len(), fixtures.len());
+        assert!(
+            results.iter().all(ConformanceResult::passed),
+            "{:?}",
+            results
+        );
+
+        // A frame from a non-conforming encoder is pinned down field by field
+        let mut frame = fixtures[6].bytes().unwrap();
+        frame[HEADER_SIZE] = 7;
+        let failures = check_frame(&frame, &expected);
+        assert_eq!(failures[0], "payload.layer_id: expected 1, got 7");
+        assert!(failures[1].contains("ChecksumMismatch"));
+        let mut expected = expected;
+        expected.payload.remove("data");
+        assert_eq!(
+            check_frame(&fixtures[6].bytes().unwrap(), &expected),
+            vec!["payload.data: not expected"]
+        );
+    }
+}
@@ -0,0 +1,466 @@
+use crate::address::{self, AddressPreference, BindOptions};
+use crate::distributed_network::{NetworkMessage, HEADER_SIZE};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, WriteBytesExt};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+
+/// pcap link type used for NNP captures (`LINKTYPE_USER0`)
+///
+/// In Wireshark, map it to the generated dissector via
+/// Preferences > Protocols > DLT_USER > `User 0 (DLT=147)` with payload protocol `nnp`,
+/// or load the Lua dissector which registers itself for this link type.
+pub const LINKTYPE_NNP: u32 = 147;
+
+/// Largest frame stored in a capture record
+const SNAPLEN: u32 = 65_535;
+
+/// Direction byte prefixed to every captured frame
+///
+/// Record layout: `[DIRECTION: 1 byte][NNP frame: header + payload]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum CaptureDirection {
+    /// From the connecting client to the upstream node
+    ClientToServer = 0,
+    /// From the upstream node back to the client
+    ServerToClient = 1,
+}
+
+/// Writer for classic (libpcap 2.4) capture files containing NNP frames
+pub struct PcapWriter<W: Write> {
+    writer: W,
+    records: u64,
+}
+
+impl<W: Write> PcapWriter<W> {
+    /// Write the global pcap header
+    pub fn new(mut writer: W) -> std::io::Result<Self> {
+        writer.write_u32::<LittleEndian>(0xa1b2_c3d4)?; // magic, microsecond timestamps
+        writer.write_u16::<LittleEndian>(2)?; // version major
+        writer.write_u16::<LittleEndian>(4)?; // version minor
+        writer.write_i32::<LittleEndian>(0)?; // thiszone
+        writer.write_u32::<LittleEndian>(0)?; // sigfigs
+        writer.write_u32::<LittleEndian>(SNAPLEN)?;
+        writer.write_u32::<LittleEndian>(LINKTYPE_NNP)?;
+        Ok(Self { writer, records: 0 })
+    }
+
+    /// Append one NNP frame captured at `timestamp`
+    pub fn write_frame(
+        &mut self,
+        timestamp: SystemTime,
+        direction: CaptureDirection,
+        frame: &[u8],
+    ) -> std::io::Result<()> {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let original_len = frame.len() as u32 + 1;
+        let captured_len = original_len.min(SNAPLEN);
+
+        self.writer
+            .write_u32::<LittleEndian>(since_epoch.as_secs() as u32)?;
+        self.writer
+            .write_u32::<LittleEndian>(since_epoch.subsec_micros())?;
+        self.writer.write_u32::<LittleEndian>(captured_len)?;
+        self.writer.write_u32::<LittleEndian>(original_len)?;
+        self.writer.write_u8(direction as u8)?;
+        self.writer.write_all(&frame[..captured_len as usize - 1])?;
+        self.writer.flush()?;
+        self.records += 1;
+        Ok(())
+    }
+
+    /// Number of frames written so far
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+}
+
+/// Wireshark Lua dissector for captures written by `PcapWriter`
+pub fn lua_dissector() -> String {
+    LUA_DISSECTOR.to_string()
+}
+
+const LUA_DISSECTOR: &str = r#"-- Wireshark dissector for the Benny Neural Network Protocol (NNP)
+-- Generated by `benny protocol dump --lua`. Load with: wireshark -X lua_script:nnp.lua capture.pcap
+
+local nnp = Proto("nnp", "Neural Network Protocol")
+
+local msg_types = {
+    [0x01] = "Handshake", [0x02] = "HandshakeAck", [0x03] = "Ack",
+    [0x10] = "ForwardData", [0x11] = "BackwardData", [0x12] = "HebbianData",
+    [0x13] = "WeightSync", [0x14] = "SpikeEvents", [0x20] = "Heartbeat", [0x21] = "Disconnect",
+    [0x22] = "Info", [0x23] = "InfoResponse", [0x24] = "ConfigUpdate",
+    [0x25] = "ModelPush", [0x26] = "ModelPushAck", [0x27] = "Ping", [0x28] = "Pong",
+    [0xFF] = "Error",
+}
+local directions = { [0] = "client -> server", [1] = "server -> client" }
+
+local f = nnp.fields
+f.direction = ProtoField.uint8("nnp.direction", "Direction", base.DEC, directions)
+f.magic = ProtoField.bytes("nnp.magic", "Magic")
+f.version = ProtoField.uint8("nnp.version", "Version", base.DEC, nil, 0x0F)
+f.ack_required = ProtoField.bool("nnp.ack_required", "ACK required", 8, nil, 0x80)
+f.msg_type = ProtoField.uint8("nnp.type", "Message type", base.HEX, msg_types)
+f.length = ProtoField.uint32("nnp.length", "Payload length", base.DEC)
+f.sequence = ProtoField.uint64("nnp.sequence", "Sequence", base.DEC)
+f.checksum = ProtoField.uint32("nnp.checksum", "CRC32", base.HEX)
+f.network_id = ProtoField.guid("nnp.network_id", "Network ID")
+f.name = ProtoField.string("nnp.name", "Name")
+f.layer = ProtoField.uint16("nnp.layer", "Layer size", base.DEC)
+f.capabilities = ProtoField.uint32("nnp.capabilities", "Capabilities", base.HEX)
+f.namespace = ProtoField.string("nnp.namespace", "Namespace")
+f.accepted = ProtoField.bool("nnp.accepted", "Accepted")
+f.reason = ProtoField.string("nnp.reason", "Reason")
+f.acked_sequence = ProtoField.uint64("nnp.acked_sequence", "Acknowledged sequence", base.DEC)
+f.layer_id = ProtoField.uint8("nnp.layer_id", "Layer ID", base.DEC)
+f.count = ProtoField.uint32("nnp.count", "Value count", base.DEC)
+f.value = ProtoField.float("nnp.value", "Value")
+f.learning_rate = ProtoField.float("nnp.learning_rate", "Learning rate")
+f.timestamp = ProtoField.uint64("nnp.timestamp", "Timestamp", base.DEC)
+f.received_at = ProtoField.uint64("nnp.received_at", "Received at (µs)", base.DEC)
+f.replied_at = ProtoField.uint64("nnp.replied_at", "Replied at (µs)", base.DEC)
+f.sent_at = ProtoField.uint64("nnp.sent_at", "Sent at (µs, receiver clock)", base.DEC)
+f.neuron = ProtoField.uint32("nnp.neuron", "Neuron", base.DEC)
+f.spike_time = ProtoField.uint64("nnp.spike_time", "Spike time (µs)", base.DEC)
+f.text = ProtoField.string("nnp.text", "Text")
+f.error_code = ProtoField.uint16("nnp.error_code", "Error code", base.DEC)
+f.parameters = ProtoField.uint64("nnp.parameters", "Parameters", base.DEC)
+f.learning_mode = ProtoField.string("nnp.learning_mode", "Learning mode")
+f.uptime = ProtoField.uint64("nnp.uptime", "Uptime (s)", base.DEC)
+f.peers = ProtoField.uint32("nnp.peers", "Peers", base.DEC)
+f.messages_sent = ProtoField.uint64("nnp.messages_sent", "Messages sent", base.DEC)
+f.messages_received = ProtoField.uint64("nnp.messages_received", "Messages received", base.DEC)
+f.bytes_sent = ProtoField.uint64("nnp.bytes_sent", "Bytes sent", base.DEC)
+f.bytes_received = ProtoField.uint64("nnp.bytes_received", "Bytes received", base.DEC)
+f.weight_norm = ProtoField.float("nnp.weight_norm", "Weight norm")
+f.loss = ProtoField.float("nnp.loss", "Loss")
+f.config_present = ProtoField.uint8("nnp.config_present", "Present fields", base.HEX)
+f.hebbian_rate = ProtoField.float("nnp.hebbian_rate", "Hebbian rate")
+f.online_learning = ProtoField.bool("nnp.online_learning", "Online learning")
+f.decay_rate = ProtoField.float("nnp.decay_rate", "Decay rate")
+f.transfer_id = ProtoField.uint64("nnp.transfer_id", "Transfer ID", base.DEC)
+f.model_version = ProtoField.uint64("nnp.model_version", "Model version", base.DEC)
+f.chunk_index = ProtoField.uint32("nnp.chunk_index", "Chunk index", base.DEC)
+f.chunk_count = ProtoField.uint32("nnp.chunk_count", "Chunk count", base.DEC)
+f.model_checksum = ProtoField.uint32("nnp.model_checksum", "Model CRC32", base.HEX)
+f.chunk = ProtoField.bytes("nnp.chunk", "Chunk data")
+f.previous_version = ProtoField.uint64("nnp.previous_version", "Previous version", base.DEC)
+
+local function floats(buf, tree, offset)
+    local count = buf(offset, 4):uint()
+    tree:add(f.count, buf(offset, 4))
+    offset = offset + 4
+    for _ = 1, count do
+        tree:add(f.value, buf(offset, 4))
+        offset = offset + 4
+    end
+    return offset
+end
+
+local function short_string(buf, tree, field, offset)
+    local len = buf(offset, 1):uint()
+    tree:add(field, buf(offset + 1, len))
+    return offset + 1 + len
+end
+
+local payloads = {
+    [0x01] = function(buf, tree, o)
+        tree:add(f.network_id, buf(o, 16)); o = o + 16
+        o = short_string(buf, tree, f.name, o)
+        local layers = buf(o, 1):uint(); o = o + 1
+        for _ = 1, layers do tree:add(f.layer, buf(o, 2)); o = o + 2 end
+        tree:add(f.capabilities, buf(o, 4)); o = o + 4
+        if o < buf:len() then short_string(buf, tree, f.namespace, o) end
+    end,
+    [0x02] = function(buf, tree, o)
+        tree:add(f.network_id, buf(o, 16))
+        tree:add(f.accepted, buf(o + 16, 1))
+        if o + 17 < buf:len() then short_string(buf, tree, f.reason, o + 17) end
+    end,
+    [0x03] = function(buf, tree, o)
+        tree:add(f.acked_sequence, buf(o, 8))
+        if o + 8 < buf:len() then
+            tree:add(f.received_at, buf(o + 8, 8))
+            tree:add(f.replied_at, buf(o + 16, 8))
+        end
+    end,
+    [0x10] = function(buf, tree, o)
+        tree:add(f.layer_id, buf(o, 1))
+        local data_end = floats(buf, tree, o + 1)
+        if data_end < buf:len() then tree:add(f.sent_at, buf(data_end, 8)) end
+    end,
+    [0x11] = function(buf, tree, o) tree:add(f.layer_id, buf(o, 1)); floats(buf, tree, o + 1) end,
+    [0x12] = function(buf, tree, o)
+        tree:add(f.layer_id, buf(o, 1))
+        tree:add(f.learning_rate, buf(o + 1, 4))
+        local data_end = floats(buf, tree, o + 5)
+        if data_end < buf:len() then tree:add(f.sent_at, buf(data_end, 8)) end
+    end,
+    [0x13] = function(buf, tree, o)
+        tree:add(f.layer_id, buf(o, 1))
+        local weights_end = floats(buf, tree:add(nnp, buf(o + 1), "Weights"), o + 1)
+        floats(buf, tree:add(nnp, buf(weights_end), "Biases"), weights_end)
+    end,
+    [0x14] = function(buf, tree, o)
+        tree:add(f.layer_id, buf(o, 1))
+        local count = buf(o + 1, 4):uint()
+        tree:add(f.count, buf(o + 1, 4))
+        o = o + 5
+        for _ = 1, count do
+            local spike = tree:add(nnp, buf(o, 12), "Spike")
+            spike:add(f.neuron, buf(o, 4))
+            spike:add(f.spike_time, buf(o + 4, 8))
+            o = o + 12
+        end
+    end,
+    [0x20] = function(buf, tree, o) tree:add(f.timestamp, buf(o, 8)) end,
+    [0x21] = function(buf, tree, o) short_string(buf, tree, f.text, o) end,
+    [0x23] = function(buf, tree, o)
+        tree:add(f.network_id, buf(o, 16)); o = o + 16
+        o = short_string(buf, tree, f.name, o)
+        local layers = buf(o, 1):uint(); o = o + 1
+        for _ = 1, layers do tree:add(f.layer, buf(o, 2)); o = o + 2 end
+        tree:add(f.parameters, buf(o, 8)); o = o + 8
+        o = short_string(buf, tree, f.learning_mode, o)
+        tree:add(f.uptime, buf(o, 8)); o = o + 8
+        tree:add(f.peers, buf(o, 4)); o = o + 4
+        for _, field in ipairs({ f.messages_sent, f.messages_received, f.bytes_sent, f.bytes_received }) do
+            tree:add(field, buf(o, 8)); o = o + 8
+        end
+        if o < buf:len() then
+            tree:add(f.weight_norm, buf(o, 4))
+            tree:add(f.loss, buf(o + 4, 4))
+            local count = buf(o + 8, 4):uint(); o = o + 12
+            for _ = 1, count do tree:add(f.value, buf(o, 4)); o = o + 4 end
+        end
+    end,
+    [0x24] = function(buf, tree, o)
+        local present = buf(o, 1):uint()
+        tree:add(f.config_present, buf(o, 1))
+        if bit.band(present, 0x01) ~= 0 then tree:add(f.hebbian_rate, buf(o + 1, 4)) end
+        if bit.band(present, 0x02) ~= 0 then tree:add(f.online_learning, buf(o + 5, 1)) end
+        if bit.band(present, 0x04) ~= 0 then tree:add(f.decay_rate, buf(o + 6, 4)) end
+    end,
+    [0x25] = function(buf, tree, o)
+        tree:add(f.transfer_id, buf(o, 8))
+        tree:add(f.model_version, buf(o + 8, 8))
+        tree:add(f.chunk_index, buf(o + 16, 4))
+        tree:add(f.chunk_count, buf(o + 20, 4))
+        tree:add(f.model_checksum, buf(o + 24, 4))
+        local len = buf(o + 28, 4):uint()
+        if len > 0 then tree:add(f.chunk, buf(o + 32, len)) end
+    end,
+    [0x26] = function(buf, tree, o)
+        tree:add(f.transfer_id, buf(o, 8))
+        tree:add(f.accepted, buf(o + 8, 1))
+        tree:add(f.previous_version, buf(o + 9, 8))
+        if o + 17 < buf:len() then short_string(buf, tree, f.reason, o + 17) end
+    end,
+    [0xFF] = function(buf, tree, o)
+        tree:add(f.error_code, buf(o, 2))
+        short_string(buf, tree, f.text, o + 2)
+    end,
+}
+
+function nnp.dissector(buf, pinfo, root)
+    pinfo.cols.protocol = "NNP"
+    local tree = root:add(nnp, buf(), "Neural Network Protocol")
+    tree:add(f.direction, buf(0, 1))
+
+    local header = tree:add(nnp, buf(1, 22), "Header")
+    header:add(f.magic, buf(1, 4))
+    header:add(f.version, buf(5, 1))
+    header:add(f.ack_required, buf(5, 1))
+    header:add(f.msg_type, buf(6, 1))
+    header:add(f.length, buf(7, 4))
+    header:add(f.sequence, buf(11, 8))
+    header:add(f.checksum, buf(19, 4))
+
+    local msg_type = buf(6, 1):uint()
+    pinfo.cols.info = string.format("%s %s #%d", directions[buf(0, 1):uint()] or "?",
+        msg_types[msg_type] or "Unknown", buf(11, 8):uint64():tonumber())
+
+    local payload = payloads[msg_type]
+    if payload and buf(7, 4):uint() > 0 then
+        payload(buf, tree:add(nnp, buf(23), "Payload"), 23)
+    end
+end
+
+DissectorTable.get("wtap_encap"):add(wtap.USER0, nnp)
+"#;
+
+/// Relay that forwards NNP traffic to an upstream node and records every frame
+///
+/// Clients connect to the proxy instead of the node; both directions are
+/// split on NNP frame boundaries and written to the capture unchanged.
+pub struct ProtocolDumpProxy {
+    upstream_address: String,
+    upstream_port: u16,
+    capture: Arc<Mutex<PcapWriter<std::fs::File>>>,
+}
+
+impl ProtocolDumpProxy {
+    /// Create a proxy for `upstream_address:upstream_port` writing to `output`
+    pub fn new<P: AsRef<Path>>(
+        upstream_address: &str,
+        upstream_port: u16,
+        output: P,
+    ) -> std::io::Result<Self> {
+        let file = std::fs::File::create(output)?;
+        Ok(Self {
+            upstream_address: upstream_address.to_string(),
+            upstream_port,
+            capture: Arc::new(Mutex::new(PcapWriter::new(file)?)),
+        })
+    }
+
+    /// Frames captured so far
+    pub fn records(&self) -> u64 {
+        self.capture.lock().unwrap().records()
+    }
+
+    /// Accept clients on `address:port` and relay them until the listener fails
+    pub async fn run(&self, address: &str, port: u16) -> std::io::Result<()> {
+        let listener = address::bind_tcp_listener(address, port, BindOptions::default()).await?;
+        println!(
+            "🔎 Capturing NNP traffic on {} -> {}",
+            listener.local_addr()?,
+            address::format_host_port(&self.upstream_address, self.upstream_port)
+        );
+        self.serve(listener).await
+    }
+
+    /// Relay clients accepted on an already bound listener
+    pub async fn serve(&self, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        loop {
+            let (client, peer_addr) = listener.accept().await?;
+            let upstream = match address::connect_tcp(
+                &self.upstream_address,
+                self.upstream_port,
+                AddressPreference::System,
+            )
+            .await
+            {
+                Ok(stream) => stream,
+                Err(e) => {
+                    println!("❌ Upstream unreachable for {}: {}", peer_addr, e);
+                    continue;
+                }
+            };
+            println!("🔗 Relaying {}", peer_addr);
+
+            let (client_read, client_write) = client.into_split();
+            let (upstream_read, upstream_write) = upstream.into_split();
+            let capture = self.capture.clone();
+            tokio::spawn(Self::relay(
+                client_read,
+                upstream_write,
+                CaptureDirection::ClientToServer,
+                capture.clone(),
+            ));
+            tokio::spawn(Self::relay(
+                upstream_read,
+                client_write,
+                CaptureDirection::ServerToClient,
+                capture,
+            ));
+        }
+    }
+
+    /// Copy frames from `reader` to `writer`, recording each one
+    async fn relay(
+        mut reader: OwnedReadHalf,
+        mut writer: OwnedWriteHalf,
+        direction: CaptureDirection,
+        capture: Arc<Mutex<PcapWriter<std::fs::File>>>,
+    ) -> std::io::Result<()> {
+        let mut header = [0u8; HEADER_SIZE];
+        loop {
+            if let Err(e) = reader.read_exact(&mut header).await {
+                let _ = writer.shutdown().await;
+                return match e.kind() {
+                    std::io::ErrorKind::UnexpectedEof => Ok(()),
+                    _ => Err(e),
+                };
+            }
+
+            let payload_len = BigEndian::read_u32(&header[6..10]) as usize;
+            let mut frame = vec![0u8; HEADER_SIZE + payload_len];
+            frame[..HEADER_SIZE].copy_from_slice(&header);
+            reader.read_exact(&mut frame[HEADER_SIZE..]).await?;
+
+            match NetworkMessage::from_bytes(&frame) {
+                Ok(message) => println!(
+                    "📼 {:?} {:?} #{}",
+                    direction, message.msg_type, message.sequence
+                ),
+                Err(e) => println!("📼 {:?} undecodable frame: {:?}", direction, e),
+            }
+            capture
+                .lock()
+                .unwrap()
+                .write_frame(SystemTime::now(), direction, &frame)?;
+            writer.write_all(&frame).await?;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributed_network::DistributedNetwork;
+    use crate::neural_network::NeuralNetwork;
+
+    #[test]
+    fn test_pcap_layout() {
+        let mut bytes = Vec::new();
+        let mut writer = PcapWriter::new(&mut bytes).unwrap();
+        writer
+            .write_frame(UNIX_EPOCH, CaptureDirection::ServerToClient, &[0x4E, 0x4E])
+            .unwrap();
+        assert_eq!(writer.records(), 1);
+
+        assert_eq!(bytes.len(), 24 + 16 + 3);
+        assert_eq!(LittleEndian::read_u32(&bytes[0..4]), 0xa1b2_c3d4);
+        assert_eq!(LittleEndian::read_u32(&bytes[20..24]), LINKTYPE_NNP);
+        assert_eq!(LittleEndian::read_u32(&bytes[32..36]), 3);
+        assert_eq!(&bytes[40..], &[1, 0x4E, 0x4E]);
+    }
+
+    #[tokio::test]
+    async fn test_proxy_records_handshake() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let upstream_port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let (upstream, _inbox) = DistributedNetwork::new(
+            "upstream".to_string(),
+            "127.0.0.1".to_string(),
+            upstream_port,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        upstream.start_server().await.unwrap();
+
+        let path = std::env::temp_dir().join(format!("benny-dump-{}.pcap", uuid::Uuid::new_v4()));
+        let proxy = Arc::new(ProtocolDumpProxy::new("127.0.0.1", upstream_port, &path).unwrap());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+        let relay = proxy.clone();
+        tokio::spawn(async move { relay.serve(listener).await });
+
+        let (client, _) = DistributedNetwork::new(
+            "client".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+        );
+        client.connect_to("127.0.0.1", proxy_port).await.unwrap();
+
+        // Handshake and its acknowledgment
+        assert_eq!(proxy.records(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
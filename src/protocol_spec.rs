@@ -0,0 +1,891 @@
+use crate::distributed_network::{MessageType, HEADER_SIZE};
+use byteorder::{BigEndian, ByteOrder};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// Every message type on the wire, in code order
+pub const ALL_MESSAGE_TYPES: [MessageType; 18] = [
+    MessageType::Handshake,
+    MessageType::HandshakeAck,
+    MessageType::Ack,
+    MessageType::ForwardData,
+    MessageType::BackwardData,
+    MessageType::HebbianData,
+    MessageType::WeightSync,
+    MessageType::SpikeEvents,
+    MessageType::Heartbeat,
+    MessageType::Disconnect,
+    MessageType::Info,
+    MessageType::InfoResponse,
+    MessageType::ConfigUpdate,
+    MessageType::ModelPush,
+    MessageType::ModelPushAck,
+    MessageType::Ping,
+    MessageType::Pong,
+    MessageType::Error,
+];
+
+/// Encoding of a single field; all integers are big-endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    U8,
+    U16,
+    U32,
+    U64,
+    F32,
+    /// Boolean stored as one byte (0 = false)
+    Bool,
+    /// 16 raw UUID bytes
+    Uuid,
+    /// Fixed byte string
+    Bytes(usize),
+    /// `u8` length followed by that many UTF-8 bytes
+    String8,
+    /// `u8` element count followed by `u16` values
+    U16List8,
+    /// `u8` element count followed by `String8` values
+    StringList8,
+    /// `u8` element count followed by (`String8` name, `Bool` ready, `String8` detail) triples
+    ComponentList8,
+    /// `u32` element count followed by `f32` values
+    F32List32,
+    /// `u32` element count followed by (`u32` neuron, `u64` microseconds) pairs
+    SpikeList32,
+    /// `u32` length followed by that many raw bytes
+    Bytes32,
+}
+
+impl FieldKind {
+    /// Size in bytes when the field has a fixed width
+    pub fn fixed_size(self) -> Option<usize> {
+        match self {
+            FieldKind::U8 | FieldKind::Bool => Some(1),
+            FieldKind::U16 => Some(2),
+            FieldKind::U32 | FieldKind::F32 => Some(4),
+            FieldKind::U64 => Some(8),
+            FieldKind::Uuid => Some(16),
+            FieldKind::Bytes(len) => Some(len),
+            FieldKind::String8
+            | FieldKind::U16List8
+            | FieldKind::StringList8
+            | FieldKind::ComponentList8
+            | FieldKind::F32List32
+            | FieldKind::SpikeList32
+            | FieldKind::Bytes32 => None,
+        }
+    }
+
+    fn label(self) -> String {
+        match self {
+            FieldKind::U8 => "u8".to_string(),
+            FieldKind::U16 => "u16".to_string(),
+            FieldKind::U32 => "u32".to_string(),
+            FieldKind::U64 => "u64".to_string(),
+            FieldKind::F32 => "f32".to_string(),
+            FieldKind::Bool => "bool (u8)".to_string(),
+            FieldKind::Uuid => "uuid (16 bytes)".to_string(),
+            FieldKind::Bytes(len) => format!("bytes[{}]", len),
+            FieldKind::String8 => "u8 len + utf-8".to_string(),
+            FieldKind::U16List8 => "u8 count + u16[]".to_string(),
+            FieldKind::StringList8 => "u8 count + (u8 len + utf-8)[]".to_string(),
+            FieldKind::ComponentList8 => {
+                "u8 count + (u8 len + utf-8, bool, u8 len + utf-8)[]".to_string()
+            }
+            FieldKind::F32List32 => "u32 count + f32[]".to_string(),
+            FieldKind::SpikeList32 => "u32 count + (u32, u64)[]".to_string(),
+            FieldKind::Bytes32 => "u32 len + bytes".to_string(),
+        }
+    }
+
+    /// Number of bytes the field occupies at the start of `bytes`
+    fn measure(self, bytes: &[u8]) -> Option<usize> {
+        let size = match self {
+            FieldKind::String8 => 1 + *bytes.first()? as usize,
+            FieldKind::U16List8 => 1 + *bytes.first()? as usize * 2,
+            FieldKind::StringList8 => {
+                let mut size = 1;
+                for _ in 0..*bytes.first()? {
+                    size += 1 + *bytes.get(size)? as usize;
+                }
+                size
+            }
+            FieldKind::ComponentList8 => {
+                let mut size = 1;
+                for _ in 0..*bytes.first()? {
+                    size += 1 + *bytes.get(size)? as usize + 1;
+                    size += 1 + *bytes.get(size)? as usize;
+                }
+                size
+            }
+            FieldKind::F32List32 => 4 + BigEndian::read_u32(bytes.get(..4)?) as usize * 4,
+            FieldKind::SpikeList32 => 4 + BigEndian::read_u32(bytes.get(..4)?) as usize * 12,
+            FieldKind::Bytes32 => 4 + BigEndian::read_u32(bytes.get(..4)?) as usize,
+            fixed => fixed.fixed_size()?,
+        };
+        (size <= bytes.len()).then_some(size)
+    }
+}
+
+/// JSON value of an `f32`, with the values JSON lacks spelled out as strings
+fn f32_value(value: f32) -> Value {
+    if value.is_nan() {
+        Value::from("NaN")
+    } else if value.is_infinite() {
+        Value::from(if value > 0.0 { "inf" } else { "-inf" })
+    } else {
+        Value::from(value as f64)
+    }
+}
+
+fn string_value(bytes: &[u8]) -> Option<(Value, usize)> {
+    let len = *bytes.first()? as usize;
+    let text = std::str::from_utf8(bytes.get(1..1 + len)?).ok()?;
+    Some((Value::from(text), 1 + len))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl FieldKind {
+    /// Value of the field at the start of `bytes` and the bytes it occupies
+    ///
+    /// Integers and floats become JSON numbers (non-finite floats the strings
+    /// `NaN`, `inf` and `-inf`), UUIDs their hyphenated form, raw bytes
+    /// lowercase hex, and lists arrays; spikes and health components are
+    /// objects named after their fields.
+    pub fn decode(self, bytes: &[u8]) -> Option<(Value, usize)> {
+        let size = self.fixed_size().map_or_else(|| self.measure(bytes), Some)?;
+        let field = bytes.get(..size)?;
+        let value = match self {
+            FieldKind::U8 => Value::from(field[0]),
+            FieldKind::U16 => Value::from(BigEndian::read_u16(field)),
+            FieldKind::U32 => Value::from(BigEndian::read_u32(field)),
+            FieldKind::U64 => Value::from(BigEndian::read_u64(field)),
+            FieldKind::F32 => f32_value(BigEndian::read_f32(field)),
+            FieldKind::Bool => Value::from(field[0] != 0),
+            FieldKind::Uuid => Value::from(uuid::Uuid::from_slice(field).ok()?.to_string()),
+            FieldKind::Bytes(_) => Value::from(hex(field)),
+            FieldKind::String8 => string_value(field)?.0,
+            FieldKind::U16List8 => Value::from(
+                field[1..]
+                    .chunks(2)
+                    .map(BigEndian::read_u16)
+                    .collect::<Vec<_>>(),
+            ),
+            FieldKind::StringList8 => {
+                let mut values = Vec::new();
+                let mut offset = 1;
+                while offset < field.len() {
+                    let (value, len) = string_value(&field[offset..])?;
+                    values.push(value);
+                    offset += len;
+                }
+                Value::from(values)
+            }
+            FieldKind::ComponentList8 => {
+                let mut components = Vec::new();
+                let mut offset = 1;
+                while offset < field.len() {
+                    let (name, len) = string_value(&field[offset..])?;
+                    offset += len;
+                    let ready = field[offset] != 0;
+                    let (detail, len) = string_value(&field[offset + 1..])?;
+                    offset += 1 + len;
+                    components.push(serde_json::json!({
+                        "name": name,
+                        "ready": ready,
+                        "detail": detail,
+                    }));
+                }
+                Value::from(components)
+            }
+            FieldKind::F32List32 => Value::from(
+                field[4..]
+                    .chunks(4)
+                    .map(|chunk| f32_value(BigEndian::read_f32(chunk)))
+                    .collect::<Vec<_>>(),
+            ),
+            FieldKind::SpikeList32 => Value::from(
+                field[4..]
+                    .chunks(12)
+                    .map(|chunk| {
+                        serde_json::json!({
+                            "neuron": BigEndian::read_u32(&chunk[..4]),
+                            "timestamp": BigEndian::read_u64(&chunk[4..]),
+                        })
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            FieldKind::Bytes32 => Value::from(hex(&field[4..])),
+        };
+        Some((value, size))
+    }
+}
+
+/// Decode `bytes` field by field with `fields`, leaving out the optional trailing fields it omits
+fn decode_fields(
+    section: &str,
+    fields: &[FieldSpec],
+    bytes: &[u8],
+) -> Result<Map<String, Value>, String> {
+    let mut values = Map::new();
+    let mut offset = 0;
+    for spec in fields {
+        if spec.optional && offset == bytes.len() {
+            break;
+        }
+        let (value, size) = spec
+            .kind
+            .decode(&bytes[offset..])
+            .ok_or_else(|| format!("{}.{} truncated at byte {}", section, spec.name, offset))?;
+        values.insert(spec.name.to_string(), value);
+        offset += size;
+    }
+    if offset != bytes.len() {
+        return Err(format!(
+            "{} has {} bytes past its last field",
+            section,
+            bytes.len() - offset
+        ));
+    }
+    Ok(values)
+}
+
+/// Header fields of a frame, by name
+pub fn decode_header(frame: &[u8]) -> Result<Map<String, Value>, String> {
+    let header = frame
+        .get(..HEADER_SIZE)
+        .ok_or_else(|| format!("Frame of {} bytes is shorter than the header", frame.len()))?;
+    decode_fields("header", &header_fields(), header)
+}
+
+/// Payload fields of a `msg_type` payload, by name
+pub fn decode_payload(msg_type: MessageType, payload: &[u8]) -> Result<Map<String, Value>, String> {
+    decode_fields(&format!("{:?}", msg_type), &payload_fields(msg_type), payload)
+}
+
+/// One field of the header or of a payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpec {
+    pub name: &'static str,
+    pub kind: FieldKind,
+    pub description: &'static str,
+    /// Trailing field that older peers may omit
+    pub optional: bool,
+}
+
+const fn field(name: &'static str, kind: FieldKind, description: &'static str) -> FieldSpec {
+    FieldSpec {
+        name,
+        kind,
+        description,
+        optional: false,
+    }
+}
+
+/// Fields of the fixed 22-byte header
+pub fn header_fields() -> Vec<FieldSpec> {
+    vec![
+        field("magic", FieldKind::Bytes(4), "\"NNP\\0\" (0x4E4E5000)"),
+        field(
+            "version",
+            FieldKind::U8,
+            "Low nibble: protocol version (2, 1 still accepted); high nibble: flags (0x80 = ACK_REQUIRED, 0x40 = STALE)",
+        ),
+        field("msg_type", FieldKind::U8, "Message type code"),
+        field("length", FieldKind::U32, "Payload length in bytes"),
+        field("sequence", FieldKind::U64, "Sender sequence number"),
+        field("checksum", FieldKind::U32, "CRC32 of the payload"),
+    ]
+}
+
+/// Payload fields of `msg_type`, in wire order
+///
+/// This match is exhaustive so adding a message type fails to compile until
+/// its layout is described here; the tests check each layout against the
+/// actual serializer.
+pub fn payload_fields(msg_type: MessageType) -> Vec<FieldSpec> {
+    match msg_type {
+        MessageType::Handshake => vec![
+            field("network_id", FieldKind::Uuid, "Sender node identity"),
+            field("name", FieldKind::String8, "Human-readable node name"),
+            field(
+                "layers",
+                FieldKind::U16List8,
+                "Layer sizes of the sender's network",
+            ),
+            field("capabilities", FieldKind::U32, "Capability bitfield"),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "namespace",
+                    FieldKind::String8,
+                    "Cluster namespace (absent or empty = default)",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "channels",
+                    FieldKind::StringList8,
+                    "Names of the sender's forward data channels, indexed by tag",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "ranges",
+                    FieldKind::Bytes(16),
+                    "f32 input min/max then output min/max the sender declares (NaN = undeclared)",
+                )
+            },
+        ],
+        MessageType::HandshakeAck => vec![
+            field("network_id", FieldKind::Uuid, "Responder node identity"),
+            field(
+                "accepted",
+                FieldKind::Bool,
+                "Whether the handshake was accepted",
+            ),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "reason",
+                    FieldKind::String8,
+                    "Why the handshake was refused (absent or empty when accepted)",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "ranges",
+                    FieldKind::Bytes(16),
+                    "f32 input min/max then output min/max the sender declares (NaN = undeclared)",
+                )
+            },
+        ],
+        MessageType::Ack => vec![
+            field(
+                "sequence",
+                FieldKind::U64,
+                "Sequence number being acknowledged",
+            ),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "received_at",
+                    FieldKind::U64,
+                    "Acker time in microseconds when the message arrived (heartbeats only)",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "replied_at",
+                    FieldKind::U64,
+                    "Acker time in microseconds when the ack was sent (heartbeats only)",
+                )
+            },
+        ],
+        MessageType::ForwardData => vec![
+            field("layer_id", FieldKind::U8, "Target layer"),
+            field("data", FieldKind::F32List32, "Activations"),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "timestamp",
+                    FieldKind::U64,
+                    "Send time in microseconds on the receiver's clock (0 = unstamped)",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "channel",
+                    FieldKind::U8,
+                    "Tag into the sender's handshake channel table",
+                )
+            },
+        ],
+        MessageType::BackwardData => vec![
+            field("layer_id", FieldKind::U8, "Target layer"),
+            field("gradients", FieldKind::F32List32, "Gradients"),
+        ],
+        MessageType::HebbianData => vec![
+            field("layer_id", FieldKind::U8, "Target layer"),
+            field("learning_rate", FieldKind::F32, "Hebbian learning rate"),
+            field("correlations", FieldKind::F32List32, "Correlation values"),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "timestamp",
+                    FieldKind::U64,
+                    "Send time in microseconds on the receiver's clock",
+                )
+            },
+        ],
+        MessageType::WeightSync => vec![
+            field("layer_id", FieldKind::U8, "Layer being synchronized"),
+            field("weights", FieldKind::F32List32, "Row-major weights"),
+            field("biases", FieldKind::F32List32, "Biases"),
+        ],
+        MessageType::SpikeEvents => vec![
+            field("layer_id", FieldKind::U8, "Source layer"),
+            field(
+                "events",
+                FieldKind::SpikeList32,
+                "Firing neuron and time in microseconds on the receiver's clock",
+            ),
+        ],
+        MessageType::Heartbeat => vec![field(
+            "timestamp",
+            FieldKind::U64,
+            "Sender time in milliseconds",
+        )],
+        MessageType::Disconnect => vec![field(
+            "reason",
+            FieldKind::String8,
+            "Reason for disconnecting",
+        )],
+        MessageType::Info => vec![],
+        MessageType::InfoResponse => vec![
+            field("network_id", FieldKind::Uuid, "Responder node identity"),
+            field("name", FieldKind::String8, "Human-readable node name"),
+            field(
+                "layers",
+                FieldKind::U16List8,
+                "Layer sizes of the responder's network",
+            ),
+            field("parameters", FieldKind::U64, "Weights and biases"),
+            field("learning_mode", FieldKind::String8, "Hebbian learning mode"),
+            field(
+                "uptime_secs",
+                FieldKind::U64,
+                "Seconds since the node started",
+            ),
+            field("peers", FieldKind::U32, "Peers the node knows"),
+            field(
+                "messages_sent",
+                FieldKind::U64,
+                "Messages sent to all peers",
+            ),
+            field(
+                "messages_received",
+                FieldKind::U64,
+                "Messages received from all peers",
+            ),
+            field("bytes_sent", FieldKind::U64, "Bytes sent to all peers"),
+            field(
+                "bytes_received",
+                FieldKind::U64,
+                "Bytes received from all peers",
+            ),
+            FieldSpec {
+                optional: true,
+                ..field("weight_norm", FieldKind::F32, "L2 norm of the weights")
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "loss",
+                    FieldKind::F32,
+                    "Latest training loss (NaN when none was recorded)",
+                )
+            },
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "outputs",
+                    FieldKind::F32List32,
+                    "Outputs of the latest forward pass",
+                )
+            },
+        ],
+        MessageType::ConfigUpdate => vec![
+            field(
+                "present",
+                FieldKind::U8,
+                "Fields to apply: 0x01 hebbian_rate, 0x02 online_learning, 0x04 decay_rate",
+            ),
+            field("hebbian_rate", FieldKind::F32, "New Hebbian learning rate"),
+            field("online_learning", FieldKind::Bool, "Enable online learning"),
+            field("decay_rate", FieldKind::F32, "New weight decay rate"),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "token",
+                    FieldKind::String8,
+                    "API token with the admin scope, for nodes that require one",
+                )
+            },
+        ],
+        MessageType::ModelPush => vec![
+            field("transfer_id", FieldKind::U64, "Push this chunk belongs to"),
+            field(
+                "version",
+                FieldKind::U64,
+                "Version the model becomes active as",
+            ),
+            field("index", FieldKind::U32, "Chunk index"),
+            field("count", FieldKind::U32, "Chunks in the push"),
+            field(
+                "checksum",
+                FieldKind::U32,
+                "CRC32 of the complete serialized model",
+            ),
+            field(
+                "data",
+                FieldKind::Bytes32,
+                "Slice of the bincode-encoded model",
+            ),
+        ],
+        MessageType::ModelPushAck => vec![
+            field("transfer_id", FieldKind::U64, "Push being answered"),
+            field(
+                "accepted",
+                FieldKind::Bool,
+                "Whether the model was activated",
+            ),
+            field(
+                "previous_version",
+                FieldKind::U64,
+                "Model version active before the push",
+            ),
+            FieldSpec {
+                optional: true,
+                ..field(
+                    "reason",
+                    FieldKind::String8,
+                    "Why the model was refused (absent when accepted)",
+                )
+            },
+        ],
+        MessageType::Ping => vec![],
+        MessageType::Pong => vec![field(
+            "components",
+            FieldKind::ComponentList8,
+            "Health of each component; the node is ready when all are",
+        )],
+        MessageType::Error => vec![
+            field("code", FieldKind::U16, "Error code"),
+            field("message", FieldKind::String8, "Error description"),
+        ],
+    }
+}
+
+/// Walk `payload` with the layout of `msg_type`, returning the bytes consumed
+///
+/// Optional trailing fields are skipped when the payload ends before them.
+pub fn measure_payload(msg_type: MessageType, payload: &[u8]) -> Result<usize, String> {
+    let mut offset = 0;
+    for spec in payload_fields(msg_type) {
+        if spec.optional && offset == payload.len() {
+            break;
+        }
+        offset += spec
+            .kind
+            .measure(&payload[offset..])
+            .ok_or_else(|| format!("{:?}.{} truncated at byte {}", msg_type, spec.name, offset))?;
+    }
+    Ok(offset)
+}
+
+fn render_table(out: &mut String, fields: &[FieldSpec], base_offset: usize) {
+    out.push_str("| Offset | Field | Type | Description |\n");
+    out.push_str("|--------|-------|------|-------------|\n");
+    let mut offset = Some(base_offset);
+    for spec in fields {
+        let position = match offset {
+            Some(offset) => offset.to_string(),
+            None => "var".to_string(),
+        };
+        let name = if spec.optional {
+            format!("{} (optional)", spec.name)
+        } else {
+            spec.name.to_string()
+        };
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | {} | {} |",
+            position,
+            name,
+            spec.kind.label(),
+            spec.description
+        );
+        offset = offset.zip(spec.kind.fixed_size()).map(|(o, size)| o + size);
+    }
+}
+
+/// Render the complete NNP wire format as Markdown
+pub fn render_markdown() -> String {
+    let mut out = String::new();
+    out.push_str("# Neural Network Protocol (NNP) wire format\n\n");
+    out.push_str("Generated by `benny protocol spec` from the protocol definitions. ");
+    out.push_str(
+        "All integers are big-endian; offsets are relative to the start of the section.\n\n",
+    );
+
+    let _ = writeln!(out, "## Header ({} bytes)\n", HEADER_SIZE);
+    render_table(&mut out, &header_fields(), 0);
+
+    out.push_str("\n## Message types\n\n");
+    out.push_str("| Code | Type |\n|------|------|\n");
+    for msg_type in ALL_MESSAGE_TYPES {
+        let _ = writeln!(out, "| 0x{:02X} | {:?} |", msg_type as u8, msg_type);
+    }
+
+    for msg_type in ALL_MESSAGE_TYPES {
+        let _ = writeln!(out, "\n## {:?} (0x{:02X})\n", msg_type, msg_type as u8);
+        render_table(&mut out, &payload_fields(msg_type), 0);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributed_network::{MessagePayload, NetworkMessage, PeerInfo};
+    use crate::health::{ComponentHealth, HealthReport};
+    use crate::model_push::ModelChunk;
+    use crate::remote_config::ConfigUpdate;
+    use crate::scaling::{SignalRanges, ValueRange};
+    use crate::spikes::SpikeEvent;
+    use uuid::Uuid;
+
+    fn samples() -> Vec<MessagePayload> {
+        vec![
+            MessagePayload::Handshake {
+                network_id: Uuid::new_v4(),
+                name: "node".to_string(),
+                layers: vec![3, 2],
+                capabilities: 7,
+                namespace: None,
+                channels: Vec::new(),
+                ranges: None,
+            },
+            MessagePayload::Handshake {
+                network_id: Uuid::new_v4(),
+                name: "node".to_string(),
+                layers: vec![],
+                capabilities: 7,
+                namespace: Some("cluster".to_string()),
+                channels: Vec::new(),
+                ranges: None,
+            },
+            MessagePayload::Handshake {
+                network_id: Uuid::new_v4(),
+                name: "node".to_string(),
+                layers: vec![2],
+                capabilities: 7,
+                namespace: None,
+                channels: vec!["imu".to_string(), "lidar".to_string()],
+                ranges: None,
+            },
+            MessagePayload::Handshake {
+                network_id: Uuid::new_v4(),
+                name: "node".to_string(),
+                layers: vec![2],
+                capabilities: 7,
+                namespace: None,
+                channels: Vec::new(),
+                ranges: Some(SignalRanges {
+                    input: Some(ValueRange::UNIT),
+                    output: None,
+                }),
+            },
+            MessagePayload::HandshakeAck {
+                network_id: Uuid::new_v4(),
+                accepted: true,
+                reason: None,
+                ranges: None,
+            },
+            MessagePayload::HandshakeAck {
+                network_id: Uuid::new_v4(),
+                accepted: true,
+                reason: None,
+                ranges: Some(SignalRanges {
+                    input: None,
+                    output: Some(ValueRange::BIPOLAR),
+                }),
+            },
+            MessagePayload::HandshakeAck {
+                network_id: Uuid::new_v4(),
+                accepted: false,
+                reason: Some("incompatible layer shapes".to_string()),
+                ranges: None,
+            },
+            MessagePayload::Ack {
+                sequence: 9,
+                timestamps: None,
+            },
+            MessagePayload::Ack {
+                sequence: 9,
+                timestamps: Some((1_000, 1_020)),
+            },
+            MessagePayload::ForwardData {
+                layer_id: 1,
+                data: vec![0.5, 0.25],
+                timestamp: None,
+                channel: None,
+            },
+            MessagePayload::ForwardData {
+                layer_id: 1,
+                data: vec![0.5],
+                timestamp: Some(1_700_000_000_000_000),
+                channel: None,
+            },
+            MessagePayload::ForwardData {
+                layer_id: 0,
+                data: vec![0.5],
+                timestamp: None,
+                channel: Some(1),
+            },
+            MessagePayload::BackwardData {
+                layer_id: 1,
+                gradients: vec![0.1],
+            },
+            MessagePayload::HebbianData {
+                layer_id: 0,
+                correlations: vec![0.1, 0.2, 0.3],
+                learning_rate: 0.01,
+                timestamp: Some(7),
+            },
+            MessagePayload::WeightSync {
+                layer_id: 0,
+                weights: vec![1.0, 2.0],
+                biases: vec![0.5],
+            },
+            MessagePayload::SpikeEvents {
+                layer_id: 2,
+                events: vec![
+                    SpikeEvent {
+                        neuron: 3,
+                        timestamp: 1_000,
+                    },
+                    SpikeEvent {
+                        neuron: 0,
+                        timestamp: 1_250,
+                    },
+                ],
+            },
+            MessagePayload::Heartbeat { timestamp: 42 },
+            MessagePayload::Disconnect {
+                reason: "bye".to_string(),
+            },
+            MessagePayload::Info,
+            MessagePayload::ModelPush {
+                chunk: ModelChunk {
+                    transfer_id: 4,
+                    version: 2,
+                    index: 0,
+                    count: 1,
+                    checksum: 0xDEAD_BEEF,
+                    data: vec![1, 2, 3],
+                },
+            },
+            MessagePayload::ModelPushAck {
+                transfer_id: 4,
+                accepted: true,
+                previous_version: 1,
+                reason: None,
+            },
+            MessagePayload::ModelPushAck {
+                transfer_id: 4,
+                accepted: false,
+                previous_version: 1,
+                reason: Some("stale version".to_string()),
+            },
+            MessagePayload::Ping,
+            MessagePayload::Pong {
+                report: HealthReport::default(),
+            },
+            MessagePayload::Pong {
+                report: HealthReport {
+                    components: vec![
+                        ComponentHealth::new("peers", true, "2 connected"),
+                        ComponentHealth::queue("send_queue", 5000, 1024),
+                    ],
+                },
+            },
+            MessagePayload::ConfigUpdate {
+                update: ConfigUpdate {
+                    hebbian_rate: Some(0.05),
+                    online_learning: None,
+                    decay_rate: Some(0.001),
+                },
+                token: None,
+            },
+            MessagePayload::ConfigUpdate {
+                update: ConfigUpdate {
+                    online_learning: Some(true),
+                    ..ConfigUpdate::default()
+                },
+                token: Some("benny_0badc0de_secret".to_string()),
+            },
+            MessagePayload::InfoResponse {
+                info: PeerInfo {
+                    network_id: Uuid::new_v4(),
+                    name: "node".to_string(),
+                    layers: vec![4, 8, 2],
+                    parameters: 58,
+                    learning_mode: "Oja".to_string(),
+                    uptime_secs: 3_600,
+                    peers: 2,
+                    messages_sent: 10,
+                    messages_received: 12,
+                    bytes_sent: 1_024,
+                    bytes_received: 2_048,
+                    weight_norm: Some(3.5),
+                    loss: None,
+                    outputs: vec![0.25, 0.75],
+                },
+            },
+            MessagePayload::Error {
+                code: 3,
+                message: "oops".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_layouts_match_serializer() {
+        let mut covered = Vec::new();
+        for payload in samples() {
+            let msg_type = payload.message_type();
+            let bytes = NetworkMessage {
+                msg_type,
+                sequence: 1,
+                payload,
+                flags: 0,
+            }
+            .to_bytes();
+            let payload_bytes = &bytes[HEADER_SIZE..];
+            assert_eq!(
+                measure_payload(msg_type, payload_bytes),
+                Ok(payload_bytes.len()),
+                "layout of {:?} drifted from its serializer",
+                msg_type
+            );
+            covered.push(msg_type);
+        }
+
+        for msg_type in ALL_MESSAGE_TYPES {
+            assert!(covered.contains(&msg_type), "no sample for {:?}", msg_type);
+            assert_eq!(MessageType::from(msg_type as u8), msg_type);
+        }
+    }
+
+    #[test]
+    fn test_header_layout_size() {
+        let size: usize = header_fields()
+            .iter()
+            .map(|spec| spec.kind.fixed_size().unwrap())
+            .sum();
+        assert_eq!(size, HEADER_SIZE);
+
+        let markdown = render_markdown();
+        assert!(markdown.contains("## ForwardData (0x10)"));
+        assert!(markdown.contains("| var | `namespace (optional)` |"));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// How far `prune_neurons` shrinks a network
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PruneConfig {
+    /// Parameters of the original network per parameter of the pruned one,
+    /// e.g. 2 halves the model; reached as closely as whole neurons allow
+    pub compression: f64,
+    /// Epochs of `train` over the data after the neurons are removed
+    pub fine_tune_epochs: usize,
+    /// Neurons every hidden layer keeps
+    pub min_neurons: usize,
+}
+
+impl Default for PruneConfig {
+    fn default() -> Self {
+        Self {
+            compression: 2.0,
+            fine_tune_epochs: 20,
+            min_neurons: 1,
+        }
+    }
+}
+
+/// What `prune_neurons` removed and what it cost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PruneReport {
+    pub layers_before: Vec<usize>,
+    pub layers_after: Vec<usize>,
+    pub parameters_before: usize,
+    pub parameters_after: usize,
+    /// Removed `(layer, neuron)` pairs, numbered as in the original network
+    pub removed: Vec<(usize, usize)>,
+    pub mse_before: f64,
+    /// MSE right after the removal, before fine-tuning
+    pub mse_pruned: f64,
+    pub mse_after: f64,
+}
+
+impl PruneReport {
+    pub fn compression(&self) -> f64 {
+        self.parameters_before as f64 / self.parameters_after.max(1) as f64
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "✂️  Pruned {} hidden neurons: {:?} -> {:?}\n",
+            self.removed.len(),
+            self.layers_before,
+            self.layers_after
+        );
+        let _ = writeln!(
+            out,
+            "   Parameters: {} -> {} ({:.2}x smaller)",
+            self.parameters_before,
+            self.parameters_after,
+            self.compression()
+        );
+        let _ = writeln!(
+            out,
+            "   MSE: {:.6} before, {:.6} after removal, {:.6} after fine-tuning",
+            self.mse_before, self.mse_pruned, self.mse_after
+        );
+        out
+    }
+}
+
+fn mse(network: &NeuralNetwork, inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> f64 {
+    let (mut squared, mut values) = (0.0, 0);
+    for (output, target) in network.forward_batch(inputs).iter().zip(targets) {
+        for (o, t) in output.iter().zip(target) {
+            squared += (o - t).powi(2);
+            values += 1;
+        }
+    }
+    squared / values.max(1) as f64
+}
+
+/// Remove whole hidden neurons until the network is `compression` times smaller, then fine-tune it
+///
+/// A neuron's salience is its mean absolute activation over `inputs` times
+/// the norm of its outgoing weights: how much it contributes to the next
+/// layer. The least salient neurons across all hidden layers go first, each
+/// layer keeping at least `min_neurons`, and the weight matrices are rewired
+/// around them (see `NeuralNetwork::remove_neurons`). The pruned network is
+/// then trained for `fine_tune_epochs` on `inputs` and `targets` with its own
+/// learning settings, to recover what the removal cost.
+pub fn prune_neurons(
+    network: &NeuralNetwork,
+    inputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+    config: &PruneConfig,
+) -> Result<(NeuralNetwork, PruneReport), String> {
+    if config.compression < 1.0 || !config.compression.is_finite() {
+        return Err(format!(
+            "Compression {} must be at least 1",
+            config.compression
+        ));
+    }
+    if inputs.is_empty() || inputs.len() != targets.len() {
+        return Err("Pruning needs as many targets as inputs, and at least one".to_string());
+    }
+    let layers = network.get_layers().to_vec();
+    let hidden = 1..layers.len() - 1;
+
+    let mut activity: Vec<Vec<f64>> = layers.iter().map(|&size| vec![0.0; size]).collect();
+    for input in inputs {
+        for (sums, values) in activity.iter_mut().zip(network.forward_all_layers(input)) {
+            for (sum, value) in sums.iter_mut().zip(values) {
+                *sum += value.abs() / inputs.len() as f64;
+            }
+        }
+    }
+    let mut candidates: Vec<(f64, usize, usize)> = hidden
+        .clone()
+        .flat_map(|layer| {
+            let weights = network.get_layer_weights(layer);
+            let width = layers[layer + 1];
+            let activity = &activity[layer];
+            (0..layers[layer]).map(move |neuron| {
+                let outgoing = weights[neuron * width..(neuron + 1) * width]
+                    .iter()
+                    .map(|w| w * w)
+                    .sum::<f64>()
+                    .sqrt();
+                (activity[neuron] * outgoing, layer, neuron)
+            })
+        })
+        .collect();
+    candidates.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    // Each removed neuron frees its incoming weights and bias and its outgoing weights
+    let parameters_before = network.num_parameters();
+    let budget = (parameters_before as f64 / config.compression).ceil() as usize;
+    let mut sizes = layers.clone();
+    let mut parameters = parameters_before;
+    let mut removed = Vec::new();
+    for (_, layer, neuron) in candidates {
+        if parameters <= budget {
+            break;
+        }
+        if sizes[layer] <= config.min_neurons.max(1) {
+            continue;
+        }
+        sizes[layer] -= 1;
+        parameters -= sizes[layer - 1] + 1 + sizes[layer + 1];
+        removed.push((layer, neuron));
+    }
+    removed.sort_unstable();
+
+    let mut pruned = network.clone();
+    for layer in hidden {
+        let keep: Vec<usize> = (0..layers[layer])
+            .filter(|&neuron| !removed.contains(&(layer, neuron)))
+            .collect();
+        if keep.len() < layers[layer] {
+            pruned.remove_neurons(layer, &keep)?;
+        }
+    }
+    let mse_pruned = mse(&pruned, inputs, targets);
+    for _ in 0..config.fine_tune_epochs {
+        for (input, target) in inputs.iter().zip(targets) {
+            pruned.train(input, target);
+        }
+    }
+
+    let report = PruneReport {
+        layers_before: layers,
+        layers_after: pruned.get_layers().to_vec(),
+        parameters_before,
+        parameters_after: pruned.num_parameters(),
+        removed,
+        mse_before: mse(network, inputs, targets),
+        mse_pruned,
+        mse_after: mse(&pruned, inputs, targets),
+    };
+    Ok((pruned, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prune_neurons() {
+        let mut network = NeuralNetwork::with_layers(&[2, 8, 6, 1], 0.0);
+        network.set_backprop_enabled(true, 0.5);
+        // Silence one hidden neuron: it can go without changing any output
+        let mut weights = network.get_layer_weights(1);
+        weights[3 * 6..4 * 6].fill(0.0);
+        let biases = network.get_layer_biases(1).to_vec();
+        network.set_layer_parameters(1, &weights, &biases).unwrap();
+
+        let inputs: Vec<Vec<f64>> = (0..20)
+            .map(|i| vec![(i % 5) as f64 / 4.0, (i / 5) as f64 / 3.0])
+            .collect();
+        let targets: Vec<Vec<f64>> = inputs.iter().map(|x| vec![(x[0] + x[1]) / 2.0]).collect();
+
+        let config = PruneConfig {
+            compression: 1.05,
+            fine_tune_epochs: 0,
+            min_neurons: 1,
+        };
+        let (pruned, report) = prune_neurons(&network, &inputs, &targets, &config).unwrap();
+        assert_eq!(report.removed, vec![(1, 3)]);
+        assert_eq!(pruned.get_layers(), &[2, 7, 6, 1]);
+        assert_eq!(report.parameters_after, pruned.num_parameters());
+        assert!((report.mse_pruned - report.mse_before).abs() < 1e-12);
+
+        let config = PruneConfig {
+            compression: 3.0,
+            fine_tune_epochs: 5,
+            min_neurons: 2,
+        };
+        let (pruned, report) = prune_neurons(&network, &inputs, &targets, &config).unwrap();
+        assert!(report.compression() >= 3.0);
+        assert!(pruned.get_layers()[1..3].iter().all(|&size| size >= 2));
+        assert_eq!(pruned.forward_static(&inputs[0]).0.len(), 1);
+        assert!(report.to_text().contains("x smaller"));
+        assert!(prune_neurons(
+            &network,
+            &inputs,
+            &targets,
+            &PruneConfig {
+                compression: 0.5,
+                ..PruneConfig::default()
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::scaling::ValueRange;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// One network output driving one PWM channel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PwmChannel {
+    /// Index of the network output
+    pub output: usize,
+    /// Channel number on the PWM chip
+    pub pwm: u32,
+    /// Output values mapped linearly onto `[min_duty, max_duty]`
+    #[serde(default = "unit_range")]
+    pub range: ValueRange,
+    /// Lowest duty cycle ever applied, as a fraction of the period
+    #[serde(default)]
+    pub min_duty: f64,
+    /// Highest duty cycle ever applied, as a fraction of the period
+    #[serde(default = "default_max_duty")]
+    pub max_duty: f64,
+    /// Largest change of the duty cycle from one output to the next
+    #[serde(default)]
+    pub max_step: Option<f64>,
+    /// Duty cycle applied while no outputs arrive and when the sink shuts down
+    #[serde(default)]
+    pub failsafe_duty: f64,
+}
+
+fn unit_range() -> ValueRange {
+    ValueRange::UNIT
+}
+
+fn default_max_duty() -> f64 {
+    1.0
+}
+
+impl PwmChannel {
+    /// Duty cycle for `value`, clamped and limited to `max_step` away from `previous`
+    ///
+    /// A value that is not finite puts the channel straight into failsafe.
+    pub fn duty(&self, value: f64, previous: Option<f64>) -> f64 {
+        if !value.is_finite() {
+            return self.failsafe_duty;
+        }
+        let level = ((value - self.range.min) / (self.range.max - self.range.min)).clamp(0.0, 1.0);
+        let mut duty = self.min_duty + level * (self.max_duty - self.min_duty);
+        if let (Some(step), Some(previous)) = (self.max_step, previous) {
+            duty = duty.clamp(previous - step, previous + step);
+        }
+        duty.clamp(self.min_duty, self.max_duty)
+    }
+}
+
+/// Network outputs driving motors, servos or LEDs through the Linux sysfs PWM interface
+///
+/// On a Raspberry Pi the hardware PWM appears as `/sys/class/pwm/pwmchip0`
+/// once the `pwm` or `pwm-2chan` overlay is enabled. Outputs are applied as
+/// they arrive and never queued, since a stale command is worse than a
+/// dropped one for an actuator.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PwmSinkConfig {
+    #[serde(default = "default_chip")]
+    pub chip: PathBuf,
+    /// Period of every channel in nanoseconds; 20 ms suits hobby servos
+    #[serde(default = "default_period_ns")]
+    pub period_ns: u64,
+    pub channels: Vec<PwmChannel>,
+    /// Milliseconds without outputs after which every channel falls back to its failsafe duty
+    #[serde(default = "default_failsafe_ms")]
+    pub failsafe_after_ms: u64,
+}
+
+fn default_chip() -> PathBuf {
+    PathBuf::from("/sys/class/pwm/pwmchip0")
+}
+
+fn default_period_ns() -> u64 {
+    20_000_000
+}
+
+fn default_failsafe_ms() -> u64 {
+    500
+}
+
+impl PwmSinkConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.period_ns == 0 {
+            return Err("PWM period must be positive".to_string());
+        }
+        if self.channels.is_empty() {
+            return Err("A PWM sink needs at least one channel".to_string());
+        }
+        if self.failsafe_after_ms == 0 {
+            return Err("PWM failsafe timeout must be positive".to_string());
+        }
+        for (i, channel) in self.channels.iter().enumerate() {
+            if self.channels[..i]
+                .iter()
+                .any(|other| other.pwm == channel.pwm)
+            {
+                return Err(format!("PWM channel {} is driven twice", channel.pwm));
+            }
+            let range = channel.range;
+            if !(range.min.is_finite() && range.max.is_finite() && range.min < range.max) {
+                return Err(format!(
+                    "Invalid output range of PWM channel {}: [{}, {}]",
+                    channel.pwm, range.min, range.max
+                ));
+            }
+            let fraction = |duty: f64| (0.0..=1.0).contains(&duty);
+            if !(fraction(channel.min_duty)
+                && fraction(channel.max_duty)
+                && channel.min_duty <= channel.max_duty)
+            {
+                return Err(format!(
+                    "Duty limits of PWM channel {} must satisfy 0 <= {} <= {} <= 1",
+                    channel.pwm, channel.min_duty, channel.max_duty
+                ));
+            }
+            if !fraction(channel.failsafe_duty) {
+                return Err(format!(
+                    "Failsafe duty {} of PWM channel {} is not within [0, 1]",
+                    channel.failsafe_duty, channel.pwm
+                ));
+            }
+            if channel
+                .max_step
+                .is_some_and(|step| step.is_nan() || step <= 0.0)
+            {
+                return Err(format!(
+                    "Max step of PWM channel {} must be positive",
+                    channel.pwm
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn failsafe_after(&self) -> Duration {
+        Duration::from_millis(self.failsafe_after_ms)
+    }
+}
+
+fn write(path: &Path, value: impl ToString) -> Result<(), String> {
+    fs::write(path, value.to_string())
+        .map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+/// Exported PWM channels of a running sink
+#[derive(Debug)]
+pub struct PwmOutputs {
+    config: PwmSinkConfig,
+    duties: Vec<Option<f64>>,
+}
+
+impl PwmOutputs {
+    /// Export and enable every configured channel at its failsafe duty
+    pub fn open(config: PwmSinkConfig) -> Result<Self, String> {
+        config.validate()?;
+        for channel in &config.channels {
+            let dir = config.chip.join(format!("pwm{}", channel.pwm));
+            if !dir.exists() {
+                write(&config.chip.join("export"), channel.pwm)?;
+            }
+            // A period shorter than the current duty cycle is rejected, so clear the duty first
+            write(&dir.join("duty_cycle"), 0)?;
+            write(&dir.join("period"), config.period_ns)?;
+        }
+        let mut outputs = Self {
+            duties: vec![None; config.channels.len()],
+            config,
+        };
+        outputs.failsafe()?;
+        for channel in &outputs.config.channels {
+            write(&outputs.channel_dir(channel).join("enable"), 1)?;
+        }
+        Ok(outputs)
+    }
+
+    pub fn config(&self) -> &PwmSinkConfig {
+        &self.config
+    }
+
+    fn channel_dir(&self, channel: &PwmChannel) -> PathBuf {
+        self.config.chip.join(format!("pwm{}", channel.pwm))
+    }
+
+    fn set_duty(&mut self, index: usize, duty: f64) -> Result<(), String> {
+        let channel = self.config.channels[index];
+        let ns = (duty * self.config.period_ns as f64).round() as u64;
+        write(&self.channel_dir(&channel).join("duty_cycle"), ns)?;
+        self.duties[index] = Some(duty);
+        Ok(())
+    }
+
+    /// Drive every channel from its network output; channels whose output is missing keep their duty
+    pub fn apply(&mut self, outputs: &[f64]) -> Result<(), String> {
+        for index in 0..self.config.channels.len() {
+            let channel = self.config.channels[index];
+            if let Some(&value) = outputs.get(channel.output) {
+                self.set_duty(index, channel.duty(value, self.duties[index]))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Put every channel at its failsafe duty
+    pub fn failsafe(&mut self) -> Result<(), String> {
+        for index in 0..self.config.channels.len() {
+            let duty = self.config.channels[index].failsafe_duty;
+            self.set_duty(index, duty)?;
+        }
+        Ok(())
+    }
+
+    /// Current duty cycle of every channel, as fractions of the period
+    pub fn duties(&self) -> Vec<f64> {
+        self.duties.iter().map(|duty| duty.unwrap_or(0.0)).collect()
+    }
+}
+
+impl Drop for PwmOutputs {
+    fn drop(&mut self) {
+        if let Err(e) = self.failsafe() {
+            println!("❌ Failed to put PWM outputs in failsafe: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pwm_outputs() {
+        // A fake chip with channel 0 already exported
+        let chip = std::env::temp_dir().join(format!("benny_pwm_{}", std::process::id()));
+        fs::create_dir_all(chip.join("pwm0")).unwrap();
+        fs::create_dir_all(chip.join("pwm1")).unwrap();
+        let config: PwmSinkConfig = serde_json::from_value(serde_json::json!({
+            "chip": chip,
+            "period_ns": 1000,
+            "channels": [
+                {"output": 1, "pwm": 0, "range": {"min": -1, "max": 1}, "min_duty": 0.05, "max_duty": 0.1, "failsafe_duty": 0.075},
+                {"output": 0, "pwm": 1, "max_step": 0.25}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(config.failsafe_after_ms, 500);
+        let read = |path: &str| fs::read_to_string(chip.join(path)).unwrap();
+
+        let mut outputs = PwmOutputs::open(config.clone()).unwrap();
+        assert_eq!(read("pwm0/period"), "1000");
+        assert_eq!(read("pwm0/duty_cycle"), "75");
+        assert_eq!(read("pwm1/enable"), "1");
+        assert!(!chip.join("export").exists());
+
+        // Clamped to the duty limits, and ramped by at most max_step
+        outputs.apply(&[1.0, 5.0]).unwrap();
+        assert_eq!(outputs.duties(), vec![0.1, 0.25]);
+        assert_eq!(read("pwm0/duty_cycle"), "100");
+        outputs.apply(&[1.0, -1.0]).unwrap();
+        assert_eq!(outputs.duties(), vec![0.05, 0.5]);
+        outputs.apply(&[f64::NAN]).unwrap();
+        assert_eq!(outputs.duties(), vec![0.05, 0.0]);
+
+        drop(outputs);
+        assert_eq!(read("pwm0/duty_cycle"), "75");
+        assert_eq!(read("pwm1/duty_cycle"), "0");
+
+        let mut twice = config;
+        twice.channels[1].pwm = 0;
+        assert!(twice.validate().unwrap_err().contains("driven twice"));
+        fs::remove_dir_all(&chip).unwrap();
+    }
+}
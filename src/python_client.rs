@@ -0,0 +1,514 @@
+use crate::distributed_network::{capabilities, message_flags, HEADER_SIZE, PROTOCOL_VERSION};
+use crate::protocol_spec::{self, FieldKind, ALL_MESSAGE_TYPES};
+use std::fmt::Write;
+
+/// Capability bits the generated module names, in bit order
+const CAPABILITIES: [(&str, u32); 12] = [
+    ("FORWARD_PROPAGATION", capabilities::FORWARD_PROPAGATION),
+    ("BACKPROPAGATION", capabilities::BACKPROPAGATION),
+    ("HEBBIAN_LEARNING", capabilities::HEBBIAN_LEARNING),
+    ("WEIGHT_SYNC", capabilities::WEIGHT_SYNC),
+    ("CORRELATION_ANALYSIS", capabilities::CORRELATION_ANALYSIS),
+    ("MULTI_LAYER", capabilities::MULTI_LAYER),
+    ("REAL_TIME", capabilities::REAL_TIME),
+    ("COMPRESSION", capabilities::COMPRESSION),
+    ("ACKNOWLEDGED_DELIVERY", capabilities::ACKNOWLEDGED_DELIVERY),
+    ("SPIKE_EVENTS", capabilities::SPIKE_EVENTS),
+    ("REMOTE_CONFIG", capabilities::REMOTE_CONFIG),
+    ("MODEL_DEPLOYMENT", capabilities::MODEL_DEPLOYMENT),
+];
+
+/// Codec and client of the generated module; the `@NAME@` markers are filled in by `generate`
+const TEMPLATE: &str = r#""""Minimal client for the Neural Network Protocol (NNP) spoken by Benny nodes.
+
+Generated by `benny protocol client` for protocol version @VERSION@; do not edit,
+regenerate it instead. Needs nothing but the Python 3 standard library.
+
+Field values are represented as in the conformance fixtures
+(`benny protocol fixtures`): integers and floats as numbers, UUIDs as
+hyphenated strings, raw bytes as lowercase hex strings, lists as lists, and
+spikes and health components as dicts.
+
+    with NnpClient("127.0.0.1", 8080, name="rig", layers=[2]) as node:
+        node.handshake()
+        node.send_forward([0.5, 0.25])
+        print(node.heartbeat())
+        print(node.info())
+
+Check this module against the fixtures with `python3 nnp_client.py check DIR`,
+or probe a node with `python3 nnp_client.py ping HOST:PORT`.
+"""
+
+import json
+import math
+import os
+import socket
+import struct
+import sys
+import time
+import uuid
+import zlib
+
+MAGIC = b"NNP\x00"
+VERSION = @VERSION@
+HEADER_SIZE = @HEADER_SIZE@
+VERSION_MASK = 0x0F
+ACK_REQUIRED = @ACK_REQUIRED@
+
+@CAPABILITIES@
+
+MESSAGE_TYPES = {
+@MESSAGE_TYPES@
+}
+MESSAGE_NAMES = {code: name for name, code in MESSAGE_TYPES.items()}
+
+# Payload fields of every message type in wire order: (name, kind, optional)
+LAYOUTS = {
+@LAYOUTS@
+}
+
+
+class ProtocolError(Exception):
+    """A frame that does not follow the NNP layout."""
+
+
+def _string8(value):
+    data = value.encode("utf-8")
+    if len(data) > 255:
+        raise ProtocolError("string of %d bytes does not fit a u8 length" % len(data))
+    return bytes([len(data)]) + data
+
+
+def _encode_field(kind, value):
+    if kind == "u8":
+        return struct.pack(">B", value)
+    if kind == "u16":
+        return struct.pack(">H", value)
+    if kind == "u32":
+        return struct.pack(">I", value)
+    if kind == "u64":
+        return struct.pack(">Q", value)
+    if kind == "f32":
+        return struct.pack(">f", float(value))
+    if kind == "bool":
+        return b"\x01" if value else b"\x00"
+    if kind == "uuid":
+        return uuid.UUID(value).bytes
+    if kind.startswith("bytes:"):
+        data = bytes.fromhex(value)
+        if len(data) != int(kind[6:]):
+            raise ProtocolError("expected %s, got %d bytes" % (kind, len(data)))
+        return data
+    if kind == "string8":
+        return _string8(value)
+    if kind == "u16list8":
+        return bytes([len(value)]) + b"".join(struct.pack(">H", v) for v in value)
+    if kind == "stringlist8":
+        return bytes([len(value)]) + b"".join(_string8(v) for v in value)
+    if kind == "componentlist8":
+        return bytes([len(value)]) + b"".join(
+            _string8(c["name"]) + (b"\x01" if c["ready"] else b"\x00") + _string8(c["detail"])
+            for c in value
+        )
+    if kind == "f32list32":
+        return struct.pack(">I", len(value)) + b"".join(struct.pack(">f", float(v)) for v in value)
+    if kind == "spikelist32":
+        return struct.pack(">I", len(value)) + b"".join(
+            struct.pack(">IQ", s["neuron"], s["timestamp"]) for s in value
+        )
+    if kind == "bytes32":
+        data = bytes.fromhex(value)
+        return struct.pack(">I", len(data)) + data
+    raise ProtocolError("unknown field kind %s" % kind)
+
+
+def _take(data, offset, size):
+    if offset + size > len(data):
+        raise ProtocolError("truncated at byte %d" % offset)
+    return data[offset:offset + size], offset + size
+
+
+def _decode_string8(data, offset):
+    (length,), offset = struct.unpack(">B", _take(data, offset, 1)[0]), offset + 1
+    raw, offset = _take(data, offset, length)
+    return raw.decode("utf-8"), offset
+
+
+def _decode_field(kind, data, offset):
+    fixed = {"u8": ">B", "u16": ">H", "u32": ">I", "u64": ">Q", "f32": ">f"}
+    if kind in fixed:
+        raw, offset = _take(data, offset, struct.calcsize(fixed[kind]))
+        return struct.unpack(fixed[kind], raw)[0], offset
+    if kind == "bool":
+        raw, offset = _take(data, offset, 1)
+        return raw[0] != 0, offset
+    if kind == "uuid":
+        raw, offset = _take(data, offset, 16)
+        return str(uuid.UUID(bytes=raw)), offset
+    if kind.startswith("bytes:"):
+        raw, offset = _take(data, offset, int(kind[6:]))
+        return raw.hex(), offset
+    if kind == "string8":
+        return _decode_string8(data, offset)
+    if kind in ("u16list8", "stringlist8", "componentlist8"):
+        raw, offset = _take(data, offset, 1)
+        values = []
+        for _ in range(raw[0]):
+            if kind == "u16list8":
+                value, offset = _decode_field("u16", data, offset)
+            elif kind == "stringlist8":
+                value, offset = _decode_string8(data, offset)
+            else:
+                name, offset = _decode_string8(data, offset)
+                ready, offset = _decode_field("bool", data, offset)
+                detail, offset = _decode_string8(data, offset)
+                value = {"name": name, "ready": ready, "detail": detail}
+            values.append(value)
+        return values, offset
+    if kind in ("f32list32", "spikelist32", "bytes32"):
+        count, offset = _decode_field("u32", data, offset)
+        if kind == "bytes32":
+            raw, offset = _take(data, offset, count)
+            return raw.hex(), offset
+        values = []
+        for _ in range(count):
+            if kind == "f32list32":
+                value, offset = _decode_field("f32", data, offset)
+            else:
+                raw, offset = _take(data, offset, 12)
+                neuron, timestamp = struct.unpack(">IQ", raw)
+                value = {"neuron": neuron, "timestamp": timestamp}
+            values.append(value)
+        return values, offset
+    raise ProtocolError("unknown field kind %s" % kind)
+
+
+def encode_payload(msg_type, fields):
+    """Payload bytes of `msg_type` (a name of MESSAGE_TYPES) with `fields`.
+
+    Optional trailing fields may be left out; every field after the first
+    one left out is then left out too.
+    """
+    out = b""
+    for name, kind, optional in LAYOUTS[msg_type]:
+        if name not in fields:
+            if optional:
+                break
+            raise ProtocolError("%s needs the field %s" % (msg_type, name))
+        out += _encode_field(kind, fields[name])
+    return out
+
+
+def decode_payload(msg_type, data):
+    """Fields of a `msg_type` payload, without the optional fields it omits."""
+    fields, offset = {}, 0
+    for name, kind, optional in LAYOUTS[msg_type]:
+        if optional and offset == len(data):
+            break
+        fields[name], offset = _decode_field(kind, data, offset)
+    if offset != len(data):
+        raise ProtocolError("%s has %d bytes past its last field" % (msg_type, len(data) - offset))
+    return fields
+
+
+def encode_frame(msg_type, fields, sequence, flags=0, version=VERSION):
+    """A complete frame: header, then payload."""
+    payload = encode_payload(msg_type, fields)
+    header = MAGIC + struct.pack(
+        ">BBIQI",
+        version | (flags & ~VERSION_MASK & 0xFF),
+        MESSAGE_TYPES[msg_type],
+        len(payload),
+        sequence,
+        zlib.crc32(payload) & 0xFFFFFFFF,
+    )
+    return header + payload
+
+
+def decode_header(header):
+    """Header fields of a frame, as named in the fixtures."""
+    if len(header) < HEADER_SIZE or header[:4] != MAGIC:
+        raise ProtocolError("not an NNP frame")
+    version, code, length, sequence, checksum = struct.unpack(">BBIQI", header[4:HEADER_SIZE])
+    return {
+        "magic": MAGIC.hex(),
+        "version": version,
+        "msg_type": code,
+        "length": length,
+        "sequence": sequence,
+        "checksum": checksum,
+    }
+
+
+def decode_frame(frame):
+    """(message type name, header fields, payload fields) of a complete frame."""
+    header = decode_header(frame)
+    payload = frame[HEADER_SIZE:]
+    if len(payload) != header["length"]:
+        raise ProtocolError("payload of %d bytes, header says %d" % (len(payload), header["length"]))
+    if zlib.crc32(payload) & 0xFFFFFFFF != header["checksum"]:
+        raise ProtocolError("checksum mismatch")
+    msg_type = MESSAGE_NAMES.get(header["msg_type"])
+    if msg_type is None:
+        raise ProtocolError("unknown message type 0x%02X" % header["msg_type"])
+    return msg_type, header, decode_payload(msg_type, payload)
+
+
+def _recv_exact(sock, size):
+    data = b""
+    while len(data) < size:
+        chunk = sock.recv(size - len(data))
+        if not chunk:
+            raise ConnectionError("connection closed by the node")
+        data += chunk
+    return data
+
+
+class NnpClient:
+    """One NNP connection to a Benny node."""
+
+    def __init__(self, host, port, name="python", layers=(), capabilities=FORWARD_PROPAGATION,
+                 namespace=None, network_id=None, timeout=5.0):
+        self.host, self.port, self.timeout = host, port, timeout
+        self.name, self.layers, self.capabilities = name, list(layers), capabilities
+        self.namespace = namespace
+        self.network_id = network_id or str(uuid.uuid4())
+        self.sequence = 0
+        self.sock = None
+
+    def __enter__(self):
+        self.connect()
+        return self
+
+    def __exit__(self, *_):
+        self.close()
+
+    def connect(self):
+        self.sock = socket.create_connection((self.host, self.port), timeout=self.timeout)
+
+    def close(self, reason=None):
+        """Close the connection, first saying why with a Disconnect when `reason` is given."""
+        if self.sock is not None:
+            if reason is not None:
+                self.send("Disconnect", {"reason": reason})
+            self.sock.close()
+            self.sock = None
+
+    def send(self, msg_type, fields, flags=0):
+        """Send one message, returning its sequence number."""
+        self.sequence += 1
+        self.sock.sendall(encode_frame(msg_type, fields, self.sequence, flags))
+        return self.sequence
+
+    def recv(self):
+        """Next message from the node: (type name, header fields, payload fields)."""
+        header = _recv_exact(self.sock, HEADER_SIZE)
+        length = decode_header(header)["length"]
+        return decode_frame(header + _recv_exact(self.sock, length))
+
+    def _recv_type(self, msg_type):
+        while True:
+            received, _, fields = self.recv()
+            if received == msg_type:
+                return fields
+            if received == "Error":
+                raise ProtocolError("node error %d: %s" % (fields["code"], fields["message"]))
+
+    def handshake(self):
+        """Introduce this client; returns the node's HandshakeAck fields."""
+        fields = {
+            "network_id": self.network_id,
+            "name": self.name,
+            "layers": self.layers,
+            "capabilities": self.capabilities,
+        }
+        if self.namespace is not None:
+            fields["namespace"] = self.namespace
+        self.send("Handshake", fields)
+        ack = self._recv_type("HandshakeAck")
+        if not ack["accepted"]:
+            raise ProtocolError("handshake refused: %s" % ack.get("reason", ""))
+        return ack
+
+    def send_forward(self, data, layer_id=0, channel=None):
+        """Send activations; `channel` tags them with an index into the handshake channels."""
+        fields = {"layer_id": layer_id, "data": list(data)}
+        if channel is not None:
+            fields["timestamp"] = 0
+            fields["channel"] = channel
+        return self.send("ForwardData", fields)
+
+    def heartbeat(self):
+        """Send an acknowledged heartbeat; returns the round trip time in seconds."""
+        started = time.monotonic()
+        sequence = self.send("Heartbeat", {"timestamp": int(time.time() * 1000)}, ACK_REQUIRED)
+        while self._recv_type("Ack")["sequence"] != sequence:
+            pass
+        return time.monotonic() - started
+
+    def info(self):
+        """The node's architecture and activity statistics."""
+        self.send("Info", {})
+        return self._recv_type("InfoResponse")
+
+    def ping(self):
+        """Health components of the node; works without a handshake."""
+        self.send("Ping", {})
+        return self._recv_type("Pong")["components"]
+
+
+def _fixture_value(value):
+    if value in ("NaN", "inf", "-inf"):
+        return float(value.replace("NaN", "nan"))
+    if isinstance(value, list):
+        return [_fixture_value(v) for v in value]
+    return value
+
+
+def _same(a, b):
+    if isinstance(a, float) and isinstance(b, float) and math.isnan(a) and math.isnan(b):
+        return True
+    if isinstance(a, list) and isinstance(b, list):
+        return len(a) == len(b) and all(_same(x, y) for x, y in zip(a, b))
+    return a == b
+
+
+def check_fixtures(directory):
+    """Decode and re-encode every fixture of `directory`, returning the failures."""
+    failures = []
+    names = sorted(f[:-5] for f in os.listdir(directory) if f.endswith(".json"))
+    for name in names:
+        with open(os.path.join(directory, name + ".json")) as f:
+            expected = json.load(f)
+        with open(os.path.join(directory, name + ".bin"), "rb") as f:
+            frame = f.read()
+        payload = {k: _fixture_value(v) for k, v in expected["payload"].items()}
+        try:
+            msg_type, header, fields = decode_frame(frame)
+            if msg_type != expected["type"] or header != expected["header"]:
+                failures.append("%s: header %s instead of %s" % (name, header, expected["header"]))
+            if fields.keys() != payload.keys() or not all(_same(fields[k], payload[k]) for k in payload):
+                failures.append("%s: payload %s instead of %s" % (name, fields, payload))
+            encoded = encode_frame(msg_type, payload, header["sequence"],
+                                   header["version"] & ~VERSION_MASK, header["version"] & VERSION_MASK)
+            if encoded != frame:
+                failures.append("%s: re-encoding gives different bytes" % name)
+        except (ProtocolError, KeyError, struct.error) as e:
+            failures.append("%s: %s" % (name, e))
+    return names, failures
+
+
+def main(argv):
+    if len(argv) == 3 and argv[1] == "check":
+        names, failures = check_fixtures(argv[2])
+        for failure in failures:
+            print("FAIL", failure)
+        print("%d of %d fixtures pass" % (len(names) - len(failures), len(names)))
+        return 1 if failures or not names else 0
+    if len(argv) == 3 and argv[1] == "ping":
+        host, _, port = argv[2].rpartition(":")
+        with NnpClient(host, int(port)) as node:
+            components = node.ping()
+        for component in components:
+            print("%s %s: %s" % ("ok  " if component["ready"] else "FAIL", component["name"],
+                                 component["detail"]))
+        return 0 if all(c["ready"] for c in components) else 1
+    print("usage: %s check FIXTURE_DIR | ping HOST:PORT" % argv[0])
+    return 2
+
+
+if __name__ == "__main__":
+    sys.exit(main(sys.argv))
+"#;
+
+/// Name of a field kind in the generated `LAYOUTS` table
+fn kind_name(kind: FieldKind) -> String {
+    match kind {
+        FieldKind::U8 => "u8".to_string(),
+        FieldKind::U16 => "u16".to_string(),
+        FieldKind::U32 => "u32".to_string(),
+        FieldKind::U64 => "u64".to_string(),
+        FieldKind::F32 => "f32".to_string(),
+        FieldKind::Bool => "bool".to_string(),
+        FieldKind::Uuid => "uuid".to_string(),
+        FieldKind::Bytes(len) => format!("bytes:{}", len),
+        FieldKind::String8 => "string8".to_string(),
+        FieldKind::U16List8 => "u16list8".to_string(),
+        FieldKind::StringList8 => "stringlist8".to_string(),
+        FieldKind::ComponentList8 => "componentlist8".to_string(),
+        FieldKind::F32List32 => "f32list32".to_string(),
+        FieldKind::SpikeList32 => "spikelist32".to_string(),
+        FieldKind::Bytes32 => "bytes32".to_string(),
+    }
+}
+
+/// Source of a Python 3 module speaking NNP, with the layouts of `benny protocol spec`
+///
+/// The codec is table-driven, so every message type can be encoded and
+/// decoded; the client wraps the handshake, forward data, heartbeats, info
+/// requests and pings.
+pub fn generate() -> String {
+    let mut capabilities = String::new();
+    for (name, bit) in CAPABILITIES {
+        let _ = writeln!(capabilities, "{} = 0x{:03X}", name, bit);
+    }
+    let mut message_types = String::new();
+    for msg_type in ALL_MESSAGE_TYPES {
+        let _ = writeln!(
+            message_types,
+            "    \"{:?}\": 0x{:02X},",
+            msg_type, msg_type as u8
+        );
+    }
+    let mut layouts = String::new();
+    for msg_type in ALL_MESSAGE_TYPES {
+        let _ = write!(layouts, "    \"{:?}\": [", msg_type);
+        for spec in protocol_spec::payload_fields(msg_type) {
+            let _ = write!(
+                layouts,
+                "\n        (\"{}\", \"{}\", {}),",
+                spec.name,
+                kind_name(spec.kind),
+                if spec.optional { "True" } else { "False" }
+            );
+        }
+        if !layouts.ends_with('[') {
+            layouts.push_str("\n    ");
+        }
+        layouts.push_str("],\n");
+    }
+    TEMPLATE
+        .replace("@VERSION@", &PROTOCOL_VERSION.to_string())
+        .replace("@HEADER_SIZE@", &HEADER_SIZE.to_string())
+        .replace(
+            "@ACK_REQUIRED@",
+            &format!("0x{:02X}", message_flags::ACK_REQUIRED),
+        )
+        .replace("@CAPABILITIES@", capabilities.trim_end())
+        .replace("@MESSAGE_TYPES@", message_types.trim_end())
+        .replace("@LAYOUTS@", layouts.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_client_is_shipped() {
+        let source = generate();
+        assert!(!source.contains('@'), "unfilled marker in the template");
+        assert!(source.contains("VERSION = 2\n"));
+        assert!(source.contains("    \"ForwardData\": 0x10,\n"));
+        assert!(source.contains("        (\"channel\", \"u8\", True),\n"));
+        assert!(source.contains("    \"Ping\": [],\n"));
+        assert!(source.contains("MODEL_DEPLOYMENT = 0x800\n"));
+
+        // The checked-in client is the one this build generates
+        let shipped =
+            std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("clients/python/nnp_client.py");
+        assert!(
+            std::fs::read_to_string(shipped).unwrap() == source,
+            "clients/python/nnp_client.py is stale; run `benny protocol client -o clients/python/nnp_client.py`"
+        );
+    }
+}
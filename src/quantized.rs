@@ -0,0 +1,489 @@
+//! Int8 and half-precision inference kernels for exported models
+//!
+//! `QuantizedModel::quantize` shrinks an `InferenceModel` to one byte (int8)
+//! or two bytes (f16) per weight. Int8 layers keep one scale per layer and
+//! run their dot products in integer arithmetic on inputs quantized on the
+//! fly; f16 layers widen each weight to `f32` as it is read, trading a little
+//! precision for half the memory traffic. Biases stay `f32` in both. Use
+//! `AccuracyReport` to measure what the smaller model costs in accuracy.
+
+use crate::activation::Activation;
+use crate::inference::{
+    activate, activation_code, activation_from_code, InferenceLayer, InferenceModel, Reader,
+};
+use alloc::format;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// First bytes of a serialized `QuantizedModel`
+pub const QUANTIZED_MAGIC: [u8; 4] = *b"BNYQ";
+
+/// Serialization format version
+pub const QUANTIZED_VERSION: u8 = 1;
+
+/// Storage format of quantized weights
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Precision {
+    /// Signed bytes with one symmetric scale per layer
+    Int8,
+    /// IEEE 754 half precision
+    Half,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Weights {
+    Int8 { values: Vec<i8>, scale: f32 }, // weight = value * scale
+    Half(Vec<u16>),
+}
+
+/// One dense layer with quantized weights, laid out like `InferenceLayer`
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedLayer {
+    inputs: usize,
+    activation: Activation,
+    weights: Weights,
+    biases: Vec<f32>,
+}
+
+impl QuantizedLayer {
+    pub fn quantize(layer: &InferenceLayer, precision: Precision) -> Self {
+        let original = (0..layer.outputs()).flat_map(|to| layer.row(to).iter().copied());
+        let weights = match precision {
+            Precision::Int8 => {
+                let (values, scale) = quantize_i8(&original.collect::<Vec<_>>());
+                Weights::Int8 { values, scale }
+            }
+            Precision::Half => Weights::Half(original.map(f32_to_f16).collect()),
+        };
+        Self {
+            inputs: layer.inputs(),
+            activation: layer.activation(),
+            weights,
+            biases: layer.biases().to_vec(),
+        }
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> usize {
+        self.biases.len()
+    }
+
+    pub fn activation(&self) -> Activation {
+        self.activation
+    }
+
+    pub fn precision(&self) -> Precision {
+        match self.weights {
+            Weights::Int8 { .. } => Precision::Int8,
+            Weights::Half(_) => Precision::Half,
+        }
+    }
+
+    /// Weight of input `from` into output `to`, as the kernels see it
+    pub fn weight(&self, from: usize, to: usize) -> f32 {
+        let index = to * self.inputs + from;
+        match &self.weights {
+            Weights::Int8 { values, scale } => values[index] as f32 * scale,
+            Weights::Half(values) => f16_to_f32(values[index]),
+        }
+    }
+
+    /// Write the layer's outputs for `inputs` into `outputs`
+    pub fn forward_into(&self, inputs: &[f32], outputs: &mut [f32]) {
+        self.forward_with(inputs, outputs, &mut Vec::new());
+    }
+
+    /// `forward_into` reusing `scratch` for the quantized inputs of int8 layers
+    fn forward_with(&self, inputs: &[f32], outputs: &mut [f32], scratch: &mut Vec<i8>) {
+        match &self.weights {
+            Weights::Int8 { values, scale } => {
+                scratch.clear();
+                scratch.resize(self.inputs, 0);
+                let inputs = &inputs[..inputs.len().min(self.inputs)];
+                let input_scale = quantize_i8_into(inputs, scratch);
+                let combined = scale * input_scale;
+                for (to, (output, &bias)) in outputs.iter_mut().zip(&self.biases).enumerate() {
+                    let row = &values[to * self.inputs..(to + 1) * self.inputs];
+                    let dot: i32 = row
+                        .iter()
+                        .zip(scratch.iter())
+                        .map(|(&w, &x)| w as i32 * x as i32)
+                        .sum();
+                    *output = activate(self.activation, bias + dot as f32 * combined);
+                }
+            }
+            Weights::Half(values) => {
+                for (to, (output, &bias)) in outputs.iter_mut().zip(&self.biases).enumerate() {
+                    let row = &values[to * self.inputs..(to + 1) * self.inputs];
+                    let sum = row
+                        .iter()
+                        .zip(inputs)
+                        .fold(bias, |sum, (&w, x)| sum + f16_to_f32(w) * x);
+                    *output = activate(self.activation, sum);
+                }
+            }
+        }
+    }
+}
+
+/// Feed-forward network with quantized weights
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedModel {
+    layers: Vec<QuantizedLayer>,
+}
+
+impl QuantizedModel {
+    pub fn quantize(model: &InferenceModel, precision: Precision) -> Self {
+        Self {
+            layers: model
+                .layers()
+                .iter()
+                .map(|layer| QuantizedLayer::quantize(layer, precision))
+                .collect(),
+        }
+    }
+
+    pub fn layers(&self) -> &[QuantizedLayer] {
+        &self.layers
+    }
+
+    pub fn input_size(&self) -> usize {
+        self.layers[0].inputs()
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.layers[self.layers.len() - 1].outputs()
+    }
+
+    /// Output activations for `inputs`; missing inputs read as 0 and extra ones are ignored
+    pub fn forward(&self, inputs: &[f32]) -> Vec<f32> {
+        let mut current: Vec<f32> = inputs.to_vec();
+        current.resize(self.input_size(), 0.0);
+        let mut next = Vec::new();
+        let mut scratch = Vec::new();
+        for layer in &self.layers {
+            next.clear();
+            next.resize(layer.outputs(), 0.0);
+            layer.forward_with(&current, &mut next, &mut scratch);
+            core::mem::swap(&mut current, &mut next);
+        }
+        current
+    }
+
+    /// Little-endian encoding in the style of `InferenceModel::to_bytes`
+    ///
+    /// Layout: magic, version (u8), layer count (u32), then per layer its
+    /// input and output counts (u32), activation (u8), precision (u8), for
+    /// int8 the weight scale (f32), then weights (i8 or f16) and biases (f32).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&QUANTIZED_MAGIC);
+        bytes.push(QUANTIZED_VERSION);
+        bytes.extend_from_slice(&(self.layers.len() as u32).to_le_bytes());
+        for layer in &self.layers {
+            bytes.extend_from_slice(&(layer.inputs() as u32).to_le_bytes());
+            bytes.extend_from_slice(&(layer.outputs() as u32).to_le_bytes());
+            bytes.push(activation_code(layer.activation));
+            match &layer.weights {
+                Weights::Int8 { values, scale } => {
+                    bytes.push(0);
+                    bytes.extend_from_slice(&scale.to_le_bytes());
+                    bytes.extend(values.iter().map(|&value| value as u8));
+                }
+                Weights::Half(values) => {
+                    bytes.push(1);
+                    for value in values {
+                        bytes.extend_from_slice(&value.to_le_bytes());
+                    }
+                }
+            }
+            for bias in &layer.biases {
+                bytes.extend_from_slice(&bias.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { bytes, offset: 0 };
+        if reader.take(4)? != QUANTIZED_MAGIC {
+            return Err("Not a Benny quantized model".into());
+        }
+        let version = reader.take(1)?[0];
+        if version != QUANTIZED_VERSION {
+            return Err(format!("Unsupported quantized model version {}", version));
+        }
+
+        let count = reader.u32()? as usize;
+        let mut layers = Vec::new();
+        for _ in 0..count {
+            let inputs = reader.u32()? as usize;
+            let outputs = reader.u32()? as usize;
+            let activation = activation_from_code(reader.take(1)?[0])?;
+            let weight_count = inputs.checked_mul(outputs).ok_or("Model too large")?;
+            let weights = match reader.take(1)?[0] {
+                0 => {
+                    let scale = f32::from_le_bytes(reader.take(4)?.try_into().unwrap());
+                    let values = reader.take(weight_count)?;
+                    Weights::Int8 {
+                        values: values.iter().map(|&value| value as i8).collect(),
+                        scale,
+                    }
+                }
+                1 => {
+                    let values =
+                        reader.take(weight_count.checked_mul(2).ok_or("Model too large")?)?;
+                    Weights::Half(
+                        values
+                            .chunks_exact(2)
+                            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+                            .collect(),
+                    )
+                }
+                code => return Err(format!("Unknown precision code {}", code)),
+            };
+            let biases = reader.f32s(outputs)?;
+            layers.push(QuantizedLayer {
+                inputs,
+                activation,
+                weights,
+                biases,
+            });
+        }
+        if reader.offset != bytes.len() {
+            return Err(format!(
+                "{} trailing bytes after the model",
+                bytes.len() - reader.offset
+            ));
+        }
+
+        if layers.is_empty() {
+            return Err("A model needs at least one layer".into());
+        }
+        for (index, pair) in layers.windows(2).enumerate() {
+            if pair[0].outputs() != pair[1].inputs() {
+                return Err(format!(
+                    "Layer {} has {} outputs but layer {} expects {} inputs",
+                    index,
+                    pair[0].outputs(),
+                    index + 1,
+                    pair[1].inputs()
+                ));
+            }
+        }
+        Ok(Self { layers })
+    }
+}
+
+/// How far a model's outputs stray from reference outputs over a set of samples
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AccuracyReport {
+    pub samples: usize,
+    /// Largest absolute difference of any single output
+    pub max_abs_error: f64,
+    /// Samples whose largest output is at the same position as the reference's
+    pub argmax_matches: usize,
+    total_abs_error: f64,
+    outputs: usize,
+}
+
+impl AccuracyReport {
+    /// Add the outputs of one sample
+    pub fn record(&mut self, reference: &[f64], actual: &[f32]) {
+        for (&expected, &value) in reference.iter().zip(actual) {
+            let error = value as f64 - expected;
+            let error = if error < 0.0 { -error } else { error };
+            self.max_abs_error = self.max_abs_error.max(error);
+            self.total_abs_error += error;
+            self.outputs += 1;
+        }
+        if argmax(reference.iter().copied()) == argmax(actual.iter().map(|&x| x as f64)) {
+            self.argmax_matches += 1;
+        }
+        self.samples += 1;
+    }
+
+    /// Mean absolute difference over all outputs (zero before the first sample)
+    pub fn mean_abs_error(&self) -> f64 {
+        match self.outputs {
+            0 => 0.0,
+            outputs => self.total_abs_error / outputs as f64,
+        }
+    }
+
+    /// Fraction of samples with matching argmax (1.0 before the first sample)
+    pub fn argmax_agreement(&self) -> f64 {
+        match self.samples {
+            0 => 1.0,
+            samples => self.argmax_matches as f64 / samples as f64,
+        }
+    }
+}
+
+fn argmax(values: impl Iterator<Item = f64>) -> Option<usize> {
+    values
+        .enumerate()
+        .fold(
+            None,
+            |best: Option<(usize, f64)>, (index, value)| match best {
+                Some((_, top)) if top >= value => best,
+                _ => Some((index, value)),
+            },
+        )
+        .map(|(index, _)| index)
+}
+
+/// Symmetric int8 quantization of `values` with the scale that maps the largest magnitude to 127
+fn quantize_i8(values: &[f32]) -> (Vec<i8>, f32) {
+    let mut quantized = vec![0; values.len()];
+    let scale = quantize_i8_into(values, &mut quantized);
+    (quantized, scale)
+}
+
+fn quantize_i8_into(values: &[f32], quantized: &mut [i8]) -> f32 {
+    let max = values.iter().fold(
+        0.0f32,
+        |max, &x| if x.is_nan() { max } else { max.max(x.abs()) },
+    );
+    if max == 0.0 || max.is_infinite() {
+        quantized.iter_mut().for_each(|q| *q = 0);
+        return 0.0;
+    }
+    let scale = max / 127.0;
+    for (q, &x) in quantized.iter_mut().zip(values) {
+        let scaled = x / scale;
+        // Casts saturate and map NaN to 0; adding ±0.5 first rounds to nearest
+        *q = (if scaled >= 0.0 {
+            scaled + 0.5
+        } else {
+            scaled - 0.5
+        }) as i8;
+    }
+    scale
+}
+
+/// Nearest IEEE 754 half-precision value, as bits (ties to even)
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let full = mantissa | 0x80_0000;
+    let shift = if exponent > 0 {
+        13
+    } else if exponent >= -10 {
+        // Subnormal: the implicit leading bit becomes part of the mantissa
+        (14 - exponent) as u32
+    } else {
+        return sign;
+    };
+    let half = if exponent > 0 {
+        ((exponent as u32) << 10) | (mantissa >> 13)
+    } else {
+        full >> shift
+    };
+    let remainder = full & ((1 << shift) - 1);
+    let halfway = 1 << (shift - 1);
+    // A carry out of the mantissa correctly bumps the exponent, up to infinity
+    let round = remainder > halfway || (remainder == halfway && half & 1 == 1);
+    sign | (half + round as u32) as u16
+}
+
+/// The `f32` value of IEEE 754 half-precision bits
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half & 0x8000) as u32) << 16;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match exponent {
+        0 => {
+            // Zero or subnormal: mantissa * 2^-24, exact in f32
+            let magnitude = mantissa as f32 / 16_777_216.0;
+            return f32::from_bits(sign | magnitude.to_bits());
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 127 - 15) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> InferenceModel {
+        let hidden = InferenceLayer::new(
+            3,
+            vec![0.5, -1.0, 0.25, 2.0, 0.125, -0.75],
+            vec![0.1, -0.1],
+            Activation::Tanh,
+        )
+        .unwrap();
+        let output = InferenceLayer::new(
+            2,
+            vec![1.0, -1.5, 0.3, 0.9],
+            vec![0.0, 0.2],
+            Activation::Sigmoid,
+        )
+        .unwrap();
+        InferenceModel::new(vec![hidden, output]).unwrap()
+    }
+
+    #[test]
+    fn test_f16_conversion() {
+        for value in [0.0, -0.0, 1.0, -2.5, 0.333_251_95, 65504.0, 6.103_515_6e-5] {
+            assert_eq!(f16_to_f32(f32_to_f16(value)).to_bits(), value.to_bits());
+        }
+        assert_eq!(f32_to_f16(1.0), 0x3c00);
+        assert_eq!(f32_to_f16(-2.0), 0xc000);
+        // Smallest subnormal, and ties rounding to even
+        assert_eq!(f16_to_f32(0x0001), 5.960_464_5e-8);
+        assert_eq!(f32_to_f16(5.960_464_5e-8), 0x0001);
+        assert_eq!(f32_to_f16(1.0 + 1.0 / 2048.0), 0x3c00);
+        assert_eq!(f32_to_f16(1.0 + 3.0 / 2048.0), 0x3c02);
+        assert_eq!(f32_to_f16(1e6), 0x7c00);
+        assert_eq!(f32_to_f16(1e-10), 0);
+        assert!(f16_to_f32(f32_to_f16(f32::NAN)).is_nan());
+    }
+
+    #[test]
+    fn test_quantized_models_track_reference() {
+        let model = model();
+        let samples = [[0.2, -0.7, 0.9], [1.0, 0.0, -1.0], [0.05, 0.5, 0.3]];
+        for (precision, tolerance) in [(Precision::Int8, 0.02), (Precision::Half, 1e-3)] {
+            let quantized = QuantizedModel::quantize(&model, precision);
+            assert_eq!(quantized.layers()[0].precision(), precision);
+            let mut report = AccuracyReport::default();
+            for inputs in &samples {
+                let reference: Vec<f64> = model.forward(inputs).iter().map(|&x| x as f64).collect();
+                report.record(&reference, &quantized.forward(inputs));
+            }
+            assert_eq!(report.samples, 3);
+            assert!(report.max_abs_error < tolerance, "{:?}", report);
+            assert!(report.mean_abs_error() <= report.max_abs_error);
+            assert_eq!(report.argmax_agreement(), 1.0);
+
+            let restored = QuantizedModel::from_bytes(&quantized.to_bytes()).unwrap();
+            assert_eq!(restored, quantized);
+        }
+
+        let int8 = QuantizedModel::quantize(&model, Precision::Int8);
+        // The largest weight of a layer maps to 127
+        assert!((int8.layers()[0].weight(0, 1) - 2.0).abs() < 1e-6);
+        let bytes = int8.to_bytes();
+        assert!(QuantizedModel::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(QuantizedModel::from_bytes(&model.to_bytes()).is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use crate::layer_spec::LayerSpec;
+use crate::neural_network::NeuralNetwork;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// How a `LayerSpec::Rbm` entry pre-trains the Dense layer before it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RbmTraining {
+    #[serde(default = "default_epochs")]
+    pub epochs: usize,
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+}
+
+fn default_epochs() -> usize {
+    10
+}
+
+fn default_learning_rate() -> f64 {
+    0.1
+}
+
+fn default_batch_size() -> usize {
+    10
+}
+
+impl Default for RbmTraining {
+    fn default() -> Self {
+        Self {
+            epochs: default_epochs(),
+            learning_rate: default_learning_rate(),
+            batch_size: default_batch_size(),
+        }
+    }
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn sample<R: Rng>(probabilities: &[f64], rng: &mut R) -> Vec<f64> {
+    probabilities
+        .iter()
+        .map(|&p| if rng.gen::<f64>() < p { 1.0 } else { 0.0 })
+        .collect()
+}
+
+/// Restricted Boltzmann machine with stochastic binary visible and hidden units
+///
+/// Trained with one step of contrastive divergence (CD-1). Real-valued
+/// visible data is read as the probability of each unit being on, so it
+/// should lie in `[0, 1]`. The weights and hidden biases have the layout of
+/// a sigmoid `NeuralNetwork` layer, which lets a trained machine initialize
+/// a layer that is then fine-tuned with backpropagation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rbm {
+    weights: Vec<Vec<f64>>, // weights[visible][hidden]
+    visible_bias: Vec<f64>,
+    hidden_bias: Vec<f64>,
+}
+
+impl Rbm {
+    pub fn new<R: Rng>(visible: usize, hidden: usize, rng: &mut R) -> Self {
+        Self {
+            weights: (0..visible)
+                .map(|_| (0..hidden).map(|_| rng.gen_range(-0.05..0.05)).collect())
+                .collect(),
+            visible_bias: vec![0.0; visible],
+            hidden_bias: vec![0.0; hidden],
+        }
+    }
+
+    pub fn visible(&self) -> usize {
+        self.visible_bias.len()
+    }
+
+    pub fn hidden(&self) -> usize {
+        self.hidden_bias.len()
+    }
+
+    /// `p(h_j = 1 | v)` for every hidden unit
+    pub fn hidden_probabilities(&self, visible: &[f64]) -> Vec<f64> {
+        assert_eq!(visible.len(), self.visible(), "Visible size mismatch");
+        self.hidden_bias
+            .iter()
+            .enumerate()
+            .map(|(j, &bias)| {
+                let input: f64 = visible
+                    .iter()
+                    .zip(&self.weights)
+                    .map(|(v, row)| v * row[j])
+                    .sum();
+                sigmoid(bias + input)
+            })
+            .collect()
+    }
+
+    /// `p(v_i = 1 | h)` for every visible unit
+    pub fn visible_probabilities(&self, hidden: &[f64]) -> Vec<f64> {
+        assert_eq!(hidden.len(), self.hidden(), "Hidden size mismatch");
+        self.weights
+            .iter()
+            .zip(&self.visible_bias)
+            .map(|(row, &bias)| {
+                let input: f64 = row.iter().zip(hidden).map(|(w, h)| w * h).sum();
+                sigmoid(bias + input)
+            })
+            .collect()
+    }
+
+    pub fn sample_hidden<R: Rng>(&self, visible: &[f64], rng: &mut R) -> Vec<f64> {
+        sample(&self.hidden_probabilities(visible), rng)
+    }
+
+    pub fn sample_visible<R: Rng>(&self, hidden: &[f64], rng: &mut R) -> Vec<f64> {
+        sample(&self.visible_probabilities(hidden), rng)
+    }
+
+    /// One CD-1 update from a batch; returns its mean squared reconstruction error
+    ///
+    /// The positive phase uses the data, the negative phase the reconstruction
+    /// from sampled hidden states. Probabilities rather than samples drive the
+    /// statistics, which keeps the updates low-variance.
+    pub fn contrastive_divergence<R: Rng>(
+        &mut self,
+        batch: &[Vec<f64>],
+        learning_rate: f64,
+        rng: &mut R,
+    ) -> f64 {
+        if batch.is_empty() {
+            return 0.0;
+        }
+        let mut weight_delta = vec![vec![0.0; self.hidden()]; self.visible()];
+        let mut visible_delta = vec![0.0; self.visible()];
+        let mut hidden_delta = vec![0.0; self.hidden()];
+        let mut error = 0.0;
+
+        for data in batch {
+            let positive = self.hidden_probabilities(data);
+            let reconstruction = self.visible_probabilities(&sample(&positive, rng));
+            let negative = self.hidden_probabilities(&reconstruction);
+
+            for (i, row) in weight_delta.iter_mut().enumerate() {
+                for (j, delta) in row.iter_mut().enumerate() {
+                    *delta += data[i] * positive[j] - reconstruction[i] * negative[j];
+                }
+                visible_delta[i] += data[i] - reconstruction[i];
+                error += (data[i] - reconstruction[i]).powi(2);
+            }
+            for (delta, (p, n)) in hidden_delta.iter_mut().zip(positive.iter().zip(&negative)) {
+                *delta += p - n;
+            }
+        }
+
+        let scale = learning_rate / batch.len() as f64;
+        for (row, deltas) in self.weights.iter_mut().zip(&weight_delta) {
+            for (weight, delta) in row.iter_mut().zip(deltas) {
+                *weight += scale * delta;
+            }
+        }
+        for (bias, delta) in self.visible_bias.iter_mut().zip(&visible_delta) {
+            *bias += scale * delta;
+        }
+        for (bias, delta) in self.hidden_bias.iter_mut().zip(&hidden_delta) {
+            *bias += scale * delta;
+        }
+        error / (batch.len() * self.visible()) as f64
+    }
+
+    /// Train on `data` in batches; returns the reconstruction error of the last epoch
+    pub fn train<R: Rng>(&mut self, data: &[Vec<f64>], training: &RbmTraining, rng: &mut R) -> f64 {
+        let mut error = f64::NAN;
+        for _ in 0..training.epochs {
+            let mut total = 0.0;
+            for batch in data.chunks(training.batch_size.max(1)) {
+                total += self.contrastive_divergence(batch, training.learning_rate, rng)
+                    * batch.len() as f64;
+            }
+            error = total / data.len().max(1) as f64;
+        }
+        error
+    }
+
+    /// Mean-field reconstruction: visible probabilities given the hidden probabilities of `visible`
+    pub fn reconstruct(&self, visible: &[f64]) -> Vec<f64> {
+        self.visible_probabilities(&self.hidden_probabilities(visible))
+    }
+
+    /// Draw a visible pattern by Gibbs sampling from random binary states
+    ///
+    /// Returns the visible probabilities after `steps` alternating updates.
+    pub fn generate<R: Rng>(&self, steps: usize, rng: &mut R) -> Vec<f64> {
+        let mut visible = sample(&vec![0.5; self.visible()], rng);
+        for _ in 1..steps {
+            let hidden = self.sample_hidden(&visible, rng);
+            visible = self.sample_visible(&hidden, rng);
+        }
+        self.visible_probabilities(&self.sample_hidden(&visible, rng))
+    }
+
+    /// Copy the weights and hidden biases into weight layer `layer` of `network`
+    pub fn write_to_layer(&self, network: &mut NeuralNetwork, layer: usize) -> Result<(), String> {
+        let weights: Vec<f64> = self.weights.iter().flatten().copied().collect();
+        network.set_layer_parameters(layer, &weights, &self.hidden_bias)
+    }
+}
+
+/// Greedily pre-train the layers marked by `LayerSpec::Rbm` entries, lowest first
+///
+/// Each marked layer is trained as an RBM on the activations `inputs`
+/// produce at its input, going through the layers pre-trained before it.
+/// Returns every pre-trained weight layer with its final reconstruction error.
+pub fn pretrain_layers<R: Rng>(
+    network: &mut NeuralNetwork,
+    specs: &[LayerSpec],
+    inputs: &[Vec<f64>],
+    rng: &mut R,
+) -> Result<Vec<(usize, f64)>, String> {
+    let mut errors = Vec::new();
+    for (layer, training) in LayerSpec::rbm_layers(specs) {
+        let data: Vec<Vec<f64>> = inputs
+            .iter()
+            .map(|input| network.forward_all_layers(input).swap_remove(layer))
+            .collect();
+        let layers = network.get_layers();
+        let mut rbm = Rbm::new(layers[layer], layers[layer + 1], rng);
+        let error = rbm.train(&data, &training, rng);
+        rbm.write_to_layer(network, layer)?;
+        errors.push((layer, error));
+    }
+    Ok(errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::activation::Activation;
+    use crate::neural_network::HebbianLearningMode;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn patterns() -> Vec<Vec<f64>> {
+        vec![
+            vec![1.0, 1.0, 1.0, 0.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0],
+        ]
+    }
+
+    #[test]
+    fn test_cd1_learns_patterns() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut rbm = Rbm::new(6, 4, &mut rng);
+        let data: Vec<Vec<f64>> = patterns().into_iter().cycle().take(20).collect();
+        let training = RbmTraining {
+            epochs: 500,
+            learning_rate: 0.5,
+            batch_size: 4,
+        };
+        let error = rbm.train(&data, &training, &mut rng);
+        assert!(error < 0.05, "reconstruction error {}", error);
+
+        for pattern in patterns() {
+            let reconstruction = rbm.reconstruct(&pattern);
+            for (r, p) in reconstruction.iter().zip(&pattern) {
+                assert!((r - p).abs() < 0.3);
+            }
+        }
+
+        // Generated samples settle on one of the stored patterns
+        let generated = rbm.generate(50, &mut rng);
+        let closest = patterns()
+            .iter()
+            .map(|p| {
+                p.iter()
+                    .zip(&generated)
+                    .map(|(a, b)| (a - b).abs())
+                    .sum::<f64>()
+            })
+            .fold(f64::INFINITY, f64::min);
+        assert!(closest < 1.5, "generated {:?}", generated);
+        assert!(rbm
+            .sample_hidden(&patterns()[0], &mut rng)
+            .iter()
+            .all(|&h| h == 0.0 || h == 1.0));
+    }
+
+    #[test]
+    fn test_pretrain_marked_layers() {
+        let specs = vec![
+            LayerSpec::Input { n: 6, shape: None },
+            LayerSpec::Dense {
+                n: 3,
+                activation: Activation::Sigmoid,
+            },
+            LayerSpec::Rbm(RbmTraining {
+                epochs: 300,
+                learning_rate: 0.5,
+                batch_size: 2,
+            }),
+            LayerSpec::Dense {
+                n: 1,
+                activation: Activation::Sigmoid,
+            },
+        ];
+        let mut network =
+            NeuralNetwork::from_layer_specs(&specs, 0.0, HebbianLearningMode::Classic).unwrap();
+        let output_weights = network.get_layer_weights(1);
+        let mut rng = StdRng::seed_from_u64(1);
+        let errors = pretrain_layers(&mut network, &specs, &patterns(), &mut rng).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 0);
+        assert!(errors[0].1 < 0.1, "reconstruction error {}", errors[0].1);
+        assert_eq!(network.get_layer_weights(1), output_weights);
+
+        // Pre-training applies to sigmoid Dense layers only
+        let mut tanh = specs.clone();
+        tanh[1] = LayerSpec::Dense {
+            n: 3,
+            activation: Activation::Tanh,
+        };
+        assert!(NeuralNetwork::from_layer_specs(&tanh, 0.0, HebbianLearningMode::Classic).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use std::sync::{Mutex, TryLockError};
+use std::time::{Duration, Instant};
+
+/// Time a real-time prediction may take, and how its cost is estimated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencyBudget {
+    /// Time from the request to its outputs
+    pub budget: Duration,
+    /// Weight of the newest measurement in the moving average of the cost
+    pub smoothing: f64,
+    /// Factor on the estimated cost before it is compared with the time left
+    pub headroom: f64,
+}
+
+impl LatencyBudget {
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            smoothing: 0.2,
+            headroom: 1.5,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.budget.is_zero() {
+            return Err("Latency budget must be positive".to_string());
+        }
+        if !(self.smoothing > 0.0 && self.smoothing <= 1.0) {
+            return Err(format!(
+                "Latency smoothing {} must lie within (0, 1]",
+                self.smoothing
+            ));
+        }
+        if !(self.headroom >= 1.0 && self.headroom.is_finite()) {
+            return Err(format!(
+                "Latency headroom {} must be at least 1",
+                self.headroom
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Outputs of a real-time prediction
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prediction {
+    pub outputs: Vec<f64>,
+    /// The outputs were computed for an earlier request, as this one would have missed its deadline
+    pub stale: bool,
+    /// Time since the outputs were computed; zero when fresh
+    pub age: Duration,
+}
+
+/// How real-time predictions were served
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RealtimeStats {
+    pub fresh: u64,
+    pub stale: u64,
+    /// Fresh predictions that still finished after their deadline
+    pub late: u64,
+}
+
+/// Runs predictions under a deadline, falling back to the last outputs instead of blocking
+///
+/// A request is served from the last outputs when the estimated cost of
+/// preprocessing and the forward pass exceeds the time left, or when the
+/// network is locked elsewhere, e.g. by a reload or weight sync. The very
+/// first request always runs, since there is nothing to fall back on.
+#[derive(Debug, Clone)]
+pub struct RealtimePredictor {
+    budget: LatencyBudget,
+    estimate: Option<Duration>,
+    last: Option<(Vec<f64>, Instant)>,
+    stats: RealtimeStats,
+}
+
+impl RealtimePredictor {
+    pub fn new(budget: LatencyBudget) -> Self {
+        Self {
+            budget,
+            estimate: None,
+            last: None,
+            stats: RealtimeStats::default(),
+        }
+    }
+
+    pub fn budget(&self) -> &LatencyBudget {
+        &self.budget
+    }
+
+    /// Deadline of a request made now
+    pub fn deadline(&self) -> Instant {
+        Instant::now() + self.budget.budget
+    }
+
+    /// Moving average of the time a prediction takes
+    pub fn estimate(&self) -> Option<Duration> {
+        self.estimate
+    }
+
+    pub fn stats(&self) -> RealtimeStats {
+        self.stats
+    }
+
+    /// Run `forward` on the locked network if it can finish by `deadline`
+    ///
+    /// Otherwise the last outputs are returned flagged stale, and the
+    /// estimate is lowered a little so that a single slow pass cannot keep
+    /// every later request from running.
+    pub fn predict<T, E>(
+        &mut self,
+        network: &Mutex<T>,
+        deadline: Instant,
+        forward: impl FnOnce(&mut T) -> Result<Vec<f64>, E>,
+    ) -> Result<Prediction, E> {
+        let started = Instant::now();
+        if self.last.is_some() {
+            let left = deadline.saturating_duration_since(started);
+            let needed = self.estimate.map_or(Duration::ZERO, |estimate| {
+                estimate.mul_f64(self.budget.headroom)
+            });
+            if left.is_zero() || needed > left {
+                self.estimate = self
+                    .estimate
+                    .map(|estimate| estimate.mul_f64(1.0 - self.budget.smoothing));
+                return Ok(self.stale());
+            }
+        }
+
+        let mut guard = match network.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::WouldBlock) if self.last.is_some() => return Ok(self.stale()),
+            Err(_) => network.lock().unwrap(),
+        };
+        let outputs = forward(&mut guard)?;
+        drop(guard);
+
+        let finished = Instant::now();
+        let cost = finished - started;
+        let smoothing = self.budget.smoothing;
+        self.estimate = Some(match self.estimate {
+            Some(estimate) => estimate.mul_f64(1.0 - smoothing) + cost.mul_f64(smoothing),
+            None => cost,
+        });
+        self.stats.fresh += 1;
+        if finished > deadline {
+            self.stats.late += 1;
+        }
+        self.last = Some((outputs.clone(), finished));
+        Ok(Prediction {
+            outputs,
+            stale: false,
+            age: Duration::ZERO,
+        })
+    }
+
+    fn stale(&mut self) -> Prediction {
+        self.stats.stale += 1;
+        let (outputs, at) = self.last.as_ref().expect("stale only with a last output");
+        Prediction {
+            outputs: outputs.clone(),
+            stale: true,
+            age: at.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_realtime_predictor() {
+        let network = Mutex::new(0.0);
+        let mut predictor = RealtimePredictor::new(LatencyBudget::new(Duration::from_millis(50)));
+        let slow = |value: &mut f64| {
+            std::thread::sleep(Duration::from_millis(20));
+            *value += 1.0;
+            Ok::<_, String>(vec![*value])
+        };
+
+        // Nothing to fall back on: the first request runs even when already past its deadline
+        let first = predictor.predict(&network, Instant::now(), slow).unwrap();
+        assert_eq!(first.outputs, vec![1.0]);
+        assert!(!first.stale);
+        assert_eq!(predictor.stats().late, 1);
+        let cost = predictor.estimate().unwrap();
+        assert!(cost >= Duration::from_millis(20));
+
+        // Enough time left runs the network; too little serves the last outputs
+        let fresh = predictor
+            .predict(&network, predictor.deadline(), slow)
+            .unwrap();
+        assert_eq!(fresh.outputs, vec![2.0]);
+        let estimate = predictor.estimate().unwrap();
+        let tight = Instant::now() + Duration::from_millis(25);
+        let stale = predictor.predict(&network, tight, slow).unwrap();
+        assert!(stale.stale);
+        assert_eq!(stale.outputs, vec![2.0]);
+        assert!(predictor.estimate().unwrap() < estimate);
+
+        // A network locked elsewhere is never waited for
+        let held = network.lock().unwrap();
+        let blocked = predictor
+            .predict(&network, predictor.deadline(), slow)
+            .unwrap();
+        assert!(blocked.stale);
+        drop(held);
+
+        let errors = predictor.predict(&network, predictor.deadline(), |_| Err("bad input"));
+        assert_eq!(errors, Err("bad input"));
+        assert_eq!(
+            predictor.stats(),
+            RealtimeStats {
+                fresh: 2,
+                stale: 2,
+                late: 1
+            }
+        );
+
+        let mut invalid = LatencyBudget::new(Duration::from_millis(5));
+        invalid.headroom = 0.5;
+        assert!(invalid.validate().unwrap_err().contains("at least 1"));
+    }
+}
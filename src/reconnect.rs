@@ -0,0 +1,486 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Exponential backoff policy used when a target connection is lost
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReconnectPolicy {
+    /// Reconnect automatically after a failed send
+    pub enabled: bool,
+    /// Delay before the first reconnect attempt (milliseconds)
+    pub initial_delay_ms: u64,
+    /// Upper bound for the delay between attempts (milliseconds)
+    pub max_delay_ms: u64,
+    /// Factor applied to the delay after every failed attempt
+    pub multiplier: f64,
+    /// Random jitter as a fraction of the delay (0.0 - 1.0)
+    pub jitter: f64,
+    /// Give up after this many attempts (`None` retries forever)
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_delay_ms: 250,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+            max_attempts: None,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Policy that never reconnects
+    pub fn disabled() -> Self {
+        Self {
+            enabled: false,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before the given attempt (0-based) without jitter
+    pub fn base_delay(&self, attempt: u32) -> Duration {
+        let delay = self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(attempt as i32);
+        Duration::from_millis(delay.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// Delay before the given attempt (0-based) with random jitter applied
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        let base = self.base_delay(attempt).as_millis() as f64;
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        if jitter == 0.0 {
+            return Duration::from_millis(base as u64);
+        }
+        let factor = rand::thread_rng().gen_range(1.0 - jitter..=1.0 + jitter);
+        Duration::from_millis((base * factor) as u64)
+    }
+
+    /// Whether another attempt is allowed after `attempts` failures
+    pub fn allows_attempt(&self, attempts: u32) -> bool {
+        self.enabled && self.max_attempts.is_none_or(|max| attempts < max)
+    }
+}
+
+/// Which sample to discard when a bounded buffer is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Evict the oldest queued sample to make room
+    #[default]
+    DropOldest,
+    /// Reject the incoming sample
+    DropNewest,
+}
+
+/// What to do with data produced while the target is unreachable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BufferPolicy {
+    /// Discard data while disconnected
+    Drop,
+    /// Keep up to `capacity` samples in memory
+    BoundedQueue {
+        capacity: usize,
+        #[serde(default)]
+        overflow: OverflowPolicy,
+    },
+    /// Append samples to a JSON-lines file and replay them after reconnecting
+    PersistToDisk {
+        path: String,
+        max_entries: Option<usize>,
+        #[serde(default)]
+        overflow: OverflowPolicy,
+    },
+}
+
+impl Default for BufferPolicy {
+    fn default() -> Self {
+        BufferPolicy::BoundedQueue {
+            capacity: 1024,
+            overflow: OverflowPolicy::DropOldest,
+        }
+    }
+}
+
+/// Connection state reported to status callbacks
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionStatus {
+    Connected,
+    Disconnected,
+    Reconnecting { attempt: u32, delay: Duration },
+    GaveUp { attempts: u32 },
+}
+
+/// Callback invoked whenever the connection status changes
+pub type StatusCallback = std::sync::Arc<dyn Fn(&ConnectionStatus) + Send + Sync>;
+
+/// Delivery counters for a sink queue
+#[derive(Debug, Default)]
+pub struct DeliveryMetrics {
+    delivered: AtomicU64,
+    queued: AtomicU64,
+    dropped: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+}
+
+/// Point-in-time copy of `DeliveryMetrics`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeliveryStats {
+    /// Samples successfully written to the sink
+    pub delivered: u64,
+    /// Samples accepted into the queue
+    pub queued: u64,
+    /// Samples discarded by the overflow policy
+    pub dropped: u64,
+    /// Reconnect attempts made after a failure
+    pub retries: u64,
+    /// Failed delivery attempts
+    pub failures: u64,
+    /// Samples currently waiting in the queue
+    pub pending: u64,
+}
+
+impl DeliveryMetrics {
+    pub fn record_delivered(&self) {
+        self.delivered.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a pushed sample as queued or dropped, plus any samples it evicted
+    pub fn record_queued(&self, outcome: PushOutcome) {
+        if outcome.queued {
+            self.queued.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        self.record_dropped(outcome.evicted);
+    }
+
+    pub fn record_dropped(&self, count: usize) {
+        self.dropped.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Snapshot the counters together with the current queue length
+    pub fn snapshot(&self, pending: usize) -> DeliveryStats {
+        DeliveryStats {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            queued: self.queued.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            retries: self.retries.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            pending: pending as u64,
+        }
+    }
+}
+
+/// Result of buffering one sample
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PushOutcome {
+    /// The sample was added to the buffer
+    pub queued: bool,
+    /// Older samples evicted to make room for it
+    pub evicted: usize,
+}
+
+impl PushOutcome {
+    fn queued() -> Self {
+        Self {
+            queued: true,
+            evicted: 0,
+        }
+    }
+
+    fn rejected() -> Self {
+        Self::default()
+    }
+
+    /// Samples discarded by this push, the rejected sample included
+    pub fn discarded(&self) -> usize {
+        self.evicted + usize::from(!self.queued)
+    }
+}
+
+/// Data buffered according to a `BufferPolicy` while the target is unreachable
+#[derive(Debug)]
+pub struct PendingBuffer {
+    policy: BufferPolicy,
+    queue: VecDeque<Vec<f64>>,
+    dropped: u64,
+}
+
+impl PendingBuffer {
+    pub fn new(policy: BufferPolicy) -> Self {
+        Self {
+            policy,
+            queue: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Buffer a sample, reporting whether it was kept and what it evicted
+    pub fn push(&mut self, data: Vec<f64>) -> std::io::Result<PushOutcome> {
+        match &self.policy {
+            BufferPolicy::Drop => {
+                self.dropped += 1;
+                Ok(PushOutcome::rejected())
+            }
+            BufferPolicy::BoundedQueue { capacity, overflow } => {
+                let mut outcome = PushOutcome::queued();
+                if self.queue.len() >= *capacity {
+                    self.dropped += 1;
+                    if *overflow == OverflowPolicy::DropNewest || *capacity == 0 {
+                        return Ok(PushOutcome::rejected());
+                    }
+                    self.queue.pop_front();
+                    outcome.evicted = 1;
+                }
+                self.queue.push_back(data);
+                Ok(outcome)
+            }
+            BufferPolicy::PersistToDisk {
+                path,
+                max_entries,
+                overflow,
+            } => {
+                let path = PathBuf::from(path);
+                let mut outcome = PushOutcome::queued();
+                if let Some(max) = *max_entries {
+                    let count = Self::count_lines(&path)?;
+                    if count >= max {
+                        if *overflow == OverflowPolicy::DropNewest || max == 0 {
+                            self.dropped += 1;
+                            return Ok(PushOutcome::rejected());
+                        }
+                        outcome.evicted = count + 1 - max;
+                        self.dropped += outcome.evicted as u64;
+                        Self::drop_first_lines(&path, outcome.evicted)?;
+                    }
+                }
+                let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+                writeln!(file, "{}", serde_json::to_string(&data)?)?;
+                Ok(outcome)
+            }
+        }
+    }
+
+    /// Take all buffered samples in the order they were produced
+    pub fn drain(&mut self) -> std::io::Result<Vec<Vec<f64>>> {
+        match &self.policy {
+            BufferPolicy::PersistToDisk { path, .. } => {
+                let path = PathBuf::from(path);
+                if !path.exists() {
+                    return Ok(Vec::new());
+                }
+                let file = std::fs::File::open(&path)?;
+                let mut samples = Vec::new();
+                for line in BufReader::new(file).lines() {
+                    let line = line?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    samples.push(serde_json::from_str(&line)?);
+                }
+                std::fs::remove_file(&path)?;
+                Ok(samples)
+            }
+            _ => Ok(self.queue.drain(..).collect()),
+        }
+    }
+
+    /// Put samples that could not be delivered back at the front of the buffer
+    ///
+    /// Returns the number of samples the buffer policy discarded on the way.
+    pub fn requeue(&mut self, samples: Vec<Vec<f64>>) -> std::io::Result<usize> {
+        let pending = self.drain()?;
+        let mut discarded = 0;
+        for sample in samples.into_iter().chain(pending) {
+            discarded += self.push(sample)?.discarded();
+        }
+        Ok(discarded)
+    }
+
+    /// Number of samples currently buffered
+    pub fn len(&self) -> usize {
+        match &self.policy {
+            BufferPolicy::PersistToDisk { path, .. } => {
+                Self::count_lines(&PathBuf::from(path)).unwrap_or(0)
+            }
+            _ => self.queue.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of samples discarded because of the buffer policy
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Rewrite the buffer file without its first `count` samples
+    fn drop_first_lines(path: &PathBuf, count: usize) -> std::io::Result<()> {
+        let kept: Vec<String> = BufReader::new(std::fs::File::open(path)?)
+            .lines()
+            .skip(count)
+            .collect::<std::io::Result<_>>()?;
+        let mut file = std::fs::File::create(path)?;
+        for line in kept {
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    fn count_lines(path: &PathBuf) -> std::io::Result<usize> {
+        match std::fs::File::open(path) {
+            Ok(file) => Ok(BufReader::new(file).lines().count()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = ReconnectPolicy {
+            initial_delay_ms: 100,
+            max_delay_ms: 1000,
+            multiplier: 2.0,
+            jitter: 0.0,
+            ..ReconnectPolicy::default()
+        };
+        assert_eq!(policy.delay_for_attempt(0), Duration::from_millis(100));
+        assert_eq!(policy.delay_for_attempt(3), Duration::from_millis(800));
+        assert_eq!(policy.delay_for_attempt(10), Duration::from_millis(1000));
+
+        let jittered = ReconnectPolicy {
+            jitter: 0.5,
+            ..policy.clone()
+        };
+        let delay = jittered.delay_for_attempt(1).as_millis();
+        assert!((100..=300).contains(&delay));
+    }
+
+    #[test]
+    fn test_bounded_queue_overflow() {
+        let mut buffer = PendingBuffer::new(BufferPolicy::BoundedQueue {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+        });
+        assert_eq!(buffer.push(vec![0.0]).unwrap(), PushOutcome::queued());
+        assert_eq!(buffer.push(vec![1.0]).unwrap(), PushOutcome::queued());
+        assert_eq!(
+            buffer.push(vec![2.0]).unwrap(),
+            PushOutcome {
+                queued: true,
+                evicted: 1
+            }
+        );
+        assert_eq!(buffer.dropped(), 1);
+        assert_eq!(buffer.drain().unwrap(), vec![vec![1.0], vec![2.0]]);
+        assert!(buffer.is_empty());
+
+        let mut buffer = PendingBuffer::new(BufferPolicy::BoundedQueue {
+            capacity: 2,
+            overflow: OverflowPolicy::DropNewest,
+        });
+        for i in 0..2 {
+            buffer.push(vec![i as f64]).unwrap();
+        }
+        assert_eq!(buffer.push(vec![2.0]).unwrap(), PushOutcome::rejected());
+        assert_eq!(buffer.drain().unwrap(), vec![vec![0.0], vec![1.0]]);
+    }
+
+    /// Push `count` samples into a queue of capacity 2, then deliver what is left
+    fn delivery_stats(overflow: OverflowPolicy, count: usize) -> DeliveryStats {
+        let metrics = DeliveryMetrics::default();
+        let mut buffer = PendingBuffer::new(BufferPolicy::BoundedQueue { capacity: 2, overflow });
+        for i in 0..count {
+            metrics.record_queued(buffer.push(vec![i as f64]).unwrap());
+        }
+        for _ in buffer.drain().unwrap() {
+            metrics.record_delivered();
+        }
+        metrics.snapshot(buffer.len())
+    }
+
+    #[test]
+    fn test_delivery_stats_count_overflow() {
+        let stats = delivery_stats(OverflowPolicy::DropOldest, 5);
+        assert_eq!((stats.queued, stats.dropped, stats.delivered), (5, 3, 2));
+
+        let stats = delivery_stats(OverflowPolicy::DropNewest, 5);
+        assert_eq!((stats.queued, stats.dropped, stats.delivered), (2, 3, 2));
+        assert_eq!(stats.pending, 0);
+    }
+
+    #[test]
+    fn test_requeue_reports_evictions() {
+        let mut buffer = PendingBuffer::new(BufferPolicy::BoundedQueue {
+            capacity: 2,
+            overflow: OverflowPolicy::DropOldest,
+        });
+        buffer.push(vec![2.0]).unwrap();
+        assert_eq!(buffer.requeue(vec![vec![0.0], vec![1.0]]).unwrap(), 1);
+        assert_eq!(buffer.drain().unwrap(), vec![vec![1.0], vec![2.0]]);
+    }
+
+    #[test]
+    fn test_disk_buffer_roundtrip() {
+        let path =
+            std::env::temp_dir().join(format!("benny-buffer-{}.jsonl", uuid::Uuid::new_v4()));
+        let mut buffer = PendingBuffer::new(BufferPolicy::PersistToDisk {
+            path: path.to_string_lossy().to_string(),
+            max_entries: Some(2),
+            overflow: OverflowPolicy::DropNewest,
+        });
+        assert!(buffer.push(vec![0.1, 0.2]).unwrap().queued);
+        assert!(buffer.push(vec![0.3, 0.4]).unwrap().queued);
+        assert!(!buffer.push(vec![0.5, 0.6]).unwrap().queued);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(
+            buffer.drain().unwrap(),
+            vec![vec![0.1, 0.2], vec![0.3, 0.4]]
+        );
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_disk_buffer_drops_oldest_by_default() {
+        let path =
+            std::env::temp_dir().join(format!("benny-buffer-{}.jsonl", uuid::Uuid::new_v4()));
+        let policy: BufferPolicy = serde_json::from_value(serde_json::json!({
+            "type": "persist_to_disk",
+            "path": path.to_string_lossy(),
+            "max_entries": 2,
+        }))
+        .unwrap();
+        let mut buffer = PendingBuffer::new(policy);
+        for i in 0..3 {
+            buffer.push(vec![i as f64]).unwrap();
+        }
+        assert_eq!(buffer.dropped(), 1);
+        assert_eq!(buffer.drain().unwrap(), vec![vec![1.0], vec![2.0]]);
+    }
+}
@@ -0,0 +1,412 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Truncated backpropagation-through-time settings of a `RecurrentNetwork`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BpttConfig {
+    /// Steps unrolled per weight update; the state carries over between
+    /// windows but gradients stop at the window start
+    #[serde(default = "default_window")]
+    pub window: usize,
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    /// Largest L2 norm of a window's gradient; larger ones are scaled down
+    /// (no clipping when `None`)
+    #[serde(default = "default_clip_norm")]
+    pub clip_norm: Option<f64>,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_window() -> usize {
+    16
+}
+
+fn default_learning_rate() -> f64 {
+    0.05
+}
+
+fn default_clip_norm() -> Option<f64> {
+    Some(5.0)
+}
+
+impl Default for BpttConfig {
+    fn default() -> Self {
+        Self {
+            window: default_window(),
+            learning_rate: default_learning_rate(),
+            clip_norm: default_clip_norm(),
+            seed: 0,
+        }
+    }
+}
+
+impl BpttConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window == 0 {
+            return Err("BPTT window must span at least one step".to_string());
+        }
+        if self.learning_rate <= 0.0 || self.learning_rate.is_nan() {
+            return Err(format!(
+                "Learning rate {} must be positive",
+                self.learning_rate
+            ));
+        }
+        if self.clip_norm.is_some_and(|norm| norm <= 0.0 || norm.is_nan()) {
+            return Err("Gradient clip norm must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Elman network: a tanh hidden layer fed back to itself, with sigmoid outputs
+///
+/// The hidden state carries over between calls, so inputs are expected in
+/// time order. Training unrolls the network over windows of
+/// `BpttConfig::window` steps and backpropagates through each window
+/// (truncated BPTT) on the cross-entropy of the outputs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecurrentNetwork {
+    config: BpttConfig,
+    input_weights: Vec<Vec<f64>>,     // input_weights[hidden][0] is the bias
+    recurrent_weights: Vec<Vec<f64>>, // recurrent_weights[to][from]
+    output_weights: Vec<Vec<f64>>,    // output_weights[output][0] is the bias
+    state: Vec<f64>,
+}
+
+/// Activations of one unrolled step
+struct Step {
+    input: Vec<f64>,
+    previous: Vec<f64>,
+    hidden: Vec<f64>,
+    output: Vec<f64>,
+}
+
+/// Gradients of every weight matrix, laid out like the weights
+struct Gradients {
+    input: Vec<Vec<f64>>,
+    recurrent: Vec<Vec<f64>>,
+    output: Vec<Vec<f64>>,
+}
+
+impl Gradients {
+    fn norm(&self) -> f64 {
+        [&self.input, &self.recurrent, &self.output]
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|g| g * g)
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    /// Scale the gradient down to `max_norm` if it is longer; returns the norm before clipping
+    fn clip(&mut self, max_norm: f64) -> f64 {
+        let norm = self.norm();
+        if norm > max_norm {
+            let scale = max_norm / norm;
+            for g in [&mut self.input, &mut self.recurrent, &mut self.output]
+                .into_iter()
+                .flatten()
+                .flatten()
+            {
+                *g *= scale;
+            }
+        }
+        norm
+    }
+}
+
+impl RecurrentNetwork {
+    pub fn new(
+        inputs: usize,
+        hidden: usize,
+        outputs: usize,
+        config: BpttConfig,
+    ) -> Result<Self, String> {
+        config.validate()?;
+        if hidden == 0 {
+            return Err("Recurrent network needs at least one hidden neuron".to_string());
+        }
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut matrix = |rows: usize, columns: usize, fan_in: usize| -> Vec<Vec<f64>> {
+            let limit = 1.0 / (fan_in as f64).sqrt();
+            (0..rows)
+                .map(|_| {
+                    (0..columns)
+                        .map(|_| rng.gen_range(-limit..=limit))
+                        .collect()
+                })
+                .collect()
+        };
+        Ok(Self {
+            input_weights: matrix(hidden, 1 + inputs, 1 + inputs + hidden),
+            recurrent_weights: matrix(hidden, hidden, 1 + inputs + hidden),
+            output_weights: matrix(outputs, 1 + hidden, 1 + hidden),
+            state: vec![0.0; hidden],
+            config,
+        })
+    }
+
+    pub fn config(&self) -> &BpttConfig {
+        &self.config
+    }
+
+    pub fn num_inputs(&self) -> usize {
+        self.input_weights.first().map_or(0, |row| row.len() - 1)
+    }
+
+    pub fn num_outputs(&self) -> usize {
+        self.output_weights.len()
+    }
+
+    pub fn hidden_size(&self) -> usize {
+        self.state.len()
+    }
+
+    pub fn state(&self) -> &[f64] {
+        &self.state
+    }
+
+    /// Forget the input history, e.g. between independent sequences
+    pub fn reset_state(&mut self) {
+        self.state.iter_mut().for_each(|h| *h = 0.0);
+    }
+
+    /// Advance the hidden state by one input and read out the outputs
+    pub fn step(&mut self, input: &[f64]) -> Vec<f64> {
+        assert_eq!(input.len(), self.num_inputs(), "Input size mismatch");
+        let step = self.unroll(input, self.state.clone());
+        self.state = step.hidden;
+        step.output
+    }
+
+    /// Outputs for a whole sequence, continuing from the current state
+    pub fn predict_sequence(&mut self, inputs: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        inputs.iter().map(|input| self.step(input)).collect()
+    }
+
+    /// One pass of truncated BPTT over a sequence; returns the mean squared error
+    ///
+    /// The sequence continues from the current state; call `reset_state`
+    /// first when it is independent of what came before. Weights are updated
+    /// after every window.
+    pub fn train_sequence(
+        &mut self,
+        inputs: &[Vec<f64>],
+        targets: &[Vec<f64>],
+    ) -> Result<f64, String> {
+        if inputs.len() != targets.len() {
+            return Err(format!(
+                "Got {} inputs but {} targets",
+                inputs.len(),
+                targets.len()
+            ));
+        }
+        if let Some(input) = inputs.iter().find(|input| input.len() != self.num_inputs()) {
+            return Err(format!(
+                "Expected {} inputs per step, got {}",
+                self.num_inputs(),
+                input.len()
+            ));
+        }
+        if let Some(target) = targets
+            .iter()
+            .find(|target| target.len() != self.num_outputs())
+        {
+            return Err(format!(
+                "Expected {} targets per step, got {}",
+                self.num_outputs(),
+                target.len()
+            ));
+        }
+        if inputs.is_empty() {
+            return Ok(0.0);
+        }
+
+        let mut squared_error = 0.0;
+        for (inputs, targets) in inputs
+            .chunks(self.config.window)
+            .zip(targets.chunks(self.config.window))
+        {
+            // The state entering the window is a constant: gradients stop here
+            let mut steps = Vec::with_capacity(inputs.len());
+            for input in inputs {
+                let step = self.unroll(input, self.state.clone());
+                self.state = step.hidden.clone();
+                steps.push(step);
+            }
+            squared_error += steps
+                .iter()
+                .zip(targets)
+                .flat_map(|(step, target)| step.output.iter().zip(target))
+                .map(|(output, target)| (output - target).powi(2))
+                .sum::<f64>();
+
+            let mut gradients = self.backpropagate(&steps, targets);
+            if let Some(max_norm) = self.config.clip_norm {
+                gradients.clip(max_norm);
+            }
+            self.apply(&gradients, inputs.len());
+        }
+        Ok(squared_error / (inputs.len() * self.num_outputs()).max(1) as f64)
+    }
+
+    fn unroll(&self, input: &[f64], previous: Vec<f64>) -> Step {
+        let hidden: Vec<f64> = self
+            .input_weights
+            .iter()
+            .zip(&self.recurrent_weights)
+            .map(|(input_row, recurrent_row)| {
+                (input_row[0] + dot(&input_row[1..], input) + dot(recurrent_row, &previous)).tanh()
+            })
+            .collect();
+        let output = self
+            .output_weights
+            .iter()
+            .map(|row| sigmoid(row[0] + dot(&row[1..], &hidden)))
+            .collect();
+        Step {
+            input: input.to_vec(),
+            previous,
+            hidden,
+            output,
+        }
+    }
+
+    fn backpropagate(&self, steps: &[Step], targets: &[Vec<f64>]) -> Gradients {
+        let zeros = |matrix: &Vec<Vec<f64>>| -> Vec<Vec<f64>> {
+            matrix.iter().map(|row| vec![0.0; row.len()]).collect()
+        };
+        let mut gradients = Gradients {
+            input: zeros(&self.input_weights),
+            recurrent: zeros(&self.recurrent_weights),
+            output: zeros(&self.output_weights),
+        };
+
+        // Gradient reaching each hidden unit from the following step
+        let mut carried = vec![0.0; self.hidden_size()];
+        for (step, target) in steps.iter().zip(targets).rev() {
+            // Sigmoid outputs under cross-entropy: the error is the output delta
+            let output_deltas: Vec<f64> =
+                step.output.iter().zip(target).map(|(y, t)| y - t).collect();
+            let mut hidden_grads = carried;
+            for ((row, gradient_row), delta) in self
+                .output_weights
+                .iter()
+                .zip(&mut gradients.output)
+                .zip(&output_deltas)
+            {
+                gradient_row[0] += delta;
+                for (j, h) in step.hidden.iter().enumerate() {
+                    gradient_row[1 + j] += delta * h;
+                    hidden_grads[j] += delta * row[1 + j];
+                }
+            }
+
+            let deltas: Vec<f64> = hidden_grads
+                .iter()
+                .zip(&step.hidden)
+                .map(|(grad, h)| grad * (1.0 - h * h))
+                .collect();
+            carried = vec![0.0; self.hidden_size()];
+            for (j, delta) in deltas.iter().enumerate() {
+                let input_row = &mut gradients.input[j];
+                input_row[0] += delta;
+                for (k, x) in step.input.iter().enumerate() {
+                    input_row[1 + k] += delta * x;
+                }
+                for (k, h) in step.previous.iter().enumerate() {
+                    gradients.recurrent[j][k] += delta * h;
+                    carried[k] += delta * self.recurrent_weights[j][k];
+                }
+            }
+        }
+        gradients
+    }
+
+    fn apply(&mut self, gradients: &Gradients, steps: usize) {
+        let rate = self.config.learning_rate / steps as f64;
+        for (weights, grads) in [
+            (&mut self.input_weights, &gradients.input),
+            (&mut self.recurrent_weights, &gradients.recurrent),
+            (&mut self.output_weights, &gradients.output),
+        ] {
+            for (w, g) in weights.iter_mut().flatten().zip(grads.iter().flatten()) {
+                *w -= rate * g;
+            }
+        }
+    }
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets;
+
+    #[test]
+    fn test_bptt_learns_delayed_echo() {
+        // Each target is the input of two steps earlier: impossible without memory
+        let data = datasets::temporal_parity(400, 3);
+        let inputs = data.inputs;
+        let targets: Vec<Vec<f64>> = (0..inputs.len())
+            .map(|t| {
+                if t < 2 {
+                    vec![0.0]
+                } else {
+                    inputs[t - 2].clone()
+                }
+            })
+            .collect();
+
+        let config = BpttConfig {
+            window: 8,
+            learning_rate: 0.5,
+            ..BpttConfig::default()
+        };
+        let mut network = RecurrentNetwork::new(1, 8, 1, config).unwrap();
+        let mut error = f64::MAX;
+        for _ in 0..150 {
+            network.reset_state();
+            error = network.train_sequence(&inputs, &targets).unwrap();
+        }
+        assert!(error < 0.05, "error {} after training", error);
+
+        network.reset_state();
+        let outputs = network.predict_sequence(&inputs);
+        let correct = outputs[2..]
+            .iter()
+            .zip(&targets[2..])
+            .filter(|(output, target)| (output[0] > 0.5) == (target[0] > 0.5))
+            .count();
+        assert!(correct as f64 / (inputs.len() - 2) as f64 > 0.95);
+        assert!(network.train_sequence(&inputs, &targets[1..]).is_err());
+    }
+
+    #[test]
+    fn test_gradient_clipping() {
+        let mut gradients = Gradients {
+            input: vec![vec![3.0]],
+            recurrent: vec![vec![0.0]],
+            output: vec![vec![4.0]],
+        };
+        assert_eq!(gradients.clip(1.0), 5.0);
+        assert!((gradients.norm() - 1.0).abs() < 1e-12);
+        assert!((gradients.input[0][0] - 0.6).abs() < 1e-12);
+
+        let config = BpttConfig {
+            clip_norm: Some(0.0),
+            ..BpttConfig::default()
+        };
+        assert!(RecurrentNetwork::new(1, 4, 1, config).is_err());
+    }
+}
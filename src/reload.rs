@@ -0,0 +1,171 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::Future;
+use std::path::Path;
+
+/// What a configuration reload changed
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReloadReport {
+    /// Changes already in effect
+    pub applied: Vec<String>,
+    /// Changed settings that only take effect after a restart
+    pub restart_required: Vec<String>,
+}
+
+impl ReloadReport {
+    pub fn is_empty(&self) -> bool {
+        self.applied.is_empty() && self.restart_required.is_empty()
+    }
+
+    pub fn applied(&mut self, change: impl Into<String>) {
+        self.applied.push(change.into());
+    }
+
+    pub fn restart_required(&mut self, setting: impl Into<String>) {
+        self.restart_required.push(setting.into());
+    }
+
+    /// Print one line per change
+    pub fn print(&self) {
+        if self.is_empty() {
+            println!("🔄 Configuration reloaded: nothing changed");
+            return;
+        }
+        println!("🔄 Configuration reloaded");
+        for change in &self.applied {
+            println!("   ✅ {}", change);
+        }
+        for setting in &self.restart_required {
+            println!("   ⚠️ {} changed; restart to apply it", setting);
+        }
+    }
+}
+
+/// Entries of two lists matched up by id
+#[derive(Debug)]
+pub struct ListDiff<'a, T> {
+    pub added: Vec<&'a T>,
+    pub removed: Vec<&'a T>,
+    /// New versions of entries whose id stayed but whose settings differ
+    pub changed: Vec<&'a T>,
+}
+
+/// Compare `old` and `new` entry by entry, matching entries on `id`
+pub fn diff_by_id<'a, T, F>(old: &'a [T], new: &'a [T], id: F) -> ListDiff<'a, T>
+where
+    T: PartialEq,
+    F: Fn(&T) -> &str,
+{
+    let find = |list: &'a [T], key: &str| list.iter().find(|entry| id(entry) == key);
+    ListDiff {
+        added: new
+            .iter()
+            .filter(|entry| find(old, id(entry)).is_none())
+            .collect(),
+        removed: old
+            .iter()
+            .filter(|entry| find(new, id(entry)).is_none())
+            .collect(),
+        changed: new
+            .iter()
+            .filter(|entry| find(old, id(entry)).is_some_and(|previous| previous != *entry))
+            .collect(),
+    }
+}
+
+/// Top-level fields whose serialized values differ between `old` and `new`
+pub fn changed_fields<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    let mut changed: Vec<String> = new
+        .iter()
+        .filter(|(field, value)| old.get(*field) != Some(*value))
+        .map(|(field, _)| field.clone())
+        .chain(old.keys().filter(|field| !new.contains_key(*field)).cloned())
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// Read a configuration file: JSON when it ends in `.json`, TOML otherwise
+pub fn load_config<T: DeserializeOwned>(
+    path: &Path,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    let content = std::fs::read_to_string(path)?;
+    if path.extension().and_then(|ext| ext.to_str()) == Some("json") {
+        Ok(serde_json::from_str(&content)?)
+    } else {
+        Ok(toml::from_str(&content)?)
+    }
+}
+
+/// Run `reload` every time the process receives SIGHUP
+///
+/// SIGHUP only exists on Unix; elsewhere nothing is spawned.
+pub fn spawn_on_sighup<F, Fut>(mut reload: F)
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send,
+{
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut hangups = match signal(SignalKind::hangup()) {
+            Ok(hangups) => hangups,
+            Err(e) => {
+                println!("⚠️ Cannot listen for SIGHUP, configuration reload disabled: {}", e);
+                return;
+            }
+        };
+        println!("🔄 Send SIGHUP to reload the configuration");
+        tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                reload().await;
+            }
+        });
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = &mut reload;
+        println!("⚠️ Configuration reload needs SIGHUP, which this platform lacks");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize)]
+    struct Target {
+        id: String,
+        port: u16,
+    }
+
+    fn target(id: &str, port: u16) -> Target {
+        Target {
+            id: id.to_string(),
+            port,
+        }
+    }
+
+    #[test]
+    fn test_diff_by_id_and_changed_fields() {
+        let old = vec![target("a", 1), target("b", 2), target("c", 3)];
+        let new = vec![target("a", 1), target("c", 4), target("d", 5)];
+        let diff = diff_by_id(&old, &new, |t| &t.id);
+        assert_eq!(diff.added, vec![&new[2]]);
+        assert_eq!(diff.removed, vec![&old[1]]);
+        assert_eq!(diff.changed, vec![&new[1]]);
+
+        assert_eq!(changed_fields(&old[2], &new[1]), vec!["port".to_string()]);
+        assert!(changed_fields(&old[0], &new[0]).is_empty());
+
+        let mut report = ReloadReport::default();
+        assert!(report.is_empty());
+        report.restart_required("web_port");
+        assert!(!report.is_empty());
+    }
+}
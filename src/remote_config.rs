@@ -0,0 +1,125 @@
+use crate::distributed_network::NetworkId;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
+
+/// Hebbian learning rates a remote peer may set
+pub const HEBBIAN_RATE_RANGE: RangeInclusive<f32> = 0.0..=1.0;
+/// Weight decay rates a remote peer may set
+pub const DECAY_RATE_RANGE: RangeInclusive<f32> = 0.0..=0.5;
+
+/// Runtime settings a peer asks a node to change; `None` leaves a setting alone
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConfigUpdate {
+    pub hebbian_rate: Option<f32>,
+    pub online_learning: Option<bool>,
+    pub decay_rate: Option<f32>,
+}
+
+impl ConfigUpdate {
+    /// Whether the update changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.hebbian_rate.is_none() && self.online_learning.is_none() && self.decay_rate.is_none()
+    }
+
+    /// Reject empty updates and values outside the allowed ranges
+    pub fn validate(&self) -> Result<(), String> {
+        if self.is_empty() {
+            return Err("Config update changes nothing".to_string());
+        }
+        check_range("hebbian_rate", self.hebbian_rate, &HEBBIAN_RATE_RANGE)?;
+        check_range("decay_rate", self.decay_rate, &DECAY_RATE_RANGE)
+    }
+
+    /// Apply the update to `network`, returning the settings it replaced
+    pub fn apply(&self, network: &mut NeuralNetwork) -> ConfigUpdate {
+        let previous = ConfigUpdate {
+            hebbian_rate: self.hebbian_rate.map(|_| network.get_hebbian_rate() as f32),
+            online_learning: self.online_learning.map(|_| network.is_online_learning()),
+            decay_rate: self.decay_rate.map(|_| network.get_decay_rate() as f32),
+        };
+
+        if let Some(rate) = self.hebbian_rate {
+            network.set_hebbian_rate(rate as f64);
+        }
+        if let Some(enabled) = self.online_learning {
+            network.set_online_learning(enabled);
+        }
+        if let Some(rate) = self.decay_rate {
+            network.set_decay_rate(rate as f64);
+        }
+        previous
+    }
+}
+
+fn check_range(name: &str, value: Option<f32>, range: &RangeInclusive<f32>) -> Result<(), String> {
+    match value {
+        Some(value) if !range.contains(&value) => Err(format!(
+            "{} {} is outside {}..={}",
+            name,
+            value,
+            range.start(),
+            range.end()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// One remote configuration attempt, accepted or not
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigAuditEntry {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    pub peer_id: NetworkId,
+    /// Common name of the peer's certificate, when it is known
+    pub peer_name: Option<String>,
+    pub update: ConfigUpdate,
+    /// Settings replaced by the update (`None` when it was rejected)
+    pub previous: Option<ConfigUpdate>,
+    /// Why the update was rejected
+    pub rejection: Option<String>,
+}
+
+impl ConfigAuditEntry {
+    pub fn accepted(&self) -> bool {
+        self.rejection.is_none()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_update_bounds_and_apply() {
+        assert!(ConfigUpdate::default().validate().is_err());
+        let too_fast = ConfigUpdate {
+            hebbian_rate: Some(2.0),
+            ..Default::default()
+        };
+        assert!(too_fast.validate().unwrap_err().contains("hebbian_rate"));
+        let nan = ConfigUpdate {
+            decay_rate: Some(f32::NAN),
+            ..Default::default()
+        };
+        assert!(nan.validate().is_err());
+
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let update = ConfigUpdate {
+            hebbian_rate: Some(0.25),
+            online_learning: Some(true),
+            decay_rate: None,
+        };
+        update.validate().unwrap();
+        let old_rate = network.get_hebbian_rate() as f32;
+        let old_decay = network.get_decay_rate();
+
+        let previous = update.apply(&mut network);
+        assert_eq!(previous.hebbian_rate, Some(old_rate));
+        assert_eq!(previous.online_learning, Some(false));
+        assert_eq!(previous.decay_rate, None);
+        assert_eq!(network.get_hebbian_rate(), 0.25);
+        assert!(network.is_online_learning());
+        assert_eq!(network.get_decay_rate(), old_decay);
+    }
+}
@@ -0,0 +1,325 @@
+use crate::cli::parse_input_string;
+use crate::neural_network::NeuralNetwork;
+use rustyline::completion::{Completer, FilenameCompleter, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::fmt::Write;
+use std::path::PathBuf;
+
+/// Interactive commands with their usage and description
+pub const COMMANDS: &[(&str, &str, &str)] = &[
+    (
+        "predict",
+        "predict <input>",
+        "Run prediction (e.g., 'predict 0.5,0.8')",
+    ),
+    ("train", "train <input> <target>", "Train on single sample"),
+    ("info", "info", "Show network information"),
+    (
+        "weights",
+        "weights <layer>",
+        "Show the weights and biases feeding layer + 1",
+    ),
+    ("hebbian", "hebbian", "Show Hebbian learning statistics"),
+    ("save", "save <file>", "Save network to file"),
+    ("load", "load <file>", "Load network from file"),
+    ("help", "help", "List commands"),
+    ("quit", "quit", "Exit interactive mode"),
+];
+
+/// Commands whose argument is a file path
+const PATH_COMMANDS: &[&str] = &["save", "load"];
+
+/// Largest weight matrix printed in full by `weights`
+const MAX_PRINTED_NEURONS: usize = 16;
+
+/// Result of one interactive command
+#[derive(Debug, PartialEq)]
+pub enum Reply {
+    Output(String),
+    Quit,
+}
+
+/// Network state shared by the commands of one interactive session
+pub struct Session {
+    pub network: NeuralNetwork,
+}
+
+impl Session {
+    pub fn new(network: NeuralNetwork) -> Self {
+        Self { network }
+    }
+
+    /// Run one command line; blank lines and `#` comments produce no output
+    pub fn execute(&mut self, line: &str) -> Result<Reply, String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some(&command) = parts.first() else {
+            return Ok(Reply::Output(String::new()));
+        };
+        if command.starts_with('#') {
+            return Ok(Reply::Output(String::new()));
+        }
+
+        let output = match command {
+            "predict" => {
+                let input = argument(&parts, 1, "predict <input>")?;
+                let input_values = parse_values(input, self.network.input_size())?;
+                let (output, _) = self.network.forward(&input_values);
+                format!("📤 Input: {:?} -> Output: {:?}", input_values, output)
+            }
+            "train" => {
+                let input = argument(&parts, 1, "train <input> <target>")?;
+                let target = argument(&parts, 2, "train <input> <target>")?;
+                let input_values = parse_values(input, self.network.input_size())?;
+                let outputs = *self.network.get_layers().last().unwrap_or(&0);
+                let target_values = parse_values(target, outputs)?;
+                let error = self.network.train(&input_values, &target_values);
+                format!("📈 Training error: {:.6}", error)
+            }
+            "info" => format!(
+                "🧠 Network Information:\n   {}\n   Parameters: {}\n   Hebbian rate: {}\n   Online learning: {}",
+                self.network.info(),
+                self.network.num_parameters(),
+                self.network.get_hebbian_rate(),
+                self.network.is_online_learning()
+            ),
+            "weights" => {
+                let layer = argument(&parts, 1, "weights <layer>")?
+                    .parse::<usize>()
+                    .map_err(|e| format!("Invalid layer: {}", e))?;
+                self.describe_weights(layer)?
+            }
+            "hebbian" => self.describe_hebbian(),
+            "save" => {
+                let path = argument(&parts, 1, "save <file>")?;
+                self.network
+                    .save_to_file(path)
+                    .map_err(|e| format!("Error saving: {}", e))?;
+                format!("✅ Network saved to: {}", path)
+            }
+            "load" => {
+                let path = argument(&parts, 1, "load <file>")?;
+                self.network = NeuralNetwork::load_from_file(path)
+                    .map_err(|e| format!("Error loading: {}", e))?;
+                format!("✅ Network loaded from: {}", path)
+            }
+            "help" => help(),
+            "quit" | "exit" => return Ok(Reply::Quit),
+            _ => return Err(format!("Unknown command: {}", command)),
+        };
+        Ok(Reply::Output(output))
+    }
+
+    fn describe_weights(&self, layer: usize) -> Result<String, String> {
+        let layers = self.network.get_layers();
+        if layer + 1 >= layers.len() {
+            return Err(format!(
+                "Layer {} has no outgoing weights (expected 0..{})",
+                layer,
+                layers.len().saturating_sub(1)
+            ));
+        }
+        let (rows, cols) = (layers[layer], layers[layer + 1]);
+        let weights = self.network.get_layer_weights(layer);
+        let biases = self.network.get_layer_biases(layer);
+
+        let mut out = format!("🔍 Weights {} -> {} ({}×{})", layer, layer + 1, rows, cols);
+        if rows <= MAX_PRINTED_NEURONS && cols <= MAX_PRINTED_NEURONS {
+            for row in weights.chunks(cols) {
+                let cells: Vec<String> = row.iter().map(|w| format!("{:>8.4}", w)).collect();
+                let _ = write!(out, "\n   {}", cells.join(" "));
+            }
+            let cells: Vec<String> = biases.iter().map(|b| format!("{:>8.4}", b)).collect();
+            let _ = write!(out, "\n   biases: {}", cells.join(" "));
+        }
+
+        let min = weights.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = weights.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let mean = weights.iter().sum::<f64>() / weights.len().max(1) as f64;
+        let norm = weights.iter().map(|w| w * w).sum::<f64>().sqrt();
+        let _ = write!(
+            out,
+            "\n   min {:.4}, max {:.4}, mean {:.4}, L2 norm {:.4}",
+            min, max, mean, norm
+        );
+        Ok(out)
+    }
+
+    fn describe_hebbian(&self) -> String {
+        let network = &self.network;
+        let layers = network.get_layers();
+        let mut out = format!(
+            "🧬 Hebbian statistics:\n   Mode: {:?}\n   Rate: {}, decay: {}\n   History size: {}\n   Online learning: {}",
+            network.get_learning_mode(),
+            network.get_hebbian_rate(),
+            network.get_decay_rate(),
+            network.get_history_size(),
+            network.is_online_learning()
+        );
+
+        if network.get_activation_history(0, 0).is_empty() {
+            out.push_str("\n   No activity recorded yet");
+            return out;
+        }
+        for (layer, &size) in layers.iter().enumerate() {
+            let mean = (0..size)
+                .map(|neuron| network.get_average_activation(layer, neuron))
+                .sum::<f64>()
+                / size.max(1) as f64;
+            let _ = write!(
+                out,
+                "\n   Layer {}: mean recent activity {:.4}",
+                layer, mean
+            );
+        }
+        for layer in 0..layers.len().saturating_sub(1) {
+            let pairs = layers[layer] * layers[layer + 1];
+            let total: f64 = (0..layers[layer])
+                .flat_map(|from| (0..layers[layer + 1]).map(move |to| (from, to)))
+                .map(|(from, to)| {
+                    network
+                        .get_neuron_correlation(layer, from, layer + 1, to)
+                        .abs()
+                })
+                .sum();
+            let _ = write!(
+                out,
+                "\n   Layers {} -> {}: mean |correlation| {:.4}",
+                layer,
+                layer + 1,
+                total / pairs.max(1) as f64
+            );
+        }
+        out
+    }
+}
+
+fn argument<'a>(parts: &[&'a str], index: usize, usage: &str) -> Result<&'a str, String> {
+    parts
+        .get(index)
+        .copied()
+        .ok_or_else(|| format!("Usage: {}", usage))
+}
+
+fn parse_values(input: &str, expected: usize) -> Result<Vec<f64>, String> {
+    let values = parse_input_string(input).map_err(|e| format!("Error parsing values: {}", e))?;
+    if values.len() != expected {
+        return Err(format!(
+            "Expected {} values, got {}",
+            expected,
+            values.len()
+        ));
+    }
+    Ok(values)
+}
+
+/// Command overview printed by `help` and on startup
+pub fn help() -> String {
+    let mut out = String::from("Commands:");
+    for (_, usage, description) in COMMANDS {
+        let _ = write!(out, "\n  {:<24} - {}", usage, description);
+    }
+    out
+}
+
+/// Commands starting with `prefix`
+pub fn command_candidates(prefix: &str) -> Vec<&'static str> {
+    COMMANDS
+        .iter()
+        .map(|(name, _, _)| *name)
+        .filter(|name| name.starts_with(prefix))
+        .collect()
+}
+
+/// File the line editor keeps its history in
+pub fn history_path() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".benny_history"))
+}
+
+/// Line editor helper completing command names and file paths
+#[derive(Default)]
+pub struct ReplHelper {
+    files: FilenameCompleter,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let before = &line[..pos];
+        let start = before.rfind(char::is_whitespace).map_or(0, |i| i + 1);
+        if start == 0 {
+            let candidates = command_candidates(before)
+                .into_iter()
+                .map(|name| Pair {
+                    display: name.to_string(),
+                    replacement: format!("{} ", name),
+                })
+                .collect();
+            return Ok((0, candidates));
+        }
+
+        let command = before.split_whitespace().next().unwrap_or("");
+        if PATH_COMMANDS.contains(&command) {
+            return self.files.complete(line, pos, ctx);
+        }
+        Ok((pos, Vec::new()))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_commands() {
+        assert_eq!(command_candidates("h"), vec!["hebbian", "help"]);
+        assert_eq!(command_candidates("we"), vec!["weights"]);
+
+        let mut session = Session::new(NeuralNetwork::with_layers(&[2, 3, 1], 0.1));
+        assert_eq!(
+            session.execute("  # comment"),
+            Ok(Reply::Output(String::new()))
+        );
+        assert!(session.execute("predict").unwrap_err().starts_with("Usage"));
+        assert!(session
+            .execute("predict 0.5")
+            .unwrap_err()
+            .contains("Expected 2"));
+        assert!(session.execute("frobnicate").is_err());
+
+        let before = session.network.get_layer_weights(1);
+        session.execute("train 0.5,0.8 1.0").unwrap();
+        assert_ne!(session.network.get_layer_weights(1), before);
+
+        let Reply::Output(weights) = session.execute("weights 1").unwrap() else {
+            panic!("weights quit the session");
+        };
+        assert!(weights.contains("(3×1)"));
+        assert!(weights.contains("L2 norm"));
+        assert!(session.execute("weights 2").is_err());
+
+        let Reply::Output(stats) = session.execute("hebbian").unwrap() else {
+            panic!("hebbian quit the session");
+        };
+        assert!(stats.contains("Mode:"));
+        assert_eq!(session.execute("quit"), Ok(Reply::Quit));
+    }
+}
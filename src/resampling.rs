@@ -0,0 +1,171 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// How a value is produced for an output tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ResampleMode {
+    /// Linear interpolation between the samples around the tick
+    #[default]
+    Interpolate,
+    /// The newest sample at or before the tick
+    Latest,
+}
+
+/// Serve buffered outputs at a fixed rate instead of as they arrive
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Resampling {
+    /// Values produced per second
+    pub rate_hz: f64,
+    #[serde(default)]
+    pub mode: ResampleMode,
+    /// How far each tick lags behind the current time, in milliseconds
+    ///
+    /// Interpolation needs a sample on both sides of a tick, so this should
+    /// cover the usual gap between messages plus their jitter.
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+    /// Samples kept at most
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+}
+
+fn default_delay_ms() -> u64 {
+    50
+}
+
+fn default_capacity() -> usize {
+    256
+}
+
+impl Resampling {
+    pub fn new(rate_hz: f64, mode: ResampleMode) -> Self {
+        Self {
+            rate_hz,
+            mode,
+            delay_ms: default_delay_ms(),
+            capacity: default_capacity(),
+        }
+    }
+
+    /// Time between two output ticks
+    pub fn period(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate_hz.max(f64::EPSILON))
+    }
+}
+
+/// Timestamped outputs waiting to be served at fixed ticks
+///
+/// Timestamps are microseconds on the local clock, as carried by
+/// `ForwardData`. Ticks past the newest sample hold its value rather than
+/// extrapolating; ticks before the first sample produce nothing.
+#[derive(Debug, Clone)]
+pub struct Resampler {
+    config: Resampling,
+    samples: VecDeque<(u64, Vec<f64>)>,
+}
+
+impl Resampler {
+    pub fn new(config: Resampling) -> Self {
+        Self {
+            config,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> &Resampling {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Buffer a sample; late arrivals are sorted into place
+    pub fn push(&mut self, timestamp: u64, values: Vec<f64>) {
+        let index = self.samples.partition_point(|(t, _)| *t <= timestamp);
+        self.samples.insert(index, (timestamp, values));
+        while self.samples.len() > self.config.capacity.max(2) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Value at `time`, without consuming anything
+    pub fn value_at(&self, time: u64) -> Option<Vec<f64>> {
+        let after = self.samples.partition_point(|(t, _)| *t <= time);
+        if after == 0 {
+            return None;
+        }
+        let (t0, v0) = &self.samples[after - 1];
+        match (self.config.mode, self.samples.get(after)) {
+            (ResampleMode::Interpolate, Some((t1, v1))) => {
+                let fraction = (time - t0) as f64 / (t1 - t0) as f64;
+                Some(
+                    v0.iter()
+                        .zip(v1)
+                        .map(|(a, b)| a + (b - a) * fraction)
+                        .collect(),
+                )
+            }
+            _ => Some(v0.clone()),
+        }
+    }
+
+    /// Value for the tick at `now`, dropping samples no later tick can need
+    pub fn tick(&mut self, now: u64) -> Option<Vec<f64>> {
+        let time = now.saturating_sub(self.config.delay_ms * 1000);
+        let value = self.value_at(time)?;
+        let after = self.samples.partition_point(|(t, _)| *t <= time);
+        self.samples.drain(..after - 1);
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolates_between_samples() {
+        let mut resampler = Resampler::new(Resampling {
+            delay_ms: 1,
+            ..Resampling::new(100.0, ResampleMode::Interpolate)
+        });
+        assert_eq!(resampler.tick(5_000), None);
+
+        resampler.push(10_000, vec![0.0, 1.0]);
+        resampler.push(30_000, vec![1.0, 0.0]);
+        resampler.push(20_000, vec![0.4, 0.6]);
+        assert_eq!(resampler.value_at(15_000), Some(vec![0.2, 0.8]));
+        let value = resampler.tick(26_000).unwrap();
+        assert!((value[0] - 0.7).abs() < 1e-12 && (value[1] - 0.3).abs() < 1e-12);
+        assert_eq!(resampler.len(), 2);
+
+        // Past the newest sample its value is held
+        assert_eq!(resampler.tick(100_000), Some(vec![1.0, 0.0]));
+        assert_eq!(resampler.len(), 1);
+        assert_eq!(
+            Resampling::new(50.0, ResampleMode::Latest).period(),
+            Duration::from_millis(20)
+        );
+    }
+
+    #[test]
+    fn test_latest_and_capacity() {
+        let mut resampler = Resampler::new(Resampling {
+            delay_ms: 0,
+            capacity: 3,
+            ..Resampling::new(10.0, ResampleMode::Latest)
+        });
+        for step in 0..5 {
+            resampler.push(step * 1_000, vec![step as f64]);
+        }
+        assert_eq!(resampler.len(), 3);
+        assert_eq!(resampler.value_at(1_500), None);
+        assert_eq!(resampler.tick(3_900), Some(vec![3.0]));
+    }
+}
@@ -0,0 +1,257 @@
+use serde::Serialize;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Shortest time between two samples of a training run
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Monitor behind the metrics endpoint; utilization is measured between scrapes
+static PROCESS_MONITOR: Mutex<Option<ResourceMonitor>> = Mutex::new(None);
+
+/// Resource usage of this process, as reported by the operating system
+///
+/// Memory and thread counts come from `/proc` and are only known on Linux;
+/// CPU time and peak memory are known on every Unix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ResourceSample {
+    pub rss_bytes: Option<u64>,
+    pub peak_rss_bytes: Option<u64>,
+    /// User and system CPU time since the process started
+    pub cpu_seconds: f64,
+    pub threads: Option<usize>,
+}
+
+impl ResourceSample {
+    pub fn current() -> Self {
+        let mut sample = Self::default();
+        #[cfg(target_os = "linux")]
+        if let Ok(status) = std::fs::read_to_string("/proc/self/status") {
+            sample = parse_status(&status);
+        }
+        #[cfg(unix)]
+        {
+            // SAFETY: getrusage only writes the zeroed struct it is given
+            let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+            if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } == 0 {
+                let seconds = |time: libc::timeval| time.tv_sec as f64 + time.tv_usec as f64 / 1e6;
+                sample.cpu_seconds = seconds(usage.ru_utime) + seconds(usage.ru_stime);
+                // Kilobytes on Linux, bytes on macOS
+                let max_rss = usage.ru_maxrss.max(0) as u64;
+                let max_rss = if cfg!(target_os = "macos") {
+                    max_rss
+                } else {
+                    max_rss * 1024
+                };
+                sample.peak_rss_bytes = sample.peak_rss_bytes.max(Some(max_rss));
+            }
+        }
+        sample
+    }
+}
+
+/// Memory and thread counts of a `/proc/<pid>/status` file
+pub fn parse_status(status: &str) -> ResourceSample {
+    let field = |name: &str| {
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .and_then(|value| value.split_whitespace().next()?.parse::<u64>().ok())
+    };
+    ResourceSample {
+        rss_bytes: field("VmRSS").map(|kb| kb * 1024),
+        peak_rss_bytes: field("VmHWM").map(|kb| kb * 1024),
+        cpu_seconds: 0.0,
+        threads: field("Threads").map(|threads| threads as usize),
+    }
+}
+
+/// A sample with the CPU utilization since the previous one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct ResourceUsage {
+    #[serde(flatten)]
+    pub sample: ResourceSample,
+    /// Share of one core used since the previous sample, in percent (200 is two busy cores)
+    pub cpu_percent: f64,
+}
+
+impl ResourceUsage {
+    /// One-line summary for training logs
+    pub fn summary(&self) -> String {
+        let mut out = format!("CPU {:.0}%", self.cpu_percent);
+        if let Some(rss) = self.sample.rss_bytes {
+            let _ = write!(out, ", RSS {}", format_bytes(rss));
+        }
+        if let Some(peak) = self.sample.peak_rss_bytes {
+            let _ = write!(out, ", peak RSS {}", format_bytes(peak));
+        }
+        if let Some(threads) = self.sample.threads {
+            let _ = write!(out, ", {} threads", threads);
+        }
+        out
+    }
+
+    /// Prometheus gauges and counters of the usage
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: Option<f64>| {
+            if let Some(value) = value {
+                let _ = writeln!(out, "# HELP {} {}", name, help);
+                let _ = writeln!(out, "# TYPE {} {}", name, kind);
+                let _ = writeln!(out, "{} {}", name, value);
+            }
+        };
+        let sample = &self.sample;
+        metric(
+            "benny_process_resident_memory_bytes",
+            "gauge",
+            "Resident set size of the process",
+            sample.rss_bytes.map(|bytes| bytes as f64),
+        );
+        metric(
+            "benny_process_peak_resident_memory_bytes",
+            "gauge",
+            "Largest resident set size of the process so far",
+            sample.peak_rss_bytes.map(|bytes| bytes as f64),
+        );
+        metric(
+            "benny_process_cpu_seconds_total",
+            "counter",
+            "User and system CPU time of the process",
+            Some(sample.cpu_seconds),
+        );
+        metric(
+            "benny_process_cpu_utilization_percent",
+            "gauge",
+            "CPU used since the previous scrape, in percent of one core",
+            Some(self.cpu_percent),
+        );
+        metric(
+            "benny_process_threads",
+            "gauge",
+            "Threads of the process",
+            sample.threads.map(|threads| threads as f64),
+        );
+        out
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+/// Samples the process over time, tracking CPU utilization between samples
+#[derive(Debug, Clone)]
+pub struct ResourceMonitor {
+    last: (Instant, f64),
+    peak_rss_bytes: Option<u64>,
+}
+
+impl Default for ResourceMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        let sample = ResourceSample::current();
+        Self {
+            last: (Instant::now(), sample.cpu_seconds),
+            peak_rss_bytes: sample.peak_rss_bytes,
+        }
+    }
+
+    pub fn sample(&mut self) -> ResourceUsage {
+        self.record(Instant::now(), ResourceSample::current())
+    }
+
+    /// Take `sample` as taken at `at`
+    pub fn record(&mut self, at: Instant, sample: ResourceSample) -> ResourceUsage {
+        let (since, cpu_seconds) = self.last;
+        let elapsed = at.saturating_duration_since(since).as_secs_f64();
+        let cpu_percent = if elapsed > 0.0 {
+            (sample.cpu_seconds - cpu_seconds).max(0.0) / elapsed * 100.0
+        } else {
+            0.0
+        };
+        self.last = (at, sample.cpu_seconds);
+        self.peak_rss_bytes = self
+            .peak_rss_bytes
+            .max(sample.peak_rss_bytes.or(sample.rss_bytes));
+        ResourceUsage {
+            sample: ResourceSample {
+                peak_rss_bytes: self.peak_rss_bytes,
+                ..sample
+            },
+            cpu_percent,
+        }
+    }
+
+    /// Whether `SAMPLE_INTERVAL` has passed since the last sample
+    pub fn is_due(&self) -> bool {
+        self.last.0.elapsed() >= SAMPLE_INTERVAL
+    }
+}
+
+/// Start measuring the CPU utilization reported by the first `process_usage`
+pub fn start_process_monitor() {
+    PROCESS_MONITOR
+        .lock()
+        .unwrap()
+        .get_or_insert_with(ResourceMonitor::new);
+}
+
+/// Usage of this process since the previous call, for the metrics endpoint
+pub fn process_usage() -> ResourceUsage {
+    PROCESS_MONITOR
+        .lock()
+        .unwrap()
+        .get_or_insert_with(ResourceMonitor::new)
+        .sample()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resource_sampling() {
+        let status = "Name:\tbenny\nVmHWM:\t   20480 kB\nVmRSS:\t   10240 kB\nThreads:\t7\n";
+        let sample = parse_status(status);
+        assert_eq!(sample.rss_bytes, Some(10 * 1024 * 1024));
+        assert_eq!(sample.peak_rss_bytes, Some(20 * 1024 * 1024));
+        assert_eq!(sample.threads, Some(7));
+        assert_eq!(parse_status("").threads, None);
+
+        // Two busy cores over half a second
+        let mut monitor = ResourceMonitor::new();
+        let (start, cpu) = monitor.last;
+        let usage = monitor.record(
+            start + Duration::from_millis(500),
+            ResourceSample {
+                cpu_seconds: cpu + 1.0,
+                ..sample
+            },
+        );
+        assert!((usage.cpu_percent - 200.0).abs() < 1e-6);
+        assert!(usage.summary().starts_with("CPU 200%, RSS 10.0 MiB"));
+        assert!(usage.summary().ends_with("7 threads"));
+        // The peak never goes down
+        let usage = monitor.record(start + Duration::from_secs(1), parse_status("VmRSS: 1 kB"));
+        assert!(usage.sample.peak_rss_bytes >= Some(20 * 1024 * 1024));
+
+        let text = usage.to_prometheus();
+        assert!(text.contains("# TYPE benny_process_cpu_seconds_total counter\n"));
+        assert!(text.contains("benny_process_resident_memory_bytes 1024\n"));
+        assert!(!text.contains("benny_process_threads"));
+
+        #[cfg(target_os = "linux")]
+        {
+            let current = ResourceSample::current();
+            assert!(current.rss_bytes.unwrap() > 0);
+            assert!(current.threads.unwrap() >= 1);
+            assert!(current.cpu_seconds > 0.0);
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::cli::{CategoricalColumn, TrainingData};
+use crate::evaluation::Evaluation;
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Days searched for the next match before a schedule is declared impossible (e.g. `0 0 30 2 *`)
+const SEARCH_DAYS: i64 = 366 * 8;
+
+/// A five-field cron schedule: `minute hour day-of-month month day-of-week`, in UTC
+///
+/// Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and
+/// comma-separated lists of those; day-of-week runs from 0 (Sunday) to 6,
+/// with 7 also meaning Sunday. As in cron, a day matches either day field
+/// when both are restricted. `@hourly`, `@daily`, `@weekly` and `@monthly`
+/// are shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    weekdays: Vec<u32>,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+/// Values of one cron field within `[min, max]`, sorted and deduplicated
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<u32>, String> {
+    let mut values = Vec::new();
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("Invalid step in '{}'", part))?;
+                if step == 0 {
+                    return Err(format!("Step of '{}' must be positive", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (low, high) = match range {
+            "*" => (min, max),
+            _ => {
+                let number = |s: &str| {
+                    s.parse::<u32>()
+                        .map_err(|_| format!("Invalid value '{}' in '{}'", s, field))
+                };
+                match range.split_once('-') {
+                    Some((low, high)) => (number(low)?, number(high)?),
+                    // `5/10` runs from 5 to the end of the field
+                    None if step > 1 => (number(range)?, max),
+                    None => (number(range)?, number(range)?),
+                }
+            }
+        };
+        if low < min || high > max || low > high {
+            return Err(format!(
+                "'{}' is outside {}-{} in '{}'",
+                range, min, max, field
+            ));
+        }
+        values.extend((low..=high).step_by(step as usize));
+    }
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let spec = match spec.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            spec => spec,
+        };
+        let fields: Vec<&str> = spec.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "Schedule '{}' needs 5 fields (minute hour day month weekday), got {}",
+                spec,
+                fields.len()
+            ));
+        };
+        let mut weekdays = parse_field(weekday, 0, 7)?;
+        if weekdays.last() == Some(&7) {
+            weekdays.pop();
+            if weekdays.first() != Some(&0) {
+                weekdays.insert(0, 0);
+            }
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)?,
+            days: parse_field(day, 1, 31)?,
+            months: parse_field(month, 1, 12)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl CronSchedule {
+    fn matches_day(&self, date: DateTime<Utc>) -> bool {
+        if !self.months.contains(&date.month()) {
+            return false;
+        }
+        let day = self.days.contains(&date.day());
+        let weekday = self
+            .weekdays
+            .contains(&date.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            (false, true) => day,
+            (true, false) => weekday,
+            (true, true) => true,
+        }
+    }
+
+    /// First minute strictly after `after` the schedule fires at, if any
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = Utc
+            .with_ymd_and_hms(after.year(), after.month(), after.day(), 0, 0, 0)
+            .single()?;
+        (0..SEARCH_DAYS)
+            .map(|day| start + Duration::days(day))
+            .filter(|&date| self.matches_day(date))
+            .find_map(|date| {
+                self.hours.iter().find_map(|&hour| {
+                    self.minutes.iter().find_map(|&minute| {
+                        let time = date + Duration::minutes((hour * 60 + minute) as i64);
+                        (time > after).then_some(time)
+                    })
+                })
+            })
+    }
+}
+
+/// Metric a retrained model has to improve on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PromotionMetric {
+    /// Lower is better
+    Mse,
+    /// Higher is better
+    Accuracy,
+}
+
+impl PromotionMetric {
+    pub fn of(self, evaluation: &Evaluation) -> f64 {
+        match self {
+            PromotionMetric::Mse => evaluation.mse,
+            PromotionMetric::Accuracy => evaluation.accuracy,
+        }
+    }
+
+    /// How much better `candidate` is than `production`; positive means better
+    pub fn improvement(self, production: f64, candidate: f64) -> f64 {
+        match self {
+            PromotionMetric::Mse => production - candidate,
+            PromotionMetric::Accuracy => candidate - production,
+        }
+    }
+}
+
+/// Outcome of comparing a retrained model with the one in production
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromotionDecision {
+    pub metric: PromotionMetric,
+    /// Metric of the production model, if there was one
+    pub production: Option<f64>,
+    pub candidate: f64,
+    pub promote: bool,
+}
+
+impl PromotionDecision {
+    /// Promote `candidate` when there is no production model or it beats it by at least `margin`
+    pub fn decide(
+        metric: PromotionMetric,
+        margin: f64,
+        production: Option<&Evaluation>,
+        candidate: &Evaluation,
+    ) -> Self {
+        let production = production.map(|evaluation| metric.of(evaluation));
+        let candidate = metric.of(candidate);
+        let promote = candidate.is_finite()
+            && production
+                .is_none_or(|production| metric.improvement(production, candidate) >= margin);
+        Self {
+            metric,
+            production,
+            candidate,
+            promote,
+        }
+    }
+}
+
+/// The dataset files of a directory with their sizes and modification times
+///
+/// Two snapshots compare equal when no file was added, removed or rewritten
+/// in between, which is when retraining would see the same data again.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatasetSnapshot {
+    pub files: Vec<(PathBuf, u64, Option<SystemTime>)>,
+}
+
+impl DatasetSnapshot {
+    /// The `.csv` and `.json` files directly inside `dir`, in name order
+    pub fn of(dir: &Path) -> Result<Self, String> {
+        let entries =
+            fs::read_dir(dir).map_err(|e| format!("Cannot read {}: {}", dir.display(), e))?;
+        let mut files: Vec<(PathBuf, u64, Option<SystemTime>)> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+                let extension = path.extension()?.to_str()?;
+                if !matches!(extension, "csv" | "json") {
+                    return None;
+                }
+                let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+                Some((path, metadata.len(), metadata.modified().ok()))
+            })
+            .collect();
+        files.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(Self { files })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Every file's samples, concatenated in name order
+    pub fn load(&self, categorical: &[CategoricalColumn]) -> Result<TrainingData, String> {
+        let mut data = TrainingData {
+            inputs: Vec::new(),
+            targets: Vec::new(),
+        };
+        for (path, _, _) in &self.files {
+            let loaded = if path.extension().is_some_and(|e| e == "csv") {
+                TrainingData::load_from_csv_with_categories(path, categorical)
+            } else {
+                TrainingData::load_from_json(path)
+            }
+            .map_err(|e| format!("Cannot load {}: {}", path.display(), e))?;
+            data.inputs.extend(loaded.inputs);
+            data.targets.extend(loaded.targets);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_and_promotion() {
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+        let daily: CronSchedule = "30 3 * * *".parse().unwrap();
+        assert_eq!(
+            daily.next_after(at("2024-05-01T03:30:00Z")),
+            Some(at("2024-05-02T03:30:00Z"))
+        );
+        let quarter: CronSchedule = "*/15 9-17 * * 1-5".parse().unwrap();
+        // Friday evening waits for Monday morning
+        assert_eq!(
+            quarter.next_after(at("2024-05-03T17:50:00Z")),
+            Some(at("2024-05-06T09:00:00Z"))
+        );
+        // Either day field matches when both are restricted; 7 is Sunday
+        let either: CronSchedule = "0 0 1 * 7".parse().unwrap();
+        assert_eq!(
+            either.next_after(at("2024-05-01T12:00:00Z")),
+            Some(at("2024-05-05T00:00:00Z"))
+        );
+        assert_eq!(
+            "@weekly".parse::<CronSchedule>().unwrap(),
+            "0 0 * * 0".parse().unwrap()
+        );
+        assert_eq!(
+            "0 0 30 2 *"
+                .parse::<CronSchedule>()
+                .unwrap()
+                .next_after(at("2024-01-01T00:00:00Z")),
+            None
+        );
+        assert!("* * *".parse::<CronSchedule>().is_err());
+        assert!("61 * * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+
+        let evaluation = |mse: f64, accuracy: f64| Evaluation {
+            mse,
+            accuracy,
+            ..Evaluation::compute(std::iter::empty::<(&[f64], &[f64])>())
+        };
+        let (production, candidate) = (evaluation(0.10, 0.80), evaluation(0.08, 0.79));
+        let decide = |metric, margin, production| {
+            PromotionDecision::decide(metric, margin, production, &candidate).promote
+        };
+        assert!(decide(PromotionMetric::Mse, 0.01, Some(&production)));
+        assert!(!decide(PromotionMetric::Mse, 0.05, Some(&production)));
+        assert!(!decide(PromotionMetric::Accuracy, 0.0, Some(&production)));
+        assert!(decide(PromotionMetric::Accuracy, 0.0, None));
+    }
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+
+/// Outlier-robust Hebbian updates for noisy sensor feeds
+///
+/// A single extreme activation can dominate correlation-driven updates.
+/// Three independent safeguards limit its influence:
+/// - `winsorize` clips every activation to the quantiles of its neuron's
+///   recent history, cutting that fraction off each tail
+/// - `median_correlation` replaces the Pearson correlation of histories with
+///   the comedian correlation, `med((x - med x)(y - med y)) / (MAD x · MAD y)`
+/// - `mad_cap` limits every weight change to `median + mad_cap · MAD` of the
+///   update magnitudes, tracked per weight layer as a running estimate
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RobustHebbian {
+    /// Fraction of each tail of the activation history clipped away, in [0, 0.5)
+    pub winsorize: Option<f64>,
+    /// Correlate histories by medians instead of means
+    pub median_correlation: bool,
+    /// Update magnitude cap in running MADs above the running median
+    pub mad_cap: Option<f64>,
+    /// Weight of the newest update batch in the running estimates, in (0, 1]
+    pub smoothing: f64,
+    scales: Vec<Option<(f64, f64)>>, // scales[layer], running (median, MAD) of |Δw|
+}
+
+impl RobustHebbian {
+    pub fn new(
+        winsorize: Option<f64>,
+        median_correlation: bool,
+        mad_cap: Option<f64>,
+        smoothing: f64,
+    ) -> Result<Self, String> {
+        if let Some(fraction) = winsorize {
+            if !(0.0..0.5).contains(&fraction) {
+                return Err(format!("Winsorize fraction {} must be in [0, 0.5)", fraction));
+            }
+        }
+        if let Some(cap) = mad_cap {
+            if cap.is_nan() || cap < 0.0 {
+                return Err(format!("MAD cap {} must not be negative", cap));
+            }
+        }
+        if !(smoothing > 0.0 && smoothing <= 1.0) {
+            return Err(format!("Smoothing {} must be in (0, 1]", smoothing));
+        }
+        Ok(Self {
+            winsorize,
+            median_correlation,
+            mad_cap,
+            smoothing,
+            scales: Vec::new(),
+        })
+    }
+
+    /// `value` clipped to the winsorizing quantiles of `history`
+    pub fn winsorize(&self, value: f64, history: &[f64]) -> f64 {
+        match self.bounds(history) {
+            Some((low, high)) => value.clamp(low, high),
+            None => value,
+        }
+    }
+
+    /// `history` with every entry clipped to its own winsorizing quantiles
+    pub fn winsorize_history(&self, history: &[f64]) -> Vec<f64> {
+        match self.bounds(history) {
+            Some((low, high)) => history.iter().map(|v| v.clamp(low, high)).collect(),
+            None => history.to_vec(),
+        }
+    }
+
+    /// Correlation of two activation histories after winsorizing
+    pub fn correlation(&self, history1: &[f64], history2: &[f64]) -> f64 {
+        let history1 = self.winsorize_history(history1);
+        let history2 = self.winsorize_history(history2);
+        if self.median_correlation {
+            comedian_correlation(&history1, &history2)
+        } else {
+            pearson_correlation(&history1, &history2)
+        }
+    }
+
+    /// Cap the updates that turned `before` into `after` for weight layer `layer`
+    ///
+    /// The running median and MAD of the update magnitudes absorb this batch
+    /// before the cap is applied; returns the number of capped weights.
+    pub fn cap(&mut self, layer: usize, before: &[Vec<f64>], after: &mut [Vec<f64>]) -> usize {
+        let Some(cap) = self.mad_cap else {
+            return 0;
+        };
+        let magnitudes: Vec<f64> = before
+            .iter()
+            .flatten()
+            .zip(after.iter().flatten())
+            .map(|(old, new)| (new - old).abs())
+            .filter(|magnitude| *magnitude > 0.0)
+            .collect();
+        if magnitudes.is_empty() {
+            return 0;
+        }
+        let batch_median = median(&magnitudes);
+        let deviations: Vec<f64> = magnitudes.iter().map(|m| (m - batch_median).abs()).collect();
+        let batch_mad = median(&deviations);
+
+        if self.scales.len() <= layer {
+            self.scales.resize(layer + 1, None);
+        }
+        let (center, spread) = match self.scales[layer] {
+            Some((center, spread)) => (
+                center + self.smoothing * (batch_median - center),
+                spread + self.smoothing * (batch_mad - spread),
+            ),
+            None => (batch_median, batch_mad),
+        };
+        self.scales[layer] = Some((center, spread));
+
+        let limit = center + cap * spread;
+        let mut capped = 0;
+        for (old, new) in before.iter().flatten().zip(after.iter_mut().flatten()) {
+            let update = *new - old;
+            if update.abs() > limit {
+                *new = old + limit.copysign(update);
+                capped += 1;
+            }
+        }
+        capped
+    }
+
+    /// Running median and MAD of the update magnitudes of weight layer `layer`
+    pub fn update_scale(&self, layer: usize) -> Option<(f64, f64)> {
+        self.scales.get(layer).copied().flatten()
+    }
+
+    fn bounds(&self, history: &[f64]) -> Option<(f64, f64)> {
+        let fraction = self.winsorize.filter(|f| *f > 0.0)?;
+        if history.is_empty() {
+            return None;
+        }
+        let mut sorted = history.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let cut = ((sorted.len() as f64 * fraction).floor() as usize).min(sorted.len() / 2);
+        Some((sorted[cut], sorted[sorted.len() - 1 - cut]))
+    }
+}
+
+/// Median of `values`, 0 for none
+pub fn median(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len();
+    (sorted[(n - 1) / 2] + sorted[n / 2]) / 2.0
+}
+
+/// Comedian-based correlation, clamped to [-1, 1]; 0 when either side has no spread
+pub fn comedian_correlation(x: &[f64], y: &[f64]) -> f64 {
+    let (median_x, median_y) = (median(x), median(y));
+    let dx: Vec<f64> = x.iter().map(|v| v - median_x).collect();
+    let dy: Vec<f64> = y.iter().map(|v| v - median_y).collect();
+    let mad_x = median(&dx.iter().map(|d| d.abs()).collect::<Vec<_>>());
+    let mad_y = median(&dy.iter().map(|d| d.abs()).collect::<Vec<_>>());
+    if mad_x * mad_y <= 1e-10 {
+        return 0.0;
+    }
+    let products: Vec<f64> = dx.iter().zip(&dy).map(|(a, b)| a * b).collect();
+    (median(&products) / (mad_x * mad_y)).clamp(-1.0, 1.0)
+}
+
+/// Pearson correlation, 0 when either side has no variance
+pub fn pearson_correlation(x: &[f64], y: &[f64]) -> f64 {
+    let mean_x = x.iter().sum::<f64>() / x.len() as f64;
+    let mean_y = y.iter().sum::<f64>() / y.len() as f64;
+    let (mut numerator, mut sum_sq_x, mut sum_sq_y) = (0.0, 0.0, 0.0);
+    for (a, b) in x.iter().zip(y) {
+        let (dx, dy) = (a - mean_x, b - mean_y);
+        numerator += dx * dy;
+        sum_sq_x += dx * dx;
+        sum_sq_y += dy * dy;
+    }
+    let denominator = (sum_sq_x * sum_sq_y).sqrt();
+    if denominator > 1e-10 {
+        numerator / denominator
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outliers_are_contained() {
+        let robust = RobustHebbian::new(Some(0.1), true, None, 0.5).unwrap();
+        let history = [0.1, 0.2, 0.3, 0.2, 0.1, 0.3, 0.2, 0.1, 0.2, 50.0];
+        assert_eq!(robust.winsorize(50.0, &history), 0.3);
+        assert_eq!(robust.winsorize(-4.0, &history), 0.1);
+        assert_eq!(robust.winsorize(0.25, &history), 0.25);
+
+        // One spike makes Pearson see a strong correlation; the comedian does not
+        let x = [0.1, 0.3, 0.2, 0.4, 0.1, 0.3, 0.2, 0.4, 0.1, 9.0];
+        let y = [0.4, 0.1, 0.3, 0.2, 0.3, 0.2, 0.1, 0.4, 0.2, 9.0];
+        assert!(pearson_correlation(&x, &y) > 0.9);
+        assert!(comedian_correlation(&x, &y).abs() < 0.5);
+        let plain = RobustHebbian::new(None, false, None, 0.5).unwrap();
+        assert_eq!(plain.correlation(&x, &y), pearson_correlation(&x, &y));
+
+        let mut capping = RobustHebbian::new(None, false, Some(3.0), 0.5).unwrap();
+        let before = vec![vec![0.0; 4]; 2];
+        let mut after = vec![vec![0.01, 0.012, -0.011, 0.009], vec![0.01, 5.0, 0.011, -0.01]];
+        assert_eq!(capping.cap(0, &before, &mut after), 1);
+        let (center, spread) = capping.update_scale(0).unwrap();
+        assert!((after[1][1] - (center + 3.0 * spread)).abs() < 1e-12);
+        assert!(after[1][1] < 0.02);
+        assert_eq!(after[0][2], -0.011);
+        assert_eq!(capping.update_scale(1), None);
+
+        assert!(RobustHebbian::new(Some(0.5), false, None, 0.5).is_err());
+        assert!(RobustHebbian::new(None, false, Some(-1.0), 0.5).is_err());
+        assert!(RobustHebbian::new(None, false, None, 0.0).is_err());
+    }
+}
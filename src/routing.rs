@@ -0,0 +1,216 @@
+use crate::distributed_network::NetworkId;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Tuning for latency-aware routing
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RoutingConfig {
+    /// How often heartbeat probes measure round-trip latency
+    pub probe_interval: Duration,
+    /// Consecutive failures after which a peer is considered unhealthy
+    pub failure_threshold: u32,
+    /// Smoothing factor for the latency moving average (0.0 - 1.0)
+    pub ewma_alpha: f64,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval: Duration::from_secs(5),
+            failure_threshold: 3,
+            ewma_alpha: 0.3,
+        }
+    }
+}
+
+/// Health and latency of one peer serving a logical network
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteEntry {
+    pub peer_id: NetworkId,
+    /// Smoothed round-trip time, `None` until the first successful probe
+    pub rtt: Option<Duration>,
+    pub consecutive_failures: u32,
+}
+
+/// Chooses the fastest healthy peer among redundant peers of the same logical network
+#[derive(Debug, Clone, Default)]
+pub struct LatencyRouter {
+    routes: Arc<Mutex<HashMap<String, Vec<RouteEntry>>>>,
+    config: RoutingConfig,
+}
+
+impl LatencyRouter {
+    pub fn new(config: RoutingConfig) -> Self {
+        Self {
+            routes: Arc::new(Mutex::new(HashMap::new())),
+            config,
+        }
+    }
+
+    pub fn config(&self) -> RoutingConfig {
+        self.config
+    }
+
+    /// Register `peer_id` as a replica of the logical network `name`
+    pub fn add_route(&self, name: &str, peer_id: NetworkId) {
+        let mut routes = self.routes.lock().unwrap();
+        let entries = routes.entry(name.to_string()).or_default();
+        if !entries.iter().any(|entry| entry.peer_id == peer_id) {
+            entries.push(RouteEntry {
+                peer_id,
+                rtt: None,
+                consecutive_failures: 0,
+            });
+        }
+    }
+
+    /// Remove `peer_id` from the logical network `name`
+    pub fn remove_route(&self, name: &str, peer_id: NetworkId) {
+        if let Some(entries) = self.routes.lock().unwrap().get_mut(name) {
+            entries.retain(|entry| entry.peer_id != peer_id);
+        }
+    }
+
+    /// Remove `peer_id` from every logical network, e.g. after it left the cluster
+    pub fn remove_peer(&self, peer_id: NetworkId) {
+        for entries in self.routes.lock().unwrap().values_mut() {
+            entries.retain(|entry| entry.peer_id != peer_id);
+        }
+    }
+
+    /// All registered routes, by logical network name
+    pub fn routes(&self) -> HashMap<String, Vec<RouteEntry>> {
+        self.routes.lock().unwrap().clone()
+    }
+
+    /// Every peer that takes part in at least one route
+    pub fn peers(&self) -> Vec<NetworkId> {
+        let mut peers: Vec<NetworkId> = self
+            .routes
+            .lock()
+            .unwrap()
+            .values()
+            .flatten()
+            .map(|entry| entry.peer_id)
+            .collect();
+        peers.sort();
+        peers.dedup();
+        peers
+    }
+
+    fn is_healthy(&self, entry: &RouteEntry) -> bool {
+        entry.consecutive_failures < self.config.failure_threshold
+    }
+
+    /// Peers for `name` in preference order: healthy before unhealthy, then lowest latency
+    ///
+    /// Peers that have not been measured yet sort after measured ones.
+    pub fn candidates(&self, name: &str) -> Vec<NetworkId> {
+        let routes = self.routes.lock().unwrap();
+        let Some(entries) = routes.get(name) else {
+            return Vec::new();
+        };
+
+        let mut ranked: Vec<&RouteEntry> = entries.iter().collect();
+        ranked.sort_by_key(|entry| {
+            (
+                !self.is_healthy(entry),
+                entry.rtt.is_none(),
+                entry.rtt.unwrap_or(Duration::MAX),
+            )
+        });
+        ranked.into_iter().map(|entry| entry.peer_id).collect()
+    }
+
+    /// The preferred healthy peer for `name`
+    pub fn best_peer(&self, name: &str) -> Option<NetworkId> {
+        let routes = self.routes.lock().unwrap();
+        let healthy = routes.get(name)?.iter().any(|entry| self.is_healthy(entry));
+        drop(routes);
+
+        if healthy {
+            self.candidates(name).into_iter().next()
+        } else {
+            None
+        }
+    }
+
+    fn update_peer(&self, peer_id: NetworkId, update: impl Fn(&mut RouteEntry)) {
+        let mut routes = self.routes.lock().unwrap();
+        for entry in routes.values_mut().flatten() {
+            if entry.peer_id == peer_id {
+                update(entry);
+            }
+        }
+    }
+
+    /// Record a successful round trip to `peer_id`
+    pub fn record_rtt(&self, peer_id: NetworkId, rtt: Duration) {
+        let alpha = self.config.ewma_alpha.clamp(0.0, 1.0);
+        self.update_peer(peer_id, |entry| {
+            entry.consecutive_failures = 0;
+            entry.rtt = Some(match entry.rtt {
+                Some(previous) => previous.mul_f64(1.0 - alpha) + rtt.mul_f64(alpha),
+                None => rtt,
+            });
+        });
+    }
+
+    /// Record a failed probe or delivery to `peer_id`
+    pub fn record_failure(&self, peer_id: NetworkId) {
+        self.update_peer(peer_id, |entry| {
+            entry.consecutive_failures = entry.consecutive_failures.saturating_add(1);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_prefers_fastest_healthy_peer() {
+        let router = LatencyRouter::new(RoutingConfig {
+            failure_threshold: 2,
+            ..RoutingConfig::default()
+        });
+        let (fast, slow, unmeasured) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        for peer in [slow, unmeasured, fast] {
+            router.add_route("classifier", peer);
+        }
+
+        router.record_rtt(slow, Duration::from_millis(40));
+        router.record_rtt(fast, Duration::from_millis(5));
+        assert_eq!(
+            router.candidates("classifier"),
+            vec![fast, slow, unmeasured]
+        );
+        assert_eq!(router.best_peer("classifier"), Some(fast));
+
+        // Failing over once the fastest peer becomes unhealthy
+        router.record_failure(fast);
+        router.record_failure(fast);
+        assert_eq!(router.best_peer("classifier"), Some(slow));
+
+        // A successful probe restores it
+        router.record_rtt(fast, Duration::from_millis(5));
+        assert_eq!(router.best_peer("classifier"), Some(fast));
+    }
+
+    #[test]
+    fn test_no_healthy_peer() {
+        let router = LatencyRouter::new(RoutingConfig {
+            failure_threshold: 1,
+            ..RoutingConfig::default()
+        });
+        let peer = Uuid::new_v4();
+        router.add_route("sink", peer);
+        router.record_failure(peer);
+
+        assert_eq!(router.best_peer("sink"), None);
+        assert_eq!(router.candidates("sink"), vec![peer]);
+        assert_eq!(router.best_peer("unknown"), None);
+    }
+}
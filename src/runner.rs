@@ -1,43 +1,145 @@
+use crate::address::{self, AddressPreference};
+use crate::alerts::{Alerter, TrainingAlert};
+use crate::batch_tuning::BatchTuner;
+use crate::benchmark;
+use crate::checkpoint::CheckpointStore;
+use crate::compatibility::ShapePolicy;
+use crate::datasets;
+use crate::decision_boundary::{self, BoundaryAnimation, DecisionGrid};
+use crate::deployment_plan::{DeploymentPlan, NodeShape};
+use crate::differential::{self, DifferentialSide};
+use crate::distributed_network;
+use crate::dry_run::{self, ExecutionPlan};
+use crate::evaluation::Evaluation;
+use crate::feature_importance;
+use crate::hopfield::HopfieldNetwork;
+use crate::identity::IdentitySource;
+use crate::launcher::{self, ClusterConfig};
+use crate::layer_spec::LayerSpec;
+use crate::live_evaluation::LiveEvaluationConfig;
+use crate::lottery::{self, LotteryConfig};
 use crate::cli::*;
-use crate::neural_network::{HebbianLearningMode, NeuralNetwork};
+use crate::config_overrides::ConfigOverrides;
+use crate::config_schema;
+use crate::mmap_model::{self, MappedModel};
+use crate::model_metadata::ModelMetadata;
+use crate::neural_network::{self, HebbianLearningMode, NeuralNetwork};
+use crate::quantized::Precision;
+use crate::profiling::Phase;
+use crate::projection::{self, Pca, ProjectionMethod};
+use crate::pruning::{self, PruneConfig};
+use crate::rbm;
+use crate::realtime::LatencyBudget;
+use crate::recurrent::{BpttConfig, RecurrentNetwork};
+use crate::retraining::{CronSchedule, DatasetSnapshot, PromotionDecision};
+use crate::scaling::ValueRange;
+use crate::sequence::{self, SequenceData};
+use crate::repl::{self, ReplHelper, Reply, Session};
+use crate::{nnp_tool, protocol_compat, protocol_conformance, protocol_dump, protocol_spec, python_client};
+use crate::transport;
 use crate::server::{ServerConfig, run_daemon};
+use crate::shadow_capture::CaptureConfig;
+use crate::soak::{self, SoakConfig};
+use crate::split::{self, DataSplit};
+use crate::storage::StagedFile;
+use crate::spsa::Spsa;
+use crate::swa::WeightAverage;
+use crate::sweep::{self, SweepConfig, SweepOptions};
+use crate::resources::{ResourceMonitor, ResourceUsage};
+use crate::telemetry::{EpochRecord, ResourceRecord, RunParams, RunStore};
+use crate::thresholds;
+use crate::tokens::TokenStore;
+use crate::top;
+use crate::training_plots::TrainingCurves;
+use crate::visualize::{self, ImageFormat, VisualizeOptions};
+use crate::weight_animation::WeightAnimation;
+use crate::weight_ensemble::WeightEnsemble;
+use uuid::Uuid;
 use chrono::Utc;
-use rand::Rng;
+use clap::CommandFactory;
+use clap_complete::Shell;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::Editor;
 use std::fs;
-use std::io::{self, Write};
-use std::path::PathBuf;
-use std::time::Instant;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-pub fn run_training(
-    config_path: PathBuf,
-    data_path: PathBuf,
-    output_path: Option<PathBuf>,
-    epochs: usize,
-    verbose: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run_training(args: TrainArgs) -> Result<(), Box<dyn std::error::Error>> {
     println!("🧠 Starting Neural Network Training");
     println!("==================================");
+    let TrainArgs {
+        config: config_path,
+        data: data_path,
+        output: output_path,
+        epochs,
+        init_from,
+        verbose,
+        ..
+    } = args.clone();
+    let mut metadata = ModelMetadata::for_dataset(&data_path);
+    for tag in &args.tags {
+        metadata.add_tag(tag)?;
+    }
 
     // Load configuration
-    let config = NetworkConfig::load_from_file(&config_path)?;
+    let config = NetworkConfig::load_from_file(StagedFile::input(&config_path)?)?;
     println!("✅ Loaded configuration from: {}", config_path.display());
 
-    // Load training data
-    let training_data = if data_path.extension().and_then(|s| s.to_str()) == Some("csv") {
-        TrainingData::load_from_csv(&data_path)?
+    // Load training data, downloading it first when it is remote
+    let data_file = StagedFile::input(&data_path)?;
+    if SequenceData::is_sequence_file(&data_file)? {
+        let data = SequenceData::load(&data_file)?;
+        println!(
+            "✅ Loaded {} episodes ({} steps) from: {}",
+            data.episodes.len(),
+            data.num_steps(),
+            data_path.display()
+        );
+        return run_sequence_training(&config, data, &args, metadata);
+    }
+    let mut training_data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(&data_file, &config.categorical)?
     } else {
-        TrainingData::load_from_json(&data_path)?
+        TrainingData::load_from_json(&data_file)?
     };
     println!(
         "✅ Loaded {} training samples from: {}",
         training_data.inputs.len(),
         data_path.display()
     );
+    let (input_coder, output_coder) = (config.input_coder()?, config.output_coder()?);
+    if !input_coder.is_empty() || !output_coder.is_empty() {
+        for input in &mut training_data.inputs {
+            *input = input_coder.encode(input);
+        }
+        for target in &mut training_data.targets {
+            *target = output_coder.encode(target);
+        }
+        println!(
+            "🎛️  Population-coded {} input and {} output columns",
+            config.population_inputs.len(),
+            config.population_outputs.len()
+        );
+    }
 
     // Create network
     let mut network = config.create_network()?;
     println!("✅ Created network: {}", network.info());
     println!("   Parameters: {}", network.num_parameters());
+    if let Some(init_path) = &init_from {
+        let previous = load_network(init_path)?;
+        network.warm_start_from(&previous)?;
+        println!(
+            "♻️  Warm-started from: {} ({:?} -> {:?})",
+            init_path.display(),
+            previous.get_layers(),
+            network.get_layers()
+        );
+    }
 
     if verbose {
         println!("   Architecture: {:?}", config.architecture);
@@ -46,46 +148,274 @@ pub fn run_training(
         println!("   Online learning: {}", config.online_learning);
     }
 
-    // Split data for validation if specified
-    let validation_split = config.training.validation_split;
-    let split_index = ((1.0 - validation_split) * training_data.inputs.len() as f64) as usize;
-
-    let train_inputs = &training_data.inputs[..split_index];
-    let train_targets = &training_data.targets[..split_index];
-    let val_inputs = &training_data.inputs[split_index..];
-    let val_targets = &training_data.targets[split_index..];
+    // Split data into training, validation and test sets
+    let data_split = DataSplit::load_or_compute(
+        &training_data.targets,
+        config.training.validation_split,
+        config.training.test_split,
+        &config.training.split,
+    )?;
+    if let Some(path) = &config.training.split.indices_file {
+        println!("✅ Split indices in: {}", path.display());
+    }
+    let train_inputs = split::select(&training_data.inputs, &data_split.train);
+    let train_targets = split::select(&training_data.targets, &data_split.train);
+    let val_inputs = split::select(&training_data.inputs, &data_split.validation);
+    let val_targets = split::select(&training_data.targets, &data_split.validation);
+    let test_inputs = split::select(&training_data.inputs, &data_split.test);
+    let test_targets = split::select(&training_data.targets, &data_split.test);
 
     println!("\n📊 Training Configuration:");
     println!("   Training samples: {}", train_inputs.len());
     println!("   Validation samples: {}", val_inputs.len());
+    if !test_inputs.is_empty() {
+        println!("   Test samples: {}", test_inputs.len());
+    }
     println!("   Epochs: {}", epochs);
-    println!("   Batch size: {}", config.training.batch_size);
+    let mut tuner = config.training.auto_batch_size.then(|| {
+        BatchTuner::new(
+            train_inputs.len(),
+            network.num_parameters(),
+            rayon::current_num_threads(),
+        )
+    });
+    match &tuner {
+        Some(tuner) => println!("   Batch size: auto, trying {:?}", tuner.candidates()),
+        None => println!("   Batch size: {}", config.training.batch_size),
+    }
+
+    if args.dry_run {
+        let mut plan = ExecutionPlan::new("train");
+        plan.step("Configuration", config_path.display());
+        plan.step("Network", network.info());
+        plan.problems.extend(dry_run::check_dataset(
+            &training_data.inputs,
+            &training_data.targets,
+            network.input_size(),
+            network.get_layers().last().copied().unwrap_or(0),
+        ));
+        plan.step(
+            "Samples",
+            format!(
+                "{} training, {} validation, {} test",
+                train_inputs.len(),
+                val_inputs.len(),
+                test_inputs.len()
+            ),
+        );
+        plan.step("Epochs", epochs);
+        if let Some(budget) = args.max_duration {
+            plan.step("Time budget", humantime::format_duration(budget));
+        }
+        match &tuner {
+            Some(tuner) => plan.step("Batches", format!("size tuned among {:?}", tuner.candidates())),
+            None => {
+                let batch_size = config.training.batch_size.max(1);
+                let batches = train_inputs.len().div_ceil(batch_size);
+                plan.step(
+                    "Batches",
+                    format!(
+                        "{} per epoch of up to {} samples, {} in total",
+                        batches,
+                        batch_size,
+                        batches * epochs
+                    ),
+                );
+            }
+        }
+        if let Some(dir) = &config.training.checkpoint_dir {
+            plan.step(
+                "Checkpoints",
+                format!(
+                    "every {} epochs into {}",
+                    config.training.checkpoint_interval,
+                    dir.display()
+                ),
+            );
+        }
+        plan.step(
+            "Output",
+            output_path
+                .as_ref()
+                .map_or_else(|| "not saved".to_string(), |path| path.display().to_string()),
+        );
+        print!("{}", plan.to_text());
+        return Ok(plan.into_result()?);
+    }
+
+    let specs = config.layer_specs();
+    if !LayerSpec::rbm_layers(&specs).is_empty() {
+        if init_from.is_some() {
+            println!("🧱 Skipping RBM pre-training of warm-started weights");
+        } else {
+            println!("\n🧱 RBM pre-training...");
+            let pretrained =
+                rbm::pretrain_layers(&mut network, &specs, &train_inputs, &mut rand::thread_rng())?;
+            for (layer, error) in pretrained {
+                println!("   Layer {}: reconstruction error {:.6}", layer, error);
+            }
+        }
+    }
 
     // Training loop
     println!("\n🚀 Starting training...");
     let start_time = Instant::now();
     let mut best_val_error = f64::INFINITY;
     let mut patience_counter = 0;
+    let mut checkpoints = match &config.training.checkpoint_dir {
+        Some(dir) => {
+            println!("💾 Checkpointing to: {}", dir.display());
+            Some(CheckpointStore::open(dir, config.training.checkpoint_tolerance)?)
+        }
+        None => None,
+    };
+    let mut alerter = Alerter::new(config.training.alerts.clone());
+    if alerter.is_enabled() {
+        println!("🔔 Training alerts enabled");
+    }
+    let telemetry = match &config.training.telemetry_db {
+        Some(path) => {
+            let store = RunStore::open(path)?;
+            let run_id = store.start_run(&RunParams {
+                config_path: config_path.display().to_string(),
+                data_path: data_path.display().to_string(),
+                architecture: network.get_layers().to_vec(),
+                learning_rate: config.learning_rate,
+                batch_size: config.training.batch_size,
+                hyperparameters: serde_json::to_string(&config)?,
+            })?;
+            println!("🗃️  Recording run {} in: {}", run_id, path.display());
+            Some((store, run_id))
+        }
+        None => None,
+    };
+    let mut epochs_run = 0;
+    let mut last_errors = (f64::NAN, None);
+    let mut curves = args.plots_dir.is_some().then(TrainingCurves::new);
+    let mut boundary_animation = match &args.boundary_animation {
+        Some(path) => {
+            if network.input_size() != 2 {
+                return Err(format!(
+                    "--boundary-animation needs a network with 2 inputs, this one takes {}",
+                    network.input_size()
+                )
+                .into());
+            }
+            println!("🎞️  Animating the decision surface into: {}", path.display());
+            Some(BoundaryAnimation::new(DecisionGrid::around(&train_inputs, 60)))
+        }
+        None => None,
+    };
+    let mut weight_animation = args.weight_animation.as_ref().map(|path| {
+        println!("🎞️  Animating the weight matrices into: {}", path.display());
+        WeightAnimation::new()
+    });
+    let mut status = "completed";
+
+    let augmentation = &config.training.augmentation;
+    if augmentation.is_enabled() {
+        println!("🎲 Augmenting training samples (seed {})", augmentation.seed);
+    }
+    let mut swa = config.training.swa.is_enabled().then(|| {
+        println!(
+            "📐 Averaging weights of the last {} snapshots from epoch {}",
+            config.training.swa.window, config.training.swa.start_epoch
+        );
+        WeightAverage::new(config.training.swa.window)
+    });
+    let mut spsa = config.training.spsa.enabled.then(|| {
+        println!("🎯 Training by simultaneous perturbation (SPSA), two forward passes per batch");
+        Spsa::new(config.training.spsa.clone())
+    });
+    let deadline = args.max_duration.map(|budget| {
+        println!("⏱️  Time budget: {}", humantime::format_duration(budget));
+        start_time + budget
+    });
+    let out_of_time = || deadline.is_some_and(|deadline| Instant::now() >= deadline);
+    // Sampled about once a second for the telemetry, and over the whole run for the summary
+    let mut resources = ResourceMonitor::new();
+    let mut run_resources = ResourceMonitor::new();
 
     for epoch in 0..epochs {
         let mut total_train_error = 0.0;
         let mut _batch_count = 0;
+        // Samples trained on this epoch, fewer than all when the time budget runs out
+        let mut samples_seen = 0;
+        let mut interrupted = false;
+        let mut augmenter = augmentation
+            .is_enabled()
+            .then(|| augmentation.for_epoch(epoch));
+        let batch_size = tuner
+            .as_ref()
+            .map_or(config.training.batch_size, BatchTuner::batch_size);
+        let epoch_start = Instant::now();
 
         // Training batches
-        for batch_start in (0..train_inputs.len()).step_by(config.training.batch_size) {
-            let batch_end = (batch_start + config.training.batch_size).min(train_inputs.len());
+        for batch_start in (0..train_inputs.len()).step_by(batch_size) {
+            let batch_end = (batch_start + batch_size).min(train_inputs.len());
             let mut batch_error = 0.0;
 
-            for i in batch_start..batch_end {
-                let error = network.train(&train_inputs[i], &train_targets[i]);
-                batch_error += error;
+            if tuner.is_some() || spsa.is_some() {
+                // Tuned sizes only pay off through the parallel batch step
+                let batch: Vec<(Vec<f64>, Vec<f64>)> = (batch_start..batch_end)
+                    .map(|i| match &mut augmenter {
+                        Some(augmenter) => (
+                            augmenter.input(&train_inputs[i]),
+                            augmenter.target(&train_targets[i]),
+                        ),
+                        None => (train_inputs[i].clone(), train_targets[i].clone()),
+                    })
+                    .collect();
+                let error = match &mut spsa {
+                    Some(spsa) => spsa.step_supervised(&mut network, &batch),
+                    None => network.train_batch(&batch),
+                };
+                batch_error += error * batch.len() as f64;
+            } else {
+                for i in batch_start..batch_end {
+                    let error = match &mut augmenter {
+                        Some(augmenter) => {
+                            let input = augmenter.input(&train_inputs[i]);
+                            let target = augmenter.target(&train_targets[i]);
+                            network.train(&input, &target)
+                        }
+                        None => network.train(&train_inputs[i], &train_targets[i]),
+                    };
+                    batch_error += error;
+                }
             }
 
             total_train_error += batch_error;
+            samples_seen += batch_end - batch_start;
             _batch_count += 1;
+            if out_of_time() {
+                interrupted = true;
+                break;
+            }
+        }
+
+        if let Some(tuner) = &mut tuner {
+            if let Some(chosen) = tuner.record(samples_seen, epoch_start.elapsed()) {
+                for (size, rate) in tuner.measurements() {
+                    println!("   Batch size {}: {:.0} samples/sec", size, rate);
+                }
+                println!("⚙️  Auto batch size: {}", chosen);
+                if let Some((store, run_id)) = &telemetry {
+                    store.record_batch_size(*run_id, chosen)?;
+                }
+            }
         }
 
-        let avg_train_error = total_train_error / train_inputs.len() as f64;
+        let avg_train_error = total_train_error / samples_seen as f64;
+        epochs_run = epoch + 1;
+        if !avg_train_error.is_finite() {
+            let alert = TrainingAlert::Diverged { epoch };
+            alerter.notify(&alert);
+            if let Some((store, run_id)) = &telemetry {
+                store.finish_run(*run_id, "diverged")?;
+            }
+            return Err(alert.message().into());
+        }
 
         // Validation
         let mut total_val_error = 0.0;
@@ -100,10 +430,41 @@ pub fn run_training(
                 total_val_error += error;
             }
             total_val_error /= val_inputs.len() as f64;
+            if let Some(alert) = alerter.check_improvement(epoch, total_val_error) {
+                alerter.notify(&alert);
+            }
+        }
+        last_errors = (
+            avg_train_error,
+            (!val_inputs.is_empty()).then_some(total_val_error),
+        );
+        if let Some(curves) = &mut curves {
+            curves.record(&network, last_errors.0, last_errors.1);
+        }
+        if let Some((store, run_id)) = &telemetry {
+            store.record_epoch(
+                *run_id,
+                &EpochRecord {
+                    epoch,
+                    train_error: last_errors.0,
+                    val_error: last_errors.1,
+                    seconds: start_time.elapsed().as_secs_f64(),
+                },
+            )?;
+            if resources.is_due() {
+                let usage = resources.sample();
+                store.record_resources(*run_id, &resource_record(epoch, start_time, &usage))?;
+            }
         }
 
         // Print progress
         if epoch % config.training.print_interval == 0 || verbose {
+            if let Some(animation) = &mut boundary_animation {
+                animation.record(epoch, &network)?;
+            }
+            if let Some(animation) = &mut weight_animation {
+                animation.record(epoch, &network);
+            }
             if val_inputs.is_empty() {
                 println!("   Epoch {}: Train Error = {:.6}", epoch, avg_train_error);
             } else {
@@ -114,6 +475,39 @@ pub fn run_training(
             }
         }
 
+        if let Some(average) = &mut swa {
+            if config.training.swa.samples_epoch(epoch) {
+                average.update(&network);
+            }
+        }
+
+        let stopping = interrupted || out_of_time();
+        if let Some(store) = &mut checkpoints {
+            let interval = config.training.checkpoint_interval.max(1);
+            if (epoch + 1) % interval == 0 || stopping {
+                let bytes = store.save(&network)?;
+                if let Some((telemetry, run_id)) = &telemetry {
+                    let path = store.latest_path();
+                    telemetry.record_checkpoint(*run_id, epoch, &path.display().to_string())?;
+                }
+                if verbose || stopping {
+                    println!("   Checkpoint at epoch {}: {} bytes", epoch, bytes);
+                }
+            }
+        }
+        if stopping {
+            println!(
+                "⏱️  Time budget reached in epoch {} of {} after {} of {} samples: Train Error = {:.6}",
+                epoch,
+                epochs,
+                samples_seen,
+                train_inputs.len(),
+                avg_train_error
+            );
+            status = "time_limit";
+            break;
+        }
+
         // Early stopping
         if !val_inputs.is_empty() {
             if total_val_error < best_val_error - config.training.early_stop_threshold {
@@ -126,73 +520,478 @@ pub fn run_training(
                         "🛑 Early stopping at epoch {} (best val error: {:.6})",
                         epoch, best_val_error
                     );
+                    alerter.notify(&TrainingAlert::EarlyStopped {
+                        epoch,
+                        best_val_error,
+                    });
+                    status = "early_stopped";
                     break;
                 }
             }
         }
     }
 
+    // Training that ends while still tuning keeps the fastest size measured
+    if let Some(tuner) = tuner.as_ref().filter(|tuner| tuner.is_tuning()) {
+        if let (Some(best), Some((store, run_id))) = (tuner.best(), &telemetry) {
+            store.record_batch_size(*run_id, best)?;
+        }
+    }
+
     let training_time = start_time.elapsed();
     println!(
         "\n✅ Training completed in {:.2}s",
         training_time.as_secs_f64()
     );
+    let usage = run_resources.sample();
+    println!("🖥️  Resources: {}", usage.summary());
+    if let Some((store, run_id)) = &telemetry {
+        let epoch = epochs_run.saturating_sub(1);
+        store.record_resources(*run_id, &resource_record(epoch, start_time, &usage))?;
+    }
+    alerter.notify(&TrainingAlert::Completed {
+        epochs: epochs_run,
+        train_error: last_errors.0,
+        val_error: last_errors.1,
+        seconds: training_time.as_secs_f64(),
+    });
+    if let (Some(curves), Some(dir)) = (&curves, &args.plots_dir) {
+        write_plots(curves, dir, args.plot_format)?;
+    }
+    if let (Some(animation), Some(path)) = (&mut boundary_animation, &args.boundary_animation) {
+        let last_epoch = epochs_run.saturating_sub(1);
+        if animation.epochs().last() != Some(&last_epoch) {
+            animation.record(last_epoch, &network)?;
+        }
+        let samples = labelled_samples(&train_inputs, &train_targets);
+        animation.write_gif(path, &samples, 200)?;
+        println!(
+            "🎞️  Wrote {} frames of the decision surface to: {}",
+            animation.epochs().len(),
+            path.display()
+        );
+    }
+    if let (Some(animation), Some(path)) = (&mut weight_animation, &args.weight_animation) {
+        let last_epoch = epochs_run.saturating_sub(1);
+        if animation.epochs().last() != Some(&last_epoch) {
+            animation.record(last_epoch, &network);
+        }
+        animation.write_gif(path, 200)?;
+        println!(
+            "🎞️  Wrote {} frames of the weight matrices to: {}",
+            animation.epochs().len(),
+            path.display()
+        );
+    }
+
+    // Record provenance and final metrics in the saved models
+    metadata.finish_training(training_time);
+    metadata.record("epochs", epochs_run as f64);
+    metadata.record("train_error", last_errors.0);
+    metadata.record("cpu_percent", usage.cpu_percent);
+    if let Some(peak) = usage.sample.peak_rss_bytes {
+        metadata.record("peak_rss_bytes", peak as f64);
+    }
+    if let Some(val_error) = last_errors.1 {
+        metadata.record("val_error", val_error);
+    }
+    let record_accuracy = |network: &mut NeuralNetwork| {
+        let mut metadata = metadata.clone();
+        if !val_inputs.is_empty() {
+            let accuracy = binary_accuracy(network, &val_inputs, &val_targets);
+            metadata.record("val_accuracy", accuracy);
+        }
+        if !test_inputs.is_empty() {
+            let accuracy = binary_accuracy(network, &test_inputs, &test_targets);
+            metadata.record("test_accuracy", accuracy);
+        }
+        network.set_metadata(metadata);
+    };
+    record_accuracy(&mut network);
+    let mut swa_network = swa.as_ref().and_then(WeightAverage::averaged);
+    if let Some(averaged) = &mut swa_network {
+        record_accuracy(averaged);
+    }
 
     // Save model if output path specified
-    if let Some(output_path) = output_path {
-        if output_path.extension().and_then(|s| s.to_str()) == Some("bin") {
-            network.save_to_binary(&output_path)?;
-            println!(
-                "💾 Model saved to: {} (binary format)",
-                output_path.display()
-            );
-        } else {
-            network.save_to_file(&output_path)?;
-            println!("💾 Model saved to: {} (JSON format)", output_path.display());
+    if let Some(output_path) = &output_path {
+        save_model(&network, output_path)?;
+        let mut saved = vec![output_path.clone()];
+        if let Some(averaged) = &swa_network {
+            let swa_path = config.training.swa.output_path(output_path);
+            save_model(averaged, &swa_path)?;
+            saved.push(swa_path);
+        }
+        if let Some((store, run_id)) = &telemetry {
+            let epoch = epochs_run.saturating_sub(1);
+            for path in saved {
+                store.record_checkpoint(*run_id, epoch, &path.display().to_string())?;
+            }
         }
     }
+    if let Some((store, run_id)) = &telemetry {
+        store.finish_run(*run_id, status)?;
+    }
 
     // Final evaluation
     println!("\n📈 Final Evaluation:");
-    if !val_inputs.is_empty() {
-        let mut correct = 0;
-        for i in 0..val_inputs.len() {
-            let (output, _) = network.forward(&val_inputs[i]);
-            let predicted = if output[0] > 0.5 { 1.0 } else { 0.0 };
-            if (predicted - val_targets[i][0]).abs() < 0.1 {
-                correct += 1;
+    let metrics = &network.metadata().metrics;
+    if let Some(accuracy) = metrics.get("val_accuracy") {
+        println!("   Validation Accuracy: {:.2}%", accuracy * 100.0);
+    }
+    if let Some(accuracy) = metrics.get("test_accuracy") {
+        println!("   Test Accuracy: {:.2}%", accuracy * 100.0);
+    }
+    if let Some(averaged) = &swa_network {
+        let metrics = &averaged.metadata().metrics;
+        if let Some(accuracy) = metrics.get("val_accuracy") {
+            println!("   SWA Validation Accuracy: {:.2}%", accuracy * 100.0);
+        }
+        if let Some(accuracy) = metrics.get("test_accuracy") {
+            println!("   SWA Test Accuracy: {:.2}%", accuracy * 100.0);
+        }
+    }
+
+    Ok(())
+}
+
+/// Train on episodes, resetting the network's temporal state at the start of each one
+fn run_sequence_training(
+    config: &NetworkConfig,
+    data: SequenceData,
+    args: &TrainArgs,
+    mut metadata: ModelMetadata,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (epochs, verbose) = (args.epochs, args.verbose);
+    if args.boundary_animation.is_some() || args.weight_animation.is_some() {
+        println!("⚠️  Animations are not supported for sequence data, skipping them");
+    }
+    let mut network = config.create_network()?;
+    println!("✅ Created network: {}", network.info());
+    if let Some(init_path) = &args.init_from {
+        let previous = load_network(init_path)?;
+        network.warm_start_from(&previous)?;
+        println!("♻️  Warm-started from: {}", init_path.display());
+    }
+
+    let (train, validation) = data.split(config.training.validation_split);
+    println!(
+        "📊 Episodes: {} training, {} validation",
+        train.episodes.len(),
+        validation.episodes.len()
+    );
+    if args.dry_run {
+        let mut plan = ExecutionPlan::new("train");
+        plan.step("Network", network.info());
+        plan.step(
+            "Episodes",
+            format!(
+                "{} training ({} steps), {} validation",
+                train.episodes.len(),
+                train.num_steps(),
+                validation.episodes.len()
+            ),
+        );
+        plan.step("Epochs", epochs);
+        if let Some(budget) = args.max_duration {
+            plan.step("Time budget", humantime::format_duration(budget));
+        }
+        plan.step(
+            "Output",
+            args.output
+                .as_ref()
+                .map_or_else(|| "not saved".to_string(), |path| path.display().to_string()),
+        );
+        print!("{}", plan.to_text());
+        return Ok(plan.into_result()?);
+    }
+
+    println!("\n🚀 Starting training...");
+    let start_time = Instant::now();
+    let mut curves = args.plots_dir.is_some().then(TrainingCurves::new);
+    let mut epochs_run = 0;
+    for epoch in 0..epochs {
+        // Episodes are not split, so the time budget is checked between epochs
+        if args
+            .max_duration
+            .is_some_and(|budget| start_time.elapsed() >= budget)
+        {
+            println!("⏱️  Time budget reached after {} of {} epochs", epoch, epochs);
+            break;
+        }
+        epochs_run = epoch + 1;
+        let train_error = sequence::train_epoch(&mut network, &train);
+        let printed = epoch % config.training.print_interval.max(1) == 0 || verbose;
+        let val_error = (!validation.episodes.is_empty() && (printed || curves.is_some()))
+            .then(|| sequence::evaluate(&mut network, &validation));
+        if let Some(curves) = &mut curves {
+            curves.record(&network, train_error, val_error);
+        }
+        if printed {
+            match val_error {
+                None => println!("   Epoch {}: Train Error = {:.6}", epoch, train_error),
+                Some(val_error) => println!(
+                    "   Epoch {}: Train Error = {:.6}, Val Error = {:.6}",
+                    epoch, train_error, val_error
+                ),
             }
         }
-        let accuracy = correct as f64 / val_inputs.len() as f64;
-        println!("   Validation Accuracy: {:.2}%", accuracy * 100.0);
     }
+    let training_time = start_time.elapsed();
+    println!(
+        "\n✅ Training completed in {:.2}s",
+        training_time.as_secs_f64()
+    );
+    if let (Some(curves), Some(dir)) = (&curves, &args.plots_dir) {
+        write_plots(curves, dir, args.plot_format)?;
+    }
+
+    metadata.finish_training(training_time);
+    metadata.record("epochs", epochs_run as f64);
+    metadata.record("train_error", sequence::evaluate(&mut network, &train));
+    if !validation.episodes.is_empty() {
+        metadata.record("val_error", sequence::evaluate(&mut network, &validation));
+    }
+    network.set_metadata(metadata);
+    if let Some(output_path) = &args.output {
+        save_model(&network, output_path)?;
+    }
+
+    println!("\n📈 Final Evaluation:");
+    let metrics = &network.metadata().metrics;
+    println!("   Training Error: {:.6}", metrics["train_error"]);
+    if let Some(val_error) = metrics.get("val_error") {
+        println!("   Validation Error: {:.6}", val_error);
+    }
+    Ok(())
+}
+
+/// Telemetry row of a resource sample taken in `epoch`
+fn resource_record(epoch: usize, start_time: Instant, usage: &ResourceUsage) -> ResourceRecord {
+    ResourceRecord {
+        epoch,
+        seconds: start_time.elapsed().as_secs_f64(),
+        rss_bytes: usage.sample.rss_bytes,
+        peak_rss_bytes: usage.sample.peak_rss_bytes,
+        cpu_percent: usage.cpu_percent,
+        threads: usage.sample.threads,
+    }
+}
+
+/// Inputs paired with the class of their target, for drawing over decision surfaces
+fn labelled_samples(inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> Vec<(Vec<f64>, usize)> {
+    inputs
+        .iter()
+        .zip(targets)
+        .map(|(input, target)| (input.clone(), projection::class_of(target)))
+        .collect()
+}
+
+/// Write the charts of a finished run into `dir`
+fn write_plots(
+    curves: &TrainingCurves,
+    dir: &Path,
+    format: ImageFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let written = curves.write(dir, format)?;
+    println!("📉 Wrote {} training plots to: {}", written.len(), dir.display());
+    Ok(())
+}
 
+/// Save a model in the format picked by the file extension (`.bin`, `.mmap` or JSON)
+fn save_model(network: &NeuralNetwork, output_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let model_file = StagedFile::output(output_path)?;
+    if model_file.extension().and_then(|s| s.to_str()) == Some("bin") {
+        network.save_to_binary(&model_file)?;
+        println!(
+            "💾 Model saved to: {} (binary format)",
+            output_path.display()
+        );
+    } else if model_file.extension().and_then(|s| s.to_str()) == Some("mmap") {
+        network.save_to_mmap(&model_file)?;
+        println!(
+            "💾 Model saved to: {} (memory-mappable format)",
+            output_path.display()
+        );
+    } else {
+        network.save_to_file(&model_file)?;
+        println!("💾 Model saved to: {} (JSON format)", output_path.display());
+    }
+    model_file.publish()?;
     Ok(())
 }
 
+/// Share of samples whose first output, thresholded at 0.5, matches the target
+fn binary_accuracy(network: &mut NeuralNetwork, inputs: &[Vec<f64>], targets: &[Vec<f64>]) -> f64 {
+    let mut correct = 0;
+    for (input, target) in inputs.iter().zip(targets) {
+        let (output, _) = network.forward(input);
+        let predicted = if output[0] > 0.5 { 1.0 } else { 0.0 };
+        if (predicted - target[0]).abs() < 0.1 {
+            correct += 1;
+        }
+    }
+    correct as f64 / inputs.len() as f64
+}
+
+/// Load a trained network from a JSON, binary or memory-mappable model file
+fn load_network(model_path: &Path) -> Result<NeuralNetwork, Box<dyn std::error::Error>> {
+    let model_file = StagedFile::input(model_path)?;
+    Ok(match model_file.extension().and_then(|s| s.to_str()) {
+        Some("mmap") => MappedModel::open(&model_file)?.to_network(),
+        Some("bin") => NeuralNetwork::load_from_binary(&model_file)?,
+        _ => NeuralNetwork::load_from_file(&model_file)?,
+    })
+}
+
+/// Forward pass of whichever model representation was loaded
+type Predictor = Box<dyn FnMut(&[f64]) -> Vec<f64>>;
+
+/// Load a model file; memory-mapped models are served from the mapping
+fn load_predictor(model_path: &Path) -> Result<Predictor, Box<dyn std::error::Error>> {
+    Ok(load_predictor_with_metadata(model_path)?.0)
+}
+
+/// Load a model file with the metadata saved in it; memory-mapped models carry none
+fn load_predictor_with_metadata(
+    model_path: &Path,
+) -> Result<(Predictor, ModelMetadata), Box<dyn std::error::Error>> {
+    let model_file = StagedFile::input(model_path)?;
+    let mut network = match model_file.extension().and_then(|s| s.to_str()) {
+        Some("mmap") => {
+            let model = MappedModel::open(&model_file)?;
+            return Ok((
+                Box::new(move |inputs| model.forward(inputs)),
+                ModelMetadata::default(),
+            ));
+        }
+        Some("bin") => NeuralNetwork::load_from_binary(&model_file)?,
+        _ => NeuralNetwork::load_from_file(&model_file)?,
+    };
+    let metadata = network.metadata().clone();
+    Ok((Box::new(move |inputs| network.forward(inputs).0), metadata))
+}
+
+/// Load labelled data, keeping only the validation samples when `config` holds some out
+fn load_validation_data(
+    data_path: &Path,
+    config: Option<&NetworkConfig>,
+) -> Result<TrainingData, Box<dyn std::error::Error>> {
+    let categorical = config.map_or(&[][..], |c| &c.categorical[..]);
+    let data_file = StagedFile::input(data_path)?;
+    let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(&data_file, categorical)?
+    } else {
+        TrainingData::load_from_json(&data_file)?
+    };
+
+    // The same validation samples training held out, split on the coded targets
+    if let Some(config) = config {
+        let output_coder = config.output_coder()?;
+        let coded: Vec<Vec<f64>> = data.targets.iter().map(|t| output_coder.encode(t)).collect();
+        let data_split = DataSplit::load_or_compute(
+            &coded,
+            config.training.validation_split,
+            config.training.test_split,
+            &config.training.split,
+        )?;
+        if !data_split.validation.is_empty() {
+            data.inputs = split::select(&data.inputs, &data_split.validation);
+            data.targets = split::select(&data.targets, &data_split.validation);
+        }
+    }
+    Ok(data)
+}
+
+/// Sample `network` differently on every call, for averaging over calls
+///
+/// With `ensemble` the calls cycle through that many noisy copies of the
+/// weights (see `WeightEnsemble`); otherwise dropout stays on (MC dropout).
+fn stochastic_predictor(
+    network: NeuralNetwork,
+    ensemble: Option<(usize, f64)>,
+) -> Result<(Predictor, ModelMetadata), Box<dyn std::error::Error>> {
+    let metadata = network.metadata().clone();
+    if let Some((members, noise_scale)) = ensemble {
+        let ensemble = WeightEnsemble::new(&network, members, noise_scale, 0)?;
+        let mut calls = 0;
+        return Ok((
+            Box::new(move |inputs| {
+                let member = &ensemble.members()[calls % members];
+                calls += 1;
+                member.forward_static(inputs).0
+            }),
+            metadata,
+        ));
+    }
+    if !network.has_dropout() {
+        return Err("The model has no dropout, so every pass would be the same; \
+                    use --ensemble or train with dropout"
+            .into());
+    }
+    Ok((
+        Box::new(move |inputs| network.forward_with_dropout(inputs)),
+        metadata,
+    ))
+}
+
+/// Wrap a predictor in the population input and output stages of `config`
+fn with_population_coding(
+    mut predict: Predictor,
+    config: &NetworkConfig,
+) -> Result<Predictor, Box<dyn std::error::Error>> {
+    let (inputs, outputs) = (config.input_coder()?, config.output_coder()?);
+    if inputs.is_empty() && outputs.is_empty() {
+        return Ok(predict);
+    }
+    Ok(Box::new(move |values| {
+        outputs.decode(&predict(&inputs.encode(values)))
+    }))
+}
+
 pub fn run_prediction(
     config_path: Option<PathBuf>,
     input: String,
     model_path: Option<PathBuf>,
     format: OutputFormat,
+    mc_samples: Option<usize>,
+    ensemble: Option<(usize, f64)>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("🔮 Neural Network Prediction");
     println!("===========================");
 
-    // Create or load network
-    let mut network = if let Some(model_path) = model_path {
-        if model_path.extension().and_then(|s| s.to_str()) == Some("bin") {
-            NeuralNetwork::load_from_binary(&model_path)?
-        } else {
-            NeuralNetwork::load_from_file(&model_path)?
+    // Load configuration
+    let config = match &config_path {
+        Some(config_path) => {
+            let config = NetworkConfig::load_from_file(StagedFile::input(config_path)?)?;
+            println!("✅ Loaded configuration from: {}", config_path.display());
+            Some(config)
         }
-    } else if let Some(config_path) = config_path {
-        // Load configuration
-        let config = NetworkConfig::load_from_file(&config_path)?;
-        println!("✅ Loaded configuration from: {}", config_path.display());
-        config.create_network()?
-    } else {
-        return Err("Either config or model path must be provided".into());
+        None => None,
+    };
+
+    // Create or load network; uncertainty estimates average several differing passes
+    let samples = mc_samples.or(ensemble.map(|(members, _)| members));
+    let (predict, metadata): (Predictor, ModelMetadata) = match (model_path, &config) {
+        (Some(model_path), _) if samples.is_some() => {
+            stochastic_predictor(load_network(&model_path)?, ensemble)?
+        }
+        (Some(model_path), _) => load_predictor_with_metadata(&model_path)?,
+        (None, Some(config)) if samples.is_some() => {
+            stochastic_predictor(config.create_network()?, ensemble)?
+        }
+        (None, Some(config)) => {
+            let mut network = config.create_network()?;
+            (
+                Box::new(move |inputs| network.forward(inputs).0),
+                ModelMetadata::default(),
+            )
+        }
+        (None, None) => return Err("Either config or model path must be provided".into()),
+    };
+    let mut predict = match &config {
+        Some(config) => with_population_coding(predict, config)?,
+        None => predict,
     };
 
     // Parse input
@@ -201,7 +1000,14 @@ pub fn run_prediction(
 
     // Run prediction
     let start_time = Instant::now();
-    let (output, _) = network.forward(&input_values);
+    let (output, variance) = match samples {
+        Some(samples) => {
+            let passes = (0..samples.max(1)).map(|_| predict(&input_values));
+            let (mean, variance) = neural_network::output_moments(passes);
+            (mean, Some(variance))
+        }
+        None => (predict(&input_values), None),
+    };
     let processing_time = start_time.elapsed();
 
     // Calculate confidence (simple heuristic)
@@ -215,6 +1021,8 @@ pub fn run_prediction(
         input: input_values,
         output: output.clone(),
         confidence,
+        decisions: metadata.decisions(&output),
+        variance,
         processing_time_ms: processing_time.as_secs_f64() * 1000.0,
     };
 
@@ -239,7 +1047,22 @@ pub fn run_prediction(
         OutputFormat::Plain => {
             println!("📤 Result:");
             println!("   Output: {:?}", output);
+            if let Some(variance) = &result.variance {
+                let passes = match ensemble {
+                    Some(_) => "ensemble members",
+                    None => "dropout passes",
+                };
+                println!(
+                    "   Variance over {} {}: {:?}",
+                    samples.unwrap_or_default().max(1),
+                    passes,
+                    variance
+                );
+            }
             println!("   Confidence: {:.2}%", confidence * 100.0);
+            if let Some(decisions) = &result.decisions {
+                println!("   Decisions: {:?} (thresholds {:?})", decisions, metadata.thresholds);
+            }
             println!("   Processing time: {:.2}ms", result.processing_time_ms);
         }
     }
@@ -247,6 +1070,62 @@ pub fn run_prediction(
     Ok(())
 }
 
+pub fn run_evaluation(
+    model_path: PathBuf,
+    data_path: PathBuf,
+    config_path: Option<PathBuf>,
+    format: OutputFormat,
+    output_path: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut predict = load_predictor(&model_path)?;
+
+    // Categorical columns and population codes are only known from the configuration
+    let categorical = match &config_path {
+        Some(config_path) => {
+            let config = NetworkConfig::load_from_file(StagedFile::input(config_path)?)?;
+            predict = with_population_coding(predict, &config)?;
+            config.categorical
+        }
+        None => Vec::new(),
+    };
+    let data_file = StagedFile::input(&data_path)?;
+    let data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(&data_file, &categorical)?
+    } else {
+        TrainingData::load_from_json(&data_file)?
+    };
+
+    let outputs: Vec<Vec<f64>> = data.inputs.iter().map(|input| predict(input)).collect();
+    let evaluation = Evaluation::compute(
+        outputs
+            .iter()
+            .zip(&data.targets)
+            .map(|(output, target)| (output.as_slice(), target.as_slice())),
+    );
+    let report = match format {
+        OutputFormat::Json => serde_json::to_string_pretty(&evaluation)?,
+        OutputFormat::Csv => evaluation.to_csv(),
+        OutputFormat::Plain => evaluation.to_text(),
+    };
+
+    match output_path {
+        Some(output_path) => {
+            let report_file = StagedFile::output(&output_path)?;
+            fs::write(&report_file, report)?;
+            report_file.publish()?;
+            println!(
+                "✅ Evaluated {} on {} samples from {}: report written to {}",
+                model_path.display(),
+                evaluation.samples,
+                data_path.display(),
+                output_path.display()
+            );
+        }
+        None => print!("{}", report),
+    }
+    Ok(())
+}
+
 pub fn create_sample_config(
     output_path: PathBuf,
     network_type: NetworkType,
@@ -304,6 +1183,7 @@ pub fn create_sample_config(
 
 pub fn run_interactive_mode(
     config_path: Option<PathBuf>,
+    script: Option<PathBuf>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("🎮 Interactive Neural Network Mode");
     println!("==================================");
@@ -315,102 +1195,75 @@ pub fn run_interactive_mode(
         NetworkConfig::default()
     };
 
-    let mut network = config.create_network()?;
+    let network = config.create_network()?;
     println!("✅ Network created: {}", network.info());
-    println!("\nCommands:");
-    println!("  predict <input>  - Run prediction (e.g., 'predict 0.5,0.8')");
-    println!("  train <input> <target> - Train on single sample");
-    println!("  info             - Show network information");
-    println!("  save <file>      - Save network to file");
-    println!("  load <file>      - Load network from file");
-    println!("  quit             - Exit interactive mode");
-
-    loop {
-        print!("\nbenny> ");
-        io::stdout().flush()?;
+    let mut session = Session::new(network);
+
+    // Scripts stop at the first failing command
+    if let Some(script) = script {
+        let commands = fs::read_to_string(&script)?;
+        for (number, line) in commands.lines().enumerate() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            println!("\nbenny> {}", line.trim());
+            match session.execute(line) {
+                Ok(Reply::Output(output)) => println!("{}", output),
+                Ok(Reply::Quit) => break,
+                Err(e) => {
+                    return Err(format!("{}:{}: {}", script.display(), number + 1, e).into())
+                }
+            }
+        }
+        return Ok(());
+    }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let input = input.trim();
+    println!("\n{}", repl::help());
+    let mut editor: Editor<ReplHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper::default()));
+    let history = repl::history_path();
+    if let Some(path) = &history {
+        let _ = editor.load_history(path);
+    }
 
-        if input.is_empty() {
+    loop {
+        let line = match editor.readline("\nbenny> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => {
+                println!("👋 Goodbye!");
+                break;
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if line.trim().is_empty() {
             continue;
         }
+        let _ = editor.add_history_entry(line.as_str());
 
-        let parts: Vec<&str> = input.split_whitespace().collect();
-        match parts[0] {
-            "predict" => {
-                if parts.len() < 2 {
-                    println!("❌ Usage: predict <input>");
-                    continue;
-                }
-                match parse_input_string(parts[1]) {
-                    Ok(input_values) => {
-                        let (output, _) = network.forward(&input_values);
-                        println!("📤 Input: {:?} -> Output: {:?}", input_values, output);
-                    }
-                    Err(e) => println!("❌ Error parsing input: {}", e),
-                }
-            }
-            "train" => {
-                if parts.len() < 3 {
-                    println!("❌ Usage: train <input> <target>");
-                    continue;
-                }
-                match (parse_input_string(parts[1]), parse_input_string(parts[2])) {
-                    (Ok(input_values), Ok(target_values)) => {
-                        let error = network.train(&input_values, &target_values);
-                        println!("📈 Training error: {:.6}", error);
-                    }
-                    (Err(e), _) | (_, Err(e)) => println!("❌ Error parsing values: {}", e),
-                }
-            }
-            "info" => {
-                println!("🧠 Network Information:");
-                println!("   {}", network.info());
-                println!("   Parameters: {}", network.num_parameters());
-                println!("   Hebbian rate: {}", network.get_hebbian_rate());
-                println!("   Online learning: {}", network.is_online_learning());
-            }
-            "save" => {
-                if parts.len() < 2 {
-                    println!("❌ Usage: save <filename>");
-                    continue;
-                }
-                match network.save_to_file(parts[1]) {
-                    Ok(()) => println!("✅ Network saved to: {}", parts[1]),
-                    Err(e) => println!("❌ Error saving: {}", e),
-                }
-            }
-            "load" => {
-                if parts.len() < 2 {
-                    println!("❌ Usage: load <filename>");
-                    continue;
-                }
-                match NeuralNetwork::load_from_file(parts[1]) {
-                    Ok(loaded_network) => {
-                        network = loaded_network;
-                        println!("✅ Network loaded from: {}", parts[1]);
-                    }
-                    Err(e) => println!("❌ Error loading: {}", e),
-                }
-            }
-            "quit" | "exit" => {
+        match session.execute(&line) {
+            Ok(Reply::Output(output)) => println!("{}", output),
+            Ok(Reply::Quit) => {
                 println!("👋 Goodbye!");
                 break;
             }
-            _ => {
-                println!("❌ Unknown command: {}", parts[0]);
-            }
+            Err(e) => println!("❌ {}", e),
         }
     }
 
+    if let Some(path) = &history {
+        if let Err(e) = editor.save_history(path) {
+            println!("⚠️ Failed to save history to {}: {}", path.display(), e);
+        }
+    }
     Ok(())
 }
 
 pub fn run_benchmark(
     config_path: PathBuf,
     iterations: usize,
+    seed: Option<String>,
+    compare_modes: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("⚡ Neural Network Benchmark");
     println!("==========================");
@@ -421,9 +1274,14 @@ pub fn run_benchmark(
     println!("✅ Network: {}", network.info());
     println!("   Parameters: {}", network.num_parameters());
     println!("   Iterations: {}", iterations);
+    let seed = match &seed {
+        Some(name) => benchmark::experiment_seed(name),
+        None => rand::thread_rng().gen(),
+    };
+    println!("   Seed: {}", seed);
 
     // Generate random test data
-    let mut rng = rand::thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed);
     let test_inputs: Vec<Vec<f64>> = (0..iterations)
         .map(|_| {
             (0..config.architecture[0])
@@ -434,6 +1292,8 @@ pub fn run_benchmark(
 
     // Benchmark forward pass
     println!("\n🚀 Benchmarking forward pass...");
+    network.set_cost_accounting(true);
+    network.set_profiling(true);
     let start_time = Instant::now();
 
     for input in &test_inputs {
@@ -482,6 +1342,83 @@ pub fn run_benchmark(
     println!("   Parameters: {}", param_count);
     println!("   Estimated memory: {:.2} MB", memory_mb);
 
+    if let Some(report) = network.cost_report() {
+        println!("\n🔢 Compute Cost (per call):");
+        for (name, calls) in [("Forward", report.forward), ("Training", report.train)] {
+            let cost = calls.per_call();
+            println!(
+                "   {}: {} MACs, {} activations, ~{} FLOPs",
+                name, cost.macs, cost.activations, cost.flops
+            );
+        }
+    }
+
+    if let Some(report) = network.timing_report() {
+        let total = report.total().as_secs_f64().max(f64::EPSILON);
+        println!("\n⏱️  Time Breakdown:");
+        for phase in Phase::ALL {
+            let time = report.phase(phase).as_secs_f64();
+            println!(
+                "   {}: {:.2}ms ({:.1}%)",
+                phase.name(),
+                time * 1000.0,
+                time / total * 100.0
+            );
+        }
+        for layer in 0..report.layer_ns.len() {
+            let time = report.layer(layer).as_secs_f64();
+            println!(
+                "   layer {} forward: {:.2}ms ({:.1}%)",
+                layer,
+                time * 1000.0,
+                time / total * 100.0
+            );
+        }
+    }
+
+    if network.to_inference_model().is_ok() {
+        println!("\n🗜️  Quantized Inference:");
+        let inputs_f32: Vec<Vec<f32>> = test_inputs
+            .iter()
+            .map(|input| input.iter().map(|&x| x as f32).collect())
+            .collect();
+        for (name, precision) in [("Int8", Precision::Int8), ("F16", Precision::Half)] {
+            let model = network.to_quantized_model(precision)?;
+            let start_time = Instant::now();
+            for input in &inputs_f32 {
+                let _ = model.forward(input);
+            }
+            let per_sec = iterations as f64 / start_time.elapsed().as_secs_f64();
+            let report = network.quantization_accuracy(&model, &test_inputs);
+            println!(
+                "   {}: {:.0} ops/sec ({:.1}x), max error {:.2e}, mean error {:.2e}",
+                name,
+                per_sec,
+                per_sec / forward_per_sec,
+                report.max_abs_error,
+                report.mean_abs_error()
+            );
+        }
+    }
+
+    if compare_modes {
+        println!("\n🧪 Comparing learning modes (seed {})...", seed);
+        println!(
+            "   {:<12} {:>14} {:>14} {:>12}",
+            "Mode", "Train ops/sec", "Final error", "Norm growth"
+        );
+        let fresh = config.create_network()?;
+        for result in benchmark::compare_modes(&fresh, seed, iterations) {
+            println!(
+                "   {:<12} {:>14.0} {:>14.6} {:>11.3}x",
+                format!("{:?}", result.mode),
+                result.ops_per_sec,
+                result.final_error,
+                result.weight_norm_growth
+            );
+        }
+    }
+
     Ok(())
 }
 
@@ -493,18 +1430,18 @@ pub fn run_demo(demo_type: DemoType) -> Result<(), Box<dyn std::error::Error>> {
             run_demo(DemoType::Hebbian)?;
             println!("\n{}", "=".repeat(50));
             run_demo(DemoType::Serialization)?;
+            println!("\n{}", "=".repeat(50));
+            run_demo(DemoType::Hopfield)?;
+            println!("\n{}", "=".repeat(50));
+            run_demo(DemoType::Recurrent)?;
         }
         DemoType::Xor => {
             println!("🔀 XOR Problem Demo");
             println!("==================");
 
             let mut nn = NeuralNetwork::new(2, 4, 1, 0.5);
-            let training_data = vec![
-                (vec![0.0, 0.0], vec![0.0]),
-                (vec![0.0, 1.0], vec![1.0]),
-                (vec![1.0, 0.0], vec![1.0]),
-                (vec![1.0, 1.0], vec![0.0]),
-            ];
+            let table = datasets::xor_table(2);
+            let training_data: Vec<_> = table.inputs.into_iter().zip(table.targets).collect();
 
             println!("Training XOR network...");
             for epoch in 0..1000 {
@@ -571,6 +1508,109 @@ pub fn run_demo(demo_type: DemoType) -> Result<(), Box<dyn std::error::Error>> {
             // Cleanup
             let _ = fs::remove_file("demo_network.json");
         }
+        DemoType::Hopfield => {
+            println!("🧲 Hopfield Associative Memory Demo");
+            println!("==================================");
+
+            // 5x5 letters as bipolar patterns
+            let letters = [
+                ("T", "#####..#....#....#....#.."),
+                ("L", "#....#....#....#....#####"),
+                ("X", "#...#.#.#...#...#.#.#...#"),
+            ];
+            let patterns: Vec<Vec<f64>> = letters
+                .iter()
+                .map(|(_, cells)| {
+                    cells
+                        .chars()
+                        .map(|c| if c == '#' { 1.0 } else { -1.0 })
+                        .collect()
+                })
+                .collect();
+
+            let mut network = HopfieldNetwork::new(25);
+            for pattern in &patterns {
+                network.store(pattern)?;
+            }
+            println!(
+                "Stored {} patterns in {} units (capacity ~{})",
+                network.patterns(),
+                network.size(),
+                network.capacity()
+            );
+
+            let mut rng = StdRng::seed_from_u64(7);
+            for ((name, _), pattern) in letters.iter().zip(&patterns) {
+                let mut noisy = pattern.clone();
+                for cell in rand::seq::index::sample(&mut rng, noisy.len(), 4) {
+                    noisy[cell] = -noisy[cell];
+                }
+                let (recalled, sweeps) = network.recall(&noisy, 20)?;
+                println!(
+                    "\n{} with 4 flipped cells -> recalled in {} sweeps ({}), energy {:.2} -> {:.2}",
+                    name,
+                    sweeps,
+                    if &recalled == pattern { "exact" } else { "spurious state" },
+                    network.energy(&noisy),
+                    network.energy(&recalled)
+                );
+                let row = |state: &[f64]| -> String {
+                    state.iter().map(|&s| if s > 0.0 { '#' } else { '.' }).collect()
+                };
+                for (noisy_row, recalled_row) in noisy.chunks(5).zip(recalled.chunks(5)) {
+                    println!("   {}   ->   {}", row(noisy_row), row(recalled_row));
+                }
+            }
+        }
+        DemoType::Recurrent => {
+            println!("🔁 Recurrent Network (truncated BPTT) Demo");
+            println!("=========================================");
+
+            // XOR of the current and the previous bit: no single input decides the answer
+            let sequence = |samples, seed| {
+                let bits = datasets::temporal_parity(samples, seed).inputs;
+                let targets: Vec<Vec<f64>> = bits
+                    .iter()
+                    .enumerate()
+                    .map(|(t, bit)| {
+                        let previous = if t == 0 { 0.0 } else { bits[t - 1][0] };
+                        vec![((bit[0] > 0.5) != (previous > 0.5)) as u8 as f64]
+                    })
+                    .collect();
+                (bits, targets)
+            };
+            let (train_inputs, train_targets) = sequence(2000, 1);
+            let (test_inputs, test_targets) = sequence(500, 2);
+            let config = BpttConfig {
+                window: 12,
+                learning_rate: 0.5,
+                ..BpttConfig::default()
+            };
+            let mut network = RecurrentNetwork::new(1, 12, 1, config)?;
+            let accuracy = |network: &mut RecurrentNetwork| {
+                network.reset_state();
+                let outputs = network.predict_sequence(&test_inputs);
+                let correct = outputs
+                    .iter()
+                    .zip(&test_targets)
+                    .filter(|(output, target)| (output[0] > 0.5) == (target[0] > 0.5))
+                    .count();
+                100.0 * correct as f64 / test_inputs.len() as f64
+            };
+            println!("Accuracy before training: {:.1}%", accuracy(&mut network));
+            for epoch in 1..=20 {
+                network.reset_state();
+                let error = network.train_sequence(&train_inputs, &train_targets)?;
+                if epoch % 5 == 0 {
+                    println!(
+                        "Epoch {:>2}: MSE {:.4}, test accuracy {:.1}%",
+                        epoch,
+                        error,
+                        accuracy(&mut network)
+                    );
+                }
+            }
+        }
         _ => {
             println!("Demo type not implemented yet");
         }
@@ -579,38 +1619,90 @@ pub fn run_demo(demo_type: DemoType) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-pub fn run_server(
-    config_path: PathBuf,
-    model_path: Option<PathBuf>,
-    port: u16,
-    cert_path: Option<PathBuf>,
-    key_path: Option<PathBuf>,
-    output_endpoints: Vec<String>,
-    daemon_mode: bool,
-    hebbian_learning: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run_server(args: ServerArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let ServerArgs {
+        config: config_path,
+        model: model_path,
+        port,
+        cert: cert_path,
+        key: key_path,
+        outputs: output_endpoints,
+        daemon: daemon_mode,
+        hebbian_learning,
+        metrics_port,
+        bandwidth_limit,
+        state_file,
+        state_compression,
+        node_id,
+        identity_file,
+        namespace,
+        bridged_namespaces,
+        lock_pages,
+        adapt_shapes,
+        batch_window_us,
+        max_batch_size,
+        protocol_version,
+        input_min,
+        input_max,
+        token_file,
+        label_channel,
+        evaluation_window,
+        capture,
+        capture_rate,
+        capture_max_samples,
+        capture_max_files,
+        capture_drop_inputs,
+        latency_budget_ms,
+        dry_run,
+    } = args;
+
     println!("🚀 Starting Neural Network Server");
     println!("=================================");
 
     // Load configuration
-    let config = NetworkConfig::load_from_file(&config_path)?;
+    let config = NetworkConfig::load_from_file(StagedFile::input(&config_path)?)?;
     println!("✅ Loaded configuration from: {}", config_path.display());
 
-    // Create or load network
-    let network = if let Some(model_path) = model_path {
+    // Create or load network; memory-mapped models are served from the mapping
+    let (network, mapped) = if let Some(model_path) = &model_path {
         println!(
             "📂 Loading pre-trained model from: {}",
             model_path.display()
         );
-        NeuralNetwork::load_from_file(&model_path)?
+        let model_file = StagedFile::input(model_path)?;
+        if model_file.extension().and_then(|s| s.to_str()) == Some("mmap") {
+            let model = MappedModel::open(&model_file)?;
+            (model.skeleton(), Some(model))
+        } else {
+            (NeuralNetwork::load_from_file(&model_file)?, None)
+        }
     } else {
         println!("🆕 Creating new network from configuration");
-        config.create_network()?
+        (config.create_network()?, None)
     };
 
     println!("✅ Network ready: {}", network.info());
-    println!("   Parameters: {}", network.num_parameters());
+    if mapped.is_some() {
+        println!("   Weights: served from the memory-mapped file");
+    } else {
+        println!("   Parameters: {}", network.num_parameters());
+    }
     println!("   Hebbian Learning: {}", hebbian_learning);
+    if lock_pages && !dry_run {
+        match &mapped {
+            Some(model) => {
+                model
+                    .lock()
+                    .map_err(|e| format!("Failed to lock the model in memory: {}", e))?;
+                println!("🔒 Model pages locked in RAM");
+            }
+            None => {
+                mmap_model::lock_process_memory()
+                    .map_err(|e| format!("Failed to lock memory: {}", e))?;
+                println!("🔒 Process memory locked in RAM");
+            }
+        }
+    }
 
     // Create server configuration
     let server_config = ServerConfig {
@@ -622,6 +1714,44 @@ pub fn run_server(
         output_endpoints,
         hebbian_learning,
         daemon_mode,
+        metrics_port,
+        bandwidth_limit,
+        state_file,
+        state_compression,
+        model_file: model_path,
+        identity: match (node_id, identity_file) {
+            (Some(id), _) => IdentitySource::Fixed { id },
+            (None, Some(path)) => IdentitySource::File { path },
+            (None, None) => IdentitySource::Random,
+        },
+        namespace,
+        bridged_namespaces,
+        shape_policy: if adapt_shapes {
+            ShapePolicy::Adapt
+        } else {
+            ShapePolicy::Reject
+        },
+        batch_window: batch_window_us.map(Duration::from_micros),
+        max_batch_size: max_batch_size.max(1),
+        protocol_version,
+        input_range: input_min.zip(input_max).map(|(min, max)| ValueRange::new(min, max)),
+        config_file: Some(config_path.clone()),
+        token_file,
+        live_evaluation: label_channel.map(|label_channel| LiveEvaluationConfig {
+            label_channel,
+            window: evaluation_window,
+            ..LiveEvaluationConfig::default()
+        }),
+        capture: capture.map(|path| CaptureConfig {
+            sample_rate: capture_rate,
+            max_samples: capture_max_samples,
+            max_files: capture_max_files,
+            drop_inputs: capture_drop_inputs,
+            ..CaptureConfig::new(path)
+        }),
+        latency_budget: latency_budget_ms.map(|ms| {
+            LatencyBudget::new(Duration::try_from_secs_f64(ms / 1000.0).unwrap_or_default())
+        }),
     };
 
     if daemon_mode {
@@ -648,12 +1778,1401 @@ pub fn run_server(
         server_config.address, server_config.port
     );
     println!("📡 Using Neural Network Protocol (NNP)");
+    if let Some(metrics_port) = server_config.metrics_port {
+        println!("📈 Metrics endpoint on port {}", metrics_port);
+    }
+    if let Some(limit) = server_config.bandwidth_limit {
+        println!("🚦 Per-peer bandwidth cap: {} bytes/s", limit);
+    }
+    if let Some(range) = server_config.input_range {
+        println!("📏 Expected input range: [{}, {}]", range.min, range.max);
+    }
+    match &server_config.identity {
+        IdentitySource::Fixed { id } => println!("🪪 Node identity: {}", id),
+        IdentitySource::File { path } => println!("🪪 Node identity file: {}", path.display()),
+        IdentitySource::Random => {}
+    }
+    if let Some(namespace) = &server_config.namespace {
+        println!("🏷️  Namespace: {}", namespace);
+        for bridged in &server_config.bridged_namespaces {
+            println!("   Bridged namespace: {}", bridged);
+        }
+    }
+    if let Some(state_file) = &server_config.state_file {
+        println!("💾 Node state file: {}", state_file.display());
+    }
+    if let Some(version) = server_config.protocol_version {
+        println!("🔀 Speaking protocol v{} for a rolling upgrade", version);
+    }
+    if let Some(budget) = &server_config.latency_budget {
+        println!("⏱️  Latency budget: {:?} per forward request", budget.budget);
+    }
+    if let Some(window) = server_config.batch_window {
+        println!(
+            "📦 Batching forward requests: up to {} per batch within {}µs",
+            server_config.max_batch_size,
+            window.as_micros()
+        );
+    }
+    if dry_run {
+        let plan = server_plan(&config, &network, &server_config, lock_pages);
+        print!("{}", plan.to_text());
+        return Ok(plan.into_result()?);
+    }
     println!();
 
     // Start the server using async runtime
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async { run_daemon(network, server_config).await })
+    rt.block_on(async { run_daemon(network, mapped, server_config).await })
         .map_err(|e| format!("Server error: {:?}", e))?;
 
     Ok(())
 }
+
+/// What `server --dry-run` reports: the resolved node, and whatever would stop it from starting
+fn server_plan(
+    config: &NetworkConfig,
+    network: &NeuralNetwork,
+    server_config: &ServerConfig,
+    lock_pages: bool,
+) -> ExecutionPlan {
+    let mut plan = ExecutionPlan::new("server");
+    plan.step("Network", network.info());
+    if network.get_layers() != config.architecture.as_slice() {
+        plan.step(
+            "Layers",
+            format!(
+                "{:?} from the model, the configuration describes {:?}",
+                network.get_layers(),
+                config.architecture
+            ),
+        );
+    }
+    plan.step(
+        "Listen",
+        format!("{}:{} (NNP)", server_config.address, server_config.port),
+    );
+    plan.check(dry_run::check_port(&server_config.address, server_config.port));
+    if let Some(metrics_port) = server_config.metrics_port {
+        plan.step("Metrics", format!("{}:{}", server_config.address, metrics_port));
+        if metrics_port == server_config.port {
+            plan.problems.push(format!(
+                "The metrics port {} is also the NNP port",
+                metrics_port
+            ));
+        } else {
+            plan.check(dry_run::check_port(&server_config.address, metrics_port));
+        }
+    }
+    for endpoint in &server_config.output_endpoints {
+        match address::parse_host_port(endpoint, 8080) {
+            Ok((host, port)) => plan.step("Peer", format!("{}:{}", host, port)),
+            Err(e) => plan
+                .problems
+                .push(format!("Output endpoint '{}' is invalid: {}", endpoint, e)),
+        }
+    }
+    for (label, path) in [
+        ("Certificate", &server_config.cert_path),
+        ("Private key", &server_config.key_path),
+        ("Token file", &server_config.token_file),
+    ] {
+        if let Some(path) = path {
+            plan.step(label, path.display());
+            if !path.exists() {
+                plan.problems.push(format!("{} {} does not exist", label, path.display()));
+            }
+        }
+    }
+    if let Some(state_file) = &server_config.state_file {
+        let action = match (state_file.exists(), &server_config.model_file) {
+            (true, Some(_)) => "restored (network from --model) from",
+            (true, None) => "restored from",
+            (false, _) => "created at",
+        };
+        plan.step("State", format!("{} {}", action, state_file.display()));
+    }
+    if let Some(version) = server_config.protocol_version {
+        plan.step("Protocol", format!("v{}", version));
+        if !protocol_compat::is_supported(version) {
+            plan.problems.push(format!(
+                "Protocol version {} is not supported (expected {}..={})",
+                version,
+                protocol_compat::MIN_PROTOCOL_VERSION,
+                distributed_network::PROTOCOL_VERSION
+            ));
+        }
+    }
+    if let Some(budget) = &server_config.latency_budget {
+        plan.step("Latency", format!("{:?} per forward request", budget.budget));
+        if let Err(e) = budget.validate() {
+            plan.problems.push(e);
+        }
+    }
+    if let Some(window) = server_config.batch_window {
+        plan.step(
+            "Batching",
+            format!(
+                "up to {} requests within {}µs",
+                server_config.max_batch_size,
+                window.as_micros()
+            ),
+        );
+    }
+    if let Some(capture) = &server_config.capture {
+        plan.step(
+            "Capture",
+            format!("{:.1}% of requests into {}", capture.sample_rate * 100.0, capture.path.display()),
+        );
+        let input_size = network.input_size();
+        for column in capture.drop_inputs.iter().filter(|&&column| column >= input_size) {
+            plan.problems.push(format!(
+                "Dropped capture input {} is out of range, the network takes {} inputs",
+                column, input_size
+            ));
+        }
+    }
+    if lock_pages {
+        plan.step("Memory", "locked in RAM");
+    }
+    plan
+}
+
+pub fn run_soak(args: SoakArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🧪 Online Learning Soak Test");
+    println!("============================");
+
+    let mut network = match &args.model {
+        Some(model_path) if model_path.extension().and_then(|s| s.to_str()) == Some("bin") => {
+            NeuralNetwork::load_from_binary(model_path)?
+        }
+        Some(model_path) => NeuralNetwork::load_from_file(model_path)?,
+        None => NetworkConfig::load_from_file(&args.config)?.create_network()?,
+    };
+    network.set_online_learning(true);
+
+    let config = SoakConfig {
+        duration: Duration::from_secs(args.duration),
+        sample_interval: Duration::from_secs(args.interval),
+        max_steps: None,
+        seed: match &args.seed {
+            Some(name) => benchmark::experiment_seed(name),
+            None => rand::thread_rng().gen(),
+        },
+        max_weight_norm: args.max_weight_norm,
+        min_entropy: args.min_entropy,
+    };
+    println!("✅ Network: {}", network.info());
+    println!("   Duration: {}s, sampling every {}s", args.duration, args.interval);
+    println!("   Seed: {}", config.seed);
+
+    let report = soak::soak(&mut network, &config, |sample| {
+        println!(
+            "   [{:>8.0}s] steps {:>10}  weight norm {:>12.4}  entropy {:>6.3} bits  {}",
+            sample.elapsed_secs,
+            sample.steps,
+            sample.weight_norm,
+            sample.output_entropy,
+            if sample.finite { "ok" } else { "NON-FINITE" }
+        );
+    });
+
+    if let Some(path) = &args.report {
+        fs::write(path, serde_json::to_string_pretty(&report)?)?;
+        println!("📝 Report written to: {}", path.display());
+    }
+    match report.failure {
+        Some(failure) => Err(format!("Network destabilized: {}", failure).into()),
+        None => {
+            println!("✅ Stable for {} steps", report.steps);
+            Ok(())
+        }
+    }
+}
+
+pub fn run_retrain(args: RetrainArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔁 Scheduled Retraining");
+    println!("=======================");
+
+    let schedule: CronSchedule = args.schedule.parse()?;
+    let config = NetworkConfig::load_from_file(StagedFile::input(&args.config)?)?;
+    if config.training.split.indices_file.is_some() {
+        return Err("Retraining data grows between runs, so the split cannot come from an indices file; \
+                    remove training.split.indices_file"
+            .into());
+    }
+    fs::create_dir_all(&args.work_dir)?;
+    println!("✅ Watching: {}", args.data_dir.display());
+    println!("   Production model: {}", args.model.display());
+    println!("   Schedule: {} (UTC)", args.schedule);
+    println!(
+        "   Promotion: {:?} better by at least {}",
+        args.metric, args.min_improvement
+    );
+
+    let mut trained_on = DatasetSnapshot::default();
+    let mut run_now = args.now;
+    loop {
+        if !run_now {
+            let now = Utc::now();
+            let next = schedule
+                .next_after(now)
+                .ok_or_else(|| format!("Schedule '{}' never fires", args.schedule))?;
+            println!("⏰ Next run at {}", next.to_rfc3339());
+            std::thread::sleep((next - now).to_std().unwrap_or_default());
+        }
+        run_now = false;
+
+        let snapshot = DatasetSnapshot::of(&args.data_dir)?;
+        if snapshot.is_empty() {
+            println!("💤 No datasets in {} yet", args.data_dir.display());
+        } else if snapshot == trained_on {
+            println!("💤 No new data since the last run");
+        } else {
+            match retrain_once(&args, &config, &snapshot) {
+                Ok(()) => trained_on = snapshot,
+                // A failed run is retried at the next scheduled time
+                Err(e) => println!("❌ Retraining failed: {}", e),
+            }
+        }
+        if args.once {
+            return Ok(());
+        }
+    }
+}
+
+/// Train a candidate on every dataset of `snapshot` and promote it if it beats production
+fn retrain_once(
+    args: &RetrainArgs,
+    config: &NetworkConfig,
+    snapshot: &DatasetSnapshot,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = snapshot.load(&config.categorical)?;
+    println!(
+        "\n📚 Retraining on {} samples from {} files",
+        data.inputs.len(),
+        snapshot.files.len()
+    );
+    let dataset = args.work_dir.join("dataset.json");
+    data.save_to_json(&dataset)?;
+    let extension = args.model.extension().and_then(|s| s.to_str()).unwrap_or("json");
+    let candidate = args.work_dir.join(format!("candidate.{}", extension));
+
+    run_training(TrainArgs {
+        config: args.config.clone(),
+        data: dataset.clone(),
+        output: Some(candidate.clone()),
+        epochs: args.epochs,
+        init_from: None,
+        tags: vec![format!("retrained_from={}", args.data_dir.display())],
+        verbose: false,
+        plots_dir: None,
+        plot_format: ImageFormat::Svg,
+        boundary_animation: None,
+        weight_animation: None,
+        max_duration: None,
+        dry_run: false,
+    })?;
+
+    // Both models are scored on the validation samples the candidate did not train on
+    let validation = load_validation_data(&dataset, Some(config))?;
+    let evaluate = |model: &Path| -> Result<Evaluation, Box<dyn std::error::Error>> {
+        let mut predict = with_population_coding(load_predictor(model)?, config)?;
+        let outputs: Vec<Vec<f64>> = validation.inputs.iter().map(|input| predict(input)).collect();
+        Ok(Evaluation::compute(
+            outputs
+                .iter()
+                .zip(&validation.targets)
+                .map(|(output, target)| (output.as_slice(), target.as_slice())),
+        ))
+    };
+    let production = if args.model.exists() {
+        Some(evaluate(&args.model)?)
+    } else {
+        None
+    };
+    let decision = PromotionDecision::decide(
+        args.metric,
+        args.min_improvement,
+        production.as_ref(),
+        &evaluate(&candidate)?,
+    );
+    match decision.production {
+        Some(production) => println!(
+            "⚖️  Validation {:?} on {} samples: production {:.6}, candidate {:.6}",
+            decision.metric, validation.inputs.len(), production, decision.candidate
+        ),
+        None => println!(
+            "⚖️  Validation {:?} on {} samples: candidate {:.6}, no production model yet",
+            decision.metric, validation.inputs.len(), decision.candidate
+        ),
+    }
+    if !decision.promote {
+        println!("🚫 Candidate kept out of production: {}", candidate.display());
+        return Ok(());
+    }
+
+    if args.model.exists() {
+        let previous = args.model.with_extension(format!("previous.{}", extension));
+        fs::copy(&args.model, &previous)?;
+        println!("🗄️  Previous model kept as: {}", previous.display());
+    }
+    save_model(&load_network(&candidate)?, &args.model)?;
+    println!("🚀 Promoted candidate to production");
+    Ok(())
+}
+
+pub fn run_lottery(args: LotteryArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🎟️  Lottery Ticket Search");
+    println!("========================");
+
+    let config = NetworkConfig::load_from_file(StagedFile::input(&args.config)?)?;
+    let data_file = StagedFile::input(&args.data)?;
+    let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(&data_file, &config.categorical)?
+    } else {
+        TrainingData::load_from_json(&data_file)?
+    };
+    let (input_coder, output_coder) = (config.input_coder()?, config.output_coder()?);
+    for input in &mut data.inputs {
+        *input = input_coder.encode(input);
+    }
+    for target in &mut data.targets {
+        *target = output_coder.encode(target);
+    }
+    let data_split = DataSplit::load_or_compute(
+        &data.targets,
+        config.training.validation_split,
+        config.training.test_split,
+        &config.training.split,
+    )?;
+    let train_inputs = split::select(&data.inputs, &data_split.train);
+    let train_targets = split::select(&data.targets, &data_split.train);
+    let (val_inputs, val_targets) = if data_split.validation.is_empty() {
+        (train_inputs.clone(), train_targets.clone())
+    } else {
+        (
+            split::select(&data.inputs, &data_split.validation),
+            split::select(&data.targets, &data_split.validation),
+        )
+    };
+
+    let network = config.create_network()?;
+    println!("✅ Created network: {}", network.info());
+    println!(
+        "   {} training and {} validation samples, {} rounds of {} epochs",
+        train_inputs.len(),
+        val_inputs.len(),
+        args.rounds + 1,
+        args.epochs
+    );
+    let lottery = LotteryConfig {
+        rounds: args.rounds,
+        prune_fraction: args.prune,
+        epochs: args.epochs,
+        rewind_epoch: args.rewind_epoch,
+    };
+    let (mut ticket, _, report) = lottery::find_ticket(
+        &network,
+        (&train_inputs, &train_targets),
+        (&val_inputs, &val_targets),
+        &lottery,
+        |round| {
+            println!(
+                "   Round {}: {} weights ({:.1}% sparse), val accuracy {:.2}%",
+                round.round,
+                round.weights,
+                (1.0 - round.density) * 100.0,
+                round.validation_accuracy * 100.0
+            )
+        },
+    )?;
+
+    let report_text = match args.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Csv => report.to_csv(),
+        OutputFormat::Plain => report.to_text(),
+    };
+    match &args.report {
+        Some(report_path) => {
+            let report_file = StagedFile::output(report_path)?;
+            fs::write(&report_file, report_text)?;
+            report_file.publish()?;
+            println!("📄 Report written to: {}", report_path.display());
+        }
+        None => print!("\n{}", report_text),
+    }
+    if let Some(output) = &args.output {
+        let metadata = ticket.metadata_mut();
+        metadata.dataset = Some(args.data.display().to_string());
+        metadata.tags.insert(
+            "sparsity".to_string(),
+            format!("{:.4}", 1.0 - report.rounds.last().map_or(1.0, |r| r.density)),
+        );
+        save_model(&ticket, output)?;
+    }
+    Ok(())
+}
+
+pub fn run_sweep(args: SweepArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🧪 Hyperparameter Sweep");
+    println!("======================");
+
+    let sweep = SweepConfig::load(&args.file)?;
+    let runs = sweep.expand()?;
+    let cores = std::thread::available_parallelism().map_or(1, |cores| cores.get());
+    let parallel = args.parallel.clamp(1, runs.len());
+    let threads = sweep::threads_per_run(parallel, cores, sweep.threads_per_run);
+    println!("✅ Loaded {} runs from: {}", runs.len(), args.file.display());
+    println!(
+        "   {} at a time with {} threads each on {} cores",
+        parallel, threads, cores
+    );
+    if parallel * threads > cores {
+        println!(
+            "⚠️  {} threads on {} cores: runs will slow each other down",
+            parallel * threads,
+            cores
+        );
+    }
+    if let Some(dir) = &args.models_dir {
+        fs::create_dir_all(dir)?;
+    }
+
+    // Remote files are downloaded once and shared by every run
+    let config_file = StagedFile::input(&sweep.config)?;
+    let data_file = StagedFile::input(&sweep.data)?;
+    let options = SweepOptions {
+        config: config_file.to_path_buf(),
+        data: data_file.to_path_buf(),
+        epochs: sweep.epochs,
+        base: ConfigOverrides::active(),
+        models_dir: args.models_dir.clone(),
+    };
+    println!("\n🚀 Training {} runs of {} epochs...", runs.len(), sweep.epochs);
+    let report = sweep::run_sweep(&args.file, &options, &runs, parallel, threads, |result| {
+        match &result.error {
+            Some(error) => println!("   ❌ {}: {}", result.name, error),
+            None => println!(
+                "   {} {}: {} after {} epochs, val MSE {:.6} in {:.2}s",
+                if result.is_trained() { "✅" } else { "⚠️ " },
+                result.name,
+                result.status,
+                result.epochs,
+                result.validation_mse,
+                result.seconds
+            ),
+        }
+    })?;
+
+    let report_text = match args.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Csv => report.to_csv(),
+        OutputFormat::Plain => report.to_text(),
+    };
+    match &args.report {
+        Some(report_path) => {
+            let report_file = StagedFile::output(report_path)?;
+            fs::write(&report_file, report_text)?;
+            report_file.publish()?;
+            println!("📄 Report written to: {}", report_path.display());
+        }
+        None => print!("\n{}", report_text),
+    }
+    match report.best() {
+        Some(best) => {
+            println!("🏆 Best run: {} (val MSE {:.6})", best.name, best.validation_mse);
+            Ok(())
+        }
+        None => Err("No run of the sweep trained".into()),
+    }
+}
+
+pub fn run_difftest(args: DifftestArgs) -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔬 Differential Training Test");
+    println!("============================");
+
+    let config_b_path = args.config_b.clone().unwrap_or_else(|| args.config_a.clone());
+    let config_a = NetworkConfig::load_from_file(StagedFile::input(&args.config_a)?)?;
+    let config_b = NetworkConfig::load_from_file(StagedFile::input(&config_b_path)?)?;
+    let label = |path: &Path, precision| {
+        let name = path.file_name().map_or(path.display().to_string(), |name| {
+            name.to_string_lossy().into_owned()
+        });
+        format!("{} ({})", name, precision)
+    };
+    let side_a = DifferentialSide {
+        label: label(&args.config_a, args.precision_a),
+        network: config_a.create_network()?,
+        precision: args.precision_a,
+    };
+    let side_b = DifferentialSide {
+        label: label(&config_b_path, args.precision_b),
+        network: config_b.create_network()?,
+        precision: args.precision_b,
+    };
+    println!("✅ A: {} {}", side_a.label, side_a.network.info());
+    println!("✅ B: {} {}", side_b.label, side_b.network.info());
+
+    // Both sides see the data as side A's configuration encodes it
+    let data_file = StagedFile::input(&args.data)?;
+    let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(&data_file, &config_a.categorical)?
+    } else {
+        TrainingData::load_from_json(&data_file)?
+    };
+    let (input_coder, output_coder) = (config_a.input_coder()?, config_a.output_coder()?);
+    for input in &mut data.inputs {
+        *input = input_coder.encode(input);
+    }
+    for target in &mut data.targets {
+        *target = output_coder.encode(target);
+    }
+    for side in [&side_a, &side_b] {
+        let problems = dry_run::check_dataset(
+            &data.inputs,
+            &data.targets,
+            side.network.input_size(),
+            side.network.get_layers().last().copied().unwrap_or(0),
+        );
+        if let Some(problem) = problems.first() {
+            return Err(format!("{}: {}", side.label, problem).into());
+        }
+    }
+    let seed = benchmark::experiment_seed(&args.seed);
+    println!(
+        "\n🚀 Training both on {} samples for {} epochs...",
+        data.inputs.len(),
+        args.epochs
+    );
+    let report = differential::run_differential(
+        side_a,
+        side_b,
+        &data.inputs,
+        &data.targets,
+        args.epochs,
+        seed,
+        args.tolerance,
+    )?;
+
+    if let Some(dir) = &args.plots_dir {
+        for path in report.write_plots(dir, args.plot_format)? {
+            println!("📈 Plot written to: {}", path.display());
+        }
+    }
+    let report_text = match args.format {
+        OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+        OutputFormat::Csv => report.to_csv(),
+        OutputFormat::Plain => report.to_text(),
+    };
+    match &args.report {
+        Some(report_path) => {
+            let report_file = StagedFile::output(report_path)?;
+            fs::write(&report_file, report_text)?;
+            report_file.publish()?;
+            println!("📄 Report written to: {}", report_path.display());
+        }
+        None => print!("\n{}", report_text),
+    }
+    match report.first_divergent_epoch {
+        Some(epoch) => Err(format!(
+            "Outputs diverged by more than {} from epoch {}",
+            args.tolerance, epoch
+        )
+        .into()),
+        None => Ok(()),
+    }
+}
+
+pub fn run_config(command: ConfigCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ConfigCommand::Validate {
+            config,
+            no_build,
+            strict,
+        } => {
+            let overrides = ConfigOverrides::active();
+            let content = overrides.apply(&fs::read_to_string(StagedFile::input(&config)?)?)?;
+            let (_, mut report) = config_schema::validate_config(&content, !no_build);
+            overrides.annotate(&mut report);
+            print!("{}", report.to_text(&config.display().to_string(), &content));
+            if !report.is_valid() || (strict && report.warnings().next().is_some()) {
+                return Err(format!("{} failed validation", config.display()).into());
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_checkpoint(command: CheckpointCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        CheckpointCommand::Compact { dir } => {
+            let mut store = CheckpointStore::open(&dir, 0.0)?;
+            let deltas = store.num_deltas();
+            store.compact()?;
+            println!(
+                "🗜️  Compacted {} delta(s) into {}",
+                deltas,
+                dir.join("base.bin").display()
+            );
+        }
+        CheckpointCommand::Export { dir, output } => {
+            let network = CheckpointStore::open(&dir, 0.0)?.load()?;
+            match output.extension().and_then(|s| s.to_str()) {
+                Some("bin") => network.save_to_binary(&output)?,
+                Some("mmap") => network.save_to_mmap(&output)?,
+                _ => network.save_to_file(&output)?,
+            }
+            println!("💾 Exported latest checkpoint to: {}", output.display());
+        }
+    }
+    Ok(())
+}
+
+pub fn run_model(command: ModelCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ModelCommand::Info { model } => {
+            let network = load_network(&model)?;
+            println!("📦 Model: {}", model.display());
+            println!("   {}", network.info());
+            println!("   Parameters: {}", network.num_parameters());
+            let metadata = network.metadata();
+            if metadata.is_empty() {
+                println!("   No metadata saved with this model");
+            }
+            for line in metadata.lines() {
+                println!("   {}", line);
+            }
+        }
+        ModelCommand::Viz {
+            model,
+            output,
+            width,
+            height,
+            max_neurons,
+        } => {
+            let network = load_network(&model)?;
+            let options = VisualizeOptions {
+                width,
+                height,
+                max_neurons,
+            };
+            visualize::render(&network, &output, &options)?;
+            println!("🖼️  Drew {} to: {}", network.info(), output.display());
+        }
+        ModelCommand::Prune {
+            model,
+            data,
+            config,
+            compression,
+            epochs,
+            min_neurons,
+            output,
+        } => {
+            let network = load_network(&model)?;
+            let config = match &config {
+                Some(config_path) => Some(NetworkConfig::load_from_file(StagedFile::input(
+                    config_path,
+                )?)?),
+                None => None,
+            };
+            let categorical = config.as_ref().map_or(&[][..], |c| &c.categorical[..]);
+            let data_file = StagedFile::input(&data)?;
+            let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+                TrainingData::load_from_csv_with_categories(&data_file, categorical)?
+            } else {
+                TrainingData::load_from_json(&data_file)?
+            };
+            // The network sees and fine-tunes on population-coded values
+            if let Some(config) = &config {
+                let (input_coder, output_coder) = (config.input_coder()?, config.output_coder()?);
+                for input in &mut data.inputs {
+                    *input = input_coder.encode(input);
+                }
+                for target in &mut data.targets {
+                    *target = output_coder.encode(target);
+                }
+            }
+
+            let prune = PruneConfig {
+                compression,
+                fine_tune_epochs: epochs,
+                min_neurons,
+            };
+            let (mut pruned, report) =
+                pruning::prune_neurons(&network, &data.inputs, &data.targets, &prune)?;
+            pruned
+                .metadata_mut()
+                .tags
+                .insert("pruned_from".to_string(), model.display().to_string());
+            print!("{}", report.to_text());
+            save_model(&pruned, &output)?;
+        }
+    }
+    Ok(())
+}
+
+pub fn run_analyze(command: AnalyzeCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        AnalyzeCommand::Project {
+            model,
+            data,
+            config,
+            layer,
+            method,
+            perplexity,
+            iterations,
+            seed,
+            output,
+        } => {
+            let network = load_network(&model)?;
+            let (categorical, input_coder) = match &config {
+                Some(config_path) => {
+                    let config = NetworkConfig::load_from_file(StagedFile::input(config_path)?)?;
+                    let input_coder = config.input_coder()?;
+                    (config.categorical, Some(input_coder))
+                }
+                None => (Vec::new(), None),
+            };
+            let data_file = StagedFile::input(&data)?;
+            let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+                TrainingData::load_from_csv_with_categories(&data_file, &categorical)?
+            } else {
+                TrainingData::load_from_json(&data_file)?
+            };
+            if let Some(coder) = &input_coder {
+                for input in &mut data.inputs {
+                    *input = coder.encode(input);
+                }
+            }
+
+            let layer = layer.unwrap_or(network.get_layers().len().saturating_sub(2));
+            let activations = projection::layer_activations(&network, &data.inputs, layer)?;
+            let classes: Vec<usize> = data.targets.iter().map(|t| projection::class_of(t)).collect();
+            let points = match method {
+                ProjectionMethod::Pca => {
+                    let (pca, points) = Pca::project(&activations);
+                    let explained: Vec<String> = pca
+                        .explained_variance
+                        .iter()
+                        .map(|share| format!("{:.1}%", share * 100.0))
+                        .collect();
+                    println!("📐 Principal components explain {}", explained.join(" + "));
+                    points
+                }
+                #[cfg(feature = "tsne")]
+                ProjectionMethod::Tsne => {
+                    println!(
+                        "📐 Running t-SNE on {} samples (perplexity {})...",
+                        activations.len(),
+                        perplexity
+                    );
+                    projection::tsne(&activations, perplexity, iterations, seed)
+                }
+            };
+            #[cfg(not(feature = "tsne"))]
+            let _ = (perplexity, iterations, seed);
+
+            let title = format!("Layer {} ({:?})", layer, method);
+            projection::write_scatter(&output, &title, &points, &classes)?;
+            println!(
+                "✅ Projected layer {} of {} over {} samples to: {}",
+                layer,
+                model.display(),
+                points.len(),
+                output.display()
+            );
+        }
+        AnalyzeCommand::Boundary {
+            model,
+            data,
+            resolution,
+            output,
+        } => {
+            let network = load_network(&model)?;
+            let samples = match &data {
+                Some(data_path) => {
+                    let data_file = StagedFile::input(data_path)?;
+                    let data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+                        TrainingData::load_from_csv_with_categories(&data_file, &[])?
+                    } else {
+                        TrainingData::load_from_json(&data_file)?
+                    };
+                    labelled_samples(&data.inputs, &data.targets)
+                }
+                None => Vec::new(),
+            };
+            let inputs: Vec<Vec<f64>> = samples.iter().map(|(input, _)| input.clone()).collect();
+            let grid = DecisionGrid::around(&inputs, resolution);
+            decision_boundary::render(&network, &grid, &samples, &output)?;
+            println!(
+                "✅ Drew the decision surface of {} over x {:.2}..{:.2}, y {:.2}..{:.2} to: {}",
+                model.display(),
+                grid.x_range.0,
+                grid.x_range.1,
+                grid.y_range.0,
+                grid.y_range.1,
+                output.display()
+            );
+        }
+        AnalyzeCommand::Importance {
+            model,
+            data,
+            config,
+            repeats,
+            seed,
+            format,
+            output,
+        } => {
+            let mut predict = load_predictor(&model)?;
+            let config = match &config {
+                Some(config_path) => {
+                    let config = NetworkConfig::load_from_file(StagedFile::input(config_path)?)?;
+                    predict = with_population_coding(predict, &config)?;
+                    Some(config)
+                }
+                None => None,
+            };
+            let data = load_validation_data(&data, config.as_ref())?;
+
+            let report = feature_importance::permutation_importance(
+                |input| predict(input),
+                &data.inputs,
+                &data.targets,
+                repeats,
+                seed,
+            );
+            let report_text = match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+                OutputFormat::Csv => report.to_csv(),
+                OutputFormat::Plain => report.to_text(),
+            };
+            match output {
+                Some(output_path) => {
+                    let report_file = StagedFile::output(&output_path)?;
+                    fs::write(&report_file, report_text)?;
+                    report_file.publish()?;
+                    println!(
+                        "✅ Ranked {} features of {} on {} samples: report written to {}",
+                        report.features.len(),
+                        model.display(),
+                        report.samples,
+                        output_path.display()
+                    );
+                }
+                None => print!("{}", report_text),
+            }
+        }
+        AnalyzeCommand::Thresholds {
+            model,
+            data,
+            config,
+            steps,
+            min_precision,
+            save,
+            format,
+            output,
+        } => {
+            if save && model.extension().and_then(|s| s.to_str()) == Some("mmap") {
+                return Err("Memory-mapped models carry no metadata to save thresholds in".into());
+            }
+            let mut predict = load_predictor(&model)?;
+            let config = match &config {
+                Some(config_path) => {
+                    let config = NetworkConfig::load_from_file(StagedFile::input(config_path)?)?;
+                    predict = with_population_coding(predict, &config)?;
+                    Some(config)
+                }
+                None => None,
+            };
+            let data = load_validation_data(&data, config.as_ref())?;
+
+            let outputs: Vec<Vec<f64>> = data.inputs.iter().map(|input| predict(input)).collect();
+            let report =
+                thresholds::tune_thresholds(&outputs, &data.targets, steps, min_precision);
+            let report_text = match format {
+                OutputFormat::Json => serde_json::to_string_pretty(&report)?,
+                OutputFormat::Csv => report.to_csv(),
+                OutputFormat::Plain => report.to_text(),
+            };
+            match output {
+                Some(output_path) => {
+                    let report_file = StagedFile::output(&output_path)?;
+                    fs::write(&report_file, report_text)?;
+                    report_file.publish()?;
+                    println!(
+                        "✅ Tuned {} thresholds of {} on {} samples: report written to {}",
+                        report.outputs.len(),
+                        model.display(),
+                        report.samples,
+                        output_path.display()
+                    );
+                }
+                None => print!("{}", report_text),
+            }
+
+            if save {
+                let mut network = load_network(&model)?;
+                network.metadata_mut().thresholds = report.thresholds();
+                save_model(&network, &model)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn run_data(command: DataCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        DataCommand::Generate {
+            kind,
+            samples,
+            noise,
+            seed,
+            bits,
+            shape,
+            classes,
+            dims,
+            episode_length,
+            output,
+        } => {
+            let seed = match &seed {
+                Some(name) => benchmark::experiment_seed(name),
+                None => rand::thread_rng().gen(),
+            };
+            let data = match kind {
+                DatasetKind::Xor => datasets::xor(bits, samples, noise, seed),
+                DatasetKind::Spiral => {
+                    datasets::spirals(samples, shape.unwrap_or(2.0), noise, seed)
+                }
+                DatasetKind::Circles => {
+                    datasets::circles(samples, shape.unwrap_or(0.5), noise, seed)
+                }
+                DatasetKind::Blobs => datasets::blobs(samples, classes, dims, noise, seed),
+                DatasetKind::Sine => datasets::sine(samples, shape.unwrap_or(1.0), noise, seed),
+                DatasetKind::TemporalParity => datasets::temporal_parity(samples, seed),
+            };
+
+            let data_file = StagedFile::output(&output)?;
+            if let Some(length) = episode_length {
+                let sequences = SequenceData::from_samples(data, length);
+                sequences.save(&data_file)?;
+                data_file.publish()?;
+                println!(
+                    "✅ Wrote {} {:?} episodes of up to {} steps (seed {}) to: {}",
+                    sequences.episodes.len(),
+                    kind,
+                    length,
+                    seed,
+                    output.display()
+                );
+                return Ok(());
+            }
+            if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+                data.save_to_csv(&data_file)?;
+            } else {
+                data.save_to_json(&data_file)?;
+            }
+            data_file.publish()?;
+            println!(
+                "✅ Wrote {} {:?} samples (seed {}) to: {}",
+                data.inputs.len(),
+                kind,
+                seed,
+                output.display()
+            );
+        }
+    }
+    Ok(())
+}
+
+pub fn run_token(command: TokenCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        TokenCommand::Issue { name, scopes, file } => {
+            let mut store = TokenStore::load(&file)?;
+            let (token, secret) = store.issue(&name, &scopes)?;
+            store.save(&file)?;
+            println!("🔑 Issued token {} ({}) with scopes {:?}", token.id, name, token.scopes);
+            println!("   {}", secret);
+            println!("   Store it now: only its hash is kept in {}", file.display());
+        }
+        TokenCommand::List { file } => {
+            let store = TokenStore::load(&file)?;
+            if store.tokens().is_empty() {
+                println!("No tokens in {}", file.display());
+            }
+            for token in store.tokens() {
+                let status = match &token.revoked_at {
+                    Some(at) => format!("revoked {}", at),
+                    None => "active".to_string(),
+                };
+                println!(
+                    "{}  {:<20} {:<32} {} ({})",
+                    token.id,
+                    token.name,
+                    format!("{:?}", token.scopes),
+                    token.created_at,
+                    status
+                );
+            }
+        }
+        TokenCommand::Revoke { token, file } => {
+            let mut store = TokenStore::load(&file)?;
+            let revoked = store.revoke(&token)?.clone();
+            store.save(&file)?;
+            println!("🚫 Revoked token {} ({})", revoked.id, revoked.name);
+        }
+    }
+    Ok(())
+}
+
+pub fn run_runs(command: RunsCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        RunsCommand::List { db, limit } => print_runs(&RunStore::open(&db)?.runs(limit)?),
+        RunsCommand::Query { filter, db } => print_runs(&RunStore::open(&db)?.query(&filter)?),
+        RunsCommand::Show { id, db } => {
+            let store = RunStore::open(&db)?;
+            let run = store
+                .run(id)?
+                .ok_or_else(|| format!("No run {} in {}", id, db.display()))?;
+            print_runs(std::slice::from_ref(&run));
+            println!("\n⚙️  Hyperparameters:");
+            match serde_json::from_str::<serde_json::Value>(&run.hyperparameters) {
+                Ok(value) => println!("{}", serde_json::to_string_pretty(&value)?),
+                Err(_) => println!("{}", run.hyperparameters),
+            }
+            println!("\n📈 Epochs:");
+            for record in store.epochs(id)? {
+                let val = record
+                    .val_error
+                    .map_or_else(String::new, |e| format!(", val error {:.6}", e));
+                println!(
+                    "   {:>5}: train error {:.6}{} ({:.1}s)",
+                    record.epoch, record.train_error, val, record.seconds
+                );
+            }
+            let resources = store.resources(id)?;
+            if !resources.is_empty() {
+                println!("\n🖥️  Resources:");
+                let mib = |bytes: Option<u64>| {
+                    bytes.map_or_else(
+                        || "-".to_string(),
+                        |bytes| format!("{:.1} MiB", bytes as f64 / 1048576.0),
+                    )
+                };
+                for record in resources {
+                    println!(
+                        "   {:>5}: CPU {:.0}%, RSS {}, peak RSS {}, {} threads ({:.1}s)",
+                        record.epoch,
+                        record.cpu_percent,
+                        mib(record.rss_bytes),
+                        mib(record.peak_rss_bytes),
+                        record.threads.map_or_else(|| "-".to_string(), |t| t.to_string()),
+                        record.seconds
+                    );
+                }
+            }
+            println!("\n💾 Checkpoints:");
+            for (epoch, path) in store.checkpoints(id)? {
+                println!("   {:>5}: {}", epoch, path);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_runs(runs: &[crate::telemetry::RunSummary]) {
+    if runs.is_empty() {
+        println!("No matching runs");
+        return;
+    }
+    println!("   id  status          epochs  train_error    val_error       lr  batch  layers       started");
+    let error = |e: Option<f64>| e.map_or_else(|| "-".to_string(), |e| format!("{:.6}", e));
+    for run in runs {
+        println!(
+            "{:>5}  {:<14} {:>7} {:>12} {:>12} {:>8} {:>6}  {:<12} {}",
+            run.id,
+            run.status,
+            run.epochs,
+            error(run.train_error),
+            error(run.val_error),
+            run.learning_rate,
+            run.batch_size.map_or_else(|| "-".to_string(), |size| size.to_string()),
+            run.architecture,
+            run.started_at
+        );
+    }
+}
+
+pub fn run_top(nodes: Vec<String>, interval_ms: u64) -> Result<(), Box<dyn std::error::Error>> {
+    top::run(nodes, Duration::from_millis(interval_ms.max(100)))
+}
+
+pub fn run_up(file: PathBuf) -> Result<(), Box<dyn std::error::Error>> {
+    let cluster = ClusterConfig::load(&file)
+        .map_err(|e| format!("Failed to load {}: {}", file.display(), e))?;
+    // The servers are installed next to this binary
+    let bin_dir = std::env::current_exe()?
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let plan = cluster.plan(&bin_dir)?;
+    println!("🚀 Starting {} processes from {}", plan.len(), file.display());
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(launcher::run(plan))
+}
+
+pub fn run_plan(
+    file: PathBuf,
+    rate: f64,
+    output: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cluster = ClusterConfig::load(&file)
+        .map_err(|e| format!("Failed to load {}: {}", file.display(), e))?;
+    let bin_dir = std::env::current_exe()?
+        .parent()
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    let plan = DeploymentPlan::build(&cluster, &file, &bin_dir, rate, |node| {
+        let network = match &node.model {
+            Some(model) => load_network(model),
+            None => NetworkConfig::load_from_file(&node.config)
+                .and_then(|config| config.create_network()),
+        };
+        network
+            .map(|network| NodeShape::of(&network))
+            .map_err(|e| e.to_string())
+    })?;
+    let json = plan.to_json()?;
+    match &output {
+        Some(path) => {
+            fs::write(path, json)?;
+            println!("🗺️  Plan of {} written to {}", file.display(), path.display());
+        }
+        None => println!("{}", json),
+    }
+    if !plan.problems.is_empty() {
+        for problem in &plan.problems {
+            eprintln!("❌ {}", problem);
+        }
+        return Err(format!("{} has {} problem(s)", file.display(), plan.problems.len()).into());
+    }
+    Ok(())
+}
+
+pub fn run_completions(shell: Shell) -> Result<(), Box<dyn std::error::Error>> {
+    // Complete the name the binary was installed under
+    let bin_name = std::env::args()
+        .next()
+        .as_deref()
+        .and_then(|arg| PathBuf::from(arg).file_name()?.to_str().map(str::to_string))
+        .unwrap_or_else(|| "benny".to_string());
+    clap_complete::generate(shell, &mut Cli::command(), bin_name, &mut io::stdout());
+    Ok(())
+}
+
+pub fn run_examples(workflow: Option<Workflow>) -> Result<(), Box<dyn std::error::Error>> {
+    let workflows = match workflow {
+        Some(workflow) => vec![workflow],
+        None => Workflow::ALL.to_vec(),
+    };
+    for workflow in workflows {
+        println!("📘 {}", workflow.title());
+        for (description, command) in workflow.examples() {
+            println!("   # {}", description);
+            println!("   {}", command);
+        }
+        println!();
+    }
+    Ok(())
+}
+
+pub fn run_protocol(command: ProtocolCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        ProtocolCommand::Dump {
+            listen,
+            target,
+            output,
+            lua,
+        } => {
+            let (listen_host, listen_port) = address::parse_host_port(&listen, 9090)?;
+            let (target_host, target_port) = address::parse_host_port(&target, 8080)?;
+
+            if let Some(lua_path) = lua {
+                fs::write(&lua_path, protocol_dump::lua_dissector())?;
+                println!("🦈 Wrote Wireshark dissector to {}", lua_path.display());
+            }
+
+            let proxy = protocol_dump::ProtocolDumpProxy::new(&target_host, target_port, &output)?;
+            println!("📼 Writing capture to {}", output.display());
+            println!("   Point clients at {} instead of {}", listen, target);
+
+            let rt = tokio::runtime::Runtime::new()?;
+            rt.block_on(proxy.run(&listen_host, listen_port))?;
+        }
+        ProtocolCommand::Ping { target, timeout_ms } => {
+            let (host, port) = address::parse_host_port(&target, 8080)?;
+            let rt = tokio::runtime::Runtime::new()?;
+            let report = rt.block_on(distributed_network::ping(
+                &host,
+                port,
+                AddressPreference::System,
+                Duration::from_millis(timeout_ms),
+            ))
+            .map_err(|e| format!("Ping to {} failed: {:?}", target, e))?;
+            for component in &report.components {
+                let mark = if component.ready { "✅" } else { "❌" };
+                println!("{} {}: {}", mark, component.name, component.detail);
+            }
+            if !report.is_ready() {
+                return Err(format!("{} is not ready", target).into());
+            }
+            println!("🟢 {} is ready", target);
+        }
+        ProtocolCommand::Send {
+            to,
+            kind,
+            data,
+            layer,
+            ack,
+            handshake,
+            namespace,
+            wait_ms,
+        } => {
+            let (host, port) = address::parse_host_port(&to, 8080)?;
+            if let Some(namespace) = &namespace {
+                distributed_network::check_namespace(namespace)?;
+            }
+            let mut message = nnp_tool::build_message(kind, layer, &data, 1)?;
+            if ack {
+                message.flags |= distributed_network::message_flags::ACK_REQUIRED;
+            }
+            let mut messages = Vec::new();
+            if handshake || namespace.is_some() {
+                messages.push(nnp_tool::handshake(Uuid::new_v4(), data.len(), namespace));
+            }
+            messages.push(message);
+            for message in &messages {
+                println!("📤 {}", nnp_tool::describe(message));
+            }
+
+            let rt = tokio::runtime::Runtime::new()?;
+            let replies = rt
+                .block_on(nnp_tool::send(
+                    &host,
+                    port,
+                    AddressPreference::System,
+                    &messages,
+                    Duration::from_millis(wait_ms),
+                    |reply| println!("📥 {}", nnp_tool::describe(reply)),
+                ))
+                .map_err(|e| format!("Sending to {} failed: {:?}", to, e))?;
+            println!("✅ Sent {} message(s), received {} replies", messages.len(), replies);
+        }
+        ProtocolCommand::Listen { port, address } => {
+            let rt = tokio::runtime::Runtime::new()?;
+            let listener = rt.block_on(nnp_tool::bind(&address, port))?;
+            let port = listener.local_port().unwrap_or(port);
+            println!(
+                "👂 Listening for NNP on {}",
+                transport::display_address(&address, port)
+            );
+            rt.block_on(nnp_tool::listen(listener, Uuid::new_v4(), |peer, message| {
+                println!("📥 {} {}", peer, nnp_tool::describe(message))
+            }))
+            .map_err(|e| format!("Listener failed: {:?}", e))?;
+        }
+        ProtocolCommand::Spec { output } => {
+            let spec = protocol_spec::render_markdown();
+            match output {
+                Some(path) => {
+                    fs::write(&path, spec)?;
+                    println!("📄 Wrote protocol specification to {}", path.display());
+                }
+                None => print!("{}", spec),
+            }
+        }
+        ProtocolCommand::Fixtures { output } => {
+            let count = protocol_conformance::write_fixtures(&output)?;
+            println!("🧾 Wrote {} NNP fixtures to {}", count, output.display());
+        }
+        ProtocolCommand::Conformance { dir } => {
+            let results = protocol_conformance::check_dir(&dir)?;
+            for result in &results {
+                if result.passed() {
+                    println!("✅ {}", result.name);
+                }
+                for failure in &result.failures {
+                    println!("❌ {}: {}", result.name, failure);
+                }
+            }
+            let failed = results.iter().filter(|result| !result.passed()).count();
+            if failed > 0 {
+                return Err(format!("{} of {} fixtures do not conform", failed, results.len()).into());
+            }
+            println!("🟢 All {} fixtures conform", results.len());
+        }
+        ProtocolCommand::Client { output } => {
+            let source = python_client::generate();
+            match output {
+                Some(path) => {
+                    fs::write(&path, source)?;
+                    println!("🐍 Wrote Python NNP client to {}", path.display());
+                }
+                None => print!("{}", source),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_duration_stops_training_and_saves_the_model() {
+        let dir = std::env::temp_dir().join(format!("benny-max-duration-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (config_path, data_path) = (dir.join("config.toml"), dir.join("data.json"));
+        let (model_path, db_path) = (dir.join("model.json"), dir.join("runs.db"));
+
+        let mut config = NetworkConfig::default();
+        config.training.telemetry_db = Some(db_path.clone());
+        // Only the time budget may end the run
+        config.training.early_stop_patience = 1_000_000_000;
+        config.save_to_file(&config_path).unwrap();
+        TrainingData {
+            inputs: vec![vec![0.0, 0.0], vec![0.0, 1.0], vec![1.0, 0.0], vec![1.0, 1.0]],
+            targets: vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]],
+        }
+        .save_to_json(&data_path)
+        .unwrap();
+
+        let epochs = 100_000_000;
+        run_training(TrainArgs {
+            config: config_path,
+            data: data_path,
+            output: Some(model_path.clone()),
+            epochs,
+            init_from: None,
+            tags: Vec::new(),
+            verbose: false,
+            plots_dir: None,
+            plot_format: ImageFormat::Svg,
+            boundary_animation: None,
+            weight_animation: None,
+            max_duration: Some(Duration::from_millis(200)),
+            dry_run: false,
+        })
+        .unwrap();
+
+        let run = RunStore::open(&db_path).unwrap().runs(1).unwrap().remove(0);
+        let saved = model_path.exists();
+        let _ = fs::remove_dir_all(&dir);
+        assert_eq!(run.status, "time_limit");
+        assert!(run.epochs < epochs as i64);
+        assert!(saved);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::address::BindOptions;
+use crate::distributed_network::{DistributedNetwork, ProtocolError};
+use crate::metrics::MetricsServer;
+use crate::neural_network::NeuralNetwork;
+use crate::server::{NetworkServer, ServerConfig};
+use log::{error, info};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
+
+/// An `(inputs, targets)` pair queued for online training
+type Sample = (Vec<f64>, Vec<f64>);
+
+/// How samples handed to `RuntimeHandle::train` are trained on
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnlineTrainingConfig {
+    /// Samples per `train_batch` step
+    pub batch_size: usize,
+    /// Longest a partial batch waits for more samples before it is trained on
+    pub max_wait: Duration,
+    /// Samples waiting to be trained on; `train` refuses more
+    pub queue_capacity: usize,
+}
+
+impl Default for OnlineTrainingConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 32,
+            max_wait: Duration::from_secs(1),
+            queue_capacity: 1024,
+        }
+    }
+}
+
+impl OnlineTrainingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.batch_size == 0 {
+            return Err("Training batch size must be positive".to_string());
+        }
+        if self.queue_capacity == 0 {
+            return Err("Training queue capacity must be positive".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Progress of the online training loop
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TrainingStats {
+    pub batches: u64,
+    pub samples: u64,
+    /// Error of the most recent batch
+    pub last_loss: Option<f64>,
+}
+
+/// Configuration of a `BennyRuntime`, from `BennyRuntime::builder`
+pub struct RuntimeBuilder {
+    network: NeuralNetwork,
+    server: ServerConfig,
+    endpoint: bool,
+    training: Option<OnlineTrainingConfig>,
+}
+
+impl RuntimeBuilder {
+    /// Serve the network to NNP peers on `address:port`, as the `server` subcommand does
+    pub fn with_endpoint(mut self, address: &str, port: u16) -> Self {
+        self.server.address = address.to_string();
+        self.server.port = port;
+        self.endpoint = true;
+        self
+    }
+
+    /// Serve NNP with every option of the `server` subcommand
+    ///
+    /// Its `metrics_port` is kept unless `with_metrics` is called afterwards.
+    pub fn with_server_config(mut self, config: ServerConfig) -> Self {
+        self.server = config;
+        self.endpoint = true;
+        self
+    }
+
+    /// Serve `/metrics`, `/healthz` and `/readyz` on `port` of the node's address
+    pub fn with_metrics(mut self, port: u16) -> Self {
+        self.server.metrics_port = Some(port);
+        self
+    }
+
+    /// Train on the samples passed to `RuntimeHandle::train` while running
+    pub fn with_training(mut self, config: OnlineTrainingConfig) -> Self {
+        self.training = Some(config);
+        self
+    }
+
+    /// Set up the node; nothing listens until `BennyRuntime::run`
+    pub fn build(self) -> Result<BennyRuntime, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(training) = &self.training {
+            training.validate()?;
+        }
+        let (server, node) = if self.endpoint {
+            let server = NetworkServer::new(self.network, self.server.clone())?;
+            let node = server.node().clone();
+            (Some(server), node)
+        } else {
+            let (node, _) = DistributedNetwork::new(
+                self.server.name.clone(),
+                self.server.address.clone(),
+                self.server.port,
+                self.network,
+            );
+            (None, node)
+        };
+
+        let (samples, queue) = match self.training {
+            Some(config) => {
+                let (sender, receiver) = mpsc::channel(config.queue_capacity);
+                (Some(sender), Some((config, receiver)))
+            }
+            None => (None, None),
+        };
+
+        Ok(BennyRuntime {
+            handle: RuntimeHandle {
+                node,
+                samples,
+                stats: Arc::new(Mutex::new(TrainingStats::default())),
+                shutdown: Arc::new(Notify::new()),
+            },
+            server,
+            config: self.server,
+            training: queue,
+        })
+    }
+}
+
+/// A network, its NNP endpoint, online training and monitoring in one process
+///
+/// Composes what the `server` subcommand and the training loop otherwise
+/// glue together, for applications embedding the crate:
+///
+/// ```no_run
+/// # async fn embed() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// use neural_network::{BennyRuntime, NeuralNetwork, OnlineTrainingConfig};
+///
+/// let runtime = BennyRuntime::builder(NeuralNetwork::with_layers(&[2, 4, 1], 0.1))
+///     .with_endpoint("127.0.0.1", 8080)
+///     .with_metrics(9090)
+///     .with_training(OnlineTrainingConfig::default())
+///     .build()?;
+/// let handle = runtime.handle();
+/// tokio::spawn(runtime.run());
+///
+/// handle.train(vec![1.0, 0.0], vec![1.0])?;
+/// println!("{:?}", handle.predict(&[1.0, 0.0])?);
+/// handle.shutdown();
+/// # Ok(())
+/// # }
+/// ```
+pub struct BennyRuntime {
+    handle: RuntimeHandle,
+    server: Option<NetworkServer>,
+    config: ServerConfig,
+    training: Option<(OnlineTrainingConfig, mpsc::Receiver<Sample>)>,
+}
+
+impl BennyRuntime {
+    /// Start configuring a runtime around `network`; without options it only serves `RuntimeHandle::predict`
+    pub fn builder(network: NeuralNetwork) -> RuntimeBuilder {
+        RuntimeBuilder {
+            network,
+            server: ServerConfig::default(),
+            endpoint: false,
+            training: None,
+        }
+    }
+
+    /// Handle for predicting, training and shutting down, usable before and while running
+    pub fn handle(&self) -> RuntimeHandle {
+        self.handle.clone()
+    }
+
+    /// Serve, train and monitor until `RuntimeHandle::shutdown` or the endpoint fails
+    ///
+    /// Shutting down stops message processing and training at once; the
+    /// NNP listener, and the metrics listener of an endpoint, close with the
+    /// tokio runtime.
+    pub async fn run(self) -> Result<(), ProtocolError> {
+        let node = self.handle.node.clone();
+        let training = self.training.map(|(config, samples)| {
+            info!(
+                "🏋️ Training online in batches of up to {}",
+                config.batch_size
+            );
+            tokio::spawn(train_online(
+                node.clone(),
+                config,
+                samples,
+                Arc::clone(&self.handle.stats),
+            ))
+        });
+
+        let serving = async {
+            match (self.server, self.config.metrics_port) {
+                (Some(server), _) => server.start().await,
+                (None, Some(metrics_port)) => {
+                    // Layer timings are only read through the metrics endpoint
+                    node.network.lock().unwrap().set_profiling(true);
+                    MetricsServer::new(node.clone())
+                        .serve(&self.config.address, metrics_port, BindOptions::default())
+                        .await
+                        .map_err(|e| ProtocolError::IoError(std::io::Error::other(e.to_string())))
+                }
+                (None, None) => std::future::pending().await,
+            }
+        };
+
+        let result = tokio::select! {
+            result = serving => result,
+            _ = self.handle.shutdown.notified() => Ok(()),
+        };
+        if let Some(training) = training {
+            training.abort();
+        }
+        info!("🛑 Runtime stopped");
+        result
+    }
+}
+
+/// Shared access to a running `BennyRuntime`
+#[derive(Clone)]
+pub struct RuntimeHandle {
+    node: DistributedNetwork,
+    samples: Option<mpsc::Sender<Sample>>,
+    stats: Arc<Mutex<TrainingStats>>,
+    shutdown: Arc<Notify>,
+}
+
+impl RuntimeHandle {
+    /// The node, for peers, bandwidth, health checks or direct access to the network
+    pub fn node(&self) -> &DistributedNetwork {
+        &self.node
+    }
+
+    /// Outputs of the network for `inputs`, reported to `Info` requests like served ones
+    pub fn predict(&self, inputs: &[f64]) -> Result<Vec<f64>, String> {
+        let outputs = {
+            let mut network = self.node.network.lock().unwrap();
+            if inputs.len() != network.input_size() {
+                return Err(format!(
+                    "Expected {} inputs, got {}",
+                    network.input_size(),
+                    inputs.len()
+                ));
+            }
+            network.forward(inputs).0
+        };
+        self.node.record_outputs(&outputs);
+        Ok(outputs)
+    }
+
+    /// Queue a sample for the training loop, refused when the queue is full
+    pub fn train(&self, inputs: Vec<f64>, targets: Vec<f64>) -> Result<(), String> {
+        let samples = self
+            .samples
+            .as_ref()
+            .ok_or("Training is not enabled, see RuntimeBuilder::with_training")?;
+        {
+            let network = self.node.network.lock().unwrap();
+            let layers = network.get_layers();
+            let outputs = layers.last().copied().unwrap_or(0);
+            if inputs.len() != network.input_size() || targets.len() != outputs {
+                return Err(format!(
+                    "Expected {} inputs and {} targets, got {} and {}",
+                    network.input_size(),
+                    outputs,
+                    inputs.len(),
+                    targets.len()
+                ));
+            }
+        }
+        samples.try_send((inputs, targets)).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => "Training queue is full".to_string(),
+            mpsc::error::TrySendError::Closed(_) => "Training loop has stopped".to_string(),
+        })
+    }
+
+    pub fn training_stats(&self) -> TrainingStats {
+        *self.stats.lock().unwrap()
+    }
+
+    /// Stop `BennyRuntime::run`, also when called before it started
+    pub fn shutdown(&self) {
+        self.shutdown.notify_one();
+    }
+}
+
+/// Train on queued samples in batches, reporting the loss to the node's monitoring
+///
+/// A batch is trained on once full or `max_wait` after its first sample.
+async fn train_online(
+    node: DistributedNetwork,
+    config: OnlineTrainingConfig,
+    mut samples: mpsc::Receiver<Sample>,
+    stats: Arc<Mutex<TrainingStats>>,
+) {
+    while let Some(first) = samples.recv().await {
+        let deadline = tokio::time::Instant::now() + config.max_wait;
+        let mut batch = vec![first];
+        while batch.len() < config.batch_size {
+            match tokio::time::timeout_at(deadline, samples.recv()).await {
+                Ok(Some(sample)) => batch.push(sample),
+                // Window elapsed, or every handle is gone
+                _ => break,
+            }
+        }
+
+        let loss = node.network.lock().unwrap().train_batch(&batch);
+        if !loss.is_finite() {
+            error!("Training batch of {} samples diverged", batch.len());
+        }
+        node.record_loss(loss);
+        let mut stats = stats.lock().unwrap();
+        stats.batches += 1;
+        stats.samples += batch.len() as u64;
+        stats.last_loss = Some(loss);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_runtime() {
+        let runtime = BennyRuntime::builder(NeuralNetwork::with_layers(&[2, 3, 1], 0.1))
+            .with_training(OnlineTrainingConfig {
+                batch_size: 4,
+                max_wait: Duration::from_millis(20),
+                queue_capacity: 8,
+            })
+            .build()
+            .unwrap();
+        let handle = runtime.handle();
+        let run = tokio::spawn(runtime.run());
+
+        // One full batch, then a partial one flushed by the wait
+        for i in 0..6 {
+            let x = i as f64 / 6.0;
+            handle.train(vec![x, 1.0 - x], vec![x]).unwrap();
+        }
+        assert!(handle.train(vec![0.0], vec![0.0]).is_err());
+        while handle.training_stats().samples < 6 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        let stats = handle.training_stats();
+        assert_eq!(stats.batches, 2);
+        assert!(stats.last_loss.is_some_and(f64::is_finite));
+
+        assert_eq!(handle.predict(&[0.5, 0.5]).unwrap().len(), 1);
+        assert!(handle
+            .predict(&[0.5])
+            .unwrap_err()
+            .contains("Expected 2 inputs"));
+
+        handle.shutdown();
+        run.await.unwrap().unwrap();
+
+        let untrained = BennyRuntime::builder(NeuralNetwork::with_layers(&[2, 1], 0.1))
+            .build()
+            .unwrap();
+        assert!(untrained
+            .handle()
+            .train(vec![0.0, 0.0], vec![0.0])
+            .unwrap_err()
+            .contains("not enabled"));
+        assert!(
+            BennyRuntime::builder(NeuralNetwork::with_layers(&[2, 1], 0.1))
+                .with_training(OnlineTrainingConfig {
+                    batch_size: 0,
+                    ..Default::default()
+                })
+                .build()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use crate::activation::Activation;
+use crate::distributed_network::NetworkId;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+/// Interval of values a signal takes
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValueRange {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl ValueRange {
+    /// `[0, 1]`, e.g. sigmoid outputs or normalized sensor readings
+    pub const UNIT: ValueRange = ValueRange { min: 0.0, max: 1.0 };
+    /// `[-1, 1]`, e.g. tanh outputs
+    pub const BIPOLAR: ValueRange = ValueRange {
+        min: -1.0,
+        max: 1.0,
+    };
+
+    pub fn new(min: f64, max: f64) -> Self {
+        Self { min, max }
+    }
+
+    /// Range of a network's outputs, from the activation of its last layer
+    pub fn of_outputs(network: &NeuralNetwork) -> Option<Self> {
+        Self::of_activation(network.get_activation(network.num_layers().saturating_sub(2)))
+    }
+
+    /// Range of a bounded activation function (`None` for ReLU, linear and custom units)
+    pub fn of_activation(activation: Activation) -> Option<Self> {
+        match activation {
+            Activation::Sigmoid | Activation::Step => Some(Self::UNIT),
+            Activation::Tanh | Activation::Sign => Some(Self::BIPOLAR),
+            Activation::Relu | Activation::Linear | Activation::Custom(_) => None,
+        }
+    }
+
+    pub fn contains(&self, value: f64) -> bool {
+        value >= self.min && value <= self.max
+    }
+
+    /// Map `value` linearly from this range onto `to`
+    pub fn rescale(&self, value: f64, to: &ValueRange) -> f64 {
+        let width = self.max - self.min;
+        if width.abs() < f64::EPSILON {
+            return to.min;
+        }
+        to.min + (value - self.min) / width * (to.max - to.min)
+    }
+}
+
+/// Value ranges a node announces in its handshake
+///
+/// `input` is what the node expects to receive, `output` what it sends.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct SignalRanges {
+    pub input: Option<ValueRange>,
+    pub output: Option<ValueRange>,
+}
+
+impl SignalRanges {
+    pub fn is_empty(&self) -> bool {
+        self.input.is_none() && self.output.is_none()
+    }
+}
+
+/// What `PeerRanges::fit` did to outgoing data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeFit {
+    /// The ranges agree, or too little is known to compare them
+    Unchanged,
+    /// Mapped from the sender's output range onto the receiver's input range
+    Rescaled,
+    /// Sent as is although some values lie outside the receiver's input range
+    OutOfRange,
+}
+
+/// Ranges announced by peers, shared by all clones of a node
+#[derive(Debug, Clone, Default)]
+pub struct PeerRanges {
+    peers: Arc<Mutex<HashMap<NetworkId, SignalRanges>>>,
+    warned: Arc<Mutex<HashSet<NetworkId>>>,
+}
+
+impl PeerRanges {
+    pub fn record(&self, peer: NetworkId, ranges: SignalRanges) {
+        self.peers.lock().unwrap().insert(peer, ranges);
+        self.warned.lock().unwrap().remove(&peer);
+    }
+
+    pub fn get(&self, peer: NetworkId) -> Option<SignalRanges> {
+        self.peers.lock().unwrap().get(&peer).copied()
+    }
+
+    pub fn forget(&self, peer: NetworkId) {
+        self.peers.lock().unwrap().remove(&peer);
+        self.warned.lock().unwrap().remove(&peer);
+    }
+
+    /// Prepare `data` produced in `local.output` for the input range `peer` announced
+    ///
+    /// Known ranges that differ are bridged by rescaling. Without a local
+    /// output range the data is only checked, so that values the peer would
+    /// saturate on are reported. Each peer is warned about once.
+    pub fn fit(&self, peer: NetworkId, local: &SignalRanges, data: &mut [f64]) -> RangeFit {
+        let Some(expected) = self.get(peer).and_then(|ranges| ranges.input) else {
+            return RangeFit::Unchanged;
+        };
+        let fit = match local.output {
+            Some(produced) if produced == expected => RangeFit::Unchanged,
+            Some(produced) => {
+                for value in data.iter_mut() {
+                    *value = produced.rescale(*value, &expected);
+                }
+                RangeFit::Rescaled
+            }
+            None if data.iter().all(|&value| expected.contains(value)) => RangeFit::Unchanged,
+            None => RangeFit::OutOfRange,
+        };
+
+        if fit != RangeFit::Unchanged && self.warned.lock().unwrap().insert(peer) {
+            match (fit, local.output) {
+                (RangeFit::Rescaled, Some(produced)) => println!(
+                    "📏 Rescaling data for peer {} from [{}, {}] to its input range [{}, {}]",
+                    peer, produced.min, produced.max, expected.min, expected.max
+                ),
+                _ => println!(
+                    "⚠️ Sending values outside the input range [{}, {}] of peer {}; declare an output range to rescale them",
+                    expected.min, expected.max, peer
+                ),
+            }
+        }
+        fit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_fit_rescales_or_warns() {
+        let ranges = PeerRanges::default();
+        let peer = Uuid::new_v4();
+        let tanh = SignalRanges {
+            input: None,
+            output: ValueRange::of_activation(Activation::Tanh),
+        };
+
+        // Nothing is known about the peer yet
+        let mut data = vec![-1.0, 0.0, 1.0];
+        assert_eq!(ranges.fit(peer, &tanh, &mut data), RangeFit::Unchanged);
+
+        ranges.record(
+            peer,
+            SignalRanges {
+                input: Some(ValueRange::UNIT),
+                output: None,
+            },
+        );
+        assert_eq!(ranges.fit(peer, &tanh, &mut data), RangeFit::Rescaled);
+        assert_eq!(data, vec![0.0, 0.5, 1.0]);
+
+        let unknown = SignalRanges::default();
+        assert_eq!(
+            ranges.fit(peer, &unknown, &mut [0.2, 0.9]),
+            RangeFit::Unchanged
+        );
+        assert_eq!(
+            ranges.fit(peer, &unknown, &mut [-0.5, 3.0]),
+            RangeFit::OutOfRange
+        );
+        assert_eq!(
+            ValueRange::new(0.0, 255.0).rescale(51.0, &ValueRange::UNIT),
+            0.2
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, EvalAltResult, Scope, AST};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A Rhai script transforming the values of each message
+///
+/// The script sees the message's values as the array `values` and the
+/// network as `network_id`, and evaluates to the transformed values: an array
+/// of numbers, or a single number. For example `values.map(|v| v * 2.0)` or
+/// `[values[0] + values[1], if values[2] > 0.5 { 1.0 } else { 0.0 }]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptConfig {
+    /// Script source, inline
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Script file, instead of `source`
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Longest a single message may take, in milliseconds
+    #[serde(default = "default_time_limit_ms")]
+    pub time_limit_ms: u64,
+    /// Most Rhai operations a single message may take
+    #[serde(default = "default_max_operations")]
+    pub max_operations: u64,
+}
+
+fn default_time_limit_ms() -> u64 {
+    5
+}
+
+fn default_max_operations() -> u64 {
+    100_000
+}
+
+impl ScriptConfig {
+    /// An inline script with the default limits
+    pub fn inline(source: &str) -> Self {
+        Self {
+            source: Some(source.to_string()),
+            path: None,
+            time_limit_ms: default_time_limit_ms(),
+            max_operations: default_max_operations(),
+        }
+    }
+
+    /// A script file with the default limits
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: None,
+            path: Some(path.into()),
+            ..Self::inline("")
+        }
+    }
+
+    fn load_source(&self) -> Result<String, String> {
+        match (&self.source, &self.path) {
+            (Some(source), None) => Ok(source.clone()),
+            (None, Some(path)) => std::fs::read_to_string(path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e)),
+            _ => Err("A script needs exactly one of source or path".to_string()),
+        }
+    }
+}
+
+/// A compiled `ScriptConfig`, run once per message in a sandbox
+///
+/// Scripts cannot import modules or reach the file system, and run under
+/// limits on operations, call depth and collection sizes. A script still
+/// running when its time limit expires is stopped and the message rejected.
+pub struct ScriptTransform {
+    engine: Engine,
+    ast: AST,
+    time_limit: Duration,
+    epoch: Instant,
+    /// Nanoseconds after `epoch` at which the running script is stopped
+    deadline: Arc<AtomicU64>,
+    running: Mutex<()>,
+}
+
+impl ScriptTransform {
+    pub fn new(config: &ScriptConfig) -> Result<Self, String> {
+        let source = config.load_source()?;
+        let epoch = Instant::now();
+        let deadline = Arc::new(AtomicU64::new(u64::MAX));
+
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(config.max_operations);
+        engine.set_max_call_levels(32);
+        engine.set_max_expr_depths(64, 32);
+        engine.set_max_string_size(4096);
+        engine.set_max_array_size(65_536);
+        engine.set_max_map_size(1024);
+        let expires = Arc::clone(&deadline);
+        engine.on_progress(move |_| {
+            let now = epoch.elapsed().as_nanos() as u64;
+            (now > expires.load(Ordering::Relaxed)).then(|| Dynamic::from("time limit"))
+        });
+
+        let ast = engine
+            .compile(&source)
+            .map_err(|e| format!("Script does not compile: {}", e))?;
+        Ok(Self {
+            engine,
+            ast,
+            time_limit: Duration::from_millis(config.time_limit_ms),
+            epoch,
+            deadline,
+            running: Mutex::new(()),
+        })
+    }
+
+    /// Run the script on one message's values
+    pub fn apply(&self, network_id: &str, values: &[f64]) -> Result<Vec<f64>, String> {
+        let _running = self.running.lock().unwrap();
+        let deadline = (self.epoch.elapsed() + self.time_limit).as_nanos() as u64;
+        self.deadline.store(deadline, Ordering::Relaxed);
+
+        let mut scope = Scope::new();
+        scope.push(
+            "values",
+            values.iter().copied().map(Dynamic::from).collect::<Array>(),
+        );
+        scope.push_constant("network_id", network_id.to_string());
+        let result = self
+            .engine
+            .eval_ast_with_scope::<Dynamic>(&mut scope, &self.ast)
+            .map_err(|e| match *e {
+                EvalAltResult::ErrorTerminated(..) => {
+                    format!(
+                        "Script exceeded its {} ms time limit",
+                        self.time_limit.as_millis()
+                    )
+                }
+                e => format!("Script failed: {}", e),
+            })?;
+
+        let number = |value: &Dynamic| -> Result<f64, String> {
+            let number = value
+                .as_float()
+                .or_else(|_| value.as_int().map(|i| i as f64))
+                .map_err(|kind| format!("Script returned a {} instead of a number", kind))?;
+            if number.is_finite() {
+                Ok(number)
+            } else {
+                Err(format!("Script returned {}", number))
+            }
+        };
+        if result.is_array() {
+            result
+                .into_array()
+                .unwrap_or_default()
+                .iter()
+                .map(number)
+                .collect()
+        } else {
+            Ok(vec![number(&result)?])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_transform_values_in_a_sandbox() {
+        let scale = ScriptTransform::new(&ScriptConfig::inline("values.map(|v| v * 2.0)")).unwrap();
+        assert_eq!(
+            scale.apply("main-network", &[0.5, -1.0]).unwrap(),
+            vec![1.0, -2.0]
+        );
+
+        let combine = ScriptTransform::new(&ScriptConfig::inline(
+            r#"if network_id == "main-network" { [values[0] + values[1], 1] } else { 0.0 }"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            combine.apply("main-network", &[0.25, 0.5]).unwrap(),
+            vec![0.75, 1.0]
+        );
+        assert_eq!(combine.apply("other", &[0.25, 0.5]).unwrap(), vec![0.0]);
+
+        assert!(ScriptTransform::new(&ScriptConfig::inline("values.map(")).is_err());
+        assert!(
+            ScriptTransform::new(&ScriptConfig::inline(r#"import "secrets" as s; 1"#))
+                .unwrap()
+                .apply("main-network", &[])
+                .is_err()
+        );
+        let text = ScriptTransform::new(&ScriptConfig::inline(r#""hello""#)).unwrap();
+        assert!(text.apply("main-network", &[]).is_err());
+
+        // Runaway scripts are stopped by the operation and time limits
+        let endless = ScriptTransform::new(&ScriptConfig::inline("loop {}")).unwrap();
+        assert!(endless.apply("main-network", &[]).is_err());
+        let mut slow = ScriptConfig::inline("loop {}");
+        slow.max_operations = 0;
+        slow.time_limit_ms = 20;
+        let started = Instant::now();
+        let error = ScriptTransform::new(&slow)
+            .unwrap()
+            .apply("main-network", &[])
+            .unwrap_err();
+        assert!(error.contains("time limit"), "{}", error);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let json: ScriptConfig = serde_json::from_str(r#"{"path":"scale.rhai"}"#).unwrap();
+        assert_eq!(json.time_limit_ms, 5);
+        assert!(ScriptTransform::new(&json).is_err());
+    }
+}
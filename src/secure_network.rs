@@ -1,3 +1,4 @@
+use rustls::server::AllowAnyAuthenticatedClient;
 use rustls::{Certificate, ClientConfig, PrivateKey, ServerConfig};
 use rustls_pemfile::{certs, pkcs8_private_keys};
 use std::collections::HashMap;
@@ -5,16 +6,21 @@ use std::fs::File;
 use std::io::BufReader;
 use std::path::Path;
 use std::sync::{Arc, Mutex};
-use tokio::net::{TcpListener, TcpStream};
+use byteorder::{BigEndian, ByteOrder};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::mpsc;
 use tokio_rustls::{TlsAcceptor, TlsConnector};
 use uuid::Uuid;
 use x509_parser::prelude::*;
 
+use crate::address::{self, AddressPreference, BindOptions};
 use crate::distributed_network::{
-    MessagePayload, NetworkId, NetworkMessage, ProtocolError, capabilities,
+    self, MessagePayload, MessageType, NetworkId, NetworkMessage, ProtocolError, capabilities,
+    HEADER_SIZE,
 };
 use crate::neural_network::NeuralNetwork;
+use crate::remote_config::{ConfigAuditEntry, ConfigUpdate};
+use crate::tokens::{AuthError, Scope, TokenGuard};
 
 /// Certificate-based authentication for neural networks
 #[derive(Debug, Clone)]
@@ -31,8 +37,18 @@ pub struct NetworkCertificate {
 impl NetworkCertificate {
     /// Parse a certificate from PEM data
     pub fn from_pem(pem_data: &[u8]) -> Result<Self, SecureNetworkError> {
+        let (_, pem) = x509_parser::pem::parse_x509_pem(pem_data)
+            .map_err(|_| SecureNetworkError::InvalidCertificate)?;
+        Ok(NetworkCertificate {
+            certificate_data: pem_data.to_vec(),
+            ..Self::from_der(&pem.contents)?
+        })
+    }
+
+    /// Parse a DER certificate, as presented by a TLS peer
+    pub fn from_der(der: &[u8]) -> Result<Self, SecureNetworkError> {
         let cert =
-            parse_x509_certificate(pem_data).map_err(|_| SecureNetworkError::InvalidCertificate)?;
+            parse_x509_certificate(der).map_err(|_| SecureNetworkError::InvalidCertificate)?;
 
         let cert = cert.1;
 
@@ -57,7 +73,7 @@ impl NetworkCertificate {
             valid_from,
             valid_until,
             capabilities,
-            certificate_data: pem_data.to_vec(),
+            certificate_data: der.to_vec(),
         })
     }
 
@@ -90,18 +106,19 @@ impl NetworkCertificate {
 
     /// Extract subject field from certificate
     fn extract_subject_field(
-        _cert: &X509Certificate,
+        cert: &X509Certificate,
         field: &str,
     ) -> Result<String, SecureNetworkError> {
-        // Simplified implementation for demonstration
-        // In a real implementation, you would properly parse the subject DN
-        match field {
-            "CN" => Ok("demo-network".to_string()),
-            "O" => Ok("Demo Organization".to_string()),
-            _ => Err(SecureNetworkError::MissingCertificateField(
-                field.to_string(),
-            )),
-        }
+        let subject = cert.subject();
+        let value = match field {
+            "CN" => subject.iter_common_name().next(),
+            "O" => subject.iter_organization().next(),
+            _ => None,
+        };
+        value
+            .and_then(|attribute| attribute.as_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| SecureNetworkError::MissingCertificateField(field.to_string()))
     }
 
     /// Extract neural network capabilities from certificate extensions
@@ -123,6 +140,22 @@ impl NetworkCertificate {
         Ok(caps)
     }
 
+    /// Check that the certificate was issued to the node identity `expected`
+    ///
+    /// Both the parsed network ID and a UUID common name must match, so a
+    /// returning peer is only accepted with a certificate for the persisted
+    /// identity it announces.
+    pub fn verify_identity(&self, expected: NetworkId) -> Result<(), SecureNetworkError> {
+        let cn_id = Uuid::parse_str(&self.common_name).ok();
+        if self.network_id != expected || cn_id.is_some_and(|id| id != expected) {
+            return Err(SecureNetworkError::IdentityMismatch {
+                expected,
+                found: cn_id.unwrap_or(self.network_id),
+            });
+        }
+        Ok(())
+    }
+
     /// Verify certificate is valid for current time
     pub fn is_valid(&self) -> bool {
         let now = std::time::SystemTime::now()
@@ -144,9 +177,17 @@ impl NetworkCertificate {
 pub enum SecureNetworkError {
     InvalidCertificate,
     InvalidNetworkId,
+    IdentityMismatch {
+        expected: NetworkId,
+        found: NetworkId,
+    },
     MissingCertificateField(String),
     CertificateExpired,
     InsufficientCapabilities,
+    /// A remote configuration update failed validation
+    ConfigRejected(String),
+    /// A remote configuration update came without a valid `admin` API token
+    Unauthorized(AuthError),
     TlsError(String),
     IoError(std::io::Error),
     ProtocolError(ProtocolError),
@@ -157,11 +198,20 @@ impl std::fmt::Display for SecureNetworkError {
         match self {
             SecureNetworkError::InvalidCertificate => write!(f, "Invalid certificate"),
             SecureNetworkError::InvalidNetworkId => write!(f, "Invalid network ID"),
+            SecureNetworkError::IdentityMismatch { expected, found } => write!(
+                f,
+                "Certificate identity {} does not match node identity {}",
+                found, expected
+            ),
             SecureNetworkError::MissingCertificateField(field) => {
                 write!(f, "Missing certificate field: {}", field)
             }
             SecureNetworkError::CertificateExpired => write!(f, "Certificate expired"),
             SecureNetworkError::InsufficientCapabilities => write!(f, "Insufficient capabilities"),
+            SecureNetworkError::ConfigRejected(reason) => {
+                write!(f, "Config update rejected: {}", reason)
+            }
+            SecureNetworkError::Unauthorized(e) => write!(f, "Unauthorized: {}", e),
             SecureNetworkError::TlsError(msg) => write!(f, "TLS error: {}", msg),
             SecureNetworkError::IoError(err) => write!(f, "IO error: {}", err),
             SecureNetworkError::ProtocolError(err) => write!(f, "Protocol error: {:?}", err),
@@ -191,6 +241,7 @@ impl From<ProtocolError> for SecureNetworkError {
 }
 
 /// Secure distributed neural network with TLS encryption and certificate authentication
+#[derive(Clone)]
 pub struct SecureDistributedNetwork {
     pub id: NetworkId,
     pub certificate: NetworkCertificate,
@@ -199,6 +250,13 @@ pub struct SecureDistributedNetwork {
     pub message_sender: mpsc::UnboundedSender<NetworkMessage>,
     pub sequence_counter: Arc<Mutex<u64>>,
     pub tls_config: TlsConfig,
+    config_audit: Arc<Mutex<Vec<ConfigAuditEntry>>>,
+    /// Token sent with our config updates
+    api_token: Option<String>,
+    /// Tokens that incoming config updates must carry, when set
+    token_guard: Option<TokenGuard>,
+    /// Cluster namespace announced in handshakes; peers must announce the same
+    namespace: Option<String>,
 }
 
 /// TLS configuration for secure connections
@@ -211,6 +269,9 @@ pub struct TlsConfig {
 
 impl TlsConfig {
     /// Create TLS configuration from certificate and private key files
+    ///
+    /// With a CA file, servers only accept peers presenting a client certificate
+    /// signed by one of its CAs, and clients present `cert_file` in return.
     pub fn from_files(
         cert_file: &Path,
         key_file: &Path,
@@ -248,18 +309,32 @@ impl TlsConfig {
             Vec::new()
         };
 
-        // Create server config
-        let server_config = ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
+        // Peers in both directions are verified against the CA certificates
+        let mut roots = rustls::RootCertStore::empty();
+        for ca in &ca_certificates {
+            roots
+                .add(ca)
+                .map_err(|e| SecureNetworkError::TlsError(e.to_string()))?;
+        }
+
+        // Create server config, requiring client certificates when there is a CA
+        let server_builder = ServerConfig::builder().with_safe_defaults();
+        let server_builder = if ca_certificates.is_empty() {
+            server_builder.with_no_client_auth()
+        } else {
+            server_builder
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots.clone()).boxed())
+        };
+        let server_config = server_builder
             .with_single_cert(certs.clone(), private_key.clone())
             .map_err(|e| SecureNetworkError::TlsError(e.to_string()))?;
 
-        // Create client config
+        // Create client config, trusting the CA certificates for peer servers
         let client_config = ClientConfig::builder()
             .with_safe_defaults()
-            .with_root_certificates(rustls::RootCertStore::empty())
-            .with_no_client_auth();
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certs, private_key)
+            .map_err(|e| SecureNetworkError::TlsError(e.to_string()))?;
 
         Ok(TlsConfig {
             server_config: Arc::new(server_config),
@@ -275,6 +350,8 @@ pub struct SecureConnection {
     pub peer_id: NetworkId,
     pub peer_certificate: NetworkCertificate,
     pub last_heartbeat: u64,
+    /// Messages queued for the task writing to the peer's TLS stream
+    pub writer: mpsc::UnboundedSender<NetworkMessage>,
 }
 
 impl SecureDistributedNetwork {
@@ -297,6 +374,10 @@ impl SecureDistributedNetwork {
             message_sender: sender,
             sequence_counter: Arc::new(Mutex::new(0)),
             tls_config,
+            config_audit: Arc::new(Mutex::new(Vec::new())),
+            api_token: None,
+            token_guard: None,
+            namespace: None,
         };
 
         (secure_network, receiver)
@@ -308,12 +389,12 @@ impl SecureDistributedNetwork {
         address: &str,
         port: u16,
     ) -> Result<(), SecureNetworkError> {
-        let listener = TcpListener::bind(format!("{}:{}", address, port)).await?;
+        let listener = address::bind_tcp_listener(address, port, BindOptions::default()).await?;
         let acceptor = TlsAcceptor::from(self.tls_config.server_config.clone());
 
         println!(
-            "🔒 Secure Neural Network Protocol server listening on {}:{}",
-            address, port
+            "🔒 Secure Neural Network Protocol server listening on {}",
+            address::format_host_port(address, port)
         );
         println!("📡 Network ID: {}", self.id);
         println!(
@@ -328,24 +409,28 @@ impl SecureDistributedNetwork {
                     println!("🔗 New secure connection from {}", peer_addr);
 
                     let acceptor = acceptor.clone();
-                    let message_sender = self.message_sender.clone();
-                    let connections = self.connections.clone();
+                    let node = self.clone();
 
                     tokio::spawn(async move {
                         match acceptor.accept(stream).await {
-                            Ok(_tls_stream) => {
+                            Ok(tls_stream) => {
                                 println!("✅ TLS handshake successful with {}", peer_addr);
 
                                 // Verify peer certificate
-                                if let Err(e) = Self::verify_peer_certificate().await {
-                                    println!("❌ Certificate verification failed: {:?}", e);
-                                    return;
-                                }
+                                let presented = tls_stream.get_ref().1.peer_certificates();
+                                let peer_certificate =
+                                    match Self::verify_peer_certificate(presented) {
+                                        Ok(certificate) => certificate,
+                                        Err(e) => {
+                                            println!("❌ Certificate verification failed: {:?}", e);
+                                            return;
+                                        }
+                                    };
 
                                 // Handle secure connection
-                                if let Err(e) =
-                                    Self::handle_secure_connection(message_sender, connections)
-                                        .await
+                                if let Err(e) = node
+                                    .handle_secure_connection(tls_stream, peer_certificate)
+                                    .await
                                 {
                                     println!("❌ Secure connection error: {:?}", e);
                                 }
@@ -370,13 +455,13 @@ impl SecureDistributedNetwork {
         port: u16,
     ) -> Result<NetworkId, SecureNetworkError> {
         let connector = TlsConnector::from(self.tls_config.client_config.clone());
-        let stream = TcpStream::connect(format!("{}:{}", address, port)).await?;
+        let stream = address::connect_tcp(address, port, AddressPreference::System).await?;
 
         // Perform TLS handshake
-        let domain = rustls::ServerName::try_from(address)
+        let domain = rustls::ServerName::try_from(address::strip_brackets(address))
             .map_err(|e| SecureNetworkError::TlsError(e.to_string()))?;
 
-        let _tls_stream = connector
+        let tls_stream = connector
             .connect(domain, stream)
             .await
             .map_err(|e| SecureNetworkError::TlsError(e.to_string()))?;
@@ -387,78 +472,191 @@ impl SecureDistributedNetwork {
         );
 
         // Verify peer certificate
-        let peer_cert = Self::verify_peer_certificate().await?;
+        let peer_cert = Self::verify_peer_certificate(tls_stream.get_ref().1.peer_certificates())?;
 
         // Send handshake message
-        let _handshake = NetworkMessage {
+        let layers: Vec<u16> = {
+            let network = self.network.lock().unwrap();
+            network.get_layers().iter().map(|&x| x as u16).collect()
+        };
+        let (mut reader, writer) = tokio::io::split(tls_stream);
+        let writer = spawn_writer(writer);
+        let handshake = NetworkMessage {
             msg_type: crate::distributed_network::MessageType::Handshake,
             sequence: self.next_sequence(),
             payload: MessagePayload::Handshake {
                 network_id: self.id,
                 name: self.certificate.common_name.clone(),
                 capabilities: self.certificate.capabilities,
-                layers: vec![3, 6, 2], // Example layer configuration
+                layers,
+                namespace: self.namespace.clone(),
+                channels: Vec::new(),
+                ranges: None,
             },
+            flags: 0,
         };
-
-        // Send handshake (implementation would serialize and send over TLS stream)
-        // For now, we'll simulate successful handshake
+        writer.send(handshake).map_err(|_| connection_closed())?;
+
+        // The acknowledgment tells us which network we reached
+        let peer_id = match read_frame(&mut reader).await?.payload {
+            MessagePayload::HandshakeAck {
+                network_id,
+                accepted: true,
+                ..
+            } => network_id,
+            MessagePayload::HandshakeAck { reason, .. } => {
+                return Err(ProtocolError::HandshakeRejected(reason).into())
+            }
+            _ => return Err(ProtocolError::InvalidPayload.into()),
+        };
+        // The network we reached must be the one its certificate was issued to
+        peer_cert.verify_identity(peer_id)?;
 
         // Store secure connection
         let secure_conn = SecureConnection {
-            peer_id: peer_cert.network_id,
+            peer_id,
             peer_certificate: peer_cert.clone(),
             last_heartbeat: std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
+            writer,
         };
 
         {
             let mut connections = self.connections.lock().unwrap();
-            connections.insert(peer_cert.network_id, secure_conn);
+            connections.insert(peer_id, secure_conn);
         }
+        tokio::spawn(self.clone().read_loop(reader, peer_id));
 
         println!(
             "✅ Authenticated connection to network {} ({})",
-            peer_cert.network_id, peer_cert.common_name
+            peer_id, peer_cert.common_name
         );
 
-        Ok(peer_cert.network_id)
+        Ok(peer_id)
     }
 
-    /// Verify peer certificate and extract network information
-    async fn verify_peer_certificate() -> Result<NetworkCertificate, SecureNetworkError> {
-        // In a real implementation, this would:
-        // 1. Extract the peer certificate from the TLS stream
-        // 2. Verify the certificate chain against trusted CAs
-        // 3. Check certificate validity period
-        // 4. Extract neural network capabilities
-        // 5. Verify the network ID matches the certificate
-
-        // For demonstration, we'll create a mock certificate
-        Ok(NetworkCertificate {
-            network_id: Uuid::new_v4(),
-            common_name: "peer-network".to_string(),
-            organization: "Neural Research Lab".to_string(),
-            valid_from: 0,
-            valid_until: u64::MAX,
-            capabilities: capabilities::FORWARD_PROPAGATION | capabilities::HEBBIAN_LEARNING,
-            certificate_data: Vec::new(),
-        })
+    /// Extract the network identity and capabilities from the certificate a peer presented
+    ///
+    /// rustls has already verified the chain against our CA certificates during
+    /// the TLS handshake; peers without a certificate are refused here.
+    fn verify_peer_certificate(
+        presented: Option<&[Certificate]>,
+    ) -> Result<NetworkCertificate, SecureNetworkError> {
+        let Some(certificate) = presented.and_then(|chain| chain.first()) else {
+            return Err(SecureNetworkError::TlsError(
+                "Peer presented no certificate".to_string(),
+            ));
+        };
+        let certificate = NetworkCertificate::from_der(&certificate.0)?;
+        if !certificate.is_valid() {
+            return Err(SecureNetworkError::CertificateExpired);
+        }
+        Ok(certificate)
     }
 
     /// Handle secure connection with certificate authentication
-    async fn handle_secure_connection(
-        _message_sender: mpsc::UnboundedSender<NetworkMessage>,
-        _connections: Arc<Mutex<HashMap<NetworkId, SecureConnection>>>,
-    ) -> Result<(), SecureNetworkError> {
-        // Implementation would handle secure message exchange
-        // over the encrypted TLS stream
-        println!("🔐 Handling secure authenticated connection");
+    ///
+    /// The peer identifies itself with a `Handshake`, which is refused unless
+    /// its certificate was issued to the announced identity and it announces our
+    /// namespace; every later message is passed to `message_sender` until the
+    /// peer disconnects.
+    async fn handle_secure_connection<S>(
+        self,
+        stream: S,
+        peer_certificate: NetworkCertificate,
+    ) -> Result<(), SecureNetworkError>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    {
+        let (mut reader, writer) = tokio::io::split(stream);
+        let MessagePayload::Handshake {
+            network_id,
+            namespace,
+            ..
+        } = read_frame(&mut reader).await?.payload
+        else {
+            return Err(ProtocolError::InvalidPayload.into());
+        };
+
+        let writer = spawn_writer(writer);
+        // The identity a returning peer announces must be the one its certificate
+        // names, and only peers of our own namespace are accepted
+        let namespace = namespace.filter(|ns| !ns.is_empty());
+        let reason = match peer_certificate.verify_identity(network_id) {
+            Err(e) => Some(e.to_string()),
+            Ok(()) if namespace != self.namespace => Some(format!(
+                "Namespace {:?} is not accepted (local namespace {:?})",
+                namespace, self.namespace
+            )),
+            Ok(()) => None,
+        };
+        let ack = NetworkMessage {
+            msg_type: MessageType::HandshakeAck,
+            sequence: 1,
+            payload: MessagePayload::HandshakeAck {
+                network_id: self.id,
+                accepted: reason.is_none(),
+                reason: reason.clone(),
+                ranges: None,
+            },
+            flags: 0,
+        };
+        writer.send(ack).map_err(|_| connection_closed())?;
+        if let Some(reason) = reason {
+            return Err(ProtocolError::HandshakeRejected(Some(reason)).into());
+        }
+
+        let peer_id = peer_certificate.network_id;
+        let connection = SecureConnection {
+            peer_id,
+            peer_certificate,
+            last_heartbeat: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            writer,
+        };
+        self.connections.lock().unwrap().insert(peer_id, connection);
+        println!("🔐 Handling secure authenticated connection from {}", peer_id);
+
+        self.read_loop(reader, peer_id).await;
         Ok(())
     }
 
+    /// Pass messages from an authenticated peer on until it disconnects, then forget it
+    ///
+    /// Config updates are applied here rather than passed on, attributed to the
+    /// identity of the peer's certificate.
+    async fn read_loop<R: AsyncRead + Unpin>(self, mut reader: R, peer_id: NetworkId) {
+        loop {
+            match read_frame(&mut reader).await {
+                Ok(NetworkMessage {
+                    payload: MessagePayload::ConfigUpdate { update, token },
+                    ..
+                }) => {
+                    // Outcomes are recorded in the audit log
+                    let _ = self.handle_config_update(peer_id, update, token.as_deref());
+                }
+                Ok(message) => {
+                    if self.message_sender.send(message).is_err() {
+                        break;
+                    }
+                }
+                Err(ProtocolError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                    println!("🔌 Secure peer {} disconnected", peer_id);
+                    break;
+                }
+                Err(e) => {
+                    println!("❌ Secure connection to {} failed: {:?}", peer_id, e);
+                    break;
+                }
+            }
+        }
+        self.connections.lock().unwrap().remove(&peer_id);
+    }
+
     /// Get next sequence number
     fn next_sequence(&self) -> u64 {
         let mut counter = self.sequence_counter.lock().unwrap();
@@ -476,12 +674,7 @@ impl SecureDistributedNetwork {
 
         if let Some(connection) = connections.get(&peer_id) {
             // Verify peer has required capabilities for this message type
-            let required_capability = match message.payload {
-                MessagePayload::ForwardData { .. } => capabilities::FORWARD_PROPAGATION,
-                MessagePayload::BackwardData { .. } => capabilities::BACKPROPAGATION,
-                MessagePayload::HebbianData { .. } => capabilities::HEBBIAN_LEARNING,
-                _ => 0,
-            };
+            let required_capability = message.payload.required_capability();
 
             if required_capability != 0
                 && !connection
@@ -497,19 +690,203 @@ impl SecureDistributedNetwork {
             }
 
             // Send message over secure TLS connection
-            // Implementation would serialize and send over the encrypted stream
-            println!(
-                "🔐 Sending secure message to authenticated peer {}",
-                peer_id
-            );
-
-            Ok(())
+            connection
+                .writer
+                .send(message)
+                .map_err(|_| connection_closed())
         } else {
             Err(SecureNetworkError::ProtocolError(
                 ProtocolError::InvalidPayload,
             ))
         }
     }
+
+    /// Send an arbitrary payload to an authenticated peer with a fresh sequence number
+    pub async fn send_secure_payload(
+        &self,
+        peer_id: NetworkId,
+        payload: MessagePayload,
+    ) -> Result<(), SecureNetworkError> {
+        let message = NetworkMessage {
+            msg_type: payload.message_type(),
+            sequence: self.next_sequence(),
+            payload,
+            flags: 0,
+        };
+        self.send_secure_message(peer_id, message).await
+    }
+
+    /// Send `token` with every config update, for peers that require API tokens
+    pub fn set_api_token(&mut self, token: String) {
+        self.api_token = Some(token);
+    }
+
+    /// Only accept config updates carrying a token with the `admin` scope
+    pub fn require_api_tokens(&mut self, guard: TokenGuard) {
+        self.token_guard = Some(guard);
+    }
+
+    /// Join a namespace so the node only talks to peers of the same cluster
+    pub fn set_namespace(&mut self, namespace: Option<String>) -> Result<(), String> {
+        if let Some(namespace) = &namespace {
+            distributed_network::check_namespace(namespace)?;
+        }
+        self.namespace = namespace.filter(|ns| !ns.is_empty());
+        Ok(())
+    }
+
+    /// Ask a peer to change its Hebbian rate, online learning or decay rate at runtime
+    ///
+    /// Both certificates must carry `REMOTE_CONFIG`: ours to be allowed to
+    /// send the update, the peer's to show it accepts remote configuration.
+    pub async fn send_config_update(
+        &self,
+        peer_id: NetworkId,
+        update: ConfigUpdate,
+    ) -> Result<(), SecureNetworkError> {
+        update.validate().map_err(SecureNetworkError::ConfigRejected)?;
+        if !self.certificate.has_capability(capabilities::REMOTE_CONFIG) {
+            return Err(SecureNetworkError::InsufficientCapabilities);
+        }
+
+        let message = NetworkMessage {
+            msg_type: MessageType::ConfigUpdate,
+            sequence: self.next_sequence(),
+            payload: MessagePayload::ConfigUpdate {
+                update,
+                token: self.api_token.clone(),
+            },
+            flags: 0,
+        };
+        self.send_secure_message(peer_id, message).await
+    }
+
+    /// Apply a `ConfigUpdate` received from an authenticated peer
+    ///
+    /// Called by the connection's read loop for every `ConfigUpdate` frame.
+    /// The peer's certificate must be valid and carry `REMOTE_CONFIG`, the
+    /// message must carry an `admin` token when `require_api_tokens` is set,
+    /// and the values must lie within the bounds of `remote_config`. Every
+    /// attempt is recorded in the audit log; accepted ones return the
+    /// replaced settings.
+    pub fn handle_config_update(
+        &self,
+        peer_id: NetworkId,
+        update: ConfigUpdate,
+        token: Option<&str>,
+    ) -> Result<ConfigUpdate, SecureNetworkError> {
+        let peer_certificate = self
+            .connections
+            .lock()
+            .unwrap()
+            .get(&peer_id)
+            .map(|connection| connection.peer_certificate.clone());
+
+        let result = match &peer_certificate {
+            Some(certificate) => Self::authorize_config_update(certificate, &update)
+                .and_then(|()| match &self.token_guard {
+                    Some(guard) => guard
+                        .authorize(token, Scope::Admin)
+                        .map(|_| ())
+                        .map_err(SecureNetworkError::Unauthorized),
+                    None => Ok(()),
+                })
+                .map(|()| update.apply(&mut self.network.lock().unwrap())),
+            None => Err(SecureNetworkError::ProtocolError(ProtocolError::PeerNotFound)),
+        };
+
+        let entry = ConfigAuditEntry {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            peer_id,
+            peer_name: peer_certificate.map(|certificate| certificate.common_name),
+            update,
+            previous: result.as_ref().ok().copied(),
+            rejection: result.as_ref().err().map(|e| e.to_string()),
+        };
+        match &entry.rejection {
+            None => println!(
+                "📝 Config update from {} ({}): {:?} (was {:?})",
+                peer_id,
+                entry.peer_name.as_deref().unwrap_or("unknown"),
+                update,
+                entry.previous
+            ),
+            Some(reason) => println!(
+                "📝 Rejected config update from {}: {:?} ({})",
+                peer_id, update, reason
+            ),
+        }
+        self.config_audit.lock().unwrap().push(entry);
+
+        result
+    }
+
+    /// Check that `certificate` may change this node's settings to `update`
+    fn authorize_config_update(
+        certificate: &NetworkCertificate,
+        update: &ConfigUpdate,
+    ) -> Result<(), SecureNetworkError> {
+        if !certificate.is_valid() {
+            return Err(SecureNetworkError::CertificateExpired);
+        }
+        if !certificate.has_capability(capabilities::REMOTE_CONFIG) {
+            return Err(SecureNetworkError::InsufficientCapabilities);
+        }
+        update.validate().map_err(SecureNetworkError::ConfigRejected)
+    }
+
+    /// Every remote configuration attempt so far, oldest first
+    pub fn config_audit_log(&self) -> Vec<ConfigAuditEntry> {
+        self.config_audit.lock().unwrap().clone()
+    }
+}
+
+fn connection_closed() -> SecureNetworkError {
+    SecureNetworkError::IoError(std::io::Error::new(
+        std::io::ErrorKind::BrokenPipe,
+        "Secure connection closed",
+    ))
+}
+
+/// Largest frame payload accepted from a TLS peer
+pub const MAX_PAYLOAD_SIZE: usize = 16 * 1024 * 1024;
+
+/// Read one NNP frame from a TLS stream
+///
+/// Frames announcing more than `MAX_PAYLOAD_SIZE` bytes are refused before
+/// anything is allocated for them.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> Result<NetworkMessage, ProtocolError> {
+    let mut header = [0u8; HEADER_SIZE];
+    reader.read_exact(&mut header).await?;
+
+    let payload_len = BigEndian::read_u32(&header[6..10]) as usize;
+    if payload_len > MAX_PAYLOAD_SIZE {
+        return Err(ProtocolError::InvalidLength);
+    }
+    let mut frame = vec![0u8; HEADER_SIZE + payload_len];
+    frame[..HEADER_SIZE].copy_from_slice(&header);
+    reader.read_exact(&mut frame[HEADER_SIZE..]).await?;
+    NetworkMessage::from_bytes(&frame)
+}
+
+/// Write queued messages to a TLS stream until the queue or the stream is closed
+fn spawn_writer<W>(mut writer: W) -> mpsc::UnboundedSender<NetworkMessage>
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (sender, mut queue) = mpsc::unbounded_channel::<NetworkMessage>();
+    tokio::spawn(async move {
+        while let Some(message) = queue.recv().await {
+            if writer.write_all(&message.to_bytes()).await.is_err() || writer.flush().await.is_err()
+            {
+                break;
+            }
+        }
+    });
+    sender
 }
 
 /// Certificate generation utilities for testing and development
@@ -574,6 +951,197 @@ pub mod cert_utils {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tokens::TokenStore;
+    use std::time::Duration;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/tls")
+            .join(name)
+    }
+
+    /// A TLS node with one of the test certificates, trusting the test CA
+    fn secure_node(
+        name: &str,
+    ) -> (SecureDistributedNetwork, mpsc::UnboundedReceiver<NetworkMessage>) {
+        let cert = fixture(&format!("{}.pem", name));
+        let key = fixture(&format!("{}-key.pem", name));
+        let tls = TlsConfig::from_files(&cert, &key, Some(&fixture("ca.pem"))).unwrap();
+        let certificate = NetworkCertificate::from_pem(&std::fs::read(&cert).unwrap()).unwrap();
+        SecureDistributedNetwork::new(
+            name.to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[2, 1], 0.0),
+            certificate,
+            tls,
+        )
+    }
+
+    /// Serve `node` on a free loopback port and return the port
+    async fn serve(node: Arc<SecureDistributedNetwork>) -> u16 {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        tokio::spawn(async move { node.start_secure_server("127.0.0.1", port).await });
+        for _ in 0..50 {
+            if tokio::net::TcpStream::connect(("127.0.0.1", port))
+                .await
+                .is_ok()
+            {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        port
+    }
+
+    #[test]
+    fn test_certificate_from_pem() {
+        let certificate =
+            NetworkCertificate::from_pem(&std::fs::read(fixture("node-a.pem")).unwrap()).unwrap();
+        assert_eq!(certificate.common_name, certificate.network_id.to_string());
+        assert_eq!(certificate.organization, "Benny Test");
+        assert!(certificate.is_valid());
+        assert!(certificate.has_capability(capabilities::REMOTE_CONFIG));
+        assert!(!certificate.has_capability(capabilities::HEBBIAN_LEARNING));
+    }
+
+    #[tokio::test]
+    async fn test_peer_identity_comes_from_certificate() {
+        let (server, _) = secure_node("node-a");
+        let server_id = server.id;
+        let port = serve(Arc::new(server)).await;
+
+        let (client, _) = secure_node("node-b");
+        assert_eq!(
+            client.connect_to_secure("127.0.0.1", port).await.unwrap(),
+            server_id
+        );
+
+        // A peer claiming another identity than its certificate's is refused
+        let (mut impostor, _) = secure_node("node-b");
+        impostor.certificate.network_id = Uuid::new_v4();
+        impostor.id = impostor.certificate.network_id;
+        assert!(matches!(
+            impostor.connect_to_secure("127.0.0.1", port).await,
+            Err(SecureNetworkError::ProtocolError(
+                ProtocolError::HandshakeRejected(Some(_))
+            ))
+        ));
+    }
+
+    /// Wait until `node` has audited `count` config updates
+    async fn audit_log(node: &SecureDistributedNetwork, count: usize) -> Vec<ConfigAuditEntry> {
+        for _ in 0..250 {
+            let log = node.config_audit_log();
+            if log.len() >= count {
+                return log;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("timed out waiting for {} config updates", count);
+    }
+
+    #[tokio::test]
+    async fn test_config_update_over_tls() {
+        let server = Arc::new(secure_node("node-a").0);
+        let port = serve(server.clone()).await;
+        let update = ConfigUpdate {
+            hebbian_rate: Some(0.25),
+            ..Default::default()
+        };
+
+        let (operator, _) = secure_node("node-b");
+        let peer = operator.connect_to_secure("127.0.0.1", port).await.unwrap();
+        operator.send_config_update(peer, update).await.unwrap();
+        let log = audit_log(&server, 1).await;
+        assert!(log[0].accepted(), "{:?}", log[0].rejection);
+        assert_eq!(log[0].peer_id, operator.id);
+        assert_eq!(server.network.lock().unwrap().get_hebbian_rate(), 0.25);
+
+        // node-c's certificate lacks REMOTE_CONFIG, whatever it sends
+        let (observer, _) = secure_node("node-c");
+        let peer = observer.connect_to_secure("127.0.0.1", port).await.unwrap();
+        let payload = MessagePayload::ConfigUpdate {
+            update: ConfigUpdate {
+                hebbian_rate: Some(0.5),
+                ..Default::default()
+            },
+            token: None,
+        };
+        observer.send_secure_payload(peer, payload).await.unwrap();
+        let log = audit_log(&server, 2).await;
+        assert_eq!(log[1].peer_id, observer.id);
+        assert!(!log[1].accepted());
+        assert_eq!(server.network.lock().unwrap().get_hebbian_rate(), 0.25);
+    }
+
+    #[tokio::test]
+    async fn test_config_update_over_tls_requires_admin_token() {
+        let path = std::env::temp_dir().join(format!("benny-tokens-{}.json", Uuid::new_v4()));
+        let mut store = TokenStore::default();
+        let (_, admin) = store.issue("operator", &[Scope::Admin]).unwrap();
+        let (_, metrics) = store.issue("dashboard", &[Scope::Metrics]).unwrap();
+        store.save(&path).unwrap();
+
+        let (mut server, _) = secure_node("node-a");
+        server.require_api_tokens(TokenGuard::open(&path).unwrap());
+        let server = Arc::new(server);
+        let port = serve(server.clone()).await;
+        let update = ConfigUpdate {
+            decay_rate: Some(0.125),
+            ..Default::default()
+        };
+
+        let (mut operator, _) = secure_node("node-b");
+        let peer = operator.connect_to_secure("127.0.0.1", port).await.unwrap();
+        operator.send_config_update(peer, update).await.unwrap();
+        operator.set_api_token(metrics);
+        operator.send_config_update(peer, update).await.unwrap();
+        let log = audit_log(&server, 2).await;
+        assert!(log.iter().all(|entry| !entry.accepted()));
+        assert_ne!(server.network.lock().unwrap().get_decay_rate(), 0.125);
+
+        operator.set_api_token(admin);
+        operator.send_config_update(peer, update).await.unwrap();
+        assert!(audit_log(&server, 3).await[2].accepted());
+        assert_eq!(server.network.lock().unwrap().get_decay_rate(), 0.125);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_namespace_is_checked_over_tls() {
+        let (mut server, _) = secure_node("node-a");
+        server.set_namespace(Some("cluster-a".to_string())).unwrap();
+        assert!(server.set_namespace(Some("x".repeat(256))).is_err());
+        let port = serve(Arc::new(server)).await;
+
+        let client = |namespace: &str| {
+            let (mut node, _) = secure_node("node-b");
+            node.set_namespace(Some(namespace.to_string())).unwrap();
+            node
+        };
+        assert!(client("cluster-a").connect_to_secure("127.0.0.1", port).await.is_ok());
+        assert!(matches!(
+            client("cluster-b").connect_to_secure("127.0.0.1", port).await,
+            Err(SecureNetworkError::ProtocolError(
+                ProtocolError::HandshakeRejected(Some(_))
+            ))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_frame_is_refused() {
+        let mut header = [0u8; HEADER_SIZE];
+        BigEndian::write_u32(&mut header[6..10], u32::MAX);
+        assert!(matches!(
+            read_frame(&mut &header[..]).await,
+            Err(ProtocolError::InvalidLength)
+        ));
+    }
 
     #[test]
     fn test_certificate_capabilities() {
@@ -623,4 +1191,65 @@ mod tests {
 
         assert!(!expired_cert.is_valid());
     }
+
+    #[test]
+    fn test_certificate_identity() {
+        let id = Uuid::new_v4();
+        let cert = NetworkCertificate {
+            network_id: id,
+            common_name: id.to_string(),
+            organization: "Test Org".to_string(),
+            valid_from: 0,
+            valid_until: u64::MAX,
+            capabilities: capabilities::FORWARD_PROPAGATION,
+            certificate_data: Vec::new(),
+        };
+        assert!(cert.verify_identity(id).is_ok());
+
+        let other = Uuid::new_v4();
+        assert!(matches!(
+            cert.verify_identity(other),
+            Err(SecureNetworkError::IdentityMismatch { expected, found }) if expected == other && found == id
+        ));
+    }
+
+    #[test]
+    fn test_config_update_authorization() {
+        let mut cert = NetworkCertificate {
+            network_id: Uuid::new_v4(),
+            common_name: "operator".to_string(),
+            organization: "Test Org".to_string(),
+            valid_from: 0,
+            valid_until: u64::MAX,
+            capabilities: capabilities::FORWARD_PROPAGATION,
+            certificate_data: Vec::new(),
+        };
+        let update = ConfigUpdate {
+            hebbian_rate: Some(0.02),
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            SecureDistributedNetwork::authorize_config_update(&cert, &update),
+            Err(SecureNetworkError::InsufficientCapabilities)
+        ));
+
+        cert.capabilities |= capabilities::REMOTE_CONFIG;
+        assert!(SecureDistributedNetwork::authorize_config_update(&cert, &update).is_ok());
+
+        let out_of_bounds = ConfigUpdate {
+            decay_rate: Some(-1.0),
+            ..Default::default()
+        };
+        assert!(matches!(
+            SecureDistributedNetwork::authorize_config_update(&cert, &out_of_bounds),
+            Err(SecureNetworkError::ConfigRejected(_))
+        ));
+
+        cert.valid_until = 1;
+        assert!(matches!(
+            SecureDistributedNetwork::authorize_config_update(&cert, &update),
+            Err(SecureNetworkError::CertificateExpired)
+        ));
+    }
 }
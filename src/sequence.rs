@@ -0,0 +1,228 @@
+use crate::cli::TrainingData;
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Name of the first CSV column in the sequence layout
+pub const EPISODE_COLUMN: &str = "episode";
+
+/// One independent sequence of timesteps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Episode {
+    pub inputs: Vec<Vec<f64>>,
+    pub targets: Vec<Vec<f64>>,
+}
+
+impl Episode {
+    pub fn len(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inputs.is_empty()
+    }
+}
+
+/// Training data for temporal tasks: episodes whose timesteps must be seen in order
+///
+/// Stored as JSON (`{"episodes": [{"inputs": [...], "targets": [...]}]}`)
+/// or as CSV whose first column, `episode`, names the episode of each row,
+/// followed by the inputs and the target like `TrainingData`'s CSV layout.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SequenceData {
+    pub episodes: Vec<Episode>,
+}
+
+impl SequenceData {
+    /// Cut a time series into consecutive episodes of `episode_length` steps
+    pub fn from_samples(data: TrainingData, episode_length: usize) -> Self {
+        let length = episode_length.max(1);
+        let episodes = data
+            .inputs
+            .chunks(length)
+            .zip(data.targets.chunks(length))
+            .map(|(inputs, targets)| Episode {
+                inputs: inputs.to_vec(),
+                targets: targets.to_vec(),
+            })
+            .collect();
+        Self { episodes }
+    }
+
+    pub fn num_steps(&self) -> usize {
+        self.episodes.iter().map(Episode::len).sum()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (index, episode) in self.episodes.iter().enumerate() {
+            if episode.inputs.len() != episode.targets.len() {
+                return Err(format!(
+                    "Episode {} has {} inputs but {} targets",
+                    index,
+                    episode.inputs.len(),
+                    episode.targets.len()
+                ));
+            }
+            if episode.is_empty() {
+                return Err(format!("Episode {} has no timesteps", index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `path` holds sequence data rather than independent samples
+    pub fn is_sequence_file(path: &Path) -> Result<bool, Box<dyn std::error::Error>> {
+        if path.extension().and_then(|s| s.to_str()) == Some("csv") {
+            let mut reader = csv::Reader::from_path(path)?;
+            return Ok(reader.headers()?.get(0) == Some(EPISODE_COLUMN));
+        }
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(value.get("episodes").is_some())
+    }
+
+    /// Load JSON, or CSV when the file ends in `.csv`
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let data = if path.extension().and_then(|s| s.to_str()) == Some("csv") {
+            Self::load_from_csv(path)?
+        } else {
+            serde_json::from_str(&fs::read_to_string(path)?)?
+        };
+        data.validate()?;
+        Ok(data)
+    }
+
+    /// Load the CSV layout; consecutive rows with the same episode name form one episode
+    pub fn load_from_csv(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut reader = csv::Reader::from_path(path)?;
+        let mut data = SequenceData::default();
+        let mut current: Option<String> = None;
+        for result in reader.records() {
+            let record = result?;
+            if record.len() < 3 {
+                return Err("Sequence CSV needs an episode, an input and a target column".into());
+            }
+            let values = record
+                .iter()
+                .skip(1)
+                .map(str::parse::<f64>)
+                .collect::<Result<Vec<_>, _>>()?;
+            if current.as_deref() != Some(&record[0]) {
+                current = Some(record[0].to_string());
+                data.episodes.push(Episode {
+                    inputs: Vec::new(),
+                    targets: Vec::new(),
+                });
+            }
+            let episode = data.episodes.last_mut().unwrap();
+            episode.inputs.push(values[..values.len() - 1].to_vec());
+            episode.targets.push(vec![values[values.len() - 1]]);
+        }
+        Ok(data)
+    }
+
+    /// Write JSON, or CSV when the file ends in `.csv`
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        if path.extension().and_then(|s| s.to_str()) != Some("csv") {
+            fs::write(path, serde_json::to_string_pretty(self)?)?;
+            return Ok(());
+        }
+        let episodes = self.episodes.iter();
+        if episodes.flat_map(|e| &e.targets).any(|target| target.len() != 1) {
+            return Err("CSV holds a single target column; save as JSON instead".into());
+        }
+        let mut writer = csv::Writer::from_path(path)?;
+        let inputs = self
+            .episodes
+            .first()
+            .and_then(|episode| episode.inputs.first())
+            .map_or(0, Vec::len);
+        let mut header = vec![EPISODE_COLUMN.to_string()];
+        header.extend((0..inputs).map(|i| format!("x{}", i)));
+        header.push("y".to_string());
+        writer.write_record(&header)?;
+        for (index, episode) in self.episodes.iter().enumerate() {
+            for (input, target) in episode.inputs.iter().zip(&episode.targets) {
+                let mut row = vec![index.to_string()];
+                row.extend(input.iter().chain(target).map(f64::to_string));
+                writer.write_record(&row)?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Split off the last `validation_split` of the episodes, keeping each episode whole
+    pub fn split(mut self, validation_split: f64) -> (Self, Self) {
+        let count = self.episodes.len();
+        let validation = ((count as f64 * validation_split).round() as usize).min(count);
+        let held_out = self.episodes.split_off(count - validation);
+        (self, SequenceData { episodes: held_out })
+    }
+}
+
+/// Train on every episode in order, forgetting the activation history before each one
+///
+/// Returns the mean training error per timestep.
+pub fn train_epoch(network: &mut NeuralNetwork, data: &SequenceData) -> f64 {
+    let mut total = 0.0;
+    for episode in &data.episodes {
+        network.reset_activation_history();
+        for (input, target) in episode.inputs.iter().zip(&episode.targets) {
+            total += network.train(input, target);
+        }
+    }
+    total / data.num_steps().max(1) as f64
+}
+
+/// Mean squared error per timestep, replaying each episode from a fresh state
+pub fn evaluate(network: &mut NeuralNetwork, data: &SequenceData) -> f64 {
+    let mut total = 0.0;
+    for episode in &data.episodes {
+        network.reset_activation_history();
+        for (input, target) in episode.inputs.iter().zip(&episode.targets) {
+            let (output, _) = network.forward(input);
+            total += output
+                .iter()
+                .zip(target)
+                .map(|(o, t)| (o - t).powi(2))
+                .sum::<f64>();
+        }
+    }
+    total / data.num_steps().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datasets;
+
+    #[test]
+    fn test_episodes_round_trip_and_split() {
+        let data = SequenceData::from_samples(datasets::temporal_parity(10, 1), 4);
+        let lengths: Vec<usize> = data.episodes.iter().map(Episode::len).collect();
+        assert_eq!(lengths, vec![4, 4, 2]);
+        assert_eq!(data.num_steps(), 10);
+
+        let dir = std::env::temp_dir().join(format!("benny-sequence-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["episodes.json", "episodes.csv"] {
+            let path = dir.join(name);
+            data.save(&path).unwrap();
+            assert!(SequenceData::is_sequence_file(&path).unwrap());
+            assert_eq!(SequenceData::load(&path).unwrap(), data);
+        }
+        let flat = dir.join("flat.json");
+        datasets::temporal_parity(10, 1).save_to_json(&flat).unwrap();
+        assert!(!SequenceData::is_sequence_file(&flat).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let (train, validation) = data.split(0.34);
+        assert_eq!((train.episodes.len(), validation.episodes.len()), (2, 1));
+        assert_eq!(validation.num_steps(), 2);
+
+        let mut network = NeuralNetwork::with_layers(&[1, 3, 1], 0.1);
+        assert!(train_epoch(&mut network, &train).is_finite());
+        assert!(evaluate(&mut network, &validation).is_finite());
+    }
+}
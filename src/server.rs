@@ -1,12 +1,33 @@
+use crate::address::{self, BindOptions};
+use crate::async_io::Compression;
+use crate::bandwidth::BandwidthLimit;
+use crate::cli::NetworkConfig;
+use crate::compatibility::{self, ShapePolicy};
 use crate::distributed_network::{
-    DistributedNetwork, MessagePayload, NetworkMessage, ProtocolError,
+    message_flags, DistributedNetwork, MessagePayload, NetworkMessage, ProtocolError,
 };
+use crate::identity::IdentitySource;
+use crate::live_evaluation::{LiveEvaluationConfig, LiveEvaluator};
+use crate::metrics::MetricsServer;
+use crate::mmap_model::MappedModel;
 use crate::neural_network::NeuralNetwork;
+use crate::realtime::{LatencyBudget, RealtimePredictor};
+use crate::reload::{self, ReloadReport};
+use crate::remote_config::ConfigUpdate;
+use crate::scaling::{SignalRanges, ValueRange};
+use crate::shadow_capture::{CaptureConfig, ShadowCapture};
+use crate::storage::StagedFile;
+use crate::tokens::TokenGuard;
 use log::{debug, error, info, warn};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
+/// How often the node state is written to `ServerConfig::state_file`
+const STATE_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Server configuration for neural network daemon
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
@@ -18,6 +39,79 @@ pub struct ServerConfig {
     pub output_endpoints: Vec<String>,
     pub hebbian_learning: bool,
     pub daemon_mode: bool,
+    /// Port for the HTTP metrics endpoint (disabled when `None`)
+    pub metrics_port: Option<u16>,
+    /// Per-peer outgoing bandwidth cap in bytes per second
+    pub bandwidth_limit: Option<u64>,
+    /// File the node state is restored from on startup and saved to periodically
+    pub state_file: Option<PathBuf>,
+    /// Compression of the saved node state (restoring detects it)
+    pub state_compression: Compression,
+    /// Model file the network was explicitly loaded from; it replaces the network
+    /// of a restored node state
+    pub model_file: Option<PathBuf>,
+    /// Where the node's `NetworkId` comes from
+    pub identity: IdentitySource,
+    /// Cluster namespace announced in handshakes
+    pub namespace: Option<String>,
+    /// Foreign namespaces whose peers are still accepted
+    pub bridged_namespaces: Vec<String>,
+    /// What to do with peers whose layer shapes do not match the network
+    pub shape_policy: ShapePolicy,
+    /// Collect forward requests for up to this long and run them as one batch
+    /// (every request runs on its own when `None`)
+    pub batch_window: Option<Duration>,
+    /// Requests per batch; a full batch runs without waiting out the window
+    pub max_batch_size: usize,
+    /// Older protocol version to speak during a rolling upgrade (current when `None`)
+    pub protocol_version: Option<u8>,
+    /// Input range announced in handshakes so senders rescale their data
+    pub input_range: Option<ValueRange>,
+    /// Network configuration re-read on SIGHUP
+    pub config_file: Option<PathBuf>,
+    /// API tokens required by the metrics port, which then also serves the REST API
+    pub token_file: Option<PathBuf>,
+    /// Score the outputs against labels streamed on a channel, reported on the metrics port
+    pub live_evaluation: Option<LiveEvaluationConfig>,
+    /// Sample the served inputs and predictions into a rotating dataset for retraining
+    pub capture: Option<CaptureConfig>,
+    /// Serve single forward requests under this budget, forwarding the last
+    /// outputs flagged `STALE` when a fresh pass would miss it
+    pub latency_budget: Option<LatencyBudget>,
+}
+
+impl Default for ServerConfig {
+    /// A loopback node on the default NNP port with every optional feature off
+    fn default() -> Self {
+        Self {
+            name: "benny".to_string(),
+            address: "127.0.0.1".to_string(),
+            port: 8080,
+            cert_path: None,
+            key_path: None,
+            output_endpoints: Vec::new(),
+            hebbian_learning: false,
+            daemon_mode: false,
+            metrics_port: None,
+            bandwidth_limit: None,
+            state_file: None,
+            state_compression: Compression::default(),
+            model_file: None,
+            identity: IdentitySource::default(),
+            namespace: None,
+            bridged_namespaces: Vec::new(),
+            shape_policy: ShapePolicy::default(),
+            batch_window: None,
+            max_batch_size: 64,
+            protocol_version: None,
+            input_range: None,
+            config_file: None,
+            token_file: None,
+            live_evaluation: None,
+            capture: None,
+            latency_budget: None,
+        }
+    }
 }
 
 /// Neural network server using existing distributed network infrastructure
@@ -25,6 +119,11 @@ pub struct NetworkServer {
     distributed_network: DistributedNetwork,
     config: ServerConfig,
     message_receiver: mpsc::UnboundedReceiver<NetworkMessage>,
+    tokens: Option<TokenGuard>,
+    evaluator: Option<LiveEvaluator>,
+    capture: Option<Mutex<ShadowCapture>>,
+    realtime: Option<Mutex<RealtimePredictor>>,
+    mapped: Option<Arc<MappedModel>>,
 }
 
 impl NetworkServer {
@@ -32,27 +131,146 @@ impl NetworkServer {
         network: NeuralNetwork,
         config: ServerConfig,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        // Create distributed network
-        let (distributed_network, message_receiver) = DistributedNetwork::new(
-            config.name.clone(),
-            config.address.clone(),
-            config.port,
-            network,
-        );
+        // Restore the previous node state if available, otherwise start fresh
+        let restored = match &config.state_file {
+            Some(path) if path.exists() => match DistributedNetwork::restore(path) {
+                Ok(restored) => {
+                    info!("💾 Restored node state from {}", path.display());
+                    Some(restored)
+                }
+                Err(e) => {
+                    warn!("⚠️ Failed to restore node state from {}: {}", path.display(), e);
+                    None
+                }
+            },
+            _ => None,
+        };
+        let restored_from_state = restored.as_ref().map(|(node, _)| node.id);
+        let (mut distributed_network, message_receiver) = match restored {
+            Some((node, receiver)) => {
+                if let Some(model_file) = &config.model_file {
+                    warn!("⚠️ Model {} replaces the restored network", model_file.display());
+                    *node.network.lock().unwrap() = network;
+                }
+                (node, receiver)
+            }
+            None => DistributedNetwork::new(
+                config.name.clone(),
+                config.address.clone(),
+                config.port,
+                network,
+            ),
+        };
+        if restored_from_state.is_none() || config.identity != IdentitySource::Random {
+            let id = config.identity.resolve()?;
+            if restored_from_state.is_some_and(|previous| previous != id) {
+                warn!("⚠️ Configured identity {} replaces restored identity {}", id, distributed_network.id);
+            }
+            distributed_network.set_id(id);
+        }
+        distributed_network.set_namespace(config.namespace.clone())?;
+        for namespace in &config.bridged_namespaces {
+            distributed_network.bridge_namespace(namespace);
+        }
+        distributed_network.set_shape_policy(config.shape_policy);
+        let outputs = ValueRange::of_outputs(&distributed_network.network.lock().unwrap());
+        distributed_network.set_signal_ranges(SignalRanges {
+            input: config.input_range,
+            output: outputs,
+        });
+        if let Some(version) = config.protocol_version {
+            distributed_network.set_protocol_version(version)?;
+        }
+        distributed_network.info.name = config.name.clone();
+        distributed_network.info.address = config.address.clone();
+        distributed_network.info.port = config.port;
+        if let Some(limit) = config.bandwidth_limit {
+            distributed_network.set_bandwidth_limit(Some(BandwidthLimit::new(limit)));
+        }
+
+        let tokens = match &config.token_file {
+            Some(path) => {
+                info!(
+                    "🔑 API tokens from {} guard the metrics port and REST API",
+                    path.display()
+                );
+                Some(TokenGuard::open(path)?)
+            }
+            None => None,
+        };
+
+        let evaluator = config.live_evaluation.clone().map(|evaluation| {
+            info!(
+                "🏷️ Evaluating outputs against labels on channel '{}'",
+                evaluation.label_channel
+            );
+            let evaluator = LiveEvaluator::new(evaluation);
+            evaluator.listen(&distributed_network);
+            evaluator
+        });
+
+        let capture = match &config.capture {
+            Some(capture) => {
+                let capture = ShadowCapture::new(capture.clone())?;
+                info!(
+                    "📼 Capturing {:.2}% of requests to {}",
+                    capture.config().sample_rate * 100.0,
+                    capture.current_file().display()
+                );
+                Some(Mutex::new(capture))
+            }
+            None => None,
+        };
+
+        let realtime = match config.latency_budget {
+            Some(budget) => {
+                budget.validate()?;
+                info!("⏱️ Serving forward requests within {:?}", budget.budget);
+                Some(Mutex::new(RealtimePredictor::new(budget)))
+            }
+            None => None,
+        };
 
         Ok(Self {
             distributed_network,
             config,
             message_receiver,
+            tokens,
+            evaluator,
+            capture,
+            realtime,
+            mapped: None,
         })
     }
 
+    /// Answer forward requests from a memory-mapped model
+    ///
+    /// The weights stay in the mapping; the node only holds the model's
+    /// `MappedModel::skeleton`. A mapped model is read-only, so Hebbian
+    /// learning is skipped and the REST API refuses to train it.
+    pub fn with_mapped_model(mut self, model: MappedModel) -> Self {
+        if self.config.hebbian_learning {
+            warn!("⚠️ Hebbian learning does not apply to a memory-mapped model");
+        }
+        *self.distributed_network.network.lock().unwrap() = model.skeleton();
+        self.mapped = Some(Arc::new(model));
+        self
+    }
+
+    /// The node serving the network, sharing its weights with the server
+    pub fn node(&self) -> &DistributedNetwork {
+        &self.distributed_network
+    }
+
     /// Start the server using existing distributed network infrastructure
     pub async fn start(mut self) -> Result<(), ProtocolError> {
         info!("🚀 Starting Neural Network Server");
         info!("   Name: {}", self.config.name);
         info!("   Address: {}:{}", self.config.address, self.config.port);
         info!("   Network ID: {}", self.distributed_network.id);
+        if let Some(namespace) = &self.config.namespace {
+            info!("   Namespace: {}", namespace);
+        }
         info!("   Hebbian Learning: {}", self.config.hebbian_learning);
         info!("   Output Endpoints: {:?}", self.config.output_endpoints);
 
@@ -68,18 +286,98 @@ impl NetworkServer {
 
         self.distributed_network.start_server().await?;
 
+        if let Some(metrics_port) = self.config.metrics_port {
+            // Layer timings are only read through the metrics endpoint
+            self.distributed_network.network.lock().unwrap().set_profiling(true);
+            let mut metrics = MetricsServer::new(self.distributed_network.clone());
+            if let Some(guard) = self.tokens.clone() {
+                metrics = metrics.with_tokens(guard);
+            }
+            if let Some(evaluator) = self.evaluator.clone() {
+                metrics = metrics.with_live_evaluation(evaluator);
+            }
+            if let Some(model) = self.mapped.clone() {
+                metrics = metrics.with_mapped_model(model);
+            }
+            let address = self.config.address.clone();
+            tokio::spawn(async move {
+                if let Err(e) = metrics
+                    .serve(&address, metrics_port, BindOptions::default())
+                    .await
+                {
+                    error!("Metrics endpoint failed: {}", e);
+                }
+            });
+        }
+
+        if let Some(path) = self.config.state_file.clone() {
+            let node = self.distributed_network.clone();
+            let compression = self.config.state_compression;
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(STATE_SAVE_INTERVAL);
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = node.snapshot_async(&path, compression).await {
+                        error!("Failed to save node state to {}: {}", path.display(), e);
+                    }
+                }
+            });
+        }
+
+        if let Some(path) = self.config.config_file.clone() {
+            self.spawn_config_reload(path);
+        }
+
         // Start message processing loop
         self.start_message_processing().await?;
 
         Ok(())
     }
 
+    /// Re-read the network configuration whenever the process receives SIGHUP
+    fn spawn_config_reload(&self, path: PathBuf) {
+        let mut current = match read_network_config(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!(
+                    "⚠️ Configuration reload disabled, cannot read {}: {}",
+                    path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        let node = self.distributed_network.clone();
+        reload::spawn_on_sighup(move || {
+            let result = read_network_config(&path).and_then(|config| {
+                let mut network = node.network.lock().unwrap();
+                let report = apply_network_config(&current, &config, &mut network)?;
+                current = config;
+                Ok(report)
+            });
+            match result {
+                Ok(report) => report.print(),
+                Err(e) => error!("Failed to reload {}: {}", path.display(), e),
+            }
+            async {}
+        });
+    }
+
     /// Start message processing loop
     async fn start_message_processing(&mut self) -> Result<(), ProtocolError> {
         info!("📡 Starting message processing loop");
 
         while let Some(message) = self.message_receiver.recv().await {
-            if let Err(e) = self.process_message(message).await {
+            let result = match (self.config.batch_window, message.payload) {
+                (Some(window), MessagePayload::ForwardData { layer_id, data, .. }) => {
+                    self.process_batch((layer_id, data), window).await
+                }
+                (_, payload) => {
+                    self.process_message(NetworkMessage { payload, ..message })
+                        .await
+                }
+            };
+            if let Err(e) = result {
                 error!("Error processing message: {:?}", e);
             }
         }
@@ -92,13 +390,14 @@ impl NetworkServer {
         debug!("Processing message: {:?}", message.msg_type);
 
         match message.payload {
-            MessagePayload::ForwardData { layer_id, data } => {
+            MessagePayload::ForwardData { layer_id, data, .. } => {
                 self.handle_forward_data(layer_id, data).await?;
             }
             MessagePayload::HebbianData {
                 layer_id,
                 correlations,
                 learning_rate,
+                ..
             } => {
                 if self.config.hebbian_learning {
                     self.handle_hebbian_data(layer_id, correlations, learning_rate)
@@ -110,6 +409,7 @@ impl NetworkServer {
                 name,
                 layers,
                 capabilities,
+                ..
             } => {
                 self.handle_handshake(network_id, name, layers, capabilities)
                     .await?;
@@ -125,32 +425,151 @@ impl NetworkServer {
         Ok(())
     }
 
-    /// Handle forward data (neural network activation)
-    async fn handle_forward_data(&self, layer_id: u8, data: Vec<f32>) -> Result<(), ProtocolError> {
+    /// Collect forward requests after `first` until `window` elapses or the batch is full, then run them together
+    ///
+    /// Other messages arriving meanwhile are processed right away.
+    async fn process_batch(
+        &mut self,
+        first: (u8, Vec<f32>),
+        window: Duration,
+    ) -> Result<(), ProtocolError> {
+        let deadline = tokio::time::Instant::now() + window;
+        let mut requests = vec![first];
+        while requests.len() < self.config.max_batch_size {
+            match tokio::time::timeout_at(deadline, self.message_receiver.recv()).await {
+                Ok(Some(NetworkMessage {
+                    payload: MessagePayload::ForwardData { layer_id, data, .. },
+                    ..
+                })) => requests.push((layer_id, data)),
+                Ok(Some(message)) => {
+                    if let Err(e) = self.process_message(message).await {
+                        error!("Error processing message: {:?}", e);
+                    }
+                }
+                // Window elapsed, or the network shut down
+                _ => break,
+            }
+        }
+        self.handle_forward_batch(requests).await
+    }
+
+    /// Handle a batch of forward requests, forwarding each request's outputs separately
+    async fn handle_forward_batch(&self, requests: Vec<(u8, Vec<f32>)>) -> Result<(), ProtocolError> {
         let start_time = std::time::Instant::now();
+        let received = requests.len();
+        let batch: Vec<Vec<f64>> = requests
+            .into_iter()
+            .filter_map(|(layer_id, data)| self.fit_request(layer_id, data).ok())
+            .collect();
+
+        let outputs = match &self.mapped {
+            Some(model) => batch.iter().map(|inputs| model.forward(inputs)).collect(),
+            None => {
+                let mut network = self.distributed_network.network.lock().unwrap();
+                forward_requests(&mut network, &batch, self.config.hebbian_learning)
+            }
+        };
+
+        if let Some(last) = outputs.last() {
+            self.distributed_network.record_outputs(last);
+        }
+        if let Some(evaluator) = &self.evaluator {
+            for outputs in &outputs {
+                evaluator.record_prediction(outputs);
+            }
+        }
+        for (inputs, outputs) in batch.iter().zip(&outputs) {
+            self.capture(inputs, outputs);
+        }
+
+        let processing_time = start_time.elapsed().as_secs_f64() * 1000.0;
+        info!(
+            "⚡ Processed batch of {} requests ({} rejected) in {:.2}ms",
+            batch.len(),
+            received - batch.len(),
+            processing_time
+        );
+
+        for outputs in &outputs {
+            self.forward_outputs(outputs, 0).await?;
+        }
 
+        Ok(())
+    }
+
+    /// Network inputs for a forward request, fitted to the network's input size
+    fn fit_request(&self, layer_id: u8, data: Vec<f32>) -> Result<Vec<f64>, ProtocolError> {
+        let input_size = self.distributed_network.network.lock().unwrap().input_size();
+        self.fit_inputs(layer_id, data, input_size)
+    }
+
+    /// Network inputs for a forward request, fitted to `input_size`
+    fn fit_inputs(
+        &self,
+        layer_id: u8,
+        data: Vec<f32>,
+        input_size: usize,
+    ) -> Result<Vec<f64>, ProtocolError> {
         // Convert f32 to f64 for neural network processing
         let inputs: Vec<f64> = data.iter().map(|&x| x as f64).collect();
+        let inputs = compatibility::fit_inputs(inputs, input_size, self.config.shape_policy)
+            .map_err(|e| {
+                warn!("⚠️ {}", e);
+                ProtocolError::InvalidPayload
+            })?;
 
         info!(
             "📥 Received activation for layer {} with {} inputs",
             layer_id,
             inputs.len()
         );
+        Ok(inputs)
+    }
 
-        // Process through neural network
-        let outputs = {
-            let mut network = self.distributed_network.network.lock().unwrap();
-            let (outputs, _hidden) = network.forward(&inputs);
-
-            // Apply Hebbian learning if enabled
-            if self.config.hebbian_learning {
-                network.hebbian_update(&inputs);
-                info!("🧠 Applied Hebbian learning update");
+    /// Handle forward data (neural network activation)
+    async fn handle_forward_data(&self, layer_id: u8, data: Vec<f32>) -> Result<(), ProtocolError> {
+        let start_time = std::time::Instant::now();
+        let (inputs, outputs) = match &self.realtime {
+            Some(realtime) => {
+                let mut served = None;
+                let prediction = {
+                    let mut predictor = realtime.lock().unwrap();
+                    let deadline = start_time + predictor.budget().budget;
+                    let forward = |network: &mut NeuralNetwork| -> Result<_, ProtocolError> {
+                        let inputs = self.fit_inputs(layer_id, data, network.input_size())?;
+                        let outputs = self.forward(network, &inputs);
+                        served = Some(inputs);
+                        Ok(outputs)
+                    };
+                    predictor.predict(&self.distributed_network.network, deadline, forward)?
+                };
+                match served {
+                    Some(inputs) => (inputs, prediction.outputs),
+                    None => {
+                        warn!(
+                            "⏱️ Over the latency budget, forwarding outputs from {:.2}ms ago as stale",
+                            prediction.age.as_secs_f64() * 1000.0
+                        );
+                        return self
+                            .forward_outputs(&prediction.outputs, message_flags::STALE)
+                            .await;
+                    }
+                }
+            }
+            None => {
+                let inputs = self.fit_request(layer_id, data)?;
+                let outputs = {
+                    let mut network = self.distributed_network.network.lock().unwrap();
+                    self.forward(&mut network, &inputs)
+                };
+                (inputs, outputs)
             }
-
-            outputs
         };
+        self.distributed_network.record_outputs(&outputs);
+        if let Some(evaluator) = &self.evaluator {
+            evaluator.record_prediction(&outputs);
+        }
+        self.capture(&inputs, &outputs);
 
         let processing_time = start_time.elapsed().as_secs_f64() * 1000.0;
         info!(
@@ -159,11 +578,35 @@ impl NetworkServer {
         );
 
         // Forward outputs to connected networks
-        self.forward_outputs(&outputs).await?;
+        self.forward_outputs(&outputs, 0).await?;
 
         Ok(())
     }
 
+    /// Process one request through the network, applying Hebbian learning if enabled
+    ///
+    /// A mapped model answers in place of `network`, which then only holds its skeleton.
+    fn forward(&self, network: &mut NeuralNetwork, inputs: &[f64]) -> Vec<f64> {
+        if let Some(model) = &self.mapped {
+            return model.forward(inputs);
+        }
+        let (outputs, _hidden) = network.forward(inputs);
+        if self.config.hebbian_learning {
+            network.hebbian_update(inputs);
+            info!("🧠 Applied Hebbian learning update");
+        }
+        outputs
+    }
+
+    /// Offer a served request to the shadow capture, if one is configured
+    fn capture(&self, inputs: &[f64], outputs: &[f64]) {
+        if let Some(capture) = &self.capture {
+            if let Err(e) = capture.lock().unwrap().record(inputs, outputs) {
+                warn!("⚠️ {}", e);
+            }
+        }
+    }
+
     /// Handle Hebbian learning data
     async fn handle_hebbian_data(
         &self,
@@ -202,7 +645,7 @@ impl NetworkServer {
     }
 
     /// Forward outputs to connected networks using NNP protocol
-    async fn forward_outputs(&self, outputs: &[f64]) -> Result<(), ProtocolError> {
+    async fn forward_outputs(&self, outputs: &[f64], flags: u8) -> Result<(), ProtocolError> {
         if self.config.output_endpoints.is_empty() {
             return Ok(());
         }
@@ -213,7 +656,7 @@ impl NetworkServer {
             debug!("   Outputs: {:?}", outputs);
             
             // Try to connect and send outputs via NNP protocol
-            if let Err(e) = self.send_outputs_via_nnp(endpoint, outputs).await {
+            if let Err(e) = self.send_outputs_via_nnp(endpoint, outputs, flags).await {
                 warn!("⚠️ Failed to send outputs to {}: {:?}", endpoint, e);
             } else {
                 info!("✅ Successfully sent outputs to {} via NNP", endpoint);
@@ -224,33 +667,62 @@ impl NetworkServer {
     }
 
     /// Send outputs to a specific endpoint using NNP protocol
-    async fn send_outputs_via_nnp(&self, endpoint: &str, outputs: &[f64]) -> Result<(), ProtocolError> {
+    async fn send_outputs_via_nnp(
+        &self,
+        endpoint: &str,
+        outputs: &[f64],
+        flags: u8,
+    ) -> Result<(), ProtocolError> {
         // Parse the endpoint address and port
-        let (address, port) = if endpoint.contains(':') {
-            let parts: Vec<&str> = endpoint.split(':').collect();
-            if parts.len() != 2 {
-                return Err(ProtocolError::InvalidPayload);
-            }
-            let port = parts[1].parse::<u16>()
-                .map_err(|_| ProtocolError::InvalidPayload)?;
-            (parts[0].to_string(), port)
-        } else {
-            (endpoint.to_string(), 8080) // Default NNP port
-        };
+        // Default NNP port is 8080; IPv6 literals must be bracketed when a port is given
+        let (address, port) = address::parse_host_port(endpoint, 8080)
+            .map_err(|_| ProtocolError::InvalidPayload)?;
         
         // Connect to the endpoint and send via NNP
         let peer_id = self.distributed_network.connect_to(&address, port).await?;
         
         // Send forward data using NNP protocol
         // Layer 0 represents the output layer of this network
-        self.distributed_network.send_forward_data(peer_id, 0, outputs.to_vec()).await?;
+        self.distributed_network
+            .send_forward_data_flagged(peer_id, 0, outputs.to_vec(), flags)
+            .await?;
         
         Ok(())
     }
 }
 
+/// Outputs for a batch of inputs, in order
+///
+/// Without online learning the whole batch goes through `forward_batch` in
+/// parallel; online learning adapts weights between requests, so they run one
+/// at a time. Hebbian updates follow each request's forward pass either way.
+fn forward_requests(
+    network: &mut NeuralNetwork,
+    batch: &[Vec<f64>],
+    hebbian_learning: bool,
+) -> Vec<Vec<f64>> {
+    let outputs = if network.is_online_learning() {
+        batch.iter().map(|inputs| network.forward(inputs).0).collect()
+    } else {
+        network.forward_batch(batch)
+    };
+    if hebbian_learning {
+        for inputs in batch {
+            network.hebbian_update(inputs);
+        }
+        info!("🧠 Applied Hebbian learning update to {} requests", batch.len());
+    }
+    outputs
+}
+
 /// Run server in daemon mode using existing distributed network infrastructure
-pub async fn run_daemon(network: NeuralNetwork, config: ServerConfig) -> Result<(), ProtocolError> {
+///
+/// A `mapped` model is served from its mapping, see `NetworkServer::with_mapped_model`.
+pub async fn run_daemon(
+    network: NeuralNetwork,
+    mapped: Option<MappedModel>,
+    config: ServerConfig,
+) -> Result<(), ProtocolError> {
     // Initialize logging
     env_logger::init();
 
@@ -267,8 +739,11 @@ pub async fn run_daemon(network: NeuralNetwork, config: ServerConfig) -> Result<
     }
 
     // Create and start the server
-    let server = NetworkServer::new(network, config)
+    let mut server = NetworkServer::new(network, config)
         .map_err(|_| ProtocolError::IoError(std::io::Error::other("Failed to create server")))?;
+    if let Some(model) = mapped {
+        server = server.with_mapped_model(model);
+    }
     server.start().await?;
 
     Ok(())
@@ -335,3 +810,140 @@ impl NetworkClient {
             .await
     }
 }
+
+fn read_network_config(path: &Path) -> Result<NetworkConfig, String> {
+    let file = StagedFile::input(path).map_err(|e| e.to_string())?;
+    NetworkConfig::load_from_file(&file).map_err(|e| e.to_string())
+}
+
+/// Bring `network` in line with a re-read configuration
+///
+/// Learning settings are applied in place, the same way a remote
+/// `ConfigUpdate` is; every other change rebuilds the network and is only
+/// reported as needing a restart.
+pub fn apply_network_config(
+    current: &NetworkConfig,
+    config: &NetworkConfig,
+    network: &mut NeuralNetwork,
+) -> Result<ReloadReport, String> {
+    let mut report = ReloadReport::default();
+    let mut update = ConfigUpdate::default();
+    for field in reload::changed_fields(current, config) {
+        match field.as_str() {
+            "hebbian_rate" => update.hebbian_rate = Some(config.hebbian_rate as f32),
+            "decay_rate" => update.decay_rate = Some(config.decay_rate as f32),
+            "online_learning" => update.online_learning = Some(config.online_learning),
+            _ => report.restart_required(field),
+        }
+    }
+    if !update.is_empty() {
+        update.validate()?;
+        update.apply(network);
+        report.applied(format!("Learning settings updated: {:?}", update));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forward_requests_matches_single_requests() {
+        let network = NeuralNetwork::with_layers(&[3, 5, 2], 0.1);
+        let batch = vec![vec![0.2, -0.4, 0.9], vec![1.0, 0.0, 0.5], vec![0.3, 0.3, 0.3]];
+
+        let mut batched = network.clone();
+        let outputs = forward_requests(&mut batched, &batch, false);
+        assert_eq!(outputs.len(), 3);
+        for (inputs, outputs) in batch.iter().zip(&outputs) {
+            let expected = network.forward_static(inputs).0;
+            for (a, b) in outputs.iter().zip(&expected) {
+                assert!((a - b).abs() < 1e-12);
+            }
+        }
+
+        // Online learning changes the weights between requests, so order matters
+        let mut online = network.clone();
+        online.set_online_learning(true);
+        let mut sequential = online.clone();
+        let outputs = forward_requests(&mut online, &batch, false);
+        for (inputs, outputs) in batch.iter().zip(&outputs) {
+            assert_eq!(outputs, &sequential.forward(inputs).0);
+        }
+    }
+
+    #[test]
+    fn test_apply_network_config_updates_learning_settings() {
+        let current = NetworkConfig::default();
+        let mut network = NeuralNetwork::with_layers(&[3, 5, 2], 0.1);
+
+        let mut config = current.clone();
+        config.hebbian_rate = 0.02;
+        config.online_learning = !current.online_learning;
+        config.learning_rate = current.learning_rate * 2.0;
+        let report = apply_network_config(&current, &config, &mut network).unwrap();
+        assert_eq!(report.applied.len(), 1);
+        assert_eq!(report.restart_required, vec!["learning_rate".to_string()]);
+        assert!((network.get_hebbian_rate() - 0.02).abs() < 1e-9);
+        assert_eq!(network.is_online_learning(), config.online_learning);
+
+        config.decay_rate = 10.0;
+        assert!(apply_network_config(&current, &config, &mut network).is_err());
+        assert!(apply_network_config(&current, &current, &mut network)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_explicit_model_replaces_restored_network() {
+        let path = std::env::temp_dir().join(format!("benny-state-{}.json", Uuid::new_v4()));
+        let (node, _) = DistributedNetwork::new(
+            "node".to_string(),
+            "127.0.0.1".to_string(),
+            0,
+            NeuralNetwork::with_layers(&[3, 5, 2], 0.1),
+        );
+        node.snapshot(&path).unwrap();
+        let config = ServerConfig {
+            state_file: Some(path.clone()),
+            ..ServerConfig::default()
+        };
+
+        let model = NeuralNetwork::with_layers(&[4, 2], 0.1);
+        let restored = NetworkServer::new(model.clone(), config.clone()).unwrap();
+        let layers = restored.distributed_network.network.lock().unwrap().get_layers().to_vec();
+        assert_eq!(layers, [3, 5, 2]);
+
+        let config = ServerConfig {
+            model_file: Some(PathBuf::from("model.json")),
+            ..config
+        };
+        let explicit = NetworkServer::new(model, config).unwrap();
+        assert_eq!(explicit.distributed_network.id, restored.distributed_network.id);
+        let layers = explicit.distributed_network.network.lock().unwrap().get_layers().to_vec();
+        assert_eq!(layers, [4, 2]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_mapped_model_answers_forward_requests() {
+        let path = std::env::temp_dir().join(format!("benny-served-{}.mmap", Uuid::new_v4()));
+        let model = NeuralNetwork::with_layers(&[3, 4, 2], 0.1);
+        model.save_to_mmap(&path).unwrap();
+        let mapped = MappedModel::open(&path).unwrap();
+        let server = NetworkServer::new(mapped.skeleton(), ServerConfig::default())
+            .unwrap()
+            .with_mapped_model(mapped);
+
+        let inputs = [0.2, 0.7, -0.1];
+        let mut skeleton = server.distributed_network.network.lock().unwrap().clone();
+        assert_eq!(skeleton.get_layers(), model.get_layers());
+        assert_eq!(
+            server.forward(&mut skeleton, &inputs),
+            model.forward_static(&inputs).0
+        );
+        drop(server);
+        let _ = std::fs::remove_file(&path);
+    }
+}
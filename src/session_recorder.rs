@@ -0,0 +1,185 @@
+use crate::cli::TrainingData;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Where and how an InputServer records the inputs it sends
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingConfig {
+    /// Dataset file, `.csv` or `.json`; samples are appended to an existing file
+    pub path: PathBuf,
+    /// WebSocket URL of a paired OutputServer whose outputs become the targets
+    #[serde(default)]
+    pub output_url: Option<String>,
+    /// Longest wait for the output answering an input, in milliseconds
+    #[serde(default = "default_pairing_window_ms")]
+    pub pairing_window_ms: u64,
+    /// Record only the inputs sent to this network; every network when unset
+    #[serde(default)]
+    pub network_id: Option<String>,
+}
+
+fn default_pairing_window_ms() -> u64 {
+    1000
+}
+
+impl RecordingConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            output_url: None,
+            pairing_window_ms: default_pairing_window_ms(),
+            network_id: None,
+        }
+    }
+}
+
+/// Turns an interactive session into a training dataset
+///
+/// Without a paired OutputServer every input is saved with an empty target
+/// (JSON only). With one, an input waits up to the pairing window for the
+/// next output and is saved with it as the target; inputs superseded or
+/// unanswered within the window are dropped. The file is rewritten after
+/// every sample, so an interrupted session loses nothing.
+pub struct SessionRecorder {
+    config: RecordingConfig,
+    data: TrainingData,
+    pending: Option<(Vec<f64>, Instant)>,
+    dropped: usize,
+}
+
+impl SessionRecorder {
+    pub fn new(config: RecordingConfig) -> Result<Self, String> {
+        let csv = match config.path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => true,
+            Some("json") => false,
+            _ => {
+                return Err(format!(
+                    "Recording file {} must end in .csv or .json",
+                    config.path.display()
+                ))
+            }
+        };
+        if csv && config.output_url.is_none() {
+            return Err("CSV recordings need paired outputs as their target column".to_string());
+        }
+        let data = if config.path.exists() {
+            let loaded = if csv {
+                TrainingData::load_from_csv(&config.path)
+            } else {
+                TrainingData::load_from_json(&config.path)
+            };
+            loaded.map_err(|e| format!("Cannot append to {}: {}", config.path.display(), e))?
+        } else {
+            TrainingData {
+                inputs: Vec::new(),
+                targets: Vec::new(),
+            }
+        };
+        Ok(Self {
+            config,
+            data,
+            pending: None,
+            dropped: 0,
+        })
+    }
+
+    pub fn config(&self) -> &RecordingConfig {
+        &self.config
+    }
+
+    /// Samples in the dataset, including those already in the file when recording began
+    pub fn len(&self) -> usize {
+        self.data.inputs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.inputs.is_empty()
+    }
+
+    /// Inputs that never got a paired output
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Note inputs sent to `network_id`, returning whether a sample was saved
+    pub fn record_input(&mut self, network_id: &str, inputs: &[f64]) -> Result<bool, String> {
+        if self
+            .config
+            .network_id
+            .as_deref()
+            .is_some_and(|id| id != network_id)
+        {
+            return Ok(false);
+        }
+        if self.config.output_url.is_none() {
+            return self.push(inputs.to_vec(), Vec::new());
+        }
+        if self.pending.replace((inputs.to_vec(), Instant::now())).is_some() {
+            self.dropped += 1;
+        }
+        Ok(false)
+    }
+
+    /// Pair observed outputs with the waiting input, returning whether a sample was saved
+    pub fn record_output(&mut self, outputs: &[f64]) -> Result<bool, String> {
+        let window = Duration::from_millis(self.config.pairing_window_ms);
+        match self.pending.take() {
+            Some((inputs, sent)) if sent.elapsed() <= window => self.push(inputs, outputs.to_vec()),
+            Some(_) => {
+                self.dropped += 1;
+                Ok(false)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn push(&mut self, inputs: Vec<f64>, targets: Vec<f64>) -> Result<bool, String> {
+        self.data.inputs.push(inputs);
+        self.data.targets.push(targets);
+        let saved = if self.config.path.extension().is_some_and(|e| e == "csv") {
+            self.data.save_to_csv(&self.config.path)
+        } else {
+            self.data.save_to_json(&self.config.path)
+        };
+        if let Err(e) = saved {
+            self.data.inputs.pop();
+            self.data.targets.pop();
+            return Err(format!("Cannot write {}: {}", self.config.path.display(), e));
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inputs_pair_with_observed_outputs() {
+        let path = std::env::temp_dir().join(format!("benny-session-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert!(SessionRecorder::new(RecordingConfig::new(&path)).is_err());
+        assert!(SessionRecorder::new(RecordingConfig::new("session.txt")).is_err());
+
+        let mut config = RecordingConfig::new(&path);
+        config.output_url = Some("ws://127.0.0.1:12001".to_string());
+        config.network_id = Some("main-network".to_string());
+        let mut recorder = SessionRecorder::new(config.clone()).unwrap();
+        assert!(!recorder.record_output(&[0.5]).unwrap());
+        assert!(!recorder.record_input("other-network", &[9.0, 9.0]).unwrap());
+        assert!(!recorder.record_input("main-network", &[0.1, 0.2]).unwrap());
+        assert!(!recorder.record_input("main-network", &[0.3, 0.4]).unwrap());
+        assert!(recorder.record_output(&[0.7]).unwrap());
+        assert_eq!(recorder.dropped(), 1);
+
+        // A new session appends to the same file
+        let mut recorder = SessionRecorder::new(config).unwrap();
+        recorder.record_input("main-network", &[0.5, 0.6]).unwrap();
+        recorder.record_output(&[0.9]).unwrap();
+        let data = TrainingData::load_from_csv(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(data.inputs, vec![vec![0.3, 0.4], vec![0.5, 0.6]]);
+        assert_eq!(data.targets, vec![vec![0.7], vec![0.9]]);
+    }
+}
@@ -0,0 +1,290 @@
+use crate::cli::TrainingData;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where and how much served traffic is captured for retraining
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CaptureConfig {
+    /// Dataset file, `.csv` or `.json`; each file of the rotation gets a number
+    /// before the extension (`traffic.json` becomes `traffic-000001.json`, ...)
+    pub path: PathBuf,
+    /// Share of the requests captured
+    #[serde(default = "default_sample_rate")]
+    pub sample_rate: f64,
+    /// Samples per file before the next one is started
+    #[serde(default = "default_max_samples")]
+    pub max_samples: usize,
+    /// Files kept; the oldest is deleted when a new one would exceed it
+    #[serde(default = "default_max_files")]
+    pub max_files: usize,
+    /// Captured samples held in memory between writes of the current file
+    #[serde(default = "default_flush_every")]
+    pub flush_every: usize,
+    /// Input columns never written, e.g. identifiers or other personal data
+    #[serde(default)]
+    pub drop_inputs: Vec<usize>,
+    /// Seed of the sampling; drawn from entropy when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+fn default_sample_rate() -> f64 {
+    0.01
+}
+
+fn default_max_samples() -> usize {
+    10_000
+}
+
+fn default_max_files() -> usize {
+    10
+}
+
+fn default_flush_every() -> usize {
+    100
+}
+
+impl CaptureConfig {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            sample_rate: default_sample_rate(),
+            max_samples: default_max_samples(),
+            max_files: default_max_files(),
+            flush_every: default_flush_every(),
+            drop_inputs: Vec::new(),
+            seed: None,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.sample_rate) {
+            return Err(format!(
+                "Capture sample rate {} is not between 0 and 1",
+                self.sample_rate
+            ));
+        }
+        if self.max_samples == 0 || self.max_files == 0 {
+            return Err("Captures need room for at least one sample and one file".to_string());
+        }
+        match self.path.extension().and_then(|e| e.to_str()) {
+            Some("csv" | "json") => Ok(()),
+            _ => Err(format!(
+                "Capture file {} must end in .csv or .json",
+                self.path.display()
+            )),
+        }
+    }
+
+    /// File number `index` of the rotation
+    pub fn file(&self, index: u64) -> PathBuf {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = self.path.extension().unwrap_or_default().to_string_lossy();
+        self.path
+            .with_file_name(format!("{}-{:06}.{}", stem, index, extension))
+    }
+
+    /// Numbers of the rotation files already on disk, oldest first
+    pub fn existing_files(&self) -> Vec<u64> {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = self.path.extension().unwrap_or_default().to_string_lossy();
+        let directory = match self.path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        let mut indices: Vec<u64> = fs::read_dir(directory)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name().into_string().ok()?;
+                let index = name
+                    .strip_prefix(&format!("{}-", stem))?
+                    .strip_suffix(&format!(".{}", extension))?;
+                (index.len() == 6).then(|| index.parse().ok())?
+            })
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+/// Edits a captured `(inputs, prediction)` pair in place, or rejects it by returning `false`
+pub type CaptureFilter = Box<dyn FnMut(&mut Vec<f64>, &mut Vec<f64>) -> bool + Send>;
+
+/// Samples served `(input, prediction)` pairs into a rotating dataset
+///
+/// A random `sample_rate` share of the requests is kept, stripped of the
+/// `drop_inputs` columns and passed through the filters, and written with
+/// the prediction as its target, so every file can be fed to `train -d`
+/// once the targets are checked or relabelled. Numbering continues after
+/// the files already on disk, so restarts never overwrite a capture.
+pub struct ShadowCapture {
+    config: CaptureConfig,
+    filters: Vec<CaptureFilter>,
+    rng: StdRng,
+    file: u64,
+    data: TrainingData,
+    unflushed: usize,
+    captured: u64,
+}
+
+impl ShadowCapture {
+    pub fn new(config: CaptureConfig) -> Result<Self, String> {
+        config.validate()?;
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let file = config.existing_files().last().map_or(1, |last| last + 1);
+        Ok(Self {
+            config,
+            filters: Vec::new(),
+            rng,
+            file,
+            data: TrainingData {
+                inputs: Vec::new(),
+                targets: Vec::new(),
+            },
+            unflushed: 0,
+            captured: 0,
+        })
+    }
+
+    /// Run `filter` on every sample before it is written, after the dropped columns are removed
+    pub fn with_filter<F>(mut self, filter: F) -> Self
+    where
+        F: FnMut(&mut Vec<f64>, &mut Vec<f64>) -> bool + Send + 'static,
+    {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    pub fn config(&self) -> &CaptureConfig {
+        &self.config
+    }
+
+    /// Samples captured since the start
+    pub fn captured(&self) -> u64 {
+        self.captured
+    }
+
+    /// File the next samples go to
+    pub fn current_file(&self) -> PathBuf {
+        self.config.file(self.file)
+    }
+
+    /// Maybe capture one served request, returning whether it was kept
+    pub fn record(&mut self, inputs: &[f64], prediction: &[f64]) -> Result<bool, String> {
+        if self.rng.gen::<f64>() >= self.config.sample_rate {
+            return Ok(false);
+        }
+        let mut inputs: Vec<f64> = inputs
+            .iter()
+            .enumerate()
+            .filter(|(column, _)| !self.config.drop_inputs.contains(column))
+            .map(|(_, &value)| value)
+            .collect();
+        let mut prediction = prediction.to_vec();
+        for filter in &mut self.filters {
+            if !filter(&mut inputs, &mut prediction) {
+                return Ok(false);
+            }
+        }
+
+        self.data.inputs.push(inputs);
+        self.data.targets.push(prediction);
+        self.unflushed += 1;
+        self.captured += 1;
+        if self.data.inputs.len() >= self.config.max_samples {
+            self.flush()?;
+            self.rotate();
+        } else if self.unflushed >= self.config.flush_every {
+            self.flush()?;
+        }
+        Ok(true)
+    }
+
+    /// Write the current file with every sample captured into it so far
+    pub fn flush(&mut self) -> Result<(), String> {
+        if self.unflushed == 0 {
+            return Ok(());
+        }
+        let path = self.current_file();
+        let saved = if path.extension().is_some_and(|e| e == "csv") {
+            self.data.save_to_csv(&path)
+        } else {
+            self.data.save_to_json(&path)
+        };
+        saved.map_err(|e| format!("Cannot write capture {}: {}", path.display(), e))?;
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Start the next file, deleting the oldest beyond `max_files`
+    fn rotate(&mut self) {
+        self.file += 1;
+        self.data.inputs.clear();
+        self.data.targets.clear();
+        let existing = self.config.existing_files();
+        // The new file is not on disk yet but counts towards the limit
+        let excess = (existing.len() + 1).saturating_sub(self.config.max_files);
+        for &old in existing.iter().take(excess) {
+            let _ = fs::remove_file(self.config.file(old));
+        }
+    }
+}
+
+impl Drop for ShadowCapture {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_samples_filters_and_rotates() {
+        let dir = std::env::temp_dir().join(format!("benny-capture-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut config = CaptureConfig::new(dir.join("traffic.json"));
+        config.sample_rate = 1.0;
+        config.max_samples = 2;
+        config.max_files = 2;
+        config.drop_inputs = vec![0];
+        assert!(CaptureConfig::new("traffic.txt").validate().is_err());
+
+        let mut capture = ShadowCapture::new(config.clone())
+            .unwrap()
+            .with_filter(|_, prediction| prediction[0] >= 0.0);
+        for i in 0..5 {
+            let user_id = 1000.0 + i as f64;
+            capture.record(&[user_id, i as f64], &[0.5]).unwrap();
+        }
+        assert!(!capture.record(&[0.0, 9.0], &[-1.0]).unwrap());
+        drop(capture);
+
+        // Five samples: two full files, the first of which was rotated out, and one more
+        assert_eq!(config.existing_files(), vec![2, 3]);
+        let second = TrainingData::load_from_json(config.file(2)).unwrap();
+        assert_eq!(second.inputs, vec![vec![2.0], vec![3.0]]);
+        assert_eq!(second.targets, vec![vec![0.5], vec![0.5]]);
+        let third = TrainingData::load_from_json(config.file(3)).unwrap();
+        assert_eq!(third.inputs, vec![vec![4.0]]);
+
+        // A restart carries on after the last file
+        let capture = ShadowCapture::new(config.clone()).unwrap();
+        assert_eq!(capture.current_file(), config.file(4));
+
+        // Nothing is kept at a zero rate
+        config.sample_rate = 0.0;
+        let mut capture = ShadowCapture::new(config).unwrap();
+        assert!(!capture.record(&[0.0, 1.0], &[0.5]).unwrap());
+        assert_eq!(capture.captured(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,432 @@
+use crate::datasets::gaussian;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A parameterized virtual sensor, contributing one or more channels to every sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum VirtualSensor {
+    /// `offset + amplitude * sin(2π f t + phase)` plus Gaussian noise of standard deviation `noise`
+    NoisySine {
+        #[serde(default = "one")]
+        amplitude: f64,
+        frequency_hz: f64,
+        #[serde(default)]
+        phase: f64,
+        #[serde(default)]
+        offset: f64,
+        #[serde(default)]
+        noise: f64,
+    },
+    /// Gaussian steps of standard deviation `step`, reflected back inside `[min, max]` when set
+    RandomWalk {
+        #[serde(default)]
+        start: f64,
+        step: f64,
+        #[serde(default)]
+        min: Option<f64>,
+        #[serde(default)]
+        max: Option<f64>,
+    },
+    /// Cycles through `levels`, holding each for `hold_s` seconds
+    Step { levels: Vec<f64>, hold_s: f64 },
+    /// `channels` Gaussian channels whose pairwise correlation is `correlation`
+    Correlated {
+        channels: usize,
+        correlation: f64,
+        #[serde(default)]
+        mean: f64,
+        #[serde(default = "one")]
+        std_dev: f64,
+    },
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+impl VirtualSensor {
+    pub fn channel_count(&self) -> usize {
+        match self {
+            VirtualSensor::Correlated { channels, .. } => *channels,
+            _ => 1,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            VirtualSensor::NoisySine {
+                amplitude,
+                frequency_hz,
+                noise,
+                ..
+            } => {
+                if !(amplitude.is_finite() && frequency_hz.is_finite() && *noise >= 0.0) {
+                    return Err(format!(
+                        "Sine sensor needs a finite amplitude and frequency and a non-negative noise, not {}, {} Hz and {}",
+                        amplitude, frequency_hz, noise
+                    ));
+                }
+            }
+            VirtualSensor::RandomWalk {
+                start,
+                step,
+                min,
+                max,
+            } => {
+                if !(*step >= 0.0 && step.is_finite()) {
+                    return Err(format!("Random walk step {} must be non-negative", step));
+                }
+                let low = min.unwrap_or(f64::NEG_INFINITY);
+                let high = max.unwrap_or(f64::INFINITY);
+                if !(low < high && (low..=high).contains(start)) {
+                    return Err(format!(
+                        "Random walk start {} must lie within [{}, {}]",
+                        start, low, high
+                    ));
+                }
+                if step * 2.0 > high - low {
+                    return Err(format!(
+                        "Random walk step {} is too large for [{}, {}]",
+                        step, low, high
+                    ));
+                }
+            }
+            VirtualSensor::Step { levels, hold_s } => {
+                if levels.is_empty() {
+                    return Err("Step sensor needs at least one level".to_string());
+                }
+                if !(*hold_s > 0.0 && hold_s.is_finite()) {
+                    return Err(format!("Step hold {} s must be positive", hold_s));
+                }
+            }
+            VirtualSensor::Correlated {
+                channels,
+                correlation,
+                std_dev,
+                ..
+            } => {
+                if *channels == 0 {
+                    return Err("Correlated sensor group needs at least one channel".to_string());
+                }
+                if !(0.0..=1.0).contains(correlation) {
+                    return Err(format!(
+                        "Correlation {} must lie within [0, 1]",
+                        correlation
+                    ));
+                }
+                if std_dev.is_nan() || *std_dev < 0.0 {
+                    return Err(format!(
+                        "Standard deviation {} must be non-negative",
+                        std_dev
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Virtual sensors feeding an `InputNode` in place of hardware
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimSourceConfig {
+    /// Channels of every sample, sensor after sensor
+    pub sensors: Vec<VirtualSensor>,
+    /// Samples per second; sensors are evaluated at `n / rate_hz` for sample `n`
+    #[serde(default = "default_rate_hz")]
+    pub rate_hz: f64,
+    /// Seed of the noise; drawn from entropy when unset
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Stop after this many samples; runs until the node stops when unset
+    #[serde(default)]
+    pub samples: Option<u64>,
+}
+
+fn default_rate_hz() -> f64 {
+    10.0
+}
+
+impl SimSourceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sensors.is_empty() {
+            return Err("A simulated source needs at least one sensor".to_string());
+        }
+        if !(self.rate_hz > 0.0 && self.rate_hz.is_finite()) {
+            return Err(format!("Sensor rate {} Hz must be positive", self.rate_hz));
+        }
+        for (i, sensor) in self.sensors.iter().enumerate() {
+            sensor
+                .validate()
+                .map_err(|e| format!("Sensor {}: {}", i, e))?;
+        }
+        Ok(())
+    }
+
+    pub fn channel_count(&self) -> usize {
+        self.sensors.iter().map(VirtualSensor::channel_count).sum()
+    }
+
+    /// Time between two samples
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate_hz)
+    }
+}
+
+/// Running state of the configured sensors
+///
+/// Samples depend only on the seed and their index, so a seeded run
+/// reproduces exactly whatever the timing of the caller.
+#[derive(Debug, Clone)]
+pub struct VirtualSensors {
+    config: SimSourceConfig,
+    rng: StdRng,
+    sample: u64,
+    /// Current position of every random walk, by sensor
+    walks: Vec<f64>,
+}
+
+impl VirtualSensors {
+    pub fn new(config: SimSourceConfig) -> Result<Self, String> {
+        config.validate()?;
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        let walks = config
+            .sensors
+            .iter()
+            .map(|sensor| match sensor {
+                VirtualSensor::RandomWalk { start, .. } => *start,
+                _ => 0.0,
+            })
+            .collect();
+        Ok(Self {
+            config,
+            rng,
+            sample: 0,
+            walks,
+        })
+    }
+
+    pub fn config(&self) -> &SimSourceConfig {
+        &self.config
+    }
+
+    /// Whether the configured number of samples has been produced
+    pub fn is_finished(&self) -> bool {
+        self.config
+            .samples
+            .is_some_and(|samples| self.sample >= samples)
+    }
+
+    /// Read every sensor once, advancing time by one sample
+    pub fn next_sample(&mut self) -> Vec<f64> {
+        let t = self.sample as f64 / self.config.rate_hz;
+        let mut values = Vec::with_capacity(self.config.channel_count());
+        for (i, sensor) in self.config.sensors.iter().enumerate() {
+            match sensor {
+                VirtualSensor::NoisySine {
+                    amplitude,
+                    frequency_hz,
+                    phase,
+                    offset,
+                    noise,
+                } => {
+                    let clean = offset + amplitude * (2.0 * PI * frequency_hz * t + phase).sin();
+                    values.push(clean + noise * gaussian(&mut self.rng));
+                }
+                VirtualSensor::RandomWalk { step, min, max, .. } => {
+                    values.push(self.walks[i]);
+                    let mut next = self.walks[i] + step * gaussian(&mut self.rng);
+                    // Steps are small against the range, so one reflection keeps the walk inside
+                    if let Some(min) = *min {
+                        if next < min {
+                            next = 2.0 * min - next;
+                        }
+                    }
+                    if let Some(max) = *max {
+                        if next > max {
+                            next = 2.0 * max - next;
+                        }
+                    }
+                    self.walks[i] = next.clamp(
+                        min.unwrap_or(f64::NEG_INFINITY),
+                        max.unwrap_or(f64::INFINITY),
+                    );
+                }
+                VirtualSensor::Step { levels, hold_s } => {
+                    let level = (t / hold_s).floor() as usize % levels.len();
+                    values.push(levels[level]);
+                }
+                VirtualSensor::Correlated {
+                    channels,
+                    correlation,
+                    mean,
+                    std_dev,
+                } => {
+                    let shared = gaussian(&mut self.rng);
+                    let (common, own) = (correlation.sqrt(), (1.0 - correlation).sqrt());
+                    for _ in 0..*channels {
+                        let z = common * shared + own * gaussian(&mut self.rng);
+                        values.push(mean + std_dev * z);
+                    }
+                }
+            }
+        }
+        self.sample += 1;
+        values
+    }
+}
+
+/// A command received by a virtual actuator
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActuatorCommand {
+    /// Time since the actuator was created
+    pub elapsed: Duration,
+    pub values: Vec<f64>,
+}
+
+/// Records the outputs delivered to it, for tests and demos without hardware
+///
+/// Clones share one log, so a handle kept by the caller sees every command
+/// the `OutputNode` delivers to the copy it was given.
+#[derive(Debug, Clone)]
+pub struct VirtualActuator {
+    name: String,
+    verbose: bool,
+    started: Instant,
+    commands: Arc<Mutex<Vec<ActuatorCommand>>>,
+}
+
+impl VirtualActuator {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            verbose: false,
+            started: Instant::now(),
+            commands: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Also print every command as it arrives
+    pub fn verbose(mut self) -> Self {
+        self.verbose = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn record(&self, values: &[f64]) {
+        if self.verbose {
+            println!("🦾 {} received {:?}", self.name, values);
+        }
+        self.commands.lock().unwrap().push(ActuatorCommand {
+            elapsed: self.started.elapsed(),
+            values: values.to_vec(),
+        });
+    }
+
+    /// Every command received so far, oldest first
+    pub fn commands(&self) -> Vec<ActuatorCommand> {
+        self.commands.lock().unwrap().clone()
+    }
+
+    pub fn last(&self) -> Option<ActuatorCommand> {
+        self.commands.lock().unwrap().last().cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        self.commands.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_sensors_and_actuator() {
+        let config: SimSourceConfig = serde_json::from_str(
+            r#"{
+                "sensors": [
+                    {"type": "NoisySine", "frequency_hz": 1.0, "offset": 2.0},
+                    {"type": "RandomWalk", "start": 0.5, "step": 0.1, "min": 0.0, "max": 1.0},
+                    {"type": "Step", "levels": [0, 1, 5], "hold_s": 0.5},
+                    {"type": "Correlated", "channels": 2, "correlation": 0.9}
+                ],
+                "rate_hz": 4,
+                "seed": 3,
+                "samples": 2000
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.channel_count(), 5);
+
+        let mut sensors = VirtualSensors::new(config.clone()).unwrap();
+        let samples: Vec<Vec<f64>> = (0..2000).map(|_| sensors.next_sample()).collect();
+        assert!(sensors.is_finished());
+
+        // Noise-free sine at quarter periods, and steps held for two samples each
+        let sine: Vec<f64> = samples[..4].iter().map(|s| s[0]).collect();
+        for (value, expected) in sine.iter().zip([2.0, 3.0, 2.0, 1.0]) {
+            assert!((value - expected).abs() < 1e-9);
+        }
+        let steps: Vec<f64> = samples[..7].iter().map(|s| s[2]).collect();
+        assert_eq!(steps, vec![0.0, 0.0, 1.0, 1.0, 5.0, 5.0, 0.0]);
+
+        assert_eq!(samples[0][1], 0.5);
+        assert!(samples.iter().all(|s| (0.0..=1.0).contains(&s[1])));
+        assert!(samples.windows(2).any(|w| w[0][1] != w[1][1]));
+
+        // Sample correlation of the group close to the configured one
+        let pairs: Vec<(f64, f64)> = samples.iter().map(|s| (s[3], s[4])).collect();
+        let n = pairs.len() as f64;
+        let (ma, mb) = pairs
+            .iter()
+            .fold((0.0, 0.0), |(a, b), (x, y)| (a + x / n, b + y / n));
+        let (cov, va, vb) = pairs.iter().fold((0.0, 0.0, 0.0), |(c, va, vb), (x, y)| {
+            (
+                c + (x - ma) * (y - mb),
+                va + (x - ma).powi(2),
+                vb + (y - mb).powi(2),
+            )
+        });
+        assert!((cov / (va * vb).sqrt() - 0.9).abs() < 0.05);
+
+        // A seeded run reproduces exactly
+        let mut again = VirtualSensors::new(config.clone()).unwrap();
+        assert_eq!(again.next_sample(), samples[0]);
+
+        let actuator = VirtualActuator::new("arm");
+        let handle = actuator.clone();
+        actuator.record(&[0.25, 0.75]);
+        actuator.record(&[1.0]);
+        assert_eq!(handle.len(), 2);
+        assert_eq!(handle.last().unwrap().values, vec![1.0]);
+        assert!(handle.commands()[0].elapsed <= handle.commands()[1].elapsed);
+
+        let mut invalid = config;
+        invalid.sensors[3] = VirtualSensor::Correlated {
+            channels: 2,
+            correlation: 1.5,
+            mean: 0.0,
+            std_dev: 1.0,
+        };
+        assert!(invalid.validate().unwrap_err().starts_with("Sensor 3"));
+    }
+}
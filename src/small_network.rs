@@ -0,0 +1,153 @@
+//! Allocation-free inference for tiny networks whose shape is known at compile time
+
+use crate::activation::Activation;
+use crate::inference::{activate, InferenceModel};
+use alloc::format;
+use alloc::string::String;
+
+/// A trained `I`-`H`-`O` network stored in fixed-size arrays
+///
+/// For control-sized networks (e.g. 4-8-2) the heap allocations of
+/// `NeuralNetwork::forward_static` cost more than the arithmetic; here a
+/// forward pass touches only the stack. Like `InferenceModel`, it computes in
+/// `f32` and is available without the `std` feature.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SmallNetwork<const I: usize, const H: usize, const O: usize> {
+    hidden_weights: [[f32; I]; H], // hidden_weights[to][from]
+    hidden_biases: [f32; H],
+    hidden_activation: Activation,
+    output_weights: [[f32; H]; O], // output_weights[to][from]
+    output_biases: [f32; O],
+    output_activation: Activation,
+}
+
+impl<const I: usize, const H: usize, const O: usize> SmallNetwork<I, H, O> {
+    pub fn new(
+        hidden_weights: [[f32; I]; H],
+        hidden_biases: [f32; H],
+        hidden_activation: Activation,
+        output_weights: [[f32; H]; O],
+        output_biases: [f32; O],
+        output_activation: Activation,
+    ) -> Self {
+        Self {
+            hidden_weights,
+            hidden_biases,
+            hidden_activation,
+            output_weights,
+            output_biases,
+            output_activation,
+        }
+    }
+
+    /// Output activations for `inputs`
+    pub fn forward(&self, inputs: &[f32; I]) -> [f32; O] {
+        let hidden = layer(
+            &self.hidden_weights,
+            &self.hidden_biases,
+            self.hidden_activation,
+            inputs,
+        );
+        layer(
+            &self.output_weights,
+            &self.output_biases,
+            self.output_activation,
+            &hidden,
+        )
+    }
+}
+
+fn layer<const N: usize, const M: usize>(
+    weights: &[[f32; N]; M],
+    biases: &[f32; M],
+    activation: Activation,
+    inputs: &[f32; N],
+) -> [f32; M] {
+    let mut outputs = [0.0; M];
+    for ((output, row), &bias) in outputs.iter_mut().zip(weights).zip(biases) {
+        let sum = row.iter().zip(inputs).fold(bias, |sum, (w, x)| sum + w * x);
+        *output = activate(activation, sum);
+    }
+    outputs
+}
+
+impl<const I: usize, const H: usize, const O: usize> TryFrom<&InferenceModel>
+    for SmallNetwork<I, H, O>
+{
+    type Error = String;
+
+    fn try_from(model: &InferenceModel) -> Result<Self, String> {
+        let shape = model.layers();
+        if shape.len() != 2
+            || shape[0].inputs() != I
+            || shape[0].outputs() != H
+            || shape[1].outputs() != O
+        {
+            let sizes: String = shape
+                .iter()
+                .map(|layer| format!("{}-", layer.inputs()))
+                .collect();
+            return Err(format!(
+                "Model is {}{}, not {}-{}-{}",
+                sizes,
+                model.output_size(),
+                I,
+                H,
+                O
+            ));
+        }
+
+        let mut network = Self::new(
+            [[0.0; I]; H],
+            [0.0; H],
+            shape[0].activation(),
+            [[0.0; H]; O],
+            [0.0; O],
+            shape[1].activation(),
+        );
+        for (to, row) in network.hidden_weights.iter_mut().enumerate() {
+            row.copy_from_slice(shape[0].row(to));
+        }
+        network.hidden_biases.copy_from_slice(shape[0].biases());
+        for (to, row) in network.output_weights.iter_mut().enumerate() {
+            row.copy_from_slice(shape[1].row(to));
+        }
+        network.output_biases.copy_from_slice(shape[1].biases());
+        Ok(network)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<const I: usize, const H: usize, const O: usize> TryFrom<&crate::NeuralNetwork>
+    for SmallNetwork<I, H, O>
+{
+    type Error = String;
+
+    /// Convert a trained plain network with a single hidden layer
+    fn try_from(network: &crate::NeuralNetwork) -> Result<Self, String> {
+        Self::try_from(&network.to_inference_model()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::NeuralNetwork;
+
+    #[test]
+    fn test_small_network_matches_inference_model() {
+        let mut trained = NeuralNetwork::with_layers(&[4, 8, 2], 0.1);
+        trained.set_activation(0, Activation::Relu);
+        trained.train(&[0.1, 0.4, 0.2, 0.9], &[1.0, 0.0]);
+        let model = trained.to_inference_model().unwrap();
+
+        let small = SmallNetwork::<4, 8, 2>::try_from(&trained).unwrap();
+        let inputs = [0.3, -0.2, 0.8, 0.5];
+        assert_eq!(small.forward(&inputs).to_vec(), model.forward(&inputs));
+
+        let error = SmallNetwork::<4, 6, 2>::try_from(&model).unwrap_err();
+        assert_eq!(error, "Model is 4-8-2, not 4-6-2");
+        let deep = NeuralNetwork::with_layers(&[4, 8, 8, 2], 0.1);
+        assert!(SmallNetwork::<4, 8, 2>::try_from(&deep).is_err());
+    }
+}
@@ -0,0 +1,238 @@
+use crate::neural_network::NeuralNetwork;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// Histogram bins used to estimate the entropy of each output unit
+const ENTROPY_BINS: usize = 16;
+
+/// Settings of a soak run
+#[derive(Debug, Clone)]
+pub struct SoakConfig {
+    /// Wall-clock length of the run
+    pub duration: Duration,
+    /// Time between two health samples
+    pub sample_interval: Duration,
+    /// Stop after this many online steps even if time remains
+    pub max_steps: Option<u64>,
+    /// Seed of the synthetic input generator
+    pub seed: u64,
+    /// Fail once the weight norm exceeds this value
+    pub max_weight_norm: f64,
+    /// Fail once the output entropy of a sample window drops below this many bits
+    pub min_entropy: f64,
+}
+
+impl Default for SoakConfig {
+    fn default() -> Self {
+        Self {
+            duration: Duration::from_secs(3600),
+            sample_interval: Duration::from_secs(60),
+            max_steps: None,
+            seed: 0,
+            max_weight_norm: 1e6,
+            min_entropy: 0.0,
+        }
+    }
+}
+
+/// Health of the network at one point of a soak run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SoakSample {
+    pub elapsed_secs: f64,
+    pub steps: u64,
+    pub weight_norm: f64,
+    /// Mean entropy in bits of each output unit's values over the sample window
+    pub output_entropy: f64,
+    /// Whether every output of the window and every weight was finite
+    pub finite: bool,
+}
+
+/// Outcome of a soak run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SoakReport {
+    pub seed: u64,
+    pub steps: u64,
+    pub samples: Vec<SoakSample>,
+    /// Why the network was considered destabilized, if it was
+    pub failure: Option<String>,
+}
+
+impl SoakReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Noisy repetitions of a few seeded prototype patterns
+struct SyntheticInputs {
+    prototypes: Vec<Vec<f64>>,
+    rng: StdRng,
+}
+
+impl SyntheticInputs {
+    const PROTOTYPES: usize = 4;
+    const NOISE: f64 = 0.1;
+
+    fn new(size: usize, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let prototypes = (0..Self::PROTOTYPES)
+            .map(|_| (0..size).map(|_| rng.gen_range(0.0..1.0)).collect())
+            .collect();
+        Self { prototypes, rng }
+    }
+
+    fn next(&mut self) -> Vec<f64> {
+        let prototype = &self.prototypes[self.rng.gen_range(0..self.prototypes.len())];
+        prototype
+            .iter()
+            .map(|x| x + self.rng.gen_range(-Self::NOISE..Self::NOISE))
+            .collect()
+    }
+}
+
+/// Run online learning on synthetic inputs until `config.duration` elapses or the network destabilizes
+///
+/// Every `sample_interval` the weight norm, output entropy and finiteness are
+/// recorded and passed to `on_sample`. Non-finite values, a weight norm above
+/// `max_weight_norm` or an output entropy below `min_entropy` end the run with
+/// a failure. A final sample is always taken.
+pub fn soak<F>(network: &mut NeuralNetwork, config: &SoakConfig, mut on_sample: F) -> SoakReport
+where
+    F: FnMut(&SoakSample),
+{
+    let mut inputs = SyntheticInputs::new(network.get_layers()[0], config.seed);
+    let start = Instant::now();
+    let mut next_sample = config.sample_interval;
+    let mut window: Vec<Vec<f64>> = Vec::new();
+    let mut report = SoakReport {
+        seed: config.seed,
+        steps: 0,
+        samples: Vec::new(),
+        failure: None,
+    };
+
+    loop {
+        let elapsed = start.elapsed();
+        let done =
+            elapsed >= config.duration || config.max_steps.is_some_and(|max| report.steps >= max);
+        if done || elapsed >= next_sample {
+            let sample = SoakSample {
+                elapsed_secs: elapsed.as_secs_f64(),
+                steps: report.steps,
+                weight_norm: network.weight_norm(),
+                output_entropy: output_entropy(&window),
+                finite: network.weight_norm().is_finite()
+                    && window.iter().flatten().all(|x| x.is_finite()),
+            };
+            on_sample(&sample);
+            report.failure = check(&sample, config);
+            report.samples.push(sample);
+            window.clear();
+            next_sample = elapsed + config.sample_interval;
+            if done || report.failure.is_some() {
+                return report;
+            }
+        }
+
+        let (outputs, _) = network.forward_with_online_learning(&inputs.next());
+        report.steps += 1;
+        if outputs.iter().any(|x| !x.is_finite()) {
+            // Sample right away rather than running on with a broken network
+            next_sample = Duration::ZERO;
+        }
+        window.push(outputs);
+    }
+}
+
+fn check(sample: &SoakSample, config: &SoakConfig) -> Option<String> {
+    if !sample.finite {
+        Some(format!(
+            "Non-finite outputs or weights after {} steps",
+            sample.steps
+        ))
+    } else if sample.weight_norm > config.max_weight_norm {
+        Some(format!(
+            "Weight norm {:.3e} exceeds {:.3e} after {} steps",
+            sample.weight_norm, config.max_weight_norm, sample.steps
+        ))
+    } else if sample.steps > 0 && sample.output_entropy < config.min_entropy {
+        Some(format!(
+            "Output entropy {:.4} bits fell below {:.4} after {} steps",
+            sample.output_entropy, config.min_entropy, sample.steps
+        ))
+    } else {
+        None
+    }
+}
+
+/// Mean entropy in bits of each output unit, with values binned over [0, 1]
+fn output_entropy(window: &[Vec<f64>]) -> f64 {
+    let Some(units) = window.first().map(Vec::len).filter(|&n| n > 0) else {
+        return 0.0;
+    };
+    let total: f64 = (0..units)
+        .map(|unit| {
+            let mut bins = [0usize; ENTROPY_BINS];
+            for outputs in window {
+                let value = outputs[unit];
+                let bin = if value.is_finite() {
+                    (value.clamp(0.0, 1.0) * ENTROPY_BINS as f64) as usize
+                } else {
+                    0
+                };
+                bins[bin.min(ENTROPY_BINS - 1)] += 1;
+            }
+            bins.iter()
+                .filter(|&&count| count > 0)
+                .map(|&count| {
+                    let p = count as f64 / window.len() as f64;
+                    p * (1.0 / p).log2()
+                })
+                .sum::<f64>()
+        })
+        .sum();
+    total / units as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_entropy() {
+        assert_eq!(output_entropy(&[]), 0.0);
+        assert_eq!(output_entropy(&[vec![0.5], vec![0.5]]), 0.0);
+        assert_eq!(output_entropy(&[vec![0.0], vec![1.0]]), 1.0);
+    }
+
+    #[test]
+    fn test_stable_network_passes() {
+        let mut network = NeuralNetwork::with_layers(&[4, 6, 3], 0.01);
+        let config = SoakConfig {
+            sample_interval: Duration::ZERO,
+            max_steps: Some(200),
+            seed: 3,
+            ..SoakConfig::default()
+        };
+        let mut seen = 0;
+        let report = soak(&mut network, &config, |_| seen += 1);
+        assert!(report.passed(), "{:?}", report.failure);
+        assert_eq!(report.steps, 200);
+        assert_eq!(seen, report.samples.len());
+        assert!(report.samples.iter().all(|s| s.finite));
+    }
+
+    #[test]
+    fn test_weight_growth_fails() {
+        let mut network = NeuralNetwork::with_layers(&[4, 6, 3], 0.01);
+        let config = SoakConfig {
+            max_steps: Some(10),
+            max_weight_norm: network.weight_norm() * 0.5,
+            ..SoakConfig::default()
+        };
+        let report = soak(&mut network, &config, |_| {});
+        assert!(report.failure.unwrap().contains("Weight norm"));
+    }
+}
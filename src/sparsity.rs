@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+/// Population sparseness of one layer's activations
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Sparseness {
+    /// Hoyer measure: 0 when all values are equal, 1 when one value carries everything
+    pub hoyer: f64,
+    /// Gini index of the value magnitudes: 0 when all are equal, approaching 1 for one winner
+    pub gini: f64,
+    /// Fraction of values that are non-zero
+    pub active_fraction: f64,
+}
+
+impl Sparseness {
+    pub fn of(values: &[f64]) -> Self {
+        let active = values.iter().filter(|&&x| x != 0.0).count();
+        Self {
+            hoyer: hoyer(values),
+            gini: gini(values),
+            active_fraction: if values.is_empty() {
+                0.0
+            } else {
+                active as f64 / values.len() as f64
+            },
+        }
+    }
+}
+
+/// Hoyer sparseness `(sqrt(n) - L1 / L2) / (sqrt(n) - 1)`, in [0, 1]
+///
+/// Zero vectors and single values count as not sparse.
+pub fn hoyer(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let l1: f64 = values.iter().map(|x| x.abs()).sum();
+    let l2 = values.iter().map(|x| x * x).sum::<f64>().sqrt();
+    if values.len() < 2 || l2 == 0.0 {
+        return 0.0;
+    }
+    ((n.sqrt() - l1 / l2) / (n.sqrt() - 1.0)).clamp(0.0, 1.0)
+}
+
+/// Gini index of the value magnitudes (Hurley and Rickard, 2009), in [0, 1)
+pub fn gini(values: &[f64]) -> f64 {
+    let mut sorted: Vec<f64> = values.iter().map(|x| x.abs()).collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let l1: f64 = sorted.iter().sum();
+    if l1 == 0.0 {
+        return 0.0;
+    }
+    let n = sorted.len() as f64;
+    1.0 - 2.0
+        * sorted
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x / l1 * (n - i as f64 - 0.5) / n)
+            .sum::<f64>()
+}
+
+/// Keep the `k` largest values and zero the rest; ties go to the lower index
+pub fn k_winners_take_all(values: &mut [f64], k: usize) {
+    if k >= values.len() {
+        return;
+    }
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| values[b].total_cmp(&values[a]).then(a.cmp(&b)));
+    for &loser in &order[k..] {
+        values[loser] = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparseness_measures() {
+        let dense = [0.5; 4];
+        let one_hot = [0.0, 0.0, 0.9, 0.0];
+        assert_eq!(hoyer(&dense), 0.0);
+        assert!((hoyer(&one_hot) - 1.0).abs() < 1e-12);
+        assert_eq!(gini(&dense), 0.0);
+        assert!((gini(&one_hot) - 0.75).abs() < 1e-12);
+        assert!(hoyer(&[0.1, 0.2, 0.9, 0.0]) > hoyer(&[0.4, 0.5, 0.6, 0.5]));
+        assert_eq!(hoyer(&[0.0; 3]), 0.0);
+        assert_eq!(Sparseness::of(&one_hot).active_fraction, 0.25);
+    }
+
+    #[test]
+    fn test_k_winners_take_all() {
+        let mut values = [0.3, 0.9, 0.1, 0.9, 0.5];
+        k_winners_take_all(&mut values, 3);
+        assert_eq!(values, [0.0, 0.9, 0.0, 0.9, 0.5]);
+        k_winners_take_all(&mut values, 1);
+        assert_eq!(values, [0.0, 0.9, 0.0, 0.0, 0.0]);
+        k_winners_take_all(&mut values, 10);
+        assert_eq!(values, [0.0, 0.9, 0.0, 0.0, 0.0]);
+    }
+}
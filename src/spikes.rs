@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// A neuron firing at a point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpikeEvent {
+    pub neuron: u32,
+    /// Firing time in microseconds
+    pub timestamp: u64,
+}
+
+/// Spike-timing-dependent plasticity: pair-based, with exponential windows
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StdpParams {
+    /// Potentiation when the presynaptic spike precedes the postsynaptic one
+    pub a_plus: f64,
+    /// Depression when the postsynaptic spike precedes the presynaptic one
+    pub a_minus: f64,
+    /// Time constant of the potentiation window, in microseconds
+    pub tau_plus: f64,
+    /// Time constant of the depression window, in microseconds
+    pub tau_minus: f64,
+}
+
+impl Default for StdpParams {
+    fn default() -> Self {
+        Self {
+            a_plus: 0.01,
+            a_minus: 0.012,
+            tau_plus: 20_000.0,
+            tau_minus: 20_000.0,
+        }
+    }
+}
+
+impl StdpParams {
+    /// Weight change for a postsynaptic spike `dt` microseconds after a presynaptic one
+    pub fn window(&self, dt: i64) -> f64 {
+        if dt > 0 {
+            self.a_plus * (-(dt as f64) / self.tau_plus).exp()
+        } else if dt < 0 {
+            -self.a_minus * (dt as f64 / self.tau_minus).exp()
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Spike events for every neuron whose activation reaches `threshold`
+pub fn encode_spikes(activations: &[f64], threshold: f64, timestamp: u64) -> Vec<SpikeEvent> {
+    activations
+        .iter()
+        .enumerate()
+        .filter(|(_, &activation)| activation >= threshold)
+        .map(|(neuron, _)| SpikeEvent {
+            neuron: neuron as u32,
+            timestamp,
+        })
+        .collect()
+}
+
+/// Dense activations from spikes: each neuron's latest spike decays with time constant `tau`
+///
+/// A neuron that fired at `now` reads 1.0, one that never fired (or only
+/// fires after `now`) reads 0.0. Events for neurons beyond `size` are ignored.
+pub fn decode_spikes(events: &[SpikeEvent], size: usize, now: u64, tau: f64) -> Vec<f64> {
+    let mut activations = vec![0.0; size];
+    for event in events {
+        let Some(activation) = activations.get_mut(event.neuron as usize) else {
+            continue;
+        };
+        if event.timestamp <= now {
+            let age = (now - event.timestamp) as f64;
+            *activation = f64::max(*activation, (-age / tau).exp());
+        }
+    }
+    activations
+}
+
+/// Apply pair-based STDP between presynaptic and postsynaptic spikes to `weights[pre][post]`
+///
+/// Every pair of a presynaptic and a postsynaptic spike contributes. Spikes
+/// of neurons outside `weights` are ignored.
+pub fn stdp_update(
+    weights: &mut [Vec<f64>],
+    pre_spikes: &[SpikeEvent],
+    post_spikes: &[SpikeEvent],
+    params: &StdpParams,
+) {
+    for pre in pre_spikes {
+        let Some(row) = weights.get_mut(pre.neuron as usize) else {
+            continue;
+        };
+        for post in post_spikes {
+            if let Some(weight) = row.get_mut(post.neuron as usize) {
+                *weight += params.window(post.timestamp as i64 - pre.timestamp as i64);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spike(neuron: u32, timestamp: u64) -> SpikeEvent {
+        SpikeEvent { neuron, timestamp }
+    }
+
+    #[test]
+    fn test_encode_decode_spikes() {
+        let events = encode_spikes(&[0.2, 0.9, 0.5, 0.7], 0.5, 1_000);
+        assert_eq!(
+            events,
+            vec![spike(1, 1_000), spike(2, 1_000), spike(3, 1_000)]
+        );
+
+        // Neuron 0 fired twice, 9 is out of range and 3 only fires later
+        let events = [
+            spike(0, 0),
+            spike(0, 1_000),
+            spike(2, 1_000),
+            spike(9, 1_000),
+            spike(3, 5_000),
+        ];
+        let dense = decode_spikes(&events, 4, 1_000, 1_000.0);
+        assert_eq!(dense[0], 1.0);
+        assert_eq!(dense[1], 0.0);
+        assert_eq!(dense[2], 1.0);
+        assert_eq!(dense[3], 0.0);
+
+        let dense = decode_spikes(&[spike(0, 0)], 1, 1_000, 1_000.0);
+        assert!((dense[0] - (-1.0f64).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_stdp_potentiates_causal_pairs() {
+        let params = StdpParams::default();
+        let mut weights = vec![vec![0.5, 0.5], vec![0.5, 0.5]];
+        // Neuron 0 fires before post 0 and after post 1; pre neuron 1 is silent
+        stdp_update(
+            &mut weights,
+            &[spike(0, 10_000), spike(7, 10_000)],
+            &[spike(0, 15_000), spike(1, 5_000)],
+            &params,
+        );
+        assert!(weights[0][0] > 0.5);
+        assert!(weights[0][1] < 0.5);
+        assert_eq!(weights[1], vec![0.5, 0.5]);
+        assert!((weights[0][0] - 0.5 - params.window(5_000)).abs() < 1e-12);
+        assert_eq!(params.window(0), 0.0);
+    }
+}
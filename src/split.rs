@@ -0,0 +1,212 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How samples are assigned to the train, validation and test sets (`[training.split]`)
+///
+/// By default the validation and test sets are taken from the tail of the
+/// data, in file order.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SplitConfig {
+    /// Assign samples at random (from `seed`) instead of from the tail
+    #[serde(default)]
+    pub shuffle: bool,
+    /// Keep each class's share the same in every set
+    ///
+    /// The class of a multi-output target is its largest output; a single
+    /// output is rounded to the nearest integer.
+    #[serde(default)]
+    pub stratify: bool,
+    #[serde(default)]
+    pub seed: u64,
+    /// Reuse the split stored here, or store the computed split if the file is missing
+    #[serde(default)]
+    pub indices_file: Option<PathBuf>,
+}
+
+/// Sample indices of each set
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataSplit {
+    pub train: Vec<usize>,
+    pub validation: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+impl DataSplit {
+    /// Split `targets.len()` samples into `validation` and `test` fractions, the rest training
+    pub fn compute(
+        targets: &[Vec<f64>],
+        validation: f64,
+        test: f64,
+        config: &SplitConfig,
+    ) -> Result<Self, String> {
+        if validation < 0.0 || test < 0.0 || validation + test >= 1.0 {
+            return Err(format!(
+                "Validation ({}) and test ({}) fractions must be non-negative and sum to less than 1",
+                validation, test
+            ));
+        }
+
+        let mut strata: BTreeMap<i64, Vec<usize>> = BTreeMap::new();
+        for (index, target) in targets.iter().enumerate() {
+            let class = if config.stratify { class_of(target) } else { 0 };
+            strata.entry(class).or_default().push(index);
+        }
+
+        let mut rng = StdRng::seed_from_u64(config.seed);
+        let mut split = DataSplit::default();
+        for mut indices in strata.into_values() {
+            if config.shuffle {
+                indices.shuffle(&mut rng);
+            }
+            let n = indices.len() as f64;
+            let train_end = ((1.0 - validation - test) * n) as usize;
+            let validation_end = (((1.0 - test) * n) as usize).max(train_end);
+            split.train.extend_from_slice(&indices[..train_end]);
+            split
+                .validation
+                .extend_from_slice(&indices[train_end..validation_end]);
+            split.test.extend_from_slice(&indices[validation_end..]);
+        }
+
+        for set in [&mut split.train, &mut split.validation, &mut split.test] {
+            if config.shuffle {
+                set.shuffle(&mut rng);
+            } else {
+                set.sort_unstable();
+            }
+        }
+        Ok(split)
+    }
+
+    /// The split stored in `config.indices_file`, or a newly computed one (stored there if set)
+    pub fn load_or_compute(
+        targets: &[Vec<f64>],
+        validation: f64,
+        test: f64,
+        config: &SplitConfig,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if let Some(path) = &config.indices_file {
+            if path.exists() {
+                let split = Self::load(path)?;
+                split.check(targets.len())?;
+                return Ok(split);
+            }
+        }
+        let split = Self::compute(targets, validation, test, config)?;
+        if let Some(path) = &config.indices_file {
+            split.save(path)?;
+        }
+        Ok(split)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn std::error::Error>> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn std::error::Error>> {
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Error unless every index refers to one of `samples` samples, at most once
+    pub fn check(&self, samples: usize) -> Result<(), String> {
+        let mut seen = vec![false; samples];
+        for &index in self.train.iter().chain(&self.validation).chain(&self.test) {
+            match seen.get_mut(index) {
+                Some(seen) if !*seen => *seen = true,
+                Some(_) => return Err(format!("Sample {} is in more than one set", index)),
+                None => {
+                    return Err(format!(
+                        "Split refers to sample {} but the data has {} samples",
+                        index, samples
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Rows of `rows` at `indices`, in order
+pub fn select(rows: &[Vec<f64>], indices: &[usize]) -> Vec<Vec<f64>> {
+    indices.iter().map(|&index| rows[index].clone()).collect()
+}
+
+fn class_of(target: &[f64]) -> i64 {
+    match target {
+        [value] => value.round() as i64,
+        _ => target
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .map_or(0, |(index, _)| index as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stratified_split_is_reproducible() {
+        // 80 samples of class 0 followed by 20 of class 1
+        let targets: Vec<Vec<f64>> = (0..100).map(|i| vec![(i >= 80) as u8 as f64]).collect();
+        let count = |set: &[usize]| set.iter().filter(|&&i| i >= 80).count();
+
+        // The default keeps the old behaviour: validation from the tail
+        let tail = DataSplit::compute(&targets, 0.2, 0.0, &SplitConfig::default()).unwrap();
+        assert_eq!(tail.train, (0..80).collect::<Vec<_>>());
+        assert_eq!(count(&tail.validation), 20);
+
+        let config = SplitConfig {
+            shuffle: true,
+            stratify: true,
+            seed: 3,
+            indices_file: None,
+        };
+        let split = DataSplit::compute(&targets, 0.2, 0.1, &config).unwrap();
+        assert_eq!(
+            (split.train.len(), split.validation.len(), split.test.len()),
+            (70, 20, 10)
+        );
+        assert_eq!(
+            (
+                count(&split.train),
+                count(&split.validation),
+                count(&split.test)
+            ),
+            (14, 4, 2)
+        );
+        split.check(100).unwrap();
+        assert_eq!(
+            split,
+            DataSplit::compute(&targets, 0.2, 0.1, &config).unwrap()
+        );
+        assert!(split.check(90).is_err());
+        assert!(DataSplit::compute(&targets, 0.6, 0.4, &config).is_err());
+
+        let path = std::env::temp_dir().join(format!("benny-split-{}.json", uuid::Uuid::new_v4()));
+        let persisted = SplitConfig {
+            seed: 4,
+            indices_file: Some(path.clone()),
+            ..config
+        };
+        let stored = DataSplit::load_or_compute(&targets, 0.2, 0.1, &persisted).unwrap();
+        assert_ne!(stored, split);
+        // A different seed no longer matters once the split is stored
+        let reloaded = SplitConfig {
+            seed: 5,
+            ..persisted
+        };
+        assert_eq!(
+            DataSplit::load_or_compute(&targets, 0.2, 0.1, &reloaded).unwrap(),
+            stored
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
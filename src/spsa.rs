@@ -0,0 +1,222 @@
+use crate::neural_network::NeuralNetwork;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+/// Gradient-free training by simultaneous perturbation (`[training.spsa]`)
+///
+/// Off unless `enabled`. Step `k` perturbs every parameter by
+/// `±perturbation / (k + 1)^gamma` and moves them by
+/// `learning_rate / (k + 1 + stability)^alpha` times the estimated gradient,
+/// Spall's usual gain sequences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpsaConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_learning_rate")]
+    pub learning_rate: f64,
+    /// Size of the perturbation of the first step
+    #[serde(default = "default_perturbation")]
+    pub perturbation: f64,
+    /// Decay exponent of the learning rate
+    #[serde(default = "default_alpha")]
+    pub alpha: f64,
+    /// Decay exponent of the perturbation
+    #[serde(default = "default_gamma")]
+    pub gamma: f64,
+    /// Steps added to the learning rate decay, keeping the first steps small
+    #[serde(default = "default_stability")]
+    pub stability: f64,
+    #[serde(default)]
+    pub seed: u64,
+}
+
+fn default_learning_rate() -> f64 {
+    0.1
+}
+
+fn default_perturbation() -> f64 {
+    0.05
+}
+
+fn default_alpha() -> f64 {
+    0.602
+}
+
+fn default_gamma() -> f64 {
+    0.101
+}
+
+fn default_stability() -> f64 {
+    10.0
+}
+
+impl Default for SpsaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            learning_rate: default_learning_rate(),
+            perturbation: default_perturbation(),
+            alpha: default_alpha(),
+            gamma: default_gamma(),
+            stability: default_stability(),
+            seed: 0,
+        }
+    }
+}
+
+/// Simultaneous perturbation stochastic approximation of a network's gradient
+///
+/// Each step needs only two evaluations of a scalar loss, whatever the
+/// number of parameters: all of them are nudged in a random ±1 direction,
+/// the loss is measured on both sides, and its difference estimates the
+/// slope along every parameter at once. The loss can be any score of the
+/// network, such as a reward from an environment, so the network's learning
+/// mode and whether backpropagation is enabled do not matter. The weights,
+/// biases, normalization, skip and embedding parameters are trained.
+#[derive(Debug, Clone)]
+pub struct Spsa {
+    config: SpsaConfig,
+    rng: StdRng,
+    iteration: u64,
+}
+
+impl Spsa {
+    pub fn new(config: SpsaConfig) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(config.seed),
+            config,
+            iteration: 0,
+        }
+    }
+
+    pub fn config(&self) -> &SpsaConfig {
+        &self.config
+    }
+
+    /// Steps taken so far
+    pub fn iteration(&self) -> u64 {
+        self.iteration
+    }
+
+    /// Learning rate and perturbation of the next step
+    pub fn gains(&self) -> (f64, f64) {
+        let k = self.iteration as f64;
+        let config = &self.config;
+        (
+            config.learning_rate / (k + 1.0 + config.stability).powf(config.alpha),
+            config.perturbation / (k + 1.0).powf(config.gamma),
+        )
+    }
+
+    /// Move `network` against the estimated gradient of `loss`, lower being better
+    ///
+    /// Returns the mean of the two perturbed losses, an estimate of the loss
+    /// before the step.
+    pub fn step<F>(&mut self, network: &mut NeuralNetwork, mut loss: F) -> f64
+    where
+        F: FnMut(&NeuralNetwork) -> f64,
+    {
+        let (rate, perturbation) = self.gains();
+        let original: Vec<f64> = network
+            .backprop_parameters_mut()
+            .into_iter()
+            .map(|p| *p)
+            .collect();
+        let direction: Vec<f64> = original
+            .iter()
+            .map(|_| if self.rng.gen::<bool>() { 1.0 } else { -1.0 })
+            .collect();
+
+        let mut perturbed = |sign: f64, network: &mut NeuralNetwork| {
+            for ((p, original), d) in network
+                .backprop_parameters_mut()
+                .into_iter()
+                .zip(&original)
+                .zip(&direction)
+            {
+                *p = original + sign * perturbation * d;
+            }
+            loss(network)
+        };
+        let plus = perturbed(1.0, network);
+        let minus = perturbed(-1.0, network);
+
+        // With ±1 directions, dividing by a component equals multiplying by it
+        let slope = (plus - minus) / (2.0 * perturbation);
+        for ((p, original), d) in network
+            .backprop_parameters_mut()
+            .into_iter()
+            .zip(&original)
+            .zip(&direction)
+        {
+            *p = original - rate * slope * d;
+        }
+        self.iteration += 1;
+        (plus + minus) / 2.0
+    }
+
+    /// One step on the squared error `0.5 * sum((target - output)^2)` of a batch, averaged over its samples
+    pub fn step_supervised(
+        &mut self,
+        network: &mut NeuralNetwork,
+        batch: &[(Vec<f64>, Vec<f64>)],
+    ) -> f64 {
+        self.step(network, |network| {
+            let total: f64 = batch
+                .iter()
+                .map(|(inputs, targets)| {
+                    let outputs = network.forward_static(inputs).0;
+                    0.5 * outputs
+                        .iter()
+                        .zip(targets)
+                        .map(|(o, t)| (t - o).powi(2))
+                        .sum::<f64>()
+                })
+                .sum();
+            total / batch.len().max(1) as f64
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spsa_minimizes_a_scalar_score() {
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.0);
+        let batch: Vec<(Vec<f64>, Vec<f64>)> = vec![
+            (vec![0.0, 0.0], vec![0.2]),
+            (vec![0.0, 1.0], vec![0.8]),
+            (vec![1.0, 0.0], vec![0.8]),
+            (vec![1.0, 1.0], vec![0.2]),
+        ];
+        let mut spsa = Spsa::new(SpsaConfig {
+            enabled: true,
+            seed: 3,
+            ..SpsaConfig::default()
+        });
+        let (first_rate, first_perturbation) = spsa.gains();
+
+        let first = spsa.step_supervised(&mut network, &batch);
+        let mut last = first;
+        for _ in 0..500 {
+            last = spsa.step_supervised(&mut network, &batch);
+        }
+        assert!(last < first, "loss went from {} to {}", first, last);
+        assert_eq!(spsa.iteration(), 501);
+        let (rate, perturbation) = spsa.gains();
+        assert!(rate < first_rate && perturbation < first_perturbation);
+
+        // Only the score matters: pull the single output towards 0.9
+        let mut spsa = Spsa::new(SpsaConfig::default());
+        let score =
+            |network: &NeuralNetwork| (network.forward_static(&[1.0, 1.0]).0[0] - 0.9).abs();
+        let before = score(&network);
+        for _ in 0..300 {
+            spsa.step(&mut network, score);
+        }
+        assert!(score(&network) < before);
+    }
+}
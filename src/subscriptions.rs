@@ -0,0 +1,310 @@
+use crate::distributed_network::{MessagePayload, NetworkId, NetworkMessage};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Events of each type held back until the first subscriber registers
+pub const DEFAULT_QUEUE_CAPACITY: usize = 1024;
+
+/// Forward activations received from a peer
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForwardDataEvent {
+    pub sender: NetworkId,
+    pub sequence: u64,
+    pub layer_id: u8,
+    pub data: Vec<f64>,
+    /// Send time in microseconds on this node's clock, if the sender stamped it
+    pub timestamp: Option<u64>,
+    /// Channel the sender tagged the data with, if any
+    pub channel: Option<String>,
+}
+
+/// Layer parameters pushed by a peer
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightSyncEvent {
+    pub sender: NetworkId,
+    pub sequence: u64,
+    pub layer_id: u8,
+    pub weights: Vec<f64>,
+    pub biases: Vec<f64>,
+}
+
+/// A peer announcing that it leaves the cluster
+#[derive(Debug, Clone, PartialEq)]
+pub struct DisconnectEvent {
+    pub sender: NetworkId,
+    pub reason: String,
+}
+
+type Callback<T> = Arc<dyn Fn(&T) + Send + Sync>;
+
+/// Subscribers of one event type, and the events waiting for the first of them
+struct Channel<T> {
+    callbacks: Vec<Callback<T>>,
+    backlog: VecDeque<T>,
+    dropped: u64,
+}
+
+impl<T> Default for Channel<T> {
+    fn default() -> Self {
+        Self {
+            callbacks: Vec::new(),
+            backlog: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+}
+
+impl<T> Channel<T> {
+    fn subscribe(&mut self, callback: Callback<T>) {
+        for event in self.backlog.drain(..) {
+            callback(&event);
+        }
+        self.callbacks.push(callback);
+    }
+
+    /// Hand `event` to the subscribers or queue it, returning `false` if it was dropped
+    fn publish(&mut self, event: T, capacity: usize) -> bool {
+        if !self.callbacks.is_empty() {
+            for callback in &self.callbacks {
+                callback(&event);
+            }
+            return true;
+        }
+        if capacity == 0 {
+            self.dropped += 1;
+            return false;
+        }
+        if self.backlog.len() >= capacity {
+            self.backlog.pop_front();
+            self.dropped += 1;
+        }
+        self.backlog.push_back(event);
+        true
+    }
+}
+
+struct Inner {
+    capacity: usize,
+    forward_data: Channel<ForwardDataEvent>,
+    weight_sync: Channel<WeightSyncEvent>,
+    disconnect: Channel<DisconnectEvent>,
+}
+
+/// Typed subscriptions to the messages a `DistributedNetwork` receives
+///
+/// Callbacks run in order of arrival on the connection task that received the
+/// message, so they should return quickly and must not subscribe again.
+/// Until a type has its first subscriber, up to `capacity` events of that type
+/// are queued and replayed to it on registration; older ones are dropped and
+/// counted.
+#[derive(Clone)]
+pub struct Subscriptions {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl Default for Subscriptions {
+    fn default() -> Self {
+        Self::new(DEFAULT_QUEUE_CAPACITY)
+    }
+}
+
+impl Subscriptions {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                capacity,
+                forward_data: Channel::default(),
+                weight_sync: Channel::default(),
+                disconnect: Channel::default(),
+            })),
+        }
+    }
+
+    /// Call `callback` for every forward data message, starting with queued ones
+    pub fn on_forward_data<F>(&self, callback: F)
+    where
+        F: Fn(&ForwardDataEvent) + Send + Sync + 'static,
+    {
+        self.inner
+            .lock()
+            .unwrap()
+            .forward_data
+            .subscribe(Arc::new(callback));
+    }
+
+    /// Call `callback` for every weight sync message, starting with queued ones
+    pub fn on_weight_sync<F>(&self, callback: F)
+    where
+        F: Fn(&WeightSyncEvent) + Send + Sync + 'static,
+    {
+        self.inner
+            .lock()
+            .unwrap()
+            .weight_sync
+            .subscribe(Arc::new(callback));
+    }
+
+    /// Call `callback` whenever a peer leaves with a `Disconnect`, starting with queued ones
+    pub fn on_disconnect<F>(&self, callback: F)
+    where
+        F: Fn(&DisconnectEvent) + Send + Sync + 'static,
+    {
+        self.inner
+            .lock()
+            .unwrap()
+            .disconnect
+            .subscribe(Arc::new(callback));
+    }
+
+    /// Change how many events of each type are queued while nobody subscribes
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+        let Inner {
+            forward_data,
+            weight_sync,
+            disconnect,
+            ..
+        } = &mut *inner;
+        trim(forward_data, capacity);
+        trim(weight_sync, capacity);
+        trim(disconnect, capacity);
+    }
+
+    /// Events dropped because their queue was full, as (forward data, weight sync, disconnect)
+    pub fn dropped(&self) -> (u64, u64, u64) {
+        let inner = self.inner.lock().unwrap();
+        (
+            inner.forward_data.dropped,
+            inner.weight_sync.dropped,
+            inner.disconnect.dropped,
+        )
+    }
+
+    /// Deliver a message received from `sender` to the subscribers of its type
+    ///
+    /// Returns whether subscribers received the message or it was queued for
+    /// them; messages of other types and dropped events return `false`.
+    pub fn dispatch(&self, sender: NetworkId, message: &NetworkMessage) -> bool {
+        self.dispatch_on(sender, message, None)
+    }
+
+    /// Like `dispatch`, naming the channel a tagged forward data message was sent on
+    pub fn dispatch_on(
+        &self,
+        sender: NetworkId,
+        message: &NetworkMessage,
+        channel: Option<String>,
+    ) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        let capacity = inner.capacity;
+        match &message.payload {
+            MessagePayload::ForwardData {
+                layer_id,
+                data,
+                timestamp,
+                ..
+            } => inner.forward_data.publish(
+                ForwardDataEvent {
+                    sender,
+                    sequence: message.sequence,
+                    layer_id: *layer_id,
+                    data: data.iter().map(|&x| x as f64).collect(),
+                    timestamp: *timestamp,
+                    channel,
+                },
+                capacity,
+            ),
+            MessagePayload::WeightSync {
+                layer_id,
+                weights,
+                biases,
+            } => inner.weight_sync.publish(
+                WeightSyncEvent {
+                    sender,
+                    sequence: message.sequence,
+                    layer_id: *layer_id,
+                    weights: weights.iter().map(|&x| x as f64).collect(),
+                    biases: biases.iter().map(|&x| x as f64).collect(),
+                },
+                capacity,
+            ),
+            MessagePayload::Disconnect { reason } => inner.disconnect.publish(
+                DisconnectEvent {
+                    sender,
+                    reason: reason.clone(),
+                },
+                capacity,
+            ),
+            _ => false,
+        }
+    }
+}
+
+fn trim<T>(channel: &mut Channel<T>, capacity: usize) {
+    while channel.backlog.len() > capacity {
+        channel.backlog.pop_front();
+        channel.dropped += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::distributed_network::MessageType;
+
+    fn forward_data(sequence: u64) -> NetworkMessage {
+        NetworkMessage {
+            msg_type: MessageType::ForwardData,
+            sequence,
+            payload: MessagePayload::ForwardData {
+                layer_id: 0,
+                data: vec![sequence as f32],
+                timestamp: None,
+                channel: None,
+            },
+            flags: 0,
+        }
+    }
+
+    #[test]
+    fn test_backlog_is_replayed_to_first_subscriber() {
+        let subscriptions = Subscriptions::new(2);
+        let sender = NetworkId::new_v4();
+        for sequence in 1..=3 {
+            subscriptions.dispatch(sender, &forward_data(sequence));
+        }
+        assert_eq!(subscriptions.dropped(), (1, 0, 0));
+
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let sink = received.clone();
+        subscriptions.on_forward_data(move |event| sink.lock().unwrap().push(event.sequence));
+        assert!(subscriptions.dispatch(sender, &forward_data(4)));
+        assert_eq!(*received.lock().unwrap(), vec![2, 3, 4]);
+
+        let synced = Arc::new(Mutex::new(Vec::new()));
+        let sink = synced.clone();
+        subscriptions.on_weight_sync(move |event| sink.lock().unwrap().push(event.clone()));
+        subscriptions.dispatch(
+            sender,
+            &NetworkMessage {
+                msg_type: MessageType::WeightSync,
+                sequence: 5,
+                payload: MessagePayload::WeightSync {
+                    layer_id: 1,
+                    weights: vec![0.5],
+                    biases: vec![0.25],
+                },
+                flags: 0,
+            },
+        );
+        let synced = synced.lock().unwrap();
+        assert_eq!(synced.len(), 1);
+        assert_eq!(synced[0].sender, sender);
+        assert_eq!(synced[0].weights, vec![0.5]);
+        assert_eq!(received.lock().unwrap().len(), 3);
+
+        // Without a queue, events nobody subscribed to are dropped
+        assert!(!Subscriptions::new(0).dispatch(sender, &forward_data(6)));
+    }
+}
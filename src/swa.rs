@@ -0,0 +1,182 @@
+use crate::neural_network::NeuralNetwork;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Stochastic weight averaging during training (`[training.swa]`)
+///
+/// Off while `window` is 0. From `start_epoch` on, every `interval`-th epoch
+/// contributes its weights, and the average of the last `window` of them is
+/// saved next to the trained model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SwaConfig {
+    /// Number of most recent snapshots averaged
+    #[serde(default)]
+    pub window: usize,
+    /// First epoch whose weights are averaged
+    #[serde(default)]
+    pub start_epoch: usize,
+    /// Epochs between snapshots; match `checkpoint_interval` to average checkpoints
+    #[serde(default = "default_interval")]
+    pub interval: usize,
+    /// Where the averaged model is saved (the output path with `.swa` before
+    /// its extension when unset)
+    #[serde(default)]
+    pub output: Option<PathBuf>,
+}
+
+fn default_interval() -> usize {
+    1
+}
+
+impl Default for SwaConfig {
+    fn default() -> Self {
+        Self {
+            window: 0,
+            start_epoch: 0,
+            interval: default_interval(),
+            output: None,
+        }
+    }
+}
+
+impl SwaConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.window > 0
+    }
+
+    /// Whether the weights after `epoch` join the average
+    pub fn samples_epoch(&self, epoch: usize) -> bool {
+        epoch >= self.start_epoch && (epoch - self.start_epoch + 1).is_multiple_of(self.interval.max(1))
+    }
+
+    /// Path of the averaged model for a model saved to `model_path`
+    pub fn output_path(&self, model_path: &Path) -> PathBuf {
+        if let Some(output) = &self.output {
+            return output.clone();
+        }
+        let stem = model_path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("model");
+        let name = match model_path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => format!("{}.swa.{}", stem, ext),
+            None => format!("{}.swa", stem),
+        };
+        model_path.with_file_name(name)
+    }
+}
+
+/// Running average of the learned values of the last `window` snapshots of a network
+///
+/// Covers every value `CheckpointStore` stores, so batch normalization
+/// statistics are averaged along with the weights. Settings and activation
+/// history of the averaged network come from the latest snapshot.
+#[derive(Debug, Clone)]
+pub struct WeightAverage {
+    window: usize,
+    snapshots: VecDeque<Vec<f64>>,
+    sum: Vec<f64>,
+    latest: Option<NeuralNetwork>,
+}
+
+impl WeightAverage {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            snapshots: VecDeque::new(),
+            sum: Vec::new(),
+            latest: None,
+        }
+    }
+
+    /// Number of snapshots currently averaged
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Add the current state of `network`, dropping the oldest snapshot once the window is full
+    ///
+    /// A network with a different parameter count restarts the average.
+    pub fn update(&mut self, network: &NeuralNetwork) {
+        let mut latest = network.clone();
+        let values: Vec<f64> = latest
+            .state_parameters_mut()
+            .into_iter()
+            .map(|value| *value)
+            .collect();
+        if values.len() != self.sum.len() {
+            self.snapshots.clear();
+            self.sum = vec![0.0; values.len()];
+        }
+
+        for (sum, value) in self.sum.iter_mut().zip(&values) {
+            *sum += value;
+        }
+        self.snapshots.push_back(values);
+        if self.snapshots.len() > self.window {
+            if let Some(oldest) = self.snapshots.pop_front() {
+                for (sum, value) in self.sum.iter_mut().zip(oldest) {
+                    *sum -= value;
+                }
+            }
+        }
+        self.latest = Some(latest);
+    }
+
+    /// Network holding the averaged values, if any snapshot was added
+    pub fn averaged(&self) -> Option<NeuralNetwork> {
+        let mut network = self.latest.clone()?;
+        let count = self.snapshots.len() as f64;
+        for (value, sum) in network.state_parameters_mut().into_iter().zip(&self.sum) {
+            *value = sum / count;
+        }
+        Some(network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_of_last_snapshots() {
+        let mut nn = NeuralNetwork::with_layers(&[2, 1], 0.0);
+        let mut average = WeightAverage::new(2);
+        assert!(average.averaged().is_none());
+
+        for bias in [1.0, 2.0, 4.0] {
+            nn.set_layer_parameters(0, &[bias, -bias], &[bias]).unwrap();
+            average.update(&nn);
+        }
+        assert_eq!(average.len(), 2);
+        let averaged = average.averaged().unwrap();
+        assert_eq!(averaged.get_layer_weights(0), vec![3.0, -3.0]);
+        assert_eq!(averaged.get_layer_biases(0), &[3.0]);
+
+        // A new architecture restarts the average
+        average.update(&NeuralNetwork::with_layers(&[3, 1], 0.0));
+        assert_eq!(average.len(), 1);
+    }
+
+    #[test]
+    fn test_sampled_epochs_and_output_path() {
+        let config = SwaConfig {
+            window: 3,
+            start_epoch: 10,
+            interval: 5,
+            output: None,
+        };
+        let sampled: Vec<usize> = (0..30).filter(|&e| config.samples_epoch(e)).collect();
+        assert_eq!(sampled, vec![14, 19, 24, 29]);
+        assert_eq!(
+            config.output_path(Path::new("out/model.bin")),
+            PathBuf::from("out/model.swa.bin")
+        );
+        assert!(!SwaConfig::default().is_enabled());
+    }
+}
@@ -0,0 +1,662 @@
+use crate::benchmark::{experiment_seed, seed_parameters};
+use crate::cli::TrainingData;
+use crate::config_overrides::{ConfigOverrides, OverrideSource};
+use crate::config_schema::validate_config;
+use crate::dry_run::check_dataset;
+use crate::evaluation::Evaluation;
+use crate::neural_network::NeuralNetwork;
+use crate::split::{self, DataSplit};
+use crate::storage::Location;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+fn default_epochs() -> usize {
+    100
+}
+
+/// A sweep file: one base configuration trained with several sets of overrides
+///
+/// ```toml
+/// config = "config.toml"
+/// data = "data.json"
+/// epochs = 200
+/// seed = "xor-sweep"
+///
+/// [grid]
+/// learning_rate = [0.01, 0.1]
+/// training.batch_size = [8, 32]
+///
+/// [[run]]
+/// name = "wide"
+/// set = { architecture = [2, 16, 1] }
+/// ```
+///
+/// Every combination of the `grid` values is a run, followed by the named
+/// `[[run]]` tables. Relative paths are taken from the sweep file's directory.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepConfig {
+    pub config: PathBuf,
+    pub data: PathBuf,
+    #[serde(default = "default_epochs")]
+    pub epochs: usize,
+    /// Experiment seed of the initial weights: a number or any name (random when unset)
+    #[serde(default)]
+    pub seed: Option<String>,
+    /// Rayon threads of every run (the cores shared out between the parallel runs when unset)
+    #[serde(default)]
+    pub threads_per_run: Option<usize>,
+    /// Settings and the values each is swept over
+    #[serde(default)]
+    pub grid: toml::Table,
+    #[serde(default, rename = "run")]
+    pub runs: Vec<SweepRunSpec>,
+}
+
+/// A named `[[run]]` of a sweep file
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SweepRunSpec {
+    pub name: String,
+    #[serde(default)]
+    pub set: toml::Table,
+    /// Seed of this run's initial weights instead of the sweep's
+    #[serde(default)]
+    pub seed: Option<String>,
+}
+
+/// One training run of a sweep, with the settings it overrides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SweepRun {
+    pub name: String,
+    /// `(dotted key, TOML value)` pairs applied over the base configuration
+    pub settings: Vec<(String, String)>,
+    pub seed: u64,
+}
+
+/// Dotted keys of the leaves of `table`, nested tables included
+fn flatten(prefix: &str, table: &toml::Table, out: &mut Vec<(String, toml::Value)>) {
+    for (key, value) in table {
+        let key = match prefix {
+            "" => key.clone(),
+            prefix => format!("{}.{}", prefix, key),
+        };
+        match value {
+            toml::Value::Table(table) => flatten(&key, table, out),
+            value => out.push((key, value.clone())),
+        }
+    }
+}
+
+/// A value as written in a run name: strings unquoted, the rest as TOML
+fn label(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl SweepConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let mut sweep: Self =
+            toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for file in [&mut sweep.config, &mut sweep.data] {
+            let remote = Location::from_path(file).is_ok_and(|location| location.is_remote());
+            if file.is_relative() && !remote {
+                *file = dir.join(&*file);
+            }
+        }
+        Ok(sweep)
+    }
+
+    /// The grid combinations then the named runs, or the base configuration alone
+    pub fn expand(&self) -> Result<Vec<SweepRun>, String> {
+        let seed = self
+            .seed
+            .as_deref()
+            .map_or_else(rand::random, experiment_seed);
+        let mut axes = Vec::new();
+        flatten("", &self.grid, &mut axes);
+        let mut combinations: Vec<Vec<(String, toml::Value)>> = vec![Vec::new()];
+        for (key, values) in axes {
+            let toml::Value::Array(values) = values else {
+                return Err(format!("grid.{} must be an array of values", key));
+            };
+            if values.is_empty() {
+                return Err(format!("grid.{} has no values", key));
+            }
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    let key = &key;
+                    values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.push((key.clone(), value.clone()));
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        let mut runs: Vec<SweepRun> = combinations
+            .into_iter()
+            .filter(|combination| !combination.is_empty())
+            .map(|combination| SweepRun {
+                name: combination
+                    .iter()
+                    .map(|(key, value)| format!("{}={}", key, label(value)))
+                    .collect::<Vec<_>>()
+                    .join(","),
+                settings: combination
+                    .into_iter()
+                    .map(|(key, value)| (key, value.to_string()))
+                    .collect(),
+                seed,
+            })
+            .collect();
+        for spec in &self.runs {
+            let mut settings = Vec::new();
+            flatten("", &spec.set, &mut settings);
+            runs.push(SweepRun {
+                name: spec.name.clone(),
+                settings: settings
+                    .into_iter()
+                    .map(|(key, value)| (key, value.to_string()))
+                    .collect(),
+                seed: spec.seed.as_deref().map_or(seed, experiment_seed),
+            });
+        }
+        if runs.is_empty() {
+            runs.push(SweepRun {
+                name: "base".to_string(),
+                settings: Vec::new(),
+                seed,
+            });
+        }
+        for (index, run) in runs.iter().enumerate() {
+            if runs[..index].iter().any(|other| other.name == run.name) {
+                return Err(format!("Two runs of the sweep are named '{}'", run.name));
+            }
+        }
+        Ok(runs)
+    }
+}
+
+/// Rayon threads each of `parallel` runs gets on `cores`, unless `requested`
+pub fn threads_per_run(parallel: usize, cores: usize, requested: Option<usize>) -> usize {
+    requested.unwrap_or(cores / parallel.max(1)).max(1)
+}
+
+/// Outcome of one run of a sweep
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepResult {
+    pub name: String,
+    pub settings: BTreeMap<String, String>,
+    pub seed: u64,
+    /// `completed`, `early_stopped`, `diverged` or `failed`
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub epochs: usize,
+    pub parameters: usize,
+    pub train_mse: f64,
+    /// On the validation split, or the training samples when it is empty
+    pub validation_mse: f64,
+    pub validation_accuracy: f64,
+    pub seconds: f64,
+    /// Where the trained model was saved
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<PathBuf>,
+}
+
+impl SweepResult {
+    fn failed(run: &SweepRun, error: String) -> Self {
+        Self {
+            name: run.name.clone(),
+            settings: run.settings.iter().cloned().collect(),
+            seed: run.seed,
+            status: "failed".to_string(),
+            error: Some(error),
+            epochs: 0,
+            parameters: 0,
+            train_mse: f64::NAN,
+            validation_mse: f64::NAN,
+            validation_accuracy: f64::NAN,
+            seconds: 0.0,
+            model: None,
+        }
+    }
+
+    /// Whether training went through and the metrics mean something
+    pub fn is_trained(&self) -> bool {
+        matches!(self.status.as_str(), "completed" | "early_stopped")
+    }
+}
+
+/// Results of every run of a sweep, in the order of the sweep file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SweepReport {
+    pub source: PathBuf,
+    pub parallel: usize,
+    pub threads_per_run: usize,
+    pub seconds: f64,
+    pub results: Vec<SweepResult>,
+}
+
+impl SweepReport {
+    /// Trained run with the lowest validation error
+    pub fn best(&self) -> Option<&SweepResult> {
+        self.results
+            .iter()
+            .filter(|result| result.is_trained())
+            .min_by(|a, b| a.validation_mse.total_cmp(&b.validation_mse))
+    }
+
+    /// `name,status,epochs,parameters,train_mse,validation_mse,validation_accuracy,seconds,seed` rows
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from(
+            "name,status,epochs,parameters,train_mse,validation_mse,validation_accuracy,seconds,seed\n",
+        );
+        for result in &self.results {
+            let _ = writeln!(
+                out,
+                "\"{}\",{},{},{},{},{},{},{},{}",
+                result.name.replace('"', "\"\""),
+                result.status,
+                result.epochs,
+                result.parameters,
+                result.train_mse,
+                result.validation_mse,
+                result.validation_accuracy,
+                result.seconds,
+                result.seed
+            );
+        }
+        out
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "🧪 Sweep of {}: {} runs, {} at a time with {} threads each, in {:.2}s\n",
+            self.source.display(),
+            self.results.len(),
+            self.parallel,
+            self.threads_per_run,
+            self.seconds
+        );
+        let width = self
+            .results
+            .iter()
+            .map(|result| result.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let _ = writeln!(
+            out,
+            "   {:<width$}  status         epochs  train MSE  val MSE   val accuracy  seconds",
+            "run"
+        );
+        let best = self.best().map(|best| best.name.as_str());
+        for result in &self.results {
+            let _ = write!(
+                out,
+                "   {:<width$}  {:<13}  {:>6}",
+                result.name, result.status, result.epochs
+            );
+            if result.is_trained() {
+                let _ = write!(
+                    out,
+                    "  {:>9.6}  {:>8.6}  {:>11.2}%  {:>7.2}",
+                    result.train_mse,
+                    result.validation_mse,
+                    result.validation_accuracy * 100.0,
+                    result.seconds
+                );
+            }
+            if let Some(error) = &result.error {
+                let _ = write!(out, "  {}", error);
+            }
+            if best == Some(result.name.as_str()) {
+                out.push_str("  🏆");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn mse_and_accuracy(
+    network: &NeuralNetwork,
+    inputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+) -> (f64, f64) {
+    let outputs = network.forward_batch(inputs);
+    let evaluation = Evaluation::compute(
+        outputs
+            .iter()
+            .zip(targets)
+            .map(|(output, target)| (output.as_slice(), target.as_slice())),
+    );
+    (evaluation.mse, evaluation.accuracy)
+}
+
+/// Options shared by every run of a sweep
+#[derive(Debug, Clone)]
+pub struct SweepOptions {
+    /// Local configuration and data files of the sweep
+    pub config: PathBuf,
+    pub data: PathBuf,
+    pub epochs: usize,
+    /// Environment and `--set` overrides every run starts from
+    pub base: ConfigOverrides,
+    /// Directory the trained model of every run is saved in, as `<name>.json`
+    pub models_dir: Option<PathBuf>,
+}
+
+/// Train the network `run` describes, per sample with early stopping like `benny train`
+fn train_run(options: &SweepOptions, run: &SweepRun) -> Result<SweepResult, String> {
+    let started = Instant::now();
+    let mut overrides = options.base.clone();
+    for (key, value) in &run.settings {
+        overrides.push(key, value, OverrideSource::Sweep(run.name.clone()));
+    }
+    let content = fs::read_to_string(&options.config)
+        .map_err(|e| format!("{}: {}", options.config.display(), e))?;
+    let content = overrides.apply(&content)?;
+    let (config, mut report) = validate_config(&content, false);
+    overrides.annotate(&mut report);
+    let config = config.ok_or_else(|| {
+        report
+            .errors()
+            .map(|issue| format!("{}: {}", issue.key, issue.message))
+            .collect::<Vec<_>>()
+            .join("; ")
+    })?;
+
+    let data_file = &options.data;
+    let mut data = if data_file.extension().and_then(|s| s.to_str()) == Some("csv") {
+        TrainingData::load_from_csv_with_categories(data_file, &config.categorical)
+    } else {
+        TrainingData::load_from_json(data_file)
+    }
+    .map_err(|e| format!("{}: {}", data_file.display(), e))?;
+    let (input_coder, output_coder) = (config.input_coder()?, config.output_coder()?);
+    for input in &mut data.inputs {
+        *input = input_coder.encode(input);
+    }
+    for target in &mut data.targets {
+        *target = output_coder.encode(target);
+    }
+    let data_split = DataSplit::load_or_compute(
+        &data.targets,
+        config.training.validation_split,
+        config.training.test_split,
+        &config.training.split,
+    )
+    .map_err(|e| e.to_string())?;
+    let train_inputs = split::select(&data.inputs, &data_split.train);
+    let train_targets = split::select(&data.targets, &data_split.train);
+    let (val_inputs, val_targets) = if data_split.validation.is_empty() {
+        (train_inputs.clone(), train_targets.clone())
+    } else {
+        (
+            split::select(&data.inputs, &data_split.validation),
+            split::select(&data.targets, &data_split.validation),
+        )
+    };
+
+    let mut network = config.create_network().map_err(|e| e.to_string())?;
+    let problems = check_dataset(
+        &data.inputs,
+        &data.targets,
+        network.input_size(),
+        network.get_layers().last().copied().unwrap_or(0),
+    );
+    if let Some(problem) = problems.first() {
+        return Err(problem.clone());
+    }
+    seed_parameters(&mut network, &mut StdRng::seed_from_u64(run.seed));
+    let mut status = "completed";
+    let mut epochs = 0;
+    let (mut best, mut patience) = (f64::INFINITY, 0);
+    for _ in 0..options.epochs {
+        let mut error = 0.0;
+        for (input, target) in train_inputs.iter().zip(&train_targets) {
+            error += network.train(input, target);
+        }
+        epochs += 1;
+        if !error.is_finite() {
+            status = "diverged";
+            break;
+        }
+        let (validation_mse, _) = mse_and_accuracy(&network, &val_inputs, &val_targets);
+        if validation_mse < best - config.training.early_stop_threshold {
+            best = validation_mse;
+            patience = 0;
+        } else {
+            patience += 1;
+            if patience >= config.training.early_stop_patience {
+                status = "early_stopped";
+                break;
+            }
+        }
+    }
+
+    let (train_mse, _) = mse_and_accuracy(&network, &train_inputs, &train_targets);
+    let (validation_mse, validation_accuracy) =
+        mse_and_accuracy(&network, &val_inputs, &val_targets);
+    let model = match &options.models_dir {
+        Some(dir) if status != "diverged" => {
+            let file_name: String = run
+                .name
+                .chars()
+                .map(|c| match c {
+                    '/' | '\\' | ':' | ' ' => '_',
+                    c => c,
+                })
+                .collect();
+            let path = dir.join(format!("{}.json", file_name));
+            network
+                .save_to_file(&path)
+                .map_err(|e| format!("{}: {}", path.display(), e))?;
+            Some(path)
+        }
+        _ => None,
+    };
+    Ok(SweepResult {
+        name: run.name.clone(),
+        settings: run.settings.iter().cloned().collect(),
+        seed: run.seed,
+        status: status.to_string(),
+        error: None,
+        epochs,
+        parameters: network.num_parameters(),
+        train_mse,
+        validation_mse,
+        validation_accuracy,
+        seconds: started.elapsed().as_secs_f64(),
+        model,
+    })
+}
+
+/// Train `runs`, `parallel` at a time, each in its own rayon pool of `threads_per_run` threads
+///
+/// Runs share nothing but the files they read: each builds its network from
+/// its own configuration and draws its weights from its own seeded RNG, so a
+/// run gives the same result alone or next to others. `on_finish` is called
+/// as runs complete, from the thread that trained them; the report lists them
+/// in the order of `runs`.
+pub fn run_sweep<F>(
+    source: &Path,
+    options: &SweepOptions,
+    runs: &[SweepRun],
+    parallel: usize,
+    threads_per_run: usize,
+    on_finish: F,
+) -> Result<SweepReport, String>
+where
+    F: Fn(&SweepResult) + Sync,
+{
+    let started = Instant::now();
+    let parallel = parallel.clamp(1, runs.len().max(1));
+    let next = AtomicUsize::new(0);
+    let results: Mutex<Vec<Option<SweepResult>>> = Mutex::new(vec![None; runs.len()]);
+    let pools = (0..parallel)
+        .map(|worker| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads_per_run)
+                .thread_name(move |thread| format!("sweep-{}-{}", worker, thread))
+                .build()
+                .map_err(|e| e.to_string())
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    std::thread::scope(|scope| {
+        for pool in &pools {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(run) = runs.get(index) else {
+                    break;
+                };
+                let result = pool
+                    .install(|| train_run(options, run))
+                    .unwrap_or_else(|error| SweepResult::failed(run, error));
+                on_finish(&result);
+                results.lock().unwrap()[index] = Some(result);
+            });
+        }
+    });
+
+    Ok(SweepReport {
+        source: source.to_path_buf(),
+        parallel,
+        threads_per_run,
+        seconds: started.elapsed().as_secs_f64(),
+        results: results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::NetworkConfig;
+
+    #[test]
+    fn test_sweep_grid_and_parallel_runs() {
+        let sweep: SweepConfig = toml::from_str(
+            r#"
+            config = "config.toml"
+            data = "data.json"
+            epochs = 30
+            seed = "sweep-test"
+
+            [grid]
+            learning_rate = [0.05, 0.5]
+            training.batch_size = [1, 4]
+
+            [[run]]
+            name = "wide"
+            set = { architecture = [2, 8, 1], hebbian_mode = "Oja" }
+            seed = "7"
+            "#,
+        )
+        .unwrap();
+        let runs = sweep.expand().unwrap();
+        let names: Vec<&str> = runs.iter().map(|run| run.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "learning_rate=0.05,training.batch_size=1",
+                "learning_rate=0.05,training.batch_size=4",
+                "learning_rate=0.5,training.batch_size=1",
+                "learning_rate=0.5,training.batch_size=4",
+                "wide",
+            ]
+        );
+        assert_eq!(runs[0].seed, experiment_seed("sweep-test"));
+        assert_eq!(runs[4].seed, 7);
+        assert!(runs[4]
+            .settings
+            .contains(&("hebbian_mode".to_string(), "\"Oja\"".to_string())));
+        assert_eq!(threads_per_run(4, 8, None), 2);
+        assert_eq!(threads_per_run(4, 2, None), 1);
+        assert_eq!(threads_per_run(4, 8, Some(3)), 3);
+
+        let duplicate: SweepConfig = toml::from_str(
+            "config = \"c.toml\"\ndata = \"d.json\"\n[[run]]\nname = \"a\"\n[[run]]\nname = \"a\"\n",
+        )
+        .unwrap();
+        assert!(duplicate.expand().is_err());
+
+        let dir = std::env::temp_dir().join(format!("benny_sweep_{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut config = NetworkConfig {
+            architecture: vec![2, 4, 1],
+            ..NetworkConfig::default()
+        };
+        config.training.validation_split = 0.0;
+        config.save_to_file(dir.join("config.toml")).unwrap();
+        let xor = TrainingData {
+            inputs: vec![
+                vec![0.0, 0.0],
+                vec![0.0, 1.0],
+                vec![1.0, 0.0],
+                vec![1.0, 1.0],
+            ],
+            targets: vec![vec![0.0], vec![1.0], vec![1.0], vec![0.0]],
+        };
+        xor.save_to_json(dir.join("data.json")).unwrap();
+        let mut runs = runs[..2].to_vec();
+        runs.push(SweepRun {
+            name: "broken".to_string(),
+            settings: vec![("architecture".to_string(), "[3, 1]".to_string())],
+            seed: 1,
+        });
+        let options = SweepOptions {
+            config: dir.join("config.toml"),
+            data: dir.join("data.json"),
+            epochs: 20,
+            base: ConfigOverrides::default(),
+            models_dir: Some(dir.clone()),
+        };
+        let finished = AtomicUsize::new(0);
+        let report = run_sweep(Path::new("sweep.toml"), &options, &runs, 8, 1, |_| {
+            finished.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(finished.into_inner(), 3);
+        assert_eq!(report.parallel, 3);
+        let statuses: Vec<&str> = report.results.iter().map(|r| r.status.as_str()).collect();
+        assert_eq!(statuses[2], "failed");
+        assert!(report.results[..2].iter().all(SweepResult::is_trained));
+        assert!(report.results[0].model.as_ref().unwrap().exists());
+        assert!(report
+            .best()
+            .unwrap()
+            .name
+            .starts_with("learning_rate=0.05"));
+        assert!(report.to_text().contains("🏆"));
+        assert_eq!(report.to_csv().lines().count(), 4);
+
+        // The same seed gives the same run, whatever trains next to it
+        let alone =
+            run_sweep(Path::new("sweep.toml"), &options, &runs[1..2], 1, 1, |_| {}).unwrap();
+        assert_eq!(alone.results[0].train_mse, report.results[1].train_mse);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,152 @@
+use serde::{Deserialize, Serialize};
+
+/// Synaptic tagging and capture: temporary protection of recently important weights
+///
+/// Every weight keeps a running average of the magnitude of its learning
+/// updates. Once that average reaches `threshold` the weight is tagged for
+/// `duration` decay steps, during which it decays at only `protection` times
+/// the normal rate. Weights that keep changing strongly stay tagged, so newly
+/// learned associations survive ongoing decay while idle weights still fade.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SynapticTagging {
+    /// Average update magnitude at which a weight is tagged
+    pub threshold: f64,
+    /// Fraction of the normal decay a tagged weight still undergoes, in [0, 1]
+    pub protection: f64,
+    /// Decay steps a tag lasts after the weight last crossed the threshold
+    pub duration: u32,
+    /// Weight of the newest update in the running average, in (0, 1]
+    pub smoothing: f64,
+    traces: Vec<Vec<Vec<f64>>>, // traces[layer][from][to], running mean of |Δw|
+    tags: Vec<Vec<Vec<u32>>>,   // tags[layer][from][to], remaining protected decay steps
+}
+
+impl SynapticTagging {
+    pub fn new(
+        threshold: f64,
+        protection: f64,
+        duration: u32,
+        smoothing: f64,
+    ) -> Result<Self, String> {
+        if !(0.0..=1.0).contains(&protection) {
+            return Err(format!("Protection {} must be in [0, 1]", protection));
+        }
+        if !(smoothing > 0.0 && smoothing <= 1.0) {
+            return Err(format!("Smoothing {} must be in (0, 1]", smoothing));
+        }
+        if threshold < 0.0 {
+            return Err(format!("Threshold {} must not be negative", threshold));
+        }
+        Ok(Self {
+            threshold,
+            protection,
+            duration,
+            smoothing,
+            traces: Vec::new(),
+            tags: Vec::new(),
+        })
+    }
+
+    /// Record the updates that turned `before` into `after` for weight layer `layer`
+    ///
+    /// State for a layer is reset whenever its shape changes.
+    pub fn observe(&mut self, layer: usize, before: &[Vec<f64>], after: &[Vec<f64>]) {
+        if self.traces.len() <= layer {
+            self.traces.resize(layer + 1, Vec::new());
+            self.tags.resize(layer + 1, Vec::new());
+        }
+        let same_shape = self.traces[layer].len() == after.len()
+            && self.traces[layer]
+                .iter()
+                .zip(after)
+                .all(|(trace, row)| trace.len() == row.len());
+        if !same_shape {
+            self.traces[layer] = after.iter().map(|row| vec![0.0; row.len()]).collect();
+            self.tags[layer] = after.iter().map(|row| vec![0; row.len()]).collect();
+        }
+
+        let traces = self.traces[layer].iter_mut().flatten();
+        let tags = self.tags[layer].iter_mut().flatten();
+        let updates = before
+            .iter()
+            .flatten()
+            .zip(after.iter().flatten())
+            .map(|(old, new)| (new - old).abs());
+        for ((trace, tag), update) in traces.zip(tags).zip(updates) {
+            *trace += self.smoothing * (update - *trace);
+            if *trace >= self.threshold {
+                *tag = self.duration;
+            }
+        }
+    }
+
+    /// Decay weight layer `layer` by `rate`, sparing tagged weights, and age their tags
+    pub fn decay(&mut self, layer: usize, weights: &mut [Vec<f64>], rate: f64) {
+        let tags = self.tags.get_mut(layer);
+        let mut tags = tags.into_iter().flatten().flatten();
+        for weight in weights.iter_mut().flatten() {
+            match tags.next() {
+                Some(tag) if *tag > 0 => {
+                    *weight *= 1.0 - rate * self.protection;
+                    *tag -= 1;
+                }
+                _ => *weight *= 1.0 - rate,
+            }
+        }
+    }
+
+    /// Whether the weight from `from` to `to` in weight layer `layer` is currently tagged
+    pub fn is_tagged(&self, layer: usize, from: usize, to: usize) -> bool {
+        self.tags
+            .get(layer)
+            .and_then(|tags| tags.get(from))
+            .and_then(|tags| tags.get(to))
+            .is_some_and(|&tag| tag > 0)
+    }
+
+    /// Number of currently tagged weights across all layers
+    pub fn tagged_count(&self) -> usize {
+        self.tags
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|&&tag| tag > 0)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tagged_weights_decay_slower() {
+        let mut tagging = SynapticTagging::new(0.05, 0.0, 3, 0.5).unwrap();
+        let before = vec![vec![1.0, 1.0]];
+        let after = vec![vec![1.2, 1.01]];
+        tagging.observe(0, &before, &after);
+        assert!(tagging.is_tagged(0, 0, 0));
+        assert!(!tagging.is_tagged(0, 0, 1));
+        assert_eq!(tagging.tagged_count(), 1);
+
+        let mut weights = after.clone();
+        for _ in 0..3 {
+            tagging.decay(0, &mut weights, 0.1);
+        }
+        assert_eq!(weights[0][0], 1.2);
+        assert!((weights[0][1] - 1.01 * 0.9f64.powi(3)).abs() < 1e-12);
+
+        // The tag has run out, so the weight decays normally again
+        assert_eq!(tagging.tagged_count(), 0);
+        tagging.decay(0, &mut weights, 0.1);
+        assert!((weights[0][0] - 1.08).abs() < 1e-12);
+
+        // Untracked layers decay normally
+        let mut other = vec![vec![2.0]];
+        tagging.decay(3, &mut other, 0.5);
+        assert_eq!(other, vec![vec![1.0]]);
+
+        assert!(SynapticTagging::new(0.1, 1.5, 3, 0.5).is_err());
+        assert!(SynapticTagging::new(0.1, 0.5, 3, 0.0).is_err());
+    }
+}
@@ -0,0 +1,551 @@
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::path::Path;
+
+/// Run columns usable in `RunStore::query` filters
+///
+/// `train_error` is the error of the last epoch and `val_error` the best
+/// validation error seen during the run.
+pub const QUERY_FIELDS: &[&str] = &[
+    "id",
+    "status",
+    "epochs",
+    "train_error",
+    "val_error",
+    "learning_rate",
+    "batch_size",
+    "architecture",
+    "started_at",
+    "finished_at",
+    "config_path",
+    "data_path",
+];
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS runs (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        started_at TEXT NOT NULL,
+        finished_at TEXT,
+        status TEXT NOT NULL,
+        config_path TEXT NOT NULL,
+        data_path TEXT NOT NULL,
+        architecture TEXT NOT NULL,
+        learning_rate REAL NOT NULL,
+        batch_size INTEGER,
+        hyperparameters TEXT NOT NULL,
+        epochs INTEGER NOT NULL DEFAULT 0,
+        train_error REAL,
+        val_error REAL
+    );
+    CREATE TABLE IF NOT EXISTS epochs (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        epoch INTEGER NOT NULL,
+        train_error REAL NOT NULL,
+        val_error REAL,
+        seconds REAL NOT NULL,
+        PRIMARY KEY (run_id, epoch)
+    );
+    CREATE TABLE IF NOT EXISTS checkpoints (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        epoch INTEGER NOT NULL,
+        path TEXT NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS resources (
+        run_id INTEGER NOT NULL REFERENCES runs(id),
+        epoch INTEGER NOT NULL,
+        seconds REAL NOT NULL,
+        rss_bytes INTEGER,
+        peak_rss_bytes INTEGER,
+        cpu_percent REAL NOT NULL,
+        threads INTEGER
+    );
+";
+
+const RUN_COLUMNS: &str = "id, started_at, finished_at, status, config_path, data_path, \
+    architecture, learning_rate, hyperparameters, epochs, train_error, val_error, batch_size";
+
+/// What a run was started with
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunParams {
+    pub config_path: String,
+    pub data_path: String,
+    pub architecture: Vec<usize>,
+    pub learning_rate: f64,
+    /// Configured batch size, replaced by `RunStore::record_batch_size` when tuned
+    pub batch_size: usize,
+    /// Full network configuration as JSON
+    pub hyperparameters: String,
+}
+
+/// One row of the `runs` table
+#[derive(Debug, Clone, PartialEq)]
+pub struct RunSummary {
+    pub id: i64,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// `running`, `completed`, `early_stopped`, `time_limit` or `diverged`
+    pub status: String,
+    pub config_path: String,
+    pub data_path: String,
+    pub architecture: String,
+    pub learning_rate: f64,
+    pub hyperparameters: String,
+    pub epochs: i64,
+    pub train_error: Option<f64>,
+    pub val_error: Option<f64>,
+    /// Batch size trained with (unknown for databases predating the column)
+    pub batch_size: Option<usize>,
+}
+
+/// Metrics of one epoch
+#[derive(Debug, Clone, PartialEq)]
+pub struct EpochRecord {
+    pub epoch: usize,
+    pub train_error: f64,
+    pub val_error: Option<f64>,
+    /// Time since the start of training
+    pub seconds: f64,
+}
+
+/// Process resources sampled during an epoch (see `resources::ResourceUsage`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceRecord {
+    pub epoch: usize,
+    /// Time since the start of training
+    pub seconds: f64,
+    pub rss_bytes: Option<u64>,
+    pub peak_rss_bytes: Option<u64>,
+    /// CPU used since the previous sample, in percent of one core
+    pub cpu_percent: f64,
+    pub threads: Option<usize>,
+}
+
+/// Durable experiment history in a SQLite database
+pub struct RunStore {
+    conn: Connection,
+}
+
+impl RunStore {
+    /// Open (creating if needed) the database at `path`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, rusqlite::Error> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    /// Store that lives only as long as the process
+    pub fn in_memory() -> Result<Self, rusqlite::Error> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, rusqlite::Error> {
+        conn.execute_batch(SCHEMA)?;
+        // Databases created before batch sizes were recorded lack the column
+        let has_batch_size = conn
+            .prepare("SELECT 1 FROM pragma_table_info('runs') WHERE name = 'batch_size'")?
+            .exists([])?;
+        if !has_batch_size {
+            conn.execute_batch("ALTER TABLE runs ADD COLUMN batch_size INTEGER")?;
+        }
+        Ok(Self { conn })
+    }
+
+    /// Record the start of a run and return its id
+    pub fn start_run(&self, params: &RunParams) -> Result<i64, rusqlite::Error> {
+        let architecture = params
+            .architecture
+            .iter()
+            .map(|size| size.to_string())
+            .collect::<Vec<_>>()
+            .join("-");
+        self.conn.execute(
+            "INSERT INTO runs (started_at, status, config_path, data_path, architecture, \
+             learning_rate, batch_size, hyperparameters) \
+             VALUES (?1, 'running', ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                now(),
+                params.config_path,
+                params.data_path,
+                architecture,
+                params.learning_rate,
+                params.batch_size as i64,
+                params.hyperparameters
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Record an epoch and update the run's running totals
+    pub fn record_epoch(&self, run_id: i64, record: &EpochRecord) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO epochs (run_id, epoch, train_error, val_error, seconds) \
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                run_id,
+                record.epoch as i64,
+                record.train_error,
+                record.val_error,
+                record.seconds
+            ],
+        )?;
+        self.conn.execute(
+            "UPDATE runs SET epochs = ?2, train_error = ?3, \
+             val_error = CASE WHEN val_error IS NULL OR ?4 < val_error THEN ?4 ELSE val_error END \
+             WHERE id = ?1",
+            params![
+                run_id,
+                record.epoch as i64 + 1,
+                record.train_error,
+                record.val_error
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record a checkpoint or model file written during the run
+    pub fn record_checkpoint(
+        &self,
+        run_id: i64,
+        epoch: usize,
+        path: &str,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO checkpoints (run_id, epoch, path) VALUES (?1, ?2, ?3)",
+            params![run_id, epoch as i64, path],
+        )?;
+        Ok(())
+    }
+
+    /// Record a sample of the process resources
+    pub fn record_resources(
+        &self,
+        run_id: i64,
+        record: &ResourceRecord,
+    ) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "INSERT INTO resources \
+             (run_id, epoch, seconds, rss_bytes, peak_rss_bytes, cpu_percent, threads) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                run_id,
+                record.epoch as i64,
+                record.seconds,
+                record.rss_bytes.map(|bytes| bytes as i64),
+                record.peak_rss_bytes.map(|bytes| bytes as i64),
+                record.cpu_percent,
+                record.threads.map(|threads| threads as i64)
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record the batch size picked by batch size tuning
+    pub fn record_batch_size(&self, run_id: i64, batch_size: usize) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE runs SET batch_size = ?2 WHERE id = ?1",
+            params![run_id, batch_size as i64],
+        )?;
+        Ok(())
+    }
+
+    pub fn finish_run(&self, run_id: i64, status: &str) -> Result<(), rusqlite::Error> {
+        self.conn.execute(
+            "UPDATE runs SET status = ?2, finished_at = ?3 WHERE id = ?1",
+            params![run_id, status, now()],
+        )?;
+        Ok(())
+    }
+
+    pub fn run(&self, run_id: i64) -> Result<Option<RunSummary>, rusqlite::Error> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM runs WHERE id = ?1", RUN_COLUMNS),
+                [run_id],
+                summary,
+            )
+            .optional()
+    }
+
+    /// Most recent runs first
+    pub fn runs(&self, limit: usize) -> Result<Vec<RunSummary>, rusqlite::Error> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT {} FROM runs ORDER BY id DESC LIMIT ?1",
+            RUN_COLUMNS
+        ))?;
+        let runs = statement.query_map([limit as i64], summary)?;
+        runs.collect()
+    }
+
+    pub fn epochs(&self, run_id: i64) -> Result<Vec<EpochRecord>, rusqlite::Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT epoch, train_error, val_error, seconds FROM epochs \
+             WHERE run_id = ?1 ORDER BY epoch",
+        )?;
+        let epochs = statement.query_map([run_id], |row| {
+            Ok(EpochRecord {
+                epoch: row.get::<_, i64>(0)? as usize,
+                train_error: row.get(1)?,
+                val_error: row.get(2)?,
+                seconds: row.get(3)?,
+            })
+        })?;
+        epochs.collect()
+    }
+
+    /// Resource samples of a run, oldest first
+    pub fn resources(&self, run_id: i64) -> Result<Vec<ResourceRecord>, rusqlite::Error> {
+        let mut statement = self.conn.prepare(
+            "SELECT epoch, seconds, rss_bytes, peak_rss_bytes, cpu_percent, threads \
+             FROM resources WHERE run_id = ?1 ORDER BY rowid",
+        )?;
+        let records = statement.query_map([run_id], |row| {
+            Ok(ResourceRecord {
+                epoch: row.get::<_, i64>(0)? as usize,
+                seconds: row.get(1)?,
+                rss_bytes: row.get::<_, Option<i64>>(2)?.map(|bytes| bytes as u64),
+                peak_rss_bytes: row.get::<_, Option<i64>>(3)?.map(|bytes| bytes as u64),
+                cpu_percent: row.get(4)?,
+                threads: row.get::<_, Option<i64>>(5)?.map(|threads| threads as usize),
+            })
+        })?;
+        records.collect()
+    }
+
+    /// `(epoch, path)` of every checkpoint of a run, oldest first
+    pub fn checkpoints(&self, run_id: i64) -> Result<Vec<(usize, String)>, rusqlite::Error> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT epoch, path FROM checkpoints WHERE run_id = ?1 ORDER BY rowid")?;
+        let checkpoints = statement.query_map([run_id], |row| {
+            Ok((row.get::<_, i64>(0)? as usize, row.get(1)?))
+        })?;
+        checkpoints.collect()
+    }
+
+    /// Runs matching a filter such as `val_error < 0.01 and status = 'completed'`
+    ///
+    /// Filters compare the fields in `QUERY_FIELDS` against numbers or quoted
+    /// strings with `=`, `!=`, `<`, `<=`, `>` or `>=`, joined by `and` / `or`.
+    pub fn query(&self, filter: &str) -> Result<Vec<RunSummary>, String> {
+        let (condition, values) = compile_filter(filter)?;
+        let mut statement = self
+            .conn
+            .prepare(&format!(
+                "SELECT {} FROM runs WHERE {} ORDER BY id DESC",
+                RUN_COLUMNS, condition
+            ))
+            .map_err(|e| e.to_string())?;
+        let runs = statement
+            .query_map(params_from_iter(values), summary)
+            .map_err(|e| e.to_string())?;
+        runs.collect::<Result<_, _>>().map_err(|e| e.to_string())
+    }
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+fn summary(row: &rusqlite::Row<'_>) -> Result<RunSummary, rusqlite::Error> {
+    Ok(RunSummary {
+        id: row.get(0)?,
+        started_at: row.get(1)?,
+        finished_at: row.get(2)?,
+        status: row.get(3)?,
+        config_path: row.get(4)?,
+        data_path: row.get(5)?,
+        architecture: row.get(6)?,
+        learning_rate: row.get(7)?,
+        hyperparameters: row.get(8)?,
+        epochs: row.get(9)?,
+        train_error: row.get(10)?,
+        val_error: row.get(11)?,
+        batch_size: row.get::<_, Option<i64>>(12)?.map(|size| size as usize),
+    })
+}
+
+/// SQL condition and bound values for a query filter
+///
+/// Field names come from a whitelist and values are bound as parameters, so
+/// a filter can never inject SQL.
+fn compile_filter(filter: &str) -> Result<(String, Vec<Value>), String> {
+    let tokens = tokenize(filter)?;
+    let mut condition = String::new();
+    let mut values = Vec::new();
+    let mut rest = tokens.as_slice();
+
+    loop {
+        let [field, op, value, tail @ ..] = rest else {
+            return Err(format!(
+                "Expected '<field> <op> <value>' in filter: {}",
+                filter
+            ));
+        };
+        let field = field.to_ascii_lowercase();
+        if !QUERY_FIELDS.contains(&field.as_str()) {
+            return Err(format!(
+                "Unknown field '{}' (expected one of: {})",
+                field,
+                QUERY_FIELDS.join(", ")
+            ));
+        }
+        if !["=", "!=", "<", "<=", ">", ">="].contains(&op.as_str()) {
+            return Err(format!("Unknown operator '{}'", op));
+        }
+        condition.push_str(&format!("{} {} ?", field, op));
+        values.push(parse_value(value));
+
+        match tail {
+            [] => return Ok((condition, values)),
+            [conjunction, tail @ ..] => {
+                let conjunction = conjunction.to_ascii_lowercase();
+                if conjunction != "and" && conjunction != "or" {
+                    return Err(format!("Expected 'and' or 'or', found '{}'", conjunction));
+                }
+                condition.push_str(&format!(" {} ", conjunction.to_ascii_uppercase()));
+                rest = tail;
+            }
+        }
+    }
+}
+
+fn parse_value(token: &str) -> Value {
+    if let Some(text) = token.strip_prefix('\'') {
+        return Value::Text(text.to_string());
+    }
+    if let Ok(integer) = token.parse::<i64>() {
+        return Value::Integer(integer);
+    }
+    match token.parse::<f64>() {
+        Ok(real) => Value::Real(real),
+        Err(_) => Value::Text(token.to_string()),
+    }
+}
+
+/// Split a filter into words, operators and quoted strings
+///
+/// Quoted strings keep their opening quote so they are never read as numbers.
+fn tokenize(filter: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = filter.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            chars.next();
+            let mut text = String::from('\'');
+            loop {
+                match chars.next() {
+                    Some(end) if end == c => break,
+                    Some(ch) => text.push(ch),
+                    None => return Err(format!("Unterminated string in filter: {}", filter)),
+                }
+            }
+            tokens.push(text);
+        } else if "<>=!".contains(c) {
+            let mut op = String::new();
+            while let Some(&ch) = chars.peek().filter(|ch| "<>=!".contains(**ch)) {
+                op.push(ch);
+                chars.next();
+            }
+            tokens.push(op);
+        } else {
+            let mut word = String::new();
+            while let Some(&ch) = chars
+                .peek()
+                .filter(|ch| !ch.is_whitespace() && !"<>=!'\"".contains(**ch))
+            {
+                word.push(ch);
+                chars.next();
+            }
+            tokens.push(word);
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(learning_rate: f64) -> RunParams {
+        RunParams {
+            config_path: "config.toml".to_string(),
+            data_path: "data.csv".to_string(),
+            architecture: vec![2, 4, 1],
+            learning_rate,
+            batch_size: 32,
+            hyperparameters: "{}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_run_history_and_queries() {
+        let store = RunStore::in_memory().unwrap();
+        let good = store.start_run(&params(0.1)).unwrap();
+        for (epoch, val_error) in [(0, 0.2), (1, 0.005), (2, 0.008)] {
+            store
+                .record_epoch(
+                    good,
+                    &EpochRecord {
+                        epoch,
+                        train_error: 0.1 / (epoch + 1) as f64,
+                        val_error: Some(val_error),
+                        seconds: epoch as f64,
+                    },
+                )
+                .unwrap();
+        }
+        store
+            .record_checkpoint(good, 1, "ckpt/delta-000001.bin")
+            .unwrap();
+        store.record_batch_size(good, 64).unwrap();
+        let resources = ResourceRecord {
+            epoch: 2,
+            seconds: 2.5,
+            rss_bytes: Some(50 << 20),
+            peak_rss_bytes: Some(60 << 20),
+            cpu_percent: 180.0,
+            threads: None,
+        };
+        store.record_resources(good, &resources).unwrap();
+        store.finish_run(good, "completed").unwrap();
+
+        let bad = store.start_run(&params(0.5)).unwrap();
+        store
+            .record_epoch(
+                bad,
+                &EpochRecord {
+                    epoch: 0,
+                    train_error: 0.4,
+                    val_error: Some(0.3),
+                    seconds: 0.0,
+                },
+            )
+            .unwrap();
+        store.finish_run(bad, "early_stopped").unwrap();
+
+        let run = store.run(good).unwrap().unwrap();
+        assert_eq!(run.epochs, 3);
+        assert_eq!(run.val_error, Some(0.005));
+        assert_eq!(run.architecture, "2-4-1");
+        assert_eq!(run.batch_size, Some(64));
+        assert_eq!(store.epochs(good).unwrap().len(), 3);
+        assert_eq!(
+            store.checkpoints(good).unwrap(),
+            vec![(1, "ckpt/delta-000001.bin".to_string())]
+        );
+        assert_eq!(store.resources(good).unwrap(), vec![resources]);
+        assert_eq!(store.runs(10).unwrap()[0].id, bad);
+
+        let ids = |filter: &str| -> Vec<i64> {
+            store.query(filter).unwrap().iter().map(|r| r.id).collect()
+        };
+        assert_eq!(ids("val_error < 0.01"), vec![good]);
+        assert_eq!(ids("status = 'early_stopped'"), vec![bad]);
+        assert_eq!(ids("learning_rate>=0.1 AND epochs > 1"), vec![good]);
+        assert_eq!(ids("id = 1 or id = 2"), vec![bad, good]);
+        assert_eq!(ids("batch_size > 32"), vec![good]);
+        assert!(store.query("val_error < 0.01; DROP TABLE runs").is_err());
+        assert!(store.query("hyperparameters = 'x'").is_err());
+        assert!(store.query("val_error <").is_err());
+    }
+}
@@ -0,0 +1,202 @@
+use crate::neural_network::NeuralNetwork;
+use std::fs;
+use std::path::Path;
+
+/// Summary statistics over every dense weight of a network
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightStats {
+    pub count: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl WeightStats {
+    pub fn of(network: &NeuralNetwork) -> Self {
+        let weights: Vec<f64> = weights(network).map(|(_, _, _, w)| w).collect();
+        let count = weights.len();
+        let mean = weights.iter().sum::<f64>() / count.max(1) as f64;
+        let variance =
+            weights.iter().map(|w| (w - mean).powi(2)).sum::<f64>() / count.max(1) as f64;
+        Self {
+            count,
+            min: weights.iter().copied().fold(f64::INFINITY, f64::min),
+            max: weights.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            mean,
+            std_dev: variance.sqrt(),
+        }
+    }
+}
+
+/// Every weight as `(layer, from, to, weight)`
+fn weights(network: &NeuralNetwork) -> impl Iterator<Item = (usize, usize, usize, f64)> + '_ {
+    let layers = network.get_layers();
+    (0..layers.len() - 1).flat_map(move |layer| {
+        let to_size = layers[layer + 1];
+        network
+            .get_layer_weights(layer)
+            .into_iter()
+            .enumerate()
+            .map(move |(index, weight)| (layer, index / to_size, index % to_size, weight))
+    })
+}
+
+/// Panic unless every weight and bias is finite
+#[track_caller]
+pub fn assert_weights_finite(network: &NeuralNetwork) {
+    if let Some((layer, from, to, weight)) = weights(network).find(|(_, _, _, w)| !w.is_finite()) {
+        panic!("Weight {} -> {} of layer {} is {}", from, to, layer, weight);
+    }
+    for layer in 0..network.num_layers() - 1 {
+        let biases = network.get_layer_biases(layer);
+        if let Some((neuron, bias)) = biases.iter().enumerate().find(|(_, b)| !b.is_finite()) {
+            panic!("Bias {} of layer {} is {}", neuron, layer, bias);
+        }
+    }
+}
+
+/// Panic unless every weight lies in `[-bound, bound]`
+#[track_caller]
+pub fn assert_weights_within(network: &NeuralNetwork, bound: f64) {
+    assert_weights_finite(network);
+    if let Some((layer, from, to, weight)) = weights(network).find(|(_, _, _, w)| w.abs() > bound) {
+        panic!(
+            "Weight {} -> {} of layer {} is {}, outside ±{}",
+            from, to, layer, weight, bound
+        );
+    }
+}
+
+/// Panic unless each decoder layer is the transpose of its mirror encoder layer
+///
+/// Layer `l` of `n` weight layers mirrors layer `n - 1 - l`, as in tied autoencoders.
+#[track_caller]
+pub fn assert_weights_mirrored(network: &NeuralNetwork, tolerance: f64) {
+    let last = network.num_layers() - 2;
+    for (layer, from, to, weight) in weights(network).filter(|(layer, ..)| *layer <= last / 2) {
+        let mirrored = network.get_weight(last - layer, to, from);
+        if (weight - mirrored).abs() > tolerance {
+            panic!(
+                "Weight {} -> {} of layer {} is {}, but its mirror in layer {} is {}",
+                from,
+                to,
+                layer,
+                weight,
+                last - layer,
+                mirrored
+            );
+        }
+    }
+}
+
+/// Apply `update` `updates` times and panic unless the weights stay finite and settle
+///
+/// The weights have settled when no weight moved by more than `tolerance`
+/// during the last update. Returns the statistics of the settled weights.
+#[track_caller]
+pub fn assert_stable<F>(
+    network: &mut NeuralNetwork,
+    updates: usize,
+    tolerance: f64,
+    mut update: F,
+) -> WeightStats
+where
+    F: FnMut(&mut NeuralNetwork),
+{
+    let mut last_change = 0.0;
+    for step in 0..updates {
+        let before: Vec<f64> = weights(network).map(|(_, _, _, w)| w).collect();
+        update(network);
+        if weights(network).any(|(_, _, _, w)| !w.is_finite()) {
+            panic!("Weights diverged after {} of {} updates", step + 1, updates);
+        }
+        last_change = weights(network)
+            .zip(before)
+            .map(|((_, _, _, after), before)| (after - before).abs())
+            .fold(0.0, f64::max);
+    }
+    assert!(
+        last_change <= tolerance,
+        "Weights still moved by {} after {} updates (tolerance {})",
+        last_change,
+        updates,
+        tolerance
+    );
+    WeightStats::of(network)
+}
+
+/// Panic unless `actual` matches the golden `expected` values within `tolerance`
+#[track_caller]
+pub fn assert_close(actual: &[f64], expected: &[f64], tolerance: f64) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "Expected {} values, got {}",
+        expected.len(),
+        actual.len()
+    );
+    for (index, (a, e)) in actual.iter().zip(expected).enumerate() {
+        if (a - e).is_nan() || (a - e).abs() > tolerance {
+            panic!(
+                "Value {} is {}, expected {} (tolerance {})",
+                index, a, e, tolerance
+            );
+        }
+    }
+}
+
+/// Compare `actual` with the golden values stored as JSON at `path`
+///
+/// The file is written instead when it does not exist yet or when the
+/// `UPDATE_GOLDEN` environment variable is set, so regenerating golden
+/// values after an intended change is one test run away.
+#[track_caller]
+pub fn assert_golden<P: AsRef<Path>>(path: P, actual: &[f64], tolerance: f64) {
+    let path = path.as_ref();
+    if !path.exists() || std::env::var_os("UPDATE_GOLDEN").is_some() {
+        let json = serde_json::to_string_pretty(actual).expect("Failed to serialize values");
+        fs::write(path, json)
+            .unwrap_or_else(|e| panic!("Failed to write '{}': {}", path.display(), e));
+        return;
+    }
+    let json = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("Failed to read '{}': {}", path.display(), e));
+    let expected: Vec<f64> = serde_json::from_str(&json)
+        .unwrap_or_else(|e| panic!("Failed to parse '{}': {}", path.display(), e));
+    assert_close(actual, &expected, tolerance);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::neural_network::HebbianLearningMode;
+
+    #[test]
+    fn test_weight_assertions() {
+        let mut nn = NeuralNetwork::with_layers_and_mode(&[3, 2], 0.05, HebbianLearningMode::Oja);
+        let stats = WeightStats::of(&nn);
+        assert_eq!(stats.count, 6);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+        assert_weights_within(&nn, 1.0);
+
+        // Oja's rule keeps the weights bounded under repeated presentation
+        let stats = assert_stable(&mut nn, 2000, 1e-2, |nn| {
+            nn.train_unsupervised(&[0.9, 0.1, 0.5])
+        });
+        assert!(stats.max.is_finite());
+
+        let mut tied = NeuralNetwork::with_layers(&[4, 2, 4], 0.05);
+        tied.set_tied_weights(true).unwrap();
+        assert_weights_mirrored(&tied, 0.0);
+
+        let path = std::env::temp_dir().join(format!("benny-golden-{}.json", std::process::id()));
+        let outputs = nn.get_layer_weights(0);
+        assert_golden(&path, &outputs, 0.0);
+        assert_golden(&path, &outputs, 0.0);
+        let drifted: Vec<f64> = outputs.iter().map(|w| w + 1e-3).collect();
+        let result = std::panic::catch_unwind(|| assert_golden(&path, &drifted, 1e-6));
+        fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+
+/// Decision threshold used when none was tuned
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// Precision and recall of one output with a given decision threshold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdPoint {
+    pub threshold: f64,
+    /// Share of the samples called positive that are positive; 1 when none are called
+    pub precision: f64,
+    pub recall: f64,
+    pub f1: f64,
+}
+
+/// Threshold sweep of one output and the operating point chosen from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdSweep {
+    pub output: usize,
+    /// Samples whose target for this output is positive
+    pub positives: usize,
+    /// Every threshold swept, lowest first
+    pub curve: Vec<ThresholdPoint>,
+    pub chosen: ThresholdPoint,
+    /// The operating point at the default threshold, for comparison
+    pub default: ThresholdPoint,
+}
+
+/// Tuned decision thresholds of every output of a model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThresholdReport {
+    pub samples: usize,
+    /// Precision the chosen thresholds had to reach, if any; otherwise they maximise F1
+    pub min_precision: Option<f64>,
+    pub outputs: Vec<ThresholdSweep>,
+}
+
+/// Operating point of `scores` against the binary `labels` at `threshold`
+fn point(scores: &[f64], labels: &[bool], threshold: f64) -> ThresholdPoint {
+    let (mut true_positives, mut called, mut positives) = (0usize, 0usize, 0usize);
+    for (&score, &label) in scores.iter().zip(labels) {
+        let positive = score >= threshold;
+        called += positive as usize;
+        positives += label as usize;
+        true_positives += (positive && label) as usize;
+    }
+    let precision = if called > 0 {
+        true_positives as f64 / called as f64
+    } else {
+        1.0
+    };
+    let recall = true_positives as f64 / positives.max(1) as f64;
+    let f1 = if precision + recall > 0.0 {
+        2.0 * precision * recall / (precision + recall)
+    } else {
+        0.0
+    };
+    ThresholdPoint {
+        threshold,
+        precision,
+        recall,
+        f1,
+    }
+}
+
+/// Sweep the decision threshold of every output over `steps` points of `(0, 1)`
+///
+/// Targets of 0.5 and above count as positive. Each output gets the
+/// threshold with the best F1, or with `min_precision` the one with the best
+/// recall among those reaching that precision; ties go to the threshold
+/// nearest the default, so it only moves when that pays off. Best run on
+/// validation data the model was not fitted to.
+pub fn tune_thresholds(
+    outputs: &[Vec<f64>],
+    targets: &[Vec<f64>],
+    steps: usize,
+    min_precision: Option<f64>,
+) -> ThresholdReport {
+    let steps = steps.max(2);
+    let width = outputs
+        .iter()
+        .chain(targets)
+        .map(Vec::len)
+        .min()
+        .unwrap_or(0);
+    let outputs = (0..width)
+        .map(|output| {
+            let scores: Vec<f64> = outputs.iter().map(|o| o[output]).collect();
+            let labels: Vec<bool> = targets
+                .iter()
+                .map(|t| t[output] >= DEFAULT_THRESHOLD)
+                .collect();
+            let curve: Vec<ThresholdPoint> = (1..steps)
+                .map(|step| point(&scores, &labels, step as f64 / steps as f64))
+                .collect();
+            let score = |p: &ThresholdPoint| match min_precision {
+                Some(min) if p.precision >= min => p.recall,
+                Some(_) => f64::NEG_INFINITY,
+                None => p.f1,
+            };
+            let chosen = curve
+                .iter()
+                .max_by(|a, b| {
+                    score(a).total_cmp(&score(b)).then_with(|| {
+                        let distance = |p: &ThresholdPoint| (p.threshold - DEFAULT_THRESHOLD).abs();
+                        distance(b).total_cmp(&distance(a))
+                    })
+                })
+                .filter(|p| score(p).is_finite())
+                .cloned()
+                .unwrap_or_else(|| point(&scores, &labels, DEFAULT_THRESHOLD));
+            ThresholdSweep {
+                output,
+                positives: labels.iter().filter(|&&l| l).count(),
+                default: point(&scores, &labels, DEFAULT_THRESHOLD),
+                curve,
+                chosen,
+            }
+        })
+        .collect();
+
+    ThresholdReport {
+        samples: targets.len(),
+        min_precision,
+        outputs,
+    }
+}
+
+impl ThresholdReport {
+    /// Chosen threshold of every output, as stored in the model metadata
+    pub fn thresholds(&self) -> Vec<f64> {
+        self.outputs.iter().map(|o| o.chosen.threshold).collect()
+    }
+
+    /// `output,threshold,precision,recall,f1` rows of every swept threshold
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("output,threshold,precision,recall,f1\n");
+        for output in &self.outputs {
+            for p in &output.curve {
+                let _ = writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    output.output, p.threshold, p.precision, p.recall, p.f1
+                );
+            }
+        }
+        out
+    }
+
+    /// Chosen operating points, with the trade-off at every tenth of the range
+    pub fn to_text(&self) -> String {
+        let mut out = format!("🎚️  Decision thresholds tuned on {} samples", self.samples);
+        match self.min_precision {
+            Some(min) => {
+                let _ = writeln!(out, " (best recall at precision ≥ {:.2})", min);
+            }
+            None => out.push_str(" (best F1)\n"),
+        }
+        for output in &self.outputs {
+            let _ = writeln!(
+                out,
+                "   Output {} ({} positives): threshold {:.3}, precision {:.3}, recall {:.3}, F1 {:.3} (at {}: {:.3}, {:.3}, {:.3})",
+                output.output,
+                output.positives,
+                output.chosen.threshold,
+                output.chosen.precision,
+                output.chosen.recall,
+                output.chosen.f1,
+                DEFAULT_THRESHOLD,
+                output.default.precision,
+                output.default.recall,
+                output.default.f1
+            );
+            out.push_str("     threshold  precision  recall     F1\n");
+            let every = (output.curve.len() + 1) / 10;
+            for p in output
+                .curve
+                .iter()
+                .skip(every.saturating_sub(1))
+                .step_by(every.max(1))
+            {
+                let _ = writeln!(
+                    out,
+                    "     {:>9.3}  {:>9.3}  {:>6.3}  {:>5.3}",
+                    p.threshold, p.precision, p.recall, p.f1
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tune_thresholds() {
+        // Positives score 0.3 and above, negatives below: 0.5 misses two of them
+        let scores = [0.1, 0.2, 0.25, 0.3, 0.35, 0.6, 0.9];
+        let outputs: Vec<Vec<f64>> = scores.iter().map(|&s| vec![s, s]).collect();
+        let targets: Vec<Vec<f64>> = scores
+            .iter()
+            .map(|&s| vec![(s >= 0.3) as u8 as f64, (s >= 0.8) as u8 as f64])
+            .collect();
+
+        let report = tune_thresholds(&outputs, &targets, 20, None);
+        assert_eq!(report.samples, 7);
+        assert_eq!(report.outputs[0].positives, 4);
+        assert_eq!(report.outputs[0].curve.len(), 19);
+        assert_eq!(report.outputs[0].default.recall, 0.5);
+        assert_eq!(report.outputs[0].chosen.threshold, 0.3);
+        assert_eq!(report.outputs[0].chosen.f1, 1.0);
+        // Any threshold in (0.6, 0.9] is perfect; the one nearest 0.5 wins
+        assert_eq!(report.outputs[1].chosen.threshold, 0.65);
+
+        // Precision nobody reaches leaves only calling nothing positive
+        let outputs = vec![vec![0.7], vec![0.7]];
+        let targets = vec![vec![1.0], vec![0.0]];
+        let strict = tune_thresholds(&outputs, &targets, 10, Some(0.9));
+        assert_eq!(strict.thresholds(), vec![0.8]);
+        assert_eq!(strict.outputs[0].chosen.recall, 0.0);
+
+        assert!(report
+            .to_csv()
+            .starts_with("output,threshold,precision,recall,f1\n0,0.05,"));
+        assert!(report.to_text().contains("threshold 0.300"));
+    }
+}
@@ -0,0 +1,286 @@
+use hyper::{Body, Request, Response, StatusCode};
+use rand::RngCore;
+use ring::digest;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Prefix of every issued token, so leaked tokens are easy to search for
+pub const TOKEN_PREFIX: &str = "benny_";
+
+/// What a token may be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    /// Run forward passes
+    Predict,
+    /// Update weights with training samples
+    Train,
+    /// Change runtime settings, locally or with remote-config NNP messages; implies every other scope
+    Admin,
+    /// Read metrics and bandwidth statistics
+    Metrics,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum AuthError {
+    #[error("missing API token")]
+    Missing,
+    #[error("unknown API token")]
+    Invalid,
+    #[error("API token {0} was revoked")]
+    Revoked(String),
+    #[error("API token {0} lacks the {1:?} scope")]
+    MissingScope(String, Scope),
+}
+
+/// An issued token; only the hash of its secret is stored
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiToken {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    /// RFC 3339 issue time
+    pub created_at: String,
+    pub revoked_at: Option<String>,
+    secret_sha256: String,
+}
+
+impl ApiToken {
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+
+    pub fn allows(&self, scope: Scope) -> bool {
+        self.scopes.contains(&scope) || self.scopes.contains(&Scope::Admin)
+    }
+}
+
+/// Issued tokens, kept in a JSON file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenStore {
+    tokens: Vec<ApiToken>,
+}
+
+impl TokenStore {
+    /// Read a token file; a missing file holds no tokens
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid token file {}: {}", path.display(), e))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, content).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+    }
+
+    pub fn tokens(&self) -> &[ApiToken] {
+        &self.tokens
+    }
+
+    /// Issue a token, returning it together with its secret, which is not stored
+    pub fn issue(&mut self, name: &str, scopes: &[Scope]) -> Result<(ApiToken, String), String> {
+        if scopes.is_empty() {
+            return Err("A token needs at least one scope".to_string());
+        }
+        let mut random = [0u8; 24];
+        rand::rngs::OsRng.fill_bytes(&mut random);
+        let id = hex(&random[..4]);
+        let secret = format!("{}{}_{}", TOKEN_PREFIX, id, hex(&random[4..]));
+
+        let mut unique = Vec::new();
+        for &scope in scopes {
+            if !unique.contains(&scope) {
+                unique.push(scope);
+            }
+        }
+        let token = ApiToken {
+            id,
+            name: name.to_string(),
+            scopes: unique,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            revoked_at: None,
+            secret_sha256: sha256_hex(&secret),
+        };
+        self.tokens.push(token.clone());
+        Ok((token, secret))
+    }
+
+    /// Revoke the token with this id, or the only one with this name
+    pub fn revoke(&mut self, id_or_name: &str) -> Result<&ApiToken, String> {
+        let matches: Vec<usize> = (0..self.tokens.len())
+            .filter(|&i| self.tokens[i].id == id_or_name || self.tokens[i].name == id_or_name)
+            .collect();
+        let index = match matches[..] {
+            [index] => index,
+            [] => return Err(format!("No token named or with id {}", id_or_name)),
+            _ => {
+                return Err(format!(
+                    "Several tokens are named {}; revoke one by id",
+                    id_or_name
+                ))
+            }
+        };
+        let token = &mut self.tokens[index];
+        if token.revoked_at.is_none() {
+            token.revoked_at = Some(chrono::Utc::now().to_rfc3339());
+        }
+        Ok(token)
+    }
+
+    /// The token whose secret is `secret`, if it is live and covers `scope`
+    pub fn authorize(&self, secret: &str, scope: Scope) -> Result<&ApiToken, AuthError> {
+        let hash = sha256_hex(secret);
+        let token = self
+            .tokens
+            .iter()
+            .find(|token| token.secret_sha256 == hash)
+            .ok_or(AuthError::Invalid)?;
+        if token.is_revoked() {
+            return Err(AuthError::Revoked(token.id.clone()));
+        }
+        if !token.allows(scope) {
+            return Err(AuthError::MissingScope(token.id.clone(), scope));
+        }
+        Ok(token)
+    }
+}
+
+/// A token file checked by running servers, re-read whenever it changes
+///
+/// Tokens issued or revoked with `benny token` take effect without a restart.
+#[derive(Debug, Clone)]
+pub struct TokenGuard {
+    path: PathBuf,
+    state: Arc<Mutex<(Option<SystemTime>, TokenStore)>>,
+}
+
+impl TokenGuard {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let store = TokenStore::load(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            state: Arc::new(Mutex::new((modified(path), store))),
+        })
+    }
+
+    /// Check `secret` against the current token file
+    pub fn authorize(&self, secret: Option<&str>, scope: Scope) -> Result<ApiToken, AuthError> {
+        let secret = secret.ok_or(AuthError::Missing)?;
+        let mut state = self.state.lock().unwrap();
+        let changed = modified(&self.path);
+        if changed != state.0 {
+            match TokenStore::load(&self.path) {
+                Ok(store) => *state = (changed, store),
+                Err(e) => log::warn!(
+                    "Keeping the previous tokens, {} is unreadable: {}",
+                    self.path.display(),
+                    e
+                ),
+            }
+        }
+        state.1.authorize(secret, scope).cloned()
+    }
+}
+
+/// 401 for missing, unknown or revoked tokens, 403 for tokens without the scope
+pub fn denied_response(error: &AuthError) -> Response<Body> {
+    let status = match error {
+        AuthError::MissingScope(..) => StatusCode::FORBIDDEN,
+        _ => StatusCode::UNAUTHORIZED,
+    };
+    Response::builder()
+        .status(status)
+        .header("www-authenticate", "Bearer")
+        .body(Body::from(error.to_string()))
+        .unwrap()
+}
+
+/// Token of an `Authorization: Bearer <token>` header
+pub fn bearer_token(request: &Request<Body>) -> Option<&str> {
+    request
+        .headers()
+        .get(hyper::header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+        .map(str::trim)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+fn sha256_hex(secret: &str) -> String {
+    hex(digest::digest(&digest::SHA256, secret.as_bytes()).as_ref())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_and_revocation() {
+        let mut store = TokenStore::default();
+        assert!(store.issue("empty", &[]).is_err());
+        let (reader, reader_secret) = store.issue("dashboard", &[Scope::Metrics]).unwrap();
+        let (_, admin_secret) = store.issue("ops", &[Scope::Admin]).unwrap();
+        assert!(reader_secret.starts_with(TOKEN_PREFIX));
+
+        assert_eq!(
+            store.authorize(&reader_secret, Scope::Metrics).unwrap().id,
+            reader.id
+        );
+        assert_eq!(
+            store.authorize(&reader_secret, Scope::Train),
+            Err(AuthError::MissingScope(reader.id.clone(), Scope::Train))
+        );
+        assert!(store.authorize(&admin_secret, Scope::Train).is_ok());
+        assert_eq!(
+            store.authorize("benny_guess", Scope::Metrics),
+            Err(AuthError::Invalid)
+        );
+
+        let path = std::env::temp_dir().join(format!("benny-tokens-{}.json", std::process::id()));
+        store.save(&path).unwrap();
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains(&reader_secret));
+        let guard = TokenGuard::open(&path).unwrap();
+        assert_eq!(
+            guard.authorize(None, Scope::Metrics),
+            Err(AuthError::Missing)
+        );
+        assert!(guard
+            .authorize(Some(&reader_secret), Scope::Metrics)
+            .is_ok());
+
+        store.revoke("dashboard").unwrap();
+        store.save(&path).unwrap();
+        // Make sure the guard sees a new modification time
+        let later = SystemTime::now() + std::time::Duration::from_secs(5);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        assert_eq!(
+            guard.authorize(Some(&reader_secret), Scope::Metrics),
+            Err(AuthError::Revoked(reader.id))
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,386 @@
+use crate::address::{self, AddressPreference};
+use crate::distributed_network::{self, PeerInfo};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::widgets::{BarChart, Block, Paragraph, Row, Sparkline, Table};
+use ratatui::Frame;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Samples kept for every trend
+pub const HISTORY_LEN: usize = 120;
+
+/// Trends of one monitored node, built from successive `Info` replies
+#[derive(Debug)]
+pub struct NodeMonitor {
+    /// `host:port` the node is polled at
+    pub endpoint: String,
+    /// Latest reply
+    pub info: Option<PeerInfo>,
+    /// Why the latest poll failed
+    pub error: Option<String>,
+    /// Messages sent and received per second
+    pub message_rates: VecDeque<f64>,
+    pub losses: VecDeque<f32>,
+    pub weight_norms: VecDeque<f32>,
+}
+
+impl NodeMonitor {
+    pub fn new(endpoint: String) -> Self {
+        Self {
+            endpoint,
+            info: None,
+            error: None,
+            message_rates: VecDeque::new(),
+            losses: VecDeque::new(),
+            weight_norms: VecDeque::new(),
+        }
+    }
+
+    /// Fold in a poll taken `elapsed` after the previous one
+    ///
+    /// Failed polls keep the trends and the last reply, so a node that
+    /// restarts or drops a reply does not wipe its history.
+    pub fn record(&mut self, result: Result<PeerInfo, String>, elapsed: Duration) {
+        let info = match result {
+            Ok(info) => info,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+
+        let total = |info: &PeerInfo| info.messages_sent + info.messages_received;
+        if let Some(previous) = &self.info {
+            // A restarted node resets its counters; skip that sample
+            if previous.network_id == info.network_id && total(&info) >= total(previous) {
+                let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+                push(
+                    &mut self.message_rates,
+                    (total(&info) - total(previous)) as f64 / seconds,
+                );
+            }
+        }
+        if let Some(loss) = info.loss {
+            push(&mut self.losses, loss);
+        }
+        if let Some(norm) = info.weight_norm {
+            push(&mut self.weight_norms, norm);
+        }
+        self.info = Some(info);
+        self.error = None;
+    }
+}
+
+fn push<T>(history: &mut VecDeque<T>, value: T) {
+    if history.len() == HISTORY_LEN {
+        history.pop_front();
+    }
+    history.push_back(value);
+}
+
+/// State of the `benny top` dashboard
+#[derive(Debug)]
+pub struct Dashboard {
+    pub nodes: Vec<NodeMonitor>,
+    /// Node whose panels are shown
+    pub selected: usize,
+}
+
+impl Dashboard {
+    pub fn new(endpoints: Vec<String>) -> Self {
+        Self {
+            nodes: endpoints.into_iter().map(NodeMonitor::new).collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.nodes.is_empty() {
+            self.selected = (self.selected + 1) % self.nodes.len();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.nodes.is_empty() {
+            self.selected = (self.selected + self.nodes.len() - 1) % self.nodes.len();
+        }
+    }
+
+    /// Render the node table, the selected node's trends and its latest outputs
+    pub fn draw(&self, frame: &mut Frame) {
+        let [table_area, panels_area, help_area] = Layout::vertical([
+            Constraint::Length(self.nodes.len() as u16 + 3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.draw_table(frame, table_area);
+
+        if let Some(node) = self.nodes.get(self.selected) {
+            let [trends_area, outputs_area] =
+                Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                    .areas(panels_area);
+            let [rate_area, loss_area, norm_area] =
+                Layout::vertical([Constraint::Ratio(1, 3); 3]).areas(trends_area);
+
+            let rate = node.message_rates.back().copied();
+            draw_trend(
+                frame,
+                rate_area,
+                &format!("Messages/s {}", latest(rate.map(|r| r as f32))),
+                node.message_rates.iter().copied(),
+            );
+            let loss = node.losses.back().copied();
+            draw_trend(
+                frame,
+                loss_area,
+                &format!("Training loss {}", latest(loss)),
+                node.losses.iter().map(|&l| l as f64),
+            );
+            let norm = node.weight_norms.back().copied();
+            draw_trend(
+                frame,
+                norm_area,
+                &format!("Weight norm {}", latest(norm)),
+                node.weight_norms.iter().map(|&n| n as f64),
+            );
+            draw_outputs(frame, outputs_area, node);
+        }
+
+        frame.render_widget(
+            Paragraph::new("q quit · ↑/↓ select node").style(Style::default().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+
+    fn draw_table(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.nodes.iter().enumerate().map(|(index, node)| {
+            let (name, peers, mode, uptime) = match &node.info {
+                Some(info) => (
+                    info.name.clone(),
+                    info.peers.to_string(),
+                    info.learning_mode.clone(),
+                    format!("{}s", info.uptime_secs),
+                ),
+                None => Default::default(),
+            };
+            let status = match &node.error {
+                Some(e) => format!("✗ {}", e),
+                None if node.info.is_some() => "✓ online".to_string(),
+                None => "… waiting".to_string(),
+            };
+            let rate = node
+                .message_rates
+                .back()
+                .map_or_else(String::new, |rate| format!("{:.1}", rate));
+            let style = if index == self.selected {
+                Style::default().add_modifier(Modifier::REVERSED)
+            } else {
+                Style::default()
+            };
+            Row::new(vec![
+                node.endpoint.clone(),
+                name,
+                peers,
+                rate,
+                mode,
+                uptime,
+                status,
+            ])
+            .style(style)
+        });
+        let widths = [
+            Constraint::Length(22),
+            Constraint::Length(20),
+            Constraint::Length(6),
+            Constraint::Length(10),
+            Constraint::Length(12),
+            Constraint::Length(10),
+            Constraint::Min(10),
+        ];
+        let header = Row::new(vec![
+            "Endpoint", "Name", "Peers", "Msgs/s", "Mode", "Uptime", "Status",
+        ])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+        frame.render_widget(
+            Table::new(rows, widths)
+                .header(header)
+                .block(Block::bordered().title(" Nodes ")),
+            area,
+        );
+    }
+}
+
+fn latest(value: Option<f32>) -> String {
+    value.map_or_else(|| "(none)".to_string(), |v| format!("{:.4}", v))
+}
+
+/// Sparkline of `values`, scaled so their range fills the panel
+fn draw_trend(frame: &mut Frame, area: Rect, title: &str, values: impl Iterator<Item = f64>) {
+    let values: Vec<f64> = values.collect();
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let span = (max - min).max(f64::EPSILON);
+    let bars: Vec<u64> = values
+        .iter()
+        .map(|v| 1 + ((v - min) / span * 99.0) as u64)
+        .collect();
+    frame.render_widget(
+        Sparkline::default()
+            .data(&bars)
+            .max(100)
+            .style(Style::default().fg(Color::Cyan))
+            .block(Block::bordered().title(format!(" {} ", title))),
+        area,
+    );
+}
+
+fn draw_outputs(frame: &mut Frame, area: Rect, node: &NodeMonitor) {
+    let outputs = node.info.as_ref().map_or(&[][..], |info| &info.outputs[..]);
+    let labels: Vec<String> = (0..outputs.len()).map(|i| i.to_string()).collect();
+    let bars: Vec<(&str, u64)> = labels
+        .iter()
+        .zip(outputs)
+        .map(|(label, &value)| (label.as_str(), (value.clamp(0.0, 1.0) * 100.0) as u64))
+        .collect();
+    frame.render_widget(
+        BarChart::default()
+            .data(&bars[..])
+            .max(100)
+            .bar_width(3)
+            .bar_style(Style::default().fg(Color::Green))
+            .block(Block::bordered().title(" Output activations (%) ")),
+        area,
+    );
+}
+
+/// Poll `endpoints` every `interval` and show them until `q` is pressed
+///
+/// Nodes are polled with anonymous `Info` requests, so nodes that belong to
+/// a namespace do not answer.
+pub fn run(endpoints: Vec<String>, interval: Duration) -> Result<(), Box<dyn std::error::Error>> {
+    let targets = endpoints
+        .iter()
+        .map(|endpoint| address::parse_host_port(endpoint, 8080))
+        .collect::<Result<Vec<_>, _>>()?;
+    let runtime = tokio::runtime::Runtime::new()?;
+    let mut dashboard = Dashboard::new(endpoints);
+
+    let mut terminal = ratatui::try_init()?;
+    let mut last_poll: Option<Instant> = None;
+    let result = loop {
+        let polls = targets.iter().map(|(host, port)| {
+            distributed_network::query_info(host, *port, AddressPreference::System, interval)
+        });
+        let replies = runtime.block_on(futures::future::join_all(polls));
+        let elapsed = last_poll.map_or(interval, |at| at.elapsed());
+        last_poll = Some(Instant::now());
+        for (node, reply) in dashboard.nodes.iter_mut().zip(replies) {
+            node.record(reply.map_err(|e| format!("{:?}", e)), elapsed);
+        }
+
+        if let Err(e) = terminal.draw(|frame| dashboard.draw(frame)) {
+            break Err(e.into());
+        }
+        match wait_for_keys(&mut dashboard, interval) {
+            Ok(true) => break Ok(()),
+            Ok(false) => {}
+            Err(e) => break Err(e.into()),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+/// Handle key presses until `timeout` elapses, returning whether to quit
+fn wait_for_keys(dashboard: &mut Dashboard, timeout: Duration) -> std::io::Result<bool> {
+    let deadline = Instant::now() + timeout;
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if !event::poll(remaining)? {
+            break;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(true),
+                KeyCode::Down | KeyCode::Tab | KeyCode::Char('j') => dashboard.select_next(),
+                KeyCode::Up | KeyCode::BackTab | KeyCode::Char('k') => dashboard.select_previous(),
+                _ => {}
+            }
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use uuid::Uuid;
+
+    fn info(network_id: Uuid, messages: u64, loss: Option<f32>) -> PeerInfo {
+        PeerInfo {
+            network_id,
+            name: "worker".to_string(),
+            layers: vec![2, 3, 2],
+            parameters: 17,
+            learning_mode: "Oja".to_string(),
+            uptime_secs: 5,
+            peers: 1,
+            messages_sent: messages,
+            messages_received: messages,
+            bytes_sent: 0,
+            bytes_received: 0,
+            weight_norm: Some(1.5),
+            loss,
+            outputs: vec![0.2, 0.9],
+        }
+    }
+
+    #[test]
+    fn test_dashboard_trends_and_render() {
+        let id = Uuid::new_v4();
+        let mut dashboard = Dashboard::new(vec!["127.0.0.1:8080".to_string(), "b:1".to_string()]);
+        let node = &mut dashboard.nodes[0];
+        node.record(Ok(info(id, 10, Some(0.5))), Duration::from_secs(1));
+        node.record(Ok(info(id, 15, Some(0.25))), Duration::from_secs(2));
+        assert_eq!(node.message_rates, vec![5.0]);
+        assert_eq!(node.losses, vec![0.5, 0.25]);
+
+        // Failures and restarts keep the history
+        node.record(Err("timeout".to_string()), Duration::from_secs(1));
+        assert_eq!(node.error.as_deref(), Some("timeout"));
+        node.record(Ok(info(Uuid::new_v4(), 0, None)), Duration::from_secs(1));
+        assert_eq!(node.message_rates.len(), 1);
+        assert_eq!(node.losses.len(), 2);
+        assert!(node.error.is_none());
+
+        for _ in 0..HISTORY_LEN + 5 {
+            node.record(Ok(info(id, 0, Some(1.0))), Duration::from_secs(1));
+        }
+        assert_eq!(node.losses.len(), HISTORY_LEN);
+
+        dashboard.select_previous();
+        assert_eq!(dashboard.selected, 1);
+        dashboard.select_next();
+        assert_eq!(dashboard.selected, 0);
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| dashboard.draw(frame)).unwrap();
+        let screen: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|cell| cell.symbol())
+            .collect();
+        assert!(screen.contains("worker"));
+        assert!(screen.contains("Training loss"));
+        assert!(screen.contains("waiting"));
+    }
+}
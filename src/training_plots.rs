@@ -0,0 +1,212 @@
+use crate::neural_network::NeuralNetwork;
+use crate::visualize::ImageFormat;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PLOT_SIZE: (u32, u32) = (800, 500);
+pub(crate) const TRAIN_COLOR: RGBColor = RGBColor(33, 102, 172);
+pub(crate) const VAL_COLOR: RGBColor = RGBColor(230, 126, 34);
+
+/// A named line of `(epoch, value)` points
+pub(crate) type Series<'a> = (&'a str, RGBColor, Vec<(f64, f64)>);
+
+/// Per-epoch values of a training run, plotted to image files when it ends
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrainingCurves {
+    pub train_errors: Vec<f64>,
+    /// Validation error of each epoch, when there was validation data
+    pub val_errors: Vec<Option<f64>>,
+    pub learning_rates: Vec<f64>,
+    pub weight_norms: Vec<f64>,
+}
+
+impl TrainingCurves {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the errors of the epoch `network` just finished, with its current rate and weight norm
+    pub fn record(&mut self, network: &NeuralNetwork, train_error: f64, val_error: Option<f64>) {
+        self.train_errors.push(train_error);
+        self.val_errors.push(val_error);
+        self.learning_rates.push(network.get_hebbian_rate());
+        self.weight_norms.push(network.weight_norm());
+    }
+
+    /// Write `error`, `learning_rate` and `weight_norm` charts into `dir`, returning their paths
+    ///
+    /// `dir` is created when missing. SVG charts carry titles, axis labels
+    /// and a legend; PNG charts are drawn without text, as no font is
+    /// bundled to rasterize it.
+    pub fn write<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        format: ImageFormat,
+    ) -> Result<Vec<PathBuf>, Box<dyn std::error::Error>> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let mut errors = vec![(
+            "train",
+            TRAIN_COLOR,
+            points(self.train_errors.iter().map(|&e| Some(e))),
+        )];
+        let val_errors = points(self.val_errors.iter().copied());
+        if !val_errors.is_empty() {
+            errors.push(("validation", VAL_COLOR, val_errors));
+        }
+        let charts = [
+            ("error", "Error", errors),
+            (
+                "learning_rate",
+                "Learning rate",
+                vec![(
+                    "learning rate",
+                    TRAIN_COLOR,
+                    points(self.learning_rates.iter().map(|&r| Some(r))),
+                )],
+            ),
+            (
+                "weight_norm",
+                "Weight norm",
+                vec![(
+                    "weight norm",
+                    TRAIN_COLOR,
+                    points(self.weight_norms.iter().map(|&n| Some(n))),
+                )],
+            ),
+        ];
+
+        let mut written = Vec::new();
+        for (name, title, series) in &charts {
+            let path = dir.join(name).with_extension(match format {
+                ImageFormat::Svg => "svg",
+                ImageFormat::Png => "png",
+            });
+            write_chart(&path, format, title, series)?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+}
+
+/// Draw `series` against the epoch into the `format` image at `path`
+pub(crate) fn write_chart(
+    path: &Path,
+    format: ImageFormat,
+    title: &str,
+    series: &[Series],
+) -> Result<(), Box<dyn std::error::Error>> {
+    match format {
+        ImageFormat::Svg => {
+            let area = SVGBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw_chart(&area, title, series, true)?;
+        }
+        ImageFormat::Png => {
+            let area = BitMapBackend::new(path, PLOT_SIZE).into_drawing_area();
+            draw_chart(&area, title, series, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// `(epoch, value)` points of the finite values among `values`
+pub(crate) fn points(values: impl Iterator<Item = Option<f64>>) -> Vec<(f64, f64)> {
+    values
+        .enumerate()
+        .filter_map(|(epoch, value)| value.map(|value| (epoch as f64, value)))
+        .filter(|(_, value)| value.is_finite())
+        .collect()
+}
+
+/// Line chart of `series` against the epoch, with text only when `labels` is set
+fn draw_chart<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    title: &str,
+    series: &[Series],
+    labels: bool,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    area.fill(&WHITE)?;
+    let all = || series.iter().flat_map(|(_, _, points)| points.iter());
+    let last_epoch = all().map(|&(epoch, _)| epoch).fold(1.0, f64::max);
+    let (mut low, mut high) = all()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &(_, v)| {
+            (lo.min(v), hi.max(v))
+        });
+    if !low.is_finite() {
+        (low, high) = (0.0, 1.0);
+    }
+    let pad = ((high - low) * 0.05).max(high.abs() * 0.05).max(1e-9);
+
+    let mut builder = ChartBuilder::on(area);
+    builder.margin(20);
+    if labels {
+        builder
+            .caption(title, ("sans-serif", 20))
+            .x_label_area_size(40)
+            .y_label_area_size(70);
+    }
+    let mut chart = builder.build_cartesian_2d(0.0..last_epoch, (low - pad)..(high + pad))?;
+    if labels {
+        chart
+            .configure_mesh()
+            .x_desc("Epoch")
+            .y_desc(title)
+            .draw()?;
+    } else {
+        chart.plotting_area().draw(&Rectangle::new(
+            [(0.0, low - pad), (last_epoch, high + pad)],
+            BLACK.stroke_width(1),
+        ))?;
+    }
+    for (name, color, points) in series {
+        let drawn = chart.draw_series(LineSeries::new(
+            points.iter().copied(),
+            color.stroke_width(2),
+        ))?;
+        if labels {
+            let color = *color;
+            drawn.label(*name).legend(move |(x, y)| {
+                PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+            });
+        }
+    }
+    if labels && series.len() > 1 {
+        chart
+            .configure_series_labels()
+            .background_style(WHITE.mix(0.8))
+            .border_style(BLACK)
+            .draw()?;
+    }
+    area.present()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_training_plots() {
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let mut curves = TrainingCurves::new();
+        curves.record(&network, 0.5, None);
+        network.set_hebbian_rate(0.05);
+        curves.record(&network, 0.3, Some(0.4));
+        curves.record(&network, 0.2, Some(0.35));
+        assert_eq!(curves.learning_rates, vec![0.1, 0.05, 0.05]);
+
+        let dir = std::env::temp_dir().join(format!("benny-plots-{}", std::process::id()));
+        let written = curves.write(&dir, ImageFormat::Svg).unwrap();
+        assert_eq!(written.len(), 3);
+        let errors = fs::read_to_string(dir.join("error.svg")).unwrap();
+        assert!(errors.contains("validation"));
+        assert!(errors.contains("Epoch"));
+
+        let written = curves.write(&dir, ImageFormat::Png).unwrap();
+        let png = fs::read(&written[2]).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(written[2].ends_with("weight_norm.png"));
+        assert_eq!(&png[..4], b"\x89PNG");
+    }
+}
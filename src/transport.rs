@@ -0,0 +1,298 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::address::{self, AddressPreference, BindOptions};
+
+/// Prefix marking an address as a Unix domain socket path, e.g. `unix:/run/benny.sock`
+pub const UNIX_SCHEME: &str = "unix:";
+
+/// Return the socket path when `address` refers to a Unix domain socket
+///
+/// Both `unix:/path/to.sock` and bare absolute paths (`/path/to.sock`) are accepted.
+pub fn unix_socket_path(address: &str) -> Option<&str> {
+    if let Some(path) = address.strip_prefix(UNIX_SCHEME) {
+        Some(path)
+    } else if address.starts_with('/') {
+        Some(address)
+    } else {
+        None
+    }
+}
+
+/// Format an address for display, omitting the port for Unix domain sockets
+pub fn display_address(address: &str, port: u16) -> String {
+    match unix_socket_path(address) {
+        Some(path) => format!("{}{}", UNIX_SCHEME, path),
+        None => address::format_host_port(address, port),
+    }
+}
+
+/// Bind a Unix socket at `path` that is never reachable with a mode other than `mode`
+///
+/// `bind` creates the socket with permissions from the umask, so it is bound
+/// inside a directory only the owner can enter, chmodded there and renamed
+/// into place.
+#[cfg(unix)]
+fn bind_unix_with_mode(path: &std::path::Path, mode: u32) -> io::Result<UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    // Short names: socket paths are limited to about 100 bytes
+    let staging = path.with_file_name(format!(".nnp-{:08x}", rand::random::<u32>()));
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+
+    let staged = staging.join("s");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(mode))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    bound
+}
+
+#[cfg(not(unix))]
+fn unix_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Unix domain sockets are not supported on this platform",
+    )
+}
+
+/// A connected NNP stream over TCP or a Unix domain socket
+#[derive(Debug)]
+pub enum NnpStream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl NnpStream {
+    /// Connect to a TCP host/port or a Unix socket path
+    pub async fn connect(
+        address: &str,
+        port: u16,
+        preference: AddressPreference,
+    ) -> io::Result<Self> {
+        match unix_socket_path(address) {
+            #[cfg(unix)]
+            Some(path) => Ok(NnpStream::Unix(UnixStream::connect(path).await?)),
+            #[cfg(not(unix))]
+            Some(_) => Err(unix_unsupported()),
+            None => Ok(NnpStream::Tcp(
+                address::connect_tcp(address, port, preference).await?,
+            )),
+        }
+    }
+}
+
+impl AsyncRead for NnpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            NnpStream::Tcp(stream) => Pin::new(stream).poll_read(cx, buf),
+            #[cfg(unix)]
+            NnpStream::Unix(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for NnpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            NnpStream::Tcp(stream) => Pin::new(stream).poll_write(cx, buf),
+            #[cfg(unix)]
+            NnpStream::Unix(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            NnpStream::Tcp(stream) => Pin::new(stream).poll_flush(cx),
+            #[cfg(unix)]
+            NnpStream::Unix(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            NnpStream::Tcp(stream) => Pin::new(stream).poll_shutdown(cx),
+            #[cfg(unix)]
+            NnpStream::Unix(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+/// A listener accepting NNP connections over TCP or a Unix domain socket
+#[derive(Debug)]
+pub enum NnpListener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(UnixListener),
+}
+
+impl NnpListener {
+    /// Bind a TCP host/port or a Unix socket path
+    ///
+    /// A stale socket file left behind by a previous run, one that refuses connections,
+    /// is removed before binding; a socket another process still listens on is left
+    /// alone. With `BindOptions::socket_mode` set, the socket is created in a
+    /// private directory next to `path`, given its mode there and only then
+    /// moved into place, so it is never reachable with looser permissions.
+    pub async fn bind(address: &str, port: u16, options: BindOptions) -> io::Result<Self> {
+        match unix_socket_path(address) {
+            #[cfg(unix)]
+            Some(path) => {
+                use std::os::unix::fs::FileTypeExt;
+
+                if let Ok(metadata) = std::fs::symlink_metadata(path) {
+                    if metadata.file_type().is_socket() {
+                        match UnixStream::connect(path).await {
+                            Ok(_) => {
+                                return Err(io::Error::new(
+                                    io::ErrorKind::AddrInUse,
+                                    format!("'{}' is in use by a running server", path),
+                                ))
+                            }
+                            Err(e) if e.kind() == io::ErrorKind::ConnectionRefused => {
+                                std::fs::remove_file(path)?
+                            }
+                            Err(e) => return Err(e),
+                        }
+                    } else {
+                        return Err(io::Error::new(
+                            io::ErrorKind::AlreadyExists,
+                            format!("'{}' exists and is not a socket", path),
+                        ));
+                    }
+                }
+
+                let listener = match options.socket_mode {
+                    Some(mode) => bind_unix_with_mode(std::path::Path::new(path), mode)?,
+                    None => UnixListener::bind(path)?,
+                };
+                Ok(NnpListener::Unix(listener))
+            }
+            #[cfg(not(unix))]
+            Some(_) => Err(unix_unsupported()),
+            None => Ok(NnpListener::Tcp(
+                address::bind_tcp_listener(address, port, options).await?,
+            )),
+        }
+    }
+
+    /// TCP port the listener is bound to; `None` for a Unix socket
+    pub fn local_port(&self) -> Option<u16> {
+        match self {
+            NnpListener::Tcp(listener) => listener.local_addr().ok().map(|addr| addr.port()),
+            #[cfg(unix)]
+            NnpListener::Unix(_) => None,
+        }
+    }
+
+    /// Accept a connection, returning the stream and a printable peer description
+    pub async fn accept(&self) -> io::Result<(NnpStream, String)> {
+        match self {
+            NnpListener::Tcp(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                Ok((NnpStream::Tcp(stream), peer.to_string()))
+            }
+            #[cfg(unix)]
+            NnpListener::Unix(listener) => {
+                let (stream, peer) = listener.accept().await?;
+                let peer = peer
+                    .as_pathname()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_else(|| "unix peer".to_string());
+                Ok((NnpStream::Unix(stream), peer))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unix_socket_path() {
+        assert_eq!(
+            unix_socket_path("unix:/tmp/benny.sock"),
+            Some("/tmp/benny.sock")
+        );
+        assert_eq!(unix_socket_path("/tmp/benny.sock"), Some("/tmp/benny.sock"));
+        assert_eq!(unix_socket_path("127.0.0.1"), None);
+        assert_eq!(unix_socket_path("::1"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_roundtrip_and_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let path = std::env::temp_dir().join(format!("benny-{}.sock", uuid::Uuid::new_v4()));
+        let address = format!("{}{}", UNIX_SCHEME, path.display());
+        let options = BindOptions {
+            socket_mode: Some(0o600),
+            ..BindOptions::default()
+        };
+
+        let listener = NnpListener::bind(&address, 0, options).await.unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // The private directory the socket was created in is gone
+        let mut entries = std::fs::read_dir(std::env::temp_dir()).unwrap();
+        assert!(!entries.any(|entry| {
+            let name = entry.unwrap().file_name();
+            name.to_string_lossy().starts_with(".nnp-")
+        }));
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).await.unwrap();
+            buf
+        });
+
+        let mut client = NnpStream::connect(&address, 0, AddressPreference::System)
+            .await
+            .unwrap();
+        client.write_all(b"NNP\0").await.unwrap();
+        assert_eq!(&server.await.unwrap(), b"NNP\0");
+
+        // Rebinding over the stale socket file must succeed
+        assert!(NnpListener::bind(&address, 0, options).await.is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_unix_bind_keeps_live_socket() {
+        let path = std::env::temp_dir().join(format!("benny-{}.sock", uuid::Uuid::new_v4()));
+        let address = format!("{}{}", UNIX_SCHEME, path.display());
+
+        let _listener = NnpListener::bind(&address, 0, BindOptions::default())
+            .await
+            .unwrap();
+        let err = NnpListener::bind(&address, 0, BindOptions::default())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AddrInUse);
+        assert!(NnpStream::connect(&address, 0, AddressPreference::System)
+            .await
+            .is_ok());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::neural_network::NeuralNetwork;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::path::Path;
+
+const MARGIN: i32 = 40;
+const LABEL_HEIGHT: i32 = 30;
+const POSITIVE: (u8, u8, u8) = (33, 102, 172);
+const NEGATIVE: (u8, u8, u8) = (178, 24, 43);
+const INPUT: RGBColor = RGBColor(160, 160, 160);
+
+/// Image format written by `render`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ImageFormat {
+    Svg,
+    Png,
+}
+
+impl ImageFormat {
+    /// Format named by the extension of `path`
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path
+            .as_ref()
+            .extension()?
+            .to_str()?
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "svg" => Some(ImageFormat::Svg),
+            "png" => Some(ImageFormat::Png),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VisualizeOptions {
+    pub width: u32,
+    pub height: u32,
+    /// Neurons drawn per layer; larger layers show their first neurons and a count of the rest
+    pub max_neurons: usize,
+}
+
+impl Default for VisualizeOptions {
+    fn default() -> Self {
+        Self {
+            width: 1200,
+            height: 800,
+            max_neurons: 24,
+        }
+    }
+}
+
+/// Blue for positive, red for negative, more opaque the closer `value` is to `scale`
+fn weight_color(value: f64, scale: f64) -> RGBAColor {
+    let strength = if scale > 0.0 {
+        (value.abs() / scale).min(1.0)
+    } else {
+        0.0
+    };
+    let (r, g, b) = if value < 0.0 { NEGATIVE } else { POSITIVE };
+    RGBAColor(r, g, b, 0.1 + 0.9 * strength)
+}
+
+/// Draw neurons as circles filled by bias and connections as lines whose
+/// width and colour follow the weight; layer sizes are labelled when `labels`
+/// is set (bitmaps have no font to draw them with)
+fn draw<DB: DrawingBackend>(
+    network: &NeuralNetwork,
+    area: &DrawingArea<DB, Shift>,
+    options: &VisualizeOptions,
+    labels: bool,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    area.fill(&WHITE)?;
+    let layers = network.get_layers();
+    let (width, height) = area.dim_in_pixel();
+    let (width, height) = (width as i32, height as i32);
+    let shown: Vec<usize> = layers
+        .iter()
+        .map(|&size| size.min(options.max_neurons.max(1)))
+        .collect();
+
+    let x = |layer: usize| {
+        if layers.len() > 1 {
+            MARGIN + layer as i32 * (width - 2 * MARGIN) / (layers.len() as i32 - 1)
+        } else {
+            width / 2
+        }
+    };
+    let bottom = height - MARGIN - if labels { LABEL_HEIGHT } else { 0 };
+    let spacing = |layer: usize| (bottom - MARGIN) / shown[layer].max(1) as i32;
+    let y = |layer: usize, neuron: usize| MARGIN + spacing(layer) * (2 * neuron as i32 + 1) / 2;
+    let radius = (0..shown.len())
+        .map(|layer| spacing(layer) / 3)
+        .chain(std::iter::once(
+            (width - 2 * MARGIN) / (4 * layers.len() as i32),
+        ))
+        .min()
+        .unwrap_or(0)
+        .clamp(2, 14);
+
+    let weight_scale = (0..layers.len().saturating_sub(1))
+        .flat_map(|layer| network.get_layer_weights(layer))
+        .fold(0.0f64, |max, w| max.max(w.abs()));
+    for layer in 0..layers.len().saturating_sub(1) {
+        // Only dense layers store one weight per pair of neurons
+        if network.get_layer_weights(layer).len() != layers[layer] * layers[layer + 1] {
+            continue;
+        }
+        for from in 0..shown[layer] {
+            for to in 0..shown[layer + 1] {
+                let weight = network.get_weight(layer, from, to);
+                let strength = if weight_scale > 0.0 {
+                    weight.abs() / weight_scale
+                } else {
+                    0.0
+                };
+                area.draw(&PathElement::new(
+                    vec![(x(layer), y(layer, from)), (x(layer + 1), y(layer + 1, to))],
+                    ShapeStyle::from(weight_color(weight, weight_scale))
+                        .stroke_width(1 + (strength * 4.0).round() as u32),
+                ))?;
+            }
+        }
+    }
+
+    let bias_scale = (0..layers.len().saturating_sub(1))
+        .flat_map(|layer| network.get_layer_biases(layer).iter().copied())
+        .fold(0.0f64, |max, b| max.max(b.abs()));
+    for (layer, &count) in shown.iter().enumerate() {
+        for neuron in 0..count {
+            let fill = if layer == 0 {
+                INPUT.to_rgba()
+            } else {
+                let biases = network.get_layer_biases(layer - 1);
+                weight_color(biases.get(neuron).copied().unwrap_or(0.0), bias_scale)
+            };
+            let center = (x(layer), y(layer, neuron));
+            area.draw(&Circle::new(center, radius, fill.filled()))?;
+            area.draw(&Circle::new(center, radius, BLACK.stroke_width(1)))?;
+        }
+        if labels {
+            let label = if layers[layer] > count {
+                format!("{} (+{})", count, layers[layer] - count)
+            } else {
+                layers[layer].to_string()
+            };
+            let style = ("sans-serif", 14)
+                .into_font()
+                .color(&BLACK)
+                .pos(Pos::new(HPos::Center, VPos::Top));
+            area.draw(&Text::new(label, (x(layer), bottom + 10), style))?;
+        }
+    }
+    area.present()
+}
+
+/// Render `network` as an SVG document
+pub fn render_svg(
+    network: &NeuralNetwork,
+    options: &VisualizeOptions,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let mut svg = String::new();
+    {
+        let area =
+            SVGBackend::with_string(&mut svg, (options.width, options.height)).into_drawing_area();
+        draw(network, &area, options, true)?;
+    }
+    Ok(svg)
+}
+
+/// Render `network` to `path` as SVG or PNG, following its extension
+pub fn render<P: AsRef<Path>>(
+    network: &NeuralNetwork,
+    path: P,
+    options: &VisualizeOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    match ImageFormat::from_path(path) {
+        Some(ImageFormat::Svg) => std::fs::write(path, render_svg(network, options)?)?,
+        Some(ImageFormat::Png) => {
+            let area =
+                BitMapBackend::new(path, (options.width, options.height)).into_drawing_area();
+            draw(network, &area, options, false)?;
+        }
+        None => {
+            return Err(format!(
+                "Unknown image format for '{}' (expected .svg or .png)",
+                path.display()
+            )
+            .into())
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_network() {
+        let nn = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let svg = render_svg(&nn, &VisualizeOptions::default()).unwrap();
+        // Each neuron is filled and outlined, each pair of neurons connected
+        assert_eq!(svg.matches("<circle").count(), 2 * 6);
+        assert_eq!(svg.matches("<polyline").count(), 2 * 3 + 3);
+
+        let wide = NeuralNetwork::with_layers(&[30, 2], 0.1);
+        let options = VisualizeOptions {
+            max_neurons: 10,
+            ..VisualizeOptions::default()
+        };
+        let svg = render_svg(&wide, &options).unwrap();
+        assert_eq!(svg.matches("<circle").count(), 2 * 12);
+        assert!(svg.contains("10 (+20)"));
+
+        let path = std::env::temp_dir().join(format!("benny-network-{}.png", std::process::id()));
+        render(&nn, &path, &options).unwrap();
+        let png = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&png[..4], b"\x89PNG");
+
+        assert!(render(&nn, "network.gif", &options).is_err());
+    }
+}
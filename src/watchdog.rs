@@ -0,0 +1,257 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// How long a webhook may take before the alert is dropped
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Failsafe values an `OutputNode` sends its sink when fresh network outputs stop arriving
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    /// Milliseconds without a fresh output after which the failsafe values are sent
+    pub timeout_ms: u64,
+    /// Values delivered to the sink in place of the network outputs
+    pub failsafe: Vec<f64>,
+    /// Send the failsafe values again every `timeout_ms` until fresh outputs return
+    #[serde(default)]
+    pub repeat: bool,
+    /// URL the alerts are POSTed to as JSON, besides being logged
+    #[serde(default)]
+    pub webhook: Option<String>,
+}
+
+impl WatchdogConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.timeout_ms == 0 {
+            return Err("Watchdog timeout must be positive".to_string());
+        }
+        if self.failsafe.is_empty() {
+            return Err("Watchdog needs failsafe values".to_string());
+        }
+        if let Some(value) = self.failsafe.iter().find(|value| !value.is_finite()) {
+            return Err(format!("Failsafe value {} is not finite", value));
+        }
+        Ok(())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms)
+    }
+}
+
+/// A change of the watchdog state worth alerting on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WatchdogAlert {
+    /// No fresh output arrived in time; the sink now receives the failsafe values
+    Tripped { silent_ms: u64 },
+    /// Fresh outputs are flowing again after the failsafe was engaged
+    Recovered { failsafe_ms: u64 },
+}
+
+impl WatchdogAlert {
+    /// One-line human-readable description
+    pub fn message(&self) -> String {
+        match self {
+            WatchdogAlert::Tripped { silent_ms } => format!(
+                "🚨 No fresh network output for {}ms, sending failsafe values",
+                silent_ms
+            ),
+            WatchdogAlert::Recovered { failsafe_ms } => format!(
+                "✅ Fresh network outputs resumed after {}ms of failsafe",
+                failsafe_ms
+            ),
+        }
+    }
+
+    /// Log the alert and POST it to `webhook`, if any, without waiting for the answer
+    pub fn raise(&self, node: &str, webhook: Option<&str>) {
+        println!("{} ({})", self.message(), node);
+        let Some(url) = webhook else {
+            return;
+        };
+        let mut body = serde_json::to_value(self).unwrap_or_default();
+        body["node"] = serde_json::Value::String(node.to_string());
+        body["message"] = serde_json::Value::String(self.message());
+        let url = url.to_string();
+        std::thread::spawn(move || {
+            let result = ureq::post(&url)
+                .timeout(WEBHOOK_TIMEOUT)
+                .set("Content-Type", "application/json")
+                .send_string(&body.to_string());
+            if let Err(e) = result {
+                println!("⚠️ Failed to send watchdog alert to {}: {}", url, e);
+            }
+        });
+    }
+}
+
+/// Tracks fresh outputs against the timeout and decides when the failsafe is due
+///
+/// Stale outputs (flagged `STALE` by a node over its latency budget) never
+/// feed the watchdog, and are withheld from the sink once it has tripped.
+#[derive(Debug, Clone)]
+pub struct Watchdog {
+    config: WatchdogConfig,
+    last_fresh: Instant,
+    tripped_at: Option<Instant>,
+    /// When the failsafe values were last sent
+    last_failsafe: Option<Instant>,
+}
+
+impl Watchdog {
+    /// A watchdog armed at `now`, so a network that never answers trips it too
+    pub fn new(config: WatchdogConfig, now: Instant) -> Self {
+        Self {
+            config,
+            last_fresh: now,
+            tripped_at: None,
+            last_failsafe: None,
+        }
+    }
+
+    pub fn config(&self) -> &WatchdogConfig {
+        &self.config
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some()
+    }
+
+    /// When the failsafe values are next due, if ever
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.last_failsafe {
+            None => Some(self.last_fresh + self.config.timeout()),
+            Some(sent) if self.config.repeat => Some(sent + self.config.timeout()),
+            Some(_) => None,
+        }
+    }
+
+    /// Whether an output is passed on to the sink
+    pub fn accepts(&self, stale: bool) -> bool {
+        !(stale && self.is_tripped())
+    }
+
+    /// Record a fresh output, returning the recovery alert if the watchdog had tripped
+    pub fn fresh(&mut self, now: Instant) -> Option<WatchdogAlert> {
+        self.last_fresh = now;
+        self.last_failsafe = None;
+        self.tripped_at
+            .take()
+            .map(|tripped_at| WatchdogAlert::Recovered {
+                failsafe_ms: now.duration_since(tripped_at).as_millis() as u64,
+            })
+    }
+
+    /// The deadline passed: the failsafe values to send, with the alert when this trips the watchdog
+    pub fn expire(&mut self, now: Instant) -> (Vec<f64>, Option<WatchdogAlert>) {
+        self.last_failsafe = Some(now);
+        let alert = match self.tripped_at {
+            Some(_) => None,
+            None => {
+                self.tripped_at = Some(now);
+                Some(WatchdogAlert::Tripped {
+                    silent_ms: now.duration_since(self.last_fresh).as_millis() as u64,
+                })
+            }
+        };
+        (self.config.failsafe.clone(), alert)
+    }
+}
+
+/// Watchdog state shared with health checks
+#[derive(Debug, Default)]
+pub struct WatchdogStatus {
+    tripped: AtomicBool,
+    trips: AtomicU64,
+}
+
+/// Point-in-time copy of `WatchdogStatus`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchdogStats {
+    /// The sink is receiving failsafe values
+    pub tripped: bool,
+    /// Times the watchdog tripped since the node started
+    pub trips: u64,
+}
+
+impl WatchdogStatus {
+    pub fn record(&self, alert: &WatchdogAlert) {
+        match alert {
+            WatchdogAlert::Tripped { .. } => {
+                self.tripped.store(true, Ordering::Relaxed);
+                self.trips.fetch_add(1, Ordering::Relaxed);
+            }
+            WatchdogAlert::Recovered { .. } => self.tripped.store(false, Ordering::Relaxed),
+        }
+    }
+
+    pub fn snapshot(&self) -> WatchdogStats {
+        WatchdogStats {
+            tripped: self.tripped.load(Ordering::Relaxed),
+            trips: self.trips.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watchdog() {
+        let config: WatchdogConfig =
+            serde_json::from_str(r#"{"timeout_ms": 100, "failsafe": [0.0, 0.5]}"#).unwrap();
+        assert!(!config.repeat);
+        let start = Instant::now();
+        let ms = |ms: u64| start + Duration::from_millis(ms);
+        let mut watchdog = Watchdog::new(config.clone(), start);
+        assert_eq!(watchdog.deadline(), Some(ms(100)));
+
+        watchdog.fresh(ms(60));
+        assert_eq!(watchdog.deadline(), Some(ms(160)));
+        assert!(watchdog.accepts(true));
+
+        // Tripped once, with no repeat until fresh outputs return
+        let (failsafe, alert) = watchdog.expire(ms(160));
+        assert_eq!(failsafe, vec![0.0, 0.5]);
+        assert_eq!(alert, Some(WatchdogAlert::Tripped { silent_ms: 100 }));
+        assert_eq!(watchdog.deadline(), None);
+        assert!(!watchdog.accepts(true));
+        assert!(watchdog.accepts(false));
+        assert_eq!(
+            watchdog.fresh(ms(400)),
+            Some(WatchdogAlert::Recovered { failsafe_ms: 240 })
+        );
+        assert_eq!(watchdog.fresh(ms(450)), None);
+
+        let mut repeating = Watchdog::new(
+            WatchdogConfig {
+                repeat: true,
+                ..config.clone()
+            },
+            start,
+        );
+        assert!(repeating.expire(ms(100)).1.is_some());
+        assert_eq!(repeating.deadline(), Some(ms(200)));
+        assert_eq!(repeating.expire(ms(200)).1, None);
+
+        let status = WatchdogStatus::default();
+        status.record(&WatchdogAlert::Tripped { silent_ms: 100 });
+        assert_eq!(
+            status.snapshot(),
+            WatchdogStats {
+                tripped: true,
+                trips: 1
+            }
+        );
+        status.record(&WatchdogAlert::Recovered { failsafe_ms: 5 });
+        assert!(!status.snapshot().tripped);
+
+        let invalid = WatchdogConfig {
+            failsafe: vec![f64::NAN],
+            ..config
+        };
+        assert!(invalid.validate().unwrap_err().contains("not finite"));
+    }
+}
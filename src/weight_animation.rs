@@ -0,0 +1,167 @@
+use crate::neural_network::NeuralNetwork;
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::path::Path;
+
+const PANEL_HEIGHT: u32 = 400;
+const PANEL_WIDTH: u32 = 300;
+const GAP: i32 = 10;
+const PROGRESS_HEIGHT: i32 = 6;
+const POSITIVE: (f64, f64, f64) = (33.0, 102.0, 172.0);
+const NEGATIVE: (f64, f64, f64) = (178.0, 24.0, 43.0);
+
+/// The weights of one layer, `values[from * cols + to]`
+#[derive(Debug, Clone, PartialEq)]
+struct Matrix {
+    rows: usize,
+    cols: usize,
+    values: Vec<f64>,
+}
+
+/// Weight matrices captured while a network trains, written as an animated GIF of heatmaps
+///
+/// Each frame shows every weight layer side by side, one row per source
+/// neuron and one column per target neuron, blue for positive and red for
+/// negative weights on a scale shared by all frames, so growth and decay
+/// under the different Hebbian rules stay comparable. A bar along the bottom
+/// tracks the epoch.
+#[derive(Debug, Clone, Default)]
+pub struct WeightAnimation {
+    frames: Vec<(usize, Vec<Matrix>)>,
+}
+
+impl WeightAnimation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Capture the weights of `network` after `epoch`
+    pub fn record(&mut self, epoch: usize, network: &NeuralNetwork) {
+        let layers = network.get_layers();
+        let matrices = (0..layers.len().saturating_sub(1))
+            .map(|layer| {
+                let values = network.get_layer_weights(layer);
+                let rows = layers[layer];
+                let cols = values.len().checked_div(rows).unwrap_or(0);
+                Matrix { rows, cols, values }
+            })
+            .collect();
+        self.frames.push((epoch, matrices));
+    }
+
+    /// Epochs captured so far
+    pub fn epochs(&self) -> Vec<usize> {
+        self.frames.iter().map(|(epoch, _)| *epoch).collect()
+    }
+
+    /// Write every frame to `path`, showing each for `frame_delay_ms`
+    pub fn write_gif<P: AsRef<Path>>(
+        &self,
+        path: P,
+        frame_delay_ms: u32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let panels = self.frames.first().map_or(1, |(_, m)| m.len().max(1)) as u32;
+        let size = (panels * PANEL_WIDTH, PANEL_HEIGHT);
+        let scale = self
+            .frames
+            .iter()
+            .flat_map(|(_, matrices)| matrices.iter().flat_map(|m| m.values.iter()))
+            .fold(0.0f64, |max, w| max.max(w.abs()));
+        let last_epoch = self.frames.last().map_or(0, |(epoch, _)| *epoch);
+
+        let area = BitMapBackend::gif(path, size, frame_delay_ms)?.into_drawing_area();
+        for (epoch, matrices) in &self.frames {
+            let progress = if last_epoch > 0 {
+                *epoch as f64 / last_epoch as f64
+            } else {
+                1.0
+            };
+            draw_frame(&area, matrices, scale, progress)?;
+        }
+        Ok(())
+    }
+}
+
+/// White at 0, deepening to blue (positive) or red (negative) at `scale`
+fn heat_color(value: f64, scale: f64) -> RGBColor {
+    let strength = if scale > 0.0 {
+        (value.abs() / scale).min(1.0)
+    } else {
+        0.0
+    };
+    let (r, g, b) = if value < 0.0 { NEGATIVE } else { POSITIVE };
+    let mix = |c: f64| (255.0 - (255.0 - c) * strength) as u8;
+    RGBColor(mix(r), mix(g), mix(b))
+}
+
+fn draw_frame<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    matrices: &[Matrix],
+    scale: f64,
+    progress: f64,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    area.fill(&WHITE)?;
+    let (width, height) = area.dim_in_pixel();
+    let (width, height) = (width as i32, height as i32);
+    area.draw(&Rectangle::new(
+        [
+            (0, height - PROGRESS_HEIGHT),
+            ((width as f64 * progress) as i32, height),
+        ],
+        BLACK.filled(),
+    ))?;
+
+    let panel_width = width / matrices.len().max(1) as i32;
+    let panel_height = height - PROGRESS_HEIGHT - GAP;
+    for (panel, matrix) in matrices.iter().enumerate() {
+        if matrix.rows == 0 || matrix.cols == 0 {
+            continue;
+        }
+        // Square cells as large as fit, centred in the panel
+        let cell = ((panel_width - 2 * GAP) as f64 / matrix.cols as f64)
+            .min((panel_height - 2 * GAP) as f64 / matrix.rows as f64);
+        let left =
+            panel as i32 * panel_width + (panel_width - (cell * matrix.cols as f64) as i32) / 2;
+        let top = (panel_height - (cell * matrix.rows as f64) as i32) / 2;
+        for row in 0..matrix.rows {
+            for col in 0..matrix.cols {
+                let x = left + (col as f64 * cell) as i32;
+                let y = top + (row as f64 * cell) as i32;
+                let next_x = left + ((col + 1) as f64 * cell) as i32;
+                let next_y = top + ((row + 1) as f64 * cell) as i32;
+                area.draw(&Rectangle::new(
+                    [(x, y), (next_x.max(x + 1), next_y.max(y + 1))],
+                    heat_color(matrix.values[row * matrix.cols + col], scale).filled(),
+                ))?;
+            }
+        }
+    }
+    area.present()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_animation() {
+        let mut network = NeuralNetwork::with_layers(&[2, 3, 1], 0.1);
+        let mut animation = WeightAnimation::new();
+        animation.record(0, &network);
+        network.train(&[1.0, 0.0], &[1.0]);
+        animation.record(5, &network);
+        assert_eq!(animation.epochs(), vec![0, 5]);
+        let (_, matrices) = &animation.frames[1];
+        assert_eq!((matrices[0].rows, matrices[0].cols), (2, 3));
+        assert_eq!(matrices[1].values, network.get_layer_weights(1));
+
+        assert_eq!(heat_color(0.0, 1.0), WHITE);
+        assert_eq!(heat_color(-2.0, 1.0), RGBColor(178, 24, 43));
+
+        let path = std::env::temp_dir().join(format!("benny-weights-{}.gif", std::process::id()));
+        animation.write_gif(&path, 100).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(&bytes[..3], b"GIF");
+    }
+}
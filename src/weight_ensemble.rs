@@ -0,0 +1,117 @@
+use crate::datasets::gaussian;
+use crate::neural_network::{output_moments, NeuralNetwork};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// Copies of a trained network with noise added to their weights, run as an ensemble
+///
+/// Each of the `members` copies gets Gaussian noise of standard deviation
+/// `noise_scale` on every trained parameter (weights, biases, normalization,
+/// skip and embedding parameters). Inputs the network fits robustly barely
+/// move under the noise; the spread of the members' outputs is a cheap
+/// uncertainty estimate that needs neither retraining nor dropout.
+#[derive(Debug, Clone)]
+pub struct WeightEnsemble {
+    members: Vec<NeuralNetwork>,
+    noise_scale: f64,
+}
+
+impl WeightEnsemble {
+    pub fn new(
+        network: &NeuralNetwork,
+        members: usize,
+        noise_scale: f64,
+        seed: u64,
+    ) -> Result<Self, String> {
+        if members == 0 {
+            return Err("An ensemble needs at least one member".to_string());
+        }
+        if !(noise_scale >= 0.0 && noise_scale.is_finite()) {
+            return Err(format!(
+                "Noise scale {} must be a non-negative number",
+                noise_scale
+            ));
+        }
+        let mut rng = StdRng::seed_from_u64(seed);
+        let members = (0..members)
+            .map(|_| {
+                let mut member = network.clone();
+                for parameter in member.backprop_parameters_mut() {
+                    *parameter += noise_scale * gaussian(&mut rng);
+                }
+                member
+            })
+            .collect();
+        Ok(Self {
+            members,
+            noise_scale,
+        })
+    }
+
+    pub fn members(&self) -> &[NeuralNetwork] {
+        &self.members
+    }
+
+    pub fn noise_scale(&self) -> f64 {
+        self.noise_scale
+    }
+
+    /// Mean and variance of every output across the members
+    pub fn predict(&self, inputs: &[f64]) -> (Vec<f64>, Vec<f64>) {
+        output_moments(
+            self.members
+                .iter()
+                .map(|member| member.forward_static(inputs).0),
+        )
+    }
+
+    /// `predict` for every input of a batch, each member running it through `forward_batch`
+    pub fn predict_batch(&self, inputs: &[Vec<f64>]) -> Vec<(Vec<f64>, Vec<f64>)> {
+        let outputs: Vec<Vec<Vec<f64>>> = self
+            .members
+            .iter()
+            .map(|member| member.forward_batch(inputs))
+            .collect();
+        (0..inputs.len())
+            .map(|sample| output_moments(outputs.iter().map(|member| member[sample].clone())))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight_ensemble_spread() {
+        let network = NeuralNetwork::with_layers(&[2, 6, 2], 0.0);
+        let inputs = vec![vec![0.1, 0.9], vec![0.8, 0.3]];
+
+        // Without noise every member is the trained network
+        let exact = WeightEnsemble::new(&network, 4, 0.0, 1).unwrap();
+        let (mean, variance) = exact.predict(&inputs[0]);
+        let expected = network.forward_static(&inputs[0]).0;
+        assert!(mean
+            .iter()
+            .zip(&expected)
+            .all(|(m, e)| (m - e).abs() < 1e-12));
+        assert!(variance.iter().all(|&v| v < 1e-20));
+
+        let small = WeightEnsemble::new(&network, 16, 0.01, 1).unwrap();
+        let large = WeightEnsemble::new(&network, 16, 0.5, 1).unwrap();
+        assert_eq!(large.members().len(), 16);
+        let spread = |ensemble: &WeightEnsemble| ensemble.predict(&inputs[1]).1[0];
+        assert!(spread(&small) > 0.0);
+        assert!(spread(&large) > spread(&small));
+
+        // Batches match one input at a time
+        let batch = large.predict_batch(&inputs);
+        for (input, (mean, variance)) in inputs.iter().zip(&batch) {
+            let (single_mean, single_variance) = large.predict(input);
+            assert!((mean[0] - single_mean[0]).abs() < 1e-12);
+            assert!((variance[1] - single_variance[1]).abs() < 1e-12);
+        }
+        assert!(WeightEnsemble::new(&network, 0, 0.1, 1).is_err());
+        assert!(WeightEnsemble::new(&network, 2, -0.1, 1).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// How long WebSocket sessions and their recent data are kept
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// Seconds a session can be resumed after its last connection closed
+    pub ttl_secs: u64,
+    /// Most sessions kept; the longest disconnected are dropped first
+    pub max_sessions: usize,
+    /// Latest samples kept per stream and replayed to resuming clients
+    pub recent_samples: usize,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: 600,
+            max_sessions: 1024,
+            recent_samples: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Session<S> {
+    state: S,
+    /// Open connections using the session
+    connections: usize,
+    /// When the last connection closed
+    last_seen: Instant,
+}
+
+/// Sessions of WebSocket clients and the recent samples they are replayed
+///
+/// Each connection is issued a session token. A client that reconnects
+/// presents its previous token to pick up that session's state `S` and the
+/// samples `T` it missed, instead of starting from an empty view. Sessions
+/// outlive their last connection by the configured time to live.
+#[derive(Debug)]
+pub struct SessionStore<S, T> {
+    config: SessionConfig,
+    sessions: HashMap<String, Session<S>>,
+    recent: HashMap<String, VecDeque<(u64, T)>>,
+}
+
+impl<S: Clone + Default, T: Clone> SessionStore<S, T> {
+    pub fn new(config: SessionConfig) -> Self {
+        Self {
+            config,
+            sessions: HashMap::new(),
+            recent: HashMap::new(),
+        }
+    }
+
+    /// Start a session for a new connection, returning its token
+    pub fn open(&mut self, now: Instant) -> String {
+        self.prune(now);
+        let token = Uuid::new_v4().simple().to_string();
+        self.sessions.insert(
+            token.clone(),
+            Session {
+                state: S::default(),
+                connections: 1,
+                last_seen: now,
+            },
+        );
+        token
+    }
+
+    /// Attach another connection to the session `token`, returning its state
+    ///
+    /// `None` when the token is unknown or the session expired.
+    pub fn resume(&mut self, token: &str, now: Instant) -> Option<S> {
+        self.prune(now);
+        let session = self.sessions.get_mut(token)?;
+        session.connections += 1;
+        Some(session.state.clone())
+    }
+
+    /// Detach a connection from the session `token`, which expires once the time to live passes
+    pub fn close(&mut self, token: &str, now: Instant) {
+        if let Some(session) = self.sessions.get_mut(token) {
+            session.connections = session.connections.saturating_sub(1);
+            session.last_seen = now;
+        }
+    }
+
+    /// Forget the session `token` right away, e.g. a fresh one replaced by a resumed session
+    pub fn discard(&mut self, token: &str) {
+        self.sessions.remove(token);
+    }
+
+    /// Change the state of the session `token`; `false` when there is no such session
+    pub fn update(&mut self, token: &str, change: impl FnOnce(&mut S)) -> bool {
+        match self.sessions.get_mut(token) {
+            Some(session) => {
+                change(&mut session.state);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn state(&self, token: &str) -> Option<&S> {
+        self.sessions.get(token).map(|session| &session.state)
+    }
+
+    pub fn len(&self) -> usize {
+        self.sessions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    /// Keep `sample` of `stream`, dropping its oldest beyond `recent_samples`
+    pub fn record(&mut self, stream: &str, timestamp: u64, sample: T) {
+        if self.config.recent_samples == 0 {
+            return;
+        }
+        let samples = self.recent.entry(stream.to_string()).or_default();
+        if samples.len() == self.config.recent_samples {
+            samples.pop_front();
+        }
+        samples.push_back((timestamp, sample));
+    }
+
+    /// Kept samples of every stream newer than `since` (all of them when `None`), oldest first
+    pub fn samples_since(&self, since: Option<u64>) -> Vec<T> {
+        let mut samples: Vec<&(u64, T)> = self
+            .recent
+            .values()
+            .flatten()
+            .filter(|(timestamp, _)| since.is_none_or(|since| *timestamp > since))
+            .collect();
+        samples.sort_by_key(|(timestamp, _)| *timestamp);
+        samples
+            .into_iter()
+            .map(|(_, sample)| sample.clone())
+            .collect()
+    }
+
+    /// Drop expired sessions, then the longest disconnected ones beyond `max_sessions`
+    fn prune(&mut self, now: Instant) {
+        let ttl = Duration::from_secs(self.config.ttl_secs);
+        self.sessions.retain(|_, session| {
+            session.connections > 0 || now.saturating_duration_since(session.last_seen) <= ttl
+        });
+        let excess = (self.sessions.len() + 1).saturating_sub(self.config.max_sessions);
+        if excess == 0 {
+            return;
+        }
+        let mut idle: Vec<(Instant, String)> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| session.connections == 0)
+            .map(|(token, session)| (session.last_seen, token.clone()))
+            .collect();
+        idle.sort();
+        for (_, token) in idle.into_iter().take(excess) {
+            self.sessions.remove(&token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_resume() {
+        let config = SessionConfig {
+            ttl_secs: 60,
+            max_sessions: 2,
+            recent_samples: 2,
+        };
+        let mut store: SessionStore<Option<String>, u64> = SessionStore::new(config);
+        let start = Instant::now();
+
+        let token = store.open(start);
+        assert!(store.update(&token, |selected| *selected = Some("net".to_string())));
+        assert!(!store.update("unknown", |_| {}));
+        store.close(&token, start);
+
+        // Resumable within the time to live, with its state
+        let later = start + Duration::from_secs(30);
+        assert_eq!(store.resume(&token, later), Some(Some("net".to_string())));
+        assert_eq!(store.resume("unknown", later), None);
+        store.close(&token, later);
+        store.close(&token, later);
+
+        // Only the longest disconnected session makes way for a new one
+        let other = store.open(later);
+        let third = store.open(later + Duration::from_secs(1));
+        assert_eq!(store.len(), 2);
+        assert!(store.state(&token).is_none());
+        assert!(store.state(&other).is_some());
+        store.discard(&third);
+        assert_eq!(store.len(), 1);
+
+        // Expired once the time to live passes after the last connection closed
+        store.close(&other, later);
+        assert_eq!(store.resume(&other, later + Duration::from_secs(61)), None);
+        assert!(store.is_empty());
+
+        for (timestamp, stream) in [(1, "a"), (2, "b"), (3, "a"), (4, "a")] {
+            store.record(stream, timestamp, timestamp);
+        }
+        assert_eq!(store.samples_since(None), vec![2, 3, 4]);
+        assert_eq!(store.samples_since(Some(2)), vec![3, 4]);
+    }
+}
@@ -0,0 +1,197 @@
+//! The XOR, AND, OR and Hebbian demos of `examples/` as seeded regression tests
+//!
+//! Every network starts from weights drawn from a fixed seed, so a run is
+//! repeatable and a learning-rule change that breaks a demo fails here.
+//! Training to convergence takes a while; run with
+//! `cargo test --features slow-tests`.
+#![cfg(feature = "slow-tests")]
+
+use neural_network::testing::assert_weights_finite;
+use neural_network::{HebbianLearningMode, NeuralNetwork};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const XOR: [([f64; 2], [f64; 1]); 4] = [
+    ([0.0, 0.0], [0.0]),
+    ([0.0, 1.0], [1.0]),
+    ([1.0, 0.0], [1.0]),
+    ([1.0, 1.0], [0.0]),
+];
+const AND: [([f64; 2], [f64; 1]); 4] = [
+    ([0.0, 0.0], [0.0]),
+    ([0.0, 1.0], [0.0]),
+    ([1.0, 0.0], [0.0]),
+    ([1.0, 1.0], [1.0]),
+];
+const OR: [([f64; 2], [f64; 1]); 4] = [
+    ([0.0, 0.0], [0.0]),
+    ([0.0, 1.0], [1.0]),
+    ([1.0, 0.0], [1.0]),
+    ([1.0, 1.0], [1.0]),
+];
+
+/// Replace the random initialization of `network` with weights drawn from `seed`
+fn seeded(mut network: NeuralNetwork, seed: u64) -> NeuralNetwork {
+    let mut rng = StdRng::seed_from_u64(seed);
+    for layer in 0..network.num_layers() - 1 {
+        let weights: Vec<f64> = (0..network.get_layer_weights(layer).len())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect();
+        let biases: Vec<f64> = (0..network.get_layer_biases(layer).len())
+            .map(|_| rng.gen_range(-1.0..1.0))
+            .collect();
+        network
+            .set_layer_parameters(layer, &weights, &biases)
+            .unwrap();
+    }
+    network
+}
+
+/// Train on every sample of `data` for `epochs` epochs, returning the last epoch's mean error
+fn train(network: &mut NeuralNetwork, data: &[([f64; 2], [f64; 1])], epochs: usize) -> f64 {
+    let mut error = 0.0;
+    for _ in 0..epochs {
+        error = data
+            .iter()
+            .map(|(inputs, targets)| network.train(inputs, targets))
+            .sum::<f64>()
+            / data.len() as f64;
+    }
+    assert_weights_finite(network);
+    error
+}
+
+/// Fraction of `data` whose output lands on the right side of 0.5
+fn accuracy(network: &NeuralNetwork, data: &[([f64; 2], [f64; 1])]) -> f64 {
+    let correct = data
+        .iter()
+        .filter(|(inputs, targets)| {
+            let output = network.forward_static(inputs).0;
+            (output[0] > 0.5) == (targets[0] > 0.5)
+        })
+        .count();
+    correct as f64 / data.len() as f64
+}
+
+/// Train `network` unsupervised on `patterns` for `epochs` epochs
+fn train_unsupervised(network: &mut NeuralNetwork, patterns: &[[f64; 2]], epochs: usize) {
+    for _ in 0..epochs {
+        for pattern in patterns {
+            network.train_unsupervised(pattern);
+        }
+    }
+    assert_weights_finite(network);
+}
+
+/// Input-to-hidden weights of `network` as `(from input 0, from input 1)` per hidden neuron
+fn incoming_pairs(network: &NeuralNetwork) -> Vec<(f64, f64)> {
+    let hidden = network.get_layers()[1];
+    (0..hidden)
+        .map(|to| (network.get_weight(0, 0, to), network.get_weight(0, 1, to)))
+        .collect()
+}
+
+/// Patterns whose two inputs are high or low together
+const CORRELATED: [[f64; 2]; 7] = [
+    [1.0, 1.0],
+    [1.0, 0.9],
+    [0.9, 1.0],
+    [0.8, 0.8],
+    [0.0, 0.0],
+    [0.1, 0.0],
+    [0.0, 0.1],
+];
+
+#[test]
+fn xor_with_backprop() {
+    let mut network = seeded(NeuralNetwork::new(2, 4, 1, 0.5), 11);
+    network.set_hebbian_rate(0.0);
+    network.set_decay_rate(0.0);
+    network.set_backprop_enabled(true, 0.5);
+    let error = train(&mut network, &XOR, 3000);
+    assert!(error < 0.01, "XOR error {}", error);
+    assert_eq!(accuracy(&network, &XOR), 1.0);
+}
+
+#[test]
+fn and_with_hybrid_learning() {
+    let mut network = seeded(
+        NeuralNetwork::with_hybrid_learning(&[2, 3, 1], 0.02, 0.3),
+        3,
+    );
+    let error = train(&mut network, &AND, 1000);
+    assert!(error < 0.02, "AND error {}", error);
+    assert_eq!(accuracy(&network, &AND), 1.0);
+}
+
+#[test]
+fn or_with_hybrid_learning() {
+    let mut network = seeded(
+        NeuralNetwork::with_hybrid_learning(&[2, 3, 1], 0.02, 0.3),
+        5,
+    );
+    let error = train(&mut network, &OR, 1000);
+    assert!(error < 0.05, "OR error {}", error);
+    assert_eq!(accuracy(&network, &OR), 1.0);
+}
+
+#[test]
+fn classic_hebbian_wires_correlated_inputs_together() {
+    let mut network = seeded(
+        NeuralNetwork::with_layers_and_mode(&[2, 4, 1], 0.05, HebbianLearningMode::Classic),
+        13,
+    );
+    let before = incoming_pairs(&network);
+    train_unsupervised(&mut network, &CORRELATED, 100);
+
+    // Every hidden neuron ends up listening to both inputs about equally
+    for ((a, b), (before_a, before_b)) in incoming_pairs(&network).into_iter().zip(before) {
+        assert!(a > before_a && b > before_b);
+        assert!((a - b).abs() < 0.1 * (a + b), "weights {} and {}", a, b);
+    }
+}
+
+#[test]
+fn oja_finds_the_principal_direction_with_bounded_weights() {
+    let mut network = seeded(
+        NeuralNetwork::with_layers_and_mode(&[2, 4, 1], 0.05, HebbianLearningMode::Oja),
+        13,
+    );
+    train_unsupervised(&mut network, &CORRELATED, 100);
+
+    for (a, b) in incoming_pairs(&network) {
+        assert!(a > 0.0 && b > 0.0);
+        assert!((a - b).abs() < 0.01 * (a + b), "weights {} and {}", a, b);
+        assert!(a.hypot(b) < 2.0, "weights {} and {}", a, b);
+    }
+}
+
+#[test]
+fn anti_hebbian_weakens_co_active_connections() {
+    let mut network = seeded(
+        NeuralNetwork::with_layers_and_mode(&[2, 4, 1], 0.05, HebbianLearningMode::AntiHebbian),
+        13,
+    );
+    train_unsupervised(&mut network, &CORRELATED, 100);
+
+    for (a, b) in incoming_pairs(&network) {
+        assert!(a < -1.0 && b < -1.0, "weights {} and {}", a, b);
+    }
+}
+
+#[test]
+fn competitive_learning_strengthens_a_single_winner() {
+    let mut network = seeded(
+        NeuralNetwork::with_layers_and_mode(&[2, 4, 1], 0.05, HebbianLearningMode::Competitive),
+        13,
+    );
+    let before = incoming_pairs(&network);
+    train_unsupervised(&mut network, &CORRELATED, 100);
+
+    let grown = incoming_pairs(&network)
+        .into_iter()
+        .zip(before)
+        .filter(|((a, b), (before_a, before_b))| a - before_a > 5.0 && b - before_b > 5.0)
+        .count();
+    assert_eq!(grown, 1);
+}